@@ -88,6 +88,12 @@ struct RunArgs {
     /// Path to TLS private key file (PEM) for WSS connections
     #[arg(long, value_name = "PATH")]
     tls_key: Option<std::path::PathBuf>,
+    /// Simulate mutating tools instead of running them
+    #[arg(long)]
+    dry_run: bool,
+    /// Record provider calls and tool results to a file for replay
+    #[arg(long, value_name = "FILE")]
+    record: Option<std::path::PathBuf>,
 }
 
 impl Default for RunArgs {
@@ -103,6 +109,8 @@ impl Default for RunArgs {
             listen: None,
             tls_cert: None,
             tls_key: None,
+            dry_run: false,
+            record: None,
         }
     }
 }
@@ -114,6 +122,7 @@ async fn main() -> Result<()> {
     let config_path = cli.common.config_path();
     let mut config = Config::load(config_path)?;
     cli.common.apply_overrides(&mut config);
+    t::set_palette(config.palette);
 
     let args = match cli.command {
         Some(GatewayCommands::Run(args)) => args,
@@ -140,6 +149,12 @@ async fn main() -> Result<()> {
         .listen
         .unwrap_or_else(|| format!("{}:{}", host, args.port));
 
+    if args.dry_run {
+        config.dry_run = true;
+        // Sticky: a config reload can't switch it off.
+        rustyclaw_core::tools::force_dry_run();
+    }
+
     // Resolve TLS paths: CLI args override config
     let tls_cert = args.tls_cert.or(config.tls_cert.clone());
     let tls_key = args.tls_key.or(config.tls_key.clone());
@@ -160,7 +175,7 @@ async fn main() -> Result<()> {
     // If no password is available for a password-protected vault, the
     // gateway starts in a "vault locked" state — authenticated clients
     // can unlock it later via a control message.
-    let vault = {
+    let mut vault = {
         let creds_dir = config.credentials_dir();
         let env_password = std::env::var("RUSTYCLAW_VAULT_PASSWORD").ok();
         if env_password.is_some() {
@@ -189,6 +204,16 @@ async fn main() -> Result<()> {
         }
     };
 
+    // Fields the launcher couldn't decrypt at load time (a password
+    // vault unlocked by the prompt above).
+    if !vault.is_locked() {
+        match config.unseal_fields(&mut vault) {
+            Ok(0) => {}
+            Ok(n) => println!("  {} Decrypted {} config field(s)", t::icon_ok(""), n),
+            Err(e) => eprintln!("{} Could not decrypt config fields: {}", t::muted("⚠"), e),
+        }
+    }
+
     let shared_vault: rustyclaw_core::gateway::SharedVault =
         std::sync::Arc::new(tokio::sync::Mutex::new(vault));
 
@@ -305,7 +330,7 @@ async fn main() -> Result<()> {
         let shared_skills: rustyclaw_core::gateway::SharedSkillManager =
             std::sync::Arc::new(tokio::sync::Mutex::new(sm));
 
-        run_gateway(config, GatewayOptions { listen, tls_cert, tls_key }, model_ctx, shared_vault, shared_skills, cancel).await
+        run_gateway(config, GatewayOptions { listen, tls_cert, tls_key, record: args.record }, model_ctx, shared_vault, shared_skills, cancel).await
     };
     daemon::remove_pid(&settings_dir);
 
@@ -82,6 +82,17 @@ enum Commands {
     #[command(alias = "refresh")]
     RefreshToken(RefreshTokenArgs),
 
+    /// Replay a recorded session and compare tool results
+    Replay(ReplayArgs),
+
+    /// Encrypted backups of config, memory, sessions, skills and cron jobs
+    #[command(subcommand)]
+    Backup(BackupCommands),
+
+    /// Benchmarks (first-token latency, total latency and cost per model)
+    #[command(subcommand)]
+    Bench(BenchCommands),
+
     /// ClawHub skill registry commands (search, install, publish, …)
     #[command(name = "clawhub", alias = "hub", alias = "registry")]
     ClawHub(ClawHubCommands),
@@ -111,6 +122,72 @@ struct SetupArgs {
     remote_token: Option<String>,
 }
 
+// ── Replay ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Args)]
+struct ReplayArgs {
+    /// Recording file written by `gateway run --record`
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+    /// Simulate mutating tools instead of running them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+// ── Backup ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Subcommand)]
+enum BackupCommands {
+    /// Write an encrypted archive of the agent state
+    Create {
+        /// Archive to write (default: rustyclaw-backup-<date>.age)
+        #[arg(value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Also include the credentials vault
+        #[arg(long)]
+        include_vault: bool,
+        /// Only back up these components (comma-separated: config, memory,
+        /// sessions, skills, cron, vault)
+        #[arg(long, value_name = "LIST")]
+        only: Option<String>,
+    },
+    /// Restore an archive, or only some of its components
+    Restore {
+        /// Archive written by `backup create`
+        #[arg(value_name = "FILE")]
+        archive: PathBuf,
+        /// Only restore these components (comma-separated)
+        #[arg(long, value_name = "LIST")]
+        only: Option<String>,
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Show what an archive contains
+    Inspect {
+        #[arg(value_name = "FILE")]
+        archive: PathBuf,
+    },
+}
+
+// ── Bench ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Subcommand)]
+enum BenchCommands {
+    /// Time a standard prompt against every configured provider and model
+    Providers {
+        /// Only these providers (repeatable; admits local ones like ollama)
+        #[arg(long = "provider", value_name = "ID")]
+        providers: Vec<String>,
+        /// Only this model (repeatable)
+        #[arg(long = "model", value_name = "NAME")]
+        models: Vec<String>,
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 // ── RefreshToken ────────────────────────────────────────────────────────────
 
 #[derive(Debug, Args)]
@@ -271,6 +348,16 @@ enum ConfigCommands {
         #[arg(value_name = "PATH")]
         path: String,
     },
+    /// Encrypt a config value with a key from the vault (stored as "enc:…")
+    EncryptField {
+        /// Dot-separated path in config.toml; array items by index
+        /// (e.g. messengers.0.webhook_url)
+        #[arg(value_name = "PATH")]
+        path: String,
+        /// Value to store instead of the current one
+        #[arg(long)]
+        value: Option<String>,
+    },
 }
 
 // ── Doctor ──────────────────────────────────────────────────────────────────
@@ -387,6 +474,12 @@ struct GatewayRunArgs {
     /// Verbose logging
     #[arg(long, short)]
     verbose: bool,
+    /// Simulate mutating tools instead of running them
+    #[arg(long)]
+    dry_run: bool,
+    /// Record provider calls and tool results to a file for replay
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
 }
 
 // ── Skills subcommands ──────────────────────────────────────────────────────
@@ -517,8 +610,9 @@ async fn main() -> Result<()> {
     rustyclaw_core::theme::init_color(cli.common.no_color);
 
     let config_path = cli.common.config_path();
-    let mut config = Config::load(config_path)?;
+    let mut config = Config::load(config_path.clone())?;
     cli.common.apply_overrides(&mut config);
+    rustyclaw_core::theme::set_palette(config.palette);
 
     match cli.command.unwrap_or(Commands::Tui(TuiArgs::default())) {
         // ── Setup ───────────────────────────────────────────────
@@ -602,6 +696,37 @@ async fn main() -> Result<()> {
             run_refresh_token(&args, &mut config)?;
         }
 
+        // ── Backup ──────────────────────────────────────────────
+        Commands::Backup(sub) => {
+            run_backup(sub, &config)?;
+        }
+
+        // ── Bench ───────────────────────────────────────────────
+        Commands::Bench(sub) => {
+            run_bench(sub, &config).await?;
+        }
+
+        // ── Replay ──────────────────────────────────────────────
+        Commands::Replay(args) => {
+            use rustyclaw_core::recording::{replay, Recording};
+            use rustyclaw_core::theme as t;
+
+            let recording = Recording::load(&args.file).map_err(|e| anyhow::anyhow!(e))?;
+            rustyclaw_core::tools::set_dry_run(args.dry_run || config.dry_run);
+            // Tools run synchronously, custom ones via the runtime, so keep
+            // them off the async workers.
+            let workspace_dir = config.workspace_dir();
+            let report = tokio::task::spawn_blocking(move || replay(&recording, &workspace_dir))
+                .await?
+                .map_err(|e| anyhow::anyhow!(e))?;
+            print!("{}", report.summary());
+            if report.is_clean() {
+                println!("{}", t::icon_ok("Replay matches the recording"));
+            } else {
+                std::process::exit(1);
+            }
+        }
+
         // ── Configure ───────────────────────────────────────────
         Commands::Configure => {
             #[cfg(feature = "tui")]
@@ -637,6 +762,14 @@ async fn main() -> Result<()> {
                         &format!("Unset {}", rustyclaw_core::theme::accent_bright(&path))
                     ));
                 }
+                ConfigCommands::EncryptField { path, value } => {
+                    let file = config_path.unwrap_or_else(|| config.settings_dir.join("config.toml"));
+                    let mut secrets = open_secrets(&config)?;
+                    rustyclaw_core::secrets::config_fields::encrypt_field(&file, &path, value.as_deref(), &mut secrets)?;
+                    println!("{}", rustyclaw_core::theme::icon_ok(
+                        &format!("Encrypted {} in {}", rustyclaw_core::theme::accent_bright(&path), file.display())
+                    ));
+                }
             }
         }
 
@@ -919,6 +1052,11 @@ async fn main() -> Result<()> {
                         _ => "127.0.0.1",
                     };
                     let listen = format!("{}:{}", host, args.port);
+                    if args.dry_run {
+                        config.dry_run = true;
+                        // Sticky: a config reload can't switch it off.
+                        rustyclaw_core::tools::force_dry_run();
+                    }
                     let tls_cert = config.tls_cert.clone();
                     let tls_key = config.tls_key.clone();
                     let scheme = if tls_cert.is_some() { "wss" } else { "ws" };
@@ -978,7 +1116,7 @@ async fn main() -> Result<()> {
                     let shared_skills: rustyclaw_core::gateway::SharedSkillManager =
                         std::sync::Arc::new(tokio::sync::Mutex::new(sm));
 
-                    run_gateway(config, GatewayOptions { listen, tls_cert, tls_key }, model_ctx, shared_vault, shared_skills, cancel).await?;
+                    run_gateway(config, GatewayOptions { listen, tls_cert, tls_key, record: args.record.clone() }, model_ctx, shared_vault, shared_skills, cancel).await?;
                 }
             }
         }
@@ -1369,6 +1507,143 @@ fn open_secrets(config: &Config) -> Result<SecretsManager> {
     Ok(manager)
 }
 
+fn run_backup(sub: BackupCommands, config: &Config) -> Result<()> {
+    use rustyclaw_core::backup::{self, Component};
+    use rustyclaw_core::theme as t;
+
+    let parse = |list: Option<String>| -> Result<Option<Vec<Component>>> {
+        list.map(|l| backup::parse_components(&l).map_err(|e| anyhow::anyhow!(e))).transpose()
+    };
+
+    match sub {
+        BackupCommands::Create { output, include_vault, only } => {
+            let mut components = parse(only)?.unwrap_or_else(Component::defaults);
+            if include_vault && !components.contains(&Component::Vault) {
+                components.push(Component::Vault);
+            }
+            let output = output.unwrap_or_else(|| PathBuf::from(backup::default_file_name()));
+            let passphrase = prompt_password("Backup passphrase: ")?;
+            if passphrase != prompt_password("Repeat passphrase: ")? {
+                anyhow::bail!("Passphrases do not match");
+            }
+            let manifest = backup::create(config, &components, &output, &passphrase)
+                .map_err(|e| anyhow::anyhow!(e))?;
+            let names: Vec<&str> = manifest.components.iter().map(|c| c.as_str()).collect();
+            println!(
+                "{}",
+                t::icon_ok(&format!(
+                    "Backed up {} files ({}) to {}",
+                    manifest.files,
+                    names.join(", "),
+                    output.display()
+                ))
+            );
+        }
+        BackupCommands::Restore { archive, only, yes } => {
+            let only = parse(only)?;
+            let passphrase = prompt_password("Backup passphrase: ")?;
+            let manifest = backup::inspect(&archive, &passphrase).map_err(|e| anyhow::anyhow!(e))?;
+            let names: Vec<&str> = manifest
+                .components
+                .iter()
+                .filter(|c| only.as_ref().is_none_or(|o| o.contains(c)))
+                .map(|c| c.as_str())
+                .collect();
+            println!("Backup from {} (RustyClaw {})", manifest.created, manifest.rustyclaw);
+            if !yes {
+                use std::io::Write;
+                print!(
+                    "Restore {}? Current files are moved aside, not deleted. [y/N] ",
+                    names.join(", ")
+                );
+                std::io::stdout().flush()?;
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    println!("{}", t::muted("Restore cancelled."));
+                    return Ok(());
+                }
+            }
+            let report = backup::restore(config, &archive, &passphrase, only.as_deref())
+                .map_err(|e| anyhow::anyhow!(e))?;
+            for path in &report.moved_aside {
+                println!("  {}", t::muted(&format!("moved aside {}", path.display())));
+            }
+            println!("{}", t::icon_ok(&format!("Restored {} files ({})", report.files, names.join(", "))));
+            if report.components.contains(&Component::Config) || report.components.contains(&Component::Vault) {
+                println!("{}", t::muted("Restart the gateway to pick up the restored config."));
+            }
+        }
+        BackupCommands::Inspect { archive } => {
+            let passphrase = prompt_password("Backup passphrase: ")?;
+            let manifest = backup::inspect(&archive, &passphrase).map_err(|e| anyhow::anyhow!(e))?;
+            println!("Created:    {}", manifest.created);
+            println!("RustyClaw:  {}", manifest.rustyclaw);
+            println!("Files:      {}", manifest.files);
+            let names: Vec<&str> = manifest.components.iter().map(|c| c.as_str()).collect();
+            println!("Components: {}", names.join(", "));
+        }
+    }
+    Ok(())
+}
+
+async fn run_bench(sub: BenchCommands, config: &Config) -> Result<()> {
+    use rustyclaw_core::gateway::bench::{self, BenchStore};
+    use rustyclaw_core::theme as t;
+
+    let BenchCommands::Providers { providers, models, json } = sub;
+    let mut secrets = open_secrets(config)?;
+    let mut targets = bench::targets(config, &mut secrets, &providers);
+    if !models.is_empty() {
+        targets.retain(|target| models.contains(&target.model));
+    }
+    if targets.is_empty() {
+        anyhow::bail!("Nothing to benchmark: no provider has an API key (pass --provider for local ones)");
+    }
+
+    let path = BenchStore::path(&config.settings_dir);
+    let mut store = BenchStore::load(&path);
+    let mut results = Vec::new();
+    for target in &targets {
+        let label = format!("{}/{}", target.provider, target.model);
+        let spinner = (!json).then(|| t::spinner(&label));
+        let result = bench::run(target).await;
+        if let Some(pb) = spinner {
+            match (&result.error, result.first_token_ms) {
+                (None, Some(first)) => {
+                    let cost = result.cost_usd.map(|c| format!("${:.5}", c)).unwrap_or_else(|| "—".to_string());
+                    t::spinner_ok(
+                        &pb,
+                        &format!("{:<40} first token {:>6} ms  total {:>6} ms  {}", label, first, result.total_ms, cost),
+                    );
+                }
+                (error, _) => {
+                    let reason = error.as_deref().unwrap_or("no reply");
+                    t::spinner_fail(&pb, &format!("{:<40} {}", label, reason));
+                }
+            }
+        }
+        store.record(result.clone());
+        results.push(result);
+    }
+    store.save(&path).map_err(|e| anyhow::anyhow!(e))?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        let fastest = results
+            .iter()
+            .filter(|r| r.error.is_none())
+            .filter_map(|r| r.first_token_ms.map(|ms| (ms, r)))
+            .min_by_key(|(ms, _)| *ms);
+        if let Some((_, r)) = fastest {
+            println!("{}", t::muted(&format!("Fastest first token: {}/{}", r.provider, r.model)));
+        }
+        println!("{}", t::muted(&format!("Saved to {}", path.display())));
+    }
+    Ok(())
+}
+
 fn prompt_password(prompt: &str) -> Result<String> {
     use std::io::{self, Write};
     print!("{}", prompt);
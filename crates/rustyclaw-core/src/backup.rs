@@ -0,0 +1,416 @@
+//! Backup and restore of the complete agent state.
+//!
+//! `rustyclaw backup create` packs the agent's state into one zip archive,
+//! encrypted with a passphrase (age), so it can move to a new machine or
+//! roll back a bad week.  The archive holds a `manifest.json` and one
+//! top-level directory per [`Component`]:
+//!
+//! | component  | contents                                          |
+//! |------------|---------------------------------------------------|
+//! | `config`   | `config.toml`                                     |
+//! | `memory`   | `MEMORY.md`, `USER.md` and `memory/`              |
+//! | `sessions` | session transcripts and workspace conversations   |
+//! | `skills`   | the skills directory                              |
+//! | `cron`     | cron jobs and their run history (`.cron/`)        |
+//! | `vault`    | the encrypted credentials vault (opt-in)          |
+//!
+//! `rustyclaw backup restore` can restore every component in an archive or
+//! only some of them.  Whatever a restored component replaces is moved
+//! aside to `<name>.pre-restore-<timestamp>` rather than deleted.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::secrets::age_crypt::{self, Protection};
+
+/// Version of the archive layout.
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+/// A part of the agent state that is backed up and restored as a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Component {
+    Config,
+    Memory,
+    Sessions,
+    Skills,
+    Cron,
+    Vault,
+}
+
+impl Component {
+    /// Every component, in archive order.
+    pub const ALL: [Component; 6] = [
+        Self::Config,
+        Self::Memory,
+        Self::Sessions,
+        Self::Skills,
+        Self::Cron,
+        Self::Vault,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Memory => "memory",
+            Self::Sessions => "sessions",
+            Self::Skills => "skills",
+            Self::Cron => "cron",
+            Self::Vault => "vault",
+        }
+    }
+
+    /// Components backed up by default — everything but the vault.
+    pub fn defaults() -> Vec<Component> {
+        Self::ALL.into_iter().filter(|c| *c != Self::Vault).collect()
+    }
+}
+
+impl std::str::FromStr for Component {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|c| c.as_str()).collect();
+                format!("Unknown component '{}' (expected one of: {})", s, names.join(", "))
+            })
+    }
+}
+
+/// Parse a comma-separated component list such as `memory,cron`.
+pub fn parse_components(list: &str) -> Result<Vec<Component>, String> {
+    let mut components = Vec::new();
+    for name in list.split(',').filter(|s| !s.trim().is_empty()) {
+        let component: Component = name.parse()?;
+        if !components.contains(&component) {
+            components.push(component);
+        }
+    }
+    if components.is_empty() {
+        return Err("No components given".to_string());
+    }
+    Ok(components)
+}
+
+/// Describes what an archive holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    /// RFC 3339 time the backup was made.
+    pub created: String,
+    /// RustyClaw version that made it.
+    pub rustyclaw: String,
+    pub components: Vec<Component>,
+    /// Number of files in the archive, manifest excluded.
+    pub files: usize,
+}
+
+/// Outcome of a restore.
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub components: Vec<Component>,
+    pub files: usize,
+    /// Existing files and directories that were moved aside.
+    pub moved_aside: Vec<PathBuf>,
+}
+
+/// Paths a component covers, as (archive prefix, location on disk).  A
+/// location may be a file or a directory.
+fn sources(config: &Config, component: Component) -> Vec<(&'static str, PathBuf)> {
+    let workspace = config.workspace_dir();
+    match component {
+        Component::Config => vec![("config/config.toml", config.settings_dir.join("config.toml"))],
+        Component::Memory => vec![
+            ("memory/MEMORY.md", workspace.join("MEMORY.md")),
+            ("memory/USER.md", workspace.join("USER.md")),
+            ("memory/memory", workspace.join("memory")),
+        ],
+        Component::Sessions => vec![
+            ("sessions/sessions", config.sessions_dir()),
+            ("sessions/conversations", crate::conversations::conversations_dir(&workspace)),
+        ],
+        Component::Skills => vec![("skills/skills", config.skills_dir())],
+        Component::Cron => vec![("cron/cron", workspace.join(".cron"))],
+        Component::Vault => vec![("vault/credentials", config.credentials_dir())],
+    }
+}
+
+/// Write `components` of the agent state to an unencrypted zip at `dst`.
+pub fn write_archive(config: &Config, components: &[Component], dst: &Path) -> Result<Manifest, String> {
+    let file = File::create(dst).map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    let mut files = 0;
+    for component in Component::ALL.into_iter().filter(|c| components.contains(c)) {
+        for (prefix, path) in sources(config, component) {
+            for entry in walkdir::WalkDir::new(&path).follow_links(false).sort_by_file_name() {
+                let Ok(entry) = entry else {
+                    // Missing paths just aren't backed up.
+                    continue;
+                };
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let rel = entry.path().strip_prefix(&path).unwrap_or(Path::new(""));
+                let mut name = prefix.to_string();
+                for part in rel.components() {
+                    name.push('/');
+                    name.push_str(&part.as_os_str().to_string_lossy());
+                }
+                zip.start_file(name.as_str(), options)
+                    .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+                let mut input = File::open(entry.path())
+                    .map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+                std::io::copy(&mut input, &mut zip)
+                    .map_err(|e| format!("Failed to add {}: {}", entry.path().display(), e))?;
+                files += 1;
+            }
+        }
+    }
+
+    let manifest = Manifest {
+        version: FORMAT_VERSION,
+        created: chrono::Local::now().to_rfc3339(),
+        rustyclaw: env!("CARGO_PKG_VERSION").to_string(),
+        components: Component::ALL.into_iter().filter(|c| components.contains(c)).collect(),
+        files,
+    };
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.start_file(MANIFEST, options).map_err(|e| e.to_string())?;
+    std::io::Write::write_all(&mut zip, &json).map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| format!("Failed to finish {}: {}", dst.display(), e))?;
+    Ok(manifest)
+}
+
+fn open_archive(path: &Path) -> Result<zip::ZipArchive<BufReader<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("{} is not a backup archive: {}", path.display(), e))
+}
+
+fn manifest_of(archive: &mut zip::ZipArchive<BufReader<File>>) -> Result<Manifest, String> {
+    let entry = archive.by_name(MANIFEST).map_err(|_| "Archive has no manifest — not a RustyClaw backup".to_string())?;
+    let manifest: Manifest = serde_json::from_reader(entry).map_err(|e| format!("Invalid manifest: {}", e))?;
+    if manifest.version > FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} is newer than this RustyClaw supports ({})",
+            manifest.version, FORMAT_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Read the manifest of an unencrypted archive.
+pub fn read_manifest(src: &Path) -> Result<Manifest, String> {
+    manifest_of(&mut open_archive(src)?)
+}
+
+/// Relative path of `name` below `prefix`, or `None` if it isn't below it
+/// or would escape it.
+fn below(name: &str, prefix: &str) -> Option<PathBuf> {
+    let rest = if name == prefix {
+        ""
+    } else {
+        name.strip_prefix(prefix)?.strip_prefix('/')?
+    };
+    let mut rel = PathBuf::new();
+    for part in rest.split('/').filter(|p| !p.is_empty()) {
+        if part == "." || part == ".." || part.contains('\\') || part.contains(':') {
+            return None;
+        }
+        rel.push(part);
+    }
+    Some(rel)
+}
+
+/// Move `path` aside to `<name>.pre-restore-<stamp>`.
+fn move_aside(path: &Path, stamp: &str) -> Result<PathBuf, String> {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let aside = path.with_file_name(format!("{}.pre-restore-{}", name, stamp));
+    std::fs::rename(path, &aside).map_err(|e| format!("Failed to move {} aside: {}", path.display(), e))?;
+    Ok(aside)
+}
+
+/// Restore an unencrypted archive.  `only` limits the restore to some
+/// components; those must be in the archive.
+pub fn extract_archive(config: &Config, src: &Path, only: Option<&[Component]>) -> Result<RestoreReport, String> {
+    let mut archive = open_archive(src)?;
+    let manifest = manifest_of(&mut archive)?;
+    let components: Vec<Component> = match only {
+        Some(only) => {
+            if let Some(missing) = only.iter().find(|c| !manifest.components.contains(c)) {
+                return Err(format!("The backup has no {} component", missing.as_str()));
+            }
+            manifest.components.iter().copied().filter(|c| only.contains(c)).collect()
+        }
+        None => manifest.components.clone(),
+    };
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut report = RestoreReport { components: components.clone(), ..Default::default() };
+    let targets: Vec<(&str, PathBuf)> = components.iter().flat_map(|c| sources(config, *c)).collect();
+    for (_, path) in &targets {
+        if path.symlink_metadata().is_ok() {
+            report.moved_aside.push(move_aside(path, &stamp)?);
+        }
+    }
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        if entry.is_dir() || entry.name() == MANIFEST {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let Some((path, rel)) = targets
+            .iter()
+            .find_map(|(prefix, path)| below(&name, prefix).map(|rel| (path, rel)))
+        else {
+            continue;
+        };
+        // An empty `rel` is a single-file source like `config.toml`.
+        let dst = if rel.as_os_str().is_empty() { path.clone() } else { path.join(rel) };
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut output = File::create(&dst).map_err(|e| format!("Failed to write {}: {}", dst.display(), e))?;
+        std::io::copy(&mut entry, &mut output).map_err(|e| format!("Failed to write {}: {}", dst.display(), e))?;
+        report.files += 1;
+    }
+    Ok(report)
+}
+
+/// `rustyclaw-backup-<date>.age`.
+pub fn default_file_name() -> String {
+    format!("rustyclaw-backup-{}.age", chrono::Local::now().format("%Y-%m-%d"))
+}
+
+/// Temporary file next to `path` for the unencrypted archive.
+fn scratch_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.zip", name, std::process::id()))
+}
+
+/// Back up `components` to `dst`, encrypted with `passphrase`.
+pub fn create(config: &Config, components: &[Component], dst: &Path, passphrase: &str) -> Result<Manifest, String> {
+    if passphrase.is_empty() {
+        return Err("A passphrase is required".to_string());
+    }
+    let scratch = scratch_path(dst);
+    let result = write_archive(config, components, &scratch)
+        .and_then(|manifest| age_crypt::encrypt_file(&scratch, dst, Protection::Passphrase(passphrase), false).map(|_| manifest));
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+/// Decrypt `src` to a scratch file, run `f` on it and clean up.
+fn with_decrypted<T>(src: &Path, passphrase: &str, f: impl FnOnce(&Path) -> Result<T, String>) -> Result<T, String> {
+    let scratch = std::env::temp_dir().join(format!("rustyclaw-restore-{}.zip", std::process::id()));
+    let result = age_crypt::decrypt_file(src, &scratch, &[], Some(passphrase)).and_then(|_| f(&scratch));
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+/// Manifest of the encrypted backup `src`.
+pub fn inspect(src: &Path, passphrase: &str) -> Result<Manifest, String> {
+    with_decrypted(src, passphrase, read_manifest)
+}
+
+/// Restore the encrypted backup `src`, or only the `only` components of it.
+pub fn restore(config: &Config, src: &Path, passphrase: &str, only: Option<&[Component]>) -> Result<RestoreReport, String> {
+    with_decrypted(src, passphrase, |archive| extract_archive(config, archive, only))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(root: &Path) -> Config {
+        Config {
+            settings_dir: root.to_path_buf(),
+            workspace_dir: Some(root.join("workspace")),
+            ..Default::default()
+        }
+    }
+
+    fn write(path: &Path, text: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn test_parse_components() {
+        assert_eq!(parse_components("memory, Cron,memory").unwrap(), vec![Component::Memory, Component::Cron]);
+        assert!(parse_components("memory,photos").unwrap_err().contains("photos"));
+        assert!(!Component::defaults().contains(&Component::Vault));
+    }
+
+    #[test]
+    fn test_roundtrip_to_new_machine() {
+        let old = tempfile::tempdir().unwrap();
+        let cfg = config(old.path());
+        let ws = cfg.workspace_dir();
+        write(&old.path().join("config.toml"), "[model]\n");
+        write(&ws.join("MEMORY.md"), "likes tea");
+        write(&ws.join("memory/people/ada.md"), "Ada");
+        write(&ws.join(".cron/jobs.json"), "[]");
+        write(&cfg.credentials_dir().join("secrets.json"), "secret");
+
+        let zip = old.path().join("backup.zip");
+        let manifest = write_archive(&cfg, &Component::defaults(), &zip).unwrap();
+        assert_eq!(manifest.files, 4);
+        assert!(!manifest.components.contains(&Component::Vault));
+        assert_eq!(read_manifest(&zip).unwrap(), manifest);
+
+        let new = tempfile::tempdir().unwrap();
+        let cfg = config(new.path());
+        let report = extract_archive(&cfg, &zip, None).unwrap();
+        assert_eq!(report.files, 4);
+        assert!(report.moved_aside.is_empty());
+        let ws = cfg.workspace_dir();
+        assert_eq!(std::fs::read_to_string(ws.join("memory/people/ada.md")).unwrap(), "Ada");
+        assert_eq!(std::fs::read_to_string(new.path().join("config.toml")).unwrap(), "[model]\n");
+        assert!(!cfg.credentials_dir().exists());
+    }
+
+    #[test]
+    fn test_selective_restore_moves_current_state_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = config(dir.path());
+        let ws = cfg.workspace_dir();
+        write(&dir.path().join("config.toml"), "old config");
+        write(&ws.join("MEMORY.md"), "good week");
+        let zip = dir.path().join("backup.zip");
+        write_archive(&cfg, &[Component::Config, Component::Memory], &zip).unwrap();
+
+        write(&dir.path().join("config.toml"), "new config");
+        write(&ws.join("MEMORY.md"), "bad week");
+        write(&ws.join("memory/junk.md"), "junk");
+
+        let report = extract_archive(&cfg, &zip, Some(&[Component::Memory])).unwrap();
+        assert_eq!(report.components, vec![Component::Memory]);
+        assert_eq!(report.moved_aside.len(), 2);
+        assert_eq!(std::fs::read_to_string(ws.join("MEMORY.md")).unwrap(), "good week");
+        assert!(!ws.join("memory/junk.md").exists());
+        assert_eq!(std::fs::read_to_string(dir.path().join("config.toml")).unwrap(), "new config");
+
+        assert!(extract_archive(&cfg, &zip, Some(&[Component::Vault])).unwrap_err().contains("vault"));
+    }
+
+    #[test]
+    fn test_entries_cannot_escape_their_target() {
+        assert_eq!(below("memory/memory/a/b.md", "memory/memory"), Some(PathBuf::from("a/b.md")));
+        assert_eq!(below("memory/MEMORY.md", "memory/MEMORY.md"), Some(PathBuf::new()));
+        assert_eq!(below("memory/memoryx/a", "memory/memory"), None);
+        assert_eq!(below("memory/memory/../../etc/passwd", "memory/memory"), None);
+    }
+}
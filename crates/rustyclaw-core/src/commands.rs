@@ -28,10 +28,28 @@ pub enum CommandAction {
     ShowProviderSelector,
     /// Show the tool permissions dialog
     ShowToolPermissions,
+    /// Show the workspace picker dialog
+    ShowWorkspacePicker,
     /// Reload gateway configuration
     GatewayReload,
     /// Download media by ID (id, optional destination path)
     Download(String, Option<String>),
+    /// Resume the turn interrupted by an unexpected exit
+    ResumeSession,
+    /// Discard the interrupted turn journal
+    DiscardSession,
+    /// Pin a sticky topic note to the session (`None`: show the current one)
+    PinTopic(Option<String>),
+    /// Remove the pinned topic
+    UnpinTopic,
+    /// Watch a session or cron job in the split pane (`None`: close it)
+    SplitView(Option<String>),
+    /// Show the notification center
+    ShowNotifications,
+    /// Show the gateway's recent log events (count, minimum level)
+    ShowEvents { limit: u32, level: Option<String> },
+    /// Override dry-run for this session (`None`: back to the config)
+    SetDryRun(Option<bool>),
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +71,23 @@ pub fn command_names() -> Vec<String> {
         "help".into(),
         "clear".into(),
         "download".into(),
+        "resume".into(),
+        "resume discard".into(),
+        "pin".into(),
+        "unpin".into(),
+        "undo".into(),
+        "split".into(),
+        "split session".into(),
+        "split cron".into(),
+        "split off".into(),
+        "notifications".into(),
+        "events".into(),
+        "events warn".into(),
+        "events error".into(),
+        "dryrun".into(),
+        "dryrun on".into(),
+        "dryrun off".into(),
+        "dryrun default".into(),
         "enable-access".into(),
         "disable-access".into(),
         "onboard".into(),
@@ -67,6 +102,15 @@ pub fn command_names() -> Vec<String> {
         "skills".into(),
         "skill".into(),
         "tools".into(),
+        "workspace".into(),
+        "workspace add".into(),
+        "workspace remove".into(),
+        "workspace roots".into(),
+        "conversations".into(),
+        "conversations list".into(),
+        "conversations show".into(),
+        "conversations clear".into(),
+        "conversations export".into(),
         "skill info".into(),
         "skill remove".into(),
         "skill search".into(),
@@ -211,6 +255,15 @@ pub fn handle_command(input: &str, context: &mut CommandContext<'_>) -> CommandR
                 "  /help                    - Show this help".to_string(),
                 "  /clear                   - Clear messages and conversation memory".to_string(),
                 "  /download <id> [path]    - Download media attachment to file".to_string(),
+                "  /resume [discard]        - Resume (or discard) an interrupted session".to_string(),
+                "  /pin [note]              - Pin a topic note to every prompt (no note: show it)".to_string(),
+                "  /unpin                   - Remove the pinned topic".to_string(),
+                "  /undo                    - Restore the most recently deleted file from the trash".to_string(),
+                "  /split session|cron <id> - Watch a sub-agent or cron job below the chat (Tab: focus)".to_string(),
+                "  /split off               - Close the split pane".to_string(),
+                "  /notifications           - Background events: sub-agents, cron, pairing, messengers (Ctrl+N)".to_string(),
+                "  /events [n] [level]      - Show the gateway's last n log events (error/warn/info/debug)".to_string(),
+                "  /dryrun [on|off|default] - Simulate mutating tools this session".to_string(),
                 "  /enable-access           - Enable agent access to secrets".to_string(),
                 "  /disable-access          - Disable agent access to secrets".to_string(),
                 "  /onboard                 - Run setup wizard (use CLI: rustyclaw onboard)".to_string(),
@@ -225,6 +278,11 @@ pub fn handle_command(input: &str, context: &mut CommandContext<'_>) -> CommandR
                 "  /skills                  - Show loaded skills".to_string(),
                 "  /skill                   - Skill management (info/install/publish/link)".to_string(),
                 "  /tools                   - Edit tool permissions (allow/deny/ask/skill)".to_string(),
+                "  /workspace [path]        - Switch workspace (no path: open the picker)".to_string(),
+                "  /workspace add <path>    - Add an extra root for find/search tools".to_string(),
+                "  /workspace remove <path> - Remove an extra search root".to_string(),
+                "  /conversations [list|show|clear|export] [messenger] [chat]".to_string(),
+                "                           - Browse or archive messenger chat history".to_string(),
                 "  /secrets                 - Open the secrets vault".to_string(),
                 "  /clawhub                 - ClawHub skill registry commands".to_string(),
                 "  /agent setup             - Set up local model tools (uv, exo, ollama)".to_string(),
@@ -232,6 +290,8 @@ pub fn handle_command(input: &str, context: &mut CommandContext<'_>) -> CommandR
                 "  /exo <action> [model]    - Exo cluster admin (setup/start/stop/status/…)".to_string(),
                 "  /uv <action> [pkg …]     - Python/uv admin (setup/pip-install/list/…)".to_string(),
                 "  /npm <action> [pkg …]    - Node.js/npm admin (setup/install/run/build/…)".to_string(),
+                "".to_string(),
+                "  Ctrl+K                   - Command palette (commands, recent files, sessions, skills)".to_string(),
             ],
             action: CommandAction::None,
         },
@@ -258,6 +318,123 @@ pub fn handle_command(input: &str, context: &mut CommandContext<'_>) -> CommandR
                 }
             }
         },
+        "resume" => match parts.get(1).copied() {
+            None => CommandResponse {
+                messages: vec!["Resuming interrupted session...".to_string()],
+                action: CommandAction::ResumeSession,
+            },
+            Some("discard") => CommandResponse {
+                messages: vec!["Interrupted session discarded.".to_string()],
+                action: CommandAction::DiscardSession,
+            },
+            Some(other) => CommandResponse {
+                messages: vec![format!("Unknown resume option: {}. Usage: /resume [discard]", other)],
+                action: CommandAction::None,
+            },
+        },
+        "pin" => {
+            // Keep the note's own spacing: take everything after "pin".
+            let note = trimmed["pin".len()..].trim();
+            CommandResponse {
+                messages: Vec::new(),
+                action: CommandAction::PinTopic((!note.is_empty()).then(|| note.to_string())),
+            }
+        }
+        "unpin" => CommandResponse {
+            messages: Vec::new(),
+            action: CommandAction::UnpinTopic,
+        },
+        "undo" => {
+            let dir = crate::trash::trash_dir(&context.config.trash, &context.config.settings_dir);
+            let message = match crate::trash::restore(&dir, None) {
+                Ok(entry) => format!("Restored {} from the trash.", entry.original.display()),
+                Err(e) => e,
+            };
+            CommandResponse {
+                messages: vec![message],
+                action: CommandAction::None,
+            }
+        }
+        "notifications" => CommandResponse {
+            messages: Vec::new(),
+            action: CommandAction::ShowNotifications,
+        },
+        "events" => {
+            let mut limit = 30u32;
+            let mut level = None;
+            let mut bad = None;
+            for arg in &parts[1..] {
+                if let Ok(n) = arg.parse::<u32>() {
+                    limit = n.clamp(1, 1000);
+                } else if arg.parse::<tracing::Level>().is_ok() {
+                    level = Some(arg.to_lowercase());
+                } else {
+                    bad = Some(*arg);
+                }
+            }
+            match bad {
+                Some(arg) => CommandResponse {
+                    messages: vec![
+                        format!("Unknown argument: {}", arg),
+                        "Usage: /events [n] [error|warn|info|debug]".to_string(),
+                    ],
+                    action: CommandAction::None,
+                },
+                None => CommandResponse {
+                    messages: Vec::new(),
+                    action: CommandAction::ShowEvents { limit, level },
+                },
+            }
+        }
+        "split" => {
+            let spec = parts[1..].join(" ");
+            match parts.get(1).copied() {
+                None | Some("off") => CommandResponse {
+                    messages: Vec::new(),
+                    action: CommandAction::SplitView(None),
+                },
+                Some(_) => match crate::gateway::session_view::WatchTarget::parse(&spec) {
+                    Ok(_) => CommandResponse {
+                        messages: vec![format!("Watching {} — Tab switches focus.", spec)],
+                        action: CommandAction::SplitView(Some(spec)),
+                    },
+                    Err(e) => CommandResponse {
+                        messages: vec![format!("{}. Usage: /split session <key|label> | cron <jobId> | off", e)],
+                        action: CommandAction::None,
+                    },
+                },
+            }
+        }
+        "dryrun" => {
+            let enabled = match parts.get(1).copied() {
+                None => {
+                    let state = if context.config.dry_run { "on" } else { "off" };
+                    return CommandResponse {
+                        messages: vec![format!(
+                            "Dry-run mode is {} in config.toml. Usage: /dryrun [on|off|default]",
+                            state
+                        )],
+                        action: CommandAction::None,
+                    };
+                }
+                Some("on") => Some(true),
+                Some("off") => Some(false),
+                Some("default") => None,
+                Some(other) => {
+                    return CommandResponse {
+                        messages: vec![format!("Unknown dryrun option: {}. Usage: /dryrun [on|off|default]", other)],
+                        action: CommandAction::None,
+                    };
+                }
+            };
+            // Session-only: the gateway executes tools, and forgets the
+            // override when this client disconnects.  config.toml is left
+            // alone.
+            CommandResponse {
+                messages: Vec::new(),
+                action: CommandAction::SetDryRun(enabled),
+            }
+        }
         "enable-access" => {
             context.secrets_manager.set_agent_access(true);
             context.config.agent_access = true;
@@ -333,6 +510,8 @@ pub fn handle_command(input: &str, context: &mut CommandContext<'_>) -> CommandR
             messages: Vec::new(),
             action: CommandAction::ShowToolPermissions,
         },
+        "workspace" => handle_workspace_subcommand(&parts[1..], context),
+        "conversations" => handle_conversations_subcommand(&parts[1..], context),
         "skill" => handle_skill_subcommand(&parts[1..], context),
         "secrets" => CommandResponse {
             messages: Vec::new(),
@@ -387,6 +566,99 @@ pub fn handle_command(input: &str, context: &mut CommandContext<'_>) -> CommandR
     }
 }
 
+fn handle_workspace_subcommand(parts: &[&str], context: &mut CommandContext<'_>) -> CommandResponse {
+    let reply = |messages: Vec<String>, action| CommandResponse { messages, action };
+    match parts.first().copied() {
+        None => reply(Vec::new(), CommandAction::ShowWorkspacePicker),
+        Some("roots") => {
+            let mut messages = vec![format!("Workspace: {}", context.config.workspace_dir().display())];
+            if context.config.extra_roots.is_empty() {
+                messages.push("No extra search roots. Add one with /workspace add <path>".to_string());
+            } else {
+                messages.push("Extra search roots:".to_string());
+                for root in &context.config.extra_roots {
+                    messages.push(format!("  {}", root.display()));
+                }
+            }
+            reply(messages, CommandAction::None)
+        }
+        Some(sub @ ("add" | "remove")) => {
+            if parts.len() < 2 {
+                return reply(
+                    vec![format!("Usage: /workspace {} <path>", sub)],
+                    CommandAction::None,
+                );
+            }
+            let raw = crate::tools::expand_tilde(&parts[1..].join(" "));
+            let root = raw.canonicalize().unwrap_or(raw);
+            let roots = &mut context.config.extra_roots;
+            let msg = if sub == "add" {
+                if !root.is_dir() {
+                    return reply(
+                        vec![format!("Not a directory: {}", root.display())],
+                        CommandAction::None,
+                    );
+                }
+                if roots.contains(&root) {
+                    return reply(
+                        vec![format!("Already a search root: {}", root.display())],
+                        CommandAction::None,
+                    );
+                }
+                roots.push(root.clone());
+                format!("Added search root: {}", root.display())
+            } else {
+                let before = roots.len();
+                roots.retain(|r| *r != root);
+                if roots.len() == before {
+                    return reply(
+                        vec![format!("Not a search root: {}", root.display())],
+                        CommandAction::None,
+                    );
+                }
+                format!("Removed search root: {}", root.display())
+            };
+            let _ = context.config.save(None);
+            reply(vec![msg], CommandAction::GatewayReload)
+        }
+        Some(_) => {
+            let path = crate::tools::expand_tilde(&parts.join(" "));
+            match context.config.set_workspace(&path) {
+                Ok(ws) => {
+                    let _ = context.config.save(None);
+                    reply(
+                        vec![format!("Workspace switched to {}", ws.display())],
+                        CommandAction::GatewayReload,
+                    )
+                }
+                Err(e) => reply(vec![format!("Cannot switch workspace: {}", e)], CommandAction::None),
+            }
+        }
+    }
+}
+
+fn handle_conversations_subcommand(parts: &[&str], context: &mut CommandContext<'_>) -> CommandResponse {
+    let action = parts.first().copied().unwrap_or("list");
+    let mut args = serde_json::json!({ "action": action });
+    if let Some(messenger) = parts.get(1) {
+        args["messenger"] = (*messenger).into();
+    }
+    if let Some(chat) = parts.get(2) {
+        args["chat"] = (*chat).into();
+    }
+    if let Some(output) = parts.get(3) {
+        args["output"] = (*output).into();
+    }
+    let messages = match crate::tools::execute_tool("conversations", &args, &context.config.workspace_dir()) {
+        Ok(text) => text.lines().map(str::to_string).collect(),
+        Err(e) => vec![format!("conversations error: {}", e)],
+    };
+    CommandResponse {
+        messages,
+        action: CommandAction::None,
+    }
+}
+
 fn handle_skill_subcommand(parts: &[&str], context: &mut CommandContext<'_>) -> CommandResponse {
     match parts.first().copied() {
         Some("info") => {
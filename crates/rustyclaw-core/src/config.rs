@@ -1,9 +1,25 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::contacts::ContactsConfig;
+use crate::container::ExecutionConfig;
+use crate::dev_env::DevEnvMode;
+use crate::heartbeat::HeartbeatConfig;
+use crate::lsp::LspServerConfig;
 use crate::memory_flush::MemoryFlushConfig;
+use crate::mqtt::MqttConfig;
+use crate::presence::PresenceConfig;
+use crate::translate::TranslationConfig;
+use crate::sessions::DelegationPolicy;
+use crate::secrets::config_fields::{self, SealedFields};
+use crate::secrets::SecretsManager;
+use crate::snapshots::SnapshotConfig;
+use crate::task_queue::TaskQueueConfig;
+use crate::theme::PaletteName;
+use crate::tool_servers::ToolServerConfig;
+use crate::users::UsersConfig;
 use crate::workspace_context::WorkspaceContextConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,7 +33,7 @@ pub struct ModelProvider {
 }
 
 /// Sandbox configuration for agent isolation.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandboxConfig {
     /// Sandbox mode: "none", "path", "bwrap", "landlock"
     #[serde(default)]
@@ -28,6 +44,47 @@ pub struct SandboxConfig {
     /// Paths to allow in strict mode
     #[serde(default)]
     pub allow_paths: Vec<PathBuf>,
+    /// Keep tool writes inside the workspace (and the temp directory)
+    /// unless a path matches `allow_write` (default: true).
+    #[serde(default = "default_true")]
+    pub confine_writes: bool,
+    /// Globs outside the workspace that tools may write to,
+    /// e.g. `["~/notes", "~/.config/app/*.toml"]`.
+    #[serde(default)]
+    pub allow_write: Vec<String>,
+    /// Globs tools may never write to, even inside the workspace,
+    /// e.g. `[".git", "*.env"]`.  Defaults to the project overlay
+    /// directory, so the agent can't rewrite its own project settings.
+    #[serde(default = "default_deny_write")]
+    pub deny_write: Vec<String>,
+}
+
+fn default_deny_write() -> Vec<String> {
+    vec![crate::project::PROJECT_DIR.to_string()]
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            mode: String::new(),
+            deny_paths: Vec::new(),
+            allow_paths: Vec::new(),
+            confine_writes: true,
+            allow_write: Vec::new(),
+            deny_write: default_deny_write(),
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// The write policy the file tools and `execute_command` enforce.
+    pub fn write_policy(&self) -> crate::sandbox::WritePolicy {
+        crate::sandbox::WritePolicy {
+            confine: self.confine_writes,
+            allow: self.allow_write.clone(),
+            deny: self.deny_write.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +96,14 @@ pub struct Config {
     pub soul_path: Option<PathBuf>,
     /// Skills directory (default: `<workspace_dir>/skills`)
     pub skills_dir: Option<PathBuf>,
+    /// WASM plugins directory (default: `<settings_dir>/plugins`)
+    #[serde(default)]
+    pub plugins_dir: Option<PathBuf>,
+    /// Capabilities granted to each WASM plugin, by plugin name.  A
+    /// plugin's own manifest only requests capabilities; it gets the part
+    /// of the request granted here.
+    #[serde(default)]
+    pub plugin_grants: HashMap<String, PluginGrant>,
     /// Agent workspace directory (default: `<settings_dir>/workspace`)
     pub workspace_dir: Option<PathBuf>,
     /// Credentials directory (default: `<settings_dir>/credentials`)
@@ -71,6 +136,18 @@ pub struct Config {
     /// Set to 0 for compact output, 1 (default) for comfortable spacing.
     #[serde(default = "Config::default_message_spacing")]
     pub message_spacing: u16,
+    /// Vim-style modal editing (normal/insert) in the TUI input bar.
+    #[serde(default)]
+    pub vim_mode: bool,
+    /// Interface language (`en`, `de`, `es`).  Unset ⇒ taken from
+    /// `LC_ALL` / `LC_MESSAGES` / `LANG`, falling back to English.
+    /// Prompts sent to the model are not affected.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Colour palette for the TUI and CLI output: `default`, `red-green`
+    /// (deuteranopia/protanopia) or `blue-yellow` (tritanopia).
+    #[serde(default)]
+    pub palette: PaletteName,
     /// Number of spaces a tab character occupies in the TUI.
     /// Defaults to 5.
     #[serde(default = "Config::default_tab_width")]
@@ -90,6 +167,16 @@ pub struct Config {
     /// Messenger polling interval in milliseconds (default: 2000).
     #[serde(default)]
     pub messenger_poll_interval_ms: Option<u32>,
+    /// How long to wait for another message from the same sender before
+    /// answering, in milliseconds; messages sent within the window go to
+    /// the model as one prompt.  Unset or 0 answers each message on its own.
+    #[serde(default)]
+    pub messenger_debounce_ms: Option<u32>,
+    /// Messages per minute one sender may send before further messages are
+    /// ignored.  Ids linked to the same contact count together (see
+    /// [`crate::identity`]).  Unset or 0 means no limit.
+    #[serde(default)]
+    pub messenger_rate_limit: Option<u32>,
     /// Per-tool permission overrides. Tools not listed here default to Allow.
     #[serde(default)]
     pub tool_permissions: HashMap<String, crate::tools::ToolPermission>,
@@ -99,6 +186,19 @@ pub struct Config {
     /// Path to TLS private key file (PEM) for WSS gateway connections.
     #[serde(default)]
     pub tls_key: Option<PathBuf>,
+    /// Gateway frames of at least this many bytes are zstd-compressed when
+    /// both ends support it.  0 turns compression off.
+    #[serde(default = "Config::default_compression_threshold")]
+    pub compression_threshold: usize,
+    /// Address for the HTTP health, metrics, tasks, presence and calendar
+    /// endpoints, e.g. `127.0.0.1:9090`.  Unset leaves them off.
+    #[serde(default)]
+    pub health_listen: Option<String>,
+    /// Bearer token the health listener requires on POSTs (queueing tasks,
+    /// reporting locations).  Unset keeps the listener read-only.  Store it
+    /// encrypted (`enc:`) like any other secret field.
+    #[serde(default)]
+    pub health_token: Option<String>,
     /// Pre-compaction memory flush configuration.
     #[serde(default)]
     pub memory_flush: MemoryFlushConfig,
@@ -108,6 +208,82 @@ pub struct Config {
     /// PARA vault personality configuration.
     #[serde(default)]
     pub personality: PersonalityConfig,
+    /// Sub-agent delegation guardrails (depth, concurrency, budget).
+    #[serde(default)]
+    pub delegation: DelegationPolicy,
+    /// Background task queue worker configuration.
+    #[serde(default)]
+    pub task_queue: TaskQueueConfig,
+    /// Periodic agent check-ins (`[heartbeat]`).
+    #[serde(default)]
+    pub heartbeat: HeartbeatConfig,
+    /// Workspace snapshots before autonomous runs (`[snapshots]`).
+    #[serde(default)]
+    pub snapshots: SnapshotConfig,
+    /// Where `delete_file` moves deleted files and for how long (`[trash]`).
+    #[serde(default)]
+    pub trash: crate::trash::TrashConfig,
+    /// Roles of the people talking to the agent (`[users]`).
+    #[serde(default)]
+    pub users: UsersConfig,
+    /// Tool call time limits and the per-turn deadline (`[timeouts]`).
+    #[serde(default)]
+    pub timeouts: crate::tools::TimeoutConfig,
+    /// Trimmed tool schemas and the tool router (`[tool_schemas]`).
+    #[serde(default)]
+    pub tool_schemas: crate::tools::SchemaBudget,
+    /// Background file index for find_files/search_files (`[file_index]`).
+    #[serde(default)]
+    pub file_index: crate::tools::FileIndexConfig,
+    /// Embedding model and its on-disk cache (`[embeddings]`).
+    #[serde(default)]
+    pub embeddings: crate::embeddings::EmbeddingsConfig,
+    /// Simulate mutating tools instead of running them (`--dry-run`, `/dryrun`).
+    #[serde(default)]
+    pub dry_run: bool,
+    /// External tool servers speaking JSON over stdio.
+    #[serde(default)]
+    pub tool_servers: Vec<ToolServerConfig>,
+    /// Previously active workspaces, most recent first (`/workspace`).
+    #[serde(default)]
+    pub recent_workspaces: Vec<PathBuf>,
+    /// Project checkouts whose `.rustyclaw/settings.toml` may change the
+    /// model provider and base URL.  Other projects may only pick a model.
+    #[serde(default)]
+    pub trusted_projects: Vec<PathBuf>,
+    /// Additional roots searched by `find_files` / `search_files`.
+    #[serde(default)]
+    pub extra_roots: Vec<PathBuf>,
+    /// Where `execute_command` runs: on the host or in a container.
+    #[serde(default)]
+    pub execution: ExecutionConfig,
+    /// Run commands inside the workspace's direnv / Nix / devenv environment.
+    #[serde(default)]
+    pub dev_env: DevEnvMode,
+    /// Language servers for the `lsp_*` tools, checked before the built-ins.
+    #[serde(default)]
+    pub lsp_servers: Vec<LspServerConfig>,
+    /// Contact book sources for the `contacts` tool and message routing.
+    #[serde(default)]
+    pub contacts: ContactsConfig,
+    /// Geofence zones and presence rules evaluated by the gateway.
+    #[serde(default)]
+    pub presence: PresenceConfig,
+    /// MQTT broker connection, subscriptions and rules.
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    /// Translation provider, default target language and per-chat
+    /// auto-translation.
+    #[serde(default)]
+    pub translation: TranslationConfig,
+    /// Capability overrides by model name or glob, for models the
+    /// built-in table and provider APIs get wrong (see [`crate::model_caps`]).
+    #[serde(default)]
+    pub model_capabilities: HashMap<String, crate::model_caps::CapsOverride>,
+    /// Fields stored encrypted (`enc:`) in the file, so saving can keep
+    /// them encrypted.  See [`crate::secrets::config_fields`].
+    #[serde(skip)]
+    pub sealed_fields: SealedFields,
 }
 
 /// PARA vault personality configuration.
@@ -138,6 +314,25 @@ pub struct PersonalityConfig {
     pub daily_dir: Option<PathBuf>,
 }
 
+/// What the user allows a WASM plugin to use (`[plugin_grants.<name>]`).
+///
+/// Anything a plugin's manifest requests beyond this is withheld.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PluginGrant {
+    /// Allow the workspace access (read or write) the manifest asks for.
+    #[serde(default)]
+    pub workspace: bool,
+    /// Hosts the plugin may reach, out of those its manifest lists.
+    #[serde(default)]
+    pub network: Vec<String>,
+    /// Raise the instruction budget ceiling above the default.
+    #[serde(default)]
+    pub fuel: Option<u64>,
+    /// Raise the memory ceiling above the default.
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+}
+
 /// Configuration for a messenger backend.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MessengerConfig {
@@ -180,6 +375,21 @@ pub struct MessengerConfig {
     /// Allowed user IDs (whitelist).
     #[serde(default)]
     pub allowed_users: Vec<String>,
+    /// System prompt for conversations on this messenger.  Replaces the
+    /// global `system_prompt` when set.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Reply style: `terse`, `concise`, `verbose`, `formal`, or free-form
+    /// instructions.  Defaults to concise chat replies.
+    #[serde(default)]
+    pub style: Option<String>,
+    /// Maximum reply length in characters; longer replies are cut short.
+    #[serde(default)]
+    pub max_reply_length: Option<usize>,
+    /// Do-not-disturb window, e.g. `22:00-07:30` (local time).  Unsolicited
+    /// non-urgent messages are held until it ends; see [`crate::quiet_hours`].
+    #[serde(default)]
+    pub quiet_hours: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -193,6 +403,8 @@ impl Default for Config {
             settings_dir: home_dir.join(".rustyclaw"),
             soul_path: None,
             skills_dir: None,
+            plugins_dir: None,
+            plugin_grants: HashMap::new(),
             workspace_dir: None,
             credentials_dir: None,
             messengers: Vec::new(),
@@ -204,18 +416,50 @@ impl Default for Config {
             agent_access: false,
             agent_name: Self::default_agent_name(),
             message_spacing: Self::default_message_spacing(),
+            vim_mode: false,
+            locale: None,
+            palette: PaletteName::Default,
             tab_width: Self::default_tab_width(),
             sandbox: SandboxConfig::default(),
             clawhub_url: None,
             clawhub_token: None,
             system_prompt: None,
             messenger_poll_interval_ms: None,
+            messenger_debounce_ms: None,
+            messenger_rate_limit: None,
             tool_permissions: HashMap::new(),
             tls_cert: None,
             tls_key: None,
+            compression_threshold: Self::default_compression_threshold(),
+            health_listen: None,
+            health_token: None,
             memory_flush: MemoryFlushConfig::default(),
             workspace_context: WorkspaceContextConfig::default(),
             personality: PersonalityConfig::default(),
+            delegation: DelegationPolicy::default(),
+            task_queue: TaskQueueConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            trash: crate::trash::TrashConfig::default(),
+            timeouts: crate::tools::TimeoutConfig::default(),
+            tool_schemas: crate::tools::SchemaBudget::default(),
+            file_index: crate::tools::FileIndexConfig::default(),
+            embeddings: crate::embeddings::EmbeddingsConfig::default(),
+            users: UsersConfig::default(),
+            dry_run: false,
+            tool_servers: Vec::new(),
+            recent_workspaces: Vec::new(),
+            trusted_projects: Vec::new(),
+            extra_roots: Vec::new(),
+            execution: ExecutionConfig::default(),
+            dev_env: DevEnvMode::Off,
+            lsp_servers: Vec::new(),
+            contacts: ContactsConfig::default(),
+            presence: PresenceConfig::default(),
+            mqtt: MqttConfig::default(),
+            translation: TranslationConfig::default(),
+            model_capabilities: HashMap::new(),
+            sealed_fields: SealedFields::default(),
         }
     }
 }
@@ -233,6 +477,15 @@ impl Config {
         5
     }
 
+    fn default_compression_threshold() -> usize {
+        crate::gateway::protocol::compression::DEFAULT_THRESHOLD
+    }
+
+    /// Compression threshold for a connection, `None` when disabled.
+    pub fn compression(&self) -> Option<usize> {
+        (self.compression_threshold > 0).then_some(self.compression_threshold)
+    }
+
     // ── Derived path helpers (mirrors openclaw layout) ───────────
 
     /// Agent workspace directory — holds SOUL.md, skills/, etc.
@@ -243,6 +496,28 @@ impl Config {
             .unwrap_or_else(|| self.settings_dir.join("workspace"))
     }
 
+    /// Number of entries kept in `recent_workspaces`.
+    const MAX_RECENT_WORKSPACES: usize = 10;
+
+    /// Switch the active workspace to `path`, remembering the previous one
+    /// in `recent_workspaces`.  Returns the canonical new workspace.
+    pub fn set_workspace(&mut self, path: &Path) -> Result<PathBuf> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Workspace not found: {}", path.display()))?;
+        if !path.is_dir() {
+            anyhow::bail!("Not a directory: {}", path.display());
+        }
+        let previous = self.workspace_dir();
+        if previous != path {
+            self.recent_workspaces.retain(|p| *p != previous && *p != path);
+            self.recent_workspaces.insert(0, previous);
+            self.recent_workspaces.truncate(Self::MAX_RECENT_WORKSPACES);
+        }
+        self.workspace_dir = Some(path.clone());
+        Ok(path)
+    }
+
     /// Credentials directory — holds secrets vault, key file, OAuth tokens.
     /// Default: `<settings_dir>/credentials`
     pub fn credentials_dir(&self) -> PathBuf {
@@ -276,6 +551,13 @@ impl Config {
             .unwrap_or_else(|| self.workspace_dir().join("skills"))
     }
 
+    /// Directory scanned for `.wasm` tool plugins.
+    pub fn plugins_dir(&self) -> PathBuf {
+        self.plugins_dir
+            .clone()
+            .unwrap_or_else(|| self.settings_dir.join("plugins"))
+    }
+
     /// Returns all skills directories in priority order (lowest to highest).
     /// Order: bundled OpenClaw → user OpenClaw → user RustyClaw
     pub fn skills_dirs(&self) -> Vec<PathBuf> {
@@ -301,6 +583,13 @@ impl Config {
         dirs
     }
 
+    /// Configuration of the first enabled messenger of the given type.
+    pub fn messenger_config(&self, messenger_type: &str) -> Option<&MessengerConfig> {
+        self.messengers
+            .iter()
+            .find(|m| m.enabled && m.messenger_type == messenger_type)
+    }
+
     /// Logs directory.
     pub fn logs_dir(&self) -> PathBuf {
         self.settings_dir.join("logs")
@@ -336,7 +625,19 @@ impl Config {
 
         if config_path.exists() {
             let content = std::fs::read_to_string(&config_path)?;
-            let mut config: Config = toml::from_str(&content)?;
+            let mut table: toml::Table = toml::from_str(&content)?;
+            let sealed_fields = config_fields::unseal_on_load(&mut table)?;
+            let mut config: Config = toml::Value::Table(table).try_into().with_context(|| {
+                match sealed_fields.locked() {
+                    locked if locked.is_empty() => format!("Invalid config {}", config_path.display()),
+                    locked => format!(
+                        "Invalid config {} (encrypted fields {} need the vault; set RUSTYCLAW_VAULT_PASSWORD)",
+                        config_path.display(),
+                        locked.join(", ")
+                    ),
+                }
+            })?;
+            config.sealed_fields = sealed_fields;
             // Migrate legacy flat layout if detected.
             config.migrate_legacy_layout()?;
             Ok(config)
@@ -357,11 +658,34 @@ impl Config {
             std::fs::create_dir_all(parent)?;
         }
 
-        let content = toml::to_string_pretty(self)?;
+        let toml::Value::Table(mut table) = toml::Value::try_from(self)? else {
+            anyhow::bail!("Config did not serialize to a table");
+        };
+        self.sealed_fields.reseal(&mut table).map_err(anyhow::Error::msg)?;
+        let content = toml::to_string_pretty(&table)?;
         std::fs::write(&config_path, content)?;
         Ok(())
     }
 
+    /// Decrypt fields left encrypted at load time because the vault was
+    /// locked.  Returns how many were decrypted.
+    pub fn unseal_fields(&mut self, secrets: &mut SecretsManager) -> Result<usize> {
+        if self.sealed_fields.locked().is_empty() {
+            return Ok(0);
+        }
+        let identity = config_fields::field_key(secrets, false)?
+            .context("config.toml has encrypted fields but the vault has no config field key")?;
+        let toml::Value::Table(mut table) = toml::Value::try_from(&*self)? else {
+            anyhow::bail!("Config did not serialize to a table");
+        };
+        let mut sealed_fields = self.sealed_fields.clone();
+        let opened = sealed_fields.unseal(&mut table, Some(&identity)).map_err(anyhow::Error::msg)?;
+        let mut config: Config = toml::Value::Table(table).try_into()?;
+        config.sealed_fields = sealed_fields;
+        *self = config;
+        Ok(opened)
+    }
+
     // ── Legacy migration ────────────────────────────────────────────
 
     /// Detect the pre-restructure flat layout and move files into the
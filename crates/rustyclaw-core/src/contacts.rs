@@ -0,0 +1,542 @@
+//! Contact book: who "Bob" is on each messenger.
+//!
+//! Contacts come from three places, merged in this order (first wins on a
+//! name clash):
+//!
+//! 1. `[[contacts.entries]]` in `config.toml`,
+//! 2. a local contacts file (`<settings_dir>/contacts.toml` by default, or a
+//!    `.vcf` export),
+//! 3. a CardDAV address book, when `[contacts.carddav]` is configured.
+//!
+//! Each contact maps messenger names to ids, so "message Bob" can be routed
+//! to a Telegram chat id or a Signal phone number:
+//!
+//! ```toml
+//! [[contact]]
+//! name = "Bob Smith"
+//! aliases = ["bob"]
+//! phone = "+15551234567"
+//! identities = { telegram = "123456789", discord = "987654321" }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// How long CardDAV results are reused before fetching again.
+const CARDDAV_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// Default local contacts file name, under the settings directory.
+pub const CONTACTS_FILE: &str = "contacts.toml";
+
+/// A person and their ids on each messenger.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Messenger name (telegram, discord, signal, matrix, ...) → id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub identities: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Contact {
+    /// Whether `query` names this contact: the full name, an alias, or the
+    /// first name, ignoring case.
+    pub fn is_named(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        let name = self.name.to_lowercase();
+        name == query
+            || name.split_whitespace().next() == Some(query.as_str())
+            || self.aliases.iter().any(|a| a.to_lowercase() == query)
+    }
+
+    /// Whether `query` appears anywhere in the contact.
+    pub fn mentions(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        std::iter::once(&self.name)
+            .chain(&self.aliases)
+            .chain(self.identities.values())
+            .chain(self.phone.iter())
+            .chain(self.email.iter())
+            .chain(self.notes.iter())
+            .any(|field| field.to_lowercase().contains(&query))
+    }
+
+    /// This contact's id on `channel`.  Phone-based messengers fall back
+    /// to the phone number.
+    pub fn identity(&self, channel: &str) -> Option<String> {
+        self.identities.get(channel).cloned().or_else(|| {
+            matches!(channel, "signal" | "sms" | "whatsapp")
+                .then(|| self.phone.clone())
+                .flatten()
+        })
+    }
+
+    /// Whether `sender` on `messenger` is this contact.  Phone numbers
+    /// match regardless of spacing and punctuation; email senders also
+    /// match the contact's email address.
+    pub fn is_sender(&self, messenger: &str, sender: &str) -> bool {
+        fn normalize(id: &str) -> String {
+            id.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.'))
+                .collect::<String>()
+                .to_lowercase()
+        }
+        let sender = normalize(sender);
+        if sender.is_empty() {
+            return false;
+        }
+        let email = (messenger == "email").then_some(self.email.as_ref()).flatten();
+        self.identity(messenger)
+            .iter()
+            .chain(email)
+            .any(|id| normalize(id) == sender)
+    }
+
+    /// One-line summary for listings.
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = self
+            .identities
+            .iter()
+            .map(|(channel, id)| format!("{}: {}", channel, id))
+            .collect();
+        if let Some(phone) = &self.phone {
+            parts.push(format!("phone: {}", phone));
+        }
+        if let Some(email) = &self.email {
+            parts.push(format!("email: {}", email));
+        }
+        if parts.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, parts.join(", "))
+        }
+    }
+}
+
+/// A CardDAV address book.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardDavConfig {
+    /// Address book collection URL.
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// The `[contacts]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactsConfig {
+    /// Local contacts file (`.toml`, or a read-only `.vcf` export).
+    /// Default: `<settings_dir>/contacts.toml`.
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    #[serde(default)]
+    pub carddav: Option<CardDavConfig>,
+    /// Contacts defined inline in `config.toml`.
+    #[serde(default)]
+    pub entries: Vec<Contact>,
+}
+
+/// On-disk layout of the local TOML contacts file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ContactsFile {
+    #[serde(default, rename = "contact")]
+    contacts: Vec<Contact>,
+}
+
+/// Active config and resolved local file path, replaced on every reload.
+static CONFIG: RwLock<Option<(ContactsConfig, PathBuf)>> = RwLock::new(None);
+
+/// Last CardDAV fetch.
+static CARDDAV_CACHE: Mutex<Option<(Instant, Vec<Contact>)>> = Mutex::new(None);
+
+/// Register the contacts config.  `settings_dir` supplies the default file.
+pub fn set_config(config: ContactsConfig, settings_dir: &Path) {
+    let file = config
+        .file
+        .clone()
+        .unwrap_or_else(|| settings_dir.join(CONTACTS_FILE));
+    debug!(file = %file.display(), carddav = config.carddav.is_some(), "Setting contacts config");
+    if let Ok(mut guard) = CONFIG.write() {
+        *guard = Some((config, file));
+    }
+    if let Ok(mut cache) = CARDDAV_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+fn current() -> (ContactsConfig, PathBuf) {
+    CONFIG
+        .read()
+        .ok()
+        .and_then(|g| g.clone())
+        .unwrap_or_else(|| {
+            let settings = dirs::home_dir().unwrap_or_default().join(".rustyclaw");
+            (ContactsConfig::default(), settings.join(CONTACTS_FILE))
+        })
+}
+
+/// Path of the local contacts file.
+pub fn local_file() -> PathBuf {
+    current().1
+}
+
+fn is_vcf(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("vcf") || e.eq_ignore_ascii_case("vcard"))
+}
+
+/// Read the local contacts file (missing → empty).
+pub fn load_local(path: &Path) -> Result<Vec<Contact>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    if is_vcf(path) {
+        return Ok(parse_vcards(&text));
+    }
+    toml::from_str::<ContactsFile>(&text)
+        .map(|f| f.contacts)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Write the local contacts file.  vCard exports are read-only.
+pub fn save_local(path: &Path, contacts: &[Contact]) -> Result<(), String> {
+    if is_vcf(path) {
+        return Err(format!("{} is a vCard export and is read-only", path.display()));
+    }
+    let text = toml::to_string_pretty(&ContactsFile { contacts: contacts.to_vec() })
+        .map_err(|e| format!("Failed to serialize contacts: {}", e))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// ── vCard ───────────────────────────────────────────────────────────────────
+
+/// Parse vCard 3/4 text (one or many cards).  Messenger ids are taken from
+/// `IMPP` URIs (`IMPP:telegram:123`) and `X-<MESSENGER>` properties.
+pub fn parse_vcards(text: &str) -> Vec<Contact> {
+    // Unfold continuation lines (RFC 6350 §3.2).
+    let unfolded = text.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    let mut contacts = Vec::new();
+    let mut card: Option<Contact> = None;
+    for line in unfolded.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Drop parameters (TEL;TYPE=cell) and group prefixes (item1.EMAIL).
+        let prop = key.split(';').next().unwrap_or(key);
+        let prop = prop.rsplit('.').next().unwrap_or(prop).to_ascii_uppercase();
+        let value = value.replace("\\,", ",").replace("\\;", ";").replace("\\n", " ");
+        match prop.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => card = Some(Contact::default()),
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(c) = card.take().filter(|c| !c.name.is_empty()) {
+                    contacts.push(c);
+                }
+            }
+            _ => {
+                let Some(c) = card.as_mut() else { continue };
+                match prop.as_str() {
+                    "FN" => c.name = value.trim().to_string(),
+                    "N" if c.name.is_empty() => {
+                        let mut parts = value.split(';');
+                        let family = parts.next().unwrap_or("");
+                        let given = parts.next().unwrap_or("");
+                        c.name = format!("{} {}", given, family).trim().to_string();
+                    }
+                    "NICKNAME" => c.aliases.extend(
+                        value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from),
+                    ),
+                    "TEL" if c.phone.is_none() => {
+                        c.phone = Some(value.trim_start_matches("tel:").trim().to_string())
+                    }
+                    "EMAIL" if c.email.is_none() => c.email = Some(value.trim().to_string()),
+                    "NOTE" => c.notes = Some(value.trim().to_string()),
+                    "IMPP" => {
+                        if let Some((scheme, id)) = value.split_once(':') {
+                            c.identities
+                                .insert(scheme.to_lowercase(), id.trim_start_matches("//").to_string());
+                        }
+                    }
+                    other => {
+                        if let Some(messenger) = other.strip_prefix("X-") {
+                            let messenger = messenger.to_lowercase();
+                            if matches!(
+                                messenger.as_str(),
+                                "telegram" | "discord" | "signal" | "matrix" | "slack" | "whatsapp"
+                            ) {
+                                c.identities.insert(messenger, value.trim().to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    contacts
+}
+
+// ── CardDAV ─────────────────────────────────────────────────────────────────
+
+const ADDRESSBOOK_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<C:addressbook-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">
+  <D:prop><C:address-data/></D:prop>
+</C:addressbook-query>"#;
+
+/// Pull the vCards out of a CardDAV multistatus response.
+pub fn vcards_from_multistatus(xml: &str) -> String {
+    let re = regex::Regex::new(r"(?s)<(?:[A-Za-z0-9]+:)?address-data[^>]*>(.*?)</(?:[A-Za-z0-9]+:)?address-data>")
+        .expect("valid regex");
+    re.captures_iter(xml)
+        .map(|c| {
+            c[1].replace("<![CDATA[", "")
+                .replace("]]>", "")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&#13;", "\r")
+                .replace("&amp;", "&")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn fetch_carddav(config: &CardDavConfig) -> Result<Vec<Contact>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .user_agent("RustyClaw/0.1 (contacts)")
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let method = reqwest::Method::from_bytes(b"REPORT").map_err(|e| e.to_string())?;
+    let mut request = client
+        .request(method, &config.url)
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(ADDRESSBOOK_QUERY);
+    if let Some(user) = &config.username {
+        request = request.basic_auth(user, config.password.as_deref());
+    }
+    let response = request
+        .send()
+        .map_err(|e| format!("CardDAV request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("CardDAV server returned {}", response.status()));
+    }
+    let body = response
+        .text()
+        .map_err(|e| format!("Failed to read CardDAV response: {}", e))?;
+    Ok(parse_vcards(&vcards_from_multistatus(&body)))
+}
+
+/// CardDAV contacts, cached for [`CARDDAV_CACHE_TTL`].
+fn carddav_contacts(config: &CardDavConfig) -> Vec<Contact> {
+    if let Ok(cache) = CARDDAV_CACHE.lock() {
+        if let Some((at, contacts)) = cache.as_ref() {
+            if at.elapsed() < CARDDAV_CACHE_TTL {
+                return contacts.clone();
+            }
+        }
+    }
+    match fetch_carddav(config) {
+        Ok(contacts) => {
+            debug!(count = contacts.len(), "Fetched CardDAV contacts");
+            if let Ok(mut cache) = CARDDAV_CACHE.lock() {
+                *cache = Some((Instant::now(), contacts.clone()));
+            }
+            contacts
+        }
+        Err(e) => {
+            warn!(error = %e, "CardDAV fetch failed");
+            Vec::new()
+        }
+    }
+}
+
+// ── Lookup ──────────────────────────────────────────────────────────────────
+
+/// Every known contact, merged from config, the local file and CardDAV.
+pub fn all() -> Vec<Contact> {
+    let (config, file) = current();
+    let local = load_local(&file).unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load contacts file");
+        Vec::new()
+    });
+    let remote = config.carddav.as_ref().map(carddav_contacts).unwrap_or_default();
+
+    let mut merged: Vec<Contact> = Vec::new();
+    for contact in config.entries.into_iter().chain(local).chain(remote) {
+        if !merged.iter().any(|c| c.name.eq_ignore_ascii_case(&contact.name)) {
+            merged.push(contact);
+        }
+    }
+    merged
+}
+
+/// The single contact `name` refers to.  Several matches is an error
+/// listing them, so the caller can ask which one was meant.
+pub fn find_in(contacts: &[Contact], name: &str) -> Result<Option<Contact>, String> {
+    // An exact full-name match beats first-name/alias matches.
+    if let Some(exact) = contacts.iter().find(|c| c.name.eq_ignore_ascii_case(name.trim())) {
+        return Ok(Some(exact.clone()));
+    }
+    let matches: Vec<&Contact> = contacts.iter().filter(|c| c.is_named(name)).collect();
+    match matches.as_slice() {
+        [] => Ok(None),
+        [one] => Ok(Some((*one).clone())),
+        many => Err(format!(
+            "'{}' matches several contacts: {}",
+            name,
+            many.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Look up `name` in the contact book.
+pub fn find(name: &str) -> Result<Option<Contact>, String> {
+    find_in(&all(), name)
+}
+
+/// The contact `sender` on `messenger` belongs to.
+pub fn by_sender_in(contacts: &[Contact], messenger: &str, sender: &str) -> Option<Contact> {
+    contacts.iter().find(|c| c.is_sender(messenger, sender)).cloned()
+}
+
+/// Look up who `sender` on `messenger` is in the contact book.
+pub fn by_sender(messenger: &str, sender: &str) -> Option<Contact> {
+    by_sender_in(&all(), messenger, sender)
+}
+
+/// Channels the `message` tool can deliver to, in `auto` preference order,
+/// with the environment variable that enables each.
+const DELIVERABLE: &[(&str, &str)] = &[
+    ("discord", "DISCORD_BOT_TOKEN"),
+    ("telegram", "TELEGRAM_BOT_TOKEN"),
+];
+
+/// Resolve a message target that may be a contact name.
+///
+/// Returns `(target, channel)`.  Targets that don't name a contact are
+/// returned unchanged.  For `auto`, the first channel that is both
+/// configured and known for the contact is chosen.
+pub fn route(target: &str, channel: &str) -> Result<(String, String), String> {
+    let Some(contact) = find(target)? else {
+        return Ok((target.to_string(), channel.to_string()));
+    };
+    if channel != "auto" {
+        return contact
+            .identity(channel)
+            .map(|id| (id, channel.to_string()))
+            .ok_or_else(|| format!("{} has no {} identity in the contact book", contact.name, channel));
+    }
+    DELIVERABLE
+        .iter()
+        .filter(|(_, env)| std::env::var(env).is_ok())
+        .find_map(|(ch, _)| contact.identity(ch).map(|id| (id, ch.to_string())))
+        .or_else(|| {
+            contact
+                .identities
+                .iter()
+                .next()
+                .map(|(ch, id)| (id.clone(), ch.clone()))
+        })
+        .ok_or_else(|| format!("{} has no messenger identities in the contact book", contact.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bob() -> Contact {
+        Contact {
+            name: "Bob Smith".into(),
+            aliases: vec!["bobby".into()],
+            identities: BTreeMap::from([("telegram".into(), "123".into())]),
+            phone: Some("+15551234567".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_by_name_alias_and_first_name() {
+        let contacts = vec![
+            bob(),
+            Contact { name: "Bob Jones".into(), ..Default::default() },
+            Contact { name: "Alice".into(), ..Default::default() },
+        ];
+        assert_eq!(find_in(&contacts, "bobby").unwrap().unwrap().name, "Bob Smith");
+        assert_eq!(find_in(&contacts, "bob jones").unwrap().unwrap().name, "Bob Jones");
+        assert_eq!(find_in(&contacts, "ALICE").unwrap().unwrap().name, "Alice");
+        assert!(find_in(&contacts, "bob").unwrap_err().contains("several"));
+        assert!(find_in(&contacts, "carol").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_identity_falls_back_to_phone() {
+        let bob = bob();
+        assert_eq!(bob.identity("telegram").as_deref(), Some("123"));
+        assert_eq!(bob.identity("signal").as_deref(), Some("+15551234567"));
+        assert_eq!(bob.identity("discord"), None);
+    }
+
+    #[test]
+    fn test_by_sender_matches_any_linked_id() {
+        let contacts = vec![Contact { email: Some("Bob@Example.com".into()), ..bob() }];
+        assert_eq!(by_sender_in(&contacts, "telegram", "123").unwrap().name, "Bob Smith");
+        assert_eq!(by_sender_in(&contacts, "signal", "+1 555-123-4567").unwrap().name, "Bob Smith");
+        assert_eq!(by_sender_in(&contacts, "email", "bob@example.com").unwrap().name, "Bob Smith");
+        assert!(by_sender_in(&contacts, "discord", "123").is_none());
+        assert!(by_sender_in(&contacts, "telegram", "").is_none());
+    }
+
+    #[test]
+    fn test_parse_vcards() {
+        let vcf = "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Carol\r\n  Danvers\r\nNICKNAME:cap,carol\r\n\
+                   TEL;TYPE=cell:tel:+441234\r\nitem1.EMAIL:c@example.com\r\n\
+                   IMPP:telegram:555\r\nX-DISCORD:777\r\nEND:VCARD\r\n\
+                   BEGIN:VCARD\r\nN:Doe;Jane;;;\r\nEND:VCARD\r\n";
+        let cards = parse_vcards(vcf);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].name, "Carol Danvers");
+        assert_eq!(cards[0].aliases, vec!["cap", "carol"]);
+        assert_eq!(cards[0].phone.as_deref(), Some("+441234"));
+        assert_eq!(cards[0].email.as_deref(), Some("c@example.com"));
+        assert_eq!(cards[0].identities["telegram"], "555");
+        assert_eq!(cards[0].identities["discord"], "777");
+        assert_eq!(cards[1].name, "Jane Doe");
+    }
+
+    #[test]
+    fn test_multistatus_and_local_file_roundtrip() {
+        let xml = "<d:multistatus xmlns:d=\"DAV:\"><d:response><d:propstat><d:prop>\
+                   <card:address-data>BEGIN:VCARD\nFN:Tom &amp; Co\nEND:VCARD\n</card:address-data>\
+                   </d:prop></d:propstat></d:response></d:multistatus>";
+        let cards = parse_vcards(&vcards_from_multistatus(xml));
+        assert_eq!(cards[0].name, "Tom & Co");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("contacts.toml");
+        assert!(load_local(&path).unwrap().is_empty());
+        save_local(&path, &[bob()]).unwrap();
+        assert_eq!(load_local(&path).unwrap(), vec![bob()]);
+        assert!(save_local(&dir.path().join("x.vcf"), &[]).is_err());
+    }
+}
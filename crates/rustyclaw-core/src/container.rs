@@ -0,0 +1,342 @@
+//! Container-backed command execution.
+//!
+//! With `runtime = "docker"` (or `"podman"`) in the `[execution]` section,
+//! `execute_command` and background processes run inside a long-lived
+//! container with the workspace mounted at `/workspace`.  The container is
+//! created on first use and kept running, so packages the agent installs
+//! survive between commands without ever touching the host.
+//!
+//! ```toml
+//! [execution]
+//! runtime = "docker"
+//! image = "python:3.12-slim"
+//! env = { PIP_NO_CACHE_DIR = "1" }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, RwLock};
+use tracing::{debug, info};
+
+/// Mount point of the workspace inside the container.
+pub const CONTAINER_WORKSPACE: &str = "/workspace";
+
+/// Label holding a hash of the arguments the container was created with.
+const SPEC_LABEL: &str = "rustyclaw.spec";
+
+/// Where commands run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecRuntime {
+    /// Directly on the host (through the configured sandbox).
+    #[default]
+    Host,
+    Docker,
+    Podman,
+}
+
+/// The `[execution]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    #[serde(default)]
+    pub runtime: ExecRuntime,
+    /// Image the container is created from.
+    #[serde(default = "default_image")]
+    pub image: String,
+    /// Environment variables set for every command.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Give the container network access (needed to install packages).
+    #[serde(default = "default_network")]
+    pub network: bool,
+    /// Container name; defaults to one derived from the workspace path.
+    #[serde(default)]
+    pub container_name: Option<String>,
+}
+
+fn default_image() -> String {
+    "debian:bookworm-slim".to_string()
+}
+
+fn default_network() -> bool {
+    true
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            runtime: ExecRuntime::Host,
+            image: default_image(),
+            env: BTreeMap::new(),
+            network: default_network(),
+            container_name: None,
+        }
+    }
+}
+
+/// Active execution settings, replaced on every config (re)load.
+static EXECUTION: RwLock<Option<ExecutionConfig>> = RwLock::new(None);
+
+/// Serialises container creation so concurrent commands don't race.
+static ENSURE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Register the execution settings from the config.
+pub fn set_execution(config: ExecutionConfig) {
+    debug!(runtime = ?config.runtime, image = %config.image, "Setting execution runtime");
+    if let Ok(mut guard) = EXECUTION.write() {
+        *guard = Some(config);
+    }
+}
+
+/// The execution settings when commands should run in a container.
+pub fn active() -> Option<ExecutionConfig> {
+    let guard = EXECUTION.read().ok()?;
+    guard.clone().filter(|c| c.runtime != ExecRuntime::Host)
+}
+
+/// Stable 64-bit FNV-1a hash, used to name per-workspace containers.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Run the container engine CLI, returning trimmed stdout.
+fn engine_output(engine: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(engine)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", engine, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} {} failed: {}",
+            engine,
+            args.first().map(String::as_str).unwrap_or(""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl ExecutionConfig {
+    /// CLI binary for the configured runtime.
+    pub fn engine(&self) -> Option<&'static str> {
+        match self.runtime {
+            ExecRuntime::Host => None,
+            ExecRuntime::Docker => Some("docker"),
+            ExecRuntime::Podman => Some("podman"),
+        }
+    }
+
+    /// Name of the container serving `workspace_dir`.
+    pub fn container_name(&self, workspace_dir: &Path) -> String {
+        self.container_name.clone().unwrap_or_else(|| {
+            let hash = fnv1a(workspace_dir.to_string_lossy().as_bytes());
+            format!("rustyclaw-{:012x}", hash & 0xffff_ffff_ffff)
+        })
+    }
+
+    /// Arguments creating the long-lived container, labelled with their
+    /// own hash so a container built from an older spec is recognised.
+    fn run_args(&self, name: &str, workspace_dir: &Path) -> Vec<String> {
+        let mut args = self.create_args(name, workspace_dir);
+        let spec = format!("{}={}", SPEC_LABEL, self.spec(name, workspace_dir));
+        args.splice(2..2, ["--label".to_string(), spec]);
+        args
+    }
+
+    /// Hash of everything the container is created with.
+    fn spec(&self, name: &str, workspace_dir: &Path) -> String {
+        let args = self.create_args(name, workspace_dir);
+        format!("{:016x}", fnv1a(args.join("\0").as_bytes()))
+    }
+
+    fn create_args(&self, name: &str, workspace_dir: &Path) -> Vec<String> {
+        let mut args = vec![
+            "run".to_string(),
+            "-d".to_string(),
+            "--name".to_string(),
+            name.to_string(),
+            "--label".to_string(),
+            format!("rustyclaw.workspace={}", workspace_dir.display()),
+            "--volume".to_string(),
+            format!("{}:{}", workspace_dir.display(), CONTAINER_WORKSPACE),
+            "--workdir".to_string(),
+            CONTAINER_WORKSPACE.to_string(),
+        ];
+        if !self.network {
+            args.push("--network".to_string());
+            args.push("none".to_string());
+        }
+        args.push(self.image.clone());
+        // Keep the container alive; commands arrive through `exec`.
+        args.extend(["sleep".to_string(), "infinity".to_string()]);
+        args
+    }
+
+    /// Arguments running `command` in the container.
+    fn exec_args(&self, name: &str, workdir: &str, command: &str) -> Vec<String> {
+        let mut args = vec![
+            "exec".to_string(),
+            "-i".to_string(),
+            "--workdir".to_string(),
+            workdir.to_string(),
+        ];
+        for (key, value) in &self.env {
+            args.push("--env".to_string());
+            args.push(format!("{}={}", key, value));
+        }
+        args.extend([name.to_string(), "sh".to_string(), "-c".to_string(), command.to_string()]);
+        args
+    }
+
+    /// Make sure the workspace container exists, runs, and was created
+    /// from the current settings.  Returns its name.
+    pub fn ensure_container(&self, workspace_dir: &Path) -> Result<String, String> {
+        let engine = self.engine().ok_or("Execution runtime is the host")?;
+        let name = self.container_name(workspace_dir);
+        let _guard = ENSURE_LOCK.lock().map_err(|_| "Container lock poisoned".to_string())?;
+
+        let inspect = engine_output(
+            engine,
+            &[
+                "inspect".to_string(),
+                "-f".to_string(),
+                format!("{{{{.State.Running}}}} {{{{index .Config.Labels \"{}\"}}}}", SPEC_LABEL),
+                name.clone(),
+            ],
+        );
+        match inspect {
+            Ok(state) => {
+                let (running, spec) = state.split_once(' ').unwrap_or((state.as_str(), ""));
+                let wanted = self.spec(&name, workspace_dir);
+                if spec != wanted {
+                    info!(container = %name, old = spec, new = %wanted, "Recreating container for changed settings");
+                    engine_output(engine, &["rm".to_string(), "-f".to_string(), name.clone()])?;
+                } else {
+                    if running != "true" {
+                        debug!(container = %name, "Starting stopped container");
+                        engine_output(engine, &["start".to_string(), name.clone()])?;
+                    }
+                    return Ok(name);
+                }
+            }
+            Err(_) => debug!(container = %name, "Container does not exist yet"),
+        }
+
+        info!(container = %name, image = %self.image, "Creating workspace container");
+        engine_output(engine, &self.run_args(&name, workspace_dir))?;
+        Ok(name)
+    }
+
+    /// A command running `command` in the container, in the directory that
+    /// corresponds to `cwd` on the host.
+    pub fn command(&self, command: &str, cwd: &Path, workspace_dir: &Path) -> Result<Command, String> {
+        let engine = self.engine().ok_or("Execution runtime is the host")?;
+        let workdir = container_workdir(workspace_dir, cwd)?;
+        let name = self.ensure_container(workspace_dir)?;
+        let mut cmd = Command::new(engine);
+        cmd.args(self.exec_args(&name, &workdir, command));
+        Ok(cmd)
+    }
+}
+
+/// Map a host directory inside the workspace to its path in the container.
+pub fn container_workdir(workspace_dir: &Path, cwd: &Path) -> Result<String, String> {
+    let rel = cwd.strip_prefix(workspace_dir).map_err(|_| {
+        format!(
+            "{} is outside the workspace and not mounted in the container",
+            cwd.display()
+        )
+    })?;
+    let rel = rel.to_string_lossy();
+    if rel.is_empty() {
+        Ok(CONTAINER_WORKSPACE.to_string())
+    } else {
+        Ok(format!("{}/{}", CONTAINER_WORKSPACE, rel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: ExecutionConfig =
+            toml::from_str("runtime = \"docker\"\nimage = \"rust:1\"\nenv = { A = \"1\" }\n").unwrap();
+        assert_eq!(config.runtime, ExecRuntime::Docker);
+        assert_eq!(config.image, "rust:1");
+        assert!(config.network);
+        assert_eq!(config.engine(), Some("docker"));
+
+        let default: ExecutionConfig = toml::from_str("").unwrap();
+        assert_eq!(default, ExecutionConfig::default());
+        assert_eq!(default.engine(), None);
+    }
+
+    #[test]
+    fn test_container_workdir() {
+        let ws = Path::new("/home/me/project");
+        assert_eq!(container_workdir(ws, ws).unwrap(), "/workspace");
+        assert_eq!(
+            container_workdir(ws, &ws.join("src/bin")).unwrap(),
+            "/workspace/src/bin"
+        );
+        assert!(container_workdir(ws, Path::new("/etc")).is_err());
+    }
+
+    #[test]
+    fn test_container_name_is_stable_per_workspace() {
+        let config = ExecutionConfig::default();
+        let a = config.container_name(Path::new("/a"));
+        assert_eq!(a, config.container_name(Path::new("/a")));
+        assert_ne!(a, config.container_name(Path::new("/b")));
+        assert!(a.starts_with("rustyclaw-"));
+    }
+
+    #[test]
+    fn test_engine_args() {
+        let mut config = ExecutionConfig {
+            runtime: ExecRuntime::Docker,
+            network: false,
+            ..Default::default()
+        };
+        config.env.insert("LANG".into(), "C.UTF-8".into());
+
+        let run = config.run_args("c1", Path::new("/ws"));
+        assert!(run.contains(&"/ws:/workspace".to_string()));
+        let label = format!("{}={}", SPEC_LABEL, config.spec("c1", Path::new("/ws")));
+        assert!(run.windows(2).any(|w| w[0] == "--label" && w[1] == label));
+        assert!(run.windows(2).any(|w| w == ["--network", "none"]));
+        assert_eq!(run.last().map(String::as_str), Some("infinity"));
+
+        let exec = config.exec_args("c1", "/workspace/src", "ls");
+        assert!(exec.windows(2).any(|w| w == ["--env", "LANG=C.UTF-8"]));
+        assert_eq!(&exec[exec.len() - 4..], ["c1", "sh", "-c", "ls"]);
+    }
+
+    #[test]
+    fn test_spec_changes_with_creation_settings() {
+        let config = ExecutionConfig {
+            runtime: ExecRuntime::Docker,
+            ..Default::default()
+        };
+        let ws = Path::new("/ws");
+        let spec = config.spec("c1", ws);
+        assert_eq!(spec, config.spec("c1", ws));
+
+        let offline = ExecutionConfig { network: false, ..config.clone() };
+        assert_ne!(spec, offline.spec("c1", ws));
+        let image = ExecutionConfig { image: "alpine:3".into(), ..config.clone() };
+        assert_ne!(spec, image.spec("c1", ws));
+        assert_ne!(spec, config.spec("c1", Path::new("/other")));
+
+        // Env is passed per exec, so it doesn't need a new container.
+        let mut env = config.clone();
+        env.env.insert("A".into(), "1".into());
+        assert_eq!(spec, env.spec("c1", ws));
+    }
+}
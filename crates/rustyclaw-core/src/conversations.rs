@@ -0,0 +1,372 @@
+//! Persistent conversation history for messenger chats.
+//!
+//! The gateway keeps one conversation per chat, namespaced by messenger:
+//! `<workspace>/.conversations/<messenger>/<chat>.json`.  History survives
+//! gateway restarts, and the `conversations` tool and `/conversations`
+//! command can list, show, clear or export any chat to markdown for
+//! archiving.  Clearing takes effect on the next message, since the
+//! gateway reloads a chat's history from disk for every turn.
+
+use crate::gateway::ChatMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory holding persisted conversations for a workspace.
+pub fn conversations_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".conversations")
+}
+
+/// Get current time in milliseconds.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Map a messenger or chat id to a safe file name.  Anything outside
+/// `[A-Za-z0-9._@+-]` is percent-encoded, so distinct ids never collide.
+fn file_stem(id: &str) -> String {
+    let mut out = String::with_capacity(id.len());
+    for b in id.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'@' | b'+' | b'-') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    if out.starts_with('.') {
+        out.replace_range(..1, "%2E");
+    }
+    out
+}
+
+/// One chat's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub messenger: String,
+    pub chat: String,
+    pub started_ms: u64,
+    pub updated_ms: u64,
+    #[serde(default)]
+    pub messages: Vec<ChatMessage>,
+    /// Platform message id → text of the user message it became, so an
+    /// edit on the platform can be applied to the history.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub source_ids: HashMap<String, String>,
+}
+
+impl Conversation {
+    pub fn new(messenger: &str, chat: &str) -> Self {
+        let now = now_millis();
+        Self {
+            messenger: messenger.to_string(),
+            chat: chat.to_string(),
+            started_ms: now,
+            updated_ms: now,
+            messages: Vec::new(),
+            source_ids: HashMap::new(),
+        }
+    }
+
+    /// Remember which platform message a user message came from.
+    pub fn note_source(&mut self, message_id: &str, content: &str) {
+        self.source_ids.insert(message_id.to_string(), content.to_string());
+        // Forget messages that have been trimmed out of the history.
+        let messages = &self.messages;
+        self.source_ids
+            .retain(|_, text| messages.iter().any(|m| m.role == "user" && m.content == *text));
+    }
+
+    /// Replace the text of the user message that came from `message_id`.
+    /// Returns false if that message isn't in the history (any more).
+    pub fn apply_edit(&mut self, message_id: &str, new_content: &str) -> bool {
+        let Some(old) = self.source_ids.get(message_id).cloned() else {
+            return false;
+        };
+        let Some(message) = self
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|m| m.role == "user" && m.content == old)
+        else {
+            return false;
+        };
+        message.content = new_content.to_string();
+        self.source_ids.insert(message_id.to_string(), new_content.to_string());
+        true
+    }
+
+    /// `messenger:chat`, the key shown in listings.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.messenger, self.chat)
+    }
+
+    /// Messages worth showing a person: no system prompts or tool plumbing.
+    pub fn visible(&self) -> impl Iterator<Item = &ChatMessage> {
+        self.messages
+            .iter()
+            .filter(|m| matches!(m.role.as_str(), "user" | "assistant") && !m.display_content().is_empty())
+    }
+
+    /// Render the chat as a markdown document.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Conversation: {} / {}\n\n", self.messenger, self.chat);
+        out.push_str(&format!("- Started: {}\n", format_time(self.started_ms)));
+        out.push_str(&format!("- Last activity: {}\n", format_time(self.updated_ms)));
+        out.push_str(&format!("- Messages: {}\n", self.visible().count()));
+        for message in self.visible() {
+            let who = if message.role == "user" { "User" } else { "Assistant" };
+            out.push_str(&format!("\n## {}\n\n{}\n", who, message.display_content().trim_end()));
+        }
+        out
+    }
+}
+
+/// Listing entry for a stored conversation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationSummary {
+    pub messenger: String,
+    pub chat: String,
+    pub messages: usize,
+    pub updated_ms: u64,
+    /// Start of the most recent visible message.
+    pub preview: String,
+}
+
+/// Format a millisecond timestamp as local `YYYY-MM-DD HH:MM`.
+pub fn format_time(ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// File-backed store of messenger conversations.
+pub struct ConversationStore {
+    dir: PathBuf,
+}
+
+impl ConversationStore {
+    /// Open (creating if needed) the store in `dir`.
+    pub fn new(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create conversations directory: {}", e))?;
+        Ok(Self { dir: dir.to_path_buf() })
+    }
+
+    fn path(&self, messenger: &str, chat: &str) -> PathBuf {
+        self.dir
+            .join(file_stem(messenger))
+            .join(format!("{}.json", file_stem(chat)))
+    }
+
+    /// Load a chat's conversation, if one has been stored.
+    pub fn load(&self, messenger: &str, chat: &str) -> Result<Option<Conversation>, String> {
+        let path = self.path(messenger, chat);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Load a chat's conversation or start an empty one.
+    pub fn load_or_new(&self, messenger: &str, chat: &str) -> Result<Conversation, String> {
+        Ok(self
+            .load(messenger, chat)?
+            .unwrap_or_else(|| Conversation::new(messenger, chat)))
+    }
+
+    /// Persist a conversation, bumping its last-activity time.
+    pub fn save(&self, conversation: &mut Conversation) -> Result<(), String> {
+        conversation.updated_ms = now_millis();
+        let path = self.path(&conversation.messenger, &conversation.chat);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(conversation)
+            .map_err(|e| format!("Failed to serialize conversation: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// All stored conversations (optionally for one messenger), most
+    /// recently active first.  Unreadable files are skipped.
+    pub fn all(&self, messenger: Option<&str>) -> Result<Vec<Conversation>, String> {
+        let dirs: Vec<PathBuf> = match messenger {
+            Some(m) => vec![self.dir.join(file_stem(m))],
+            None => fs::read_dir(&self.dir)
+                .map_err(|e| format!("Failed to read conversations directory: {}", e))?
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect(),
+        };
+        let mut conversations = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|e| e == "json") {
+                    if let Some(c) = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|s| serde_json::from_str::<Conversation>(&s).ok())
+                    {
+                        conversations.push(c);
+                    }
+                }
+            }
+        }
+        conversations.sort_by(|a, b| b.updated_ms.cmp(&a.updated_ms));
+        Ok(conversations)
+    }
+
+    /// Summaries for listing.
+    pub fn list(&self, messenger: Option<&str>) -> Result<Vec<ConversationSummary>, String> {
+        Ok(self
+            .all(messenger)?
+            .into_iter()
+            .map(|c| ConversationSummary {
+                preview: c
+                    .visible()
+                    .last()
+                    .map(|m| m.display_content().chars().take(60).collect())
+                    .unwrap_or_default(),
+                messages: c.visible().count(),
+                messenger: c.messenger,
+                chat: c.chat,
+                updated_ms: c.updated_ms,
+            })
+            .collect())
+    }
+
+    /// Delete one chat's history, or every chat of `messenger` when `chat`
+    /// is `None`.  Returns how many conversations were removed.
+    pub fn clear(&self, messenger: &str, chat: Option<&str>) -> Result<usize, String> {
+        match chat {
+            Some(chat) => {
+                let path = self.path(messenger, chat);
+                if !path.exists() {
+                    return Ok(0);
+                }
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+                Ok(1)
+            }
+            None => {
+                let count = self.all(Some(messenger))?.len();
+                let dir = self.dir.join(file_stem(messenger));
+                if dir.exists() {
+                    fs::remove_dir_all(&dir)
+                        .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+                }
+                Ok(count)
+            }
+        }
+    }
+
+    /// Write a chat's history as markdown to `path`.
+    pub fn export(&self, messenger: &str, chat: &str, path: &Path) -> Result<usize, String> {
+        let conversation = self
+            .load(messenger, chat)?
+            .ok_or_else(|| format!("No conversation stored for {}:{}", messenger, chat))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(path, conversation.to_markdown())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(conversation.visible().count())
+    }
+
+    /// Default export file name for a chat.
+    pub fn export_name(messenger: &str, chat: &str) -> String {
+        format!("{}-{}.md", file_stem(messenger), file_stem(chat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat(store: &ConversationStore, messenger: &str, chat: &str, lines: &[(&str, &str)]) {
+        let mut conversation = store.load_or_new(messenger, chat).unwrap();
+        conversation.messages.push(ChatMessage::text("system", "You are helpful."));
+        for (role, content) in lines {
+            conversation.messages.push(ChatMessage::text(role, content));
+        }
+        store.save(&mut conversation).unwrap();
+    }
+
+    #[test]
+    fn test_file_stem_escapes_separators() {
+        assert_eq!(file_stem("-100123"), "-100123");
+        assert_eq!(file_stem("!room:matrix.org"), "%21room%3Amatrix.org");
+        assert_eq!(file_stem("../etc"), "%2E.%2Fetc");
+    }
+
+    #[test]
+    fn test_save_load_list_and_clear() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ConversationStore::new(dir.path()).unwrap();
+        chat(&store, "telegram", "42", &[("user", "hi"), ("assistant", "hello!")]);
+        chat(&store, "signal", "+15551234567", &[("user", "ping")]);
+
+        let loaded = store.load("telegram", "42").unwrap().unwrap();
+        assert_eq!(loaded.messages.len(), 3);
+        assert_eq!(loaded.visible().count(), 2);
+
+        let all = store.list(None).unwrap();
+        assert_eq!(all.len(), 2);
+        let telegram = store.list(Some("telegram")).unwrap();
+        assert_eq!(telegram.len(), 1);
+        assert_eq!(telegram[0].chat, "42");
+        assert_eq!(telegram[0].preview, "hello!");
+
+        assert_eq!(store.clear("telegram", Some("42")).unwrap(), 1);
+        assert!(store.load("telegram", "42").unwrap().is_none());
+        assert_eq!(store.clear("signal", None).unwrap(), 1);
+        assert!(store.list(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ConversationStore::new(&dir.path().join("store")).unwrap();
+        chat(&store, "discord", "general", &[("user", "What's up?"), ("assistant", "Not much.")]);
+
+        let out = dir.path().join("archive").join(ConversationStore::export_name("discord", "general"));
+        assert_eq!(store.export("discord", "general", &out).unwrap(), 2);
+        let md = std::fs::read_to_string(&out).unwrap();
+        assert!(md.starts_with("# Conversation: discord / general"));
+        assert!(md.contains("## User\n\nWhat's up?"));
+        assert!(md.contains("## Assistant\n\nNot much."));
+        assert!(!md.contains("You are helpful."));
+        assert!(store.export("discord", "missing", &out).is_err());
+    }
+
+    #[test]
+    fn test_apply_edit() {
+        let mut conversation = Conversation::new("telegram", "42");
+        conversation.messages.push(ChatMessage::text("user", "remind me at 5"));
+        conversation.note_source("101", "remind me at 5");
+        conversation.messages.push(ChatMessage::text("assistant", "Done."));
+
+        assert!(conversation.apply_edit("101", "remind me at 6"));
+        assert_eq!(conversation.messages[0].content, "remind me at 6");
+        assert!(!conversation.apply_edit("999", "unknown"));
+
+        // Trimmed messages are forgotten.
+        conversation.messages.remove(0);
+        conversation.messages.push(ChatMessage::text("user", "next"));
+        conversation.note_source("102", "next");
+        assert!(!conversation.source_ids.contains_key("101"));
+        assert!(conversation.source_ids.contains_key("102"));
+    }
+}
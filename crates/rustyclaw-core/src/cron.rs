@@ -1,8 +1,23 @@
 //! Cron job scheduling for RustyClaw.
 //!
 //! Provides a simple job scheduler that persists jobs to disk and can
-//! trigger agent turns or system events on schedule.
+//! trigger agent turns, system events or scripts on schedule.  The gateway
+//! polls the store for due jobs ([`CronStore::claim_due`]), runs each one
+//! and records the outcome with [`CronStore::finish_run`], which is the
+//! history `cron runs` shows.
+//!
+//! Times without an offset (`2026-03-01 09:00`) and cron expressions are
+//! read in the job's `tz` (an IANA name such as `Europe/Berlin`), or the
+//! system timezone when it has none.  The same parsing backs the `calc`
+//! tool, so the agent can check a schedule before creating it.
+//!
+//! Reminders delivered to a messenger chat are marked with
+//! [`CronStore::mark_delivered`]; a reply of `snooze 1h` or `done` in that
+//! chat then reschedules or completes the job (see [`parse_reminder_reply`]),
+//! and both show up in the job's run history.
 
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -14,11 +29,15 @@ pub type JobId = String;
 
 /// Generate a unique job ID.
 fn generate_job_id() -> JobId {
-    let timestamp = SystemTime::now()
+    format!("job-{:x}", now_ms())
+}
+
+/// Milliseconds since the Unix epoch.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
-        .as_millis();
-    format!("job-{:x}", timestamp)
+        .as_millis() as u64
 }
 
 /// Schedule kinds for cron jobs.
@@ -26,7 +45,11 @@ fn generate_job_id() -> JobId {
 #[serde(tag = "kind", rename_all = "camelCase")]
 pub enum Schedule {
     /// One-shot at an absolute time (ISO 8601).
-    At { at: String },
+    At {
+        at: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tz: Option<String>,
+    },
     /// Recurring interval in milliseconds.
     Every {
         every_ms: u64,
@@ -57,6 +80,12 @@ pub enum Payload {
         #[serde(skip_serializing_if = "Option::is_none")]
         timeout_seconds: Option<u64>,
     },
+    /// Rhai script from `skills/scripts/`.
+    Script {
+        script: String,
+        #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+        args: serde_json::Value,
+    },
 }
 
 /// Delivery configuration for isolated jobs.
@@ -71,6 +100,13 @@ pub struct Delivery {
     pub to: Option<String>,
     #[serde(default)]
     pub best_effort: bool,
+    /// Message template (see [`crate::templates`]) the run's output is
+    /// rendered into before it's announced, as `{{output}}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Extra template variables.
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub vars: serde_json::Value,
 }
 
 /// Delivery mode.
@@ -119,6 +155,19 @@ pub struct CronJob {
     pub next_run_ms: Option<u64>,
     /// Created timestamp (ms since epoch).
     pub created_ms: u64,
+    /// Chat the job was last delivered to, while a snooze/done reply is
+    /// still expected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub awaiting_reply: Option<DeliveredTo>,
+}
+
+/// Where a reminder was delivered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveredTo {
+    pub channel: String,
+    pub to: String,
+    pub at_ms: u64,
 }
 
 fn default_true() -> bool {
@@ -133,10 +182,7 @@ impl CronJob {
         session_target: SessionTarget,
         payload: Payload,
     ) -> Self {
-        let now_ms = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
+        let now_ms = now_ms();
 
         let delete_after_run = matches!(schedule, Schedule::At { .. });
 
@@ -154,10 +200,23 @@ impl CronJob {
             last_run_ms: None,
             next_run_ms: None,
             created_ms: now_ms,
+            awaiting_reply: None,
         }
     }
 }
 
+/// Text to announce for a finished run: `output` as is, or rendered into
+/// the delivery template with `output`, `job` and the job's `vars`.
+pub fn delivery_text(job: &CronJob, output: &str, workspace_dir: &Path) -> Result<String, String> {
+    let Some(delivery) = job.delivery.as_ref().filter(|d| d.template.is_some()) else {
+        return Ok(output.to_string());
+    };
+    let mut vars = crate::templates::vars_from_json(&delivery.vars);
+    vars.insert("output".to_string(), output.trim().to_string());
+    vars.insert("job".to_string(), job.name.clone().unwrap_or_else(|| job.job_id.clone()));
+    crate::templates::render_named(workspace_dir, delivery.template.as_deref().unwrap_or_default(), &vars)
+}
+
 /// Run history entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -169,6 +228,9 @@ pub struct RunEntry {
     pub status: RunStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Extra detail, e.g. when a snoozed reminder fires next.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 /// Run status.
@@ -180,6 +242,66 @@ pub enum RunStatus {
     Error,
     Timeout,
     Skipped,
+    /// Reminder postponed by a `snooze` reply.
+    Snoozed,
+    /// Reminder marked done by a `done` reply.
+    Acknowledged,
+}
+
+/// A reply to a delivered reminder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReminderReply {
+    /// Fire again after this many milliseconds.
+    Snooze(u64),
+    /// The reminder is done with.
+    Done,
+}
+
+/// Snooze length when none is given.
+pub const DEFAULT_SNOOZE_MS: u64 = 10 * 60 * 1000;
+
+/// Longest snooze; longer requests are cut to this.
+pub const MAX_SNOOZE_MS: u64 = 365 * 86_400_000;
+
+/// Read a messenger reply to a reminder: `done` (or `ack`, `✅`), or
+/// `snooze [duration]` such as `snooze 1h`, `snooze 30 min`, `snooze 2d`.
+pub fn parse_reminder_reply(text: &str) -> Option<ReminderReply> {
+    let text = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+    match text.as_str() {
+        "done" | "ack" | "acknowledged" | "✅" | "👍" => return Some(ReminderReply::Done),
+        "snooze" => return Some(ReminderReply::Snooze(DEFAULT_SNOOZE_MS)),
+        _ => {}
+    }
+    let rest = text.strip_prefix("snooze ")?.trim();
+    let rest = rest.strip_prefix("for ").unwrap_or(rest);
+    parse_duration_ms(rest).map(ReminderReply::Snooze)
+}
+
+/// Parse a short duration: `90s`, `10m`, `1h`, `2 hours`, `1d`, `1h30m`.
+/// Durations too large to represent are rejected.
+pub fn parse_duration_ms(text: &str) -> Option<u64> {
+    let mut total = 0u64;
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+        let unit_len = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+        let unit_ms = match &rest[..unit_len] {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1000,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60_000,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3_600_000,
+            "d" | "day" | "days" => 86_400_000,
+            "w" | "week" | "weeks" => 7 * 86_400_000,
+            _ => return None,
+        };
+        total = amount.checked_mul(unit_ms).and_then(|ms| total.checked_add(ms))?;
+        rest = rest[unit_len..].trim_start();
+    }
+    (total > 0).then_some(total)
 }
 
 /// Cron job store that persists jobs to disk.
@@ -230,8 +352,9 @@ impl CronStore {
         Ok(())
     }
 
-    /// Add a new job.
-    pub fn add(&mut self, job: CronJob) -> Result<JobId, String> {
+    /// Add a new job, working out when it first runs.
+    pub fn add(&mut self, mut job: CronJob) -> Result<JobId, String> {
+        job.next_run_ms = job.schedule.next_run(now_ms())?;
         let id = job.job_id.clone();
         self.jobs.insert(id.clone(), job);
         self.save()?;
@@ -265,6 +388,7 @@ impl CronStore {
             job.enabled = enabled;
         }
         if let Some(schedule) = patch.schedule {
+            job.next_run_ms = schedule.next_run(now_ms())?;
             job.schedule = schedule;
         }
         if let Some(payload) = patch.payload {
@@ -287,6 +411,148 @@ impl CronStore {
         Ok(job)
     }
 
+    /// Mark a job as delivered to a chat, so a `snooze`/`done` reply there
+    /// is matched to it.
+    pub fn mark_delivered(&mut self, job_id: &str, channel: &str, to: &str) -> Result<(), String> {
+        let job = self
+            .jobs
+            .get_mut(job_id)
+            .ok_or_else(|| format!("Job not found: {}", job_id))?;
+        job.awaiting_reply = Some(DeliveredTo {
+            channel: channel.to_string(),
+            to: to.to_string(),
+            at_ms: now_ms(),
+        });
+        self.save()
+    }
+
+    /// The reminder most recently delivered to `channel`/`to` that hasn't
+    /// been answered yet.
+    pub fn awaiting_reply(&self, channel: &str, to: &str) -> Option<&CronJob> {
+        self.jobs
+            .values()
+            .filter_map(|j| j.awaiting_reply.as_ref().map(|d| (j, d)))
+            .filter(|(_, d)| d.channel == channel && d.to == to)
+            .max_by_key(|(_, d)| d.at_ms)
+            .map(|(j, _)| j)
+    }
+
+    /// Postpone a job by `delay_ms`.  One-shot reminders are rescheduled
+    /// (and re-enabled); recurring jobs get a one-off extra run, after
+    /// which their normal schedule continues.  Delays are capped at
+    /// [`MAX_SNOOZE_MS`].  Returns the new run time.
+    pub fn snooze(&mut self, job_id: &str, delay_ms: u64, via: Option<&str>) -> Result<u64, String> {
+        let job = self
+            .jobs
+            .get_mut(job_id)
+            .ok_or_else(|| format!("Job not found: {}", job_id))?;
+        let now = now_ms();
+        let until = now.saturating_add(delay_ms.min(MAX_SNOOZE_MS));
+        if let Schedule::At { at, .. } = &mut job.schedule {
+            *at = Utc
+                .timestamp_millis_opt(until as i64)
+                .single()
+                .ok_or_else(|| format!("Invalid snooze time {}", until))?
+                .to_rfc3339();
+            job.enabled = true;
+        }
+        job.next_run_ms = Some(until);
+        job.awaiting_reply = None;
+        let note = format!(
+            "snoozed until {}{}",
+            format_ms(until, job.schedule.tz()),
+            via.map(|v| format!(" via {}", v)).unwrap_or_default()
+        );
+        self.save()?;
+        self.record_followup(job_id, now, RunStatus::Snoozed, note)?;
+        Ok(until)
+    }
+
+    /// Mark a reminder done.  One-shot reminders are completed (disabled,
+    /// so the history stays visible); recurring jobs just stop waiting for
+    /// a reply.
+    pub fn acknowledge(&mut self, job_id: &str, via: Option<&str>) -> Result<(), String> {
+        let job = self
+            .jobs
+            .get_mut(job_id)
+            .ok_or_else(|| format!("Job not found: {}", job_id))?;
+        if matches!(job.schedule, Schedule::At { .. }) {
+            job.enabled = false;
+            job.next_run_ms = None;
+        }
+        job.awaiting_reply = None;
+        let note = format!("acknowledged{}", via.map(|v| format!(" via {}", v)).unwrap_or_default());
+        self.save()?;
+        self.record_followup(job_id, now_ms(), RunStatus::Acknowledged, note)
+    }
+
+    fn record_followup(&self, job_id: &str, at_ms: u64, status: RunStatus, note: String) -> Result<(), String> {
+        self.record_run(&RunEntry {
+            job_id: job_id.to_string(),
+            run_id: format!("{}-{:x}", status_slug(&status), at_ms),
+            started_ms: at_ms,
+            finished_ms: Some(at_ms),
+            status,
+            error: None,
+            note: Some(note),
+        })
+    }
+
+    /// Enabled jobs whose next run is at or before `now`.  Each is moved on
+    /// to its following run before it is returned, so a job is claimed
+    /// once even when a run outlasts the poll interval.
+    pub fn claim_due(&mut self, now: u64) -> Result<Vec<CronJob>, String> {
+        let mut due = Vec::new();
+        for job in self.jobs.values_mut() {
+            if !job.enabled || job.next_run_ms.is_none_or(|next| next > now) {
+                continue;
+            }
+            due.push(job.clone());
+            job.last_run_ms = Some(now);
+            job.next_run_ms = match &job.schedule {
+                Schedule::At { .. } => None,
+                schedule => schedule.next_run(now).unwrap_or(None),
+            };
+        }
+        if !due.is_empty() {
+            self.save()?;
+        }
+        Ok(due)
+    }
+
+    /// Make a job due now, so the scheduler runs it on its next poll.
+    pub fn trigger(&mut self, job_id: &str) -> Result<(), String> {
+        let job = self
+            .jobs
+            .get_mut(job_id)
+            .ok_or_else(|| format!("Job not found: {}", job_id))?;
+        if !job.enabled {
+            return Err(format!("Job {} is disabled; enable it first", job_id));
+        }
+        job.next_run_ms = Some(now_ms());
+        self.save()
+    }
+
+    /// Record a finished run.  A one-shot that succeeded is deleted if it
+    /// was created with `deleteAfterRun`; otherwise a one-shot is disabled
+    /// once it has fired, unless it was snoozed in the meantime.
+    pub fn finish_run(&mut self, entry: &RunEntry) -> Result<(), String> {
+        self.record_run(entry)?;
+        let Some(job) = self.jobs.get_mut(&entry.job_id) else {
+            // Removed while it ran.
+            return Ok(());
+        };
+        if !matches!(job.schedule, Schedule::At { .. }) || job.next_run_ms.is_some() {
+            return Ok(());
+        }
+        if job.delete_after_run && entry.status == RunStatus::Ok && job.awaiting_reply.is_none() {
+            self.jobs.remove(&entry.job_id);
+        } else {
+            job.enabled = false;
+        }
+        self.save()
+    }
+
     /// Get run history for a job.
     pub fn get_runs(&self, job_id: &str, limit: usize) -> Result<Vec<RunEntry>, String> {
         let runs_file = self.runs_dir.join(format!("{}.jsonl", job_id));
@@ -322,10 +588,37 @@ impl CronStore {
         writeln!(file, "{}", line)
             .map_err(|e| format!("Failed to write run entry: {}", e))?;
 
+        if matches!(entry.status, RunStatus::Error | RunStatus::Timeout) {
+            let name = self
+                .get(&entry.job_id)
+                .and_then(|job| job.name.clone())
+                .unwrap_or_else(|| entry.job_id.clone());
+            let what = if entry.status == RunStatus::Timeout { "timed out" } else { "failed" };
+            crate::notifications::post(
+                crate::notifications::Severity::Error,
+                crate::notifications::Source::Cron,
+                format!("Cron job {} {}", name, what),
+                entry.error.clone().unwrap_or_default(),
+                Some(entry.job_id.clone()),
+            );
+        }
+
         Ok(())
     }
 }
 
+fn status_slug(status: &RunStatus) -> &'static str {
+    match status {
+        RunStatus::Running => "run",
+        RunStatus::Ok => "ok",
+        RunStatus::Error => "error",
+        RunStatus::Timeout => "timeout",
+        RunStatus::Skipped => "skip",
+        RunStatus::Snoozed => "snooze",
+        RunStatus::Acknowledged => "ack",
+    }
+}
+
 /// Patch for updating a cron job.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -342,6 +635,460 @@ pub struct CronJobPatch {
     pub delivery: Option<Delivery>,
 }
 
+// ── Schedule parsing ────────────────────────────────────────────────────────
+
+/// US-style abbreviations people actually type, mapped to the zone they
+/// mean (chrono-tz's `EST` and friends are fixed offsets with no DST).
+const TZ_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("et", "America/New_York"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("ct", "America/Chicago"),
+    ("cst", "America/Chicago"),
+    ("cdt", "America/Chicago"),
+    ("mt", "America/Denver"),
+    ("mst", "America/Denver"),
+    ("mdt", "America/Denver"),
+    ("pt", "America/Los_Angeles"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("bst", "Europe/London"),
+    ("cet", "Europe/Paris"),
+    ("cest", "Europe/Paris"),
+    ("ist", "Asia/Kolkata"),
+    ("jst", "Asia/Tokyo"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+];
+
+/// The system timezone: `TZ` if set, otherwise what the OS reports.
+pub fn local_tz() -> Tz {
+    std::env::var("TZ")
+        .ok()
+        .and_then(|name| name.trim_start_matches(':').parse().ok())
+        .or_else(|| iana_time_zone::get_timezone().ok().and_then(|name| name.parse().ok()))
+        .unwrap_or(Tz::UTC)
+}
+
+/// Parse a timezone: an IANA name (any case), `UTC`, `local`, or a common
+/// abbreviation such as `PST`.
+pub fn parse_tz(name: &str) -> Result<Tz, String> {
+    let name = name.trim();
+    let lower = name.to_lowercase();
+    match lower.as_str() {
+        "" | "local" => return Ok(local_tz()),
+        "utc" | "gmt" | "z" => return Ok(Tz::UTC),
+        _ => {}
+    }
+    if let Some((_, zone)) = TZ_ABBREVIATIONS.iter().find(|(abbr, _)| *abbr == lower) {
+        return zone.parse().map_err(|e: chrono_tz::ParseError| e.to_string());
+    }
+    name.parse::<Tz>()
+        .ok()
+        .or_else(|| chrono_tz::TZ_VARIANTS.iter().copied().find(|tz| tz.name().eq_ignore_ascii_case(name)))
+        .ok_or_else(|| format!("Unknown timezone '{}'; use an IANA name like 'Europe/Berlin' or 'America/New_York'", name))
+}
+
+/// A wall-clock time in `tz`.  Times skipped by a DST change are an error;
+/// repeated ones resolve to the first occurrence.
+pub fn localize(tz: Tz, naive: NaiveDateTime) -> Result<DateTime<Tz>, String> {
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(t) => Ok(t),
+        LocalResult::Ambiguous(first, _) => Ok(first),
+        LocalResult::None => Err(format!("{} doesn't exist in {} (skipped by a DST change)", naive, tz.name())),
+    }
+}
+
+/// Parse a date/time: RFC 3339 (with offset), `YYYY-MM-DD[ HH:MM[:SS]]`,
+/// `HH:MM` (today), or `now` / `today` / `tomorrow` / `yesterday`.  Times
+/// without an offset are in `tz`.
+pub fn parse_datetime(text: &str, tz: Tz) -> Result<DateTime<Tz>, String> {
+    let text = text.trim();
+    let now = Utc::now().with_timezone(&tz);
+    let midnight = |date: NaiveDate| localize(tz, date.and_time(NaiveTime::MIN));
+    match text.to_lowercase().as_str() {
+        "" | "now" => return Ok(now),
+        "today" => return midnight(now.date_naive()),
+        "tomorrow" => return midnight(now.date_naive() + Duration::days(1)),
+        "yesterday" => return midnight(now.date_naive() - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(text) {
+        return Ok(t.with_timezone(&tz));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return localize(tz, naive);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return midnight(date);
+    }
+    for format in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(text, format) {
+            return localize(tz, now.date_naive().and_time(time));
+        }
+    }
+    Err(format!(
+        "Can't read '{}' as a date/time; use e.g. '2026-03-01 09:00', '2026-03-01T09:00:00+01:00' or 'tomorrow'",
+        text
+    ))
+}
+
+/// `2026-03-01 09:00 CET` for a timestamp in ms.
+pub fn format_ms(ms: u64, tz: Tz) -> String {
+    match tz.timestamp_millis_opt(ms as i64) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t.format("%Y-%m-%d %H:%M %Z").to_string(),
+        LocalResult::None => ms.to_string(),
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed five-field cron expression:
+/// `minute hour day-of-month month day-of-week`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Parse one field into a bitset of allowed values.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let lower = text.to_lowercase();
+        if let Some(i) = names.iter().position(|n| *n == lower) {
+            return Ok(i as u32 + if names.len() == 12 { 1 } else { 0 });
+        }
+        let n: u32 = text.parse().map_err(|_| format!("Invalid value '{}'", text))?;
+        if n < min || n > max {
+            return Err(format!("{} is out of range {}-{}", n, min, max));
+        }
+        Ok(n)
+    };
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("Invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("Step can't be 0".to_string());
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // `5/15` means from 5 to the end, every 15.
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start > end {
+            return Err(format!("Range {} is backwards", range));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+impl CronExpr {
+    /// Parse an expression; `@hourly`, `@daily`, `@weekly`, `@monthly` and
+    /// `@yearly` are accepted too.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim().to_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@hourly" => "0 * * * *".to_string(),
+            _ => expr.trim().to_string(),
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let &[minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!(
+                "Cron expression '{}' needs 5 fields (minute hour day-of-month month day-of-week), found {}",
+                expr,
+                fields.len()
+            ));
+        };
+        let field = |text: &str, min: u32, max: u32, names: &[&str], label: &str| {
+            parse_field(text, min, max, names).map_err(|e| format!("Bad {} field in '{}': {}", label, expr, e))
+        };
+        Ok(Self {
+            minutes: field(minute, 0, 59, &[], "minute")?,
+            hours: field(hour, 0, 23, &[], "hour")? as u32,
+            days: field(day, 1, 31, &[], "day-of-month")? as u32,
+            months: field(month, 1, 12, &MONTH_NAMES, "month")? as u16,
+            // Sunday may be written as 0 or 7.
+            weekdays: field(weekday, 0, 7, &DAY_NAMES, "day-of-week").map(|b| (b | (b >> 7)) & 0x7f)? as u8,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// Day-of-month and day-of-week are OR-ed when both are restricted,
+    /// as in Vixie cron.
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days & (1 << date.day()) != 0;
+        let dow = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, searching up to
+    /// five years ahead (so `0 0 30 2 *` returns `None`).
+    pub fn next_after(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let start = after.naive_local() + Duration::minutes(1);
+        let mut t = start.date().and_hms_opt(start.hour(), start.minute(), 0)?;
+        let limit = t + Duration::days(5 * 366);
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (y, m) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(y, m, 1)?.and_time(NaiveTime::MIN);
+            } else if !self.day_matches(t.date()) {
+                t = (t.date() + Duration::days(1)).and_time(NaiveTime::MIN);
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                // A time skipped by DST has no instant; the repeated hour
+                // fires once, at its first occurrence.
+                let found = match tz.from_local_datetime(&t) {
+                    LocalResult::Single(d) | LocalResult::Ambiguous(d, _) => Some(d).filter(|d| *d > after),
+                    LocalResult::None => None,
+                };
+                if found.is_some() {
+                    return found;
+                }
+                t += Duration::minutes(1);
+            }
+        }
+        None
+    }
+}
+
+impl Schedule {
+    /// Read a schedule written as text: `every 10 minutes` (or `every 2h`),
+    /// a cron expression such as `0 9 * * mon-fri` or `@daily`, or a
+    /// date/time for a one-shot.
+    pub fn parse(text: &str, tz: Option<String>) -> Result<Self, String> {
+        let text = text.trim();
+        let lower = text.to_lowercase();
+        if let Some(rest) = lower.strip_prefix("every ") {
+            let rest = rest.trim();
+            // `every minute`, `every hour`, `every day`.
+            let every_ms = parse_duration_ms(rest)
+                .or_else(|| parse_duration_ms(&format!("1 {}", rest)))
+                .ok_or_else(|| format!("Can't read '{}' as an interval; use e.g. 'every 10 minutes'", text))?;
+            return Ok(Schedule::Every { every_ms, anchor_ms: None });
+        }
+        if text.starts_with('@') || text.split_whitespace().count() == 5 {
+            CronExpr::parse(text)?;
+            return Ok(Schedule::Cron { expr: text.to_string(), tz });
+        }
+        parse_datetime(text, parse_tz(tz.as_deref().unwrap_or("local"))?)?;
+        Ok(Schedule::At { at: text.to_string(), tz })
+    }
+
+    /// The first run strictly after `after_ms`, or `None` for a one-shot
+    /// whose time has passed.  Errors when the schedule can't be parsed.
+    pub fn next_run(&self, after_ms: u64) -> Result<Option<u64>, String> {
+        match self {
+            Schedule::At { at, tz } => {
+                let at = parse_datetime(at, parse_tz(tz.as_deref().unwrap_or("local"))?)?;
+                let ms = at.timestamp_millis().max(0) as u64;
+                Ok((ms > after_ms).then_some(ms))
+            }
+            Schedule::Every { every_ms, anchor_ms } => {
+                if *every_ms == 0 {
+                    return Err("everyMs must be greater than 0".to_string());
+                }
+                let anchor = anchor_ms.unwrap_or(after_ms);
+                if anchor > after_ms {
+                    return Ok(Some(anchor));
+                }
+                Ok(Some(anchor + ((after_ms - anchor) / every_ms + 1) * every_ms))
+            }
+            Schedule::Cron { expr, tz } => {
+                let tz = parse_tz(tz.as_deref().unwrap_or("local"))?;
+                let cron = CronExpr::parse(expr)?;
+                let after = match tz.timestamp_millis_opt(after_ms as i64) {
+                    LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t,
+                    LocalResult::None => return Err(format!("Invalid timestamp {}", after_ms)),
+                };
+                Ok(cron.next_after(after).map(|t| t.timestamp_millis() as u64))
+            }
+        }
+    }
+
+    /// Timezone the schedule is read in.
+    pub fn tz(&self) -> Tz {
+        match self {
+            Schedule::At { tz, .. } | Schedule::Cron { tz, .. } => {
+                tz.as_deref().and_then(|t| parse_tz(t).ok()).unwrap_or_else(local_tz)
+            }
+            Schedule::Every { .. } => local_tz(),
+        }
+    }
+}
+
+// ── iCal export ─────────────────────────────────────────────────────────────
+
+/// How far ahead cron-expression jobs are expanded into single events.
+const ICAL_HORIZON_MS: u64 = 60 * 24 * 3600 * 1000;
+
+/// Cap on expanded events per job, so an every-minute cron stays sane.
+const ICAL_MAX_OCCURRENCES: usize = 200;
+
+/// Render enabled jobs as an iCalendar (RFC 5545) feed.
+///
+/// One-shot reminders become a single event and `every` jobs a repeating
+/// event with an `RRULE`.  Cron expressions have no general RRULE form, so
+/// their runs over the next 60 days are listed as separate events.
+pub fn to_ical(jobs: &[&CronJob], now_ms: u64) -> String {
+    let stamp = ical_time(now_ms);
+    let mut jobs: Vec<&CronJob> = jobs.iter().copied().filter(|j| j.enabled).collect();
+    jobs.sort_by_key(|j| (j.next_run_ms.unwrap_or(u64::MAX), j.job_id.clone()));
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//RustyClaw//Cron//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:RustyClaw schedule".to_string(),
+    ];
+    for job in jobs {
+        let event = |uid: String, start_ms: u64, rrule: Option<String>| {
+            let mut event = vec![
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:{}@rustyclaw", uid),
+                format!("DTSTAMP:{}", stamp),
+                format!("DTSTART:{}", ical_time(start_ms)),
+                "DURATION:PT15M".to_string(),
+                format!("SUMMARY:{}", ical_escape(&job_summary(job))),
+            ];
+            if let Some(text) = job_details(job) {
+                event.push(format!("DESCRIPTION:{}", ical_escape(&text)));
+            }
+            event.extend(rrule.map(|r| format!("RRULE:{}", r)));
+            event.push("END:VEVENT".to_string());
+            event
+        };
+        match &job.schedule {
+            Schedule::At { .. } => {
+                if let Ok(Some(at)) = job.schedule.next_run(0) {
+                    lines.extend(event(job.job_id.clone(), at, None));
+                }
+            }
+            Schedule::Every { every_ms, anchor_ms } => {
+                let start = anchor_ms.or(job.next_run_ms).unwrap_or(now_ms);
+                lines.extend(event(job.job_id.clone(), start, Some(every_rrule(*every_ms))));
+            }
+            Schedule::Cron { .. } => {
+                let mut after = now_ms;
+                for _ in 0..ICAL_MAX_OCCURRENCES {
+                    let Ok(Some(next)) = job.schedule.next_run(after) else { break };
+                    if next > now_ms + ICAL_HORIZON_MS {
+                        break;
+                    }
+                    lines.extend(event(format!("{}-{}", job.job_id, next), next, None));
+                    after = next;
+                }
+            }
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| ical_fold(l)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+fn job_summary(job: &CronJob) -> String {
+    job.name.clone().unwrap_or_else(|| match &job.payload {
+        Payload::SystemEvent { text } => text.clone(),
+        Payload::AgentTurn { message, .. } => message.clone(),
+        Payload::Script { script, .. } => format!("Script {}", script),
+    })
+}
+
+fn job_details(job: &CronJob) -> Option<String> {
+    let payload = match &job.payload {
+        Payload::SystemEvent { text } => text.clone(),
+        Payload::AgentTurn { message, .. } => message.clone(),
+        Payload::Script { script, .. } => format!("Runs script {}", script),
+    };
+    let text = match &job.description {
+        Some(desc) => format!("{}\n\n{}", desc, payload),
+        None => payload,
+    };
+    (text != job_summary(job)).then_some(text)
+}
+
+/// RRULE for a fixed interval, in the largest unit that divides it.
+fn every_rrule(every_ms: u64) -> String {
+    let secs = (every_ms / 1000).max(1);
+    let (freq, interval) = if secs % 86_400 == 0 {
+        ("DAILY", secs / 86_400)
+    } else if secs % 3600 == 0 {
+        ("HOURLY", secs / 3600)
+    } else if secs % 60 == 0 {
+        ("MINUTELY", secs / 60)
+    } else {
+        ("SECONDLY", secs)
+    };
+    format!("FREQ={};INTERVAL={}", freq, interval)
+}
+
+/// UTC timestamp in iCal form, e.g. `20260301T080000Z`.
+fn ical_time(ms: u64) -> String {
+    Utc.timestamp_millis_opt(ms as i64)
+        .single()
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Escape a TEXT value.
+fn ical_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets, without splitting a UTF-8 character.
+fn ical_fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +1100,7 @@ mod tests {
             Some("Test Job".to_string()),
             Schedule::At {
                 at: "2026-02-12T18:00:00Z".to_string(),
+                tz: None,
             },
             SessionTarget::Main,
             Payload::SystemEvent {
@@ -421,4 +1169,250 @@ mod tests {
             assert_eq!(jobs[0].name, Some("Persistent".to_string()));
         }
     }
+
+    #[test]
+    fn test_script_payload_serde() {
+        let payload: Payload =
+            serde_json::from_str(r#"{"kind":"script","script":"backup"}"#).unwrap();
+        match &payload {
+            Payload::Script { script, args } => {
+                assert_eq!(script, "backup");
+                assert!(args.is_null());
+            }
+            other => panic!("unexpected payload: {:?}", other),
+        }
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(json, r#"{"kind":"script","script":"backup"}"#);
+    }
+
+    #[test]
+    fn test_delivery_template() {
+        let dir = TempDir::new().unwrap();
+        let templates = crate::templates::templates_dir(dir.path());
+        fs::create_dir_all(&templates).unwrap();
+        fs::write(templates.join("report.md"), "## {{job}} ({{team}})\n{{output}}\n").unwrap();
+
+        let mut job = CronJob::new(
+            Some("Nightly build".to_string()),
+            Schedule::Every { every_ms: 86_400_000, anchor_ms: None },
+            SessionTarget::Isolated,
+            Payload::SystemEvent { text: "build".into() },
+        );
+        assert_eq!(delivery_text(&job, "green", dir.path()).unwrap(), "green");
+
+        job.delivery = serde_json::from_str(r#"{"template":"report","vars":{"team":"infra"}}"#).unwrap();
+        assert_eq!(
+            delivery_text(&job, "green\n", dir.path()).unwrap(),
+            "## Nightly build (infra)\ngreen"
+        );
+    }
+
+    #[test]
+    fn test_cron_expr_parse() {
+        assert!(CronExpr::parse("*/15 9-17 * * mon-fri").is_ok());
+        assert_eq!(CronExpr::parse("@daily").unwrap(), CronExpr::parse("0 0 * * *").unwrap());
+        assert_eq!(CronExpr::parse("0 0 * * 7").unwrap(), CronExpr::parse("0 0 * * sun").unwrap());
+        assert!(CronExpr::parse("0 0 * *").unwrap_err().contains("5 fields"));
+        assert!(CronExpr::parse("60 0 * * *").unwrap_err().contains("minute"));
+        assert!(CronExpr::parse("0 0 * * */0").is_err());
+    }
+
+    #[test]
+    fn test_cron_next_after_in_timezone() {
+        let tz = parse_tz("Europe/Berlin").unwrap();
+        let cron = CronExpr::parse("30 9 * * mon-fri").unwrap();
+        // Saturday 2026-03-07 12:00 Berlin → Monday 09:30 Berlin.
+        let after = tz.with_ymd_and_hms(2026, 3, 7, 12, 0, 0).unwrap();
+        let next = cron.next_after(after).unwrap();
+        assert_eq!(next.format("%Y-%m-%d %H:%M %a").to_string(), "2026-03-09 09:30 Mon");
+        assert_eq!(next.with_timezone(&Utc).format("%H:%M").to_string(), "08:30");
+        assert!(CronExpr::parse("0 0 30 2 *").unwrap().next_after(after).is_none());
+    }
+
+    #[test]
+    fn test_cron_skips_dst_gap() {
+        // 02:30 doesn't exist in Berlin on 2026-03-29.
+        let tz = parse_tz("Europe/Berlin").unwrap();
+        let after = tz.with_ymd_and_hms(2026, 3, 28, 12, 0, 0).unwrap();
+        let next = CronExpr::parse("30 2 * * *").unwrap().next_after(after).unwrap();
+        assert_eq!(next.format("%m-%d %H:%M").to_string(), "03-30 02:30");
+    }
+
+    #[test]
+    fn test_parse_tz_and_datetime() {
+        assert_eq!(parse_tz("america/new_york").unwrap().name(), "America/New_York");
+        assert_eq!(parse_tz("PST").unwrap().name(), "America/Los_Angeles");
+        assert!(parse_tz("Mars/Olympus").is_err());
+        let tz = parse_tz("Asia/Tokyo").unwrap();
+        let t = parse_datetime("2026-03-01 09:00", tz).unwrap();
+        assert_eq!(t.with_timezone(&Utc).to_rfc3339(), "2026-03-01T00:00:00+00:00");
+        let t = parse_datetime("2026-03-01T09:00:00Z", tz).unwrap();
+        assert_eq!(t.format("%H:%M").to_string(), "18:00");
+        assert!(parse_datetime("next blue moon", tz).is_err());
+        let gap = parse_datetime("2026-03-29 02:30", parse_tz("Europe/Berlin").unwrap());
+        assert!(gap.unwrap_err().contains("DST"));
+    }
+
+    #[test]
+    fn test_schedule_next_run() {
+        let every = Schedule::Every { every_ms: 1000, anchor_ms: Some(500) };
+        assert_eq!(every.next_run(2600).unwrap(), Some(3500));
+        assert_eq!(every.next_run(100).unwrap(), Some(500));
+        let at = Schedule::At { at: "2026-02-12T18:00:00Z".into(), tz: None };
+        let at_ms = 1_770_919_200_000;
+        assert_eq!(at.next_run(at_ms - 1).unwrap(), Some(at_ms));
+        assert_eq!(at.next_run(at_ms).unwrap(), None);
+        let cron = Schedule::Cron { expr: "0 18 * * *".into(), tz: Some("UTC".into()) };
+        assert_eq!(cron.next_run(at_ms - 60_000).unwrap(), Some(at_ms));
+        let bad = Schedule::Cron { expr: "every day".into(), tz: None };
+        assert!(bad.next_run(0).is_err());
+    }
+
+    #[test]
+    fn test_parse_text_schedule() {
+        assert!(matches!(
+            Schedule::parse("every 10 minutes", None).unwrap(),
+            Schedule::Every { every_ms: 600_000, anchor_ms: None }
+        ));
+        assert!(matches!(Schedule::parse("Every hour", None).unwrap(), Schedule::Every { every_ms: 3_600_000, .. }));
+        assert!(matches!(Schedule::parse("0 9 * * mon-fri", None).unwrap(), Schedule::Cron { .. }));
+        assert!(matches!(Schedule::parse("@daily", None).unwrap(), Schedule::Cron { .. }));
+        assert!(matches!(Schedule::parse("2026-03-01 09:00", Some("UTC".into())).unwrap(), Schedule::At { .. }));
+        assert!(Schedule::parse("every so often", None).is_err());
+        assert!(Schedule::parse("0 99 * * *", None).is_err());
+    }
+
+    #[test]
+    fn test_claim_due_and_finish_run() {
+        let dir = TempDir::new().unwrap();
+        let mut store = CronStore::new(dir.path()).unwrap();
+        let every = CronJob::new(
+            Some("tick".to_string()),
+            Schedule::Every { every_ms: 60_000, anchor_ms: None },
+            SessionTarget::Isolated,
+            Payload::SystemEvent { text: "tick".into() },
+        );
+        let once = CronJob::new(
+            Some("once".to_string()),
+            Schedule::At { at: "2099-01-01T00:00:00Z".into(), tz: None },
+            SessionTarget::Isolated,
+            Payload::SystemEvent { text: "once".into() },
+        );
+        let every_id = store.add(every).unwrap();
+        let once_id = store.add(once).unwrap();
+
+        let now = now_ms();
+        assert!(store.claim_due(now).unwrap().is_empty());
+        store.trigger(&every_id).unwrap();
+        store.trigger(&once_id).unwrap();
+        let due = store.claim_due(now_ms()).unwrap();
+        assert_eq!(due.len(), 2);
+        // Claimed jobs move on, so the next poll doesn't run them again.
+        assert!(store.claim_due(now_ms()).unwrap().is_empty());
+        assert!(store.get(&every_id).unwrap().next_run_ms.unwrap() > now);
+        assert!(store.get(&once_id).unwrap().next_run_ms.is_none());
+
+        let run = |job_id: &str, status: RunStatus| RunEntry {
+            job_id: job_id.to_string(),
+            run_id: "run-1".into(),
+            started_ms: now,
+            finished_ms: Some(now + 5),
+            status,
+            error: None,
+            note: Some("tick".into()),
+        };
+        store.finish_run(&run(&every_id, RunStatus::Ok)).unwrap();
+        assert!(store.get(&every_id).unwrap().enabled);
+        store.finish_run(&run(&once_id, RunStatus::Ok)).unwrap();
+        assert!(store.get(&once_id).is_none());
+        assert_eq!(store.get_runs(&every_id, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_to_ical() {
+        let mut reminder = CronJob::new(
+            Some("Dentist, 2nd floor".to_string()),
+            Schedule::At { at: "2026-02-12T18:00:00Z".into(), tz: None },
+            SessionTarget::Main,
+            Payload::SystemEvent { text: "Leave at 17:30".into() },
+        );
+        reminder.job_id = "job-1".into();
+        let mut daily = CronJob::new(
+            None,
+            Schedule::Every { every_ms: 2 * 3600 * 1000, anchor_ms: Some(1_770_919_200_000) },
+            SessionTarget::Isolated,
+            Payload::SystemEvent { text: "Stretch".into() },
+        );
+        daily.job_id = "job-2".into();
+        let mut weekly = CronJob::new(
+            Some("Review".to_string()),
+            Schedule::Cron { expr: "0 9 * * 1".into(), tz: Some("UTC".into()) },
+            SessionTarget::Main,
+            Payload::SystemEvent { text: "Weekly review".into() },
+        );
+        weekly.job_id = "job-3".into();
+
+        let now = 1_770_000_000_000;
+        let ics = to_ical(&[&reminder, &daily, &weekly], now);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:job-1@rustyclaw\r\nDTSTAMP:"));
+        assert!(ics.contains("DTSTART:20260212T180000Z"));
+        assert!(ics.contains("SUMMARY:Dentist\\, 2nd floor"));
+        assert!(ics.contains("DESCRIPTION:Leave at 17:30"));
+        assert!(ics.contains("SUMMARY:Stretch\r\n"));
+        assert!(ics.contains("RRULE:FREQ=HOURLY;INTERVAL=2"));
+        // 60 days of Monday 09:00 runs.
+        assert_eq!(ics.matches("SUMMARY:Review").count(), 9);
+        assert!(ics.lines().all(|l| l.len() <= 75));
+    }
+
+    #[test]
+    fn test_parse_reminder_reply() {
+        assert_eq!(parse_reminder_reply("Done!"), Some(ReminderReply::Done));
+        assert_eq!(parse_reminder_reply("snooze"), Some(ReminderReply::Snooze(DEFAULT_SNOOZE_MS)));
+        assert_eq!(parse_reminder_reply("snooze 1h"), Some(ReminderReply::Snooze(3_600_000)));
+        assert_eq!(parse_reminder_reply("Snooze for 2 hours"), Some(ReminderReply::Snooze(7_200_000)));
+        assert_eq!(parse_reminder_reply("snooze 1h30m"), Some(ReminderReply::Snooze(5_400_000)));
+        assert_eq!(parse_reminder_reply("snooze until later"), None);
+        assert_eq!(parse_duration_ms("99999999999999999999w"), None);
+        assert_eq!(parse_duration_ms("9999999999999999w"), None);
+        assert_eq!(parse_reminder_reply("done with the report, thanks"), None);
+    }
+
+    #[test]
+    fn test_snooze_and_acknowledge_reminder() {
+        let dir = TempDir::new().unwrap();
+        let mut store = CronStore::new(dir.path()).unwrap();
+        let job = CronJob::new(
+            Some("Call mum".to_string()),
+            Schedule::At { at: "2026-02-12T18:00:00Z".into(), tz: None },
+            SessionTarget::Main,
+            Payload::SystemEvent { text: "Call mum".into() },
+        );
+        let id = store.add(job).unwrap();
+
+        store.mark_delivered(&id, "signal", "+4912345").unwrap();
+        assert_eq!(store.awaiting_reply("signal", "+4912345").unwrap().job_id, id);
+        assert!(store.awaiting_reply("telegram", "+4912345").is_none());
+
+        let until = store.snooze(&id, 3_600_000, Some("signal")).unwrap();
+        let job = store.get(&id).unwrap();
+        assert_eq!(job.next_run_ms, Some(until));
+        assert_eq!(job.schedule.next_run(until - 1).unwrap(), Some(until));
+        assert!(store.awaiting_reply("signal", "+4912345").is_none());
+
+        store.mark_delivered(&id, "signal", "+4912345").unwrap();
+        store.acknowledge(&id, Some("signal")).unwrap();
+        assert!(!store.get(&id).unwrap().enabled);
+
+        let runs = store.get_runs(&id, 10).unwrap();
+        assert_eq!(runs[0].status, RunStatus::Acknowledged);
+        assert_eq!(runs[1].status, RunStatus::Snoozed);
+        assert!(runs[1].note.as_deref().unwrap().starts_with("snoozed until"));
+
+        // Absurd delays are capped instead of overflowing.
+        let until = store.snooze(&id, u64::MAX, None).unwrap();
+        assert!(until <= now_ms() + MAX_SNOOZE_MS);
+    }
 }
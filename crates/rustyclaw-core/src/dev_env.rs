@@ -0,0 +1,199 @@
+//! Project development environments (direnv, Nix flakes, devenv).
+//!
+//! Many projects only build inside their declared environment.  When
+//! activation is enabled, `execute_command` wraps each command so it runs
+//! the way it would in the user's own shell:
+//!
+//! | Marker        | Wrapper                                   |
+//! |---------------|-------------------------------------------|
+//! | `.envrc`      | `direnv exec <ws> sh -c <cmd>`            |
+//! | `devenv.nix`  | `devenv shell -- sh -c <cmd>`             |
+//! | `flake.nix`   | `nix develop <ws> --command sh -c <cmd>`  |
+//! | `shell.nix`   | `nix-shell <ws>/shell.nix --run <cmd>`    |
+//!
+//! Activation is off by default.  Set `dev_env = "auto"` in `config.toml`,
+//! or per workspace in `.rustyclaw/settings.toml`.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
+use tracing::debug;
+
+/// Which environment to activate for commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DevEnvMode {
+    /// Run commands as-is.
+    #[default]
+    Off,
+    /// Use whichever environment the workspace declares.
+    Auto,
+    Direnv,
+    Devenv,
+    Nix,
+}
+
+/// A detected project environment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevEnv {
+    Direnv,
+    Devenv,
+    NixFlake,
+    NixShell,
+}
+
+impl DevEnv {
+    /// Human-readable name for status output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Direnv => "direnv",
+            Self::Devenv => "devenv",
+            Self::NixFlake => "nix flake",
+            Self::NixShell => "nix-shell",
+        }
+    }
+
+    /// Whether the tool needed to enter this environment is installed.
+    pub fn available(&self) -> bool {
+        let bin = match self {
+            Self::Direnv => "direnv",
+            Self::Devenv => "devenv",
+            Self::NixFlake => "nix",
+            Self::NixShell => "nix-shell",
+        };
+        which::which(bin).is_ok()
+    }
+}
+
+/// Detect the environment `mode` asks for in `workspace_dir`.  `.envrc`
+/// wins in auto mode because it usually wraps the Nix setup itself.
+pub fn detect(workspace_dir: &Path, mode: DevEnvMode) -> Option<DevEnv> {
+    let has = |file: &str| workspace_dir.join(file).is_file();
+    let nix = || {
+        if has("flake.nix") {
+            Some(DevEnv::NixFlake)
+        } else if has("shell.nix") {
+            Some(DevEnv::NixShell)
+        } else {
+            None
+        }
+    };
+    match mode {
+        DevEnvMode::Off => None,
+        DevEnvMode::Direnv => has(".envrc").then_some(DevEnv::Direnv),
+        DevEnvMode::Devenv => has("devenv.nix").then_some(DevEnv::Devenv),
+        DevEnvMode::Nix => nix(),
+        DevEnvMode::Auto => {
+            if has(".envrc") {
+                Some(DevEnv::Direnv)
+            } else if has("devenv.nix") {
+                Some(DevEnv::Devenv)
+            } else {
+                nix()
+            }
+        }
+    }
+}
+
+/// Quote `s` for a POSIX shell.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Wrap `command` so it runs inside `env` for `workspace_dir`.
+pub fn wrap_command(env: DevEnv, workspace_dir: &Path, command: &str) -> String {
+    let ws = quote(&workspace_dir.to_string_lossy());
+    let cmd = quote(command);
+    match env {
+        DevEnv::Direnv => format!("direnv exec {} sh -c {}", ws, cmd),
+        DevEnv::Devenv => format!("cd {} && devenv shell -- sh -c {}", ws, cmd),
+        DevEnv::NixFlake => format!("nix develop {} --command sh -c {}", ws, cmd),
+        DevEnv::NixShell => format!(
+            "nix-shell {} --run {}",
+            quote(&workspace_dir.join("shell.nix").to_string_lossy()),
+            cmd
+        ),
+    }
+}
+
+/// Activation mode for the current workspace, replaced on every reload.
+static MODE: RwLock<DevEnvMode> = RwLock::new(DevEnvMode::Off);
+
+/// Register the activation mode from the (project-overlaid) config.
+pub fn set_mode(mode: DevEnvMode) {
+    debug!(?mode, "Setting dev environment activation");
+    if let Ok(mut guard) = MODE.write() {
+        *guard = mode;
+    }
+}
+
+/// The registered activation mode.
+pub fn mode() -> DevEnvMode {
+    MODE.read().map(|m| *m).unwrap_or_default()
+}
+
+/// Wrap `command` for the workspace's environment when activation is on
+/// and the environment's tool is installed; otherwise return it unchanged.
+pub fn activate(workspace_dir: &Path, command: &str) -> String {
+    match detect(workspace_dir, mode()) {
+        Some(env) if env.available() => {
+            debug!(env = env.label(), "Running command in dev environment");
+            wrap_command(env, workspace_dir, command)
+        }
+        Some(env) => {
+            debug!(env = env.label(), "Dev environment tool not installed; running command as-is");
+            command.to_string()
+        }
+        None => command.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in files {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_detect() {
+        let both = workspace(&[".envrc", "flake.nix"]);
+        assert_eq!(detect(both.path(), DevEnvMode::Auto), Some(DevEnv::Direnv));
+        assert_eq!(detect(both.path(), DevEnvMode::Nix), Some(DevEnv::NixFlake));
+        assert_eq!(detect(both.path(), DevEnvMode::Off), None);
+        assert_eq!(detect(both.path(), DevEnvMode::Devenv), None);
+
+        let shell = workspace(&["shell.nix"]);
+        assert_eq!(detect(shell.path(), DevEnvMode::Auto), Some(DevEnv::NixShell));
+        assert_eq!(detect(shell.path(), DevEnvMode::Direnv), None);
+    }
+
+    #[test]
+    fn test_wrap_command_quotes() {
+        let ws = Path::new("/src/my proj");
+        assert_eq!(
+            wrap_command(DevEnv::NixFlake, ws, "echo 'hi'"),
+            r"nix develop '/src/my proj' --command sh -c 'echo '\''hi'\'''"
+        );
+        assert_eq!(
+            wrap_command(DevEnv::Direnv, ws, "cargo build"),
+            "direnv exec '/src/my proj' sh -c 'cargo build'"
+        );
+    }
+
+    #[test]
+    fn test_mode_from_toml() {
+        #[derive(Deserialize)]
+        struct T {
+            dev_env: DevEnvMode,
+        }
+        let t: T = toml::from_str("dev_env = \"auto\"").unwrap();
+        assert_eq!(t.dev_env, DevEnvMode::Auto);
+    }
+}
@@ -0,0 +1,327 @@
+//! Text embeddings with a persistent, content-addressed cache.
+//!
+//! Every embedding is stored under the SHA-256 of the text it was computed
+//! from, one append-only file per model in `<settings_dir>/embeddings/`.
+//! Re-indexing memory, docs or skills therefore only sends new or changed
+//! chunks to the provider; unchanged ones come straight from disk.  Hit and
+//! miss counts are exported on the gateway's `/metrics` endpoint.
+//!
+//! ```toml
+//! [embeddings]
+//! enabled = true
+//! provider = "openai"                 # any OpenAI-compatible /embeddings endpoint
+//! model = "text-embedding-3-small"
+//! # base_url = "http://localhost:11434/v1"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The `[embeddings]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Provider id; its API key is taken from the vault or environment.
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    #[serde(default = "default_model")]
+    pub model: String,
+    /// Endpoint base; defaults to the provider's.
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+fn default_provider() -> String {
+    "openai".to_string()
+}
+
+fn default_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: default_provider(),
+            model: default_model(),
+            base_url: None,
+        }
+    }
+}
+
+/// Cache hits, misses and size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Embeddings held, across all models loaded so far.
+    pub entries: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    hash: String,
+    vector: Vec<f32>,
+}
+
+/// Key of `text` in the cache.
+pub fn content_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Embeddings on disk, keyed by model and content hash.
+pub struct EmbeddingCache {
+    dir: PathBuf,
+    models: Mutex<HashMap<String, HashMap<String, Vec<f32>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EmbeddingCache {
+    pub fn open(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            models: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn file(&self, model: &str) -> PathBuf {
+        let slug: String = model
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.jsonl", slug))
+    }
+
+    /// Run `f` on the embeddings of `model`, reading them from disk the
+    /// first time.
+    fn with_model<R>(&self, model: &str, f: impl FnOnce(&mut HashMap<String, Vec<f32>>) -> R) -> R {
+        let mut models = self.models.lock().unwrap_or_else(|e| e.into_inner());
+        let entries = models.entry(model.to_string()).or_insert_with(|| {
+            let text = std::fs::read_to_string(self.file(model)).unwrap_or_default();
+            text.lines()
+                .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+                .map(|e| (e.hash, e.vector))
+                .collect()
+        });
+        f(entries)
+    }
+
+    pub fn get(&self, model: &str, text: &str) -> Option<Vec<f32>> {
+        let hash = content_hash(text);
+        self.with_model(model, |entries| entries.get(&hash).cloned())
+    }
+
+    pub fn insert(&self, model: &str, text: &str, vector: Vec<f32>) -> Result<(), String> {
+        let hash = content_hash(text);
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        let line = serde_json::to_string(&Entry { hash: hash.clone(), vector: vector.clone() })
+            .map_err(|e| e.to_string())?;
+        let path = self.file(model);
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        self.with_model(model, |entries| entries.insert(hash, vector));
+        Ok(())
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let models = self.models.lock().unwrap_or_else(|e| e.into_inner());
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: models.values().map(|m| m.len() as u64).sum(),
+        }
+    }
+
+    /// Embeddings of `texts` in order.  Only the texts not in the cache
+    /// are passed to `fetch`, in one batch, and their results are stored.
+    pub async fn embed_with<F, Fut>(&self, model: &str, texts: &[String], fetch: F) -> Result<Vec<Vec<f32>>, String>
+    where
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<Vec<Vec<f32>>, String>>,
+    {
+        let mut vectors: Vec<Option<Vec<f32>>> = texts.iter().map(|t| self.get(model, t)).collect();
+        let missing: Vec<String> = texts
+            .iter()
+            .zip(&vectors)
+            .filter(|(_, v)| v.is_none())
+            .map(|(t, _)| t.clone())
+            .collect();
+        self.hits.fetch_add((texts.len() - missing.len()) as u64, Ordering::Relaxed);
+        self.misses.fetch_add(missing.len() as u64, Ordering::Relaxed);
+
+        if !missing.is_empty() {
+            let fetched = fetch(missing.clone()).await?;
+            if fetched.len() != missing.len() {
+                return Err(format!("Expected {} embeddings, got {}", missing.len(), fetched.len()));
+            }
+            let mut fetched = missing.iter().zip(fetched);
+            for slot in vectors.iter_mut().filter(|v| v.is_none()) {
+                let (text, vector) = fetched.next().expect("one fetched embedding per missing text");
+                self.insert(model, text, vector.clone())?;
+                *slot = Some(vector);
+            }
+        }
+        Ok(vectors.into_iter().flatten().collect())
+    }
+}
+
+/// Cosine similarity of two vectors; 0 for empty or mismatched ones.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 { 0.0 } else { dot / denom }
+}
+
+struct Embedder {
+    config: EmbeddingsConfig,
+    base_url: String,
+    api_key: Option<String>,
+    cache: Arc<EmbeddingCache>,
+}
+
+static EMBEDDER: Mutex<Option<Arc<Embedder>>> = Mutex::new(None);
+
+/// Set up embeddings from the config.  Called at gateway startup and on
+/// reload; the cache is shared by every caller.
+pub fn set_config(config: EmbeddingsConfig, settings_dir: &Path, api_key: Option<String>) {
+    let Ok(mut guard) = EMBEDDER.lock() else {
+        return;
+    };
+    let dir = settings_dir.join("embeddings");
+    let cache = match guard.as_ref() {
+        Some(e) if e.cache.dir == dir => e.cache.clone(),
+        _ => Arc::new(EmbeddingCache::open(&dir)),
+    };
+    let base_url = config
+        .base_url
+        .clone()
+        .or_else(|| crate::providers::base_url_for_provider(&config.provider).map(String::from))
+        .unwrap_or_default();
+    let api_key = api_key.or_else(|| {
+        crate::providers::secret_key_for_provider(&config.provider).and_then(|name| std::env::var(name).ok())
+    });
+    *guard = Some(Arc::new(Embedder { config, base_url, api_key, cache }));
+}
+
+fn embedder() -> Option<Arc<Embedder>> {
+    EMBEDDER.lock().ok()?.clone()
+}
+
+/// Whether embeddings are configured and enabled.
+pub fn enabled() -> bool {
+    embedder().is_some_and(|e| e.config.enabled)
+}
+
+/// Cache statistics, if embeddings are set up.
+pub fn cache_stats() -> Option<CacheStats> {
+    embedder().map(|e| e.cache.stats())
+}
+
+/// Embed `texts` with the configured model, through the cache.
+pub async fn embed(texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let embedder = embedder().filter(|e| e.config.enabled).ok_or("Embeddings are not enabled")?;
+    let fetch = |batch: Vec<String>| request(&embedder, batch);
+    embedder.cache.embed_with(&embedder.config.model, texts, fetch).await
+}
+
+/// Call the provider's `/embeddings` endpoint.
+async fn request(embedder: &Embedder, input: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+    let url = format!("{}/embeddings", embedder.base_url.trim_end_matches('/'));
+    let mut builder = reqwest::Client::new()
+        .post(&url)
+        .json(&serde_json::json!({ "model": embedder.config.model, "input": input }));
+    if let Some(key) = &embedder.api_key {
+        builder = builder.bearer_auth(key);
+    }
+    let resp = builder.send().await.map_err(|e| format!("Embedding request failed: {}", e))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("Embedding request failed ({}): {}", status.as_u16(), body.trim()));
+    }
+    let data: serde_json::Value = resp.json().await.map_err(|e| format!("Invalid embedding response: {}", e))?;
+    let mut items: Vec<(u64, Vec<f32>)> = data["data"]
+        .as_array()
+        .ok_or("Invalid embedding response: no data")?
+        .iter()
+        .map(|item| {
+            let index = item["index"].as_u64().unwrap_or(0);
+            let vector = item["embedding"]
+                .as_array()
+                .map(|v| v.iter().filter_map(|x| x.as_f64()).map(|x| x as f32).collect())
+                .unwrap_or_default();
+            (index, vector)
+        })
+        .collect();
+    items.sort_by_key(|(index, _)| *index);
+    Ok(items.into_iter().map(|(_, v)| v).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn fake(batch: Vec<String>) -> Vec<Vec<f32>> {
+        batch.iter().map(|t| vec![t.len() as f32, 1.0]).collect()
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_texts_are_not_refetched() {
+        let dir = tempfile::tempdir().unwrap();
+        let fetched = AtomicUsize::new(0);
+        let texts: Vec<String> = vec!["alpha".into(), "beta".into()];
+
+        let cache = EmbeddingCache::open(dir.path());
+        let first = cache
+            .embed_with("m", &texts, |batch| {
+                fetched.fetch_add(batch.len(), Ordering::SeqCst);
+                async move { Ok(fake(batch)) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(first, vec![vec![5.0, 1.0], vec![4.0, 1.0]]);
+
+        // A fresh cache on the same directory reads the file back.
+        let cache = EmbeddingCache::open(dir.path());
+        let texts: Vec<String> = vec!["beta".into(), "gamma!".into(), "alpha".into()];
+        let second = cache
+            .embed_with("m", &texts, |batch| {
+                fetched.fetch_add(batch.len(), Ordering::SeqCst);
+                assert_eq!(batch, vec!["gamma!".to_string()]);
+                async move { Ok(fake(batch)) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(second, vec![vec![4.0, 1.0], vec![6.0, 1.0], vec![5.0, 1.0]]);
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 1, entries: 3 });
+        assert!(cache.get("other-model", "alpha").is_none());
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine(&[1.0], &[1.0, 2.0]), 0.0);
+    }
+}
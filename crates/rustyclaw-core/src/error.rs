@@ -2,7 +2,7 @@
 //!
 //! ## Error handling patterns
 //!
-//! RustyClaw uses two error handling strategies:
+//! RustyClaw uses three error handling strategies:
 //!
 //! 1. **Tools** (`Result<String, String>`): Tools return simple string errors
 //!    because these are sent back to the AI model. The error message is displayed
@@ -11,12 +11,117 @@
 //! 2. **Application logic** (`anyhow::Result`): Internal application code uses
 //!    `anyhow` for its rich error context and easy propagation.
 //!
+//! 3. **Client-facing errors** ([`Error`]): Whatever reaches a client over
+//!    the gateway protocol — failed turns, provider failures, denied or
+//!    failed tool calls — is classified so the client can render and react
+//!    to it (e.g. point at the API key on a 401) instead of parsing text.
+//!    Providers return it wrapped in `anyhow`; [`Error::from`] recovers it.
+//!
 //! ## Converting between error types
 //!
 //! Use the `anyhow_to_tool_err` and `tool_err_to_anyhow` functions to convert
 //! between the two error types when needed.
 
 use anyhow::Result as AnyhowResult;
+use serde::{Deserialize, Serialize};
+
+/// A classified error, as carried by the gateway protocol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub enum Error {
+    /// The request itself was wrong: bad arguments, unknown target, missing
+    /// configuration.
+    #[error("{0}")]
+    UserError(String),
+    /// A tool call blocked by the tool permissions or refused by the user.
+    #[error("Tool '{tool}' {reason}")]
+    PolicyDenied { tool: String, reason: String },
+    /// The model provider failed; `status` is the HTTP status, when the
+    /// request got that far.
+    #[error("{}", provider_message(provider, *status, message))]
+    ProviderError {
+        provider: String,
+        status: Option<u16>,
+        message: String,
+    },
+    /// A tool ran and failed.
+    #[error("{tool}: {message}")]
+    ToolError { tool: String, message: String },
+    /// Anything else: I/O, protocol and internal failures.
+    #[error("{0}")]
+    Internal(String),
+}
+
+fn provider_message(provider: &str, status: Option<u16>, message: &str) -> String {
+    match status {
+        Some(status) => format!("{} returned {} — {}", provider, status, message),
+        None => format!("{}: {}", provider, message),
+    }
+}
+
+impl Error {
+    pub fn provider(provider: &str, status: Option<u16>, message: impl Into<String>) -> Self {
+        Error::ProviderError {
+            provider: provider.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// Short category name: `user`, `policy`, `provider`, `tool` or `internal`.
+    pub fn category(&self) -> &'static str {
+        match self {
+            Error::UserError(_) => "user",
+            Error::PolicyDenied { .. } => "policy",
+            Error::ProviderError { .. } => "provider",
+            Error::ToolError { .. } => "tool",
+            Error::Internal(_) => "internal",
+        }
+    }
+
+    /// HTTP status of a provider error.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::ProviderError { status, .. } => *status,
+            _ => None,
+        }
+    }
+
+    /// Whether trying the same request again later may succeed: rate
+    /// limits, provider overload and server errors, and network failures.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::ProviderError { status: None, .. } => true,
+            Error::ProviderError { status: Some(s), .. } => *s == 429 || *s >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    /// Recover a classified error from an `anyhow` chain; anything else is
+    /// [`Error::Internal`].
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast_ref::<Error>() {
+            Some(classified) => classified.clone(),
+            None => Error::Internal(err.to_string()),
+        }
+    }
+}
+
+/// Split a tool's result into the text sent to the model and, on failure,
+/// the classified error sent to the client.
+pub fn tool_outcome(tool: &str, result: Result<String, String>) -> (String, Option<Error>) {
+    match result {
+        Ok(text) => (text, None),
+        Err(message) => {
+            let error = Error::ToolError {
+                tool: tool.to_string(),
+                message: message.clone(),
+            };
+            (message, Some(error))
+        }
+    }
+}
 
 /// Convert an anyhow error to a tool error (string message).
 pub fn anyhow_to_tool_err(err: anyhow::Error) -> String {
@@ -37,3 +142,34 @@ pub fn tool_err_to_anyhow(err: String) -> anyhow::Error {
 pub fn tool_to_anyhow_result<T>(result: Result<T, String>) -> AnyhowResult<T> {
     result.map_err(tool_err_to_anyhow)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_error_roundtrips_through_anyhow() {
+        let err: anyhow::Error = Error::provider("anthropic", Some(429), "rate limited").into();
+        let err = err.context("model call failed");
+        let recovered = Error::from(err);
+        assert_eq!(recovered.category(), "provider");
+        assert_eq!(recovered.status(), Some(429));
+        assert!(recovered.is_retryable());
+        assert_eq!(recovered.to_string(), "anthropic returned 429 — rate limited");
+    }
+
+    #[test]
+    fn test_unclassified_errors_are_internal() {
+        let recovered = Error::from(anyhow::anyhow!("disk full"));
+        assert_eq!(recovered, Error::Internal("disk full".into()));
+        assert!(!Error::provider("openai", Some(401), "bad key").is_retryable());
+    }
+
+    #[test]
+    fn test_tool_outcome() {
+        assert_eq!(tool_outcome("read_file", Ok("hi".into())), ("hi".into(), None));
+        let (text, error) = tool_outcome("read_file", Err("not found".into()));
+        assert_eq!(text, "not found");
+        assert_eq!(error.unwrap().to_string(), "read_file: not found");
+    }
+}
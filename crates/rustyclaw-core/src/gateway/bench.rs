@@ -0,0 +1,373 @@
+//! Provider benchmarks for `rustyclaw bench providers`.
+//!
+//! A fixed prompt is streamed from each configured provider and model,
+//! timing the first token and the whole reply; the cost comes from
+//! [`usage::price_for_model`](super::usage::price_for_model).  Results are
+//! kept in `<settings_dir>/bench.json`, newest per model.  When
+//! `[tool_schemas.router]` has no `model`, the tool router uses the model
+//! with the fastest first token on the turn's provider.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::types::{ChatMessage, CopilotSession};
+use super::{auth, providers as provider_calls, usage};
+use crate::config::Config;
+use crate::providers;
+use crate::secrets::SecretsManager;
+
+/// The prompt every model is timed on.
+pub const PROMPT: &str = "List the eight planets of the solar system in order from the sun, \
+                          one per line, with no other text.";
+
+/// Give up on a model after this long.
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// One benchmark run of one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub provider: String,
+    pub model: String,
+    /// Time until the first streamed text arrived.
+    pub first_token_ms: Option<u64>,
+    /// Time until the reply was complete.
+    pub total_ms: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Cost in USD, when the model's price is known.
+    pub cost_usd: Option<f64>,
+    /// Why the run failed, if it did.
+    pub error: Option<String>,
+    /// When the run finished (ms since the epoch).
+    pub at_ms: u64,
+}
+
+/// Benchmark results, newest per provider and model.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BenchStore {
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchStore {
+    pub fn path(settings_dir: &Path) -> PathBuf {
+        settings_dir.join("bench.json")
+    }
+
+    /// Load the results, or an empty store when there are none yet.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Add a result, replacing the previous one for the same model.
+    pub fn record(&mut self, result: BenchResult) {
+        self.results
+            .retain(|r| !(r.provider == result.provider && r.model == result.model));
+        self.results.push(result);
+    }
+
+    /// The successful result on `provider` with the fastest first token.
+    pub fn fastest(&self, provider: &str) -> Option<&BenchResult> {
+        self.results
+            .iter()
+            .filter(|r| r.provider == provider && r.error.is_none())
+            .filter_map(|r| r.first_token_ms.map(|ms| (ms, r)))
+            .min_by_key(|(ms, _)| *ms)
+            .map(|(_, r)| r)
+    }
+}
+
+static STORE: Mutex<Option<BenchStore>> = Mutex::new(None);
+
+/// Load the saved results for the routing layer.  Called at gateway
+/// startup and on reload.
+pub fn load_results(settings_dir: &Path) {
+    if let Ok(mut guard) = STORE.lock() {
+        *guard = Some(BenchStore::load(&BenchStore::path(settings_dir)));
+    }
+}
+
+/// The benchmarked model with the fastest first token on `provider`.
+pub fn fastest_model(provider: &str) -> Option<String> {
+    let guard = STORE.lock().ok()?;
+    guard.as_ref()?.fastest(provider).map(|r| r.model.clone())
+}
+
+/// A provider and model to benchmark.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub provider: String,
+    pub model: String,
+    pub base_url: String,
+    pub api_key: Option<String>,
+}
+
+/// What to benchmark: the configured model, and every model of each other
+/// provider that has a key in the vault.  `only` limits the run to those
+/// providers, which also admits keyless local providers like Ollama.
+pub fn targets(config: &Config, secrets: &mut SecretsManager, only: &[String]) -> Vec<Target> {
+    let configured = config.model.as_ref();
+    let mut targets = Vec::new();
+    for def in providers::PROVIDERS {
+        if !only.is_empty() && !only.iter().any(|p| p == def.id) {
+            continue;
+        }
+        let is_configured = configured.is_some_and(|m| m.provider == def.id);
+        let api_key = def.secret_key.and_then(|key| secrets.get_secret(key, true).ok().flatten());
+        let usable = match def.secret_key {
+            Some(_) => api_key.is_some(),
+            None => is_configured || !only.is_empty(),
+        };
+        if !usable {
+            continue;
+        }
+        let base_url = configured
+            .filter(|_| is_configured)
+            .and_then(|m| m.base_url.clone())
+            .or_else(|| def.base_url.map(String::from))
+            .unwrap_or_default();
+        let mut models: Vec<String> = def.models.iter().map(|m| m.to_string()).collect();
+        if let Some(model) = configured.filter(|_| is_configured).and_then(|m| m.model.clone()) {
+            models.retain(|m| *m != model);
+            models.insert(0, model);
+        }
+        for model in models {
+            targets.push(Target {
+                provider: def.id.to_string(),
+                model,
+                base_url: base_url.clone(),
+                api_key: api_key.clone(),
+            });
+        }
+    }
+    targets
+}
+
+/// Text and token counts gathered from a provider's stream.
+#[derive(Debug, Default)]
+struct Tally {
+    text: String,
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+}
+
+/// Fold one streamed event into `tally`; returns whether it carried text.
+fn read_event(provider: &str, event: &Value, tally: &mut Tally) -> bool {
+    let text = match provider {
+        "anthropic" => {
+            if let Some(n) = event.pointer("/message/usage/input_tokens").and_then(|v| v.as_u64()) {
+                tally.prompt_tokens = Some(n);
+            }
+            if let Some(n) = event.pointer("/usage/output_tokens").and_then(|v| v.as_u64()) {
+                tally.completion_tokens = Some(n);
+            }
+            event.pointer("/delta/text").and_then(|v| v.as_str())
+        }
+        "google" => {
+            if let Some(usage) = event.get("usageMetadata") {
+                tally.prompt_tokens = usage["promptTokenCount"].as_u64().or(tally.prompt_tokens);
+                tally.completion_tokens = usage["candidatesTokenCount"].as_u64().or(tally.completion_tokens);
+            }
+            event.pointer("/candidates/0/content/parts/0/text").and_then(|v| v.as_str())
+        }
+        _ => {
+            if let Some(usage) = event.get("usage").filter(|u| u.is_object()) {
+                tally.prompt_tokens = usage["prompt_tokens"].as_u64();
+                tally.completion_tokens = usage["completion_tokens"].as_u64();
+            }
+            event.pointer("/choices/0/delta/content").and_then(|v| v.as_str())
+        }
+    };
+    match text.filter(|t| !t.is_empty()) {
+        Some(text) => {
+            tally.text.push_str(text);
+            true
+        }
+        None => false,
+    }
+}
+
+fn request(http: &reqwest::Client, target: &Target, api_key: Option<&str>) -> reqwest::RequestBuilder {
+    let base = target.base_url.trim_end_matches('/');
+    match target.provider.as_str() {
+        "anthropic" => http
+            .post(format!("{}/v1/messages", base))
+            .header("x-api-key", api_key.unwrap_or(""))
+            .header("anthropic-version", "2023-06-01")
+            .json(&json!({
+                "model": target.model,
+                "max_tokens": 256,
+                "stream": true,
+                "messages": [{ "role": "user", "content": PROMPT }],
+            })),
+        "google" => http
+            .post(format!(
+                "{}/models/{}:streamGenerateContent?alt=sse&key={}",
+                base,
+                target.model,
+                api_key.unwrap_or("")
+            ))
+            .json(&json!({ "contents": [{ "role": "user", "parts": [{ "text": PROMPT }] }] })),
+        _ => {
+            let messages = [ChatMessage::text("user", PROMPT)];
+            let mut builder = http.post(format!("{}/chat/completions", base)).json(&json!({
+                "model": target.model,
+                "messages": [{ "role": "user", "content": PROMPT }],
+                "stream": true,
+                "stream_options": { "include_usage": true },
+            }));
+            if let Some(key) = api_key {
+                builder = builder.bearer_auth(key);
+            }
+            provider_calls::apply_copilot_headers(builder, &target.provider, &messages)
+        }
+    }
+}
+
+/// Stream the prompt from `target`, timing the first token and the reply.
+async fn measure(http: &reqwest::Client, target: &Target) -> Result<(Option<Duration>, Duration, Tally), String> {
+    use futures_util::StreamExt;
+
+    let session = target
+        .api_key
+        .clone()
+        .filter(|_| providers::needs_copilot_session(&target.provider))
+        .map(CopilotSession::new);
+    let api_key = auth::resolve_bearer_token(http, &target.provider, target.api_key.as_deref(), session.as_ref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let started = Instant::now();
+    let resp = provider_calls::send_with_retry(request(http, target, api_key.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("HTTP {}: {}", status.as_u16(), body.trim()));
+    }
+
+    let mut first_token = None;
+    let mut tally = Tally::default();
+    let mut buffer = String::new();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let Ok(event) = serde_json::from_str::<Value>(data.trim()) else {
+                continue;
+            };
+            if read_event(&target.provider, &event, &mut tally) && first_token.is_none() {
+                first_token = Some(started.elapsed());
+            }
+        }
+    }
+    Ok((first_token, started.elapsed(), tally))
+}
+
+/// Benchmark one target.  Failures are recorded in the result, not
+/// returned.
+pub async fn run(target: &Target) -> BenchResult {
+    let http = reqwest::Client::new();
+    let outcome = match tokio::time::timeout(TIMEOUT, measure(&http, target)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(format!("no reply within {}s", TIMEOUT.as_secs())),
+    };
+    let mut result = BenchResult {
+        provider: target.provider.clone(),
+        model: target.model.clone(),
+        first_token_ms: None,
+        total_ms: 0,
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        cost_usd: None,
+        error: None,
+        at_ms: crate::cron::now_ms(),
+    };
+    match outcome {
+        Ok((first_token, total, tally)) => {
+            result.first_token_ms = first_token.map(|d| d.as_millis() as u64);
+            result.total_ms = total.as_millis() as u64;
+            result.prompt_tokens = tally.prompt_tokens.unwrap_or((PROMPT.len() / 4) as u64);
+            result.completion_tokens = tally.completion_tokens.unwrap_or((tally.text.len() / 4) as u64);
+            result.cost_usd = usage::price_for_model(&target.model).map(|p| {
+                (result.prompt_tokens as f64 * p.input + result.completion_tokens as f64 * p.output) / 1_000_000.0
+            });
+            if first_token.is_none() {
+                result.error = Some("the reply had no text".to_string());
+            }
+        }
+        Err(err) => result.error = Some(err),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(model: &str, first_token_ms: Option<u64>, error: Option<&str>) -> BenchResult {
+        BenchResult {
+            provider: "openai".into(),
+            model: model.into(),
+            first_token_ms,
+            total_ms: 1000,
+            prompt_tokens: 20,
+            completion_tokens: 10,
+            cost_usd: None,
+            error: error.map(String::from),
+            at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_store_keeps_newest_and_finds_fastest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = BenchStore::path(dir.path());
+        let mut store = BenchStore::load(&path);
+        store.record(result("gpt-4.1", Some(900), None));
+        store.record(result("gpt-4.1-mini", Some(400), None));
+        store.record(result("gpt-4.1-nano", Some(100), Some("HTTP 404")));
+        store.record(result("gpt-4.1", Some(300), None));
+        store.save(&path).unwrap();
+
+        let store = BenchStore::load(&path);
+        assert_eq!(store.results.len(), 3);
+        assert_eq!(store.fastest("openai").unwrap().model, "gpt-4.1");
+        assert!(store.fastest("anthropic").is_none());
+    }
+
+    #[test]
+    fn test_read_stream_events() {
+        let mut tally = Tally::default();
+        let start = json!({"type": "message_start", "message": {"usage": {"input_tokens": 25}}});
+        assert!(!read_event("anthropic", &start, &mut tally));
+        let delta = json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "Mercury"}});
+        assert!(read_event("anthropic", &delta, &mut tally));
+        let end = json!({"type": "message_delta", "usage": {"output_tokens": 30}});
+        read_event("anthropic", &end, &mut tally);
+        assert_eq!((tally.text.as_str(), tally.prompt_tokens, tally.completion_tokens), ("Mercury", Some(25), Some(30)));
+
+        let mut tally = Tally::default();
+        assert!(!read_event("openai", &json!({"choices": [{"delta": {"role": "assistant"}}]}), &mut tally));
+        assert!(read_event("ollama", &json!({"choices": [{"delta": {"content": "Venus"}}]}), &mut tally));
+        let google = json!({"candidates": [{"content": {"parts": [{"text": "Earth"}]}}], "usageMetadata": {"promptTokenCount": 12}});
+        assert!(read_event("google", &google, &mut tally));
+        assert_eq!(tally.text, "VenusEarth");
+    }
+}
@@ -0,0 +1,278 @@
+//! Cron scheduler for the gateway.
+//!
+//! Polls the workspace's cron store for due jobs and runs each one:
+//! agent turns go through the headless tool loop in a fresh session of
+//! kind `cron` (labelled `cron:<jobId>`), scripts run in-process and
+//! system events are delivered as they are.  The result is announced to
+//! the job's delivery target (or the notification center when it has
+//! none) and recorded in the job's run history.
+
+use crate::config::Config;
+use crate::cron::{self, CronJob, CronStore, DeliveryMode, Payload, RunEntry, RunStatus};
+use crate::notifications::{self, Severity, Source};
+use crate::observability::trace as turn_trace;
+use crate::sessions::{session_manager, SessionKind};
+use crate::tools::ToolPermission;
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn, Instrument};
+
+use super::task_worker::run_headless_turn;
+use super::{ModelContext, SharedSkillManager, SharedVault};
+
+/// How often the store is checked for due jobs.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Tool rounds one agent-turn job may use.
+const MAX_TOOL_ROUNDS: usize = 20;
+
+/// Characters of a run's output kept as its history note.
+const NOTE_CHARS: usize = 200;
+
+const SYSTEM_PROMPT: &str = "You are running a scheduled cron job without a user present. \
+     Do what the job asks using the available tools and finish with a short result for the user.";
+
+/// Run due cron jobs until cancelled.
+pub async fn run_cron_scheduler(
+    config: Config,
+    model_ctx: Option<Arc<ModelContext>>,
+    vault: SharedVault,
+    skill_mgr: SharedSkillManager,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let workspace_dir = config.workspace_dir();
+    let cron_dir = workspace_dir.join(".cron");
+    let permissions = Arc::new(config.tool_permissions.clone());
+    let http = reqwest::Client::new();
+
+    info!(dir = %cron_dir.display(), "Starting cron scheduler");
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Shutting down cron scheduler");
+                break;
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL) => {
+                let due = match CronStore::new(&cron_dir).and_then(|mut s| s.claim_due(cron::now_ms())) {
+                    Ok(due) => due,
+                    Err(e) => {
+                        error!(error = %e, "Failed to read cron jobs");
+                        continue;
+                    }
+                };
+                for job in due {
+                    let runner = JobRunner {
+                        http: http.clone(),
+                        model_ctx: model_ctx.clone(),
+                        vault: vault.clone(),
+                        skill_mgr: skill_mgr.clone(),
+                        permissions: permissions.clone(),
+                        workspace_dir: workspace_dir.clone(),
+                        cron_dir: cron_dir.clone(),
+                    };
+                    let trace_id = turn_trace::new_trace_id();
+                    let span = turn_trace::turn_span(&trace_id, "cron");
+                    let owner = trace_id.clone();
+                    tokio::spawn(
+                        turn_trace::scope(trace_id, async move {
+                            runner.run(job).await;
+                            crate::tools::release_file_locks(&owner);
+                        })
+                        .instrument(span),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// What a job run needs from the gateway.
+struct JobRunner {
+    http: reqwest::Client,
+    model_ctx: Option<Arc<ModelContext>>,
+    vault: SharedVault,
+    skill_mgr: SharedSkillManager,
+    permissions: Arc<HashMap<String, ToolPermission>>,
+    workspace_dir: PathBuf,
+    cron_dir: PathBuf,
+}
+
+impl JobRunner {
+    /// Run one claimed job, deliver its output and record the run.
+    async fn run(self, job: CronJob) {
+        let name = job.name.clone().unwrap_or_else(|| job.job_id.clone());
+        debug!(job_id = %job.job_id, job = %name, "Running cron job");
+        let started_ms = cron::now_ms();
+
+        let snapshot_dir = self.workspace_dir.clone();
+        let reason = format!("cron {}", job.job_id);
+        let _ = tokio::task::spawn_blocking(move || crate::snapshots::before_run(&snapshot_dir, &reason)).await;
+
+        let outcome = self.execute(&job).await;
+        let delivered = match &outcome {
+            Ok(output) => self.deliver(&job, &name, output).await,
+            Err(_) => None,
+        };
+
+        let (status, error, note) = match outcome {
+            Ok(output) => {
+                let mut note: String = output.trim().chars().take(NOTE_CHARS).collect();
+                if let Some(to) = &delivered {
+                    note = format!("delivered to {}: {}", to, note);
+                }
+                (RunStatus::Ok, None, Some(note))
+            }
+            Err((status, err)) => (status, Some(err), None),
+        };
+        let entry = RunEntry {
+            job_id: job.job_id.clone(),
+            run_id: format!("run-{:x}", started_ms),
+            started_ms,
+            finished_ms: Some(cron::now_ms()),
+            status,
+            error,
+            note,
+        };
+        match CronStore::new(&self.cron_dir).and_then(|mut s| s.finish_run(&entry)) {
+            Ok(()) if entry.status == RunStatus::Ok => info!(job_id = %job.job_id, "Cron job finished"),
+            Ok(()) => warn!(job_id = %job.job_id, error = ?entry.error, "Cron job failed"),
+            Err(e) => error!(job_id = %job.job_id, error = %e, "Failed to record cron run"),
+        }
+    }
+
+    /// The job's output, or the status and error it failed with.
+    async fn execute(&self, job: &CronJob) -> Result<String, (RunStatus, String)> {
+        match &job.payload {
+            Payload::SystemEvent { text } => Ok(text.clone()),
+            Payload::Script { script, args } => {
+                let (script, args, dir) = (script.clone(), args.clone(), self.workspace_dir.clone());
+                tokio::task::spawn_blocking(move || crate::scripting::run_named(&script, &args, &dir))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r)
+                    .map_err(|e| (RunStatus::Error, e))
+            }
+            Payload::AgentTurn { message, model, timeout_seconds, .. } => {
+                let Some(ctx) = &self.model_ctx else {
+                    return Err((RunStatus::Skipped, "No model is configured".to_string()));
+                };
+                let ctx = match model {
+                    Some(model) => Arc::new(ModelContext { model: model.clone(), ..(**ctx).clone() }),
+                    None => ctx.clone(),
+                };
+                let session_key = cron_session(&job.job_id, message);
+                let turn = run_headless_turn(
+                    &self.http,
+                    &ctx,
+                    &self.vault,
+                    &self.skill_mgr,
+                    &self.permissions,
+                    &self.workspace_dir,
+                    SYSTEM_PROMPT,
+                    message,
+                    MAX_TOOL_ROUNDS,
+                );
+                let outcome = match timeout_seconds {
+                    Some(secs) => match tokio::time::timeout(Duration::from_secs(*secs), turn).await {
+                        Ok(result) => result.map(|t| t.text).map_err(|e| (RunStatus::Error, e.to_string())),
+                        Err(_) => Err((RunStatus::Timeout, format!("Timed out after {}s", secs))),
+                    },
+                    None => turn.await.map(|t| t.text).map_err(|e| (RunStatus::Error, e.to_string())),
+                };
+                finish_session(session_key.as_deref(), &outcome);
+                outcome
+            }
+        }
+    }
+
+    /// Announce a run's output: to the delivery target through the
+    /// `message` tool (which respects quiet hours), or as a notification.
+    /// Returns where it went when it was sent to a chat.
+    async fn deliver(&self, job: &CronJob, name: &str, output: &str) -> Option<String> {
+        let delivery = job.delivery.clone().unwrap_or_default();
+        if delivery.mode == DeliveryMode::None || output.trim().is_empty() {
+            return None;
+        }
+        let text = match cron::delivery_text(job, output, &self.workspace_dir) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!(job_id = %job.job_id, error = %e, "Failed to render delivery template");
+                output.to_string()
+            }
+        };
+        let Some(to) = delivery.to.clone() else {
+            let body: String = text.trim().chars().take(500).collect();
+            notifications::post(Severity::Info, Source::Cron, name, body, Some(job.job_id.clone()));
+            return None;
+        };
+        let channel = delivery.channel.clone().unwrap_or_else(|| "auto".to_string());
+        let args = json!({ "action": "send", "message": text, "target": to, "channel": channel });
+        let dir = self.workspace_dir.clone();
+        let sent = tokio::task::spawn_blocking(move || crate::tools::execute_tool("message", &args, &dir))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r);
+        match sent {
+            Ok(_) => {
+                if let Err(e) = mark_delivered(&self.cron_dir, &job.job_id, &channel, &to) {
+                    warn!(job_id = %job.job_id, error = %e, "Failed to record delivery");
+                }
+                Some(format!("{} {}", channel, to))
+            }
+            Err(e) => {
+                warn!(job_id = %job.job_id, error = %e, "Failed to deliver cron output");
+                if !delivery.best_effort {
+                    notifications::post(
+                        Severity::Warning,
+                        Source::Cron,
+                        format!("Couldn't deliver {}", name),
+                        e,
+                        Some(job.job_id.clone()),
+                    );
+                }
+                None
+            }
+        }
+    }
+}
+
+fn mark_delivered(cron_dir: &Path, job_id: &str, channel: &str, to: &str) -> Result<(), String> {
+    CronStore::new(cron_dir)?.mark_delivered(job_id, channel, to)
+}
+
+/// A fresh `cron` session for this run, with the prompt recorded.
+fn cron_session(job_id: &str, prompt: &str) -> Option<String> {
+    let mut mgr = session_manager().lock().ok()?;
+    let key = mgr.spawn_subagent("cron", prompt, Some(format!("cron:{}", job_id)), None);
+    if let Some(session) = mgr.get_mut(&key) {
+        session.kind = SessionKind::Cron;
+        session.add_message("user", prompt);
+    }
+    Some(key)
+}
+
+fn finish_session(key: Option<&str>, outcome: &Result<String, (RunStatus, String)>) {
+    let (Some(key), Ok(mut mgr)) = (key, session_manager().lock()) else {
+        return;
+    };
+    if let Some(session) = mgr.get_mut(key) {
+        match outcome {
+            Ok(text) => {
+                session.add_message("assistant", text);
+                session.complete();
+            }
+            Err((_, err)) => {
+                session.add_message("system", &format!("Cron job failed: {}", err));
+                session.error();
+            }
+        }
+    }
+}
@@ -3,9 +3,16 @@
 //! Provides HTTP endpoints for:
 //! - /health - Simple health check (returns 200 OK if running)
 //! - /status - Detailed status with metrics
+//! - /tasks - List (GET) or enqueue (POST) background tasks
+//! - /presence - Node positions (GET) or report a location fix (POST)
+//! - /calendar.ics - Scheduled cron jobs and reminders as an iCal feed
+//!
+//! POSTs run work as the owner, so they need `Authorization: Bearer
+//! <health_token>`.  Without a `health_token` the listener is read-only.
 
 use serde_json::json;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::net::TcpListener;
@@ -81,18 +88,113 @@ impl Default for HealthStats {
 
 pub type SharedHealthStats = Arc<HealthStats>;
 
+/// Task queue directory served by the `/tasks` endpoint.
+static TASKS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Enable the `/tasks` endpoint for the given task queue directory.
+pub fn set_tasks_dir(dir: PathBuf) {
+    let _ = TASKS_DIR.set(dir);
+}
+
+/// Cron store directory served by the `/calendar.ics` endpoint.
+static CRON_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Enable the `/calendar.ics` endpoint for the given cron directory.
+pub fn set_cron_dir(dir: PathBuf) {
+    let _ = CRON_DIR.set(dir);
+}
+
+/// Largest request head (request line plus headers) accepted.
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Largest request body accepted.
+const MAX_BODY: usize = 1024 * 1024;
+
+/// A parsed HTTP request.
+struct Request {
+    method: String,
+    /// Path without the query string.
+    path: String,
+    /// Token from an `Authorization: Bearer` header.
+    bearer: Option<String>,
+    body: String,
+}
+
+/// Read one HTTP/1.1 request: the head up to the blank line, then exactly
+/// `Content-Length` bytes of body however many reads they take.
+async fn read_request<R>(reader: &mut R) -> Result<Request>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    let mut head_len = 0;
+    let mut request_line = String::new();
+    head_len += reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().context("Empty request")?.to_string();
+    let target = parts.next().context("Missing request path")?;
+    let path = target.split('?').next().unwrap_or("/").to_string();
+
+    let mut content_length = 0;
+    let mut bearer = None;
+    loop {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line).await?;
+        head_len += n;
+        if head_len > MAX_HEAD {
+            anyhow::bail!("Request head too large");
+        }
+        let line = line.trim_end();
+        if n == 0 || line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().context("Invalid Content-Length")?;
+            } else if name.trim().eq_ignore_ascii_case("authorization") {
+                bearer = value.trim().strip_prefix("Bearer ").map(|t| t.trim().to_string());
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        anyhow::bail!("Request body too large");
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Request {
+        method,
+        path,
+        bearer,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
 /// Start HTTP health check server
+///
+/// `token` is the bearer token POSTs must carry; `None` refuses them all.
 pub async fn start_health_server(
     listen_addr: &str,
     stats: SharedHealthStats,
+    token: Option<String>,
     cancel: CancellationToken,
 ) -> Result<()> {
     let listener = TcpListener::bind(listen_addr)
         .await
         .context("Failed to bind health check server")?;
 
-    info!(address = %listen_addr, "Health check server listening");
+    info!(address = %listen_addr, writable = token.is_some(), "Health check server listening");
+    serve(listener, stats, token.map(Arc::new), cancel).await
+}
 
+/// Answer health requests on `listener` until cancelled.
+async fn serve(
+    listener: TcpListener,
+    stats: SharedHealthStats,
+    token: Option<Arc<String>>,
+    cancel: CancellationToken,
+) -> Result<()> {
     loop {
         tokio::select! {
             _ = cancel.cancelled() => {
@@ -102,9 +204,10 @@ pub async fn start_health_server(
             accepted = listener.accept() => {
                 let (stream, _) = accepted?;
                 let stats_clone = stats.clone();
+                let token = token.clone();
 
                 tokio::spawn(async move {
-                    if let Err(e) = handle_health_request(stream, stats_clone).await {
+                    if let Err(e) = handle_health_request(stream, stats_clone, token.as_deref().map(String::as_str)).await {
                         debug!(error = %e, "Health check request error");
                     }
                 });
@@ -115,26 +218,60 @@ pub async fn start_health_server(
     Ok(())
 }
 
+/// Whether `request` may change anything: reads always may, writes need
+/// the configured token.
+fn authorize(request: &Request, token: Option<&str>) -> std::result::Result<(), (&'static str, &'static str)> {
+    if matches!(request.method.as_str(), "GET" | "HEAD") {
+        return Ok(());
+    }
+    let Some(token) = token else {
+        return Err(("403 Forbidden", "This endpoint is read-only; set health_token to allow POSTs"));
+    };
+    match request.bearer.as_deref() {
+        Some(given) if tokens_match(given, token) => Ok(()),
+        _ => Err(("401 Unauthorized", "Missing or invalid bearer token")),
+    }
+}
+
+/// Compare tokens without leaking how much of them matched.
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 async fn handle_health_request(
     mut stream: tokio::net::TcpStream,
     stats: SharedHealthStats,
+    token: Option<&str>,
 ) -> Result<()> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::AsyncWriteExt;
+
+    let (read_half, mut stream) = stream.split();
+    let request = read_request(&mut tokio::io::BufReader::new(read_half)).await?;
+
+    let (status, content_type, body) = match authorize(&request, token) {
+        Ok(()) => route(&request, &stats),
+        Err((status, error)) => (status, "application/json", json!({ "error": error }).to_string()),
+    };
+
+    // Send HTTP response
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
 
-    // Read HTTP request (simple, just get the path)
-    let mut buffer = [0u8; 1024];
-    let n = stream.read(&mut buffer).await?;
-    let request = String::from_utf8_lossy(&buffer[..n]);
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
 
-    // Parse path
-    let path = request
-        .lines()
-        .next()
-        .and_then(|line| line.split_whitespace().nth(1))
-        .unwrap_or("/");
+    Ok(())
+}
 
-    // Generate response
-    let (status, content_type, body) = match path {
+/// Answer an authorized request: status line, content type and body.
+fn route(request: &Request, stats: &HealthStats) -> (&'static str, &'static str, String) {
+    let method = request.method.as_str();
+    match request.path.as_str() {
         "/health" => {
             // Simple health check
             let response = json!({
@@ -177,6 +314,7 @@ async fn handle_health_request(
             let model_errs = stats.model_errors.load(Ordering::Relaxed);
             let tool_calls = stats.tool_calls.load(Ordering::Relaxed);
             let tool_errs = stats.tool_errors.load(Ordering::Relaxed);
+            let cache = crate::embeddings::cache_stats().unwrap_or_default();
             
             let body = format!(
                 "# HELP rustyclaw_up Whether RustyClaw is running (1 = up)\n\
@@ -217,33 +355,259 @@ async fn handle_health_request(
                  \n\
                  # HELP rustyclaw_tool_errors_total Total tool execution errors\n\
                  # TYPE rustyclaw_tool_errors_total counter\n\
-                 rustyclaw_tool_errors_total {}\n",
+                 rustyclaw_tool_errors_total {}\n\
+                 \n\
+                 # HELP rustyclaw_embedding_cache_hits_total Embeddings served from the cache\n\
+                 # TYPE rustyclaw_embedding_cache_hits_total counter\n\
+                 rustyclaw_embedding_cache_hits_total {}\n\
+                 \n\
+                 # HELP rustyclaw_embedding_cache_misses_total Embeddings fetched from the provider\n\
+                 # TYPE rustyclaw_embedding_cache_misses_total counter\n\
+                 rustyclaw_embedding_cache_misses_total {}\n\
+                 \n\
+                 # HELP rustyclaw_embedding_cache_entries Embeddings held in the loaded cache\n\
+                 # TYPE rustyclaw_embedding_cache_entries gauge\n\
+                 rustyclaw_embedding_cache_entries {}\n",
                 version, uptime, total_conn, active_conn, total_msgs,
-                model_reqs, model_errs, tool_calls, tool_errs
+                model_reqs, model_errs, tool_calls, tool_errs,
+                cache.hits, cache.misses, cache.entries
             );
             ("200 OK", "text/plain; version=0.0.4; charset=utf-8", body)
         }
+        "/tasks" => match TASKS_DIR.get() {
+            Some(dir) => {
+                match handle_tasks_request(method, &request.body, dir) {
+                    Ok(response) => ("200 OK", "application/json", response.to_string()),
+                    Err(e) => (
+                        "400 Bad Request",
+                        "application/json",
+                        json!({ "error": e }).to_string(),
+                    ),
+                }
+            }
+            None => (
+                "503 Service Unavailable",
+                "application/json",
+                json!({ "error": "Task queue not enabled" }).to_string(),
+            ),
+        },
+        "/presence" => {
+            match handle_presence_request(method, &request.body) {
+                Ok(response) => ("200 OK", "application/json", response.to_string()),
+                Err(e) => (
+                    "400 Bad Request",
+                    "application/json",
+                    json!({ "error": e }).to_string(),
+                ),
+            }
+        }
+        "/calendar.ics" => match CRON_DIR.get().map(|dir| crate::cron::CronStore::new(dir)) {
+            Some(Ok(store)) => (
+                "200 OK",
+                "text/calendar; charset=utf-8",
+                crate::cron::to_ical(&store.list(false), crate::cron::now_ms()),
+            ),
+            Some(Err(e)) => (
+                "500 Internal Server Error",
+                "application/json",
+                json!({ "error": e }).to_string(),
+            ),
+            None => (
+                "503 Service Unavailable",
+                "application/json",
+                json!({ "error": "Cron store not available" }).to_string(),
+            ),
+        },
         _ => {
             // 404 Not Found
             let response = json!({
                 "error": "Not Found",
-                "available_endpoints": ["/health", "/status", "/metrics"],
+                "available_endpoints": ["/health", "/status", "/metrics", "/tasks", "/presence", "/calendar.ics"],
             });
             ("404 Not Found", "application/json", response.to_string())
         }
-    };
+    }
+}
 
-    // Send HTTP response
-    let response = format!(
-        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\n\r\n{}",
-        status,
-        content_type,
-        body.len(),
-        body
-    );
+/// List tasks (GET) or enqueue one from a JSON body (POST).
+///
+/// POST body: `{"prompt": "...", "priority": "high", "maxRetries": 1}`
+fn handle_tasks_request(
+    method: &str,
+    body: &str,
+    dir: &std::path::Path,
+) -> std::result::Result<serde_json::Value, String> {
+    use crate::task_queue::{QueuedTask, TaskPriority, TaskQueue};
 
-    stream.write_all(response.as_bytes()).await?;
-    stream.flush().await?;
+    let mut queue = TaskQueue::new(dir)?;
+    match method {
+        "GET" => Ok(json!({ "tasks": queue.list(true) })),
+        "POST" => {
+            let req: serde_json::Value =
+                serde_json::from_str(body).map_err(|e| format!("Invalid JSON body: {}", e))?;
+            let prompt = req
+                .get("prompt")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required field: prompt")?;
+            let priority = match req.get("priority").and_then(|v| v.as_str()) {
+                Some(p) => p.parse::<TaskPriority>()?,
+                None => TaskPriority::Normal,
+            };
+            let max_retries = req
+                .get("maxRetries")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(2) as u32;
 
-    Ok(())
+            let mut task = QueuedTask::new(prompt, priority, max_retries);
+            task.source = Some("http".to_string());
+            let id = queue.enqueue(task)?;
+            Ok(json!({ "id": id, "status": "queued" }))
+        }
+        other => Err(format!("Unsupported method: {}", other)),
+    }
+}
+
+/// Show node positions (GET) or record a location fix (POST).
+///
+/// POST body: `{"node": "phone", "latitude": 51.5, "longitude": -0.12}`
+fn handle_presence_request(
+    method: &str,
+    body: &str,
+) -> std::result::Result<serde_json::Value, String> {
+    match method {
+        "GET" => Ok(crate::presence::status()),
+        "POST" => {
+            let req: serde_json::Value =
+                serde_json::from_str(body).map_err(|e| format!("Invalid JSON body: {}", e))?;
+            let node = req
+                .get("node")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required field: node")?;
+            let coord = |key: &str| {
+                req.get(key)
+                    .and_then(|v| v.as_f64())
+                    .ok_or_else(|| format!("Missing required field: {}", key))
+            };
+            let events = crate::presence::report(node, coord("latitude")?, coord("longitude")?)?;
+            Ok(json!({ "events": events }))
+        }
+        other => Err(format!("Unsupported method: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Send a raw request and return the status line and body.
+    async fn request(addr: std::net::SocketAddr, raw: &[u8]) -> (String, String) {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream.write_all(raw).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    async fn get(addr: std::net::SocketAddr, path: &str) -> (String, String) {
+        request(addr, format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await
+    }
+
+    #[tokio::test]
+    async fn test_every_route() {
+        let dir = tempfile::TempDir::new().unwrap();
+        set_tasks_dir(dir.path().join("tasks"));
+        set_cron_dir(dir.path().join("cron"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cancel = CancellationToken::new();
+        let token = Some(Arc::new("s3cret".to_string()));
+        tokio::spawn(serve(listener, Arc::new(HealthStats::new()), token, cancel.clone()));
+
+        let (status, body) = get(addr, "/health").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.contains("\"status\":\"ok\""));
+
+        let (status, body) = get(addr, "/status?verbose=1").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.contains("active_connections"));
+
+        let (status, body) = get(addr, "/metrics").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.contains("rustyclaw_up 1"));
+
+        // The body arrives in a separate write from the head.
+        let payload = r#"{"prompt": "tidy the inbox", "priority": "high"}"#;
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let head = format!(
+            "POST /tasks HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer s3cret\r\nContent-Length: {}\r\n\r\n",
+            payload.len()
+        );
+        stream.write_all(head.as_bytes()).await.unwrap();
+        stream.flush().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        stream.write_all(payload.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.contains("\"status\":\"queued\""));
+
+        let (status, body) = get(addr, "/tasks").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.contains("tidy the inbox"));
+
+        let (status, _) = get(addr, "/presence").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+
+        let (status, body) = get(addr, "/calendar.ics").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.starts_with("BEGIN:VCALENDAR"));
+
+        let (status, _) = get(addr, "/nope").await;
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+
+        cancel.cancel();
+    }
+
+    fn post(path: &str, auth: Option<&str>, body: &str) -> Vec<u8> {
+        let auth = auth.map(|t| format!("Authorization: Bearer {}\r\n", t)).unwrap_or_default();
+        format!(
+            "POST {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}",
+            path,
+            auth,
+            body.len(),
+            body
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_posts_need_the_token() {
+        let dir = tempfile::TempDir::new().unwrap();
+        set_tasks_dir(dir.path().join("tasks"));
+        let body = r#"{"prompt": "rm -rf ~"}"#;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cancel = CancellationToken::new();
+        let token = Some(Arc::new("s3cret".to_string()));
+        tokio::spawn(serve(listener, Arc::new(HealthStats::new()), token, cancel.clone()));
+        let (status, _) = request(addr, &post("/tasks", None, body)).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        let (status, _) = request(addr, &post("/presence", Some("guess"), r#"{"node": "phone"}"#)).await;
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+
+        // Without a token the listener is read-only.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Arc::new(HealthStats::new()), None, cancel.clone()));
+        let (status, _) = request(addr, &post("/tasks", Some("s3cret"), body)).await;
+        assert_eq!(status, "HTTP/1.1 403 Forbidden");
+        let (status, _) = get(addr, "/health").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+
+        cancel.cancel();
+    }
 }
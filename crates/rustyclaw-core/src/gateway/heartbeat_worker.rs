@@ -0,0 +1,168 @@
+//! Heartbeat loop for the gateway.
+//!
+//! Wakes the agent every `[heartbeat] interval_minutes` inside the active
+//! hours and runs the heartbeat prompt through the same headless tool loop
+//! as queued tasks, in a sub-agent session labelled `heartbeat`.  Replies of
+//! `HEARTBEAT_OK` are dropped; anything else becomes a notification.  See
+//! [`crate::heartbeat`] for the prompt and budget rules.
+
+use crate::config::Config;
+use crate::heartbeat::{self, Budget, HeartbeatConfig};
+use crate::notifications::{self, Severity, Source};
+use crate::observability::trace as turn_trace;
+use crate::sessions::session_manager;
+use anyhow::Result;
+use chrono::Local;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn, Instrument};
+
+use super::task_worker::run_headless_turn;
+use super::{ModelContext, SharedSkillManager, SharedVault};
+
+/// Label of the session heartbeats run in.
+const SESSION_LABEL: &str = "heartbeat";
+
+const SYSTEM_PROMPT: &str = "You are running a scheduled heartbeat check-in without a user present. \
+     Look for things that need attention and handle what you can with the available tools. \
+     Only report what the user needs to know.";
+
+/// Run heartbeats until cancelled.
+pub async fn run_heartbeat_loop(
+    config: Config,
+    model_ctx: Option<Arc<ModelContext>>,
+    vault: SharedVault,
+    skill_mgr: SharedSkillManager,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let model_ctx = match model_ctx {
+        Some(ctx) => ctx,
+        None => {
+            warn!("No model context — heartbeat disabled");
+            return Ok(());
+        }
+    };
+
+    let hb: HeartbeatConfig = config.heartbeat.clone();
+    if let Some(Err(e)) = hb.active_hours.as_deref().map(heartbeat::parse_active_hours) {
+        warn!(error = %e, "Heartbeat active_hours is invalid — heartbeats will not run");
+    }
+    let permissions = config.tool_permissions.clone();
+    let workspace_dir = config.workspace_dir();
+    let interval = Duration::from_secs(hb.interval_minutes.max(1) * 60);
+    let http = reqwest::Client::new();
+    let mut budget = Budget::new(Local::now().date_naive());
+
+    info!(
+        interval_minutes = interval.as_secs() / 60,
+        active_hours = hb.active_hours.as_deref().unwrap_or("always"),
+        "Starting heartbeat"
+    );
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Shutting down heartbeat");
+                break;
+            }
+            _ = tokio::time::sleep(interval) => {
+                let now = Local::now();
+                if !heartbeat::within_active_hours(hb.active_hours.as_deref(), now.time()) {
+                    debug!("Outside heartbeat active hours, skipping");
+                    continue;
+                }
+                if let Err(reason) = budget.check(&hb, now.date_naive()) {
+                    debug!(reason = %reason, "Heartbeat budget exhausted, skipping");
+                    continue;
+                }
+
+                let snapshot_dir = workspace_dir.clone();
+                let _ = tokio::task::spawn_blocking(move || crate::snapshots::before_run(&snapshot_dir, "heartbeat")).await;
+
+                let prompt = heartbeat::build_prompt(&hb, &workspace_dir);
+                let session_key = heartbeat_session(&prompt);
+                let trace_id = turn_trace::new_trace_id();
+                let span = turn_trace::turn_span(&trace_id, "heartbeat");
+                let outcome = turn_trace::scope(
+                    trace_id.clone(),
+                    run_headless_turn(
+                        &http,
+                        &model_ctx,
+                        &vault,
+                        &skill_mgr,
+                        &permissions,
+                        &workspace_dir,
+                        SYSTEM_PROMPT,
+                        &prompt,
+                        hb.max_tool_rounds.max(1),
+                    ),
+                )
+                .instrument(span)
+                .await;
+                crate::tools::release_file_locks(&trace_id);
+
+                match outcome {
+                    Ok(turn) => {
+                        budget.record(turn.tokens);
+                        record_in_session(session_key.as_deref(), "assistant", &turn.text);
+                        if heartbeat::is_ok(&turn.text) {
+                            debug!(tokens = turn.tokens, "Heartbeat: nothing to report");
+                        } else {
+                            info!(tokens = turn.tokens, "Heartbeat has something to report");
+                            let body: String = turn.text.trim().chars().take(500).collect();
+                            notifications::post(
+                                Severity::Info,
+                                Source::Heartbeat,
+                                "Heartbeat",
+                                body,
+                                session_key.clone(),
+                            );
+                        }
+                        if budget.check(&hb, now.date_naive()).is_err() {
+                            info!(
+                                runs = budget.runs,
+                                tokens = budget.tokens,
+                                "Heartbeat budget for today used up"
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        // A failed run still counts, so a broken provider
+                        // can't retry all day.
+                        budget.record(0);
+                        warn!(error = %e, "Heartbeat failed");
+                        record_in_session(session_key.as_deref(), "system", &format!("Heartbeat failed: {}", e));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The `heartbeat` session, created on first use, with this run's prompt
+/// appended.
+fn heartbeat_session(prompt: &str) -> Option<String> {
+    let mut mgr = session_manager().lock().ok()?;
+    let key = match mgr.get_by_label(SESSION_LABEL) {
+        Some(session) => session.key.clone(),
+        None => mgr.spawn_subagent("heartbeat", "Periodic heartbeat check-ins", Some(SESSION_LABEL.to_string()), None),
+    };
+    if let Some(session) = mgr.get_mut(&key) {
+        session.add_message("user", prompt);
+    }
+    Some(key)
+}
+
+fn record_in_session(key: Option<&str>, role: &str, text: &str) {
+    let (Some(key), Ok(mut mgr)) = (key, session_manager().lock()) else {
+        return;
+    };
+    if let Some(session) = mgr.get_mut(key) {
+        session.add_message(role, text);
+        // Idle between runs; don't count against sub-agent concurrency.
+        session.complete();
+    }
+}
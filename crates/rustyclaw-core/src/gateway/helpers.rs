@@ -8,37 +8,9 @@ use super::protocol::types::ChatMessage;
 // ── Context window helpers ──────────────────────────────────────────────────
 
 /// Return the context-window size (in tokens) for a given model name.
-/// Conservative defaults — these are *input* token limits.
+/// These are *input* token limits, from [`crate::model_caps`].
 pub fn context_window_for_model(model: &str) -> usize {
-    let m = model.to_lowercase();
-    let window = if m.contains("claude-opus") {
-        200_000
-    } else if m.contains("claude-sonnet") {
-        200_000
-    } else if m.contains("claude-haiku") {
-        200_000
-    } else if m.starts_with("gpt-4.1") {
-        1_000_000
-    } else if m.starts_with("o3") || m.starts_with("o4") {
-        200_000
-    } else if m.contains("gemini-2.5-pro") {
-        1_000_000
-    } else if m.contains("gemini-2.5-flash") {
-        1_000_000
-    } else if m.contains("gemini-2.0-flash") {
-        1_000_000
-    } else if m.contains("grok-3") {
-        131_072
-    } else if m.contains("llama") {
-        128_000
-    } else if m.contains("mistral") {
-        128_000
-    } else if m.contains("deepseek") {
-        128_000
-    } else {
-        // Fallback: 128k is a safe default for modern models
-        128_000
-    };
+    let window = crate::model_caps::lookup(model).context_window;
     debug!(model, window, "Context window for model");
     window
 }
@@ -0,0 +1,284 @@
+//! Media transfer over the gateway WebSocket.
+//!
+//! Tools that produce files (TTS audio, screenshots, camera snaps, plots)
+//! end their result with `MEDIA: <path>`.  That path is only valid on the
+//! gateway host, so after such a result the gateway also streams the file
+//! to clients that negotiated `binary-frames`, as a run of `MediaChunk`
+//! frames.  Every chunk repeats the name, content type, size and SHA-256
+//! of the whole file so the client can start a transfer from any chunk;
+//! [`MediaAssembler`] puts them back together and checks the digest.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Payload bytes per chunk.
+pub const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Files larger than this stay on the gateway.
+pub const MAX_MEDIA_BYTES: u64 = 50 * 1024 * 1024;
+
+/// One piece of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaChunk {
+    /// Identifies the transfer; unique per gateway process.
+    pub transfer_id: u64,
+    /// File name, without directories.
+    pub name: String,
+    pub content_type: String,
+    /// Size of the whole file in bytes.
+    pub size: u64,
+    /// Hex SHA-256 of the whole file.
+    pub sha256: String,
+    pub index: u32,
+    pub count: u32,
+    pub data: Vec<u8>,
+}
+
+/// A file received in full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedMedia {
+    pub name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Paths named by `MEDIA:` lines in a tool result.
+pub fn media_paths(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("MEDIA:"))
+        .map(|path| PathBuf::from(path.trim()))
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect()
+}
+
+/// MIME type from the file extension.
+pub fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" | "opus" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "csv" => "text/csv",
+        "txt" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn next_transfer_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Split `data` into chunks for a file called `name`.
+pub fn chunk_bytes(name: &str, content_type: &str, data: &[u8]) -> Vec<MediaChunk> {
+    let transfer_id = next_transfer_id();
+    let sha256 = sha256_hex(data);
+    // An empty file is still one (empty) chunk.
+    let pieces: Vec<&[u8]> = if data.is_empty() {
+        vec![&[]]
+    } else {
+        data.chunks(CHUNK_SIZE).collect()
+    };
+    let count = pieces.len() as u32;
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(index, piece)| MediaChunk {
+            transfer_id,
+            name: name.to_string(),
+            content_type: content_type.to_string(),
+            size: data.len() as u64,
+            sha256: sha256.clone(),
+            index: index as u32,
+            count,
+            data: piece.to_vec(),
+        })
+        .collect()
+}
+
+/// Read `path` and split it into chunks.
+pub fn chunk_file(path: &Path) -> Result<Vec<MediaChunk>, String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if meta.len() > MAX_MEDIA_BYTES {
+        return Err(format!(
+            "{} is too large to send ({} bytes, limit {})",
+            path.display(),
+            meta.len(),
+            MAX_MEDIA_BYTES
+        ));
+    }
+    let data = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "media".to_string());
+    Ok(chunk_bytes(&name, content_type(path), &data))
+}
+
+/// A transfer still missing chunks.
+struct Partial {
+    name: String,
+    content_type: String,
+    size: u64,
+    sha256: String,
+    chunks: Vec<Option<Vec<u8>>>,
+    received: u32,
+}
+
+/// Client-side reassembly of chunked transfers.
+#[derive(Default)]
+pub struct MediaAssembler {
+    partial: HashMap<u64, Partial>,
+}
+
+impl MediaAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk.  Returns the file once its last chunk arrived, or an
+    /// error if the chunks don't add up to the announced size and digest.
+    pub fn push(&mut self, chunk: MediaChunk) -> Option<Result<ReceivedMedia, String>> {
+        if chunk.count == 0 || chunk.index >= chunk.count {
+            return Some(Err(format!(
+                "Bad media chunk {}/{} for {}",
+                chunk.index, chunk.count, chunk.name
+            )));
+        }
+        let partial = self.partial.entry(chunk.transfer_id).or_insert_with(|| Partial {
+            name: chunk.name.clone(),
+            content_type: chunk.content_type.clone(),
+            size: chunk.size,
+            sha256: chunk.sha256.clone(),
+            chunks: vec![None; chunk.count as usize],
+            received: 0,
+        });
+        let slot = partial.chunks.get_mut(chunk.index as usize)?;
+        if slot.is_none() {
+            *slot = Some(chunk.data);
+            partial.received += 1;
+        }
+        if (partial.received as usize) < partial.chunks.len() {
+            return None;
+        }
+
+        let partial = self.partial.remove(&chunk.transfer_id)?;
+        let data: Vec<u8> = partial.chunks.into_iter().flatten().flatten().collect();
+        if data.len() as u64 != partial.size {
+            return Some(Err(format!(
+                "{}: received {} of {} bytes",
+                partial.name,
+                data.len(),
+                partial.size
+            )));
+        }
+        if sha256_hex(&data) != partial.sha256 {
+            return Some(Err(format!("{}: checksum mismatch", partial.name)));
+        }
+        Some(Ok(ReceivedMedia {
+            name: partial.name,
+            content_type: partial.content_type,
+            data,
+        }))
+    }
+}
+
+/// Save received media in `dir` without overwriting earlier files of the
+/// same name (`shot.png`, `shot-1.png`, …).
+pub fn save(dir: &Path, media: &ReceivedMedia) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    // Never trust a name from the wire to stay inside `dir`.
+    let name = Path::new(&media.name)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "media".to_string());
+    let stem = Path::new(&name).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = Path::new(&name).extension().map(|e| format!(".{}", e.to_string_lossy()));
+    let mut path = dir.join(&name);
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}{}", stem, n, ext.as_deref().unwrap_or("")));
+        n += 1;
+    }
+    std::fs::write(&path, &media.data).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_paths() {
+        let output = "TTS conversion complete:\n- Voice: alloy\n\nMEDIA: /tmp/.tts/speech_1.mp3";
+        assert_eq!(media_paths(output), vec![PathBuf::from("/tmp/.tts/speech_1.mp3")]);
+        assert!(media_paths("no media here").is_empty());
+        assert_eq!(content_type(Path::new("speech_1.MP3")), "audio/mpeg");
+    }
+
+    #[test]
+    fn test_chunks_reassemble_in_any_order() {
+        let data: Vec<u8> = (0..(CHUNK_SIZE * 2 + 10)).map(|i| (i % 251) as u8).collect();
+        let mut chunks = chunk_bytes("shot.png", "image/png", &data);
+        assert_eq!(chunks.len(), 3);
+        chunks.reverse();
+
+        let mut assembler = MediaAssembler::new();
+        let mut done = None;
+        for chunk in chunks {
+            if let Some(result) = assembler.push(chunk) {
+                done = Some(result.unwrap());
+            }
+        }
+        let media = done.expect("transfer completes");
+        assert_eq!(media.name, "shot.png");
+        assert_eq!(media.content_type, "image/png");
+        assert_eq!(media.data, data);
+    }
+
+    #[test]
+    fn test_corrupt_transfer_is_rejected() {
+        let mut chunks = chunk_bytes("a.bin", "application/octet-stream", b"hello");
+        chunks[0].data[0] ^= 0xff;
+        let mut assembler = MediaAssembler::new();
+        let result = assembler.push(chunks.remove(0)).unwrap();
+        assert!(result.unwrap_err().contains("checksum"));
+    }
+
+    #[test]
+    fn test_save_keeps_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let media = ReceivedMedia {
+            name: "../../shot.png".into(),
+            content_type: "image/png".into(),
+            data: vec![1, 2, 3],
+        };
+        let first = save(dir.path(), &media).unwrap();
+        let second = save(dir.path(), &media).unwrap();
+        assert_eq!(first, dir.path().join("shot.png"));
+        assert_eq!(second, dir.path().join("shot-1.png"));
+    }
+}
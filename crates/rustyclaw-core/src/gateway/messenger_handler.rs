@@ -5,23 +5,33 @@
 //! them through the model for processing with full tool loop support.
 
 use crate::config::{Config, MessengerConfig};
+use crate::conversations;
+use crate::heartbeat;
+use crate::identity;
+use crate::users;
+use crate::notifications::{self, Severity, Source};
+use crate::observability::trace as turn_trace;
 use crate::messengers::{
-    DiscordMessenger, MediaAttachment, Message, Messenger, MessengerManager, SendOptions,
-    TelegramMessenger, WebhookMessenger,
+    Choice, DiscordMessenger, MediaAttachment, Message, MessageEvent, Messenger,
+    MessengerManager, SendOptions, TelegramMessenger, WebhookMessenger,
 };
 use crate::tools;
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex, Semaphore};
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, trace, warn, Instrument};
 
 use super::providers;
 use super::secrets_handler;
 use super::skills_handler;
+use super::stats;
+use super::stream;
 use super::{ChatMessage, MediaRef, ModelContext, ProviderRequest, SharedSkillManager, SharedVault, ToolCallResult};
 
 #[cfg(feature = "matrix")]
@@ -33,9 +43,47 @@ use crate::messengers::SignalMessenger;
 /// Shared messenger manager for the gateway.
 pub type SharedMessengerManager = Arc<Mutex<MessengerManager>>;
 
-/// Conversation history storage per chat.
-/// Key: "messenger_type:chat_id" or "messenger_type:sender_id"
-type ConversationStore = Arc<Mutex<HashMap<String, Vec<ChatMessage>>>>;
+/// Persistent conversation history, one file per messenger chat (chat id,
+/// or sender id for direct messages).  The mutex serializes writers.
+type ConversationStore = Arc<Mutex<conversations::ConversationStore>>;
+
+/// What a prompt sent to a chat is waiting for.
+enum PendingKind {
+    /// A gated tool call waiting for the user's go-ahead.
+    Approval { tool: String, args: Value },
+    /// An `ask_user` question with the choices offered.
+    Question { title: String, choices: Vec<Choice> },
+}
+
+/// A prompt sent to a chat that a reaction, button press or typed reply
+/// can answer.
+struct PendingPrompt {
+    kind: PendingKind,
+    /// The prompt message, if the messenger returned its id.
+    prompt_id: Option<String>,
+    requested: Instant,
+    /// Sender whose message led to the prompt, and their role.
+    requester: String,
+    requester_role: users::Role,
+}
+
+impl PendingPrompt {
+    /// Only the person who asked, or an owner, may answer — not whoever
+    /// else is in the chat.
+    fn may_answer(&self, sender: &str, role: users::Role) -> bool {
+        role == users::Role::Owner || sender == self.requester
+    }
+}
+
+/// Pending prompts, at most one per chat (`messenger:chat`).
+type PendingPrompts = Arc<Mutex<HashMap<String, PendingPrompt>>>;
+
+/// How long a prompt can still be answered.
+const PROMPT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Button data for approving or rejecting a tool call.
+const APPROVE: &str = "approve";
+const REJECT: &str = "reject";
 
 /// Maximum messages to keep in conversation history per chat.
 const MAX_HISTORY_MESSAGES: usize = 50;
@@ -43,6 +91,12 @@ const MAX_HISTORY_MESSAGES: usize = 50;
 /// Maximum tool loop rounds.
 const MAX_TOOL_ROUNDS: usize = 25;
 
+/// Turns running at once across all chats.
+const MAX_CONCURRENT_TURNS: usize = 4;
+
+/// A chat's worker exits after this long without messages.
+const CHAT_IDLE: Duration = Duration::from_secs(60);
+
 /// Maximum image size to download (10 MB).
 const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
 
@@ -77,6 +131,13 @@ pub async fn create_messenger_manager(config: &Config) -> Result<MessengerManage
                     error = %e,
                     "Failed to initialize messenger"
                 );
+                notifications::post(
+                    Severity::Error,
+                    Source::Messenger,
+                    format!("{} failed to start", messenger_config.messenger_type),
+                    e.to_string(),
+                    None,
+                );
             }
         }
     }
@@ -184,13 +245,35 @@ pub async fn run_messenger_loop(
             .max(500) as u64,
     );
 
-    // Per-chat conversation history
-    let conversations: ConversationStore = Arc::new(Mutex::new(HashMap::new()));
+    // Per-chat conversation history, persisted in the workspace
+    let conversations: ConversationStore = Arc::new(Mutex::new(
+        conversations::ConversationStore::new(&conversations::conversations_dir(&config.workspace_dir()))
+            .map_err(|e| anyhow::anyhow!(e))?,
+    ));
 
-    let http = reqwest::Client::new();
+    let prompts: PendingPrompts = Arc::new(Mutex::new(HashMap::new()));
+
+    let debounce = Duration::from_millis(config.messenger_debounce_ms.unwrap_or(0) as u64);
+
+    let ctx = TurnContext {
+        http: reqwest::Client::new(),
+        debounce,
+        config: Arc::new(config),
+        messenger_mgr,
+        model_ctx,
+        vault,
+        skill_mgr,
+        conversations,
+        prompts,
+        permits: Arc::new(Semaphore::new(MAX_CONCURRENT_TURNS)),
+        queued: Arc::new(AtomicUsize::new(0)),
+    };
+    let queues = ChatQueues::default();
 
     info!(
         poll_interval_ms = poll_interval.as_millis(),
+        max_concurrent = MAX_CONCURRENT_TURNS,
+        debounce_ms = debounce.as_millis(),
         "Starting messenger loop"
     );
 
@@ -203,27 +286,13 @@ pub async fn run_messenger_loop(
             _ = tokio::time::sleep(poll_interval) => {
                 // Poll all messengers for incoming messages
                 let messages = {
-                    let mgr = messenger_mgr.lock().await;
+                    let mgr = ctx.messenger_mgr.lock().await;
                     poll_all_messengers(&mgr).await
                 };
 
-                // Process each message
+                // Hand each message to its chat's worker
                 for (messenger_type, msg) in messages {
-                    if let Err(e) = process_incoming_message(
-                        &http,
-                        &config,
-                        &messenger_mgr,
-                        &model_ctx,
-                        &vault,
-                        &skill_mgr,
-                        &conversations,
-                        &messenger_type,
-                        msg,
-                    )
-                    .await
-                    {
-                        error!(error = %e, "Error processing message");
-                    }
+                    dispatch(&queues, &ctx, messenger_type, msg);
                 }
             }
         }
@@ -232,6 +301,218 @@ pub async fn run_messenger_loop(
     Ok(())
 }
 
+/// Everything a messenger turn needs, shared by the chat workers.
+#[derive(Clone)]
+struct TurnContext {
+    http: reqwest::Client,
+    /// Window for coalescing consecutive messages; zero turns it off.
+    debounce: Duration,
+    config: Arc<Config>,
+    messenger_mgr: SharedMessengerManager,
+    model_ctx: Arc<ModelContext>,
+    vault: SharedVault,
+    skill_mgr: SharedSkillManager,
+    conversations: ConversationStore,
+    prompts: PendingPrompts,
+    /// Bounds the turns running at once.
+    permits: Arc<Semaphore>,
+    /// Messages received but not started yet.
+    queued: Arc<AtomicUsize>,
+}
+
+/// One queue per chat (`messenger:chat`), drained in order by that chat's
+/// worker task.  Different chats run concurrently, so a slow tool loop in
+/// one doesn't hold up the others.  A worker removes its own entry when it
+/// goes idle, under the lock, so a chat never has two workers at once.
+type ChatQueues = Arc<std::sync::Mutex<HashMap<String, mpsc::UnboundedSender<(String, Message)>>>>;
+
+/// The chat a message belongs to: its channel, or the sender for DMs.
+fn chat_key(messenger_type: &str, msg: &Message) -> String {
+    format!("{}:{}", messenger_type, msg.channel.as_deref().unwrap_or(&msg.sender))
+}
+
+/// Queue a message behind earlier ones from the same chat, starting a
+/// worker for the chat if it has none.
+fn dispatch(queues: &ChatQueues, ctx: &TurnContext, messenger_type: String, msg: Message) {
+    let queued = ctx.queued.fetch_add(1, Ordering::SeqCst) + 1;
+    stats::set_messenger_queue(queued);
+
+    let key = chat_key(&messenger_type, &msg);
+    if let Some(rx) = enqueue(queues, &key, (messenger_type, msg)) {
+        let ctx = ctx.clone();
+        let debounce = ctx.debounce;
+        let run = move |messenger_type: String, msg: Message, count: usize| {
+            let ctx = ctx.clone();
+            async move { run_turn(&ctx, &messenger_type, msg, count).await }
+        };
+        tokio::spawn(chat_worker(queues.clone(), key, rx, CHAT_IDLE, debounce, run));
+    }
+}
+
+/// Add a message to its chat's queue.  Returns the receiver for a new
+/// worker when the chat has none.
+fn enqueue(
+    queues: &ChatQueues,
+    key: &str,
+    item: (String, Message),
+) -> Option<mpsc::UnboundedReceiver<(String, Message)>> {
+    let mut queues = queues.lock().unwrap_or_else(|e| e.into_inner());
+    let item = match queues.get(key) {
+        Some(tx) => match tx.send(item) {
+            Ok(()) => return None,
+            // The worker died without cleaning up.
+            Err(mpsc::error::SendError(returned)) => returned,
+        },
+        None => item,
+    };
+    let (tx, rx) = mpsc::unbounded_channel();
+    let _ = tx.send(item);
+    queues.insert(key.to_string(), tx);
+    Some(rx)
+}
+
+/// Handle one chat's messages in order, passing each (coalesced) turn to
+/// `run`, until nothing arrives for `idle`.
+async fn chat_worker<F, Fut>(
+    queues: ChatQueues,
+    key: String,
+    mut rx: mpsc::UnboundedReceiver<(String, Message)>,
+    idle: Duration,
+    debounce: Duration,
+    mut run: F,
+) where
+    F: FnMut(String, Message, usize) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    // A message that ended the previous batch without joining it.
+    let mut held: Option<(String, Message)> = None;
+    loop {
+        let first = match held.take() {
+            Some(item) => item,
+            None => match tokio::time::timeout(idle, rx.recv()).await {
+                Ok(Some(item)) => item,
+                Ok(None) => break,
+                // Idle: leave the map while holding its lock, so the next
+                // message either lands here first or starts a new worker
+                // after this one is gone — never both.
+                Err(_) => {
+                    let mut queues = queues.lock().unwrap_or_else(|e| e.into_inner());
+                    match rx.try_recv() {
+                        Ok(item) => item,
+                        Err(_) => {
+                            queues.remove(&key);
+                            break;
+                        }
+                    }
+                }
+            },
+        };
+        let (messenger_type, batch, next) = collect_batch(&mut rx, first, debounce).await;
+        held = next;
+        let count = batch.len();
+        run(messenger_type, coalesce(batch), count).await;
+    }
+}
+
+/// Whether a message can be folded into a batch: plain new text, not a
+/// command, edit, reaction or button press.
+fn batchable(msg: &Message) -> bool {
+    msg.event == MessageEvent::New && !msg.content.trim_start().starts_with('/')
+}
+
+/// Starting from `first`, keep taking messages from the same sender that
+/// arrive within `window` of the previous one.  Returns the batch and the
+/// message that ended it, if one did.
+async fn collect_batch(
+    rx: &mut mpsc::UnboundedReceiver<(String, Message)>,
+    first: (String, Message),
+    window: Duration,
+) -> (String, Vec<Message>, Option<(String, Message)>) {
+    let (messenger_type, first) = first;
+    if window.is_zero() || !batchable(&first) {
+        return (messenger_type, vec![first], None);
+    }
+    let mut batch = vec![first];
+    while let Ok(Some((next_type, next))) = tokio::time::timeout(window, rx.recv()).await {
+        if !batchable(&next) || next.sender != batch[0].sender {
+            return (messenger_type, batch, Some((next_type, next)));
+        }
+        batch.push(next);
+    }
+    (messenger_type, batch, None)
+}
+
+/// Fold a batch into one message: the texts one per line and all the
+/// attachments.  It takes the id and time of the last message, which is
+/// the one a reply should follow.
+fn coalesce(mut batch: Vec<Message>) -> Message {
+    let mut merged = batch.pop().expect("batches are never empty");
+    if batch.is_empty() {
+        return merged;
+    }
+    debug!(messages = batch.len() + 1, sender = %merged.sender, "Coalescing consecutive messages");
+    let mut content: Vec<String> = batch.iter().map(|m| m.content.clone()).collect();
+    content.push(std::mem::take(&mut merged.content));
+    merged.content = content.into_iter().filter(|c| !c.trim().is_empty()).collect::<Vec<_>>().join("\n");
+
+    let mut media: Vec<MediaAttachment> = batch.iter_mut().filter_map(|m| m.media.take()).flatten().collect();
+    media.extend(merged.media.take().unwrap_or_default());
+    merged.media = (!media.is_empty()).then_some(media);
+    if merged.reply_to.is_none() {
+        merged.reply_to = batch.into_iter().find_map(|m| m.reply_to);
+    }
+    merged
+}
+
+/// Process one prompt (`count` coalesced messages) once a turn slot is free.
+async fn run_turn(ctx: &TurnContext, messenger_type: &str, msg: Message, count: usize) {
+    let Ok(_permit) = ctx.permits.acquire().await else {
+        return;
+    };
+    let queued = ctx.queued.fetch_sub(count, Ordering::SeqCst).saturating_sub(count);
+    stats::set_messenger_queue(queued);
+
+    // Each message is one traced turn: its trace ID tags every
+    // log line, provider call and tool execution it causes.
+    let trace_id = turn_trace::new_trace_id();
+    let span = turn_trace::turn_span(&trace_id, messenger_type);
+    let started = Instant::now();
+    // Sub-agents this turn spawns get a fresh tree budget.
+    if let Ok(mut mgr) = crate::sessions::session_manager().lock() {
+        mgr.reset_tree(crate::sessions::MAIN_SESSION);
+    }
+    let conversation = format!("chat:{}", chat_key(messenger_type, &msg));
+    let turn = process_incoming_message(
+        &ctx.http,
+        &ctx.config,
+        &ctx.messenger_mgr,
+        &ctx.model_ctx,
+        &ctx.vault,
+        &ctx.skill_mgr,
+        &ctx.conversations,
+        &ctx.prompts,
+        messenger_type,
+        msg,
+    );
+    let turn = crate::sessions::conversation_scope(conversation, turn);
+    let result = turn_trace::scope(trace_id.clone(), turn).instrument(span.clone()).await;
+    crate::tools::release_file_locks(&trace_id);
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(()) => info!(parent: &span, elapsed_ms, "Messenger turn finished"),
+        Err(e) => {
+            error!(parent: &span, elapsed_ms, error = %e, "Error processing message");
+            notifications::post(
+                Severity::Error,
+                Source::Messenger,
+                format!("{} message failed", messenger_type),
+                e.to_string(),
+                None,
+            );
+        }
+    }
+}
+
 /// Poll all messengers and collect incoming messages.
 async fn poll_all_messengers(mgr: &MessengerManager) -> Vec<(String, Message)> {
     let mut all_messages = Vec::new();
@@ -265,8 +546,9 @@ async fn process_incoming_message(
     vault: &SharedVault,
     skill_mgr: &SharedSkillManager,
     conversations: &ConversationStore,
+    prompts: &PendingPrompts,
     messenger_type: &str,
-    msg: Message,
+    mut msg: Message,
 ) -> Result<()> {
     debug!(
         sender = %msg.sender,
@@ -281,21 +563,176 @@ async fn process_incoming_message(
 
     let workspace_dir = config.workspace_dir();
 
-    // Build conversation key for this chat
-    let conv_key = format!(
-        "{}:{}",
-        messenger_type,
-        msg.channel.as_deref().unwrap_or(&msg.sender)
+    // Conversations are namespaced by messenger, then chat
+    let chat_id = msg.channel.as_deref().unwrap_or(&msg.sender).to_string();
+
+    // ── Identity linking: ids in the contact book belong to one person ──
+    let person = {
+        let (messenger, sender) = (messenger_type.to_string(), msg.sender.clone());
+        tokio::task::spawn_blocking(move || identity::resolve(&messenger, &sender))
+            .await
+            .ok()
+            .flatten()
+    };
+    let user = users::resolve(&config.users, person.as_ref(), messenger_type, &msg.sender);
+
+    // A linked person's direct chats share one history across messengers.
+    let (history_ns, history_chat) = match (&person, &msg.channel) {
+        (Some(person), None) => (identity::PERSON_NAMESPACE, person.key.clone()),
+        _ => (messenger_type, chat_id.clone()),
+    };
+
+    if let Some(limit) = config.messenger_rate_limit.filter(|n| *n > 0) {
+        if matches!(msg.event, MessageEvent::New) {
+            let key = identity::rate_key(person.as_ref(), messenger_type, &msg.sender);
+            if let Err(wait) = identity::check_rate(&key, limit) {
+                info!(sender = %key, retry_in_secs = wait.as_secs(), "Rate limit reached, ignoring message");
+                return Ok(());
+            }
+        }
+    }
+
+    // ── Edits and reactions ──
+    match msg.event.clone() {
+        MessageEvent::New => {}
+        MessageEvent::Edited => {
+            let store = conversations.lock().await;
+            if let Ok(Some(mut conv)) = store.load(history_ns, &history_chat) {
+                if conv.apply_edit(&msg.id, &msg.content) {
+                    debug!(message_id = %msg.id, "Applied edit to conversation history");
+                    if let Err(e) = store.save(&mut conv) {
+                        warn!(error = %e, "Failed to persist edited conversation");
+                    }
+                }
+            }
+            return Ok(());
+        }
+        MessageEvent::Reaction { emoji, removed } => {
+            let Some(approved) = approval_from_reaction(&emoji).filter(|_| !removed) else {
+                debug!(emoji = %emoji, removed, "Ignoring reaction");
+                return Ok(());
+            };
+            let key = format!("{}:{}", messenger_type, chat_id);
+            let is_approval = |p: &PendingPrompt| matches!(p.kind, PendingKind::Approval { .. });
+            let Some(PendingPrompt { kind: PendingKind::Approval { tool, args }, .. }) =
+                take_prompt(prompts, &key, &msg, &user, msg.reply_to.as_deref(), is_approval).await
+            else {
+                return Ok(());
+            };
+            let outcome = ApprovalOutcome { approved, tool: &tool, args: &args };
+            if !resolve_approval(messenger_mgr, vault, skill_mgr, &workspace_dir, messenger_type, &mut msg, &user, outcome).await {
+                return Ok(());
+            }
+        }
+        MessageEvent::Button { data } => {
+            let key = format!("{}:{}", messenger_type, chat_id);
+            let Some(pending) = take_prompt(prompts, &key, &msg, &user, msg.reply_to.as_deref(), |_| true).await else {
+                debug!(data = %data, "Button press for no pending prompt");
+                return Ok(());
+            };
+            match pending.kind {
+                PendingKind::Approval { tool, args } => {
+                    let outcome = ApprovalOutcome { approved: data == APPROVE, tool: &tool, args: &args };
+                    if !resolve_approval(messenger_mgr, vault, skill_mgr, &workspace_dir, messenger_type, &mut msg, &user, outcome).await {
+                        return Ok(());
+                    }
+                }
+                PendingKind::Question { title, choices } => {
+                    let Some(choice) = choices.iter().find(|c| c.data == data) else {
+                        return Ok(());
+                    };
+                    msg.content = format!("Answer to \"{}\": {}", title, choice.label);
+                    msg.event = MessageEvent::New;
+                }
+            }
+        }
+    }
+
+    // A typed reply to an open question: "2" or an option's label picks it.
+    {
+        let key = format!("{}:{}", messenger_type, chat_id);
+        let is_question = |p: &PendingPrompt| matches!(p.kind, PendingKind::Question { .. });
+        if let Some(PendingPrompt { kind: PendingKind::Question { title, choices }, .. }) =
+            take_prompt(prompts, &key, &msg, &user, None, is_question).await
+        {
+            if let Some(choice) = typed_choice(&msg.content, &choices) {
+                msg.content = format!("Answer to \"{}\": {}", title, choice.label);
+            }
+        }
+    }
+
+    crate::scripting::spawn_hooks(
+        "message",
+        crate::scripting::message_event(messenger_type, &msg.sender, &msg.content),
+        workspace_dir.clone(),
     );
 
+    // ── Reminder follow-ups: "snooze 1h" / "done" after a delivered reminder ──
+    if let Some(reply) = handle_reminder_reply(config, messenger_type, &msg) {
+        send_reply(messenger_mgr, messenger_type, &msg, &reply).await;
+        return Ok(());
+    }
+
+    // ── /task command: queue background work instead of running it now ──
+    if let Some(rest) = msg.content.strip_prefix("/task ") {
+        // Background tasks run with the owner's tools.
+        let queued = if user.role == users::Role::Owner {
+            enqueue_task(config, rest.trim(), messenger_type)
+        } else {
+            Err("only the owner can queue tasks".to_string())
+        };
+        let reply = match queued {
+            Ok(id) => format!("Queued task {}", id),
+            Err(e) => format!("Could not queue task: {}", e),
+        };
+        send_reply(messenger_mgr, messenger_type, &msg, &reply).await;
+        return Ok(());
+    }
+
+    // ── Per-chat auto-translation: the agent works in default_target ──
+    let target = config.translation.default_target.clone();
+    let translation = config
+        .translation
+        .chat_rule(messenger_type, msg.channel.as_deref().unwrap_or(&msg.sender))
+        .cloned();
+    let mut chat_language = translation.as_ref().and_then(|t| t.language.clone());
+    let mut content = msg.content.clone();
+    if translation.as_ref().is_some_and(|t| t.incoming) {
+        match translate_text(msg.content.clone(), chat_language.clone(), target.clone()).await {
+            Ok(t) => {
+                debug!(source = ?t.source, provider = t.provider, "Translated incoming message");
+                content = t.text;
+                chat_language = chat_language.or(t.source);
+            }
+            Err(e) => warn!(error = %e, "Failed to translate incoming message"),
+        }
+    }
+    let chat_language = chat_language.filter(|l| !crate::translate::same_language(l, &target));
+
     // Get or create conversation history
     let mut messages = {
-        let mut store = conversations.lock().await;
-        store.entry(conv_key.clone()).or_insert_with(Vec::new).clone()
+        let store = conversations.lock().await;
+        match store.load(history_ns, &history_chat) {
+            Ok(conv) => conv.map(|c| c.messages).unwrap_or_default(),
+            Err(e) => {
+                warn!(error = %e, "Failed to load conversation; starting fresh");
+                Vec::new()
+            }
+        }
     };
 
     // Build system prompt
-    let system_prompt = build_messenger_system_prompt(config, messenger_type, &msg);
+    let mut system_prompt = build_messenger_system_prompt(config, messenger_type, &msg, person.as_ref(), &user);
+    if let (Some(lang), Some(rule)) = (&chat_language, &translation) {
+        system_prompt.push_str(&format!(
+            "\n\n## Translation\n\
+            This chat is in '{}'.{} Reply in '{}'{}.",
+            lang,
+            if rule.incoming { format!(" Messages are machine-translated into '{}' for you.", target) } else { String::new() },
+            target,
+            if rule.outgoing { "; replies are translated back automatically" } else { "" }
+        ));
+    }
 
     // Add system message if not present
     if messages.is_empty() || messages[0].role != "system" {
@@ -318,12 +755,15 @@ async fn process_incoming_message(
     if !images.is_empty() {
         debug!(image_count = images.len(), "Processing images (vision not yet supported in messenger handler)");
     }
+    // Tell the sender when the model can't read their images.
+    let vision_notice = (!images.is_empty() && !crate::model_caps::lookup(&model_ctx.model).vision)
+        .then(|| crate::model_caps::no_vision_notice(&model_ctx.model, images.len()));
 
     // Build media refs for history storage
     let media_refs: Vec<MediaRef> = images.iter().map(|img| img.media_ref.clone()).collect();
 
     // Add user message to history (with media refs, not raw data)
-    messages.push(ChatMessage::user_with_media(&msg.content, media_refs.clone()));
+    messages.push(ChatMessage::user_with_media(&content, media_refs.clone()));
 
     // Build request - ProviderRequest expects Vec<ChatMessage>
     let mut resolved = ProviderRequest {
@@ -332,19 +772,42 @@ async fn process_incoming_message(
         base_url: model_ctx.base_url.clone(),
         api_key: model_ctx.api_key.clone(),
         messages: messages.clone(),
+        tools: None,
     };
+    resolved.tools = providers::route_tools(http, &resolved).await;
 
     // Run the agentic tool loop
     let mut final_response = String::new();
+    let mut deadline = tools::TurnDeadline::start();
+    let mut loop_guard = crate::loop_guard::LoopGuard::new();
 
     for _round in 0..MAX_TOOL_ROUNDS {
-        let result = if resolved.provider == "anthropic" {
-            providers::call_anthropic_with_tools(http, &resolved, None).await
-        } else if resolved.provider == "google" {
-            providers::call_google_with_tools(http, &resolved).await
+        if !deadline.allow_round() {
+            warn!("Turn time limit reached");
+            if final_response.is_empty() {
+                final_response = "Sorry, I ran out of time on this one.".to_string();
+            }
+            break;
+        }
+        // Calls that can run unattended start as soon as their arguments
+        // are complete.  Not after a loop warning: the turn may yet be
+        // aborted.
+        let mut early = if loop_guard.warned() {
+            stream::EarlyTools::disabled()
         } else {
-            providers::call_openai_with_tools(http, &resolved).await
+            stream::EarlyTools::new(&workspace_dir, &deadline)
         };
+        let call_started = Instant::now();
+        let result = stream::call(http, &resolved, None, |tc| {
+            let allowed = tc.name != "ask_user"
+                && users::tool_denial(&user, &tc.name, &tc.arguments).is_none()
+                && users::scoped_args(&user, &tc.name, &tc.arguments).is_none()
+                && tools::permission_for(&config.tool_permissions, &tc.name, &tc.arguments)
+                    == tools::ToolPermission::Allow;
+            early.offer(tc, allowed);
+        })
+        .await;
+        stats::record_provider_call(&resolved.provider, call_started.elapsed());
 
         let model_resp = match result {
             Ok(r) => r,
@@ -364,27 +827,52 @@ async fn process_incoming_message(
             break;
         }
 
+        let round = model_resp.tool_calls.iter().map(|tc| (tc.name.as_str(), &tc.arguments));
+        let loop_note = match loop_guard.check(round) {
+            crate::loop_guard::Verdict::Ok => None,
+            crate::loop_guard::Verdict::Warn(note) => Some(note),
+            crate::loop_guard::Verdict::Abort(message) => {
+                warn!(error = %message, "Aborting turn");
+                if final_response.is_empty() {
+                    final_response = message;
+                }
+                break;
+            }
+        };
+
         // Execute each requested tool
         let mut tool_results: Vec<ToolCallResult> = Vec::new();
+        let mut pending = stats::PendingTools::new(model_resp.tool_calls.len());
 
         for tc in &model_resp.tool_calls {
             debug!(tool_name = %tc.name, tool_id = %tc.id, "Executing tool call");
 
-            let (output, is_error) = if tools::is_secrets_tool(&tc.name) {
-                match secrets_handler::execute_secrets_tool(&tc.name, &tc.arguments, vault).await {
-                    Ok(text) => (text, false),
-                    Err(err) => (err, true),
-                }
-            } else if tools::is_skill_tool(&tc.name) {
-                match skills_handler::execute_skill_tool(&tc.name, &tc.arguments, skill_mgr).await {
+            let (output, is_error) = if let Some(denial) = users::tool_denial(&user, &tc.name, &tc.arguments) {
+                (denial, true)
+            } else if tc.name == "ask_user" {
+                ask_in_chat(messenger_mgr, prompts, messenger_type, &msg, &user, &tc.arguments).await
+            } else if let Some(denial) =
+                tools::unattended_denial(&config.tool_permissions, &tc.name, &tc.arguments)
+            {
+                // Ask-gated calls can be approved from the chat (button or 👍).
+                let askable = tools::permission_for(&config.tool_permissions, &tc.name, &tc.arguments)
+                    == tools::ToolPermission::Ask;
+                let note = if askable {
+                    request_approval(messenger_mgr, prompts, messenger_type, &msg, &user, &tc.name, &tc.arguments)
+                        .await
+                } else {
+                    None
+                };
+                (note.unwrap_or(denial), true)
+            } else if let Some(run) = early.take(&tc.id) {
+                match run.await {
                     Ok(text) => (text, false),
                     Err(err) => (err, true),
                 }
             } else {
-                match tools::execute_tool(&tc.name, &tc.arguments, &workspace_dir) {
-                    Ok(text) => (text, false),
-                    Err(err) => (err, true),
-                }
+                let scoped = users::scoped_args(&user, &tc.name, &tc.arguments);
+                let args = scoped.as_ref().unwrap_or(&tc.arguments);
+                run_tool(&user, &tc.name, args, vault, skill_mgr, &workspace_dir, &deadline).await
             };
 
             trace!(
@@ -397,6 +885,7 @@ async fn process_incoming_message(
                 },
                 "Tool result"
             );
+            pending.done();
 
             tool_results.push(ToolCallResult {
                 id: tc.id.clone(),
@@ -413,15 +902,24 @@ async fn process_incoming_message(
             &model_resp,
             &tool_results,
         );
+        if let Some(note) = loop_note {
+            resolved.messages.push(ChatMessage::text("system", &note));
+        }
+        for tc in model_resp.tool_calls.iter().filter(|tc| tc.name == "request_tools") {
+            tools::load_requested(&mut resolved.tools, &tc.arguments);
+        }
     }
 
     // Update conversation history
     {
-        let mut store = conversations.lock().await;
-        let history = store.entry(conv_key).or_insert_with(Vec::new);
+        let store = conversations.lock().await;
+        let mut conv = store
+            .load_or_new(history_ns, &history_chat)
+            .unwrap_or_else(|_| conversations::Conversation::new(history_ns, &history_chat));
+        let history = &mut conv.messages;
 
         // Add user message (with media refs)
-        history.push(ChatMessage::user_with_media(&msg.content, media_refs.clone()));
+        history.push(ChatMessage::user_with_media(&content, media_refs.clone()));
 
         // Add assistant response
         if !final_response.is_empty() {
@@ -436,13 +934,33 @@ async fn process_incoming_message(
                 break;
             }
         }
+
+        // Let a later edit of this message on the platform update it here.
+        conv.note_source(&msg.id, &content);
+
+        if let Err(e) = store.save(&mut conv) {
+            warn!(error = %e, "Failed to persist conversation");
+        }
     }
 
     // Send response back via messenger
     if !final_response.is_empty()
         && final_response.trim() != "NO_REPLY"
-        && final_response.trim() != "HEARTBEAT_OK"
+        && !heartbeat::is_ok(&final_response)
     {
+        if let Some(lang) = chat_language.filter(|_| translation.as_ref().is_some_and(|t| t.outgoing)) {
+            match translate_text(final_response.clone(), Some(target.clone()), lang).await {
+                Ok(t) => final_response = t.text,
+                Err(e) => warn!(error = %e, "Failed to translate reply; sending it untranslated"),
+            }
+        }
+        if let Some(max) = config.messenger_config(messenger_type).and_then(|m| m.max_reply_length) {
+            final_response = limit_reply(&final_response, max);
+        }
+        if let Some(notice) = &vision_notice {
+            final_response = format!("({})\n\n{}", notice, final_response);
+        }
+
         let mgr = messenger_mgr.lock().await;
         if let Some(messenger) = mgr.get_messenger_by_type(messenger_type) {
             let recipient = msg.channel.as_deref().unwrap_or(&msg.sender);
@@ -453,6 +971,7 @@ async fn process_incoming_message(
                 reply_to: Some(&msg.id),
                 silent: false,
                 media: None,
+                buttons: &[],
             };
 
             match messenger.send_message_with_options(opts).await {
@@ -469,6 +988,13 @@ async fn process_incoming_message(
                 }
                 Err(e) => {
                     warn!(error = %e, "Failed to send response");
+                    notifications::post(
+                        Severity::Warning,
+                        Source::Messenger,
+                        format!("{} reply not delivered", messenger_type),
+                        e.to_string(),
+                        None,
+                    );
                 }
             }
         }
@@ -477,18 +1003,399 @@ async fn process_incoming_message(
     Ok(())
 }
 
+/// Send a short reply (confirmation, notice) to the chat `msg` came from.
+async fn send_reply(messenger_mgr: &SharedMessengerManager, messenger_type: &str, msg: &Message, text: &str) {
+    let mgr = messenger_mgr.lock().await;
+    if let Some(messenger) = mgr.get_messenger_by_type(messenger_type) {
+        let opts = SendOptions {
+            recipient: msg.channel.as_deref().unwrap_or(&msg.sender),
+            content: text,
+            reply_to: Some(&msg.id),
+            silent: false,
+            media: None,
+            buttons: &[],
+        };
+        if let Err(e) = messenger.send_message_with_options(opts).await {
+            warn!(error = %e, "Failed to send reply");
+        }
+    }
+}
+
+/// Execute one tool call for `user`, routing secrets and skill tools to
+/// their handlers.
+async fn run_tool(
+    user: &users::User,
+    name: &str,
+    args: &Value,
+    vault: &SharedVault,
+    skill_mgr: &SharedSkillManager,
+    workspace_dir: &Path,
+    deadline: &tools::TurnDeadline,
+) -> (String, bool) {
+    let result = if tools::is_secrets_tool(name) {
+        deadline.run(name, secrets_handler::execute_secrets_tool(name, args, vault, workspace_dir)).await
+    } else if tools::is_skill_tool(name) {
+        deadline.run(name, skills_handler::execute_skill_tool(name, args, skill_mgr)).await
+    } else {
+        let run = tools::execute_tool_offloaded(name, args, workspace_dir);
+        deadline.run(name, users::user_scope(user.clone(), run)).await
+    };
+    match result {
+        Ok(text) => (text, false),
+        Err(err) => (err, true),
+    }
+}
+
+/// `tool action` (or just `tool`) for prompts and log lines.
+fn describe_call(name: &str, args: &Value) -> String {
+    match args.get("action").and_then(|v| v.as_str()) {
+        Some(action) => format!("{} {}", name, action),
+        None => name.to_string(),
+    }
+}
+
+/// Whether a reaction approves (👍) or rejects (👎) a pending tool call.
+/// Skin tones and emoji presentation selectors are ignored.
+fn approval_from_reaction(emoji: &str) -> Option<bool> {
+    let base: String = emoji
+        .chars()
+        .filter(|c| !matches!(c, '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}'))
+        .collect();
+    match base.as_str() {
+        "👍" | "👌" | "✅" => Some(true),
+        "👎" | "❌" => Some(false),
+        _ => None,
+    }
+}
+
+/// Send `text` with `buttons` to the chat `msg` came from and remember
+/// what it's waiting for.  Returns false if it couldn't be sent.
+async fn send_prompt(
+    messenger_mgr: &SharedMessengerManager,
+    prompts: &PendingPrompts,
+    messenger_type: &str,
+    msg: &Message,
+    user: &users::User,
+    text: &str,
+    buttons: &[Choice],
+    kind: PendingKind,
+) -> bool {
+    let prompt_id = {
+        let mgr = messenger_mgr.lock().await;
+        let Some(messenger) = mgr.get_messenger_by_type(messenger_type) else {
+            return false;
+        };
+        let opts = SendOptions {
+            recipient: msg.channel.as_deref().unwrap_or(&msg.sender),
+            content: text,
+            reply_to: Some(&msg.id),
+            silent: false,
+            media: None,
+            buttons,
+        };
+        match messenger.send_message_with_options(opts).await {
+            Ok(id) => id,
+            Err(e) => {
+                warn!(error = %e, "Failed to send prompt");
+                return false;
+            }
+        }
+    };
+
+    let key = format!("{}:{}", messenger_type, msg.channel.as_deref().unwrap_or(&msg.sender));
+    prompts.lock().await.insert(
+        key,
+        PendingPrompt {
+            kind,
+            prompt_id: Some(prompt_id),
+            requested: Instant::now(),
+            requester: msg.sender.clone(),
+            requester_role: user.role,
+        },
+    );
+    true
+}
+
+/// Remove and return the chat's pending prompt if `accept` matches it,
+/// `answer` comes from someone who may answer it and, when the answer
+/// points at a message, it's the prompt's own message.  Expired prompts
+/// are dropped.
+async fn take_prompt(
+    prompts: &PendingPrompts,
+    key: &str,
+    answer: &Message,
+    user: &users::User,
+    reply_to: Option<&str>,
+    accept: impl Fn(&PendingPrompt) -> bool,
+) -> Option<PendingPrompt> {
+    let mut prompts = prompts.lock().await;
+    let pending = prompts.get(key)?;
+    if pending.requested.elapsed() > PROMPT_TTL {
+        prompts.remove(key);
+        return None;
+    }
+    let on_prompt = match (reply_to, pending.prompt_id.as_deref()) {
+        (Some(target), Some(id)) => target == id,
+        _ => true,
+    };
+    if !on_prompt || !accept(pending) {
+        return None;
+    }
+    if !pending.may_answer(&answer.sender, user.role) {
+        debug!(
+            sender = %answer.sender,
+            requester = %pending.requester,
+            requester_role = pending.requester_role.as_str(),
+            "Ignoring answer to someone else's prompt"
+        );
+        return None;
+    }
+    prompts.remove(key)
+}
+
+/// The user's answer to an approval prompt.
+struct ApprovalOutcome<'a> {
+    approved: bool,
+    tool: &'a str,
+    args: &'a Value,
+}
+
+/// Act on the user's answer to an approval prompt.  When approved, the
+/// call runs and `msg` becomes a note with its result so the agent carries
+/// on; returns false when there's nothing more to do.
+async fn resolve_approval(
+    messenger_mgr: &SharedMessengerManager,
+    vault: &SharedVault,
+    skill_mgr: &SharedSkillManager,
+    workspace_dir: &Path,
+    messenger_type: &str,
+    msg: &mut Message,
+    user: &users::User,
+    outcome: ApprovalOutcome<'_>,
+) -> bool {
+    let call = describe_call(outcome.tool, outcome.args);
+    if !outcome.approved {
+        send_reply(messenger_mgr, messenger_type, msg, &format!("Cancelled `{}`.", call)).await;
+        return false;
+    }
+
+    info!(call = %call, "Running tool call approved in chat");
+    let deadline = tools::TurnDeadline::start();
+    let (output, is_error) = run_tool(user, outcome.tool, outcome.args, vault, skill_mgr, workspace_dir, &deadline).await;
+    msg.content = format!(
+        "I approved `{}`. {}:\n{}",
+        call,
+        if is_error { "It failed" } else { "Result" },
+        output
+    );
+    msg.event = MessageEvent::New;
+    true
+}
+
+/// Ask the chat to approve a gated tool call.  Returns the note for the
+/// model, or `None` if the prompt couldn't be sent.
+async fn request_approval(
+    messenger_mgr: &SharedMessengerManager,
+    prompts: &PendingPrompts,
+    messenger_type: &str,
+    msg: &Message,
+    user: &users::User,
+    name: &str,
+    args: &Value,
+) -> Option<String> {
+    let call = describe_call(name, args);
+    let text = format!(
+        "🔐 `{}` needs your OK:\n{}\n\nApprove or reject below (or react 👍 / 👎).",
+        call, args
+    );
+    let buttons = [Choice::new("✅ Approve", APPROVE), Choice::new("❌ Reject", REJECT)];
+    let kind = PendingKind::Approval { tool: name.to_string(), args: args.clone() };
+    if !send_prompt(messenger_mgr, prompts, messenger_type, msg, user, &text, &buttons, kind).await {
+        return None;
+    }
+    Some(format!(
+        "'{}' needs confirmation. The user has been asked to approve it in this chat; \
+         it will run once they do, and you'll get the result then. Don't retry it now.",
+        call
+    ))
+}
+
+/// Choices offered by an `ask_user` call: `select` options, or Yes/No for
+/// `confirm`.  Other prompt types are answered by typing.
+fn ask_user_choices(args: &Value) -> Vec<Choice> {
+    match args.get("prompt_type").and_then(|v| v.as_str()) {
+        Some("confirm") => vec![Choice::new("Yes", "yes"), Choice::new("No", "no")],
+        Some("select") | Some("multi_select") => args
+            .get("options")
+            .and_then(|v| v.as_array())
+            .map(|options| {
+                options
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, o)| {
+                        let label = o.as_str().or_else(|| o.get("label").and_then(|l| l.as_str()))?;
+                        Some(Choice::new(label, i.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// The choice a typed reply picks: its number (1-based) or its label.
+fn typed_choice<'a>(reply: &str, choices: &'a [Choice]) -> Option<&'a Choice> {
+    let reply = reply.trim();
+    if let Ok(n) = reply.parse::<usize>() {
+        return n.checked_sub(1).and_then(|i| choices.get(i));
+    }
+    choices.iter().find(|c| c.label.eq_ignore_ascii_case(reply))
+}
+
+/// Run `ask_user` in a chat: send the question with buttons for its
+/// choices.  The answer arrives later as the user's next message.
+async fn ask_in_chat(
+    messenger_mgr: &SharedMessengerManager,
+    prompts: &PendingPrompts,
+    messenger_type: &str,
+    msg: &Message,
+    user: &users::User,
+    args: &Value,
+) -> (String, bool) {
+    let title = args
+        .get("title")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Question")
+        .to_string();
+    let text = match args.get("description").and_then(|v| v.as_str()) {
+        Some(desc) => format!("❓ {}\n{}", title, desc),
+        None => format!("❓ {}", title),
+    };
+    let choices = ask_user_choices(args);
+    let kind = PendingKind::Question { title: title.clone(), choices: choices.clone() };
+    if !send_prompt(messenger_mgr, prompts, messenger_type, msg, user, &text, &choices, kind).await {
+        return ("Could not send the question to the chat.".to_string(), true);
+    }
+    (
+        format!(
+            "Asked the user \"{}\" in the chat. Their answer will arrive as their next \
+             message; end this turn without guessing it.",
+            title
+        ),
+        false,
+    )
+}
+
+/// Translate off the async runtime; providers use blocking HTTP or a CLI.
+async fn translate_text(
+    text: String,
+    source: Option<String>,
+    target: String,
+) -> Result<crate::translate::Translation, String> {
+    tokio::task::spawn_blocking(move || {
+        crate::translate::translate(&text, source.as_deref(), Some(&target), None)
+    })
+    .await
+    .map_err(|e| format!("Translation task panicked: {}", e))?
+}
+
+/// Apply a `snooze …` / `done` reply to the reminder last delivered to this
+/// chat.  Returns the confirmation to send, or `None` if the message isn't
+/// a reminder reply (or no reminder is waiting for one), so it goes on to
+/// the agent as usual.
+fn handle_reminder_reply(config: &Config, messenger_type: &str, msg: &Message) -> Option<String> {
+    use crate::cron::{format_ms, parse_reminder_reply, CronStore, ReminderReply};
+
+    let reply = parse_reminder_reply(&msg.content)?;
+    let mut store = CronStore::new(&config.workspace_dir().join(".cron")).ok()?;
+    let chat = msg.channel.as_deref().unwrap_or(&msg.sender);
+    let job = store.awaiting_reply(messenger_type, chat)?;
+    let job_id = job.job_id.clone();
+    let name = job.name.clone().unwrap_or_else(|| job_id.clone());
+    let tz = job.schedule.tz();
+
+    let result = match reply {
+        ReminderReply::Snooze(delay) => store
+            .snooze(&job_id, delay, Some(messenger_type))
+            .map(|until| format!("💤 Snoozed '{}' until {}", name, format_ms(until, tz))),
+        ReminderReply::Done => store
+            .acknowledge(&job_id, Some(messenger_type))
+            .map(|()| format!("✅ Marked '{}' done", name)),
+    };
+    debug!(job_id = %job_id, ?reply, "Reminder reply");
+    Some(result.unwrap_or_else(|e| format!("Could not update reminder: {}", e)))
+}
+
+/// Enqueue a task from a `/task [priority] <prompt>` messenger command.
+fn enqueue_task(config: &Config, args: &str, messenger_type: &str) -> Result<String, String> {
+    use crate::task_queue::{queue_dir, QueuedTask, TaskPriority, TaskQueue};
+
+    let (priority, prompt) = match args.split_once(' ') {
+        Some((first, rest)) => match first.parse::<TaskPriority>() {
+            Ok(p) => (p, rest.trim()),
+            Err(_) => (TaskPriority::Normal, args),
+        },
+        None => (TaskPriority::Normal, args),
+    };
+    if prompt.is_empty() {
+        return Err("Usage: /task [low|normal|high|urgent] <prompt>".to_string());
+    }
+
+    let mut task = QueuedTask::new(prompt, priority, config.task_queue.default_max_retries);
+    task.source = Some(messenger_type.to_string());
+    let mut queue = TaskQueue::new(&queue_dir(&config.workspace_dir()))?;
+    queue.enqueue(task)
+}
+
+/// Turn a messenger's `style` setting into a reply instruction.
+fn style_instruction(style: Option<&str>) -> String {
+    match style.map(str::trim).unwrap_or("") {
+        "" | "concise" => "Be concise and appropriate for chat".to_string(),
+        "terse" => "Be terse: a sentence or two, no preamble, no formatting".to_string(),
+        "verbose" => "Be thorough: full sentences, context and detail are welcome".to_string(),
+        "formal" => "Write formally, as in a professional email, with a greeting and sign-off"
+            .to_string(),
+        custom => custom.to_string(),
+    }
+}
+
+/// Cut a reply down to `max_chars` characters, ending it with an ellipsis.
+fn limit_reply(reply: &str, max_chars: usize) -> String {
+    if reply.chars().count() <= max_chars {
+        return reply.to_string();
+    }
+    let kept: String = reply.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
 /// Build system prompt with messenger context and workspace files.
-fn build_messenger_system_prompt(config: &Config, messenger_type: &str, msg: &Message) -> String {
+///
+/// The messenger's own `system_prompt` takes precedence over the global
+/// one, and its `style` and `max_reply_length` become reply instructions.
+fn build_messenger_system_prompt(
+    config: &Config,
+    messenger_type: &str,
+    msg: &Message,
+    person: Option<&identity::Person>,
+    user: &users::User,
+) -> String {
     use crate::workspace_context::{SessionType, WorkspaceContext};
 
-    let base_prompt = config.system_prompt.clone().unwrap_or_else(|| {
-        "You are a helpful AI assistant.".to_string()
-    });
+    let channel_config = config.messenger_config(messenger_type);
+    let base_prompt = channel_config
+        .and_then(|m| m.system_prompt.clone())
+        .or_else(|| config.system_prompt.clone())
+        .unwrap_or_else(|| "You are a helpful AI assistant.".to_string());
+    let style = style_instruction(channel_config.and_then(|m| m.style.as_deref()));
+    let length_limit = channel_config
+        .and_then(|m| m.max_reply_length)
+        .map(|n| format!("- Keep replies under {} characters\n", n))
+        .unwrap_or_default();
 
     // Determine session type based on messenger context
     // Direct messages are treated as main session, channels/groups as group session
-    let session_type = if msg.channel.is_some() {
-        // Channel/group messages have restricted access
+    let session_type = if msg.channel.is_some() || user.role != users::Role::Owner {
+        // Channel/group messages and non-owners have restricted access
         SessionType::Group
     } else {
         // Direct messages have full access
@@ -517,14 +1424,44 @@ fn build_messenger_system_prompt(config: &Config, messenger_type: &str, msg: &Me
         - Platform: {}\n\
         \n\
         When responding:\n\
-        - Be concise and appropriate for chat\n\
+        - {}\n\
+        {}\
         - You have access to tools — use them when helpful\n\
         - If you have nothing to say, reply with: NO_REPLY",
         msg.channel.as_deref().unwrap_or("direct"),
-        msg.sender,
-        messenger_type
+        person.map(|p| format!("{} ({})", p.name, msg.sender)).unwrap_or_else(|| msg.sender.clone()),
+        messenger_type,
+        style,
+        length_limit
     ));
 
+    if user.role != users::Role::Owner {
+        parts.push(format!(
+            "## Access\n\
+            This user's role is {}. Don't reveal the owner's memory, secrets or other people's \
+            conversations. Keep notes about this user under `{}/`; tools outside their role are refused.",
+            user.role.as_str(),
+            user.namespace()
+        ));
+    }
+
+    if let Some(person) = person {
+        let file = identity::memory_file(&config.workspace_dir(), person);
+        let mut section = format!(
+            "## About {}\n\
+            The sender is {}, known to you on several messengers. Keep durable facts about them \
+            (preferences, ongoing topics) in `memory/people/{}.md`.",
+            person.name, person.name, person.key
+        );
+        if let Ok(facts) = std::fs::read_to_string(&file) {
+            if !facts.trim().is_empty() {
+                section.push_str("\n\n");
+                section.push_str(facts.trim());
+            }
+        }
+        parts.push(section);
+    }
+
     parts.join("\n\n")
 }
 
@@ -819,3 +1756,118 @@ fn build_multimodal_user_message(text: &str, images: &[ImageData], provider: &st
         "content": content
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(sender: &str) -> Message {
+        Message {
+            id: "m2".into(),
+            sender: sender.into(),
+            content: "👍".into(),
+            timestamp: 0,
+            channel: Some("group".into()),
+            reply_to: None,
+            media: None,
+            event: MessageEvent::New,
+        }
+    }
+
+    fn chat_message(content: &str) -> (String, Message) {
+        let mut msg = message("alice");
+        msg.content = content.into();
+        ("telegram".into(), msg)
+    }
+
+    type Seen = Arc<std::sync::Mutex<Vec<String>>>;
+
+    /// Start a worker whose turns take `delay` and record their text.
+    fn spawn_worker(
+        queues: &ChatQueues,
+        rx: mpsc::UnboundedReceiver<(String, Message)>,
+        idle: Duration,
+        delay: Duration,
+    ) -> (Seen, tokio::task::JoinHandle<()>) {
+        let seen = Seen::default();
+        let record = seen.clone();
+        let run = move |_: String, msg: Message, _: usize| {
+            let record = record.clone();
+            async move {
+                tokio::time::sleep(delay).await;
+                record.lock().unwrap().push(msg.content);
+            }
+        };
+        let handle = tokio::spawn(chat_worker(queues.clone(), "telegram:group".into(), rx, idle, Duration::ZERO, run));
+        (seen, handle)
+    }
+
+    #[tokio::test]
+    async fn test_chat_worker_keeps_order() {
+        let queues = ChatQueues::default();
+        let key = "telegram:group";
+        let rx = enqueue(&queues, key, chat_message("1")).expect("first message starts a worker");
+        let (seen, handle) = spawn_worker(&queues, rx, Duration::from_millis(200), Duration::from_millis(20));
+        for n in 2..=5 {
+            assert!(enqueue(&queues, key, chat_message(&n.to_string())).is_none());
+        }
+
+        handle.await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), ["1", "2", "3", "4", "5"]);
+    }
+
+    #[tokio::test]
+    async fn test_idle_chat_worker_leaves_the_queue_map() {
+        let queues = ChatQueues::default();
+        let key = "telegram:group";
+        let rx = enqueue(&queues, key, chat_message("hello")).unwrap();
+        let (seen, handle) = spawn_worker(&queues, rx, Duration::from_millis(50), Duration::ZERO);
+
+        handle.await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), ["hello"]);
+        assert!(!queues.lock().unwrap().contains_key(key));
+
+        // The next message gets a fresh worker.
+        assert!(enqueue(&queues, key, chat_message("again")).is_some());
+    }
+
+    fn user(role: users::Role) -> users::User {
+        users::User { role, key: "k".into() }
+    }
+
+    async fn pending_approval() -> PendingPrompts {
+        let prompts: PendingPrompts = Arc::new(Mutex::new(HashMap::new()));
+        prompts.lock().await.insert(
+            "telegram:group".into(),
+            PendingPrompt {
+                kind: PendingKind::Approval { tool: "process".into(), args: json!({}) },
+                prompt_id: Some("p1".into()),
+                requested: Instant::now(),
+                requester: "alice".into(),
+                requester_role: users::Role::Trusted,
+            },
+        );
+        prompts
+    }
+
+    #[tokio::test]
+    async fn test_only_requester_or_owner_answers_prompts() {
+        let prompts = pending_approval().await;
+        let key = "telegram:group";
+
+        // Another guest or trusted user in the chat can't approve.
+        let mallory = message("mallory");
+        assert!(take_prompt(&prompts, key, &mallory, &user(users::Role::Guest), None, |_| true).await.is_none());
+        assert!(take_prompt(&prompts, key, &mallory, &user(users::Role::Trusted), None, |_| true).await.is_none());
+        assert!(prompts.lock().await.contains_key(key));
+
+        // The requester can.
+        let alice = message("alice");
+        assert!(take_prompt(&prompts, key, &alice, &user(users::Role::Trusted), None, |_| true).await.is_some());
+
+        // So can an owner.
+        let prompts = pending_approval().await;
+        let ada = message("ada");
+        assert!(take_prompt(&prompts, key, &ada, &user(users::Role::Owner), None, |_| true).await.is_some());
+    }
+}
@@ -0,0 +1,222 @@
+//! Built-in `mock` provider for hermetic tests.
+//!
+//! Selected with `provider = "mock"` in the `[model]` config section.  The
+//! provider's `base_url` is the path of a JSON fixture that scripts the
+//! replies; with no fixture the mock simply echoes the last user message.
+//!
+//! ```json
+//! {
+//!   "turns": [
+//!     { "match": "list files",
+//!       "responses": [
+//!         { "tool_calls": [{ "name": "list_directory", "arguments": { "path": "." } }] },
+//!         { "text": "Those are the files." }
+//!       ] }
+//!   ],
+//!   "default": "I only know how to list files."
+//! }
+//! ```
+//!
+//! Responses are chosen without any hidden state, so replies depend only on
+//! the conversation: the turn is the first one whose `match` appears in the
+//! latest user message (or, for turns without `match`, the one at the same
+//! position as that user message), and the response within the turn is
+//! given by the number of assistant messages since that user message.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+
+use super::types::{ChatMessage, ModelResponse, ParsedToolCall, ProviderRequest};
+
+/// Provider id of the mock provider.
+pub const MOCK_PROVIDER: &str = "mock";
+
+/// A scripted tool call.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// One scripted model response.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MockResponse {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub tool_calls: Vec<MockToolCall>,
+}
+
+/// Responses for one user turn.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockTurn {
+    /// Case-insensitive substring of the user message that selects this turn.
+    #[serde(default, rename = "match")]
+    pub match_text: Option<String>,
+    pub responses: Vec<MockResponse>,
+}
+
+/// A mock provider fixture.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MockScript {
+    #[serde(default)]
+    pub turns: Vec<MockTurn>,
+    /// Reply used when no turn applies or a turn's responses run out.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl MockScript {
+    /// Load a fixture file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mock fixture {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid mock fixture {}", path.display()))
+    }
+
+    /// Produce the response for a conversation.
+    pub fn respond(&self, messages: &[ChatMessage]) -> ModelResponse {
+        let last_user = messages.iter().rposition(|m| m.role == "user");
+        let user_text = last_user.map(|i| messages[i].content.as_str()).unwrap_or("");
+        let user_ordinal = messages.iter().filter(|m| m.role == "user").count().saturating_sub(1);
+        let step = last_user
+            .map(|i| messages[i + 1..].iter().filter(|m| m.role == "assistant").count())
+            .unwrap_or(0);
+
+        let lowered = user_text.to_lowercase();
+        let turn_idx = self
+            .turns
+            .iter()
+            .position(|t| {
+                t.match_text
+                    .as_ref()
+                    .is_some_and(|m| lowered.contains(&m.to_lowercase()))
+            })
+            .or_else(|| {
+                self.turns
+                    .get(user_ordinal)
+                    .filter(|t| t.match_text.is_none())
+                    .map(|_| user_ordinal)
+            });
+
+        let scripted = turn_idx.and_then(|t| self.turns[t].responses.get(step).map(|r| (t, r)));
+        match scripted {
+            Some((t, resp)) => {
+                let tool_calls: Vec<ParsedToolCall> = resp
+                    .tool_calls
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tc)| ParsedToolCall {
+                        id: format!("mock_{}_{}_{}", t, step, i),
+                        name: tc.name.clone(),
+                        arguments: tc.arguments.clone(),
+                    })
+                    .collect();
+                finish(resp.text.clone(), tool_calls)
+            }
+            None => {
+                let text = self
+                    .default
+                    .clone()
+                    .unwrap_or_else(|| format!("Mock response to: {}", user_text));
+                finish(text, Vec::new())
+            }
+        }
+    }
+}
+
+fn finish(text: String, tool_calls: Vec<ParsedToolCall>) -> ModelResponse {
+    let finish_reason = if tool_calls.is_empty() { "stop" } else { "tool_calls" };
+    ModelResponse {
+        text,
+        tool_calls,
+        finish_reason: Some(finish_reason.to_string()),
+        prompt_tokens: None,
+        completion_tokens: None,
+        streamed: false,
+    }
+}
+
+/// Answer a request from the fixture named by `req.base_url` (echo mode
+/// when empty).  Never touches the network.
+pub fn call_mock_with_tools(req: &ProviderRequest) -> Result<ModelResponse> {
+    let script = if req.base_url.trim().is_empty() {
+        MockScript::default()
+    } else {
+        MockScript::load(Path::new(req.base_url.trim()))?
+    };
+    Ok(script.respond(&req.messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn script() -> MockScript {
+        serde_json::from_value(json!({
+            "turns": [
+                { "match": "files",
+                  "responses": [
+                      { "tool_calls": [{ "name": "list_directory", "arguments": { "path": "." } }] },
+                      { "text": "Done listing." }
+                  ] },
+                { "responses": [{ "text": "second turn" }] }
+            ],
+            "default": "fallback"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scripted_tool_sequence() {
+        let s = script();
+        let mut messages = vec![ChatMessage::text("user", "List FILES please")];
+
+        let first = s.respond(&messages);
+        assert_eq!(first.tool_calls.len(), 1);
+        assert_eq!(first.tool_calls[0].name, "list_directory");
+        assert_eq!(first.finish_reason.as_deref(), Some("tool_calls"));
+
+        messages.push(ChatMessage::text("assistant", "{}"));
+        messages.push(ChatMessage::text("tool", "a.txt"));
+        let second = s.respond(&messages);
+        assert!(second.tool_calls.is_empty());
+        assert_eq!(second.text, "Done listing.");
+
+        messages.push(ChatMessage::text("assistant", "Done listing."));
+        assert_eq!(s.respond(&messages).text, "fallback");
+    }
+
+    #[test]
+    fn test_positional_turn_and_default() {
+        let s = script();
+        let messages = vec![
+            ChatMessage::text("user", "hello"),
+            ChatMessage::text("assistant", "fallback"),
+            ChatMessage::text("user", "again"),
+        ];
+        assert_eq!(s.respond(&messages).text, "second turn");
+
+        let messages = vec![ChatMessage::text("user", "unrelated")];
+        assert_eq!(s.respond(&messages).text, "fallback");
+    }
+
+    #[test]
+    fn test_echo_without_fixture() {
+        let req = ProviderRequest {
+            messages: vec![ChatMessage::text("user", "ping")],
+            model: "mock".into(),
+            provider: MOCK_PROVIDER.into(),
+            base_url: String::new(),
+            api_key: None,
+            tools: None,
+        };
+        let resp = call_mock_with_tools(&req).unwrap();
+        assert_eq!(resp.text, "Mock response to: ping");
+    }
+}
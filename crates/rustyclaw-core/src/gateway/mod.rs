@@ -6,15 +6,26 @@
 //! for incoming messages and routes them through the model.
 
 mod auth;
+pub mod bench;
+mod cron_worker;
 pub mod csrf;
 pub mod health;
+mod heartbeat_worker;
 mod helpers;
+pub mod media;
 mod messenger_handler;
+pub mod mock_provider;
 mod providers;
 pub mod protocol;
 mod secrets_handler;
+pub mod session_view;
 mod skills_handler;
+pub mod stats;
+mod stream;
+mod subagent_worker;
+mod task_worker;
 mod types;
+pub mod usage;
 
 // Re-export protocol types
 pub use protocol::{
@@ -28,16 +39,25 @@ pub use types::{
     ModelResponse, ParsedToolCall, ProbeResult, ProviderRequest, ToolCallResult,
 };
 
+// Re-export task worker entry point
+pub use task_worker::run_task_worker;
+
 // Re-export messenger handler types
 pub use messenger_handler::{
     create_messenger_manager, run_messenger_loop, SharedMessengerManager,
 };
 
 use crate::config::Config;
+use crate::error::{self, Error};
+use crate::journal::TurnJournal;
+use crate::notifications;
+use crate::observability::trace as turn_trace;
 use crate::providers as crate_providers;
 use crate::secrets::SecretsManager;
 use crate::skills::SkillManager;
 use crate::tools;
+use protocol::compression::CompressingSink;
+use protocol::version::{self, Negotiated};
 use anyhow::{Context, Result};
 use dirs;
 use futures_util::stream::SplitSink;
@@ -51,7 +71,7 @@ use tokio::sync::{Mutex, RwLock};
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::WebSocketStream;
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, trace, warn, Instrument};
 
 /// Shared flag for cancelling the tool loop from another task.
 pub type ToolCancelFlag = Arc<AtomicBool>;
@@ -64,7 +84,7 @@ impl<T: AsyncRead + AsyncWrite + Send + Unpin> AsyncStream for T {}
 type MaybeTlsStream = Box<dyn AsyncStream>;
 
 /// Type alias for the server-side WebSocket write half.
-type WsWriter = SplitSink<WebSocketStream<MaybeTlsStream>, Message>;
+type WsWriter = CompressingSink<SplitSink<WebSocketStream<MaybeTlsStream>, Message>>;
 
 /// Gateway-owned secrets vault, shared across connections.
 ///
@@ -93,6 +113,8 @@ pub use protocol::server::{
 
 // Re-export validate_model_connection for external use
 pub use providers::validate_model_connection;
+// One-shot completions with the configured model, for tools
+pub use providers::{complete_blocking, set_tool_model};
 
 // ── Constants ───────────────────────────────────────────────────────────────
 
@@ -168,6 +190,14 @@ pub async fn run_gateway(
     skill_mgr: SharedSkillManager,
     cancel: CancellationToken,
 ) -> Result<()> {
+    // Merge the workspace's `.rustyclaw/` project overlay, if any.
+    let mut config = config;
+    let mut model_ctx = model_ctx;
+    if apply_project_overlay(&mut config, &skill_mgr).await {
+        let mut v = vault.lock().await;
+        model_ctx = ModelContext::resolve(&config, &mut v).ok();
+    }
+
     // Register the credentials directory so file-access tools can enforce
     // the vault boundary (blocks read_file, execute_command, etc.).
     tools::set_credentials_dir(config.credentials_dir());
@@ -183,6 +213,73 @@ pub async fn run_gateway(
         config.credentials_dir(),
         config.sandbox.deny_paths.clone(),
     );
+    tools::set_write_policy(config.sandbox.write_policy());
+
+    // Record provider calls and tool results for later replay.
+    if let Some(ref path) = options.record {
+        crate::recording::start_recording(path).map_err(|e| anyhow::anyhow!(e))?;
+        info!(path = %path.display(), "Recording session");
+    }
+
+    // Extra directories searched by find_files / search_files.
+    tools::set_extra_roots(config.extra_roots.clone());
+
+    // Run commands on the host or in the configured container.
+    crate::container::set_execution(config.execution.clone());
+    crate::dev_env::set_mode(config.dev_env);
+    crate::lsp::set_servers(config.lsp_servers.clone());
+    crate::contacts::set_config(config.contacts.clone(), &config.settings_dir);
+    crate::presence::set_config(config.presence.clone(), &config.workspace_dir());
+    crate::quiet_hours::set_config(&config.messengers, &config.workspace_dir());
+    crate::snapshots::set_config(config.snapshots.clone());
+    crate::trash::set_config(config.trash.clone(), &config.settings_dir);
+    crate::mqtt::set_config(config.mqtt.clone(), &config.workspace_dir());
+    crate::translate::set_config(config.translation.clone());
+    if let Some(engine) = config.execution.engine() {
+        info!(engine, image = %config.execution.image, "Commands run in a container");
+    }
+
+    // Simulate mutating tools when running in dry-run mode.
+    tools::set_dry_run(config.dry_run);
+    tools::set_permissions(config.tool_permissions.clone());
+    tools::set_timeouts(config.timeouts.clone());
+    tools::set_schema_budget(config.tool_schemas.clone());
+    crate::model_caps::set_overrides(config.model_capabilities.clone());
+    bench::load_results(&config.settings_dir);
+    tools::set_file_index(config.file_index.clone(), &config.workspace_dir());
+    let embeddings_key = model_ctx
+        .as_ref()
+        .filter(|ctx| ctx.provider == config.embeddings.provider)
+        .and_then(|ctx| ctx.api_key.clone());
+    crate::embeddings::set_config(config.embeddings.clone(), &config.settings_dir, embeddings_key);
+    if config.dry_run {
+        info!("Dry-run mode: mutating tools will be simulated");
+    }
+
+    // Register tools exported by sandboxed WASM plugins.
+    #[cfg(feature = "wasm-plugins")]
+    {
+        let plugin_tools = crate::plugins::register_plugins(&config.plugins_dir(), &config.plugin_grants);
+        if !plugin_tools.is_empty() {
+            info!(tools = ?plugin_tools, "Registered plugin tools");
+        }
+    }
+
+    // Start external stdio tool servers and register their tools.
+    if !config.tool_servers.is_empty() {
+        let server_tools =
+            crate::tool_servers::start_tool_servers(&config.tool_servers, cancel.child_token()).await;
+        info!(tools = ?server_tools, "Registered tool server tools");
+    }
+
+    // Expose config to scripts and run their start hooks.
+    crate::scripting::set_config(&config);
+    crate::scripting::spawn_hooks("start", serde_json::Value::Null, config.workspace_dir());
+
+    // Apply sub-agent delegation guardrails.
+    if let Ok(mut mgr) = crate::sessions::session_manager().lock() {
+        mgr.set_policy(config.delegation.clone());
+    }
 
     let addr = helpers::resolve_listen_addr(&options.listen)?;
     let listener = TcpListener::bind(addr)
@@ -293,6 +390,7 @@ pub async fn run_gateway(
     };
 
     let model_ctx = model_ctx.map(Arc::new);
+    providers::set_tool_model(model_ctx.clone());
     let shared_config: SharedConfig = Arc::new(RwLock::new(config.clone()));
     let shared_model_ctx: SharedModelCtx = Arc::new(RwLock::new(model_ctx.clone()));
     let rate_limiter = auth::new_rate_limiter();
@@ -338,6 +436,118 @@ pub async fn run_gateway(
         None
     };
 
+    health::set_cron_dir(config.workspace_dir().join(".cron"));
+
+    // ── Start background task worker ────────────────────────────────
+    if config.task_queue.enabled {
+        health::set_tasks_dir(crate::task_queue::queue_dir(&config.workspace_dir()));
+        let worker_config = config.clone();
+        let worker_ctx = model_ctx.clone();
+        let worker_vault = vault.clone();
+        let worker_skills = skill_mgr.clone();
+        let worker_cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = task_worker::run_task_worker(
+                worker_config,
+                worker_ctx,
+                worker_vault,
+                worker_skills,
+                worker_cancel,
+            ).await {
+                error!(error = %e, "Task worker error");
+            }
+        });
+    }
+
+    // ── Run spawned sub-agents ──────────────────────────────────────
+    {
+        let sub_config = config.clone();
+        let sub_ctx = model_ctx.clone();
+        let sub_vault = vault.clone();
+        let sub_skills = skill_mgr.clone();
+        let sub_cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = subagent_worker::run_subagent_worker(
+                sub_config,
+                sub_ctx,
+                sub_vault,
+                sub_skills,
+                sub_cancel,
+            ).await {
+                error!(error = %e, "Sub-agent worker error");
+            }
+        });
+    }
+
+    // ── Start heartbeat check-ins ───────────────────────────────────
+    if config.heartbeat.enabled {
+        let hb_config = config.clone();
+        let hb_ctx = model_ctx.clone();
+        let hb_vault = vault.clone();
+        let hb_skills = skill_mgr.clone();
+        let hb_cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = heartbeat_worker::run_heartbeat_loop(
+                hb_config,
+                hb_ctx,
+                hb_vault,
+                hb_skills,
+                hb_cancel,
+            ).await {
+                error!(error = %e, "Heartbeat error");
+            }
+        });
+    }
+
+    // ── Run due cron jobs ───────────────────────────────────────────
+    {
+        let cron_config = config.clone();
+        let cron_ctx = model_ctx.clone();
+        let cron_vault = vault.clone();
+        let cron_skills = skill_mgr.clone();
+        let cron_cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = cron_worker::run_cron_scheduler(
+                cron_config,
+                cron_ctx,
+                cron_vault,
+                cron_skills,
+                cron_cancel,
+            ).await {
+                error!(error = %e, "Cron scheduler error");
+            }
+        });
+    }
+
+    // ── Start presence polling (idles until zones are enabled) ─────
+    tokio::spawn(crate::presence::run_presence_loop(cancel.child_token()));
+
+    // ── Deliver messages held for quiet hours ───────────────────────
+    tokio::spawn(crate::quiet_hours::run_quiet_hours_loop(cancel.child_token()));
+
+    // ── Start the MQTT client (idles until [mqtt] is enabled) ──────
+    tokio::spawn(crate::mqtt::run_mqtt_loop(cancel.child_token()));
+
+    // ── Keep the file index fresh (idles until [file_index] is enabled) ─
+    tokio::spawn(tools::run_file_index_loop(cancel.child_token()));
+
+    // ── Track cron jobs due soon for the TUI header ─────────────────
+    tokio::spawn(stats::run_cron_watch(config.workspace_dir(), cancel.child_token()));
+
+    // ── Serve health, metrics, tasks, presence and calendar over HTTP ─
+    let health_stats: health::SharedHealthStats = Arc::new(health::HealthStats::new());
+    if let Some(ref health_addr) = config.health_listen {
+        let health_addr = health_addr.clone();
+        let health_stats = health_stats.clone();
+        let health_token = config.health_token.clone();
+        let health_cancel = cancel.child_token();
+        tokio::spawn(async move {
+            if let Err(e) = health::start_health_server(&health_addr, health_stats, health_token, health_cancel).await {
+                error!(error = %e, "Health check server error");
+            }
+        });
+    }
+
     info!(address = %addr, "Gateway listening");
     if messenger_mgr.is_some() {
         info!("Messenger polling enabled");
@@ -358,6 +568,8 @@ pub async fn run_gateway(
                 let limiter_clone = rate_limiter.clone();
                 let child_cancel = cancel.child_token();
                 let tls = tls_acceptor.clone();
+                let conn_stats = health_stats.clone();
+                conn_stats.total_connections.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                 tokio::spawn(async move {
                     // Wrap in TLS if configured, otherwise use plain TCP.
                     let boxed_stream: MaybeTlsStream = if let Some(acceptor) = tls {
@@ -372,21 +584,40 @@ pub async fn run_gateway(
                         Box::new(stream)
                     };
 
-                    if let Err(err) = handle_connection(
+                    conn_stats.active_connections.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    // A `/dryrun` toggle only applies to, and lasts as long
+                    // as, this connection.
+                    let connection = handle_connection(
                         boxed_stream, peer, shared_cfg, shared_ctx,
                         session_clone, vault_clone, skill_clone,
                         limiter_clone, child_cancel,
-                    ).await {
+                    );
+                    if let Err(err) = tools::dry_run_session_scope(tools::DryRunSession::default(), connection).await {
                         debug!(peer = %peer, error = %err, "Connection error");
                     }
+                    conn_stats.active_connections.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
                 });
             }
         }
     }
 
+    // Language servers are child processes; don't leave them behind.
+    let _ = tokio::task::spawn_blocking(crate::lsp::shutdown_all).await;
+    // Nor an ssh-agent holding vault keys.
+    let _ = tokio::task::spawn_blocking(crate::secrets::ssh_agent::stop).await;
+
     Ok(())
 }
 
+/// Drops a connection's plan when the connection ends.
+struct ConnectionPlan(String);
+
+impl Drop for ConnectionPlan {
+    fn drop(&mut self) {
+        crate::plan::remove_plan(&self.0);
+    }
+}
+
 async fn handle_connection(
     stream: MaybeTlsStream,
     peer: SocketAddr,
@@ -401,7 +632,8 @@ async fn handle_connection(
     let ws_stream: WebSocketStream<MaybeTlsStream> = tokio_tungstenite::accept_async(stream)
         .await
         .context("WebSocket handshake failed")?;
-    let (mut writer, mut reader) = ws_stream.split();
+    let (writer, mut reader) = ws_stream.split();
+    let mut writer = CompressingSink::new(writer);
     let peer_ip = peer.ip();
 
     // Snapshot config and model context for this connection.
@@ -552,7 +784,8 @@ async fn handle_connection(
             let display = crate_providers::display_name_for_provider(&ctx.provider);
 
             // 1. Model configured
-            let detail = format!("{} / {}", display, ctx.model);
+            let caps = crate::model_caps::lookup(&ctx.model);
+            let detail = format!("{} / {} ({})", display, ctx.model, caps.describe());
             protocol::server::send_status(&mut writer, StatusType::ModelConfigured, &detail)
                 .await
                 .context("Failed to send model_configured status")?;
@@ -739,6 +972,26 @@ async fn handle_connection(
         }
     });
 
+    // Live header stats: send the current numbers, then every change.
+    let mut stats_rx = stats::subscribe();
+    protocol::server::send_stats(&mut writer, stats_rx.borrow_and_update().clone()).await?;
+
+    // Clients that never negotiate only get the frames protocol version 1
+    // had; a `Negotiate` frame widens this.
+    let mut negotiated = Negotiated::legacy();
+
+    // Notification center: forward each new event to clients that asked
+    // for it.  The backlog is replayed when they negotiate.
+    let mut notify_rx = notifications::subscribe();
+
+    // Turns on this connection share one plan, which no other connection
+    // or chat sees.
+    let connection_plan = ConnectionPlan(format!("connection:{}", peer));
+
+    // Split-pane view: the watched target and the view last sent for it.
+    let mut watched: Option<(session_view::WatchTarget, Option<session_view::SessionView>)> = None;
+    let mut view_tick = tokio::time::interval(session_view::VIEW_REFRESH);
+
     // Main message handling loop — receives from channel
     loop {
         tokio::select! {
@@ -746,6 +999,27 @@ async fn handle_connection(
                 let _ = writer.send(Message::Close(None)).await;
                 break;
             }
+            Ok(()) = stats_rx.changed() => {
+                let snapshot = stats_rx.borrow_and_update().clone();
+                protocol::server::send_stats(&mut writer, snapshot).await?;
+            }
+            Ok(notification) = notify_rx.recv() => {
+                if negotiated.has(version::CAP_NOTIFICATIONS) {
+                    protocol::server::send_notification(&mut writer, notification).await?;
+                }
+            }
+            _ = view_tick.tick(), if watched.is_some() => {
+                let workspace_dir = shared_config.read().await.workspace_dir();
+                if let Some((target, last)) = watched.as_mut() {
+                    // A finished sub-agent may be gone; keep the last view.
+                    if let Ok(view) = session_view::build_view(target, &workspace_dir) {
+                        if last.as_ref() != Some(&view) {
+                            protocol::server::send_session_view(&mut writer, Some(view.clone())).await?;
+                            *last = Some(view);
+                        }
+                    }
+                }
+            }
             msg = msg_rx.recv() => {
                 let message = match msg {
                     Some(m) => m,
@@ -766,14 +1040,7 @@ async fn handle_connection(
                             Err(e) => {
                                 debug!(error = %e, "Failed to deserialize ClientFrame");
                                 // Send error response
-                                let error_frame = ServerFrame {
-                                    frame_type: ServerFrameType::Error,
-                                    payload: ServerPayload::Error {
-                                        ok: false,
-                                        message: format!("Failed to parse client frame: {}", e),
-                                    },
-                                };
-                                send_frame(&mut writer, &error_frame).await?;
+                                protocol::server::send_error(&mut writer, Error::Internal(format!("Failed to parse client frame: {}", e))).await?;
                                 continue;
                             }
                         };
@@ -813,19 +1080,24 @@ async fn handle_connection(
                                 send_secrets_list_result(&mut writer, true, dto_entries).await?;
                             }
                             ClientPayload::SecretsStore { key, value } => {
-                                let mut v = vault.lock().await;
-                                let result = v.store_secret(&key, &value);
-                                match result {
-                                    Ok(()) => send_secrets_store_result(
+                                // Provider API keys are checked before they are stored.
+                                let base_url = config
+                                    .model
+                                    .as_ref()
+                                    .filter(|m| crate_providers::provider_for_secret_key(&key).is_some_and(|p| p.id == m.provider))
+                                    .and_then(|m| m.base_url.clone());
+                                let checked = secrets_handler::check_provider_key(&key, &value, base_url.as_deref()).await;
+                                let result = match &checked {
+                                    Ok(_) => vault.lock().await.store_secret(&key, &value).map_err(|e| format!("Failed to store secret: {}", e)),
+                                    Err(e) => Err(e.clone()),
+                                };
+                                match result.and(checked) {
+                                    Ok(key_note) => send_secrets_store_result(
                                         &mut writer,
                                         true,
-                                        &format!("Secret '{}' stored.", key),
-                                    ).await?,
-                                    Err(e) => send_secrets_store_result(
-                                        &mut writer,
-                                        false,
-                                        &format!("Failed to store secret: {}", e),
+                                        &format!("Secret '{}' stored.{}", key, key_note),
                                     ).await?,
+                                    Err(e) => send_secrets_store_result(&mut writer, false, &e).await?,
                                 };
                             }
                             ClientPayload::SecretsGet { key } => {
@@ -957,9 +1229,13 @@ async fn handle_connection(
                                 let settings_dir = config.settings_dir.clone();
                                 let config_path = settings_dir.join("config.toml");
                                 match Config::load(Some(config_path)) {
-                                    Ok(new_config) => {
+                                    Ok(mut new_config) => {
+                                        apply_project_overlay(&mut new_config, &skill_mgr).await;
                                         let new_model_ctx = {
                                             let mut v = vault.lock().await;
+                                            if let Err(e) = new_config.unseal_fields(&mut v) {
+                                                warn!(error = %e, "Could not decrypt config fields");
+                                            }
                                             ModelContext::resolve(&new_config, &mut v).ok().map(Arc::new)
                                         };
 
@@ -969,6 +1245,35 @@ async fn handle_connection(
                                             ("(none)".to_string(), "(none)".to_string())
                                         };
 
+                                        tools::set_dry_run(new_config.dry_run);
+                                        tools::set_permissions(new_config.tool_permissions.clone());
+                                        tools::set_timeouts(new_config.timeouts.clone());
+                                        tools::set_schema_budget(new_config.tool_schemas.clone());
+                                        crate::model_caps::set_overrides(new_config.model_capabilities.clone());
+                                        bench::load_results(&new_config.settings_dir);
+                                        tools::set_file_index(new_config.file_index.clone(), &new_config.workspace_dir());
+                                        let embeddings_key = new_model_ctx
+                                            .as_ref()
+                                            .filter(|ctx| ctx.provider == new_config.embeddings.provider)
+                                            .and_then(|ctx| ctx.api_key.clone());
+                                        crate::embeddings::set_config(
+                                            new_config.embeddings.clone(),
+                                            &new_config.settings_dir,
+                                            embeddings_key,
+                                        );
+                                        tools::set_extra_roots(new_config.extra_roots.clone());
+                                        tools::set_write_policy(new_config.sandbox.write_policy());
+                                        crate::container::set_execution(new_config.execution.clone());
+                                        crate::dev_env::set_mode(new_config.dev_env);
+                                        crate::lsp::set_servers(new_config.lsp_servers.clone());
+                                        crate::contacts::set_config(new_config.contacts.clone(), &new_config.settings_dir);
+                                        crate::presence::set_config(new_config.presence.clone(), &new_config.workspace_dir());
+                                        crate::quiet_hours::set_config(&new_config.messengers, &new_config.workspace_dir());
+                                        crate::snapshots::set_config(new_config.snapshots.clone());
+                                        crate::trash::set_config(new_config.trash.clone(), &new_config.settings_dir);
+                                        crate::mqtt::set_config(new_config.mqtt.clone(), &new_config.workspace_dir());
+                                        crate::translate::set_config(new_config.translation.clone());
+                                        crate::scripting::set_config(&new_config);
                                         {
                                             let mut cfg = shared_config.write().await;
                                             *cfg = new_config;
@@ -976,13 +1281,15 @@ async fn handle_connection(
                                         {
                                             let mut ctx = shared_model_ctx.write().await;
                                             *ctx = new_model_ctx.clone();
+                                            providers::set_tool_model(new_model_ctx.clone());
                                         }
 
                                         send_reload_result(&mut writer, true, &provider, &model, None).await?;
 
                                         if let Some(ref ctx) = new_model_ctx {
                                             let display = crate_providers::display_name_for_provider(&ctx.provider);
-                                            let detail = format!("{} / {} (reloaded)", display, ctx.model);
+                                            let caps = crate::model_caps::lookup(&ctx.model);
+                                            let detail = format!("{} / {} ({}; reloaded)", display, ctx.model, caps.describe());
                                             protocol::server::send_status(
                                                 &mut writer,
                                                 StatusType::ModelConfigured,
@@ -993,7 +1300,7 @@ async fn handle_connection(
                                     Err(e) => {
                                         protocol::server::send_error(
                                             &mut writer,
-                                            &format!("Failed to reload config: {}", e),
+                                            Error::UserError(format!("Failed to reload config: {}", e)),
                                         ).await?;
                                     }
                                 }
@@ -1001,7 +1308,8 @@ async fn handle_connection(
                             ClientPayload::Chat { messages } => {
                                 // Re-read model_ctx from shared state for each dispatch
                                 let current_model_ctx = shared_model_ctx.read().await.clone();
-                                let workspace_dir = config.workspace_dir();
+                                // The workspace can be switched by a reload (`/workspace`).
+                                let workspace_dir = shared_config.read().await.workspace_dir();
 
                                 // Build a ChatRequest from the messages
                                 let chat_request = ChatRequest {
@@ -1013,7 +1321,14 @@ async fn handle_connection(
                                     api_key: None,
                                 };
 
-                                if let Err(err) = dispatch_text_message(
+                                // Sub-agents this turn spawns get a fresh tree budget.
+                                if let Ok(mut mgr) = crate::sessions::session_manager().lock() {
+                                    mgr.reset_tree(crate::sessions::MAIN_SESSION);
+                                }
+                                let trace_id = turn_trace::new_trace_id();
+                                let span = turn_trace::turn_span(&trace_id, "tui");
+                                let started = std::time::Instant::now();
+                                let turn = dispatch_text_message(
                                     &http,
                                     &chat_request,
                                     current_model_ctx.as_deref(),
@@ -1026,17 +1341,63 @@ async fn handle_connection(
                                     &shared_config,
                                     &approval_rx,
                                     &user_prompt_rx,
-                                )
-                                .await
-                                {
-                                    let error_frame = ServerFrame {
-                                        frame_type: ServerFrameType::Error,
-                                        payload: ServerPayload::Error {
-                                            ok: false,
-                                            message: err.to_string(),
-                                        },
-                                    };
-                                    send_frame(&mut writer, &error_frame).await?;
+                                    &negotiated,
+                                );
+                                let turn = crate::sessions::conversation_scope(connection_plan.0.clone(), turn);
+                                let result = turn_trace::scope(trace_id.clone(), turn).instrument(span.clone()).await;
+                                tools::release_file_locks(&trace_id);
+                                debug!(parent: &span, elapsed_ms = started.elapsed().as_millis() as u64, "Chat turn finished");
+                                if let Err(err) = result {
+                                    protocol::server::send_error(&mut writer, Error::from(err)).await?;
+                                }
+                            }
+                            ClientPayload::WatchSession { target } => {
+                                let Some(spec) = target else {
+                                    watched = None;
+                                    protocol::server::send_session_view(&mut writer, None).await?;
+                                    continue;
+                                };
+                                let workspace_dir = shared_config.read().await.workspace_dir();
+                                let view = session_view::WatchTarget::parse(&spec).and_then(|target| {
+                                    session_view::build_view(&target, &workspace_dir).map(|view| (target, view))
+                                });
+                                match view {
+                                    Ok((target, view)) => {
+                                        protocol::server::send_session_view(&mut writer, Some(view.clone())).await?;
+                                        watched = Some((target, Some(view)));
+                                    }
+                                    Err(e) => protocol::server::send_error(&mut writer, Error::UserError(e)).await?,
+                                }
+                            }
+                            ClientPayload::SetDryRun { enabled } => {
+                                tools::set_session_dry_run(enabled);
+                                let state = if tools::is_dry_run() { "on" } else { "off" };
+                                protocol::server::send_info(&mut writer, &format!("Dry-run mode is {} for this session.", state)).await?;
+                            }
+                            ClientPayload::EventsTail { limit, level } => {
+                                let min_level = level.and_then(|l| l.parse::<tracing::Level>().ok());
+                                let events = crate::observability::event_log::tail(limit as usize, min_level);
+                                protocol::server::send_events_tail(&mut writer, events).await?;
+                            }
+                            ClientPayload::Negotiate { protocol_version, capabilities } => {
+                                match Negotiated::negotiate(protocol_version, &capabilities) {
+                                    Ok(agreed) => {
+                                        debug!(version = agreed.version, capabilities = ?agreed.capabilities, "Client negotiated protocol");
+                                        let replay = agreed.has(version::CAP_NOTIFICATIONS)
+                                            && !negotiated.has(version::CAP_NOTIFICATIONS);
+                                        negotiated = agreed;
+                                        protocol::server::send_negotiated(&mut writer, &negotiated).await?;
+                                        if negotiated.has(version::CAP_COMPRESSION) {
+                                            writer.set_threshold(config.compression());
+                                        }
+                                        // Catch up on what happened while no client was connected.
+                                        if replay {
+                                            for notification in notifications::recent() {
+                                                protocol::server::send_notification(&mut writer, notification).await?;
+                                            }
+                                        }
+                                    }
+                                    Err(e) => protocol::server::send_error(&mut writer, Error::UserError(e)).await?,
                                 }
                             }
                             ClientPayload::Empty | ClientPayload::AuthChallenge { .. } | ClientPayload::AuthResponse { .. } | ClientPayload::ToolApprovalResponse { .. } | ClientPayload::UserPromptResponse { .. } => {
@@ -1048,14 +1409,7 @@ async fn handle_connection(
                     }
                     Message::Text(_) => {
                         // Reject text frames - only binary is supported
-                        let error_frame = ServerFrame {
-                            frame_type: ServerFrameType::Error,
-                            payload: ServerPayload::Error {
-                                ok: false,
-                                message: "Text frames are not supported. Use binary protocol.".to_string(),
-                            },
-                        };
-                        send_frame(&mut writer, &error_frame).await?;
+                        protocol::server::send_error(&mut writer, Error::Internal("Text frames are not supported. Use binary protocol.".to_string())).await?;
                     }
                     Message::Close(_) => {
                         break;
@@ -1292,22 +1646,183 @@ async fn dispatch_text_message(
     shared_config: &SharedConfig,
     approval_rx: &Arc<Mutex<tokio::sync::mpsc::Receiver<(String, bool)>>>,
     user_prompt_rx: &Arc<Mutex<tokio::sync::mpsc::Receiver<(String, bool, crate::user_prompt_types::PromptResponseValue)>>>,
+    negotiated: &Negotiated,
+) -> Result<()> {
+    // ── Write-ahead journal ─────────────────────────────────────────
+    // Record the turn before doing any work so that a crash mid-turn can
+    // be resumed on the next start.  Journal failures are never fatal.
+    let sessions_dir = shared_config.read().await.sessions_dir();
+    let turn = match TurnJournal::open(&sessions_dir)
+        .and_then(|j| j.begin(&req.messages).map(|id| (j, id)))
+    {
+        Ok(turn) => Some(turn),
+        Err(err) => {
+            warn!(error = %err, "Turn journal unavailable");
+            None
+        }
+    };
+
+    let result = run_agent_loop(
+        http,
+        req,
+        model_ctx,
+        copilot_session,
+        writer,
+        workspace_dir,
+        vault,
+        skill_mgr,
+        tool_cancel,
+        shared_config,
+        approval_rx,
+        user_prompt_rx,
+        turn.as_ref(),
+        negotiated,
+    )
+    .await;
+
+    // Only commit when the turn ran to completion.  An Err here means the
+    // connection to the client dropped, so the turn is left open for resume.
+    if result.is_ok() {
+        if let Some((journal, turn_id)) = &turn {
+            if let Err(err) = journal.commit(turn_id) {
+                warn!(error = %err, "Failed to commit turn journal");
+            }
+        }
+    }
+    result
+}
+
+/// Record tool results and assistant text into the active turn, if any.
+fn journal_round(
+    turn: Option<&(TurnJournal, String)>,
+    text: &str,
+    tool_results: &[ToolCallResult],
+) {
+    let Some((journal, turn_id)) = turn else { return };
+    let mut res = journal.record_text(turn_id, text);
+    for tr in tool_results {
+        res = res.and(journal.record_tool_result(
+            turn_id,
+            &tr.id,
+            &tr.name,
+            &tr.output,
+            tr.is_error,
+        ));
+    }
+    if let Err(err) = res {
+        warn!(error = %err, "Failed to write turn journal");
+    }
+}
+
+/// Apply the project overlay for the configured workspace to `config` and
+/// the skill manager.  Returns `true` when the model selection changed.
+async fn apply_project_overlay(config: &mut Config, skill_mgr: &SharedSkillManager) -> bool {
+    match crate::project::ProjectOverlay::for_config(config) {
+        Ok(Some(overlay)) => {
+            info!(dir = %overlay.dir.display(), "Applying project overlay");
+            overlay.apply_to_skills(&mut *skill_mgr.lock().await);
+            overlay.apply_to_config(config)
+        }
+        Ok(None) => false,
+        Err(e) => {
+            warn!(error = %e, "Ignoring invalid project overlay");
+            false
+        }
+    }
+}
+
+/// Add the project memory after the leading system prompt unless the
+/// conversation already carries it.
+fn inject_project_memory(messages: &mut Vec<ChatMessage>, workspace_dir: &std::path::Path) {
+    use crate::project::{project_memory_section, PROJECT_MEMORY_HEADER};
+
+    if messages.iter().any(|m| m.role == "system" && m.content.contains(PROJECT_MEMORY_HEADER)) {
+        return;
+    }
+    if let Some(section) = project_memory_section(workspace_dir) {
+        let at = messages.iter().take_while(|m| m.role == "system").count();
+        messages.insert(at, ChatMessage::text("system", &section));
+    }
+}
+
+/// Replace (or drop) the compact plan system message so the model always
+/// sees the latest checklist.  It sits just after the leading system prompt.
+fn sync_plan_message(messages: &mut Vec<ChatMessage>) {
+    use crate::plan::{plan_snapshot, PLAN_PROMPT_HEADER};
+
+    messages.retain(|m| !(m.role == "system" && m.content.starts_with(PLAN_PROMPT_HEADER)));
+    if let Some(plan) = plan_snapshot() {
+        let at = messages.iter().take_while(|m| m.role == "system").count();
+        messages.insert(at, ChatMessage::text("system", &plan.render_compact()));
+    }
+}
+
+/// The agentic loop behind [`dispatch_text_message`], with progress
+/// recorded into the active journal turn.
+/// Drive a tool call to completion, sending a `Progress` frame whenever
+/// a tool operation advances in the meantime (and once more at the end,
+/// so finished operations disappear from the TUI).
+async fn forward_progress<T>(writer: &mut WsWriter, run: impl std::future::Future<Output = T>) -> Result<T> {
+    let mut progress_rx = crate::progress::subscribe();
+    progress_rx.mark_unchanged();
+    tokio::pin!(run);
+    let output = loop {
+        tokio::select! {
+            output = &mut run => break output,
+            Ok(()) = progress_rx.changed() => {
+                let ops = progress_rx.borrow_and_update().clone();
+                protocol::server::send_progress(writer, ops).await?;
+            }
+        }
+    };
+    if progress_rx.has_changed().unwrap_or(false) {
+        let ops = progress_rx.borrow_and_update().clone();
+        protocol::server::send_progress(writer, ops).await?;
+    }
+    Ok(output)
+}
+
+async fn run_agent_loop(
+    http: &reqwest::Client,
+    req: &ChatRequest,
+    model_ctx: Option<&ModelContext>,
+    copilot_session: Option<&CopilotSession>,
+    writer: &mut WsWriter,
+    workspace_dir: &std::path::Path,
+    vault: &SharedVault,
+    skill_mgr: &SharedSkillManager,
+    tool_cancel: &ToolCancelFlag,
+    shared_config: &SharedConfig,
+    approval_rx: &Arc<Mutex<tokio::sync::mpsc::Receiver<(String, bool)>>>,
+    user_prompt_rx: &Arc<Mutex<tokio::sync::mpsc::Receiver<(String, bool, crate::user_prompt_types::PromptResponseValue)>>>,
+    turn: Option<&(TurnJournal, String)>,
+    negotiated: &Negotiated,
 ) -> Result<()> {
     let mut resolved = match providers::resolve_request(req.clone(), model_ctx) {
         Ok(r) => r,
         Err(msg) => {
-            let error_frame = ServerFrame {
-                frame_type: ServerFrameType::Error,
-                payload: ServerPayload::Error {
-                    ok: false,
-                    message: msg,
-                },
-            };
-            send_frame(writer, &error_frame).await.context("Failed to send error frame")?;
+            protocol::server::send_error(writer, Error::UserError(msg)).await.context("Failed to send error frame")?;
             return Ok(());
         }
     };
 
+    inject_project_memory(&mut resolved.messages, workspace_dir);
+
+    // Refuse images up front rather than let a text-only model fail on them.
+    let caps = crate::model_caps::lookup(&resolved.model);
+    let images = resolved
+        .messages
+        .iter()
+        .rfind(|m| m.role == "user")
+        .map(crate::model_caps::image_count)
+        .unwrap_or(0);
+    if images > 0 && !caps.vision {
+        let notice = crate::model_caps::no_vision_notice(&resolved.model, images);
+        protocol::server::send_error(writer, Error::UserError(notice)).await?;
+        providers::send_response_done(writer).await?;
+        return Ok(());
+    }
+
     // If we still don't have an API key, try fetching it fresh from
     // the vault.  This handles the case where a key was stored after
     // the gateway started (e.g. user entered it via the TUI dialog).
@@ -1344,14 +1859,21 @@ async fn dispatch_text_message(
         cfg.memory_flush.clone()
     };
     let mut memory_flush = MemoryFlush::new(flush_config);
+    let mut deadline = tools::TurnDeadline::start();
+    let mut loop_guard = crate::loop_guard::LoopGuard::new();
 
-    for _round in 0..MAX_TOOL_ROUNDS {
+    for round in 0..MAX_TOOL_ROUNDS {
         // ── Check for cancellation ──────────────────────────────────
         if tool_cancel.load(Ordering::Relaxed) {
             protocol::server::send_info(writer, "Tool loop cancelled by user.").await?;
             providers::send_response_done(writer).await?;
             return Ok(());
         }
+        if !deadline.allow_round() {
+            protocol::server::send_info(writer, "Turn time limit reached.").await?;
+            providers::send_response_done(writer).await?;
+            return Ok(());
+        }
 
         // Refresh the bearer token before each model call.
         // For Copilot providers, this ensures the session token is still valid.
@@ -1365,11 +1887,21 @@ async fn dispatch_text_message(
         {
             Ok(token) => resolved.api_key = token,
             Err(err) => {
-                protocol::server::send_error(writer, &format!("Token refresh failed: {}", err)).await?;
+                let error = Error::provider(&resolved.provider, None, format!("Token refresh failed: {}", err));
+                protocol::server::send_error(writer, error).await?;
                 return Ok(());
             }
         }
 
+        // ── Pick the tools offered this turn ────────────────────────
+        if round == 0 {
+            resolved.tools = providers::route_tools(http, &resolved).await;
+            if !caps.tools {
+                let notice = crate::model_caps::no_tools_notice(&resolved.model);
+                protocol::server::send_info(writer, &notice).await?;
+            }
+        }
+
         // ── Pre-compaction memory flush ─────────────────────────────
         // Check if we should trigger a memory flush before compaction
         let estimated = helpers::estimate_tokens(&resolved.messages);
@@ -1413,33 +1945,64 @@ async fn dispatch_text_message(
             }
         }
 
-        let result = if resolved.provider == "anthropic" {
-            // Anthropic: use streaming mode with writer for real-time chunks
-            providers::call_anthropic_with_tools(http, &resolved, Some(writer)).await
-        } else if resolved.provider == "google" {
-            providers::call_google_with_tools(http, &resolved).await
+        // ── Keep the current plan in the system prompt ─────────────
+        sync_plan_message(&mut resolved.messages);
+
+        // Snapshot current tool permissions (cheap clone of a HashMap).
+        let tool_permissions = {
+            let cfg = shared_config.read().await;
+            cfg.tool_permissions.clone()
+        };
+
+        // Allowed tool calls start running as soon as their arguments are
+        // complete.  Not after a loop warning: the turn may yet be aborted.
+        let mut early = if loop_guard.warned() {
+            stream::EarlyTools::disabled()
         } else {
-            providers::call_openai_with_tools(http, &resolved).await
+            stream::EarlyTools::new(workspace_dir, &deadline)
         };
 
+        // Stream text to clients that negotiated it, as it arrives.
+        let stream_to = if negotiated.has(version::CAP_STREAMING) { Some(&mut *writer) } else { None };
+        let call_started = std::time::Instant::now();
+        let result = stream::call(http, &resolved, stream_to, |tc| {
+            let allowed = tools::permission_for(&tool_permissions, &tc.name, &tc.arguments)
+                == tools::ToolPermission::Allow;
+            early.offer(tc, allowed);
+        })
+        .await;
+        stats::record_provider_call(&resolved.provider, call_started.elapsed());
+        protocol::server::send_stats(writer, stats::snapshot()).await?;
+
         let model_resp = match result {
             Ok(r) => r,
             Err(err) => {
-                protocol::server::send_error(writer, &err.to_string()).await?;
+                protocol::server::send_error(writer, Error::from(err)).await?;
                 return Ok(());
             }
         };
+        if negotiated.has(version::CAP_USAGE) {
+            protocol::server::send_usage(
+                writer,
+                usage::measure(&resolved.model, &resolved.messages, &model_resp),
+            )
+            .await?;
+        }
+
+        if let Some(rec) = crate::recording::recorder() {
+            if let Err(err) = rec.record_model_call(&resolved, &model_resp) {
+                warn!(error = %err, "Failed to record model call");
+            }
+        }
 
-        // Stream any text content to the client.
-        // For Anthropic, text is already streamed via the writer, so skip if empty.
-        // For other providers, send the accumulated text.
+        // Send the text to the client unless it was already streamed.
         trace!(
             provider = %resolved.provider,
             text_len = model_resp.text.len(),
             tool_calls = model_resp.tool_calls.len(),
             "Model response received"
         );
-        if !model_resp.text.is_empty() && resolved.provider != "anthropic" {
+        if !model_resp.text.is_empty() && !model_resp.streamed {
             trace!(chars = model_resp.text.len(), "Sending chunk to TUI");
             providers::send_chunk(writer, &model_resp.text).await?;
         }
@@ -1559,56 +2122,72 @@ async fn dispatch_text_message(
         // Reset continuation counter — model made an actual tool call
         consecutive_continues = 0;
 
+        let round = model_resp.tool_calls.iter().map(|tc| (tc.name.as_str(), &tc.arguments));
+        let loop_note = match loop_guard.check(round) {
+            crate::loop_guard::Verdict::Ok => None,
+            crate::loop_guard::Verdict::Warn(note) => Some(note),
+            crate::loop_guard::Verdict::Abort(message) => {
+                protocol::server::send_error(writer, Error::Internal(message)).await?;
+                providers::send_response_done(writer).await?;
+                return Ok(());
+            }
+        };
+
         // ── Execute each requested tool ─────────────────────────────
         let mut tool_results: Vec<ToolCallResult> = Vec::new();
+        let mut pending = stats::PendingTools::new(model_resp.tool_calls.len());
+        protocol::server::send_stats(writer, stats::snapshot()).await?;
 
-        // Snapshot current tool permissions (cheap clone of a HashMap).
-        let tool_permissions = {
-            let cfg = shared_config.read().await;
-            cfg.tool_permissions.clone()
-        };
-
+        let tool_events = negotiated.has(version::CAP_TOOL_EVENTS);
         for tc in &model_resp.tool_calls {
             // Stringify arguments once for the wire protocol (tool args are
             // inherently schemaless JSON from the LLM).
             let args_str = serde_json::to_string(&tc.arguments).unwrap_or_default();
 
             // ── Permission check ────────────────────────────────────
-            let permission = tool_permissions
-                .get(&tc.name)
-                .cloned()
-                .unwrap_or_default(); // default = Allow
+            // Default = Allow; power actions and the like default to Ask.
+            let permission = tools::permission_for(&tool_permissions, &tc.name, &tc.arguments);
 
-            let (output, is_error) = match permission {
+            let (output, error) = match permission {
                 tools::ToolPermission::Deny => {
                     // Notify the client about the denied tool call.
-                    protocol::server::send_tool_call(
-                        writer,
-                        &tc.id,
-                        &tc.name,
-                        &args_str,
-                    ).await?;
+                    if tool_events {
+                        protocol::server::send_tool_call(
+                            writer,
+                            &tc.id,
+                            &tc.name,
+                            &args_str,
+                        ).await?;
+                    }
 
                     let msg = format!(
                         "Tool '{}' is denied by user policy. The user has blocked this tool from being executed.",
                         tc.name
                     );
-                    (msg, true)
+                    (msg, Some(Error::PolicyDenied {
+                        tool: tc.name.clone(),
+                        reason: "is denied by user policy".into(),
+                    }))
                 }
                 tools::ToolPermission::SkillOnly(_) => {
                     // In direct chat, SkillOnly tools are denied.
-                    protocol::server::send_tool_call(
-                        writer,
-                        &tc.id,
-                        &tc.name,
-                        &args_str,
-                    ).await?;
+                    if tool_events {
+                        protocol::server::send_tool_call(
+                            writer,
+                            &tc.id,
+                            &tc.name,
+                            &args_str,
+                        ).await?;
+                    }
 
                     let msg = format!(
                         "Tool '{}' is restricted to skill-based invocations only. It cannot be used in direct chat.",
                         tc.name
                     );
-                    (msg, true)
+                    (msg, Some(Error::PolicyDenied {
+                        tool: tc.name.clone(),
+                        reason: "is restricted to skills".into(),
+                    }))
                 }
                 tools::ToolPermission::Ask => {
                     // Send approval request to the TUI and wait for response.
@@ -1635,89 +2214,126 @@ async fn dispatch_text_message(
 
                     if !approved {
                         // Notify the client about the denied tool call.
-                        protocol::server::send_tool_call(
-                            writer,
-                            &tc.id,
-                            &tc.name,
-                            &args_str,
-                        ).await?;
+                        if tool_events {
+                            protocol::server::send_tool_call(
+                                writer,
+                                &tc.id,
+                                &tc.name,
+                                &args_str,
+                            ).await?;
+                        }
 
                         let msg = format!(
                             "Tool '{}' was denied by the user.",
                             tc.name
                         );
-                        (msg, true)
+                        (msg, Some(Error::PolicyDenied {
+                            tool: tc.name.clone(),
+                            reason: "was denied by the user".into(),
+                        }))
                     } else {
                         // User approved — proceed with execution.
-                        protocol::server::send_tool_call(
-                            writer,
-                            &tc.id,
-                            &tc.name,
-                            &args_str,
-                        ).await?;
+                        if tool_events {
+                            protocol::server::send_tool_call(
+                                writer,
+                                &tc.id,
+                                &tc.name,
+                                &args_str,
+                            ).await?;
+                        }
 
                         if tools::is_user_prompt_tool(&tc.name) {
-                            execute_user_prompt(writer, &tc.id, &tc.arguments, user_prompt_rx).await
+                            let (text, failed) = execute_user_prompt(writer, &tc.id, &tc.arguments, user_prompt_rx).await;
+                            error::tool_outcome(&tc.name, if failed { Err(text) } else { Ok(text) })
                         } else if tools::is_secrets_tool(&tc.name) {
-                            match secrets_handler::execute_secrets_tool(&tc.name, &tc.arguments, vault).await {
-                                Ok(text) => (text, false),
-                                Err(err) => (err, true),
-                            }
+                            let run = secrets_handler::execute_secrets_tool(&tc.name, &tc.arguments, vault, workspace_dir);
+                            error::tool_outcome(&tc.name, deadline.run(&tc.name, run).await)
                         } else if tools::is_skill_tool(&tc.name) {
-                            match skills_handler::execute_skill_tool(&tc.name, &tc.arguments, skill_mgr).await {
-                                Ok(text) => (text, false),
-                                Err(err) => (err, true),
-                            }
+                            let run = skills_handler::execute_skill_tool(&tc.name, &tc.arguments, skill_mgr);
+                            error::tool_outcome(&tc.name, forward_progress(writer, deadline.run(&tc.name, run)).await?)
                         } else {
-                            match tools::execute_tool(&tc.name, &tc.arguments, workspace_dir) {
-                                Ok(text) => (text, false),
-                                Err(err) => (err, true),
-                            }
+                            let run = tools::execute_tool_offloaded(&tc.name, &tc.arguments, workspace_dir);
+                            error::tool_outcome(&tc.name, forward_progress(writer, deadline.run(&tc.name, run)).await?)
                         }
                     }
                 }
                 tools::ToolPermission::Allow => {
                     // Notify the client about the tool call.
-                    protocol::server::send_tool_call(
-                        writer,
-                        &tc.id,
-                        &tc.name,
-                        &args_str,
-                    ).await?;
+                    if tool_events {
+                        protocol::server::send_tool_call(
+                            writer,
+                            &tc.id,
+                            &tc.name,
+                            &args_str,
+                        ).await?;
+                    }
 
                     // Execute the tool.
                     if tools::is_user_prompt_tool(&tc.name) {
-                        execute_user_prompt(writer, &tc.id, &tc.arguments, user_prompt_rx).await
+                        let (text, failed) = execute_user_prompt(writer, &tc.id, &tc.arguments, user_prompt_rx).await;
+                        error::tool_outcome(&tc.name, if failed { Err(text) } else { Ok(text) })
                     } else if tools::is_secrets_tool(&tc.name) {
-                        match secrets_handler::execute_secrets_tool(&tc.name, &tc.arguments, vault).await {
-                            Ok(text) => (text, false),
-                            Err(err) => (err, true),
-                        }
+                        let run = secrets_handler::execute_secrets_tool(&tc.name, &tc.arguments, vault, workspace_dir);
+                        error::tool_outcome(&tc.name, deadline.run(&tc.name, run).await)
                     } else if tools::is_skill_tool(&tc.name) {
-                        match skills_handler::execute_skill_tool(&tc.name, &tc.arguments, skill_mgr).await {
-                            Ok(text) => (text, false),
-                            Err(err) => (err, true),
-                        }
+                        let run = skills_handler::execute_skill_tool(&tc.name, &tc.arguments, skill_mgr);
+                        error::tool_outcome(&tc.name, forward_progress(writer, deadline.run(&tc.name, run)).await?)
+                    } else if let Some(run) = early.take(&tc.id) {
+                        // Started while the model was still streaming.
+                        error::tool_outcome(&tc.name, forward_progress(writer, run).await?)
                     } else {
-                        match tools::execute_tool(&tc.name, &tc.arguments, workspace_dir) {
-                            Ok(text) => (text, false),
-                            Err(err) => (err, true),
-                        }
+                        let run = tools::execute_tool_offloaded(&tc.name, &tc.arguments, workspace_dir);
+                        error::tool_outcome(&tc.name, forward_progress(writer, deadline.run(&tc.name, run)).await?)
                     }
                 }
             };
 
+            // Files the tool produced; read before sanitizing can cut the
+            // trailing `MEDIA:` lines off a long result.
+            let media_paths = if error.is_none() && negotiated.has(version::CAP_BINARY_FRAMES) {
+                media::media_paths(&output)
+            } else {
+                Vec::new()
+            };
+
             // Sanitize the output (truncate large outputs, warn about garbage).
             let output = tools::sanitize_tool_output(output);
+            let is_error = error.is_some();
 
             // Notify the client about the result.
-            protocol::server::send_tool_result(
-                writer,
-                &tc.id,
-                &tc.name,
-                &output,
-                is_error,
-            ).await?;
+            if tool_events {
+                protocol::server::send_tool_result(
+                    writer,
+                    &tc.id,
+                    &tc.name,
+                    &output,
+                    error,
+                ).await?;
+            }
+            // Send the files themselves to clients that can't read the
+            // gateway's disk.
+            for path in media_paths {
+                let chunks = tokio::task::spawn_blocking(move || media::chunk_file(&path))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()));
+                match chunks {
+                    Ok(chunks) => protocol::server::send_media(writer, chunks).await?,
+                    Err(e) => warn!(error = %e, "Media not sent to client"),
+                }
+            }
+            pending.done();
+            protocol::server::send_stats(writer, stats::snapshot()).await?;
+
+            if let Some(rec) = crate::recording::recorder() {
+                if let Err(err) = rec.record_tool_result(tc, &output, is_error) {
+                    warn!(error = %err, "Failed to record tool result");
+                }
+            }
+
+            // Refresh the TUI checklist after the plan changes.
+            if tc.name == "plan" && !is_error {
+                protocol::server::send_plan_update(writer, crate::plan::plan_snapshot()).await?;
+            }
 
             tool_results.push(ToolCallResult {
                 id: tc.id.clone(),
@@ -1727,6 +2343,8 @@ async fn dispatch_text_message(
             });
         }
 
+        journal_round(turn, &model_resp.text, &tool_results);
+
         // ── Append assistant + tool-result messages to conversation ──
         // The model's response (possibly with text + tool calls) becomes
         // an assistant message, and each tool result becomes a tool message.
@@ -1736,12 +2354,18 @@ async fn dispatch_text_message(
             &model_resp,
             &tool_results,
         );
+        if let Some(note) = loop_note {
+            resolved.messages.push(ChatMessage::text("system", &note));
+        }
+        for tc in model_resp.tool_calls.iter().filter(|tc| tc.name == "request_tools") {
+            tools::load_requested(&mut resolved.tools, &tc.arguments);
+        }
     }
 
     // If we exhausted all rounds, send what we have and stop.
     protocol::server::send_error(
         writer,
-        &format!("Safety limit reached ({} tool rounds) — stopping to prevent infinite loop.", MAX_TOOL_ROUNDS),
+        Error::Internal(format!("Safety limit reached ({} tool rounds) — stopping to prevent infinite loop.", MAX_TOOL_ROUNDS)),
     ).await?;
     providers::send_response_done(writer).await?;
     Ok(())
@@ -0,0 +1,139 @@
+//! zstd compression of large frames.
+//!
+//! Tool results and whole conversations can run to hundreds of KB per
+//! frame, which stalls remote sessions on slow links.  Once both sides
+//! have agreed on the `compression` capability, binary messages larger
+//! than the configured threshold are sent as a zstd frame instead of raw
+//! bincode.
+//!
+//! The receiver needs no extra state: [`deserialize_frame`] recognises the
+//! zstd magic number and decompresses first.  No bincode frame starts with
+//! it — the second byte would have to be payload variant 0xB5.
+//!
+//! [`deserialize_frame`]: super::frames::deserialize_frame
+
+use futures_util::Sink;
+use std::borrow::Cow;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Magic number every zstd frame starts with.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Default threshold in bytes; see `Config::compression_threshold`.
+pub const DEFAULT_THRESHOLD: usize = 16 * 1024;
+
+/// zstd level: fast, and most of the gain on JSON-ish text.
+const LEVEL: i32 = 3;
+
+/// Largest frame accepted after decompression (tungstenite's default
+/// message size limit).
+const MAX_DECOMPRESSED: usize = 64 * 1024 * 1024;
+
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// Compress `bytes` if they're at least `threshold` long and compression
+/// actually makes them smaller.
+pub fn compress(bytes: &[u8], threshold: usize) -> Option<Vec<u8>> {
+    if bytes.len() < threshold {
+        return None;
+    }
+    zstd::bulk::compress(bytes, LEVEL)
+        .ok()
+        .filter(|compressed| compressed.len() < bytes.len())
+}
+
+/// The frame bytes, decompressed if they were compressed.
+pub fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, String> {
+    if !is_compressed(bytes) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    zstd::bulk::decompress(bytes, MAX_DECOMPRESSED)
+        .map(Cow::Owned)
+        .map_err(|e| format!("decompression failed: {}", e))
+}
+
+/// A WebSocket sink that compresses large binary messages once a
+/// threshold is set.
+pub struct CompressingSink<S> {
+    inner: S,
+    threshold: Option<usize>,
+}
+
+impl<S> CompressingSink<S> {
+    /// Starts uncompressed: the peer may not understand compression yet.
+    pub fn new(inner: S) -> Self {
+        Self { inner, threshold: None }
+    }
+
+    /// Compress binary messages of at least `threshold` bytes from now on;
+    /// `None` turns compression off.
+    pub fn set_threshold(&mut self, threshold: Option<usize>) {
+        self.threshold = threshold;
+    }
+}
+
+impl<S: Sink<Message> + Unpin> Sink<Message> for CompressingSink<S> {
+    type Error = S::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        let item = match (item, self.threshold) {
+            (Message::Binary(data), Some(threshold)) => match compress(&data, threshold) {
+                Some(compressed) => Message::Binary(compressed.into()),
+                None => Message::Binary(data),
+            },
+            (item, _) => item,
+        };
+        Pin::new(&mut self.inner).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_frames_stay_uncompressed() {
+        assert_eq!(compress(b"tiny", DEFAULT_THRESHOLD), None);
+        assert_eq!(decompress(b"tiny").unwrap(), Cow::Borrowed(&b"tiny"[..]));
+    }
+
+    #[test]
+    fn test_large_frames_roundtrip() {
+        let text = "fn main() { println!(\"hello\"); }\n".repeat(2_000);
+        let compressed = compress(text.as_bytes(), DEFAULT_THRESHOLD).expect("compresses");
+        assert!(is_compressed(&compressed));
+        assert!(compressed.len() < text.len() / 10);
+        assert_eq!(decompress(&compressed).unwrap().as_ref(), text.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_sink_compresses_after_threshold_is_set() {
+        use futures_util::SinkExt;
+        let mut sink = CompressingSink::new(Vec::<Message>::new());
+        let big = vec![b'a'; 64 * 1024];
+        sink.send(Message::Binary(big.clone().into())).await.unwrap();
+        sink.set_threshold(Some(DEFAULT_THRESHOLD));
+        sink.send(Message::Binary(big.clone().into())).await.unwrap();
+
+        let sent: Vec<_> = sink.inner.into_iter().map(Message::into_data).collect();
+        assert!(!is_compressed(&sent[0]));
+        assert!(is_compressed(&sent[1]));
+        assert_eq!(decompress(&sent[1]).unwrap().as_ref(), &big[..]);
+    }
+}
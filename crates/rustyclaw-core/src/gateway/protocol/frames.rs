@@ -46,6 +46,14 @@ pub enum ClientFrameType {
     ToolApprovalResponse = 17,
     /// User response to a structured prompt (ask_user tool).
     UserPromptResponse = 18,
+    /// Watch a sub-agent session or cron job in the split pane.
+    WatchSession = 19,
+    /// Read the gateway's recent log events.
+    EventsTail = 20,
+    /// Protocol version and capabilities of the client, sent after `Hello`.
+    Negotiate = 21,
+    /// Turn dry-run on or off for this connection only (`/dryrun`).
+    SetDryRun = 22,
 }
 
 /// Outgoing frame types from gateway to client.
@@ -114,6 +122,24 @@ pub enum ServerFrameType {
     ToolApprovalRequest = 29,
     /// Structured user prompt request (ask_user tool).
     UserPromptRequest = 30,
+    /// Agent plan changed (plan tool).
+    PlanUpdate = 31,
+    /// Live gateway stats for the TUI header.
+    Stats = 32,
+    /// View of the watched session for the split pane.
+    SessionView = 33,
+    /// Long-running tool operations in flight (progress bars).
+    Progress = 34,
+    /// A background event for the notification center.
+    Notification = 35,
+    /// Context usage and cost of a model call.
+    Usage = 36,
+    /// Recent gateway log events.
+    EventsTailResult = 37,
+    /// Version and capabilities agreed with the client.
+    Negotiated = 38,
+    /// Part of a file produced by a tool (audio, screenshot, …).
+    MediaChunk = 39,
 }
 
 /// Status frame sub-types.
@@ -207,6 +233,24 @@ pub enum ClientPayload {
         dismissed: bool,
         value: crate::user_prompt_types::PromptResponseValue,
     },
+    /// `session <key|label>` or `cron <jobId>`; `None` stops watching.
+    WatchSession {
+        target: Option<String>,
+    },
+    /// The last `limit` log events at `level` (e.g. `warn`) or above.
+    EventsTail {
+        limit: u32,
+        level: Option<String>,
+    },
+    /// The client's protocol version and the capabilities it wants.
+    Negotiate {
+        protocol_version: u32,
+        capabilities: Vec<String>,
+    },
+    /// `None` goes back to the configured setting.
+    SetDryRun {
+        enabled: Option<bool>,
+    },
 }
 
 /// Generic server frame envelope.
@@ -226,6 +270,10 @@ pub enum ServerPayload {
         vault_locked: bool,
         provider: Option<String>,
         model: Option<String>,
+        // Fields added in protocol version 2 go last: version 1 clients
+        // stop decoding after `model` and ignore the rest.
+        protocol_version: u32,
+        capabilities: Vec<String>,
     },
     AuthChallenge {
         method: String,
@@ -307,6 +355,9 @@ pub enum ServerPayload {
     Error {
         ok: bool,
         message: String,
+        // Added in protocol version 2, last like `Hello`'s new fields so
+        // version 1 clients stop after `message`.
+        error: crate::error::Error,
     },
     Info {
         message: String,
@@ -330,6 +381,9 @@ pub enum ServerPayload {
         name: String,
         result: String,
         is_error: bool,
+        /// Why the call failed or was denied; `None` on success.  Version 2
+        /// only, after the fields version 1 clients decode.
+        error: Option<crate::error::Error>,
     },
     ResponseDone {
         ok: bool,
@@ -343,6 +397,43 @@ pub enum ServerPayload {
         id: String,
         prompt: crate::user_prompt_types::UserPrompt,
     },
+    /// Current plan, or `None` once cleared.
+    PlanUpdate {
+        plan: Option<crate::plan::Plan>,
+    },
+    /// Provider latency, pending tools, messenger queue and due cron jobs.
+    Stats {
+        stats: crate::gateway::stats::GatewayStats,
+    },
+    /// Current view of the watched target, or `None` once unwatched.
+    SessionView {
+        view: Option<crate::gateway::session_view::SessionView>,
+    },
+    /// Every tool operation in flight; empty once they've all finished.
+    Progress {
+        ops: Vec<crate::progress::ProgressOp>,
+    },
+    /// A background event (sub-agent, cron, pairing, messenger).
+    Notification {
+        notification: crate::notifications::Notification,
+    },
+    /// Tokens and cost of the model call that just finished.
+    Usage {
+        usage: crate::gateway::usage::Usage,
+    },
+    /// Recent log events, oldest first.
+    EventsTailResult {
+        events: Vec<crate::observability::event_log::LogEvent>,
+    },
+    /// Reply to `Negotiate`.
+    Negotiated {
+        protocol_version: u32,
+        capabilities: Vec<String>,
+    },
+    /// Part of a file named by a tool result's `MEDIA:` line.
+    MediaChunk {
+        chunk: crate::gateway::media::MediaChunk,
+    },
 }
 
 /// DTO for secret entries in list results.
@@ -364,9 +455,11 @@ pub fn serialize_frame<T: serde::Serialize>(frame: &T) -> Result<Vec<u8>, String
     bincode::serde::encode_to_vec(frame, bincode::config::standard()).map_err(|e| e.to_string())
 }
 
-/// Deserialize a frame from binary using bincode with serde.
+/// Deserialize a frame from binary using bincode with serde, decompressing
+/// it first if it was sent compressed.
 pub fn deserialize_frame<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
-    let (result, _) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+    let bytes = super::compression::decompress(bytes)?;
+    let (result, _) = bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
         .map_err(|e| e.to_string())?;
     Ok(result)
 }
@@ -433,6 +526,15 @@ mod tests {
             assert_eq!(ServerFrameType::ResponseDone as u8, 28);
             assert_eq!(ServerFrameType::ToolApprovalRequest as u8, 29);
             assert_eq!(ServerFrameType::UserPromptRequest as u8, 30);
+            assert_eq!(ServerFrameType::PlanUpdate as u8, 31);
+            assert_eq!(ServerFrameType::Stats as u8, 32);
+            assert_eq!(ServerFrameType::SessionView as u8, 33);
+            assert_eq!(ServerFrameType::Progress as u8, 34);
+            assert_eq!(ServerFrameType::Notification as u8, 35);
+            assert_eq!(ServerFrameType::Usage as u8, 36);
+            assert_eq!(ServerFrameType::EventsTailResult as u8, 37);
+            assert_eq!(ServerFrameType::Negotiated as u8, 38);
+            assert_eq!(ServerFrameType::MediaChunk as u8, 39);
         }
 
         #[test]
@@ -456,6 +558,10 @@ mod tests {
             assert_eq!(ClientFrameType::Chat as u8, 16);
             assert_eq!(ClientFrameType::ToolApprovalResponse as u8, 17);
             assert_eq!(ClientFrameType::UserPromptResponse as u8, 18);
+            assert_eq!(ClientFrameType::WatchSession as u8, 19);
+            assert_eq!(ClientFrameType::EventsTail as u8, 20);
+            assert_eq!(ClientFrameType::Negotiate as u8, 21);
+            assert_eq!(ClientFrameType::SetDryRun as u8, 22);
         }
 
         #[test]
@@ -480,6 +586,8 @@ mod tests {
                     vault_locked: false,
                     provider: Some("anthropic".into()),
                     model: Some("claude-3".into()),
+                    protocol_version: 2,
+                    capabilities: vec!["streaming".into(), "usage".into()],
                 },
             };
 
@@ -494,17 +602,184 @@ mod tests {
                     vault_locked,
                     provider,
                     model,
+                    protocol_version,
+                    capabilities,
                 } => {
                     assert_eq!(agent, "test-agent");
                     assert_eq!(settings_dir, "/tmp/settings");
                     assert!(!vault_locked);
                     assert_eq!(provider, Some("anthropic".into()));
                     assert_eq!(model, Some("claude-3".into()));
+                    assert_eq!(protocol_version, 2);
+                    assert_eq!(capabilities, vec!["streaming".to_string(), "usage".to_string()]);
                 }
                 _ => panic!("Expected Hello payload"),
             }
         }
 
+        #[test]
+        fn test_hello_decodes_with_version_one_layout() {
+            // The Hello a version 1 client was built against.
+            #[derive(Deserialize)]
+            enum V1Payload {
+                #[allow(dead_code)]
+                Empty,
+                Hello {
+                    agent: String,
+                    settings_dir: String,
+                    vault_locked: bool,
+                    provider: Option<String>,
+                    model: Option<String>,
+                },
+            }
+            #[derive(Deserialize)]
+            struct V1Frame {
+                frame_type: ServerFrameType,
+                payload: V1Payload,
+            }
+
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Hello,
+                payload: ServerPayload::Hello {
+                    agent: "rustyclaw".into(),
+                    settings_dir: "/tmp/settings".into(),
+                    vault_locked: true,
+                    provider: None,
+                    model: Some("gpt-4.1".into()),
+                    protocol_version: 2,
+                    capabilities: vec!["tool-events".into()],
+                },
+            };
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: V1Frame = deserialize_frame(&bytes).expect("version 1 layout should decode");
+            assert_eq!(decoded.frame_type, ServerFrameType::Hello);
+            match decoded.payload {
+                V1Payload::Hello { agent, settings_dir, vault_locked, provider, model } => {
+                    assert_eq!(agent, "rustyclaw");
+                    assert_eq!(settings_dir, "/tmp/settings");
+                    assert!(vault_locked);
+                    assert_eq!(provider, None);
+                    assert_eq!(model.as_deref(), Some("gpt-4.1"));
+                }
+                V1Payload::Empty => panic!("Expected Hello payload"),
+            }
+        }
+
+        #[test]
+        fn test_errors_decode_with_version_one_layout() {
+            // Error and ToolResult as a version 1 client was built against;
+            // the unit variants only hold the other payloads' indices.
+            #[allow(dead_code)]
+            #[derive(Deserialize)]
+            enum V1Payload {
+                P0, P1, P2, P3, P4, P5, P6, P7, P8, P9,
+                P10, P11, P12, P13, P14, P15, P16, P17, P18, P19,
+                Error { ok: bool, message: String },
+                P21, P22, P23, P24, P25, P26, P27,
+                ToolResult { id: String, name: String, result: String, is_error: bool },
+            }
+            #[derive(Deserialize)]
+            struct V1Frame {
+                frame_type: ServerFrameType,
+                payload: V1Payload,
+            }
+
+            let error = crate::error::Error::provider("openai", Some(429), "slow down");
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Error,
+                payload: ServerPayload::Error { ok: false, message: error.to_string(), error: error.clone() },
+            };
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: V1Frame = deserialize_frame(&bytes).expect("version 1 layout should decode");
+            assert_eq!(decoded.frame_type, ServerFrameType::Error);
+            match decoded.payload {
+                V1Payload::Error { ok, message } => {
+                    assert!(!ok);
+                    assert_eq!(message, error.to_string());
+                }
+                _ => panic!("Expected Error payload"),
+            }
+
+            let denied = crate::error::Error::PolicyDenied {
+                tool: "execute_command".into(),
+                reason: "is denied by user policy".into(),
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::ToolResult,
+                payload: ServerPayload::ToolResult {
+                    id: "call_1".into(),
+                    name: "execute_command".into(),
+                    result: "denied".into(),
+                    is_error: true,
+                    error: Some(denied.clone()),
+                },
+            };
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: V1Frame = deserialize_frame(&bytes).expect("version 1 layout should decode");
+            match decoded.payload {
+                V1Payload::ToolResult { id, name, result, is_error } => {
+                    assert_eq!(id, "call_1");
+                    assert_eq!(name, "execute_command");
+                    assert_eq!(result, "denied");
+                    assert!(is_error);
+                }
+                _ => panic!("Expected ToolResult payload"),
+            }
+
+            // A version 2 client still gets the classified error.
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            match decoded.payload {
+                ServerPayload::ToolResult { error, .. } => assert_eq!(error, Some(denied)),
+                _ => panic!("Expected ToolResult payload"),
+            }
+        }
+
+        #[test]
+        fn test_set_dry_run_roundtrip() {
+            let frame = ClientFrame {
+                frame_type: ClientFrameType::SetDryRun,
+                payload: ClientPayload::SetDryRun { enabled: Some(true) },
+            };
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ClientFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            assert_eq!(decoded.frame_type, ClientFrameType::SetDryRun);
+            match decoded.payload {
+                ClientPayload::SetDryRun { enabled } => assert_eq!(enabled, Some(true)),
+                _ => panic!("Expected SetDryRun payload"),
+            }
+        }
+
+        #[test]
+        fn test_negotiate_roundtrip() {
+            let request = ClientFrame {
+                frame_type: ClientFrameType::Negotiate,
+                payload: ClientPayload::Negotiate {
+                    protocol_version: 2,
+                    capabilities: vec!["notifications".into()],
+                },
+            };
+            let bytes = serialize_frame(&request).expect("serialize should succeed");
+            let decoded: ClientFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            match decoded.payload {
+                ClientPayload::Negotiate { protocol_version, capabilities } => {
+                    assert_eq!(protocol_version, 2);
+                    assert_eq!(capabilities, vec!["notifications".to_string()]);
+                }
+                _ => panic!("Expected Negotiate payload"),
+            }
+
+            let reply = ServerFrame {
+                frame_type: ServerFrameType::Negotiated,
+                payload: ServerPayload::Negotiated { protocol_version: 2, capabilities: vec![] },
+            };
+            let bytes = serialize_frame(&reply).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            assert!(matches!(
+                decoded.payload,
+                ServerPayload::Negotiated { protocol_version: 2, ref capabilities } if capabilities.is_empty()
+            ));
+        }
+
         #[test]
         fn test_server_frame_roundtrip_chunk() {
             let frame = ServerFrame {
@@ -696,6 +971,200 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_server_frame_roundtrip_plan_update() {
+            let mut plan = crate::plan::Plan::new("Fix bug", &["Reproduce".into(), "Patch".into()]);
+            plan.set_status(1, crate::plan::StepStatus::Done).unwrap();
+
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::PlanUpdate,
+                payload: ServerPayload::PlanUpdate {
+                    plan: Some(plan.clone()),
+                },
+            };
+
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+
+            assert_eq!(decoded.frame_type, ServerFrameType::PlanUpdate);
+            match decoded.payload {
+                ServerPayload::PlanUpdate { plan: p } => assert_eq!(p, Some(plan)),
+                _ => panic!("Expected PlanUpdate payload"),
+            }
+        }
+
+        #[test]
+        fn test_server_frame_roundtrip_stats() {
+            let stats = crate::gateway::stats::GatewayStats {
+                last_provider: Some("anthropic".into()),
+                last_latency_ms: Some(1840),
+                pending_tool_calls: 2,
+                messenger_queue: 1,
+                cron_due_soon: 1,
+                next_cron_job: Some("backup".into()),
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Stats,
+                payload: ServerPayload::Stats { stats: stats.clone() },
+            };
+
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+
+            assert_eq!(decoded.frame_type, ServerFrameType::Stats);
+            match decoded.payload {
+                ServerPayload::Stats { stats: s } => assert_eq!(s, stats),
+                _ => panic!("Expected Stats payload"),
+            }
+        }
+
+        #[test]
+        fn test_server_frame_roundtrip_session_view() {
+            use crate::gateway::session_view::{SessionView, ViewLine};
+            let view = SessionView {
+                title: "research".into(),
+                status: "active".into(),
+                lines: vec![ViewLine { role: "assistant".into(), text: "Found 3 papers".into() }],
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::SessionView,
+                payload: ServerPayload::SessionView { view: Some(view.clone()) },
+            };
+
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+
+            match decoded.payload {
+                ServerPayload::SessionView { view: v } => assert_eq!(v, Some(view)),
+                _ => panic!("Expected SessionView payload"),
+            }
+        }
+
+        #[test]
+        fn test_server_frame_roundtrip_progress() {
+            use crate::progress::{ProgressOp, Unit};
+            let op = ProgressOp {
+                id: 3,
+                label: "Installing skill weather".into(),
+                unit: Unit::Bytes,
+                done: 4096,
+                total: Some(16384),
+                elapsed_ms: 1200,
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Progress,
+                payload: ServerPayload::Progress { ops: vec![op.clone()] },
+            };
+
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+
+            match decoded.payload {
+                ServerPayload::Progress { ops } => assert_eq!(ops, vec![op]),
+                _ => panic!("Expected Progress payload"),
+            }
+        }
+
+        #[test]
+        fn test_server_frame_roundtrip_notification() {
+            use crate::notifications::{Notification, Severity, Source};
+            let notification = Notification {
+                id: 9,
+                severity: Severity::Warning,
+                source: Source::Messenger,
+                title: "telegram error".into(),
+                body: "Failed to send response".into(),
+                session: None,
+                at_ms: 1_700_000_000_000,
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Notification,
+                payload: ServerPayload::Notification { notification: notification.clone() },
+            };
+
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+
+            match decoded.payload {
+                ServerPayload::Notification { notification: n } => assert_eq!(n, notification),
+                _ => panic!("Expected Notification payload"),
+            }
+        }
+
+        #[test]
+        fn test_server_frame_roundtrip_usage() {
+            let usage = crate::gateway::usage::Usage {
+                model: "claude-sonnet-4".into(),
+                context_tokens: 42_000,
+                context_window: 200_000,
+                prompt_tokens: 42_000,
+                completion_tokens: 800,
+                cost_usd: Some(0.138),
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Usage,
+                payload: ServerPayload::Usage { usage: usage.clone() },
+            };
+
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+
+            match decoded.payload {
+                ServerPayload::Usage { usage: u } => assert_eq!(u, usage),
+                _ => panic!("Expected Usage payload"),
+            }
+        }
+
+        #[test]
+        fn test_events_tail_roundtrip() {
+            let request = ClientFrame {
+                frame_type: ClientFrameType::EventsTail,
+                payload: ClientPayload::EventsTail { limit: 50, level: Some("warn".into()) },
+            };
+            let bytes = serialize_frame(&request).expect("serialize should succeed");
+            let decoded: ClientFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            match decoded.payload {
+                ClientPayload::EventsTail { limit, level } => {
+                    assert_eq!(limit, 50);
+                    assert_eq!(level.as_deref(), Some("warn"));
+                }
+                _ => panic!("Expected EventsTail payload"),
+            }
+
+            let event = crate::observability::event_log::LogEvent {
+                at_ms: 1_700_000_000_000,
+                level: "WARN".into(),
+                target: "rustyclaw_core::gateway::messenger_handler".into(),
+                message: "Failed to send response error=timeout".into(),
+            };
+            let result = ServerFrame {
+                frame_type: ServerFrameType::EventsTailResult,
+                payload: ServerPayload::EventsTailResult { events: vec![event.clone()] },
+            };
+            let bytes = serialize_frame(&result).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            match decoded.payload {
+                ServerPayload::EventsTailResult { events } => assert_eq!(events, vec![event]),
+                _ => panic!("Expected EventsTailResult payload"),
+            }
+        }
+
+        #[test]
+        fn test_media_chunk_roundtrip() {
+            let chunk = crate::gateway::media::chunk_bytes("speech.mp3", "audio/mpeg", &[0, 255, 7, 42])
+                .remove(0);
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::MediaChunk,
+                payload: ServerPayload::MediaChunk { chunk: chunk.clone() },
+            };
+            let bytes = serialize_frame(&frame).expect("serialize should succeed");
+            let decoded: ServerFrame = deserialize_frame(&bytes).expect("deserialize should succeed");
+            match decoded.payload {
+                ServerPayload::MediaChunk { chunk: c } => assert_eq!(c, chunk),
+                _ => panic!("Expected MediaChunk payload"),
+            }
+        }
+
         #[test]
         fn test_client_frame_roundtrip_auth_response() {
             let frame = ClientFrame {
@@ -10,9 +10,11 @@
 //! Each frame has a type enum as the first field to allow dispatch.
 //! Text frames are not supported and will be rejected.
 
+pub mod compression;
 pub mod frames;
 pub mod server;
 pub mod types;
+pub mod version;
 
 pub use frames::{
     ClientFrame, ClientFrameType, ClientPayload, SecretEntryDto, ServerFrame, ServerFrameType,
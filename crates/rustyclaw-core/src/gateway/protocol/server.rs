@@ -28,7 +28,8 @@ pub fn parse_client_frame(bytes: &[u8]) -> Result<ClientFrame> {
     deserialize_frame(bytes).map_err(|e| anyhow::anyhow!("parse failed: {}", e))
 }
 
-/// Build and send a hello frame.
+/// Build and send a hello frame, announcing this build's protocol
+/// version and capabilities.
 pub async fn send_hello<S>(
     writer: &mut S,
     agent: &str,
@@ -48,6 +49,8 @@ where
             vault_locked,
             provider: provider.map(|s| s.into()),
             model: model.map(|s| s.into()),
+            protocol_version: super::version::PROTOCOL_VERSION,
+            capabilities: super::version::capability_list(super::version::CAPABILITIES),
         },
     };
     send_frame(writer, &frame).await
@@ -89,7 +92,7 @@ where
 }
 
 /// Build and send an error frame.
-pub async fn send_error<S>(writer: &mut S, message: &str) -> Result<()>
+pub async fn send_error<S>(writer: &mut S, error: crate::error::Error) -> Result<()>
 where
     S: SinkExt<Message> + Unpin,
 {
@@ -97,7 +100,8 @@ where
         frame_type: ServerFrameType::Error,
         payload: ServerPayload::Error {
             ok: false,
-            message: message.into(),
+            message: error.to_string(),
+            error,
         },
     };
     send_frame(writer, &frame).await
@@ -465,7 +469,7 @@ pub async fn send_tool_result<S>(
     id: &str,
     name: &str,
     result: &str,
-    is_error: bool,
+    error: Option<crate::error::Error>,
 ) -> Result<()>
 where
     S: SinkExt<Message> + Unpin,
@@ -476,7 +480,8 @@ where
             id: id.into(),
             name: name.into(),
             result: result.into(),
-            is_error,
+            is_error: error.is_some(),
+            error,
         },
     };
     send_frame(writer, &frame).await
@@ -521,3 +526,120 @@ where
     };
     send_frame(writer, &frame).await
 }
+
+/// Build and send a plan update frame.
+pub async fn send_plan_update<S>(writer: &mut S, plan: Option<crate::plan::Plan>) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::PlanUpdate,
+        payload: ServerPayload::PlanUpdate { plan },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Build and send a gateway stats frame.
+pub async fn send_stats<S>(writer: &mut S, stats: crate::gateway::stats::GatewayStats) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::Stats,
+        payload: ServerPayload::Stats { stats },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Build and send a split-pane session view frame.
+pub async fn send_session_view<S>(
+    writer: &mut S,
+    view: Option<crate::gateway::session_view::SessionView>,
+) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::SessionView,
+        payload: ServerPayload::SessionView { view },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Build and send a frame listing the tool operations in flight.
+pub async fn send_progress<S>(writer: &mut S, ops: Vec<crate::progress::ProgressOp>) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::Progress,
+        payload: ServerPayload::Progress { ops },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Send a notification-center event.
+pub async fn send_notification<S>(writer: &mut S, notification: crate::notifications::Notification) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::Notification,
+        payload: ServerPayload::Notification { notification },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Report the context usage and cost of a finished model call.
+pub async fn send_usage<S>(writer: &mut S, usage: crate::gateway::usage::Usage) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::Usage,
+        payload: ServerPayload::Usage { usage },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Send the requested tail of the gateway's event log.
+pub async fn send_events_tail<S>(writer: &mut S, events: Vec<crate::observability::event_log::LogEvent>) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::EventsTailResult,
+        payload: ServerPayload::EventsTailResult { events },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Build and send the reply to a client's `Negotiate` frame.
+pub async fn send_negotiated<S>(writer: &mut S, negotiated: &super::version::Negotiated) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    let frame = ServerFrame {
+        frame_type: ServerFrameType::Negotiated,
+        payload: ServerPayload::Negotiated {
+            protocol_version: negotiated.version,
+            capabilities: negotiated.capabilities.clone(),
+        },
+    };
+    send_frame(writer, &frame).await
+}
+
+/// Stream a file to the client as `MediaChunk` frames.
+pub async fn send_media<S>(writer: &mut S, chunks: Vec<crate::gateway::media::MediaChunk>) -> Result<()>
+where
+    S: SinkExt<Message> + Unpin,
+{
+    for chunk in chunks {
+        let frame = ServerFrame {
+            frame_type: ServerFrameType::MediaChunk,
+            payload: ServerPayload::MediaChunk { chunk },
+        };
+        send_frame(writer, &frame).await?;
+    }
+    Ok(())
+}
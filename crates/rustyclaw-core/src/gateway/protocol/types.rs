@@ -163,4 +163,7 @@ pub struct ModelResponse {
     /// Token counts reported by the provider (when available).
     pub prompt_tokens: Option<u64>,
     pub completion_tokens: Option<u64>,
+    /// Whether `text` was already sent to the client as chunk frames.
+    #[serde(skip)]
+    pub streamed: bool,
 }
@@ -0,0 +1,120 @@
+//! Protocol versioning and capability negotiation.
+//!
+//! The gateway announces [`PROTOCOL_VERSION`] and the capabilities it
+//! supports in its `Hello` frame.  A client that understands negotiation
+//! answers with a `Negotiate` frame carrying its own version and
+//! capabilities; the gateway replies with the agreed set and from then on
+//! only sends frames the client asked for.
+//!
+//! Clients built before negotiation existed never send `Negotiate`.  They
+//! get [`Negotiated::legacy`]: the frames protocol version 1 had, and
+//! nothing newer that they could not decode.
+
+use serde::{Deserialize, Serialize};
+
+/// Version spoken by this build.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest client version the gateway still serves.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Response text arrives as `StreamStart` / `Chunk` frames.
+pub const CAP_STREAMING: &str = "streaming";
+/// `ToolCall` / `ToolResult` frames for every tool the agent runs.
+pub const CAP_TOOL_EVENTS: &str = "tool-events";
+/// Binary payloads (images, audio) in their own frames.
+pub const CAP_BINARY_FRAMES: &str = "binary-frames";
+/// `Notification` frames for the notification center.
+pub const CAP_NOTIFICATIONS: &str = "notifications";
+/// `Usage` frames after each model call.
+pub const CAP_USAGE: &str = "usage";
+/// Large binary messages may be zstd-compressed, in both directions.
+pub const CAP_COMPRESSION: &str = "compression";
+
+/// Every capability this gateway can provide.
+pub const CAPABILITIES: &[&str] = &[
+    CAP_STREAMING,
+    CAP_TOOL_EVENTS,
+    CAP_BINARY_FRAMES,
+    CAP_NOTIFICATIONS,
+    CAP_USAGE,
+    CAP_COMPRESSION,
+];
+
+/// What a version 1 client understands.
+const LEGACY_CAPABILITIES: &[&str] = &[CAP_STREAMING, CAP_TOOL_EVENTS];
+
+/// The version and capabilities agreed with one client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Negotiated {
+    pub version: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl Negotiated {
+    /// For clients that never negotiate.
+    pub fn legacy() -> Self {
+        Self {
+            version: MIN_PROTOCOL_VERSION,
+            capabilities: LEGACY_CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Agree on the lower of the two versions and the capabilities both
+    /// sides support.  Unknown client capabilities are dropped.
+    pub fn negotiate(client_version: u32, client_capabilities: &[String]) -> Result<Self, String> {
+        if client_version < MIN_PROTOCOL_VERSION {
+            return Err(format!(
+                "Protocol version {} is no longer supported (gateway speaks {}–{}); please upgrade the client",
+                client_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ));
+        }
+        let capabilities = client_capabilities
+            .iter()
+            .filter(|c| CAPABILITIES.contains(&c.as_str()))
+            .cloned()
+            .collect();
+        Ok(Self {
+            version: client_version.min(PROTOCOL_VERSION),
+            capabilities,
+        })
+    }
+
+    pub fn has(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+/// Capabilities as owned strings, for `Hello` and `Negotiate` frames.
+pub fn capability_list(capabilities: &[&str]) -> Vec<String> {
+    capabilities.iter().map(|c| c.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_clients_get_version_one_frames() {
+        let legacy = Negotiated::legacy();
+        assert_eq!(legacy.version, 1);
+        assert!(legacy.has(CAP_TOOL_EVENTS));
+        assert!(!legacy.has(CAP_NOTIFICATIONS));
+        assert!(!legacy.has(CAP_USAGE));
+        assert!(!legacy.has(CAP_COMPRESSION));
+    }
+
+    #[test]
+    fn test_negotiate_intersects_capabilities() {
+        let client = capability_list(&[CAP_STREAMING, CAP_USAGE, "holograms"]);
+        let agreed = Negotiated::negotiate(PROTOCOL_VERSION + 1, &client).unwrap();
+        assert_eq!(agreed.version, PROTOCOL_VERSION);
+        assert_eq!(agreed.capabilities, capability_list(&[CAP_STREAMING, CAP_USAGE]));
+        assert!(!agreed.has(CAP_TOOL_EVENTS));
+    }
+
+    #[test]
+    fn test_negotiate_rejects_old_versions() {
+        assert!(Negotiated::negotiate(0, &[]).is_err());
+    }
+}
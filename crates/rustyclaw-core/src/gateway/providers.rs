@@ -1,13 +1,17 @@
 use anyhow::{Context, Result};
 use serde_json::json;
-use tracing::{debug, trace, warn};
+use std::sync::Arc;
+use tracing::{debug, instrument, warn};
 
+use super::mock_provider;
 use super::protocol::server;
+use super::stream;
 use super::types::{
     ChatMessage, CopilotSession, ModelContext, ModelResponse, ParsedToolCall, ProviderRequest,
     ProbeResult, ToolCallResult,
 };
 use super::{ServerFrame, ServerFrameType, ServerPayload, WsWriter};
+use crate::error::Error;
 use crate::providers;
 use crate::tools;
 
@@ -60,6 +64,18 @@ pub async fn send_with_retry(
     }
 }
 
+/// [`send_with_retry`], turning a non-success status into a provider error
+/// carrying the response body.
+pub async fn send_checked(builder: reqwest::RequestBuilder, provider: &str) -> Result<reqwest::Response> {
+    let resp = send_with_retry(builder).await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(Error::provider(provider, Some(status.as_u16()), text).into());
+    }
+    Ok(resp)
+}
+
 // ── Streaming helpers ───────────────────────────────────────────────────────
 
 /// Send a single chunk frame as binary.
@@ -163,6 +179,7 @@ pub fn resolve_request(
         provider,
         base_url,
         api_key,
+        tools: None,
     })
 }
 
@@ -383,15 +400,10 @@ pub async fn compact_conversation(
         provider: resolved.provider.clone(),
         base_url: resolved.base_url.clone(),
         api_key: resolved.api_key.clone(),
+        tools: Some(Vec::new()),
     };
 
-    let summary_result = if resolved.provider == "anthropic" {
-        call_anthropic_with_tools(http, &summary_req, None).await
-    } else if resolved.provider == "google" {
-        call_google_with_tools(http, &summary_req).await
-    } else {
-        call_openai_with_tools(http, &summary_req).await
-    };
+    let summary_result = call_model(http, &summary_req).await;
 
     let summary = match summary_result {
         Ok(resp) if !resp.text.is_empty() => resp.text,
@@ -435,6 +447,114 @@ pub async fn compact_conversation(
     Ok(())
 }
 
+// ── Tool routing ────────────────────────────────────────────────────────────
+
+/// The tools to offer at the start of a turn: the router's picks, and the
+/// core tools when tools are loaded lazily.  `None` offers every tool;
+/// models that can't call tools get none (see [`crate::model_caps`]).
+pub async fn route_tools(http: &reqwest::Client, resolved: &ProviderRequest) -> Option<Vec<String>> {
+    crate::model_caps::refresh_in_background(
+        &resolved.provider,
+        &resolved.base_url,
+        resolved.api_key.as_deref(),
+        &resolved.model,
+    );
+    if !crate::model_caps::lookup(&resolved.model).tools {
+        debug!(model = %resolved.model, "Model doesn't support tools; offering none");
+        return Some(Vec::new());
+    }
+    tools::turn_tools(pick_tools(http, resolved).await)
+}
+
+/// Ask the router model which tools this turn needs.
+///
+/// Returns `None` when the router is off, there is no user message, or the
+/// router fails or replies with nothing usable.
+async fn pick_tools(http: &reqwest::Client, resolved: &ProviderRequest) -> Option<Vec<String>> {
+    let config = tools::schema_budget().router;
+    if !config.enabled {
+        return None;
+    }
+    let request = resolved.messages.iter().rev().find(|m| m.role == "user")?;
+    let catalog = tools::tool_catalog();
+    let known: Vec<String> = catalog.iter().map(|(name, _)| name.clone()).collect();
+    let prompt = tools::router_prompt(&request.content, &catalog, config.max_tools);
+    let router_req = ProviderRequest {
+        messages: vec![ChatMessage::text("user", &prompt)],
+        model: config
+            .model
+            .clone()
+            .or_else(|| super::bench::fastest_model(&resolved.provider))
+            .unwrap_or_else(|| resolved.model.clone()),
+        provider: resolved.provider.clone(),
+        base_url: resolved.base_url.clone(),
+        api_key: resolved.api_key.clone(),
+        tools: Some(Vec::new()),
+    };
+
+    let result = call_model(http, &router_req).await;
+    let reply = match result {
+        Ok(resp) => resp.text,
+        Err(err) => {
+            warn!(error = %err, "Tool router failed, offering all tools");
+            return None;
+        }
+    };
+
+    let mut picked = tools::parse_router_reply(&reply, &known, config.max_tools);
+    if picked.is_empty() && !reply.to_lowercase().contains("none") {
+        warn!(reply = %reply, "Tool router reply named no tools, offering all tools");
+        return None;
+    }
+    for name in config.always {
+        if !picked.contains(&name) {
+            picked.push(name);
+        }
+    }
+    debug!(tools = ?picked, "Tool router picked this turn's tools");
+    Some(picked)
+}
+
+// ── Model access for tools ──────────────────────────────────────────────────
+
+/// The model tools use for their own one-shot completions (`review_diff`).
+/// Kept in step with the gateway's model context on startup and reload.
+static TOOL_MODEL: std::sync::RwLock<Option<Arc<ModelContext>>> = std::sync::RwLock::new(None);
+
+/// Set the model tools complete with; `None` when no model is configured.
+pub fn set_tool_model(ctx: Option<Arc<ModelContext>>) {
+    if let Ok(mut guard) = TOOL_MODEL.write() {
+        *guard = ctx;
+    }
+}
+
+/// Send a single prompt, without tools, to the configured model and
+/// return its reply.
+///
+/// Blocks on the gateway runtime, so only call it from blocking tool code.
+pub fn complete_blocking(prompt: &str) -> std::result::Result<String, String> {
+    let ctx = TOOL_MODEL
+        .read()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .ok_or_else(|| "No model is configured".to_string())?;
+    let handle = tokio::runtime::Handle::try_current()
+        .map_err(|_| "Calling the model requires the gateway runtime".to_string())?;
+    let req = ProviderRequest {
+        messages: vec![ChatMessage::text("user", prompt)],
+        model: ctx.model.clone(),
+        provider: ctx.provider.clone(),
+        base_url: ctx.base_url.clone(),
+        api_key: ctx.api_key.clone(),
+        tools: Some(Vec::new()),
+    };
+    let http = reqwest::Client::new();
+    handle
+        .block_on(call_model(&http, &req))
+        .map(|resp| resp.text)
+        .map_err(|e| format!("Model request failed: {}", e))
+}
+
 // ── Model connection probe ──────────────────────────────────────────────────
 
 /// Validate the model connection by probing the provider.
@@ -455,6 +575,19 @@ pub async fn validate_model_connection(
     ctx: &ModelContext,
     copilot_session: Option<&CopilotSession>,
 ) -> ProbeResult {
+    // The mock provider needs no network; only check its fixture parses.
+    if ctx.provider == mock_provider::MOCK_PROVIDER {
+        if ctx.base_url.trim().is_empty() {
+            return ProbeResult::Ready;
+        }
+        return match mock_provider::MockScript::load(std::path::Path::new(ctx.base_url.trim())) {
+            Ok(_) => ProbeResult::Ready,
+            Err(err) => ProbeResult::Unreachable {
+                detail: err.to_string(),
+            },
+        };
+    }
+
     // Resolve the bearer token (session token for Copilot, raw key otherwise).
     let effective_key = match super::auth::resolve_bearer_token(
         http,
@@ -545,334 +678,28 @@ pub async fn validate_model_connection(
 }
 
 // ── Provider-specific callers ───────────────────────────────────────────────
-
-/// Parse SSE text that was already fully received (not streaming).
-/// This handles cases where the response was buffered as text but contains SSE format.
-fn consume_sse_text(text: &str) -> Result<serde_json::Value> {
-    let mut content = String::new();
-    let mut tool_calls: Vec<serde_json::Value> = Vec::new();
-    let mut finish_reason: Option<String> = None;
-    let mut usage: Option<serde_json::Value> = None;
-    let mut model = String::new();
-
-    for line in text.lines() {
-        if let Some(data) = line.strip_prefix("data: ") {
-            if data.trim() == "[DONE]" {
-                break;
-            }
-
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
-                // Extract model name
-                if let Some(m) = json.get("model").and_then(|v| v.as_str()) {
-                    model = m.to_string();
-                }
-
-                // Extract usage if present
-                if let Some(u) = json.get("usage") {
-                    if !u.is_null() {
-                        usage = Some(u.clone());
-                    }
-                }
-
-                // Process choices
-                if let Some(choices) = json.get("choices").and_then(|v| v.as_array()) {
-                    for choice in choices {
-                        if let Some(fr) = choice.get("finish_reason").and_then(|v| v.as_str()) {
-                            finish_reason = Some(fr.to_string());
-                        }
-
-                        if let Some(delta) = choice.get("delta") {
-                            if let Some(c) = delta.get("content").and_then(|v| v.as_str()) {
-                                content.push_str(c);
-                            }
-
-                            if let Some(tc_array) = delta.get("tool_calls").and_then(|v| v.as_array()) {
-                                for tc in tc_array {
-                                    let index = tc.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                                    while tool_calls.len() <= index {
-                                        tool_calls.push(json!({
-                                            "id": "",
-                                            "type": "function",
-                                            "function": { "name": "", "arguments": "" }
-                                        }));
-                                    }
-                                    if let Some(id) = tc.get("id").and_then(|v| v.as_str()) {
-                                        tool_calls[index]["id"] = json!(id);
-                                    }
-                                    if let Some(func) = tc.get("function") {
-                                        if let Some(name) = func.get("name").and_then(|v| v.as_str()) {
-                                            tool_calls[index]["function"]["name"] = json!(name);
-                                        }
-                                        if let Some(args) = func.get("arguments").and_then(|v| v.as_str()) {
-                                            let existing = tool_calls[index]["function"]["arguments"]
-                                                .as_str()
-                                                .unwrap_or("");
-                                            tool_calls[index]["function"]["arguments"] =
-                                                json!(format!("{}{}", existing, args));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    // Count incomplete tool calls for debugging
-    let incomplete_count = tool_calls.iter().filter(|tc| {
-        let id = tc["id"].as_str().unwrap_or("");
-        let name = tc["function"]["name"].as_str().unwrap_or("");
-        id.is_empty() || name.is_empty()
-    }).count();
-
-    // Filter out incomplete tool calls (missing id or name)
-    let tool_calls: Vec<serde_json::Value> = tool_calls
-        .into_iter()
-        .filter(|tc| {
-            let id = tc["id"].as_str().unwrap_or("");
-            let name = tc["function"]["name"].as_str().unwrap_or("");
-            !id.is_empty() && !name.is_empty()
-        })
-        .collect();
-
-    // Log if we filtered any tool calls (for debugging stall issues)
-    if incomplete_count > 0 {
-        debug!(
-            incomplete = incomplete_count,
-            remaining = tool_calls.len(),
-            "Filtered incomplete tool calls"
-        );
-    }
-
-    let mut message = json!({
-        "role": "assistant",
-        "content": if content.trim().is_empty() { serde_json::Value::Null } else { json!(content) }
-    });
-
-    if !tool_calls.is_empty() {
-        message["tool_calls"] = json!(tool_calls);
-    }
-
-    let mut response = json!({
-        "model": model,
-        "choices": [{
-            "index": 0,
-            "message": message,
-            "finish_reason": finish_reason.unwrap_or_else(|| "stop".to_string())
-        }]
-    });
-
-    if let Some(u) = usage {
-        response["usage"] = u;
-    }
-
-    Ok(response)
-}
-
-/// Consume an SSE (Server-Sent Events) stream and reassemble it into
-/// an OpenAI-compatible JSON response structure.
-///
-/// This handles the case where a provider returns a streaming response
-/// even though we didn't request `"stream": true`.
-async fn consume_sse_stream(resp: reqwest::Response) -> Result<serde_json::Value> {
-    use futures_util::StreamExt;
-    use std::time::Duration;
-    use tokio::time::timeout;
-
-    let mut stream = resp.bytes_stream();
-    let mut buffer = String::new();
-
-    // Accumulated response fields
-    let mut content = String::new();
-    let mut tool_calls: Vec<serde_json::Value> = Vec::new();
-    let mut finish_reason: Option<String> = None;
-    let mut usage: Option<serde_json::Value> = None;
-    let mut model = String::new();
-
-    // Timeout for waiting on next chunk — if exceeded, assume stream is done
-    let chunk_timeout = Duration::from_secs(30);
-
-    'outer: loop {
-        // Wait for next chunk with timeout
-        let chunk_result = match timeout(chunk_timeout, stream.next()).await {
-            Ok(Some(result)) => result,
-            Ok(None) => {
-                trace!("SSE stream ended normally");
-                break 'outer;
-            }
-            Err(_) => {
-                // Timeout — stream stalled, return what we have
-                warn!(timeout_secs = chunk_timeout.as_secs(), "SSE stream timeout");
-                break 'outer;
-            }
-        };
-
-        let chunk = chunk_result.context("SSE stream read error")?;
-        let chunk_str = String::from_utf8_lossy(&chunk);
-
-        buffer.push_str(&chunk_str);
-
-        // Process complete SSE events (terminated by double newline)
-        while let Some(event_end) = buffer.find("\n\n") {
-            let event = buffer[..event_end].to_string();
-            buffer = buffer[event_end + 2..].to_string();
-
-            for line in event.lines() {
-                if let Some(data) = line.strip_prefix("data: ") {
-                    if data.trim() == "[DONE]" {
-                        // Stream complete — exit all loops
-                        trace!(content_len = content.len(), tool_calls = tool_calls.len(), "SSE received [DONE]");
-                        break 'outer;
-                    }
-
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
-                        // Extract model name
-                        if let Some(m) = json.get("model").and_then(|v| v.as_str()) {
-                            model = m.to_string();
-                        }
-
-                        // Extract usage if present (usually in final chunk)
-                        if let Some(u) = json.get("usage") {
-                            if !u.is_null() {
-                                usage = Some(u.clone());
-                            }
-                        }
-
-                        // Track if this chunk signals completion
-                        let mut should_exit = false;
-
-                        // Process choices
-                        if let Some(choices) = json.get("choices").and_then(|v| v.as_array()) {
-                            for choice in choices {
-                                // Extract delta content FIRST (before checking finish_reason)
-                                if let Some(delta) = choice.get("delta") {
-                                    // Text content
-                                    if let Some(c) = delta.get("content").and_then(|v| v.as_str()) {
-                                        content.push_str(c);
-                                    }
-
-                                    // Tool calls (streamed incrementally)
-                                    if let Some(tc_array) = delta.get("tool_calls").and_then(|v| v.as_array()) {
-                                        for tc in tc_array {
-                                            let index = tc.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-
-                                            // Ensure tool_calls vec is big enough
-                                            while tool_calls.len() <= index {
-                                                tool_calls.push(json!({
-                                                    "id": "",
-                                                    "type": "function",
-                                                    "function": { "name": "", "arguments": "" }
-                                                }));
-                                            }
-
-                                            // Update tool call fields
-                                            if let Some(id) = tc.get("id").and_then(|v| v.as_str()) {
-                                                tool_calls[index]["id"] = json!(id);
-                                            }
-                                            if let Some(func) = tc.get("function") {
-                                                if let Some(name) = func.get("name").and_then(|v| v.as_str()) {
-                                                    tool_calls[index]["function"]["name"] = json!(name);
-                                                }
-                                                if let Some(args) = func.get("arguments").and_then(|v| v.as_str()) {
-                                                    // Append to existing arguments
-                                                    let existing = tool_calls[index]["function"]["arguments"]
-                                                        .as_str()
-                                                        .unwrap_or("");
-                                                    tool_calls[index]["function"]["arguments"] =
-                                                        json!(format!("{}{}", existing, args));
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // Check finish_reason AFTER extracting delta data
-                                if let Some(fr) = choice.get("finish_reason").and_then(|v| v.as_str()) {
-                                    finish_reason = Some(fr.to_string());
-                                    // Terminal reasons mean the model is done
-                                    if fr == "stop" || fr == "tool_calls" || fr == "tool_use" || fr == "length" || fr == "end_turn" {
-                                        trace!(
-                                            finish_reason = fr,
-                                            content_len = content.len(),
-                                            tool_calls = tool_calls.len(),
-                                            "SSE stream completed"
-                                        );
-                                        should_exit = true;
-                                    }
-                                }
-                            }
-                        }
-
-                        // Exit after processing all data in this chunk
-                        if should_exit {
-                            break 'outer;
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    // Count incomplete tool calls for debugging
-    let incomplete_count = tool_calls.iter().filter(|tc| {
-        let id = tc["id"].as_str().unwrap_or("");
-        let name = tc["function"]["name"].as_str().unwrap_or("");
-        id.is_empty() || name.is_empty()
-    }).count();
-
-    // Filter out incomplete tool calls (missing id or name)
-    let tool_calls: Vec<serde_json::Value> = tool_calls
-        .into_iter()
-        .filter(|tc| {
-            let id = tc["id"].as_str().unwrap_or("");
-            let name = tc["function"]["name"].as_str().unwrap_or("");
-            !id.is_empty() && !name.is_empty()
-        })
-        .collect();
-
-    // Log if we filtered any tool calls (for debugging stall issues)
-    if incomplete_count > 0 {
-        debug!(
-            incomplete = incomplete_count,
-            remaining = tool_calls.len(),
-            "Filtered incomplete tool calls from stream"
-        );
-    }
-
-    // Build a standard OpenAI-style response object
-    let mut message = json!({
-        "role": "assistant",
-        "content": if content.trim().is_empty() { serde_json::Value::Null } else { json!(content) }
-    });
-
-    if !tool_calls.is_empty() {
-        message["tool_calls"] = json!(tool_calls);
-    }
-
-    let mut response = json!({
-        "model": model,
-        "choices": [{
-            "index": 0,
-            "message": message,
-            "finish_reason": finish_reason.unwrap_or_else(|| "stop".to_string())
-        }]
-    });
-
-    if let Some(u) = usage {
-        response["usage"] = u;
+//
+// Each provider has a request builder shared by the streaming variants in
+// [`stream`] and the batch calls below.  The `call_*_with_tools` functions
+// stream whenever they have a `writer` to forward deltas to.
+
+/// Send a batch request (no streaming) to whichever provider `req` names.
+pub async fn call_model(http: &reqwest::Client, req: &ProviderRequest) -> Result<ModelResponse> {
+    if req.provider == "anthropic" {
+        call_anthropic_with_tools(http, req, None).await
+    } else if req.provider == "google" {
+        call_google_with_tools(http, req, None).await
+    } else if req.provider == mock_provider::MOCK_PROVIDER {
+        mock_provider::call_mock_with_tools(req)
+    } else {
+        call_openai_with_tools(http, req, None).await
     }
-
-    Ok(response)
 }
 
-/// Call an OpenAI-compatible `/chat/completions` endpoint (non-streaming)
-/// with tool definitions.  Returns structured text + tool calls.
-pub async fn call_openai_with_tools(
-    http: &reqwest::Client,
-    req: &ProviderRequest,
-) -> Result<ModelResponse> {
+/// Build an OpenAI-compatible `/chat/completions` request.  It always asks
+/// for a stream; [`stream::open_openai`] copes with servers that ignore
+/// that.
+pub fn openai_request(http: &reqwest::Client, req: &ProviderRequest) -> reqwest::RequestBuilder {
     let url = format!("{}/chat/completions", req.base_url.trim_end_matches('/'));
 
     // Build the messages array.  Most messages are simple role+content,
@@ -893,7 +720,7 @@ pub async fn call_openai_with_tools(
         })
         .collect();
 
-    let tool_defs = tools::tools_openai();
+    let tool_defs = tools::tools_openai(&req.provider, &req.model, req.tools.as_deref());
 
     let mut body = json!({
         "model": req.model,
@@ -909,41 +736,11 @@ pub async fn call_openai_with_tools(
     if let Some(ref key) = req.api_key {
         builder = builder.bearer_auth(key);
     }
-    builder = apply_copilot_headers(builder, &req.provider, &req.messages);
-
-    let resp = send_with_retry(builder).await?;
-
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let text = resp.text().await.unwrap_or_default();
-        anyhow::bail!("Provider returned {} — {}", status, text);
-    }
-
-    // Check if the server returned a streaming response (SSE) despite us
-    // not requesting one.  Some providers (e.g. GitHub Copilot) may force
-    // streaming.  If so, consume the SSE stream and reassemble the response.
-    let content_type = resp
-        .headers()
-        .get(reqwest::header::CONTENT_TYPE)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
-
-    // Detect SSE by content-type (may include charset, e.g., "text/event-stream; charset=utf-8")
-    let data: serde_json::Value = if content_type.contains("text/event-stream") {
-        // Server is streaming — parse SSE events.
-        consume_sse_stream(resp).await?
-    } else {
-        // Normal JSON response — but check if it actually looks like SSE
-        let text = resp.text().await.context("Failed to read response body")?;
-
-        if text.trim_start().starts_with("data:") {
-            // Looks like SSE despite content-type — parse it
-            consume_sse_text(&text)?
-        } else {
-            serde_json::from_str(&text).context("Invalid JSON from provider")?
-        }
-    };
+    apply_copilot_headers(builder, &req.provider, &req.messages)
+}
 
+/// Parse a whole (non-streamed) `/chat/completions` response.
+pub fn parse_openai_response(data: &serde_json::Value) -> ModelResponse {
     let choice = &data["choices"][0];
     let message = &choice["message"];
 
@@ -988,25 +785,32 @@ pub async fn call_openai_with_tools(
         result.completion_tokens = usage["completion_tokens"].as_u64();
     }
 
-    Ok(result)
+    result
 }
 
-/// Call the Anthropic Messages API with tool definitions.
-///
-/// When `writer` is provided, streams thinking and text deltas to the TUI
-/// in real-time. When `None`, operates in batch mode (for internal calls
-/// like context compaction).
+/// Call an OpenAI-compatible `/chat/completions` endpoint with tool
+/// definitions.  Returns structured text + tool calls.
 ///
-/// Extended thinking is automatically enabled for supported models when
-/// the model name contains "opus" or "sonnet" and the request appears
-/// complex enough to benefit from reasoning.
-pub async fn call_anthropic_with_tools(
+/// When `writer` is provided, text deltas from the SSE stream are sent to
+/// the client as they arrive.
+#[instrument(name = "provider_call", skip_all, fields(provider = %req.provider, model = %req.model))]
+pub async fn call_openai_with_tools(
     http: &reqwest::Client,
     req: &ProviderRequest,
     mut writer: Option<&mut WsWriter>,
 ) -> Result<ModelResponse> {
-    use futures_util::StreamExt;
+    if let Some(w) = writer.as_deref_mut() {
+        server::send_stream_start(w).await?;
+    }
+    let events = stream::open_openai(http, req).await?;
+    stream::collect(events, writer, |_| {}).await
+}
 
+/// Build an Anthropic Messages API request.
+///
+/// Streamed requests get a larger `max_tokens` to allow for longer
+/// responses, within what the model can produce.
+pub fn anthropic_request(http: &reqwest::Client, req: &ProviderRequest, stream: bool) -> reqwest::RequestBuilder {
     let url = format!("{}/v1/messages", req.base_url.trim_end_matches('/'));
 
     let system = req
@@ -1034,19 +838,15 @@ pub async fn call_anthropic_with_tools(
         })
         .collect();
 
-    let tool_defs = tools::tools_anthropic();
+    let tool_defs = tools::tools_anthropic(&req.model, req.tools.as_deref());
 
-    // Use streaming when we have a writer to forward chunks to
-    let use_streaming = writer.is_some();
-
-    // Increase max_tokens when streaming to allow for longer responses
-    let max_tokens = if use_streaming { 16384 } else { 4096 };
+    let max_tokens = (if stream { 16384 } else { 4096 }).min(crate::model_caps::lookup(&req.model).max_output);
 
     let mut body = json!({
         "model": req.model,
         "max_tokens": max_tokens,
         "messages": messages,
-        "stream": use_streaming,
+        "stream": stream,
     });
 
     if !system.is_empty() {
@@ -1056,203 +856,39 @@ pub async fn call_anthropic_with_tools(
         body["tools"] = json!(tool_defs);
     }
 
-    // Send immediate "waiting" indicator BEFORE the HTTP request
-    // This is where the model processing time is spent
-    if let Some(ref mut w) = writer {
-        server::send_stream_start(w).await?;
-    }
-
     let api_key = req.api_key.as_deref().unwrap_or("");
-    let builder = http
-        .post(&url)
+    http.post(&url)
         .header("x-api-key", api_key)
         .header("anthropic-version", "2023-06-01")
-        .json(&body);
-    let resp = send_with_retry(builder).await?;
-
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let text = resp.text().await.unwrap_or_default();
-        anyhow::bail!("Anthropic returned {} — {}", status, text);
-    }
+        .json(&body)
+}
 
-    // Non-streaming path (for internal calls like compaction)
-    if !use_streaming {
+/// Call the Anthropic Messages API with tool definitions.
+///
+/// When `writer` is provided, streams thinking and text deltas to the TUI
+/// in real-time. When `None`, operates in batch mode (for internal calls
+/// like context compaction).
+///
+/// Extended thinking is automatically enabled for supported models when
+/// the model name contains "opus" or "sonnet" and the request appears
+/// complex enough to benefit from reasoning.
+#[instrument(name = "provider_call", skip_all, fields(provider = %req.provider, model = %req.model))]
+pub async fn call_anthropic_with_tools(
+    http: &reqwest::Client,
+    req: &ProviderRequest,
+    writer: Option<&mut WsWriter>,
+) -> Result<ModelResponse> {
+    let Some(writer) = writer else {
+        let resp = send_checked(anthropic_request(http, req, false), "anthropic").await?;
         let data: serde_json::Value = resp.json().await.context("Invalid JSON from Anthropic")?;
         return parse_anthropic_response(&data);
-    }
-
-    // Streaming path — parse SSE and forward to TUI
-    let writer = writer.unwrap();
-
-    let mut stream = resp.bytes_stream();
-    let mut buffer = String::new();
-
-    // Accumulated response
-    let mut result = ModelResponse::default();
-    let mut current_tool_index = 0;
-    let mut in_thinking_block = false;
-    let mut thinking_content = String::new();
-    let mut tool_args_buffer: std::collections::HashMap<usize, String> = std::collections::HashMap::new();
-
-    while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result.context("Stream read error")?;
-        buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-        while let Some(event_end) = buffer.find("\n\n") {
-            let event = buffer[..event_end].to_string();
-            buffer = buffer[event_end + 2..].to_string();
-
-            let mut event_type = String::new();
-            let mut event_data = String::new();
-
-            for line in event.lines() {
-                if let Some(typ) = line.strip_prefix("event: ") {
-                    event_type = typ.to_string();
-                } else if let Some(data) = line.strip_prefix("data: ") {
-                    event_data = data.to_string();
-                }
-            }
-
-            // Debug: log event types we receive
-            trace!(event_type = %event_type, data_len = event_data.len(), "Anthropic SSE event");
-
-            if event_data.is_empty() {
-                continue;
-            }
-
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&event_data) {
-                match event_type.as_str() {
-                    "message_start" => {
-                        // Extract usage from message start if present
-                        if let Some(usage) = json.get("message").and_then(|m| m.get("usage")) {
-                            result.prompt_tokens = usage["input_tokens"].as_u64();
-                        }
-                    }
-                    "content_block_start" => {
-                        if let Some(block) = json.get("content_block") {
-                            match block["type"].as_str() {
-                                Some("thinking") => {
-                                    // Extended thinking block started
-                                    in_thinking_block = true;
-                                    thinking_content.clear();
-                                    let _ = send_thinking_start(writer).await;
-                                }
-                                Some("tool_use") => {
-                                    let id = block["id"].as_str().unwrap_or("").to_string();
-                                    let name = block["name"].as_str().unwrap_or("").to_string();
-                                    current_tool_index = json["index"].as_u64().unwrap_or(0) as usize;
-
-                                    // Initialize tool call
-                                    result.tool_calls.push(ParsedToolCall {
-                                        id,
-                                        name,
-                                        arguments: json!({}),
-                                    });
-                                    tool_args_buffer.insert(current_tool_index, String::new());
-                                }
-                                Some("text") => {
-                                    // Regular text block - nothing special to do on start
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    "content_block_delta" => {
-                        if let Some(delta) = json.get("delta") {
-                            match delta["type"].as_str() {
-                                Some("thinking_delta") => {
-                                    // Extended thinking content streaming
-                                    if let Some(thinking) = delta["thinking"].as_str() {
-                                        thinking_content.push_str(thinking);
-                                        let _ = send_thinking_delta(writer, thinking).await;
-                                    }
-                                }
-                                Some("text_delta") => {
-                                    if let Some(text) = delta["text"].as_str() {
-                                        result.text.push_str(text);
-                                        let _ = send_chunk(writer, text).await;
-                                    }
-                                }
-                                Some("input_json_delta") => {
-                                    if let Some(partial) = delta["partial_json"].as_str() {
-                                        if let Some(buf) = tool_args_buffer.get_mut(&current_tool_index) {
-                                            buf.push_str(partial);
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    "content_block_stop" => {
-                        // A content block finished
-                        if in_thinking_block {
-                            in_thinking_block = false;
-                            // Generate a brief summary from the thinking content
-                            let summary = if thinking_content.len() > 100 {
-                                let truncated = &thinking_content[..100];
-                                if let Some(period_pos) = truncated.find(". ") {
-                                    Some(&truncated[..=period_pos])
-                                } else {
-                                    Some(truncated)
-                                }
-                            } else if !thinking_content.is_empty() {
-                                Some(thinking_content.as_str())
-                            } else {
-                                None
-                            };
-                            let _ = send_thinking_end(writer, summary).await;
-                        }
-
-                        // Finalize tool call arguments
-                        let block_index = json["index"].as_u64().unwrap_or(0) as usize;
-                        if let Some(args_str) = tool_args_buffer.remove(&block_index) {
-                            if !args_str.is_empty() {
-                                if let Some(tc) = result.tool_calls.get_mut(block_index) {
-                                    tc.arguments = serde_json::from_str(&args_str).unwrap_or(json!({}));
-                                }
-                            }
-                        }
-                    }
-                    "message_delta" => {
-                        // Extract stop_reason and usage from message delta
-                        if let Some(delta) = json.get("delta") {
-                            if let Some(sr) = delta["stop_reason"].as_str() {
-                                result.finish_reason = Some(sr.to_string());
-                            }
-                        }
-                        if let Some(usage) = json.get("usage") {
-                            result.completion_tokens = usage["output_tokens"].as_u64();
-                        }
-                    }
-                    "message_stop" => {
-                        // Stream complete
-                        return Ok(result);
-                    }
-                    "error" => {
-                        let msg = json["error"]["message"]
-                            .as_str()
-                            .unwrap_or("Unknown error");
-                        anyhow::bail!("Anthropic stream error: {}", msg);
-                    }
-                    _ => {
-                        trace!(event_type = %event_type, "Unhandled Anthropic SSE event type");
-                    }
-                }
-            }
-        }
-    }
-
-    // Debug: log final result
-    trace!(
-        text_len = result.text.len(),
-        tool_calls = result.tool_calls.len(),
-        finish_reason = ?result.finish_reason,
-        "Anthropic SSE stream complete"
-    );
+    };
 
-    Ok(result)
+    // Send immediate "waiting" indicator BEFORE the HTTP request
+    // This is where the model processing time is spent
+    server::send_stream_start(writer).await?;
+    let events = stream::open_anthropic(http, req).await?;
+    stream::collect(events, Some(writer), |_| {}).await
 }
 
 /// Parse a non-streaming Anthropic response into ModelResponse.
@@ -1299,18 +935,25 @@ fn parse_anthropic_response(data: &serde_json::Value) -> Result<ModelResponse> {
     Ok(result)
 }
 
-/// Call Google Gemini with function declarations (non-streaming).
-pub async fn call_google_with_tools(
-    http: &reqwest::Client,
-    req: &ProviderRequest,
-) -> Result<ModelResponse> {
+/// Build a Gemini request: `streamGenerateContent?alt=sse` when `stream`
+/// is set, `generateContent` otherwise.
+pub fn google_request(http: &reqwest::Client, req: &ProviderRequest, stream: bool) -> reqwest::RequestBuilder {
     let api_key = req.api_key.as_deref().unwrap_or("");
-    let url = format!(
-        "{}/models/{}:generateContent?key={}",
-        req.base_url.trim_end_matches('/'),
-        req.model,
-        api_key,
-    );
+    let url = if stream {
+        format!(
+            "{}/models/{}:streamGenerateContent?alt=sse&key={}",
+            req.base_url.trim_end_matches('/'),
+            req.model,
+            api_key,
+        )
+    } else {
+        format!(
+            "{}/models/{}:generateContent?key={}",
+            req.base_url.trim_end_matches('/'),
+            req.model,
+            api_key,
+        )
+    };
 
     let system = req
         .messages
@@ -1337,7 +980,7 @@ pub async fn call_google_with_tools(
         })
         .collect();
 
-    let tool_defs = tools::tools_google();
+    let tool_defs = tools::tools_google(&req.model, req.tools.as_deref());
 
     let mut body = json!({ "contents": contents });
     if !system.is_empty() {
@@ -1347,17 +990,26 @@ pub async fn call_google_with_tools(
         body["tools"] = json!([{ "function_declarations": tool_defs }]);
     }
 
-    let builder = http
-        .post(&url)
-        .json(&body);
-    let resp = send_with_retry(builder).await?;
+    http.post(&url).json(&body)
+}
 
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let text = resp.text().await.unwrap_or_default();
-        anyhow::bail!("Google returned {} — {}", status, text);
+/// Call Google Gemini with function declarations.
+///
+/// When `writer` is provided, uses `streamGenerateContent` and sends text
+/// to the client as it arrives.
+#[instrument(name = "provider_call", skip_all, fields(provider = %req.provider, model = %req.model))]
+pub async fn call_google_with_tools(
+    http: &reqwest::Client,
+    req: &ProviderRequest,
+    writer: Option<&mut WsWriter>,
+) -> Result<ModelResponse> {
+    if let Some(writer) = writer {
+        server::send_stream_start(writer).await?;
+        let events = stream::open_google(http, req).await?;
+        return stream::collect(events, Some(writer), |_| {}).await;
     }
 
+    let resp = send_checked(google_request(http, req, false), "google").await?;
     let data: serde_json::Value = resp.json().await.context("Invalid JSON from Google")?;
 
     let mut result = ModelResponse::default();
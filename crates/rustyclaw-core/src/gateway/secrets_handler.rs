@@ -1,6 +1,13 @@
+use std::path::{Path, PathBuf};
 use tracing::{debug, warn, instrument};
 
-use crate::secrets::{AccessContext, AccessPolicy, CredentialValue, SecretEntry, SecretKind};
+use crate::secrets::age_crypt::{self, Protection};
+use crate::secrets::{
+    AccessContext, AccessPolicy, CredentialValue, SecretEntry, SecretKind, SecretPolicy,
+    TotpSeed,
+};
+use crate::providers::KeyCheckError;
+use crate::tools::{check_write, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
 
 use super::SharedVault;
 
@@ -8,7 +15,8 @@ use super::SharedVault;
 ///
 /// These are intercepted before the generic `tools::execute_tool` path
 /// because they require `SharedVault` access — the normal tool signature
-/// only receives `(args, workspace_dir)`.
+/// only receives `(args, workspace_dir)`.  `workspace_dir` is passed on
+/// for the file tools (`encrypt_file` / `decrypt_file`).
 ///
 /// Access control is delegated entirely to [`SecretsManager::check_access`]
 /// and the per-credential [`AccessPolicy`].  The agent gets an
@@ -20,17 +28,29 @@ use super::SharedVault;
 /// - `WithApproval` credentials are only readable if `agent_access_enabled`
 ///   is set in config.
 /// - `WithAuth` and `SkillOnly` credentials are denied.
-#[instrument(skip(args, vault), fields(%name))]
+#[instrument(skip(args, vault, workspace_dir), fields(%name))]
 pub async fn execute_secrets_tool(
     name: &str,
     args: &serde_json::Value,
     vault: &SharedVault,
+    workspace_dir: &Path,
 ) -> Result<String, String> {
     debug!("Executing secrets tool");
+    if crate::tools::is_dry_run() {
+        if let Some(description) = dry_run_description(name, args) {
+            debug!(tool = name, "Dry-run: simulated secrets tool call");
+            return Ok(format!("[dry-run] {} {} — no changes were made.", name, description));
+        }
+    }
     match name {
         "secrets_list" => exec_secrets_list(vault).await,
         "secrets_get" => exec_secrets_get(args, vault).await,
         "secrets_store" => exec_secrets_store(args, vault).await,
+        "generate_secret" => exec_generate_secret(args, vault).await,
+        "totp" => exec_totp(args, vault).await,
+        "ssh_key" => exec_ssh_key(args, vault).await,
+        "encrypt_file" => exec_encrypt_file(args, vault, workspace_dir).await,
+        "decrypt_file" => exec_decrypt_file(args, vault, workspace_dir).await,
         _ => {
             warn!("Unknown secrets tool requested");
             Err(format!("Unknown secrets tool: {}", name))
@@ -38,6 +58,30 @@ pub async fn execute_secrets_tool(
     }
 }
 
+/// Describe a secrets tool call that would change the vault or the SSH
+/// agent.  `encrypt_file` and `decrypt_file` check dry-run themselves,
+/// after validating their paths.
+fn dry_run_description(name: &str, args: &serde_json::Value) -> Option<String> {
+    let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str());
+    let action = str_arg("action").unwrap_or("");
+    match name {
+        "secrets_store" => Some(format!("would store '{}' in the vault", str_arg("name").unwrap_or("(unspecified)"))),
+        "generate_secret" => str_arg("storeAs").map(|n| format!("would generate and store '{}' in the vault", n)),
+        "totp" if matches!(action, "add" | "remove") => Some(format!(
+            "would {} TOTP seed '{}'",
+            action,
+            str_arg("name").unwrap_or("(unspecified)")
+        )),
+        "ssh_key" if action == "stop" => Some("would stop the SSH agent".to_string()),
+        "ssh_key" if matches!(action, "generate" | "import" | "load" | "unload") => Some(format!(
+            "would {} SSH key '{}'",
+            action,
+            str_arg("name").unwrap_or("(unspecified)")
+        )),
+        _ => None,
+    }
+}
+
 /// List all credentials in the vault (names, kinds, policies — no values).
 #[instrument(skip(vault))]
 pub async fn exec_secrets_list(vault: &SharedVault) -> Result<String, String> {
@@ -115,6 +159,17 @@ pub fn format_credential_value(
     value: &CredentialValue,
 ) -> String {
     match value {
+        CredentialValue::Single(_) if entry.kind == SecretKind::Totp => {
+            format!("[{}] {} = <seed hidden; use the totp tool for a code>", entry.kind, name)
+        }
+        CredentialValue::Single(identity) if entry.kind == SecretKind::AgeKey => {
+            format!(
+                "[{}] {}\n  recipient: {}\n  identity: <hidden; use decrypt_file>",
+                entry.kind,
+                name,
+                age_crypt::recipient_for(identity).unwrap_or_default(),
+            )
+        }
         CredentialValue::Single(v) => {
             format!("[{}] {} = {}", entry.kind, name, v)
         }
@@ -224,6 +279,10 @@ pub async fn exec_secrets_store(
         disabled: false,
     };
 
+    let key_note = check_provider_key(cred_name, value, None)
+        .await
+        .map_err(|e| format!("Credential not stored. {}", e))?;
+
     let mut mgr = vault.lock().await;
     mgr.store_credential(cred_name, &entry, value, username)
         .map_err(|e| {
@@ -233,7 +292,540 @@ pub async fn exec_secrets_store(
 
     debug!(credential = cred_name, "Credential stored successfully");
     Ok(format!(
-        "Credential '{}' stored successfully (kind: {}, policy: {}).",
-        cred_name, entry.kind, entry.policy,
+        "Credential '{}' stored successfully (kind: {}, policy: {}).{}",
+        cred_name, entry.kind, entry.policy, key_note,
     ))
 }
+
+/// Check a value being stored under a provider's API key name (e.g.
+/// `OPENAI_API_KEY`) against that provider.  A rejected key is an error
+/// so it never reaches the vault; otherwise the result is a note for the
+/// confirmation message (empty for other secrets).
+pub async fn check_provider_key(name: &str, value: &str, base_url: Option<&str>) -> Result<String, String> {
+    let Some(def) = crate::providers::provider_for_secret_key(name) else {
+        return Ok(String::new());
+    };
+    match crate::providers::check_api_key(def.id, value, base_url).await {
+        Ok(check) => {
+            debug!(provider = def.id, "API key check passed");
+            Ok(format!(" {}: {}.", def.display, check.summary()))
+        }
+        Err(KeyCheckError::Unverified(msg)) => {
+            warn!(provider = def.id, %msg, "API key could not be checked");
+            Ok(format!(" Not verified: {}", msg))
+        }
+        Err(KeyCheckError::Rejected(msg)) => {
+            warn!(provider = def.id, "API key rejected by provider");
+            Err(msg)
+        }
+    }
+}
+
+/// Generate a password, passphrase, token or PIN.
+///
+/// With `storeAs` the value goes straight into the vault and only metadata
+/// is returned, so the secret never appears in the conversation.
+#[instrument(skip(args, vault))]
+pub async fn exec_generate_secret(
+    args: &serde_json::Value,
+    vault: &SharedVault,
+) -> Result<String, String> {
+    let policy = SecretPolicy::from_args(args)?;
+    let secret = crate::secrets::generate(&policy)?;
+    let style = args.get("style").and_then(|v| v.as_str()).unwrap_or("password");
+
+    let Some(cred_name) = args.get("storeAs").and_then(|v| v.as_str()) else {
+        debug!(style, "Generated secret for display");
+        return Ok(serde_json::json!({
+            "value": secret.value,
+            "style": style,
+            "entropyBits": secret.entropy_bits.round(),
+        })
+        .to_string());
+    };
+
+    let username = args.get("username").and_then(|v| v.as_str());
+    let kind = if username.is_some() {
+        SecretKind::UsernamePassword
+    } else if matches!(policy, SecretPolicy::Token { .. }) {
+        SecretKind::ApiKey
+    } else {
+        SecretKind::Other
+    };
+    let entry = SecretEntry {
+        label: cred_name.to_string(),
+        kind,
+        policy: AccessPolicy::default(), // WithApproval
+        description: args
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        disabled: false,
+    };
+
+    let mut mgr = vault.lock().await;
+    mgr.store_credential(cred_name, &entry, &secret.value, username)
+        .map_err(|e| {
+            warn!(credential = cred_name, error = %e, "Failed to store generated secret");
+            format!("Failed to store credential: {}", e)
+        })?;
+
+    debug!(credential = cred_name, style, "Generated secret stored");
+    Ok(serde_json::json!({
+        "stored": cred_name,
+        "kind": entry.kind.to_string(),
+        "style": style,
+        "length": secret.value.chars().count(),
+        "entropyBits": secret.entropy_bits.round(),
+        "note": "The value was written to the vault and not shown. Use secrets_get if you need it.",
+    })
+    .to_string())
+}
+
+/// Manage TOTP seeds and hand out current codes.
+///
+/// Seeds go in through `add` and never come back out: `code` returns only
+/// the current code, subject to the credential's access policy, and each
+/// request is recorded in the vault's audit log.
+#[instrument(skip(args, vault))]
+pub async fn exec_totp(args: &serde_json::Value, vault: &SharedVault) -> Result<String, String> {
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("code");
+    let name = args.get("name").and_then(|v| v.as_str());
+    let mut mgr = vault.lock().await;
+
+    match action {
+        "list" => {
+            let seeds: Vec<_> = mgr
+                .list_credentials()
+                .into_iter()
+                .filter(|(_, entry)| entry.kind == SecretKind::Totp)
+                .collect();
+            if seeds.is_empty() {
+                return Ok("No TOTP seeds stored. Add one with action 'add'.".into());
+            }
+            let mut out = format!("{} TOTP seed(s):\n", seeds.len());
+            for (name, entry) in seeds {
+                let desc = entry.description.map(|d| format!(" — {}", d)).unwrap_or_default();
+                out.push_str(&format!("  • {} (policy: {}){}\n", name, entry.policy, desc));
+            }
+            Ok(out)
+        }
+        "add" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            let secret = args
+                .get("secret")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: secret (base32 seed or otpauth:// URI)")?;
+            let mut seed = TotpSeed::parse(secret).map_err(|e| e.to_string())?;
+            if let Some(digits) = args.get("digits").and_then(|v| v.as_u64()) {
+                seed.digits = digits as usize;
+            }
+            if let Some(period) = args.get("period").and_then(|v| v.as_u64()) {
+                seed.period = period;
+            }
+            if let Some(algorithm) = args.get("algorithm").and_then(|v| v.as_str()) {
+                seed.algorithm = algorithm.to_uppercase();
+            }
+            // Catch bad overrides now rather than on first use.
+            seed.code_at(0).map_err(|e| e.to_string())?;
+            let policy = match args.get("policy").and_then(|v| v.as_str()).unwrap_or("ask") {
+                "always" => AccessPolicy::Always,
+                "ask" => AccessPolicy::WithApproval,
+                "auth" => AccessPolicy::WithAuth,
+                other => {
+                    return Err(format!("Unknown policy '{}'. Use always, ask or auth.", other));
+                }
+            };
+            let description = args
+                .get("description")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .or_else(|| match (&seed.issuer, &seed.account) {
+                    (Some(issuer), Some(account)) => Some(format!("{} ({})", issuer, account)),
+                    (Some(issuer), None) => Some(issuer.clone()),
+                    (None, account) => account.clone(),
+                });
+            let entry = SecretEntry {
+                label: name.to_string(),
+                kind: SecretKind::Totp,
+                policy,
+                description,
+                disabled: false,
+            };
+            mgr.store_totp(name, &entry, &seed).map_err(|e| {
+                warn!(credential = name, error = %e, "Failed to store TOTP seed");
+                format!("Failed to store TOTP seed: {}", e)
+            })?;
+            debug!(credential = name, "TOTP seed stored");
+            Ok(format!(
+                "TOTP seed '{}' stored ({} digits every {}s, policy: {}). Use action 'code' to get the current code.",
+                name, seed.digits, seed.period, entry.policy,
+            ))
+        }
+        "code" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            let ctx = AccessContext {
+                user_approved: false,
+                authenticated: false,
+                active_skill: None,
+            };
+            match mgr.totp_code(name, &ctx) {
+                Ok(Some(code)) => {
+                    debug!(credential = name, "TOTP code issued");
+                    Ok(serde_json::json!({
+                        "name": name,
+                        "code": code.code,
+                        "secondsRemaining": code.seconds_remaining,
+                        "hint": if code.seconds_remaining < 5 {
+                            "The code is about to roll over; wait and ask again if it is rejected."
+                        } else {
+                            "Enter the code promptly."
+                        },
+                    })
+                    .to_string())
+                }
+                Ok(None) => Err(format!(
+                    "TOTP seed '{}' not found. Use action 'list' to see stored seeds.",
+                    name,
+                )),
+                Err(e) => {
+                    warn!(credential = name, error = %e, "TOTP code denied");
+                    Err(e.to_string())
+                }
+            }
+        }
+        "remove" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            match mgr.list_credentials().into_iter().find(|(n, _)| n == name) {
+                Some((_, entry)) if entry.kind == SecretKind::Totp => {}
+                Some((_, entry)) => {
+                    return Err(format!("'{}' is a {} credential, not a TOTP seed.", name, entry.kind));
+                }
+                None => return Err(format!("TOTP seed '{}' not found.", name)),
+            }
+            mgr.delete_credential(name)
+                .map_err(|e| format!("Failed to remove TOTP seed: {}", e))?;
+            Ok(format!("TOTP seed '{}' removed.", name))
+        }
+        other => Err(format!("Unknown action: {}. Use add, code, list or remove", other)),
+    }
+}
+
+/// Manage vault-held SSH keys and the session ssh-agent.
+///
+/// `load` is the only action that reads a private key, and it is subject
+/// to the credential's access policy; the key goes straight to `ssh-add`
+/// and is never returned.
+#[instrument(skip(args, vault))]
+pub async fn exec_ssh_key(args: &serde_json::Value, vault: &SharedVault) -> Result<String, String> {
+    use crate::secrets::ssh_agent;
+
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("list");
+    let name = args.get("name").and_then(|v| v.as_str());
+    let policy = match args.get("policy").and_then(|v| v.as_str()).unwrap_or("ask") {
+        "always" => AccessPolicy::Always,
+        "ask" => AccessPolicy::WithApproval,
+        "auth" => AccessPolicy::WithAuth,
+        other => return Err(format!("Unknown policy '{}'. Use always, ask or auth.", other)),
+    };
+    let mut mgr = vault.lock().await;
+
+    match action {
+        "list" => {
+            let loaded = ssh_agent::loaded();
+            let keys: Vec<serde_json::Value> = mgr
+                .list_credentials()
+                .into_iter()
+                .filter(|(_, entry)| entry.kind == SecretKind::SshKey)
+                .map(|(name, entry)| {
+                    serde_json::json!({
+                        "name": name,
+                        "policy": entry.policy.to_string(),
+                        "description": entry.description,
+                        "loaded": loaded.iter().any(|k| k.name == name),
+                    })
+                })
+                .collect();
+            Ok(serde_json::json!({
+                "keys": keys,
+                "agent": ssh_agent::socket().map(|s| s.display().to_string()),
+            })
+            .to_string())
+        }
+        "generate" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            let comment = args.get("comment").and_then(|v| v.as_str()).unwrap_or("rustyclaw");
+            let public = mgr
+                .generate_ssh_key(name, comment, policy)
+                .map_err(|e| format!("Failed to generate SSH key: {}", e))?;
+            Ok(format!(
+                "Generated Ed25519 key '{}'. Add this public key to the remote ~/.ssh/authorized_keys:\n{}",
+                name, public,
+            ))
+        }
+        "import" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            let pem = match (
+                args.get("privateKey").and_then(|v| v.as_str()),
+                args.get("path").and_then(|v| v.as_str()),
+            ) {
+                (Some(pem), _) => pem.to_string(),
+                (None, Some(path)) => {
+                    let path = match path.strip_prefix("~/") {
+                        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                        None => std::path::PathBuf::from(path),
+                    };
+                    std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+                }
+                (None, None) => return Err("Provide 'privateKey' or 'path'".into()),
+            };
+            let passphrase = args.get("passphrase").and_then(|v| v.as_str());
+            let public = mgr
+                .import_ssh_key(name, &pem, passphrase, policy)
+                .map_err(|e| format!("Failed to import SSH key: {}", e))?;
+            debug!(credential = name, "SSH key imported");
+            Ok(format!("Imported SSH key '{}'.\nPublic key: {}", name, public))
+        }
+        "public" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            mgr.public_key(name)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("SSH key '{}' not found.", name))
+        }
+        "load" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            let ctx = AccessContext::default();
+            let (_, value) = mgr
+                .get_credential(name, &ctx)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("SSH key '{}' not found.", name))?;
+            let CredentialValue::SshKeyPair { private_key, .. } = value else {
+                return Err(format!("'{}' is not an SSH key.", name));
+            };
+            drop(mgr);
+            let lifetime = args.get("lifetime").and_then(|v| v.as_u64()).unwrap_or(3600);
+            let owned = name.to_string();
+            let key = tokio::task::spawn_blocking(move || {
+                ssh_agent::add_key(&owned, &private_key, Some(lifetime))
+            })
+            .await
+            .map_err(|e| format!("ssh-agent task failed: {}", e))??;
+            Ok(format!(
+                "Loaded '{}' ({}) into the session ssh-agent for {}s. The nodes tool now authenticates with it.",
+                key.name, key.fingerprint, lifetime,
+            ))
+        }
+        "unload" => {
+            let name = name.ok_or("Missing required parameter: name")?;
+            let public = mgr
+                .public_key(name)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("SSH key '{}' not found.", name))?;
+            drop(mgr);
+            if ssh_agent::remove_key(name, &public)? {
+                Ok(format!("Removed '{}' from the session ssh-agent.", name))
+            } else {
+                Ok(format!("'{}' was not loaded.", name))
+            }
+        }
+        "stop" => {
+            drop(mgr);
+            if ssh_agent::stop()? {
+                Ok("Session ssh-agent stopped; all keys removed.".into())
+            } else {
+                Ok("No session ssh-agent is running.".into())
+            }
+        }
+        other => Err(format!(
+            "Unknown action: {}. Use list, generate, import, public, load, unload or stop",
+            other,
+        )),
+    }
+}
+
+/// Resolve a tool path argument, refusing the credentials directory.
+fn file_arg(args: &serde_json::Value, key: &str, workspace_dir: &Path) -> Result<Option<PathBuf>, String> {
+    let Some(path) = args.get(key).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let path = resolve_path(workspace_dir, path);
+    if is_protected_path(&path) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    Ok(Some(path))
+}
+
+/// Read a passphrase held in the vault (subject to its access policy).
+fn vault_passphrase(mgr: &mut crate::secrets::SecretsManager, name: &str) -> Result<String, String> {
+    match mgr.get_credential(name, &AccessContext::default()) {
+        Ok(Some((_, CredentialValue::Single(value)))) => Ok(value),
+        Ok(Some((_, CredentialValue::UserPass { password, .. }))) => Ok(password),
+        Ok(Some((entry, _))) => Err(format!("'{}' is a {} credential, not a passphrase.", name, entry.kind)),
+        Ok(None) => Err(format!("Passphrase secret '{}' not found.", name)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Encrypt a file with age to vault keys, raw recipients, or a vault-held
+/// passphrase.
+#[instrument(skip(args, vault, workspace_dir))]
+pub async fn exec_encrypt_file(
+    args: &serde_json::Value,
+    vault: &SharedVault,
+    workspace_dir: &Path,
+) -> Result<String, String> {
+    let src = file_arg(args, "path", workspace_dir)?.ok_or("Missing required parameter: path")?;
+    let dst = match file_arg(args, "output", workspace_dir)? {
+        Some(dst) => dst,
+        None => PathBuf::from(format!("{}.age", src.display())),
+    };
+    check_write(&dst, workspace_dir)?;
+    let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+    if dst.exists() && !overwrite {
+        return Err(format!("{} already exists; pass overwrite: true to replace it", dst.display()));
+    }
+    let armor = args.get("armor").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let mut names: Vec<String> = args
+        .get("recipients")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    if let Some(key) = args.get("key").and_then(|v| v.as_str()) {
+        names.push(key.to_string());
+    }
+    let passphrase_secret = args.get("passphraseSecret").and_then(|v| v.as_str());
+    if names.is_empty() && passphrase_secret.is_none() {
+        return Err("Provide 'key', 'recipients' or 'passphraseSecret'".into());
+    }
+    if !names.is_empty() && passphrase_secret.is_some() {
+        return Err("age can't combine a passphrase with recipients; use one or the other".into());
+    }
+    if crate::tools::is_dry_run() {
+        return Ok(format!(
+            "[dry-run] encrypt_file would encrypt {} → {} — no changes were made.",
+            src.display(),
+            dst.display()
+        ));
+    }
+
+    let mut mgr = vault.lock().await;
+    let mut created = None;
+    let passphrase = match passphrase_secret {
+        Some(secret) => Some(vault_passphrase(&mut mgr, secret)?),
+        None => None,
+    };
+    let mut recipients = Vec::with_capacity(names.len());
+    for name in &names {
+        if age_crypt::is_recipient(name) {
+            recipients.push(name.clone());
+            continue;
+        }
+        match mgr.public_key(name).map_err(|e| e.to_string())? {
+            Some(recipient) if age_crypt::is_recipient(&recipient) => recipients.push(recipient),
+            Some(_) => return Err(format!("'{}' is not an age key.", name)),
+            None => {
+                // First use of a key name creates it, so "encrypt this with
+                // my backup key" works without a separate setup step.
+                let recipient = mgr
+                    .generate_age_key(name, AccessPolicy::default())
+                    .map_err(|e| format!("Failed to create age key '{}': {}", name, e))?;
+                created = Some(name.clone());
+                recipients.push(recipient);
+            }
+        }
+    }
+    drop(mgr);
+
+    debug!(src = %src.display(), dst = %dst.display(), recipients = recipients.len(), "Encrypting file");
+    let (src_c, dst_c) = (src.clone(), dst.clone());
+    tokio::task::spawn_blocking(move || {
+        let protection = match passphrase.as_deref() {
+            Some(p) => Protection::Passphrase(p),
+            None => Protection::Recipients(&recipients),
+        };
+        age_crypt::encrypt_file(&src_c, &dst_c, protection, armor)
+    })
+    .await
+    .map_err(|e| format!("Encryption task failed: {}", e))??;
+
+    let mut out = format!("Encrypted {} → {}", src.display(), dst.display());
+    if let Some(name) = created {
+        out.push_str(&format!(
+            "\nCreated age key '{}' in the vault; decrypt_file with key '{}' opens it.",
+            name, name,
+        ));
+    }
+    Ok(out)
+}
+
+/// Decrypt an age file with vault keys or a vault-held passphrase.
+#[instrument(skip(args, vault, workspace_dir))]
+pub async fn exec_decrypt_file(
+    args: &serde_json::Value,
+    vault: &SharedVault,
+    workspace_dir: &Path,
+) -> Result<String, String> {
+    let src = file_arg(args, "path", workspace_dir)?.ok_or("Missing required parameter: path")?;
+    let dst = match file_arg(args, "output", workspace_dir)? {
+        Some(dst) => dst,
+        None => match src.to_string_lossy().strip_suffix(".age") {
+            Some(stem) => PathBuf::from(stem),
+            None => PathBuf::from(format!("{}.decrypted", src.display())),
+        },
+    };
+    check_write(&dst, workspace_dir)?;
+    let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+    if dst.exists() && !overwrite {
+        return Err(format!("{} already exists; pass overwrite: true to replace it", dst.display()));
+    }
+    if crate::tools::is_dry_run() {
+        return Ok(format!(
+            "[dry-run] decrypt_file would decrypt {} → {} — no changes were made.",
+            src.display(),
+            dst.display()
+        ));
+    }
+
+    let mut mgr = vault.lock().await;
+    let passphrase = match args.get("passphraseSecret").and_then(|v| v.as_str()) {
+        Some(secret) => Some(vault_passphrase(&mut mgr, secret)?),
+        None => None,
+    };
+    // A named key must be readable; otherwise try every age key the
+    // policies allow.
+    let names: Vec<String> = match args.get("key").and_then(|v| v.as_str()) {
+        Some(key) => vec![key.to_string()],
+        None => mgr
+            .list_credentials()
+            .into_iter()
+            .filter(|(_, entry)| entry.kind == SecretKind::AgeKey)
+            .map(|(name, _)| name)
+            .collect(),
+    };
+    let explicit = args.get("key").is_some();
+    let mut identities = Vec::new();
+    for name in &names {
+        match mgr.get_credential(name, &AccessContext::default()) {
+            Ok(Some((entry, CredentialValue::Single(identity)))) if entry.kind == SecretKind::AgeKey => {
+                identities.push(identity)
+            }
+            Ok(Some((entry, _))) => return Err(format!("'{}' is a {} credential, not an age key.", name, entry.kind)),
+            Ok(None) => return Err(format!("age key '{}' not found.", name)),
+            Err(e) if explicit => return Err(e.to_string()),
+            Err(e) => debug!(credential = %name, error = %e, "Skipping age key"),
+        }
+    }
+    drop(mgr);
+
+    debug!(src = %src.display(), dst = %dst.display(), keys = identities.len(), "Decrypting file");
+    let (src_c, dst_c) = (src.clone(), dst.clone());
+    tokio::task::spawn_blocking(move || {
+        age_crypt::decrypt_file(&src_c, &dst_c, &identities, passphrase.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Decryption task failed: {}", e))??;
+
+    Ok(format!("Decrypted {} → {}", src.display(), dst.display()))
+}
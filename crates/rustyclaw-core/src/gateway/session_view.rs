@@ -0,0 +1,197 @@
+//! Read-only views of background work for the TUI's split pane.
+//!
+//! A TUI connection can watch one target — a sub-agent session or a cron
+//! job's run log — while chatting with the main agent.  The gateway builds
+//! a [`SessionView`] of the target, sends it as a `SessionView` frame and
+//! re-sends it whenever it changes.
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// How often a watched target is re-read.
+pub const VIEW_REFRESH: Duration = Duration::from_secs(2);
+
+/// Most recent lines kept in a view.
+const MAX_VIEW_LINES: usize = 200;
+
+/// Cron runs shown in a cron job view.
+const MAX_CRON_RUNS: usize = 50;
+
+/// What the split pane shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchTarget {
+    /// A sub-agent session, by key or label.
+    Session(String),
+    /// A cron job's run log, by job ID.
+    Cron(String),
+}
+
+impl WatchTarget {
+    /// Parse `session <key|label>` or `cron <jobId>`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.split_whitespace();
+        let kind = parts.next().unwrap_or("");
+        let id = parts.next().ok_or_else(|| format!("Missing {} to watch", kind))?;
+        match kind {
+            "session" | "agent" => Ok(Self::Session(id.to_string())),
+            "cron" => Ok(Self::Cron(id.to_string())),
+            other => Err(format!("Unknown view '{}' (expected session or cron)", other)),
+        }
+    }
+}
+
+/// One line of a view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewLine {
+    /// `user`, `assistant`, `tool`, `system`, or a cron run status.
+    pub role: String,
+    pub text: String,
+}
+
+/// Snapshot of a watched target.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionView {
+    pub title: String,
+    pub status: String,
+    pub lines: Vec<ViewLine>,
+}
+
+/// Build the current view of `target`.
+pub fn build_view(target: &WatchTarget, workspace_dir: &Path) -> Result<SessionView, String> {
+    match target {
+        WatchTarget::Session(id) => session_view(id),
+        WatchTarget::Cron(id) => cron_view(id, workspace_dir),
+    }
+}
+
+fn session_view(id: &str) -> Result<SessionView, String> {
+    let manager = crate::sessions::session_manager()
+        .lock()
+        .map_err(|_| "Session manager lock poisoned".to_string())?;
+    let session = manager
+        .get(id)
+        .or_else(|| manager.get_by_label(id))
+        .ok_or_else(|| format!("Session not found: {}", id))?;
+
+    let title = session
+        .label
+        .clone()
+        .or_else(|| session.task.clone())
+        .unwrap_or_else(|| session.key.clone());
+    let skip = session.messages.len().saturating_sub(MAX_VIEW_LINES);
+    let lines = session
+        .messages
+        .iter()
+        .skip(skip)
+        .map(|m| ViewLine {
+            role: m.role.clone(),
+            text: match &m.tool_name {
+                Some(tool) => format!("[{}] {}", tool, m.content),
+                None => m.content.clone(),
+            },
+        })
+        .collect();
+
+    Ok(SessionView {
+        title,
+        status: format!("{:?}", session.status).to_lowercase(),
+        lines,
+    })
+}
+
+fn cron_view(job_id: &str, workspace_dir: &Path) -> Result<SessionView, String> {
+    let store = crate::cron::CronStore::new(&workspace_dir.join(".cron"))?;
+    let job = store
+        .get(job_id)
+        .ok_or_else(|| format!("Cron job not found: {}", job_id))?;
+
+    let lines = store
+        .get_runs(job_id, MAX_CRON_RUNS)?
+        .into_iter()
+        .rev()
+        .map(|run| {
+            let mut text = local_time(run.started_ms);
+            if let Some(finished) = run.finished_ms {
+                text.push_str(&format!(" ({}ms)", finished.saturating_sub(run.started_ms)));
+            }
+            if let Some(detail) = run.error.or(run.note) {
+                text.push_str(&format!(" — {}", detail));
+            }
+            ViewLine {
+                role: format!("{:?}", run.status).to_lowercase(),
+                text,
+            }
+        })
+        .collect();
+
+    let status = match (job.enabled, job.next_run_ms) {
+        (false, _) => "disabled".to_string(),
+        (true, Some(next)) => format!("next {}", local_time(next)),
+        (true, None) => "idle".to_string(),
+    };
+    Ok(SessionView {
+        title: job.name.clone().unwrap_or_else(|| job.job_id.clone()),
+        status,
+        lines,
+    })
+}
+
+fn local_time(ms: u64) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_watch_target() {
+        assert_eq!(
+            WatchTarget::parse("session research").unwrap(),
+            WatchTarget::Session("research".into())
+        );
+        assert_eq!(
+            WatchTarget::parse("cron job-1").unwrap(),
+            WatchTarget::Cron("job-1".into())
+        );
+        assert!(WatchTarget::parse("cron").is_err());
+        assert!(WatchTarget::parse("logs x").is_err());
+    }
+
+    #[test]
+    fn test_cron_view_lists_runs() {
+        use crate::cron::{CronJob, CronStore, Payload, Schedule, SessionTarget};
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = CronStore::new(&dir.path().join(".cron")).unwrap();
+        let job = CronJob::new(
+            Some("backup".to_string()),
+            Schedule::Every { every_ms: 3_600_000, anchor_ms: None },
+            SessionTarget::Main,
+            Payload::SystemEvent { text: "tick".into() },
+        );
+        let id = store.add(job).unwrap();
+        store
+            .record_run(&crate::cron::RunEntry {
+                job_id: id.clone(),
+                run_id: "run-1".into(),
+                started_ms: 1_000,
+                finished_ms: Some(1_250),
+                status: crate::cron::RunStatus::Error,
+                error: Some("disk full".into()),
+                note: None,
+            })
+            .unwrap();
+
+        let view = build_view(&WatchTarget::Cron(id), dir.path()).unwrap();
+        assert_eq!(view.title, "backup");
+        assert_eq!(view.lines.len(), 1);
+        assert_eq!(view.lines[0].role, "error");
+        assert!(view.lines[0].text.ends_with("(250ms) — disk full"));
+    }
+}
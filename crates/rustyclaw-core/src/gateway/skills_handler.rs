@@ -150,17 +150,25 @@ pub async fn exec_gw_skill_install(
 
     debug!(skill = name, version = version.unwrap_or("latest"), "Installing skill from registry");
 
-    let mut mgr = skill_mgr.lock().await;
-    mgr.install_from_registry(name, version).map_err(|e| {
-        warn!(skill = name, error = %e, "Failed to install skill");
-        e.to_string()
-    })?;
+    // Download and extraction block, so they run on the blocking pool
+    // (which also lets the gateway forward their progress meanwhile).
+    let shared = skill_mgr.clone();
+    let (skill, pinned) = (name.to_string(), version.map(str::to_string));
+    tokio::task::spawn_blocking(move || {
+        let mut mgr = shared.blocking_lock();
+        mgr.install_from_registry(&skill, pinned.as_deref()).map_err(|e| {
+            warn!(skill = %skill, error = %e, "Failed to install skill");
+            e.to_string()
+        })?;
 
-    // Reload skills so the new one is available immediately.
-    mgr.load_skills().map_err(|e| {
-        warn!(error = %e, "Failed to reload skills after install");
-        e.to_string()
-    })?;
+        // Reload skills so the new one is available immediately.
+        mgr.load_skills().map_err(|e| {
+            warn!(error = %e, "Failed to reload skills after install");
+            e.to_string()
+        })
+    })
+    .await
+    .map_err(|e| format!("Skill install failed: {}", e))??;
 
     let version_note = version
         .map(|v| format!(" v{}", v))
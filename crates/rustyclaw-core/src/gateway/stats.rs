@@ -0,0 +1,185 @@
+//! Live operational stats shown in the TUI header.
+//!
+//! The gateway's agent loops, messenger poller and cron watcher update a
+//! process-wide [`GatewayStats`] through the helpers below; every change
+//! is published on a `watch` channel that each client connection forwards
+//! to its TUI as a `Stats` frame.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// Cron jobs whose next run is within this window count as "due soon".
+pub const CRON_DUE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// How often the cron jobs are re-read.
+const CRON_REFRESH: Duration = Duration::from_secs(30);
+
+/// Snapshot of what the gateway is doing right now.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GatewayStats {
+    /// Provider of the most recent model call.
+    pub last_provider: Option<String>,
+    /// How long the most recent model call took.
+    pub last_latency_ms: Option<u64>,
+    /// Tool calls requested by the model that haven't finished yet.
+    pub pending_tool_calls: u32,
+    /// Messenger messages received but not yet processed.
+    pub messenger_queue: u32,
+    /// Enabled cron jobs due within [`CRON_DUE_WINDOW`].
+    pub cron_due_soon: u32,
+    /// Name of the next cron job to run, if one is due soon.
+    pub next_cron_job: Option<String>,
+}
+
+fn channel() -> &'static watch::Sender<GatewayStats> {
+    static STATS: OnceLock<watch::Sender<GatewayStats>> = OnceLock::new();
+    STATS.get_or_init(|| watch::channel(GatewayStats::default()).0)
+}
+
+/// Apply `f`, notifying subscribers only if something changed.
+fn update(f: impl FnOnce(&mut GatewayStats)) {
+    channel().send_if_modified(|stats| {
+        let before = stats.clone();
+        f(stats);
+        *stats != before
+    });
+}
+
+/// Current stats.
+pub fn snapshot() -> GatewayStats {
+    channel().borrow().clone()
+}
+
+/// Receive every stats change.
+pub fn subscribe() -> watch::Receiver<GatewayStats> {
+    channel().subscribe()
+}
+
+/// Record a finished model call.
+pub fn record_provider_call(provider: &str, latency: Duration) {
+    update(|s| {
+        s.last_provider = Some(provider.to_string());
+        s.last_latency_ms = Some(latency.as_millis() as u64);
+    });
+}
+
+/// Set how many polled messenger messages are still waiting.
+pub fn set_messenger_queue(depth: usize) {
+    update(|s| s.messenger_queue = depth as u32);
+}
+
+/// Tool calls of one model round, counted as pending until each is marked
+/// done.  Dropping the guard releases whatever is left (e.g. when a turn
+/// is cancelled mid-round), so the count can't leak.
+pub struct PendingTools {
+    remaining: u32,
+}
+
+impl PendingTools {
+    pub fn new(count: usize) -> Self {
+        let remaining = count as u32;
+        update(|s| s.pending_tool_calls += remaining);
+        Self { remaining }
+    }
+
+    /// Mark one tool call finished.
+    pub fn done(&mut self) {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            update(|s| s.pending_tool_calls = s.pending_tool_calls.saturating_sub(1));
+        }
+    }
+}
+
+impl Drop for PendingTools {
+    fn drop(&mut self) {
+        let remaining = self.remaining;
+        if remaining > 0 {
+            update(|s| s.pending_tool_calls = s.pending_tool_calls.saturating_sub(remaining));
+        }
+    }
+}
+
+/// Count enabled cron jobs due within [`CRON_DUE_WINDOW`] of `now_ms`.
+/// Returns the count and the name of the soonest one.
+pub fn cron_due_soon(workspace_dir: &Path, now_ms: u64) -> (u32, Option<String>) {
+    let Ok(store) = crate::cron::CronStore::new(&workspace_dir.join(".cron")) else {
+        return (0, None);
+    };
+    let horizon = now_ms + CRON_DUE_WINDOW.as_millis() as u64;
+    let mut due: Vec<(u64, String)> = store
+        .list(false)
+        .into_iter()
+        .filter_map(|job| {
+            let next = job
+                .next_run_ms
+                .filter(|&ms| ms >= now_ms)
+                .or_else(|| job.schedule.next_run(now_ms).ok().flatten())?;
+            let name = job.name.clone().unwrap_or_else(|| job.job_id.clone());
+            (next <= horizon).then_some((next, name))
+        })
+        .collect();
+    due.sort();
+    (due.len() as u32, due.into_iter().next().map(|(_, name)| name))
+}
+
+/// Refresh the cron part of the stats.
+pub fn refresh_cron(workspace_dir: &Path) {
+    let (count, next) = cron_due_soon(workspace_dir, crate::cron::now_ms());
+    update(|s| {
+        s.cron_due_soon = count;
+        s.next_cron_job = next;
+    });
+}
+
+/// Keep the cron stat current until cancelled.
+pub async fn run_cron_watch(workspace_dir: PathBuf, cancel: CancellationToken) {
+    loop {
+        refresh_cron(&workspace_dir);
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep(CRON_REFRESH) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_tools_guard_releases_on_drop() {
+        let before = snapshot().pending_tool_calls;
+        {
+            let mut pending = PendingTools::new(3);
+            pending.done();
+            assert_eq!(snapshot().pending_tool_calls, before + 2);
+        }
+        assert_eq!(snapshot().pending_tool_calls, before);
+    }
+
+    #[test]
+    fn test_cron_due_soon() {
+        use crate::cron::{CronJob, CronStore, Payload, Schedule, SessionTarget};
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = CronStore::new(&dir.path().join(".cron")).unwrap();
+        let every = |name: &str, minutes: u64| {
+            CronJob::new(
+                Some(name.to_string()),
+                Schedule::Every { every_ms: minutes * 60_000, anchor_ms: None },
+                SessionTarget::Main,
+                Payload::SystemEvent { text: "tick".into() },
+            )
+        };
+        store.add(every("backup", 60)).unwrap();
+        store.add(every("poll", 5)).unwrap();
+
+        let (count, next) = cron_due_soon(dir.path(), crate::cron::now_ms());
+        assert_eq!(count, 1);
+        assert_eq!(next.as_deref(), Some("poll"));
+    }
+}
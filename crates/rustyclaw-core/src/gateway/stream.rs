@@ -0,0 +1,941 @@
+//! Streaming model responses.
+//!
+//! The provider callers ask for `stream: true` and read the reply as
+//! server-sent events.  [`open`] sends the request and returns an async
+//! [`Stream`] of [`StreamEvent`]s — text and thinking deltas, tool-call
+//! deltas, and a [`StreamEvent::ToolCall`] as soon as a call's arguments
+//! are complete:
+//!
+//! - **OpenAI-compatible**: a call is complete when the next call starts
+//!   or the choice finishes.
+//! - **Anthropic**: on the tool block's `content_block_stop`.
+//! - **Google**: `functionCall` parts always arrive whole.
+//!
+//! [`collect`] folds the events back into a [`ModelResponse`], forwarding
+//! deltas to the client.  The tool loops pass it a callback that hands
+//! finished calls to an [`EarlyTools`] runner, so tools start executing
+//! while the model is still writing the rest of its reply.
+
+use anyhow::{Context, Result};
+use futures_util::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, instrument, trace, warn, Instrument};
+
+use super::mock_provider;
+use super::providers::{self, send_checked};
+use super::protocol::server;
+use super::types::{ModelResponse, ParsedToolCall, ProviderRequest};
+use super::WsWriter;
+use crate::error::Error;
+use crate::tools;
+
+/// How long an OpenAI-compatible stream may go quiet before it is treated
+/// as finished.  Some proxies never close the connection.
+const OPENAI_IDLE: Duration = Duration::from_secs(30);
+
+/// One piece of a streamed model reply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// Answer text.
+    Text(String),
+    /// An extended-thinking block started.
+    ThinkingStart,
+    /// Thinking text.
+    ThinkingDelta(String),
+    /// The thinking block ended, with a short summary of it.
+    ThinkingEnd(Option<String>),
+    /// The model started a tool call.
+    ToolCallStart { index: usize, id: String, name: String },
+    /// More of a tool call's JSON arguments.
+    ToolCallDelta { index: usize, arguments: String },
+    /// A tool call whose arguments are complete.
+    ToolCall(ParsedToolCall),
+    /// Token counts; either may be missing from a given event.
+    Usage { prompt_tokens: Option<u64>, completion_tokens: Option<u64> },
+    /// Why the model stopped (`stop`, `tool_calls`, `end_turn`, …).
+    Finish(String),
+}
+
+/// A model reply as it streams in.
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>;
+
+// ── Opening streams ─────────────────────────────────────────────────────────
+
+/// Send `req` to its provider with streaming on.
+pub async fn open(http: &reqwest::Client, req: &ProviderRequest) -> Result<EventStream> {
+    if req.provider == "anthropic" {
+        open_anthropic(http, req).await
+    } else if req.provider == "google" {
+        open_google(http, req).await
+    } else if req.provider == mock_provider::MOCK_PROVIDER {
+        mock_provider::call_mock_with_tools(req).map(replay)
+    } else {
+        open_openai(http, req).await
+    }
+}
+
+/// Stream from an OpenAI-compatible `/chat/completions` endpoint.
+///
+/// Some servers ignore `stream: true` and answer with one JSON body (or
+/// SSE text under the wrong content type); both are replayed as events.
+pub async fn open_openai(http: &reqwest::Client, req: &ProviderRequest) -> Result<EventStream> {
+    let resp = send_checked(providers::openai_request(http, req), &req.provider).await?;
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if content_type.contains("text/event-stream") {
+        return Ok(sse_events(resp.bytes_stream(), Assembler::new(Dialect::OpenAi), Some(OPENAI_IDLE)));
+    }
+
+    let text = resp.text().await.context("Failed to read response body")?;
+    if text.trim_start().starts_with("data:") {
+        let body = futures_util::stream::iter([Ok::<_, reqwest::Error>(text.into_bytes())]);
+        return Ok(sse_events(body, Assembler::new(Dialect::OpenAi), None));
+    }
+    let data: Value = serde_json::from_str(&text).context("Invalid JSON from provider")?;
+    Ok(replay(providers::parse_openai_response(&data)))
+}
+
+/// Stream from the Anthropic Messages API.
+pub async fn open_anthropic(http: &reqwest::Client, req: &ProviderRequest) -> Result<EventStream> {
+    let resp = send_checked(providers::anthropic_request(http, req, true), "anthropic").await?;
+    Ok(sse_events(resp.bytes_stream(), Assembler::new(Dialect::Anthropic), None))
+}
+
+/// Stream from Gemini's `streamGenerateContent?alt=sse`.
+pub async fn open_google(http: &reqwest::Client, req: &ProviderRequest) -> Result<EventStream> {
+    let resp = send_checked(providers::google_request(http, req, true), "google").await?;
+    Ok(sse_events(resp.bytes_stream(), Assembler::new(Dialect::Google), None))
+}
+
+/// The events a streamed reply would have produced, for a reply that
+/// arrived whole.
+pub fn replay(resp: ModelResponse) -> EventStream {
+    let mut events = Vec::new();
+    if !resp.text.is_empty() {
+        events.push(StreamEvent::Text(resp.text));
+    }
+    events.extend(resp.tool_calls.into_iter().map(StreamEvent::ToolCall));
+    if resp.prompt_tokens.is_some() || resp.completion_tokens.is_some() {
+        events.push(StreamEvent::Usage {
+            prompt_tokens: resp.prompt_tokens,
+            completion_tokens: resp.completion_tokens,
+        });
+    }
+    events.extend(resp.finish_reason.map(StreamEvent::Finish));
+    Box::pin(futures_util::stream::iter(events.into_iter().map(Ok)))
+}
+
+// ── Collecting ──────────────────────────────────────────────────────────────
+
+/// Read a stream to the end and assemble the [`ModelResponse`].
+///
+/// Text and thinking deltas go to `writer` as they arrive; `on_tool_call`
+/// sees each tool call as soon as its arguments are complete.
+pub async fn collect(
+    mut events: EventStream,
+    mut writer: Option<&mut WsWriter>,
+    mut on_tool_call: impl FnMut(&ParsedToolCall),
+) -> Result<ModelResponse> {
+    let mut result = ModelResponse { streamed: writer.is_some(), ..Default::default() };
+    while let Some(event) = events.next().await {
+        match event? {
+            StreamEvent::Text(text) => {
+                if let Some(w) = writer.as_deref_mut() {
+                    let _ = providers::send_chunk(w, &text).await;
+                }
+                result.text.push_str(&text);
+            }
+            StreamEvent::ThinkingStart => {
+                if let Some(w) = writer.as_deref_mut() {
+                    let _ = providers::send_thinking_start(w).await;
+                }
+            }
+            StreamEvent::ThinkingDelta(text) => {
+                if let Some(w) = writer.as_deref_mut() {
+                    let _ = providers::send_thinking_delta(w, &text).await;
+                }
+            }
+            StreamEvent::ThinkingEnd(summary) => {
+                if let Some(w) = writer.as_deref_mut() {
+                    let _ = providers::send_thinking_end(w, summary.as_deref()).await;
+                }
+            }
+            StreamEvent::ToolCallStart { index, name, .. } => {
+                trace!(index, tool = %name, "Tool call started");
+            }
+            StreamEvent::ToolCallDelta { .. } => {}
+            StreamEvent::ToolCall(tc) => {
+                on_tool_call(&tc);
+                result.tool_calls.push(tc);
+            }
+            StreamEvent::Usage { prompt_tokens, completion_tokens } => {
+                result.prompt_tokens = prompt_tokens.or(result.prompt_tokens);
+                result.completion_tokens = completion_tokens.or(result.completion_tokens);
+            }
+            StreamEvent::Finish(reason) => result.finish_reason = Some(reason),
+        }
+    }
+    // Whitespace-only text is dropped; sending it back upsets some APIs.
+    if result.text.trim().is_empty() {
+        result.text.clear();
+    }
+    trace!(
+        text_len = result.text.len(),
+        tool_calls = result.tool_calls.len(),
+        finish_reason = ?result.finish_reason,
+        "Model stream complete"
+    );
+    Ok(result)
+}
+
+/// Send `req` with streaming on and collect the reply; see [`open`] and
+/// [`collect`].  A `stream_start` frame goes to `writer` before the
+/// request.
+#[instrument(name = "provider_call", skip_all, fields(provider = %req.provider, model = %req.model))]
+pub async fn call(
+    http: &reqwest::Client,
+    req: &ProviderRequest,
+    mut writer: Option<&mut WsWriter>,
+    on_tool_call: impl FnMut(&ParsedToolCall),
+) -> Result<ModelResponse> {
+    if req.provider == mock_provider::MOCK_PROVIDER {
+        // The mock's replies arrive whole; the loop sends their text.
+        writer = None;
+    }
+    if let Some(w) = writer.as_deref_mut() {
+        server::send_stream_start(w).await?;
+    }
+    let events = open(http, req).await?;
+    collect(events, writer, on_tool_call).await
+}
+
+// ── SSE decoding ────────────────────────────────────────────────────────────
+
+/// One server-sent event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseFrame {
+    /// The `event:` field, empty when absent.
+    pub event: String,
+    /// The `data:` lines, joined with newlines.
+    pub data: String,
+}
+
+/// Splits a byte stream into [`SseFrame`]s.  Events may be split across
+/// chunks anywhere, including inside a UTF-8 character.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add bytes; returns the events they complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<SseFrame> {
+        // Gemini separates events with CRLF pairs.
+        self.buffer.extend(bytes.iter().filter(|&&b| b != b'\r'));
+        let mut frames = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let raw: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            frames.extend(parse_frame(&String::from_utf8_lossy(&raw[..end])));
+        }
+        frames
+    }
+
+    /// The last event, when the stream ends without a blank line.
+    pub fn finish(&mut self) -> Option<SseFrame> {
+        let raw = std::mem::take(&mut self.buffer);
+        parse_frame(&String::from_utf8_lossy(&raw))
+    }
+}
+
+fn parse_frame(raw: &str) -> Option<SseFrame> {
+    let mut frame = SseFrame::default();
+    let mut data: Vec<&str> = Vec::new();
+    for line in raw.lines() {
+        if let Some(event) = line.strip_prefix("event:") {
+            frame.event = event.trim().to_string();
+        } else if let Some(d) = line.strip_prefix("data:") {
+            data.push(d.strip_prefix(' ').unwrap_or(d));
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    frame.data = data.join("\n");
+    Some(frame)
+}
+
+// ── Event assembly ──────────────────────────────────────────────────────────
+
+/// The SSE format a provider speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    OpenAi,
+    Anthropic,
+    Google,
+}
+
+/// A tool call whose arguments are still arriving.
+#[derive(Debug, Default)]
+struct PartialCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl PartialCall {
+    fn finish(self) -> ParsedToolCall {
+        let arguments = if self.arguments.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(&self.arguments).unwrap_or(json!({}))
+        };
+        ParsedToolCall { id: self.id, name: self.name, arguments }
+    }
+}
+
+/// Turns a provider's SSE frames into [`StreamEvent`]s.
+#[derive(Debug)]
+pub struct Assembler {
+    dialect: Dialect,
+    /// Calls still receiving arguments, by index.
+    calls: HashMap<usize, PartialCall>,
+    /// Calls emitted so far (Gemini ids are numbered by it).
+    emitted: usize,
+    thinking: Option<String>,
+    done: bool,
+}
+
+impl Assembler {
+    pub fn new(dialect: Dialect) -> Self {
+        Self { dialect, calls: HashMap::new(), emitted: 0, thinking: None, done: false }
+    }
+
+    /// Whether the provider signalled the end of the reply.
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// The events in one frame.
+    pub fn frame(&mut self, frame: &SseFrame) -> Result<Vec<StreamEvent>> {
+        let mut events = Vec::new();
+        if self.done {
+            return Ok(events);
+        }
+        if frame.data.trim() == "[DONE]" {
+            trace!("SSE received [DONE]");
+            self.done = true;
+            return Ok(self.flush());
+        }
+        let Ok(json) = serde_json::from_str::<Value>(&frame.data) else {
+            trace!(event = %frame.event, "Skipping unparsable SSE data");
+            return Ok(events);
+        };
+        match self.dialect {
+            Dialect::OpenAi => self.openai(&json, &mut events),
+            Dialect::Anthropic => self.anthropic(&frame.event, &json, &mut events)?,
+            Dialect::Google => self.google(&json, &mut events)?,
+        }
+        Ok(events)
+    }
+
+    /// Calls still open when the stream ended.
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        self.flush()
+    }
+
+    /// Emit every open call, in index order.  Calls without an id or name
+    /// never got started properly and are dropped.
+    fn flush(&mut self) -> Vec<StreamEvent> {
+        let mut open: Vec<(usize, PartialCall)> = self.calls.drain().collect();
+        open.sort_by_key(|(i, _)| *i);
+        self.emit(open)
+    }
+
+    fn emit(&mut self, calls: Vec<(usize, PartialCall)>) -> Vec<StreamEvent> {
+        let total = calls.len();
+        let events: Vec<StreamEvent> = calls
+            .into_iter()
+            .filter(|(_, c)| !c.id.is_empty() && !c.name.is_empty())
+            .map(|(_, c)| StreamEvent::ToolCall(c.finish()))
+            .collect();
+        if events.len() < total {
+            debug!(incomplete = total - events.len(), remaining = events.len(), "Filtered incomplete tool calls");
+        }
+        self.emitted += events.len();
+        events
+    }
+
+    fn openai(&mut self, json: &Value, events: &mut Vec<StreamEvent>) {
+        let mut finished = None;
+        for choice in json["choices"].as_array().into_iter().flatten() {
+            let delta = &choice["delta"];
+            if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+                events.push(StreamEvent::Text(text.to_string()));
+            }
+            for tc in delta["tool_calls"].as_array().into_iter().flatten() {
+                let index = tc["index"].as_u64().unwrap_or(0) as usize;
+                // A new call means the ones before it are complete.
+                let done: Vec<usize> = self.calls.keys().copied().filter(|&i| i < index).collect();
+                if !done.is_empty() {
+                    let mut ready: Vec<(usize, PartialCall)> =
+                        done.into_iter().filter_map(|i| self.calls.remove(&i).map(|c| (i, c))).collect();
+                    ready.sort_by_key(|(i, _)| *i);
+                    events.extend(self.emit(ready));
+                }
+                let call = self.calls.entry(index).or_default();
+                if let Some(id) = tc["id"].as_str().filter(|s| !s.is_empty()) {
+                    call.id = id.to_string();
+                }
+                if let Some(name) = tc["function"]["name"].as_str().filter(|s| !s.is_empty()) {
+                    call.name = name.to_string();
+                    events.push(StreamEvent::ToolCallStart { index, id: call.id.clone(), name: call.name.clone() });
+                }
+                if let Some(args) = tc["function"]["arguments"].as_str().filter(|s| !s.is_empty()) {
+                    call.arguments.push_str(args);
+                    events.push(StreamEvent::ToolCallDelta { index, arguments: args.to_string() });
+                }
+            }
+            if let Some(fr) = choice["finish_reason"].as_str() {
+                finished = Some(fr.to_string());
+            }
+        }
+        if let Some(usage) = json.get("usage").filter(|u| !u.is_null()) {
+            events.push(StreamEvent::Usage {
+                prompt_tokens: usage["prompt_tokens"].as_u64(),
+                completion_tokens: usage["completion_tokens"].as_u64(),
+            });
+        }
+        if let Some(fr) = finished {
+            events.extend(self.flush());
+            // Terminal reasons mean the model is done; don't wait for
+            // [DONE] from servers that never send it.
+            if matches!(fr.as_str(), "stop" | "tool_calls" | "tool_use" | "length" | "end_turn") {
+                trace!(finish_reason = %fr, "SSE stream completed");
+                self.done = true;
+            }
+            events.push(StreamEvent::Finish(fr));
+        }
+    }
+
+    fn anthropic(&mut self, event: &str, json: &Value, events: &mut Vec<StreamEvent>) -> Result<()> {
+        trace!(event_type = %event, "Anthropic SSE event");
+        let index = json["index"].as_u64().unwrap_or(0) as usize;
+        match event {
+            "message_start" => {
+                let usage = &json["message"]["usage"];
+                if let Some(input) = usage["input_tokens"].as_u64() {
+                    events.push(StreamEvent::Usage { prompt_tokens: Some(input), completion_tokens: None });
+                }
+            }
+            "content_block_start" => {
+                let block = &json["content_block"];
+                match block["type"].as_str() {
+                    Some("thinking") => {
+                        self.thinking = Some(String::new());
+                        events.push(StreamEvent::ThinkingStart);
+                    }
+                    Some("tool_use") => {
+                        let call = PartialCall {
+                            id: block["id"].as_str().unwrap_or("").to_string(),
+                            name: block["name"].as_str().unwrap_or("").to_string(),
+                            arguments: String::new(),
+                        };
+                        events.push(StreamEvent::ToolCallStart { index, id: call.id.clone(), name: call.name.clone() });
+                        self.calls.insert(index, call);
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_delta" => {
+                let delta = &json["delta"];
+                match delta["type"].as_str() {
+                    Some("thinking_delta") => {
+                        if let Some(text) = delta["thinking"].as_str() {
+                            if let Some(thinking) = self.thinking.as_mut() {
+                                thinking.push_str(text);
+                            }
+                            events.push(StreamEvent::ThinkingDelta(text.to_string()));
+                        }
+                    }
+                    Some("text_delta") => {
+                        if let Some(text) = delta["text"].as_str() {
+                            events.push(StreamEvent::Text(text.to_string()));
+                        }
+                    }
+                    Some("input_json_delta") => {
+                        if let Some(partial) = delta["partial_json"].as_str() {
+                            if let Some(call) = self.calls.get_mut(&index) {
+                                call.arguments.push_str(partial);
+                            }
+                            events.push(StreamEvent::ToolCallDelta { index, arguments: partial.to_string() });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                if let Some(thinking) = self.thinking.take() {
+                    events.push(StreamEvent::ThinkingEnd(thinking_summary(&thinking)));
+                }
+                if let Some(call) = self.calls.remove(&index) {
+                    events.extend(self.emit(vec![(index, call)]));
+                }
+            }
+            "message_delta" => {
+                if let Some(output) = json["usage"]["output_tokens"].as_u64() {
+                    events.push(StreamEvent::Usage { prompt_tokens: None, completion_tokens: Some(output) });
+                }
+                if let Some(reason) = json["delta"]["stop_reason"].as_str() {
+                    events.push(StreamEvent::Finish(reason.to_string()));
+                }
+            }
+            "message_stop" => {
+                events.extend(self.flush());
+                self.done = true;
+            }
+            "error" => {
+                let msg = json["error"]["message"].as_str().unwrap_or("Unknown error");
+                return Err(Error::provider("anthropic", None, format!("stream error: {}", msg)).into());
+            }
+            _ => trace!(event_type = %event, "Unhandled Anthropic SSE event type"),
+        }
+        Ok(())
+    }
+
+    fn google(&mut self, json: &Value, events: &mut Vec<StreamEvent>) -> Result<()> {
+        if let Some(msg) = json["error"]["message"].as_str() {
+            return Err(Error::provider("google", None, format!("stream error: {}", msg)).into());
+        }
+        let candidate = &json["candidates"][0];
+        for part in candidate["content"]["parts"].as_array().into_iter().flatten() {
+            if let Some(text) = part["text"].as_str().filter(|t| !t.is_empty()) {
+                events.push(StreamEvent::Text(text.to_string()));
+            }
+            if let Some(fc) = part.get("functionCall") {
+                events.push(StreamEvent::ToolCall(ParsedToolCall {
+                    id: format!("google_call_{}", self.emitted),
+                    name: fc["name"].as_str().unwrap_or("").to_string(),
+                    arguments: fc["args"].clone(),
+                }));
+                self.emitted += 1;
+            }
+        }
+        if let Some(usage) = json.get("usageMetadata") {
+            events.push(StreamEvent::Usage {
+                prompt_tokens: usage["promptTokenCount"].as_u64(),
+                completion_tokens: usage["candidatesTokenCount"].as_u64(),
+            });
+        }
+        if let Some(fr) = candidate["finishReason"].as_str() {
+            events.push(StreamEvent::Finish(fr.to_lowercase()));
+        }
+        Ok(())
+    }
+}
+
+/// First sentence (or first 100 bytes) of a thinking block.
+fn thinking_summary(thinking: &str) -> Option<String> {
+    if thinking.is_empty() {
+        return None;
+    }
+    if thinking.len() <= 100 {
+        return Some(thinking.to_string());
+    }
+    let mut end = 100;
+    while !thinking.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &thinking[..end];
+    Some(match head.find(". ") {
+        Some(period) => head[..=period].to_string(),
+        None => head.to_string(),
+    })
+}
+
+/// Events from an SSE response body.  With `idle` set, a body that goes
+/// quiet that long counts as ended.
+pub fn sse_events<S, B>(body: S, assembler: Assembler, idle: Option<Duration>) -> EventStream
+where
+    S: Stream<Item = reqwest::Result<B>> + Send + 'static,
+    B: AsRef<[u8]> + Send,
+{
+    struct Reader<S> {
+        body: Pin<Box<S>>,
+        decoder: SseDecoder,
+        assembler: Assembler,
+        queue: VecDeque<StreamEvent>,
+        idle: Option<Duration>,
+        ended: bool,
+    }
+
+    impl<S> Reader<S> {
+        fn frames(&mut self, frames: impl IntoIterator<Item = SseFrame>) -> Result<()> {
+            for frame in frames {
+                let events = self.assembler.frame(&frame)?;
+                self.queue.extend(events);
+                if self.assembler.done() {
+                    self.ended = true;
+                    break;
+                }
+            }
+            Ok(())
+        }
+
+        fn end(&mut self) -> Result<()> {
+            let last = self.decoder.finish();
+            if !self.ended {
+                self.frames(last)?;
+            }
+            self.ended = true;
+            self.queue.extend(self.assembler.finish());
+            Ok(())
+        }
+    }
+
+    let reader = Reader {
+        body: Box::pin(body),
+        decoder: SseDecoder::new(),
+        assembler,
+        queue: VecDeque::new(),
+        idle,
+        ended: false,
+    };
+    Box::pin(futures_util::stream::unfold(reader, |mut r| async move {
+        loop {
+            if let Some(event) = r.queue.pop_front() {
+                return Some((Ok(event), r));
+            }
+            if r.ended {
+                return None;
+            }
+            let next = match r.idle {
+                Some(idle) => match tokio::time::timeout(idle, r.body.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        warn!(timeout_secs = idle.as_secs(), "SSE stream timeout");
+                        None
+                    }
+                },
+                None => r.body.next().await,
+            };
+            let step = match next {
+                Some(Ok(chunk)) => {
+                    let frames = r.decoder.push(chunk.as_ref());
+                    let step = r.frames(frames);
+                    if r.ended { step.and_then(|()| r.end()) } else { step }
+                }
+                Some(Err(e)) => Err(anyhow::Error::new(e).context("SSE stream read error")),
+                None => r.end(),
+            };
+            if let Err(e) = step {
+                r.ended = true;
+                r.queue.clear();
+                return Some((Err(e), r));
+            }
+        }
+    }))
+}
+
+// ── Early tool starts ───────────────────────────────────────────────────────
+
+type Job = (ParsedToolCall, oneshot::Sender<Result<String, String>>);
+
+/// Runs tool calls while the model is still streaming.
+///
+/// Calls run one at a time, in the order the model made them, so a
+/// `write_file` followed by a `read_file` behaves as it would in the tool
+/// loop.  The loop offers each call as it completes, saying whether it may
+/// run unattended; once one may not (it needs approval, asks the user, or
+/// touches secrets) nothing after it in the round starts early either.
+/// Dropping the runner stops calls that haven't started.
+pub struct EarlyTools {
+    workspace_dir: PathBuf,
+    deadline: tools::TurnDeadline,
+    queue: Option<mpsc::UnboundedSender<Job>>,
+    worker: Option<tokio::task::JoinHandle<()>>,
+    results: HashMap<String, oneshot::Receiver<Result<String, String>>>,
+    closed: bool,
+}
+
+impl EarlyTools {
+    pub fn new(workspace_dir: &std::path::Path, deadline: &tools::TurnDeadline) -> Self {
+        Self {
+            workspace_dir: workspace_dir.to_path_buf(),
+            deadline: deadline.clone(),
+            queue: None,
+            worker: None,
+            results: HashMap::new(),
+            closed: false,
+        }
+    }
+
+    /// A runner that never starts anything.
+    pub fn disabled() -> Self {
+        let mut early = Self::new(std::path::Path::new("."), &tools::TurnDeadline::new(None));
+        early.closed = true;
+        early
+    }
+
+    /// Offer a finished call.  Built-in tools only; skill and secrets
+    /// tools, and anything `eligible` rules out, wait for the loop.
+    pub fn offer(&mut self, tc: &ParsedToolCall, eligible: bool) {
+        let eligible = eligible
+            && !tools::is_user_prompt_tool(&tc.name)
+            && !tools::is_secrets_tool(&tc.name)
+            && !tools::is_skill_tool(&tc.name);
+        if !eligible {
+            self.closed = true;
+        }
+        if self.closed || self.results.contains_key(&tc.id) {
+            return;
+        }
+        let queue = self.queue.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            self.worker = Some(spawn_worker(rx, self.workspace_dir.clone(), self.deadline.clone()));
+            tx
+        });
+        let (done, result) = oneshot::channel();
+        if queue.send((tc.clone(), done)).is_ok() {
+            debug!(tool = %tc.name, id = %tc.id, "Starting tool call early");
+            self.results.insert(tc.id.clone(), result);
+        }
+    }
+
+    /// The pending result of a call started early.
+    pub fn take(&mut self, id: &str) -> Option<impl std::future::Future<Output = Result<String, String>> + use<>> {
+        let result = self.results.remove(id)?;
+        Some(async move { result.await.unwrap_or_else(|_| Err("Tool execution was cancelled".to_string())) })
+    }
+}
+
+impl Drop for EarlyTools {
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.abort();
+        }
+    }
+}
+
+fn spawn_worker(
+    mut rx: mpsc::UnboundedReceiver<Job>,
+    workspace_dir: PathBuf,
+    deadline: tools::TurnDeadline,
+) -> tokio::task::JoinHandle<()> {
+    let trace_id = crate::observability::trace::current();
+    let session = crate::sessions::current_session();
+    let conversation = crate::sessions::current_conversation();
+    let dry_run = tools::current_dry_run_session();
+    let work = async move {
+        while let Some((tc, done)) = rx.recv().await {
+            let run = tools::execute_tool_offloaded(&tc.name, &tc.arguments, &workspace_dir);
+            let _ = done.send(deadline.run(&tc.name, run).await);
+        }
+    };
+    let work = work.instrument(tracing::Span::current());
+    // Task-locals don't cross `spawn`; carry the turn's over so plans,
+    // session budgets and `/dryrun` apply to calls started early.
+    let work = async move {
+        match dry_run {
+            Some(session) => tools::dry_run_session_scope(session, work).await,
+            None => work.await,
+        }
+    };
+    let work = async move {
+        match conversation {
+            Some(key) => crate::sessions::conversation_scope(key, work).await,
+            None => work.await,
+        }
+    };
+    let work = async move {
+        match session {
+            Some(key) => crate::sessions::session_scope(key, work).await,
+            None => work.await,
+        }
+    };
+    tokio::spawn(async move {
+        match trace_id {
+            Some(id) => crate::observability::trace::scope(id, work).await,
+            None => work.await,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(chunks: &[&str]) -> impl Stream<Item = reqwest::Result<Vec<u8>>> + Send + use<> {
+        let chunks: Vec<reqwest::Result<Vec<u8>>> = chunks.iter().map(|c| Ok(c.as_bytes().to_vec())).collect();
+        futures_util::stream::iter(chunks)
+    }
+
+    async fn events(chunks: &[&str], dialect: Dialect) -> Vec<StreamEvent> {
+        let stream = sse_events(body(chunks), Assembler::new(dialect), None);
+        stream.map(|e| e.unwrap()).collect().await
+    }
+
+    fn tool_calls(events: &[StreamEvent]) -> Vec<(usize, &ParsedToolCall)> {
+        events
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| match e {
+                StreamEvent::ToolCall(tc) => Some((i, tc)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decoder_splits_frames_across_chunks() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"event: ping\r\ndata: {\"a\"").is_empty());
+        let frames = decoder.push(b":1}\r\n\r\ndata:2\n\ndata: 3");
+        assert_eq!(
+            frames,
+            vec![
+                SseFrame { event: "ping".into(), data: "{\"a\":1}".into() },
+                SseFrame { event: String::new(), data: "2".into() },
+            ]
+        );
+        assert_eq!(decoder.finish().unwrap().data, "3");
+
+        // A multi-byte character split between chunks survives.
+        let mut decoder = SseDecoder::new();
+        let bytes = "data: é\n\n".as_bytes();
+        assert!(decoder.push(&bytes[..7]).is_empty());
+        assert_eq!(decoder.push(&bytes[7..])[0].data, "é");
+    }
+
+    #[tokio::test]
+    async fn test_openai_tool_call_completes_when_next_starts() {
+        let events = events(
+            &[
+                "data: {\"choices\":[{\"delta\":{\"content\":\"Reading\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"c1\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"path\\\":\"}}]}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"a.txt\\\"}\"}}]}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"c2\",\"function\":{\"name\":\"list_directory\",\"arguments\":\"{}\"}}]}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+                "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":7}}\n\n",
+            ],
+            Dialect::OpenAi,
+        )
+        .await;
+
+        assert_eq!(events[0], StreamEvent::Text("Reading".into()));
+        let calls = tool_calls(&events);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].1.name, "read_file");
+        assert_eq!(calls[0].1.arguments, json!({"path": "a.txt"}));
+        // The first call is out before the second one's arguments.
+        let second_start = events
+            .iter()
+            .position(|e| matches!(e, StreamEvent::ToolCallStart { index: 1, .. }))
+            .unwrap();
+        assert!(calls[0].0 < second_start);
+        assert_eq!(calls[1].1.id, "c2");
+        assert_eq!(events.last(), Some(&StreamEvent::Finish("tool_calls".into())));
+    }
+
+    #[tokio::test]
+    async fn test_openai_incomplete_calls_are_dropped() {
+        let events = events(
+            &[
+                "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{}\"}}]}}]}\n\n",
+                "data: [DONE]\n\n",
+            ],
+            Dialect::OpenAi,
+        )
+        .await;
+        assert!(tool_calls(&events).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_stream() {
+        let events = events(
+            &[
+                "event: message_start\ndata: {\"message\":{\"usage\":{\"input_tokens\":12}}}\n\n",
+                "event: content_block_start\ndata: {\"index\":0,\"content_block\":{\"type\":\"thinking\"}}\n\n",
+                "event: content_block_delta\ndata: {\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"Hmm.\"}}\n\n",
+                "event: content_block_stop\ndata: {\"index\":0}\n\n",
+                "event: content_block_start\ndata: {\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"t1\",\"name\":\"read_file\"}}\n\n",
+                "event: content_block_delta\ndata: {\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"path\\\":\\\"a\\\"}\"}}\n\n",
+                "event: content_block_stop\ndata: {\"index\":1}\n\n",
+                "event: message_delta\ndata: {\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":3}}\n\n",
+                "event: message_stop\ndata: {}\n\n",
+                "event: ping\ndata: {}\n\n",
+            ],
+            Dialect::Anthropic,
+        )
+        .await;
+
+        assert_eq!(events[1], StreamEvent::ThinkingStart);
+        assert_eq!(events[3], StreamEvent::ThinkingEnd(Some("Hmm.".into())));
+        let calls = tool_calls(&events);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].1.arguments, json!({"path": "a"}));
+        assert_eq!(events.last(), Some(&StreamEvent::Finish("tool_use".into())));
+
+        let err = sse_events(
+            body(&["event: error\ndata: {\"error\":{\"message\":\"overloaded\"}}\n\n"]),
+            Assembler::new(Dialect::Anthropic),
+            None,
+        )
+        .next()
+        .await
+        .unwrap();
+        assert!(err.unwrap_err().to_string().contains("overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_google_stream() {
+        let events = events(
+            &[
+                "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hi\"}]}}]}\r\n\r\n",
+                "data: {\"candidates\":[{\"content\":{\"parts\":[{\"functionCall\":{\"name\":\"a\",\"args\":{}}},{\"functionCall\":{\"name\":\"b\",\"args\":{\"x\":1}}}]},\"finishReason\":\"STOP\"}]}\r\n\r\n",
+            ],
+            Dialect::Google,
+        )
+        .await;
+        let calls = tool_calls(&events);
+        assert_eq!(calls[0].1.id, "google_call_0");
+        assert_eq!(calls[1].1.id, "google_call_1");
+        assert_eq!(events.last(), Some(&StreamEvent::Finish("stop".into())));
+    }
+
+    #[tokio::test]
+    async fn test_collect_assembles_response() {
+        let resp = ModelResponse {
+            text: "Done.".into(),
+            tool_calls: vec![ParsedToolCall { id: "1".into(), name: "a".into(), arguments: json!({}) }],
+            finish_reason: Some("stop".into()),
+            prompt_tokens: Some(4),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        let out = collect(replay(resp), None, |tc| seen.push(tc.id.clone())).await.unwrap();
+        assert_eq!(out.text, "Done.");
+        assert_eq!(seen, vec!["1"]);
+        assert_eq!(out.prompt_tokens, Some(4));
+        assert_eq!(out.finish_reason.as_deref(), Some("stop"));
+        assert!(!out.streamed);
+    }
+
+    #[test]
+    fn test_thinking_summary() {
+        assert_eq!(thinking_summary(""), None);
+        let long = format!("First thought. {}", "é".repeat(60));
+        assert_eq!(thinking_summary(&long).as_deref(), Some("First thought."));
+    }
+}
@@ -0,0 +1,165 @@
+//! Sub-agent runner for the gateway.
+//!
+//! `sessions_spawn` and `orchestrate` only create sub-agent sessions and
+//! queue them on the session manager.  This worker picks the queued runs
+//! up and runs each one as its own task — so an orchestration's members
+//! work in parallel — with the same headless tool loop used for queued
+//! tasks.  The final text is stored as the session's last assistant
+//! message, which is what `sessions_history` and `orchestrate aggregate`
+//! read.
+
+use crate::config::Config;
+use crate::observability::trace as turn_trace;
+use crate::sessions::{runs_queued, session_manager, SubagentRun};
+use crate::tools::ToolPermission;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn, Instrument};
+
+use super::task_worker::run_headless_turn;
+use super::{ModelContext, SharedSkillManager, SharedVault};
+
+/// Maximum tool loop rounds per sub-agent run.
+const MAX_TOOL_ROUNDS: usize = 50;
+
+const SUBAGENT_SYSTEM_PROMPT: &str = "You are a sub-agent working on a task delegated by another agent. \
+     Complete the task using the available tools and finish with a concise report of your results.";
+
+/// Run queued sub-agents until cancelled.
+pub async fn run_subagent_worker(
+    config: Config,
+    model_ctx: Option<Arc<ModelContext>>,
+    vault: SharedVault,
+    skill_mgr: SharedSkillManager,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let model_ctx = match model_ctx {
+        Some(ctx) => ctx,
+        None => {
+            warn!("No model context — sub-agent worker disabled");
+            return Ok(());
+        }
+    };
+
+    let permissions = Arc::new(config.tool_permissions.clone());
+    let http = reqwest::Client::new();
+    info!("Starting sub-agent worker");
+
+    loop {
+        let runs = session_manager()
+            .lock()
+            .map(|mut mgr| mgr.take_runs())
+            .unwrap_or_default();
+
+        for run in runs {
+            let http = http.clone();
+            let model_ctx = model_ctx.clone();
+            let vault = vault.clone();
+            let skill_mgr = skill_mgr.clone();
+            let permissions = permissions.clone();
+            let trace_id = turn_trace::new_trace_id();
+            let span = turn_trace::turn_span(&trace_id, "subagent");
+            let owner = trace_id.clone();
+            tokio::spawn(
+                turn_trace::scope(trace_id, async move {
+                    run_subagent(&http, &model_ctx, &vault, &skill_mgr, &permissions, run).await;
+                    crate::tools::release_file_locks(&owner);
+                })
+                .instrument(span),
+            );
+        }
+
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Shutting down sub-agent worker");
+                break;
+            }
+            _ = runs_queued().notified() => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Run one sub-agent session to completion and record its output.
+async fn run_subagent(
+    http: &reqwest::Client,
+    model_ctx: &Arc<ModelContext>,
+    vault: &SharedVault,
+    skill_mgr: &SharedSkillManager,
+    permissions: &HashMap<String, ToolPermission>,
+    run: SubagentRun,
+) {
+    debug!(session_key = %run.session_key, model = ?run.model, "Running sub-agent");
+
+    let model_ctx = match run.model {
+        Some(ref model) => Arc::new(ModelContext {
+            model: model.clone(),
+            ..(**model_ctx).clone()
+        }),
+        None => model_ctx.clone(),
+    };
+
+    // Siblings may have used up the tree's tokens while this run waited.
+    let budget = session_manager().lock().map(|mut mgr| {
+        if let Some(session) = mgr.get_mut(&run.session_key) {
+            session.add_message("user", &run.task);
+        }
+        mgr.check_tokens(&run.session_key)
+    });
+
+    // Tools see the sub-agent as the current session, so anything it
+    // spawns is its child and counts against the same tree.
+    let outcome = match budget {
+        Ok(Err(err)) => Err(anyhow::anyhow!(err)),
+        _ => {
+            let snapshot_dir = run.workspace.clone();
+            let reason = format!("sub-agent {}", run.session_key);
+            let _ = tokio::task::spawn_blocking(move || crate::snapshots::before_run(&snapshot_dir, &reason)).await;
+
+            crate::sessions::session_scope(
+                run.session_key.clone(),
+                run_headless_turn(
+                    http,
+                    &model_ctx,
+                    vault,
+                    skill_mgr,
+                    permissions,
+                    &run.workspace,
+                    SUBAGENT_SYSTEM_PROMPT,
+                    &run.task,
+                    MAX_TOOL_ROUNDS,
+                ),
+            )
+            .await
+        }
+    };
+
+    crate::plan::remove_plan(&run.session_key);
+
+    // The turn charged its tokens to the tree as it went.
+    let Ok(mut mgr) = session_manager().lock() else {
+        return;
+    };
+    let Some(session) = mgr.get_mut(&run.session_key) else {
+        return;
+    };
+    // The parent may have stopped the session meanwhile.
+    if session.status != crate::sessions::SessionStatus::Active {
+        return;
+    }
+    match outcome {
+        Ok(turn) => {
+            session.add_message("assistant", &turn.text);
+            session.complete();
+            info!(session_key = %run.session_key, "Sub-agent finished");
+        }
+        Err(err) => {
+            session.add_message("system", &format!("Sub-agent failed: {}", err));
+            session.error();
+            warn!(session_key = %run.session_key, error = %err, "Sub-agent failed");
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Background task worker for the gateway.
+//!
+//! Polls the persistent task queue and runs each claimed task in its own
+//! worker session (a sub-agent session labelled `task:<id>`), with the same
+//! headless tool loop used for messenger conversations.  Concurrency and
+//! retries are governed by [`TaskQueueConfig`].
+
+use crate::config::Config;
+use crate::observability::trace as turn_trace;
+use crate::sessions::session_manager;
+use crate::task_queue::{
+    queue_dir, track_running, untrack_running, QueuedTask, TaskQueue, TaskQueueConfig, TaskStatus,
+};
+use crate::tools::{self, ToolPermission};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn, Instrument};
+
+use super::mock_provider;
+use super::providers;
+use super::secrets_handler;
+use super::skills_handler;
+use super::stats;
+use super::usage;
+use super::{ChatMessage, ModelContext, ProviderRequest, SharedSkillManager, SharedVault, ToolCallResult};
+
+/// Maximum tool loop rounds per task.
+const MAX_TOOL_ROUNDS: usize = 50;
+
+const TASK_SYSTEM_PROMPT: &str = "You are a background worker running a queued task without a user present. \
+     Complete the task using the available tools and finish with a short summary of what you did.";
+
+/// Run the task worker loop until cancelled.
+pub async fn run_task_worker(
+    config: Config,
+    model_ctx: Option<Arc<ModelContext>>,
+    vault: SharedVault,
+    skill_mgr: SharedSkillManager,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let model_ctx = match model_ctx {
+        Some(ctx) => ctx,
+        None => {
+            warn!("No model context — task worker disabled");
+            return Ok(());
+        }
+    };
+
+    let queue_cfg: TaskQueueConfig = config.task_queue.clone();
+    let permissions = Arc::new(config.tool_permissions.clone());
+    let workspace_dir = config.workspace_dir();
+    let dir = queue_dir(&workspace_dir);
+
+    // Tasks marked running by a previous process will never finish.
+    match TaskQueue::new(&dir).and_then(|mut q| q.requeue_stale()) {
+        Ok(0) => {}
+        Ok(n) => info!(count = n, "Re-queued tasks interrupted by restart"),
+        Err(e) => warn!(error = %e, "Failed to open task queue"),
+    }
+
+    let poll_interval = Duration::from_secs(queue_cfg.poll_interval_secs.max(1));
+    let http = reqwest::Client::new();
+
+    info!(
+        max_concurrent = queue_cfg.max_concurrent,
+        poll_interval_secs = poll_interval.as_secs(),
+        "Starting task worker"
+    );
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => {
+                info!("Shutting down task worker");
+                break;
+            }
+            _ = tokio::time::sleep(poll_interval) => {
+                loop {
+                    let claimed = TaskQueue::new(&dir)
+                        .and_then(|mut q| q.claim_next(queue_cfg.max_concurrent));
+                    let task = match claimed {
+                        Ok(Some(task)) => task,
+                        Ok(None) => break,
+                        Err(e) => {
+                            error!(error = %e, "Failed to claim task");
+                            break;
+                        }
+                    };
+
+                    let http = http.clone();
+                    let model_ctx = model_ctx.clone();
+                    let vault = vault.clone();
+                    let skill_mgr = skill_mgr.clone();
+                    let workspace_dir = workspace_dir.clone();
+                    let dir = dir.clone();
+                    let permissions = permissions.clone();
+                    let trace_id = turn_trace::new_trace_id();
+                    let span = turn_trace::turn_span(&trace_id, "task");
+                    let owner = trace_id.clone();
+                    tokio::spawn(
+                        turn_trace::scope(trace_id, async move {
+                            run_task(&http, &model_ctx, &vault, &skill_mgr, &permissions, &workspace_dir, &dir, task).await;
+                            crate::tools::release_file_locks(&owner);
+                        })
+                        .instrument(span),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run one claimed task in a worker session and record the outcome.
+async fn run_task(
+    http: &reqwest::Client,
+    model_ctx: &Arc<ModelContext>,
+    vault: &SharedVault,
+    skill_mgr: &SharedSkillManager,
+    permissions: &HashMap<String, ToolPermission>,
+    workspace_dir: &Path,
+    dir: &Path,
+    task: QueuedTask,
+) {
+    debug!(task_id = %task.id, attempt = task.attempts, "Running queued task");
+
+    let session_key = session_manager().lock().ok().map(|mut mgr| {
+        let key = mgr.spawn_subagent("worker", &task.prompt, Some(format!("task:{}", task.id)), None);
+        if let Some(session) = mgr.get_mut(&key) {
+            session.add_message("user", &task.prompt);
+        }
+        key
+    });
+    if let Some(ref key) = session_key {
+        if let Err(e) = TaskQueue::new(dir).and_then(|mut q| q.set_session(&task.id, key)) {
+            warn!(task_id = %task.id, error = %e, "Failed to record worker session");
+        }
+    }
+
+    let snapshot_dir = workspace_dir.to_path_buf();
+    let reason = format!("task {}", task.id);
+    let _ = tokio::task::spawn_blocking(move || crate::snapshots::before_run(&snapshot_dir, &reason)).await;
+
+    // Cancelling the task from the queue stops the turn.
+    let cancelled = track_running(&task.id);
+    let cancelled_early = TaskQueue::new(dir)
+        .is_ok_and(|q| q.get(&task.id).is_some_and(|t| t.status == TaskStatus::Cancelled));
+    if cancelled_early {
+        cancelled.cancel();
+    }
+    let turn = run_headless_turn(
+        http,
+        model_ctx,
+        vault,
+        skill_mgr,
+        permissions,
+        workspace_dir,
+        TASK_SYSTEM_PROMPT,
+        &task.prompt,
+        MAX_TOOL_ROUNDS,
+    );
+    // Run as the worker session, so the task's plan and anything it spawns
+    // belong to it.
+    let turn = async {
+        match session_key.clone() {
+            Some(key) => crate::sessions::session_scope(key, turn).await,
+            None => turn.await,
+        }
+    };
+    let outcome = tokio::select! {
+        outcome = turn => Some(outcome.map(|turn| turn.text)),
+        _ = cancelled.cancelled() => None,
+    };
+    untrack_running(&task.id);
+    if let Some(ref key) = session_key {
+        crate::plan::remove_plan(key);
+    }
+
+    let Some(outcome) = outcome else {
+        if let (Some(key), Ok(mut mgr)) = (&session_key, session_manager().lock()) {
+            if let Some(session) = mgr.get_mut(key) {
+                session.add_message("system", "Task cancelled.");
+                session.stop();
+            }
+        }
+        info!(task_id = %task.id, "Task cancelled while running");
+        return;
+    };
+
+    if let (Some(key), Ok(mut mgr)) = (&session_key, session_manager().lock()) {
+        if let Some(session) = mgr.get_mut(key) {
+            match &outcome {
+                Ok(text) => {
+                    session.add_message("assistant", text);
+                    session.complete();
+                }
+                Err(err) => {
+                    session.add_message("system", &format!("Task failed: {}", err));
+                    session.error();
+                }
+            }
+        }
+    }
+
+    let recorded = TaskQueue::new(dir).and_then(|mut q| match &outcome {
+        Ok(text) => q.complete(&task.id, text).map(|_| false),
+        Err(err) => q.fail(&task.id, &err.to_string()),
+    });
+    match (recorded, &outcome) {
+        (Ok(_), Ok(_)) => info!(task_id = %task.id, "Task completed"),
+        (Ok(true), Err(err)) => warn!(task_id = %task.id, error = %err, "Task failed, will retry"),
+        (Ok(false), Err(err)) => error!(task_id = %task.id, error = %err, "Task failed"),
+        (Err(e), _) => error!(task_id = %task.id, error = %e, "Failed to record task outcome"),
+    }
+}
+
+/// Final text and token count of a headless turn.
+pub(super) struct HeadlessTurn {
+    pub text: String,
+    /// Prompt plus completion tokens over all model calls.
+    pub tokens: u64,
+}
+
+/// Run a single prompt through the model with tools and return the final text.
+pub(super) async fn run_headless_turn(
+    http: &reqwest::Client,
+    model_ctx: &Arc<ModelContext>,
+    vault: &SharedVault,
+    skill_mgr: &SharedSkillManager,
+    permissions: &HashMap<String, ToolPermission>,
+    workspace_dir: &Path,
+    system_prompt: &str,
+    prompt: &str,
+    max_rounds: usize,
+) -> Result<HeadlessTurn> {
+    let mut resolved = ProviderRequest {
+        provider: model_ctx.provider.clone(),
+        model: model_ctx.model.clone(),
+        base_url: model_ctx.base_url.clone(),
+        api_key: model_ctx.api_key.clone(),
+        messages: vec![
+            ChatMessage::text("system", system_prompt),
+            ChatMessage::text("user", prompt),
+        ],
+        tools: None,
+    };
+    resolved.tools = providers::route_tools(http, &resolved).await;
+
+    let mut final_response = String::new();
+    let mut tokens = 0;
+    let mut deadline = tools::TurnDeadline::start();
+    let mut loop_guard = crate::loop_guard::LoopGuard::new();
+
+    for _round in 0..max_rounds {
+        if !deadline.allow_round() {
+            anyhow::bail!("Turn exceeded its time limit");
+        }
+        let call_started = Instant::now();
+        let result = if resolved.provider == "anthropic" {
+            providers::call_anthropic_with_tools(http, &resolved, None).await
+        } else if resolved.provider == "google" {
+            providers::call_google_with_tools(http, &resolved, None).await
+        } else if resolved.provider == mock_provider::MOCK_PROVIDER {
+            mock_provider::call_mock_with_tools(&resolved)
+        } else {
+            providers::call_openai_with_tools(http, &resolved, None).await
+        };
+        stats::record_provider_call(&resolved.provider, call_started.elapsed());
+        let model_resp = result?;
+        let used = usage::measure(&resolved.model, &resolved.messages, &model_resp);
+        let used = used.prompt_tokens + used.completion_tokens;
+        tokens += used;
+        // Charge the session's tree as the turn goes, so runs that fail or
+        // are cancelled still count against its token budget.
+        if let Some(key) = crate::sessions::current_session() {
+            if let Ok(mut mgr) = session_manager().lock() {
+                mgr.record_tokens(&key, used);
+            }
+        }
+
+        if !model_resp.text.is_empty() {
+            final_response.push_str(&model_resp.text);
+        }
+
+        if model_resp.tool_calls.is_empty() {
+            return Ok(HeadlessTurn { text: final_response, tokens });
+        }
+
+        let round = model_resp.tool_calls.iter().map(|tc| (tc.name.as_str(), &tc.arguments));
+        let loop_note = match loop_guard.check(round) {
+            crate::loop_guard::Verdict::Ok => None,
+            crate::loop_guard::Verdict::Warn(note) => Some(note),
+            crate::loop_guard::Verdict::Abort(message) => anyhow::bail!(message),
+        };
+
+        let mut tool_results: Vec<ToolCallResult> = Vec::new();
+        let mut pending = stats::PendingTools::new(model_resp.tool_calls.len());
+        for tc in &model_resp.tool_calls {
+            // Nobody is around to answer interactive prompts.
+            let (output, is_error) = if tools::is_user_prompt_tool(&tc.name) {
+                ("No user is available for background tasks.".to_string(), true)
+            } else if let Some(denial) = tools::unattended_denial(permissions, &tc.name, &tc.arguments) {
+                (denial, true)
+            } else if tools::is_secrets_tool(&tc.name) {
+                let run = secrets_handler::execute_secrets_tool(&tc.name, &tc.arguments, vault, workspace_dir);
+                match deadline.run(&tc.name, run).await {
+                    Ok(text) => (text, false),
+                    Err(err) => (err, true),
+                }
+            } else if tools::is_skill_tool(&tc.name) {
+                let run = skills_handler::execute_skill_tool(&tc.name, &tc.arguments, skill_mgr);
+                match deadline.run(&tc.name, run).await {
+                    Ok(text) => (text, false),
+                    Err(err) => (err, true),
+                }
+            } else {
+                let run = tools::execute_tool_offloaded(&tc.name, &tc.arguments, workspace_dir);
+                match deadline.run(&tc.name, run).await {
+                    Ok(text) => (text, false),
+                    Err(err) => (err, true),
+                }
+            };
+            pending.done();
+
+            tool_results.push(ToolCallResult {
+                id: tc.id.clone(),
+                name: tc.name.clone(),
+                output: tools::sanitize_tool_output(output),
+                is_error,
+            });
+        }
+
+        providers::append_tool_round(
+            &resolved.provider,
+            &mut resolved.messages,
+            &model_resp,
+            &tool_results,
+        );
+        if let Some(note) = loop_note {
+            resolved.messages.push(ChatMessage::text("system", &note));
+        }
+        for tc in model_resp.tool_calls.iter().filter(|tc| tc.name == "request_tools") {
+            tools::load_requested(&mut resolved.tools, &tc.arguments);
+        }
+    }
+
+    anyhow::bail!("Turn exceeded {} tool rounds", max_rounds)
+}
@@ -18,6 +18,8 @@ pub struct GatewayOptions {
     pub tls_cert: Option<PathBuf>,
     /// Path to TLS private key file (PEM).
     pub tls_key: Option<PathBuf>,
+    /// Record every provider call and tool result to this file.
+    pub record: Option<PathBuf>,
 }
 
 // ── Chat protocol types ─────────────────────────────────────────────────────
@@ -57,6 +59,8 @@ pub struct ProviderRequest {
     pub provider: String,
     pub base_url: String,
     pub api_key: Option<String>,
+    /// Tools offered to the model; `None` offers all of them.
+    pub tools: Option<Vec<String>>,
 }
 
 // ── Model context (resolved once at startup) ────────────────────────────────
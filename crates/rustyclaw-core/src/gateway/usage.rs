@@ -0,0 +1,132 @@
+//! Per-call context and cost accounting for the TUI's usage bar.
+//!
+//! After every model call the agent loop measures how full the context
+//! window is and what the call cost, and sends the result to the client as
+//! a `Usage` frame.  Token counts reported by the provider are used when
+//! present; otherwise they're estimated the same way compaction does.
+
+use serde::{Deserialize, Serialize};
+
+use super::helpers::{context_window_for_model, estimate_tokens};
+use super::protocol::types::{ChatMessage, ModelResponse};
+
+/// Token usage and cost of one model call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub model: String,
+    /// Tokens in the context sent with this call.
+    pub context_tokens: u64,
+    /// The model's context window.
+    pub context_window: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Cost of the call in USD, when the model's price is known.
+    pub cost_usd: Option<f64>,
+}
+
+/// USD per million input and output tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// List price for `model`, or `None` for local and unknown models.
+/// More specific names are matched before their families.
+pub fn price_for_model(model: &str) -> Option<Price> {
+    let m = model.to_lowercase();
+    let (input, output) = if m.contains("claude-opus") {
+        (15.0, 75.0)
+    } else if m.contains("claude-sonnet") {
+        (3.0, 15.0)
+    } else if m.contains("claude-haiku") {
+        (0.80, 4.0)
+    } else if m.starts_with("gpt-4.1-nano") {
+        (0.10, 0.40)
+    } else if m.starts_with("gpt-4.1-mini") {
+        (0.40, 1.60)
+    } else if m.starts_with("gpt-4.1") {
+        (2.0, 8.0)
+    } else if m.starts_with("gpt-4o-mini") {
+        (0.15, 0.60)
+    } else if m.starts_with("gpt-4o") {
+        (2.50, 10.0)
+    } else if m.starts_with("o4-mini") {
+        (1.10, 4.40)
+    } else if m.starts_with("o3") {
+        (2.0, 8.0)
+    } else if m.contains("gemini-2.5-pro") {
+        (1.25, 10.0)
+    } else if m.contains("gemini-2.5-flash") {
+        (0.30, 2.50)
+    } else if m.contains("gemini-2.0-flash") {
+        (0.10, 0.40)
+    } else if m.contains("grok-3") {
+        (3.0, 15.0)
+    } else if m.contains("deepseek") {
+        (0.27, 1.10)
+    } else {
+        return None;
+    };
+    Some(Price { input, output })
+}
+
+/// Measure a finished model call made with `messages`.
+pub fn measure(model: &str, messages: &[ChatMessage], resp: &ModelResponse) -> Usage {
+    let estimated_prompt = estimate_tokens(messages) as u64;
+    let prompt_tokens = resp.prompt_tokens.unwrap_or(estimated_prompt);
+    let completion_tokens = resp.completion_tokens.unwrap_or_else(|| {
+        let chars = resp.text.len()
+            + resp.tool_calls.iter().map(|c| c.name.len() + c.arguments.to_string().len()).sum::<usize>();
+        (chars / 3) as u64
+    });
+    let cost_usd = price_for_model(model).map(|p| {
+        (prompt_tokens as f64 * p.input + completion_tokens as f64 * p.output) / 1_000_000.0
+    });
+    Usage {
+        model: model.to_string(),
+        context_tokens: prompt_tokens,
+        context_window: context_window_for_model(model) as u64,
+        prompt_tokens,
+        completion_tokens,
+        cost_usd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_prefers_specific_models() {
+        assert_eq!(price_for_model("gpt-4.1-mini").unwrap().input, 0.40);
+        assert_eq!(price_for_model("gpt-4.1").unwrap().input, 2.0);
+        assert_eq!(price_for_model("claude-sonnet-4-20250514").unwrap().output, 15.0);
+        assert_eq!(price_for_model("llama3.1:8b"), None);
+    }
+
+    #[test]
+    fn test_measure_uses_reported_tokens() {
+        let resp = ModelResponse {
+            text: "done".into(),
+            prompt_tokens: Some(10_000),
+            completion_tokens: Some(1_000),
+            ..Default::default()
+        };
+        let usage = measure("claude-sonnet-4", &[ChatMessage::text("user", "hi")], &resp);
+        assert_eq!(usage.context_tokens, 10_000);
+        assert_eq!(usage.context_window, 200_000);
+        // 10k × $3/M + 1k × $15/M
+        assert!((usage.cost_usd.unwrap() - 0.045).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measure_estimates_missing_tokens() {
+        let messages = [ChatMessage::text("user", &"x".repeat(3_000))];
+        let resp = ModelResponse { text: "y".repeat(300), ..Default::default() };
+        let usage = measure("my-local-model", &messages, &resp);
+        assert!(usage.prompt_tokens >= 1_000);
+        assert_eq!(usage.completion_tokens, 100);
+        assert_eq!(usage.cost_usd, None);
+    }
+}
@@ -0,0 +1,227 @@
+//! Heartbeat: periodic agent check-ins without a user message.
+//!
+//! When enabled, the gateway wakes the agent every `interval_minutes`
+//! (within `active_hours`) with a short prompt asking whether anything
+//! needs attention — due cron jobs, open todos in memory, the checklist
+//! in the workspace's `HEARTBEAT.md`.  Runs happen in a dedicated
+//! `heartbeat` session with their own tool-round cap.
+//!
+//! If nothing needs attention the agent replies exactly [`OK_TOKEN`] and
+//! nothing is delivered.  Any other reply is posted to the notification
+//! center.  Messenger conversations treat a bare `HEARTBEAT_OK` the same
+//! way and don't send it.
+//!
+//! Heartbeats cost tokens even when idle, so runs are capped per day, and
+//! once the day's token budget is spent the remaining heartbeats are
+//! skipped until midnight.
+//!
+//! ```toml
+//! [heartbeat]
+//! enabled = true
+//! interval_minutes = 60
+//! active_hours = "08:00-22:00"
+//! max_runs_per_day = 12
+//! daily_token_budget = 100000
+//! ```
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The reply that means "nothing to report".
+pub const OK_TOKEN: &str = "HEARTBEAT_OK";
+
+/// Prompt used when the config doesn't set one.
+pub const DEFAULT_PROMPT: &str = "Heartbeat check-in. Is anything pending? Check due or failed cron jobs, \
+     open todos in memory and the HEARTBEAT.md checklist below, and deal with what you can. \
+     If nothing needs the user's attention, reply with exactly HEARTBEAT_OK and nothing else; \
+     otherwise reply with a short note for the user.";
+
+/// The `[heartbeat]` config section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes between heartbeats.
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u64,
+    /// Local time window such as `08:00-22:00` (may wrap past midnight);
+    /// unset means around the clock.
+    #[serde(default)]
+    pub active_hours: Option<String>,
+    /// Replaces [`DEFAULT_PROMPT`].
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Tool rounds one heartbeat may use.
+    #[serde(default = "default_max_tool_rounds")]
+    pub max_tool_rounds: usize,
+    #[serde(default = "default_max_runs_per_day")]
+    pub max_runs_per_day: u32,
+    /// Prompt plus completion tokens all heartbeats of a day may use.
+    #[serde(default = "default_daily_token_budget")]
+    pub daily_token_budget: u64,
+}
+
+fn default_interval_minutes() -> u64 {
+    30
+}
+
+fn default_max_tool_rounds() -> usize {
+    5
+}
+
+fn default_max_runs_per_day() -> u32 {
+    24
+}
+
+fn default_daily_token_budget() -> u64 {
+    200_000
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_interval_minutes(),
+            active_hours: None,
+            prompt: None,
+            max_tool_rounds: default_max_tool_rounds(),
+            max_runs_per_day: default_max_runs_per_day(),
+            daily_token_budget: default_daily_token_budget(),
+        }
+    }
+}
+
+/// Whether a reply means there's nothing to deliver.
+pub fn is_ok(reply: &str) -> bool {
+    let reply = reply.trim().trim_end_matches('.');
+    reply.eq_ignore_ascii_case(OK_TOKEN)
+}
+
+/// Parse an `HH:MM-HH:MM` window.
+pub fn parse_active_hours(spec: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let (start, end) = spec
+        .split_once('-')
+        .ok_or_else(|| format!("active_hours '{}' should look like 08:00-22:00", spec))?;
+    let parse = |t: &str| {
+        NaiveTime::parse_from_str(t.trim(), "%H:%M")
+            .map_err(|_| format!("Can't read '{}' in active_hours as HH:MM", t.trim()))
+    };
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// Whether `now` falls inside the configured window.  A window that can't
+/// be parsed never matches, so a typo doesn't run heartbeats all night.
+pub fn within_active_hours(active_hours: Option<&str>, now: NaiveTime) -> bool {
+    let Some(spec) = active_hours else {
+        return true;
+    };
+    match parse_active_hours(spec) {
+        Ok((start, end)) if start <= end => now >= start && now < end,
+        // Overnight, e.g. 22:00-06:00.
+        Ok((start, end)) => now >= start || now < end,
+        Err(_) => false,
+    }
+}
+
+/// The heartbeat prompt, followed by `HEARTBEAT.md` if the workspace has
+/// one.
+pub fn build_prompt(config: &HeartbeatConfig, workspace_dir: &Path) -> String {
+    let mut prompt = config.prompt.clone().unwrap_or_else(|| DEFAULT_PROMPT.to_string());
+    if let Ok(checklist) = std::fs::read_to_string(workspace_dir.join("HEARTBEAT.md")) {
+        if !checklist.trim().is_empty() {
+            prompt.push_str("\n\n## HEARTBEAT.md\n");
+            prompt.push_str(checklist.trim());
+        }
+    }
+    prompt
+}
+
+/// Runs and tokens used today.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Budget {
+    day: NaiveDate,
+    pub runs: u32,
+    pub tokens: u64,
+}
+
+impl Budget {
+    pub fn new(today: NaiveDate) -> Self {
+        Self { day: today, runs: 0, tokens: 0 }
+    }
+
+    /// `Err` with the reason when today's caps are used up.
+    pub fn check(&mut self, config: &HeartbeatConfig, today: NaiveDate) -> Result<(), String> {
+        if today != self.day {
+            *self = Self::new(today);
+        }
+        if self.runs >= config.max_runs_per_day {
+            return Err(format!("{} heartbeats already ran today", self.runs));
+        }
+        if self.tokens >= config.daily_token_budget {
+            return Err(format!(
+                "today's token budget is spent ({} of {})",
+                self.tokens, config.daily_token_budget
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn record(&mut self, tokens: u64) {
+        self.runs += 1;
+        self.tokens += tokens;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_is_ok() {
+        assert!(is_ok("HEARTBEAT_OK"));
+        assert!(is_ok("  heartbeat_ok.\n"));
+        assert!(!is_ok("HEARTBEAT_OK, but the backup job failed"));
+    }
+
+    #[test]
+    fn test_active_hours() {
+        assert!(within_active_hours(None, time(3, 0)));
+        assert!(within_active_hours(Some("08:00-22:00"), time(8, 0)));
+        assert!(!within_active_hours(Some("08:00-22:00"), time(22, 0)));
+        assert!(within_active_hours(Some("22:00-06:00"), time(23, 30)));
+        assert!(within_active_hours(Some("22:00-06:00"), time(5, 59)));
+        assert!(!within_active_hours(Some("22:00-06:00"), time(12, 0)));
+        assert!(!within_active_hours(Some("eight to ten"), time(9, 0)));
+    }
+
+    #[test]
+    fn test_budget_caps_and_resets() {
+        let config = HeartbeatConfig { max_runs_per_day: 2, daily_token_budget: 1_000, ..Default::default() };
+        let monday = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let mut budget = Budget::new(monday);
+
+        assert!(budget.check(&config, monday).is_ok());
+        budget.record(400);
+        assert!(budget.check(&config, monday).is_ok());
+        budget.record(700);
+        assert!(budget.check(&config, monday).unwrap_err().contains("token budget"));
+
+        let tuesday = monday.succ_opt().unwrap();
+        assert!(budget.check(&config, tuesday).is_ok());
+        assert_eq!(budget.tokens, 0);
+    }
+
+    #[test]
+    fn test_prompt_includes_checklist() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HeartbeatConfig::default();
+        assert_eq!(build_prompt(&config, dir.path()), DEFAULT_PROMPT);
+        std::fs::write(dir.path().join("HEARTBEAT.md"), "- water the plants\n").unwrap();
+        assert!(build_prompt(&config, dir.path()).ends_with("## HEARTBEAT.md\n- water the plants"));
+    }
+}
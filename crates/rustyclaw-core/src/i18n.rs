@@ -0,0 +1,408 @@
+//! Localized user-facing strings.
+//!
+//! Interface text (TUI labels, dialog titles, status messages, onboarding)
+//! is looked up by key in a per-locale bundle.  The locale comes from the
+//! `locale` config key, else from `LC_ALL` / `LC_MESSAGES` / `LANG`, else
+//! English.  Missing translations fall back to English.
+//!
+//! Prompts sent to the model are not localized here; they stay whatever
+//! the configuration (`system_prompt`, SOUL.md, messenger overrides) says.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// A supported interface language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::De, Locale::Es];
+
+    /// Parse a language tag such as `de`, `es-MX` or `de_DE.UTF-8`.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let lang = tag
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    /// The locale named by the environment, if it's one we support.
+    pub fn from_env() -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value))
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Es => "es",
+        }
+    }
+
+    fn bundle(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+            Locale::Es => ES,
+        }
+    }
+}
+
+/// Pick the locale: the configured one, else the environment's, else English.
+pub fn resolve(configured: Option<&str>) -> Locale {
+    configured
+        .and_then(Locale::parse)
+        .or_else(Locale::from_env)
+        .unwrap_or_default()
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// Set the interface locale for this process.
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL.iter().position(|l| *l == locale).unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+/// The interface locale.
+pub fn locale() -> Locale {
+    Locale::ALL[CURRENT.load(Ordering::Relaxed) as usize % Locale::ALL.len()]
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    locale.bundle().iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// The string for `key` in the current locale.
+pub fn t(key: &'static str) -> &'static str {
+    lookup(locale(), key)
+        .or_else(|| lookup(Locale::En, key))
+        .unwrap_or(key)
+}
+
+/// Like [`t`], with each `{}` replaced by the next argument.
+pub fn tf(key: &'static str, args: &[&str]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut parts = t(key).split("{}").peekable();
+    while let Some(part) = parts.next() {
+        out.push_str(part);
+        if parts.peek().is_some() {
+            out.push_str(args.next().copied().unwrap_or(""));
+        }
+    }
+    out
+}
+
+// ── Bundles ─────────────────────────────────────────────────────────────────
+
+const EN: &[(&str, &str)] = &[
+    ("status.hint", "Ctrl+C quit · /help commands · ↑↓ scroll"),
+    ("status.streaming", "Streaming response {}"),
+    ("status.no_model", "(no model)"),
+    ("sidebar.session", " Session"),
+    ("sidebar.status", "Status: {}"),
+    ("sidebar.tasks", " Tasks"),
+    ("sidebar.streaming", "Streaming {}"),
+    ("task.idle", "Idle"),
+    ("task.streaming", "Streaming…"),
+    ("session.untitled", "Untitled session"),
+    ("session.interrupted", "Interrupted session found (\"{}\", {} tool result(s) saved) — /resume to restore or /resume discard"),
+    ("session.none_interrupted", "No interrupted session to resume."),
+    ("pin.pinned", "Pinned — the note is now part of every prompt."),
+    ("pin.current", "Pinned: {}"),
+    ("pin.none", "Nothing pinned. Usage: /pin <note>"),
+    ("pin.removed", "Pinned topic removed."),
+    ("split.needs_gateway", "Split view needs a gateway connection."),
+    ("events.needs_gateway", "/events needs a gateway connection."),
+    ("media.received", "Received {} from the gateway → {}"),
+    ("media.failed", "Media transfer failed: {}"),
+    ("error.hint.auth", "Check the API key with /secrets, or switch with /provider."),
+    ("error.hint.rate_limit", "Rate limited by the provider — try again in a moment."),
+    ("error.hint.retry", "The provider may be down or overloaded — try again."),
+    ("error.hint.policy", "Change tool permissions with /tools."),
+    ("attach.offer", "📎 {} — press Enter again to attach it as context, or edit the input to send it as text."),
+    ("attach.failed", "Couldn't attach: {}"),
+    ("paste.label", "[pasted {} chars]"),
+    ("paste.confirm", "Send {}? Press Enter again to send it, or Esc to discard the paste."),
+    ("paste.discard", "Esc discards"),
+    ("paste.expand", "(Ctrl+O to expand)"),
+    ("paste.collapse", "(Ctrl+O to collapse)"),
+    ("dialog.auth", "🔑 Gateway Authentication"),
+    ("dialog.secrets", "🔐 Secrets Vault"),
+    ("dialog.skills", "⚡ Skills"),
+    ("dialog.tool_approval", "🔐 Tool Approval Required"),
+    ("dialog.tool_perms", "🔧 Tool Permissions"),
+    ("dialog.vault_locked", "🔒 Vault Locked"),
+    ("dialog.workspaces", "📁 Workspaces"),
+    ("dialog.palette", "⌘ Command Palette"),
+    ("dialog.notifications", "🔔 Notifications"),
+    ("notify.empty", "  No notifications."),
+    ("notify.subagent", "sub-agent"),
+    ("notify.cron", "cron"),
+    ("notify.pairing", "pairing"),
+    ("notify.messenger", "messenger"),
+    ("notify.heartbeat", "heartbeat"),
+    ("key.jump", "jump to session"),
+    ("key.dismiss", "dismiss"),
+    ("key.clear_all", "clear all"),
+    ("palette.placeholder", "Search commands, files, sessions and skills…"),
+    ("palette.empty", "  No matches."),
+    ("palette.command", "command"),
+    ("palette.file", "file"),
+    ("palette.session", "session"),
+    ("palette.skill", "skill"),
+    ("approval.allow", "Allow (y)"),
+    ("approval.deny", "Deny (n)"),
+    ("secrets.empty", "  No credentials stored.  Press 'a' to add one."),
+    ("secrets.add", "Add Secret"),
+    ("skills.empty", "  No skills loaded."),
+    ("key.navigate", "navigate"),
+    ("key.toggle", "toggle"),
+    ("key.switch", "switch"),
+    ("key.select", "select"),
+    ("key.cycle_policy", "cycle policy"),
+    ("key.cycle_permission", "cycle permission"),
+    ("key.add", "add"),
+    ("key.delete", "delete"),
+    ("key.close", "close"),
+    ("auth.cancelled", "Authentication cancelled."),
+    ("onboard.warning", "⚠  Important: Please read before continuing."),
+    ("onboard.cancelled", "Onboarding cancelled."),
+    ("onboard.name_heading", "Name your agent:"),
+    ("onboard.agent_name", "Agent name: {}"),
+    ("onboard.vault_heading", "Secrets vault setup:"),
+    ("approval.footer", "y allow · n/Esc deny · Tab toggle · Enter confirm"),
+    ("vault.unlock_cancelled", "Vault unlock cancelled."),
+    ("onboard.header", "🦀  RustyClaw Onboarding  🦀"),
+    ("onboard.safety", "RustyClaw is an agentic coding tool, meaning it can\nread, write, and execute code on your machine on your\nbehalf. Like any powerful tool, it should be used with\ncare and awareness.\n\n• It can create and modify files in your project\n• It can run commands in your terminal\n• It can interact with external APIs using your credentials\n\nAlways review actions before approving them, especially\nin production environments. You are responsible for any\nchanges made by the tool."),
+    ("onboard.acknowledge", "Do you acknowledge and wish to continue? [y/N]:"),
+    ("onboard.name_help", "Give your RustyClaw agent a name. This appears in the TUI\ntitle bar, authenticator app labels, and anywhere the\nagent identifies itself."),
+    ("onboard.name_prompt", "Agent name [{}]: "),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("status.hint", "Strg+C beenden · /help Befehle · ↑↓ scrollen"),
+    ("status.streaming", "Antwort wird gestreamt {}"),
+    ("status.no_model", "(kein Modell)"),
+    ("sidebar.session", " Sitzung"),
+    ("sidebar.status", "Status: {}"),
+    ("sidebar.tasks", " Aufgaben"),
+    ("sidebar.streaming", "Streamt {}"),
+    ("task.idle", "Bereit"),
+    ("task.streaming", "Streamt…"),
+    ("session.untitled", "Unbenannte Sitzung"),
+    ("session.interrupted", "Unterbrochene Sitzung gefunden (\"{}\", {} Tool-Ergebnis(se) gespeichert) — /resume zum Fortsetzen oder /resume discard"),
+    ("session.none_interrupted", "Keine unterbrochene Sitzung zum Fortsetzen."),
+    ("pin.pinned", "Angeheftet — die Notiz ist jetzt Teil jedes Prompts."),
+    ("pin.current", "Angeheftet: {}"),
+    ("pin.none", "Nichts angeheftet. Verwendung: /pin <Notiz>"),
+    ("pin.removed", "Angeheftetes Thema entfernt."),
+    ("split.needs_gateway", "Die geteilte Ansicht braucht eine Gateway-Verbindung."),
+    ("events.needs_gateway", "/events braucht eine Gateway-Verbindung."),
+    ("media.received", "{} vom Gateway empfangen → {}"),
+    ("media.failed", "Medienübertragung fehlgeschlagen: {}"),
+    ("error.hint.auth", "API-Schlüssel mit /secrets prüfen oder mit /provider wechseln."),
+    ("error.hint.rate_limit", "Vom Anbieter gedrosselt — gleich noch einmal versuchen."),
+    ("error.hint.retry", "Der Anbieter ist eventuell nicht erreichbar oder überlastet — erneut versuchen."),
+    ("error.hint.policy", "Tool-Berechtigungen mit /tools ändern."),
+    ("attach.offer", "📎 {} — erneut Enter drücken, um die Datei als Kontext anzuhängen, oder die Eingabe bearbeiten, um sie als Text zu senden."),
+    ("attach.failed", "Anhängen fehlgeschlagen: {}"),
+    ("paste.label", "[{} Zeichen eingefügt]"),
+    ("paste.confirm", "{} senden? Erneut Enter drücken zum Senden oder Esc zum Verwerfen."),
+    ("paste.discard", "Esc verwirft"),
+    ("paste.expand", "(Strg+O zum Aufklappen)"),
+    ("paste.collapse", "(Strg+O zum Zuklappen)"),
+    ("dialog.auth", "🔑 Gateway-Anmeldung"),
+    ("dialog.secrets", "🔐 Geheimnis-Tresor"),
+    ("dialog.skills", "⚡ Skills"),
+    ("dialog.tool_approval", "🔐 Tool-Freigabe erforderlich"),
+    ("dialog.tool_perms", "🔧 Tool-Berechtigungen"),
+    ("dialog.vault_locked", "🔒 Tresor gesperrt"),
+    ("dialog.workspaces", "📁 Arbeitsbereiche"),
+    ("dialog.palette", "⌘ Befehlspalette"),
+    ("dialog.notifications", "🔔 Benachrichtigungen"),
+    ("notify.empty", "  Keine Benachrichtigungen."),
+    ("notify.subagent", "Sub-Agent"),
+    ("notify.cron", "Cron"),
+    ("notify.pairing", "Kopplung"),
+    ("notify.messenger", "Messenger"),
+    ("notify.heartbeat", "Heartbeat"),
+    ("key.jump", "zur Sitzung"),
+    ("key.dismiss", "verwerfen"),
+    ("key.clear_all", "alle löschen"),
+    ("palette.placeholder", "Befehle, Dateien, Sitzungen und Skills durchsuchen…"),
+    ("palette.empty", "  Keine Treffer."),
+    ("palette.command", "Befehl"),
+    ("palette.file", "Datei"),
+    ("palette.session", "Sitzung"),
+    ("palette.skill", "Skill"),
+    ("approval.allow", "Erlauben (y)"),
+    ("approval.deny", "Ablehnen (n)"),
+    ("secrets.empty", "  Keine Zugangsdaten gespeichert.  'a' drücken, um welche hinzuzufügen."),
+    ("secrets.add", "Geheimnis hinzufügen"),
+    ("skills.empty", "  Keine Skills geladen."),
+    ("key.navigate", "navigieren"),
+    ("key.toggle", "umschalten"),
+    ("key.switch", "wechseln"),
+    ("key.select", "auswählen"),
+    ("key.cycle_policy", "Richtlinie wechseln"),
+    ("key.cycle_permission", "Berechtigung wechseln"),
+    ("key.add", "hinzufügen"),
+    ("key.delete", "löschen"),
+    ("key.close", "schließen"),
+    ("auth.cancelled", "Anmeldung abgebrochen."),
+    ("onboard.warning", "⚠  Wichtig: Bitte vor dem Fortfahren lesen."),
+    ("onboard.cancelled", "Einrichtung abgebrochen."),
+    ("onboard.name_heading", "Benenne deinen Agenten:"),
+    ("onboard.agent_name", "Agentenname: {}"),
+    ("onboard.vault_heading", "Einrichtung des Geheimnis-Tresors:"),
+    ("approval.footer", "y erlauben · n/Esc ablehnen · Tab umschalten · Enter bestätigen"),
+    ("vault.unlock_cancelled", "Entsperren des Tresors abgebrochen."),
+    ("onboard.header", "🦀  RustyClaw-Einrichtung  🦀"),
+    ("onboard.safety", "RustyClaw ist ein agentisches Programmierwerkzeug: Es kann\nin deinem Auftrag Code auf deinem Rechner lesen, schreiben\nund ausführen. Wie jedes mächtige Werkzeug sollte es mit\nSorgfalt und Bedacht eingesetzt werden.\n\n• Es kann Dateien in deinem Projekt anlegen und ändern\n• Es kann Befehle in deinem Terminal ausführen\n• Es kann mit deinen Zugangsdaten externe APIs ansprechen\n\nPrüfe Aktionen immer, bevor du sie freigibst, besonders\nin Produktivumgebungen. Du bist für alle Änderungen\nverantwortlich, die das Werkzeug vornimmt."),
+    ("onboard.acknowledge", "Hast du das verstanden und möchtest fortfahren? [y/N]:"),
+    ("onboard.name_help", "Gib deinem RustyClaw-Agenten einen Namen. Er erscheint in der\nTitelleiste der TUI, in Authenticator-Apps und überall, wo\nsich der Agent vorstellt."),
+    ("onboard.name_prompt", "Agentenname [{}]: "),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("status.hint", "Ctrl+C salir · /help comandos · ↑↓ desplazar"),
+    ("status.streaming", "Recibiendo respuesta {}"),
+    ("status.no_model", "(sin modelo)"),
+    ("sidebar.session", " Sesión"),
+    ("sidebar.status", "Estado: {}"),
+    ("sidebar.tasks", " Tareas"),
+    ("sidebar.streaming", "Recibiendo {}"),
+    ("task.idle", "Inactivo"),
+    ("task.streaming", "Recibiendo…"),
+    ("session.untitled", "Sesión sin título"),
+    ("session.interrupted", "Sesión interrumpida encontrada (\"{}\", {} resultado(s) de herramientas guardados) — /resume para restaurar o /resume discard"),
+    ("session.none_interrupted", "No hay ninguna sesión interrumpida que reanudar."),
+    ("pin.pinned", "Fijado — la nota ahora forma parte de cada prompt."),
+    ("pin.current", "Fijado: {}"),
+    ("pin.none", "Nada fijado. Uso: /pin <nota>"),
+    ("pin.removed", "Tema fijado eliminado."),
+    ("split.needs_gateway", "La vista dividida necesita una conexión con el gateway."),
+    ("events.needs_gateway", "/events necesita una conexión con el gateway."),
+    ("media.received", "{} recibido del gateway → {}"),
+    ("media.failed", "La transferencia de medios falló: {}"),
+    ("error.hint.auth", "Revisa la clave de API con /secrets o cambia con /provider."),
+    ("error.hint.rate_limit", "El proveedor limita las peticiones — inténtalo de nuevo en un momento."),
+    ("error.hint.retry", "El proveedor puede estar caído o sobrecargado — inténtalo de nuevo."),
+    ("error.hint.policy", "Cambia los permisos de herramientas con /tools."),
+    ("attach.offer", "📎 {} — pulsa Enter de nuevo para adjuntarlo como contexto, o edita la entrada para enviarlo como texto."),
+    ("attach.failed", "No se pudo adjuntar: {}"),
+    ("paste.label", "[{} caracteres pegados]"),
+    ("paste.confirm", "¿Enviar {}? Pulsa Enter de nuevo para enviarlo, o Esc para descartarlo."),
+    ("paste.discard", "Esc descarta"),
+    ("paste.expand", "(Ctrl+O para expandir)"),
+    ("paste.collapse", "(Ctrl+O para contraer)"),
+    ("dialog.auth", "🔑 Autenticación del gateway"),
+    ("dialog.secrets", "🔐 Bóveda de secretos"),
+    ("dialog.skills", "⚡ Habilidades"),
+    ("dialog.tool_approval", "🔐 Se requiere aprobar la herramienta"),
+    ("dialog.tool_perms", "🔧 Permisos de herramientas"),
+    ("dialog.vault_locked", "🔒 Bóveda bloqueada"),
+    ("dialog.workspaces", "📁 Espacios de trabajo"),
+    ("dialog.palette", "⌘ Paleta de comandos"),
+    ("dialog.notifications", "🔔 Notificaciones"),
+    ("notify.empty", "  No hay notificaciones."),
+    ("notify.subagent", "subagente"),
+    ("notify.cron", "cron"),
+    ("notify.pairing", "emparejamiento"),
+    ("notify.messenger", "mensajería"),
+    ("notify.heartbeat", "latido"),
+    ("key.jump", "ir a la sesión"),
+    ("key.dismiss", "descartar"),
+    ("key.clear_all", "borrar todo"),
+    ("palette.placeholder", "Buscar comandos, archivos, sesiones y habilidades…"),
+    ("palette.empty", "  Sin resultados."),
+    ("palette.command", "comando"),
+    ("palette.file", "archivo"),
+    ("palette.session", "sesión"),
+    ("palette.skill", "habilidad"),
+    ("approval.allow", "Permitir (y)"),
+    ("approval.deny", "Denegar (n)"),
+    ("secrets.empty", "  No hay credenciales guardadas.  Pulsa 'a' para añadir una."),
+    ("secrets.add", "Añadir secreto"),
+    ("skills.empty", "  No hay habilidades cargadas."),
+    ("key.navigate", "navegar"),
+    ("key.toggle", "activar"),
+    ("key.switch", "cambiar"),
+    ("key.select", "seleccionar"),
+    ("key.cycle_policy", "cambiar política"),
+    ("key.cycle_permission", "cambiar permiso"),
+    ("key.add", "añadir"),
+    ("key.delete", "borrar"),
+    ("key.close", "cerrar"),
+    ("auth.cancelled", "Autenticación cancelada."),
+    ("onboard.warning", "⚠  Importante: lee esto antes de continuar."),
+    ("onboard.cancelled", "Configuración cancelada."),
+    ("onboard.name_heading", "Ponle nombre a tu agente:"),
+    ("onboard.agent_name", "Nombre del agente: {}"),
+    ("onboard.vault_heading", "Configuración de la bóveda de secretos:"),
+    ("approval.footer", "y permitir · n/Esc denegar · Tab alternar · Enter confirmar"),
+    ("vault.unlock_cancelled", "Desbloqueo de la bóveda cancelado."),
+    ("onboard.header", "🦀  Configuración de RustyClaw  🦀"),
+    ("onboard.safety", "RustyClaw es una herramienta de programación agéntica: puede\nleer, escribir y ejecutar código en tu equipo en tu nombre.\nComo cualquier herramienta potente, debe usarse con\ncuidado y atención.\n\n• Puede crear y modificar archivos de tu proyecto\n• Puede ejecutar comandos en tu terminal\n• Puede interactuar con APIs externas usando tus credenciales\n\nRevisa siempre las acciones antes de aprobarlas, sobre todo\nen entornos de producción. Eres responsable de cualquier\ncambio que haga la herramienta."),
+    ("onboard.acknowledge", "¿Lo entiendes y deseas continuar? [y/N]:"),
+    ("onboard.name_help", "Ponle un nombre a tu agente RustyClaw. Aparece en la barra de\ntítulo de la TUI, en las apps de autenticación y allí donde\nel agente se identifique."),
+    ("onboard.name_prompt", "Nombre del agente [{}]: "),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale_tags() {
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::parse("es-MX"), Some(Locale::Es));
+        assert_eq!(Locale::parse("EN"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr_FR"), None);
+        assert_eq!(resolve(Some("es")), Locale::Es);
+    }
+
+    #[test]
+    fn test_bundles_cover_every_english_key() {
+        for locale in [Locale::De, Locale::Es] {
+            for (key, en) in EN {
+                let text = lookup(locale, key).unwrap_or_else(|| panic!("{} missing {}", locale.code(), key));
+                assert_eq!(
+                    text.matches("{}").count(),
+                    en.matches("{}").count(),
+                    "{} placeholders differ for {}",
+                    locale.code(),
+                    key
+                );
+            }
+            assert_eq!(locale.bundle().len(), EN.len());
+        }
+    }
+
+    #[test]
+    fn test_tf_fills_placeholders() {
+        assert_eq!(lookup(Locale::De, "pin.current"), Some("Angeheftet: {}"));
+        let en = tf("sidebar.status", &["Connected"]);
+        assert!(en.ends_with("Connected"));
+        assert_eq!(t("no.such.key"), "no.such.key");
+    }
+}
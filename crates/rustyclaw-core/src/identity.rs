@@ -0,0 +1,151 @@
+//! Identity linking: one person across messengers.
+//!
+//! The same human writes from a Telegram id, a Signal number and an email
+//! address.  The contact book (see [`crate::contacts`]) already lists each
+//! person's ids, so it doubles as the identity map:
+//!
+//! ```toml
+//! [[contacts.entries]]
+//! name = "Ada Lovelace"
+//! phone = "+441234567890"              # Signal, WhatsApp, SMS
+//! email = "ada@example.com"
+//! identities = { telegram = "123456789", discord = "987654321" }
+//! ```
+//!
+//! When a message comes from a linked id, the gateway treats the sender as
+//! that [`Person`]:
+//!
+//! - direct-chat history is kept per person, so a conversation started on
+//!   Telegram continues on Signal (group chats stay per chat),
+//! - facts about them live in `memory/people/<key>.md`, which is shown to
+//!   the agent whichever messenger they write from,
+//! - `messenger_rate_limit` counts all their messages together.
+//!
+//! Unlinked senders keep per-platform history and limits.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Conversation namespace for per-person history.
+pub const PERSON_NAMESPACE: &str = "person";
+
+/// Window `messenger_rate_limit` counts messages in.
+pub const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// A contact a message was linked to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+    /// Stable file-safe key derived from the name.
+    pub key: String,
+}
+
+impl Person {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), key: person_key(name) }
+    }
+}
+
+/// `"Ada Lovelace"` → `"ada-lovelace"`.
+pub fn person_key(name: &str) -> String {
+    let mut key = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            key.push(c);
+        } else if !key.is_empty() && !key.ends_with('-') {
+            key.push('-');
+        }
+    }
+    key.trim_end_matches('-').to_string()
+}
+
+/// The person `sender` on `messenger` is linked to, if any.  May fetch
+/// CardDAV contacts, so call it off the async runtime.
+pub fn resolve(messenger: &str, sender: &str) -> Option<Person> {
+    crate::contacts::by_sender(messenger, sender)
+        .map(|c| Person::new(&c.name))
+        .filter(|p| !p.key.is_empty())
+}
+
+/// Memory file holding facts about `person`.
+pub fn memory_file(workspace_dir: &Path, person: &Person) -> PathBuf {
+    workspace_dir.join("memory").join("people").join(format!("{}.md", person.key))
+}
+
+/// Key rate limits are counted under: the person, or the platform id for
+/// unlinked senders.
+pub fn rate_key(person: Option<&Person>, messenger: &str, sender: &str) -> String {
+    match person {
+        Some(person) => format!("{}:{}", PERSON_NAMESPACE, person.key),
+        None => format!("{}:{}", messenger, sender),
+    }
+}
+
+/// Sliding-window message counter.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    hits: HashMap<String, VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    /// Count a message from `key` at `now`.  `Err` with the time until
+    /// the next message is allowed when `limit` messages already arrived
+    /// within [`RATE_WINDOW`]; refused messages aren't counted.
+    pub fn check(&mut self, key: &str, limit: u32, now: Instant) -> Result<(), Duration> {
+        let hits = self.hits.entry(key.to_string()).or_default();
+        while hits.front().is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW) {
+            hits.pop_front();
+        }
+        if hits.len() >= limit as usize {
+            let oldest = hits.front().copied().unwrap_or(now);
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        hits.push_back(now);
+        // Forget idle senders.
+        self.hits.retain(|_, h| h.back().is_some_and(|t| now.duration_since(*t) < RATE_WINDOW));
+        Ok(())
+    }
+}
+
+static LIMITER: Mutex<Option<RateLimiter>> = Mutex::new(None);
+
+/// Count a message against the gateway-wide limiter.
+pub fn check_rate(key: &str, limit: u32) -> Result<(), Duration> {
+    let Ok(mut guard) = LIMITER.lock() else {
+        return Ok(());
+    };
+    guard.get_or_insert_with(RateLimiter::default).check(key, limit, Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_person_key() {
+        assert_eq!(person_key("Ada Lovelace"), "ada-lovelace");
+        assert_eq!(person_key("  Dr. Who?! "), "dr-who");
+        assert_eq!(person_key("Zoë"), "zoë");
+    }
+
+    #[test]
+    fn test_rate_key_shares_linked_ids() {
+        let ada = Person::new("Ada Lovelace");
+        assert_eq!(rate_key(Some(&ada), "telegram", "123"), rate_key(Some(&ada), "signal", "+44"));
+        assert_eq!(rate_key(None, "telegram", "123"), "telegram:123");
+    }
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let mut limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.check("person:ada", 2, start).is_ok());
+        assert!(limiter.check("person:ada", 2, start + Duration::from_secs(10)).is_ok());
+        let wait = limiter.check("person:ada", 2, start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(40));
+        assert!(limiter.check("telegram:1", 2, start + Duration::from_secs(20)).is_ok());
+        assert!(limiter.check("person:ada", 2, start + Duration::from_secs(61)).is_ok());
+    }
+}
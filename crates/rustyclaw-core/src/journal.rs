@@ -0,0 +1,485 @@
+//! Write-ahead journal of in-progress agent turns.
+//!
+//! Every turn the gateway dispatches is recorded here before any work is
+//! done: the incoming conversation, then each tool result and assistant
+//! text fragment as it arrives, and finally a commit marker once the
+//! response is complete.  If the TUI or gateway dies mid-turn, the journal
+//! ends with an uncommitted turn which can be replayed on the next start.
+//!
+//! The journal is a JSONL file under the sessions directory so that both
+//! the gateway (writer) and the TUI (reader, on startup) can find it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::gateway::ChatMessage;
+
+/// File name of the journal inside the sessions directory.
+const JOURNAL_FILE: &str = "turn_journal.jsonl";
+
+/// Serialises appends with compaction, which rewrites the file.
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Generate a unique turn ID.
+fn generate_turn_id() -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("turn-{:x}", timestamp)
+}
+
+/// Get current time in milliseconds.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A single record in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum JournalEntry {
+    /// A turn started with the given conversation as input.
+    Begin {
+        turn_id: String,
+        started_ms: u64,
+        messages: Vec<ChatMessage>,
+        /// Request trace ID, for matching the turn with logs and spans.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trace_id: Option<String>,
+    },
+    /// Assistant text produced during the turn.
+    Text { turn_id: String, text: String },
+    /// A tool finished executing during the turn.
+    ToolResult {
+        turn_id: String,
+        id: String,
+        name: String,
+        output: String,
+        is_error: bool,
+    },
+    /// The turn completed normally.
+    Commit { turn_id: String, finished_ms: u64 },
+}
+
+impl JournalEntry {
+    fn turn_id(&self) -> &str {
+        match self {
+            Self::Begin { turn_id, .. }
+            | Self::Text { turn_id, .. }
+            | Self::ToolResult { turn_id, .. }
+            | Self::Commit { turn_id, .. } => turn_id,
+        }
+    }
+}
+
+/// A tool result recovered from an interrupted turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredToolResult {
+    pub id: String,
+    pub name: String,
+    pub output: String,
+    pub is_error: bool,
+}
+
+/// State of a turn that never reached its commit marker.
+#[derive(Debug, Clone)]
+pub struct InterruptedTurn {
+    pub turn_id: String,
+    pub started_ms: u64,
+    /// Conversation as it was when the turn began.
+    pub messages: Vec<ChatMessage>,
+    /// Assistant text streamed before the interruption.
+    pub partial_text: String,
+    /// Tool calls that completed before the interruption.
+    pub tool_results: Vec<RecoveredToolResult>,
+}
+
+impl InterruptedTurn {
+    /// The user input that started the interrupted turn, if any.
+    pub fn user_input(&self) -> Option<&str> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.role == "user")
+            .map(|m| m.content.as_str())
+    }
+
+    /// Build the conversation to re-dispatch when resuming.
+    ///
+    /// The original messages are kept as-is.  Partial progress (text and
+    /// completed tool results) is summarised in a trailing system note so
+    /// the model can pick up where it left off instead of redoing work.
+    pub fn replay_messages(&self) -> Vec<ChatMessage> {
+        let mut messages = self.messages.clone();
+
+        if self.partial_text.is_empty() && self.tool_results.is_empty() {
+            return messages;
+        }
+
+        let mut note = String::from(
+            "The previous attempt at this turn was interrupted by an unexpected exit. \
+             Progress recorded before the interruption:\n",
+        );
+        if !self.partial_text.is_empty() {
+            note.push_str(&format!("\nPartial response:\n{}\n", self.partial_text));
+        }
+        for tr in &self.tool_results {
+            let status = if tr.is_error { "error" } else { "ok" };
+            note.push_str(&format!(
+                "\nTool `{}` ({}) already ran [{}]:\n{}\n",
+                tr.name, tr.id, status, tr.output
+            ));
+        }
+        note.push_str("\nContinue the task without repeating completed tool calls.");
+        messages.push(ChatMessage::text("system", &note));
+        messages
+    }
+}
+
+/// Append-only journal of in-progress turns.
+pub struct TurnJournal {
+    path: PathBuf,
+}
+
+impl TurnJournal {
+    /// Open (or create) the journal in the given sessions directory.
+    pub fn open(sessions_dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(sessions_dir)
+            .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+        Ok(Self {
+            path: sessions_dir.join(JOURNAL_FILE),
+        })
+    }
+
+    /// Path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record the start of a new turn and return its ID.
+    pub fn begin(&self, messages: &[ChatMessage]) -> Result<String, String> {
+        let turn_id = generate_turn_id();
+        self.append(&JournalEntry::Begin {
+            turn_id: turn_id.clone(),
+            started_ms: now_millis(),
+            messages: messages.to_vec(),
+            trace_id: crate::observability::trace::current(),
+        })?;
+        Ok(turn_id)
+    }
+
+    /// Record assistant text produced during a turn.
+    pub fn record_text(&self, turn_id: &str, text: &str) -> Result<(), String> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.append(&JournalEntry::Text {
+            turn_id: turn_id.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    /// Record a completed tool call.
+    pub fn record_tool_result(
+        &self,
+        turn_id: &str,
+        id: &str,
+        name: &str,
+        output: &str,
+        is_error: bool,
+    ) -> Result<(), String> {
+        self.append(&JournalEntry::ToolResult {
+            turn_id: turn_id.to_string(),
+            id: id.to_string(),
+            name: name.to_string(),
+            output: output.to_string(),
+            is_error,
+        })
+    }
+
+    /// Mark a turn as finished.
+    ///
+    /// The commit marker is appended, then the journal is compacted: the
+    /// entries of committed turns are dropped, so the file never grows
+    /// beyond the turns that are currently in flight.  Other turns running
+    /// concurrently keep their entries.
+    pub fn commit(&self, turn_id: &str) -> Result<(), String> {
+        let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.append_locked(&JournalEntry::Commit {
+            turn_id: turn_id.to_string(),
+            finished_ms: now_millis(),
+        })?;
+        self.compact_locked()
+    }
+
+    /// Drop one turn's entries and keep everyone else's, e.g. once an
+    /// interrupted turn has been resumed and is journaled afresh.
+    pub fn discard(&self, turn_id: &str) -> Result<(), String> {
+        let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.retain_locked(|e| e.turn_id() != turn_id)
+    }
+
+    /// Return the most recent turn that was begun but never committed.
+    pub fn recover(&self) -> Result<Option<InterruptedTurn>, String> {
+        let mut turns: Vec<InterruptedTurn> = Vec::new();
+        for entry in self.entries()? {
+            match entry {
+                JournalEntry::Begin {
+                    turn_id,
+                    started_ms,
+                    messages,
+                    ..
+                } => {
+                    turns.push(InterruptedTurn {
+                        turn_id,
+                        started_ms,
+                        messages,
+                        partial_text: String::new(),
+                        tool_results: Vec::new(),
+                    });
+                }
+                other => {
+                    let Some(pos) = turns.iter().position(|t| t.turn_id == other.turn_id()) else {
+                        continue;
+                    };
+                    let turn = &mut turns[pos];
+                    match other {
+                        JournalEntry::Text { text, .. } => turn.partial_text.push_str(&text),
+                        JournalEntry::ToolResult {
+                            id,
+                            name,
+                            output,
+                            is_error,
+                            ..
+                        } => turn.tool_results.push(RecoveredToolResult {
+                            id,
+                            name,
+                            output,
+                            is_error,
+                        }),
+                        JournalEntry::Commit { .. } => {
+                            turns.remove(pos);
+                        }
+                        JournalEntry::Begin { .. } => unreachable!(),
+                    }
+                }
+            }
+        }
+
+        Ok(turns.pop())
+    }
+
+    /// Parse every intact entry in the journal.
+    fn entries(&self) -> Result<Vec<JournalEntry>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read turn journal: {}", e))?;
+
+        // A crash can leave a torn final line; skip anything unparsable.
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Drop the entries of committed turns.  Caller holds `JOURNAL_LOCK`.
+    fn compact_locked(&self) -> Result<(), String> {
+        let committed: HashSet<String> = self
+            .entries()?
+            .iter()
+            .filter(|e| matches!(e, JournalEntry::Commit { .. }))
+            .map(|e| e.turn_id().to_string())
+            .collect();
+        self.retain_locked(|e| !committed.contains(e.turn_id()))
+    }
+
+    /// Rewrite the journal with only the entries `keep` accepts, or remove
+    /// it when nothing is left.  Caller holds `JOURNAL_LOCK`.
+    fn retain_locked(&self, keep: impl Fn(&JournalEntry) -> bool) -> Result<(), String> {
+        let entries = self.entries()?;
+        let open: Vec<&JournalEntry> = entries.iter().filter(|e| keep(e)).collect();
+        if open.is_empty() {
+            return self.clear();
+        }
+
+        let mut content = String::new();
+        for entry in open {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut file = fs::File::create(&tmp)
+            .map_err(|e| format!("Failed to compact turn journal: {}", e))?;
+        file.write_all(content.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("Failed to compact turn journal: {}", e))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to compact turn journal: {}", e))
+    }
+
+    /// Discard the journal (e.g. after the user declines to resume).
+    pub fn clear(&self) -> Result<(), String> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)
+                .map_err(|e| format!("Failed to clear turn journal: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn append(&self, entry: &JournalEntry) -> Result<(), String> {
+        let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.append_locked(entry)
+    }
+
+    fn append_locked(&self, entry: &JournalEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open turn journal: {}", e))?;
+
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write journal entry: {}", e))?;
+        // Flush to disk so the entry survives a crash right after this call.
+        file.sync_data()
+            .map_err(|e| format!("Failed to sync turn journal: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_committed_turn_is_not_recovered() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+
+        let id = journal.begin(&[ChatMessage::text("user", "hello")]).unwrap();
+        journal.record_text(&id, "hi").unwrap();
+        journal.commit(&id).unwrap();
+
+        assert!(journal.recover().unwrap().is_none());
+        assert!(!journal.path().exists());
+    }
+
+    #[test]
+    fn test_commit_keeps_concurrent_turns() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+
+        let a = journal.begin(&[ChatMessage::text("user", "first")]).unwrap();
+        let b = journal.begin(&[ChatMessage::text("user", "second")]).unwrap();
+        journal.record_text(&a, "working on it").unwrap();
+        journal.record_text(&b, "done").unwrap();
+        journal.commit(&b).unwrap();
+
+        let content = fs::read_to_string(journal.path()).unwrap();
+        assert!(!content.contains(&b));
+        let turn = journal.recover().unwrap().expect("first turn is still open");
+        assert_eq!(turn.turn_id, a);
+        assert_eq!(turn.partial_text, "working on it");
+
+        journal.commit(&a).unwrap();
+        assert!(journal.recover().unwrap().is_none());
+        assert!(!journal.path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_begin_records_trace_id() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+
+        crate::observability::trace::scope("4bf92f3577b34da6a3ce929d0e0e4736".to_string(), async {
+            journal.begin(&[ChatMessage::text("user", "hello")]).unwrap();
+        })
+        .await;
+
+        let content = std::fs::read_to_string(journal.path()).unwrap();
+        assert!(content.contains(r#""trace_id":"4bf92f3577b34da6a3ce929d0e0e4736""#));
+    }
+
+    #[test]
+    fn test_interrupted_turn_is_recovered() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+
+        let id = journal
+            .begin(&[ChatMessage::text("user", "list files")])
+            .unwrap();
+        journal.record_text(&id, "Let me look").unwrap();
+        journal
+            .record_tool_result(&id, "call_1", "list_directory", "a.txt\nb.txt", false)
+            .unwrap();
+
+        // Re-open as a fresh process would.
+        let journal = TurnJournal::open(dir.path()).unwrap();
+        let turn = journal.recover().unwrap().expect("turn should be recovered");
+        assert_eq!(turn.turn_id, id);
+        assert_eq!(turn.user_input(), Some("list files"));
+        assert_eq!(turn.partial_text, "Let me look");
+        assert_eq!(turn.tool_results.len(), 1);
+        assert_eq!(turn.tool_results[0].name, "list_directory");
+
+        let replay = turn.replay_messages();
+        assert_eq!(replay.len(), 2);
+        assert_eq!(replay[1].role, "system");
+        assert!(replay[1].content.contains("list_directory"));
+    }
+
+    #[test]
+    fn test_torn_trailing_line_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+        journal.begin(&[ChatMessage::text("user", "x")]).unwrap();
+
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        write!(file, "{{\"kind\":\"text\",\"turnId").unwrap();
+
+        assert!(journal.recover().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_discard_keeps_other_turns() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+        let other = journal.begin(&[ChatMessage::text("user", "other")]).unwrap();
+        let resumed = journal.begin(&[ChatMessage::text("user", "resumed")]).unwrap();
+        journal.record_text(&resumed, "partial").unwrap();
+
+        journal.discard(&resumed).unwrap();
+        let turn = journal.recover().unwrap().unwrap();
+        assert_eq!(turn.turn_id, other);
+
+        journal.discard(&other).unwrap();
+        assert!(!journal.path().exists());
+    }
+
+    #[test]
+    fn test_clear() {
+        let dir = TempDir::new().unwrap();
+        let journal = TurnJournal::open(dir.path()).unwrap();
+        journal.begin(&[ChatMessage::text("user", "x")]).unwrap();
+        journal.clear().unwrap();
+        assert!(journal.recover().unwrap().is_none());
+    }
+}
@@ -5,33 +5,69 @@
 // skills, providers, commands, and shared display types.
 
 pub mod args;
+pub mod backup;
 pub mod commands;
 pub mod config;
+pub mod contacts;
+pub mod conversations;
+pub mod container;
 pub mod cron;
 pub mod daemon;
+pub mod dev_env;
+pub mod embeddings;
 pub mod error;
 pub mod gateway;
+pub mod heartbeat;
+pub mod i18n;
+pub mod identity;
+pub mod journal;
 pub mod logging;
+pub mod loop_guard;
+pub mod lsp;
 pub mod memory;
 pub mod memory_flush;
 pub mod messengers;
+pub mod model_caps;
+pub mod mqtt;
+pub mod notifications;
 pub mod observability;
+pub mod plan;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugins;
+pub mod presence;
 pub mod process_manager;
+pub mod progress;
+pub mod project;
 pub mod providers;
+pub mod quiet_hours;
+pub mod recording;
 pub mod retry;
 pub mod runtime;
 pub mod sandbox;
+pub mod scripting;
 pub mod secrets;
 pub mod security;
+pub mod session_meta;
 pub mod sessions;
 pub mod skills;
+pub mod snapshots;
 pub mod soul;
 pub mod streaming;
+pub mod task_queue;
+pub mod templates;
 pub mod theme;
+pub mod tool_servers;
+#[cfg(feature = "testkit")]
+pub mod testkit;
 pub mod tools;
+pub mod translate;
+pub mod trash;
 pub mod types;
 pub mod user_prompt_types;
+pub mod users;
+pub mod wildcard;
 pub mod workspace_context;
+pub mod worktree;
 
 // Re-export messenger types at crate root for convenience
 pub use messengers::{Message, Messenger, MessengerManager, SendOptions};
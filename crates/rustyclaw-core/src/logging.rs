@@ -7,6 +7,8 @@
 //!
 //! - `RUSTYCLAW_LOG` or `RUST_LOG`: Set log level (e.g., `debug`, `rustyclaw=debug,hyper=warn`)
 //! - `RUSTYCLAW_LOG_FORMAT`: Set output format (`pretty`, `compact`, `json`)
+//! - `RUSTYCLAW_OTLP_ENDPOINT` or `OTEL_EXPORTER_OTLP_ENDPOINT`: Export
+//!   request traces to an OpenTelemetry collector (OTLP/HTTP)
 //!
 //! ## Examples
 //!
@@ -16,8 +18,18 @@
 //!
 //! # JSON output for production
 //! RUSTYCLAW_LOG_FORMAT=json rustyclaw gateway run
+//!
+//! # Send turn traces to a local Jaeger / OpenTelemetry Collector
+//! RUSTYCLAW_OTLP_ENDPOINT=http://localhost:4318 rustyclaw gateway run
 //! ```
+//!
+//! Every gateway turn runs inside a `turn` span carrying its `trace_id`,
+//! so all log lines for one messenger message or chat request can be
+//! found with a single grep.  The most recent events are also kept in
+//! memory and can be read from the TUI with `/events`.
 
+use crate::observability::event_log::EventLogLayer;
+use crate::observability::otlp::OtlpLayer;
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
     prelude::*,
@@ -62,6 +74,8 @@ pub struct LogConfig {
     pub with_thread_ids: bool,
     /// Include target (module path)
     pub with_target: bool,
+    /// OTLP collector to export request traces to
+    pub otlp_endpoint: Option<String>,
 }
 
 impl Default for LogConfig {
@@ -73,6 +87,7 @@ impl Default for LogConfig {
             with_file: false,
             with_thread_ids: false,
             with_target: true,
+            otlp_endpoint: None,
         }
     }
 }
@@ -88,9 +103,15 @@ impl LogConfig {
             .map(|s| LogFormat::from_str(&s))
             .unwrap_or_default();
 
+        let otlp_endpoint = std::env::var("RUSTYCLAW_OTLP_ENDPOINT")
+            .or_else(|_| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT"))
+            .ok()
+            .filter(|e| !e.trim().is_empty());
+
         Self {
             filter,
             format,
+            otlp_endpoint,
             ..Default::default()
         }
     }
@@ -142,9 +163,11 @@ pub fn init(config: LogConfig) {
         FmtSpan::NONE
     };
 
+    let otlp = || config.otlp_endpoint.as_deref().map(OtlpLayer::new);
+
     match config.format {
         LogFormat::Json => {
-            let subscriber = tracing_subscriber::registry().with(env_filter).with(
+            let subscriber = tracing_subscriber::registry().with(env_filter).with(otlp()).with(EventLogLayer).with(
                 fmt::layer()
                     .json()
                     .with_span_events(span_events)
@@ -156,7 +179,7 @@ pub fn init(config: LogConfig) {
             let _ = tracing::subscriber::set_global_default(subscriber);
         }
         LogFormat::Compact => {
-            let subscriber = tracing_subscriber::registry().with(env_filter).with(
+            let subscriber = tracing_subscriber::registry().with(env_filter).with(otlp()).with(EventLogLayer).with(
                 fmt::layer()
                     .compact()
                     .with_span_events(span_events)
@@ -168,7 +191,7 @@ pub fn init(config: LogConfig) {
             let _ = tracing::subscriber::set_global_default(subscriber);
         }
         LogFormat::Pretty => {
-            let subscriber = tracing_subscriber::registry().with(env_filter).with(
+            let subscriber = tracing_subscriber::registry().with(env_filter).with(otlp()).with(EventLogLayer).with(
                 fmt::layer()
                     .pretty()
                     .with_span_events(span_events)
@@ -210,11 +233,14 @@ mod tests {
             std::env::remove_var("RUSTYCLAW_LOG");
             std::env::remove_var("RUST_LOG");
             std::env::remove_var("RUSTYCLAW_LOG_FORMAT");
+            std::env::remove_var("RUSTYCLAW_OTLP_ENDPOINT");
+            std::env::remove_var("OTEL_EXPORTER_OTLP_ENDPOINT");
         }
 
         let config = LogConfig::from_env();
         assert_eq!(config.filter, "rustyclaw=info,warn");
         assert_eq!(config.format, LogFormat::Pretty);
+        assert!(config.otlp_endpoint.is_none());
     }
 
     #[test]
@@ -0,0 +1,145 @@
+//! Loop detection for the tool loop.
+//!
+//! Models sometimes get stuck: they read the same file over and over, or
+//! flip between two calls (write, read, write, read) without making
+//! progress.  A [`LoopGuard`] watches the tool calls of one turn and flags
+//!
+//! - the same call with identical arguments [`REPEAT_LIMIT`] times in a
+//!   row, and
+//! - rounds alternating between two states (A, B, A, B).
+//!
+//! The first time, the tool loop adds a corrective note to the
+//! conversation; if the model keeps looping anyway, the turn is aborted.
+//! Process-wide counters of both are shown by `session_status`.
+
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identical calls in a row that count as a loop.
+pub const REPEAT_LIMIT: usize = 3;
+
+static WARNINGS: AtomicU64 = AtomicU64::new(0);
+static ABORTS: AtomicU64 = AtomicU64::new(0);
+
+/// What the tool loop should do about a round of tool calls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// No loop.
+    Ok,
+    /// Looping; run the calls, then add this note for the model.
+    Warn(String),
+    /// Still looping after a warning; stop the turn with this error.
+    Abort(String),
+}
+
+/// Tool-call history of one turn.
+#[derive(Debug, Default)]
+pub struct LoopGuard {
+    /// Every call so far, as `name(args)`.
+    calls: Vec<String>,
+    /// Every round so far, as its calls joined.
+    rounds: Vec<String>,
+    warned: bool,
+}
+
+impl LoopGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a round of tool calls and check it for loops.
+    pub fn check<'a>(&mut self, calls: impl IntoIterator<Item = (&'a str, &'a Value)>) -> Verdict {
+        let round: Vec<String> = calls.into_iter().map(|(name, args)| format!("{}({})", name, args)).collect();
+        if round.is_empty() {
+            return Verdict::Ok;
+        }
+        self.rounds.push(round.join("\n"));
+        self.calls.extend(round);
+
+        let Some(problem) = self.repeated_call().or_else(|| self.alternating()) else {
+            return Verdict::Ok;
+        };
+        if std::mem::replace(&mut self.warned, true) {
+            ABORTS.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(problem = %problem, "Tool loop detected again, aborting turn");
+            Verdict::Abort(format!("Stopped a tool loop: {}.", problem))
+        } else {
+            WARNINGS.fetch_add(1, Ordering::Relaxed);
+            tracing::debug!(problem = %problem, "Tool loop detected");
+            Verdict::Warn(format!(
+                "Loop detected: {}. Repeating it will not give a different result. \
+                 Try a different approach, or answer the user with what you have. \
+                 If this continues the turn will be stopped.",
+                problem
+            ))
+        }
+    }
+
+    /// Whether a loop was already flagged this turn, so the next one
+    /// aborts it.
+    pub fn warned(&self) -> bool {
+        self.warned
+    }
+
+    fn repeated_call(&self) -> Option<String> {
+        let last = self.calls.last()?;
+        let run = self.calls.iter().rev().take_while(|c| *c == last).count();
+        (run >= REPEAT_LIMIT).then(|| {
+            let name = last.split('(').next().unwrap_or(last);
+            format!("{} was called {} times in a row with the same arguments", name, run)
+        })
+    }
+
+    fn alternating(&self) -> Option<String> {
+        let [.., a, b, c, d] = self.rounds.as_slice() else {
+            return None;
+        };
+        (a == c && b == d && a != b).then(|| "the last rounds alternate between the same two tool calls".to_string())
+    }
+}
+
+/// Loops warned about and turns aborted since the process started.
+pub fn counters() -> (u64, u64) {
+    (WARNINGS.load(Ordering::Relaxed), ABORTS.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_repeated_call_warns_then_aborts() {
+        let args = json!({"path": "a.txt"});
+        let mut guard = LoopGuard::new();
+        assert_eq!(guard.check([("read_file", &args)]), Verdict::Ok);
+        assert_eq!(guard.check([("read_file", &args)]), Verdict::Ok);
+        let Verdict::Warn(note) = guard.check([("read_file", &args)]) else {
+            panic!("expected a warning");
+        };
+        assert!(note.contains("read_file was called 3 times"));
+        assert!(guard.warned());
+        assert!(matches!(guard.check([("read_file", &args)]), Verdict::Abort(_)));
+    }
+
+    #[test]
+    fn test_different_arguments_are_not_a_loop() {
+        let mut guard = LoopGuard::new();
+        for i in 0..6 {
+            let args = json!({"path": format!("{}.txt", i)});
+            assert_eq!(guard.check([("read_file", &args)]), Verdict::Ok);
+        }
+    }
+
+    #[test]
+    fn test_alternating_rounds() {
+        let (write, read) = (json!({"path": "x", "content": "1"}), json!({"path": "x"}));
+        let mut guard = LoopGuard::new();
+        assert_eq!(guard.check([("write_file", &write)]), Verdict::Ok);
+        assert_eq!(guard.check([("read_file", &read)]), Verdict::Ok);
+        assert_eq!(guard.check([("write_file", &write)]), Verdict::Ok);
+        assert!(matches!(guard.check([("read_file", &read)]), Verdict::Warn(_)));
+        // Moving on after the warning is fine.
+        assert_eq!(guard.check([("list_directory", &read)]), Verdict::Ok);
+    }
+}
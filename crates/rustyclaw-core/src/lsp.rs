@@ -0,0 +1,592 @@
+//! Language server client for the code intelligence tools.
+//!
+//! `lsp_definition`, `lsp_references`, `lsp_diagnostics` and `lsp_rename`
+//! talk to the project's language server over stdio (JSON-RPC with
+//! `Content-Length` framing).  A server is started on first use for each
+//! (project root, language) pair and kept running; files are synced from
+//! disk before every request, so edits made by other tools are seen.
+//!
+//! Built-in servers cover Rust, TypeScript/JavaScript, Python and Go.
+//! Others can be added, or the built-ins overridden, in `config.toml`:
+//!
+//! ```toml
+//! [[lsp_servers]]
+//! language = "c"
+//! command = "clangd"
+//! extensions = ["c", "h", "cpp", "hpp"]
+//! markers = ["compile_commands.json"]
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// How long to wait for the `initialize` handshake (servers index first).
+const INIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to wait for an ordinary request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A language server and the files it handles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LspServerConfig {
+    /// Language id sent in `didOpen` (e.g. "rust", "python").
+    pub language: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// File extensions (without the dot) this server handles.
+    pub extensions: Vec<String>,
+    /// Files marking a project root for this server.
+    #[serde(default)]
+    pub markers: Vec<String>,
+}
+
+impl LspServerConfig {
+    fn new(language: &str, command: &str, args: &[&str], extensions: &[&str], markers: &[&str]) -> Self {
+        let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        Self {
+            language: language.to_string(),
+            command: command.to_string(),
+            args: strings(args),
+            extensions: strings(extensions),
+            markers: strings(markers),
+        }
+    }
+}
+
+/// The servers used when the config doesn't override them.
+pub fn builtin_servers() -> Vec<LspServerConfig> {
+    vec![
+        LspServerConfig::new("rust", "rust-analyzer", &[], &["rs"], &["Cargo.toml"]),
+        LspServerConfig::new(
+            "typescript",
+            "typescript-language-server",
+            &["--stdio"],
+            &["ts", "tsx", "js", "jsx", "mjs", "cjs"],
+            &["tsconfig.json", "jsconfig.json", "package.json"],
+        ),
+        LspServerConfig::new(
+            "python",
+            "pyright-langserver",
+            &["--stdio"],
+            &["py", "pyi"],
+            &["pyproject.toml", "setup.py", "setup.cfg", "requirements.txt"],
+        ),
+        LspServerConfig::new("go", "gopls", &[], &["go"], &["go.mod"]),
+    ]
+}
+
+/// Servers configured in `config.toml`, consulted before the built-ins.
+static CONFIGURED: RwLock<Vec<LspServerConfig>> = RwLock::new(Vec::new());
+
+/// Register the configured servers.
+pub fn set_servers(servers: Vec<LspServerConfig>) {
+    if let Ok(mut guard) = CONFIGURED.write() {
+        *guard = servers;
+    }
+}
+
+/// The server responsible for `path`, by file extension.
+pub fn server_for(path: &Path) -> Option<LspServerConfig> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let configured = CONFIGURED.read().map(|c| c.clone()).unwrap_or_default();
+    configured
+        .into_iter()
+        .chain(builtin_servers())
+        .find(|s| s.extensions.iter().any(|e| *e == ext))
+}
+
+/// Nearest ancestor of `file` containing one of `markers`, bounded by
+/// `workspace_dir`; the workspace itself when none is found.
+pub fn project_root(file: &Path, markers: &[String], workspace_dir: &Path) -> PathBuf {
+    let mut found = None;
+    for dir in file.ancestors().skip(1) {
+        if markers.iter().any(|m| dir.join(m).exists()) {
+            // Keep going: a Cargo workspace root beats the member crate.
+            found = Some(dir.to_path_buf());
+        }
+        if dir == workspace_dir {
+            break;
+        }
+    }
+    found.unwrap_or_else(|| workspace_dir.to_path_buf())
+}
+
+// ── Positions and URIs ──────────────────────────────────────────────────────
+
+/// `file://` URI for `path`.
+pub fn path_to_uri(path: &Path) -> String {
+    url::Url::from_file_path(path)
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| format!("file://{}", path.display()))
+}
+
+/// Filesystem path for a `file://` URI.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    url::Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+/// UTF-16 offset (what LSP counts) of the `col`-th character of `line`.
+pub fn utf16_col(line: &str, col: usize) -> usize {
+    line.chars().take(col).map(char::len_utf16).sum()
+}
+
+/// Byte offset in `text` of an LSP position (0-based line, UTF-16 column).
+/// Positions past the end of a line clamp to the line end.
+pub fn byte_offset(text: &str, line: usize, character: usize) -> usize {
+    let mut start = 0;
+    for _ in 0..line {
+        match text[start..].find('\n') {
+            Some(i) => start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let line_text = text[start..].split('\n').next().unwrap_or("");
+    let mut units = 0;
+    for (i, c) in line_text.char_indices() {
+        if units >= character {
+            return start + i;
+        }
+        units += c.len_utf16();
+    }
+    start + line_text.len()
+}
+
+/// Apply LSP `TextEdit`s to `text`.  Edits must not overlap.
+pub fn apply_text_edits(text: &str, edits: &[Value]) -> Result<String, String> {
+    let pos = |v: &Value, key: &str| -> Result<usize, String> {
+        let p = &v["range"][key];
+        let line = p["line"].as_u64().ok_or("Invalid edit range")? as usize;
+        let character = p["character"].as_u64().ok_or("Invalid edit range")? as usize;
+        Ok(byte_offset(text, line, character))
+    };
+    let mut spans = Vec::with_capacity(edits.len());
+    for edit in edits {
+        let new_text = edit["newText"].as_str().ok_or("Edit without newText")?;
+        spans.push((pos(edit, "start")?, pos(edit, "end")?, new_text));
+    }
+    // Apply back to front so earlier offsets stay valid.
+    spans.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+    let mut out = text.to_string();
+    let mut limit = usize::MAX;
+    for (start, end, new_text) in spans {
+        if end > limit || start > end {
+            return Err("Overlapping edits in workspace edit".to_string());
+        }
+        out.replace_range(start..end, new_text);
+        limit = start;
+    }
+    Ok(out)
+}
+
+/// Group the edits of a `WorkspaceEdit` by file.
+pub fn workspace_edit_files(edit: &Value) -> Result<Vec<(PathBuf, Vec<Value>)>, String> {
+    let mut files: Vec<(PathBuf, Vec<Value>)> = Vec::new();
+    let mut add = |uri: &str, edits: &[Value]| -> Result<(), String> {
+        let path = uri_to_path(uri).ok_or_else(|| format!("Unsupported URI: {}", uri))?;
+        match files.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, existing)) => existing.extend_from_slice(edits),
+            None => files.push((path, edits.to_vec())),
+        }
+        Ok(())
+    };
+    if let Some(changes) = edit["changes"].as_object() {
+        for (uri, edits) in changes {
+            add(uri, edits.as_array().map(Vec::as_slice).unwrap_or(&[]))?;
+        }
+    }
+    if let Some(doc_changes) = edit["documentChanges"].as_array() {
+        for change in doc_changes {
+            // Resource operations (create/rename/delete) carry a `kind`.
+            if let Some(kind) = change["kind"].as_str() {
+                return Err(format!("Rename needs a file {} operation, which is not supported", kind));
+            }
+            let uri = change["textDocument"]["uri"].as_str().ok_or("Edit without document URI")?;
+            add(uri, change["edits"].as_array().map(Vec::as_slice).unwrap_or(&[]))?;
+        }
+    }
+    Ok(files)
+}
+
+// ── Wire format ─────────────────────────────────────────────────────────────
+
+/// Read one `Content-Length` framed message.  `Ok(None)` at end of stream.
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        let n = reader.read_line(&mut header).map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>().map_err(|e| e.to_string())?);
+        }
+    }
+    let length = length.ok_or("Message without Content-Length")?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&body).map(Some).map_err(|e| e.to_string())
+}
+
+fn write_message(writer: &Mutex<ChildStdin>, message: &Value) -> Result<(), String> {
+    let body = message.to_string();
+    let mut w = writer.lock().map_err(|_| "LSP writer lock poisoned".to_string())?;
+    write!(w, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| w.flush())
+        .map_err(|e| format!("Language server write failed: {}", e))
+}
+
+// ── Client ──────────────────────────────────────────────────────────────────
+
+/// Diagnostics last published for each document, with a counter that
+/// bumps on every publish.
+#[derive(Default)]
+struct DiagnosticStore {
+    by_uri: HashMap<String, Vec<Value>>,
+    generation: HashMap<String, u64>,
+}
+
+/// A running language server.
+pub struct LspClient {
+    pub root: PathBuf,
+    pub server: LspServerConfig,
+    child: Mutex<Child>,
+    writer: Arc<Mutex<ChildStdin>>,
+    next_id: AtomicI64,
+    pending: Arc<Mutex<HashMap<i64, mpsc::Sender<Value>>>>,
+    diagnostics: Arc<Mutex<DiagnosticStore>>,
+    /// Open documents: version and the text last sent.
+    documents: Mutex<HashMap<PathBuf, (i64, String)>>,
+}
+
+impl LspClient {
+    /// Start `server` for the project at `root` and run the handshake.
+    pub fn start(server: LspServerConfig, root: PathBuf) -> Result<Self, String> {
+        let mut child = Command::new(&server.command)
+            .args(&server.args)
+            .current_dir(&root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start {} ({}): {}", server.command, server.language, e))?;
+        let stdin = child.stdin.take().ok_or("Language server has no stdin")?;
+        let stdout = child.stdout.take().ok_or("Language server has no stdout")?;
+
+        let client = Self {
+            root,
+            server,
+            child: Mutex::new(child),
+            writer: Arc::new(Mutex::new(stdin)),
+            next_id: AtomicI64::new(1),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            diagnostics: Arc::new(Mutex::new(DiagnosticStore::default())),
+            documents: Mutex::new(HashMap::new()),
+        };
+        client.spawn_reader(stdout);
+        client.initialize()?;
+        info!(server = %client.server.command, root = %client.root.display(), "Language server started");
+        Ok(client)
+    }
+
+    /// Route responses to waiting requests, keep diagnostics, and answer
+    /// server requests so the server never blocks on us.
+    fn spawn_reader(&self, stdout: std::process::ChildStdout) {
+        let pending = self.pending.clone();
+        let diagnostics = self.diagnostics.clone();
+        let writer = self.writer.clone();
+        let name = self.server.command.clone();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let message = match read_message(&mut reader) {
+                    Ok(Some(m)) => m,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!(server = %name, error = %e, "Bad message from language server");
+                        break;
+                    }
+                };
+                match (message.get("id"), message.get("method").and_then(|m| m.as_str())) {
+                    (Some(id), Some(method)) => {
+                        // Server → client request: accept with an empty result.
+                        debug!(server = %name, method, "Answering server request");
+                        let _ = write_message(&writer, &json!({"jsonrpc": "2.0", "id": id, "result": null}));
+                    }
+                    (Some(id), None) => {
+                        let sender = id.as_i64().and_then(|id| pending.lock().ok()?.remove(&id));
+                        if let Some(sender) = sender {
+                            let _ = sender.send(message);
+                        }
+                    }
+                    (None, Some("textDocument/publishDiagnostics")) => {
+                        let params = &message["params"];
+                        if let (Some(uri), Ok(mut store)) = (params["uri"].as_str(), diagnostics.lock()) {
+                            let items = params["diagnostics"].as_array().cloned().unwrap_or_default();
+                            store.by_uri.insert(uri.to_string(), items);
+                            *store.generation.entry(uri.to_string()).or_insert(0) += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            debug!(server = %name, "Language server output closed");
+        });
+    }
+
+    fn initialize(&self) -> Result<(), String> {
+        let root_uri = path_to_uri(&self.root);
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": "workspace" }],
+            "capabilities": {
+                "textDocument": {
+                    "synchronization": { "didSave": false },
+                    "definition": { "linkSupport": false },
+                    "references": {},
+                    "rename": { "prepareSupport": false },
+                    "publishDiagnostics": { "relatedInformation": false },
+                },
+                "workspace": {
+                    "workspaceEdit": { "documentChanges": true },
+                    "workspaceFolders": true,
+                    "configuration": true,
+                },
+            },
+        });
+        self.request_with_timeout("initialize", params, INIT_TIMEOUT)?;
+        self.notify("initialized", json!({}))
+    }
+
+    /// Whether the server process is still running.
+    pub fn is_alive(&self) -> bool {
+        self.child
+            .lock()
+            .map(|mut c| matches!(c.try_wait(), Ok(None)))
+            .unwrap_or(false)
+    }
+
+    pub fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        write_message(&self.writer, &json!({"jsonrpc": "2.0", "method": method, "params": params}))
+    }
+
+    pub fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.request_with_timeout(method, params, REQUEST_TIMEOUT)
+    }
+
+    fn request_with_timeout(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = mpsc::channel();
+        self.pending
+            .lock()
+            .map_err(|_| "LSP lock poisoned".to_string())?
+            .insert(id, tx);
+        write_message(
+            &self.writer,
+            &json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}),
+        )?;
+        let response = rx.recv_timeout(timeout).map_err(|_| {
+            if let Ok(mut p) = self.pending.lock() {
+                p.remove(&id);
+            }
+            format!("{} timed out after {}s", method, timeout.as_secs())
+        })?;
+        if let Some(error) = response.get("error") {
+            return Err(format!(
+                "{} failed: {}",
+                method,
+                error["message"].as_str().unwrap_or("unknown error")
+            ));
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// Send the file's current disk contents to the server (open or
+    /// change).  Returns its URI.
+    pub fn sync_document(&self, path: &Path) -> Result<String, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let uri = path_to_uri(path);
+        let mut docs = self.documents.lock().map_err(|_| "LSP lock poisoned".to_string())?;
+        match docs.get_mut(path) {
+            None => {
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({"textDocument": {
+                        "uri": uri, "languageId": self.server.language, "version": 1, "text": text,
+                    }}),
+                )?;
+                docs.insert(path.to_path_buf(), (1, text));
+            }
+            Some((version, sent)) if *sent != text => {
+                *version += 1;
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": *version },
+                        "contentChanges": [{ "text": text }],
+                    }),
+                )?;
+                *sent = text;
+            }
+            Some(_) => {}
+        }
+        Ok(uri)
+    }
+
+    fn diagnostics_generation(&self, uri: &str) -> u64 {
+        self.diagnostics
+            .lock()
+            .map(|d| d.generation.get(uri).copied().unwrap_or(0))
+            .unwrap_or(0)
+    }
+
+    /// Diagnostics for `path`, waiting up to `wait` for the server to
+    /// publish a fresh set after the file is synced.
+    pub fn diagnostics(&self, path: &Path, wait: Duration) -> Result<Vec<Value>, String> {
+        let uri = path_to_uri(path);
+        let before = self.diagnostics_generation(&uri);
+        self.sync_document(path)?;
+        let deadline = Instant::now() + wait;
+        while self.diagnostics_generation(&uri) == before && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        Ok(self
+            .diagnostics
+            .lock()
+            .map(|d| d.by_uri.get(&uri).cloned().unwrap_or_default())
+            .unwrap_or_default())
+    }
+
+    /// Ask the server to exit.
+    pub fn shutdown(&self) {
+        let _ = self.request_with_timeout("shutdown", Value::Null, Duration::from_secs(5));
+        let _ = self.notify("exit", Value::Null);
+        if let Ok(mut child) = self.child.lock() {
+            let _ = child.kill();
+        }
+    }
+}
+
+// ── Client pool ─────────────────────────────────────────────────────────────
+
+type ClientKey = (PathBuf, String);
+
+static CLIENTS: OnceLock<Mutex<HashMap<ClientKey, Arc<LspClient>>>> = OnceLock::new();
+
+/// The running client for `file`, starting its server if needed.
+pub fn client_for(file: &Path, workspace_dir: &Path) -> Result<Arc<LspClient>, String> {
+    let server = server_for(file).ok_or_else(|| {
+        format!("No language server configured for {}", file.display())
+    })?;
+    let root = project_root(file, &server.markers, workspace_dir);
+    let key = (root.clone(), server.language.clone());
+
+    let clients = CLIENTS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut clients = clients.lock().map_err(|_| "LSP lock poisoned".to_string())?;
+    if let Some(client) = clients.get(&key) {
+        if client.is_alive() {
+            return Ok(client.clone());
+        }
+        warn!(server = %server.command, "Language server exited; restarting");
+    }
+    let client = Arc::new(LspClient::start(server, root)?);
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// Stop every running language server.
+pub fn shutdown_all() {
+    if let Some(clients) = CLIENTS.get() {
+        if let Ok(mut clients) = clients.lock() {
+            for (_, client) in clients.drain() {
+                client.shutdown();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_positions() {
+        assert_eq!(utf16_col("a😀b", 2), 3);
+        let text = "fn a() {}\nlet 😀x = 1;\n";
+        assert_eq!(byte_offset(text, 1, 0), 10);
+        // The emoji is two UTF-16 units and four bytes.
+        assert_eq!(&text[byte_offset(text, 1, 6)..byte_offset(text, 1, 7)], "x");
+        assert_eq!(byte_offset(text, 5, 0), text.len());
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let text = "let foo = 1;\nprintln!(\"{}\", foo);\n";
+        let edit = |line, start, end| {
+            json!({"range": {"start": {"line": line, "character": start},
+                             "end": {"line": line, "character": end}}, "newText": "bar"})
+        };
+        let out = apply_text_edits(text, &[edit(0, 4, 7), edit(1, 15, 18)]).unwrap();
+        assert_eq!(out, "let bar = 1;\nprintln!(\"{}\", bar);\n");
+        assert!(apply_text_edits(text, &[edit(0, 4, 7), edit(0, 5, 6)]).is_err());
+    }
+
+    #[test]
+    fn test_workspace_edit_files() {
+        let uri = path_to_uri(Path::new("/tmp/a.rs"));
+        let range = json!({"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 1}});
+        let edit = json!({
+            "changes": { uri.clone(): [{"range": range, "newText": "x"}] },
+            "documentChanges": [{"textDocument": {"uri": uri, "version": 1},
+                                 "edits": [{"range": range, "newText": "y"}]}],
+        });
+        let files = workspace_edit_files(&edit).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, PathBuf::from("/tmp/a.rs"));
+        assert_eq!(files[0].1.len(), 2);
+
+        let create = json!({"documentChanges": [{"kind": "create", "uri": "file:///tmp/b.rs"}]});
+        assert!(workspace_edit_files(&create).is_err());
+    }
+
+    #[test]
+    fn test_read_message() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+        let raw = format!("Content-Length: {}\r\nContent-Type: x\r\n\r\n{}", body.len(), body);
+        let mut reader = std::io::Cursor::new(raw.into_bytes());
+        let msg = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(msg["id"], 1);
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_server_selection_and_root() {
+        assert_eq!(server_for(Path::new("src/main.rs")).unwrap().command, "rust-analyzer");
+        assert_eq!(server_for(Path::new("app.TSX")).unwrap().language, "typescript");
+        assert!(server_for(Path::new("README.md")).is_none());
+
+        let ws = tempfile::TempDir::new().unwrap();
+        let member = ws.path().join("crates/core/src");
+        std::fs::create_dir_all(&member).unwrap();
+        std::fs::write(ws.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(ws.path().join("crates/core/Cargo.toml"), "").unwrap();
+        let markers = vec!["Cargo.toml".to_string()];
+        assert_eq!(project_root(&member.join("lib.rs"), &markers, ws.path()), ws.path());
+    }
+}
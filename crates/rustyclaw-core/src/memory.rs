@@ -1,9 +1,12 @@
 //! Memory search and retrieval for RustyClaw.
 //!
 //! Provides semantic-like search over `MEMORY.md` and `memory/*.md` files.
-//! Current implementation uses keyword/BM25-style matching with temporal decay
-//! for recency weighting. Embeddings can be added later for true semantic search.
+//! Keyword search uses BM25-style matching with temporal decay for recency
+//! weighting. With `[embeddings]` enabled, [`MemoryIndex::search_semantic`]
+//! ranks chunks by embedding similarity; chunk embeddings come from the
+//! shared cache, so re-indexing only embeds chunks that changed.
 
+use crate::progress::{Progress, Unit};
 use chrono::{NaiveDate, Utc};
 use std::collections::HashMap;
 use std::fs;
@@ -57,17 +60,18 @@ impl MemoryIndex {
     /// Index all memory files in a workspace.
     pub fn index_workspace(workspace: &Path) -> Result<Self, String> {
         let mut index = Self::new();
+        let mut progress = Progress::start("Indexing memory files", Unit::Items, None);
 
         // Index MEMORY.md if it exists
         let memory_md = workspace.join("MEMORY.md");
         if memory_md.exists() {
-            index.index_file(&memory_md, "MEMORY.md")?;
+            index.index_file(&memory_md, "MEMORY.md", &mut progress)?;
         }
 
         // Index memory/*.md
         let memory_dir = workspace.join("memory");
         if memory_dir.exists() && memory_dir.is_dir() {
-            index.index_directory(&memory_dir, "memory")?;
+            index.index_directory(&memory_dir, "memory", &mut progress)?;
         }
 
         // Build inverted index
@@ -77,7 +81,7 @@ impl MemoryIndex {
     }
 
     /// Index a single file.
-    fn index_file(&mut self, path: &Path, relative_path: &str) -> Result<(), String> {
+    fn index_file(&mut self, path: &Path, relative_path: &str, progress: &mut Progress) -> Result<(), String> {
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read {}: {}", relative_path, e))?;
 
@@ -85,12 +89,13 @@ impl MemoryIndex {
         // For simplicity, we chunk by paragraphs or heading sections
         let chunks = self.chunk_content(&content, relative_path);
         self.chunks.extend(chunks);
+        progress.advance(1);
 
         Ok(())
     }
 
     /// Index a directory recursively.
-    fn index_directory(&mut self, dir: &Path, relative_prefix: &str) -> Result<(), String> {
+    fn index_directory(&mut self, dir: &Path, relative_prefix: &str, progress: &mut Progress) -> Result<(), String> {
         let entries = fs::read_dir(dir)
             .map_err(|e| format!("Failed to read directory {}: {}", relative_prefix, e))?;
 
@@ -100,9 +105,9 @@ impl MemoryIndex {
             let relative = format!("{}/{}", relative_prefix, name);
 
             if path.is_file() && name.ends_with(".md") {
-                self.index_file(&path, &relative)?;
+                self.index_file(&path, &relative, progress)?;
             } else if path.is_dir() && !name.starts_with('.') {
-                self.index_directory(&path, &relative)?;
+                self.index_directory(&path, &relative, progress)?;
             }
         }
 
@@ -302,6 +307,37 @@ impl MemoryIndex {
             .collect()
     }
 
+    /// Search by embedding similarity to the query.  Fails when embeddings
+    /// are not enabled or the provider can't be reached.
+    pub async fn search_semantic(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, String> {
+        if query.trim().is_empty() || self.chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut texts: Vec<String> = self.chunks.iter().map(|c| c.text.clone()).collect();
+        texts.push(query.to_string());
+        let vectors = crate::embeddings::embed(&texts).await?;
+        let Some((query_vec, chunk_vecs)) = vectors.split_last() else {
+            return Ok(Vec::new());
+        };
+
+        let mut scores: Vec<(usize, f64)> = chunk_vecs
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| (idx, crate::embeddings::cosine(query_vec, v) as f64))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(scores
+            .into_iter()
+            .take(max_results)
+            .map(|(idx, score)| SearchResult {
+                chunk: self.chunks[idx].clone(),
+                score,
+            })
+            .collect())
+    }
+
     /// Check if a file path is "evergreen" (shouldn't decay).
     ///
     /// Evergreen files include MEMORY.md and any file not in the memory/ directory.
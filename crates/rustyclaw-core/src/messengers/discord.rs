@@ -1,6 +1,6 @@
 //! Discord messenger using bot token and REST API.
 
-use super::{Message, Messenger};
+use super::{Message, Messenger, SendOptions};
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -72,8 +72,59 @@ impl Messenger for DiscordMessenger {
         }
     }
 
+    async fn send_message_with_options(&self, opts: SendOptions<'_>) -> Result<String> {
+        let url = format!(
+            "https://discord.com/api/v10/channels/{}/messages",
+            opts.recipient
+        );
+
+        let mut payload = serde_json::json!({ "content": opts.content });
+        if let Some(reply_to) = opts.reply_to {
+            payload["message_reference"] = serde_json::json!({ "message_id": reply_to });
+        }
+        if !opts.buttons.is_empty() {
+            // Action rows hold up to five buttons; custom_id comes back in
+            // the interaction when one is pressed.
+            let rows: Vec<_> = opts
+                .buttons
+                .chunks(5)
+                .map(|row| {
+                    let buttons: Vec<_> = row
+                        .iter()
+                        .map(|b| serde_json::json!({
+                            "type": 2,
+                            "style": 1,
+                            "label": b.label,
+                            "custom_id": b.data,
+                        }))
+                        .collect();
+                    serde_json::json!({ "type": 1, "components": buttons })
+                })
+                .collect();
+            payload["components"] = serde_json::json!(rows);
+        }
+
+        let resp = self
+            .http
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.bot_token))
+            .json(&payload)
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            let data: serde_json::Value = resp.json().await?;
+            Ok(data["id"].as_str().unwrap_or("unknown").to_string())
+        } else {
+            anyhow::bail!("Discord send failed: {}", resp.status())
+        }
+    }
+
     async fn receive_messages(&self) -> Result<Vec<Message>> {
-        // Real implementation would use Discord gateway WebSocket
+        // Real implementation would use Discord gateway WebSocket; its
+        // MESSAGE_UPDATE, MESSAGE_REACTION_ADD and component
+        // INTERACTION_CREATE events map onto MessageEvent::Edited,
+        // MessageEvent::Reaction and MessageEvent::Button.
         Ok(Vec::new())
     }
 
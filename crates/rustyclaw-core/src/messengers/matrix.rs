@@ -185,6 +185,7 @@ impl Messenger for MatrixMessenger {
                     channel: Some(room.room_id().to_string()),
                     reply_to: None,
                     media: None,
+                    event: Default::default(),
                 };
 
                 pending.lock().await.push(message);
@@ -210,15 +211,16 @@ impl Messenger for MatrixMessenger {
 
     async fn send_message_with_options(&self, opts: SendOptions<'_>) -> Result<String> {
         let room = self.resolve_room(opts.recipient).await?;
+        let body = super::with_choices_text(opts.content, opts.buttons);
 
-        let mut content = RoomMessageEventContent::text_plain(opts.content);
+        let mut content = RoomMessageEventContent::text_plain(&body);
 
         // Handle reply
         if let Some(reply_to) = opts.reply_to {
             if let Ok(_event_id) = matrix_sdk::ruma::OwnedEventId::try_from(reply_to) {
                 // For proper threading, we'd need to fetch the original event
                 // For now, just reference it in the body
-                let reply_body = format!("> Replying to {}\n\n{}", reply_to, opts.content);
+                let reply_body = format!("> Replying to {}\n\n{}", reply_to, body);
                 content = RoomMessageEventContent::text_plain(reply_body);
             }
         }
@@ -22,6 +22,58 @@ pub struct Message {
     pub reply_to: Option<String>,
     #[serde(default)]
     pub media: Option<Vec<MediaAttachment>>,
+    /// New message, edit or reaction.
+    #[serde(default)]
+    pub event: MessageEvent,
+}
+
+/// What a [`Message`] from a poller represents.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MessageEvent {
+    /// A new message.
+    #[default]
+    New,
+    /// The sender edited message `id`; `content` holds the new text.
+    Edited,
+    /// The sender reacted to message `reply_to` with `emoji` (or took the
+    /// reaction back when `removed`).
+    Reaction {
+        emoji: String,
+        #[serde(default)]
+        removed: bool,
+    },
+    /// The sender pressed a button carrying `data` on message `reply_to`.
+    Button { data: String },
+}
+
+/// A button offered with a message (Telegram inline keyboard, Discord
+/// component).  Pressing it comes back as [`MessageEvent::Button`] with
+/// `data`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Choice {
+    pub label: String,
+    pub data: String,
+}
+
+impl Choice {
+    pub fn new(label: impl Into<String>, data: impl Into<String>) -> Self {
+        Self { label: label.into(), data: data.into() }
+    }
+}
+
+/// Message text with the choices spelled out, for messengers without
+/// native buttons; the user answers by typing a number or label.
+pub fn with_choices_text(content: &str, buttons: &[Choice]) -> String {
+    if buttons.is_empty() {
+        return content.to_string();
+    }
+    let mut text = format!("{}\n", content);
+    for (i, choice) in buttons.iter().enumerate() {
+        text.push_str(&format!("\n{}. {}", i + 1, choice.label));
+    }
+    text.push_str("\n\nReply with a number or option.");
+    text
 }
 
 /// Media attachment in a message
@@ -41,6 +93,8 @@ pub struct SendOptions<'a> {
     pub reply_to: Option<&'a str>,
     pub silent: bool,
     pub media: Option<&'a str>,
+    /// Buttons to attach; shown as a numbered list where unsupported.
+    pub buttons: &'a [Choice],
 }
 
 // ── Messenger trait ─────────────────────────────────────────────────────────
@@ -62,8 +116,10 @@ pub trait Messenger: Send + Sync {
 
     /// Send a message with additional options
     async fn send_message_with_options(&self, opts: SendOptions<'_>) -> Result<String> {
-        // Default implementation ignores options
-        self.send_message(opts.recipient, opts.content).await
+        // Default implementation ignores options other than buttons, which
+        // become a numbered list
+        self.send_message(opts.recipient, &with_choices_text(opts.content, opts.buttons))
+            .await
     }
 
     /// Receive pending messages (non-blocking poll)
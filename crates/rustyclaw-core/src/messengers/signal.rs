@@ -193,7 +193,8 @@ impl Messenger for SignalMessenger {
     async fn send_message_with_options(&self, opts: SendOptions<'_>) -> Result<String> {
         // Signal doesn't have native reply support in the same way
         // We could quote the message, but for now just send normally
-        self.send_message(opts.recipient, opts.content).await
+        self.send_message(opts.recipient, &super::with_choices_text(opts.content, opts.buttons))
+            .await
     }
 
     async fn receive_messages(&self) -> Result<Vec<Message>> {
@@ -222,6 +223,7 @@ impl Messenger for SignalMessenger {
                                 channel: None, // Signal doesn't have channels in the same way
                                 reply_to: None,
                                 media: None,
+                                event: Default::default(),
                             };
                             messages.push(message);
                         }
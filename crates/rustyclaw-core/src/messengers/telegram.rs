@@ -1,8 +1,10 @@
 //! Telegram messenger using Bot API.
 
-use super::{Message, Messenger, SendOptions};
+use super::{Choice, Message, MessageEvent, Messenger, SendOptions};
 use anyhow::Result;
 use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// Telegram messenger using bot API
 pub struct TelegramMessenger {
@@ -10,7 +12,7 @@ pub struct TelegramMessenger {
     bot_token: String,
     connected: bool,
     http: reqwest::Client,
-    last_update_id: i64,
+    last_update_id: AtomicI64,
 }
 
 impl TelegramMessenger {
@@ -20,7 +22,7 @@ impl TelegramMessenger {
             bot_token,
             connected: false,
             http: reqwest::Client::new(),
-            last_update_id: 0,
+            last_update_id: AtomicI64::new(0),
         }
     }
 
@@ -29,6 +31,91 @@ impl TelegramMessenger {
     }
 }
 
+/// Telegram limits `callback_data` to 64 bytes.
+const MAX_CALLBACK_DATA: usize = 64;
+
+/// Inline keyboard for `buttons`, up to three per row.
+fn inline_keyboard(buttons: &[Choice]) -> Value {
+    let rows: Vec<Value> = buttons
+        .chunks(3)
+        .map(|row| {
+            Value::Array(
+                row.iter()
+                    .map(|b| {
+                        let mut data = b.data.clone();
+                        while data.len() > MAX_CALLBACK_DATA {
+                            data.pop();
+                        }
+                        serde_json::json!({ "text": b.label, "callback_data": data })
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    serde_json::json!({ "inline_keyboard": rows })
+}
+
+/// Turn one `getUpdates` entry into a message: new messages, edits
+/// (`edited_message`), emoji reactions (`message_reaction`) and inline
+/// keyboard presses (`callback_query`).
+fn parse_update(update: &Value) -> Option<Message> {
+    if let Some(query) = update.get("callback_query") {
+        let data = query["data"].as_str()?.to_string();
+        return Some(Message {
+            id: format!("callback-{}", query["id"].as_str().unwrap_or_default()),
+            sender: query["from"]["id"].to_string(),
+            content: data.clone(),
+            timestamp: query["message"]["date"].as_i64().unwrap_or(0),
+            channel: Some(query["message"]["chat"]["id"].to_string()),
+            reply_to: Some(query["message"]["message_id"].to_string()),
+            media: None,
+            event: MessageEvent::Button { data },
+        });
+    }
+
+    if let Some(reaction) = update.get("message_reaction") {
+        let emoji_of = |key: &str| {
+            reaction[key]
+                .as_array()
+                .and_then(|r| r.iter().find_map(|r| r["emoji"].as_str()))
+                .map(str::to_string)
+        };
+        let (emoji, removed) = match (emoji_of("new_reaction"), emoji_of("old_reaction")) {
+            (Some(emoji), _) => (emoji, false),
+            (None, Some(emoji)) => (emoji, true),
+            (None, None) => return None,
+        };
+        return Some(Message {
+            id: format!("reaction-{}", update["update_id"]),
+            sender: reaction["user"]["id"].to_string(),
+            content: emoji.clone(),
+            timestamp: reaction["date"].as_i64().unwrap_or(0),
+            channel: Some(reaction["chat"]["id"].to_string()),
+            reply_to: Some(reaction["message_id"].to_string()),
+            media: None,
+            event: MessageEvent::Reaction { emoji, removed },
+        });
+    }
+
+    let (msg, event) = match (update.get("message"), update.get("edited_message")) {
+        (Some(msg), _) => (msg, MessageEvent::New),
+        (None, Some(msg)) => (msg, MessageEvent::Edited),
+        (None, None) => return None,
+    };
+    Some(Message {
+        id: msg["message_id"].to_string(),
+        sender: msg["from"]["id"].to_string(),
+        content: msg["text"].as_str().unwrap_or("").to_string(),
+        timestamp: msg["edit_date"].as_i64().or(msg["date"].as_i64()).unwrap_or(0),
+        channel: Some(msg["chat"]["id"].to_string()),
+        reply_to: msg["reply_to_message"]["message_id"]
+            .as_i64()
+            .map(|id| id.to_string()),
+        media: None,
+        event,
+    })
+}
+
 #[async_trait]
 impl Messenger for TelegramMessenger {
     fn name(&self) -> &str {
@@ -85,6 +172,10 @@ impl Messenger for TelegramMessenger {
             payload["disable_notification"] = serde_json::json!(true);
         }
 
+        if !opts.buttons.is_empty() {
+            payload["reply_markup"] = inline_keyboard(opts.buttons);
+        }
+
         if let Some(reply_to) = opts.reply_to {
             if let Ok(msg_id) = reply_to.parse::<i64>() {
                 payload["reply_to_message_id"] = serde_json::json!(msg_id);
@@ -112,9 +203,9 @@ impl Messenger for TelegramMessenger {
             .http
             .post(self.api_url("getUpdates"))
             .json(&serde_json::json!({
-                "offset": self.last_update_id + 1,
+                "offset": self.last_update_id.load(Ordering::Relaxed) + 1,
                 "timeout": 0,
-                "allowed_updates": ["message"]
+                "allowed_updates": ["message", "edited_message", "message_reaction", "callback_query"]
             }))
             .send()
             .await?;
@@ -123,39 +214,31 @@ impl Messenger for TelegramMessenger {
             return Ok(Vec::new());
         }
 
-        let data: serde_json::Value = resp.json().await?;
+        let data: Value = resp.json().await?;
         if data["ok"].as_bool() != Some(true) {
             return Ok(Vec::new());
         }
 
-        let updates = data["result"].as_array();
-        let Some(updates) = updates else {
+        let Some(updates) = data["result"].as_array() else {
             return Ok(Vec::new());
         };
 
         let mut messages = Vec::new();
         for update in updates {
-            let _update_id = update["update_id"].as_i64().unwrap_or(0);
-            
-            if let Some(msg) = update.get("message") {
-                let id = msg["message_id"].to_string();
-                let sender = msg["from"]["id"].to_string();
-                let content = msg["text"].as_str().unwrap_or("").to_string();
-                let timestamp = msg["date"].as_i64().unwrap_or(0);
-                let channel = msg["chat"]["id"].to_string();
-
-                messages.push(Message {
-                    id,
-                    sender,
-                    content,
-                    timestamp,
-                    channel: Some(channel),
-                    reply_to: msg["reply_to_message"]["message_id"]
-                        .as_i64()
-                        .map(|id| id.to_string()),
-                    media: None,
-                });
+            // Acknowledge the update so the next poll doesn't return it again.
+            if let Some(update_id) = update["update_id"].as_i64() {
+                self.last_update_id.fetch_max(update_id, Ordering::Relaxed);
+            }
+            // Stop the client's loading spinner on the pressed button.
+            if let Some(query_id) = update["callback_query"]["id"].as_str() {
+                let _ = self
+                    .http
+                    .post(self.api_url("answerCallbackQuery"))
+                    .json(&serde_json::json!({ "callback_query_id": query_id }))
+                    .send()
+                    .await;
             }
+            messages.extend(parse_update(update));
         }
 
         Ok(messages)
@@ -170,3 +253,68 @@ impl Messenger for TelegramMessenger {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_edit_and_reaction_updates() {
+        let edit = json!({
+            "update_id": 10,
+            "edited_message": {
+                "message_id": 5, "from": {"id": 42}, "chat": {"id": 42},
+                "date": 1000, "edit_date": 1060, "text": "fixed typo"
+            }
+        });
+        let msg = parse_update(&edit).unwrap();
+        assert_eq!(msg.event, MessageEvent::Edited);
+        assert_eq!(msg.id, "5");
+        assert_eq!(msg.content, "fixed typo");
+        assert_eq!(msg.timestamp, 1060);
+
+        let reaction = json!({
+            "update_id": 11,
+            "message_reaction": {
+                "chat": {"id": 42}, "message_id": 7, "user": {"id": 42}, "date": 1100,
+                "old_reaction": [],
+                "new_reaction": [{"type": "emoji", "emoji": "👍"}]
+            }
+        });
+        let msg = parse_update(&reaction).unwrap();
+        assert_eq!(msg.reply_to.as_deref(), Some("7"));
+        assert_eq!(
+            msg.event,
+            MessageEvent::Reaction { emoji: "👍".into(), removed: false }
+        );
+
+        assert!(parse_update(&json!({"update_id": 12, "poll": {}})).is_none());
+    }
+
+    #[test]
+    fn test_inline_keyboard_and_callback() {
+        let buttons = [
+            Choice::new("Approve", "approve"),
+            Choice::new("Reject", "reject"),
+            Choice::new("A", "0"),
+            Choice::new("B", "1"),
+        ];
+        let keyboard = inline_keyboard(&buttons);
+        let rows = keyboard["inline_keyboard"].as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0], json!({"text": "Approve", "callback_data": "approve"}));
+
+        let press = json!({
+            "update_id": 13,
+            "callback_query": {
+                "id": "abc", "from": {"id": 42}, "data": "approve",
+                "message": {"message_id": 9, "date": 1200, "chat": {"id": -100}}
+            }
+        });
+        let msg = parse_update(&press).unwrap();
+        assert_eq!(msg.event, MessageEvent::Button { data: "approve".into() });
+        assert_eq!(msg.reply_to.as_deref(), Some("9"));
+        assert_eq!(msg.channel.as_deref(), Some("-100"));
+    }
+}
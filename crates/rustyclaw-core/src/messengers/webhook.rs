@@ -1,6 +1,6 @@
 //! Webhook messenger - POST messages to a URL.
 
-use super::{Message, Messenger, SendOptions};
+use super::{Choice, Message, Messenger, SendOptions};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::Serialize;
@@ -30,6 +30,12 @@ struct WebhookPayload<'a> {
     recipient: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     reply_to: Option<&'a str>,
+    #[serde(skip_serializing_if = "no_buttons")]
+    buttons: &'a [Choice],
+}
+
+fn no_buttons(buttons: &&[Choice]) -> bool {
+    buttons.is_empty()
 }
 
 #[async_trait]
@@ -52,6 +58,7 @@ impl Messenger for WebhookMessenger {
             content,
             recipient,
             reply_to: None,
+            buttons: &[],
         };
 
         let resp = self
@@ -73,6 +80,7 @@ impl Messenger for WebhookMessenger {
             content: opts.content,
             recipient: opts.recipient,
             reply_to: opts.reply_to,
+            buttons: opts.buttons,
         };
 
         let resp = self
@@ -0,0 +1,473 @@
+//! Model capabilities: context window, output limit, tool use and vision.
+//!
+//! Used to keep requests inside what a model can take — tools aren't
+//! offered to models that can't call them, image attachments aren't sent
+//! to text-only models, `max_tokens` stays under the output limit, and
+//! compaction knows the real context window.
+//!
+//! Capabilities come from, in order of precedence:
+//!
+//! 1. `[model_capabilities]` in config.toml, keyed by model name or glob,
+//! 2. what the provider's models API reports (OpenRouter, GitHub Copilot,
+//!    Google, Anthropic and Ollama describe their models; others don't),
+//!    fetched in the background the first time a provider is used,
+//! 3. a built-in table of well-known model families.
+//!
+//! ```toml
+//! [model_capabilities."my-finetune*"]
+//! context_window = 32768
+//! tools = false
+//! ```
+//!
+//! Models nobody knows about are assumed capable; the provider has the
+//! final word.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// What a model can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCaps {
+    /// Input context window, in tokens.
+    pub context_window: usize,
+    /// Most tokens the model will generate in one response.
+    pub max_output: usize,
+    /// Accepts tool / function definitions.
+    pub tools: bool,
+    /// Accepts image input.
+    pub vision: bool,
+}
+
+impl ModelCaps {
+    const fn new(context_window: usize, max_output: usize, tools: bool, vision: bool) -> Self {
+        Self { context_window, max_output, tools, vision }
+    }
+
+    /// Short summary, e.g. "200k context · 64k output · tools · vision".
+    pub fn describe(&self) -> String {
+        let tokens = |n: usize| {
+            if n >= 1_000_000 && n % 1_000_000 == 0 {
+                format!("{}M", n / 1_000_000)
+            } else if n >= 1_000 {
+                format!("{}k", n / 1_000)
+            } else {
+                n.to_string()
+            }
+        };
+        let mut parts = vec![
+            format!("{} context", tokens(self.context_window)),
+            format!("{} output", tokens(self.max_output)),
+        ];
+        parts.push(if self.tools { "tools" } else { "no tools" }.to_string());
+        parts.push(if self.vision { "vision" } else { "text only" }.to_string());
+        parts.join(" · ")
+    }
+}
+
+/// Capability overrides for one model pattern in `[model_capabilities]`.
+/// Unset fields keep the detected value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapsOverride {
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    #[serde(default)]
+    pub max_output: Option<usize>,
+    #[serde(default)]
+    pub tools: Option<bool>,
+    #[serde(default)]
+    pub vision: Option<bool>,
+}
+
+impl CapsOverride {
+    fn apply(&self, mut caps: ModelCaps) -> ModelCaps {
+        caps.context_window = self.context_window.unwrap_or(caps.context_window);
+        caps.max_output = self.max_output.unwrap_or(caps.max_output);
+        caps.tools = self.tools.unwrap_or(caps.tools);
+        caps.vision = self.vision.unwrap_or(caps.vision);
+        caps
+    }
+}
+
+// ── Built-in table ──────────────────────────────────────────────────────────
+
+/// Well-known model families, most specific first.  Patterns are matched
+/// against the lowercased model id, so `anthropic/claude-…` (OpenRouter)
+/// matches the same rows as `claude-…`.
+const BUILTIN: &[(&str, ModelCaps)] = &[
+    ("*claude-opus-4*", ModelCaps::new(200_000, 32_000, true, true)),
+    ("*claude-sonnet-4*", ModelCaps::new(200_000, 64_000, true, true)),
+    ("*claude-3-7-sonnet*", ModelCaps::new(200_000, 64_000, true, true)),
+    ("*claude-3-haiku*", ModelCaps::new(200_000, 4_096, true, true)),
+    ("*claude*", ModelCaps::new(200_000, 8_192, true, true)),
+    ("*gpt-4.1*", ModelCaps::new(1_000_000, 32_768, true, true)),
+    ("*gpt-5*", ModelCaps::new(400_000, 128_000, true, true)),
+    ("*gpt-4o*", ModelCaps::new(128_000, 16_384, true, true)),
+    ("*gpt-4-turbo*", ModelCaps::new(128_000, 4_096, true, true)),
+    ("*gpt-3.5*", ModelCaps::new(16_385, 4_096, true, false)),
+    ("*o1-mini*", ModelCaps::new(128_000, 65_536, false, false)),
+    ("*o3-mini*", ModelCaps::new(200_000, 100_000, true, false)),
+    ("*o1*", ModelCaps::new(200_000, 100_000, true, true)),
+    ("o3*", ModelCaps::new(200_000, 100_000, true, true)),
+    ("o4*", ModelCaps::new(200_000, 100_000, true, true)),
+    ("*/o3*", ModelCaps::new(200_000, 100_000, true, true)),
+    ("*/o4*", ModelCaps::new(200_000, 100_000, true, true)),
+    ("*gemini-2.5*", ModelCaps::new(1_000_000, 65_536, true, true)),
+    ("*gemini*", ModelCaps::new(1_000_000, 8_192, true, true)),
+    ("*grok*vision*", ModelCaps::new(32_768, 8_192, true, true)),
+    ("*grok-4*", ModelCaps::new(256_000, 16_384, true, true)),
+    ("*grok*", ModelCaps::new(131_072, 16_384, true, false)),
+    ("*llava*", ModelCaps::new(4_096, 4_096, false, true)),
+    ("*llama*vision*", ModelCaps::new(128_000, 4_096, false, true)),
+    ("*-vl*", ModelCaps::new(32_768, 8_192, false, true)),
+    ("*pixtral*", ModelCaps::new(128_000, 8_192, true, true)),
+    ("*deepseek-r1*", ModelCaps::new(128_000, 8_192, false, false)),
+    ("*deepseek-reasoner*", ModelCaps::new(128_000, 8_192, false, false)),
+    ("*deepseek*", ModelCaps::new(128_000, 8_192, true, false)),
+    ("*llama*", ModelCaps::new(128_000, 4_096, true, false)),
+    ("*mistral*", ModelCaps::new(128_000, 8_192, true, false)),
+];
+
+/// Unknown models: a safe context window, everything else allowed.
+const FALLBACK: ModelCaps = ModelCaps::new(128_000, 4_096, true, true);
+
+fn builtin(model: &str) -> ModelCaps {
+    let m = model.to_lowercase();
+    BUILTIN
+        .iter()
+        .find(|(pattern, _)| crate::wildcard::glob_match(pattern, &m))
+        .map(|(_, caps)| *caps)
+        .unwrap_or(FALLBACK)
+}
+
+// ── Registry ────────────────────────────────────────────────────────────────
+
+/// Capabilities reported by provider APIs, by model id.
+static REPORTED: RwLock<Option<HashMap<String, ModelCaps>>> = RwLock::new(None);
+
+/// `[model_capabilities]` from the config, replaced on every (re)load.
+static OVERRIDES: RwLock<Vec<(String, CapsOverride)>> = RwLock::new(Vec::new());
+
+/// Providers (and, for Ollama, models) already fetched or being fetched.
+static REFRESHED: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Register the `[model_capabilities]` overrides from the config.
+pub fn set_overrides(overrides: HashMap<String, CapsOverride>) {
+    let mut overrides: Vec<(String, CapsOverride)> = overrides.into_iter().collect();
+    // Exact names before globs, then longer (more specific) patterns first.
+    overrides.sort_by_key(|(pattern, _)| (pattern.contains('*'), std::cmp::Reverse(pattern.len())));
+    if let Ok(mut guard) = OVERRIDES.write() {
+        *guard = overrides;
+    }
+}
+
+/// Capabilities of `model`.
+pub fn lookup(model: &str) -> ModelCaps {
+    let reported = REPORTED
+        .read()
+        .ok()
+        .and_then(|r| r.as_ref().and_then(|r| r.get(model).or_else(|| r.get(&model.to_lowercase())).copied()));
+    let caps = reported.unwrap_or_else(|| builtin(model));
+    let overrides = OVERRIDES.read().map(|o| o.clone()).unwrap_or_default();
+    match overrides.iter().find(|(pattern, _)| crate::wildcard::glob_match(pattern, model)) {
+        Some((_, o)) => o.apply(caps),
+        None => caps,
+    }
+}
+
+fn record(reported: Vec<(String, ModelCaps)>) {
+    if let Ok(mut guard) = REPORTED.write() {
+        let map = guard.get_or_insert_with(HashMap::new);
+        for (id, caps) in reported {
+            map.insert(id, caps);
+        }
+    }
+}
+
+// ── Gating ──────────────────────────────────────────────────────────────────
+
+/// The message shown when `model` can't call tools.
+pub fn no_tools_notice(model: &str) -> String {
+    format!("{} doesn't support tool use, so tools are off for this turn. Switch models with /model to use tools.", model)
+}
+
+/// The message shown when images are sent to a text-only model.
+pub fn no_vision_notice(model: &str, images: usize) -> String {
+    format!(
+        "{} can't read images, so the {} attached image{} {} not sent. Switch to a vision model with /model to include {}.",
+        model,
+        images,
+        if images == 1 { "" } else { "s" },
+        if images == 1 { "was" } else { "were" },
+        if images == 1 { "it" } else { "them" },
+    )
+}
+
+/// Number of images in a chat message: image media attachments plus image
+/// blocks in JSON content (the Anthropic / OpenAI content-array forms).
+pub fn image_count(message: &crate::gateway::ChatMessage) -> usize {
+    let media = message
+        .media
+        .iter()
+        .flatten()
+        .filter(|m| m.mime_type.starts_with("image/"))
+        .count();
+    let blocks = if message.content.trim_start().starts_with('[') {
+        serde_json::from_str::<Value>(&message.content)
+            .ok()
+            .and_then(|v| v.as_array().cloned())
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|b| matches!(b.get("type").and_then(|t| t.as_str()), Some("image" | "image_url")))
+                    .count()
+            })
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    media + blocks
+}
+
+// ── Refreshing from provider APIs ───────────────────────────────────────────
+
+/// Fetch capabilities for `provider` in the background, once per provider
+/// (once per model for Ollama, which describes models one at a time).
+/// Lookups use the built-in table until the fetch lands.
+pub fn refresh_in_background(provider: &str, base_url: &str, api_key: Option<&str>, model: &str) {
+    let key = if provider == "ollama" { format!("ollama:{}", model) } else { provider.to_string() };
+    {
+        let Ok(mut guard) = REFRESHED.lock() else { return };
+        if !guard.get_or_insert_with(HashSet::new).insert(key) {
+            return;
+        }
+    }
+    let Ok(handle) = tokio::runtime::Handle::try_current() else { return };
+    let (provider, base_url, api_key, model) =
+        (provider.to_string(), base_url.to_string(), api_key.map(str::to_string), model.to_string());
+    handle.spawn(async move {
+        match refresh(&provider, &base_url, api_key.as_deref(), &model).await {
+            Ok(count) => debug!(provider, count, "Refreshed model capabilities"),
+            Err(e) => warn!(provider, error = %e, "Could not refresh model capabilities; using built-in table"),
+        }
+    });
+}
+
+/// Fetch what `provider` reports about its models and record it.  Returns
+/// the number of models described.
+pub async fn refresh(provider: &str, base_url: &str, api_key: Option<&str>, model: &str) -> Result<usize, String> {
+    let base = base_url.trim_end_matches('/');
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let reported = match provider {
+        "ollama" => {
+            let root = base.trim_end_matches("/v1");
+            let body = send(client.post(format!("{}/api/show", root)).json(&serde_json::json!({ "model": model }))).await?;
+            ollama_caps(&body, model).map(|caps| vec![(model.to_string(), caps)]).unwrap_or_default()
+        }
+        "openrouter" | "github-copilot" => {
+            let mut req = client.get(format!("{}/models", base));
+            if let Some(key) = api_key {
+                req = req.bearer_auth(key);
+            }
+            parse_models(&send(req).await?)
+        }
+        "google" => {
+            let Some(key) = api_key else { return Ok(0) };
+            parse_models(&send(client.get(format!("{}/models?pageSize=1000", base)).header("x-goog-api-key", key)).await?)
+        }
+        "anthropic" => {
+            let Some(key) = api_key else { return Ok(0) };
+            let req = client
+                .get(format!("{}/v1/models?limit=1000", base))
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01");
+            parse_models(&send(req).await?)
+        }
+        // OpenAI-style /models lists carry no capability data.
+        _ => Vec::new(),
+    };
+    let count = reported.len();
+    record(reported);
+    Ok(count)
+}
+
+async fn send(req: reqwest::RequestBuilder) -> Result<Value, String> {
+    let resp = req.send().await.map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status()));
+    }
+    resp.json().await.map_err(|e| e.to_string())
+}
+
+/// Models described in a `/models` response (`data` or `models` array).
+/// Entries that report nothing beyond an id are skipped, so the built-in
+/// table still applies to them.
+fn parse_models(body: &Value) -> Vec<(String, ModelCaps)> {
+    body.get("data")
+        .or_else(|| body.get("models"))
+        .and_then(|d| d.as_array())
+        .map(|entries| entries.iter().filter_map(entry_caps).collect())
+        .unwrap_or_default()
+}
+
+/// Capabilities from one models-API entry.  Understands the OpenRouter,
+/// GitHub Copilot, Google and Anthropic field names.
+fn entry_caps(entry: &Value) -> Option<(String, ModelCaps)> {
+    let id = entry
+        .get("id")
+        .or_else(|| entry.get("name"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.strip_prefix("models/").unwrap_or(s).to_string())?;
+    let number = |pointers: &[&str]| {
+        pointers
+            .iter()
+            .find_map(|p| entry.pointer(p).and_then(|v| v.as_u64()))
+            .map(|n| n as usize)
+    };
+    let context = number(&[
+        "/context_length",
+        "/inputTokenLimit",
+        "/max_input_tokens",
+        "/capabilities/limits/max_context_window_tokens",
+    ]);
+    let output = number(&[
+        "/top_provider/max_completion_tokens",
+        "/outputTokenLimit",
+        "/max_output_tokens",
+        "/max_tokens",
+        "/capabilities/limits/max_output_tokens",
+    ]);
+    let contains = |pointer: &str, value: &str| {
+        entry
+            .pointer(pointer)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().any(|v| v.as_str() == Some(value)))
+    };
+    let tools = contains("/supported_parameters", "tools")
+        .or_else(|| entry.pointer("/capabilities/supports/tool_calls").and_then(|v| v.as_bool()));
+    let vision = contains("/architecture/input_modalities", "image")
+        .or_else(|| entry.pointer("/capabilities/supports/vision").and_then(|v| v.as_bool()));
+
+    if context.is_none() && output.is_none() && tools.is_none() && vision.is_none() {
+        return None;
+    }
+    let base = builtin(&id);
+    Some((
+        id,
+        ModelCaps {
+            context_window: context.unwrap_or(base.context_window),
+            max_output: output.unwrap_or(base.max_output),
+            tools: tools.unwrap_or(base.tools),
+            vision: vision.unwrap_or(base.vision),
+        },
+    ))
+}
+
+/// Capabilities from an Ollama `/api/show` response.
+fn ollama_caps(body: &Value, model: &str) -> Option<ModelCaps> {
+    let base = builtin(model);
+    let capabilities: Vec<&str> = body
+        .get("capabilities")?
+        .as_array()?
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect();
+    let context = body
+        .get("model_info")
+        .and_then(|i| i.as_object())
+        .and_then(|info| info.iter().find(|(k, _)| k.ends_with(".context_length")))
+        .and_then(|(_, v)| v.as_u64())
+        .map(|n| n as usize);
+    Some(ModelCaps {
+        context_window: context.unwrap_or(base.context_window),
+        max_output: base.max_output,
+        tools: capabilities.contains(&"tools"),
+        vision: capabilities.contains(&"vision"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_builtin_table() {
+        assert_eq!(builtin("claude-sonnet-4-20250514").max_output, 64_000);
+        assert_eq!(builtin("anthropic/claude-3-haiku").max_output, 4_096);
+        assert!(!builtin("o1-mini").tools);
+        assert!(builtin("o3").vision);
+        assert!(!builtin("llama3.1:8b").vision);
+        assert!(builtin("llama3.2-vision").vision);
+        assert!(!builtin("deepseek-r1:14b").tools);
+        assert_eq!(builtin("gemini-2.5-pro").context_window, 1_000_000);
+        assert_eq!(builtin("some-new-model"), FALLBACK);
+    }
+
+    #[test]
+    fn test_parse_models() {
+        let body = json!({ "data": [
+            {
+                "id": "meta-llama/llama-3.2-11b-vision-instruct",
+                "context_length": 131072,
+                "architecture": { "input_modalities": ["text", "image"] },
+                "top_provider": { "max_completion_tokens": 16384 },
+                "supported_parameters": ["max_tokens", "temperature"]
+            },
+            {
+                "id": "gpt-4o",
+                "capabilities": { "limits": { "max_context_window_tokens": 64000, "max_output_tokens": 4096 },
+                                  "supports": { "tool_calls": true, "vision": true } }
+            },
+            { "id": "plain-model" }
+        ]});
+        let models: HashMap<String, ModelCaps> = parse_models(&body).into_iter().collect();
+        assert_eq!(models.len(), 2);
+        let llama = models["meta-llama/llama-3.2-11b-vision-instruct"];
+        assert_eq!(llama, ModelCaps::new(131_072, 16_384, false, true));
+        assert_eq!(models["gpt-4o"], ModelCaps::new(64_000, 4_096, true, true));
+
+        let google = json!({ "models": [{ "name": "models/gemini-2.0-flash", "inputTokenLimit": 1048576, "outputTokenLimit": 8192 }] });
+        assert_eq!(parse_models(&google)[0].0, "gemini-2.0-flash");
+        assert_eq!(parse_models(&google)[0].1.context_window, 1_048_576);
+    }
+
+    #[test]
+    fn test_ollama_caps() {
+        let body = json!({
+            "capabilities": ["completion", "vision"],
+            "model_info": { "gemma3.context_length": 131072 }
+        });
+        let caps = ollama_caps(&body, "gemma3:12b").unwrap();
+        assert!(caps.vision);
+        assert!(!caps.tools);
+        assert_eq!(caps.context_window, 131_072);
+    }
+
+    #[test]
+    fn test_overrides_and_describe() {
+        let caps = CapsOverride { tools: Some(false), context_window: Some(32_768), ..Default::default() }
+            .apply(builtin("mistral-large"));
+        assert_eq!(caps, ModelCaps::new(32_768, 8_192, false, false));
+        assert_eq!(caps.describe(), "32k context · 8k output · no tools · text only");
+        assert_eq!(builtin("gpt-4.1").describe(), "1M context · 32k output · tools · vision");
+    }
+
+    #[test]
+    fn test_image_count() {
+        use crate::gateway::{ChatMessage, MediaRef};
+        let text = ChatMessage::text("user", "hello");
+        assert_eq!(image_count(&text), 0);
+        let with_media = ChatMessage::user_with_media("look", vec![MediaRef::new("image/png".into()), MediaRef::new("application/pdf".into())]);
+        assert_eq!(image_count(&with_media), 1);
+        let blocks = ChatMessage::text("user", r#"[{"type":"text","text":"hi"},{"type":"image","source":{}}]"#);
+        assert_eq!(image_count(&blocks), 1);
+    }
+}
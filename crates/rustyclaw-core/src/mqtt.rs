@@ -0,0 +1,490 @@
+//! MQTT client: IoT events in, commands out.
+//!
+//! The gateway keeps one connection to the broker in the `[mqtt]` config
+//! section, subscribed to `subscriptions` plus every rule's topic.  When a
+//! message arrives:
+//!
+//! - script hooks `fn on_mqtt(event)` run (see [`crate::scripting`]),
+//! - matching rules queue an agent task, so the run and its tool calls end
+//!   up in that task's session history.
+//!
+//! The `mqtt` tool publishes through the same connection and shows recent
+//! messages.
+//!
+//! ```toml
+//! [mqtt]
+//! enabled = true
+//! host = "homeassistant.local"
+//! username = "rustyclaw"
+//! password = "..."
+//! subscriptions = ["zigbee2mqtt/+/battery"]
+//!
+//! [[mqtt.rules]]
+//! topic = "zigbee2mqtt/front_door"
+//! contains = "\"contact\":false"
+//! prompt = "The front door opened ({payload}). If nobody is home, message me."
+//! ```
+//!
+//! Retained messages delivered on (re)connect are recorded but don't fire
+//! rules, so a restart doesn't replay old state as new events.
+//!
+//! The broker connection needs the `mqtt` feature; without it the config
+//! still parses and the loop only warns when MQTT is enabled.
+
+#[cfg(feature = "mqtt")]
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS, Transport};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+#[cfg(feature = "mqtt")]
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+#[cfg(feature = "mqtt")]
+use tracing::info;
+use tracing::{debug, warn};
+
+/// Messages kept for the `recent` action.
+const RECENT_LIMIT: usize = 200;
+
+/// Payload characters included in prompts.
+const PROMPT_PAYLOAD_CHARS: usize = 4000;
+
+/// Queue an agent task when a message matches.
+///
+/// `prompt` may use `{topic}`, `{payload}` and `{time}` placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MqttRule {
+    /// Topic filter; `+` and `#` wildcards are allowed.
+    pub topic: String,
+    /// Only when the payload contains this text.
+    #[serde(default)]
+    pub contains: Option<String>,
+    pub prompt: String,
+}
+
+/// The `[mqtt]` config section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_host")]
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Connect over TLS (usually port 8883).
+    #[serde(default)]
+    pub tls: bool,
+    /// Topic filters to subscribe to; rule topics are added automatically.
+    #[serde(default)]
+    pub subscriptions: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<MqttRule>,
+}
+
+fn default_host() -> String {
+    "localhost".to_string()
+}
+
+fn default_port() -> u16 {
+    1883
+}
+
+fn default_client_id() -> String {
+    "rustyclaw".to_string()
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_host(),
+            port: default_port(),
+            client_id: default_client_id(),
+            username: None,
+            password: None,
+            tls: false,
+            subscriptions: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Every topic filter to subscribe to, without duplicates.
+    pub fn topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> = Vec::new();
+        for topic in self.subscriptions.iter().chain(self.rules.iter().map(|r| &r.topic)) {
+            if !topics.contains(topic) {
+                topics.push(topic.clone());
+            }
+        }
+        topics
+    }
+}
+
+/// A received message.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: String,
+    pub retained: bool,
+    pub at: String,
+}
+
+/// MQTT topic filter matching: `+` is one level, a trailing `#` is any
+/// number of levels (including none).
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut levels = topic.split('/');
+    for part in filter.split('/') {
+        match (part, levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (p, Some(level)) if p == level => {}
+            _ => return false,
+        }
+    }
+    levels.next().is_none()
+}
+
+/// Rules matching `message`.  Retained messages never match.
+pub fn matching_rules<'a>(rules: &'a [MqttRule], message: &MqttMessage) -> Vec<&'a MqttRule> {
+    if message.retained {
+        return Vec::new();
+    }
+    rules
+        .iter()
+        .filter(|r| topic_matches(&r.topic, &message.topic))
+        .filter(|r| r.contains.as_deref().is_none_or(|c| message.payload.contains(c)))
+        .collect()
+}
+
+/// Fill a rule prompt's placeholders.
+pub fn render(template: &str, message: &MqttMessage) -> String {
+    let payload: String = message.payload.chars().take(PROMPT_PAYLOAD_CHARS).collect();
+    template
+        .replace("{topic}", &message.topic)
+        .replace("{payload}", &payload)
+        .replace("{time}", &message.at)
+}
+
+#[cfg(feature = "mqtt")]
+fn parse_qos(qos: u64) -> Result<QoS, String> {
+    match qos {
+        0 => Ok(QoS::AtMostOnce),
+        1 => Ok(QoS::AtLeastOnce),
+        2 => Ok(QoS::ExactlyOnce),
+        other => Err(format!("Invalid QoS {}; use 0, 1 or 2", other)),
+    }
+}
+
+// ── Gateway state ───────────────────────────────────────────────────────────
+
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+struct State {
+    config: MqttConfig,
+    workspace_dir: PathBuf,
+    /// Bumped on every `set_config` so the loop knows to reconnect.
+    generation: u64,
+    connected: bool,
+    #[cfg(feature = "mqtt")]
+    client: Option<AsyncClient>,
+    recent: VecDeque<MqttMessage>,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Register the MQTT config.  Called at startup and on reload; a changed
+/// config makes the running loop reconnect.
+pub fn set_config(config: MqttConfig, workspace_dir: &Path) {
+    debug!(host = %config.host, topics = config.topics().len(), "Setting MQTT config");
+    let Ok(mut guard) = STATE.lock() else { return };
+    if let Some(state) = guard.as_mut() {
+        if state.config != config {
+            state.config = config;
+            state.generation += 1;
+        }
+        state.workspace_dir = workspace_dir.to_path_buf();
+        return;
+    }
+    *guard = Some(State {
+        config,
+        workspace_dir: workspace_dir.to_path_buf(),
+        generation: 0,
+        connected: false,
+        #[cfg(feature = "mqtt")]
+        client: None,
+        recent: VecDeque::new(),
+    });
+}
+
+/// Connection state and topics, for the `mqtt` tool.
+pub fn status() -> Value {
+    let guard = STATE.lock().ok();
+    let Some(state) = guard.as_ref().and_then(|g| g.as_ref()) else {
+        return json!({ "enabled": false, "connected": false });
+    };
+    json!({
+        "enabled": state.config.enabled,
+        "connected": state.connected,
+        "broker": format!("{}:{}", state.config.host, state.config.port),
+        "topics": state.config.topics(),
+        "rules": state.config.rules.len(),
+        "received": state.recent.len(),
+    })
+}
+
+/// Recent messages on topics matching `filter`, newest last.
+pub fn recent(filter: Option<&str>, limit: usize) -> Vec<MqttMessage> {
+    let guard = STATE.lock().ok();
+    let Some(state) = guard.as_ref().and_then(|g| g.as_ref()) else {
+        return Vec::new();
+    };
+    let matched: Vec<&MqttMessage> = state
+        .recent
+        .iter()
+        .filter(|m| filter.is_none_or(|f| topic_matches(f, &m.topic)))
+        .collect();
+    matched[matched.len().saturating_sub(limit)..].iter().map(|m| (*m).clone()).collect()
+}
+
+/// Publish through the gateway's connection.
+pub fn publish(topic: &str, payload: &str, qos: u64, retain: bool) -> Result<(), String> {
+    if topic.is_empty() || topic.contains(['+', '#']) {
+        return Err(format!("Invalid publish topic: '{}'", topic));
+    }
+    #[cfg(not(feature = "mqtt"))]
+    {
+        let _ = (payload, qos, retain);
+        Err(NOT_COMPILED.to_string())
+    }
+    #[cfg(feature = "mqtt")]
+    {
+        publish_with_client(topic, payload, qos, retain)
+    }
+}
+
+#[cfg(not(feature = "mqtt"))]
+const NOT_COMPILED: &str = "MQTT support isn't compiled in. Rebuild with `--features mqtt`.";
+
+#[cfg(feature = "mqtt")]
+fn publish_with_client(topic: &str, payload: &str, qos: u64, retain: bool) -> Result<(), String> {
+    let qos = parse_qos(qos)?;
+    let guard = STATE.lock().map_err(|_| "MQTT state poisoned".to_string())?;
+    let client = guard
+        .as_ref()
+        .filter(|s| s.config.enabled)
+        .and_then(|s| s.client.as_ref())
+        .ok_or("MQTT is not enabled; configure the [mqtt] section and run the gateway")?;
+    client
+        .try_publish(topic, qos, retain, payload.as_bytes().to_vec())
+        .map_err(|e| format!("Publish failed: {}", e))
+}
+
+/// Record a message and run hooks and rules for it.
+#[cfg(feature = "mqtt")]
+fn handle(message: MqttMessage) {
+    let (rules, workspace_dir) = {
+        let Ok(mut guard) = STATE.lock() else { return };
+        let Some(state) = guard.as_mut() else { return };
+        state.recent.push_back(message.clone());
+        while state.recent.len() > RECENT_LIMIT {
+            state.recent.pop_front();
+        }
+        (state.config.rules.clone(), state.workspace_dir.clone())
+    };
+
+    let payload = serde_json::to_value(&message).unwrap_or(Value::Null);
+    crate::scripting::spawn_hooks("mqtt", payload, workspace_dir.clone());
+
+    for rule in matching_rules(&rules, &message) {
+        use crate::task_queue::{queue_dir, QueuedTask, TaskPriority, TaskQueue};
+        let queued = TaskQueue::new(&queue_dir(&workspace_dir)).and_then(|mut queue| {
+            let mut task = QueuedTask::new(&render(&rule.prompt, &message), TaskPriority::High, 1);
+            task.source = Some(format!("mqtt:{}", message.topic));
+            queue.enqueue(task)
+        });
+        match queued {
+            Ok(id) => debug!(task_id = %id, topic = %message.topic, "Queued MQTT task"),
+            Err(e) => warn!(error = %e, "Failed to queue MQTT task"),
+        }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+fn options(config: &MqttConfig) -> MqttOptions {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.as_deref().unwrap_or(""));
+    }
+    if config.tls {
+        options.set_transport(Transport::tls_with_default_config());
+    }
+    options
+}
+
+#[cfg(feature = "mqtt")]
+fn set_connection(client: Option<AsyncClient>, connected: bool) {
+    if let Ok(mut guard) = STATE.lock() {
+        if let Some(state) = guard.as_mut() {
+            state.client = client;
+            state.connected = connected;
+        }
+    }
+}
+
+/// Without the `mqtt` feature there is no connection to keep; say so once
+/// if the config asks for one.
+#[cfg(not(feature = "mqtt"))]
+pub async fn run_mqtt_loop(cancel: CancellationToken) {
+    let enabled = STATE.lock().ok().and_then(|g| g.as_ref().map(|s| s.config.enabled));
+    if enabled == Some(true) {
+        warn!("{}", NOT_COMPILED);
+    }
+    cancel.cancelled().await;
+}
+
+/// Keep the broker connection up until cancelled.  Idles while MQTT is
+/// disabled and reconnects whenever the config changes.
+#[cfg(feature = "mqtt")]
+pub async fn run_mqtt_loop(cancel: CancellationToken) {
+    let current = || {
+        STATE
+            .lock()
+            .ok()
+            .and_then(|g| g.as_ref().map(|s| (s.config.clone(), s.generation)))
+    };
+
+    loop {
+        let Some((config, generation)) = current().filter(|(c, _)| c.enabled) else {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(Duration::from_secs(30)) => {}
+            }
+            continue;
+        };
+
+        info!(host = %config.host, port = config.port, "Connecting to MQTT broker");
+        let (client, mut events) = AsyncClient::new(options(&config), 64);
+        set_connection(Some(client.clone()), false);
+        let topics = config.topics();
+
+        loop {
+            if current().is_none_or(|(_, g)| g != generation) {
+                info!("MQTT config changed; reconnecting");
+                break;
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    let _ = client.try_disconnect();
+                    set_connection(None, false);
+                    return;
+                }
+                // Wake up now and then to notice config changes.
+                _ = tokio::time::sleep(Duration::from_secs(10)) => {}
+                event = events.poll() => match event {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!(topics = topics.len(), "MQTT connected");
+                        set_connection(Some(client.clone()), true);
+                        // Subscriptions don't survive a clean session, so
+                        // renew them on every (re)connect.
+                        for topic in &topics {
+                            if let Err(e) = client.try_subscribe(topic, QoS::AtLeastOnce) {
+                                warn!(topic = %topic, error = %e, "MQTT subscribe failed");
+                            }
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        let message = MqttMessage {
+                            topic: publish.topic.clone(),
+                            payload: String::from_utf8_lossy(&publish.payload).to_string(),
+                            retained: publish.retain,
+                            at: chrono::Local::now().to_rfc3339(),
+                        };
+                        debug!(topic = %message.topic, retained = message.retained, "MQTT message");
+                        handle(message);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!(error = %e, "MQTT connection error; retrying in 5s");
+                        set_connection(Some(client.clone()), false);
+                        tokio::select! {
+                            _ = cancel.cancelled() => return,
+                            _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+                        }
+                    }
+                },
+            }
+        }
+        let _ = client.try_disconnect();
+        set_connection(None, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(topic: &str, payload: &str, retained: bool) -> MqttMessage {
+        MqttMessage {
+            topic: topic.to_string(),
+            payload: payload.to_string(),
+            retained,
+            at: "2026-01-01T08:00:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("home/+/temperature", "home/kitchen/temperature"));
+        assert!(!topic_matches("home/+/temperature", "home/kitchen/humidity"));
+        assert!(!topic_matches("home/+", "home/kitchen/temperature"));
+        assert!(topic_matches("home/#", "home"));
+        assert!(topic_matches("home/#", "home/a/b/c"));
+        assert!(topic_matches("#", "anything/at/all"));
+        assert!(!topic_matches("home/kitchen", "home"));
+    }
+
+    #[test]
+    fn test_rules_and_render() {
+        let rules = vec![
+            MqttRule {
+                topic: "door/+".into(),
+                contains: Some("open".into()),
+                prompt: "{topic} says {payload} at {time}".into(),
+            },
+            MqttRule { topic: "door/back".into(), contains: None, prompt: "back".into() },
+        ];
+        let front = message("door/front", "open", false);
+        let matched = matching_rules(&rules, &front);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(render(&matched[0].prompt, &front), "door/front says open at 2026-01-01T08:00:00+00:00");
+        assert_eq!(matching_rules(&rules, &message("door/back", "closed", false)).len(), 1);
+        assert!(matching_rules(&rules, &message("door/front", "open", true)).is_empty());
+    }
+
+    #[test]
+    fn test_topics_dedup() {
+        let config = MqttConfig {
+            subscriptions: vec!["a/#".into(), "b".into()],
+            rules: vec![MqttRule { topic: "b".into(), contains: None, prompt: String::new() }],
+            ..Default::default()
+        };
+        assert_eq!(config.topics(), vec!["a/#".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_publish_validation() {
+        assert!(publish("a/+/b", "x", 0, false).unwrap_err().contains("Invalid publish topic"));
+        #[cfg(feature = "mqtt")]
+        assert!(publish("a/b", "x", 3, false).unwrap_err().contains("QoS"));
+    }
+}
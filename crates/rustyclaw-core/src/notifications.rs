@@ -0,0 +1,120 @@
+//! Background events for the TUI's notification center.
+//!
+//! Things that happen outside the conversation — a sub-agent finishing, a
+//! cron job failing, a device asking to pair, a messenger erroring — are
+//! posted here instead of being logged and lost.  The gateway forwards
+//! each one to connected clients as a `Notification` frame and replays the
+//! most recent ones to clients that connect later.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::broadcast;
+
+/// Notifications kept for clients that connect later.
+const BACKLOG: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// What raised the notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Subagent,
+    Cron,
+    Pairing,
+    Messenger,
+    Heartbeat,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: u64,
+    pub severity: Severity,
+    pub source: Source,
+    pub title: String,
+    pub body: String,
+    /// Session (or cron job) the event belongs to, for jumping to it.
+    pub session: Option<String>,
+    pub at_ms: u64,
+}
+
+fn channel() -> &'static broadcast::Sender<Notification> {
+    static TX: OnceLock<broadcast::Sender<Notification>> = OnceLock::new();
+    TX.get_or_init(|| broadcast::channel(BACKLOG).0)
+}
+
+fn backlog() -> &'static Mutex<VecDeque<Notification>> {
+    static RECENT: OnceLock<Mutex<VecDeque<Notification>>> = OnceLock::new();
+    RECENT.get_or_init(|| Mutex::new(VecDeque::with_capacity(BACKLOG)))
+}
+
+/// Post a notification to every connected client.
+pub fn post(
+    severity: Severity,
+    source: Source,
+    title: impl Into<String>,
+    body: impl Into<String>,
+    session: Option<String>,
+) {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let notification = Notification {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        severity,
+        source,
+        title: title.into(),
+        body: body.into(),
+        session,
+        at_ms: crate::cron::now_ms(),
+    };
+    if let Ok(mut recent) = backlog().lock() {
+        if recent.len() == BACKLOG {
+            recent.pop_front();
+        }
+        recent.push_back(notification.clone());
+    }
+    // No receivers just means no client is connected right now.
+    let _ = channel().send(notification);
+}
+
+/// The most recent notifications, oldest first.
+pub fn recent() -> Vec<Notification> {
+    backlog().lock().map(|r| r.iter().cloned().collect()).unwrap_or_default()
+}
+
+/// Receive every notification posted from now on.
+pub fn subscribe() -> broadcast::Receiver<Notification> {
+    channel().subscribe()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_reaches_subscribers_and_backlog() {
+        let mut rx = subscribe();
+        post(Severity::Error, Source::Cron, "Cron job failed", "backup: exit 1", Some("job-1".into()));
+
+        let received = rx.try_recv().unwrap();
+        assert_eq!(received.source, Source::Cron);
+        assert_eq!(received.session.as_deref(), Some("job-1"));
+        assert!(recent().iter().any(|n| n.id == received.id));
+    }
+
+    #[test]
+    fn test_backlog_is_bounded() {
+        for i in 0..BACKLOG + 5 {
+            post(Severity::Info, Source::Subagent, format!("done {}", i), "", None);
+        }
+        assert!(recent().len() <= BACKLOG);
+    }
+}
@@ -0,0 +1,162 @@
+//! In-memory ring buffer of recent log events.
+//!
+//! [`EventLogLayer`] is a `tracing` layer that keeps the last
+//! [`CAPACITY`] events that pass the log filter, so a client can read the
+//! gateway's diagnostics (messenger errors, provider retries, …) over the
+//! protocol with `/events` instead of restarting it with stderr
+//! redirected to a file.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Events kept in the buffer.
+pub const CAPACITY: usize = 1000;
+
+/// One recorded log event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEvent {
+    pub at_ms: u64,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: String,
+    /// Module path the event came from.
+    pub target: String,
+    /// The message followed by its fields as `key=value`.
+    pub message: String,
+}
+
+impl LogEvent {
+    /// One display line, e.g. `12:04:31 WARN  gateway::messenger_handler  Send failed error=…`.
+    pub fn line(&self) -> String {
+        let clock = chrono::DateTime::from_timestamp_millis(self.at_ms as i64)
+            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+            .unwrap_or_default();
+        let target = self.target.strip_prefix("rustyclaw_core::").unwrap_or(&self.target);
+        format!("{} {:<5} {}  {}", clock, self.level, target, self.message)
+    }
+}
+
+fn buffer() -> &'static Mutex<VecDeque<LogEvent>> {
+    static EVENTS: OnceLock<Mutex<VecDeque<LogEvent>>> = OnceLock::new();
+    EVENTS.get_or_init(|| Mutex::new(VecDeque::with_capacity(CAPACITY)))
+}
+
+/// Add an event, dropping the oldest once the buffer is full.
+pub fn push(event: LogEvent) {
+    if let Ok(mut events) = buffer().lock() {
+        if events.len() == CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+/// The last `limit` events at `min_level` or more severe, oldest first.
+pub fn tail(limit: usize, min_level: Option<Level>) -> Vec<LogEvent> {
+    let Ok(events) = buffer().lock() else {
+        return Vec::new();
+    };
+    let mut matching: Vec<LogEvent> = events
+        .iter()
+        .rev()
+        .filter(|e| match (min_level, e.level.parse::<Level>()) {
+            // Level orders TRACE > DEBUG > … > ERROR (more verbose is greater).
+            (Some(min), Ok(level)) => level <= min,
+            _ => true,
+        })
+        .take(limit)
+        .cloned()
+        .collect();
+    matching.reverse();
+    matching
+}
+
+/// Collects an event's message and fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push_str(&format!(" {}={}", field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+/// `tracing` layer recording every event into the ring buffer.
+pub struct EventLogLayer;
+
+impl<S: Subscriber> Layer<S> for EventLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        push(LogEvent {
+            at_ms: crate::cron::now_ms(),
+            level: event.metadata().level().to_string(),
+            target: event.metadata().target().to_string(),
+            message: format!("{}{}", visitor.message, visitor.fields).trim().to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(level: &str, message: &str) -> LogEvent {
+        LogEvent {
+            at_ms: 0,
+            level: level.to_string(),
+            target: "rustyclaw_core::gateway".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tail_filters_by_level() {
+        push(event("INFO", "event-log-test connected"));
+        push(event("WARN", "event-log-test retrying"));
+        push(event("ERROR", "event-log-test failed"));
+
+        let warnings: Vec<String> = tail(CAPACITY, Some(Level::WARN))
+            .into_iter()
+            .filter(|e| e.message.starts_with("event-log-test"))
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(warnings, vec!["event-log-test retrying", "event-log-test failed"]);
+        assert_eq!(tail(1, None).len(), 1);
+    }
+
+    #[test]
+    fn test_layer_records_events() {
+        use tracing_subscriber::prelude::*;
+        let subscriber = tracing_subscriber::registry().with(EventLogLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(chat = "42", "event-log-layer send failed");
+        });
+        let recorded = tail(CAPACITY, None)
+            .into_iter()
+            .find(|e| e.message.starts_with("event-log-layer"))
+            .expect("event recorded");
+        assert_eq!(recorded.level, "WARN");
+        assert_eq!(recorded.message, "event-log-layer send failed chat=42");
+    }
+}
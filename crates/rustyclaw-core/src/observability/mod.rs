@@ -4,9 +4,16 @@
 //! metrics from the agent runtime. The modular design supports multiple backends
 //! (console logging, Prometheus, OpenTelemetry) via the [`Observer`] trait.
 //!
+//! Request tracing lives in [`trace`] (per-turn trace IDs) and [`otlp`]
+//! (optional span export to an OpenTelemetry collector); recent log events
+//! are kept in memory by [`event_log`] for `/events`.
+//!
 //! Adapted from ZeroClaw (MIT OR Apache-2.0 licensed).
 
+pub mod event_log;
 pub mod log;
+pub mod otlp;
+pub mod trace;
 pub mod traits;
 
 pub use log::LogObserver;
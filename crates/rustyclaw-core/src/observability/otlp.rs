@@ -0,0 +1,336 @@
+//! Optional OTLP span exporter.
+//!
+//! [`OtlpLayer`] is a `tracing` layer that turns the spans of traced turns
+//! (the `turn` root span and everything beneath it: provider calls, tool
+//! executions, vault access) into OpenTelemetry spans and posts them to a
+//! collector using OTLP/HTTP with JSON encoding — Jaeger, Tempo, Honeycomb
+//! and the OpenTelemetry Collector all accept it on `/v1/traces`.
+//!
+//! Enabled by setting `RUSTYCLAW_OTLP_ENDPOINT` (or the standard
+//! `OTEL_EXPORTER_OTLP_ENDPOINT`); see [`crate::logging`].  Spans outside
+//! a traced turn are ignored.  Export happens on a background thread and
+//! never blocks the caller: when the collector falls behind, spans are
+//! dropped rather than queued without bound.
+
+use serde_json::{json, Value};
+use std::fmt::Debug;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use super::trace::new_span_id;
+
+/// Spans buffered between the layer and the export thread.
+const QUEUE_CAPACITY: usize = 4096;
+
+/// Largest batch posted in one request.
+const MAX_BATCH: usize = 512;
+
+/// How long the export thread waits before sending a partial batch.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Service name reported in the OTLP resource.
+const SERVICE_NAME: &str = "rustyclaw";
+
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+/// A finished span, ready to export.
+#[derive(Debug, Clone)]
+pub struct ExportedSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start_ns: u64,
+    pub end_ns: u64,
+    pub attributes: Vec<(String, String)>,
+    /// Warning and error log lines emitted inside the span.
+    pub events: Vec<(u64, String)>,
+    pub error: bool,
+}
+
+/// Per-span state kept in the registry's extensions while the span is open.
+struct OpenSpan {
+    trace_id: Option<String>,
+    span_id: String,
+    parent_span_id: Option<String>,
+    start_ns: u64,
+    attributes: Vec<(String, String)>,
+    events: Vec<(u64, String)>,
+    error: bool,
+}
+
+/// Collects span fields as strings.
+#[derive(Default)]
+struct FieldVisitor(Vec<(String, String)>);
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+impl FieldVisitor {
+    fn take(&mut self, name: &str) -> Option<String> {
+        let at = self.0.iter().position(|(k, _)| k == name)?;
+        Some(self.0.remove(at).1)
+    }
+}
+
+/// `tracing` layer exporting traced spans over OTLP/HTTP.
+pub struct OtlpLayer {
+    tx: SyncSender<ExportedSpan>,
+}
+
+impl OtlpLayer {
+    /// Start the export thread posting to `endpoint` (the collector's base
+    /// URL, or its full `/v1/traces` URL).  Extra request headers, e.g. an
+    /// API key, come from `OTEL_EXPORTER_OTLP_HEADERS` (`k=v,k=v`).
+    pub fn new(endpoint: &str) -> Self {
+        let url = traces_url(endpoint);
+        let headers = std::env::var("OTEL_EXPORTER_OTLP_HEADERS")
+            .map(|h| parse_headers(&h))
+            .unwrap_or_default();
+        let (tx, rx) = mpsc::sync_channel(QUEUE_CAPACITY);
+        std::thread::Builder::new()
+            .name("otlp-export".into())
+            .spawn(move || export_loop(rx, &url, &headers))
+            .expect("failed to spawn OTLP export thread");
+        Self { tx }
+    }
+}
+
+/// `…/v1/traces` for a collector base URL.
+pub fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    }
+}
+
+fn parse_headers(spec: &str) -> Vec<(String, String)> {
+    spec.split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            Some((k.trim().to_string(), v.trim().to_string()))
+        })
+        .collect()
+}
+
+impl<S> Layer<S> for OtlpLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = FieldVisitor::default();
+        attrs.record(&mut fields);
+
+        // A span joins a trace by naming it (the `turn` root span) or by
+        // being opened inside a traced parent.
+        let parent = span.parent().and_then(|p| {
+            p.extensions()
+                .get::<OpenSpan>()
+                .and_then(|o| o.trace_id.clone().map(|t| (t, o.span_id.clone())))
+        });
+        let trace_id = fields.take("trace_id").or_else(|| parent.as_ref().map(|(t, _)| t.clone()));
+
+        span.extensions_mut().insert(OpenSpan {
+            trace_id,
+            span_id: new_span_id(),
+            parent_span_id: parent.map(|(_, s)| s),
+            start_ns: now_nanos(),
+            attributes: fields.0,
+            events: Vec::new(),
+            error: false,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = FieldVisitor::default();
+        values.record(&mut fields);
+        if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+            if let Some(trace_id) = fields.take("trace_id") {
+                open.trace_id = Some(trace_id);
+            }
+            open.attributes.extend(fields.0);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else { return };
+        let mut fields = FieldVisitor::default();
+        event.record(&mut fields);
+        let mut text = fields.take("message").unwrap_or_default();
+        for (k, v) in fields.0 {
+            text.push_str(&format!(" {}={}", k, v));
+        }
+        if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+            open.events.push((now_nanos(), format!("{}: {}", level, text.trim())));
+            open.error |= level == Level::ERROR;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else { return };
+        let Some(trace_id) = open.trace_id else { return };
+        // Never block the traced code: drop the span if the queue is full.
+        let _ = self.tx.try_send(ExportedSpan {
+            trace_id,
+            span_id: open.span_id,
+            parent_span_id: open.parent_span_id,
+            name: span.name().to_string(),
+            start_ns: open.start_ns,
+            end_ns: now_nanos(),
+            attributes: open.attributes,
+            events: open.events,
+            error: open.error,
+        });
+    }
+}
+
+fn export_loop(rx: Receiver<ExportedSpan>, url: &str, headers: &[(String, String)]) {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::blocking::Client::new());
+    let mut batch = Vec::new();
+    loop {
+        let disconnected = match rx.recv_timeout(FLUSH_INTERVAL) {
+            Ok(span) => {
+                batch.push(span);
+                batch.extend(rx.try_iter().take(MAX_BATCH - 1));
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if !batch.is_empty() {
+            let mut request = client.post(url).json(&to_otlp_json(&batch));
+            for (k, v) in headers {
+                request = request.header(k.as_str(), v.as_str());
+            }
+            // Exporter failures go to stderr: logging them through
+            // `tracing` would feed back into this layer.
+            match request.send() {
+                Ok(resp) if !resp.status().is_success() => {
+                    eprintln!("OTLP export to {} failed: HTTP {}", url, resp.status());
+                }
+                Err(e) => eprintln!("OTLP export to {} failed: {}", url, e),
+                Ok(_) => {}
+            }
+            batch.clear();
+        }
+        if disconnected {
+            return;
+        }
+    }
+}
+
+fn key_value(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Encode spans as an OTLP `ExportTraceServiceRequest` in JSON form.
+pub fn to_otlp_json(spans: &[ExportedSpan]) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|s| {
+            json!({
+                "traceId": s.trace_id,
+                "spanId": s.span_id,
+                "parentSpanId": s.parent_span_id.clone().unwrap_or_default(),
+                "name": s.name,
+                // SPAN_KIND_INTERNAL
+                "kind": 1,
+                "startTimeUnixNano": s.start_ns.to_string(),
+                "endTimeUnixNano": s.end_ns.to_string(),
+                "attributes": s.attributes.iter().map(|(k, v)| key_value(k, v)).collect::<Vec<_>>(),
+                "events": s.events.iter().map(|(t, text)| json!({
+                    "timeUnixNano": t.to_string(),
+                    "name": text,
+                })).collect::<Vec<_>>(),
+                // STATUS_CODE_ERROR / STATUS_CODE_UNSET
+                "status": { "code": if s.error { 2 } else { 0 } },
+            })
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": [key_value("service.name", SERVICE_NAME)] },
+            "scopeSpans": [{
+                "scope": { "name": "rustyclaw", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_traces_url() {
+        assert_eq!(traces_url("http://localhost:4318"), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_url("http://localhost:4318/"), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_url("https://otel.example/v1/traces"), "https://otel.example/v1/traces");
+        assert_eq!(
+            parse_headers("x-api-key=abc, team = ops"),
+            vec![("x-api-key".into(), "abc".into()), ("team".into(), "ops".into())]
+        );
+    }
+
+    #[test]
+    fn test_layer_exports_only_traced_spans() {
+        let (tx, rx) = mpsc::sync_channel(16);
+        let subscriber = tracing_subscriber::registry().with(OtlpLayer { tx });
+        tracing::subscriber::with_default(subscriber, || {
+            let _untraced = tracing::info_span!("background").entered();
+            let turn = tracing::info_span!("turn", trace_id = "0af7651916cd43dd8448eb211c80319c");
+            let _turn = turn.enter();
+            let tool = tracing::info_span!("tool", tool = "read_file");
+            tool.in_scope(|| tracing::warn!(path = "x", "Slow disk"));
+        });
+
+        let spans: Vec<ExportedSpan> = rx.try_iter().collect();
+        assert_eq!(spans.len(), 2);
+        let (tool, turn) = (&spans[0], &spans[1]);
+        assert_eq!(turn.name, "turn");
+        assert_eq!(turn.trace_id, "0af7651916cd43dd8448eb211c80319c");
+        assert!(turn.parent_span_id.is_none());
+        assert_eq!(tool.trace_id, turn.trace_id);
+        assert_eq!(tool.parent_span_id.as_deref(), Some(turn.span_id.as_str()));
+        assert_eq!(tool.attributes, vec![("tool".to_string(), "read_file".to_string())]);
+        assert_eq!(tool.events.len(), 1);
+        assert!(tool.events[0].1.contains("Slow disk path=x"));
+
+        let body = to_otlp_json(&spans);
+        let exported = &body["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(exported[0]["parentSpanId"], turn.span_id.as_str());
+        assert_eq!(exported[1]["attributes"].as_array().unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,83 @@
+//! Request trace IDs.
+//!
+//! Every turn the gateway handles — a TUI chat or an incoming messenger
+//! message — gets a W3C-style trace ID (32 hex digits).  The ID is held in
+//! a task-local for the duration of the turn, so anything on the turn's
+//! task (provider calls, tool executions, vault access) can stamp it on
+//! what it records, and it is attached to the turn's root `tracing` span
+//! so every log line emitted inside the turn carries `trace_id=…`.  The
+//! [`otlp`](super::otlp) layer uses the same ID when exporting spans.
+
+use rand::RngExt;
+use std::future::Future;
+use tracing::Span;
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// Generate a new random trace ID (16 bytes, lower-case hex).
+pub fn new_trace_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rng().fill(&mut bytes);
+    hex(&bytes)
+}
+
+/// Generate a new random span ID (8 bytes, lower-case hex).
+pub fn new_span_id() -> String {
+    let mut bytes = [0u8; 8];
+    rand::rng().fill(&mut bytes);
+    hex(&bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The trace ID of the turn running on the current task, if any.
+pub fn current() -> Option<String> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
+
+/// Root span for a turn.  Child spans (`provider_call`, `tool`) and log
+/// events inside it are tagged with the trace ID by the subscriber.
+pub fn turn_span(trace_id: &str, source: &str) -> Span {
+    tracing::info_span!("turn", trace_id = %trace_id, source = %source)
+}
+
+/// Run `fut` as a traced turn: `trace_id` is the task's current trace ID
+/// until the future completes.
+pub async fn scope<F: Future>(trace_id: String, fut: F) -> F::Output {
+    CURRENT.scope(trace_id, fut).await
+}
+
+/// Run `f` with `trace_id` (if any) as the current trace ID — for turn
+/// work moved off the turn's task, e.g. onto the blocking pool.
+pub fn sync_scope<R>(trace_id: Option<String>, f: impl FnOnce() -> R) -> R {
+    match trace_id {
+        Some(id) => CURRENT.sync_scope(id, f),
+        None => f(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_hex_of_the_right_length() {
+        let trace = new_trace_id();
+        assert_eq!(trace.len(), 32);
+        assert!(trace.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(new_span_id().len(), 16);
+        assert_ne!(new_trace_id(), trace);
+    }
+
+    #[tokio::test]
+    async fn test_current_is_scoped_to_the_turn() {
+        assert!(current().is_none());
+        let seen = scope("abc123".to_string(), async { current() }).await;
+        assert_eq!(seen.as_deref(), Some("abc123"));
+        assert!(current().is_none());
+    }
+}
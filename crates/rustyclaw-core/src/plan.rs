@@ -0,0 +1,320 @@
+//! Agent task plan (a live checklist).
+//!
+//! The `plan` tool lets the model lay out the steps of a multi-step task and
+//! tick them off as it works.  Plans are held in process memory, one per
+//! sub-agent session, messenger chat or TUI connection (so none of them can
+//! see or clobber another's), rendered as a checklist pane in the TUI, and
+//! injected compactly into the system prompt so the model always sees where
+//! it is.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sessions::{current_conversation, current_session};
+
+/// Prefix of the system message carrying the compact plan.  Used to find
+/// and replace the previous copy on each model call.
+pub const PLAN_PROMPT_HEADER: &str = "## Current plan";
+
+/// Get current time in milliseconds.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Status of a single plan step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    InProgress,
+    Done,
+    Skipped,
+}
+
+impl StepStatus {
+    /// Checkbox marker used in compact and checklist renderings.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Done => "[x]",
+            Self::Skipped => "[-]",
+        }
+    }
+}
+
+impl std::str::FromStr for StepStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" | "todo" => Ok(Self::Pending),
+            "in_progress" | "in-progress" | "active" => Ok(Self::InProgress),
+            "done" | "completed" | "complete" => Ok(Self::Done),
+            "skipped" | "skip" => Ok(Self::Skipped),
+            other => Err(format!(
+                "Unknown step status: {}. Valid: pending, in_progress, done, skipped",
+                other
+            )),
+        }
+    }
+}
+
+/// One step of a plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub text: String,
+    pub status: StepStatus,
+}
+
+/// An ordered checklist of steps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Plan {
+    pub title: String,
+    pub steps: Vec<PlanStep>,
+    pub updated_ms: u64,
+}
+
+impl Plan {
+    /// Create a plan with every step pending.
+    pub fn new(title: &str, steps: &[String]) -> Self {
+        Self {
+            title: title.to_string(),
+            steps: steps
+                .iter()
+                .map(|s| PlanStep {
+                    text: s.clone(),
+                    status: StepStatus::Pending,
+                })
+                .collect(),
+            updated_ms: now_millis(),
+        }
+    }
+
+    /// Set the status of a step (1-based index).
+    ///
+    /// Only one step may be in progress at a time; marking a step in
+    /// progress returns any other in-progress step to pending.
+    pub fn set_status(&mut self, step: usize, status: StepStatus) -> Result<(), String> {
+        let idx = self.index(step)?;
+        if status == StepStatus::InProgress {
+            for s in self.steps.iter_mut() {
+                if s.status == StepStatus::InProgress {
+                    s.status = StepStatus::Pending;
+                }
+            }
+        }
+        self.steps[idx].status = status;
+        self.touch();
+        Ok(())
+    }
+
+    /// Insert a new pending step at the given 1-based position.
+    /// Positions past the end append.
+    pub fn insert(&mut self, position: usize, text: &str) -> usize {
+        let idx = position.saturating_sub(1).min(self.steps.len());
+        self.steps.insert(
+            idx,
+            PlanStep {
+                text: text.to_string(),
+                status: StepStatus::Pending,
+            },
+        );
+        self.touch();
+        idx + 1
+    }
+
+    /// Remove a step (1-based index) and return it.
+    pub fn remove(&mut self, step: usize) -> Result<PlanStep, String> {
+        let idx = self.index(step)?;
+        let removed = self.steps.remove(idx);
+        self.touch();
+        Ok(removed)
+    }
+
+    /// (finished, total) where finished counts done and skipped steps.
+    pub fn progress(&self) -> (usize, usize) {
+        let finished = self
+            .steps
+            .iter()
+            .filter(|s| matches!(s.status, StepStatus::Done | StepStatus::Skipped))
+            .count();
+        (finished, self.steps.len())
+    }
+
+    /// Whether every step is done or skipped.
+    pub fn is_complete(&self) -> bool {
+        let (finished, total) = self.progress();
+        total > 0 && finished == total
+    }
+
+    /// Render the plan as a numbered checklist.
+    pub fn render(&self) -> String {
+        let (finished, total) = self.progress();
+        let mut out = format!("{} ({}/{})\n", self.title, finished, total);
+        for (i, step) in self.steps.iter().enumerate() {
+            out.push_str(&format!("{} {}. {}\n", step.status.marker(), i + 1, step.text));
+        }
+        out
+    }
+
+    /// Render the plan for the system prompt.
+    pub fn render_compact(&self) -> String {
+        format!(
+            "{}\n{}Keep this plan current with the `plan` tool as you work.",
+            PLAN_PROMPT_HEADER,
+            self.render()
+        )
+    }
+
+    fn index(&self, step: usize) -> Result<usize, String> {
+        if step == 0 || step > self.steps.len() {
+            return Err(format!(
+                "Step {} out of range (plan has {} steps)",
+                step,
+                self.steps.len()
+            ));
+        }
+        Ok(step - 1)
+    }
+
+    fn touch(&mut self) {
+        self.updated_ms = now_millis();
+    }
+}
+
+/// Plans by owner: the sub-agent session, else the chat or connection.
+/// `None` is a turn with neither, such as a one-shot CLI run.
+static PLANS: OnceLock<Mutex<HashMap<Option<String>, Plan>>> = OnceLock::new();
+
+fn plans() -> &'static Mutex<HashMap<Option<String>, Plan>> {
+    PLANS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Whose plan the current turn sees.
+fn owner() -> Option<String> {
+    current_session().or_else(current_conversation)
+}
+
+/// Run `f` on the current owner's plan slot.
+pub fn with_plan<R>(f: impl FnOnce(&mut Option<Plan>) -> R) -> Result<R, String> {
+    let key = owner();
+    let mut plans = plans()
+        .lock()
+        .map_err(|_| "Failed to acquire plan lock".to_string())?;
+    let mut slot = plans.remove(&key);
+    let out = f(&mut slot);
+    if let Some(plan) = slot {
+        plans.insert(key, plan);
+    }
+    Ok(out)
+}
+
+/// Snapshot of the current owner's plan, if one exists.
+pub fn plan_snapshot() -> Option<Plan> {
+    let key = owner();
+    plans().lock().ok().and_then(|p| p.get(&key).cloned())
+}
+
+/// Drop the plan of a finished session or a closed chat or connection.
+pub fn remove_plan(owner: &str) {
+    if let Ok(mut plans) = plans().lock() {
+        plans.remove(&Some(owner.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Plan {
+        Plan::new(
+            "Ship feature",
+            &["Design".to_string(), "Implement".to_string(), "Test".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let mut plan = sample();
+        plan.set_status(1, StepStatus::InProgress).unwrap();
+        plan.set_status(2, StepStatus::InProgress).unwrap();
+        assert_eq!(plan.steps[0].status, StepStatus::Pending);
+        assert_eq!(plan.steps[1].status, StepStatus::InProgress);
+
+        plan.set_status(1, StepStatus::Done).unwrap();
+        assert_eq!(plan.progress(), (1, 3));
+        assert!(plan.set_status(4, StepStatus::Done).is_err());
+        assert!(plan.set_status(0, StepStatus::Done).is_err());
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut plan = sample();
+        assert_eq!(plan.insert(2, "Review design"), 2);
+        assert_eq!(plan.steps[1].text, "Review design");
+        assert_eq!(plan.insert(99, "Release"), 5);
+
+        let removed = plan.remove(1).unwrap();
+        assert_eq!(removed.text, "Design");
+        assert_eq!(plan.steps.len(), 4);
+    }
+
+    #[test]
+    fn test_render_compact() {
+        let mut plan = sample();
+        plan.set_status(1, StepStatus::Done).unwrap();
+        plan.set_status(2, StepStatus::InProgress).unwrap();
+        let text = plan.render_compact();
+        assert!(text.starts_with(PLAN_PROMPT_HEADER));
+        assert!(text.contains("[x] 1. Design"));
+        assert!(text.contains("[~] 2. Implement"));
+        assert!(text.contains("(1/3)"));
+    }
+
+    #[tokio::test]
+    async fn test_plans_are_per_session() {
+        crate::sessions::session_scope("agent:sub".to_string(), async {
+            with_plan(|p| *p = Some(sample())).unwrap();
+        })
+        .await;
+        assert!(plan_snapshot().is_none_or(|p| p.title != "Ship feature"));
+
+        let sub = crate::sessions::session_scope("agent:sub".to_string(), async { plan_snapshot() }).await;
+        assert_eq!(sub.map(|p| p.title).as_deref(), Some("Ship feature"));
+
+        remove_plan("agent:sub");
+        let sub = crate::sessions::session_scope("agent:sub".to_string(), async { plan_snapshot() }).await;
+        assert!(sub.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_plans_are_per_conversation() {
+        use crate::sessions::conversation_scope;
+
+        conversation_scope("chat:telegram:1".to_string(), async {
+            with_plan(|p| *p = Some(sample())).unwrap();
+        })
+        .await;
+        let other = conversation_scope("connection:127.0.0.1:5000".to_string(), async { plan_snapshot() }).await;
+        assert!(other.is_none());
+        assert!(plan_snapshot().is_none_or(|p| p.title != "Ship feature"));
+
+        let chat = conversation_scope("chat:telegram:1".to_string(), async { plan_snapshot() }).await;
+        assert_eq!(chat.map(|p| p.title).as_deref(), Some("Ship feature"));
+        remove_plan("chat:telegram:1");
+    }
+
+    #[test]
+    fn test_status_parse() {
+        assert_eq!("in-progress".parse::<StepStatus>(), Ok(StepStatus::InProgress));
+        assert_eq!("DONE".parse::<StepStatus>(), Ok(StepStatus::Done));
+        assert!("bogus".parse::<StepStatus>().is_err());
+    }
+}
@@ -0,0 +1,583 @@
+//! Sandboxed WASM tool plugins.
+//!
+//! Every `*.wasm` file in the plugins directory (see
+//! [`Config::plugins_dir`](crate::config::Config::plugins_dir)) is loaded
+//! with wasmtime and its tools are added to the custom tool
+//! [`registry`](crate::tools::registry).  Plugins run under WASI preview 1
+//! with nothing inherited from the host: no environment, no stdio and no
+//! filesystem beyond what their manifest grants.
+//!
+//! ## ABI
+//!
+//! Strings cross the boundary as UTF-8 JSON in linear memory, returned as a
+//! packed `i64` of `(ptr << 32) | len`.  A plugin exports:
+//!
+//! - `memory`
+//! - `alloc(len: i32) -> i32` — buffer for the host to write into
+//! - `tools() -> i64` — JSON array of `{ "name", "description", "parameters" }`
+//! - `call(name_ptr, name_len, args_ptr, args_len: i32) -> i64` — runs a
+//!   tool and returns `{ "ok": "…" }` or `{ "error": "…" }`
+//!
+//! and may import `rustyclaw.http_get(url_ptr, url_len: i32) -> i64`, which
+//! returns the same envelope and only reaches hosts the manifest allows.
+//!
+//! ## Manifest
+//!
+//! An optional `<plugin>.toml` next to `<plugin>.wasm` requests capabilities:
+//!
+//! ```toml
+//! [capabilities]
+//! workspace = "read"          # "none" (default), "read" or "write"; mounted at /workspace
+//! network = ["api.github.com"]
+//! fuel = 2000000000           # instruction budget per call
+//! memory_mb = 64
+//! ```
+//!
+//! A request is only honoured as far as the user grants it in
+//! `config.toml` (see [`PluginGrant`]), since the manifest ships with the
+//! plugin itself:
+//!
+//! ```toml
+//! [plugin_grants.github]
+//! workspace = true
+//! network = ["api.github.com"]
+//! ```
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, info, warn};
+use wasmtime::{
+    AsContextMut, Caller, Engine, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap,
+};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
+
+use crate::config::PluginGrant;
+use crate::tools::{registry, Tool};
+
+/// Guest path the workspace is mounted at.
+pub const GUEST_WORKSPACE: &str = "/workspace";
+
+fn default_fuel() -> u64 {
+    2_000_000_000
+}
+
+fn default_memory_mb() -> u64 {
+    64
+}
+
+/// Filesystem access granted to a plugin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceAccess {
+    #[default]
+    None,
+    Read,
+    Write,
+}
+
+/// What a plugin is allowed to touch.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Capabilities {
+    #[serde(default)]
+    pub workspace: WorkspaceAccess,
+    /// Hosts reachable through the `http_get` import.
+    #[serde(default)]
+    pub network: Vec<String>,
+    /// Instruction budget for a single call.
+    #[serde(default = "default_fuel")]
+    pub fuel: u64,
+    /// Linear memory limit.
+    #[serde(default = "default_memory_mb")]
+    pub memory_mb: u64,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            workspace: WorkspaceAccess::None,
+            network: Vec::new(),
+            fuel: default_fuel(),
+            memory_mb: default_memory_mb(),
+        }
+    }
+}
+
+impl Capabilities {
+    /// The part of this request the user's grant allows.  Without a grant a
+    /// plugin gets the defaults: no workspace, no network.
+    pub fn granted(&self, grant: Option<&PluginGrant>) -> Capabilities {
+        let grant = grant.cloned().unwrap_or_default();
+        Capabilities {
+            workspace: if grant.workspace { self.workspace } else { WorkspaceAccess::None },
+            network: self
+                .network
+                .iter()
+                .filter(|h| grant.network.iter().any(|g| g.eq_ignore_ascii_case(h)))
+                .cloned()
+                .collect(),
+            fuel: self.fuel.min(grant.fuel.unwrap_or_else(default_fuel)),
+            memory_mb: self.memory_mb.min(grant.memory_mb.unwrap_or_else(default_memory_mb)),
+        }
+    }
+}
+
+/// Contents of a plugin's `.toml` manifest.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PluginManifest {
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+/// A tool as described by the plugin's `tools` export.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginToolDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "empty_schema")]
+    pub parameters: Value,
+}
+
+fn empty_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+struct HostState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+    network: Vec<String>,
+}
+
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).expect("wasmtime engine configuration is valid")
+    })
+}
+
+/// A compiled plugin module.
+pub struct WasmPlugin {
+    pub name: String,
+    pub path: PathBuf,
+    /// What the plugin asked for.
+    pub manifest: PluginManifest,
+    /// What it actually runs with.
+    pub capabilities: Capabilities,
+    pub tools: Vec<PluginToolDef>,
+    module: Module,
+}
+
+impl WasmPlugin {
+    /// Compile a plugin and read its tool list, limited to what `grant`
+    /// allows.
+    pub fn load(path: &Path, grant: Option<&PluginGrant>) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "plugin".to_string());
+        let manifest_path = path.with_extension("toml");
+        let manifest = if manifest_path.exists() {
+            let content = std::fs::read_to_string(&manifest_path)?;
+            toml::from_str(&content)
+                .with_context(|| format!("Invalid plugin manifest {}", manifest_path.display()))?
+        } else {
+            PluginManifest::default()
+        };
+        let module = Module::from_file(engine(), path)
+            .with_context(|| format!("Failed to compile plugin {}", path.display()))?;
+
+        let capabilities = manifest.capabilities.granted(grant);
+        let requested = &manifest.capabilities;
+        if capabilities.workspace != requested.workspace || capabilities.network.len() != requested.network.len() {
+            warn!(
+                plugin = %name,
+                workspace = ?requested.workspace,
+                network = ?requested.network,
+                "Plugin requests capabilities not granted in plugin_grants; withholding them"
+            );
+        }
+
+        let mut plugin = Self {
+            name,
+            path: path.to_path_buf(),
+            manifest,
+            capabilities,
+            tools: Vec::new(),
+            module,
+        };
+        let (mut store, instance) = plugin.instantiate(None)?;
+        let memory = guest_memory(&mut store, &instance)?;
+        let tools_fn = instance.get_typed_func::<(), i64>(&mut store, "tools")?;
+        let packed = tools_fn.call(&mut store, ())?;
+        let json = read_packed(&mut store, &memory, packed)?;
+        plugin.tools = serde_json::from_str(&json)
+            .with_context(|| format!("Plugin {} returned an invalid tool list", plugin.name))?;
+        Ok(plugin)
+    }
+
+    fn instantiate(&self, workspace_dir: Option<&Path>) -> Result<(Store<HostState>, Instance)> {
+        let caps = &self.capabilities;
+        let mut wasi = WasiCtxBuilder::new();
+        if let Some(dir) = workspace_dir {
+            match caps.workspace {
+                WorkspaceAccess::None => {}
+                WorkspaceAccess::Read => {
+                    wasi.preopened_dir(dir, GUEST_WORKSPACE, DirPerms::READ, FilePerms::READ)?;
+                }
+                WorkspaceAccess::Write => {
+                    wasi.preopened_dir(dir, GUEST_WORKSPACE, DirPerms::all(), FilePerms::all())?;
+                }
+            }
+        }
+        let state = HostState {
+            wasi: wasi.build_p1(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size((caps.memory_mb * 1024 * 1024) as usize)
+                .build(),
+            network: caps.network.clone(),
+        };
+        let mut store = Store::new(engine(), state);
+        store.limiter(|s| &mut s.limits);
+        store.set_fuel(caps.fuel)?;
+
+        let instance = linker()?.instantiate(&mut store, &self.module)?;
+        // Reactor-style modules need their constructors run first.
+        if let Ok(init) = instance.get_typed_func::<(), ()>(&mut store, "_initialize") {
+            init.call(&mut store, ())?;
+        }
+        Ok((store, instance))
+    }
+
+    /// Run one of the plugin's tools.
+    pub fn call(&self, tool: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+        let run = || -> Result<Value> {
+            let (mut store, instance) = self.instantiate(Some(workspace_dir))?;
+            let memory = guest_memory(&mut store, &instance)?;
+            let (name_ptr, name_len) = write_guest(&mut store, &instance, &memory, tool.as_bytes())?;
+            let args = args.to_string();
+            let (args_ptr, args_len) = write_guest(&mut store, &instance, &memory, args.as_bytes())?;
+            let call = instance.get_typed_func::<(i32, i32, i32, i32), i64>(&mut store, "call")?;
+            let packed = call.call(&mut store, (name_ptr, name_len, args_ptr, args_len))?;
+            let out = read_packed(&mut store, &memory, packed)?;
+            serde_json::from_str(&out).context("Plugin returned invalid JSON")
+        };
+
+        match run() {
+            Ok(envelope) => {
+                if let Some(err) = envelope.get("error") {
+                    Err(err.as_str().map(String::from).unwrap_or_else(|| err.to_string()))
+                } else {
+                    match envelope.get("ok") {
+                        Some(Value::String(s)) => Ok(s.clone()),
+                        Some(other) => Ok(other.to_string()),
+                        None => Err(format!("Plugin {} returned neither ok nor error", self.name)),
+                    }
+                }
+            }
+            Err(e) if matches!(e.downcast_ref::<Trap>(), Some(Trap::OutOfFuel)) => Err(format!(
+                "Plugin {} exceeded its instruction budget",
+                self.name
+            )),
+            Err(e) => Err(format!("Plugin {} failed: {:#}", self.name, e)),
+        }
+    }
+}
+
+fn linker() -> Result<Linker<HostState>> {
+    let mut linker: Linker<HostState> = Linker::new(engine());
+    preview1::add_to_linker_sync(&mut linker, |s| &mut s.wasi)?;
+    linker.func_wrap(
+        "rustyclaw",
+        "http_get",
+        |mut caller: Caller<'_, HostState>, url_ptr: i32, url_len: i32| -> Result<i64> {
+            let memory = caller
+                .get_export("memory")
+                .and_then(|e| e.into_memory())
+                .context("Plugin has no memory export")?;
+            let mut buf = vec![0u8; url_len.max(0) as usize];
+            memory.read(&caller, url_ptr as usize, &mut buf)?;
+            let url = String::from_utf8_lossy(&buf).to_string();
+            let envelope = match http_get(&url, &caller.data().network) {
+                Ok(body) => json!({ "ok": body }),
+                Err(e) => json!({ "error": e }),
+            };
+
+            let alloc = caller
+                .get_export("alloc")
+                .and_then(|e| e.into_func())
+                .context("Plugin has no alloc export")?
+                .typed::<i32, i32>(&caller)?;
+            let bytes = envelope.to_string().into_bytes();
+            let ptr = alloc.call(&mut caller, bytes.len() as i32)?;
+            memory.write(&mut caller, ptr as usize, &bytes)?;
+            Ok(pack(ptr, bytes.len() as i32))
+        },
+    )?;
+    Ok(linker)
+}
+
+fn host_allowed(url: &url::Url, allowed: &[String]) -> bool {
+    let host = url.host_str().unwrap_or("");
+    allowed.iter().any(|h| h.eq_ignore_ascii_case(host))
+}
+
+/// Fetch a URL on behalf of a plugin, if its host is allowed.  Redirects
+/// are followed only to allowed hosts.
+fn http_get(url: &str, allowed: &[String]) -> Result<String, String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !host_allowed(&parsed, allowed) {
+        return Err(format!("Network access to {} is not permitted", parsed.host_str().unwrap_or("")));
+    }
+    let hops = allowed.to_vec();
+    let policy = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= 10 {
+            attempt.error("too many redirects")
+        } else if host_allowed(attempt.url(), &hops) {
+            attempt.follow()
+        } else {
+            let host = attempt.url().host_str().unwrap_or("").to_string();
+            attempt.error(format!("Redirect to {} is not permitted", host))
+        }
+    });
+    debug!(url, "Plugin HTTP request");
+    reqwest::blocking::Client::builder()
+        .redirect(policy)
+        .build()
+        .and_then(|client| client.get(parsed).send())
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| e.to_string())
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+    (((ptr as u32 as u64) << 32) | len as u32 as u64) as i64
+}
+
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+fn guest_memory(store: &mut Store<HostState>, instance: &Instance) -> Result<Memory> {
+    instance
+        .get_memory(store, "memory")
+        .context("Plugin has no memory export")
+}
+
+fn write_guest(
+    store: &mut Store<HostState>,
+    instance: &Instance,
+    memory: &Memory,
+    bytes: &[u8],
+) -> Result<(i32, i32)> {
+    let alloc = instance.get_typed_func::<i32, i32>(store.as_context_mut(), "alloc")?;
+    let ptr = alloc.call(store.as_context_mut(), bytes.len() as i32)?;
+    memory.write(store.as_context_mut(), ptr as usize, bytes)?;
+    Ok((ptr, bytes.len() as i32))
+}
+
+fn read_packed(store: &mut Store<HostState>, memory: &Memory, packed: i64) -> Result<String> {
+    let (ptr, len) = unpack(packed);
+    let mut buf = vec![0u8; len];
+    memory.read(store, ptr, &mut buf)?;
+    String::from_utf8(buf).context("Plugin returned invalid UTF-8")
+}
+
+/// A registry tool backed by a plugin export.
+struct WasmTool {
+    plugin: Arc<WasmPlugin>,
+    def: PluginToolDef,
+}
+
+#[async_trait::async_trait]
+impl Tool for WasmTool {
+    fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    fn description(&self) -> &str {
+        &self.def.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.def.parameters.clone()
+    }
+
+    async fn execute(&self, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+        let plugin = self.plugin.clone();
+        let name = self.def.name.clone();
+        let args = args.clone();
+        let workspace_dir = workspace_dir.to_path_buf();
+        tokio::task::spawn_blocking(move || plugin.call(&name, &args, &workspace_dir))
+            .await
+            .map_err(|e| format!("Plugin task failed: {}", e))?
+    }
+}
+
+/// Compile every `.wasm` file in `dir`, each limited to its grant.
+pub fn discover(dir: &Path, grants: &HashMap<String, PluginGrant>) -> Vec<Result<WasmPlugin>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|p| {
+            let name = p.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            WasmPlugin::load(p, grants.get(&name))
+        })
+        .collect()
+}
+
+/// Load the plugins in `dir` and register their tools.  Returns the names
+/// of the tools registered; failures are logged and skipped.
+pub fn register_plugins(dir: &Path, grants: &HashMap<String, PluginGrant>) -> Vec<String> {
+    let mut registered = Vec::new();
+    for plugin in discover(dir, grants) {
+        let plugin = match plugin {
+            Ok(p) => Arc::new(p),
+            Err(e) => {
+                warn!(error = %format!("{:#}", e), "Skipping WASM plugin");
+                continue;
+            }
+        };
+        for def in plugin.tools.clone() {
+            let name = def.name.clone();
+            match registry().register(WasmTool { plugin: plugin.clone(), def }) {
+                Ok(()) => registered.push(name),
+                Err(e) => warn!(plugin = %plugin.name, tool = %name, error = %e, "Plugin tool not registered"),
+            }
+        }
+        info!(plugin = %plugin.name, tools = plugin.tools.len(), "Loaded WASM plugin");
+    }
+    registered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A minimal plugin in WAT: one `wasm_ping` tool whose `call` body is
+    /// supplied by the test.
+    fn ping_plugin(call_body: &str) -> String {
+        let tools = r#"[{"name":"wasm_ping","description":"Ping"}]"#;
+        let ok = r#"{"ok":"pong"}"#;
+        format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (global $heap (mut i32) (i32.const 1024))
+                (data (i32.const 0) "{tools}")
+                (data (i32.const 512) "{ok}")
+                (func (export "alloc") (param $len i32) (result i32)
+                    (local $p i32)
+                    (local.set $p (global.get $heap))
+                    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                    (local.get $p))
+                (func (export "tools") (result i64) (i64.const {tools_len}))
+                (func (export "call") (param i32 i32 i32 i32) (result i64) {call_body}))"#,
+            tools = tools.replace('"', "\\\""),
+            ok = ok.replace('"', "\\\""),
+            tools_len = tools.len(),
+            call_body = call_body.replace("OK_LEN", &ok.len().to_string()),
+        )
+    }
+
+    fn write_plugin(dir: &Path, name: &str, wat: &str) -> PathBuf {
+        // wasmtime's `wat` feature accepts the text format directly.
+        let path = dir.join(format!("{}.wasm", name));
+        std::fs::write(&path, wat).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_and_call_plugin() {
+        let dir = TempDir::new().unwrap();
+        let call = "(i64.or (i64.shl (i64.const 512) (i64.const 32)) (i64.const OK_LEN))";
+        let path = write_plugin(dir.path(), "ping", &ping_plugin(call));
+
+        let plugin = WasmPlugin::load(&path, None).unwrap();
+        assert_eq!(plugin.name, "ping");
+        assert_eq!(plugin.tools.len(), 1);
+        assert_eq!(plugin.tools[0].name, "wasm_ping");
+        assert_eq!(plugin.tools[0].parameters["type"], "object");
+        assert_eq!(plugin.manifest.capabilities.workspace, WorkspaceAccess::None);
+
+        let out = plugin.call("wasm_ping", &json!({}), dir.path()).unwrap();
+        assert_eq!(out, "pong");
+    }
+
+    #[test]
+    fn test_fuel_limit_stops_runaway_plugin() {
+        let dir = TempDir::new().unwrap();
+        let path = write_plugin(
+            dir.path(),
+            "spin",
+            &ping_plugin("(loop $l (br $l)) (unreachable)"),
+        );
+        std::fs::write(dir.path().join("spin.toml"), "[capabilities]\nfuel = 10000\n").unwrap();
+
+        let plugin = WasmPlugin::load(&path, None).unwrap();
+        assert_eq!(plugin.capabilities.fuel, 10_000);
+        let err = plugin.call("wasm_ping", &json!({}), dir.path()).unwrap_err();
+        assert!(err.contains("instruction budget"), "{}", err);
+    }
+
+    #[test]
+    fn test_network_allowlist() {
+        let err = http_get("https://example.com/", &["api.github.com".to_string()]).unwrap_err();
+        assert!(err.contains("not permitted"));
+        assert!(http_get("not a url", &[]).is_err());
+    }
+
+    #[test]
+    fn test_manifest_capabilities_need_a_grant() {
+        let requested = Capabilities {
+            workspace: WorkspaceAccess::Write,
+            network: vec!["api.github.com".into(), "evil.example".into()],
+            fuel: u64::MAX,
+            memory_mb: 4096,
+        };
+
+        let none = requested.granted(None);
+        assert_eq!(none.workspace, WorkspaceAccess::None);
+        assert!(none.network.is_empty());
+        assert_eq!(none.fuel, default_fuel());
+        assert_eq!(none.memory_mb, default_memory_mb());
+
+        let grant = PluginGrant {
+            workspace: true,
+            network: vec!["API.github.com".into()],
+            fuel: Some(5_000_000_000),
+            memory_mb: None,
+        };
+        let some = requested.granted(Some(&grant));
+        assert_eq!(some.workspace, WorkspaceAccess::Write);
+        assert_eq!(some.network, vec!["api.github.com".to_string()]);
+        assert_eq!(some.fuel, 5_000_000_000);
+        assert_eq!(some.memory_mb, default_memory_mb());
+    }
+
+    #[test]
+    fn test_discover_skips_invalid_modules() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("broken.wasm"), b"\0asm garbage").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        let found = discover(dir.path(), &HashMap::new());
+        assert_eq!(found.len(), 1);
+        assert!(found[0].is_err());
+    }
+}
@@ -0,0 +1,468 @@
+//! Geofencing: presence triggers from node locations.
+//!
+//! Zones are circles defined in the `[presence]` config section.  The
+//! gateway learns where paired nodes are in two ways: it polls ADB nodes
+//! (`nodes` → `location_get`) every `poll_interval_secs`, and phones can
+//! push fixes to the health server's `/presence` endpoint (e.g. from
+//! OwnTracks or Tasker).  When a node enters or leaves a zone:
+//!
+//! - script hooks `fn on_presence(event)` run (see [`crate::scripting`]),
+//! - matching rules fire — queueing an agent task and/or sending a message.
+//!
+//! ```toml
+//! [presence]
+//! enabled = true
+//! nodes = ["adb:R58M123"]
+//!
+//! [[presence.zones]]
+//! name = "work"
+//! latitude = 51.5033
+//! longitude = -0.1196
+//! radius_m = 200
+//!
+//! [[presence.rules]]
+//! zone = "work"
+//! on = "leave"
+//! prompt = "I just left work. Message my partner an ETA for getting home."
+//! ```
+//!
+//! The first fix for a node only establishes where it is; events fire on
+//! later changes, so a gateway restart doesn't replay "enter" for every
+//! zone the phone is already in.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Extra distance beyond the radius before a node counts as having left,
+/// so GPS jitter at the boundary doesn't flap enter/leave.
+const LEAVE_HYSTERESIS_M: f64 = 25.0;
+
+/// A circular zone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default = "default_radius")]
+    pub radius_m: f64,
+}
+
+fn default_radius() -> f64 {
+    150.0
+}
+
+/// Zone transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transition {
+    Enter,
+    Leave,
+}
+
+impl Transition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enter => "enter",
+            Self::Leave => "leave",
+        }
+    }
+}
+
+/// What to do when a node crosses a zone boundary.
+///
+/// `prompt`, `message` and `target` may use `{node}`, `{zone}`, `{event}`
+/// and `{time}` placeholders; a `template` gets the same values as
+/// `{{node}}` etc.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresenceRule {
+    pub zone: String,
+    pub on: Transition,
+    /// Only for this node (default: any).
+    #[serde(default)]
+    pub node: Option<String>,
+    /// Queue an agent task with this prompt.
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Send this message text to `target` (a chat id or contact name).
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Send this message template (see [`crate::templates`]) instead of
+    /// `message`.
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub channel: Option<String>,
+    /// Send the message even during the channel's quiet hours.
+    #[serde(default)]
+    pub urgent: bool,
+}
+
+/// The `[presence]` config section.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresenceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Nodes whose location the gateway polls.
+    #[serde(default)]
+    pub nodes: Vec<String>,
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_secs: u64,
+    #[serde(default)]
+    pub zones: Vec<Zone>,
+    #[serde(default)]
+    pub rules: Vec<PresenceRule>,
+}
+
+fn default_poll_interval() -> u64 {
+    120
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            nodes: Vec::new(),
+            poll_interval_secs: default_poll_interval(),
+            zones: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// A node entering or leaving a zone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresenceEvent {
+    pub node: String,
+    pub zone: String,
+    pub event: Transition,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub at: String,
+}
+
+/// Great-circle distance in metres.
+pub fn haversine_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = (lat2 - lat1).to_radians();
+    let dlambda = (lon2 - lon1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Which zones each node is in.
+#[derive(Debug, Default)]
+pub struct PresenceTracker {
+    inside: HashMap<String, BTreeSet<String>>,
+    last_fix: HashMap<String, (f64, f64, String)>,
+}
+
+impl PresenceTracker {
+    /// Record a fix for `node` and return the transitions it causes.
+    pub fn update(&mut self, node: &str, lat: f64, lon: f64, zones: &[Zone]) -> Vec<PresenceEvent> {
+        let at = chrono::Utc::now().to_rfc3339();
+        self.last_fix.insert(node.to_string(), (lat, lon, at.clone()));
+        let first = !self.inside.contains_key(node);
+        let previous = self.inside.entry(node.to_string()).or_default();
+
+        let mut now = BTreeSet::new();
+        for zone in zones {
+            let distance = haversine_m(lat, lon, zone.latitude, zone.longitude);
+            let was_inside = previous.contains(&zone.name);
+            let limit = if was_inside { zone.radius_m + LEAVE_HYSTERESIS_M } else { zone.radius_m };
+            if distance <= limit {
+                now.insert(zone.name.clone());
+            }
+        }
+
+        let mut events = Vec::new();
+        if !first {
+            let event = |zone: &String, event| PresenceEvent {
+                node: node.to_string(),
+                zone: zone.clone(),
+                event,
+                latitude: lat,
+                longitude: lon,
+                at: at.clone(),
+            };
+            events.extend(now.difference(previous).map(|z| event(z, Transition::Enter)));
+            events.extend(previous.difference(&now).map(|z| event(z, Transition::Leave)));
+        }
+        *previous = now;
+        events
+    }
+
+    /// Current state for status output.
+    pub fn snapshot(&self) -> Value {
+        let nodes: serde_json::Map<String, Value> = self
+            .last_fix
+            .iter()
+            .map(|(node, (lat, lon, at))| {
+                let zones: Vec<&String> = self.inside.get(node).map(|z| z.iter().collect()).unwrap_or_default();
+                (
+                    node.clone(),
+                    json!({ "latitude": lat, "longitude": lon, "at": at, "zones": zones }),
+                )
+            })
+            .collect();
+        json!({ "nodes": nodes })
+    }
+}
+
+/// Extract coordinates from `dumpsys location` output, e.g.
+/// `Location[gps 51.503300,-0.119600 hAcc=12 ...]`.
+pub fn parse_android_location(text: &str) -> Option<(f64, f64)> {
+    let re = regex::Regex::new(r"Location\[\w+ (-?\d+(?:\.\d+)?),(-?\d+(?:\.\d+)?)").ok()?;
+    let caps = re.captures(text)?;
+    Some((caps[1].parse().ok()?, caps[2].parse().ok()?))
+}
+
+/// Fill `{node}`, `{zone}`, `{event}` and `{time}` placeholders.
+pub fn render(template: &str, event: &PresenceEvent) -> String {
+    template
+        .replace("{node}", &event.node)
+        .replace("{zone}", &event.zone)
+        .replace("{event}", event.event.as_str())
+        .replace("{time}", &event.at)
+}
+
+/// Rules matching `event`.
+pub fn matching_rules<'a>(rules: &'a [PresenceRule], event: &PresenceEvent) -> Vec<&'a PresenceRule> {
+    rules
+        .iter()
+        .filter(|r| r.zone == event.zone && r.on == event.event)
+        .filter(|r| r.node.as_deref().is_none_or(|n| n == event.node))
+        .collect()
+}
+
+// ── Gateway state ───────────────────────────────────────────────────────────
+
+struct State {
+    config: PresenceConfig,
+    workspace_dir: PathBuf,
+    tracker: PresenceTracker,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Register the presence config.  Called at startup and on reload; node
+/// positions survive reloads.
+pub fn set_config(config: PresenceConfig, workspace_dir: &Path) {
+    debug!(zones = config.zones.len(), rules = config.rules.len(), "Setting presence config");
+    if let Ok(mut guard) = STATE.lock() {
+        let tracker = guard.take().map(|s| s.tracker).unwrap_or_default();
+        *guard = Some(State { config, workspace_dir: workspace_dir.to_path_buf(), tracker });
+    }
+}
+
+/// Current positions and zones, for the `/presence` endpoint.
+pub fn status() -> Value {
+    STATE
+        .lock()
+        .ok()
+        .and_then(|g| g.as_ref().map(|s| s.tracker.snapshot()))
+        .unwrap_or_else(|| json!({ "nodes": {} }))
+}
+
+/// Record a location fix for `node` and fire hooks and rules for any
+/// transitions.  Returns the transitions.
+pub fn report(node: &str, latitude: f64, longitude: f64) -> Result<Vec<PresenceEvent>, String> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(format!("Invalid coordinates: {}, {}", latitude, longitude));
+    }
+    let (events, rules, workspace_dir) = {
+        let mut guard = STATE.lock().map_err(|_| "Presence state poisoned".to_string())?;
+        let state = guard
+            .as_mut()
+            .filter(|s| s.config.enabled)
+            .ok_or("Presence is not enabled")?;
+        let events = state.tracker.update(node, latitude, longitude, &state.config.zones);
+        (events, state.config.rules.clone(), state.workspace_dir.clone())
+    };
+    for event in &events {
+        info!(node = %event.node, zone = %event.zone, event = event.event.as_str(), "Presence transition");
+        dispatch(event, &rules, &workspace_dir);
+    }
+    Ok(events)
+}
+
+/// Run hooks and rule actions for one transition, off the caller's thread.
+fn dispatch(event: &PresenceEvent, rules: &[PresenceRule], workspace_dir: &Path) {
+    let payload = serde_json::to_value(event).unwrap_or(Value::Null);
+    crate::scripting::spawn_hooks("presence", payload, workspace_dir.to_path_buf());
+
+    for rule in matching_rules(rules, event) {
+        if let Some(prompt) = &rule.prompt {
+            use crate::task_queue::{queue_dir, QueuedTask, TaskPriority, TaskQueue};
+            let queued = TaskQueue::new(&queue_dir(workspace_dir)).and_then(|mut queue| {
+                let mut task = QueuedTask::new(&render(prompt, event), TaskPriority::High, 1);
+                task.source = Some("presence".to_string());
+                queue.enqueue(task)
+            });
+            match queued {
+                Ok(id) => debug!(task_id = %id, zone = %event.zone, "Queued presence task"),
+                Err(e) => warn!(error = %e, "Failed to queue presence task"),
+            }
+        }
+        let message = match (&rule.template, &rule.message) {
+            (Some(template), _) => {
+                let vars = HashMap::from([
+                    ("node".to_string(), event.node.clone()),
+                    ("zone".to_string(), event.zone.clone()),
+                    ("event".to_string(), event.event.as_str().to_string()),
+                    ("time".to_string(), event.at.clone()),
+                ]);
+                match crate::templates::render_named(workspace_dir, template, &vars) {
+                    Ok(text) => Some(text),
+                    Err(e) => {
+                        warn!(error = %e, template = %template, "Presence template failed");
+                        None
+                    }
+                }
+            }
+            (None, Some(message)) => Some(render(message, event)),
+            (None, None) => None,
+        };
+        if let (Some(message), Some(target)) = (message, &rule.target) {
+            let args = json!({
+                "action": "send",
+                "message": message,
+                "target": render(target, event),
+                "channel": rule.channel.as_deref().unwrap_or("auto"),
+                "urgent": rule.urgent,
+            });
+            let workspace_dir = workspace_dir.to_path_buf();
+            std::thread::spawn(move || {
+                if let Err(e) = crate::tools::execute_tool("message", &args, &workspace_dir) {
+                    warn!(error = %e, "Presence message failed");
+                }
+            });
+        }
+    }
+}
+
+/// Ask a node for its location through the `nodes` tool.
+fn poll_node(node: &str, workspace_dir: &Path) -> Result<(f64, f64), String> {
+    let output = crate::tools::execute_tool(
+        "nodes",
+        &json!({ "action": "location_get", "node": node }),
+        workspace_dir,
+    )?;
+    let info = serde_json::from_str::<Value>(&output)
+        .ok()
+        .and_then(|v| v["location_info"].as_str().map(String::from))
+        .unwrap_or(output);
+    parse_android_location(&info).ok_or_else(|| format!("No location fix from {}", node))
+}
+
+/// Poll configured nodes until cancelled.  Reads the config every round,
+/// so reloads take effect without restarting the loop.
+pub async fn run_presence_loop(cancel: CancellationToken) {
+    loop {
+        let (nodes, interval, workspace_dir) = match STATE.lock().ok().and_then(|g| {
+            g.as_ref().map(|s| {
+                (
+                    if s.config.enabled { s.config.nodes.clone() } else { Vec::new() },
+                    s.config.poll_interval_secs.max(10),
+                    s.workspace_dir.clone(),
+                )
+            })
+        }) {
+            Some(round) => round,
+            None => (Vec::new(), default_poll_interval(), PathBuf::new()),
+        };
+
+        for node in nodes {
+            let ws = workspace_dir.clone();
+            let polled = tokio::task::spawn_blocking(move || {
+                let fix = poll_node(&node, &ws);
+                (node, fix)
+            })
+            .await;
+            match polled {
+                Ok((node, Ok((lat, lon)))) => {
+                    if let Err(e) = report(&node, lat, lon) {
+                        debug!(node = %node, error = %e, "Presence report skipped");
+                    }
+                }
+                Ok((node, Err(e))) => debug!(node = %node, error = %e, "Presence poll failed"),
+                Err(e) => warn!(error = %e, "Presence poll task panicked"),
+            }
+        }
+
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work() -> Zone {
+        Zone { name: "work".into(), latitude: 51.5033, longitude: -0.1196, radius_m: 200.0 }
+    }
+
+    #[test]
+    fn test_haversine() {
+        // London → Paris is about 344 km.
+        let d = haversine_m(51.5074, -0.1278, 48.8566, 2.3522);
+        assert!((d - 344_000.0).abs() < 2_000.0, "{}", d);
+        assert_eq!(haversine_m(1.0, 1.0, 1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_tracker_transitions() {
+        let zones = [work()];
+        let mut tracker = PresenceTracker::default();
+        // First fix establishes the baseline without an event.
+        assert!(tracker.update("phone", 51.5033, -0.1196, &zones).is_empty());
+        // Jitter just past the radius stays inside (hysteresis).
+        assert!(tracker.update("phone", 51.5033 + 0.00190, -0.1196, &zones).is_empty());
+        let left = tracker.update("phone", 51.52, -0.1196, &zones);
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].event, Transition::Leave);
+        let entered = tracker.update("phone", 51.5034, -0.1195, &zones);
+        assert_eq!(entered[0].event, Transition::Enter);
+        assert_eq!(tracker.snapshot()["nodes"]["phone"]["zones"][0], "work");
+    }
+
+    #[test]
+    fn test_parse_android_location() {
+        let dump = "  last location=Location[fused 51.503300,-0.119600 hAcc=12 et=+1d2h]";
+        assert_eq!(parse_android_location(dump), Some((51.5033, -0.1196)));
+        assert_eq!(parse_android_location("no fix"), None);
+    }
+
+    #[test]
+    fn test_rules_and_render() {
+        let config: PresenceConfig = toml::from_str(
+            "enabled = true\n[[zones]]\nname = \"work\"\nlatitude = 1.0\nlongitude = 2.0\n\
+             [[rules]]\nzone = \"work\"\non = \"leave\"\nmessage = \"{node} left {zone}\"\ntarget = \"Partner\"\n\
+             [[rules]]\nzone = \"work\"\non = \"leave\"\nnode = \"tablet\"\nprompt = \"x\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.zones[0].radius_m, 150.0);
+        let event = PresenceEvent {
+            node: "phone".into(),
+            zone: "work".into(),
+            event: Transition::Leave,
+            latitude: 0.0,
+            longitude: 0.0,
+            at: "t".into(),
+        };
+        let rules = matching_rules(&config.rules, &event);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(render(rules[0].message.as_deref().unwrap(), &event), "phone left work");
+    }
+}
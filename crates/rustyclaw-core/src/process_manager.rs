@@ -286,6 +286,18 @@ impl ExecSession {
             return Ok(()); // Already gone
         };
 
+        // Helper pipelines (e.g. the serial bridge) run in their own process
+        // group so that killing the session takes every member down.
+        #[cfg(unix)]
+        {
+            let pid = child.id() as libc::pid_t;
+            unsafe {
+                if libc::getpgid(pid) == pid {
+                    libc::kill(-pid, libc::SIGKILL);
+                }
+            }
+        }
+
         child
             .kill()
             .map_err(|e| format!("Failed to kill process: {}", e))?;
@@ -0,0 +1,209 @@
+//! Progress of long-running tool operations.
+//!
+//! Tools that download, convert, index or install something start a
+//! [`Progress`] handle and update it as they go.  The operations in flight
+//! are published on a `watch` channel, which the gateway forwards to the
+//! TUI as `Progress` frames while a tool runs, so it can draw percentage /
+//! ETA bars instead of a bare spinner.  Dropping the handle ends the
+//! operation.
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Minimum time between two published updates of one operation.
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Read buffer size for [`read_to_end`].
+const READ_CHUNK: usize = 64 * 1024;
+
+/// What an operation counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    Bytes,
+    Items,
+    Percent,
+}
+
+/// One operation in flight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressOp {
+    pub id: u64,
+    pub label: String,
+    pub unit: Unit,
+    pub done: u64,
+    /// `None` when the size isn't known (e.g. no Content-Length).
+    pub total: Option<u64>,
+    /// Time since the operation started, as of this update.
+    pub elapsed_ms: u64,
+}
+
+impl ProgressOp {
+    /// Completed fraction in `0.0..=1.0`, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(total) if total > 0 => Some((self.done as f64 / total as f64).min(1.0)),
+            _ => None,
+        }
+    }
+
+    /// Estimated time remaining, extrapolated from the rate so far.
+    pub fn eta(&self) -> Option<Duration> {
+        let fraction = self.fraction()?;
+        if fraction <= 0.0 || self.elapsed_ms < 500 {
+            return None;
+        }
+        let remaining = self.elapsed_ms as f64 * (1.0 - fraction) / fraction;
+        Some(Duration::from_millis(remaining as u64))
+    }
+}
+
+fn channel() -> &'static watch::Sender<Vec<ProgressOp>> {
+    static OPS: OnceLock<watch::Sender<Vec<ProgressOp>>> = OnceLock::new();
+    OPS.get_or_init(|| watch::channel(Vec::new()).0)
+}
+
+/// Operations currently in flight.
+pub fn snapshot() -> Vec<ProgressOp> {
+    channel().borrow().clone()
+}
+
+/// Receive every progress change.
+pub fn subscribe() -> watch::Receiver<Vec<ProgressOp>> {
+    channel().subscribe()
+}
+
+/// Handle for one operation; dropping it removes the operation.
+pub struct Progress {
+    id: u64,
+    started: Instant,
+    last_publish: Instant,
+    done: u64,
+    total: Option<u64>,
+}
+
+impl Progress {
+    /// Start an operation and publish it.
+    pub fn start(label: impl Into<String>, unit: Unit, total: Option<u64>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let label = label.into();
+        channel().send_modify(|ops| {
+            ops.push(ProgressOp { id, label, unit, done: 0, total, elapsed_ms: 0 })
+        });
+        let now = Instant::now();
+        Self { id, started: now, last_publish: now, done: 0, total }
+    }
+
+    /// Set how much is done.
+    pub fn set(&mut self, done: u64) {
+        self.done = done;
+        self.publish(false);
+    }
+
+    /// Add `n` to what's done.
+    pub fn advance(&mut self, n: u64) {
+        self.set(self.done.saturating_add(n));
+    }
+
+    /// Update the total once it's known.
+    pub fn set_total(&mut self, total: Option<u64>) {
+        self.total = total;
+        self.publish(true);
+    }
+
+    /// Publish unless the last update was very recent — tools may call
+    /// this per chunk, and the TUI only needs a few frames a second.
+    fn publish(&mut self, force: bool) {
+        let finished = self.total.is_some_and(|t| self.done >= t);
+        if !force && !finished && self.last_publish.elapsed() < PUBLISH_INTERVAL {
+            return;
+        }
+        self.last_publish = Instant::now();
+        let (id, done, total) = (self.id, self.done, self.total);
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        channel().send_if_modified(|ops| match ops.iter_mut().find(|op| op.id == id) {
+            Some(op) => {
+                op.done = done;
+                op.total = total;
+                op.elapsed_ms = elapsed_ms;
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let id = self.id;
+        channel().send_if_modified(|ops| {
+            let before = ops.len();
+            ops.retain(|op| op.id != id);
+            ops.len() != before
+        });
+    }
+}
+
+/// Read `reader` to the end, reporting the bytes read as `label`.
+pub fn read_to_end(mut reader: impl Read, label: &str, total: Option<u64>) -> std::io::Result<Vec<u8>> {
+    let mut progress = Progress::start(label, Unit::Bytes, total);
+    let mut out = Vec::with_capacity(total.unwrap_or(0).min(64 * 1024 * 1024) as usize);
+    let mut buf = vec![0u8; READ_CHUNK];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(out);
+        }
+        out.extend_from_slice(&buf[..n]);
+        progress.advance(n as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(id: u64) -> Option<ProgressOp> {
+        snapshot().into_iter().find(|op| op.id == id)
+    }
+
+    #[test]
+    fn test_progress_lifecycle() {
+        let mut progress = Progress::start("Downloading model", Unit::Bytes, Some(1000));
+        let id = progress.id;
+        assert_eq!(find(id).unwrap().done, 0);
+
+        // Reaching the total is always published, throttling or not.
+        progress.set(1000);
+        assert_eq!(find(id).unwrap().fraction(), Some(1.0));
+
+        drop(progress);
+        assert!(find(id).is_none());
+    }
+
+    #[test]
+    fn test_eta_extrapolates() {
+        let op = ProgressOp {
+            id: 0,
+            label: "x".into(),
+            unit: Unit::Percent,
+            done: 25,
+            total: Some(100),
+            elapsed_ms: 10_000,
+        };
+        assert_eq!(op.eta(), Some(Duration::from_secs(30)));
+        assert_eq!(ProgressOp { total: None, ..op }.eta(), None);
+    }
+
+    #[test]
+    fn test_read_to_end_reports_and_returns_bytes() {
+        let data = vec![7u8; 200_000];
+        let out = read_to_end(&data[..], "Reading", Some(data.len() as u64)).unwrap();
+        assert_eq!(out, data);
+    }
+}
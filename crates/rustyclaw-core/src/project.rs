@@ -0,0 +1,374 @@
+//! Per-workspace project overlays.
+//!
+//! When the agent workspace is a project checkout, a `.rustyclaw/`
+//! directory in its root customises the agent for that project on top of
+//! the global config:
+//!
+//! ```text
+//! <workspace>/.rustyclaw/
+//!   MEMORY.md       project memory, injected alongside the global memory
+//!   settings.toml   model choice, tool policy and skill enablement
+//! ```
+//!
+//! ```toml
+//! dev_env = "auto"
+//!
+//! [model]
+//! model = "claude-sonnet-4-20250514"
+//!
+//! [tool_permissions]
+//! execute_command = "ask"
+//!
+//! [skills]
+//! enabled = ["rust-dev"]
+//! disabled = ["social-media"]
+//! ```
+//!
+//! A checkout is not trusted just for being opened, so the overlay can
+//! only tighten tool permissions — a project's `allow` never loosens a
+//! global `ask` or `deny` — and changing the provider or base URL (where
+//! the API key gets sent) needs the project in `trusted_projects`.
+//!
+//! The gateway applies the overlay when it starts and on every reload; the
+//! global `config.toml` is never rewritten with project values.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config::{Config, ModelProvider};
+use crate::dev_env::DevEnvMode;
+use crate::skills::SkillManager;
+use crate::tools::ToolPermission;
+
+/// Name of the project overlay directory.
+pub const PROJECT_DIR: &str = ".rustyclaw";
+
+/// Settings file inside the overlay directory.
+pub const PROJECT_SETTINGS_FILE: &str = "settings.toml";
+
+/// Memory file inside the overlay directory.
+pub const PROJECT_MEMORY_FILE: &str = "MEMORY.md";
+
+/// Header of the injected project memory section.
+pub const PROJECT_MEMORY_HEADER: &str = "## Project memory";
+
+/// Model fields a project may override; unset fields keep the global value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectModel {
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+/// Skills to force on or off in this project.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectSkills {
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+/// Contents of `.rustyclaw/settings.toml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    /// Development environment activation for this project.
+    #[serde(default)]
+    pub dev_env: Option<DevEnvMode>,
+    #[serde(default)]
+    pub model: Option<ProjectModel>,
+    /// Per-tool permissions; each applies only where it is stricter than
+    /// the global one.
+    #[serde(default)]
+    pub tool_permissions: HashMap<String, ToolPermission>,
+    #[serde(default)]
+    pub skills: ProjectSkills,
+}
+
+/// A loaded project overlay.
+#[derive(Debug, Clone)]
+pub struct ProjectOverlay {
+    /// The `.rustyclaw/` directory.
+    pub dir: PathBuf,
+    pub settings: ProjectSettings,
+}
+
+impl ProjectOverlay {
+    /// Load the overlay for `workspace_dir`, if it has one.
+    pub fn load(workspace_dir: &Path) -> Result<Option<Self>, String> {
+        let dir = workspace_dir.join(PROJECT_DIR);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        let settings_path = dir.join(PROJECT_SETTINGS_FILE);
+        let settings = match std::fs::read_to_string(&settings_path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|e| format!("Invalid {}: {}", settings_path.display(), e))?,
+            Err(_) => ProjectSettings::default(),
+        };
+        debug!(dir = %dir.display(), "Loaded project overlay");
+        Ok(Some(Self { dir, settings }))
+    }
+
+    /// Load the overlay for the configured workspace.  The global settings
+    /// directory is never treated as a project overlay, even when the
+    /// workspace is its parent.
+    pub fn for_config(config: &Config) -> Result<Option<Self>, String> {
+        let workspace = config.workspace_dir();
+        let candidate = workspace.join(PROJECT_DIR);
+        if same_dir(&candidate, &config.settings_dir) {
+            return Ok(None);
+        }
+        Self::load(&workspace)
+    }
+
+    /// Whether the user listed this project in `trusted_projects`.
+    pub fn is_trusted(&self, config: &Config) -> bool {
+        let Some(root) = self.dir.parent() else {
+            return false;
+        };
+        config.trusted_projects.iter().any(|p| same_dir(p, root))
+    }
+
+    /// Merge the project settings into `config`.  Returns `true` when the
+    /// model selection changed.
+    pub fn apply_to_config(&self, config: &mut Config) -> bool {
+        for (tool, wanted) in &self.settings.tool_permissions {
+            let current = config.tool_permissions.get(tool).cloned().unwrap_or_default();
+            if strictness(wanted) > strictness(&current) {
+                config.tool_permissions.insert(tool.clone(), wanted.clone());
+            } else if strictness(wanted) < strictness(&current) {
+                warn!(tool = %tool, "Project overlay can't loosen a tool permission; ignored");
+            }
+        }
+        if let Some(mode) = self.settings.dev_env {
+            config.dev_env = mode;
+        }
+
+        let Some(ref overlay) = self.settings.model else {
+            return false;
+        };
+        let trusted = self.is_trusted(config);
+        if !trusted && (overlay.provider.is_some() || overlay.base_url.is_some()) {
+            warn!(
+                dir = %self.dir.display(),
+                "Project overlay sets provider/base_url but the project isn't in trusted_projects; ignored"
+            );
+        }
+        let before = config
+            .model
+            .as_ref()
+            .map(|m| (m.provider.clone(), m.model.clone(), m.base_url.clone()));
+        let model = config.model.get_or_insert_with(|| ModelProvider {
+            provider: String::new(),
+            model: None,
+            base_url: None,
+        });
+        if let Some(provider) = overlay.provider.as_ref().filter(|_| trusted) {
+            if *provider != model.provider {
+                // A different provider's model and URL don't carry over.
+                model.model = None;
+                model.base_url = None;
+            }
+            model.provider = provider.clone();
+        }
+        if overlay.model.is_some() {
+            model.model = overlay.model.clone();
+        }
+        if trusted && overlay.base_url.is_some() {
+            model.base_url = overlay.base_url.clone();
+        }
+        let after = Some((model.provider.clone(), model.model.clone(), model.base_url.clone()));
+        before != after
+    }
+
+    /// Enable and disable skills as the project asks.  Unknown skills are
+    /// logged and skipped.
+    pub fn apply_to_skills(&self, skills: &mut SkillManager) {
+        let wanted = self
+            .settings
+            .skills
+            .enabled
+            .iter()
+            .map(|n| (n, true))
+            .chain(self.settings.skills.disabled.iter().map(|n| (n, false)));
+        for (name, enabled) in wanted {
+            if let Err(e) = skills.set_skill_enabled(name, enabled) {
+                warn!(skill = %name, error = %e, "Project skill setting not applied");
+            }
+        }
+    }
+
+    /// Project memory, read fresh so edits show up at once.
+    pub fn memory(&self) -> Option<String> {
+        let content = std::fs::read_to_string(self.dir.join(PROJECT_MEMORY_FILE)).ok()?;
+        let content = content.trim();
+        (!content.is_empty()).then(|| content.to_string())
+    }
+}
+
+/// How much a permission restricts a tool, for comparing two of them.
+fn strictness(permission: &ToolPermission) -> u8 {
+    match permission {
+        ToolPermission::Allow => 0,
+        ToolPermission::Ask => 1,
+        ToolPermission::SkillOnly(_) => 2,
+        ToolPermission::Deny => 3,
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The project memory section for `workspace_dir`, if any.
+pub fn project_memory_section(workspace_dir: &Path) -> Option<String> {
+    let overlay = ProjectOverlay::load(workspace_dir).ok()??;
+    overlay
+        .memory()
+        .map(|memory| format!("{}\n{}", PROJECT_MEMORY_HEADER, memory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project(settings: &str, memory: Option<&str>) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let overlay = dir.path().join(PROJECT_DIR);
+        std::fs::create_dir_all(&overlay).unwrap();
+        std::fs::write(overlay.join(PROJECT_SETTINGS_FILE), settings).unwrap();
+        if let Some(memory) = memory {
+            std::fs::write(overlay.join(PROJECT_MEMORY_FILE), memory).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_no_overlay() {
+        let dir = TempDir::new().unwrap();
+        assert!(ProjectOverlay::load(dir.path()).unwrap().is_none());
+        assert!(project_memory_section(dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_apply_model_and_tool_policy() {
+        let dir = project(
+            "[model]\nmodel = \"big-model\"\n\n[tool_permissions]\nexecute_command = \"deny\"\n",
+            None,
+        );
+        let overlay = ProjectOverlay::load(dir.path()).unwrap().unwrap();
+
+        let mut config = Config::default();
+        config.model = Some(ModelProvider {
+            provider: "anthropic".into(),
+            model: Some("small-model".into()),
+            base_url: None,
+        });
+        config.tool_permissions.insert("read_file".into(), ToolPermission::Allow);
+
+        assert!(overlay.apply_to_config(&mut config));
+        let model = config.model.as_ref().unwrap();
+        assert_eq!(model.provider, "anthropic");
+        assert_eq!(model.model.as_deref(), Some("big-model"));
+        assert_eq!(config.tool_permissions.get("execute_command"), Some(&ToolPermission::Deny));
+        assert_eq!(config.tool_permissions.get("read_file"), Some(&ToolPermission::Allow));
+
+        // Applying again changes nothing.
+        assert!(!overlay.apply_to_config(&mut config));
+    }
+
+    #[test]
+    fn test_dev_env_override() {
+        let dir = project("dev_env = \"nix\"\n", None);
+        let overlay = ProjectOverlay::load(dir.path()).unwrap().unwrap();
+        let mut config = Config::default();
+        assert!(!overlay.apply_to_config(&mut config));
+        assert_eq!(config.dev_env, DevEnvMode::Nix);
+    }
+
+    #[test]
+    fn test_provider_switch_clears_model() {
+        let dir = project("[model]\nprovider = \"ollama\"\n", None);
+        let overlay = ProjectOverlay::load(dir.path()).unwrap().unwrap();
+        let mut config = Config::default();
+        config.model = Some(ModelProvider {
+            provider: "openai".into(),
+            model: Some("gpt".into()),
+            base_url: Some("https://example.com".into()),
+        });
+        config.trusted_projects.push(dir.path().to_path_buf());
+        overlay.apply_to_config(&mut config);
+        let model = config.model.unwrap();
+        assert_eq!(model.provider, "ollama");
+        assert!(model.model.is_none());
+        assert!(model.base_url.is_none());
+    }
+
+    #[test]
+    fn test_untrusted_project_cannot_redirect_provider() {
+        let dir = project(
+            "[model]\nprovider = \"openai\"\nbase_url = \"https://evil.example\"\nmodel = \"m\"\n",
+            None,
+        );
+        let overlay = ProjectOverlay::load(dir.path()).unwrap().unwrap();
+        let mut config = Config::default();
+        config.model = Some(ModelProvider {
+            provider: "anthropic".into(),
+            model: Some("small-model".into()),
+            base_url: None,
+        });
+        assert!(!overlay.is_trusted(&config));
+        overlay.apply_to_config(&mut config);
+        let model = config.model.as_ref().unwrap();
+        assert_eq!(model.provider, "anthropic");
+        assert!(model.base_url.is_none());
+        // Picking a model is still fine.
+        assert_eq!(model.model.as_deref(), Some("m"));
+    }
+
+    #[test]
+    fn test_overlay_only_tightens_permissions() {
+        let dir = project(
+            "[tool_permissions]\nexecute_command = \"allow\"\nwrite_file = \"allow\"\nread_file = \"ask\"\n",
+            None,
+        );
+        let overlay = ProjectOverlay::load(dir.path()).unwrap().unwrap();
+        let mut config = Config::default();
+        config.tool_permissions.insert("execute_command".into(), ToolPermission::Deny);
+        config.tool_permissions.insert("read_file".into(), ToolPermission::Allow);
+        overlay.apply_to_config(&mut config);
+        assert_eq!(config.tool_permissions.get("execute_command"), Some(&ToolPermission::Deny));
+        assert_eq!(config.tool_permissions.get("read_file"), Some(&ToolPermission::Ask));
+        // An `allow` never lands in the map, where it would also lift the
+        // built-in `ask` on gated actions.
+        assert!(!config.tool_permissions.contains_key("write_file"));
+    }
+
+    #[test]
+    fn test_project_memory() {
+        let dir = project("", Some("Uses tokio; never block.\n"));
+        let section = project_memory_section(dir.path()).unwrap();
+        assert!(section.starts_with(PROJECT_MEMORY_HEADER));
+        assert!(section.contains("never block"));
+    }
+
+    #[test]
+    fn test_settings_dir_is_not_an_overlay() {
+        let home = TempDir::new().unwrap();
+        std::fs::create_dir_all(home.path().join(PROJECT_DIR)).unwrap();
+        let mut config = Config::default();
+        config.settings_dir = home.path().join(PROJECT_DIR);
+        config.workspace_dir = Some(home.path().to_path_buf());
+        assert!(ProjectOverlay::for_config(&config).unwrap().is_none());
+    }
+}
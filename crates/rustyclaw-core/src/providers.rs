@@ -2,7 +2,10 @@
 //!
 //! Single source of truth for supported providers, their secret key names,
 //! base URLs, and available models.  Used by both the onboarding wizard and
-//! the TUI `/provider` + `/model` commands.
+//! the TUI `/provider` + `/model` commands.  The test-only `mock` provider
+//! is deliberately left out of the catalogue so it can't be picked there.
+
+use crate::gateway::mock_provider::MOCK_PROVIDER;
 
 /// Authentication method for a provider.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -305,6 +308,12 @@ pub async fn fetch_models(
     api_key: Option<&str>,
     base_url_override: Option<&str>,
 ) -> Result<Vec<String>, String> {
+    // The mock provider isn't listed in PROVIDERS (so it can't be picked in
+    // onboarding or /provider), and its base URL is a fixture path, not an API.
+    if provider_id == MOCK_PROVIDER {
+        return Ok(vec![MOCK_PROVIDER.to_string()]);
+    }
+
     let def = match provider_by_id(provider_id) {
         Some(d) => d,
         None => return Err(format!("Unknown provider: {}", provider_id)),
@@ -463,6 +472,200 @@ async fn fetch_google_models(
     Ok(models)
 }
 
+// ── API key checks ──────────────────────────────────────────────────────────
+
+/// The provider whose API key is stored under `secret_name`, if any.
+pub fn provider_for_secret_key(secret_name: &str) -> Option<&'static ProviderDef> {
+    PROVIDERS
+        .iter()
+        .find(|p| p.auth_method == AuthMethod::ApiKey && p.secret_key.is_some_and(|k| k.eq_ignore_ascii_case(secret_name)))
+}
+
+/// What a provider reported about a working API key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyCheck {
+    /// Number of models the key can see.
+    pub models: Option<usize>,
+    /// Organization or project the key belongs to.
+    pub organization: Option<String>,
+    /// Permission scopes, for providers that report them.
+    pub scopes: Vec<String>,
+    /// Remaining rate limit or credit, as the provider phrases it.
+    pub quota: Option<String>,
+}
+
+impl KeyCheck {
+    /// One-line report, e.g. "key OK · 42 models · org acme".
+    pub fn summary(&self) -> String {
+        let mut parts = vec!["key OK".to_string()];
+        if let Some(n) = self.models {
+            parts.push(format!("{} models", n));
+        }
+        if let Some(org) = &self.organization {
+            parts.push(format!("org {}", org));
+        }
+        if !self.scopes.is_empty() {
+            parts.push(format!("scopes {}", self.scopes.join(", ")));
+        }
+        if let Some(quota) = &self.quota {
+            parts.push(quota.clone());
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Why a key check didn't pass.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyCheckError {
+    /// The provider refused the key; storing it would only fail later.
+    Rejected(String),
+    /// The key couldn't be checked (network trouble, outage, rate limit).
+    Unverified(String),
+}
+
+impl std::fmt::Display for KeyCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyCheckError::Rejected(msg) | KeyCheckError::Unverified(msg) => f.write_str(msg),
+        }
+    }
+}
+
+/// Check `api_key` with the cheapest authenticated call the provider has
+/// (listing models) and report what it says about the key.  Providers
+/// without API-key auth pass without a call.
+pub async fn check_api_key(
+    provider_id: &str,
+    api_key: &str,
+    base_url_override: Option<&str>,
+) -> Result<KeyCheck, KeyCheckError> {
+    if provider_id == MOCK_PROVIDER {
+        return Ok(KeyCheck::default());
+    }
+    let def = provider_by_id(provider_id)
+        .ok_or_else(|| KeyCheckError::Unverified(format!("Unknown provider: {}", provider_id)))?;
+    if def.auth_method != AuthMethod::ApiKey {
+        return Ok(KeyCheck::default());
+    }
+    let key = api_key.trim();
+    if key.is_empty() {
+        return Err(KeyCheckError::Rejected("The API key is empty.".to_string()));
+    }
+    let Some(base) = base_url_override.or(def.base_url).map(|b| b.trim_end_matches('/')) else {
+        return Err(KeyCheckError::Unverified(format!(
+            "No base URL configured for {}, so the key wasn't checked.",
+            def.display
+        )));
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| KeyCheckError::Unverified(format!("Failed to create HTTP client: {}", e)))?;
+    let req = match provider_id {
+        "anthropic" => client
+            .get(format!("{}/v1/models?limit=1000", base))
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        "google" => client.get(format!("{}/models?pageSize=1000", base)).header("x-goog-api-key", key),
+        _ => client.get(format!("{}/models", base)).bearer_auth(key),
+    };
+    let resp = req.send().await.map_err(|e| {
+        KeyCheckError::Unverified(format!("Couldn't reach {} to check the key: {}", def.display, e))
+    })?;
+    let status = resp.status().as_u16();
+    let headers = resp.headers().clone();
+    let body: serde_json::Value = resp.json().await.unwrap_or_default();
+    if !(200..300).contains(&status) {
+        return Err(key_error(def, status, &body));
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let mut check = KeyCheck {
+        models: body
+            .get("data")
+            .or_else(|| body.get("models"))
+            .and_then(|m| m.as_array())
+            .map(|m| m.len()),
+        organization: header("openai-organization").or_else(|| header("anthropic-organization-id")),
+        scopes: header("x-oauth-scopes")
+            .map(|s| s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default(),
+        quota: rate_limit(&header),
+    };
+    if provider_id == "openrouter" {
+        if let Some(credit) = openrouter_credit(&client, base, key).await {
+            check.quota = Some(credit);
+        }
+    }
+    Ok(check)
+}
+
+/// "N/M requests left" from the usual rate-limit headers.
+fn rate_limit(header: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    [
+        ("x-ratelimit-remaining-requests", "x-ratelimit-limit-requests"),
+        ("anthropic-ratelimit-requests-remaining", "anthropic-ratelimit-requests-limit"),
+    ]
+    .iter()
+    .find_map(|(remaining, limit)| Some(format!("{}/{} requests left", header(remaining)?, header(limit)?)))
+}
+
+/// Remaining credit on an OpenRouter key.
+async fn openrouter_credit(client: &reqwest::Client, base: &str, key: &str) -> Option<String> {
+    let body: serde_json::Value = client
+        .get(format!("{}/key", base))
+        .bearer_auth(key)
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    let data = body.get("data")?;
+    let usage = data.get("usage").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    Some(match data.get("limit_remaining").and_then(|v| v.as_f64()) {
+        Some(left) => format!("${:.2} credit left (${:.2} used)", left, usage),
+        None => format!("no credit limit (${:.2} used)", usage),
+    })
+}
+
+/// Turn a failed key check into a message that says what to do.
+fn key_error(def: &ProviderDef, status: u16, body: &serde_json::Value) -> KeyCheckError {
+    let detail = body
+        .pointer("/error/message")
+        .or_else(|| body.get("error").filter(|e| e.is_string()))
+        .or_else(|| body.get("message"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("no details")
+        .trim()
+        .to_string();
+    let new_key = def
+        .help_url
+        .map(|url| format!(", or create a new one at {}", url))
+        .unwrap_or_default();
+    // Google reports a bad key as 400 INVALID_ARGUMENT.
+    let bad_key = status == 401 || (status == 400 && detail.to_lowercase().contains("api key"));
+    match status {
+        _ if bad_key => KeyCheckError::Rejected(format!(
+            "{} rejected this API key ({}). Check it was copied in full{}.",
+            def.display, detail, new_key
+        )),
+        403 => KeyCheckError::Rejected(format!(
+            "The key is valid but not allowed to list models on {} ({}). Check its permissions or project{}.",
+            def.display, detail, new_key
+        )),
+        402 | 429 => KeyCheckError::Unverified(format!(
+            "{} accepted the key but is refusing requests ({}). Check the account's billing and usage limits.",
+            def.display, detail
+        )),
+        _ => KeyCheckError::Unverified(format!(
+            "{} returned HTTP {} while checking the key ({}); it wasn't verified.",
+            def.display, status, detail
+        )),
+    }
+}
+
 // ── OAuth Device Flow ───────────────────────────────────────────────────────
 
 use serde::Deserialize;
@@ -629,6 +832,19 @@ pub fn needs_copilot_session(provider_id: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_mock_provider_is_not_listed() {
+        assert!(provider_by_id(MOCK_PROVIDER).is_none());
+        assert!(!provider_ids().contains(&MOCK_PROVIDER));
+        assert!(!all_model_names().contains(&"mock"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_still_resolves() {
+        assert_eq!(fetch_models(MOCK_PROVIDER, None, None).await.unwrap(), vec!["mock"]);
+        assert!(check_api_key(MOCK_PROVIDER, "", None).await.is_ok());
+    }
+
     #[test]
     fn test_provider_by_id() {
         let provider = provider_by_id("anthropic");
@@ -762,4 +978,37 @@ mod tests {
         assert!(resp.token.starts_with("tid="));
         assert_eq!(resp.expires_at, 1750000000);
     }
+
+    #[test]
+    fn test_key_check_errors() {
+        let openai = provider_by_id("openai").unwrap();
+        let body = serde_json::json!({ "error": { "message": "Incorrect API key provided" } });
+        match key_error(openai, 401, &body) {
+            KeyCheckError::Rejected(msg) => {
+                assert!(msg.contains("Incorrect API key provided"));
+                assert!(msg.contains("platform.openai.com/api-keys"));
+            }
+            other => panic!("expected Rejected, got {:?}", other),
+        }
+
+        let google = provider_by_id("google").unwrap();
+        let body = serde_json::json!({ "error": { "message": "API key not valid. Please pass a valid API key." } });
+        assert!(matches!(key_error(google, 400, &body), KeyCheckError::Rejected(_)));
+        assert!(matches!(key_error(openai, 429, &body), KeyCheckError::Unverified(_)));
+        assert!(matches!(key_error(openai, 503, &serde_json::Value::Null), KeyCheckError::Unverified(_)));
+    }
+
+    #[test]
+    fn test_key_check_summary() {
+        assert_eq!(provider_for_secret_key("anthropic_api_key").map(|p| p.id), Some("anthropic"));
+        assert!(provider_for_secret_key("GITHUB_COPILOT_TOKEN").is_none());
+
+        let check = KeyCheck {
+            models: Some(42),
+            organization: Some("acme".into()),
+            scopes: vec![],
+            quota: Some("99/100 requests left".into()),
+        };
+        assert_eq!(check.summary(), "key OK · 42 models · org acme · 99/100 requests left");
+    }
 }
@@ -0,0 +1,272 @@
+//! Quiet hours: per-messenger do-not-disturb windows.
+//!
+//! A messenger with `quiet_hours` set doesn't get unsolicited messages in
+//! that window.  Messages the agent, a rule or a template would send through
+//! the `message` tool are held in `.outbox/held.json` instead, and the
+//! gateway delivers them once the window ends.  Messages flagged `urgent`
+//! (by the user via the tool's `urgent` parameter, or by a rule) go out
+//! immediately.  Replies in a conversation the user started are never held.
+//!
+//! ```toml
+//! [[messengers]]
+//! name = "telegram"
+//! messenger_type = "telegram"
+//! quiet_hours = "22:00-07:30"
+//! ```
+//!
+//! Windows are local time and may wrap past midnight; one that can't be
+//! parsed is ignored (messages are sent) and logged at startup.
+
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::config::MessengerConfig;
+use crate::heartbeat::{parse_active_hours, within_active_hours};
+
+/// How often the gateway checks for held messages that can go out.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A message waiting for a quiet window to end.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeldMessage {
+    pub channel: String,
+    pub target: String,
+    pub message: String,
+    /// RFC 3339 time it was held.
+    pub held_at: String,
+}
+
+/// File held messages are kept in.
+pub fn held_path(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".outbox").join("held.json")
+}
+
+/// Quiet windows by channel, from the messenger configs.  Both the
+/// messenger type and its name match, case-insensitively.
+pub fn windows(messengers: &[MessengerConfig]) -> HashMap<String, String> {
+    let mut windows = HashMap::new();
+    for m in messengers.iter().filter(|m| m.enabled) {
+        let Some(window) = m.quiet_hours.as_ref().filter(|w| !w.trim().is_empty()) else {
+            continue;
+        };
+        if let Err(e) = parse_active_hours(window) {
+            warn!(messenger = %m.name, error = %e, "Ignoring quiet_hours");
+            continue;
+        }
+        for key in [&m.messenger_type, &m.name] {
+            if !key.is_empty() {
+                windows.insert(key.to_lowercase(), window.clone());
+            }
+        }
+    }
+    windows
+}
+
+/// End of the quiet window `channel` is in at `now`, if it is in one.
+pub fn quiet_until_at(windows: &HashMap<String, String>, channel: &str, now: NaiveTime) -> Option<NaiveTime> {
+    let window = windows.get(&channel.to_lowercase())?;
+    if !within_active_hours(Some(window), now) {
+        return None;
+    }
+    parse_active_hours(window).ok().map(|(_, end)| end)
+}
+
+/// Split `held` into messages that may go out at `now` and those still
+/// inside their channel's quiet window.  Order is kept.
+pub fn split_due(
+    held: Vec<HeldMessage>,
+    windows: &HashMap<String, String>,
+    now: NaiveTime,
+) -> (Vec<HeldMessage>, Vec<HeldMessage>) {
+    held.into_iter()
+        .partition(|m| quiet_until_at(windows, &m.channel, now).is_none())
+}
+
+pub fn load_held(path: &Path) -> Vec<HeldMessage> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_held(path: &Path, held: &[HeldMessage]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+    let text = serde_json::to_string_pretty(held).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// ── Gateway state ───────────────────────────────────────────────────────────
+
+struct State {
+    windows: HashMap<String, String>,
+    workspace_dir: PathBuf,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Register the messengers' quiet windows.  Called at startup and on
+/// reload.
+pub fn set_config(messengers: &[MessengerConfig], workspace_dir: &Path) {
+    let windows = windows(messengers);
+    debug!(channels = windows.len(), "Setting quiet hours");
+    if let Ok(mut guard) = STATE.lock() {
+        *guard = Some(State { windows, workspace_dir: workspace_dir.to_path_buf() });
+    }
+}
+
+/// End of the quiet window `channel` is in right now, if any.
+pub fn quiet_until(channel: &str) -> Option<NaiveTime> {
+    let guard = STATE.lock().ok()?;
+    let state = guard.as_ref()?;
+    quiet_until_at(&state.windows, channel, Local::now().time())
+}
+
+/// Hold a message until `channel`'s quiet window ends.  Returns the text
+/// the tool reports back.
+pub fn hold(channel: &str, target: &str, message: &str, until: NaiveTime) -> Result<String, String> {
+    let guard = STATE.lock().map_err(|_| "Quiet hours state is poisoned".to_string())?;
+    let state = guard.as_ref().ok_or("Quiet hours are not configured")?;
+    let path = held_path(&state.workspace_dir);
+    let mut held = load_held(&path);
+    held.push(HeldMessage {
+        channel: channel.to_string(),
+        target: target.to_string(),
+        message: message.to_string(),
+        held_at: Local::now().to_rfc3339(),
+    });
+    save_held(&path, &held)?;
+    info!(channel, target, "Holding message for quiet hours");
+    Ok(format!(
+        "Quiet hours on {} — message to {} held and will be delivered at {}. \
+         Send again with urgent=true if it can't wait.",
+        channel,
+        target,
+        until.format("%H:%M")
+    ))
+}
+
+/// Remove and return the held messages whose window has ended.
+fn take_due() -> Vec<HeldMessage> {
+    let Ok(guard) = STATE.lock() else {
+        return Vec::new();
+    };
+    let Some(state) = guard.as_ref() else {
+        return Vec::new();
+    };
+    let path = held_path(&state.workspace_dir);
+    let held = load_held(&path);
+    if held.is_empty() {
+        return Vec::new();
+    }
+    let (due, still_held) = split_due(held, &state.windows, Local::now().time());
+    if !due.is_empty() {
+        if let Err(e) = save_held(&path, &still_held) {
+            // Better to hold them another round than to send twice.
+            warn!(error = %e, "Failed to update held messages");
+            return Vec::new();
+        }
+    }
+    due
+}
+
+/// Deliver held messages as their quiet windows end, until cancelled.
+pub async fn run_quiet_hours_loop(cancel: CancellationToken) {
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep(CHECK_INTERVAL) => {
+                let due = take_due();
+                if due.is_empty() {
+                    continue;
+                }
+                info!(count = due.len(), "Delivering messages held for quiet hours");
+                let workspace_dir = STATE
+                    .lock()
+                    .ok()
+                    .and_then(|g| g.as_ref().map(|s| s.workspace_dir.clone()))
+                    .unwrap_or_default();
+                let _ = tokio::task::spawn_blocking(move || {
+                    for held in due {
+                        let args = json!({
+                            "action": "send",
+                            "message": held.message,
+                            "target": held.target,
+                            "channel": held.channel,
+                            // Already waited; don't hold again.
+                            "urgent": true,
+                        });
+                        if let Err(e) = crate::tools::execute_tool("message", &args, &workspace_dir) {
+                            warn!(channel = %held.channel, error = %e, "Failed to deliver held message");
+                        }
+                    }
+                })
+                .await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn messenger(name: &str, quiet_hours: &str) -> MessengerConfig {
+        MessengerConfig {
+            name: name.into(),
+            messenger_type: "telegram".into(),
+            enabled: true,
+            quiet_hours: Some(quiet_hours.into()),
+            ..Default::default()
+        }
+    }
+
+    fn held(channel: &str) -> HeldMessage {
+        HeldMessage {
+            channel: channel.into(),
+            target: "123".into(),
+            message: "hi".into(),
+            held_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_window_wraps_midnight() {
+        let windows = windows(&[messenger("family", "22:00-07:30")]);
+        assert_eq!(quiet_until_at(&windows, "Telegram", time(23, 0)), Some(time(7, 30)));
+        assert_eq!(quiet_until_at(&windows, "family", time(6, 0)), Some(time(7, 30)));
+        assert_eq!(quiet_until_at(&windows, "telegram", time(12, 0)), None);
+        assert_eq!(quiet_until_at(&windows, "discord", time(23, 0)), None);
+    }
+
+    #[test]
+    fn test_invalid_window_is_ignored() {
+        assert!(windows(&[messenger("x", "late")]).is_empty());
+    }
+
+    #[test]
+    fn test_split_due_and_persistence() {
+        let windows = windows(&[messenger("telegram", "22:00-07:00")]);
+        let (due, still) = split_due(vec![held("telegram"), held("discord")], &windows, time(23, 0));
+        assert_eq!(due, vec![held("discord")]);
+        assert_eq!(still, vec![held("telegram")]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = held_path(dir.path());
+        assert!(load_held(&path).is_empty());
+        save_held(&path, &still).unwrap();
+        assert_eq!(load_held(&path), still);
+    }
+}
@@ -0,0 +1,587 @@
+//! Record-and-replay of agent sessions.
+//!
+//! In recording mode the gateway appends every provider call (the request
+//! messages and the model's response) and every tool result to a JSONL
+//! file.  A recording can later be replayed: the recorded model responses
+//! stand in for the provider, while the tools are executed again — in a
+//! scratch copy of the workspace, so the real one is left alone — and their
+//! output compared with what was recorded.  Calls that would reach outside
+//! the copy (messages, commands, devices, schedules) are answered from the
+//! recording instead.  This makes it possible to
+//! regression-test prompt and tool changes without spending tokens.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::gateway::{ChatMessage, ModelResponse, ParsedToolCall, ProviderRequest};
+use crate::tools;
+
+/// Format version written to the recording header.
+pub const RECORDING_VERSION: u32 = 1;
+
+/// Get current time in milliseconds.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A single record in a session recording.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RecordEntry {
+    /// First line of every recording.
+    Header { version: u32, started_ms: u64 },
+    /// A provider call.  API keys are never recorded.
+    ModelCall {
+        provider: String,
+        model: String,
+        messages: Vec<ChatMessage>,
+        response: ModelResponse,
+    },
+    /// A tool executed in response to the preceding model call.
+    ToolResult {
+        id: String,
+        name: String,
+        arguments: Value,
+        output: String,
+        is_error: bool,
+    },
+}
+
+// ── Recording ───────────────────────────────────────────────────────────────
+
+/// Appends provider calls and tool results to a recording file.
+pub struct SessionRecorder {
+    path: PathBuf,
+}
+
+impl SessionRecorder {
+    /// Create (or truncate) a recording file and write its header.
+    pub fn create(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create recording directory: {}", e))?;
+        }
+        fs::write(path, "").map_err(|e| format!("Failed to create recording: {}", e))?;
+        let recorder = Self {
+            path: path.to_path_buf(),
+        };
+        recorder.append(&RecordEntry::Header {
+            version: RECORDING_VERSION,
+            started_ms: now_millis(),
+        })?;
+        Ok(recorder)
+    }
+
+    /// Path of the recording file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a provider call and its response.
+    pub fn record_model_call(&self, req: &ProviderRequest, resp: &ModelResponse) -> Result<(), String> {
+        // Built by hand so the response is serialized by reference.
+        let line = serde_json::json!({
+            "kind": "modelCall",
+            "provider": req.provider,
+            "model": req.model,
+            "messages": req.messages,
+            "response": resp,
+        });
+        self.append_line(&line.to_string())
+    }
+
+    /// Record the result of a tool call.
+    pub fn record_tool_result(
+        &self,
+        call: &ParsedToolCall,
+        output: &str,
+        is_error: bool,
+    ) -> Result<(), String> {
+        self.append(&RecordEntry::ToolResult {
+            id: call.id.clone(),
+            name: call.name.clone(),
+            arguments: call.arguments.clone(),
+            output: output.to_string(),
+            is_error,
+        })
+    }
+
+    fn append(&self, entry: &RecordEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize recording entry: {}", e))?;
+        self.append_line(&line)
+    }
+
+    fn append_line(&self, line: &str) -> Result<(), String> {
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open recording: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write recording: {}", e))
+    }
+}
+
+/// Global recorder, set once at gateway startup when `--record` is given.
+static RECORDER: OnceLock<SessionRecorder> = OnceLock::new();
+
+/// Start recording all agent turns to `path`.
+pub fn start_recording(path: &Path) -> Result<(), String> {
+    let recorder = SessionRecorder::create(path)?;
+    RECORDER
+        .set(recorder)
+        .map_err(|_| "Recording already started".to_string())
+}
+
+/// The active recorder, if recording is enabled.
+pub fn recorder() -> Option<&'static SessionRecorder> {
+    RECORDER.get()
+}
+
+// ── Loading ─────────────────────────────────────────────────────────────────
+
+/// A recorded tool result.
+#[derive(Debug, Clone)]
+pub struct RecordedTool {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+    pub output: String,
+    pub is_error: bool,
+}
+
+/// A recorded provider call with the tool results that followed it.
+#[derive(Debug)]
+pub struct RecordedStep {
+    pub provider: String,
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub response: ModelResponse,
+    pub tools: Vec<RecordedTool>,
+}
+
+/// A session recording loaded from disk.
+#[derive(Debug)]
+pub struct Recording {
+    pub started_ms: u64,
+    pub steps: Vec<RecordedStep>,
+}
+
+impl Recording {
+    /// Load a recording file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read recording: {}", e))?;
+
+        let mut started_ms = 0;
+        let mut steps: Vec<RecordedStep> = Vec::new();
+
+        for (lineno, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: RecordEntry = serde_json::from_str(line)
+                .map_err(|e| format!("Invalid recording entry on line {}: {}", lineno + 1, e))?;
+            match entry {
+                RecordEntry::Header { version, started_ms: ms } => {
+                    if version > RECORDING_VERSION {
+                        return Err(format!(
+                            "Unsupported recording version {} (expected {} or older)",
+                            version, RECORDING_VERSION
+                        ));
+                    }
+                    started_ms = ms;
+                }
+                RecordEntry::ModelCall {
+                    provider,
+                    model,
+                    messages,
+                    response,
+                } => steps.push(RecordedStep {
+                    provider,
+                    model,
+                    messages,
+                    response,
+                    tools: Vec::new(),
+                }),
+                RecordEntry::ToolResult {
+                    id,
+                    name,
+                    arguments,
+                    output,
+                    is_error,
+                } => {
+                    let step = steps.last_mut().ok_or_else(|| {
+                        format!("Tool result before any model call on line {}", lineno + 1)
+                    })?;
+                    step.tools.push(RecordedTool {
+                        id,
+                        name,
+                        arguments,
+                        output,
+                        is_error,
+                    });
+                }
+            }
+        }
+
+        Ok(Self { started_ms, steps })
+    }
+
+    /// Total number of recorded tool calls.
+    pub fn tool_call_count(&self) -> usize {
+        self.steps.iter().map(|s| s.tools.len()).sum()
+    }
+}
+
+// ── Replay ──────────────────────────────────────────────────────────────────
+
+/// A tool whose replayed result differs from the recording.
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    /// Index of the model call (0-based) that requested the tool.
+    pub step: usize,
+    pub tool: String,
+    pub id: String,
+    pub expected: String,
+    pub actual: String,
+    pub expected_error: bool,
+    pub actual_error: bool,
+}
+
+/// Outcome of replaying a recording.
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub model_calls: usize,
+    pub tool_calls: usize,
+    pub matched: usize,
+    /// Tools that need a live gateway (secrets, skills, prompts) or would
+    /// act outside the scratch workspace, answered from the recording
+    /// instead of being executed.
+    pub skipped: usize,
+    /// Tool calls in a model response with no recorded result.
+    pub missing: Vec<String>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// True when every replayed tool produced its recorded result.
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty() && self.missing.is_empty()
+    }
+
+    /// Human-readable summary.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "Replayed {} model call(s), {} tool call(s): {} matched, {} skipped, {} diverged, {} missing\n",
+            self.model_calls,
+            self.tool_calls,
+            self.matched,
+            self.skipped,
+            self.divergences.len(),
+            self.missing.len()
+        );
+        for d in &self.divergences {
+            out.push_str(&format!("\n✗ step {} — {} ({})\n", d.step + 1, d.tool, d.id));
+            if d.expected_error != d.actual_error {
+                out.push_str(&format!(
+                    "  error flag: expected {}, got {}\n",
+                    d.expected_error, d.actual_error
+                ));
+            }
+            out.push_str(&format!("  expected: {}\n", preview(&d.expected)));
+            out.push_str(&format!("  actual:   {}\n", preview(&d.actual)));
+        }
+        for id in &self.missing {
+            out.push_str(&format!("\n? no recorded result for tool call {}\n", id));
+        }
+        out
+    }
+}
+
+/// First line of a tool output, truncated for display.
+fn preview(s: &str) -> String {
+    let first = s.lines().next().unwrap_or("");
+    let mut p: String = first.chars().take(120).collect();
+    if p.len() < first.len() || s.lines().count() > 1 {
+        p.push('…');
+    }
+    p
+}
+
+/// Replace `from` with `to` in every string of a JSON value.
+fn rebase(value: &Value, from: &str, to: &str) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.replace(from, to)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| rebase(v, from, to)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter().map(|(k, v)| (k.clone(), rebase(v, from, to))).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Replay a recording against the tools in `workspace_dir`.
+///
+/// Each recorded model response is taken as-is; every tool call it makes
+/// is executed again and compared with the recorded output.  Tools run in
+/// a scratch copy of `workspace_dir` that is removed afterwards, with
+/// paths mapped between the two so outputs still compare equal.  Runs the
+/// tools synchronously, so call it from the blocking pool inside a runtime.
+pub fn replay(recording: &Recording, workspace_dir: &Path) -> Result<ReplayReport, String> {
+    let scratch = std::env::temp_dir().join(format!(
+        "rustyclaw-replay-{}-{}",
+        std::process::id(),
+        now_millis()
+    ));
+    let copied = if workspace_dir.exists() {
+        crate::trash::copy_tree(workspace_dir, &scratch)
+    } else {
+        fs::create_dir_all(&scratch)
+    };
+    copied.map_err(|e| format!("Failed to copy {} for replay: {}", workspace_dir.display(), e))?;
+
+    let report = replay_in(recording, workspace_dir, &scratch);
+    let _ = fs::remove_dir_all(&scratch);
+    Ok(report)
+}
+
+/// Mutating tools whose effects land in the workspace, so replay can run
+/// them against the scratch copy.
+const SCRATCH_TOOLS: &[&str] = &[
+    "write_file",
+    "edit_file",
+    "delete_file",
+    "apply_patch",
+    "plot",
+    "xlsx_write",
+    "render_report",
+    "qr",
+];
+
+/// Whether replaying a call only reads, or only changes files inside
+/// `scratch`.  Anything else — sending a message, running a command,
+/// publishing to MQTT, waking a node — must not happen again.
+fn stays_in_scratch(name: &str, args: &Value, scratch: &Path) -> bool {
+    if !tools::is_mutating(name, args, scratch) {
+        return true;
+    }
+    // Restoring from the trash writes back to wherever the file came from.
+    if !SCRATCH_TOOLS.contains(&name) || args.get("action").and_then(|v| v.as_str()) == Some("restore") {
+        return false;
+    }
+    ["path", "output"]
+        .iter()
+        .filter_map(|key| args.get(*key).and_then(|v| v.as_str()))
+        .all(|p| tools::resolve_path(scratch, &tools::expand_tilde(p).to_string_lossy()).starts_with(scratch))
+}
+
+fn replay_in(recording: &Recording, workspace_dir: &Path, scratch: &Path) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let real = workspace_dir.to_string_lossy();
+    let copy = scratch.to_string_lossy();
+
+    for (step_idx, step) in recording.steps.iter().enumerate() {
+        report.model_calls += 1;
+
+        for tc in &step.response.tool_calls {
+            report.tool_calls += 1;
+
+            let Some(recorded) = step.tools.iter().find(|t| t.id == tc.id) else {
+                report.missing.push(tc.id.clone());
+                continue;
+            };
+
+            // These need the gateway's vault, skill manager or a user.
+            if tools::is_user_prompt_tool(&tc.name)
+                || tools::is_secrets_tool(&tc.name)
+                || tools::is_skill_tool(&tc.name)
+            {
+                report.skipped += 1;
+                continue;
+            }
+
+            let arguments = rebase(&tc.arguments, &real, &copy);
+            if !stays_in_scratch(&tc.name, &arguments, scratch) {
+                report.skipped += 1;
+                continue;
+            }
+            let (actual, actual_error) = match tools::execute_tool(&tc.name, &arguments, scratch) {
+                Ok(text) => (tools::sanitize_tool_output(text.replace(&*copy, &real)), false),
+                Err(err) => (tools::sanitize_tool_output(err.replace(&*copy, &real)), true),
+            };
+
+            if actual == recorded.output && actual_error == recorded.is_error {
+                report.matched += 1;
+            } else {
+                report.divergences.push(Divergence {
+                    step: step_idx,
+                    tool: tc.name.clone(),
+                    id: tc.id.clone(),
+                    expected: recorded.output.clone(),
+                    actual,
+                    expected_error: recorded.is_error,
+                    actual_error,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn read_call(id: &str, path: &str) -> ParsedToolCall {
+        ParsedToolCall {
+            id: id.into(),
+            name: "read_file".into(),
+            arguments: json!({ "path": path }),
+        }
+    }
+
+    /// Record one model call that reads a file, with the real tool output.
+    fn record_session(dir: &Path, workspace: &Path) -> PathBuf {
+        let path = dir.join("session.jsonl");
+        let recorder = SessionRecorder::create(&path).unwrap();
+
+        let req = ProviderRequest {
+            messages: vec![ChatMessage::text("user", "show notes.txt")],
+            model: "test-model".into(),
+            provider: "openai".into(),
+            base_url: String::new(),
+            api_key: Some("sk-secret".into()),
+            tools: None,
+        };
+        let call = read_call("call_1", "notes.txt");
+        let resp = ModelResponse {
+            tool_calls: vec![call.clone()],
+            ..Default::default()
+        };
+        recorder.record_model_call(&req, &resp).unwrap();
+
+        let output = tools::execute_tool(&call.name, &call.arguments, workspace).unwrap();
+        recorder
+            .record_tool_result(&call, &tools::sanitize_tool_output(output), false)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+        let path = record_session(dir.path(), dir.path());
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("sk-secret"));
+
+        let recording = Recording::load(&path).unwrap();
+        assert_eq!(recording.steps.len(), 1);
+        assert_eq!(recording.steps[0].model, "test-model");
+        assert_eq!(recording.tool_call_count(), 1);
+        assert_eq!(recording.steps[0].tools[0].name, "read_file");
+    }
+
+    #[test]
+    fn test_replay_matches_unchanged_workspace() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+        let path = record_session(dir.path(), dir.path());
+
+        let report = replay(&Recording::load(&path).unwrap(), dir.path()).unwrap();
+        assert!(report.is_clean(), "{}", report.summary());
+        assert_eq!(report.matched, 1);
+    }
+
+    #[test]
+    fn test_replay_leaves_workspace_untouched() {
+        let dir = TempDir::new().unwrap();
+        let workspace = dir.path().join("workspace");
+        fs::create_dir_all(&workspace).unwrap();
+        let target = workspace.join("out.txt");
+
+        let path = dir.path().join("session.jsonl");
+        let recorder = SessionRecorder::create(&path).unwrap();
+        let req = ProviderRequest {
+            messages: vec![ChatMessage::text("user", "write out.txt")],
+            model: "test-model".into(),
+            provider: "openai".into(),
+            base_url: String::new(),
+            api_key: None,
+            tools: None,
+        };
+        let call = ParsedToolCall {
+            id: "call_1".into(),
+            name: "write_file".into(),
+            arguments: json!({ "path": target.to_string_lossy(), "content": "replayed" }),
+        };
+        let resp = ModelResponse {
+            tool_calls: vec![call.clone()],
+            ..Default::default()
+        };
+        recorder.record_model_call(&req, &resp).unwrap();
+        let output = tools::execute_tool(&call.name, &call.arguments, &workspace).unwrap();
+        recorder
+            .record_tool_result(&call, &tools::sanitize_tool_output(output), false)
+            .unwrap();
+        fs::remove_file(&target).unwrap();
+
+        let report = replay(&Recording::load(&path).unwrap(), &workspace).unwrap();
+        assert!(report.is_clean(), "{}", report.summary());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_replay_does_not_repeat_outside_effects() {
+        let dir = TempDir::new().unwrap();
+        let scratch = dir.path();
+        let call = |name: &str, args: Value| stays_in_scratch(name, &args, scratch);
+
+        assert!(call("read_file", json!({ "path": "notes.txt" })));
+        assert!(call("write_file", json!({ "path": "out.txt", "content": "x" })));
+        assert!(!call("write_file", json!({ "path": "../elsewhere.txt", "content": "x" })));
+        assert!(!call("execute_command", json!({ "command": "touch /tmp/x" })));
+        assert!(!call("message", json!({ "action": "send", "target": "alice", "message": "hi" })));
+        assert!(!call("mqtt", json!({ "action": "publish", "topic": "lights/on" })));
+        assert!(!call("nodes", json!({ "action": "wake", "node": "nas" })));
+        assert!(!call("delete_file", json!({ "action": "restore" })));
+    }
+
+    #[test]
+    fn test_replay_reports_divergence() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+        let path = record_session(dir.path(), dir.path());
+
+        fs::write(dir.path().join("notes.txt"), "goodbye").unwrap();
+        let report = replay(&Recording::load(&path).unwrap(), dir.path()).unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.divergences.len(), 1);
+        assert_eq!(report.divergences[0].tool, "read_file");
+        assert!(report.summary().contains("diverged"));
+    }
+
+    #[test]
+    fn test_tool_result_before_model_call_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.jsonl");
+        fs::write(
+            &path,
+            r#"{"kind":"toolResult","id":"x","name":"read_file","arguments":{},"output":"","is_error":false}"#,
+        )
+        .unwrap();
+        assert!(Recording::load(&path).is_err());
+    }
+}
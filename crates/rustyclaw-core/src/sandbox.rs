@@ -204,6 +204,109 @@ impl SandboxPolicy {
     }
 }
 
+// ── Write Policy ────────────────────────────────────────────────────────────
+
+/// Where tools may create, modify or delete files.
+///
+/// Checked by the file tools and `execute_command` before they touch the
+/// filesystem, independently of the command sandbox mode.  Patterns are
+/// globs where `*` matches any run of characters (including `/`); a
+/// pattern without `*` matches that path and everything below it.
+/// Relative patterns are taken from the workspace and `~` expands to the
+/// home directory.
+#[derive(Debug, Clone, Default)]
+pub struct WritePolicy {
+    /// Block writes outside the workspace and the temp directory unless
+    /// they match an `allow` pattern.
+    pub confine: bool,
+    /// Extra places writes are allowed.
+    pub allow: Vec<String>,
+    /// Places writes are never allowed, even inside the workspace.
+    pub deny: Vec<String>,
+}
+
+impl WritePolicy {
+    /// Check a write to `path` against the policy.
+    pub fn check(&self, path: &Path, workspace: &Path) -> Result<(), String> {
+        let target = real_path(path);
+        if let Some(pattern) = self.deny.iter().find(|p| pattern_matches(p, &target, workspace)) {
+            return Err(format!(
+                "Sandbox policy: {} matches deny_write pattern '{}'.",
+                path.display(),
+                pattern
+            ));
+        }
+        if !self.confine
+            || target.starts_with(real_path(workspace))
+            || target.starts_with(real_path(&std::env::temp_dir()))
+            || self.allow.iter().any(|p| pattern_matches(p, &target, workspace))
+        {
+            return Ok(());
+        }
+        Err(format!(
+            "Sandbox policy: writes outside the workspace are blocked ({}). \
+             Write inside the workspace, or add a glob to [sandbox] allow_write in config.toml to permit it.",
+            path.display()
+        ))
+    }
+}
+
+fn pattern_matches(pattern: &str, target: &Path, workspace: &Path) -> bool {
+    let expanded = match pattern.strip_prefix('~') {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest.trim_start_matches('/')),
+            None => return false,
+        },
+        None => workspace.join(pattern),
+    };
+    if pattern.contains('*') {
+        let full = normalize(&expanded);
+        crate::wildcard::glob_match(&full.to_string_lossy(), &target.to_string_lossy())
+            || crate::wildcard::glob_match(&real_path(&full).to_string_lossy(), &target.to_string_lossy())
+    } else {
+        target.starts_with(real_path(&expanded))
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+pub fn normalize(path: &Path) -> PathBuf {
+    use std::path::Component;
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push(component);
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Where `path` really points: `..` resolved and symlinks followed for the
+/// part of the path that exists, so a link inside the workspace can't be
+/// used to write outside it.
+pub fn real_path(path: &Path) -> PathBuf {
+    let path = normalize(path);
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canon) = existing.canonicalize() {
+            return rest.iter().rev().fold(canon, |p, part| p.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
 // ── Sandbox Mode ────────────────────────────────────────────────────────────
 
 /// Sandbox mode for command execution.
@@ -753,6 +856,48 @@ pub fn extract_paths_from_command(command: &str) -> Vec<PathBuf> {
     paths
 }
 
+/// Files a shell command writes through `>`, `>>` or `tee`.
+///
+/// Like [`extract_paths_from_command`] this is a best-effort scan; it
+/// catches the common ways a model writes files from the shell, not
+/// deliberate obfuscation.
+pub fn redirect_targets(command: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let tokens: Vec<&str> = command.split_whitespace().collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        // `>file`, `>>file`, `2>file`, `&>file`; `>&2` duplicates a descriptor.
+        let redirect = token
+            .trim_start_matches(|c: char| c.is_ascii_digit() || c == '&')
+            .strip_prefix('>')
+            .map(|rest| rest.strip_prefix('>').unwrap_or(rest));
+        if let Some(rest) = redirect.filter(|r| !r.starts_with('&')) {
+            let target = if rest.is_empty() {
+                i += 1;
+                tokens.get(i).copied().unwrap_or("")
+            } else {
+                rest
+            };
+            targets.push(target.to_string());
+        } else if token == "tee" {
+            targets.extend(
+                tokens[i + 1..]
+                    .iter()
+                    .take_while(|t| !matches!(**t, "|" | ";" | "&&" | "||"))
+                    .filter(|t| !t.starts_with('-'))
+                    .map(|t| t.to_string()),
+            );
+        }
+        i += 1;
+    }
+    targets
+        .into_iter()
+        .map(|t| t.trim_matches(|c| c == '"' || c == '\'' || c == ';').to_string())
+        .filter(|t| !t.is_empty() && t != "/dev/null" && !t.starts_with("/dev/std") && !t.starts_with('&'))
+        .collect()
+}
+
 fn run_with_path_validation(command: &str, policy: &SandboxPolicy) -> Result<std::process::Output, String> {
     // Extract explicit paths from command
     let paths = extract_paths_from_command(command);
@@ -1291,4 +1436,50 @@ mod tests {
             assert!(!result.unwrap_err().contains("Access denied"));
         }
     }
+
+    #[test]
+    fn test_write_policy_confines_to_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path().join("ws");
+        std::fs::create_dir_all(ws.join("src")).unwrap();
+        let policy = WritePolicy {
+            confine: true,
+            allow: vec!["~/notes".into()],
+            deny: vec![".git".into(), "*.env".into()],
+        };
+
+        assert!(policy.check(&ws.join("src/new/lib.rs"), &ws).is_ok());
+        assert!(policy.check(&std::env::temp_dir().join("scratch.txt"), &ws).is_ok());
+        let escape = policy.check(&ws.join("../../../../../../../../etc/passwd"), &ws).unwrap_err();
+        assert!(escape.contains("outside the workspace"));
+        assert!(policy.check(Path::new("/etc/passwd"), &ws).is_err());
+        assert!(policy.check(&dirs::home_dir().unwrap().join("notes/today.md"), &ws).is_ok());
+        assert!(policy.check(&ws.join(".git/config"), &ws).unwrap_err().contains("deny_write"));
+        assert!(policy.check(&ws.join("config/prod.env"), &ws).is_err());
+
+        let open = WritePolicy { confine: false, ..policy };
+        assert!(open.check(Path::new("/etc/passwd"), &ws).is_ok());
+        assert!(open.check(&ws.join("prod.env"), &ws).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_policy_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path().join("ws");
+        std::fs::create_dir_all(&ws).unwrap();
+        std::os::unix::fs::symlink("/usr", ws.join("link")).unwrap();
+
+        let policy = WritePolicy { confine: true, ..Default::default() };
+        assert!(policy.check(&ws.join("link/file.txt"), &ws).is_err());
+    }
+
+    #[test]
+    fn test_redirect_targets() {
+        assert_eq!(redirect_targets("echo hi > out.txt"), vec!["out.txt"]);
+        assert_eq!(redirect_targets("ls >>/tmp/log 2>&1"), vec!["/tmp/log"]);
+        assert_eq!(redirect_targets("make 2> err.log >/dev/null"), vec!["err.log"]);
+        assert_eq!(redirect_targets("echo x | tee -a a.txt ~/b.txt | wc"), vec!["a.txt", "~/b.txt"]);
+        assert!(redirect_targets("cat a.txt | grep b").is_empty());
+    }
 }
@@ -0,0 +1,407 @@
+//! Embedded Rhai scripting for small automations.
+//!
+//! Scripts live in `<workspace>/skills/scripts/*.rhai` and can be run by
+//! the `script_run` tool, by cron jobs with a `script` payload, or as
+//! hooks: a script that defines `fn on_<event>(event)` is called whenever
+//! the gateway fires that event (`start`, `message`, `presence`, `mqtt`,
+//! `logs`).
+//!
+//! Scripts see a small API:
+//!
+//! - `tool(name, #{ ... })` — run a built-in tool, returning its output
+//!   (errors can be caught with `try`/`catch`)
+//! - `sessions()` — array of session maps (`key`, `kind`, `status`, …)
+//! - `config("path.to.key")` — read a gateway config value (secrets are
+//!   redacted)
+//! - `print` / `debug` — appended to the script output
+//! - `ARGS` — the arguments passed by the caller
+//!
+//! Execution is bounded by an operation budget so a runaway loop can't
+//! stall the gateway.
+//!
+//! The engine is behind the `scripting` feature; without it scripts
+//! can't run and hooks never fire.
+
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use tracing::debug;
+
+use crate::config::Config;
+
+/// Maximum operations a single script run may perform.
+pub const MAX_OPERATIONS: u64 = 5_000_000;
+
+/// Script file extension.
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Config snapshot exposed to scripts through `config()`.
+static CONFIG: OnceLock<RwLock<Value>> = OnceLock::new();
+
+/// Publish the gateway config to scripts.  Called at startup and on reload.
+pub fn set_config(config: &Config) {
+    let mut value = serde_json::to_value(config).unwrap_or(Value::Null);
+    redact(&mut value);
+    let slot = CONFIG.get_or_init(|| RwLock::new(Value::Null));
+    if let Ok(mut guard) = slot.write() {
+        *guard = value;
+    }
+}
+
+/// Blank out anything that looks like a credential.
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if ["token", "password", "secret", "api_key", "apikey"]
+                    .iter()
+                    .any(|s| key.contains(s))
+                {
+                    *v = Value::Null;
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+fn config_value(path: &str) -> Value {
+    let Some(slot) = CONFIG.get() else {
+        return Value::Null;
+    };
+    let Ok(root) = slot.read() else {
+        return Value::Null;
+    };
+    let mut current = &*root;
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        match current.get(part) {
+            Some(v) => current = v,
+            None => return Value::Null,
+        }
+    }
+    current.clone()
+}
+
+/// Directory scripts are loaded from.
+pub fn scripts_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join("skills").join("scripts")
+}
+
+/// Path of a named script, rejecting anything that could escape the
+/// scripts directory.
+pub fn script_path(workspace_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = name.strip_suffix(".rhai").unwrap_or(name);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!("Invalid script name '{}'", name));
+    }
+    Ok(scripts_dir(workspace_dir).join(format!("{}.{}", name, SCRIPT_EXTENSION)))
+}
+
+/// Names of the available scripts, sorted.
+pub fn list_scripts(workspace_dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(scripts_dir(workspace_dir)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Run script source with `ARGS` bound to `args`.  Returns the printed
+/// output followed by the script's final value.
+pub fn run_script(code: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    #[cfg(feature = "scripting")]
+    {
+        engine::run_script(code, args, workspace_dir)
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    {
+        let _ = (code, args, workspace_dir);
+        Err("Scripting isn't compiled in. Rebuild with `--features scripting`.".to_string())
+    }
+}
+
+/// Run a named script from the scripts directory.
+pub fn run_named(name: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let path = script_path(workspace_dir, name)?;
+    let code = std::fs::read_to_string(&path)
+        .map_err(|_| format!("Script not found: {}", path.display()))?;
+    debug!(script = %name, "Running script");
+    run_script(&code, args, workspace_dir)
+}
+
+/// Call `on_<event>(payload)` in every script that defines it.  Hook
+/// functions run without the script's top-level code.  Returns the number
+/// of hooks that ran successfully; failures are logged.
+pub fn run_hooks(event: &str, payload: &Value, workspace_dir: &Path) -> usize {
+    #[cfg(feature = "scripting")]
+    {
+        engine::run_hooks(event, payload, workspace_dir)
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    {
+        let _ = (event, payload, workspace_dir);
+        0
+    }
+}
+
+/// Fire a hook without blocking the caller.
+pub fn spawn_hooks(event: &'static str, payload: Value, workspace_dir: PathBuf) {
+    if list_scripts(&workspace_dir).is_empty() {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        run_hooks(event, &payload, &workspace_dir);
+    });
+}
+
+/// Payload for the `message` hook.
+pub fn message_event(channel: &str, sender: &str, text: &str) -> Value {
+    json!({ "channel": channel, "sender": sender, "text": text })
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use super::*;
+    use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+    use std::sync::{Arc, Mutex};
+    use tracing::warn;
+
+    type Output = Arc<Mutex<Vec<String>>>;
+
+    fn push_output(output: &Output, line: String) {
+        if let Ok(mut lines) = output.lock() {
+            lines.push(line);
+        }
+    }
+
+    fn call_tool(name: &str, args: Map, workspace_dir: &Path) -> Result<String, Box<EvalAltResult>> {
+        if name == "script_run" {
+            return Err("Scripts cannot call script_run".into());
+        }
+        let mut args: Value = rhai::serde::from_dynamic(&Dynamic::from_map(args))?;
+        // A script run from a chat only gets the sender's tools.
+        if let Some(user) = crate::users::current_user() {
+            if let Some(denial) = crate::users::tool_denial(&user, name, &args) {
+                return Err(denial.into());
+            }
+            if let Some(scoped) = crate::users::scoped_args(&user, name, &args) {
+                args = scoped;
+            }
+        }
+        // Nobody can confirm a call from inside a script.
+        let permissions = crate::tools::permissions();
+        if let Some(denial) = crate::tools::unattended_denial(&permissions, name, &args) {
+            return Err(denial.into());
+        }
+        crate::tools::execute_tool(name, &args, workspace_dir).map_err(|e| e.into())
+    }
+
+    fn list_sessions() -> Result<Array, Box<EvalAltResult>> {
+        let mgr = crate::sessions::session_manager()
+            .lock()
+            .map_err(|_| Box::<EvalAltResult>::from("Session manager unavailable"))?;
+        let mut out = Array::new();
+        for session in mgr.list(None, false, 100) {
+            let mut value = serde_json::to_value(session).unwrap_or(Value::Null);
+            if let Some(obj) = value.as_object_mut() {
+                obj.remove("messages");
+            }
+            out.push(rhai::serde::to_dynamic(value)?);
+        }
+        Ok(out)
+    }
+
+    fn build_engine(workspace_dir: &Path, output: &Output) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(1 << 20);
+        engine.set_max_array_size(10_000);
+        engine.set_max_map_size(10_000);
+
+        let out = output.clone();
+        engine.on_print(move |s| push_output(&out, s.to_string()));
+        let out = output.clone();
+        engine.on_debug(move |s, _, _| push_output(&out, format!("[debug] {}", s)));
+
+        let ws = workspace_dir.to_path_buf();
+        engine.register_fn("tool", move |name: &str, args: Map| call_tool(name, args, &ws));
+        let ws = workspace_dir.to_path_buf();
+        engine.register_fn("tool", move |name: &str| call_tool(name, Map::new(), &ws));
+        engine.register_fn("sessions", list_sessions);
+        engine.register_fn("config", |path: &str| {
+            rhai::serde::to_dynamic(config_value(path)).unwrap_or(Dynamic::UNIT)
+        });
+        engine
+    }
+
+    fn render(output: &Output, result: Dynamic) -> String {
+        let mut lines = output.lock().map(|l| l.clone()).unwrap_or_default();
+        if !result.is_unit() {
+            lines.push(result.to_string());
+        }
+        lines.join("\n")
+    }
+
+    pub(super) fn run_script(code: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+        let output: Output = Arc::new(Mutex::new(Vec::new()));
+        let engine = build_engine(workspace_dir, &output);
+        let mut scope = Scope::new();
+        let args = rhai::serde::to_dynamic(args).map_err(|e| e.to_string())?;
+        scope.push_constant("ARGS", args);
+
+        let result = engine
+            .eval_with_scope::<Dynamic>(&mut scope, code)
+            .map_err(|e| format!("Script error: {}", e))?;
+        Ok(render(&output, result))
+    }
+
+    fn defines_hook(ast: &AST, hook: &str) -> bool {
+        ast.iter_functions()
+            .any(|f| f.name == hook && f.params.len() == 1)
+    }
+
+    pub(super) fn run_hooks(event: &str, payload: &Value, workspace_dir: &Path) -> usize {
+        let hook = format!("on_{}", event);
+        let mut ran = 0;
+        for name in list_scripts(workspace_dir) {
+            let Ok(path) = script_path(workspace_dir, &name) else {
+                continue;
+            };
+            let Ok(code) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let output: Output = Arc::new(Mutex::new(Vec::new()));
+            let engine = build_engine(workspace_dir, &output);
+            let ast = match engine.compile(&code) {
+                Ok(ast) => ast,
+                Err(e) => {
+                    warn!(script = %name, error = %e, "Script failed to compile");
+                    continue;
+                }
+            };
+            if !defines_hook(&ast, &hook) {
+                continue;
+            }
+            let arg = rhai::serde::to_dynamic(payload).unwrap_or(Dynamic::UNIT);
+            let options = CallFnOptions::new().eval_ast(false);
+            match engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &ast, &hook, (arg,)) {
+                Ok(result) => {
+                    ran += 1;
+                    debug!(script = %name, hook = %hook, output = %render(&output, result), "Script hook ran");
+                }
+                Err(e) => warn!(script = %name, hook = %hook, error = %e, "Script hook failed"),
+            }
+        }
+        ran
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "scripting")]
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_run_script_with_args_and_print() {
+        let dir = TempDir::new().unwrap();
+        let out = run_script(
+            r#"print("hello " + ARGS.name); ARGS.n * 2"#,
+            &json!({ "name": "world", "n": 21 }),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(out, "hello world\n42");
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_script_calls_tools() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("note.txt"), "scripted").unwrap();
+        let out = run_script(
+            r#"let text = tool("read_file", #{ path: "note.txt" }); text.contains("scripted")"#,
+            &Value::Null,
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(out, "true");
+
+        let err = run_script(r#"tool("script_run", #{ code: "1" })"#, &Value::Null, dir.path());
+        assert!(err.unwrap_err().contains("cannot call script_run"));
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_scripts_get_the_callers_tools() {
+        let dir = TempDir::new().unwrap();
+        let guest = crate::users::User { role: crate::users::Role::Guest, key: "eve".into() };
+        let err = crate::users::sync_user_scope(Some(guest), || {
+            run_script(r#"tool("execute_command", #{ command: "id" })"#, &Value::Null, dir.path())
+        });
+        assert!(err.unwrap_err().contains("isn't available to guests"));
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_operation_budget() {
+        let dir = TempDir::new().unwrap();
+        let err = run_script("loop { }", &Value::Null, dir.path()).unwrap_err();
+        assert!(err.contains("Script error"));
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_named_scripts_and_hooks() {
+        let dir = TempDir::new().unwrap();
+        let scripts = scripts_dir(dir.path());
+        std::fs::create_dir_all(&scripts).unwrap();
+        std::fs::write(
+            scripts.join("greeter.rhai"),
+            r#"
+                fn on_message(event) {
+                    if event.text != "hi" { throw "unexpected payload"; }
+                }
+                "greeter ran"
+            "#,
+        )
+        .unwrap();
+        std::fs::write(scripts.join("quiet.rhai"), "1 + 1").unwrap();
+
+        assert_eq!(list_scripts(dir.path()), vec!["greeter", "quiet"]);
+        assert_eq!(run_named("greeter", &Value::Null, dir.path()).unwrap(), "greeter ran");
+        assert!(script_path(dir.path(), "../escape").is_err());
+
+        let ran = run_hooks("message", &message_event("telegram", "ada", "hi"), dir.path());
+        assert_eq!(ran, 1);
+        assert_eq!(run_hooks("message", &message_event("telegram", "ada", "bye"), dir.path()), 0);
+        assert_eq!(run_hooks("start", &Value::Null, dir.path()), 0);
+    }
+
+    #[test]
+    fn test_config_redaction() {
+        let mut value = json!({ "model": { "provider": "x" }, "messengers": [{ "token": "abc" }] });
+        redact(&mut value);
+        assert_eq!(value["messengers"][0]["token"], Value::Null);
+        assert_eq!(value["model"]["provider"], "x");
+    }
+}
@@ -0,0 +1,160 @@
+//! File encryption with [age](https://age-encryption.org) for the
+//! `encrypt_file` / `decrypt_file` tools.
+//!
+//! X25519 identities live in the vault as `AgeKey` credentials (identity
+//! under `val:<name>`, recipient under `val:<name>:pub`); passphrase mode
+//! uses age's scrypt recipient.  Everything is streamed, so large files
+//! never sit in memory, and nothing touches a GnuPG keyring.
+
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use age::secrecy::{ExposeSecret, SecretString};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Create a fresh X25519 identity; returns `(AGE-SECRET-KEY-1…, age1…)`.
+pub fn generate_identity() -> (String, String) {
+    let identity = age::x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+    (identity.to_string().expose_secret().to_string(), recipient)
+}
+
+/// The `age1…` recipient for an identity string.
+pub fn recipient_for(identity: &str) -> Result<String, String> {
+    age::x25519::Identity::from_str(identity.trim())
+        .map(|i| i.to_public().to_string())
+        .map_err(|e| format!("Invalid age identity: {}", e))
+}
+
+/// Whether `text` looks like an age recipient rather than a vault name.
+pub fn is_recipient(text: &str) -> bool {
+    text.starts_with("age1")
+}
+
+/// How a file is protected.
+pub enum Protection<'a> {
+    Recipients(&'a [String]),
+    Passphrase(&'a str),
+}
+
+fn copy_to(src: &Path, dst: &Path, run: impl FnOnce(File, File) -> Result<(), String>) -> Result<(), String> {
+    let input = File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+    let output = File::create(dst).map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+    let result = run(input, output);
+    if result.is_err() {
+        let _ = std::fs::remove_file(dst);
+    }
+    result
+}
+
+/// Encrypt `src` to `dst`, ASCII-armored if `armor`.
+pub fn encrypt_file(src: &Path, dst: &Path, protection: Protection<'_>, armor: bool) -> Result<(), String> {
+    let encryptor = match protection {
+        Protection::Passphrase(passphrase) => {
+            age::Encryptor::with_user_passphrase(SecretString::from(passphrase.to_string()))
+        }
+        Protection::Recipients(recipients) => {
+            let parsed = recipients
+                .iter()
+                .map(|r| {
+                    age::x25519::Recipient::from_str(r.trim())
+                        .map_err(|e| format!("Invalid age recipient '{}': {}", r, e))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            if parsed.is_empty() {
+                return Err("No recipients to encrypt to".to_string());
+            }
+            age::Encryptor::with_recipients(parsed.iter().map(|r| r as &dyn age::Recipient))
+                .map_err(|e| format!("Failed to set up encryption: {}", e))?
+        }
+    };
+    copy_to(src, dst, |mut input, output| {
+        let format = if armor { Format::AsciiArmor } else { Format::Binary };
+        let armored = ArmoredWriter::wrap_output(BufWriter::new(output), format)
+            .map_err(|e| format!("Failed to write {}: {}", dst.display(), e))?;
+        let mut writer = encryptor
+            .wrap_output(armored)
+            .map_err(|e| format!("Failed to write {}: {}", dst.display(), e))?;
+        std::io::copy(&mut input, &mut writer).map_err(|e| format!("Encryption failed: {}", e))?;
+        writer
+            .finish()
+            .and_then(|armored| armored.finish())
+            .map_err(|e| format!("Failed to finish {}: {}", dst.display(), e))?;
+        Ok(())
+    })
+}
+
+/// Decrypt `src` (binary or armored) to `dst` with any of `identities`,
+/// or with `passphrase` if the file was passphrase-encrypted.
+pub fn decrypt_file(src: &Path, dst: &Path, identities: &[String], passphrase: Option<&str>) -> Result<(), String> {
+    let input = File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+    let decryptor = age::Decryptor::new(ArmoredReader::new(BufReader::new(input)))
+        .map_err(|e| format!("{} is not an age file: {}", src.display(), e))?;
+
+    let mut reader = if decryptor.is_scrypt() {
+        let passphrase = passphrase.ok_or("This file is passphrase-encrypted; supply passphraseSecret")?;
+        let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+        decryptor
+            .decrypt(std::iter::once(&identity as &dyn age::Identity))
+            .map_err(|e| format!("Decryption failed: {}", e))?
+    } else {
+        let parsed: Vec<age::x25519::Identity> = identities
+            .iter()
+            .filter_map(|i| age::x25519::Identity::from_str(i.trim()).ok())
+            .collect();
+        if parsed.is_empty() {
+            return Err("No usable age key to decrypt with".to_string());
+        }
+        decryptor
+            .decrypt(parsed.iter().map(|i| i as &dyn age::Identity))
+            .map_err(|e| format!("Decryption failed (none of the keys match?): {}", e))?
+    };
+    copy_to(src, dst, |_, output| {
+        let mut writer = BufWriter::new(output);
+        std::io::copy(&mut reader, &mut writer).map_err(|e| format!("Decryption failed: {}", e))?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipient_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plain = dir.path().join("report.txt");
+        std::fs::write(&plain, "quarterly numbers").unwrap();
+        let (identity, recipient) = generate_identity();
+        assert_eq!(recipient_for(&identity).unwrap(), recipient);
+        assert!(is_recipient(&recipient));
+
+        let sealed = dir.path().join("report.txt.age");
+        encrypt_file(&plain, &sealed, Protection::Recipients(&[recipient]), true).unwrap();
+        let armored = std::fs::read_to_string(&sealed).unwrap();
+        assert!(armored.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+
+        let out = dir.path().join("out.txt");
+        let (other, _) = generate_identity();
+        assert!(decrypt_file(&sealed, &out, &[other.clone()], None).is_err());
+        assert!(!out.exists());
+        decrypt_file(&sealed, &out, &[other, identity], None).unwrap();
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "quarterly numbers");
+    }
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plain = dir.path().join("notes.bin");
+        std::fs::write(&plain, [0u8, 1, 2, 3, 255]).unwrap();
+        let sealed = dir.path().join("notes.bin.age");
+        encrypt_file(&plain, &sealed, Protection::Passphrase("correct horse"), false).unwrap();
+
+        let out = dir.path().join("notes.out");
+        assert!(decrypt_file(&sealed, &out, &[], None).is_err());
+        assert!(decrypt_file(&sealed, &out, &[], Some("wrong")).is_err());
+        decrypt_file(&sealed, &out, &[], Some("correct horse")).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), vec![0u8, 1, 2, 3, 255]);
+    }
+}
@@ -0,0 +1,344 @@
+//! Encrypted fields in `config.toml`.
+//!
+//! Values that aren't secrets but shouldn't sit in a dotfiles repo in
+//! plaintext (webhook URLs, chat IDs, home server addresses) can be
+//! stored encrypted:
+//!
+//! ```toml
+//! [[messengers]]
+//! messenger_type = "slack"
+//! webhook_url = "enc:YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgy…"
+//! ```
+//!
+//! The value after `enc:` is the field's original TOML value, encrypted
+//! with age to an X25519 key kept in the vault under
+//! `config_field_key` and base64-encoded.  [`Config::load`] opens the
+//! vault (with its key file, or `RUSTYCLAW_VAULT_PASSWORD`) and decrypts
+//! the fields before the config is parsed; a password-protected vault
+//! that isn't unlocked yet leaves them encrypted until
+//! [`Config::unseal_fields`] is called.  Saving the config encrypts the
+//! fields again, so they never reach disk in plaintext.
+//!
+//! `rustyclaw config encrypt-field <path>` encrypts a field in place.
+//!
+//! [`Config::load`]: crate::config::Config::load
+//! [`Config::unseal_fields`]: crate::config::Config::unseal_fields
+
+use super::age_crypt;
+use super::SecretsManager;
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toml::{Table, Value};
+use tracing::{debug, warn};
+
+/// Marks an encrypted value.
+pub const PREFIX: &str = "enc:";
+
+/// Vault key holding the age identity fields are encrypted to.
+const KEY_NAME: &str = "config_field_key";
+
+/// Whether `value` is an encrypted field.
+pub fn is_sealed(value: &Value) -> bool {
+    value.as_str().is_some_and(|s| s.starts_with(PREFIX))
+}
+
+/// Encrypt `value` to `recipient` as an `enc:` string.
+pub fn seal(value: &Value, recipient: &str) -> Result<String, String> {
+    let recipient = age::x25519::Recipient::from_str(recipient).map_err(|e| format!("Invalid recipient: {}", e))?;
+    let plain = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    let encryptor = age::Encryptor::with_recipients(std::iter::once(&recipient as &dyn age::Recipient))
+        .map_err(|e| format!("Failed to set up encryption: {}", e))?;
+    let mut out = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut out).map_err(|e| e.to_string())?;
+    writer.write_all(&plain).and_then(|_| writer.finish()).map_err(|e| format!("Encryption failed: {}", e))?;
+    Ok(format!("{}{}", PREFIX, STANDARD.encode(out)))
+}
+
+/// Decrypt an `enc:` string back to the value it was made from.
+pub fn open(sealed: &str, identity: &str) -> Result<Value, String> {
+    let encoded = sealed.strip_prefix(PREFIX).ok_or("Not an encrypted value")?;
+    let bytes = STANDARD.decode(encoded.trim()).map_err(|e| format!("Not valid base64: {}", e))?;
+    let identity = age::x25519::Identity::from_str(identity.trim()).map_err(|e| format!("Invalid config key: {}", e))?;
+    let decryptor = age::Decryptor::new(&bytes[..]).map_err(|e| format!("Not an age ciphertext: {}", e))?;
+    let mut reader = decryptor
+        .decrypt(std::iter::once(&identity as &dyn age::Identity))
+        .map_err(|_| "Decryption failed (encrypted with a different vault's key?)".to_string())?;
+    let mut plain = Vec::new();
+    reader.read_to_end(&mut plain).map_err(|e| format!("Decryption failed: {}", e))?;
+    serde_json::from_slice(&plain).map_err(|e| format!("Decrypted value is malformed: {}", e))
+}
+
+/// The vault's config-field identity, created on first use if `create`.
+pub fn field_key(secrets: &mut SecretsManager, create: bool) -> Result<Option<String>> {
+    if let Some(identity) = secrets.get_secret(KEY_NAME, true)? {
+        return Ok(Some(identity));
+    }
+    if !create {
+        return Ok(None);
+    }
+    let (identity, _) = age_crypt::generate_identity();
+    secrets.store_secret(KEY_NAME, &identity)?;
+    debug!("Created config field key");
+    Ok(Some(identity))
+}
+
+// ── Paths ───────────────────────────────────────────────────────────────────
+
+/// Dotted paths (`messengers.0.webhook_url`) of every encrypted value.
+pub fn sealed_paths(table: &Table) -> Vec<String> {
+    fn walk(value: &Value, path: String, out: &mut Vec<String>) {
+        match value {
+            Value::Table(t) => t.iter().for_each(|(k, v)| walk(v, join(&path, k), out)),
+            Value::Array(a) => a.iter().enumerate().for_each(|(i, v)| walk(v, join(&path, &i.to_string()), out)),
+            v if is_sealed(v) => out.push(path),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    table.iter().for_each(|(k, v)| walk(v, k.clone(), &mut out));
+    out
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// The value at a dotted path; numeric segments index arrays.
+pub fn get_path<'a>(table: &'a mut Table, path: &str) -> Option<&'a mut Value> {
+    let mut parts = path.split('.');
+    let mut value = table.get_mut(parts.next()?)?;
+    for part in parts {
+        value = match value {
+            Value::Table(t) => t.get_mut(part)?,
+            Value::Array(a) => a.get_mut(part.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+// ── Config integration ──────────────────────────────────────────────────────
+
+/// One encrypted field of a loaded config.
+#[derive(Clone)]
+struct SealedField {
+    ciphertext: String,
+    /// The decrypted value, `None` while the vault is locked.
+    plain: Option<Value>,
+}
+
+/// The encrypted fields of a loaded config, kept so saving can put the
+/// ciphertext back.
+#[derive(Clone, Default)]
+pub struct SealedFields {
+    fields: BTreeMap<String, SealedField>,
+    /// Public half of the field key, for encrypting edited values.
+    recipient: Option<String>,
+}
+
+impl std::fmt::Debug for SealedFields {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SealedFields")
+            .field("fields", &self.fields.keys().collect::<Vec<_>>())
+            .field("locked", &self.locked())
+            .finish()
+    }
+}
+
+impl SealedFields {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Fields that are still encrypted because the vault was locked.
+    pub fn locked(&self) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|(_, f)| f.plain.is_none())
+            .map(|(p, _)| p.as_str())
+            .collect()
+    }
+
+    /// Decrypt the encrypted values in `table` in place, recording them.
+    /// With no identity they are recorded as locked and left alone.
+    pub fn unseal(&mut self, table: &mut Table, identity: Option<&str>) -> Result<usize, String> {
+        if let Some(identity) = identity {
+            self.recipient = Some(age_crypt::recipient_for(identity)?);
+        }
+        let mut opened = 0;
+        for path in sealed_paths(table) {
+            let Some(value) = get_path(table, &path) else { continue };
+            let ciphertext = value.as_str().unwrap_or_default().to_string();
+            let plain = match identity {
+                Some(identity) => {
+                    let plain = open(&ciphertext, identity).map_err(|e| format!("config field {}: {}", path, e))?;
+                    *value = plain.clone();
+                    opened += 1;
+                    Some(plain)
+                }
+                None => None,
+            };
+            self.fields.insert(path, SealedField { ciphertext, plain });
+        }
+        Ok(opened)
+    }
+
+    /// Put the encrypted values back into a serialized config.  Edited
+    /// values are encrypted afresh.
+    pub fn reseal(&self, table: &mut Table) -> Result<(), String> {
+        for (path, field) in &self.fields {
+            let Some(value) = get_path(table, path) else { continue };
+            let unchanged = field.plain.as_ref() == Some(&*value) || value.as_str() == Some(field.ciphertext.as_str());
+            if unchanged {
+                *value = Value::String(field.ciphertext.clone());
+            } else if let Some(recipient) = &self.recipient {
+                *value = Value::String(seal(value, recipient)?);
+            } else {
+                warn!(field = %path, "Saving a changed encrypted config field in plaintext: the vault is locked");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Open the vault the way a config at `table` would, without prompting:
+/// with its key file, or the password in `RUSTYCLAW_VAULT_PASSWORD`.
+fn load_time_key(table: &Table) -> Option<String> {
+    let settings_dir = table
+        .get("settings_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| crate::config::Config::default().settings_dir);
+    let creds_dir = table
+        .get("credentials_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| settings_dir.join("credentials"));
+    if !creds_dir.join("secrets.json").exists() {
+        return None;
+    }
+    let protected = table.get("secrets_password_protected").and_then(|v| v.as_bool()).unwrap_or(false);
+    let mut secrets = if protected {
+        SecretsManager::with_password(&creds_dir, std::env::var("RUSTYCLAW_VAULT_PASSWORD").ok()?)
+    } else {
+        SecretsManager::new(&creds_dir)
+    };
+    match field_key(&mut secrets, false) {
+        Ok(key) => key,
+        Err(e) => {
+            warn!(error = %e, "Cannot open the vault to decrypt config fields");
+            None
+        }
+    }
+}
+
+/// Decrypt a freshly parsed config file before it is deserialized.
+pub(crate) fn unseal_on_load(table: &mut Table) -> Result<SealedFields> {
+    let mut sealed = SealedFields::default();
+    if sealed_paths(table).is_empty() {
+        return Ok(sealed);
+    }
+    let key = load_time_key(table);
+    sealed.unseal(table, key.as_deref()).map_err(anyhow::Error::msg)?;
+    let locked = sealed.locked();
+    if !locked.is_empty() {
+        warn!(fields = ?locked, "Config fields stay encrypted until the vault is unlocked");
+    }
+    Ok(sealed)
+}
+
+/// Encrypt the field at `path` in the config file at `config_path`,
+/// replacing its value with `value` first if given.
+pub fn encrypt_field(config_path: &Path, path: &str, value: Option<&str>, secrets: &mut SecretsManager) -> Result<()> {
+    let content = std::fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let mut table: Table = toml::from_str(&content)?;
+    let identity = field_key(secrets, true)?.context("The vault has no config field key")?;
+    let recipient = age_crypt::recipient_for(&identity).map_err(anyhow::Error::msg)?;
+
+    if get_path(&mut table, path).is_none() {
+        if value.is_none() {
+            anyhow::bail!("{} is not set in {}; pass --value", path, config_path.display());
+        }
+        let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+        let parent = if parent.is_empty() {
+            &mut table
+        } else {
+            get_path(&mut table, parent)
+                .and_then(|v| v.as_table_mut())
+                .with_context(|| format!("No table at {}", parent))?
+        };
+        parent.insert(key.to_string(), Value::String(String::new()));
+    }
+    let target = get_path(&mut table, path).context("Field not found")?;
+    if let Some(value) = value {
+        *target = Value::String(value.to_string());
+    }
+    if is_sealed(target) {
+        anyhow::bail!("{} is already encrypted", path);
+    }
+    if target.is_table() {
+        anyhow::bail!("{} is a table; encrypt its fields one at a time", path);
+    }
+    *target = Value::String(seal(target, &recipient).map_err(anyhow::Error::msg)?);
+
+    std::fs::write(config_path, toml::to_string_pretty(&table)?)
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_round_trip() {
+        let (identity, recipient) = age_crypt::generate_identity();
+        for value in [Value::String("https://hooks.example.com/T0/B1".into()), Value::Integer(-1001234), Value::Boolean(true)] {
+            let sealed = seal(&value, &recipient).unwrap();
+            assert!(sealed.starts_with(PREFIX));
+            assert_eq!(open(&sealed, &identity).unwrap(), value);
+        }
+        let (other, _) = age_crypt::generate_identity();
+        assert!(open(&seal(&Value::Integer(1), &recipient).unwrap(), &other).is_err());
+    }
+
+    #[test]
+    fn test_unseal_and_reseal() {
+        let (identity, recipient) = age_crypt::generate_identity();
+        let hook = seal(&Value::String("https://hook".into()), &recipient).unwrap();
+        let chat = seal(&Value::Integer(42), &recipient).unwrap();
+        let text = format!(
+            "gateway_url = \"ws://x\"\n[[messengers]]\nwebhook_url = \"{}\"\nchat_id = \"{}\"\n",
+            hook, chat
+        );
+        let mut table: Table = toml::from_str(&text).unwrap();
+        assert_eq!(sealed_paths(&table), vec!["messengers.0.chat_id", "messengers.0.webhook_url"]);
+
+        let mut locked = SealedFields::default();
+        assert_eq!(locked.unseal(&mut table.clone(), None).unwrap(), 0);
+        assert_eq!(locked.locked().len(), 2);
+
+        let mut sealed = SealedFields::default();
+        assert_eq!(sealed.unseal(&mut table, Some(&identity)).unwrap(), 2);
+        assert_eq!(get_path(&mut table, "messengers.0.chat_id").unwrap().as_integer(), Some(42));
+
+        // Unchanged fields get their ciphertext back; edited ones are
+        // encrypted again.
+        *get_path(&mut table, "messengers.0.webhook_url").unwrap() = Value::String("https://new".into());
+        sealed.reseal(&mut table).unwrap();
+        assert_eq!(get_path(&mut table, "messengers.0.chat_id").unwrap().as_str(), Some(chat.as_str()));
+        let edited = get_path(&mut table, "messengers.0.webhook_url").unwrap().as_str().unwrap().to_string();
+        assert_ne!(edited, hook);
+        assert_eq!(open(&edited, &identity).unwrap().as_str(), Some("https://new"));
+        assert!(!format!("{:?}", sealed).contains("https://"));
+    }
+}
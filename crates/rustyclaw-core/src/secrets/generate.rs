@@ -0,0 +1,259 @@
+//! Password, passphrase, token and PIN generation for `generate_secret`.
+//!
+//! Everything comes from the OS-seeded CSPRNG behind `rand::rng()`, with
+//! rejection sampling so every symbol is equally likely.  Passphrases use
+//! the BIP-39 English list (2048 words, 11 bits each, no two words sharing
+//! a four-letter prefix).
+
+use base64::Engine as _;
+use rand::RngExt;
+use serde_json::Value;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!@#$%^&*()-_=+[]{};:,.<>?/~";
+const ALPHANUMERIC: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Characters easily confused when read or typed.
+const AMBIGUOUS: &str = "0O1lI|`'\"";
+
+/// What to generate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretPolicy {
+    Password {
+        length: usize,
+        lowercase: bool,
+        uppercase: bool,
+        digits: bool,
+        /// Symbol alphabet; empty for none.
+        symbols: String,
+        exclude_ambiguous: bool,
+    },
+    Passphrase {
+        words: usize,
+        separator: String,
+        capitalize: bool,
+        /// Append a digit to one word, for sites that insist on one.
+        number: bool,
+    },
+    Token {
+        bytes: usize,
+        /// `hex`, `base64url` or `alphanumeric`.
+        format: String,
+        prefix: String,
+    },
+    Pin {
+        length: usize,
+    },
+}
+
+/// A generated value with an entropy estimate.
+#[derive(Clone, PartialEq)]
+pub struct GeneratedSecret {
+    pub value: String,
+    pub entropy_bits: f64,
+}
+
+impl std::fmt::Debug for GeneratedSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeneratedSecret")
+            .field("value", &"<redacted>")
+            .field("entropy_bits", &self.entropy_bits)
+            .finish()
+    }
+}
+
+fn usize_arg(args: &Value, name: &str, default: usize, min: usize, max: usize) -> Result<usize, String> {
+    match args.get(name).and_then(|v| v.as_u64()) {
+        None => Ok(default),
+        Some(n) if (min as u64..=max as u64).contains(&n) => Ok(n as usize),
+        Some(n) => Err(format!("{} must be between {} and {} (got {})", name, min, max, n)),
+    }
+}
+
+fn bool_arg(args: &Value, name: &str, default: bool) -> bool {
+    args.get(name).and_then(|v| v.as_bool()).unwrap_or(default)
+}
+
+impl SecretPolicy {
+    /// Read a policy from tool arguments; `style` picks the variant.
+    pub fn from_args(args: &Value) -> Result<Self, String> {
+        let style = args.get("style").and_then(|v| v.as_str()).unwrap_or("password");
+        match style {
+            "password" => Ok(SecretPolicy::Password {
+                length: usize_arg(args, "length", 24, 8, 256)?,
+                lowercase: bool_arg(args, "lowercase", true),
+                uppercase: bool_arg(args, "uppercase", true),
+                digits: bool_arg(args, "digits", true),
+                symbols: match args.get("symbols") {
+                    Some(Value::Bool(false)) => String::new(),
+                    Some(Value::String(set)) => set.clone(),
+                    _ => SYMBOLS.to_string(),
+                },
+                exclude_ambiguous: bool_arg(args, "excludeAmbiguous", false),
+            }),
+            "passphrase" => Ok(SecretPolicy::Passphrase {
+                words: usize_arg(args, "words", 6, 4, 24)?,
+                separator: args.get("separator").and_then(|v| v.as_str()).unwrap_or("-").to_string(),
+                capitalize: bool_arg(args, "capitalize", false),
+                number: bool_arg(args, "includeNumber", false),
+            }),
+            "token" => {
+                let format = args.get("format").and_then(|v| v.as_str()).unwrap_or("base64url");
+                if !matches!(format, "hex" | "base64url" | "alphanumeric") {
+                    return Err(format!("Unknown token format '{}'; use hex, base64url or alphanumeric", format));
+                }
+                Ok(SecretPolicy::Token {
+                    bytes: usize_arg(args, "bytes", 32, 16, 256)?,
+                    format: format.to_string(),
+                    prefix: args.get("prefix").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                })
+            }
+            "pin" => Ok(SecretPolicy::Pin { length: usize_arg(args, "length", 6, 4, 32)? }),
+            other => Err(format!("Unknown style '{}'; use password, passphrase, token or pin", other)),
+        }
+    }
+}
+
+/// A uniformly random index below `n`.
+fn pick(n: usize) -> usize {
+    let n = n as u32;
+    let zone = u32::MAX - u32::MAX % n;
+    let mut rng = rand::rng();
+    loop {
+        let mut bytes = [0u8; 4];
+        rng.fill(&mut bytes);
+        let x = u32::from_le_bytes(bytes);
+        if x < zone {
+            return (x % n) as usize;
+        }
+    }
+}
+
+fn random_string(alphabet: &[char], length: usize) -> String {
+    (0..length).map(|_| alphabet[pick(alphabet.len())]).collect()
+}
+
+/// Generate a secret.
+pub fn generate(policy: &SecretPolicy) -> Result<GeneratedSecret, String> {
+    match policy {
+        SecretPolicy::Password { length, lowercase, uppercase, digits, symbols, exclude_ambiguous } => {
+            let classes: Vec<Vec<char>> = [(*lowercase, LOWERCASE), (*uppercase, UPPERCASE), (*digits, DIGITS), (true, symbols.as_str())]
+                .into_iter()
+                .filter(|(on, _)| *on)
+                .map(|(_, set)| set.chars().filter(|c| !(*exclude_ambiguous && AMBIGUOUS.contains(*c))).collect::<Vec<char>>())
+                .filter(|set| !set.is_empty())
+                .collect();
+            if classes.is_empty() {
+                return Err("Every character class is turned off".to_string());
+            }
+            if classes.len() > *length {
+                return Err(format!("length {} is too short to include all {} character classes", length, classes.len()));
+            }
+            let mut alphabet: Vec<char> = classes.iter().flatten().copied().collect();
+            alphabet.sort_unstable();
+            alphabet.dedup();
+            // Redraw until every class appears, rather than forcing
+            // positions, so the output stays uniform over valid passwords.
+            let value = loop {
+                let candidate = random_string(&alphabet, *length);
+                if classes.iter().all(|set| candidate.chars().any(|c| set.contains(&c))) {
+                    break candidate;
+                }
+            };
+            Ok(GeneratedSecret { value, entropy_bits: *length as f64 * (alphabet.len() as f64).log2() })
+        }
+        SecretPolicy::Passphrase { words, separator, capitalize, number } => {
+            let list = bip39::Language::English.word_list();
+            let mut chosen: Vec<String> = (0..*words)
+                .map(|_| {
+                    let word = list[pick(list.len())];
+                    if *capitalize {
+                        let mut chars = word.chars();
+                        chars.next().map(|f| f.to_uppercase().chain(chars).collect()).unwrap_or_default()
+                    } else {
+                        word.to_string()
+                    }
+                })
+                .collect();
+            let mut entropy = *words as f64 * (list.len() as f64).log2();
+            if *number {
+                let at = pick(chosen.len());
+                chosen[at].push_str(&pick(10).to_string());
+                entropy += (10.0 * *words as f64).log2();
+            }
+            Ok(GeneratedSecret { value: chosen.join(separator), entropy_bits: entropy })
+        }
+        SecretPolicy::Token { bytes, format, prefix } => {
+            let body = match format.as_str() {
+                "alphanumeric" => {
+                    // Same entropy as `bytes` random bytes.
+                    let alphabet: Vec<char> = ALPHANUMERIC.chars().collect();
+                    let length = (*bytes as f64 * 8.0 / (alphabet.len() as f64).log2()).ceil() as usize;
+                    random_string(&alphabet, length)
+                }
+                format => {
+                    let mut raw = vec![0u8; *bytes];
+                    rand::rng().fill(&mut raw[..]);
+                    if format == "hex" {
+                        raw.iter().map(|b| format!("{:02x}", b)).collect()
+                    } else {
+                        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&raw)
+                    }
+                }
+            };
+            Ok(GeneratedSecret { value: format!("{}{}", prefix, body), entropy_bits: *bytes as f64 * 8.0 })
+        }
+        SecretPolicy::Pin { length } => {
+            let digits: Vec<char> = DIGITS.chars().collect();
+            Ok(GeneratedSecret { value: random_string(&digits, *length), entropy_bits: *length as f64 * 10f64.log2() })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_password_policy() {
+        let policy = SecretPolicy::from_args(&json!({ "length": 12, "symbols": false, "excludeAmbiguous": true })).unwrap();
+        for _ in 0..50 {
+            let secret = generate(&policy).unwrap();
+            assert_eq!(secret.value.len(), 12);
+            assert!(secret.value.chars().all(|c| c.is_ascii_alphanumeric() && !AMBIGUOUS.contains(c)));
+            assert!(secret.value.chars().any(|c| c.is_ascii_digit()));
+            assert!(secret.value.chars().any(|c| c.is_ascii_uppercase()));
+        }
+        let custom = SecretPolicy::from_args(&json!({ "length": 8, "lowercase": false, "uppercase": false, "digits": false, "symbols": "ab" })).unwrap();
+        assert!(generate(&custom).unwrap().value.chars().all(|c| c == 'a' || c == 'b'));
+        let none = SecretPolicy::from_args(&json!({ "lowercase": false, "uppercase": false, "digits": false, "symbols": false })).unwrap();
+        assert!(generate(&none).is_err());
+        assert!(SecretPolicy::from_args(&json!({ "length": 4 })).is_err());
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let policy = SecretPolicy::from_args(&json!({ "style": "passphrase", "words": 5, "separator": " ", "capitalize": true })).unwrap();
+        let secret = generate(&policy).unwrap();
+        let words: Vec<&str> = secret.value.split(' ').collect();
+        assert_eq!(words.len(), 5);
+        assert!(words.iter().all(|w| w.chars().next().unwrap().is_uppercase()));
+        assert_eq!(secret.entropy_bits, 55.0);
+    }
+
+    #[test]
+    fn test_token_and_pin() {
+        let policy = SecretPolicy::from_args(&json!({ "style": "token", "format": "hex", "bytes": 16, "prefix": "sk_" })).unwrap();
+        let token = generate(&policy).unwrap();
+        assert!(token.value.starts_with("sk_"));
+        assert_eq!(token.value.len(), 3 + 32);
+        let url = generate(&SecretPolicy::from_args(&json!({ "style": "token" })).unwrap()).unwrap();
+        assert_eq!(url.value.len(), 43);
+        let pin = generate(&SecretPolicy::from_args(&json!({ "style": "pin" })).unwrap()).unwrap();
+        assert!(pin.value.len() == 6 && pin.value.chars().all(|c| c.is_ascii_digit()));
+        assert!(!format!("{:?}", pin).contains(&pin.value));
+    }
+}
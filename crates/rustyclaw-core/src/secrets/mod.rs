@@ -12,11 +12,20 @@
 //! | `val:<name>`           | Primary secret value (or private key PEM / note)  |
 //! | `val:<name>:user`      | Username (for `UsernamePassword` kind)             |
 //! | `val:<name>:pub`       | Public key string (for `SshKey` kind)              |
+//! |                        | or age recipient (for `AgeKey` kind)               |
 //! | `val:<name>:fields`    | JSON map of form-field key/value pairs             |
 //! | `val:<name>:card`      | JSON `{cardholder,number,expiry,cvv}`              |
 //! | `val:<name>:card_extra`| JSON map of additional payment card fields         |
 //! | `<bare key>`           | Legacy / raw secrets (API keys, TOTP, etc.)        |
-
+//!
+//! Code requests for `Totp` credentials are audited to
+//! `{credentials_dir}/secrets_audit.log`.
+
+pub mod age_crypt;
+pub mod config_fields;
+mod generate;
+pub mod ssh_agent;
+mod totp;
 mod types;
 mod vault;
 
@@ -26,6 +35,8 @@ pub use types::{
     AccessContext, AccessPolicy, BrowserStore, Cookie, CredentialValue, Secret, SecretEntry,
     SecretKind, WebStorage,
 };
+pub use generate::{generate, GeneratedSecret, SecretPolicy};
+pub use totp::{TotpCode, TotpSeed};
 
 /// Secrets manager backed by an encrypted SecureStore vault.
 pub struct SecretsManager {
@@ -897,6 +908,71 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn test_import_encrypted_ssh_key() {
+        let dir = temp_dir();
+        let mut m = SecretsManager::new(&dir);
+
+        let mut key = ssh_key::PrivateKey::random(&mut ssh_key::rand_core::OsRng, ssh_key::Algorithm::Ed25519)
+            .unwrap();
+        key.set_comment("deploy@ci");
+        let encrypted = key.encrypt(&mut ssh_key::rand_core::OsRng, "hunter2").unwrap();
+        let pem = encrypted.to_openssh(ssh_key::LineEnding::LF).unwrap();
+
+        assert!(m.import_ssh_key("deploy", &pem, None, AccessPolicy::Always).is_err());
+        assert!(m.import_ssh_key("deploy", &pem, Some("wrong"), AccessPolicy::Always).is_err());
+        let public = m
+            .import_ssh_key("deploy", &pem, Some("hunter2"), AccessPolicy::Always)
+            .unwrap();
+        assert!(public.starts_with("ssh-ed25519 "));
+        assert!(public.ends_with("deploy@ci"));
+
+        let (_, val) = m.get_credential("deploy", &AccessContext::default()).unwrap().unwrap();
+        match val {
+            CredentialValue::SshKeyPair { private_key, public_key } => {
+                let stored = ssh_key::PrivateKey::from_openssh(&private_key).unwrap();
+                assert!(!stored.is_encrypted());
+                assert_eq!(stored.public_key().key_data(), key.public_key().key_data());
+                assert_eq!(public_key, public);
+            }
+            _ => panic!("Expected SshKeyPair"),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_totp_code_respects_policy_and_audits() {
+        let dir = temp_dir();
+        let mut m = SecretsManager::new(&dir);
+
+        let entry = SecretEntry {
+            label: "GitHub".to_string(),
+            kind: SecretKind::Totp,
+            policy: AccessPolicy::WithApproval,
+            description: None,
+            disabled: false,
+        };
+        let seed = TotpSeed::parse("JBSWY3DPEHPK3PXP").unwrap();
+        m.store_totp("github", &entry, &seed).unwrap();
+
+        assert!(m.totp_code("github", &AccessContext::default()).is_err());
+        let ctx = AccessContext {
+            user_approved: true,
+            ..Default::default()
+        };
+        let code = m.totp_code("github", &ctx).unwrap().unwrap();
+        assert_eq!(code.code.len(), 6);
+        assert!((1..=30).contains(&code.seconds_remaining));
+        assert!(m.totp_code("missing", &ctx).unwrap().is_none());
+
+        let log = std::fs::read_to_string(m.audit_log_path()).unwrap();
+        assert_eq!(log.lines().count(), 3);
+        assert!(log.contains("denied"));
+        assert!(log.contains("issued"));
+        assert!(!log.contains(&seed.secret));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_form_autofill_delete_cleans_fields() {
         let dir = temp_dir();
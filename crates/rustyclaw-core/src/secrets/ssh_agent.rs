@@ -0,0 +1,199 @@
+//! Session-scoped `ssh-agent` holding keys loaded from the vault.
+//!
+//! The agent is started on the first [`add_key`] with its socket in a
+//! private temp directory, and `ssh` invocations made by the nodes tool
+//! are pointed at it through [`apply`].  Keys never touch disk: they are
+//! piped to `ssh-add -`.
+//!
+//! The agent runs under a small `sh` watchdog that exits when this process
+//! does, and `ssh-agent` exits with its child command — so a crashed
+//! gateway doesn't leave keys sitting in a stray agent.
+
+use serde::Serialize;
+use ssh_key::{HashAlg, PrivateKey, PublicKey};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// A key currently held by the session agent.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedKey {
+    /// Vault credential name.
+    pub name: String,
+    /// `SHA256:…` fingerprint, as printed by `ssh-add -l`.
+    pub fingerprint: String,
+    /// Seconds the agent keeps the key, if limited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifetime: Option<u64>,
+}
+
+struct SessionAgent {
+    dir: PathBuf,
+    socket: PathBuf,
+    watchdog: Child,
+    loaded: Vec<LoadedKey>,
+}
+
+static AGENT: Mutex<Option<SessionAgent>> = Mutex::new(None);
+
+fn start() -> Result<SessionAgent, String> {
+    let dir = std::env::temp_dir().join(format!("rustyclaw-ssh-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to restrict {}: {}", dir.display(), e))?;
+    }
+    let socket = dir.join("agent.sock");
+    let _ = std::fs::remove_file(&socket);
+
+    let watchdog = format!(
+        "while kill -0 {} 2>/dev/null; do sleep 5; done",
+        std::process::id()
+    );
+    let child = Command::new("ssh-agent")
+        .arg("-a")
+        .arg(&socket)
+        .args(["sh", "-c", &watchdog])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start ssh-agent (is OpenSSH installed?): {}", e))?;
+
+    let deadline = Instant::now() + Duration::from_secs(3);
+    while !socket.exists() {
+        if Instant::now() > deadline {
+            return Err("ssh-agent did not create its socket".to_string());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    debug!(socket = %socket.display(), "Started session ssh-agent");
+    Ok(SessionAgent { dir, socket, watchdog: child, loaded: Vec::new() })
+}
+
+fn ssh_add(socket: &PathBuf, args: &[&str], stdin: Option<&str>) -> Result<(), String> {
+    let mut child = Command::new("ssh-add")
+        .args(args)
+        .env("SSH_AUTH_SOCK", socket)
+        .env("SSH_ASKPASS_REQUIRE", "never")
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ssh-add: {}", e))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to pass key to ssh-add: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("ssh-add failed: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("ssh-add failed: {}", String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// Load a private key (OpenSSH format) into the session agent, starting
+/// the agent if needed.  Returns the key's fingerprint.
+pub fn add_key(name: &str, private_pem: &str, lifetime: Option<u64>) -> Result<LoadedKey, String> {
+    let fingerprint = PrivateKey::from_openssh(private_pem)
+        .map_err(|e| format!("Stored key '{}' is not a valid OpenSSH private key: {}", name, e))?
+        .public_key()
+        .fingerprint(HashAlg::Sha256)
+        .to_string();
+
+    let mut guard = AGENT.lock().map_err(|_| "ssh-agent state poisoned".to_string())?;
+    if guard.as_mut().is_some_and(|agent| !agent.socket.exists()) {
+        warn!("Session ssh-agent went away; restarting");
+        *guard = None;
+    }
+    if guard.is_none() {
+        *guard = Some(start()?);
+    }
+    let agent = guard.as_mut().expect("agent started above");
+
+    let seconds = lifetime.map(|s| s.to_string());
+    let mut args = vec!["-q"];
+    if let Some(ref s) = seconds {
+        args.extend(["-t", s.as_str()]);
+    }
+    args.push("-");
+    ssh_add(&agent.socket, &args, Some(private_pem))?;
+
+    let key = LoadedKey { name: name.to_string(), fingerprint, lifetime };
+    agent.loaded.retain(|k| k.name != name);
+    agent.loaded.push(key.clone());
+    debug!(credential = name, "Loaded vault key into session agent");
+    Ok(key)
+}
+
+/// Remove one key from the session agent.
+pub fn remove_key(name: &str, public_key: &str) -> Result<bool, String> {
+    let mut guard = AGENT.lock().map_err(|_| "ssh-agent state poisoned".to_string())?;
+    let Some(agent) = guard.as_mut() else {
+        return Ok(false);
+    };
+    if !agent.loaded.iter().any(|k| k.name == name) {
+        return Ok(false);
+    }
+    PublicKey::from_openssh(public_key)
+        .map_err(|e| format!("Stored public key for '{}' is invalid: {}", name, e))?;
+    let pub_path = agent.dir.join(format!("{}.pub", name.replace(['/', '\\'], "_")));
+    std::fs::write(&pub_path, format!("{}\n", public_key))
+        .map_err(|e| format!("Failed to write {}: {}", pub_path.display(), e))?;
+    let result = ssh_add(&agent.socket, &["-q", "-d", &pub_path.to_string_lossy()], None);
+    let _ = std::fs::remove_file(&pub_path);
+    result?;
+    agent.loaded.retain(|k| k.name != name);
+    Ok(true)
+}
+
+/// Keys loaded in this session (expired ones may still be listed).
+pub fn loaded() -> Vec<LoadedKey> {
+    AGENT
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|agent| agent.loaded.clone()))
+        .unwrap_or_default()
+}
+
+/// Wipe the agent's keys and shut it down.  Returns false if none was running.
+pub fn stop() -> Result<bool, String> {
+    let mut guard = AGENT.lock().map_err(|_| "ssh-agent state poisoned".to_string())?;
+    let Some(mut agent) = guard.take() else {
+        return Ok(false);
+    };
+    if let Err(e) = ssh_add(&agent.socket, &["-q", "-D"], None) {
+        warn!(error = %e, "Failed to clear session ssh-agent");
+    }
+    let _ = agent.watchdog.kill();
+    let _ = agent.watchdog.wait();
+    let _ = std::fs::remove_dir_all(&agent.dir);
+    debug!("Stopped session ssh-agent");
+    Ok(true)
+}
+
+/// The agent socket, if an agent with keys is running.
+pub fn socket() -> Option<PathBuf> {
+    let guard = AGENT.lock().ok()?;
+    let agent = guard.as_ref()?;
+    (!agent.loaded.is_empty() && agent.socket.exists()).then(|| agent.socket.clone())
+}
+
+/// Point an `ssh`/`scp` command at the session agent, if one is running.
+/// Must be called before the destination arguments are added.
+pub fn apply(cmd: &mut Command) {
+    if let Some(socket) = socket() {
+        cmd.env("SSH_AUTH_SOCK", &socket)
+            .arg("-o")
+            .arg(format!("IdentityAgent={}", socket.display()));
+    }
+}
@@ -27,6 +27,12 @@ pub enum SecretKind {
     /// Free-form encrypted note (recovery codes, license keys,
     /// security questions, PIN codes, etc.).
     SecureNote,
+    /// TOTP seed for a third-party account.  The agent receives
+    /// generated codes, never the seed itself.
+    Totp,
+    /// age X25519 identity for file encryption.  The recipient (public
+    /// key) is stored alongside it.
+    AgeKey,
     /// Catch-all for anything that doesn't fit the above.
     Other,
 }
@@ -42,6 +48,8 @@ impl std::fmt::Display for SecretKind {
             Self::FormAutofill => write!(f, "Form"),
             Self::PaymentMethod => write!(f, "Payment"),
             Self::SecureNote => write!(f, "Note"),
+            Self::Totp => write!(f, "2FA"),
+            Self::AgeKey => write!(f, "age Key"),
             Self::Other => write!(f, "Other"),
         }
     }
@@ -59,6 +67,8 @@ impl SecretKind {
             Self::FormAutofill => "📋",
             Self::PaymentMethod => "💳",
             Self::SecureNote => "📝",
+            Self::Totp => "⏱",
+            Self::AgeKey => "🗝",
             Self::Other => "🔒",
         }
     }
@@ -7,6 +7,7 @@ use totp_rs::{Algorithm, Secret as TotpSecret, TOTP};
 use super::types::{
     AccessContext, AccessPolicy, CredentialValue, SecretEntry, SecretKind,
 };
+use super::totp::{TotpCode, TotpSeed};
 use super::SecretsManager;
 
 impl SecretsManager {
@@ -512,7 +513,7 @@ impl SecretsManager {
                         ("Password".to_string(), password),
                     ]
                 }
-                SecretKind::SshKey => {
+                SecretKind::SshKey | SecretKind::AgeKey => {
                     let private_key = self.get_secret(&val_key, true)?.unwrap_or_default();
                     let pub_key = format!("val:{}:pub", name);
                     let public_key = self.get_secret(&pub_key, true)?.unwrap_or_default();
@@ -709,20 +710,115 @@ impl SecretsManager {
             description: Some(format!("Ed25519 keypair — {}", comment)),
             disabled: false,
         };
+        self.store_keypair(name, &entry, private_pem.as_str(), &public_str)?;
 
-        let meta_key = format!("cred:{}", name);
-        let val_key = format!("val:{}", name);
-        let pub_vault_key = format!("val:{}:pub", name);
+        Ok(public_str)
+    }
 
-        let meta_json =
-            serde_json::to_string(&entry).context("Failed to serialize credential metadata")?;
-        self.store_secret(&meta_key, &meta_json)?;
-        self.store_secret(&val_key, private_pem.to_string().as_str())?;
-        self.store_secret(&pub_vault_key, &public_str)?;
+    /// Import an existing OpenSSH private key into the vault as an
+    /// `SshKey` credential.
+    ///
+    /// Passphrase-protected keys are decrypted with `passphrase` and stored
+    /// unencrypted (the vault itself is the protection), so they can be
+    /// loaded into an agent without prompting.  Returns the public key.
+    pub fn import_ssh_key(
+        &mut self,
+        name: &str,
+        private_pem: &str,
+        passphrase: Option<&str>,
+        policy: AccessPolicy,
+    ) -> Result<String> {
+        use ssh_key::private::PrivateKey;
+
+        let mut private = PrivateKey::from_openssh(private_pem.trim()).map_err(|e| {
+            anyhow::anyhow!(
+                "Not an OpenSSH private key ({}). Older PEM keys can be converted with `ssh-keygen -p -f <file>`.",
+                e
+            )
+        })?;
+        if private.is_encrypted() {
+            let passphrase = passphrase
+                .ok_or_else(|| anyhow::anyhow!("Key '{}' is passphrase-protected; supply the passphrase", name))?;
+            private = private
+                .decrypt(passphrase)
+                .map_err(|_| anyhow::anyhow!("Wrong passphrase for key '{}'", name))?;
+        }
+
+        let private_pem = private
+            .to_openssh(ssh_key::LineEnding::LF)
+            .map_err(|e| anyhow::anyhow!("Failed to encode private key: {}", e))?;
+        let public_openssh = private
+            .public_key()
+            .to_openssh()
+            .map_err(|e| anyhow::anyhow!("Failed to encode public key: {}", e))?;
+        let comment = private.comment();
+        let public_str = if comment.is_empty() || public_openssh.ends_with(comment) {
+            public_openssh.to_string()
+        } else {
+            format!("{} {}", public_openssh, comment)
+        };
+
+        let entry = SecretEntry {
+            label: format!("SSH key ({})", name),
+            kind: SecretKind::SshKey,
+            policy,
+            description: Some(format!(
+                "Imported {} key — {}",
+                private.algorithm(),
+                private.public_key().fingerprint(ssh_key::HashAlg::Sha256),
+            )),
+            disabled: false,
+        };
+        self.store_keypair(name, &entry, private_pem.as_str(), &public_str)?;
 
         Ok(public_str)
     }
 
+    /// Generate a new age X25519 identity and store it as an `AgeKey`
+    /// credential.  Returns the `age1…` recipient.
+    pub fn generate_age_key(&mut self, name: &str, policy: AccessPolicy) -> Result<String> {
+        let (identity, recipient) = super::age_crypt::generate_identity();
+        let entry = SecretEntry {
+            label: format!("age key ({})", name),
+            kind: SecretKind::AgeKey,
+            policy,
+            description: Some(format!("File encryption key — {}", recipient)),
+            disabled: false,
+        };
+        self.store_keypair(name, &entry, &identity, &recipient)?;
+        Ok(recipient)
+    }
+
+    /// The stored public half of an `SshKey` or `AgeKey` credential.
+    ///
+    /// Public keys aren't secret, so no access policy applies.
+    pub fn public_key(&mut self, name: &str) -> Result<Option<String>> {
+        let Some(json) = self.get_secret(&format!("cred:{}", name), true)? else {
+            return Ok(None);
+        };
+        let entry: SecretEntry =
+            serde_json::from_str(&json).context("Corrupted credential metadata")?;
+        if !matches!(entry.kind, SecretKind::SshKey | SecretKind::AgeKey) {
+            anyhow::bail!("'{}' is a {} credential, not a key pair", name, entry.kind);
+        }
+        self.get_secret(&format!("val:{}:pub", name), true)
+    }
+
+    fn store_keypair(
+        &mut self,
+        name: &str,
+        entry: &SecretEntry,
+        private_pem: &str,
+        public_key: &str,
+    ) -> Result<()> {
+        let meta_json =
+            serde_json::to_string(entry).context("Failed to serialize credential metadata")?;
+        self.store_secret(&format!("cred:{}", name), &meta_json)?;
+        self.store_secret(&format!("val:{}", name), private_pem)?;
+        self.store_secret(&format!("val:{}:pub", name), public_key)?;
+        Ok(())
+    }
+
     // ── Access policy enforcement ───────────────────────────────────
 
     /// Evaluate whether the given [`AccessContext`] satisfies a
@@ -828,6 +924,50 @@ impl SecretsManager {
         Ok(())
     }
 
+    // ── TOTP seeds for third-party accounts ─────────────────────────
+
+    /// Path of the JSON-lines audit log kept next to the vault.
+    pub fn audit_log_path(&self) -> std::path::PathBuf {
+        self.vault_path.with_file_name("secrets_audit.log")
+    }
+
+    /// Store a TOTP seed as a `Totp` credential.
+    pub fn store_totp(&mut self, name: &str, entry: &SecretEntry, seed: &TotpSeed) -> Result<()> {
+        debug_assert_eq!(entry.kind, SecretKind::Totp);
+        let seed_json = serde_json::to_string(seed).context("Failed to serialize TOTP seed")?;
+        self.store_credential(name, entry, &seed_json, None)?;
+        super::totp::audit(&self.audit_log_path(), name, "store", "ok")
+    }
+
+    /// Generate the current code for a `Totp` credential.
+    ///
+    /// The credential's [`AccessPolicy`] is enforced exactly as for
+    /// [`get_credential`](Self::get_credential), and every request is
+    /// written to the audit log, including denied ones.
+    pub fn totp_code(&mut self, name: &str, ctx: &AccessContext) -> Result<Option<TotpCode>> {
+        let result = self.get_credential(name, ctx);
+        let log = self.audit_log_path();
+        let (entry, value) = match result {
+            Ok(Some(found)) => found,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                super::totp::audit(&log, name, "code", &format!("denied: {}", e))?;
+                return Err(e);
+            }
+        };
+        let (SecretKind::Totp, CredentialValue::Single(json)) = (&entry.kind, value) else {
+            anyhow::bail!("Credential '{}' is not a TOTP seed ({})", name, entry.kind);
+        };
+        let seed: TotpSeed = serde_json::from_str(&json).context("Corrupted TOTP seed")?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .context("System time error")?
+            .as_secs();
+        let code = seed.code_at(now)?;
+        super::totp::audit(&log, name, "code", "issued")?;
+        Ok(Some(code))
+    }
+
     /// No-op kept for API compatibility.  The securestore crate
     /// decrypts on-demand so there is no separate cache to clear.
     pub fn clear_cache(&mut self) {}
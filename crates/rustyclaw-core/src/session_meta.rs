@@ -0,0 +1,152 @@
+//! Title and pinned topic of the TUI chat session.
+//!
+//! The title is generated from the session's first user message and the
+//! pinned topic is a sticky note set with `/pin`.  Both are shown in the
+//! TUI header; the pin is also sent to the model as a system message on
+//! every turn so it survives long, multi-day sessions and compaction.
+//!
+//! The metadata lives next to the turn journal in the sessions directory
+//! so it persists across TUI restarts.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::gateway::ChatMessage;
+
+/// File name of the metadata inside the sessions directory.
+const META_FILE: &str = "session_meta.json";
+
+/// Marks the pinned-topic system message.
+pub const PIN_PROMPT_HEADER: &str = "## Pinned topic";
+
+/// Longest generated title, in characters.
+const MAX_TITLE_CHARS: usize = 48;
+
+/// Most words taken from the first message for the title.
+const MAX_TITLE_WORDS: usize = 8;
+
+/// Header information for the active session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<String>,
+}
+
+impl SessionMeta {
+    /// Load the metadata; a missing or unreadable file gives an empty one.
+    pub fn load(sessions_dir: &Path) -> Self {
+        fs::read_to_string(sessions_dir.join(META_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist the metadata.
+    pub fn save(&self, sessions_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(sessions_dir)
+            .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize session metadata: {}", e))?;
+        fs::write(sessions_dir.join(META_FILE), json)
+            .map_err(|e| format!("Failed to write session metadata: {}", e))
+    }
+
+    /// Give the session a title from `first_message` unless it has one.
+    /// Returns `true` when the title was set.
+    pub fn ensure_title(&mut self, first_message: &str) -> bool {
+        if self.title.is_some() {
+            return false;
+        }
+        self.title = generate_title(first_message);
+        self.title.is_some()
+    }
+
+    /// Set (or clear, with an empty note) the pinned topic.
+    pub fn pin(&mut self, note: &str) {
+        let note = note.trim();
+        self.pinned = (!note.is_empty()).then(|| note.to_string());
+    }
+}
+
+/// A short title for a session that starts with `text`: the first line's
+/// leading words, without markdown or trailing punctuation.
+pub fn generate_title(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let words: Vec<&str> = line
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| matches!(c, '#' | '*' | '`' | '_' | '>')))
+        .filter(|w| !w.is_empty())
+        .take(MAX_TITLE_WORDS)
+        .collect();
+    let mut title = words.join(" ");
+    if title.chars().count() > MAX_TITLE_CHARS {
+        title = title.chars().take(MAX_TITLE_CHARS - 1).collect::<String>();
+        title = format!("{}…", title.trim_end());
+    }
+    let title = title.trim_end_matches(|c: char| matches!(c, '.' | ',' | ':' | ';' | '?' | '!'));
+    let mut chars = title.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// Replace (or drop) the pinned-topic system message.  It sits just after
+/// the leading system prompt, like the plan checklist.
+pub fn sync_pin_message(messages: &mut Vec<ChatMessage>, pinned: Option<&str>) {
+    messages.retain(|m| !(m.role == "system" && m.content.starts_with(PIN_PROMPT_HEADER)));
+    if let Some(note) = pinned {
+        let at = messages.iter().take_while(|m| m.role == "system").count();
+        let text = format!(
+            "{}\nThe user pinned this note for the whole session; keep it in mind:\n{}",
+            PIN_PROMPT_HEADER, note
+        );
+        messages.insert(at, ChatMessage::text("system", &text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generate_title() {
+        assert_eq!(
+            generate_title("  \n## fix the flaky cron test, please?\nmore").as_deref(),
+            Some("Fix the flaky cron test, please")
+        );
+        let long = generate_title("supercalifragilisticexpialidocious antidisestablishmentarianism words");
+        assert!(long.unwrap().ends_with('…'));
+        assert_eq!(generate_title("   "), None);
+    }
+
+    #[test]
+    fn test_meta_round_trip_and_pin_message() {
+        let dir = TempDir::new().unwrap();
+        let mut meta = SessionMeta::load(dir.path());
+        assert_eq!(meta, SessionMeta::default());
+
+        assert!(meta.ensure_title("refactor the gateway"));
+        assert!(!meta.ensure_title("something else"));
+        meta.pin("Target release is v0.4; keep the API stable");
+        meta.save(dir.path()).unwrap();
+
+        let meta = SessionMeta::load(dir.path());
+        assert_eq!(meta.title.as_deref(), Some("Refactor the gateway"));
+
+        let mut messages = vec![
+            ChatMessage::text("system", "You are helpful."),
+            ChatMessage::text("user", "hi"),
+        ];
+        sync_pin_message(&mut messages, meta.pinned.as_deref());
+        sync_pin_message(&mut messages, meta.pinned.as_deref());
+        assert_eq!(messages.len(), 3);
+        assert!(messages[1].content.starts_with(PIN_PROMPT_HEADER));
+        assert!(messages[1].content.contains("v0.4"));
+
+        sync_pin_message(&mut messages, None);
+        assert_eq!(messages.len(), 2);
+    }
+}
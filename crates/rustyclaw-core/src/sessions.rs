@@ -5,8 +5,11 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Notify;
 
 /// Session key format: agent:<agentId>:subagent:<uuid> or agent:<agentId>:main
 pub type SessionKey = String;
@@ -18,7 +21,7 @@ fn generate_subagent_key(agent_id: &str) -> SessionKey {
 }
 
 /// Generate a simple UUID-like string.
-fn generate_uuid() -> String {
+pub(crate) fn generate_uuid() -> String {
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -80,6 +83,9 @@ pub struct Session {
     /// Parent session key (for sub-agents).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_key: Option<SessionKey>,
+    /// Isolated git worktree the sub-agent edits in, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<crate::worktree::Worktree>,
 }
 
 impl Session {
@@ -98,6 +104,7 @@ impl Session {
             messages: Vec::new(),
             run_id: None,
             parent_key: None,
+            worktree: None,
         }
     }
 
@@ -117,6 +124,7 @@ impl Session {
             messages: Vec::new(),
             run_id: Some(run_id),
             parent_key,
+            worktree: None,
         }
     }
 
@@ -139,12 +147,41 @@ impl Session {
     pub fn complete(&mut self) {
         self.status = SessionStatus::Completed;
         self.finished_ms = Some(now_millis());
+        self.notify_finished();
     }
 
     /// Mark session as errored.
     pub fn error(&mut self) {
         self.status = SessionStatus::Error;
         self.finished_ms = Some(now_millis());
+        self.notify_finished();
+    }
+
+    /// Mark session as stopped before it finished.
+    pub fn stop(&mut self) {
+        self.status = SessionStatus::Stopped;
+        self.finished_ms = Some(now_millis());
+        self.notify_finished();
+    }
+
+    /// Tell the notification center a sub-agent is done.
+    fn notify_finished(&self) {
+        use crate::notifications::{post, Severity, Source};
+        if self.kind != SessionKind::Subagent {
+            return;
+        }
+        let name = self.label.as_deref().unwrap_or(&self.key);
+        let (severity, title) = match self.status {
+            SessionStatus::Error => (Severity::Error, format!("Sub-agent {} failed", name)),
+            SessionStatus::Stopped => (Severity::Warning, format!("Sub-agent {} stopped", name)),
+            _ => (Severity::Success, format!("Sub-agent {} finished", name)),
+        };
+        let body: String = self
+            .messages
+            .last()
+            .map(|m| m.content.chars().take(200).collect())
+            .unwrap_or_default();
+        post(severity, Source::Subagent, title, body, Some(self.key.clone()));
     }
 
     /// Get runtime in seconds.
@@ -154,11 +191,142 @@ impl Session {
     }
 }
 
+/// Guardrails applied when agents delegate work to sub-agents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationPolicy {
+    /// Maximum nesting depth of sub-agents (a direct sub-agent has depth 1).
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+
+    /// Maximum number of sub-agents active at the same time.
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+
+    /// Maximum number of sub-agents spawned over the lifetime of one tree.
+    #[serde(default = "default_max_tree_spawns")]
+    pub max_tree_spawns: usize,
+
+    /// Maximum prompt plus completion tokens the sub-agents of one tree
+    /// may use between them.
+    #[serde(default = "default_max_tree_tokens")]
+    pub max_tree_tokens: u64,
+}
+
+fn default_max_depth() -> usize {
+    3
+}
+
+fn default_max_concurrent() -> usize {
+    8
+}
+
+fn default_max_tree_spawns() -> usize {
+    32
+}
+
+fn default_max_tree_tokens() -> u64 {
+    2_000_000
+}
+
+impl Default for DelegationPolicy {
+    fn default() -> Self {
+        Self {
+            max_depth: default_max_depth(),
+            max_concurrent: default_max_concurrent(),
+            max_tree_spawns: default_max_tree_spawns(),
+            max_tree_tokens: default_max_tree_tokens(),
+        }
+    }
+}
+
+/// One sub-agent taking part in an orchestration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrchestrationMember {
+    /// Role name, e.g. "researcher", "coder", "reviewer".
+    pub role: String,
+    /// Model override for this member, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub session_key: SessionKey,
+}
+
+/// A note shared between the members of an orchestration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScratchpadEntry {
+    pub author: String,
+    pub content: String,
+    pub timestamp_ms: u64,
+}
+
+/// A task fanned out to several sub-agents that share a scratchpad.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Orchestration {
+    pub id: String,
+    pub task: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_key: Option<SessionKey>,
+    pub members: Vec<OrchestrationMember>,
+    pub scratchpad: Vec<ScratchpadEntry>,
+    pub created_ms: u64,
+}
+
+/// Role specification used when starting an orchestration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleSpec {
+    pub role: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Extra role-specific instructions appended to the shared task.
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+/// Output of one member, collected when aggregating an orchestration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberOutput {
+    pub role: String,
+    pub session_key: SessionKey,
+    pub status: SessionStatus,
+    /// Last assistant message of the member, if it has produced one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// A sub-agent session waiting for the gateway to run it.
+#[derive(Debug, Clone)]
+pub struct SubagentRun {
+    pub session_key: SessionKey,
+    pub task: String,
+    /// Model override, if any.
+    pub model: Option<String>,
+    /// Directory the sub-agent's tools run in.
+    pub workspace: PathBuf,
+}
+
+/// Session top-level turns (the TUI, messengers) run in.  Sub-agents they
+/// spawn hang off it, so the tree budgets apply to them too.
+pub const MAIN_SESSION: &str = "agent:main:main";
+
 /// Global session manager.
 pub struct SessionManager {
     sessions: HashMap<SessionKey, Session>,
     /// Map labels to session keys for easy lookup.
     labels: HashMap<String, SessionKey>,
+    /// Multi-agent orchestrations by ID.
+    orchestrations: HashMap<String, Orchestration>,
+    /// Delegation guardrails.
+    policy: DelegationPolicy,
+    /// Cumulative number of sub-agents spawned per tree root.
+    tree_spawns: HashMap<SessionKey, usize>,
+    /// Tokens used by sub-agents per tree root.
+    tree_tokens: HashMap<SessionKey, u64>,
+    /// Spawned sub-agents the gateway hasn't started yet.
+    pending_runs: Vec<SubagentRun>,
 }
 
 impl SessionManager {
@@ -167,9 +335,24 @@ impl SessionManager {
         Self {
             sessions: HashMap::new(),
             labels: HashMap::new(),
+            orchestrations: HashMap::new(),
+            policy: DelegationPolicy::default(),
+            tree_spawns: HashMap::new(),
+            tree_tokens: HashMap::new(),
+            pending_runs: Vec::new(),
         }
     }
 
+    /// Replace the delegation policy.
+    pub fn set_policy(&mut self, policy: DelegationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Current delegation policy.
+    pub fn policy(&self) -> &DelegationPolicy {
+        &self.policy
+    }
+
     /// Create or get a main session.
     pub fn get_or_create_main(&mut self, agent_id: &str) -> &Session {
         let key = format!("agent:{}:main", agent_id);
@@ -186,8 +369,10 @@ impl SessionManager {
         label: Option<String>,
         parent_key: Option<SessionKey>,
     ) -> SessionKey {
+        let root = parent_key.as_deref().map(|p| self.tree_root(p));
         let session = Session::new_subagent(agent_id, task, label.clone(), parent_key);
         let key = session.key.clone();
+        *self.tree_spawns.entry(root.unwrap_or_else(|| key.clone())).or_insert(0) += 1;
 
         if let Some(ref lbl) = label {
             self.labels.insert(lbl.clone(), key.clone());
@@ -197,6 +382,131 @@ impl SessionManager {
         key
     }
 
+    /// Spawn a sub-agent session, enforcing the delegation policy.
+    pub fn try_spawn_subagent(
+        &mut self,
+        agent_id: &str,
+        task: &str,
+        label: Option<String>,
+        parent_key: Option<SessionKey>,
+    ) -> Result<SessionKey, String> {
+        self.check_spawn(parent_key.as_deref(), 1)?;
+        Ok(self.spawn_subagent(agent_id, task, label, parent_key))
+    }
+
+    /// Check whether `count` new sub-agents may be spawned under `parent_key`.
+    pub fn check_spawn(&self, parent_key: Option<&str>, count: usize) -> Result<(), String> {
+        let depth = parent_key.map(|p| self.depth(p)).unwrap_or(0) + 1;
+        if depth > self.policy.max_depth {
+            return Err(format!(
+                "Delegation depth limit reached ({} > max {})",
+                depth, self.policy.max_depth
+            ));
+        }
+
+        let active = self.active_subagents();
+        if active + count > self.policy.max_concurrent {
+            return Err(format!(
+                "Too many concurrent sub-agents ({} active, max {})",
+                active, self.policy.max_concurrent
+            ));
+        }
+
+        if let Some(parent) = parent_key {
+            let spawned = self.tree_spawn_count(parent);
+            if spawned + count > self.policy.max_tree_spawns {
+                return Err(format!(
+                    "Sub-agent budget for this tree exhausted ({} spawned, max {})",
+                    spawned, self.policy.max_tree_spawns
+                ));
+            }
+            self.check_tokens(parent)?;
+        }
+        Ok(())
+    }
+
+    /// Check whether the tree containing `key` has tokens left.
+    pub fn check_tokens(&self, key: &str) -> Result<(), String> {
+        let used = self.tree_token_count(key);
+        if used >= self.policy.max_tree_tokens {
+            return Err(format!(
+                "Token budget for this tree exhausted ({} used, max {})",
+                used, self.policy.max_tree_tokens
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of sub-agents currently active.
+    pub fn active_subagents(&self) -> usize {
+        self.sessions
+            .values()
+            .filter(|s| s.kind == SessionKind::Subagent && s.status == SessionStatus::Active)
+            .count()
+    }
+
+    /// Chain of session keys from the tree root down to `key` (inclusive).
+    pub fn lineage(&self, key: &str) -> Vec<SessionKey> {
+        let mut chain = vec![key.to_string()];
+        let mut current = key;
+        while let Some(parent) = self.sessions.get(current).and_then(|s| s.parent_key.as_deref()) {
+            // Guard against cycles from hand-edited parent keys.
+            if chain.iter().any(|k| k == parent) {
+                break;
+            }
+            chain.push(parent.to_string());
+            current = parent;
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Sub-agent nesting depth of a session (0 for main sessions).
+    pub fn depth(&self, key: &str) -> usize {
+        self.lineage(key)
+            .iter()
+            .filter(|k| {
+                self.sessions
+                    .get(k.as_str())
+                    .is_some_and(|s| s.kind == SessionKind::Subagent)
+            })
+            .count()
+    }
+
+    /// Root session key of the tree that `key` belongs to.
+    pub fn tree_root(&self, key: &str) -> SessionKey {
+        self.lineage(key).swap_remove(0)
+    }
+
+    /// Cumulative number of sub-agents spawned in the tree containing `key`.
+    pub fn tree_spawn_count(&self, key: &str) -> usize {
+        self.tree_spawns
+            .get(&self.tree_root(key))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Tokens used by the sub-agents of the tree containing `key`.
+    pub fn tree_token_count(&self, key: &str) -> u64 {
+        self.tree_tokens
+            .get(&self.tree_root(key))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Start the tree rooted at `root` over with a fresh spawn and token
+    /// budget.  Called as each top-level turn begins, so the main session's
+    /// budget is per turn rather than for the gateway's lifetime.
+    pub fn reset_tree(&mut self, root: &str) {
+        self.tree_spawns.remove(root);
+        self.tree_tokens.remove(root);
+    }
+
+    /// Charge `tokens` used by session `key` to its tree.
+    pub fn record_tokens(&mut self, key: &str, tokens: u64) {
+        *self.tree_tokens.entry(self.tree_root(key)).or_insert(0) += tokens;
+    }
+
     /// Get a session by key.
     pub fn get(&self, key: &str) -> Option<&Session> {
         self.sessions.get(key)
@@ -271,6 +581,126 @@ impl SessionManager {
         session.complete();
         Ok(())
     }
+
+    /// Queue a spawned sub-agent for the gateway's sub-agent worker.
+    pub fn queue_run(&mut self, run: SubagentRun) {
+        self.pending_runs.push(run);
+        runs_queued().notify_one();
+    }
+
+    /// Take every queued sub-agent run.
+    pub fn take_runs(&mut self) -> Vec<SubagentRun> {
+        std::mem::take(&mut self.pending_runs)
+    }
+
+    /// Fan a task out to one sub-agent per role.
+    ///
+    /// Every member is spawned as a sub-agent session of `agent_id`,
+    /// labelled `<orchestration-id>:<role>`, receives the shared task plus
+    /// its role instructions, and is queued to run in parallel with the
+    /// others in `workspace`.  Returns the orchestration ID.
+    pub fn start_orchestration(
+        &mut self,
+        agent_id: &str,
+        task: &str,
+        roles: &[RoleSpec],
+        parent_key: Option<SessionKey>,
+        workspace: &Path,
+    ) -> Result<String, String> {
+        if roles.is_empty() {
+            return Err("At least one role is required".to_string());
+        }
+        self.check_spawn(parent_key.as_deref(), roles.len())?;
+
+        let id = format!("orch-{}", generate_uuid());
+        let mut members = Vec::with_capacity(roles.len());
+
+        for spec in roles {
+            let mut role_task = format!(
+                "You are the {} in a team working on this task:\n\n{}\n\n\
+                 Share intermediate findings in the orchestration scratchpad ({}).",
+                spec.role, task, id
+            );
+            if let Some(ref extra) = spec.instructions {
+                role_task.push_str(&format!("\n\nYour instructions: {}", extra));
+            }
+            let label = format!("{}:{}", id, spec.role);
+            let key = self.spawn_subagent(agent_id, &role_task, Some(label), parent_key.clone());
+            self.queue_run(SubagentRun {
+                session_key: key.clone(),
+                task: role_task,
+                model: spec.model.clone(),
+                workspace: workspace.to_path_buf(),
+            });
+            members.push(OrchestrationMember {
+                role: spec.role.clone(),
+                model: spec.model.clone(),
+                session_key: key,
+            });
+        }
+
+        self.orchestrations.insert(
+            id.clone(),
+            Orchestration {
+                id: id.clone(),
+                task: task.to_string(),
+                parent_key,
+                members,
+                scratchpad: Vec::new(),
+                created_ms: now_millis(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Get an orchestration by ID.
+    pub fn get_orchestration(&self, id: &str) -> Option<&Orchestration> {
+        self.orchestrations.get(id)
+    }
+
+    /// Append a note to an orchestration's shared scratchpad.
+    pub fn scratchpad_write(&mut self, id: &str, author: &str, content: &str) -> Result<(), String> {
+        let orch = self
+            .orchestrations
+            .get_mut(id)
+            .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+        orch.scratchpad.push(ScratchpadEntry {
+            author: author.to_string(),
+            content: content.to_string(),
+            timestamp_ms: now_millis(),
+        });
+        Ok(())
+    }
+
+    /// Collect the latest output and status of every member.
+    pub fn aggregate_orchestration(&self, id: &str) -> Result<Vec<MemberOutput>, String> {
+        let orch = self
+            .orchestrations
+            .get(id)
+            .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+
+        Ok(orch
+            .members
+            .iter()
+            .map(|m| {
+                let session = self.sessions.get(&m.session_key);
+                MemberOutput {
+                    role: m.role.clone(),
+                    session_key: m.session_key.clone(),
+                    status: session
+                        .map(|s| s.status.clone())
+                        .unwrap_or(SessionStatus::Stopped),
+                    output: session.and_then(|s| {
+                        s.messages
+                            .iter()
+                            .rev()
+                            .find(|msg| msg.role == "assistant")
+                            .map(|msg| msg.content.clone())
+                    }),
+                }
+            })
+            .collect())
+    }
 }
 
 impl Default for SessionManager {
@@ -290,6 +720,63 @@ pub fn session_manager() -> &'static SharedSessionManager {
     SESSION_MANAGER.get_or_init(|| Arc::new(Mutex::new(SessionManager::new())))
 }
 
+tokio::task_local! {
+    static CURRENT_SESSION: SessionKey;
+    static CURRENT_CONVERSATION: String;
+}
+
+/// The session whose turn is running on the current task, if any.
+/// Sub-agents run inside their own session; top-level turns have none.
+pub fn current_session() -> Option<SessionKey> {
+    CURRENT_SESSION.try_with(|key| key.clone()).ok()
+}
+
+/// Run `fut` as a turn of session `key`.
+pub async fn session_scope<F: Future>(key: SessionKey, fut: F) -> F::Output {
+    CURRENT_SESSION.scope(key, fut).await
+}
+
+/// The session a sub-agent spawned now hangs off: the current sub-agent,
+/// or the main session for top-level turns.
+pub fn spawn_parent() -> SessionKey {
+    current_session().unwrap_or_else(|| MAIN_SESSION.to_string())
+}
+
+/// Run `f` with `key` (if any) as the current session — for turn work
+/// moved onto the blocking pool.
+pub fn sync_session_scope<R>(key: Option<SessionKey>, f: impl FnOnce() -> R) -> R {
+    match key {
+        Some(key) => CURRENT_SESSION.sync_scope(key, f),
+        None => f(),
+    }
+}
+
+/// The messenger chat or client connection the current top-level turn
+/// belongs to, if any (e.g. `chat:telegram:42`, `connection:10.0.0.2:5123`).
+pub fn current_conversation() -> Option<String> {
+    CURRENT_CONVERSATION.try_with(|key| key.clone()).ok()
+}
+
+/// Run `fut` as a turn of conversation `key`.
+pub async fn conversation_scope<F: Future>(key: String, fut: F) -> F::Output {
+    CURRENT_CONVERSATION.scope(key, fut).await
+}
+
+/// Run `f` with `key` (if any) as the current conversation — for turn
+/// work moved onto the blocking pool.
+pub fn sync_conversation_scope<R>(key: Option<String>, f: impl FnOnce() -> R) -> R {
+    match key {
+        Some(key) => CURRENT_CONVERSATION.sync_scope(key, f),
+        None => f(),
+    }
+}
+
+/// Woken whenever a sub-agent run is queued.
+pub fn runs_queued() -> &'static Notify {
+    static QUEUED: OnceLock<Notify> = OnceLock::new();
+    QUEUED.get_or_init(Notify::new)
+}
+
 /// Spawn result returned to the agent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -298,6 +785,9 @@ pub struct SpawnResult {
     pub run_id: String,
     pub session_key: SessionKey,
     pub message: String,
+    /// Worktree the sub-agent should edit in (`isolation: "worktree"`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<PathBuf>,
 }
 
 /// Get current time in milliseconds.
@@ -353,6 +843,144 @@ mod tests {
         assert_eq!(history[1].content, "Hi there!");
     }
 
+    #[test]
+    fn test_orchestration_fan_out_and_aggregate() {
+        let mut manager = SessionManager::new();
+        let roles = vec![
+            RoleSpec { role: "researcher".into(), model: None, instructions: None },
+            RoleSpec { role: "coder".into(), model: Some("gpt-4o".into()), instructions: Some("Write tests".into()) },
+        ];
+        let id = manager
+            .start_orchestration("main", "Build a parser", &roles, None, Path::new("/tmp/ws"))
+            .unwrap();
+
+        let orch = manager.get_orchestration(&id).unwrap();
+        assert_eq!(orch.members.len(), 2);
+        let coder_key = orch.members[1].session_key.clone();
+        let coder = manager.get(&coder_key).unwrap();
+        assert_eq!(coder.agent_id, "main");
+        assert!(coder.task.as_ref().unwrap().contains("Write tests"));
+        assert!(manager.get_by_label(&format!("{}:researcher", id)).is_some());
+
+        // Every member is queued to run, with its own model.
+        let runs = manager.take_runs();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].session_key, coder_key);
+        assert_eq!(runs[1].model.as_deref(), Some("gpt-4o"));
+        assert_eq!(runs[1].workspace, Path::new("/tmp/ws"));
+        assert!(manager.take_runs().is_empty());
+
+        manager.scratchpad_write(&id, "researcher", "Found a grammar").unwrap();
+        assert_eq!(manager.get_orchestration(&id).unwrap().scratchpad.len(), 1);
+
+        manager.get_mut(&coder_key).unwrap().add_message("assistant", "Done");
+        let outputs = manager.aggregate_orchestration(&id).unwrap();
+        assert_eq!(outputs[0].output, None);
+        assert_eq!(outputs[1].output.as_deref(), Some("Done"));
+    }
+
+    #[test]
+    fn test_orchestration_requires_roles() {
+        let mut manager = SessionManager::new();
+        assert!(manager.start_orchestration("main", "x", &[], None, Path::new("/tmp")).is_err());
+    }
+
+    #[test]
+    fn test_delegation_depth_limit() {
+        let mut manager = SessionManager::new();
+        manager.set_policy(DelegationPolicy {
+            max_depth: 2,
+            ..Default::default()
+        });
+
+        let a = manager.try_spawn_subagent("main", "a", None, None).unwrap();
+        let b = manager.try_spawn_subagent("main", "b", None, Some(a.clone())).unwrap();
+        assert_eq!(manager.depth(&b), 2);
+        assert_eq!(manager.lineage(&b), vec![a.clone(), b.clone()]);
+
+        let err = manager.try_spawn_subagent("main", "c", None, Some(b)).unwrap_err();
+        assert!(err.contains("depth"));
+    }
+
+    #[test]
+    fn test_delegation_concurrency_and_budget() {
+        let mut manager = SessionManager::new();
+        manager.set_policy(DelegationPolicy {
+            max_depth: 5,
+            max_concurrent: 2,
+            max_tree_spawns: 3,
+            ..Default::default()
+        });
+
+        let root = manager.get_or_create_main("main").key.clone();
+        let a = manager.try_spawn_subagent("main", "a", None, Some(root.clone())).unwrap();
+        let b = manager.try_spawn_subagent("main", "b", None, Some(root.clone())).unwrap();
+        let err = manager.try_spawn_subagent("main", "c", None, Some(root.clone())).unwrap_err();
+        assert!(err.contains("concurrent"));
+
+        manager.complete_session(&a).unwrap();
+        manager.try_spawn_subagent("main", "c", None, Some(root.clone())).unwrap();
+        assert_eq!(manager.tree_spawn_count(&root), 3);
+
+        // Budget is cumulative: finished sub-agents still count against the tree.
+        manager.complete_session(&b).unwrap();
+        let err = manager.try_spawn_subagent("main", "d", None, Some(a)).unwrap_err();
+        assert!(err.contains("budget"));
+    }
+
+    #[test]
+    fn test_delegation_token_budget() {
+        let mut manager = SessionManager::new();
+        manager.set_policy(DelegationPolicy {
+            max_tree_tokens: 1_000,
+            ..Default::default()
+        });
+
+        let a = manager.try_spawn_subagent("main", "a", None, None).unwrap();
+        let b = manager.try_spawn_subagent("main", "b", None, Some(a.clone())).unwrap();
+        manager.record_tokens(&b, 600);
+        manager.try_spawn_subagent("main", "c", None, Some(a.clone())).unwrap();
+        manager.record_tokens(&a, 400);
+        assert_eq!(manager.tree_token_count(&b), 1_000);
+
+        let err = manager.try_spawn_subagent("main", "d", None, Some(b)).unwrap_err();
+        assert!(err.contains("Token budget"));
+        // Other trees are unaffected.
+        manager.try_spawn_subagent("main", "e", None, None).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_top_level_spawns_share_the_main_budget() {
+        let mut manager = SessionManager::new();
+        manager.set_policy(DelegationPolicy {
+            max_tree_spawns: 2,
+            ..Default::default()
+        });
+
+        assert_eq!(spawn_parent(), MAIN_SESSION);
+        manager.try_spawn_subagent("main", "a", None, Some(spawn_parent())).unwrap();
+        manager.try_spawn_subagent("main", "b", None, Some(spawn_parent())).unwrap();
+        let err = manager.try_spawn_subagent("main", "c", None, Some(spawn_parent())).unwrap_err();
+        assert!(err.contains("budget"));
+
+        // The next top-level turn starts over.
+        manager.reset_tree(MAIN_SESSION);
+        manager.try_spawn_subagent("main", "c", None, Some(spawn_parent())).unwrap();
+
+        let sub = session_scope("agent:main:subagent:1".to_string(), async { spawn_parent() }).await;
+        assert_eq!(sub, "agent:main:subagent:1");
+    }
+
+    #[tokio::test]
+    async fn test_current_session_is_scoped_to_the_turn() {
+        assert_eq!(current_session(), None);
+        let seen = session_scope("agent:main:subagent:1".to_string(), async { current_session() }).await;
+        assert_eq!(seen.as_deref(), Some("agent:main:subagent:1"));
+        let seen = sync_session_scope(seen, current_session);
+        assert_eq!(seen.as_deref(), Some("agent:main:subagent:1"));
+        assert_eq!(current_session(), None);
+    }
+
     #[test]
     fn test_session_listing() {
         let mut manager = SessionManager::new();
@@ -1052,7 +1052,9 @@ impl SkillManager {
         .context("Failed to download skill from ClawHub")?;
 
         // Response is a zip file
-        let zip_bytes = resp.bytes().context("Failed to read zip data")?;
+        let size = resp.content_length();
+        let zip_bytes = crate::progress::read_to_end(resp, &format!("Downloading skill {}", name), size)
+            .context("Failed to read zip data")?;
 
         // Use last directory (user's writable dir) for installations, not first (bundled/read-only)
         let skills_dir = self
@@ -1068,7 +1070,13 @@ impl SkillManager {
         let cursor = std::io::Cursor::new(zip_bytes);
         let mut archive = zip::ZipArchive::new(cursor).context("Invalid zip archive")?;
 
+        let mut progress = crate::progress::Progress::start(
+            format!("Installing skill {}", name),
+            crate::progress::Unit::Items,
+            Some(archive.len() as u64),
+        );
         for i in 0..archive.len() {
+            progress.set(i as u64);
             let mut file = archive.by_index(i)?;
             let outpath = skill_dir.join(file.name());
 
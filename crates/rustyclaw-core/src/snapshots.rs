@@ -0,0 +1,480 @@
+//! Workspace snapshots before autonomous runs.
+//!
+//! Queued tasks, sub-agents, heartbeats and cron jobs run with nobody
+//! watching, so a bad run can trash files before anyone notices.  With
+//! `[snapshots]` enabled the gateway copies the workspace to
+//! `.snapshots/<id>/` before each such run and keeps the newest `keep`
+//! snapshots:
+//!
+//! ```toml
+//! [snapshots]
+//! enabled = true
+//! keep = 10
+//! exclude = ["data", "*.iso"]
+//! ```
+//!
+//! Files unchanged since the previous snapshot are hard-linked to its copy
+//! rather than copied again, so a snapshot costs little more than the files
+//! that changed.  A run that changed nothing since the latest snapshot
+//! doesn't get a new one.  `.git`, `target`, `node_modules` and the
+//! snapshots themselves are never included.
+//!
+//! The `snapshots` tool lists snapshots, shows what changed since one and
+//! restores files from it.  A restore snapshots the current state first, so
+//! it can be undone.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tracing::{debug, info, warn};
+
+use crate::wildcard::glob_match;
+
+/// Directories that are never snapshotted.
+const ALWAYS_EXCLUDED: &[&str] = &[".snapshots", ".git", "target", "node_modules"];
+
+const META_FILE: &str = "snapshot.json";
+const FILES_DIR: &str = "files";
+
+/// The `[snapshots]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// Snapshot the workspace before queued tasks, heartbeats and cron jobs.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Snapshots to keep; older ones are deleted.
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+    /// File or directory names to leave out; `*` matches any run of
+    /// characters, e.g. `*.iso`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+fn default_keep() -> usize {
+    10
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep: default_keep(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// A stored snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    /// Milliseconds since the epoch.
+    pub created_ms: u64,
+    /// What the snapshot was taken for, e.g. `task 3f2a`.
+    pub reason: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// How a file differs between a snapshot and the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// New since the snapshot.
+    Added(String),
+    Modified(String),
+    /// Gone since the snapshot.
+    Deleted(String),
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added(p) | Self::Modified(p) | Self::Deleted(p) => p,
+        }
+    }
+
+    /// `A`, `M` or `D`, as in `git status --short`.
+    pub fn marker(&self) -> char {
+        match self {
+            Self::Added(_) => 'A',
+            Self::Modified(_) => 'M',
+            Self::Deleted(_) => 'D',
+        }
+    }
+}
+
+/// Directory holding the snapshots of a workspace.
+pub fn snapshots_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".snapshots")
+}
+
+fn snapshot_dir(workspace_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(format!("Invalid snapshot id '{}'", id));
+    }
+    let dir = snapshots_dir(workspace_dir).join(id);
+    if !dir.join(META_FILE).is_file() {
+        return Err(format!("Snapshot '{}' not found", id));
+    }
+    Ok(dir)
+}
+
+fn excluded(name: &str, exclude: &[String]) -> bool {
+    ALWAYS_EXCLUDED.contains(&name) || exclude.iter().any(|p| glob_match(p, name))
+}
+
+/// `rel` joined with `/` on every platform.
+fn slash_path(rel: &Path) -> String {
+    let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+    parts.join("/")
+}
+
+/// Regular files under `root`, by `/`-separated relative path, with size
+/// and modification time (ms).
+fn scan(root: &Path, exclude: &[String]) -> BTreeMap<String, (u64, u64)> {
+    let mut files = BTreeMap::new();
+    let walker = walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !excluded(&e.file_name().to_string_lossy(), exclude));
+    for entry in walker.flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(rel) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(u64::MAX);
+        files.insert(slash_path(rel), (meta.len(), modified));
+    }
+    files
+}
+
+fn read_meta(dir: &Path) -> Option<Snapshot> {
+    let text = std::fs::read_to_string(dir.join(META_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Stored snapshots, newest first.
+pub fn list(workspace_dir: &Path) -> Vec<Snapshot> {
+    let Ok(entries) = std::fs::read_dir(snapshots_dir(workspace_dir)) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<Snapshot> = entries.flatten().filter_map(|e| read_meta(&e.path())).collect();
+    snapshots.sort_by(|a, b| b.created_ms.cmp(&a.created_ms).then_with(|| b.id.cmp(&a.id)));
+    snapshots
+}
+
+/// Whether a file of the workspace is the same as in snapshot `base`,
+/// judged by size and by not having been modified since `base` was taken.
+fn unchanged(current: (u64, u64), stored: Option<&(u64, u64)>, base: &Snapshot) -> bool {
+    stored.is_some_and(|(len, _)| *len == current.0) && current.1 < base.created_ms
+}
+
+/// Snapshot the workspace.  Returns the latest snapshot instead of taking
+/// a new one when nothing changed since it.
+pub fn create(workspace_dir: &Path, config: &SnapshotConfig, reason: &str) -> Result<Snapshot, String> {
+    let files = scan(workspace_dir, &config.exclude);
+    let latest = list(workspace_dir).into_iter().next();
+    let base = latest.as_ref().map(|s| {
+        let dir = snapshots_dir(workspace_dir).join(&s.id).join(FILES_DIR);
+        let stored = scan(&dir, &[]);
+        (s, dir, stored)
+    });
+
+    if let Some((latest, _, stored)) = &base {
+        let same = stored.len() == files.len()
+            && files.iter().all(|(path, current)| unchanged(*current, stored.get(path), latest));
+        if same {
+            debug!(id = %latest.id, "Workspace unchanged since the latest snapshot");
+            return Ok((*latest).clone());
+        }
+    }
+
+    let now = chrono::Local::now();
+    let id = now.format("%Y%m%d-%H%M%S-%3f").to_string();
+    let dir = snapshots_dir(workspace_dir).join(&id);
+    let files_dir = dir.join(FILES_DIR);
+    std::fs::create_dir_all(&files_dir).map_err(|e| format!("Failed to create {}: {}", files_dir.display(), e))?;
+
+    let mut bytes = 0;
+    let mut linked = 0;
+    for (path, current) in &files {
+        let src = workspace_dir.join(path);
+        let dst = files_dir.join(path);
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        // Snapshots are never written to, so sharing an unchanged file
+        // with the previous one is safe; sharing with the workspace isn't.
+        let reused = base.as_ref().is_some_and(|(latest, base_dir, stored)| {
+            unchanged(*current, stored.get(path), latest) && std::fs::hard_link(base_dir.join(path), &dst).is_ok()
+        });
+        if reused {
+            linked += 1;
+        } else if let Err(e) = std::fs::copy(&src, &dst) {
+            // A file can vanish mid-scan; the rest of the snapshot is
+            // still worth having.
+            warn!(path = %path, error = %e, "Skipping file in snapshot");
+            continue;
+        }
+        bytes += current.0;
+    }
+
+    let snapshot = Snapshot {
+        id,
+        created_ms: now.timestamp_millis() as u64,
+        reason: reason.to_string(),
+        files: files.len(),
+        bytes,
+    };
+    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(META_FILE), json).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    info!(id = %snapshot.id, files = snapshot.files, linked, reason, "Workspace snapshot taken");
+
+    prune(workspace_dir, config.keep.max(1));
+    Ok(snapshot)
+}
+
+/// Delete all but the newest `keep` snapshots.
+pub fn prune(workspace_dir: &Path, keep: usize) -> usize {
+    let mut removed = 0;
+    for old in list(workspace_dir).into_iter().skip(keep) {
+        let dir = snapshots_dir(workspace_dir).join(&old.id);
+        match std::fs::remove_dir_all(&dir) {
+            Ok(()) => removed += 1,
+            Err(e) => warn!(id = %old.id, error = %e, "Failed to delete old snapshot"),
+        }
+    }
+    removed
+}
+
+fn same_content(a: &Path, b: &Path) -> bool {
+    match (std::fs::read(a), std::fs::read(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// What changed in the workspace since snapshot `id`.
+pub fn diff(workspace_dir: &Path, id: &str, exclude: &[String]) -> Result<Vec<Change>, String> {
+    let files_dir = snapshot_dir(workspace_dir, id)?.join(FILES_DIR);
+    let stored = scan(&files_dir, &[]);
+    let current = scan(workspace_dir, exclude);
+
+    let mut changes = Vec::new();
+    for (path, (len, _)) in &stored {
+        match current.get(path) {
+            None => changes.push(Change::Deleted(path.clone())),
+            Some((current_len, _)) => {
+                if current_len != len || !same_content(&files_dir.join(path), &workspace_dir.join(path)) {
+                    changes.push(Change::Modified(path.clone()));
+                }
+            }
+        }
+    }
+    for path in current.keys().filter(|p| !stored.contains_key(*p)) {
+        changes.push(Change::Added(path.clone()));
+    }
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(changes)
+}
+
+/// Unified diff of one file between snapshot `id` and the workspace.
+pub fn diff_file(workspace_dir: &Path, id: &str, path: &str) -> Result<String, String> {
+    let rel = relative(path)?;
+    let old = snapshot_dir(workspace_dir, id)?.join(FILES_DIR).join(&rel);
+    let new = workspace_dir.join(&rel);
+    if !old.exists() && !new.exists() {
+        return Err(format!("'{}' is in neither the snapshot nor the workspace", path));
+    }
+    let null = if cfg!(windows) { "NUL" } else { "/dev/null" };
+    let old_arg = if old.exists() { old.as_os_str() } else { std::ffi::OsStr::new(null) };
+    let new_arg = if new.exists() { new.as_os_str() } else { std::ffi::OsStr::new(null) };
+    let output = Command::new("git")
+        .args(["diff", "--no-index", "--no-color", "--"])
+        .arg(old_arg)
+        .arg(new_arg)
+        .output()
+        .map_err(|e| format!("Failed to run git diff: {}", e))?;
+    // Exit status 1 just means the files differ.
+    if output.status.code().is_some_and(|c| c > 1) {
+        return Err(format!("git diff failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `path` as a relative path inside the workspace.
+fn relative(path: &str) -> Result<PathBuf, String> {
+    let mut rel = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            std::path::Component::Normal(part) => rel.push(part),
+            std::path::Component::CurDir => {}
+            _ => return Err(format!("'{}' is not a path inside the workspace", path)),
+        }
+    }
+    Ok(rel)
+}
+
+/// Outcome of a restore.
+#[derive(Debug)]
+pub struct Restored {
+    /// Snapshot of the state before the restore.
+    pub undo: Snapshot,
+    pub changes: Vec<Change>,
+}
+
+/// Undo the changes since snapshot `id`: modified and deleted files are
+/// copied back, added files removed.  Non-empty `paths` limit the restore
+/// to those files and directories.
+pub fn restore(workspace_dir: &Path, config: &SnapshotConfig, id: &str, paths: &[String]) -> Result<Restored, String> {
+    let files_dir = snapshot_dir(workspace_dir, id)?.join(FILES_DIR);
+    let prefixes = paths
+        .iter()
+        .map(|p| relative(p).map(|rel| slash_path(&rel)))
+        .collect::<Result<Vec<String>, String>>()?;
+    let selected = |path: &str| {
+        prefixes.is_empty()
+            || prefixes
+                .iter()
+                .any(|p| p.is_empty() || path == p || path.starts_with(&format!("{}/", p)))
+    };
+    let changes: Vec<Change> = diff(workspace_dir, id, &config.exclude)?
+        .into_iter()
+        .filter(|c| selected(c.path()))
+        .collect();
+    if changes.is_empty() {
+        return Err(format!("Nothing to restore — matches snapshot {}", id));
+    }
+
+    let undo = create(workspace_dir, config, &format!("before restoring {}", id))?;
+    for change in &changes {
+        let target = workspace_dir.join(change.path());
+        match change {
+            Change::Added(_) => {
+                std::fs::remove_file(&target).map_err(|e| format!("Failed to remove {}: {}", target.display(), e))?;
+            }
+            Change::Modified(path) | Change::Deleted(path) => {
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                // Copy rather than link so later edits don't reach the snapshot.
+                let _ = std::fs::remove_file(&target);
+                std::fs::copy(files_dir.join(path), &target)
+                    .map_err(|e| format!("Failed to restore {}: {}", path, e))?;
+            }
+        }
+    }
+    info!(id, files = changes.len(), undo = %undo.id, "Restored workspace snapshot");
+    Ok(Restored { undo, changes })
+}
+
+// ── Gateway state ───────────────────────────────────────────────────────────
+
+static CONFIG: Mutex<Option<SnapshotConfig>> = Mutex::new(None);
+
+/// Register the `[snapshots]` config.  Called at startup and on reload.
+pub fn set_config(config: SnapshotConfig) {
+    if let Ok(mut guard) = CONFIG.lock() {
+        *guard = Some(config);
+    }
+}
+
+/// The registered config, or the defaults.
+pub fn config() -> SnapshotConfig {
+    CONFIG.lock().ok().and_then(|g| g.clone()).unwrap_or_default()
+}
+
+/// Snapshot the workspace before an autonomous run, if enabled.  Failures
+/// are logged; they don't stop the run.
+pub fn before_run(workspace_dir: &Path, reason: &str) {
+    let config = config();
+    if !config.enabled {
+        return;
+    }
+    if let Err(e) = create(workspace_dir, &config, reason) {
+        warn!(reason, error = %e, "Failed to snapshot the workspace");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, text: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    fn config(keep: usize) -> SnapshotConfig {
+        SnapshotConfig { enabled: true, keep, exclude: vec!["*.iso".into()] }
+    }
+
+    #[test]
+    fn test_create_diff_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        write(ws, "notes.md", "keep me");
+        write(ws, "src/lib.rs", "fn a() {}");
+        write(ws, "big.iso", "x");
+        write(ws, ".git/HEAD", "ref");
+
+        let snap = create(ws, &config(5), "task 1").unwrap();
+        assert_eq!(snap.files, 2);
+        assert!(diff(ws, &snap.id, &config(5).exclude).unwrap().is_empty());
+
+        // A bad run.
+        std::fs::remove_file(ws.join("notes.md")).unwrap();
+        write(ws, "src/lib.rs", "garbage");
+        write(ws, "junk.txt", "junk");
+        let changes = diff(ws, &snap.id, &config(5).exclude).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                Change::Added("junk.txt".into()),
+                Change::Deleted("notes.md".into()),
+                Change::Modified("src/lib.rs".into()),
+            ]
+        );
+
+        let restored = restore(ws, &config(5), &snap.id, &["src".into()]).unwrap();
+        assert_eq!(restored.changes, vec![Change::Modified("src/lib.rs".into())]);
+        assert_eq!(std::fs::read_to_string(ws.join("src/lib.rs")).unwrap(), "fn a() {}");
+        assert!(!ws.join("notes.md").exists());
+
+        restore(ws, &config(5), &snap.id, &[]).unwrap();
+        assert_eq!(std::fs::read_to_string(ws.join("notes.md")).unwrap(), "keep me");
+        assert!(!ws.join("junk.txt").exists());
+        assert!(restore(ws, &config(5), &snap.id, &[]).unwrap_err().contains("Nothing to restore"));
+        assert!(restore(ws, &config(5), &snap.id, &["../x".into()]).is_err());
+    }
+
+    #[test]
+    fn test_keeps_newest_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        for i in 0..4 {
+            write(ws, "file.txt", &"x".repeat(i + 1));
+            create(ws, &config(2), &format!("run {}", i)).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let snapshots = list(ws);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].reason, "run 3");
+        assert_eq!(snapshots[1].reason, "run 2");
+    }
+}
@@ -0,0 +1,550 @@
+//! Persistent priority queue for background agent work.
+//!
+//! Tasks are enqueued from the `tasks` tool, the `/task` messenger command,
+//! or the gateway's HTTP endpoint, and persisted to disk.  The gateway's
+//! task worker claims them in priority order (FIFO within a priority),
+//! runs each one in its own worker session, and retries failures up to a
+//! per-task limit — so large batches queue up instead of all running at once.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+
+/// Unique identifier for a queued task.
+pub type TaskId = String;
+
+/// Configuration for the gateway task worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskQueueConfig {
+    /// Run queued tasks in the gateway.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Maximum number of tasks running at the same time.
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+
+    /// How often the worker checks the queue, in seconds.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+
+    /// Retries for tasks that don't specify their own limit.
+    #[serde(default = "default_max_retries")]
+    pub default_max_retries: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_concurrent() -> usize {
+    2
+}
+
+fn default_poll_interval_secs() -> u64 {
+    5
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+impl Default for TaskQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_true(),
+            max_concurrent: default_max_concurrent(),
+            poll_interval_secs: default_poll_interval_secs(),
+            default_max_retries: default_max_retries(),
+        }
+    }
+}
+
+/// Directory holding the task queue for a workspace.
+pub fn queue_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join(".tasks")
+}
+
+/// Generate a unique task ID.
+fn generate_task_id() -> TaskId {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("task-{:x}", timestamp)
+}
+
+/// Get current time in milliseconds.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Task priority. Higher priorities are claimed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl std::str::FromStr for TaskPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "urgent" => Ok(Self::Urgent),
+            other => Err(format!(
+                "Unknown priority: {}. Valid: low, normal, high, urgent",
+                other
+            )),
+        }
+    }
+}
+
+/// Lifecycle state of a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+/// A unit of background work.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedTask {
+    pub id: TaskId,
+    /// Prompt given to the worker session.
+    pub prompt: String,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    pub status: TaskStatus,
+    /// Number of times the task has been started.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Number of retries allowed after the first attempt fails.
+    pub max_retries: u32,
+    /// Where the task came from (tool, messenger, http).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub created_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_ms: Option<u64>,
+    /// Session key of the worker that ran (or is running) the task.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl QueuedTask {
+    /// Create a new queued task.
+    pub fn new(prompt: &str, priority: TaskPriority, max_retries: u32) -> Self {
+        Self {
+            id: generate_task_id(),
+            prompt: prompt.to_string(),
+            priority,
+            status: TaskStatus::Queued,
+            attempts: 0,
+            max_retries,
+            source: None,
+            created_ms: now_millis(),
+            started_ms: None,
+            finished_ms: None,
+            session_key: None,
+            result: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Serialises read-modify-write cycles on the tasks file: the worker,
+/// the `tasks` tool and the HTTP endpoint each open their own queue.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Cancellation tokens of the tasks this process is running.
+fn running_turns() -> &'static Mutex<HashMap<TaskId, CancellationToken>> {
+    static RUNNING: OnceLock<Mutex<HashMap<TaskId, CancellationToken>>> = OnceLock::new();
+    RUNNING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Register a task the worker is about to run; the token is cancelled
+/// when the task is cancelled.
+pub fn track_running(task_id: &str) -> CancellationToken {
+    let token = CancellationToken::new();
+    if let Ok(mut running) = running_turns().lock() {
+        running.insert(task_id.to_string(), token.clone());
+    }
+    token
+}
+
+/// Forget a task once its turn has ended.
+pub fn untrack_running(task_id: &str) {
+    if let Ok(mut running) = running_turns().lock() {
+        running.remove(task_id);
+    }
+}
+
+fn load(tasks_path: &Path) -> Result<HashMap<TaskId, QueuedTask>, String> {
+    if !tasks_path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = fs::read_to_string(tasks_path)
+        .map_err(|e| format!("Failed to read tasks file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse tasks file: {}", e))
+}
+
+/// Task queue that persists tasks to disk.
+pub struct TaskQueue {
+    /// Path to the tasks file.
+    tasks_path: PathBuf,
+    /// In-memory task cache.
+    tasks: HashMap<TaskId, QueuedTask>,
+}
+
+impl TaskQueue {
+    /// Create or load a task queue from the given directory.
+    pub fn new(queue_dir: &Path) -> Result<Self, String> {
+        let tasks_path = queue_dir.join("tasks.json");
+
+        fs::create_dir_all(queue_dir)
+            .map_err(|e| format!("Failed to create task queue directory: {}", e))?;
+
+        let tasks = {
+            let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            load(&tasks_path)?
+        };
+
+        Ok(Self { tasks_path, tasks })
+    }
+
+    /// Save tasks to disk, replacing the file atomically.
+    fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(&self.tasks)
+            .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
+        let tmp = self.tasks_path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| format!("Failed to write tasks file: {}", e))?;
+        fs::rename(&tmp, &self.tasks_path)
+            .map_err(|e| format!("Failed to write tasks file: {}", e))?;
+        Ok(())
+    }
+
+    /// Apply `f` to the tasks as they are on disk and save the result, so
+    /// changes made through other queues since this one was opened
+    /// aren't lost.
+    fn update<R>(
+        &mut self,
+        f: impl FnOnce(&mut HashMap<TaskId, QueuedTask>) -> Result<R, String>,
+    ) -> Result<R, String> {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.tasks = load(&self.tasks_path)?;
+        let result = f(&mut self.tasks)?;
+        self.save()?;
+        Ok(result)
+    }
+
+    /// Add a task to the queue.
+    pub fn enqueue(&mut self, task: QueuedTask) -> Result<TaskId, String> {
+        let id = task.id.clone();
+        self.update(|tasks| {
+            tasks.insert(id.clone(), task);
+            Ok(())
+        })?;
+        Ok(id)
+    }
+
+    /// Get a task by ID.
+    pub fn get(&self, task_id: &str) -> Option<&QueuedTask> {
+        self.tasks.get(task_id)
+    }
+
+    /// List tasks in claim order: running first, then queued by priority,
+    /// then finished tasks (most recent first).
+    pub fn list(&self, include_finished: bool) -> Vec<&QueuedTask> {
+        let mut tasks: Vec<_> = self
+            .tasks
+            .values()
+            .filter(|t| {
+                include_finished
+                    || matches!(t.status, TaskStatus::Queued | TaskStatus::Running)
+            })
+            .collect();
+        tasks.sort_by(|a, b| {
+            let rank = |t: &QueuedTask| match t.status {
+                TaskStatus::Running => 0,
+                TaskStatus::Queued => 1,
+                _ => 2,
+            };
+            rank(a)
+                .cmp(&rank(b))
+                .then(b.priority.cmp(&a.priority))
+                .then_with(|| match a.status {
+                    TaskStatus::Queued | TaskStatus::Running => a.created_ms.cmp(&b.created_ms),
+                    _ => b.finished_ms.cmp(&a.finished_ms),
+                })
+        });
+        tasks
+    }
+
+    /// Number of tasks currently running.
+    pub fn running(&self) -> usize {
+        running_count(&self.tasks)
+    }
+
+    /// Claim the next task if fewer than `max_concurrent` are running.
+    ///
+    /// The claimed task is marked running and its attempt count bumped.
+    pub fn claim_next(&mut self, max_concurrent: usize) -> Result<Option<QueuedTask>, String> {
+        self.update(|tasks| {
+            if running_count(tasks) >= max_concurrent {
+                return Ok(None);
+            }
+
+            let next_id = tasks
+                .values()
+                .filter(|t| t.status == TaskStatus::Queued)
+                .max_by(|a, b| {
+                    a.priority
+                        .cmp(&b.priority)
+                        .then(b.created_ms.cmp(&a.created_ms))
+                })
+                .map(|t| t.id.clone());
+
+            let Some(id) = next_id else { return Ok(None) };
+            let task = task_mut(tasks, &id)?;
+            task.status = TaskStatus::Running;
+            task.attempts += 1;
+            task.started_ms = Some(now_millis());
+            Ok(Some(task.clone()))
+        })
+    }
+
+    /// Record the worker session running a task.
+    pub fn set_session(&mut self, task_id: &str, session_key: &str) -> Result<(), String> {
+        self.update(|tasks| {
+            task_mut(tasks, task_id)?.session_key = Some(session_key.to_string());
+            Ok(())
+        })
+    }
+
+    /// Mark a task as finished successfully.  A task cancelled while it
+    /// ran stays cancelled.
+    pub fn complete(&mut self, task_id: &str, result: &str) -> Result<(), String> {
+        self.update(|tasks| {
+            let task = task_mut(tasks, task_id)?;
+            if task.status == TaskStatus::Cancelled {
+                return Ok(());
+            }
+            task.status = TaskStatus::Done;
+            task.result = Some(result.to_string());
+            task.finished_ms = Some(now_millis());
+            Ok(())
+        })
+    }
+
+    /// Record a failed attempt.  The task is re-queued while it has retries
+    /// left; returns `true` if it will be retried.
+    pub fn fail(&mut self, task_id: &str, error: &str) -> Result<bool, String> {
+        self.update(|tasks| {
+            let task = task_mut(tasks, task_id)?;
+            if task.status == TaskStatus::Cancelled {
+                return Ok(false);
+            }
+            task.last_error = Some(error.to_string());
+            let retry = task.attempts <= task.max_retries;
+            if retry {
+                task.status = TaskStatus::Queued;
+            } else {
+                task.status = TaskStatus::Failed;
+                task.finished_ms = Some(now_millis());
+            }
+            Ok(retry)
+        })
+    }
+
+    /// Cancel a queued or running task.  A running task's turn is stopped
+    /// if this process is running it.
+    pub fn cancel(&mut self, task_id: &str) -> Result<(), String> {
+        self.update(|tasks| {
+            let task = task_mut(tasks, task_id)?;
+            if !matches!(task.status, TaskStatus::Queued | TaskStatus::Running) {
+                return Err(format!("Task is not queued or running: {:?}", task.status));
+            }
+            task.status = TaskStatus::Cancelled;
+            task.finished_ms = Some(now_millis());
+            Ok(())
+        })?;
+        if let Some(token) = running_turns().lock().ok().and_then(|r| r.get(task_id).cloned()) {
+            token.cancel();
+        }
+        Ok(())
+    }
+
+    /// Put tasks left running by a previous gateway process back in the queue.
+    pub fn requeue_stale(&mut self) -> Result<usize, String> {
+        self.update(|tasks| {
+            let mut count = 0;
+            for task in tasks.values_mut() {
+                if task.status == TaskStatus::Running {
+                    task.status = TaskStatus::Queued;
+                    count += 1;
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    /// Remove finished, failed, and cancelled tasks.
+    pub fn prune(&mut self) -> Result<usize, String> {
+        self.update(|tasks| {
+            let before = tasks.len();
+            tasks.retain(|_, t| matches!(t.status, TaskStatus::Queued | TaskStatus::Running));
+            Ok(before - tasks.len())
+        })
+    }
+}
+
+fn running_count(tasks: &HashMap<TaskId, QueuedTask>) -> usize {
+    tasks
+        .values()
+        .filter(|t| t.status == TaskStatus::Running)
+        .count()
+}
+
+fn task_mut<'a>(tasks: &'a mut HashMap<TaskId, QueuedTask>, task_id: &str) -> Result<&'a mut QueuedTask, String> {
+    tasks
+        .get_mut(task_id)
+        .ok_or_else(|| format!("Task not found: {}", task_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_claim_order_by_priority() {
+        let dir = TempDir::new().unwrap();
+        let mut queue = TaskQueue::new(dir.path()).unwrap();
+
+        let low = queue.enqueue(QueuedTask::new("low", TaskPriority::Low, 0)).unwrap();
+        let urgent = queue.enqueue(QueuedTask::new("urgent", TaskPriority::Urgent, 0)).unwrap();
+        let normal = queue.enqueue(QueuedTask::new("normal", TaskPriority::Normal, 0)).unwrap();
+
+        assert_eq!(queue.claim_next(10).unwrap().unwrap().id, urgent);
+        assert_eq!(queue.claim_next(10).unwrap().unwrap().id, normal);
+        assert_eq!(queue.claim_next(10).unwrap().unwrap().id, low);
+        assert!(queue.claim_next(10).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let dir = TempDir::new().unwrap();
+        let mut queue = TaskQueue::new(dir.path()).unwrap();
+        queue.enqueue(QueuedTask::new("a", TaskPriority::Normal, 0)).unwrap();
+        queue.enqueue(QueuedTask::new("b", TaskPriority::Normal, 0)).unwrap();
+
+        let first = queue.claim_next(1).unwrap().unwrap();
+        assert!(queue.claim_next(1).unwrap().is_none());
+
+        queue.complete(&first.id, "ok").unwrap();
+        assert!(queue.claim_next(1).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_retries() {
+        let dir = TempDir::new().unwrap();
+        let mut queue = TaskQueue::new(dir.path()).unwrap();
+        let id = queue.enqueue(QueuedTask::new("flaky", TaskPriority::Normal, 1)).unwrap();
+
+        queue.claim_next(1).unwrap().unwrap();
+        assert!(queue.fail(&id, "boom").unwrap());
+        assert_eq!(queue.get(&id).unwrap().status, TaskStatus::Queued);
+
+        queue.claim_next(1).unwrap().unwrap();
+        assert!(!queue.fail(&id, "boom again").unwrap());
+        let task = queue.get(&id).unwrap();
+        assert_eq!(task.status, TaskStatus::Failed);
+        assert_eq!(task.attempts, 2);
+    }
+
+    #[test]
+    fn test_persistence_and_stale_requeue() {
+        let dir = TempDir::new().unwrap();
+        let id = {
+            let mut queue = TaskQueue::new(dir.path()).unwrap();
+            let id = queue.enqueue(QueuedTask::new("x", TaskPriority::High, 0)).unwrap();
+            queue.claim_next(1).unwrap();
+            id
+        };
+
+        let mut queue = TaskQueue::new(dir.path()).unwrap();
+        assert_eq!(queue.get(&id).unwrap().status, TaskStatus::Running);
+        assert_eq!(queue.requeue_stale().unwrap(), 1);
+        assert_eq!(queue.get(&id).unwrap().status, TaskStatus::Queued);
+    }
+
+    #[test]
+    fn test_cancel_twice_fails() {
+        let dir = TempDir::new().unwrap();
+        let mut queue = TaskQueue::new(dir.path()).unwrap();
+        let id = queue.enqueue(QueuedTask::new("x", TaskPriority::Normal, 0)).unwrap();
+        queue.cancel(&id).unwrap();
+        assert!(queue.cancel(&id).is_err());
+        assert_eq!(queue.prune().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_cancel_running_stops_turn() {
+        let dir = TempDir::new().unwrap();
+        let mut queue = TaskQueue::new(dir.path()).unwrap();
+        let id = queue.enqueue(QueuedTask::new("x", TaskPriority::Normal, 0)).unwrap();
+        queue.claim_next(1).unwrap().unwrap();
+        let token = track_running(&id);
+
+        queue.cancel(&id).unwrap();
+        assert!(token.is_cancelled());
+        untrack_running(&id);
+
+        // The worker's late result doesn't resurrect the task.
+        queue.complete(&id, "done anyway").unwrap();
+        assert_eq!(queue.get(&id).unwrap().status, TaskStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_queues_opened_together_keep_each_others_changes() {
+        let dir = TempDir::new().unwrap();
+        let mut worker = TaskQueue::new(dir.path()).unwrap();
+        let mut tool = TaskQueue::new(dir.path()).unwrap();
+
+        let a = worker.enqueue(QueuedTask::new("a", TaskPriority::Normal, 0)).unwrap();
+        let b = tool.enqueue(QueuedTask::new("b", TaskPriority::Normal, 0)).unwrap();
+        worker.claim_next(10).unwrap();
+
+        let queue = TaskQueue::new(dir.path()).unwrap();
+        assert!(queue.get(&a).is_some());
+        assert!(queue.get(&b).is_some());
+        assert_eq!(queue.running(), 1);
+        assert!(!dir.path().join("tasks.json.tmp").exists());
+    }
+}
@@ -0,0 +1,192 @@
+//! Outbound message templates.
+//!
+//! Recurring messages (a daily standup, a "left work" note, a cron report)
+//! live as Markdown files in the workspace's `templates/` directory, e.g.
+//! `templates/standup.md`:
+//!
+//! ```markdown
+//! **Standup — {{weekday}} {{date}}**
+//!
+//! Yesterday: {{yesterday}}
+//! Today: {{today}}
+//! ```
+//!
+//! `{{name}}` is replaced with a variable; `{{name|fallback}}` uses the
+//! fallback when the variable isn't set.  Besides the variables the caller
+//! passes, every template can use `date`, `time`, `datetime`, `weekday` and
+//! `workspace`.  A placeholder with neither a value nor a fallback is an
+//! error, so a half-filled message is never sent.
+//!
+//! The `message` tool takes `template` + `vars`, cron jobs take
+//! `delivery.template`, and presence rules take `template`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the templates of a workspace.
+pub fn templates_dir(workspace_dir: &Path) -> PathBuf {
+    workspace_dir.join("templates")
+}
+
+/// Names of the available templates, sorted.
+pub fn list(workspace_dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(templates_dir(workspace_dir)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "md"))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Read template `name` (with or without `.md`).
+pub fn load(workspace_dir: &Path, name: &str) -> Result<String, String> {
+    let name = name.trim().trim_end_matches(".md");
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Invalid template name '{}'", name));
+    }
+    let path = templates_dir(workspace_dir).join(format!("{}.md", name));
+    std::fs::read_to_string(&path).map_err(|_| {
+        let available = list(workspace_dir);
+        if available.is_empty() {
+            format!("Template '{}' not found — add it as templates/{}.md", name, name)
+        } else {
+            format!("Template '{}' not found. Available: {}", name, available.join(", "))
+        }
+    })
+}
+
+/// Variables every template can use.
+pub fn builtin_vars(workspace_dir: &Path) -> HashMap<String, String> {
+    let now = chrono::Local::now();
+    let workspace = workspace_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+        ("datetime".to_string(), now.format("%Y-%m-%d %H:%M").to_string()),
+        ("weekday".to_string(), now.format("%A").to_string()),
+        ("workspace".to_string(), workspace),
+    ])
+}
+
+/// Variables from a JSON object; numbers and booleans are stringified,
+/// anything else is ignored.
+pub fn vars_from_json(value: &serde_json::Value) -> HashMap<String, String> {
+    value
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .filter_map(|(k, v)| {
+                    let text = match v {
+                        serde_json::Value::String(s) => s.clone(),
+                        serde_json::Value::Number(n) => n.to_string(),
+                        serde_json::Value::Bool(b) => b.to_string(),
+                        _ => return None,
+                    };
+                    Some((k.clone(), text))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Fill the placeholders in `template`.
+pub fn render(template: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            // Unclosed braces are literal text.
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let inner = &after[..end];
+        let (name, fallback) = match inner.split_once('|') {
+            Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+            None => (inner.trim(), None),
+        };
+        match (vars.get(name), fallback) {
+            (Some(value), _) => out.push_str(value),
+            (None, Some(fallback)) => out.push_str(fallback),
+            (None, None) => {
+                if !missing.iter().any(|m| m == name) {
+                    missing.push(name.to_string());
+                }
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(format!("Template variables not set: {}", missing.join(", ")))
+    }
+}
+
+/// Load template `name` and render it with the built-in variables plus
+/// `vars` (which win on conflicts).
+pub fn render_named(workspace_dir: &Path, name: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let template = load(workspace_dir, name)?;
+    let mut all = builtin_vars(workspace_dir);
+    all.extend(vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+    render(template.trim_end(), &all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_render_variables_and_fallbacks() {
+        let text = render(
+            "Hi {{ name }}, build {{status|unknown}}. {not a var} {{",
+            &vars(&[("name", "Ada")]),
+        )
+        .unwrap();
+        assert_eq!(text, "Hi Ada, build unknown. {not a var} {{");
+    }
+
+    #[test]
+    fn test_render_reports_missing_variables() {
+        let err = render("{{a}} {{b}} {{a}}", &HashMap::new()).unwrap_err();
+        assert_eq!(err, "Template variables not set: a, b");
+    }
+
+    #[test]
+    fn test_render_named_from_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(templates_dir(dir.path())).unwrap();
+        std::fs::write(templates_dir(dir.path()).join("standup.md"), "Today ({{date}}): {{today}}\n").unwrap();
+
+        assert_eq!(list(dir.path()), vec!["standup".to_string()]);
+        let text = render_named(dir.path(), "standup", &vars(&[("today", "ship it")])).unwrap();
+        assert!(text.starts_with("Today (20") && text.ends_with("): ship it"));
+        assert!(load(dir.path(), "../secrets").is_err());
+        assert!(load(dir.path(), "weekly").unwrap_err().contains("Available: standup"));
+    }
+
+    #[test]
+    fn test_vars_from_json() {
+        let v = vars_from_json(&serde_json::json!({"n": 3, "ok": true, "s": "x", "list": [1]}));
+        assert_eq!(v.get("n").map(String::as_str), Some("3"));
+        assert_eq!(v.get("ok").map(String::as_str), Some("true"));
+        assert!(!v.contains_key("list"));
+    }
+}
@@ -0,0 +1,410 @@
+//! Scenario test harness for embedding crates.
+//!
+//! Spins up an in-process gateway on a random loopback port, backed by the
+//! built-in `mock` provider and throwaway directories, and drives it over
+//! the real WebSocket protocol.  Enable with the `testkit` feature:
+//!
+//! ```ignore
+//! use rustyclaw_core::testkit::TestGateway;
+//!
+//! let gw = TestGateway::builder()
+//!     .fixture(serde_json::json!({
+//!         "turns": [{ "responses": [
+//!             { "tool_calls": [{ "name": "list_directory", "arguments": { "path": "." } }] },
+//!             { "text": "done" }
+//!         ] }]
+//!     }))
+//!     .start()
+//!     .await?;
+//! let mut client = gw.connect().await?;
+//! let run = client.chat("list the workspace").await?;
+//! run.assert_tool_called("list_directory");
+//! assert_eq!(run.text, "done");
+//! gw.shutdown().await;
+//! ```
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{Config, ModelProvider};
+use crate::gateway::{
+    deserialize_frame, mock_provider::MOCK_PROVIDER, run_gateway, serialize_frame, ChatMessage,
+    ClientFrame, ClientFrameType, ClientPayload, GatewayOptions, ModelContext, ServerFrame,
+    ServerPayload, SharedSkillManager, SharedVault,
+};
+use crate::secrets::SecretsManager;
+use crate::skills::SkillManager;
+
+/// How long to wait for any single frame before failing the test.
+const FRAME_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Builder for a [`TestGateway`].
+#[derive(Default)]
+pub struct TestGatewayBuilder {
+    fixture: Option<serde_json::Value>,
+    files: Vec<(PathBuf, String)>,
+    dry_run: bool,
+    configure: Option<Box<dyn FnOnce(&mut Config) + Send>>,
+}
+
+impl TestGatewayBuilder {
+    /// Script the mock provider (see [`crate::gateway::mock_provider`]).
+    /// Without a fixture the mock echoes each user message.
+    pub fn fixture(mut self, fixture: serde_json::Value) -> Self {
+        self.fixture = Some(fixture);
+        self
+    }
+
+    /// Seed a file in the workspace before the gateway starts.
+    pub fn file(mut self, path: impl Into<PathBuf>, content: impl Into<String>) -> Self {
+        self.files.push((path.into(), content.into()));
+        self
+    }
+
+    /// Run with mutating tools simulated.
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+
+    /// Adjust the generated config before the gateway starts.
+    pub fn configure(mut self, f: impl FnOnce(&mut Config) + Send + 'static) -> Self {
+        self.configure = Some(Box::new(f));
+        self
+    }
+
+    /// Start the gateway and wait until it accepts connections.
+    pub async fn start(self) -> Result<TestGateway> {
+        let dir = TempDir::new().context("Failed to create test directory")?;
+
+        let mut config = Config::default();
+        config.settings_dir = dir.path().to_path_buf();
+        config.dry_run = self.dry_run;
+
+        let workspace = config.workspace_dir();
+        std::fs::create_dir_all(&workspace).context("Failed to create test workspace")?;
+        for (path, content) in &self.files {
+            let target = workspace.join(path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, content)
+                .with_context(|| format!("Failed to seed {}", target.display()))?;
+        }
+
+        let fixture_path = match &self.fixture {
+            Some(fixture) => {
+                let path = dir.path().join("mock_fixture.json");
+                std::fs::write(&path, serde_json::to_string_pretty(fixture)?)?;
+                Some(path.to_string_lossy().to_string())
+            }
+            None => None,
+        };
+        config.model = Some(ModelProvider {
+            provider: MOCK_PROVIDER.to_string(),
+            model: Some("mock".to_string()),
+            base_url: fixture_path,
+        });
+
+        if let Some(configure) = self.configure {
+            configure(&mut config);
+        }
+
+        let port = free_port()?;
+        let listen = format!("127.0.0.1:{}", port);
+
+        let mut secrets = SecretsManager::new(config.credentials_dir());
+        let model_ctx = ModelContext::resolve(&config, &mut secrets)?;
+        let vault: SharedVault = Arc::new(tokio::sync::Mutex::new(secrets));
+        let skills: SharedSkillManager =
+            Arc::new(tokio::sync::Mutex::new(SkillManager::with_dirs(Vec::new())));
+
+        let cancel = CancellationToken::new();
+        let options = GatewayOptions {
+            listen: listen.clone(),
+            tls_cert: None,
+            tls_key: None,
+            record: None,
+        };
+        let handle = tokio::spawn(run_gateway(
+            config,
+            options,
+            Some(model_ctx),
+            vault,
+            skills,
+            cancel.clone(),
+        ));
+
+        // Wait for the listener to come up.
+        let mut ready = false;
+        for _ in 0..100 {
+            if TcpStream::connect(&listen).await.is_ok() {
+                ready = true;
+                break;
+            }
+            if handle.is_finished() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        if !ready {
+            cancel.cancel();
+            anyhow::bail!("Test gateway did not start listening on {}", listen);
+        }
+
+        Ok(TestGateway {
+            url: format!("ws://{}", listen),
+            workspace,
+            cancel,
+            handle,
+            _dir: dir,
+        })
+    }
+}
+
+/// An in-process gateway with its own temporary settings and workspace.
+pub struct TestGateway {
+    url: String,
+    workspace: PathBuf,
+    cancel: CancellationToken,
+    handle: JoinHandle<Result<()>>,
+    _dir: TempDir,
+}
+
+impl TestGateway {
+    /// Start a gateway whose mock provider echoes user messages.
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    /// Configure a gateway before starting it.
+    pub fn builder() -> TestGatewayBuilder {
+        TestGatewayBuilder::default()
+    }
+
+    /// WebSocket URL of the gateway.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Workspace directory the gateway's tools operate in.
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    /// Open a protocol client and wait for the hello frame.
+    pub async fn connect(&self) -> Result<TestClient> {
+        let (ws, _) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.url))?;
+        let mut client = TestClient { ws };
+        loop {
+            let frame = client.next_frame().await?;
+            if matches!(frame.payload, ServerPayload::Hello { .. }) {
+                return Ok(client);
+            }
+        }
+    }
+
+    /// Stop the gateway and remove its directories.
+    pub async fn shutdown(self) {
+        self.cancel.cancel();
+        let _ = tokio::time::timeout(Duration::from_secs(5), self.handle).await;
+    }
+}
+
+/// A tool call observed during a chat turn.
+#[derive(Debug, Clone)]
+pub struct ToolExecution {
+    pub id: String,
+    pub name: String,
+    /// Arguments as sent by the gateway (JSON text).
+    pub arguments: String,
+    pub result: Option<String>,
+    pub is_error: bool,
+}
+
+/// Everything the gateway sent back for one chat turn.
+#[derive(Debug, Default)]
+pub struct ChatRun {
+    /// Concatenated response text.
+    pub text: String,
+    pub tools: Vec<ToolExecution>,
+    pub errors: Vec<crate::error::Error>,
+    pub info: Vec<String>,
+    pub frames: Vec<ServerFrame>,
+}
+
+impl ChatRun {
+    /// Names of the tools called, in order.
+    pub fn tool_names(&self) -> Vec<&str> {
+        self.tools.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    /// The first call of the named tool, if any.
+    pub fn tool(&self, name: &str) -> Option<&ToolExecution> {
+        self.tools.iter().find(|t| t.name == name)
+    }
+
+    /// Panic unless the named tool was called; returns the first call.
+    pub fn assert_tool_called(&self, name: &str) -> &ToolExecution {
+        self.tool(name).unwrap_or_else(|| {
+            panic!("expected tool `{}` to be called; called: {:?}", name, self.tool_names())
+        })
+    }
+
+    /// Panic if the named tool was called.
+    pub fn assert_tool_not_called(&self, name: &str) {
+        assert!(
+            self.tool(name).is_none(),
+            "expected tool `{}` not to be called; called: {:?}",
+            name,
+            self.tool_names()
+        );
+    }
+}
+
+/// A WebSocket client speaking the gateway protocol.
+pub struct TestClient {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl TestClient {
+    /// Send a raw client frame.
+    pub async fn send(&mut self, frame: &ClientFrame) -> Result<()> {
+        let bytes = serialize_frame(frame).map_err(|e| anyhow::anyhow!(e))?;
+        self.ws
+            .send(Message::Binary(bytes.into()))
+            .await
+            .context("Failed to send frame")
+    }
+
+    /// Receive the next server frame.
+    pub async fn next_frame(&mut self) -> Result<ServerFrame> {
+        loop {
+            let msg = tokio::time::timeout(FRAME_TIMEOUT, self.ws.next())
+                .await
+                .context("Timed out waiting for a gateway frame")?
+                .context("Gateway closed the connection")??;
+            match msg {
+                Message::Binary(data) => {
+                    return deserialize_frame(&data).map_err(|e| anyhow::anyhow!(e));
+                }
+                Message::Close(_) => anyhow::bail!("Gateway closed the connection"),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Send a single user prompt and collect the response.
+    pub async fn chat(&mut self, prompt: &str) -> Result<ChatRun> {
+        self.chat_messages(vec![ChatMessage::text("user", prompt)]).await
+    }
+
+    /// Send a full conversation and collect the response.
+    pub async fn chat_messages(&mut self, messages: Vec<ChatMessage>) -> Result<ChatRun> {
+        self.send(&ClientFrame {
+            frame_type: ClientFrameType::Chat,
+            payload: ClientPayload::Chat { messages },
+        })
+        .await?;
+
+        let mut run = ChatRun::default();
+        loop {
+            let frame = self.next_frame().await?;
+            let done = match &frame.payload {
+                ServerPayload::Chunk { delta } => {
+                    run.text.push_str(delta);
+                    false
+                }
+                ServerPayload::ToolCall { id, name, arguments } => {
+                    run.tools.push(ToolExecution {
+                        id: id.clone(),
+                        name: name.clone(),
+                        arguments: arguments.clone(),
+                        result: None,
+                        is_error: false,
+                    });
+                    false
+                }
+                ServerPayload::ToolResult { id, result, is_error, .. } => {
+                    if let Some(t) = run.tools.iter_mut().rev().find(|t| &t.id == id) {
+                        t.result = Some(result.clone());
+                        t.is_error = *is_error;
+                    }
+                    false
+                }
+                ServerPayload::Error { error, .. } => {
+                    run.errors.push(error.clone());
+                    false
+                }
+                ServerPayload::Info { message } => {
+                    run.info.push(message.clone());
+                    false
+                }
+                ServerPayload::ResponseDone { .. } => true,
+                _ => false,
+            };
+            run.frames.push(frame);
+            if done {
+                return Ok(run);
+            }
+        }
+    }
+}
+
+/// Reserve a free loopback port.
+fn free_port() -> Result<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0").context("Failed to reserve a port")?;
+    Ok(listener.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_echo_round_trip() {
+        let gw = TestGateway::start().await.unwrap();
+        let mut client = gw.connect().await.unwrap();
+        let run = client.chat("ping").await.unwrap();
+        assert!(run.text.contains("ping"), "{:?}", run);
+        assert!(run.tools.is_empty());
+        gw.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_scripted_tool_call() {
+        let gw = TestGateway::builder()
+            .file("notes.txt", "remember the milk")
+            .fixture(json!({
+                "turns": [{ "responses": [
+                    { "tool_calls": [{ "name": "read_file", "arguments": { "path": "notes.txt" } }] },
+                    { "text": "The note says to remember the milk." }
+                ] }]
+            }))
+            .start()
+            .await
+            .unwrap();
+
+        let mut client = gw.connect().await.unwrap();
+        let run = client.chat("what does my note say?").await.unwrap();
+
+        let read = run.assert_tool_called("read_file");
+        assert!(!read.is_error);
+        assert!(read.result.as_deref().unwrap_or("").contains("remember the milk"));
+        assert!(run.text.contains("remember the milk"));
+        gw.shutdown().await;
+    }
+}
@@ -2,9 +2,11 @@
 //!
 //! Mirrors openclaw's "lobster palette" (`src/terminal/palette.ts`) and
 //! `src/terminal/theme.ts`.  Respects the `NO_COLOR` env-var and the
-//! `--no-color` CLI flag.
+//! `--no-color` CLI flag, degrades to the basic ANSI colours on terminals
+//! without truecolor, and offers colour-blind-safe palettes
+//! ([`PaletteName`]).
 //!
-//! # Palette (from openclaw docs/cli/index.md)
+//! # Default palette (from openclaw docs/cli/index.md)
 //!
 //! | Token          | Hex       | Usage                          |
 //! |----------------|-----------|--------------------------------|
@@ -19,21 +21,72 @@
 
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::time::Duration;
 
-// ── Global color toggle ─────────────────────────────────────────────────────
+// ── Colour level ────────────────────────────────────────────────────────────
+
+/// How much colour the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorLevel {
+    /// `NO_COLOR`, `--no-color` or `TERM=dumb`: plain text only.
+    None,
+    /// The eight basic ANSI colours.
+    Ansi8,
+    /// The 256-colour xterm palette.
+    Ansi256,
+    /// 24-bit RGB.
+    TrueColor,
+}
+
+impl ColorLevel {
+    /// Detect the level from `COLORTERM` and `TERM`.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default().to_ascii_lowercase();
+        let term = match std::env::var("TERM") {
+            Ok(term) => term.to_ascii_lowercase(),
+            // No TERM at all (e.g. Windows Terminal): assume a modern console.
+            Err(_) => return ColorLevel::TrueColor,
+        };
+        Self::from_env_values(&term, &colorterm)
+    }
+
+    fn from_env_values(term: &str, colorterm: &str) -> Self {
+        if term == "dumb" {
+            ColorLevel::None
+        } else if colorterm == "truecolor" || colorterm == "24bit" || term.contains("direct") {
+            ColorLevel::TrueColor
+        } else if term.contains("256") {
+            ColorLevel::Ansi256
+        } else {
+            ColorLevel::Ansi8
+        }
+    }
+
+    fn from_u8(v: u8) -> Self {
+        match v {
+            0 => ColorLevel::None,
+            1 => ColorLevel::Ansi8,
+            2 => ColorLevel::Ansi256,
+            _ => ColorLevel::TrueColor,
+        }
+    }
+}
 
-static COLOR_DISABLED: AtomicBool = AtomicBool::new(false);
+// ── Global colour state ─────────────────────────────────────────────────────
+
+static COLOR_LEVEL: AtomicU8 = AtomicU8::new(ColorLevel::TrueColor as u8);
+static PALETTE: AtomicU8 = AtomicU8::new(PaletteName::Default as u8);
 
 /// Call once at startup (after CLI parsing) to disable colour globally.
 pub fn disable_color() {
-    COLOR_DISABLED.store(true, Ordering::Relaxed);
+    COLOR_LEVEL.store(ColorLevel::None as u8, Ordering::Relaxed);
     colored::control::set_override(false);
 }
 
 /// Initialise the colour system.  Checks `NO_COLOR` env-var and optional
-/// `--no-color` flag.
+/// `--no-color` flag, then detects how many colours the terminal supports.
 pub fn init_color(no_color_flag: bool) {
     if no_color_flag
         || std::env::var("NO_COLOR")
@@ -41,34 +94,226 @@ pub fn init_color(no_color_flag: bool) {
             .unwrap_or(false)
     {
         disable_color();
+        return;
+    }
+    let level = ColorLevel::detect();
+    if level == ColorLevel::None {
+        disable_color();
+    } else {
+        COLOR_LEVEL.store(level as u8, Ordering::Relaxed);
     }
 }
 
+/// The colour level in effect.
+pub fn color_level() -> ColorLevel {
+    ColorLevel::from_u8(COLOR_LEVEL.load(Ordering::Relaxed))
+}
+
 fn is_color() -> bool {
-    !COLOR_DISABLED.load(Ordering::Relaxed)
+    color_level() != ColorLevel::None
+}
+
+// ── Palettes ────────────────────────────────────────────────────────────────
+
+/// Which palette to draw with (`palette` in config.toml).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaletteName {
+    /// The regular lobster / teal colours.
+    #[default]
+    Default,
+    /// Safe for red-green colour blindness (deuteranopia, protanopia):
+    /// success is blue, errors are orange.
+    #[serde(alias = "deuteranopia", alias = "protanopia")]
+    RedGreen,
+    /// Safe for blue-yellow colour blindness (tritanopia): success is
+    /// teal, warnings are magenta, errors are red.
+    #[serde(alias = "tritanopia")]
+    BlueYellow,
 }
 
-// ── Lobster palette ─────────────────────────────────────────────────────────
+impl PaletteName {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => PaletteName::RedGreen,
+            2 => PaletteName::BlueYellow,
+            _ => PaletteName::Default,
+        }
+    }
+}
+
+/// Select the palette used by the print helpers and the TUI.
+pub fn set_palette(name: PaletteName) {
+    PALETTE.store(name as u8, Ordering::Relaxed);
+}
 
-/// Lobster palette hex values — source of truth.
+/// The palette in effect.
+pub fn palette_name() -> PaletteName {
+    PaletteName::from_u8(PALETTE.load(Ordering::Relaxed))
+}
+
+/// Palette hex values — source of truth.
 pub mod palette {
-    pub const ACCENT: (u8, u8, u8) = (0xFF, 0x5A, 0x2D);
-    pub const ACCENT_BRIGHT: (u8, u8, u8) = (0xFF, 0x7A, 0x3D);
-    pub const ACCENT_DIM: (u8, u8, u8) = (0xD1, 0x4A, 0x22);
-    pub const INFO: (u8, u8, u8) = (0xFF, 0x8A, 0x5B);
-    pub const SUCCESS: (u8, u8, u8) = (0x2F, 0xBF, 0x71);
-    pub const WARN: (u8, u8, u8) = (0xFF, 0xB0, 0x20);
-    pub const ERROR: (u8, u8, u8) = (0xE2, 0x3D, 0x2D);
-    pub const MUTED: (u8, u8, u8) = (0x8B, 0x7F, 0x77);
+    /// One set of semantic colours.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Palette {
+        pub accent: (u8, u8, u8),
+        pub accent_bright: (u8, u8, u8),
+        pub accent_dim: (u8, u8, u8),
+        pub info: (u8, u8, u8),
+        pub success: (u8, u8, u8),
+        pub warn: (u8, u8, u8),
+        pub error: (u8, u8, u8),
+        pub muted: (u8, u8, u8),
+    }
+
+    /// The lobster palette.
+    pub const LOBSTER: Palette = Palette {
+        accent: (0xFF, 0x5A, 0x2D),
+        accent_bright: (0xFF, 0x7A, 0x3D),
+        accent_dim: (0xD1, 0x4A, 0x22),
+        info: (0xFF, 0x8A, 0x5B),
+        success: (0x2F, 0xBF, 0x71),
+        warn: (0xFF, 0xB0, 0x20),
+        error: (0xE2, 0x3D, 0x2D),
+        muted: (0x8B, 0x7F, 0x77),
+    };
+
+    /// Okabe–Ito colours, avoiding red/green contrasts.
+    pub const RED_GREEN: Palette = Palette {
+        accent: (0xE6, 0x9F, 0x00),
+        accent_bright: (0xF0, 0xB8, 0x40),
+        accent_dim: (0xA0, 0x70, 0x00),
+        info: (0x56, 0xB4, 0xE9),
+        success: (0x00, 0x72, 0xB2),
+        warn: (0xF0, 0xE4, 0x42),
+        error: (0xD5, 0x5E, 0x00),
+        muted: (0x8C, 0x8C, 0x8C),
+    };
+
+    /// Avoids blue/yellow contrasts; relies on red vs. teal.
+    pub const BLUE_YELLOW: Palette = Palette {
+        accent: (0xCC, 0x79, 0xA7),
+        accent_bright: (0xE0, 0x9C, 0xC4),
+        accent_dim: (0x99, 0x55, 0x7D),
+        info: (0x00, 0xA5, 0xA5),
+        success: (0x00, 0x9E, 0x73),
+        warn: (0xE0, 0x5C, 0xB0),
+        error: (0xD7, 0x26, 0x1E),
+        muted: (0x8C, 0x8C, 0x8C),
+    };
+
+    pub const ACCENT: (u8, u8, u8) = LOBSTER.accent;
+    pub const ACCENT_BRIGHT: (u8, u8, u8) = LOBSTER.accent_bright;
+    pub const ACCENT_DIM: (u8, u8, u8) = LOBSTER.accent_dim;
+    pub const INFO: (u8, u8, u8) = LOBSTER.info;
+    pub const SUCCESS: (u8, u8, u8) = LOBSTER.success;
+    pub const WARN: (u8, u8, u8) = LOBSTER.warn;
+    pub const ERROR: (u8, u8, u8) = LOBSTER.error;
+    pub const MUTED: (u8, u8, u8) = LOBSTER.muted;
+}
+
+/// The active CLI palette.
+pub fn current_palette() -> &'static palette::Palette {
+    match palette_name() {
+        PaletteName::Default => &palette::LOBSTER,
+        PaletteName::RedGreen => &palette::RED_GREEN,
+        PaletteName::BlueYellow => &palette::BLUE_YELLOW,
+    }
+}
+
+// ── Degradation ─────────────────────────────────────────────────────────────
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Index (0–7) of the basic ANSI colour closest to `rgb`.
+///
+/// Buckets by hue rather than raw distance so that e.g. orange stays red
+/// instead of collapsing to grey; low-saturation colours become black or
+/// white.
+pub fn nearest_ansi8(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = (rgb.0 as i32, rgb.1 as i32, rgb.2 as i32);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let range = max - min;
+    if range < 40 {
+        // The 8-colour set has no grey.
+        return if max < 60 { 0 } else { 7 };
+    }
+    let hue = if max == r {
+        (60 * (g - b) / range).rem_euclid(360)
+    } else if max == g {
+        60 * (b - r) / range + 120
+    } else {
+        60 * (r - g) / range + 240
+    };
+    match hue {
+        30..=89 => 3,   // yellow
+        90..=149 => 2,  // green
+        150..=209 => 6, // cyan
+        210..=269 => 4, // blue
+        270..=329 => 5, // magenta
+        _ => 1,         // red
+    }
+}
+
+/// Index into the 256-colour xterm palette closest to `rgb`.
+pub fn nearest_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    const STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| {
+        STEPS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, s)| (c as i32 - **s as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+    let cube_rgb = (STEPS[r as usize], STEPS[g as usize], STEPS[b as usize]);
+
+    let avg = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey_idx = ((avg.saturating_sub(3)) / 10).min(23) as u8;
+    let grey_v = 8 + 10 * grey_idx;
+    if distance(rgb, (grey_v, grey_v, grey_v)) < distance(rgb, cube_rgb) {
+        232 + grey_idx
+    } else {
+        cube
+    }
 }
 
 // ── Themed formatting helpers ───────────────────────────────────────────────
 //
 // Each function returns a `String` so callers can `println!("{}", accent("…"))`.
 
+fn basic_color(index: u8) -> colored::Color {
+    use colored::Color::*;
+    match index {
+        0 => Black,
+        1 => Red,
+        2 => Green,
+        3 => Yellow,
+        4 => Blue,
+        5 => Magenta,
+        6 => Cyan,
+        _ => White,
+    }
+}
+
+fn color_for(rgb: (u8, u8, u8)) -> colored::Color {
+    match color_level() {
+        ColorLevel::TrueColor => colored::Color::TrueColor { r: rgb.0, g: rgb.1, b: rgb.2 },
+        // `colored` has no 256-colour mode; the basic set is the safe choice.
+        _ => basic_color(nearest_ansi8(rgb)),
+    }
+}
+
 fn apply(text: &str, rgb: (u8, u8, u8)) -> String {
     if is_color() {
-        text.truecolor(rgb.0, rgb.1, rgb.2).to_string()
+        text.color(color_for(rgb)).to_string()
     } else {
         text.to_string()
     }
@@ -76,7 +321,7 @@ fn apply(text: &str, rgb: (u8, u8, u8)) -> String {
 
 fn apply_bold(text: &str, rgb: (u8, u8, u8)) -> String {
     if is_color() {
-        text.truecolor(rgb.0, rgb.1, rgb.2).bold().to_string()
+        text.color(color_for(rgb)).bold().to_string()
     } else {
         text.to_string()
     }
@@ -84,47 +329,47 @@ fn apply_bold(text: &str, rgb: (u8, u8, u8)) -> String {
 
 /// Primary accent (headings, labels).
 pub fn accent(text: &str) -> String {
-    apply(text, palette::ACCENT)
+    apply(text, current_palette().accent)
 }
 
 /// Bright accent (command names, emphasis).
 pub fn accent_bright(text: &str) -> String {
-    apply(text, palette::ACCENT_BRIGHT)
+    apply(text, current_palette().accent_bright)
 }
 
 /// Dim accent (secondary highlight).
 pub fn accent_dim(text: &str) -> String {
-    apply(text, palette::ACCENT_DIM)
+    apply(text, current_palette().accent_dim)
 }
 
 /// Informational values.
 pub fn info(text: &str) -> String {
-    apply(text, palette::INFO)
+    apply(text, current_palette().info)
 }
 
 /// Success state.
 pub fn success(text: &str) -> String {
-    apply(text, palette::SUCCESS)
+    apply(text, current_palette().success)
 }
 
 /// Warning / attention.
 pub fn warn(text: &str) -> String {
-    apply(text, palette::WARN)
+    apply(text, current_palette().warn)
 }
 
 /// Error / failure.
 pub fn error(text: &str) -> String {
-    apply(text, palette::ERROR)
+    apply(text, current_palette().error)
 }
 
 /// De-emphasis / metadata.
 pub fn muted(text: &str) -> String {
-    apply(text, palette::MUTED)
+    apply(text, current_palette().muted)
 }
 
 /// Bold heading in accent colour.
 pub fn heading(text: &str) -> String {
-    apply_bold(text, palette::ACCENT)
+    apply_bold(text, current_palette().accent)
 }
 
 /// Bold text (no colour).
@@ -181,6 +426,9 @@ pub fn label_value(label: &str, value: &str) -> String {
 /// Spinner character set mimicking openclaw's clack spinners.
 const SPINNER_CHARS: &[&str] = &["◒", "◐", "◓", "◑"];
 
+/// indicatif's names for the basic ANSI colours, by index.
+const INDICATIF_COLORS: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
 /// Create an indeterminate spinner with a message.
 ///
 /// Returns a `ProgressBar` that the caller should call `.finish_with_message()`
@@ -190,7 +438,7 @@ pub fn spinner(message: &str) -> ProgressBar {
     let style = if is_color() {
         ProgressStyle::with_template(&format!(
             "{{spinner:.{}}}  {{msg}}",
-            "red" // indicatif colour name closest to lobster accent
+            INDICATIF_COLORS[nearest_ansi8(current_palette().accent) as usize]
         ))
         .unwrap()
         .tick_strings(SPINNER_CHARS)
@@ -256,7 +504,7 @@ mod tests {
     #[test]
     fn test_no_color_output() {
         // Force no-color mode (both our flag AND the colored crate).
-        COLOR_DISABLED.store(true, Ordering::Relaxed);
+        COLOR_LEVEL.store(ColorLevel::None as u8, Ordering::Relaxed);
         colored::control::set_override(false);
         assert_eq!(accent("hello"), "hello");
         assert_eq!(success("ok"), "ok");
@@ -265,16 +513,44 @@ mod tests {
         assert_eq!(icon_fail("bad"), "✗ bad");
         // Reset for other tests.
         colored::control::unset_override();
-        COLOR_DISABLED.store(false, Ordering::Relaxed);
+        COLOR_LEVEL.store(ColorLevel::TrueColor as u8, Ordering::Relaxed);
     }
 
     #[test]
     fn test_label_value() {
-        COLOR_DISABLED.store(true, Ordering::Relaxed);
+        COLOR_LEVEL.store(ColorLevel::None as u8, Ordering::Relaxed);
         let out = label_value("Key", "/some/path");
         assert!(out.contains("Key"));
         assert!(out.contains("/some/path"));
-        COLOR_DISABLED.store(false, Ordering::Relaxed);
+        COLOR_LEVEL.store(ColorLevel::TrueColor as u8, Ordering::Relaxed);
+    }
+
+    #[test]
+    fn test_color_level_detection() {
+        assert_eq!(ColorLevel::from_env_values("dumb", ""), ColorLevel::None);
+        assert_eq!(ColorLevel::from_env_values("xterm-256color", "truecolor"), ColorLevel::TrueColor);
+        assert_eq!(ColorLevel::from_env_values("screen-256color", ""), ColorLevel::Ansi256);
+        assert_eq!(ColorLevel::from_env_values("linux", ""), ColorLevel::Ansi8);
+    }
+
+    #[test]
+    fn test_degrades_to_nearest_ansi() {
+        // Lobster red stays red, success green stays green.
+        assert_eq!(nearest_ansi8(palette::LOBSTER.error), 1);
+        assert_eq!(nearest_ansi8(palette::LOBSTER.success), 2);
+        assert_eq!(nearest_ansi8(palette::RED_GREEN.success), 6);
+        assert_eq!(nearest_ansi8(palette::LOBSTER.warn), 3);
+        assert_eq!(nearest_ansi8((20, 20, 20)), 0);
+        assert_eq!(nearest_ansi256((255, 0, 0)), 196);
+        assert_eq!(nearest_ansi256((128, 128, 128)), 244);
+    }
+
+    #[test]
+    fn test_palette_names() {
+        let name: PaletteName = serde_json::from_str("\"deuteranopia\"").unwrap();
+        assert_eq!(name, PaletteName::RedGreen);
+        let name: PaletteName = serde_json::from_str("\"blue-yellow\"").unwrap();
+        assert_eq!(name, PaletteName::BlueYellow);
     }
 }
 
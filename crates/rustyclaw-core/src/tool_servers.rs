@@ -0,0 +1,569 @@
+//! External tool servers over stdio.
+//!
+//! For tools that can't be compiled to WASM, a tool server is a long-lived
+//! child process that speaks newline-delimited JSON on stdin/stdout.  The
+//! gateway starts each configured server, asks it for its tools, registers
+//! them in the custom tool [`registry`](crate::tools::registry), and keeps
+//! it alive: servers are pinged periodically and restarted (up to
+//! `max_restarts` times in a row) when they exit, hang, or stop answering.
+//! A server that stays up for `stable_secs` earns its restarts back.
+//! The processes are tracked by the [process manager](crate::process_manager)
+//! as `toolserver-<name>`, so the `process` tool can list them.
+//!
+//! ```toml
+//! [[tool_servers]]
+//! name = "jira"
+//! command = "python3"
+//! args = ["-u", "jira_tools.py"]
+//! ```
+//!
+//! ## Protocol
+//!
+//! Every request is one JSON line `{"id": n, "method": "…", "params": …}`
+//! and must be answered by a line `{"id": n, "result": …}` or
+//! `{"id": n, "error": "…"}`.  Lines with other ids are ignored.
+//!
+//! - `initialize` → `{"tools": [{ "name", "description", "parameters" }]}`
+//! - `call` with `{"name", "arguments", "workspace"}` → result text
+//! - `ping` → anything
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::process_manager::{ExecSession, SessionId};
+use crate::tools::{process_manager, registry, Tool};
+
+fn default_call_timeout_secs() -> u64 {
+    60
+}
+
+fn default_health_interval_secs() -> u64 {
+    30
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_stable_secs() -> u64 {
+    300
+}
+
+/// Configuration for one external tool server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolServerConfig {
+    /// Name used in logs and errors.
+    pub name: String,
+
+    /// Program to run.
+    pub command: String,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Extra environment variables for the server.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+
+    /// Working directory (default: the gateway's).
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+
+    /// How long a single request may take, in seconds.
+    #[serde(default = "default_call_timeout_secs")]
+    pub call_timeout_secs: u64,
+
+    /// Seconds between health-check pings (0 disables them).
+    #[serde(default = "default_health_interval_secs")]
+    pub health_interval_secs: u64,
+
+    /// Restarts allowed before the server is given up on.
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+
+    /// Seconds a server must stay up for its restart count to reset.
+    #[serde(default = "default_stable_secs")]
+    pub stable_secs: u64,
+}
+
+/// A tool as described by the server's `initialize` reply.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteToolDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "empty_schema")]
+    pub parameters: Value,
+}
+
+fn empty_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// Why an exchange with a tool server failed.
+#[derive(Debug, thiserror::Error)]
+enum ExchangeError {
+    /// The server answered with an error; the process itself is fine.
+    #[error("{0}")]
+    Remote(String),
+    /// The process died, hung or broke the pipe, so it must be restarted.
+    #[error("{0}")]
+    Transport(String),
+}
+
+/// A running server process, registered with the process manager.
+struct Connection {
+    session_id: SessionId,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+}
+
+impl Connection {
+    fn has_exited(&self) -> bool {
+        let Ok(mut mgr) = process_manager().lock() else {
+            return true;
+        };
+        mgr.get_mut(&self.session_id).is_none_or(|session| session.check_exit())
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Ok(mut mgr) = process_manager().lock() {
+            if let Some(mut session) = mgr.remove(&self.session_id) {
+                let _ = session.kill();
+            }
+        }
+    }
+}
+
+/// A supervised tool server process.
+pub struct ToolServer {
+    config: ToolServerConfig,
+    conn: Mutex<Option<Connection>>,
+    starts: AtomicU32,
+    last_start: std::sync::Mutex<Option<Instant>>,
+    failed: AtomicBool,
+}
+
+impl ToolServer {
+    pub fn new(config: ToolServerConfig) -> Self {
+        Self {
+            config,
+            conn: Mutex::new(None),
+            starts: AtomicU32::new(0),
+            last_start: std::sync::Mutex::new(None),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Number of times the process has been restarted.
+    pub fn restarts(&self) -> u32 {
+        self.starts.load(Ordering::Relaxed).saturating_sub(1)
+    }
+
+    /// Whether the server exceeded its restart budget.
+    pub fn has_failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    fn spawn(&self) -> Result<Connection, String> {
+        // A long stable run means the next crash starts a fresh budget.
+        if let Ok(mut last) = self.last_start.lock() {
+            let stable = Duration::from_secs(self.config.stable_secs);
+            if last.is_some_and(|t| t.elapsed() >= stable) && self.starts.load(Ordering::Relaxed) > 1 {
+                debug!(server = %self.config.name, "Tool server was stable; resetting restart count");
+                self.starts.store(1, Ordering::Relaxed);
+            }
+            *last = Some(Instant::now());
+        }
+        let starts = self.starts.fetch_add(1, Ordering::Relaxed);
+        if starts > self.config.max_restarts {
+            self.failed.store(true, Ordering::Relaxed);
+            return Err(format!(
+                "Tool server {} disabled after {} restarts",
+                self.config.name, self.config.max_restarts
+            ));
+        }
+        if starts > 0 {
+            warn!(server = %self.config.name, restart = starts, "Restarting tool server");
+        }
+
+        let mut cmd = Command::new(&self.config.command);
+        cmd.args(&self.config.args)
+            .envs(&self.config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref cwd) = self.config.cwd {
+            cmd.current_dir(cwd);
+        }
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to start tool server {}: {}", self.config.name, e))?;
+
+        // The pipes are ours; the process manager only tracks the process.
+        let pipes = (child.stdin.take(), child.stdout.take(), child.stderr.take());
+        let mut session = ExecSession::new(
+            format!("{} {}", self.config.command, self.config.args.join(" ")),
+            self.config.cwd.as_deref().unwrap_or(Path::new(".")).display().to_string(),
+            None,
+            child,
+        );
+        session.id = format!("toolserver-{}", self.config.name);
+        let session_id = process_manager()
+            .lock()
+            .map_err(|_| "Failed to acquire process manager lock".to_string())?
+            .insert(session);
+        // Dropping this on any error below kills the process again.
+        let pending = PendingSession(Some(session_id));
+
+        let (Some(stdin), Some(stdout), stderr) = pipes else {
+            return Err("Tool server pipes unavailable".to_string());
+        };
+        let stdin = ChildStdin::from_std(stdin).map_err(|e| format!("Tool server stdin: {}", e))?;
+        let stdout = ChildStdout::from_std(stdout).map_err(|e| format!("Tool server stdout: {}", e))?;
+
+        // Forward the server's stderr to our logs.
+        if let Some(stderr) = stderr.and_then(|e| ChildStderr::from_std(e).ok()) {
+            let name = self.config.name.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!(server = %name, "{}", line);
+                }
+            });
+        }
+
+        Ok(Connection {
+            session_id: pending.take(),
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            next_id: 1,
+        })
+    }
+
+    /// Send a request and wait for its reply, starting the process if
+    /// needed.  A failed exchange drops the process so the next request
+    /// starts a fresh one.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        if self.has_failed() {
+            return Err(format!("Tool server {} is disabled", self.config.name));
+        }
+        let mut guard = self.conn.lock().await;
+
+        let exited = guard.as_ref().is_none_or(Connection::has_exited);
+        if exited {
+            *guard = None;
+            *guard = Some(self.spawn()?);
+            // A restarted server must be re-initialized before use.
+            if method != "initialize" {
+                let conn = guard.as_mut().expect("connection just started");
+                if let Err(e) = self.exchange(conn, "initialize", Value::Null).await {
+                    *guard = None;
+                    return Err(e.to_string());
+                }
+            }
+        }
+
+        let conn = guard.as_mut().expect("connection started");
+        let result = self.exchange(conn, method, params).await;
+        if let Err(ExchangeError::Transport(ref e)) = result {
+            warn!(server = %self.config.name, error = %e, "Tool server exchange failed");
+            *guard = None;
+        }
+        result.map_err(|e| e.to_string())
+    }
+
+    async fn exchange(&self, conn: &mut Connection, method: &str, params: Value) -> Result<Value, ExchangeError> {
+        let id = conn.next_id;
+        conn.next_id += 1;
+        let mut line = json!({ "id": id, "method": method, "params": params }).to_string();
+        line.push('\n');
+
+        let timeout = Duration::from_secs(self.config.call_timeout_secs.max(1));
+        let io = async {
+            let transport = |what: &str, e: std::io::Error| ExchangeError::Transport(format!("{} failed: {}", what, e));
+            conn.stdin.write_all(line.as_bytes()).await.map_err(|e| transport("write", e))?;
+            conn.stdin.flush().await.map_err(|e| transport("write", e))?;
+            loop {
+                let Some(reply) = conn.stdout.next_line().await.map_err(|e| transport("read", e))? else {
+                    return Err(ExchangeError::Transport("server closed its stdout".to_string()));
+                };
+                let Ok(reply) = serde_json::from_str::<Value>(&reply) else {
+                    debug!(server = %self.config.name, line = %reply, "Ignoring non-JSON output");
+                    continue;
+                };
+                if reply.get("id").and_then(|v| v.as_u64()) != Some(id) {
+                    continue;
+                }
+                if let Some(err) = reply.get("error") {
+                    let msg = err.as_str().map(String::from).unwrap_or_else(|| err.to_string());
+                    return Err(ExchangeError::Remote(msg));
+                }
+                return Ok(reply.get("result").cloned().unwrap_or(Value::Null));
+            }
+        };
+        match tokio::time::timeout(timeout, io).await {
+            Ok(result) => result,
+            Err(_) => Err(ExchangeError::Transport(format!(
+                "no reply to {} within {}s",
+                method,
+                timeout.as_secs()
+            ))),
+        }
+    }
+
+    /// Ask the server for its tools.
+    pub async fn initialize(&self) -> Result<Vec<RemoteToolDef>, String> {
+        let result = self.request("initialize", Value::Null).await?;
+        let tools = result.get("tools").cloned().unwrap_or(Value::Array(Vec::new()));
+        serde_json::from_value(tools)
+            .map_err(|e| format!("Tool server {} sent an invalid tool list: {}", self.config.name, e))
+    }
+
+    /// Run one of the server's tools.
+    pub async fn call(&self, tool: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+        let params = json!({
+            "name": tool,
+            "arguments": args,
+            "workspace": workspace_dir.display().to_string(),
+        });
+        match self.request("call", params).await? {
+            Value::String(s) => Ok(s),
+            other => Ok(other.to_string()),
+        }
+    }
+
+    /// Ping the server, restarting it if it doesn't answer.
+    pub async fn health_check(&self) -> bool {
+        self.request("ping", Value::Null).await.is_ok()
+    }
+
+    /// Stop the process.
+    pub async fn shutdown(&self) {
+        // Dropping the connection kills the process.
+        self.conn.lock().await.take();
+    }
+}
+
+/// Kills a just-registered server process unless spawning completes.
+struct PendingSession(Option<SessionId>);
+
+impl PendingSession {
+    fn take(mut self) -> SessionId {
+        self.0.take().expect("session taken once")
+    }
+}
+
+impl Drop for PendingSession {
+    fn drop(&mut self) {
+        let Some(id) = self.0.take() else { return };
+        if let Ok(mut mgr) = process_manager().lock() {
+            if let Some(mut session) = mgr.remove(&id) {
+                let _ = session.kill();
+            }
+        }
+    }
+}
+
+/// A registry tool answered by a tool server.
+struct RemoteTool {
+    server: Arc<ToolServer>,
+    def: RemoteToolDef,
+}
+
+#[async_trait::async_trait]
+impl Tool for RemoteTool {
+    fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    fn description(&self) -> &str {
+        &self.def.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.def.parameters.clone()
+    }
+
+    async fn execute(&self, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+        self.server.call(&self.def.name, args, workspace_dir).await
+    }
+}
+
+/// Start the configured tool servers, register their tools and supervise
+/// them until `cancel` fires.  Returns the names of the tools registered.
+pub async fn start_tool_servers(
+    configs: &[ToolServerConfig],
+    cancel: CancellationToken,
+) -> Vec<String> {
+    let mut registered = Vec::new();
+    for config in configs {
+        let server = Arc::new(ToolServer::new(config.clone()));
+        let tools = match server.initialize().await {
+            Ok(tools) => tools,
+            Err(e) => {
+                warn!(server = %config.name, error = %e, "Tool server failed to start");
+                server.shutdown().await;
+                continue;
+            }
+        };
+        info!(server = %config.name, tools = tools.len(), "Tool server started");
+        for def in tools {
+            let name = def.name.clone();
+            match registry().register(RemoteTool { server: server.clone(), def }) {
+                Ok(()) => registered.push(name),
+                Err(e) => warn!(server = %config.name, tool = %name, error = %e, "Tool server tool not registered"),
+            }
+        }
+        tokio::spawn(supervise(server, cancel.clone()));
+    }
+    registered
+}
+
+async fn supervise(server: Arc<ToolServer>, cancel: CancellationToken) {
+    let interval = server.config.health_interval_secs;
+    if interval == 0 {
+        cancel.cancelled().await;
+        server.shutdown().await;
+        return;
+    }
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+    ticker.tick().await;
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = ticker.tick() => {
+                if server.has_failed() {
+                    warn!(server = %server.name(), "Tool server gave up; stopping health checks");
+                    break;
+                }
+                if !server.health_check().await {
+                    warn!(server = %server.name(), "Tool server failed health check");
+                }
+            }
+        }
+    }
+    server.shutdown().await;
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// A tool server in POSIX sh: one `shout` tool that always answers
+    /// "HI", plus `crash` which exits mid-request.
+    const SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed 's/^{"id":\([0-9]*\).*/\1/')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"id":%s,"result":{"tools":[{"name":"shout","description":"Shout"}]}}\n' "$id" ;;
+    *'"name":"crash"'*) exit 1 ;;
+    *'"method":"call"'*)
+      printf 'log noise\n{"id":%s,"result":"HI"}\n' "$id" ;;
+    *'"method":"ping"'*)
+      printf '{"id":%s,"result":"pong"}\n' "$id" ;;
+    *)
+      printf '{"id":%s,"error":"unknown method"}\n' "$id" ;;
+  esac
+done
+"#;
+
+    /// Each test uses its own name, since it's the process manager key.
+    fn config(name: &str) -> ToolServerConfig {
+        ToolServerConfig {
+            name: name.into(),
+            command: "sh".into(),
+            args: vec!["-c".into(), SERVER.into()],
+            env: HashMap::new(),
+            cwd: None,
+            call_timeout_secs: 5,
+            health_interval_secs: 0,
+            max_restarts: 1,
+            stable_secs: 300,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_call() {
+        let server = ToolServer::new(config("call"));
+        let tools = server.initialize().await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "shout");
+        assert_eq!(tools[0].parameters["type"], "object");
+
+        let out = server.call("shout", &json!({}), Path::new("/tmp")).await.unwrap();
+        assert_eq!(out, "HI");
+        assert!(server.health_check().await);
+        assert_eq!(server.restarts(), 0);
+        server.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_remote_error_keeps_process() {
+        let server = ToolServer::new(config("remote"));
+        server.initialize().await.unwrap();
+        let err = server.request("bogus", Value::Null).await.unwrap_err();
+        assert_eq!(err, "unknown method");
+        assert_eq!(server.restarts(), 0);
+        server.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_restart_after_crash_then_give_up() {
+        let server = ToolServer::new(config("crash"));
+        server.initialize().await.unwrap();
+
+        assert!(server.call("crash", &json!({}), Path::new("/tmp")).await.is_err());
+        // Next request restarts the process and re-initializes it.
+        assert!(server.health_check().await);
+        assert_eq!(server.restarts(), 1);
+
+        assert!(server.call("crash", &json!({}), Path::new("/tmp")).await.is_err());
+        assert!(!server.health_check().await);
+        assert!(server.has_failed());
+    }
+
+    #[tokio::test]
+    async fn test_stable_server_earns_restarts_back() {
+        let server = ToolServer::new(ToolServerConfig {
+            stable_secs: 0,
+            ..config("stable")
+        });
+        server.initialize().await.unwrap();
+        for _ in 0..3 {
+            assert!(server.call("crash", &json!({}), Path::new("/tmp")).await.is_err());
+            assert!(server.health_check().await);
+        }
+        assert!(!server.has_failed());
+        assert_eq!(server.restarts(), 1);
+        server.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_process_is_tracked_by_the_process_manager() {
+        let server = ToolServer::new(config("tracked"));
+        server.initialize().await.unwrap();
+        assert!(process_manager().lock().unwrap().get("toolserver-tracked").is_some());
+        server.shutdown().await;
+        assert!(process_manager().lock().unwrap().get("toolserver-tracked").is_none());
+    }
+}
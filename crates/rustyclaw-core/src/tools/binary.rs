@@ -0,0 +1,176 @@
+//! Binary file detection for `read_file`.
+//!
+//! Binary content read as text fills the context with garbage.  `read_file`
+//! returns a one-line summary for binaries instead — the type from the
+//! file's magic bytes and its size — and a bounded hexdump when called with
+//! `read_binary`.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes looked at to decide whether a file is binary.
+const SNIFF_BYTES: usize = 8192;
+
+/// Default and largest hexdump lengths.
+pub const DEFAULT_DUMP_BYTES: u64 = 256;
+pub const MAX_DUMP_BYTES: u64 = 4096;
+
+/// Magic bytes at the start of common binary formats.
+const MAGIC: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (b"\xff\xd8\xff", "JPEG image"),
+    (b"GIF87a", "GIF image"),
+    (b"GIF89a", "GIF image"),
+    (b"BM", "BMP image"),
+    (b"\x00\x00\x01\x00", "ICO image"),
+    (b"%PDF-", "PDF document"),
+    (b"PK\x03\x04", "ZIP archive (also docx/xlsx/jar/apk)"),
+    (b"\x1f\x8b", "gzip archive"),
+    (b"BZh", "bzip2 archive"),
+    (b"\xfd7zXZ\x00", "xz archive"),
+    (b"7z\xbc\xaf\x27\x1c", "7-Zip archive"),
+    (b"\x28\xb5\x2f\xfd", "zstd archive"),
+    (b"Rar!\x1a\x07", "RAR archive"),
+    (b"\x7fELF", "ELF executable"),
+    (b"MZ", "Windows executable"),
+    (b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+    (b"\xca\xfe\xba\xbe", "Mach-O universal binary or Java class"),
+    (b"\x00asm", "WebAssembly module"),
+    (b"SQLite format 3\x00", "SQLite database"),
+    (b"OggS", "Ogg media"),
+    (b"fLaC", "FLAC audio"),
+    (b"ID3", "MP3 audio"),
+    (b"\x1a\x45\xdf\xa3", "Matroska/WebM video"),
+    (b"wOFF", "WOFF font"),
+    (b"wOF2", "WOFF2 font"),
+];
+
+/// The file type named by `head`'s magic bytes, if known.
+pub fn file_type(head: &[u8]) -> Option<&'static str> {
+    if head.len() >= 12 && &head[..4] == b"RIFF" {
+        return match &head[8..12] {
+            b"WEBP" => Some("WebP image"),
+            b"WAVE" => Some("WAV audio"),
+            b"AVI " => Some("AVI video"),
+            _ => Some("RIFF container"),
+        };
+    }
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return Some("MP4/QuickTime media");
+    }
+    MAGIC.iter().find(|(magic, _)| head.starts_with(magic)).map(|(_, name)| *name)
+}
+
+/// Whether `head` (the start of a file) looks like binary data: it has NUL
+/// bytes, or is not UTF-8 and mostly control characters.
+pub fn looks_binary(head: &[u8]) -> bool {
+    let head = &head[..head.len().min(SNIFF_BYTES)];
+    if head.contains(&0) {
+        return true;
+    }
+    // A multi-byte character may be cut at the end of the sample.
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        Err(e) if e.error_len().is_none() => false,
+        Err(_) => {
+            let control = head
+                .iter()
+                .filter(|b| b.is_ascii_control() && !b.is_ascii_whitespace())
+                .count();
+            control * 10 > head.len()
+        }
+    }
+}
+
+/// Read the start of `path` for sniffing.
+pub fn read_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    std::fs::File::open(path)?.take(SNIFF_BYTES as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// One-line summary of a binary file.
+pub fn summary(head: &[u8], size: u64) -> String {
+    format!(
+        "Binary file: {}, {} bytes. Not shown as text; call read_file with \
+         read_binary=true for a hexdump (offset/length select the bytes).",
+        file_type(head).unwrap_or("unknown type"),
+        size
+    )
+}
+
+/// `xxd`-style dump of `bytes`, addressed from `offset`.
+pub fn hexdump(bytes: &[u8], offset: u64) -> String {
+    let mut out = String::new();
+    for (i, row) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = row.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = row
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}: {:<47}  {}\n", offset + (i * 16) as u64, hex.join(" "), ascii));
+    }
+    out
+}
+
+/// Hexdump of `length` bytes of `path` from `offset`, with a header line.
+pub fn dump_file(path: &Path, offset: u64, length: u64) -> Result<String, String> {
+    let fail = |e: std::io::Error| format!("Failed to read file '{}': {}", path.display(), e);
+    let mut file = std::fs::File::open(path).map_err(fail)?;
+    let size = file.metadata().map_err(fail)?.len();
+    if offset > size {
+        return Err(format!("offset {} is past end of file ({} bytes)", offset, size));
+    }
+    let length = length.clamp(1, MAX_DUMP_BYTES);
+    file.seek(SeekFrom::Start(offset)).map_err(fail)?;
+    let mut bytes = Vec::new();
+    file.take(length).read_to_end(&mut bytes).map_err(fail)?;
+
+    let head = if offset == 0 { bytes.clone() } else { read_head(path).map_err(fail)? };
+    let mut out = format!(
+        "{}, {} bytes; showing {}..{}\n",
+        file_type(&head).unwrap_or("unknown type"),
+        size,
+        offset,
+        offset + bytes.len() as u64
+    );
+    out.push_str(&hexdump(&bytes, offset));
+    if offset + (bytes.len() as u64) < size {
+        out.push_str(&format!("… {} more bytes", size - offset - bytes.len() as u64));
+    }
+    Ok(out.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_binaries_by_content() {
+        assert_eq!(file_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("PNG image"));
+        assert_eq!(file_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("WebP image"));
+        assert_eq!(file_type(b"hello"), None);
+        assert!(looks_binary(b"\x7fELF\x02\x01\x01\0\0"));
+        assert!(!looks_binary("plain text, ünïcode too\n".as_bytes()));
+        // A multi-byte character cut at the end of the sample is still text.
+        assert!(!looks_binary(&"é".as_bytes()[..1]));
+        assert!(looks_binary(&[0xff, 0x01, 0x02, 0x03, 0x04, b'a']));
+    }
+
+    #[test]
+    fn test_hexdump_is_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        let mut data = b"\x7fELF".to_vec();
+        data.extend(0u8..=255);
+        std::fs::write(&path, &data).unwrap();
+
+        let dump = dump_file(&path, 0, 20).unwrap();
+        assert!(dump.starts_with("ELF executable, 260 bytes; showing 0..20"));
+        assert!(dump.contains("00000000: 7f 45 4c 46 00 01 02 03 04 05 06 07 08 09 0a 0b  .ELF............"));
+        assert!(dump.contains("00000010: 0c 0d 0e 0f"));
+        assert!(dump.ends_with("… 240 more bytes"));
+        assert!(dump_file(&path, 1000, 16).is_err());
+        assert_eq!(summary(&data, 260).split('.').next().unwrap(), "Binary file: ELF executable, 260 bytes");
+    }
+}
@@ -0,0 +1,335 @@
+//! Bluetooth Low Energy tool using btleplug.
+//!
+//! Scans for nearby devices, reads and subscribes to GATT characteristics
+//! (sensors, heart-rate straps, battery levels) and writes simple commands.
+//! Real Bluetooth access needs the `ble` feature; without it every action
+//! explains how to enable it.  `write` is gated by the permission system
+//! (`ble.write`), like the nodes power actions.
+
+use serde_json::{json, Value};
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Bluetooth SIG base UUID; 16-bit ids are slotted into the first group.
+const BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805f9b34fb";
+
+/// Expand a 16- or 32-bit short UUID (`2a19`, `0x2A19`) to its full form.
+#[cfg_attr(not(feature = "ble"), allow(dead_code))]
+fn full_uuid(id: &str) -> Result<String, String> {
+    let id = id.trim().trim_start_matches("0x").to_lowercase();
+    let is_hex = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit());
+    match id.len() {
+        4 | 8 if is_hex(&id) => Ok(format!("{:0>8}{}", id, BASE_UUID_SUFFIX)),
+        36 if id.split('-').map(str::len).eq([8, 4, 4, 4, 12]) && is_hex(&id.replace('-', "")) => Ok(id),
+        _ => Err(format!("Invalid characteristic UUID: {}", id)),
+    }
+}
+
+/// The 16-bit assigned number of a SIG UUID, if it is one.
+#[cfg_attr(not(feature = "ble"), allow(dead_code))]
+fn short_uuid(uuid: &str) -> Option<&str> {
+    let uuid = uuid.strip_suffix(BASE_UUID_SUFFIX)?;
+    uuid.strip_prefix("0000")
+}
+
+/// IEEE-11073 32-bit FLOAT: 24-bit signed mantissa, 8-bit signed exponent.
+#[cfg_attr(not(feature = "ble"), allow(dead_code))]
+fn medfloat32(bytes: &[u8]) -> Option<f64> {
+    let raw = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?);
+    let mantissa = ((raw & 0x00FF_FFFF) << 8) as i32 >> 8;
+    let exponent = (raw >> 24) as i8;
+    Some(mantissa as f64 * 10f64.powi(exponent as i32))
+}
+
+/// Decode a characteristic value.  Well-known characteristics get a
+/// human-readable `value`; everything gets `hex`, plus `text` if printable.
+#[cfg_attr(not(feature = "ble"), allow(dead_code))]
+fn decode(uuid: &str, data: &[u8]) -> Value {
+    let mut out = json!({ "hex": data.iter().map(|b| format!("{:02x}", b)).collect::<String>() });
+    if let Ok(text) = std::str::from_utf8(data) {
+        if !text.is_empty() && text.chars().all(|c| !c.is_control() || c == '\n') {
+            out["text"] = json!(text);
+        }
+    }
+    let u16_at = |i: usize| data.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let decoded = match short_uuid(uuid) {
+        Some("2a19") => data.first().map(|b| json!({ "battery_percent": b })),
+        Some("2a6e") => u16_at(0).map(|v| json!({ "temperature_c": v as i16 as f64 / 100.0 })),
+        Some("2a6f") => u16_at(0).map(|v| json!({ "humidity_percent": v as f64 / 100.0 })),
+        Some("2a1c") => data.first().and_then(|flags| {
+            let value = medfloat32(data.get(1..)?)?;
+            let unit = if flags & 1 == 0 { "temperature_c" } else { "temperature_f" };
+            Some(json!({ unit: (value * 100.0).round() / 100.0 }))
+        }),
+        Some("2a37") => data.first().and_then(|flags| {
+            // Bit 0 of the flags selects a 16-bit heart rate.
+            let bpm = if flags & 1 == 0 { *data.get(1)? as u16 } else { u16_at(1)? };
+            Some(json!({ "heart_rate_bpm": bpm }))
+        }),
+        _ => None,
+    };
+    if let Some(value) = decoded {
+        out["value"] = value;
+    }
+    out
+}
+
+/// Parse a value to write: `0x01ff` / `hex:01 ff` as bytes, else UTF-8 text.
+#[cfg_attr(not(feature = "ble"), allow(dead_code))]
+fn parse_value(value: &str) -> Result<Vec<u8>, String> {
+    let hex = value.strip_prefix("0x").or_else(|| value.strip_prefix("hex:"));
+    let Some(hex) = hex else { return Ok(value.as_bytes().to_vec()) };
+    let digits: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() % 2 != 0 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex value: {}", value));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Scan, read, subscribe to or write BLE characteristics.
+#[instrument(skip(args, _workspace_dir), fields(action))]
+pub fn exec_ble(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    tracing::Span::current().record("action", action);
+    debug!("Executing ble tool");
+
+    if !matches!(action, "scan" | "services" | "read" | "subscribe" | "write") {
+        return Err(format!(
+            "Unknown action: {}. Valid: scan, services, read, subscribe, write",
+            action
+        ));
+    }
+
+    #[cfg(feature = "ble")]
+    {
+        real::run(args.clone(), action.to_string())
+    }
+
+    #[cfg(not(feature = "ble"))]
+    {
+        Err("Bluetooth support isn't compiled in. Rebuild with `--features ble` \
+             (Linux needs BlueZ and libdbus)."
+            .to_string())
+    }
+}
+
+#[cfg(feature = "ble")]
+mod real {
+    use super::*;
+    use btleplug::api::{Central, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType};
+    use btleplug::platform::{Adapter, Manager, Peripheral};
+    use futures_util::StreamExt;
+    use std::time::Duration;
+
+    fn err(e: btleplug::Error) -> String {
+        format!("Bluetooth error: {}", e)
+    }
+
+    /// Run on a private runtime in its own thread: tool calls are
+    /// synchronous and may already be inside the gateway's runtime.
+    pub fn run(args: Value, action: String) -> Result<String, String> {
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| format!("Failed to start runtime: {}", e))?;
+            rt.block_on(dispatch(&args, &action))
+        })
+        .join()
+        .map_err(|_| "Bluetooth worker panicked".to_string())?
+    }
+
+    async fn adapter() -> Result<Adapter, String> {
+        let manager = Manager::new().await.map_err(err)?;
+        manager
+            .adapters()
+            .await
+            .map_err(err)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No Bluetooth adapter found".to_string())
+    }
+
+    fn duration(args: &Value, default_ms: u64) -> Duration {
+        let ms = args.get("durationMs").and_then(|v| v.as_u64()).unwrap_or(default_ms);
+        Duration::from_millis(ms.clamp(500, 60_000))
+    }
+
+    fn str_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+        args.get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("Missing required parameter: {}", key))
+    }
+
+    async fn dispatch(args: &Value, action: &str) -> Result<String, String> {
+        let central = adapter().await?;
+        if action == "scan" {
+            return scan(&central, duration(args, 5000)).await;
+        }
+
+        let device = str_arg(args, "device")?;
+        let peripheral = find(&central, device, Duration::from_secs(10)).await?;
+        peripheral.connect().await.map_err(err)?;
+        let result = on_connected(&peripheral, args, action).await;
+        let _ = peripheral.disconnect().await;
+        result
+    }
+
+    async fn on_connected(peripheral: &Peripheral, args: &Value, action: &str) -> Result<String, String> {
+        peripheral.discover_services().await.map_err(err)?;
+        if action == "services" {
+            let chars: Vec<Value> = peripheral
+                .characteristics()
+                .iter()
+                .map(|c| {
+                    json!({
+                        "service": c.service_uuid.to_string(),
+                        "characteristic": c.uuid.to_string(),
+                        "properties": format!("{:?}", c.properties),
+                    })
+                })
+                .collect();
+            return Ok(json!({ "characteristics": chars }).to_string());
+        }
+
+        let characteristic = characteristic(peripheral, str_arg(args, "characteristic")?)?;
+        let uuid = characteristic.uuid.to_string();
+        match action {
+            "read" => {
+                let data = peripheral.read(&characteristic).await.map_err(err)?;
+                Ok(json!({ "characteristic": uuid, "data": decode(&uuid, &data) }).to_string())
+            }
+            "subscribe" => {
+                let window = duration(args, 5000);
+                peripheral.subscribe(&characteristic).await.map_err(err)?;
+                let mut stream = peripheral.notifications().await.map_err(err)?;
+                let mut values = Vec::new();
+                let _ = tokio::time::timeout(window, async {
+                    while let Some(n) = stream.next().await {
+                        if n.uuid == characteristic.uuid {
+                            values.push(decode(&uuid, &n.value));
+                            if values.len() >= 50 {
+                                break;
+                            }
+                        }
+                    }
+                })
+                .await;
+                let _ = peripheral.unsubscribe(&characteristic).await;
+                Ok(json!({ "characteristic": uuid, "values": values }).to_string())
+            }
+            "write" => {
+                let data = parse_value(str_arg(args, "value")?)?;
+                let no_response = args.get("withoutResponse").and_then(|v| v.as_bool()).unwrap_or(false);
+                let kind = if no_response { WriteType::WithoutResponse } else { WriteType::WithResponse };
+                peripheral.write(&characteristic, &data, kind).await.map_err(err)?;
+                Ok(format!("Wrote {} byte(s) to {}", data.len(), uuid))
+            }
+            _ => unreachable!("action validated by exec_ble"),
+        }
+    }
+
+    fn characteristic(peripheral: &Peripheral, id: &str) -> Result<Characteristic, String> {
+        let wanted = full_uuid(id)?;
+        peripheral
+            .characteristics()
+            .into_iter()
+            .find(|c| c.uuid.to_string() == wanted)
+            .ok_or_else(|| format!("Characteristic {} not found; use the services action to list them", id))
+    }
+
+    async fn scan(central: &Adapter, window: Duration) -> Result<String, String> {
+        central.start_scan(ScanFilter::default()).await.map_err(err)?;
+        tokio::time::sleep(window).await;
+        let _ = central.stop_scan().await;
+
+        let mut devices = Vec::new();
+        for p in central.peripherals().await.map_err(err)? {
+            let Ok(Some(props)) = p.properties().await else { continue };
+            devices.push(json!({
+                "address": props.address.to_string(),
+                "name": props.local_name,
+                "rssi": props.rssi,
+                "services": props.services.iter().map(|u| u.to_string()).collect::<Vec<_>>(),
+                "manufacturer_ids": props.manufacturer_data.keys().collect::<Vec<_>>(),
+            }));
+        }
+        // Strongest signal first; unnamed devices are usually beacons.
+        devices.sort_by_key(|d| std::cmp::Reverse(d["rssi"].as_i64().unwrap_or(i64::MIN)));
+        Ok(json!({ "devices": devices }).to_string())
+    }
+
+    /// Scan until a device matching `device` (address, or name substring)
+    /// shows up.
+    async fn find(central: &Adapter, device: &str, window: Duration) -> Result<Peripheral, String> {
+        central.start_scan(ScanFilter::default()).await.map_err(err)?;
+        let wanted = device.to_lowercase();
+        let deadline = tokio::time::Instant::now() + window;
+        let found = loop {
+            let mut hit = None;
+            for p in central.peripherals().await.map_err(err)? {
+                let Ok(Some(props)) = p.properties().await else { continue };
+                let name = props.local_name.unwrap_or_default().to_lowercase();
+                if props.address.to_string().to_lowercase() == wanted || (!name.is_empty() && name.contains(&wanted)) {
+                    hit = Some(p);
+                    break;
+                }
+            }
+            if hit.is_some() || tokio::time::Instant::now() >= deadline {
+                break hit;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        };
+        let _ = central.stop_scan().await;
+        found.ok_or_else(|| format!("No BLE device matching '{}' found; run scan first", device))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_uuid() {
+        assert_eq!(full_uuid("2A19").unwrap(), "00002a19-0000-1000-8000-00805f9b34fb");
+        assert_eq!(full_uuid("0x180d").unwrap(), "0000180d-0000-1000-8000-00805f9b34fb");
+        let custom = "6e400002-b5a3-f393-e0a9-e50e24dcca9e";
+        assert_eq!(full_uuid(custom).unwrap(), custom);
+        assert!(full_uuid("xyz").is_err());
+    }
+
+    #[test]
+    fn test_decode_known_characteristics() {
+        let uuid = |s| full_uuid(s).unwrap();
+        assert_eq!(decode(&uuid("2a19"), &[87])["value"]["battery_percent"], 87);
+        assert_eq!(decode(&uuid("2a6e"), &[0x2a, 0x09])["value"]["temperature_c"], 23.46);
+        assert_eq!(decode(&uuid("2a37"), &[0x00, 72])["value"]["heart_rate_bpm"], 72);
+        assert_eq!(decode(&uuid("2a37"), &[0x01, 0x2c, 0x01])["value"]["heart_rate_bpm"], 300);
+        // 3650 * 10^-2 °C
+        assert_eq!(decode(&uuid("2a1c"), &[0x00, 0x42, 0x0e, 0x00, 0xfe])["value"]["temperature_c"], 36.5);
+        let name = decode(&uuid("2a00"), b"Thermo");
+        assert_eq!(name["text"], "Thermo");
+        assert_eq!(name["hex"], "546865726d6f");
+        assert!(name.get("value").is_none());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("0x01ff").unwrap(), vec![0x01, 0xff]);
+        assert_eq!(parse_value("hex:01 02").unwrap(), vec![1, 2]);
+        assert_eq!(parse_value("on").unwrap(), b"on".to_vec());
+        assert!(parse_value("0x123").is_err());
+    }
+
+    #[test]
+    fn test_unknown_action() {
+        let result = exec_ble(&json!({ "action": "pair" }), Path::new("/tmp"));
+        assert!(result.unwrap_err().contains("Unknown action"));
+    }
+}
@@ -0,0 +1,816 @@
+//! The `calc` tool: exact arithmetic, unit and currency conversion,
+//! timezone conversion and date math.
+//!
+//! Timezones, date parsing and cron expressions come from [`crate::cron`],
+//! so `op=schedule` shows exactly when a cron job with the same schedule
+//! would fire.  Exchange rates are fetched at most every twelve hours and
+//! cached under `.cache/` in the workspace; a stale cache is used (and
+//! flagged) when the rates service can't be reached.
+
+use crate::cron::{format_ms, localize, now_ms, parse_datetime, parse_tz, CronExpr};
+use chrono::{DateTime, Datelike, Duration, Months, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, instrument, warn};
+
+const RATES_URL: &str = "https://open.er-api.com/v6/latest/USD";
+const RATES_TTL_MS: u64 = 12 * 3600 * 1000;
+
+/// Most runs `op=schedule` will list.
+const MAX_SCHEDULE_RUNS: usize = 50;
+
+// ── Arithmetic ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], '.' | '_')) {
+                    i += 1;
+                }
+                // Exponent, but not the constant `e` after a number.
+                if i + 1 < chars.len() && matches!(chars[i], 'e' | 'E') {
+                    let sign = usize::from(matches!(chars[i + 1], '+' | '-'));
+                    if chars.get(i + 1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                        i += 1 + sign;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().filter(|c| **c != '_').collect();
+                tokens.push(Token::Num(text.parse().map_err(|_| format!("Invalid number '{}'", text))?));
+            }
+            c if c.is_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(Token::Op('^'));
+                i += 2;
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '×' | '÷' => {
+                tokens.push(Token::Op(match c {
+                    '×' => '*',
+                    '÷' => '/',
+                    c => c,
+                }));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            other => return Err(format!("Unexpected character '{}'", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent evaluator: `+ -` < `* / %` (and implicit
+/// multiplication, `2pi`) < unary minus < `^` (right-associative).
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expr(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Op(op @ ('*' | '/' | '%'))) => {
+                    let op = *op;
+                    self.pos += 1;
+                    let rhs = self.unary()?;
+                    value = match op {
+                        '*' => value * rhs,
+                        '/' => value / rhs,
+                        _ => value % rhs,
+                    };
+                }
+                Some(Token::Num(_) | Token::Ident(_) | Token::LParen) => value *= self.unary()?,
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                Ok(-self.unary()?)
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.primary()?;
+        if self.peek() == Some(&Token::Op('^')) {
+            self.pos += 1;
+            return Ok(base.powf(self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(n),
+            Some(Token::LParen) => {
+                let value = self.expr()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(value),
+                    _ => Err("Missing ')'".to_string()),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return constant(&name);
+                }
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::RParen) {
+                    loop {
+                        args.push(self.expr()?);
+                        match self.next() {
+                            Some(Token::Comma) => continue,
+                            Some(Token::RParen) => break,
+                            _ => return Err(format!("Missing ')' after arguments to {}", name)),
+                        }
+                    }
+                } else {
+                    self.pos += 1;
+                }
+                function(&name, &args)
+            }
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn constant(name: &str) -> Result<f64, String> {
+    match name.to_lowercase().as_str() {
+        "pi" | "π" => Ok(std::f64::consts::PI),
+        "tau" => Ok(std::f64::consts::TAU),
+        "e" => Ok(std::f64::consts::E),
+        _ => Err(format!("Unknown name '{}'", name)),
+    }
+}
+
+fn function(name: &str, args: &[f64]) -> Result<f64, String> {
+    let lower = name.to_lowercase();
+    let one = |f: fn(f64) -> f64| match args {
+        [x] => Ok(f(*x)),
+        _ => Err(format!("{} takes 1 argument", name)),
+    };
+    match lower.as_str() {
+        "sqrt" => one(f64::sqrt),
+        "cbrt" => one(f64::cbrt),
+        "abs" => one(f64::abs),
+        "exp" => one(f64::exp),
+        "ln" => one(f64::ln),
+        "log10" => one(f64::log10),
+        "log2" => one(f64::log2),
+        "sin" => one(f64::sin),
+        "cos" => one(f64::cos),
+        "tan" => one(f64::tan),
+        "asin" => one(f64::asin),
+        "acos" => one(f64::acos),
+        "atan" => one(f64::atan),
+        "floor" => one(f64::floor),
+        "ceil" => one(f64::ceil),
+        "trunc" => one(f64::trunc),
+        "log" => match args {
+            [x] => Ok(x.log10()),
+            [x, base] => Ok(x.log(*base)),
+            _ => Err("log takes 1 or 2 arguments".to_string()),
+        },
+        "round" => match args {
+            [x] => Ok(x.round()),
+            [x, digits] => {
+                let scale = 10f64.powi(*digits as i32);
+                Ok((x * scale).round() / scale)
+            }
+            _ => Err("round takes 1 or 2 arguments".to_string()),
+        },
+        "pow" | "atan2" | "hypot" => match args {
+            [a, b] if lower == "pow" => Ok(a.powf(*b)),
+            [a, b] if lower == "atan2" => Ok(a.atan2(*b)),
+            [a, b] => Ok(a.hypot(*b)),
+            _ => Err(format!("{} takes 2 arguments", name)),
+        },
+        "min" | "max" if !args.is_empty() => Ok(args
+            .iter()
+            .copied()
+            .reduce(|a, b| if lower == "min" { a.min(b) } else { a.max(b) })
+            .unwrap_or_default()),
+        _ => Err(format!("Unknown function '{}'", name)),
+    }
+}
+
+/// Evaluate an arithmetic expression.
+pub(crate) fn eval(expr: &str) -> Result<f64, String> {
+    let mut parser = Parser { tokens: tokenize(expr)?, pos: 0 };
+    if parser.tokens.is_empty() {
+        return Err("Empty expression".to_string());
+    }
+    let value = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {:?} at token {}", token, parser.pos + 1));
+    }
+    if !value.is_finite() {
+        return Err(format!("Result is {} (division by zero or out of range?)", value));
+    }
+    Ok(value)
+}
+
+/// A number with up to 12 significant digits and no float noise.
+pub(crate) fn format_number(x: f64) -> String {
+    if x == 0.0 {
+        return "0".to_string();
+    }
+    let magnitude = x.abs().log10().floor() as i32;
+    if !(-6..15).contains(&magnitude) {
+        let text = format!("{:.11e}", x);
+        let (mantissa, exp) = text.split_once('e').unwrap_or((&text, "0"));
+        let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+        return format!("{}e{}", mantissa, exp);
+    }
+    let decimals = (11 - magnitude).clamp(0, 17) as usize;
+    let text = format!("{:.*}", decimals, x);
+    let text = if text.contains('.') { text.trim_end_matches('0').trim_end_matches('.').to_string() } else { text };
+    if text == "-0" { "0".to_string() } else { text }
+}
+
+// ── Units ───────────────────────────────────────────────────────────────────
+
+/// A unit: `si = value * factor + offset` in its dimension's base unit.
+struct Unit {
+    names: &'static [&'static str],
+    dimension: &'static str,
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(names: &'static [&'static str], dimension: &'static str, factor: f64) -> Unit {
+    Unit { names, dimension, factor, offset: 0.0 }
+}
+
+/// Units by dimension.  Where a lower-cased name is ambiguous (`mb`), the
+/// first entry wins.
+const UNITS: &[Unit] = &[
+    // length (m)
+    unit(&["m", "meter", "meters", "metre", "metres"], "length", 1.0),
+    unit(&["km", "kilometer", "kilometers", "kilometre", "kilometres"], "length", 1000.0),
+    unit(&["cm", "centimeter", "centimeters", "centimetre", "centimetres"], "length", 0.01),
+    unit(&["mm", "millimeter", "millimeters", "millimetre", "millimetres"], "length", 0.001),
+    unit(&["um", "µm", "micrometer", "micrometers", "micron", "microns"], "length", 1e-6),
+    unit(&["nm", "nanometer", "nanometers"], "length", 1e-9),
+    unit(&["in", "inch", "inches", "\""], "length", 0.0254),
+    unit(&["ft", "foot", "feet", "'"], "length", 0.3048),
+    unit(&["yd", "yard", "yards"], "length", 0.9144),
+    unit(&["mi", "mile", "miles"], "length", 1609.344),
+    unit(&["nmi", "nautical mile", "nautical miles"], "length", 1852.0),
+    unit(&["au", "astronomical unit"], "length", 1.495978707e11),
+    unit(&["ly", "light year", "light years", "lightyear", "lightyears"], "length", 9.4607304725808e15),
+    // mass (kg)
+    unit(&["kg", "kilogram", "kilograms", "kilo", "kilos"], "mass", 1.0),
+    unit(&["g", "gram", "grams"], "mass", 0.001),
+    unit(&["mg", "milligram", "milligrams"], "mass", 1e-6),
+    unit(&["ug", "µg", "mcg", "microgram", "micrograms"], "mass", 1e-9),
+    unit(&["t", "tonne", "tonnes", "metric ton", "metric tons"], "mass", 1000.0),
+    unit(&["lb", "lbs", "pound", "pounds"], "mass", 0.45359237),
+    unit(&["oz", "ounce", "ounces"], "mass", 0.028349523125),
+    unit(&["st", "stone", "stones"], "mass", 6.35029318),
+    unit(&["ton", "tons", "short ton", "short tons"], "mass", 907.18474),
+    unit(&["long ton", "long tons"], "mass", 1016.0469088),
+    // time (s)
+    unit(&["s", "sec", "secs", "second", "seconds"], "time", 1.0),
+    unit(&["ms", "millisecond", "milliseconds"], "time", 0.001),
+    unit(&["us", "µs", "microsecond", "microseconds"], "time", 1e-6),
+    unit(&["ns", "nanosecond", "nanoseconds"], "time", 1e-9),
+    unit(&["min", "mins", "minute", "minutes"], "time", 60.0),
+    unit(&["h", "hr", "hrs", "hour", "hours"], "time", 3600.0),
+    unit(&["d", "day", "days"], "time", 86400.0),
+    unit(&["wk", "week", "weeks"], "time", 604800.0),
+    // Average Gregorian month and year.
+    unit(&["month", "months"], "time", 2629746.0),
+    unit(&["yr", "year", "years"], "time", 31556952.0),
+    // volume (m³)
+    unit(&["m3", "m³", "cubic meter", "cubic meters"], "volume", 1.0),
+    unit(&["l", "L", "liter", "liters", "litre", "litres"], "volume", 0.001),
+    unit(&["ml", "mL", "milliliter", "milliliters", "millilitre", "millilitres"], "volume", 1e-6),
+    unit(&["cl", "cL", "centiliter", "centiliters"], "volume", 1e-5),
+    unit(&["dl", "dL", "deciliter", "deciliters"], "volume", 1e-4),
+    unit(&["cm3", "cm³", "cc"], "volume", 1e-6),
+    unit(&["gal", "gallon", "gallons", "us gallon", "us gallons"], "volume", 0.003785411784),
+    unit(&["imp gal", "imperial gallon", "imperial gallons", "uk gallon", "uk gallons"], "volume", 0.00454609),
+    unit(&["qt", "quart", "quarts"], "volume", 0.000946352946),
+    unit(&["pt", "pint", "pints"], "volume", 0.000473176473),
+    unit(&["imp pt", "imperial pint", "imperial pints", "uk pint", "uk pints"], "volume", 0.00056826125),
+    unit(&["cup", "cups"], "volume", 0.0002365882365),
+    unit(&["fl oz", "floz", "fluid ounce", "fluid ounces"], "volume", 2.95735295625e-5),
+    unit(&["tbsp", "tablespoon", "tablespoons"], "volume", 1.478676478125e-5),
+    unit(&["tsp", "teaspoon", "teaspoons"], "volume", 4.92892159375e-6),
+    unit(&["ft3", "ft³", "cubic foot", "cubic feet"], "volume", 0.028316846592),
+    unit(&["in3", "in³", "cubic inch", "cubic inches"], "volume", 1.6387064e-5),
+    // area (m²)
+    unit(&["m2", "m²", "sq m", "square meter", "square meters"], "area", 1.0),
+    unit(&["km2", "km²", "sq km", "square kilometer", "square kilometers"], "area", 1e6),
+    unit(&["cm2", "cm²", "sq cm", "square centimeter", "square centimeters"], "area", 1e-4),
+    unit(&["ha", "hectare", "hectares"], "area", 1e4),
+    unit(&["acre", "acres"], "area", 4046.8564224),
+    unit(&["ft2", "ft²", "sq ft", "square foot", "square feet"], "area", 0.09290304),
+    unit(&["in2", "in²", "sq in", "square inch", "square inches"], "area", 0.00064516),
+    unit(&["yd2", "yd²", "sq yd", "square yard", "square yards"], "area", 0.83612736),
+    unit(&["mi2", "mi²", "sq mi", "square mile", "square miles"], "area", 2589988.110336),
+    // speed (m/s)
+    unit(&["m/s", "mps", "meters per second"], "speed", 1.0),
+    unit(&["km/h", "kmh", "kph", "kilometers per hour"], "speed", 1.0 / 3.6),
+    unit(&["mph", "mi/h", "miles per hour"], "speed", 0.44704),
+    unit(&["kn", "kt", "knot", "knots"], "speed", 1852.0 / 3600.0),
+    unit(&["ft/s", "fps", "feet per second"], "speed", 0.3048),
+    // temperature (K)
+    unit(&["K", "kelvin"], "temperature", 1.0),
+    Unit { names: &["C", "°C", "celsius", "centigrade"], dimension: "temperature", factor: 1.0, offset: 273.15 },
+    Unit { names: &["F", "°F", "fahrenheit"], dimension: "temperature", factor: 5.0 / 9.0, offset: 459.67 * 5.0 / 9.0 },
+    unit(&["R", "°R", "rankine"], "temperature", 5.0 / 9.0),
+    // data (bytes)
+    unit(&["B", "byte", "bytes"], "data", 1.0),
+    unit(&["bit", "bits", "b"], "data", 0.125),
+    unit(&["kB", "KB", "kilobyte", "kilobytes"], "data", 1e3),
+    unit(&["MB", "megabyte", "megabytes"], "data", 1e6),
+    unit(&["GB", "gigabyte", "gigabytes"], "data", 1e9),
+    unit(&["TB", "terabyte", "terabytes"], "data", 1e12),
+    unit(&["PB", "petabyte", "petabytes"], "data", 1e15),
+    unit(&["KiB", "kibibyte", "kibibytes"], "data", 1024.0),
+    unit(&["MiB", "mebibyte", "mebibytes"], "data", 1048576.0),
+    unit(&["GiB", "gibibyte", "gibibytes"], "data", 1073741824.0),
+    unit(&["TiB", "tebibyte", "tebibytes"], "data", 1099511627776.0),
+    unit(&["kbit", "kb", "Kb", "kilobit", "kilobits"], "data", 125.0),
+    unit(&["Mbit", "Mb", "megabit", "megabits"], "data", 125e3),
+    unit(&["Gbit", "Gb", "gigabit", "gigabits"], "data", 125e6),
+    // energy (J)
+    unit(&["J", "joule", "joules"], "energy", 1.0),
+    unit(&["kJ", "kilojoule", "kilojoules"], "energy", 1e3),
+    unit(&["MJ", "megajoule", "megajoules"], "energy", 1e6),
+    unit(&["cal", "calorie", "calories"], "energy", 4.184),
+    unit(&["kcal", "Cal", "kilocalorie", "kilocalories"], "energy", 4184.0),
+    unit(&["Wh", "watt hour", "watt hours"], "energy", 3600.0),
+    unit(&["kWh", "kilowatt hour", "kilowatt hours"], "energy", 3.6e6),
+    unit(&["BTU", "btu"], "energy", 1055.05585262),
+    unit(&["eV", "electronvolt", "electronvolts"], "energy", 1.602176634e-19),
+    // power (W)
+    unit(&["W", "watt", "watts"], "power", 1.0),
+    unit(&["kW", "kilowatt", "kilowatts"], "power", 1e3),
+    unit(&["MW", "megawatt", "megawatts"], "power", 1e6),
+    unit(&["hp", "horsepower"], "power", 745.69987158227022),
+    unit(&["PS", "metric horsepower"], "power", 735.49875),
+    // pressure (Pa)
+    unit(&["Pa", "pascal", "pascals"], "pressure", 1.0),
+    unit(&["hPa", "hectopascal", "hectopascals"], "pressure", 100.0),
+    unit(&["kPa", "kilopascal", "kilopascals"], "pressure", 1e3),
+    unit(&["bar", "bars"], "pressure", 1e5),
+    unit(&["mbar", "millibar", "millibars"], "pressure", 100.0),
+    unit(&["atm", "atmosphere", "atmospheres"], "pressure", 101325.0),
+    unit(&["psi"], "pressure", 6894.757293168),
+    unit(&["mmHg", "torr"], "pressure", 133.322387415),
+    // angle (rad)
+    unit(&["rad", "radian", "radians"], "angle", 1.0),
+    unit(&["deg", "°", "degree", "degrees"], "angle", std::f64::consts::PI / 180.0),
+    unit(&["grad", "gon"], "angle", std::f64::consts::PI / 200.0),
+    unit(&["turn", "turns", "rev", "revolution", "revolutions"], "angle", std::f64::consts::TAU),
+    unit(&["arcmin", "arcminute", "arcminutes"], "angle", std::f64::consts::PI / 10800.0),
+    unit(&["arcsec", "arcsecond", "arcseconds"], "angle", std::f64::consts::PI / 648000.0),
+    // frequency (Hz)
+    unit(&["Hz", "hertz"], "frequency", 1.0),
+    unit(&["kHz", "kilohertz"], "frequency", 1e3),
+    unit(&["MHz", "megahertz"], "frequency", 1e6),
+    unit(&["GHz", "gigahertz"], "frequency", 1e9),
+    unit(&["rpm"], "frequency", 1.0 / 60.0),
+];
+
+fn find_unit_exact(name: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|u| u.names.contains(&name.trim()))
+}
+
+/// Case-insensitive, also trying the name without a plural `s`.
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    find_unit_exact(name).or_else(|| {
+        let lower = name.trim().to_lowercase();
+        let singular = lower.strip_suffix('s');
+        UNITS.iter().find(|u| {
+            u.names
+                .iter()
+                .map(|n| n.to_lowercase())
+                .any(|n| n == lower || Some(n.as_str()) == singular)
+        })
+    })
+}
+
+/// Convert between units of the same dimension.
+fn convert_units(value: f64, from: &Unit, to: &Unit) -> Result<f64, String> {
+    if from.dimension != to.dimension {
+        return Err(format!(
+            "Can't convert {} ({}) to {} ({})",
+            from.names[0], from.dimension, to.names[0], to.dimension
+        ));
+    }
+    Ok((value * from.factor + from.offset - to.offset) / to.factor)
+}
+
+// ── Currencies ──────────────────────────────────────────────────────────────
+
+/// Exchange rates per US dollar, as cached on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Rates {
+    fetched_ms: u64,
+    /// When the provider last updated them.
+    updated: String,
+    rates: HashMap<String, f64>,
+}
+
+fn fetch_rates() -> Result<Rates, String> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?
+        .get(RATES_URL)
+        .send()
+        .map_err(|e| format!("Exchange rate request failed: {}", e))?;
+    let body: Value = response
+        .json()
+        .map_err(|e| format!("Failed to parse exchange rates: {}", e))?;
+    if body["result"] != "success" {
+        return Err(format!("Exchange rate service error: {}", body["error-type"]));
+    }
+    let rates: HashMap<String, f64> = serde_json::from_value(body["rates"].clone())
+        .map_err(|e| format!("Failed to parse exchange rates: {}", e))?;
+    Ok(Rates {
+        fetched_ms: now_ms(),
+        updated: body["time_last_update_utc"].as_str().unwrap_or_default().to_string(),
+        rates,
+    })
+}
+
+/// Cached rates, refreshed when older than the TTL.  The flag is set when
+/// a refresh failed and older rates are being used.
+fn load_rates(workspace_dir: &Path) -> Result<(Rates, bool), String> {
+    let path = workspace_dir.join(".cache").join("exchange_rates.json");
+    let cached: Option<Rates> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok());
+    if let Some(rates) = cached.as_ref().filter(|r| now_ms().saturating_sub(r.fetched_ms) < RATES_TTL_MS) {
+        return Ok((rates.clone(), false));
+    }
+    match fetch_rates() {
+        Ok(rates) => {
+            debug!(currencies = rates.rates.len(), "Fetched exchange rates");
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Ok(text) = serde_json::to_string(&rates) {
+                let _ = std::fs::write(&path, text);
+            }
+            Ok((rates, false))
+        }
+        Err(e) => match cached {
+            Some(rates) => {
+                warn!(error = %e, "Using stale exchange rates");
+                Ok((rates, true))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+fn is_currency_code(text: &str) -> bool {
+    text.len() == 3 && text.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+// ── Dates ───────────────────────────────────────────────────────────────────
+
+/// A calendar-aware duration: months and days follow the calendar (so
+/// `1d` across a DST change keeps the wall-clock time), seconds are exact.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Span {
+    months: i32,
+    days: i64,
+    seconds: f64,
+}
+
+/// Parse `3d 4h`, `-2w`, `1 month`, `1h30m`, `+90 minutes`.
+fn parse_span(text: &str) -> Result<Span, String> {
+    let re = regex::Regex::new(r"([+-]?\s*\d+(?:\.\d+)?)\s*([a-zA-Z]+)").map_err(|e| e.to_string())?;
+    let mut span = Span::default();
+    for caps in re.captures_iter(text) {
+        let amount: f64 = caps[1].replace(char::is_whitespace, "").parse().map_err(|_| format!("Invalid number in '{}'", text))?;
+        let unit = caps[2].to_lowercase();
+        let whole = |kind: &str| {
+            if amount.fract() == 0.0 {
+                Ok(amount as i64)
+            } else {
+                Err(format!("Use whole {} ('{}')", kind, &caps[0]))
+            }
+        };
+        match unit.as_str() {
+            "y" | "yr" | "yrs" | "year" | "years" => span.months += whole("years")? as i32 * 12,
+            "mo" | "mon" | "month" | "months" => span.months += whole("months")? as i32,
+            "w" | "wk" | "wks" | "week" | "weeks" => span.days += whole("weeks")? * 7,
+            "d" | "day" | "days" => span.days += whole("days")?,
+            "h" | "hr" | "hrs" | "hour" | "hours" => span.seconds += amount * 3600.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => span.seconds += amount * 60.0,
+            "s" | "sec" | "secs" | "second" | "seconds" => span.seconds += amount,
+            other => return Err(format!("Unknown duration unit '{}'; use y, mo, w, d, h, m or s", other)),
+        }
+    }
+    let significant = text.chars().filter(|c| !c.is_whitespace() && *c != ',').count();
+    let consumed: usize = re.captures_iter(text).map(|c| c[0].chars().filter(|c| !c.is_whitespace()).count()).sum();
+    if consumed == 0 || consumed != significant {
+        return Err(format!("Can't read '{}' as a duration; use e.g. '3d 4h', '-2w' or '1 month'", text));
+    }
+    Ok(span)
+}
+
+fn add_span(start: DateTime<Tz>, span: Span) -> Result<DateTime<Tz>, String> {
+    let tz = start.timezone();
+    let mut naive = start.naive_local();
+    naive = match span.months {
+        0 => naive,
+        m if m > 0 => naive.checked_add_months(Months::new(m as u32)),
+        m => naive.checked_sub_months(Months::new(m.unsigned_abs())),
+    }
+    .ok_or("Date out of range")?;
+    naive += Duration::days(span.days);
+    let shifted = localize(tz, naive)?;
+    Ok(shifted + Duration::milliseconds((span.seconds * 1000.0).round() as i64))
+}
+
+fn describe(t: &DateTime<Tz>) -> String {
+    format!("{} ({})", t.format("%a %Y-%m-%d %H:%M:%S %Z (UTC%:z)"), t.timezone().name())
+}
+
+/// Monday–Friday days in `[start, end)`.
+fn weekdays_between(start: DateTime<Tz>, end: DateTime<Tz>) -> i64 {
+    let (a, b, sign) = if start <= end { (start, end, 1) } else { (end, start, -1) };
+    let mut count = 0;
+    let mut day = a.date_naive();
+    while day < b.date_naive() {
+        if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            count += 1;
+        }
+        day = day.succ_opt().unwrap_or(day);
+    }
+    count * sign
+}
+
+fn describe_duration(total_seconds: i64) -> String {
+    let sign = if total_seconds < 0 { "-" } else { "" };
+    let s = total_seconds.unsigned_abs();
+    format!("{}{}d {}h {}m {}s", sign, s / 86400, s / 3600 % 24, s / 60 % 60, s % 60)
+}
+
+// ── Tool ────────────────────────────────────────────────────────────────────
+
+fn str_arg<'a>(args: &'a Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty())
+}
+
+/// Deterministic arithmetic, conversions and date/time math.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_calc(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let op = str_arg(args, "op").ok_or("Missing required parameter: op")?;
+    debug!(op, "Executing calc tool");
+
+    match op {
+        "eval" => {
+            let expr = str_arg(args, "expression").ok_or("Missing required parameter: expression")?;
+            Ok(format!("{} = {}", expr.trim(), format_number(eval(expr)?)))
+        }
+        "convert" => {
+            let value = match args.get("value") {
+                Some(Value::Number(n)) => n.as_f64().unwrap_or_default(),
+                Some(Value::String(s)) => eval(s)?,
+                _ => 1.0,
+            };
+            let from = str_arg(args, "from").ok_or("Missing required parameter: from")?;
+            let to = str_arg(args, "to").ok_or("Missing required parameter: to")?;
+
+            // Exact unit names first, so `cup` is a cup and not the Cuban peso.
+            if let (Some(f), Some(t)) = (find_unit_exact(from), find_unit_exact(to)) {
+                return Ok(format!("{} {} = {} {}", format_number(value), from, format_number(convert_units(value, f, t)?), to));
+            }
+            if is_currency_code(from) && is_currency_code(to) {
+                let (from_code, to_code) = (from.to_uppercase(), to.to_uppercase());
+                let (rates, stale) = load_rates(workspace_dir)?;
+                if let (Some(f), Some(t)) = (rates.rates.get(&from_code), rates.rates.get(&to_code)) {
+                    let converted = value / f * t;
+                    return Ok(format!(
+                        "{} {} = {} {} (rate {}; rates from {}{})",
+                        format_number(value),
+                        from_code,
+                        format_number((converted * 100.0).round() / 100.0),
+                        to_code,
+                        format_number(t / f),
+                        rates.updated,
+                        if stale { ", couldn't refresh" } else { "" }
+                    ));
+                }
+            }
+            let f = find_unit(from).ok_or_else(|| format!("Unknown unit or currency '{}'", from))?;
+            let t = find_unit(to).ok_or_else(|| format!("Unknown unit or currency '{}'", to))?;
+            Ok(format!("{} {} = {} {}", format_number(value), from, format_number(convert_units(value, f, t)?), to))
+        }
+        "time" => {
+            let from = parse_tz(str_arg(args, "from").or(str_arg(args, "tz")).unwrap_or("local"))?;
+            let time = parse_datetime(str_arg(args, "time").unwrap_or("now"), from)?;
+            let targets: Vec<String> = match args.get("to") {
+                Some(Value::Array(list)) => list.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+                Some(Value::String(s)) => s.split(',').map(|t| t.trim().to_string()).collect(),
+                _ => vec!["UTC".to_string()],
+            };
+            let mut lines = vec![describe(&time)];
+            for target in targets {
+                lines.push(format!("= {}", describe(&time.with_timezone(&parse_tz(&target)?))));
+            }
+            Ok(lines.join("\n"))
+        }
+        "date" => {
+            let tz = parse_tz(str_arg(args, "tz").unwrap_or("local"))?;
+            let start = parse_datetime(str_arg(args, "date").unwrap_or("now"), tz)?;
+            if let Some(span) = str_arg(args, "add") {
+                let result = add_span(start, parse_span(span)?)?;
+                return Ok(format!("{}\n+ {}\n= {}\n(unix ms {})", describe(&start), span.trim(), describe(&result), result.timestamp_millis()));
+            }
+            if let Some(until) = str_arg(args, "until") {
+                let end = parse_datetime(until, tz)?;
+                let seconds = (end - start).num_seconds();
+                return Ok(format!(
+                    "From {}\nto   {}\n= {} ({} days; {} weekdays)",
+                    describe(&start),
+                    describe(&end),
+                    describe_duration(seconds),
+                    format_number(seconds as f64 / 86400.0),
+                    weekdays_between(start, end)
+                ));
+            }
+            Ok(format!(
+                "{}\nISO week {}, day {} of the year, unix ms {}",
+                describe(&start),
+                start.iso_week().week(),
+                start.ordinal(),
+                start.timestamp_millis()
+            ))
+        }
+        "schedule" => {
+            let expr = str_arg(args, "expression").ok_or("Missing required parameter: expression (a cron expression)")?;
+            let tz = parse_tz(str_arg(args, "tz").unwrap_or("local"))?;
+            let cron = CronExpr::parse(expr)?;
+            let count = args.get("count").and_then(|v| v.as_u64()).unwrap_or(5).clamp(1, MAX_SCHEDULE_RUNS as u64) as usize;
+            let mut runs = Vec::new();
+            let mut after = Utc::now().with_timezone(&tz);
+            while runs.len() < count {
+                let Some(next) = cron.next_after(after) else { break };
+                runs.push(format!("{} = {}", describe(&next), format_ms(next.timestamp_millis() as u64, Tz::UTC)));
+                after = next;
+            }
+            if runs.is_empty() {
+                return Ok(format!("'{}' never fires (within five years).", expr));
+            }
+            Ok(format!("Next runs of '{}' in {}:\n{}", expr, tz.name(), runs.join("\n")))
+        }
+        other => Err(format!("Unknown op: {}. Use eval, convert, time, date or schedule", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval("2 + 3 * 4").unwrap(), 14.0);
+        assert_eq!(eval("(2 + 3) * 4").unwrap(), 20.0);
+        assert_eq!(eval("2 ^ 3 ^ 2").unwrap(), 512.0);
+        assert_eq!(eval("-2 ^ 2").unwrap(), -4.0);
+        assert_eq!(eval("2**10 % 1000").unwrap(), 24.0);
+        assert_eq!(format_number(eval("max(3, 7, 5) + round(2.456, 2)").unwrap()), "9.46");
+        assert_eq!(eval("1.5e3 + 1_000").unwrap(), 2500.0);
+        assert!((eval("2pi").unwrap() - std::f64::consts::TAU).abs() < 1e-12);
+        assert!(eval("1 / 0").unwrap_err().contains("division"));
+        assert!(eval("2 +").is_err());
+        assert!(eval("foo(1)").unwrap_err().contains("Unknown function"));
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0.1 + 0.2), "0.3");
+        assert_eq!(format_number(1e20), "1e20");
+        assert_eq!(format_number(-42.0), "-42");
+        assert_eq!(format_number(1234.5678), "1234.5678");
+    }
+
+    #[test]
+    fn test_convert_units() {
+        let ws = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out = exec_calc(&json!({ "op": "convert", "value": 100, "from": "C", "to": "F" }), ws).unwrap();
+        assert_eq!(out, "100 C = 212 F");
+        let out = exec_calc(&json!({ "op": "convert", "value": 5, "from": "Kilometers", "to": "miles" }), ws).unwrap();
+        assert!(out.ends_with("3.10685596119 miles"));
+        let out = exec_calc(&json!({ "op": "convert", "value": "2 * 512", "from": "MiB", "to": "GiB" }), ws).unwrap();
+        assert_eq!(out, "1024 MiB = 1 GiB");
+        let err = exec_calc(&json!({ "op": "convert", "value": 1, "from": "kg", "to": "m" }), ws).unwrap_err();
+        assert!(err.contains("mass"));
+    }
+
+    #[test]
+    fn test_parse_span() {
+        assert_eq!(parse_span("3d 4h").unwrap(), Span { months: 0, days: 3, seconds: 14400.0 });
+        assert_eq!(parse_span("1y 2mo").unwrap(), Span { months: 14, days: 0, seconds: 0.0 });
+        assert_eq!(parse_span("-2w").unwrap(), Span { months: 0, days: -14, seconds: 0.0 });
+        assert_eq!(parse_span("1h30m").unwrap().seconds, 5400.0);
+        assert!(parse_span("1.5 days").is_err());
+        assert!(parse_span("soon").is_err());
+        assert!(parse_span("3 fortnights").is_err());
+    }
+
+    #[test]
+    fn test_date_math() {
+        let ws = Path::new(env!("CARGO_MANIFEST_DIR"));
+        // Adding a day across the spring DST change keeps 09:00 local.
+        let out = exec_calc(&json!({ "op": "date", "date": "2026-03-28 09:00", "tz": "Europe/Berlin", "add": "1d" }), ws).unwrap();
+        assert!(out.contains("= Sun 2026-03-29 09:00:00 CEST"));
+        let out = exec_calc(&json!({ "op": "date", "date": "2026-01-31", "tz": "UTC", "add": "1 month" }), ws).unwrap();
+        assert!(out.contains("= Sat 2026-02-28"));
+        let out = exec_calc(&json!({ "op": "date", "date": "2026-03-02", "until": "2026-03-16", "tz": "UTC" }), ws).unwrap();
+        assert!(out.contains("14d 0h 0m 0s (14 days; 10 weekdays)"));
+    }
+
+    #[test]
+    fn test_time_and_schedule() {
+        let ws = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out = exec_calc(&json!({ "op": "time", "time": "2026-07-01 09:00", "from": "America/New_York", "to": ["Asia/Tokyo"] }), ws).unwrap();
+        assert!(out.contains("2026-07-01 22:00:00 JST"));
+        let out = exec_calc(&json!({ "op": "schedule", "expression": "0 9 * * mon", "tz": "UTC", "count": 2 }), ws).unwrap();
+        assert_eq!(out.matches("Mon ").count(), 2);
+        assert!(exec_calc(&json!({ "op": "schedule", "expression": "0 9 * *" }), ws).is_err());
+        assert!(exec_calc(&json!({ "op": "solve" }), ws).unwrap_err().contains("Unknown op"));
+    }
+}
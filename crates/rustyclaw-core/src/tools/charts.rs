@@ -0,0 +1,285 @@
+//! Chart rendering shared by the `render_report` and `plot` tools.
+//!
+//! A [`ChartSpec`] is plain JSON so the model can write one inline:
+//!
+//! ```json
+//! { "type": "bar", "title": "Signups", "labels": ["Mon", "Tue", "Wed"],
+//!   "series": [{ "name": "web", "values": [12, 18, 9] }] }
+//! ```
+//!
+//! Series without `x` values are plotted against their index, which lines
+//! them up with `labels`.
+//!
+//! Drawing needs the `charts` feature (plotters); without it specs still
+//! parse and validate, but rendering returns an error.
+
+#[cfg(feature = "charts")]
+use plotters::coord::Shift;
+#[cfg(feature = "charts")]
+use plotters::drawing::DrawingAreaErrorKind;
+#[cfg(feature = "charts")]
+use plotters::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ChartKind {
+    #[default]
+    Line,
+    Bar,
+    Scatter,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub(crate) struct Series {
+    #[serde(default)]
+    pub name: String,
+    pub values: Vec<f64>,
+    /// Numeric x positions; defaults to 0, 1, 2, ...
+    #[serde(default)]
+    pub x: Vec<f64>,
+}
+
+impl Series {
+    #[cfg_attr(not(feature = "charts"), allow(dead_code))]
+    fn points(&self) -> Vec<(f64, f64)> {
+        if self.x.is_empty() {
+            self.values.iter().enumerate().map(|(i, v)| (i as f64, *v)).collect()
+        } else {
+            self.x.iter().copied().zip(self.values.iter().copied()).collect()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChartSpec {
+    #[serde(default, rename = "type")]
+    pub kind: ChartKind,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub x_label: String,
+    #[serde(default)]
+    pub y_label: String,
+    /// Category names for the x axis.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+impl ChartSpec {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.series.is_empty() || self.series.iter().all(|s| s.values.is_empty()) {
+            return Err("Chart needs at least one series with values".to_string());
+        }
+        for s in &self.series {
+            if !s.x.is_empty() && s.x.len() != s.values.len() {
+                return Err(format!("Series '{}' has {} x values for {} values", s.name, s.x.len(), s.values.len()));
+            }
+            if s.values.iter().chain(&s.x).any(|v| !v.is_finite()) {
+                return Err(format!("Series '{}' contains a non-finite number", s.name));
+            }
+        }
+        if self.kind == ChartKind::Bar && self.series.iter().any(|s| !s.x.is_empty()) {
+            return Err("Bar charts use labels for the x axis, not x values".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether the x axis shows category labels rather than numbers.
+    #[cfg_attr(not(feature = "charts"), allow(dead_code))]
+    fn categorical(&self) -> bool {
+        self.kind == ChartKind::Bar || (!self.labels.is_empty() && self.series.iter().all(|s| s.x.is_empty()))
+    }
+
+    /// Axis ranges with a little headroom.  Bars always start from zero.
+    #[cfg_attr(not(feature = "charts"), allow(dead_code))]
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let points: Vec<(f64, f64)> = self.series.iter().flat_map(Series::points).collect();
+        let x = if self.categorical() {
+            let n = self.series.iter().map(|s| s.values.len()).max().unwrap_or(1).max(self.labels.len());
+            (-0.5, n as f64 - 0.5)
+        } else {
+            padded(points.iter().map(|p| p.0), false)
+        };
+        (x, padded(points.iter().map(|p| p.1), self.kind == ChartKind::Bar))
+    }
+}
+
+#[cfg_attr(not(feature = "charts"), allow(dead_code))]
+fn padded(values: impl Iterator<Item = f64>, include_zero: bool) -> (f64, f64) {
+    let (mut lo, mut hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if include_zero {
+        lo = lo.min(0.0);
+        hi = hi.max(0.0);
+    }
+    if !lo.is_finite() || !hi.is_finite() {
+        return (0.0, 1.0);
+    }
+    if (hi - lo).abs() < f64::EPSILON {
+        return (lo - 1.0, hi + 1.0);
+    }
+    let pad = (hi - lo) * 0.05;
+    (if include_zero && lo == 0.0 { 0.0 } else { lo - pad }, hi + pad)
+}
+
+/// Draw `spec` onto any plotters backend.
+#[cfg(feature = "charts")]
+pub(crate) fn draw<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, spec: &ChartSpec) -> Result<(), String> {
+    spec.validate()?;
+    let err = |e: DrawingAreaErrorKind<DB::ErrorType>| format!("Chart rendering failed: {}", e);
+    root.fill(&WHITE).map_err(err)?;
+
+    let ((x0, x1), (y0, y1)) = spec.bounds();
+    let mut chart = ChartBuilder::on(root)
+        .caption(&spec.title, ("sans-serif", 22))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x0..x1, y0..y1)
+        .map_err(err)?;
+
+    let labels = spec.labels.clone();
+    let categorical = spec.categorical();
+    let x_formatter = move |x: &f64| {
+        if !categorical {
+            return format!("{}", (x * 100.0).round() / 100.0);
+        }
+        let i = x.round();
+        if (x - i).abs() > 1e-6 || i < 0.0 {
+            return String::new();
+        }
+        labels.get(i as usize).cloned().unwrap_or_else(|| format!("{}", i))
+    };
+    let mut mesh = chart.configure_mesh();
+    mesh.x_desc(&spec.x_label).y_desc(&spec.y_label).x_label_formatter(&x_formatter);
+    if categorical {
+        mesh.x_labels(((x1 - x0).round() as usize).max(2)).disable_x_mesh();
+    }
+    mesh.draw().map_err(err)?;
+
+    let count = spec.series.len();
+    for (i, series) in spec.series.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let points = series.points();
+        let drawn = match spec.kind {
+            ChartKind::Line => chart
+                .draw_series(LineSeries::new(points, color.stroke_width(2)))
+                .map_err(err)?,
+            ChartKind::Scatter => chart
+                .draw_series(points.into_iter().map(|p| Circle::new(p, 4, color.filled())))
+                .map_err(err)?,
+            ChartKind::Bar => {
+                // Series share each category slot side by side.
+                let width = 0.8 / count as f64;
+                chart
+                    .draw_series(points.into_iter().map(|(x, y)| {
+                        let left = x - 0.4 + i as f64 * width;
+                        Rectangle::new([(left, 0.0), (left + width, y)], color.filled())
+                    }))
+                    .map_err(err)?
+            }
+        };
+        if !series.name.is_empty() {
+            drawn
+                .label(series.name.as_str())
+                .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 14, y + 5)], color.filled()));
+        }
+    }
+
+    if spec.series.iter().any(|s| !s.name.is_empty()) {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.85))
+            .border_style(BLACK.mix(0.3))
+            .draw()
+            .map_err(err)?;
+    }
+    root.present().map_err(err)
+}
+
+/// Render `spec` as an SVG document.
+pub(crate) fn render_svg(spec: &ChartSpec, width: u32, height: u32) -> Result<String, String> {
+    #[cfg(feature = "charts")]
+    {
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (width, height)).into_drawing_area();
+            draw(&root, spec)?;
+        }
+        Ok(svg)
+    }
+    #[cfg(not(feature = "charts"))]
+    {
+        let _ = (spec, width, height);
+        Err(NOT_COMPILED.to_string())
+    }
+}
+
+/// Render `spec` as a PNG file.
+pub(crate) fn render_png(spec: &ChartSpec, width: u32, height: u32, path: &std::path::Path) -> Result<(), String> {
+    #[cfg(feature = "charts")]
+    {
+        let root = BitMapBackend::new(path, (width, height)).into_drawing_area();
+        draw(&root, spec)
+    }
+    #[cfg(not(feature = "charts"))]
+    {
+        let _ = (spec, width, height, path);
+        Err(NOT_COMPILED.to_string())
+    }
+}
+
+#[cfg(not(feature = "charts"))]
+const NOT_COMPILED: &str = "Chart rendering isn't compiled in. Rebuild with `--features charts`.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(json: &str) -> ChartSpec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_spec_validation() {
+        assert!(spec(r#"{"series": []}"#).validate().is_err());
+        assert!(spec(r#"{"series": [{"values": [1, 2], "x": [1]}]}"#).validate().is_err());
+        assert!(spec(r#"{"type": "bar", "series": [{"values": [1], "x": [3]}]}"#).validate().is_err());
+        assert!(spec(r#"{"type": "scatter", "series": [{"values": [1, 2], "x": [3, 4]}]}"#).validate().is_ok());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bar = spec(r#"{"type": "bar", "labels": ["a", "b", "c"], "series": [{"values": [5, 10, 20]}]}"#);
+        let ((x0, x1), (y0, y1)) = bar.bounds();
+        assert_eq!((x0, x1), (-0.5, 2.5));
+        assert_eq!(y0, 0.0);
+        assert_eq!(y1, 20.0 + 20.0 * 0.05);
+
+        let flat = spec(r#"{"series": [{"values": [3, 3]}]}"#);
+        assert_eq!(flat.bounds().1, (2.0, 4.0));
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_render_svg() {
+        let chart = spec(r#"{"type": "line", "title": "Visits", "labels": ["Mon", "Tue"],
+                             "series": [{"name": "web", "values": [1, 3]}, {"name": "app", "values": [2, 2]}]}"#);
+        let svg = render_svg(&chart, 640, 360).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Visits"));
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_render_png() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chart.png");
+        let chart = spec(r#"{"type": "scatter", "series": [{"values": [1, 4, 9], "x": [1, 2, 3]}]}"#);
+        render_png(&chart, 320, 240, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"\x89PNG"));
+    }
+}
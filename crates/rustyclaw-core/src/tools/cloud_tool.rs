@@ -0,0 +1,585 @@
+//! The `cloud_status` tool: daily spend and server status across cloud
+//! providers, compact enough for a cron job to send as a morning report.
+//!
+//! Each provider is a [`CloudBackend`]; add one by implementing the trait
+//! and listing it in [`backends`].  Credentials come from the secrets vault
+//! (a credential or a plain secret, falling back to the environment):
+//!
+//! - AWS Cost Explorer: a username/password credential `aws` (access key id
+//!   / secret key), or `AWS_ACCESS_KEY_ID` + `AWS_SECRET_ACCESS_KEY`
+//!   (+ `AWS_SESSION_TOKEN`).
+//! - GCP: the BigQuery billing export named by `GCP_BILLING_TABLE`
+//!   (`project.dataset.table`), queried with `GCP_ACCESS_TOKEN` or
+//!   `gcloud auth print-access-token`.
+//! - Hetzner Cloud: `HETZNER_API_TOKEN`; spend is estimated from list prices.
+
+use super::helpers::vault;
+use crate::secrets::{AccessContext, CredentialValue};
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::time::Duration;
+use tracing::{debug, instrument, warn};
+
+/// Look a secret up in the vault (credential first, then raw secret), then
+/// in the environment.
+fn secret(name: &str) -> Option<String> {
+    if let Some(vault) = vault() {
+        let mut guard = vault.blocking_lock();
+        if let Ok(Some((_, CredentialValue::Single(value)))) = guard.get_credential(name, &AccessContext::default()) {
+            return Some(value);
+        }
+        if let Ok(Some(value)) = guard.get_secret(name, false) {
+            return Some(value);
+        }
+    }
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn http() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+/// Spend and status reported by one provider.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ProviderSummary {
+    pub provider: &'static str,
+    pub currency: String,
+    /// Cost per day, oldest first.
+    pub daily: Vec<(NaiveDate, f64)>,
+    /// Servers or instances with their state.
+    pub resources: Vec<Value>,
+    pub note: Option<String>,
+}
+
+impl ProviderSummary {
+    fn total(&self) -> f64 {
+        self.daily.iter().map(|(_, c)| c).sum()
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "provider": self.provider,
+            "currency": self.currency,
+            "total": round2(self.total()),
+            "daily": self.daily.iter().map(|(d, c)| json!({ "date": d.to_string(), "cost": round2(*c) })).collect::<Vec<_>>(),
+            "resources": self.resources,
+            "note": self.note,
+        })
+    }
+}
+
+/// A cloud provider that can report spend.
+pub(crate) trait CloudBackend {
+    fn name(&self) -> &'static str;
+    /// Whether credentials for this provider are available.
+    fn configured(&self) -> bool;
+    /// Spend for the last `days` full days (and current resource status
+    /// where the provider exposes it).
+    fn summary(&self, days: u32) -> Result<ProviderSummary, String>;
+}
+
+/// All known backends.
+pub(crate) fn backends(args: &Value) -> Vec<Box<dyn CloudBackend>> {
+    let gcp_table = args
+        .get("gcpBillingTable")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    vec![Box::new(Aws), Box::new(Gcp { table: gcp_table }), Box::new(Hetzner)]
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// The last `days` full days, as [start, end) dates.
+fn period(days: u32) -> (NaiveDate, NaiveDate) {
+    let end = Utc::now().date_naive();
+    (end - ChronoDuration::days(days.max(1) as i64), end)
+}
+
+// ── AWS ─────────────────────────────────────────────────────────────────────
+
+struct Aws;
+
+struct AwsKeys {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+}
+
+fn aws_keys() -> Option<AwsKeys> {
+    if let Some(vault) = vault() {
+        let mut guard = vault.blocking_lock();
+        if let Ok(Some((_, CredentialValue::UserPass { username, password }))) =
+            guard.get_credential("aws", &AccessContext::default())
+        {
+            return Some(AwsKeys { access_key: username, secret_key: password, session_token: None });
+        }
+    }
+    Some(AwsKeys {
+        access_key: secret("AWS_ACCESS_KEY_ID")?,
+        secret_key: secret("AWS_SECRET_ACCESS_KEY")?,
+        session_token: secret("AWS_SESSION_TOKEN"),
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 signing key for `date` (YYYYMMDD).
+fn sigv4_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date);
+    let k_region = hmac_sha256(&k_date, region);
+    let k_service = hmac_sha256(&k_region, service);
+    hmac_sha256(&k_service, "aws4_request")
+}
+
+/// `Authorization` header for a SigV4-signed POST to `/` with the given
+/// headers (lower-case names, sorted) and body.
+fn sigv4_authorization(
+    keys: &AwsKeys,
+    amz_date: &str,
+    region: &str,
+    service: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> String {
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v.trim())).collect();
+    let signed_headers = headers.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex(&Sha256::digest(body.as_bytes()))
+    );
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signature = hex(&hmac_sha256(&sigv4_key(&keys.secret_key, date, region, service), &string_to_sign));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        keys.access_key, scope, signed_headers, signature
+    )
+}
+
+fn parse_aws_costs(response: &Value) -> (String, Vec<(NaiveDate, f64)>) {
+    let mut currency = String::from("USD");
+    let daily = response["ResultsByTime"]
+        .as_array()
+        .map(|days| {
+            days.iter()
+                .filter_map(|day| {
+                    let date = NaiveDate::parse_from_str(day["TimePeriod"]["Start"].as_str()?, "%Y-%m-%d").ok()?;
+                    let cost = &day["Total"]["UnblendedCost"];
+                    if let Some(unit) = cost["Unit"].as_str() {
+                        currency = unit.to_string();
+                    }
+                    Some((date, cost["Amount"].as_str()?.parse().ok()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    (currency, daily)
+}
+
+impl CloudBackend for Aws {
+    fn name(&self) -> &'static str {
+        "aws"
+    }
+
+    fn configured(&self) -> bool {
+        aws_keys().is_some()
+    }
+
+    fn summary(&self, days: u32) -> Result<ProviderSummary, String> {
+        let keys = aws_keys().ok_or("No AWS credentials")?;
+        // Cost Explorer only has an endpoint in us-east-1.
+        let (region, host) = ("us-east-1", "ce.us-east-1.amazonaws.com");
+        let (start, end) = period(days);
+        let body = json!({
+            "TimePeriod": { "Start": start.to_string(), "End": end.to_string() },
+            "Granularity": "DAILY",
+            "Metrics": ["UnblendedCost"],
+        })
+        .to_string();
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let target = "AWSInsightsIndexService.GetCostAndUsage";
+        let content_type = "application/x-amz-json-1.1";
+        let mut headers = vec![
+            ("content-type", content_type),
+            ("host", host),
+            ("x-amz-date", amz_date.as_str()),
+        ];
+        if let Some(token) = &keys.session_token {
+            headers.push(("x-amz-security-token", token.as_str()));
+        }
+        headers.push(("x-amz-target", target));
+        let authorization = sigv4_authorization(&keys, &amz_date, region, "ce", &headers, &body);
+
+        let mut request = http()?
+            .post(format!("https://{}/", host))
+            .header("Content-Type", content_type)
+            .header("X-Amz-Date", &amz_date)
+            .header("X-Amz-Target", target)
+            .header("Authorization", authorization)
+            .body(body);
+        if let Some(token) = &keys.session_token {
+            request = request.header("X-Amz-Security-Token", token);
+        }
+        let response = request.send().map_err(|e| format!("Cost Explorer request failed: {}", e))?;
+        let status = response.status();
+        let text = response.text().unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!("Cost Explorer returned {}: {}", status, text.chars().take(300).collect::<String>()));
+        }
+        let parsed: Value = serde_json::from_str(&text).map_err(|e| format!("Bad Cost Explorer response: {}", e))?;
+        let (currency, daily) = parse_aws_costs(&parsed);
+        Ok(ProviderSummary { provider: "aws", currency, daily, ..Default::default() })
+    }
+}
+
+// ── GCP ─────────────────────────────────────────────────────────────────────
+
+struct Gcp {
+    table: Option<String>,
+}
+
+impl Gcp {
+    fn table(&self) -> Option<String> {
+        self.table.clone().or_else(|| secret("GCP_BILLING_TABLE"))
+    }
+}
+
+fn gcp_token() -> Result<String, String> {
+    if let Some(token) = secret("GCP_ACCESS_TOKEN") {
+        return Ok(token);
+    }
+    let output = std::process::Command::new("gcloud")
+        .args(["auth", "print-access-token"])
+        .output()
+        .map_err(|e| format!("No GCP_ACCESS_TOKEN and gcloud failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("gcloud auth failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Daily net cost (after credits) from the standard billing export schema.
+fn gcp_query(table: &str, days: u32) -> String {
+    format!(
+        "SELECT CAST(DATE(usage_start_time) AS STRING) AS day, \
+         SUM(cost) + SUM(IFNULL((SELECT SUM(c.amount) FROM UNNEST(credits) c), 0)) AS total, \
+         ANY_VALUE(currency) AS currency \
+         FROM `{}` \
+         WHERE DATE(usage_start_time) >= DATE_SUB(CURRENT_DATE(), INTERVAL {} DAY) \
+         AND DATE(usage_start_time) < CURRENT_DATE() \
+         GROUP BY day ORDER BY day",
+        table,
+        days.max(1)
+    )
+}
+
+fn parse_bigquery_rows(response: &Value) -> (String, Vec<(NaiveDate, f64)>) {
+    let mut currency = String::from("USD");
+    let daily = response["rows"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| {
+                    let cells = row["f"].as_array()?;
+                    let date = NaiveDate::parse_from_str(cells.first()?["v"].as_str()?, "%Y-%m-%d").ok()?;
+                    let cost = cells.get(1)?["v"].as_str()?.parse().ok()?;
+                    if let Some(c) = cells.get(2).and_then(|c| c["v"].as_str()) {
+                        currency = c.to_string();
+                    }
+                    Some((date, cost))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    (currency, daily)
+}
+
+impl CloudBackend for Gcp {
+    fn name(&self) -> &'static str {
+        "gcp"
+    }
+
+    fn configured(&self) -> bool {
+        self.table().is_some()
+    }
+
+    fn summary(&self, days: u32) -> Result<ProviderSummary, String> {
+        let table = self.table().ok_or("No GCP_BILLING_TABLE configured")?;
+        let project = table.split('.').next().filter(|p| !p.is_empty()).ok_or("GCP_BILLING_TABLE must be project.dataset.table")?;
+        let response = http()?
+            .post(format!("https://bigquery.googleapis.com/bigquery/v2/projects/{}/queries", project))
+            .bearer_auth(gcp_token()?)
+            .json(&json!({ "query": gcp_query(&table, days), "useLegacySql": false, "timeoutMs": 25000 }))
+            .send()
+            .map_err(|e| format!("BigQuery request failed: {}", e))?;
+        let status = response.status();
+        let parsed: Value = response.json().map_err(|e| format!("Bad BigQuery response: {}", e))?;
+        if !status.is_success() {
+            return Err(format!("BigQuery returned {}: {}", status, parsed["error"]["message"].as_str().unwrap_or("")));
+        }
+        let (currency, daily) = parse_bigquery_rows(&parsed);
+        Ok(ProviderSummary {
+            provider: "gcp",
+            currency,
+            daily,
+            note: Some("Billing export lags by up to a day.".to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+// ── Hetzner ─────────────────────────────────────────────────────────────────
+
+struct Hetzner;
+
+/// Servers with their status and gross hourly price at their location.
+fn parse_hetzner_servers(response: &Value) -> (Vec<Value>, f64) {
+    let mut hourly_total = 0.0;
+    let servers = response["servers"]
+        .as_array()
+        .map(|servers| {
+            servers
+                .iter()
+                .map(|s| {
+                    let location = s["datacenter"]["location"]["name"].as_str().unwrap_or("");
+                    let hourly = s["server_type"]["prices"]
+                        .as_array()
+                        .and_then(|prices| prices.iter().find(|p| p["location"] == location))
+                        .and_then(|p| p["price_hourly"]["gross"].as_str())
+                        .and_then(|p| p.parse::<f64>().ok())
+                        .unwrap_or(0.0);
+                    hourly_total += hourly;
+                    json!({
+                        "name": s["name"],
+                        "status": s["status"],
+                        "type": s["server_type"]["name"],
+                        "location": location,
+                        "created": s["created"],
+                        "hourly": hourly,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    (servers, hourly_total)
+}
+
+impl CloudBackend for Hetzner {
+    fn name(&self) -> &'static str {
+        "hetzner"
+    }
+
+    fn configured(&self) -> bool {
+        secret("HETZNER_API_TOKEN").is_some()
+    }
+
+    fn summary(&self, days: u32) -> Result<ProviderSummary, String> {
+        let token = secret("HETZNER_API_TOKEN").ok_or("No HETZNER_API_TOKEN")?;
+        let response = http()?
+            .get("https://api.hetzner.cloud/v1/servers?per_page=50")
+            .bearer_auth(token)
+            .send()
+            .map_err(|e| format!("Hetzner request failed: {}", e))?;
+        let status = response.status();
+        let parsed: Value = response.json().map_err(|e| format!("Bad Hetzner response: {}", e))?;
+        if !status.is_success() {
+            return Err(format!("Hetzner returned {}: {}", status, parsed["error"]["message"].as_str().unwrap_or("")));
+        }
+        let (servers, hourly) = parse_hetzner_servers(&parsed);
+        // Servers are billed whether running or not, so every day costs
+        // the same at today's fleet.
+        let (start, end) = period(days);
+        let daily = start.iter_days().take_while(|d| *d < end).map(|d| (d, hourly * 24.0)).collect();
+        Ok(ProviderSummary {
+            provider: "hetzner",
+            currency: "EUR".to_string(),
+            daily,
+            resources: servers,
+            note: Some("Estimated from list prices for the current servers.".to_string()),
+        })
+    }
+}
+
+// ── Report ──────────────────────────────────────────────────────────────────
+
+/// Plain-text report, one block per provider.
+fn render_text(summaries: &[ProviderSummary], errors: &[String]) -> String {
+    let mut out = Vec::new();
+    for s in summaries {
+        let yesterday = s.daily.last().map(|(_, c)| *c).unwrap_or(0.0);
+        let mut line = format!(
+            "{}: {:.2} {} yesterday, {:.2} over {} day(s)",
+            s.provider.to_uppercase(),
+            yesterday,
+            s.currency,
+            s.total(),
+            s.daily.len()
+        );
+        // Flag a jump against the average of the earlier days.
+        if s.daily.len() > 2 {
+            let earlier = &s.daily[..s.daily.len() - 1];
+            let average = earlier.iter().map(|(_, c)| c).sum::<f64>() / earlier.len() as f64;
+            if average > 0.0 && yesterday > average * 1.5 {
+                line.push_str(&format!(" ⚠ {:.0}% above the {}-day average", (yesterday / average - 1.0) * 100.0, earlier.len()));
+            }
+        }
+        out.push(line);
+        if !s.resources.is_empty() {
+            let running = s.resources.iter().filter(|r| r["status"] == "running").count();
+            out.push(format!("  {}/{} servers running", running, s.resources.len()));
+            for r in s.resources.iter().filter(|r| r["status"] != "running") {
+                out.push(format!("  - {} is {}", r["name"].as_str().unwrap_or("?"), r["status"].as_str().unwrap_or("?")));
+            }
+        }
+        if let Some(note) = &s.note {
+            out.push(format!("  ({})", note));
+        }
+    }
+    for e in errors {
+        out.push(format!("Error: {}", e));
+    }
+    if out.is_empty() {
+        return "No cloud providers configured. Store credentials in the vault (see the tool description).".to_string();
+    }
+    out.join("\n")
+}
+
+/// Summarise cloud spend and status.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_cloud_status(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("summary");
+    debug!(action, "Executing cloud_status tool");
+    let backends = backends(args);
+
+    match action {
+        "providers" => Ok(json!(backends
+            .iter()
+            .map(|b| json!({ "provider": b.name(), "configured": b.configured() }))
+            .collect::<Vec<_>>())
+        .to_string()),
+        "summary" => {
+            let wanted: Vec<String> = args
+                .get("providers")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_lowercase)).collect())
+                .unwrap_or_default();
+            if let Some(unknown) = wanted.iter().find(|w| !backends.iter().any(|b| b.name() == w.as_str())) {
+                return Err(format!("Unknown provider '{}'; use aws, gcp or hetzner", unknown));
+            }
+            let days = args.get("days").and_then(|v| v.as_u64()).unwrap_or(7).clamp(1, 90) as u32;
+
+            let mut summaries = Vec::new();
+            let mut errors = Vec::new();
+            for backend in &backends {
+                let selected = if wanted.is_empty() {
+                    backend.configured()
+                } else {
+                    wanted.iter().any(|w| w == backend.name())
+                };
+                if !selected {
+                    continue;
+                }
+                match backend.summary(days) {
+                    Ok(summary) => summaries.push(summary),
+                    Err(e) => {
+                        warn!(provider = backend.name(), error = %e, "Cloud summary failed");
+                        errors.push(format!("{}: {}", backend.name(), e));
+                    }
+                }
+            }
+
+            match args.get("format").and_then(|v| v.as_str()).unwrap_or("text") {
+                "json" => Ok(json!({
+                    "providers": summaries.iter().map(ProviderSummary::to_json).collect::<Vec<_>>(),
+                    "errors": errors,
+                })
+                .to_string()),
+                _ => Ok(render_text(&summaries, &errors)),
+            }
+        }
+        other => Err(format!("Unknown action: {}. Use summary or providers", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS SigV4 documentation.
+        let key = sigv4_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex(&key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+
+    #[test]
+    fn test_parse_provider_responses() {
+        let aws = json!({ "ResultsByTime": [
+            { "TimePeriod": { "Start": "2026-10-13" }, "Total": { "UnblendedCost": { "Amount": "1.5", "Unit": "USD" } } },
+            { "TimePeriod": { "Start": "2026-10-14" }, "Total": { "UnblendedCost": { "Amount": "2.25", "Unit": "USD" } } },
+        ]});
+        let (currency, daily) = parse_aws_costs(&aws);
+        assert_eq!(currency, "USD");
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[1].1, 2.25);
+
+        let bq = json!({ "rows": [{ "f": [{ "v": "2026-10-14" }, { "v": "3.1" }, { "v": "EUR" }] }] });
+        let (currency, daily) = parse_bigquery_rows(&bq);
+        assert_eq!(currency, "EUR");
+        assert_eq!(daily, vec![(NaiveDate::from_ymd_opt(2026, 10, 14).unwrap(), 3.1)]);
+
+        let hetzner = json!({ "servers": [{
+            "name": "web1", "status": "off", "created": "2026-01-01T00:00:00+00:00",
+            "datacenter": { "location": { "name": "fsn1" } },
+            "server_type": { "name": "cx22", "prices": [
+                { "location": "nbg1", "price_hourly": { "gross": "0.0100" } },
+                { "location": "fsn1", "price_hourly": { "gross": "0.0080" } },
+            ]},
+        }]});
+        let (servers, hourly) = parse_hetzner_servers(&hetzner);
+        assert_eq!(servers[0]["status"], "off");
+        assert_eq!(hourly, 0.008);
+    }
+
+    #[test]
+    fn test_render_text_flags_spikes() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let summary = ProviderSummary {
+            provider: "aws",
+            currency: "USD".into(),
+            daily: vec![(day(12), 1.0), (day(13), 1.0), (day(14), 3.0)],
+            resources: vec![json!({ "name": "db", "status": "off" })],
+            note: None,
+        };
+        let text = render_text(&[summary], &["gcp: no token".into()]);
+        assert!(text.starts_with("AWS: 3.00 USD yesterday, 5.00 over 3 day(s)"));
+        assert!(text.contains("200% above the 2-day average"));
+        assert!(text.contains("0/1 servers running"));
+        assert!(text.contains("- db is off"));
+        assert!(text.contains("Error: gcp: no token"));
+    }
+}
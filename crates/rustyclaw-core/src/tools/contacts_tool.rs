@@ -0,0 +1,151 @@
+//! The `contacts` tool: look people up and manage the local contact book.
+
+use crate::contacts::{self, Contact};
+use serde_json::Value;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+fn str_list(args: &Value, key: &str) -> Vec<String> {
+    args.get(key)
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+fn name_arg(args: &Value) -> Result<&str, String> {
+    args.get("name")
+        .and_then(|v| v.as_str())
+        .filter(|n| !n.trim().is_empty())
+        .ok_or_else(|| "Missing required parameter: name".to_string())
+}
+
+fn details(contact: &Contact) -> String {
+    let mut lines = vec![contact.name.clone()];
+    if !contact.aliases.is_empty() {
+        lines.push(format!("  aliases: {}", contact.aliases.join(", ")));
+    }
+    for (channel, id) in &contact.identities {
+        lines.push(format!("  {}: {}", channel, id));
+    }
+    if let Some(phone) = &contact.phone {
+        lines.push(format!("  phone: {}", phone));
+    }
+    if let Some(email) = &contact.email {
+        lines.push(format!("  email: {}", email));
+    }
+    if let Some(notes) = &contact.notes {
+        lines.push(format!("  notes: {}", notes));
+    }
+    lines.join("\n")
+}
+
+/// Look up, resolve and edit contacts.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_contacts(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    debug!(action, "Executing contacts tool");
+
+    match action {
+        "list" | "search" => {
+            let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+            if action == "search" && query.is_empty() {
+                return Err("Missing query for search".to_string());
+            }
+            let found: Vec<String> = contacts::all()
+                .iter()
+                .filter(|c| query.is_empty() || c.mentions(query))
+                .map(Contact::summary)
+                .collect();
+            if found.is_empty() {
+                return Ok("No contacts found.".to_string());
+            }
+            Ok(format!("{} contact(s):\n  {}", found.len(), found.join("\n  ")))
+        }
+        "get" => {
+            let name = name_arg(args)?;
+            match contacts::find(name)? {
+                Some(contact) => Ok(details(&contact)),
+                None => Err(format!("No contact named '{}'", name)),
+            }
+        }
+        "resolve" => {
+            let name = name_arg(args)?;
+            let channel = args.get("channel").and_then(|v| v.as_str()).unwrap_or("auto");
+            if contacts::find(name)?.is_none() {
+                return Err(format!("No contact named '{}'", name));
+            }
+            let (id, channel) = contacts::route(name, channel)?;
+            Ok(format!("{} → {} {}", name, channel, id))
+        }
+        "add" => {
+            let name = name_arg(args)?.trim().to_string();
+            let file = contacts::local_file();
+            let mut local = contacts::load_local(&file)?;
+            let existing = local.iter().position(|c| c.name.eq_ignore_ascii_case(&name));
+            let mut contact = existing
+                .map(|i| local[i].clone())
+                .unwrap_or_else(|| Contact { name: name.clone(), ..Default::default() });
+
+            // Merge, so adding a Telegram id keeps the other fields.
+            for alias in str_list(args, "aliases") {
+                if !contact.aliases.contains(&alias) {
+                    contact.aliases.push(alias);
+                }
+            }
+            if let Some(ids) = args.get("identities").and_then(|v| v.as_object()) {
+                for (channel, id) in ids {
+                    let channel = channel.to_lowercase();
+                    match id.as_str().map(str::trim) {
+                        Some("") | None => contact.identities.remove(&channel),
+                        Some(id) => contact.identities.insert(channel, id.to_string()),
+                    };
+                }
+            }
+            for (key, field) in [
+                ("phone", &mut contact.phone),
+                ("email", &mut contact.email),
+                ("notes", &mut contact.notes),
+            ] {
+                if let Some(value) = args.get(key).and_then(|v| v.as_str()) {
+                    *field = Some(value.to_string()).filter(|v| !v.trim().is_empty());
+                }
+            }
+
+            let verb = match existing {
+                Some(i) => {
+                    local[i] = contact.clone();
+                    "Updated"
+                }
+                None => {
+                    local.push(contact.clone());
+                    "Added"
+                }
+            };
+            contacts::save_local(&file, &local)?;
+            Ok(format!("{} contact in {}:\n{}", verb, file.display(), details(&contact)))
+        }
+        "remove" => {
+            let name = name_arg(args)?;
+            let file = contacts::local_file();
+            let mut local = contacts::load_local(&file)?;
+            let before = local.len();
+            local.retain(|c| !c.name.eq_ignore_ascii_case(name.trim()));
+            if local.len() == before {
+                return Err(format!(
+                    "No contact named '{}' in {} (contacts from config or CardDAV can't be removed here)",
+                    name,
+                    file.display()
+                ));
+            }
+            contacts::save_local(&file, &local)?;
+            Ok(format!("Removed {} from {}", name, file.display()))
+        }
+        other => Err(format!(
+            "Unknown action: {}. Use list, search, get, resolve, add or remove",
+            other
+        )),
+    }
+}
@@ -0,0 +1,132 @@
+//! Conversations tool: browse, clear and export persisted messenger chats.
+
+use super::helpers::{check_write, resolve_path};
+use serde_json::Value;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Default number of messages shown by `show`.
+const DEFAULT_SHOW_LIMIT: usize = 20;
+
+/// Persisted messenger conversation management.
+#[instrument(skip(args, workspace_dir), fields(action))]
+pub fn exec_conversations(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    use crate::conversations::*;
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+
+    tracing::Span::current().record("action", action);
+    debug!("Executing conversations tool");
+
+    let store = ConversationStore::new(&conversations_dir(workspace_dir))?;
+    let messenger = args.get("messenger").and_then(|v| v.as_str());
+    let chat = args.get("chat").and_then(|v| v.as_str());
+    let require_messenger =
+        || messenger.ok_or_else(|| "Missing required parameter: messenger".to_string());
+    let require_chat = || chat.ok_or_else(|| "Missing required parameter: chat".to_string());
+
+    match action {
+        "list" => {
+            let list = store.list(messenger)?;
+            if list.is_empty() {
+                return Ok("No stored conversations.".to_string());
+            }
+            let mut output = format!("{} conversation(s):\n\n", list.len());
+            for c in list {
+                output.push_str(&format!(
+                    "{}:{} — {} message(s), last active {}\n",
+                    c.messenger,
+                    c.chat,
+                    c.messages,
+                    format_time(c.updated_ms)
+                ));
+                if !c.preview.is_empty() {
+                    output.push_str(&format!("    {}\n", c.preview));
+                }
+            }
+            Ok(output)
+        }
+
+        "show" => {
+            let (messenger, chat) = (require_messenger()?, require_chat()?);
+            let conversation = store
+                .load(messenger, chat)?
+                .ok_or_else(|| format!("No conversation stored for {}:{}", messenger, chat))?;
+            let limit = args
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(DEFAULT_SHOW_LIMIT);
+            let visible: Vec<_> = conversation.visible().collect();
+            let skip = visible.len().saturating_sub(limit);
+            let mut output = format!(
+                "{} — {} message(s), started {}, last active {}\n",
+                conversation.key(),
+                visible.len(),
+                format_time(conversation.started_ms),
+                format_time(conversation.updated_ms)
+            );
+            if skip > 0 {
+                output.push_str(&format!("(showing the last {})\n", limit));
+            }
+            for message in &visible[skip..] {
+                output.push_str(&format!("\n[{}] {}\n", message.role, message.display_content()));
+            }
+            Ok(output)
+        }
+
+        "clear" => {
+            let messenger = require_messenger()?;
+            let removed = store.clear(messenger, chat)?;
+            Ok(match (removed, chat) {
+                (0, Some(chat)) => format!("No conversation stored for {}:{}", messenger, chat),
+                (_, Some(chat)) => format!("Cleared conversation {}:{}", messenger, chat),
+                (n, None) => format!("Cleared {} {} conversation(s)", n, messenger),
+            })
+        }
+
+        "export" => {
+            let output = args.get("output").and_then(|v| v.as_str());
+            match chat {
+                Some(chat) => {
+                    let messenger = require_messenger()?;
+                    let path = match output {
+                        Some(p) => resolve_path(workspace_dir, p),
+                        None => workspace_dir
+                            .join("exports")
+                            .join("conversations")
+                            .join(ConversationStore::export_name(messenger, chat)),
+                    };
+                    check_write(&path, workspace_dir)?;
+                    let count = store.export(messenger, chat, &path)?;
+                    Ok(format!("Exported {} message(s) to {}", count, path.display()))
+                }
+                None => {
+                    // Archive every matching chat into a directory.
+                    let dir = match output {
+                        Some(p) => resolve_path(workspace_dir, p),
+                        None => workspace_dir.join("exports").join("conversations"),
+                    };
+                    let all = store.all(messenger)?;
+                    if all.is_empty() {
+                        return Ok("No stored conversations to export.".to_string());
+                    }
+                    for c in &all {
+                        let path = dir.join(ConversationStore::export_name(&c.messenger, &c.chat));
+                        check_write(&path, workspace_dir)?;
+                        store.export(&c.messenger, &c.chat, &path)?;
+                    }
+                    Ok(format!("Exported {} conversation(s) to {}", all.len(), dir.display()))
+                }
+            }
+        }
+
+        _ => Err(format!(
+            "Unknown action: {}. Valid: list, show, clear, export",
+            action
+        )),
+    }
+}
@@ -1,9 +1,21 @@
 //! Cron tool: scheduled job management.
 
+use super::helpers::{check_write, resolve_path};
 use serde_json::Value;
 use std::path::Path;
 use tracing::{debug, warn, instrument};
 
+/// Accept `"schedule": "every 10 minutes"` (or a cron expression or
+/// date/time) in place of a schedule object, read in the object's `tz`.
+fn with_text_schedule(mut obj: Value) -> Result<Value, String> {
+    if let Some(text) = obj.get("schedule").and_then(|v| v.as_str()) {
+        let tz = obj.get("tz").and_then(|v| v.as_str()).map(String::from);
+        let schedule = crate::cron::Schedule::parse(text, tz)?;
+        obj["schedule"] = serde_json::to_value(schedule).map_err(|e| e.to_string())?;
+    }
+    Ok(obj)
+}
+
 /// Cron job management.
 #[instrument(skip(args, workspace_dir), fields(action))]
 pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
@@ -25,10 +37,22 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
             let jobs = store.list(false);
             let enabled_count = jobs.len();
             let all_count = store.list(true).len();
+            let next = jobs
+                .iter()
+                .filter_map(|j| j.next_run_ms.map(|ms| (ms, j)))
+                .min_by_key(|(ms, _)| *ms)
+                .map(|(ms, j)| {
+                    format!(
+                        "{} ({})",
+                        format_ms(ms, j.schedule.tz()),
+                        j.name.as_deref().unwrap_or(&j.job_id)
+                    )
+                })
+                .unwrap_or_else(|| "none".to_string());
             debug!(enabled = enabled_count, total = all_count, "Cron status");
             Ok(format!(
-                "Cron scheduler status:\n- Enabled jobs: {}\n- Total jobs: {}\n- Store: {:?}",
-                enabled_count, all_count, cron_dir
+                "Cron scheduler status:\n- Enabled jobs: {}\n- Total jobs: {}\n- Next run: {}\n- Store: {:?}",
+                enabled_count, all_count, next, cron_dir
             ))
         }
 
@@ -49,7 +73,11 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
                 let status = if job.enabled { "✓" } else { "○" };
                 let name = job.name.as_deref().unwrap_or("(unnamed)");
                 let schedule = match &job.schedule {
-                    Schedule::At { at } => format!("at {}", at),
+                    Schedule::At { at, tz } => format!(
+                        "at {}{}",
+                        at,
+                        tz.as_ref().map(|t| format!(" ({})", t)).unwrap_or_default()
+                    ),
                     Schedule::Every { every_ms, .. } => format!("every {}ms", every_ms),
                     Schedule::Cron { expr, tz } => {
                         format!(
@@ -61,9 +89,18 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
                         )
                     }
                 };
+                let next = job
+                    .next_run_ms
+                    .map(|ms| format!(" — next {}", format_ms(ms, job.schedule.tz())))
+                    .unwrap_or_default();
+                let awaiting = job
+                    .awaiting_reply
+                    .as_ref()
+                    .map(|d| format!(" — awaiting reply on {}", d.channel))
+                    .unwrap_or_default();
                 output.push_str(&format!(
-                    "{} {} [{}] — {}\n",
-                    status, job.job_id, name, schedule
+                    "{} {} [{}] — {}{}{}\n",
+                    status, job.job_id, name, schedule, next, awaiting
                 ));
             }
             Ok(output)
@@ -72,12 +109,20 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
         "add" => {
             let job_obj = args.get("job").ok_or("Missing required parameter: job")?;
 
-            let job: CronJob = serde_json::from_value(job_obj.clone())
+            let job: CronJob = serde_json::from_value(with_text_schedule(job_obj.clone())?)
                 .map_err(|e| format!("Invalid job definition: {}", e))?;
 
+            // Catch bad expressions and times already in the past before
+            // saving a job that would never fire.
+            let tz = job.schedule.tz();
+            let now = format_ms(now_ms(), tz);
+            let Some(next) = job.schedule.next_run(now_ms())? else {
+                return Err(format!("That schedule never fires after now ({}); check the date and tz", now));
+            };
+
             let id = store.add(job)?;
-            debug!(job_id = %id, "Created cron job");
-            Ok(format!("Created job: {}", id))
+            debug!(job_id = %id, next, "Created cron job");
+            Ok(format!("Created job: {} (first run {})", id, format_ms(next, tz)))
         }
 
         "update" => {
@@ -88,7 +133,7 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
 
             let patch_obj = args.get("patch").ok_or("Missing patch for update")?;
 
-            let patch: CronJobPatch = serde_json::from_value(patch_obj.clone())
+            let patch: CronJobPatch = serde_json::from_value(with_text_schedule(patch_obj.clone())?)
                 .map_err(|e| format!("Invalid patch: {}", e))?;
 
             store.update(job_id, patch)?;
@@ -118,10 +163,12 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
                 .ok_or_else(|| format!("Job not found: {}", job_id))?;
 
             debug!(job_id, "Manual run requested");
+            let name = job.name.clone().unwrap_or_else(|| "unnamed".to_string());
+            store.trigger(job_id)?;
             Ok(format!(
-                "Would run job '{}' ({}). Note: actual execution requires gateway integration.",
-                job.name.as_deref().unwrap_or("unnamed"),
-                job_id
+                "Queued job '{}' ({}). The gateway's scheduler runs it within a few seconds; \
+                 check the outcome with action 'runs'.",
+                name, job_id
             ))
         }
 
@@ -137,6 +184,7 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
                 return Ok(format!("No run history for job: {}", job_id));
             }
 
+            let tz = store.get(job_id).map(|j| j.schedule.tz()).unwrap_or_else(local_tz);
             let mut output = format!("Run history for {}:\n\n", job_id);
             for run in runs {
                 let status = match run.status {
@@ -145,16 +193,80 @@ pub fn exec_cron(args: &Value, workspace_dir: &Path) -> Result<String, String> {
                     RunStatus::Running => "⟳",
                     RunStatus::Timeout => "⏱",
                     RunStatus::Skipped => "○",
+                    RunStatus::Snoozed => "💤",
+                    RunStatus::Acknowledged => "☑",
                 };
-                output.push_str(&format!("{} {} — {:?}\n", status, run.run_id, run.status));
+                let took = run
+                    .finished_ms
+                    .map(|f| format!(" in {}s", f.saturating_sub(run.started_ms) / 1000))
+                    .unwrap_or_default();
+                let note = run.error.or(run.note).map(|n| format!(" ({})", n)).unwrap_or_default();
+                output.push_str(&format!(
+                    "{} {} {} — {:?}{}{}\n",
+                    status,
+                    format_ms(run.started_ms, tz),
+                    run.run_id,
+                    run.status,
+                    took,
+                    note
+                ));
             }
             Ok(output)
         }
 
+        "snooze" => {
+            let job_id = args
+                .get("jobId")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing jobId for snooze")?;
+            let delay = match args.get("duration").and_then(|v| v.as_str()) {
+                Some(d) => parse_duration_ms(d)
+                    .ok_or_else(|| format!("Can't read duration '{}'; use e.g. '10m', '1h' or '1d'", d))?,
+                None => DEFAULT_SNOOZE_MS,
+            };
+            let until = store.snooze(job_id, delay, None)?;
+            let tz = store.get(job_id).map(|j| j.schedule.tz()).unwrap_or_else(local_tz);
+            debug!(job_id, until, "Snoozed cron job");
+            Ok(format!("Snoozed job {} until {}", job_id, format_ms(until, tz)))
+        }
+
+        "ack" => {
+            let job_id = args
+                .get("jobId")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing jobId for ack")?;
+            store.acknowledge(job_id, None)?;
+            debug!(job_id, "Acknowledged cron job");
+            Ok(format!("Marked job {} done", job_id))
+        }
+
+        "export" => {
+            let path = match args.get("output").and_then(|v| v.as_str()) {
+                Some(p) => resolve_path(workspace_dir, p),
+                None => workspace_dir.join("exports").join("schedule.ics"),
+            };
+            check_write(&path, workspace_dir)?;
+            let jobs = store.list(false);
+            let ics = to_ical(&jobs, now_ms());
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            std::fs::write(&path, ics)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            debug!(count = jobs.len(), path = %path.display(), "Exported cron jobs as iCal");
+            Ok(format!(
+                "Exported {} job(s) to {}. The gateway also serves this feed at /calendar.ics \
+                 on its health server for calendar subscriptions.",
+                jobs.len(),
+                path.display()
+            ))
+        }
+
         _ => {
             warn!(action, "Unknown cron action");
             Err(format!(
-                "Unknown action: {}. Valid: status, list, add, update, remove, run, runs",
+                "Unknown action: {}. Valid: status, list, add, update, remove, run, runs, snooze, ack, export",
                 action
             ))
         }
@@ -0,0 +1,422 @@
+//! Dependency management: add, remove and update packages with the
+//! project's own package manager (cargo, npm, pip/uv), list outdated
+//! packages, and check locked versions against the OSV advisory database.
+//!
+//! Mutating actions report a package-level diff of the lockfile so the
+//! model can see exactly which versions moved.
+
+use super::helpers::{is_protected_path, resolve_path, run_sandboxed_command, VAULT_ACCESS_DENIED};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+/// OSV batch endpoint (accepts up to 1000 queries per request).
+const OSV_BATCH_URL: &str = "https://api.osv.dev/v1/querybatch";
+const OSV_BATCH_SIZE: usize = 1000;
+
+/// A package ecosystem the tool knows how to drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Pip,
+}
+
+impl Ecosystem {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cargo" | "rust" => Some(Self::Cargo),
+            "npm" | "node" => Some(Self::Npm),
+            "pip" | "python" | "uv" => Some(Self::Pip),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+            Self::Pip => "pip",
+        }
+    }
+
+    /// Ecosystem name used by the OSV database.
+    fn osv_name(&self) -> &'static str {
+        match self {
+            Self::Cargo => "crates.io",
+            Self::Npm => "npm",
+            Self::Pip => "PyPI",
+        }
+    }
+
+    /// Detect the ecosystem from the manifest in `dir`.
+    fn detect(dir: &Path) -> Option<Self> {
+        if dir.join("Cargo.toml").is_file() {
+            Some(Self::Cargo)
+        } else if dir.join("package.json").is_file() {
+            Some(Self::Npm)
+        } else if ["pyproject.toml", "requirements.txt", "setup.py"]
+            .iter()
+            .any(|f| dir.join(f).is_file())
+        {
+            Some(Self::Pip)
+        } else {
+            None
+        }
+    }
+
+    /// Lockfile recording resolved versions, if the project has one.
+    fn lockfile(&self, dir: &Path) -> Option<PathBuf> {
+        let candidates: &[&str] = match self {
+            Self::Cargo => &["Cargo.lock"],
+            Self::Npm => &["package-lock.json"],
+            Self::Pip => &["uv.lock", "poetry.lock", "requirements.txt"],
+        };
+        candidates.iter().map(|f| dir.join(f)).find(|p| p.is_file())
+    }
+}
+
+// ── Lockfiles ───────────────────────────────────────────────────────────────
+
+/// Resolved `name → versions` from a lockfile.  A name can map to several
+/// versions (Cargo and npm both allow duplicates).
+pub type Locked = BTreeMap<String, Vec<String>>;
+
+/// Parse any supported lockfile by its file name.
+pub fn parse_lockfile(name: &str, text: &str) -> Locked {
+    let mut locked = Locked::new();
+    let mut add = |n: &str, v: &str| {
+        let versions = locked.entry(n.to_string()).or_default();
+        if !versions.iter().any(|x| x == v) {
+            versions.push(v.to_string());
+            versions.sort();
+        }
+    };
+    match name {
+        // Cargo.lock, uv.lock and poetry.lock share the [[package]] layout.
+        "Cargo.lock" | "uv.lock" | "poetry.lock" => {
+            if let Ok(doc) = toml::from_str::<toml::Value>(text) {
+                for pkg in doc.get("package").and_then(|p| p.as_array()).into_iter().flatten() {
+                    if let (Some(n), Some(v)) = (
+                        pkg.get("name").and_then(|v| v.as_str()),
+                        pkg.get("version").and_then(|v| v.as_str()),
+                    ) {
+                        add(n, v);
+                    }
+                }
+            }
+        }
+        "package-lock.json" => {
+            if let Ok(doc) = serde_json::from_str::<Value>(text) {
+                for (key, pkg) in doc["packages"].as_object().into_iter().flatten() {
+                    // Keys look like "node_modules/a/node_modules/b"; "" is the root.
+                    let Some(n) = key.rsplit("node_modules/").next().filter(|n| !n.is_empty()) else {
+                        continue;
+                    };
+                    if let Some(v) = pkg["version"].as_str() {
+                        add(n, v);
+                    }
+                }
+            }
+        }
+        "requirements.txt" => {
+            for line in text.lines() {
+                let line = line.split('#').next().unwrap_or("").trim();
+                if let Some((n, v)) = line.split_once("==") {
+                    let n = n.split('[').next().unwrap_or(n).trim();
+                    add(n, v.split(';').next().unwrap_or(v).trim());
+                }
+            }
+        }
+        _ => {}
+    }
+    locked
+}
+
+fn read_lockfile(path: &Path) -> Locked {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    std::fs::read_to_string(path)
+        .map(|text| parse_lockfile(name, &text))
+        .unwrap_or_default()
+}
+
+/// Package-level diff between two lockfile states, one change per line:
+/// `+ name 1.0`, `- name 1.0`, `~ name 1.0 -> 1.1`.
+pub fn lock_diff(before: &Locked, after: &Locked) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (name, old) in before {
+        match after.get(name) {
+            None => lines.push(format!("- {} {}", name, old.join(", "))),
+            Some(new) if new != old => {
+                lines.push(format!("~ {} {} -> {}", name, old.join(", "), new.join(", ")))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, new) in after {
+        if !before.contains_key(name) {
+            lines.push(format!("+ {} {}", name, new.join(", ")));
+        }
+    }
+    lines
+}
+
+// ── Commands ────────────────────────────────────────────────────────────────
+
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Package names/specs from `packages` (array) or `package` (string).
+fn packages_arg(args: &Value) -> Vec<String> {
+    let mut pkgs: Vec<String> = args
+        .get("packages")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    if let Some(p) = args.get("package").and_then(|v| v.as_str()) {
+        pkgs.push(p.to_string());
+    }
+    pkgs.retain(|p| !p.trim().is_empty());
+    pkgs
+}
+
+/// Shell command for `action` in `eco`, or `None` if unsupported.
+pub fn command_for(eco: Ecosystem, action: &str, packages: &[String], dev: bool, uv: bool) -> Option<String> {
+    let pkgs = packages.iter().map(|p| quote(p)).collect::<Vec<_>>().join(" ");
+    let cmd = match (eco, action) {
+        (Ecosystem::Cargo, "list") => "cargo tree --depth 1".to_string(),
+        (Ecosystem::Cargo, "add") => format!("cargo add{} {}", if dev { " --dev" } else { "" }, pkgs),
+        (Ecosystem::Cargo, "remove") => format!("cargo remove{} {}", if dev { " --dev" } else { "" }, pkgs),
+        (Ecosystem::Cargo, "update") if packages.is_empty() => "cargo update".to_string(),
+        (Ecosystem::Cargo, "update") => format!(
+            "cargo update {}",
+            packages.iter().map(|p| format!("-p {}", quote(p))).collect::<Vec<_>>().join(" ")
+        ),
+        // Without cargo-outdated, a dry-run update shows what would move.
+        (Ecosystem::Cargo, "outdated") => "cargo update --dry-run".to_string(),
+
+        (Ecosystem::Npm, "list") => "npm ls --depth=0".to_string(),
+        (Ecosystem::Npm, "add") => format!("npm install{} {}", if dev { " --save-dev" } else { "" }, pkgs),
+        (Ecosystem::Npm, "remove") => format!("npm uninstall {}", pkgs),
+        (Ecosystem::Npm, "update") => format!("npm update {}", pkgs).trim_end().to_string(),
+        (Ecosystem::Npm, "outdated") => "npm outdated".to_string(),
+
+        (Ecosystem::Pip, "list") if uv => "uv tree --depth 1".to_string(),
+        (Ecosystem::Pip, "add") if uv => format!("uv add{} {}", if dev { " --dev" } else { "" }, pkgs),
+        (Ecosystem::Pip, "remove") if uv => format!("uv remove{} {}", if dev { " --dev" } else { "" }, pkgs),
+        (Ecosystem::Pip, "update") if uv && packages.is_empty() => "uv lock --upgrade".to_string(),
+        (Ecosystem::Pip, "update") if uv => format!(
+            "uv lock {}",
+            packages.iter().map(|p| format!("--upgrade-package {}", quote(p))).collect::<Vec<_>>().join(" ")
+        ),
+        (Ecosystem::Pip, "list") => "python -m pip list".to_string(),
+        (Ecosystem::Pip, "add") => format!("python -m pip install {}", pkgs),
+        (Ecosystem::Pip, "remove") => format!("python -m pip uninstall -y {}", pkgs),
+        (Ecosystem::Pip, "update") if !packages.is_empty() => format!("python -m pip install -U {}", pkgs),
+        (Ecosystem::Pip, "outdated") => "python -m pip list --outdated".to_string(),
+        _ => return None,
+    };
+    Some(cmd)
+}
+
+/// Run `command` in `dir` inside the project's dev environment and return
+/// combined output, failing on a non-zero exit.
+fn run(command: &str, dir: &Path, workspace_dir: &Path, allow_failure: bool) -> Result<String, String> {
+    let command = crate::dev_env::activate(workspace_dir, command);
+    debug!(command = %command, dir = %dir.display(), "Running dependency command");
+    let output = run_sandboxed_command(&command, dir)?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    // `npm outdated` exits 1 when anything is outdated.
+    if !output.status.success() && !(allow_failure && !stdout.is_empty()) {
+        return Err(if stderr.is_empty() {
+            format!("Command exited with {}", output.status)
+        } else {
+            stderr
+        });
+    }
+    Ok(match (stdout.is_empty(), stderr.is_empty()) {
+        (false, false) => format!("{}\n{}", stdout, stderr),
+        (false, true) => stdout,
+        _ => stderr,
+    })
+}
+
+// ── Advisories ──────────────────────────────────────────────────────────────
+
+/// Look up every locked package in the OSV database and return
+/// `(name, version, advisory ids)` for the vulnerable ones.
+fn osv_audit(eco: Ecosystem, locked: &Locked) -> Result<Vec<(String, String, Vec<String>)>, String> {
+    let queries: Vec<(String, String)> = locked
+        .iter()
+        .flat_map(|(n, vs)| vs.iter().map(move |v| (n.clone(), v.clone())))
+        .collect();
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent("RustyClaw/0.1 (deps audit)")
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let mut findings = Vec::new();
+    for chunk in queries.chunks(OSV_BATCH_SIZE) {
+        let body = json!({
+            "queries": chunk.iter().map(|(n, v)| json!({
+                "package": { "name": n, "ecosystem": eco.osv_name() },
+                "version": v,
+            })).collect::<Vec<_>>()
+        });
+        let response: Value = client
+            .post(OSV_BATCH_URL)
+            .json(&body)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("OSV lookup failed: {}", e))?
+            .json()
+            .map_err(|e| format!("OSV returned invalid JSON: {}", e))?;
+        let results = response["results"].as_array().cloned().unwrap_or_default();
+        for ((name, version), result) in chunk.iter().zip(results) {
+            let ids: Vec<String> = result["vulns"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v["id"].as_str().map(String::from))
+                .collect();
+            if !ids.is_empty() {
+                findings.push((name.clone(), version.clone(), ids));
+            }
+        }
+    }
+    Ok(findings)
+}
+
+// ── Tool ────────────────────────────────────────────────────────────────────
+
+/// Query and modify project dependencies.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_deps(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    let dir = match args.get("path").and_then(|v| v.as_str()) {
+        Some(p) => resolve_path(workspace_dir, p),
+        None => workspace_dir.to_path_buf(),
+    };
+    if is_protected_path(&dir) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    if !dir.is_dir() {
+        return Err(format!("Directory not found: {}", dir.display()));
+    }
+    let eco = match args.get("ecosystem").and_then(|v| v.as_str()) {
+        Some(e) => Ecosystem::parse(e)
+            .ok_or_else(|| format!("Unknown ecosystem: {}. Use cargo, npm or pip", e))?,
+        None => Ecosystem::detect(&dir)
+            .ok_or("No Cargo.toml, package.json or Python project found; pass ecosystem")?,
+    };
+    let packages = packages_arg(args);
+    let dev = args.get("dev").and_then(|v| v.as_bool()).unwrap_or(false);
+    let lockfile = eco.lockfile(&dir);
+
+    match action {
+        "audit" => {
+            let lockfile = lockfile.ok_or_else(|| {
+                format!("No {} lockfile found; resolve dependencies first", eco.label())
+            })?;
+            let locked = read_lockfile(&lockfile);
+            let total: usize = locked.values().map(Vec::len).sum();
+            let findings = osv_audit(eco, &locked)?;
+            if findings.is_empty() {
+                return Ok(format!("No known vulnerabilities in {} locked {} packages.", total, eco.label()));
+            }
+            let lines: Vec<String> = findings
+                .iter()
+                .map(|(n, v, ids)| format!("  {} {}: {}", n, v, ids.join(", ")))
+                .collect();
+            Ok(format!(
+                "{} of {} locked packages have known advisories (details: https://osv.dev/vulnerability/<id>):\n{}",
+                findings.len(),
+                total,
+                lines.join("\n")
+            ))
+        }
+        "list" | "outdated" => {
+            let cmd = command_for(eco, action, &packages, dev, lockfile_is_uv(&lockfile))
+                .ok_or_else(|| format!("'{}' is not supported for {}", action, eco.label()))?;
+            run(&cmd, &dir, workspace_dir, true)
+        }
+        "add" | "remove" | "update" => {
+            if packages.is_empty() && action != "update" {
+                return Err(format!("'{}' needs packages", action));
+            }
+            let cmd = command_for(eco, action, &packages, dev, lockfile_is_uv(&lockfile))
+                .ok_or_else(|| format!("'{}' without packages is not supported for {}", action, eco.label()))?;
+            let before = lockfile.as_deref().map(read_lockfile).unwrap_or_default();
+            let output = run(&cmd, &dir, workspace_dir, false)?;
+
+            // The lockfile may have been created by this command.
+            let after = eco.lockfile(&dir).as_deref().map(read_lockfile).unwrap_or_default();
+            let diff = lock_diff(&before, &after);
+            let summary = if diff.is_empty() {
+                "Lockfile unchanged.".to_string()
+            } else {
+                format!("Lockfile changes ({}):\n{}", diff.len(), diff.join("\n"))
+            };
+            Ok(format!("$ {}\n{}\n\n{}", cmd, output, summary))
+        }
+        other => Err(format!(
+            "Unknown action: {}. Use list, add, remove, update, outdated or audit",
+            other
+        )),
+    }
+}
+
+fn lockfile_is_uv(lockfile: &Option<PathBuf>) -> bool {
+    lockfile
+        .as_deref()
+        .and_then(|p| p.file_name())
+        .is_some_and(|n| n == "uv.lock")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lockfiles() {
+        let cargo = "version = 4\n[[package]]\nname = \"a\"\nversion = \"1.0.0\"\n\n[[package]]\nname = \"a\"\nversion = \"2.0.0\"\n";
+        assert_eq!(parse_lockfile("Cargo.lock", cargo)["a"], vec!["1.0.0", "2.0.0"]);
+
+        let npm = r#"{"packages":{"":{"version":"0.1.0"},"node_modules/x":{"version":"1.2.3"},"node_modules/x/node_modules/@s/y":{"version":"4.0.0"}}}"#;
+        let locked = parse_lockfile("package-lock.json", npm);
+        assert_eq!(locked["x"], vec!["1.2.3"]);
+        assert_eq!(locked["@s/y"], vec!["4.0.0"]);
+        assert_eq!(locked.len(), 2);
+
+        let req = "requests[socks]==2.31.0 ; python_version > '3'\n# comment\nflask>=2\n";
+        let locked = parse_lockfile("requirements.txt", req);
+        assert_eq!(locked["requests"], vec!["2.31.0"]);
+        assert!(!locked.contains_key("flask"));
+    }
+
+    #[test]
+    fn test_lock_diff() {
+        let before = parse_lockfile("requirements.txt", "a==1\nb==1\n");
+        let after = parse_lockfile("requirements.txt", "a==2\nc==1\n");
+        assert_eq!(lock_diff(&before, &after), vec!["~ a 1 -> 2", "- b 1", "+ c 1"]);
+        assert!(lock_diff(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_command_for() {
+        let pkgs = vec!["serde".to_string()];
+        assert_eq!(command_for(Ecosystem::Cargo, "add", &pkgs, true, false).unwrap(), "cargo add --dev 'serde'");
+        assert_eq!(command_for(Ecosystem::Cargo, "update", &pkgs, false, false).unwrap(), "cargo update -p 'serde'");
+        assert_eq!(command_for(Ecosystem::Pip, "add", &pkgs, false, true).unwrap(), "uv add 'serde'");
+        assert!(command_for(Ecosystem::Pip, "update", &[], false, false).is_none());
+        assert_eq!(command_for(Ecosystem::Npm, "update", &[], false, false).unwrap(), "npm update");
+    }
+}
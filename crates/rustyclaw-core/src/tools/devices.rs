@@ -6,6 +6,21 @@
 //! - VNC: For graphical remote access (requires vncdo or tigervnc)
 //! - RDP: For Windows remote desktop (requires xfreerdp or rdesktop)
 //!
+//! `discover` scans the LAN over mDNS and SSDP (see `tools::discovery`) and
+//! lists candidate devices with ready-to-use node ids; the latest results
+//! are what `pending` reports.
+//!
+//! `pair` pairs an Android 11+ device over Wi-Fi: it shows a QR code for
+//! Developer options → Wireless debugging → "Pair device with QR code",
+//! waits for the phone to advertise itself over mDNS, and runs `adb pair`.
+//!
+//! Power actions (`wake`, `sleep`, `reboot`) use Wake-on-LAN magic packets,
+//! `systemctl` over SSH, and `adb`.  They are gated separately from the
+//! rest of the tool by the permission system (see `tools::permission_for`).
+//!
+//! SSH connections go through the session agent when vault keys have been
+//! loaded with the `ssh_key` tool (see `secrets::ssh_agent`).
+//!
 //! The canvas tool opens URLs in the system browser and captures page content.
 
 use serde_json::{json, Value};
@@ -13,6 +28,14 @@ use std::path::Path;
 use std::process::{Command, Stdio};
 use tracing::{debug, warn, instrument};
 
+use super::{discovery, qr_tool};
+
+/// Candidates from the most recent `discover`, shown by `pending`.
+static DISCOVERED: std::sync::Mutex<Vec<Value>> = std::sync::Mutex::new(Vec::new());
+
+/// State of the latest ADB QR pairing, shown by `pending`.
+static PAIRING: std::sync::Mutex<Option<Value>> = std::sync::Mutex::new(None);
+
 /// Discover and control paired nodes via SSH, ADB, VNC, or RDP.
 ///
 /// Supports four transport types:
@@ -91,11 +114,38 @@ pub fn exec_nodes(args: &Value, _workspace_dir: &Path) -> Result<String, String>
                 .ok_or("Missing 'key' for key action")?;
             node_send_key(&node, key)
         }
-        // Pairing actions - not applicable for SSH/ADB/VNC/RDP model
-        "pending" => Ok(json!({
-            "pending": [],
-            "note": "Direct connection nodes don't require pairing. Use 'status' to list available devices."
-        }).to_string()),
+        "wake" => {
+            let node = args.get("node").and_then(|v| v.as_str()).unwrap_or("");
+            let mac = args.get("mac").and_then(|v| v.as_str());
+            let broadcast = args.get("broadcast").and_then(|v| v.as_str()).unwrap_or("255.255.255.255");
+            node_wake(node, mac, broadcast)
+        }
+        "sleep" | "reboot" => {
+            let node = get_node(args)?;
+            node_power(&node, action)
+        }
+        "discover" => {
+            let timeout = args.get("timeoutMs").and_then(|v| v.as_u64()).unwrap_or(3000).clamp(500, 15_000);
+            node_discover(std::time::Duration::from_millis(timeout))
+        }
+        "pair" => {
+            let timeout = args.get("timeoutMs").and_then(|v| v.as_u64()).unwrap_or(120_000).clamp(10_000, 600_000);
+            adb_qr_pair(std::time::Duration::from_millis(timeout))
+        }
+        // Direct connections need no pairing; pending lists what discover found.
+        "pending" => {
+            let found = DISCOVERED.lock().map(|d| d.clone()).unwrap_or_default();
+            let note = if found.is_empty() {
+                "Nothing discovered yet. Run 'discover' to scan the local network."
+            } else {
+                "Devices from the last 'discover'. Pass one of a device's 'nodes' ids to describe or run."
+            };
+            let mut result = json!({ "pending": found, "note": note });
+            if let Some(pairing) = PAIRING.lock().ok().and_then(|p| p.clone()) {
+                result["pairing"] = pairing;
+            }
+            Ok(result.to_string())
+        }
         "approve" | "reject" => Ok("Direct connection nodes don't require pairing approval.".to_string()),
         "invoke" => {
             // Map invoke to run for compatibility
@@ -105,7 +155,7 @@ pub fn exec_nodes(args: &Value, _workspace_dir: &Path) -> Result<String, String>
             node_run(&node, &[cmd])
         }
         _ => Err(format!(
-            "Unknown action: {}. Valid: status, describe, run, screen_snap, camera_snap, camera_list, screen_record, location_get, notify, click, type, key, invoke",
+            "Unknown action: {}. Valid: status, describe, run, screen_snap, camera_snap, camera_list, screen_record, location_get, notify, click, type, key, invoke, discover, pair, pending, wake, sleep, reboot",
             action
         )),
     }
@@ -141,6 +191,13 @@ enum NodeType {
     Rdp { user: Option<String>, host: String, port: u16 },
 }
 
+/// An `ssh` command using the session agent, if vault keys are loaded.
+fn ssh_command() -> Command {
+    let mut cmd = Command::new("ssh");
+    crate::secrets::ssh_agent::apply(&mut cmd);
+    cmd
+}
+
 fn parse_node(node: &str) -> NodeType {
     // Check for explicit prefix
     if node.starts_with("adb:") {
@@ -340,7 +397,7 @@ fn node_describe(node: &str) -> Result<String, String> {
     match parse_node(node) {
         NodeType::Ssh { user, host, port } => {
             // Try to get system info via SSH
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=5",
                     "-o", "BatchMode=yes",
@@ -460,14 +517,14 @@ fn node_describe(node: &str) -> Result<String, String> {
 }
 
 /// Run a command on a remote node.
-fn node_run(node: &str, command: &[impl AsRef<str>]) -> Result<String, String> {
+pub(super) fn node_run(node: &str, command: &[impl AsRef<str>]) -> Result<String, String> {
     debug!(node, "Running command on node");
     match parse_node(node) {
         NodeType::Ssh { user, host, port } => {
             let cmd_str = command.iter().map(|s| s.as_ref()).collect::<Vec<_>>().join(" ");
             debug!(user, host, port, cmd = %cmd_str, "SSH command");
 
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=10",
                     "-p", &port.to_string(),
@@ -567,7 +624,7 @@ fn node_screen_snap(node: &str, _facing: &str) -> Result<String, String> {
             // Take screenshot via SSH + scrot/import
             let local_path = format!("/tmp/ssh_snap_{}.png", timestamp);
 
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=10",
                     "-p", &port.to_string(),
@@ -741,7 +798,7 @@ fn node_click(node: &str, x: i32, y: i32, button: &str) -> Result<String, String
 
         NodeType::Ssh { user, host, port } => {
             // Use xdotool over SSH
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=5",
                     "-p", &port.to_string(),
@@ -831,7 +888,7 @@ fn node_type_text(node: &str, text: &str) -> Result<String, String> {
         }
 
         NodeType::Ssh { user, host, port } => {
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=5",
                     "-p", &port.to_string(),
@@ -936,7 +993,7 @@ fn node_send_key(node: &str, key: &str) -> Result<String, String> {
         }
 
         NodeType::Ssh { user, host, port } => {
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=5",
                     "-p", &port.to_string(),
@@ -991,7 +1048,7 @@ fn node_notify(node: &str, title: &str, body: &str) -> Result<String, String> {
         }
 
         NodeType::Ssh { user, host, port } => {
-            let output = Command::new("ssh")
+            let output = ssh_command()
                 .args([
                     "-o", "ConnectTimeout=5",
                     "-p", &port.to_string(),
@@ -1023,6 +1080,251 @@ fn node_notify(node: &str, title: &str, body: &str) -> Result<String, String> {
     }
 }
 
+// ── Discovery ───────────────────────────────────────────────────────────────
+
+/// Scan the LAN and list candidate devices with node ids for the ones the
+/// tool can connect to (SSH, VNC, RDP, ADB over TLS).
+fn node_discover(timeout: std::time::Duration) -> Result<String, String> {
+    let (services, errors) = discovery::scan(timeout);
+    if services.is_empty() && !errors.is_empty() {
+        return Err(format!("Discovery failed: {}", errors.join("; ")));
+    }
+    let user = std::env::var("USER").unwrap_or_else(|_| "root".to_string());
+    let found = discovery::candidates(&services, &user);
+    if let Ok(mut last) = DISCOVERED.lock() {
+        *last = found.clone();
+    }
+    debug!(devices = found.len(), "Discovery finished");
+    let mut result = json!({
+        "devices": found,
+        "note": "SSH node ids use your local user name; change it in the id if the remote account differs. \
+                 ADB devices need 'adb pair' once before 'adb connect'."
+    });
+    if !errors.is_empty() {
+        result["errors"] = json!(errors);
+    }
+    Ok(result.to_string())
+}
+
+// ── ADB QR pairing ──────────────────────────────────────────────────────────
+
+/// Random lowercase alphanumerics, for pairing names and passwords.
+fn random_token(len: usize) -> String {
+    use rand::RngExt;
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let mut bytes = vec![0u8; len];
+    rand::rng().fill(&mut bytes[..]);
+    bytes.iter().map(|b| ALPHABET[*b as usize % ALPHABET.len()] as char).collect()
+}
+
+fn set_pairing(name: &str, state: &str, detail: Value) {
+    use crate::notifications::{post, Severity, Source};
+    // Pairing finishes in the background, after the tool call returned.
+    let severity = match state {
+        "paired" => Some(Severity::Success),
+        "failed" => Some(Severity::Error),
+        "expired" => Some(Severity::Warning),
+        _ => None,
+    };
+    if let Some(severity) = severity {
+        let body = detail
+            .get("error")
+            .or_else(|| detail.get("address"))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        post(severity, Source::Pairing, format!("ADB pairing {}: {}", state, name), body, None);
+    }
+    if let Ok(mut pairing) = PAIRING.lock() {
+        *pairing = Some(json!({ "name": name, "state": state, "detail": detail }));
+    }
+}
+
+/// Show a Wireless debugging pairing QR code and pair in the background
+/// once the phone advertises `_adb-tls-pairing._tcp` with our name.
+fn adb_qr_pair(timeout: std::time::Duration) -> Result<String, String> {
+    which::which("adb").map_err(|_| "adb not found. Install Android platform-tools.".to_string())?;
+    let name = format!("rustyclaw-{}", random_token(6));
+    let password = random_token(10);
+    let payload = qr_tool::wifi_payload(&name, Some(&password), "ADB", false);
+    let art = qr_tool::render_text(&payload)?;
+    let png = std::env::temp_dir().join(format!("{}.png", name));
+    let media = match qr_tool::write_png(&payload, &png, 8) {
+        Ok(()) => format!("\n\nMEDIA: {}", png.display()),
+        Err(e) => {
+            warn!(error = %e, "Couldn't write pairing QR image");
+            String::new()
+        }
+    };
+
+    set_pairing(&name, "waiting", json!(null));
+    let (service_name, secret) = (name.clone(), password);
+    std::thread::spawn(move || {
+        let deadline = std::time::Instant::now() + timeout;
+        while std::time::Instant::now() < deadline {
+            let found = discovery::browse("_adb-tls-pairing._tcp", std::time::Duration::from_secs(3))
+                .unwrap_or_default()
+                .into_iter()
+                .find(|s| s.name == service_name && s.port.is_some());
+            let Some(service) = found else { continue };
+            let target = format!("{}:{}", service.address, service.port.unwrap_or_default());
+            debug!(%target, "Pairing with ADB device");
+            match Command::new("adb").args(["pair", &target, &secret]).output() {
+                Ok(out) if out.status.success() => {
+                    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    set_pairing(&service_name, "paired", json!({ "address": service.address, "output": text }));
+                }
+                Ok(out) => {
+                    let text = String::from_utf8_lossy(&out.stderr).trim().to_string();
+                    set_pairing(&service_name, "failed", json!({ "address": service.address, "error": text }));
+                }
+                Err(e) => set_pairing(&service_name, "failed", json!({ "error": e.to_string() })),
+            }
+            return;
+        }
+        set_pairing(&service_name, "expired", json!(null));
+    });
+
+    Ok(format!(
+        "Scan this on the phone: Settings → Developer options → Wireless debugging → Pair device with QR code.\n\
+         Waiting up to {}s in the background; check progress with 'pending', then run 'discover' for the adb: node id.\n\n\
+         ```\n{}\n```{}",
+        timeout.as_secs(),
+        art,
+        media
+    ))
+}
+
+// ── Power actions ───────────────────────────────────────────────────────────
+
+/// Parse a MAC address written with `:`, `-` or no separators.
+fn parse_mac(mac: &str) -> Result<[u8; 6], String> {
+    let hex: String = mac.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    if hex.len() != 12 || mac.chars().any(|c| !c.is_ascii_hexdigit() && !matches!(c, ':' | '-' | '.')) {
+        return Err(format!("Invalid MAC address: {}", mac));
+    }
+    let mut bytes = [0u8; 6];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|e| e.to_string())?;
+    }
+    Ok(bytes)
+}
+
+/// Wake-on-LAN magic packet: six 0xFF bytes, then the MAC sixteen times.
+fn magic_packet(mac: [u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    packet
+}
+
+/// Host part of any node identifier.
+fn node_host(node: &str) -> Option<String> {
+    match parse_node(node) {
+        NodeType::Ssh { host, .. } | NodeType::Vnc { host, .. } | NodeType::Rdp { host, .. } => Some(host),
+        NodeType::Adb { .. } => None,
+    }
+}
+
+/// Look a host's MAC up in the neighbour (ARP) table.  Only works for
+/// machines on the local network that were reachable recently.
+fn neighbour_mac(host: &str) -> Option<String> {
+    let ip = std::net::ToSocketAddrs::to_socket_addrs(&(host, 0))
+        .ok()?
+        .find(|a| a.is_ipv4())?
+        .ip()
+        .to_string();
+    let mac_re = regex::Regex::new(r"(?i)\b([0-9a-f]{1,2}(?::[0-9a-f]{1,2}){5})\b").ok()?;
+    [("ip", vec!["neigh", "show", ip.as_str()]), ("arp", vec!["-n", ip.as_str()])]
+        .iter()
+        .filter_map(|(cmd, args)| Command::new(cmd).args(args).output().ok())
+        .find_map(|o| {
+            let out = String::from_utf8_lossy(&o.stdout).to_string();
+            mac_re.captures(&out).map(|c| {
+                // arp on macOS drops leading zeros ("a:b:..."); pad them back.
+                c[1].split(':').map(|b| format!("{:0>2}", b)).collect::<Vec<_>>().join(":")
+            })
+        })
+}
+
+/// Send a Wake-on-LAN packet to `mac`, or to the MAC of `node`'s host.
+fn node_wake(node: &str, mac: Option<&str>, broadcast: &str) -> Result<String, String> {
+    let mac = match mac {
+        Some(mac) => mac.to_string(),
+        None if node.is_empty() => return Err("wake needs 'mac' or 'node'".to_string()),
+        None => node_host(node)
+            .and_then(|h| neighbour_mac(&h))
+            .ok_or_else(|| format!("Couldn't find the MAC address of {}; pass 'mac'", node))?,
+    };
+    let bytes = parse_mac(&mac)?;
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| format!("Failed to open UDP socket: {}", e))?;
+    socket
+        .set_broadcast(true)
+        .map_err(|e| format!("Failed to enable broadcast: {}", e))?;
+    let packet = magic_packet(bytes);
+    // Port 9 (discard) is the convention; some NICs only listen on 7.
+    for port in [9, 7] {
+        socket
+            .send_to(&packet, (broadcast, port))
+            .map_err(|e| format!("Failed to send magic packet to {}:{}: {}", broadcast, port, e))?;
+    }
+    debug!(mac = %mac, broadcast, "Sent Wake-on-LAN packet");
+    Ok(json!({
+        "node": node,
+        "mac": mac,
+        "broadcast": broadcast,
+        "sent": true,
+        "note": "The machine may take a minute to boot; check with describe or run."
+    }).to_string())
+}
+
+/// Suspend or reboot a node.
+fn node_power(node: &str, action: &str) -> Result<String, String> {
+    let output = match parse_node(node) {
+        NodeType::Ssh { user, host, port } => {
+            // Try without sudo first (polkit often allows it), then sudo -n so
+            // a password prompt fails instead of hanging; pmset covers macOS.
+            let remote = match action {
+                "sleep" => "systemctl suspend || sudo -n systemctl suspend || pmset sleepnow || sudo -n pmset sleepnow",
+                _ => "systemctl reboot || sudo -n systemctl reboot || sudo -n shutdown -r now",
+            };
+            ssh_command()
+                .args([
+                    "-o", "ConnectTimeout=10",
+                    "-o", "BatchMode=yes",
+                    "-p", &port.to_string(),
+                    &format!("{}@{}", user, host),
+                    remote,
+                ])
+                .output()
+                .map_err(|e| format!("Failed to run ssh: {}", e))?
+        }
+        NodeType::Adb { device } => {
+            let args: Vec<&str> = match action {
+                "sleep" => vec!["-s", &device, "shell", "input", "keyevent", "KEYCODE_SLEEP"],
+                _ => vec!["-s", &device, "reboot"],
+            };
+            Command::new("adb")
+                .args(args)
+                .output()
+                .map_err(|e| format!("Failed to run adb: {}", e))?
+        }
+        NodeType::Vnc { .. } | NodeType::Rdp { .. } => {
+            return Err(format!("{} requires an SSH or ADB node.", action));
+        }
+    };
+
+    // A reboot or suspend often drops the connection before ssh reports
+    // success (exit 255), which still means the command went through.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let dropped = output.status.code() == Some(255) && stderr.contains("closed by remote host");
+    if !output.status.success() && !dropped {
+        return Err(format!("{} failed on {}: {}", action, node, stderr.trim()));
+    }
+    Ok(json!({ "node": node, "action": action, "ok": true }).to_string())
+}
+
 // ── ADB-specific actions ────────────────────────────────────────────────────
 
 /// List cameras on Android device.
@@ -1480,6 +1782,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_magic_packet() {
+        let mac = parse_mac("AA-bb-cc:dd:ee:01").unwrap();
+        assert_eq!(mac, [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x01]);
+        let packet = magic_packet(mac);
+        assert_eq!(packet.len(), 102);
+        assert_eq!(&packet[..6], &[0xFF; 6]);
+        assert_eq!(&packet[96..], &mac);
+        assert!(parse_mac("aa:bb:cc").is_err());
+        assert!(parse_mac("zz:bb:cc:dd:ee:ff").is_err());
+    }
+
+    #[test]
+    fn test_power_actions_need_ssh_or_adb() {
+        let err = exec_nodes(&json!({ "action": "reboot", "node": "vnc:host:1" }), &PathBuf::from("/tmp"));
+        assert!(err.unwrap_err().contains("SSH or ADB"));
+        let err = exec_nodes(&json!({ "action": "wake" }), &PathBuf::from("/tmp"));
+        assert!(err.unwrap_err().contains("'mac'"));
+    }
+
+    #[test]
+    fn test_pending_before_discover() {
+        let result = exec_nodes(&json!({ "action": "pending" }), &PathBuf::from("/tmp")).unwrap();
+        let parsed: Value = serde_json::from_str(&result).unwrap();
+        assert!(parsed["pending"].is_array());
+    }
+
     #[test]
     fn test_nodes_status() {
         let args = json!({ "action": "status" });
@@ -0,0 +1,507 @@
+//! LAN device discovery over mDNS (DNS-SD) and SSDP (UPnP).
+//!
+//! Both protocols are spoken directly over UDP so no avahi/bonjour daemon
+//! is needed.  mDNS queries are sent from an ephemeral port, which makes
+//! responders answer by unicast ("legacy unicast", RFC 6762 §6.7) and lets
+//! us avoid binding 5353 alongside a system resolver.
+
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+const MDNS_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(224, 0, 0, 251), 5353);
+const SSDP_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(239, 255, 255, 250), 1900);
+
+/// DNS-SD service types we ask for, and the kind of device they indicate.
+const SERVICE_KINDS: &[(&str, &str)] = &[
+    ("_googlecast._tcp", "chromecast"),
+    ("_airplay._tcp", "airplay"),
+    ("_raop._tcp", "airplay"),
+    ("_spotify-connect._tcp", "speaker"),
+    ("_ipp._tcp", "printer"),
+    ("_ipps._tcp", "printer"),
+    ("_printer._tcp", "printer"),
+    ("_pdl-datastream._tcp", "printer"),
+    ("_scanner._tcp", "scanner"),
+    ("_ssh._tcp", "ssh"),
+    ("_sftp-ssh._tcp", "ssh"),
+    ("_rfb._tcp", "vnc"),
+    ("_rdp._tcp", "rdp"),
+    ("_adb-tls-connect._tcp", "adb"),
+    ("_adb-tls-pairing._tcp", "adb pairing"),
+    ("_smb._tcp", "file share"),
+    ("_hap._tcp", "homekit"),
+    ("_workstation._tcp", "workstation"),
+];
+
+const DNS_A: u16 = 1;
+const DNS_PTR: u16 = 12;
+const DNS_TXT: u16 = 16;
+const DNS_AAAA: u16 = 28;
+const DNS_SRV: u16 = 33;
+
+/// One service seen on the network.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Service {
+    /// IP address the service lives on.
+    pub address: String,
+    /// Instance name (mDNS) or friendly name (SSDP).
+    pub name: String,
+    /// DNS-SD service type (`_ssh._tcp`) or SSDP search target.
+    pub service: String,
+    pub port: Option<u16>,
+    /// `.local` host name, when mDNS reported one.
+    pub hostname: Option<String>,
+    /// TXT record entries or SSDP headers worth keeping.
+    pub info: BTreeMap<String, String>,
+}
+
+impl Service {
+    /// Device kind implied by the service type.
+    pub fn kind(&self) -> &'static str {
+        if let Some((_, kind)) = SERVICE_KINDS.iter().find(|(t, _)| *t == self.service) {
+            return kind;
+        }
+        let st = self.service.to_lowercase();
+        if st.contains("dial") {
+            "chromecast"
+        } else if st.contains("mediarenderer") || st.contains("mediaserver") {
+            "media"
+        } else if st.contains("internetgatewaydevice") || st.contains("wanipconnection") {
+            "router"
+        } else if st.contains("printer") {
+            "printer"
+        } else {
+            "upnp"
+        }
+    }
+
+    /// A nodes-tool identifier for this service, if it's something the
+    /// tool can connect to.
+    pub fn node_id(&self, user: &str) -> Option<String> {
+        let port = self.port?;
+        match self.kind() {
+            "ssh" => Some(format!("ssh:{}@{}:{}", user, self.address, port)),
+            "vnc" => Some(format!("vnc:{}:{}", self.address, port)),
+            "rdp" => Some(format!("rdp:{}:{}", self.address, port)),
+            "adb" => Some(format!("adb:{}:{}", self.address, port)),
+            _ => None,
+        }
+    }
+}
+
+// ── mDNS ────────────────────────────────────────────────────────────────────
+
+fn push_name(buf: &mut Vec<u8>, name: &str) {
+    for label in name.trim_end_matches('.').split('.') {
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+}
+
+/// Build a DNS query with one PTR question per service type.
+fn mdns_query(types: &[&str]) -> Vec<u8> {
+    let mut buf = vec![0, 0, 0, 0];
+    buf.extend_from_slice(&(types.len() as u16).to_be_bytes());
+    buf.extend_from_slice(&[0; 6]);
+    for t in types {
+        push_name(&mut buf, &format!("{}.local", t));
+        buf.extend_from_slice(&DNS_PTR.to_be_bytes());
+        buf.extend_from_slice(&1u16.to_be_bytes());
+    }
+    buf
+}
+
+/// Read a (possibly compressed) name at `pos`; returns the name and the
+/// offset just past it in the original record.
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Bound pointer chasing so a malicious loop can't hang us.
+    for _ in 0..64 {
+        let len = *packet.get(pos)? as usize;
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        if len & 0xC0 == 0xC0 {
+            let target = ((len & 0x3F) << 8) | *packet.get(pos + 1)? as usize;
+            end.get_or_insert(pos + 2);
+            pos = target;
+            continue;
+        }
+        let label = packet.get(pos + 1..pos + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).to_string());
+        pos += 1 + len;
+    }
+    None
+}
+
+struct Record {
+    name: String,
+    rtype: u16,
+    rdata: std::ops::Range<usize>,
+}
+
+fn read_records(packet: &[u8]) -> Option<Vec<Record>> {
+    let count = |i: usize| u16::from_be_bytes([packet[i], packet[i + 1]]) as usize;
+    if packet.len() < 12 {
+        return None;
+    }
+    let (questions, answers) = (count(4), count(6) + count(8) + count(10));
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(packet, pos)?.1 + 4;
+    }
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        let (name, next) = read_name(packet, pos)?;
+        let header = packet.get(next..next + 10)?;
+        let rtype = u16::from_be_bytes([header[0], header[1]]);
+        let len = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let start = next + 10;
+        packet.get(start..start + len)?;
+        records.push(Record { name, rtype, rdata: start..start + len });
+        pos = start + len;
+    }
+    Some(records)
+}
+
+fn parse_txt(data: &[u8]) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let len = data[pos] as usize;
+        let Some(entry) = data.get(pos + 1..pos + 1 + len) else { break };
+        let entry = String::from_utf8_lossy(entry);
+        if let Some((k, v)) = entry.split_once('=') {
+            out.insert(k.to_string(), v.to_string());
+        }
+        pos += 1 + len;
+    }
+    out
+}
+
+/// Turn mDNS responses into services.  Records may be spread over several
+/// packets, so everything is pooled before being joined up.
+fn parse_mdns(packets: &[(Vec<u8>, IpAddr)]) -> Vec<Service> {
+    let mut instances: Vec<(String, String, IpAddr)> = Vec::new();
+    let mut srv: HashMap<String, (String, u16)> = HashMap::new();
+    let mut txt: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+    let mut addrs: HashMap<String, IpAddr> = HashMap::new();
+
+    for (packet, from) in packets {
+        let Some(records) = read_records(packet) else { continue };
+        for r in records {
+            let data = &packet[r.rdata.clone()];
+            let name = r.name.to_lowercase();
+            match r.rtype {
+                DNS_PTR => {
+                    let Some((instance, _)) = read_name(packet, r.rdata.start) else { continue };
+                    let service = name.trim_end_matches(".local").to_string();
+                    if SERVICE_KINDS.iter().any(|(t, _)| *t == service)
+                        && !instances.iter().any(|(i, _, _)| i.eq_ignore_ascii_case(&instance))
+                    {
+                        instances.push((instance, service, *from));
+                    }
+                }
+                DNS_SRV if data.len() > 6 => {
+                    let port = u16::from_be_bytes([data[4], data[5]]);
+                    if let Some((target, _)) = read_name(packet, r.rdata.start + 6) {
+                        srv.insert(name, (target, port));
+                    }
+                }
+                DNS_TXT => {
+                    txt.insert(name, parse_txt(data));
+                }
+                DNS_A if data.len() == 4 => {
+                    addrs.insert(name, IpAddr::from([data[0], data[1], data[2], data[3]]));
+                }
+                DNS_AAAA if data.len() == 16 => {
+                    let mut octets = [0u8; 16];
+                    octets.copy_from_slice(data);
+                    addrs.entry(name).or_insert(IpAddr::from(octets));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    instances
+        .into_iter()
+        .map(|(instance, service, from)| {
+            let key = instance.to_lowercase();
+            let (hostname, port) = match srv.get(&key) {
+                Some((host, port)) => (Some(host.clone()), Some(*port)),
+                None => (None, None),
+            };
+            let address = hostname
+                .as_ref()
+                .and_then(|h| addrs.get(&h.to_lowercase()))
+                .copied()
+                .unwrap_or(from);
+            let suffix = format!(".{}.local", service);
+            let name = instance.strip_suffix(&suffix).unwrap_or(&instance).to_string();
+            let mut info = txt.remove(&key).unwrap_or_default();
+            // Chromecasts hide the friendly name in TXT `fn`.
+            if let Some(friendly) = info.remove("fn") {
+                info.insert("friendly_name".into(), friendly);
+            }
+            Service { address: address.to_string(), name, service, port, hostname, info }
+        })
+        .collect()
+}
+
+/// Send `payload` to a multicast group and collect replies until `timeout`.
+fn collect(group: (Ipv4Addr, u16), payload: &[u8], timeout: Duration) -> Result<Vec<(Vec<u8>, IpAddr)>, String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("Failed to open UDP socket: {}", e))?;
+    let _ = socket.set_multicast_ttl_v4(2);
+    socket
+        .send_to(payload, group)
+        .map_err(|e| format!("Failed to send to {}:{}: {}", group.0, group.1, e))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut replies = Vec::new();
+    let mut buf = [0u8; 9000];
+    while let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|d| !d.is_zero()) {
+        let _ = socket.set_read_timeout(Some(left));
+        match socket.recv_from(&mut buf) {
+            Ok((n, from)) => replies.push((buf[..n].to_vec(), from.ip())),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => break,
+            Err(e) => return Err(format!("Receive failed: {}", e)),
+        }
+    }
+    Ok(replies)
+}
+
+fn mdns_scan(timeout: Duration) -> Result<Vec<Service>, String> {
+    let types: Vec<&str> = SERVICE_KINDS.iter().map(|(t, _)| *t).collect();
+    let packets = collect(MDNS_ADDR, &mdns_query(&types), timeout)?;
+    debug!(packets = packets.len(), "mDNS responses");
+    Ok(parse_mdns(&packets))
+}
+
+/// Ask for a single DNS-SD service type, e.g. to wait for one device to
+/// advertise itself.
+pub(crate) fn browse(service: &str, timeout: Duration) -> Result<Vec<Service>, String> {
+    let packets = collect(MDNS_ADDR, &mdns_query(&[service]), timeout)?;
+    Ok(parse_mdns(&packets).into_iter().filter(|s| s.service == service).collect())
+}
+
+// ── SSDP ────────────────────────────────────────────────────────────────────
+
+/// Parse one SSDP `HTTP/1.1 200 OK` response into lower-cased headers.
+fn parse_ssdp(response: &str) -> Option<BTreeMap<String, String>> {
+    let mut lines = response.lines();
+    if !lines.next()?.to_uppercase().starts_with("HTTP/1.1 200") {
+        return None;
+    }
+    Some(
+        lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+            .collect(),
+    )
+}
+
+/// Pull `friendlyName` and `modelName` from a UPnP device description.
+fn describe_upnp(location: &str) -> BTreeMap<String, String> {
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(2)).build();
+    let Ok(body) = client.and_then(|c| c.get(location).send()).and_then(|r| r.text()) else {
+        return BTreeMap::new();
+    };
+    ["friendlyName", "modelName", "manufacturer"]
+        .iter()
+        .filter_map(|tag| {
+            let re = regex::Regex::new(&format!(r"<{0}>([^<]*)</{0}>", tag)).ok()?;
+            let value = re.captures(&body)?[1].trim().to_string();
+            Some((tag.to_string(), value))
+        })
+        .collect()
+}
+
+fn ssdp_scan(timeout: Duration) -> Result<Vec<Service>, String> {
+    let mx = timeout.as_secs().clamp(1, 5);
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: {}\r\nST: ssdp:all\r\n\r\n",
+        mx
+    );
+    let replies = collect(SSDP_ADDR, search.as_bytes(), timeout)?;
+    debug!(replies = replies.len(), "SSDP responses");
+
+    let mut seen = BTreeSet::new();
+    let mut descriptions: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+    let mut services = Vec::new();
+    for (data, from) in replies {
+        let Some(headers) = parse_ssdp(&String::from_utf8_lossy(&data)) else { continue };
+        let st = headers.get("st").cloned().unwrap_or_default();
+        // Devices answer ssdp:all once per service; the root device entry is
+        // enough to identify them.
+        if st.is_empty() || st.starts_with("uuid:") || !seen.insert((from, st.clone())) {
+            continue;
+        }
+        let mut info = BTreeMap::new();
+        for key in ["server", "location"] {
+            if let Some(v) = headers.get(key) {
+                info.insert(key.to_string(), v.clone());
+            }
+        }
+        if let Some(location) = headers.get("location") {
+            if !descriptions.contains_key(location) && descriptions.len() < 16 {
+                descriptions.insert(location.clone(), describe_upnp(location));
+            }
+            info.extend(descriptions.get(location).cloned().unwrap_or_default());
+        }
+        let port = headers
+            .get("location")
+            .and_then(|l| url::Url::parse(l).ok())
+            .and_then(|u| u.port_or_known_default());
+        services.push(Service {
+            address: from.to_string(),
+            name: info.get("friendlyName").cloned().unwrap_or_default(),
+            service: st,
+            port,
+            hostname: None,
+            info,
+        });
+    }
+    Ok(services)
+}
+
+// ── Scan ────────────────────────────────────────────────────────────────────
+
+/// Scan with both protocols in parallel.  A protocol that fails (no
+/// multicast route, firewall) is reported in the second value rather than
+/// failing the whole scan.
+pub(crate) fn scan(timeout: Duration) -> (Vec<Service>, Vec<String>) {
+    let ssdp = std::thread::spawn(move || ssdp_scan(timeout));
+    let mut errors = Vec::new();
+    let mut services = mdns_scan(timeout).unwrap_or_else(|e| {
+        errors.push(format!("mDNS: {}", e));
+        Vec::new()
+    });
+    match ssdp.join() {
+        Ok(Ok(found)) => services.extend(found),
+        Ok(Err(e)) => errors.push(format!("SSDP: {}", e)),
+        Err(_) => errors.push("SSDP: scan thread panicked".to_string()),
+    }
+    (services, errors)
+}
+
+/// Group services by address into one candidate per device.
+pub(crate) fn candidates(services: &[Service], user: &str) -> Vec<Value> {
+    let mut by_addr: BTreeMap<&str, Vec<&Service>> = BTreeMap::new();
+    for s in services {
+        by_addr.entry(s.address.as_str()).or_default().push(s);
+    }
+    by_addr
+        .into_iter()
+        .map(|(address, found)| {
+            let name = found
+                .iter()
+                .find_map(|s| s.info.get("friendly_name").or(s.info.get("friendlyName")))
+                .cloned()
+                .or_else(|| found.iter().map(|s| s.name.clone()).find(|n| !n.is_empty()))
+                .unwrap_or_else(|| address.to_string());
+            let kinds: BTreeSet<&str> = found.iter().map(|s| s.kind()).collect();
+            let nodes: BTreeSet<String> = found.iter().filter_map(|s| s.node_id(user)).collect();
+            let hostname = found.iter().find_map(|s| s.hostname.clone());
+            let services: Vec<Value> = found
+                .iter()
+                .map(|s| json!({ "type": s.service, "port": s.port, "name": s.name, "info": s.info }))
+                .collect();
+            json!({
+                "address": address,
+                "name": name,
+                "hostname": hostname,
+                "kinds": kinds,
+                "nodes": nodes,
+                "services": services,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_record(buf: &mut Vec<u8>, name: &str, rtype: u16, rdata: &[u8]) {
+        push_name(buf, name);
+        buf.extend_from_slice(&rtype.to_be_bytes());
+        buf.extend_from_slice(&1u16.to_be_bytes());
+        buf.extend_from_slice(&120u32.to_be_bytes());
+        buf.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        buf.extend_from_slice(rdata);
+    }
+
+    #[test]
+    fn test_mdns_query() {
+        let query = mdns_query(&["_ssh._tcp"]);
+        assert_eq!(&query[4..6], &[0, 1]);
+        let (name, end) = read_name(&query, 12).unwrap();
+        assert_eq!(name, "_ssh._tcp.local");
+        assert_eq!(&query[end..], &[0, 12, 0, 1]);
+    }
+
+    #[test]
+    fn test_parse_mdns_response() {
+        let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0];
+        // PTR rdata points back at nothing fancy; write the instance in full.
+        let mut ptr = Vec::new();
+        push_name(&mut ptr, "box._ssh._tcp.local");
+        push_record(&mut packet, "_ssh._tcp.local", DNS_PTR, &ptr);
+        let mut srv = vec![0, 0, 0, 0, 0x08, 0xAE];
+        push_name(&mut srv, "box.local");
+        push_record(&mut packet, "box._ssh._tcp.local", DNS_SRV, &srv);
+        push_record(&mut packet, "box._ssh._tcp.local", DNS_TXT, b"\x05u=bob");
+        push_record(&mut packet, "box.local", DNS_A, &[192, 168, 1, 20]);
+
+        let services = parse_mdns(&[(packet, "192.168.1.1".parse().unwrap())]);
+        assert_eq!(services.len(), 1);
+        let s = &services[0];
+        assert_eq!(s.name, "box");
+        assert_eq!(s.address, "192.168.1.20");
+        assert_eq!(s.port, Some(2222));
+        assert_eq!(s.hostname.as_deref(), Some("box.local"));
+        assert_eq!(s.info.get("u").map(String::as_str), Some("bob"));
+        assert_eq!(s.kind(), "ssh");
+        assert_eq!(s.node_id("me").as_deref(), Some("ssh:me@192.168.1.20:2222"));
+    }
+
+    #[test]
+    fn test_read_name_pointer_loop() {
+        // A pointer to itself must not hang.
+        assert!(read_name(&[0xC0, 0x00], 0).is_none());
+        assert!(read_records(&[0; 5]).is_none());
+    }
+
+    #[test]
+    fn test_parse_ssdp() {
+        let response = "HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=1800\r\n\
+                        LOCATION: http://192.168.1.5:8008/ssdp/device-desc.xml\r\n\
+                        ST: urn:dial-multiscreen-org:service:dial:1\r\n\r\n";
+        let headers = parse_ssdp(response).unwrap();
+        assert_eq!(headers["location"], "http://192.168.1.5:8008/ssdp/device-desc.xml");
+        let s = Service { service: headers["st"].clone(), ..Default::default() };
+        assert_eq!(s.kind(), "chromecast");
+        assert!(parse_ssdp("M-SEARCH * HTTP/1.1\r\n").is_none());
+    }
+
+    #[test]
+    fn test_candidates_group_by_address() {
+        let services = vec![
+            Service { address: "10.0.0.2".into(), name: "nas".into(), service: "_ssh._tcp".into(), port: Some(22), ..Default::default() },
+            Service { address: "10.0.0.2".into(), name: "nas".into(), service: "_smb._tcp".into(), port: Some(445), ..Default::default() },
+            Service { address: "10.0.0.3".into(), name: "Office".into(), service: "_ipp._tcp".into(), port: Some(631), ..Default::default() },
+        ];
+        let found = candidates(&services, "root");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0]["nodes"], json!(["ssh:root@10.0.0.2:22"]));
+        assert_eq!(found[0]["kinds"], json!(["file share", "ssh"]));
+        assert_eq!(found[1]["kinds"], json!(["printer"]));
+        assert_eq!(found[1]["nodes"], json!([]));
+    }
+}
@@ -0,0 +1,321 @@
+//! Dry-run mode for the tool layer.
+//!
+//! When enabled, tools that change the outside world (files, processes,
+//! outgoing messages, scheduled jobs) don't act.  Instead they return a
+//! description of what they would have done, so a prompt can be audited
+//! safely before being let loose.  Read-only tools run normally.
+//!
+//! The switch has three layers: `--dry-run` on the command line (sticky for
+//! the life of the process), a per-connection toggle from `/dryrun`, and the
+//! `dry_run` config setting, which a reload may change.
+
+use serde_json::Value;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use tracing::debug;
+
+use super::helpers::{expand_tilde, resolve_path};
+
+/// Dry-run as set in the config.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Set by `--dry-run`; nothing turns it off again.
+static FORCED: AtomicBool = AtomicBool::new(false);
+
+/// A connection's `/dryrun` override: 0 = none, 1 = off, 2 = on.  Shared
+/// by clones, so tool calls moved to other tasks see later toggles.
+#[derive(Debug, Clone, Default)]
+pub struct DryRunSession(Arc<AtomicU8>);
+
+tokio::task_local! {
+    static SESSION: DryRunSession;
+}
+
+/// The `/dryrun` override slot of the current task, if any.
+pub fn current_dry_run_session() -> Option<DryRunSession> {
+    SESSION.try_with(|session| session.clone()).ok()
+}
+
+/// Run `fut` with `session` as its `/dryrun` override slot.
+pub async fn dry_run_session_scope<F: Future>(session: DryRunSession, fut: F) -> F::Output {
+    SESSION.scope(session, fut).await
+}
+
+/// Run `f` with `session` (if any) as its `/dryrun` override slot — for
+/// tool calls moved onto the blocking pool.
+pub fn sync_dry_run_session_scope<R>(session: Option<DryRunSession>, f: impl FnOnce() -> R) -> R {
+    match session {
+        Some(session) => SESSION.sync_scope(session, f),
+        None => f(),
+    }
+}
+
+/// Enable or disable dry-run mode as configured.
+pub fn set_dry_run(enabled: bool) {
+    debug!(enabled, "Setting tool dry-run mode");
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+/// Enable dry-run mode for the rest of the process (`--dry-run`).
+pub fn force_dry_run() {
+    debug!("Forcing tool dry-run mode");
+    FORCED.store(true, Ordering::Relaxed);
+}
+
+/// Override the configured setting for the current connection (`/dryrun`);
+/// `None` goes back to the config.  Outside a [`dry_run_session_scope`]
+/// there is nothing to override and the call does nothing.
+pub fn set_session_dry_run(enabled: Option<bool>) {
+    debug!(?enabled, "Setting session dry-run override");
+    let value = match enabled {
+        None => 0,
+        Some(false) => 1,
+        Some(true) => 2,
+    };
+    let _ = SESSION.try_with(|session| session.0.store(value, Ordering::Relaxed));
+}
+
+/// Whether dry-run mode is active for the current task.
+pub fn is_dry_run() -> bool {
+    if FORCED.load(Ordering::Relaxed) {
+        return true;
+    }
+    match SESSION.try_with(|session| session.0.load(Ordering::Relaxed)) {
+        Ok(1) => false,
+        Ok(2) => true,
+        _ => DRY_RUN.load(Ordering::Relaxed),
+    }
+}
+
+/// Whether a call changes something, i.e. dry-run mode would hold it back.
+pub fn is_mutating(name: &str, args: &Value, workspace_dir: &Path) -> bool {
+    simulate(name, args, workspace_dir).is_some()
+}
+
+/// Describe what a mutating tool call would do.
+///
+/// Returns `None` for calls that don't mutate anything, which should be
+/// executed normally even in dry-run mode.
+pub fn simulate(name: &str, args: &Value, workspace_dir: &Path) -> Option<Result<String, String>> {
+    let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str());
+    let action = str_arg("action").unwrap_or("");
+
+    let description = match name {
+        "write_file" => {
+            let path = str_arg("path")?;
+            let target = resolve_path(workspace_dir, &expand_tilde(path).to_string_lossy());
+            let bytes = str_arg("content").map(|c| c.len()).unwrap_or(0);
+            let verb = if target.exists() { "overwrite" } else { "create" };
+            format!("would {} {} with {} bytes", verb, target.display(), bytes)
+        }
+        "edit_file" => {
+            let path = str_arg("path")?;
+            let target = resolve_path(workspace_dir, &expand_tilde(path).to_string_lossy());
+            let old = str_arg("old_string").unwrap_or("");
+            let matches = std::fs::read_to_string(&target)
+                .map(|content| if old.is_empty() { 0 } else { content.matches(old).count() })
+                .unwrap_or(0);
+            if matches != 1 {
+                // Report the same failure the real edit would hit.
+                return Some(Err(format!(
+                    "[dry-run] edit_file on {} would fail: old_string matches {} times (expected exactly 1)",
+                    target.display(),
+                    matches
+                )));
+            }
+            format!(
+                "would replace {} bytes with {} bytes in {}",
+                old.len(),
+                str_arg("new_string").unwrap_or("").len(),
+                target.display()
+            )
+        }
+        "delete_file" if matches!(action, "" | "trash" | "delete") => {
+            let path = str_arg("path")?;
+            let target = resolve_path(workspace_dir, &expand_tilde(path).to_string_lossy());
+            if action == "delete" {
+                format!("would permanently delete {}", target.display())
+            } else {
+                format!("would move {} to the trash", target.display())
+            }
+        }
+        "delete_file" if action == "restore" => format!(
+            "would restore {} from the trash",
+            str_arg("id").unwrap_or("the most recently trashed item")
+        ),
+        "apply_patch" => {
+            // The patch tool has its own validation-only mode; use it so the
+            // audit reports exactly which hunks would apply.
+            let mut args = args.clone();
+            args["dry_run"] = Value::Bool(true);
+            return Some(
+                super::patch::exec_apply_patch(&args, workspace_dir)
+                    .map(|out| format!("[dry-run] apply_patch not applied.\n{}", out)),
+            );
+        }
+        "execute_command" => {
+            let command = str_arg("command")?;
+            let cwd = str_arg("working_dir")
+                .map(|d| resolve_path(workspace_dir, d))
+                .unwrap_or_else(|| workspace_dir.to_path_buf());
+            format!("would run `{}` in {}", command, cwd.display())
+        }
+        "process" if matches!(action, "write" | "send_keys" | "kill" | "clear" | "remove") => {
+            format!(
+                "would {} background session {}",
+                action,
+                str_arg("sessionId").unwrap_or("(unspecified)")
+            )
+        }
+        "serial" if matches!(action, "open" | "send" | "close") => format!(
+            "would {} serial {}",
+            action,
+            str_arg("port").or(str_arg("sessionId")).unwrap_or("(unspecified)")
+        ),
+        "message" if action == "send" || action == "broadcast" => {
+            let target = str_arg("target").unwrap_or("(targets)");
+            let what = match str_arg("template") {
+                Some(template) => format!("template '{}'", template),
+                None => format!("a {}-char message", str_arg("message").unwrap_or("").chars().count()),
+            };
+            format!(
+                "would {} {} to {} via {}",
+                action,
+                what,
+                target,
+                str_arg("channel").unwrap_or("auto")
+            )
+        }
+        "lsp_rename" => format!(
+            "would rename the symbol at {}:{} to '{}' across the project",
+            str_arg("path")?,
+            args.get("line").and_then(|v| v.as_u64()).unwrap_or(0),
+            str_arg("newName").unwrap_or("")
+        ),
+        "lint" if args.get("fix").and_then(|v| v.as_bool()) == Some(true) => format!(
+            "would apply {} fixes to {}",
+            str_arg("linter").unwrap_or("linter"),
+            str_arg("path").unwrap_or("the workspace")
+        ),
+        "format" if args.get("check").and_then(|v| v.as_bool()) != Some(true) => format!(
+            "would format {} with {}",
+            str_arg("path").unwrap_or("the workspace"),
+            str_arg("formatter").unwrap_or("the project's formatter")
+        ),
+        "deps" if matches!(action, "add" | "remove" | "update") => {
+            let packages: Vec<&str> = args
+                .get("packages")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            let subject = match (packages.is_empty(), str_arg("package")) {
+                (true, Some(p)) => p.to_string(),
+                (true, None) => "all dependencies".to_string(),
+                (false, _) => packages.join(", "),
+            };
+            format!("would {} {}", action, subject)
+        }
+        "contacts" if matches!(action, "add" | "remove") => format!(
+            "would {} contact '{}' in {}",
+            action,
+            str_arg("name").unwrap_or("(unnamed)"),
+            crate::contacts::local_file().display()
+        ),
+        "nodes" if matches!(action, "wake" | "sleep" | "reboot") => format!(
+            "would {} node {}",
+            action,
+            str_arg("node").or(str_arg("mac")).unwrap_or("(unspecified)")
+        ),
+        "mqtt" if action == "publish" => format!(
+            "would publish to MQTT topic {}",
+            str_arg("topic").unwrap_or("(unspecified)")
+        ),
+        "ble" if action == "write" => format!(
+            "would write {} to characteristic {} on {}",
+            str_arg("value").unwrap_or("(nothing)"),
+            str_arg("characteristic").unwrap_or("(unspecified)"),
+            str_arg("device").unwrap_or("(unspecified)")
+        ),
+        "render_report" => format!(
+            "would write a {} report to {}",
+            str_arg("format").unwrap_or("pdf"),
+            str_arg("output")
+                .map(|o| resolve_path(workspace_dir, o).display().to_string())
+                .unwrap_or_else(|| workspace_dir.join("reports").display().to_string())
+        ),
+        "qr" if action != "decode" && str_arg("format") != Some("text") => format!(
+            "would write a QR code PNG to {}",
+            str_arg("output")
+                .map(|o| resolve_path(workspace_dir, o).with_extension("png").display().to_string())
+                .unwrap_or_else(|| workspace_dir.join("qr").display().to_string())
+        ),
+        "plot" => format!(
+            "would write a {} chart to {}",
+            str_arg("type").unwrap_or("line"),
+            str_arg("output")
+                .map(|o| resolve_path(workspace_dir, o).with_extension("png").display().to_string())
+                .unwrap_or_else(|| workspace_dir.join("plots").display().to_string())
+        ),
+        "xlsx_write" => format!(
+            "would {} workbook {} with {} operation(s)",
+            if str_arg("mode") == Some("update") { "update" } else { "write" },
+            resolve_path(workspace_dir, str_arg("path")?).display(),
+            args.get("operations").and_then(|v| v.as_array()).map(|a| a.len()).unwrap_or(0)
+        ),
+        "media_convert" if str_arg("operation") != Some("status") => format!(
+            "would {} {} with ffmpeg{}",
+            str_arg("operation").unwrap_or("convert"),
+            str_arg("input").unwrap_or("(unspecified)"),
+            str_arg("format").map(|f| format!(" to {}", f)).unwrap_or_default()
+        ),
+        "sessions_worktree" if matches!(action, "merge" | "discard") => format!(
+            "would {} the worktree of session {}",
+            action,
+            str_arg("sessionKey").unwrap_or("(unspecified)")
+        ),
+        "snapshots" if action == "restore" => format!(
+            "would restore {} from snapshot {}",
+            args.get("paths")
+                .and_then(|v| v.as_array())
+                .filter(|a| !a.is_empty())
+                .map(|a| a.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", "))
+                .unwrap_or_else(|| "the workspace".to_string()),
+            str_arg("id").unwrap_or("(unspecified)")
+        ),
+        "cron" if action == "export" => format!(
+            "would write the schedule as iCal to {}",
+            str_arg("output")
+                .map(|o| resolve_path(workspace_dir, o).display().to_string())
+                .unwrap_or_else(|| workspace_dir.join("exports").join("schedule.ics").display().to_string())
+        ),
+        "cron" if matches!(action, "add" | "update" | "remove" | "run" | "snooze" | "ack") => {
+            let subject = str_arg("jobId")
+                .map(|id| format!("job {}", id))
+                .or_else(|| {
+                    args.get("job")
+                        .and_then(|j| j.get("name"))
+                        .and_then(|n| n.as_str())
+                        .map(|n| format!("job '{}'", n))
+                })
+                .unwrap_or_else(|| "a job".to_string());
+            format!("would {} {}", action, subject)
+        }
+        "conversations" if matches!(action, "clear" | "export") => format!(
+            "would {} {}",
+            action,
+            match (str_arg("messenger"), str_arg("chat")) {
+                (Some(m), Some(c)) => format!("conversation {}:{}", m, c),
+                (Some(m), None) => format!("every {} conversation", m),
+                _ => "every conversation".to_string(),
+            }
+        ),
+        _ => return None,
+    };
+
+    Some(Ok(format!(
+        "[dry-run] {} {} — no changes were made.",
+        name, description
+    )))
+}
@@ -0,0 +1,133 @@
+//! Optimistic concurrency for file edits.
+//!
+//! `read_file` ends its output with the hash of the whole file.  Passing
+//! that hash back as `expected_hash` to `edit_file` or `apply_patch` makes
+//! the edit fail if the file changed in between — another session, the
+//! user's editor, a formatter — instead of silently clobbering the change.
+//! The error shows what changed since the read, so the model can re-read
+//! and retry.
+
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Reads remembered for diffs, most recent last.
+const REMEMBERED_READS: usize = 64;
+
+/// Larger files are hashed but not remembered.
+const MAX_REMEMBERED_BYTES: usize = 512 * 1024;
+
+/// Diff lines shown in a conflict error.
+const MAX_DIFF_LINES: usize = 40;
+
+static READS: Mutex<VecDeque<(PathBuf, String, String)>> = Mutex::new(VecDeque::new());
+
+/// Short content hash, as shown by `read_file`.
+pub fn hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes()).iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Record that `content` of `path` was read; returns its hash.
+pub fn remember(path: &Path, content: &str) -> String {
+    let digest = hash(content);
+    if content.len() <= MAX_REMEMBERED_BYTES {
+        let mut reads = READS.lock().unwrap_or_else(|e| e.into_inner());
+        reads.retain(|(p, h, _)| !(p == path && *h == digest));
+        if reads.len() >= REMEMBERED_READS {
+            reads.pop_front();
+        }
+        reads.push_back((path.to_path_buf(), digest.clone(), content.to_string()));
+    }
+    digest
+}
+
+fn remembered(path: &Path, digest: &str) -> Option<String> {
+    let reads = READS.lock().unwrap_or_else(|e| e.into_inner());
+    reads.iter().rev().find(|(p, h, _)| p == path && h == digest).map(|(_, _, c)| c.clone())
+}
+
+/// Fail if `path` no longer has the content hashed as `expected`.
+pub fn check(path: &Path, expected: Option<&str>, current: &str) -> Result<(), String> {
+    let Some(expected) = expected.map(str::trim).filter(|h| !h.is_empty()) else {
+        return Ok(());
+    };
+    let now = hash(current);
+    if now == expected {
+        return Ok(());
+    }
+    let changes = match remembered(path, expected) {
+        Some(old) => line_diff(&old, current),
+        None => "(the earlier content is not available)".to_string(),
+    };
+    Err(format!(
+        "{} changed since you read it (hash {}, now {}). Changes since then:\n{}\n\
+         Re-read the file and redo the edit against its current content.",
+        path.display(),
+        expected,
+        now,
+        changes
+    ))
+}
+
+/// The changed region between `old` and `new`: common leading and
+/// trailing lines are skipped, the rest is shown as `-`/`+` lines.
+pub fn line_diff(old: &str, new: &str) -> String {
+    let (old, new): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let removed = &old[prefix..old.len() - suffix];
+    let added = &new[prefix..new.len() - suffix];
+    if removed.is_empty() && added.is_empty() {
+        return "(only line endings or trailing newlines changed)".to_string();
+    }
+
+    let mut lines = vec![format!(
+        "@@ -{},{} +{},{} @@",
+        prefix + 1,
+        removed.len(),
+        prefix + 1,
+        added.len()
+    )];
+    lines.extend(removed.iter().map(|l| format!("-{}", l)));
+    lines.extend(added.iter().map(|l| format!("+{}", l)));
+    if lines.len() > MAX_DIFF_LINES + 1 {
+        let more = lines.len() - MAX_DIFF_LINES - 1;
+        lines.truncate(MAX_DIFF_LINES + 1);
+        lines.push(format!("… {} more lines", more));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc\nd\n", "a\nB\nx\nd\n");
+        assert_eq!(diff, "@@ -2,2 +2,2 @@\n-b\n-c\n+B\n+x");
+        assert_eq!(line_diff("a\nb", "a\nb\n"), "(only line endings or trailing newlines changed)");
+        assert_eq!(line_diff("a", "a\nb"), "@@ -2,0 +2,1 @@\n+b");
+    }
+
+    #[test]
+    fn test_check_reports_interim_changes() {
+        let path = Path::new("/tmp/edit_conflict_test.txt");
+        let read = remember(path, "one\ntwo\n");
+        assert!(check(path, Some(&read), "one\ntwo\n").is_ok());
+        assert!(check(path, None, "anything").is_ok());
+
+        let err = check(path, Some(&read), "one\n2\n").unwrap_err();
+        assert!(err.contains(&format!("changed since you read it (hash {}", read)));
+        assert!(err.contains("-two\n+2"));
+
+        let err = check(path, Some("0000000000000000"), "one\n").unwrap_err();
+        assert!(err.contains("earlier content is not available"));
+    }
+}
@@ -1,8 +1,12 @@
-//! File operation tools: read, write, edit, list, search, find.
+//! File operation tools: read, write, edit, delete, list, search, find.
 
-use super::helpers::{resolve_path, expand_tilde, is_protected_path, display_path, should_visit, VAULT_ACCESS_DENIED};
+use super::binary;
+use super::edit_conflict;
+use super::file_index;
+use super::file_locks;
+use super::helpers::{resolve_path, expand_tilde, is_protected_path, check_write, display_path, should_visit, search_roots, VAULT_ACCESS_DENIED};
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tracing::{debug, warn, instrument};
 
@@ -52,9 +56,25 @@ pub fn exec_read_file(args: &Value, workspace_dir: &Path) -> Result<String, Stri
 
     debug!(path = %path.display(), "Reading file");
 
-    // First, try reading as UTF-8 plain text.
+    if args.get("read_binary").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
+        let length = args.get("length").and_then(|v| v.as_u64()).unwrap_or(binary::DEFAULT_DUMP_BYTES);
+        return binary::dump_file(&path, offset, length);
+    }
+
+    // First, try reading as UTF-8 plain text.  Only plain text gets a
+    // content hash: it is what edit_file and apply_patch compare against.
+    let mut digest = None;
     let content = match std::fs::read_to_string(&path) {
-        Ok(text) => text,
+        // Valid UTF-8 can still be binary (NUL-padded formats and the like).
+        Ok(text) if binary::looks_binary(text.as_bytes()) => {
+            debug!(path = %path.display(), "Binary content, returning summary");
+            return Ok(binary::summary(text.as_bytes(), text.len() as u64));
+        }
+        Ok(text) => {
+            digest = Some(edit_conflict::remember(&path, &text));
+            text
+        }
         Err(e) => {
             // If the file doesn't exist or can't be accessed at all, fail fast.
             if e.kind() == std::io::ErrorKind::NotFound
@@ -94,12 +114,20 @@ pub fn exec_read_file(args: &Value, workspace_dir: &Path) -> Result<String, Stri
                     if output.status.success() {
                         let text = String::from_utf8_lossy(&output.stdout).to_string();
                         if text.trim().is_empty() {
-                            return Err(format!(
-                                "'{}' is a PDF but no text could be extracted.",
-                                path.display(),
-                            ));
+                            // No text layer: probably a scan.
+                            match super::ocr_tool::ocr_text(&path) {
+                                Some(text) => format!("[no text layer; text recognised by OCR]\n{}", text),
+                                None => {
+                                    return Err(format!(
+                                        "'{}' is a PDF but no text could be extracted \
+                                         (it may be scanned; install tesseract to OCR it).",
+                                        path.display(),
+                                    ));
+                                }
+                            }
+                        } else {
+                            text
                         }
-                        text
                     } else {
                         return Err(format!(
                             "'{}' is a PDF. Install poppler (`brew install poppler`) \
@@ -114,13 +142,30 @@ pub fn exec_read_file(args: &Value, workspace_dir: &Path) -> Result<String, Stri
                         path.display(),
                     ));
                 }
+            } else if super::ocr_tool::IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+                match super::ocr_tool::ocr_text(&path) {
+                    Some(text) => format!("[image; text recognised by OCR]\n{}", text),
+                    None => {
+                        return Err(format!(
+                            "'{}' is an image with no recognisable text (or no OCR engine); \
+                             use the image tool to describe it.",
+                            path.display(),
+                        ));
+                    }
+                }
             } else {
-                return Err(format!(
-                    "Failed to read file '{}': {} (binary file — use execute_command \
-                     to process it with an appropriate tool)",
-                    path.display(),
-                    e,
-                ));
+                let head = binary::read_head(&path)
+                    .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+                let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                if binary::looks_binary(&head) {
+                    debug!(path = %path.display(), "Binary content, returning summary");
+                    return Ok(binary::summary(&head, size));
+                }
+                // Mostly text in a legacy encoding.
+                String::from_utf8_lossy(&std::fs::read(&path).map_err(|e| {
+                    format!("Failed to read file '{}': {}", path.display(), e)
+                })?)
+                .into_owned()
             }
         }
     };
@@ -157,7 +202,11 @@ pub fn exec_read_file(args: &Value, workspace_dir: &Path) -> Result<String, Stri
         .collect();
 
     debug!(path = %path.display(), lines_read = numbered.len(), "File read complete");
-    Ok(numbered.join("\n"))
+    let mut output = numbered.join("\n");
+    if let Some(digest) = digest {
+        output.push_str(&format!("\n[hash {}]", digest));
+    }
+    Ok(output)
 }
 
 #[instrument(skip(args, workspace_dir))]
@@ -177,8 +226,10 @@ pub fn exec_write_file(args: &Value, workspace_dir: &Path) -> Result<String, Str
         warn!(path = %path.display(), "Attempted write to protected path");
         return Err(VAULT_ACCESS_DENIED.to_string());
     }
+    check_write(&path, workspace_dir)?;
 
     debug!(path = %path.display(), bytes = content.len(), "Writing file");
+    file_locks::acquire(&path, "write_file")?;
 
     // Always create parent directories.
     if let Some(parent) = path.parent() {
@@ -218,11 +269,14 @@ pub fn exec_edit_file(args: &Value, workspace_dir: &Path) -> Result<String, Stri
         warn!(path = %path.display(), "Attempted edit to protected path");
         return Err(VAULT_ACCESS_DENIED.to_string());
     }
+    check_write(&path, workspace_dir)?;
 
     debug!(path = %path.display(), "Editing file");
+    file_locks::acquire(&path, "edit_file")?;
 
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+    edit_conflict::check(&path, args.get("expected_hash").and_then(|v| v.as_str()), &content)?;
 
     let count = content.matches(old_string).count();
     if count == 0 {
@@ -247,7 +301,96 @@ pub fn exec_edit_file(args: &Value, workspace_dir: &Path) -> Result<String, Stri
         .map_err(|e| format!("Failed to write file '{}': {}", path.display(), e))?;
 
     debug!(path = %path.display(), "File edited successfully");
-    Ok(format!("Successfully edited {}", path.display()))
+    // The new hash lets the model chain edits without re-reading.
+    let digest = edit_conflict::remember(&path, &new_content);
+    Ok(format!("Successfully edited {} [hash {}]", path.display(), digest))
+}
+
+/// Delete a file or directory by moving it to the trash, or permanently
+/// with `action: "delete"`.  Also lists and restores trashed items.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_delete_file(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("trash");
+    let (config, trash_dir) = crate::trash::config();
+
+    match action {
+        "list" => {
+            let items = crate::trash::list(&trash_dir);
+            if items.is_empty() {
+                return Ok("The trash is empty.".to_string());
+            }
+            let tz = crate::cron::local_tz();
+            let lines: Vec<String> = items
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{}  {}  {}{}  ({} KB)",
+                        e.id,
+                        crate::cron::format_ms(e.deleted_ms, tz),
+                        e.original.display(),
+                        if e.is_dir { "/" } else { "" },
+                        e.bytes.div_ceil(1024)
+                    )
+                })
+                .collect();
+            Ok(lines.join("\n"))
+        }
+        "restore" => {
+            let id = args.get("id").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty());
+            let entry = crate::trash::restore(&trash_dir, id.map(str::trim))?;
+            Ok(format!("Restored {} from the trash", entry.original.display()))
+        }
+        "trash" | "delete" => {
+            let path_str = args
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: path".to_string())?;
+            let path = resolve_path(workspace_dir, path_str);
+
+            if is_protected_path(&path) {
+                warn!(path = %path.display(), "Attempted delete of protected path");
+                return Err(VAULT_ACCESS_DENIED.to_string());
+            }
+            check_write(&path, workspace_dir)?;
+            if path == workspace_dir {
+                return Err("Refusing to delete the workspace itself".to_string());
+            }
+            let meta = std::fs::symlink_metadata(&path)
+                .map_err(|e| format!("Cannot delete '{}': {}", path.display(), e))?;
+            let recursive = args.get("recursive").and_then(|v| v.as_bool()).unwrap_or(false);
+            let non_empty_dir = meta.is_dir() && std::fs::read_dir(&path).is_ok_and(|mut d| d.next().is_some());
+            if non_empty_dir && !recursive {
+                return Err(format!(
+                    "'{}' is a non-empty directory; pass recursive=true to delete it",
+                    path.display()
+                ));
+            }
+            file_locks::acquire(&path, "delete_file")?;
+
+            if action == "delete" {
+                debug!(path = %path.display(), "Deleting permanently");
+                let removed = if meta.is_dir() {
+                    std::fs::remove_dir_all(&path)
+                } else {
+                    std::fs::remove_file(&path)
+                };
+                removed.map_err(|e| format!("Failed to delete '{}': {}", path.display(), e))?;
+                return Ok(format!("Permanently deleted {}", path.display()));
+            }
+
+            crate::trash::purge_expired(&trash_dir, config.retention_days);
+            let entry = crate::trash::move_to_trash(&trash_dir, &path)?;
+            Ok(format!(
+                "Moved {} to the trash (id {}). Restore it with action=restore or /undo.",
+                path.display(),
+                entry.id
+            ))
+        }
+        _ => Err(format!(
+            "Unknown action: {}. Valid: trash, delete, list, restore",
+            action
+        )),
+    }
 }
 
 #[instrument(skip(args, workspace_dir))]
@@ -266,6 +409,11 @@ pub fn exec_list_directory(args: &Value, workspace_dir: &Path) -> Result<String,
 
     debug!(path = %path.display(), "Listing directory");
 
+    if args.get("detail").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let sort = args.get("sort").and_then(|v| v.as_str()).unwrap_or("name");
+        return list_directory_detail(&path, sort);
+    }
+
     let entries = std::fs::read_dir(&path)
         .map_err(|e| format!("Failed to read directory '{}': {}", path.display(), e))?;
 
@@ -290,6 +438,136 @@ pub fn exec_list_directory(args: &Value, workspace_dir: &Path) -> Result<String,
     Ok(items.join("\n"))
 }
 
+/// `rwxr-xr-x`-style permission string.
+#[cfg(unix)]
+fn permission_string(meta: &std::fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    (0..9)
+        .map(|i| {
+            let bit = 1 << (8 - i);
+            if mode & bit == 0 {
+                '-'
+            } else {
+                ['r', 'w', 'x'][i % 3]
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn permission_string(meta: &std::fs::Metadata) -> String {
+    if meta.permissions().readonly() { "r-" } else { "rw" }.to_string()
+}
+
+/// Names in `dir` that git ignores, or `None` outside a git repository
+/// (or without git).  Directory names carry a trailing `/`.
+fn git_ignored(dir: &Path, names: &[String]) -> Option<std::collections::HashSet<String>> {
+    use std::io::Write;
+    let mut child = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(["check-ignore", "--stdin", "-z"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let input: Vec<u8> = names.iter().flat_map(|n| n.bytes().chain([0])).collect();
+        let _ = stdin.write_all(&input);
+    }
+    let output = child.wait_with_output().ok()?;
+    // 0: some ignored, 1: none ignored, anything else: not a repository.
+    match output.status.code() {
+        Some(0) | Some(1) => Some(
+            output
+                .stdout
+                .split(|b| *b == 0)
+                .filter(|s| !s.is_empty())
+                .map(|s| String::from_utf8_lossy(s).into_owned())
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// `list_directory` with `detail`: one JSON object per entry with type,
+/// size, modification time, permissions, symlink target and whether git
+/// ignores it.  `sort` is `name`, `size` (largest first) or `mtime`
+/// (newest first).
+fn list_directory_detail(path: &Path, sort: &str) -> Result<String, String> {
+    if !matches!(sort, "name" | "size" | "mtime") {
+        return Err(format!("Unknown sort: {}. Valid: name, size, mtime", sort));
+    }
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| format!("Failed to read directory '{}': {}", path.display(), e))?;
+
+    let mut items = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Error reading entry: {}", e))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let meta = std::fs::symlink_metadata(entry.path())
+            .map_err(|e| format!("Error reading metadata for '{}': {}", name, e))?;
+        let ft = meta.file_type();
+        let kind = if ft.is_symlink() {
+            "symlink"
+        } else if ft.is_dir() {
+            "dir"
+        } else if ft.is_file() {
+            "file"
+        } else {
+            "other"
+        };
+        let modified = meta.modified().ok();
+        let timestamp = modified.map(|t| {
+            chrono::DateTime::<chrono::Local>::from(t).to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+        });
+        let mut item = serde_json::json!({
+            "name": name,
+            "type": kind,
+            "size": if ft.is_file() { Some(meta.len()) } else { None },
+            "modified": timestamp,
+            "permissions": permission_string(&meta),
+        });
+        if ft.is_symlink() {
+            let target = std::fs::read_link(entry.path()).ok();
+            item["target"] = serde_json::json!(target.map(|t| t.display().to_string()));
+            // Where the link leads, following it.
+            item["target_type"] = serde_json::json!(match std::fs::metadata(entry.path()) {
+                Ok(m) if m.is_dir() => "dir",
+                Ok(m) if m.is_file() => "file",
+                Ok(_) => "other",
+                Err(_) => "broken",
+            });
+        }
+        let size = if ft.is_file() { meta.len() } else { 0 };
+        items.push((item, size, modified));
+    }
+
+    match sort {
+        "size" => items.sort_by(|a, b| b.1.cmp(&a.1)),
+        "mtime" => items.sort_by(|a, b| b.2.cmp(&a.2)),
+        _ => items.sort_by(|a, b| a.0["name"].as_str().cmp(&b.0["name"].as_str())),
+    }
+    let mut items: Vec<Value> = items.into_iter().map(|(item, _, _)| item).collect();
+
+    let names: Vec<String> = items
+        .iter()
+        .map(|i| {
+            let name = i["name"].as_str().unwrap_or_default();
+            if i["type"] == "dir" { format!("{}/", name) } else { name.to_string() }
+        })
+        .collect();
+    if let Some(ignored) = git_ignored(path, &names) {
+        for (item, name) in items.iter_mut().zip(&names) {
+            item["gitignored"] = Value::Bool(ignored.contains(name));
+        }
+    }
+
+    debug!(path = %path.display(), count = items.len(), sort, "Detailed directory listing complete");
+    serde_json::to_string_pretty(&items).map_err(|e| e.to_string())
+}
+
 #[instrument(skip(args, workspace_dir))]
 pub fn exec_search_files(args: &Value, workspace_dir: &Path) -> Result<String, String> {
     let pattern = args
@@ -299,10 +577,10 @@ pub fn exec_search_files(args: &Value, workspace_dir: &Path) -> Result<String, S
     let search_path = args.get("path").and_then(|v| v.as_str());
     let include = args.get("include").and_then(|v| v.as_str());
 
-    let base = match search_path {
-        Some(p) if p.starts_with('~') => expand_tilde(p),
-        Some(p) => resolve_path(workspace_dir, p),
-        None => workspace_dir.to_path_buf(),
+    let bases = match search_path {
+        Some(p) if p.starts_with('~') => vec![expand_tilde(p)],
+        Some(p) => vec![resolve_path(workspace_dir, p)],
+        None => search_roots(workspace_dir),
     };
 
     let include_glob = match include {
@@ -316,36 +594,33 @@ pub fn exec_search_files(args: &Value, workspace_dir: &Path) -> Result<String, S
     // Case-insensitive content search.
     let pattern_lower = pattern.to_lowercase();
 
-    debug!(pattern, base = %base.display(), "Searching files for pattern");
+    debug!(pattern, roots = bases.len(), "Searching files for pattern");
 
     let mut results = Vec::new();
     let max_results: usize = 100;
 
-    for entry in walkdir::WalkDir::new(&base)
-        .follow_links(true)
-        .into_iter()
-        .filter_entry(should_visit)
-    {
+    // A fresh file index narrows the search to files that can match.
+    let files = bases.iter().flat_map(|base| {
+        let index = file_index::lookup(base);
+        let walked = index.is_none().then(|| walk_files(base, usize::MAX));
+        let indexed = index.map(|index| index.candidates(base, &pattern_lower));
+        indexed.into_iter().flatten().chain(walked.into_iter().flatten())
+    });
+    for path in files {
         if results.len() >= max_results {
             break;
         }
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-        if !entry.file_type().is_file() {
-            continue;
-        }
 
         // Apply include filter.
         if let Some(ref glob_pat) = include_glob {
-            if !glob_pat.matches(&entry.file_name().to_string_lossy()) {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            if !glob_pat.matches(&name) {
                 continue;
             }
         }
 
         // Read and search (case-insensitive).
-        let content = match std::fs::read_to_string(entry.path()) {
+        let content = match std::fs::read_to_string(&path) {
             Ok(c) => c,
             Err(_) => continue, // skip binary / unreadable files
         };
@@ -357,7 +632,7 @@ pub fn exec_search_files(args: &Value, workspace_dir: &Path) -> Result<String, S
             if line.to_lowercase().contains(&pattern_lower) {
                 results.push(format!(
                     "{}:{}: {}",
-                    display_path(entry.path(), workspace_dir),
+                    display_path(&path, workspace_dir),
                     line_num + 1,
                     line.trim()
                 ));
@@ -382,6 +657,19 @@ pub fn exec_search_files(args: &Value, workspace_dir: &Path) -> Result<String, S
     }
 }
 
+/// Files under `base`, at most `max_depth` levels down, skipping the
+/// directories `should_visit` rejects.
+fn walk_files(base: &Path, max_depth: usize) -> impl Iterator<Item = PathBuf> {
+    walkdir::WalkDir::new(base)
+        .follow_links(true)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_entry(should_visit)
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+}
+
 /// Returns `true` if the pattern string contains glob special characters.
 fn is_glob_pattern(s: &str) -> bool {
     s.contains('*') || s.contains('?') || s.contains('[')
@@ -395,15 +683,15 @@ pub fn exec_find_files(args: &Value, workspace_dir: &Path) -> Result<String, Str
         .ok_or_else(|| "Missing required parameter: pattern".to_string())?;
     let search_path = args.get("path").and_then(|v| v.as_str());
 
-    let base = match search_path {
-        Some(p) if p.starts_with('~') => expand_tilde(p),
-        Some(p) => resolve_path(workspace_dir, p),
-        None => workspace_dir.to_path_buf(),
+    let bases = match search_path {
+        Some(p) if p.starts_with('~') => vec![expand_tilde(p)],
+        Some(p) => vec![resolve_path(workspace_dir, p)],
+        None => search_roots(workspace_dir),
     };
 
     let max_results: usize = 200;
 
-    debug!(pattern, base = %base.display(), is_glob = is_glob_pattern(pattern), "Finding files");
+    debug!(pattern, roots = bases.len(), is_glob = is_glob_pattern(pattern), "Finding files");
 
     if is_glob_pattern(pattern) {
         // ── Glob mode ───────────────────────────────────────────────
@@ -413,18 +701,20 @@ pub fn exec_find_files(args: &Value, workspace_dir: &Path) -> Result<String, Str
             format!("**/{}", pattern)
         };
 
-        let full = base.join(&effective);
-        let full_str = full.to_string_lossy();
-
         let mut results = Vec::new();
-        for entry in glob::glob(&full_str)
-            .map_err(|e| format!("Invalid glob pattern: {}", e))?
-        {
-            if results.len() >= max_results {
-                break;
-            }
-            if let Ok(path) = entry {
-                results.push(display_path(&path, workspace_dir));
+        for base in &bases {
+            let full = base.join(&effective);
+            let full_str = full.to_string_lossy();
+
+            for entry in glob::glob(&full_str)
+                .map_err(|e| format!("Invalid glob pattern: {}", e))?
+            {
+                if results.len() >= max_results {
+                    break;
+                }
+                if let Ok(path) = entry {
+                    results.push(display_path(&path, workspace_dir));
+                }
             }
         }
 
@@ -444,27 +734,23 @@ pub fn exec_find_files(args: &Value, workspace_dir: &Path) -> Result<String, Str
 
         let mut results = Vec::new();
 
-        for entry in walkdir::WalkDir::new(&base)
-            .follow_links(true)
-            .max_depth(8)
-            .into_iter()
-            .filter_entry(should_visit)
-        {
+        // Answer from a fresh file index when there is one, else walk.
+        let files = bases.iter().flat_map(|base| {
+            let index = file_index::lookup(base);
+            let walked = index.is_none().then(|| {
+                walk_files(base, 8).filter(|path| {
+                    let name_lower = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+                    keywords.iter().any(|kw| name_lower.contains(kw.as_str()))
+                })
+            });
+            let indexed = index.map(|index| index.find(base, 8, &keywords));
+            indexed.into_iter().flatten().chain(walked.into_iter().flatten())
+        });
+        for path in files {
             if results.len() >= max_results {
                 break;
             }
-            let entry = match entry {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
-            if !entry.file_type().is_file() {
-                continue;
-            }
-
-            let name_lower = entry.file_name().to_string_lossy().to_lowercase();
-            if keywords.iter().any(|kw| name_lower.contains(kw.as_str())) {
-                results.push(display_path(entry.path(), workspace_dir));
-            }
+            results.push(display_path(&path, workspace_dir));
         }
 
         format_find_results(results, max_results)
@@ -0,0 +1,304 @@
+//! Background file index for `find_files` and `search_files`.
+//!
+//! On a large tree every `find_files` would walk millions of entries.  With
+//! `[file_index]` enabled, a background loop keeps an index of each search
+//! root — file names and mtimes, and optionally the trigrams of each file's
+//! content — and the tools consult it instead of walking:
+//!
+//! ```toml
+//! [file_index]
+//! enabled = true
+//! refresh_secs = 60      # rescan interval
+//! max_age_secs = 300     # older indexes are ignored; the tools walk live
+//! trigrams = true        # also narrow search_files to files that can match
+//! max_file_kb = 1024     # larger files are always searched
+//! ```
+//!
+//! Rescans are incremental: files whose mtime and size are unchanged keep
+//! their trigrams without being read again.  An index that has not been
+//! refreshed within `max_age_secs` is treated as missing, so the tools never
+//! answer from badly outdated data.
+
+use super::helpers::{search_roots, should_visit};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// The `[file_index]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIndexConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds between rescans.
+    #[serde(default = "default_refresh_secs")]
+    pub refresh_secs: u64,
+    /// Seconds after which an index is too stale to use.
+    #[serde(default = "default_max_age_secs")]
+    pub max_age_secs: u64,
+    /// Index content trigrams for `search_files`.
+    #[serde(default)]
+    pub trigrams: bool,
+    /// Files larger than this (KiB) get no trigrams and are always searched.
+    #[serde(default = "default_max_file_kb")]
+    pub max_file_kb: u64,
+}
+
+fn default_refresh_secs() -> u64 {
+    60
+}
+
+fn default_max_age_secs() -> u64 {
+    300
+}
+
+fn default_max_file_kb() -> u64 {
+    1024
+}
+
+impl Default for FileIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_secs: default_refresh_secs(),
+            max_age_secs: default_max_age_secs(),
+            trigrams: false,
+            max_file_kb: default_max_file_kb(),
+        }
+    }
+}
+
+/// One indexed file.
+struct IndexedFile {
+    path: PathBuf,
+    name_lower: String,
+    mtime: Option<SystemTime>,
+    size: u64,
+    /// Lowercased content trigrams; `None` when not indexed.
+    trigrams: Option<HashSet<u32>>,
+}
+
+/// Index of the files under one search root, in walk order.
+pub struct FileIndex {
+    root: PathBuf,
+    files: Vec<IndexedFile>,
+    built: Instant,
+}
+
+fn trigram_set(bytes: &[u8]) -> HashSet<u32> {
+    let lower = bytes.to_ascii_lowercase();
+    lower
+        .windows(3)
+        .map(|w| (u32::from(w[0]) << 16) | (u32::from(w[1]) << 8) | u32::from(w[2]))
+        .collect()
+}
+
+impl FileIndex {
+    /// Walk `root`, reusing the trigrams of files unchanged since
+    /// `previous`.
+    pub fn build(root: &Path, previous: Option<&FileIndex>, config: &FileIndexConfig) -> Self {
+        let known: HashMap<&Path, &IndexedFile> = previous
+            .map(|p| p.files.iter().map(|f| (f.path.as_path(), f)).collect())
+            .unwrap_or_default();
+        let max_bytes = config.max_file_kb * 1024;
+        let (mut reused, mut read) = (0usize, 0usize);
+
+        let mut files = Vec::new();
+        let walk = walkdir::WalkDir::new(root).follow_links(true).into_iter().filter_entry(should_visit);
+        for entry in walk.flatten() {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let meta = entry.metadata().ok();
+            let mtime = meta.as_ref().and_then(|m| m.modified().ok());
+            let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+            let path = entry.path().to_path_buf();
+
+            let trigrams = if !config.trigrams || size > max_bytes {
+                None
+            } else {
+                match known.get(path.as_path()) {
+                    Some(old) if old.mtime == mtime && old.size == size && old.trigrams.is_some() => {
+                        reused += 1;
+                        old.trigrams.clone()
+                    }
+                    _ => {
+                        read += 1;
+                        std::fs::read(&path).ok().map(|bytes| trigram_set(&bytes))
+                    }
+                }
+            };
+            files.push(IndexedFile {
+                name_lower: entry.file_name().to_string_lossy().to_lowercase(),
+                path,
+                mtime,
+                size,
+                trigrams,
+            });
+        }
+        debug!(root = %root.display(), files = files.len(), reused, read, "File index built");
+        Self { root: root.to_path_buf(), files, built: Instant::now() }
+    }
+
+    /// Number of files indexed.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Files under `base` at most `max_depth` levels down (1 = directly in
+    /// `base`) whose name contains any of `keywords` (lowercase).
+    pub fn find(&self, base: &Path, max_depth: usize, keywords: &[String]) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter(|f| f.path.strip_prefix(base).is_ok_and(|rel| rel.components().count() <= max_depth))
+            .filter(|f| keywords.iter().any(|kw| f.name_lower.contains(kw.as_str())))
+            .map(|f| f.path.clone())
+            .collect()
+    }
+
+    /// Files under `base` that may contain `pattern` (case-insensitive).
+    /// Files without trigrams are always candidates.
+    pub fn candidates(&self, base: &Path, pattern: &str) -> Vec<PathBuf> {
+        let wanted = trigram_set(pattern.as_bytes());
+        self.files
+            .iter()
+            .filter(|f| f.path.starts_with(base))
+            .filter(|f| match &f.trigrams {
+                Some(set) => wanted.is_subset(set),
+                None => true,
+            })
+            .map(|f| f.path.clone())
+            .collect()
+    }
+}
+
+struct State {
+    config: FileIndexConfig,
+    workspace_dir: PathBuf,
+    indexes: Vec<Arc<FileIndex>>,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Set the index config.  Called at gateway startup and on reload; indexes
+/// built under a different config are dropped.
+pub fn set_config(config: FileIndexConfig, workspace_dir: &Path) {
+    let Ok(mut guard) = STATE.lock() else {
+        return;
+    };
+    let indexes = match guard.take() {
+        Some(s) if s.config == config && s.workspace_dir == workspace_dir => s.indexes,
+        _ => Vec::new(),
+    };
+    *guard = Some(State { config, workspace_dir: workspace_dir.to_path_buf(), indexes });
+}
+
+/// A fresh index covering `base`, if there is one.  `None` means the caller
+/// should walk the tree itself.
+pub fn lookup(base: &Path) -> Option<Arc<FileIndex>> {
+    let guard = STATE.lock().ok()?;
+    let state = guard.as_ref().filter(|s| s.config.enabled)?;
+    let max_age = Duration::from_secs(state.config.max_age_secs);
+    state
+        .indexes
+        .iter()
+        .find(|i| base.starts_with(&i.root) && i.built.elapsed() <= max_age)
+        .cloned()
+}
+
+/// Rebuild the index of every search root.
+fn refresh() {
+    let Some((config, workspace_dir, previous)) = STATE.lock().ok().and_then(|g| {
+        g.as_ref()
+            .filter(|s| s.config.enabled)
+            .map(|s| (s.config.clone(), s.workspace_dir.clone(), s.indexes.clone()))
+    }) else {
+        return;
+    };
+    let indexes: Vec<Arc<FileIndex>> = search_roots(&workspace_dir)
+        .iter()
+        .map(|root| {
+            let old = previous.iter().find(|i| i.root == *root);
+            Arc::new(FileIndex::build(root, old.map(|i| i.as_ref()), &config))
+        })
+        .collect();
+    if let Ok(mut guard) = STATE.lock() {
+        // Keep the result only if the config didn't change meanwhile.
+        if let Some(state) = guard.as_mut().filter(|s| s.config == config && s.workspace_dir == workspace_dir) {
+            state.indexes = indexes;
+        }
+    }
+}
+
+/// Keep the indexes up to date.  Idles until `[file_index]` is enabled.
+pub async fn run_file_index_loop(cancel: CancellationToken) {
+    loop {
+        let interval = STATE
+            .lock()
+            .ok()
+            .and_then(|g| g.as_ref().map(|s| s.config.refresh_secs))
+            .unwrap_or_else(default_refresh_secs)
+            .max(5);
+
+        if let Err(e) = tokio::task::spawn_blocking(refresh).await {
+            warn!(error = %e, "File index task panicked");
+        }
+
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> FileIndexConfig {
+        FileIndexConfig { enabled: true, trigrams: true, ..Default::default() }
+    }
+
+    #[test]
+    fn test_find_by_name_and_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/deep")).unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join("src/Main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("src/deep/main_test.rs"), "").unwrap();
+        std::fs::write(dir.path().join("target/main.o"), "").unwrap();
+
+        let index = FileIndex::build(dir.path(), None, &config());
+        assert_eq!(index.len(), 2);
+        let found = index.find(dir.path(), 8, &["main".to_string()]);
+        assert_eq!(found.len(), 2);
+        assert_eq!(index.find(dir.path(), 2, &["main".to_string()]), [dir.path().join("src/Main.rs")]);
+        assert!(index.find(&dir.path().join("src/deep"), 8, &["lib".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_trigram_candidates_and_reuse() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        std::fs::write(&a, "Hello World").unwrap();
+        std::fs::write(&b, "goodbye").unwrap();
+
+        let first = FileIndex::build(dir.path(), None, &config());
+        assert_eq!(first.candidates(dir.path(), "world"), [a.clone()]);
+        // Too short for a trigram: everything is a candidate.
+        assert_eq!(first.candidates(dir.path(), "lo").len(), 2);
+
+        std::fs::write(&b, "goodbye world!").unwrap();
+        let second = FileIndex::build(dir.path(), Some(&first), &config());
+        let mut found = second.candidates(dir.path(), "WORLD");
+        found.sort();
+        assert_eq!(found, [a, b]);
+    }
+}
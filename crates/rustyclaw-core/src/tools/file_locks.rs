@@ -0,0 +1,143 @@
+//! Advisory file locks between concurrent sessions.
+//!
+//! Two sub-agents (or a chat turn and a queued task) editing the same file
+//! would silently interleave their writes.  `write_file`, `edit_file` and
+//! `apply_patch` therefore take a lock on each file they change.  The lock
+//! belongs to the turn (its trace ID) and lasts until the turn ends, or
+//! [`LEASE`] after the turn's last write if it never reports ending.  A
+//! different turn writing the file meanwhile gets a conflict error naming
+//! the holder.  Writes outside any turn (the CLI, tests) share one owner.
+//!
+//! The lock table is shown by the `gateway` tool's `locks` action.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a lock outlives its holder's last write.
+pub const LEASE: Duration = Duration::from_secs(120);
+
+/// Owner of writes made outside a turn.
+const LOCAL: &str = "local";
+
+struct Holder {
+    owner: String,
+    tool: String,
+    acquired: Instant,
+    renewed: Instant,
+}
+
+static LOCKS: Mutex<Option<HashMap<PathBuf, Holder>>> = Mutex::new(None);
+
+/// The key for `path`: its canonical form, resolved through the parent
+/// for files that don't exist yet.
+fn key(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    match (path.parent().and_then(|p| p.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn current_owner() -> String {
+    crate::observability::trace::current().unwrap_or_else(|| LOCAL.to_string())
+}
+
+fn short(owner: &str) -> &str {
+    &owner[..owner.len().min(8)]
+}
+
+/// Take or renew the lock on `path` for the current turn, before `tool`
+/// writes it.  Fails if another live turn holds it.
+pub fn acquire(path: &Path, tool: &str) -> Result<(), String> {
+    let owner = current_owner();
+    let now = Instant::now();
+    let mut guard = LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    let locks = guard.get_or_insert_with(HashMap::new);
+    locks.retain(|_, h| now.duration_since(h.renewed) < LEASE);
+
+    let key = key(path);
+    match locks.get_mut(&key) {
+        Some(holder) if holder.owner != owner => Err(format!(
+            "{} is locked by another session (turn {}, last written by {} {}s ago). \
+             Wait for that session to finish, or coordinate with it before editing.",
+            path.display(),
+            short(&holder.owner),
+            holder.tool,
+            now.duration_since(holder.renewed).as_secs()
+        )),
+        Some(holder) => {
+            holder.tool = tool.to_string();
+            holder.renewed = now;
+            Ok(())
+        }
+        None => {
+            locks.insert(key, Holder { owner, tool: tool.to_string(), acquired: now, renewed: now });
+            Ok(())
+        }
+    }
+}
+
+/// Release every lock held by the turn `owner` (a trace ID).  Called when
+/// a turn ends.
+pub fn release_owner(owner: &str) {
+    let mut guard = LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(locks) = guard.as_mut() {
+        locks.retain(|_, h| h.owner != owner);
+    }
+}
+
+/// The lock table, one line per held file.
+pub fn table() -> String {
+    let now = Instant::now();
+    let guard = LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut rows: Vec<String> = guard
+        .iter()
+        .flatten()
+        .filter(|(_, h)| now.duration_since(h.renewed) < LEASE)
+        .map(|(path, h)| {
+            format!(
+                "{}  turn {}  {}  held {}s, expires in {}s",
+                path.display(),
+                short(&h.owner),
+                h.tool,
+                now.duration_since(h.acquired).as_secs(),
+                LEASE.saturating_sub(now.duration_since(h.renewed)).as_secs()
+            )
+        })
+        .collect();
+    if rows.is_empty() {
+        return "No file locks held.".to_string();
+    }
+    rows.sort();
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::trace::sync_scope;
+
+    #[test]
+    fn test_second_turn_gets_a_conflict_until_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.txt");
+        let (first, second) = ("a1".repeat(16), "b2".repeat(16));
+
+        sync_scope(Some(first.clone()), || acquire(&path, "write_file")).unwrap();
+        // The holder may keep writing.
+        sync_scope(Some(first.clone()), || acquire(&path, "edit_file")).unwrap();
+
+        let err = sync_scope(Some(second.clone()), || acquire(&path, "apply_patch")).unwrap_err();
+        assert!(err.contains("locked by another session (turn a1a1a1a1, last written by edit_file"));
+        assert!(table().contains("shared.txt  turn a1a1a1a1  edit_file"));
+
+        release_owner(&first);
+        sync_scope(Some(second.clone()), || acquire(&path, "apply_patch")).unwrap();
+        release_owner(&second);
+        assert!(!table().contains("shared.txt"));
+    }
+}
@@ -135,10 +135,12 @@ pub fn exec_gateway(args: &Value, workspace_dir: &Path) -> Result<String, String
                 .to_string(),
         ),
 
+        "locks" => Ok(super::file_lock_table()),
+
         _ => {
             warn!(action, "Unknown gateway action");
             Err(format!(
-                "Unknown action: {}. Valid: restart, config.get, config.schema, config.apply, config.patch, update.run",
+                "Unknown action: {}. Valid: restart, config.get, config.schema, config.apply, config.patch, update.run, locks",
                 action
             ))
         }
@@ -164,12 +166,37 @@ fn merge_json(base: Value, patch: Value) -> Value {
     }
 }
 
+/// Message text from `message`, or from `template` rendered with `vars`
+/// (see [`crate::templates`]).
+fn message_text(args: &Value, workspace_dir: &Path, action: &str) -> Result<String, String> {
+    let message = args.get("message").and_then(|v| v.as_str());
+    let Some(template) = args.get("template").and_then(|v| v.as_str()) else {
+        return message
+            .map(str::to_string)
+            .ok_or_else(|| format!("Missing message (or template) for {} action", action));
+    };
+    let mut vars = args.get("vars").map(crate::templates::vars_from_json).unwrap_or_default();
+    if let Some(message) = message {
+        vars.entry("message".to_string()).or_insert_with(|| message.to_string());
+    }
+    crate::templates::render_named(workspace_dir, template, &vars)
+}
+
+/// The channel an `auto` send goes out on.
+fn effective_channel(channel: &str) -> &str {
+    match channel {
+        "auto" if std::env::var("DISCORD_BOT_TOKEN").is_ok() => "discord",
+        "auto" if std::env::var("TELEGRAM_BOT_TOKEN").is_ok() => "telegram",
+        other => other,
+    }
+}
+
 /// Send messages via channel plugins.
 ///
 /// Supports Discord and Telegram when bot tokens are configured via environment.
 /// Falls back to stub behavior if no tokens are available.
-#[instrument(skip(args, _workspace_dir), fields(action))]
-pub fn exec_message(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+#[instrument(skip(args, workspace_dir), fields(action))]
+pub fn exec_message(args: &Value, workspace_dir: &Path) -> Result<String, String> {
     let action = args
         .get("action")
         .and_then(|v| v.as_str())
@@ -178,12 +205,12 @@ pub fn exec_message(args: &Value, _workspace_dir: &Path) -> Result<String, Strin
     tracing::Span::current().record("action", action);
     debug!("Executing message tool");
 
+    let urgent = args.get("urgent").and_then(|v| v.as_bool()).unwrap_or(false);
+
     match action {
         "send" => {
-            let message = args
-                .get("message")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing message for send action")?;
+            let message = message_text(args, workspace_dir, action)?;
+            let message = message.as_str();
 
             let target = args
                 .get("target")
@@ -195,6 +222,18 @@ pub fn exec_message(args: &Value, _workspace_dir: &Path) -> Result<String, Strin
                 .and_then(|v| v.as_str())
                 .unwrap_or("auto");
 
+            // A contact name ("Bob") resolves to that person's id.
+            let (target, channel) = crate::contacts::route(target, channel)?;
+            let (target, channel) = (target.as_str(), channel.as_str());
+
+            // Non-urgent messages wait out the channel's quiet hours.
+            if !urgent {
+                let channel = effective_channel(channel);
+                if let Some(until) = crate::quiet_hours::quiet_until(channel) {
+                    return crate::quiet_hours::hold(channel, target, message, until);
+                }
+            }
+
             // Try to send via configured messenger
             match channel {
                 "discord" => send_discord(target, message),
@@ -230,10 +269,8 @@ pub fn exec_message(args: &Value, _workspace_dir: &Path) -> Result<String, Strin
         }
 
         "broadcast" => {
-            let message = args
-                .get("message")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing message for broadcast action")?;
+            let message = message_text(args, workspace_dir, action)?;
+            let message = message.as_str();
 
             let targets = args
                 .get("targets")
@@ -252,11 +289,19 @@ pub fn exec_message(args: &Value, _workspace_dir: &Path) -> Result<String, Strin
 
             let mut results = Vec::new();
             for target in &targets {
-                let result = match channel {
-                    "discord" => send_discord(target, message),
-                    "telegram" => send_telegram(target, message),
-                    _ => Ok(format!("Would send to {}", target)),
-                };
+                let result = crate::contacts::route(target, channel).and_then(|(id, ch)| {
+                    if !urgent {
+                        let ch = effective_channel(&ch);
+                        if let Some(until) = crate::quiet_hours::quiet_until(ch) {
+                            return crate::quiet_hours::hold(ch, &id, message, until);
+                        }
+                    }
+                    match ch.as_str() {
+                        "discord" => send_discord(&id, message),
+                        "telegram" => send_telegram(&id, message),
+                        _ => Ok(format!("Would send to {}", id)),
+                    }
+                });
                 results.push(format!("{}: {}", target, result.unwrap_or_else(|e| e)));
             }
 
@@ -266,7 +311,16 @@ pub fn exec_message(args: &Value, _workspace_dir: &Path) -> Result<String, Strin
             ))
         }
 
-        _ => Err(format!("Unknown action: {}. Valid: send, broadcast", action)),
+        "templates" => {
+            let names = crate::templates::list(workspace_dir);
+            if names.is_empty() {
+                Ok("No message templates. Add Markdown files with {{variables}} to templates/.".to_string())
+            } else {
+                Ok(format!("Message templates:\n{}", names.iter().map(|n| format!("- {}", n)).collect::<Vec<_>>().join("\n")))
+            }
+        }
+
+        _ => Err(format!("Unknown action: {}. Valid: send, broadcast, templates", action)),
     }
 }
 
@@ -1,9 +1,9 @@
 //! Helper functions and global state for the tools system.
 
 use crate::process_manager::{ProcessManager, SharedProcessManager};
-use crate::sandbox::{Sandbox, SandboxMode, SandboxPolicy};
+use crate::sandbox::{Sandbox, SandboxMode, SandboxPolicy, WritePolicy};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use tracing::{debug, warn};
 
 // ── Global process manager ──────────────────────────────────────────────────
@@ -57,6 +57,27 @@ pub fn run_sandboxed_command(command: &str, cwd: &Path) -> Result<std::process::
     }
 }
 
+/// Where tools may write, replaced on every config (re)load.  `None` (the
+/// default outside the gateway) allows every write.
+static WRITE_POLICY: RwLock<Option<WritePolicy>> = RwLock::new(None);
+
+/// Register the write policy from the `[sandbox]` config section.
+pub fn set_write_policy(policy: WritePolicy) {
+    debug!(confine = policy.confine, allow = policy.allow.len(), deny = policy.deny.len(), "Setting write policy");
+    if let Ok(mut guard) = WRITE_POLICY.write() {
+        *guard = Some(policy);
+    }
+}
+
+/// Check that a tool may write to `path`; the error is meant for the model.
+pub fn check_write(path: &Path, workspace_dir: &Path) -> Result<(), String> {
+    let guard = WRITE_POLICY.read().map_err(|_| "Sandbox policy unavailable".to_string())?;
+    match guard.as_ref() {
+        Some(policy) => policy.check(path, workspace_dir).inspect_err(|e| warn!(path = %path.display(), "{}", e)),
+        None => Ok(()),
+    }
+}
+
 // ── Credentials directory protection ────────────────────────────────────────
 
 /// Absolute path of the credentials directory, set once at gateway startup.
@@ -123,16 +144,49 @@ pub fn is_protected_path(path: &Path) -> bool {
 pub const VAULT_ACCESS_DENIED: &str =
     "Access denied: the credentials directory is protected. Use the secrets_list / secrets_get / secrets_store tools instead.";
 
+// ── Extra search roots ──────────────────────────────────────────────────────
+
+/// Additional directories searched by `find_files` / `search_files` when no
+/// explicit path is given.  Replaced on every config (re)load.
+static EXTRA_ROOTS: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+
+/// Register the extra search roots from the config.
+pub fn set_extra_roots(roots: Vec<PathBuf>) {
+    debug!(count = roots.len(), "Setting extra search roots");
+    if let Ok(mut guard) = EXTRA_ROOTS.write() {
+        *guard = roots;
+    }
+}
+
+/// The extra search roots currently registered.
+pub fn extra_roots() -> Vec<PathBuf> {
+    EXTRA_ROOTS.read().map(|r| r.clone()).unwrap_or_default()
+}
+
+/// Directories to search when a search tool is called without a `path`:
+/// the workspace first, then every extra root that exists.
+pub fn search_roots(workspace_dir: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![workspace_dir.to_path_buf()];
+    for root in extra_roots() {
+        if root.is_dir() && !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
 // ── Path helpers ────────────────────────────────────────────────────────────
 
 /// Resolve a path argument against the workspace root.
 /// Absolute paths are used as-is; relative paths are joined to `workspace_dir`.
+/// `.` and `..` are resolved, so the result shows where a path like
+/// `../../etc/passwd` really lands (see [`check_write`]).
 pub fn resolve_path(workspace_dir: &Path, path: &str) -> PathBuf {
     let p = Path::new(path);
     if p.is_absolute() {
-        p.to_path_buf()
+        crate::sandbox::normalize(p)
     } else {
-        workspace_dir.join(p)
+        crate::sandbox::normalize(&workspace_dir.join(p))
     }
 }
 
@@ -161,15 +215,20 @@ pub fn display_path(found: &Path, workspace_dir: &Path) -> String {
     }
 }
 
+/// Directories that hold build output or VCS data rather than content.
+pub fn is_noise_dir(name: &str) -> bool {
+    matches!(
+        name,
+        ".git" | "node_modules" | "target" | ".hg" | ".svn"
+            | "__pycache__" | "dist" | "build"
+    )
+}
+
 /// Filter for `walkdir` — skip common non-content directories.
 pub fn should_visit(entry: &walkdir::DirEntry) -> bool {
     let name = entry.file_name().to_string_lossy();
     if entry.file_type().is_dir() {
-        if matches!(
-            name.as_ref(),
-            ".git" | "node_modules" | "target" | ".hg" | ".svn"
-                | "__pycache__" | "dist" | "build"
-        ) {
+        if is_noise_dir(&name) {
             return false;
         }
         // Never recurse into the credentials directory.
@@ -182,6 +241,32 @@ pub fn should_visit(entry: &walkdir::DirEntry) -> bool {
     }
 }
 
+/// A private temporary directory, removed when dropped.  Used by tools that
+/// shell out to converters producing intermediate files.
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub fn new(prefix: &str) -> Result<Self, String> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("rustyclaw-{}-{}-{}", prefix, std::process::id(), nanos));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        Ok(Self(dir))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 // ── Tool output sanitization ────────────────────────────────────────────────
 
 /// Maximum size for tool output before truncation (50 KB).
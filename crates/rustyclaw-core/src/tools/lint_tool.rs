@@ -0,0 +1,393 @@
+//! Lint and format tools: run the project's linters and formatters
+//! (clippy, ruff, eslint, rustfmt, prettier) and return normalised,
+//! machine-readable results.
+
+use super::helpers::{
+    display_path, is_protected_path, resolve_path, run_sandboxed_command, should_visit,
+    VAULT_ACCESS_DENIED,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+/// Most diagnostics returned in one call.
+const MAX_DIAGNOSTICS: usize = 200;
+
+/// Files larger than this are not snapshotted for change detection.
+const MAX_SNAPSHOT_FILE: u64 = 1024 * 1024;
+
+/// A single finding, in the same shape for every linter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: u64,
+    pub column: u64,
+    /// "error", "warning" or "info".
+    pub severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+// ── Detection ───────────────────────────────────────────────────────────────
+
+/// Pick a tool for `root` from the project files present.
+fn detect(root: &Path, candidates: &[(&'static str, &[&str])]) -> Option<&'static str> {
+    candidates
+        .iter()
+        .find(|(_, markers)| markers.iter().any(|m| root.join(m).exists()))
+        .map(|(tool, _)| *tool)
+}
+
+const LINTERS: &[(&str, &[&str])] = &[
+    ("clippy", &["Cargo.toml"]),
+    ("ruff", &["ruff.toml", ".ruff.toml", "pyproject.toml", "setup.py", "requirements.txt"]),
+    ("eslint", &["eslint.config.js", "eslint.config.mjs", ".eslintrc", ".eslintrc.js", ".eslintrc.json", "package.json"]),
+];
+
+const FORMATTERS: &[(&str, &[&str])] = &[
+    ("rustfmt", &["Cargo.toml"]),
+    ("ruff", &["ruff.toml", ".ruff.toml", "pyproject.toml", "setup.py", "requirements.txt"]),
+    ("prettier", &[".prettierrc", ".prettierrc.json", "prettier.config.js", "package.json"]),
+];
+
+/// Extensions each tool touches, for change detection.
+fn extensions(tool: &str) -> &'static [&'static str] {
+    match tool {
+        "clippy" | "rustfmt" => &["rs"],
+        "ruff" => &["py", "pyi"],
+        _ => &["js", "jsx", "ts", "tsx", "mjs", "cjs", "json", "css", "scss", "md", "html", "yaml", "yml"],
+    }
+}
+
+// ── Snapshots ───────────────────────────────────────────────────────────────
+
+/// Contents of every file under `root` with one of `exts`, taken before a
+/// fix so the changed files can be reported afterwards.
+fn snapshot(root: &Path, exts: &[&str]) -> BTreeMap<PathBuf, Vec<u8>> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(should_visit)
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .and_then(|x| x.to_str())
+                .is_some_and(|x| exts.contains(&x))
+        })
+        .filter(|e| e.metadata().map(|m| m.len() <= MAX_SNAPSHOT_FILE).unwrap_or(false))
+        .take(20_000)
+        .filter_map(|e| Some((e.path().to_path_buf(), std::fs::read(e.path()).ok()?)))
+        .collect()
+}
+
+/// Files whose contents differ from `before`.
+fn changed_files(before: &BTreeMap<PathBuf, Vec<u8>>) -> Vec<PathBuf> {
+    before
+        .iter()
+        .filter(|(path, old)| std::fs::read(path).map(|new| new != **old).unwrap_or(true))
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+// ── Parsers ─────────────────────────────────────────────────────────────────
+
+/// Parse `cargo clippy --message-format=json` output.
+pub fn parse_clippy(stdout: &str, root: &Path) -> Vec<Diagnostic> {
+    stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|v| v["reason"] == "compiler-message")
+        .filter_map(|v| {
+            let msg = &v["message"];
+            let span = msg["spans"]
+                .as_array()?
+                .iter()
+                .find(|s| s["is_primary"].as_bool() == Some(true))?;
+            let level = msg["level"].as_str()?;
+            if !matches!(level, "error" | "warning") {
+                return None;
+            }
+            Some(Diagnostic {
+                file: display_path(&root.join(span["file_name"].as_str()?), root),
+                line: span["line_start"].as_u64().unwrap_or(0),
+                column: span["column_start"].as_u64().unwrap_or(0),
+                severity: level.to_string(),
+                code: msg["code"]["code"].as_str().map(String::from),
+                message: msg["message"].as_str().unwrap_or("").to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `ruff check --output-format=json` output.
+pub fn parse_ruff(stdout: &str, root: &Path) -> Vec<Diagnostic> {
+    let Ok(Value::Array(items)) = serde_json::from_str::<Value>(stdout) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .map(|v| Diagnostic {
+            file: display_path(Path::new(v["filename"].as_str().unwrap_or("")), root),
+            line: v["location"]["row"].as_u64().unwrap_or(0),
+            column: v["location"]["column"].as_u64().unwrap_or(0),
+            severity: "warning".to_string(),
+            code: v["code"].as_str().map(String::from),
+            message: v["message"].as_str().unwrap_or("").to_string(),
+        })
+        .collect()
+}
+
+/// Parse `eslint -f json` output.
+pub fn parse_eslint(stdout: &str, root: &Path) -> Vec<Diagnostic> {
+    let Ok(Value::Array(files)) = serde_json::from_str::<Value>(stdout) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for file in &files {
+        let name = display_path(Path::new(file["filePath"].as_str().unwrap_or("")), root);
+        for m in file["messages"].as_array().into_iter().flatten() {
+            out.push(Diagnostic {
+                file: name.clone(),
+                line: m["line"].as_u64().unwrap_or(0),
+                column: m["column"].as_u64().unwrap_or(0),
+                severity: if m["severity"].as_u64() == Some(2) { "error" } else { "warning" }.to_string(),
+                code: m["ruleId"].as_str().map(String::from),
+                message: m["message"].as_str().unwrap_or("").to_string(),
+            });
+        }
+    }
+    out
+}
+
+// ── Running ─────────────────────────────────────────────────────────────────
+
+/// Resolve the optional `path` argument (default: workspace root).
+fn target_arg(args: &Value, workspace_dir: &Path) -> Result<PathBuf, String> {
+    let target = match args.get("path").and_then(|v| v.as_str()) {
+        Some(p) => resolve_path(workspace_dir, p),
+        None => workspace_dir.to_path_buf(),
+    };
+    if is_protected_path(&target) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    if !target.exists() {
+        return Err(format!("Path not found: {}", target.display()));
+    }
+    Ok(target)
+}
+
+/// Project directory to run in: the target itself, or its parent for a file.
+fn run_dir(target: &Path) -> PathBuf {
+    if target.is_dir() {
+        target.to_path_buf()
+    } else {
+        target.parent().unwrap_or(target).to_path_buf()
+    }
+}
+
+fn quote(p: &Path) -> String {
+    format!("'{}'", p.to_string_lossy().replace('\'', r"'\''"))
+}
+
+/// Run `command` in `dir` inside the project's dev environment.
+fn run(command: &str, dir: &Path, workspace_dir: &Path) -> Result<(String, String, bool), String> {
+    let command = crate::dev_env::activate(workspace_dir, command);
+    debug!(command = %command, dir = %dir.display(), "Running lint/format command");
+    let output = run_sandboxed_command(&command, dir)?;
+    Ok((
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.success(),
+    ))
+}
+
+/// Last few lines of a failing tool's stderr, for the error message.
+fn stderr_tail(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.lines().collect();
+    lines[lines.len().saturating_sub(15)..].join("\n")
+}
+
+/// Report the files a fix changed, relative to the workspace.
+fn changed_json(changed: &[PathBuf], workspace_dir: &Path) -> Value {
+    json!(changed.iter().map(|p| display_path(p, workspace_dir)).collect::<Vec<_>>())
+}
+
+/// Run a linter and return its diagnostics; with `fix`, apply its fixes.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_lint(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let target = target_arg(args, workspace_dir)?;
+    let dir = run_dir(&target);
+    let fix = args.get("fix").and_then(|v| v.as_bool()).unwrap_or(false);
+    let linter = match args.get("linter").and_then(|v| v.as_str()) {
+        Some(l) => l.to_string(),
+        None => detect(&dir, LINTERS)
+            .or_else(|| detect(workspace_dir, LINTERS))
+            .ok_or("No linter detected; pass linter (clippy, ruff or eslint)")?
+            .to_string(),
+    };
+
+    let command = match (linter.as_str(), fix) {
+        ("clippy", false) => "cargo clippy --all-targets --message-format=json --quiet".to_string(),
+        ("clippy", true) => {
+            "cargo clippy --all-targets --fix --allow-dirty --allow-staged --message-format=json --quiet"
+                .to_string()
+        }
+        ("ruff", _) => format!(
+            "ruff check --output-format=json{} {}",
+            if fix { " --fix" } else { "" },
+            quote(&target)
+        ),
+        ("eslint", _) => format!(
+            "npx --no-install eslint -f json{} {}",
+            if fix { " --fix" } else { "" },
+            quote(&target)
+        ),
+        (other, _) => return Err(format!("Unknown linter: {}. Use clippy, ruff or eslint", other)),
+    };
+
+    let before = fix.then(|| snapshot(&dir, extensions(&linter)));
+    let (stdout, stderr, ok) = run(&command, &dir, workspace_dir)?;
+    let diagnostics = match linter.as_str() {
+        "clippy" => parse_clippy(&stdout, &dir),
+        "ruff" => parse_ruff(&stdout, &dir),
+        _ => parse_eslint(&stdout, &dir),
+    };
+    if diagnostics.is_empty() && !ok {
+        return Err(format!("{} failed:\n{}", linter, stderr_tail(&stderr)));
+    }
+
+    let mut result = json!({
+        "linter": linter,
+        "count": diagnostics.len(),
+        "errors": diagnostics.iter().filter(|d| d.severity == "error").count(),
+        "diagnostics": diagnostics.iter().take(MAX_DIAGNOSTICS).collect::<Vec<_>>(),
+    });
+    if diagnostics.len() > MAX_DIAGNOSTICS {
+        result["truncated"] = json!(true);
+    }
+    if let Some(before) = before {
+        result["fixed_files"] = changed_json(&changed_files(&before), workspace_dir);
+    }
+    serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
+}
+
+/// Format files, or with `check` only report which would change.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_format(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let target = target_arg(args, workspace_dir)?;
+    let dir = run_dir(&target);
+    let check = args.get("check").and_then(|v| v.as_bool()).unwrap_or(false);
+    let formatter = match args.get("formatter").and_then(|v| v.as_str()) {
+        Some(f) => f.to_string(),
+        None => detect(&dir, FORMATTERS)
+            .or_else(|| detect(workspace_dir, FORMATTERS))
+            .ok_or("No formatter detected; pass formatter (rustfmt, ruff or prettier)")?
+            .to_string(),
+    };
+
+    let command = match formatter.as_str() {
+        // A single file goes to rustfmt directly; directories use cargo fmt.
+        "rustfmt" if target.is_file() => format!(
+            "rustfmt --edition 2024{} {}",
+            if check { " --check" } else { "" },
+            quote(&target)
+        ),
+        "rustfmt" => format!("cargo fmt{}", if check { " -- --check" } else { "" }),
+        "ruff" => format!("ruff format{} {}", if check { " --check" } else { "" }, quote(&target)),
+        "prettier" => format!(
+            "npx --no-install prettier {} {}",
+            if check { "--list-different" } else { "--write" },
+            quote(&target)
+        ),
+        other => return Err(format!("Unknown formatter: {}. Use rustfmt, ruff or prettier", other)),
+    };
+
+    let snapshot_root = if target.is_file() { target.clone() } else { dir.clone() };
+    let before = (!check).then(|| snapshot(&snapshot_root, extensions(&formatter)));
+    let (stdout, stderr, ok) = run(&command, &dir, workspace_dir)?;
+
+    if check {
+        // Every checker exits non-zero when something needs formatting;
+        // the file list is all we need from its output.
+        let needs: Vec<String> = stdout
+            .lines()
+            .chain(stderr.lines())
+            .filter_map(|l| {
+                let l = l.trim();
+                l.strip_prefix("Diff in ")
+                    .map(|r| r.split(" at line").next().unwrap_or(r).trim_end_matches(':'))
+                    .or_else(|| l.strip_prefix("Would reformat: "))
+                    .or_else(|| (formatter == "prettier" && !l.is_empty() && !l.starts_with('[')).then_some(l))
+                    .map(|f| display_path(&dir.join(f), workspace_dir))
+            })
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if needs.is_empty() && !ok && stdout.trim().is_empty() {
+            return Err(format!("{} failed:\n{}", formatter, stderr_tail(&stderr)));
+        }
+        return serde_json::to_string_pretty(&json!({
+            "formatter": formatter,
+            "check": true,
+            "needs_formatting": needs,
+        }))
+        .map_err(|e| e.to_string());
+    }
+
+    if !ok {
+        return Err(format!("{} failed:\n{}", formatter, stderr_tail(&stderr)));
+    }
+    serde_json::to_string_pretty(&json!({
+        "formatter": formatter,
+        "formatted_files": changed_json(&changed_files(&before.unwrap_or_default()), workspace_dir),
+    }))
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clippy() {
+        let line = r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable: `x`","code":{"code":"unused_variables"},"spans":[{"file_name":"src/lib.rs","line_start":3,"column_start":9,"is_primary":true}]}}"#;
+        let noise = r#"{"reason":"build-finished","success":true}"#;
+        let diags = parse_clippy(&format!("{}\n{}\n", line, noise), Path::new("/p"));
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].file, "src/lib.rs");
+        assert_eq!(diags[0].line, 3);
+        assert_eq!(diags[0].code.as_deref(), Some("unused_variables"));
+    }
+
+    #[test]
+    fn test_parse_ruff_and_eslint() {
+        let ruff = r#"[{"code":"F401","message":"`os` imported but unused","filename":"/p/a.py","location":{"row":1,"column":8}}]"#;
+        let d = parse_ruff(ruff, Path::new("/p"));
+        assert_eq!(d[0].file, "a.py");
+        assert_eq!(d[0].code.as_deref(), Some("F401"));
+
+        let eslint = r#"[{"filePath":"/p/src/x.js","messages":[{"ruleId":"no-undef","severity":2,"message":"'y' is not defined.","line":4,"column":1}]}]"#;
+        let d = parse_eslint(eslint, Path::new("/p"));
+        assert_eq!(d[0].file, "src/x.js");
+        assert_eq!(d[0].severity, "error");
+    }
+
+    #[test]
+    fn test_detect_and_changed_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        assert_eq!(detect(dir.path(), LINTERS), Some("ruff"));
+        assert_eq!(detect(dir.path(), FORMATTERS), Some("ruff"));
+
+        std::fs::write(dir.path().join("a.py"), "x=1\n").unwrap();
+        std::fs::write(dir.path().join("b.py"), "y = 2\n").unwrap();
+        let before = snapshot(dir.path(), extensions("ruff"));
+        assert_eq!(before.len(), 2);
+        std::fs::write(dir.path().join("a.py"), "x = 1\n").unwrap();
+        assert_eq!(changed_files(&before), vec![dir.path().join("a.py")]);
+    }
+}
@@ -0,0 +1,334 @@
+//! The `logs` tool: tail a log file or a journald unit, optionally
+//! following it for a bounded time, and filter with regexes.
+//!
+//! Besides the matching lines the result carries counts — total and per
+//! minute, plus the most frequent messages with numbers masked — and each
+//! run fires the `logs` script hook with that summary, so an
+//! `on_logs(event)` hook (or a cron job calling this tool) can alert when
+//! an error rate spikes.
+
+use super::helpers::{is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use regex::{Regex, RegexBuilder};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+/// Longest a `follow` may run, in seconds.
+const MAX_FOLLOW_SECS: u64 = 300;
+
+/// How much of the end of a file to scan for the initial tail.
+const TAIL_WINDOW_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Lines returned by default (counts always cover everything scanned).
+const DEFAULT_MAX_LINES: usize = 200;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+struct Filter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+}
+
+impl Filter {
+    fn from_args(args: &Value) -> Result<Self, String> {
+        let ignore_case = args.get("ignoreCase").and_then(|v| v.as_bool()).unwrap_or(false);
+        let build = |key: &str| -> Result<Option<Regex>, String> {
+            args.get(key)
+                .and_then(|v| v.as_str())
+                .map(|p| {
+                    RegexBuilder::new(p)
+                        .case_insensitive(ignore_case)
+                        .build()
+                        .map_err(|e| format!("Invalid {} regex: {}", key, e))
+                })
+                .transpose()
+        };
+        Ok(Self { include: build("pattern")?, exclude: build("exclude")? })
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        self.include.as_ref().is_none_or(|r| r.is_match(line))
+            && !self.exclude.as_ref().is_some_and(|r| r.is_match(line))
+    }
+}
+
+/// Running totals over every line seen.
+#[derive(Default)]
+struct Tally {
+    scanned: usize,
+    matched: Vec<String>,
+    followed_matches: usize,
+}
+
+impl Tally {
+    fn push(&mut self, line: &str, filter: &Filter, following: bool) {
+        self.scanned += 1;
+        if filter.matches(line) {
+            self.matched.push(line.to_string());
+            if following {
+                self.followed_matches += 1;
+            }
+        }
+    }
+}
+
+/// Collapse a log line to its "shape": numbers, hex ids and timestamps
+/// masked, so repeats of the same message group together.
+fn shape(line: &str) -> String {
+    static MASK: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let mask = MASK.get_or_init(|| Regex::new(r"\b0x[0-9a-fA-F]+\b|\b[0-9a-fA-F]{8,}\b|\d+").unwrap());
+    mask.replace_all(line.trim(), "#").chars().take(160).collect()
+}
+
+fn top_shapes(lines: &[String], limit: usize) -> Vec<Value> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for line in lines {
+        *counts.entry(shape(line)).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+        .into_iter()
+        .take(limit)
+        .map(|(message, count)| json!({ "message": message, "count": count }))
+        .collect()
+}
+
+/// The last `n` lines of a file, and the offset the file ended at.
+fn tail_file(path: &Path, n: usize) -> Result<(Vec<String>, u64), String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let start = len.saturating_sub(TAIL_WINDOW_BYTES);
+    file.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines: Vec<&str> = text.lines().collect();
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0); // probably a partial line
+    }
+    let skip = lines.len().saturating_sub(n);
+    Ok((lines[skip..].iter().map(|s| s.to_string()).collect(), len))
+}
+
+/// Read lines appended to `path` after `offset` until `deadline`.
+fn follow_file(path: &Path, mut offset: u64, deadline: Instant, mut on_line: impl FnMut(&str)) -> Result<(), String> {
+    let mut pending = String::new();
+    while Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+        let Ok(mut file) = std::fs::File::open(path) else {
+            continue; // rotated away; wait for it to come back
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < offset {
+            debug!(path = %path.display(), "Log truncated or rotated; reading from the start");
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        offset += bytes.len() as u64;
+        pending.push_str(&String::from_utf8_lossy(&bytes));
+        while let Some(end) = pending.find('\n') {
+            let line: String = pending.drain(..=end).collect();
+            on_line(line.trim_end_matches(['\r', '\n']));
+        }
+    }
+    if !pending.is_empty() {
+        on_line(&pending);
+    }
+    Ok(())
+}
+
+fn journalctl(unit: &str, args: &Value) -> Command {
+    let mut cmd = Command::new("journalctl");
+    cmd.args(["--no-pager", "-o", "short-iso", "-u", unit]);
+    if let Some(since) = args.get("since").and_then(|v| v.as_str()) {
+        cmd.args(["--since", since]);
+    }
+    if let Some(priority) = args.get("priority").and_then(|v| v.as_str()) {
+        cmd.args(["-p", priority]);
+    }
+    cmd
+}
+
+fn tail_journal(unit: &str, n: usize, args: &Value) -> Result<Vec<String>, String> {
+    let output = journalctl(unit, args)
+        .args(["-n", &n.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run journalctl (is this a systemd system?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!("journalctl failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.starts_with("-- "))
+        .map(String::from)
+        .collect())
+}
+
+fn follow_journal(unit: &str, args: &Value, deadline: Instant, mut on_line: impl FnMut(&str)) -> Result<(), String> {
+    let mut child = journalctl(unit, args)
+        .args(["-f", "-n", "0"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run journalctl: {}", e))?;
+    let stdout = child.stdout.take().ok_or("journalctl produced no output stream")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match rx.recv_timeout(remaining) {
+            Ok(line) if !line.starts_with("-- ") => on_line(&line),
+            Ok(_) => {}
+            Err(mpsc::RecvTimeoutError::Timeout) => break,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Ok(())
+}
+
+/// Tail, follow and filter a log file or journald unit.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_logs(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let lines = args.get("lines").and_then(|v| v.as_u64()).unwrap_or(100).min(100_000) as usize;
+    let follow = args.get("follow").and_then(|v| v.as_u64()).unwrap_or(0).min(MAX_FOLLOW_SECS);
+    let max_lines = args.get("maxLines").and_then(|v| v.as_u64()).map(|n| n as usize).unwrap_or(DEFAULT_MAX_LINES);
+    let threshold = args.get("threshold").and_then(|v| v.as_u64()).map(|n| n as usize);
+    let filter = Filter::from_args(args)?;
+    let mut tally = Tally::default();
+    let deadline = Instant::now() + Duration::from_secs(follow);
+
+    let source = match (
+        args.get("path").and_then(|v| v.as_str()),
+        args.get("unit").and_then(|v| v.as_str()),
+    ) {
+        (Some(path), None) => {
+            let path = resolve_path(workspace_dir, path);
+            if is_protected_path(&path) {
+                return Err(VAULT_ACCESS_DENIED.to_string());
+            }
+            debug!(path = %path.display(), lines, follow, "Tailing log file");
+            let (initial, offset) = tail_file(&path, lines)?;
+            initial.iter().for_each(|l| tally.push(l, &filter, false));
+            if follow > 0 {
+                follow_file(&path, offset, deadline, |l| tally.push(l, &filter, true))?;
+            }
+            path.display().to_string()
+        }
+        (None, Some(unit)) => {
+            debug!(unit, lines, follow, "Tailing journald unit");
+            if lines > 0 {
+                tail_journal(unit, lines, args)?.iter().for_each(|l| tally.push(l, &filter, false));
+            }
+            if follow > 0 {
+                follow_journal(unit, args, deadline, |l| tally.push(l, &filter, true))?;
+            }
+            format!("journald:{}", unit)
+        }
+        (Some(_), Some(_)) => return Err("Give either 'path' or 'unit', not both".to_string()),
+        (None, None) => return Err("Missing required parameter: path or unit".to_string()),
+    };
+
+    let matched = tally.matched.len();
+    let rate = (follow > 0).then(|| (tally.followed_matches as f64 * 600.0 / follow as f64).round() / 10.0);
+    let alert = threshold.is_some_and(|t| matched >= t);
+    let shown = &tally.matched[matched.saturating_sub(max_lines)..];
+    let summary = json!({
+        "source": source,
+        "pattern": args.get("pattern"),
+        "scanned": tally.scanned,
+        "matched": matched,
+        "followedSeconds": follow,
+        "newMatches": tally.followed_matches,
+        "matchesPerMinute": rate,
+        "threshold": threshold,
+        "alert": alert,
+        "top": top_shapes(&tally.matched, 10),
+    });
+    crate::scripting::spawn_hooks("logs", summary.clone(), workspace_dir.to_path_buf());
+
+    let mut result = summary;
+    result["lines"] = json!(shown);
+    result["truncated"] = json!(shown.len() < matched);
+    Ok(result.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_log(dir: &Path) {
+        let lines: Vec<String> = (0..30)
+            .map(|i| match i % 3 {
+                0 => format!("2026-10-15T10:00:{:02} ERROR db timeout after {}ms conn=0x{:x}", i, 100 + i, 4096 + i),
+                1 => format!("2026-10-15T10:00:{:02} WARN slow request id={}", i, i),
+                _ => format!("2026-10-15T10:00:{:02} INFO ok", i),
+            })
+            .collect();
+        std::fs::write(dir.join("app.log"), lines.join("\n") + "\n").unwrap();
+    }
+
+    #[test]
+    fn test_tail_and_filter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write_log(dir.path());
+        let args = json!({ "path": "app.log", "lines": 12, "pattern": "error|warn", "ignoreCase": true, "exclude": "slow", "threshold": 4 });
+        let result: Value = serde_json::from_str(&exec_logs(&args, dir.path()).unwrap()).unwrap();
+        assert_eq!(result["scanned"], 12);
+        assert_eq!(result["matched"], 4);
+        assert_eq!(result["alert"], true);
+        assert_eq!(result["top"][0]["count"], 4);
+        assert_eq!(result["top"][0]["message"], "#-#-#T#:#:# ERROR db timeout after #ms conn=#");
+        assert!(result["lines"][0].as_str().unwrap().contains("ERROR"));
+    }
+
+    #[test]
+    fn test_follow_picks_up_appends() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("live.log");
+        std::fs::write(&path, "start\n").unwrap();
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(400));
+                let mut f = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+                std::io::Write::write_all(&mut f, b"ERROR one\nINFO two\nERROR three\n").unwrap();
+            })
+        };
+        let args = json!({ "path": "live.log", "follow": 1, "pattern": "ERROR", "maxLines": 1 });
+        let result: Value = serde_json::from_str(&exec_logs(&args, dir.path()).unwrap()).unwrap();
+        writer.join().unwrap();
+        assert_eq!(result["newMatches"], 2);
+        assert_eq!(result["matchesPerMinute"], 120.0);
+        assert_eq!(result["lines"], json!(["ERROR three"]));
+        assert_eq!(result["truncated"], true);
+    }
+
+    #[test]
+    fn test_requires_source() {
+        assert!(exec_logs(&json!({}), Path::new(".")).is_err());
+        assert!(exec_logs(&json!({ "path": "a", "unit": "b" }), Path::new(".")).is_err());
+        assert!(exec_logs(&json!({ "path": "a", "pattern": "(" }), Path::new(".")).is_err());
+    }
+}
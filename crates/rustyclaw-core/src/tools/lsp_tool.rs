@@ -0,0 +1,236 @@
+//! Code intelligence tools backed by language servers: lsp_definition,
+//! lsp_references, lsp_diagnostics, lsp_rename.
+
+use super::helpers::{check_write, display_path, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use crate::lsp::{self, LspClient};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+/// Resolve the `path` argument to an existing, unprotected file.
+fn file_arg(args: &Value, workspace_dir: &Path) -> Result<PathBuf, String> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: path".to_string())?;
+    let path = resolve_path(workspace_dir, path);
+    if is_protected_path(&path) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// Resolve the 1-based `line` plus either a 1-based `column` or a `symbol`
+/// on that line to an LSP position (0-based line, UTF-16 character).
+fn position_arg(args: &Value, path: &Path) -> Result<Value, String> {
+    let line = args
+        .get("line")
+        .and_then(|v| v.as_u64())
+        .filter(|l| *l >= 1)
+        .ok_or_else(|| "Missing required parameter: line (1-based)".to_string())? as usize;
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let line_text = text
+        .lines()
+        .nth(line - 1)
+        .ok_or_else(|| format!("{} has fewer than {} lines", path.display(), line))?;
+
+    let col = if let Some(symbol) = args.get("symbol").and_then(|v| v.as_str()) {
+        let byte = line_text
+            .find(symbol)
+            .ok_or_else(|| format!("'{}' not found on line {}", symbol, line))?;
+        line_text[..byte].chars().count()
+    } else {
+        args.get("column")
+            .and_then(|v| v.as_u64())
+            .filter(|c| *c >= 1)
+            .ok_or_else(|| "Provide either column (1-based) or symbol".to_string())? as usize
+            - 1
+    };
+    Ok(json!({ "line": line - 1, "character": lsp::utf16_col(line_text, col) }))
+}
+
+/// Start (or reuse) the server for `path`, sync the file, and build the
+/// `TextDocumentPositionParams` for the request.
+fn prepare(args: &Value, workspace_dir: &Path) -> Result<(Arc<LspClient>, PathBuf, Value), String> {
+    let path = file_arg(args, workspace_dir)?;
+    let position = position_arg(args, &path)?;
+    let client = lsp::client_for(&path, workspace_dir)?;
+    let uri = client.sync_document(&path)?;
+    let params = json!({ "textDocument": { "uri": uri }, "position": position });
+    Ok((client, path, params))
+}
+
+/// `path:line:col: source line` for an LSP `Location` or `LocationLink`.
+fn format_location(location: &Value, workspace_dir: &Path) -> Option<String> {
+    let uri = location["uri"].as_str().or(location["targetUri"].as_str())?;
+    let range = if location["range"].is_object() {
+        &location["range"]
+    } else {
+        &location["targetSelectionRange"]
+    };
+    let line = range["start"]["line"].as_u64()? as usize;
+    let character = range["start"]["character"].as_u64()? as usize;
+    let path = lsp::uri_to_path(uri)?;
+    let source = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|t| t.lines().nth(line).map(|l| l.trim().to_string()))
+        .unwrap_or_default();
+    Some(format!(
+        "{}:{}:{}: {}",
+        display_path(&path, workspace_dir),
+        line + 1,
+        character + 1,
+        source
+    ))
+}
+
+/// Normalise a definition/references result (single, array, or null).
+fn format_locations(result: &Value, workspace_dir: &Path, limit: usize) -> Vec<String> {
+    let items = match result {
+        Value::Array(items) => items.clone(),
+        Value::Null => Vec::new(),
+        single => vec![single.clone()],
+    };
+    items
+        .iter()
+        .filter_map(|l| format_location(l, workspace_dir))
+        .take(limit)
+        .collect()
+}
+
+/// Jump to where the symbol at a position is defined.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_lsp_definition(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let (client, path, params) = prepare(args, workspace_dir)?;
+    debug!(path = %path.display(), "LSP definition");
+    let result = client.request("textDocument/definition", params)?;
+    let locations = format_locations(&result, workspace_dir, 50);
+    if locations.is_empty() {
+        return Ok("No definition found (the language server may still be indexing).".to_string());
+    }
+    Ok(locations.join("\n"))
+}
+
+/// Find every reference to the symbol at a position.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_lsp_references(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let include_declaration = args
+        .get("includeDeclaration")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let (client, path, mut params) = prepare(args, workspace_dir)?;
+    debug!(path = %path.display(), "LSP references");
+    params["context"] = json!({ "includeDeclaration": include_declaration });
+    let result = client.request("textDocument/references", params)?;
+    let total = result.as_array().map(Vec::len).unwrap_or(0);
+    let locations = format_locations(&result, workspace_dir, 200);
+    if locations.is_empty() {
+        return Ok("No references found (the language server may still be indexing).".to_string());
+    }
+    let mut output = format!("{} reference(s):\n{}", total, locations.join("\n"));
+    if total > locations.len() {
+        output.push_str(&format!("\n\n(Showing first {})", locations.len()));
+    }
+    Ok(output)
+}
+
+/// Compiler/linter diagnostics for a file.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_lsp_diagnostics(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let path = file_arg(args, workspace_dir)?;
+    let wait = args.get("waitSecs").and_then(|v| v.as_u64()).unwrap_or(10).min(120);
+    let client = lsp::client_for(&path, workspace_dir)?;
+    let diagnostics = client.diagnostics(&path, Duration::from_secs(wait))?;
+    debug!(path = %path.display(), count = diagnostics.len(), "LSP diagnostics");
+
+    let shown = display_path(&path, workspace_dir);
+    if diagnostics.is_empty() {
+        return Ok(format!("No diagnostics for {}.", shown));
+    }
+    let lines: Vec<String> = diagnostics
+        .iter()
+        .map(|d| {
+            let severity = match d["severity"].as_u64() {
+                Some(1) => "error",
+                Some(2) => "warning",
+                Some(3) => "info",
+                _ => "hint",
+            };
+            let source = d["source"]
+                .as_str()
+                .map(|s| format!(" [{}]", s))
+                .unwrap_or_default();
+            format!(
+                "{}:{}:{}: {}: {}{}",
+                shown,
+                d["range"]["start"]["line"].as_u64().unwrap_or(0) + 1,
+                d["range"]["start"]["character"].as_u64().unwrap_or(0) + 1,
+                severity,
+                d["message"].as_str().unwrap_or("").lines().next().unwrap_or(""),
+                source
+            )
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// Rename the symbol at a position across the project.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_lsp_rename(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let new_name = args
+        .get("newName")
+        .and_then(|v| v.as_str())
+        .filter(|n| !n.trim().is_empty())
+        .ok_or_else(|| "Missing required parameter: newName".to_string())?
+        .to_string();
+    let (client, path, mut params) = prepare(args, workspace_dir)?;
+    debug!(path = %path.display(), new_name = %new_name, "LSP rename");
+    params["newName"] = json!(new_name);
+    let edit = client.request("textDocument/rename", params)?;
+    if edit.is_null() {
+        return Err("The language server can't rename the symbol at this position.".to_string());
+    }
+
+    // Compute every new file before writing any, so a bad edit leaves the
+    // tree untouched.
+    let mut updates = Vec::new();
+    let mut edit_count = 0;
+    for (file, edits) in lsp::workspace_edit_files(&edit)? {
+        if is_protected_path(&file) {
+            return Err(VAULT_ACCESS_DENIED.to_string());
+        }
+        check_write(&file, workspace_dir)?;
+        let text = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let updated = lsp::apply_text_edits(&text, &edits)
+            .map_err(|e| format!("{}: {}", file.display(), e))?;
+        edit_count += edits.len();
+        updates.push((file, updated));
+    }
+    if updates.is_empty() {
+        return Ok("Nothing to rename.".to_string());
+    }
+    for (file, updated) in &updates {
+        std::fs::write(file, updated).map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+        // Keep the server's view in sync with what we wrote.
+        let _ = client.sync_document(file);
+    }
+
+    let files: Vec<String> = updates
+        .iter()
+        .map(|(f, _)| format!("  {}", display_path(f, workspace_dir)))
+        .collect();
+    Ok(format!(
+        "Renamed to '{}': {} edit(s) in {} file(s):\n{}",
+        new_name,
+        edit_count,
+        files.len(),
+        files.join("\n")
+    ))
+}
@@ -0,0 +1,95 @@
+//! ffmpeg/ffprobe helpers shared by the media tools.
+
+use std::path::Path;
+use std::process::Command;
+use tracing::debug;
+
+/// Error when ffmpeg (or ffprobe) isn't on the PATH.
+pub(crate) fn require(binary: &str) -> Result<(), String> {
+    which::which(binary).map(|_| ()).map_err(|_| {
+        format!(
+            "{} not found. Install ffmpeg (e.g. `apt install ffmpeg` or `brew install ffmpeg`).",
+            binary
+        )
+    })
+}
+
+/// Duration of a media file in seconds, via ffprobe.
+pub(crate) fn probe_duration(path: &Path) -> Option<f64> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path)
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|d| d.is_finite() && *d > 0.0)
+}
+
+/// Extract mono audio from `input` into `output`, optionally just the span
+/// `[start, start + length)`.  `codec` is the encoder arguments, e.g.
+/// `["-ar", "16000", "-c:a", "pcm_s16le"]`.
+pub(crate) fn extract_audio(
+    input: &Path,
+    output: &Path,
+    span: Option<(f64, f64)>,
+    codec: &[&str],
+) -> Result<(), String> {
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args(["-nostdin", "-hide_banner", "-loglevel", "error", "-y"]);
+    if let Some((start, _)) = span {
+        cmd.args(["-ss", &format!("{:.3}", start)]);
+    }
+    cmd.arg("-i").arg(input);
+    if let Some((_, length)) = span {
+        cmd.args(["-t", &format!("{:.3}", length)]);
+    }
+    cmd.args(["-vn", "-ac", "1"]).args(codec).arg(output);
+    debug!(input = %input.display(), output = %output.display(), ?span, "Extracting audio");
+
+    let result = cmd.output().map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !result.status.success() {
+        return Err(format!("ffmpeg failed: {}", String::from_utf8_lossy(&result.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// `HH:MM:SS` for a number of seconds.
+pub(crate) fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    format!("{:02}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60)
+}
+
+/// Parse `[[HH:]MM:]SS[.fff]` into seconds.
+pub(crate) fn parse_timestamp(text: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    for part in &parts {
+        let value: f64 = part.parse().ok()?;
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamps() {
+        assert_eq!(format_timestamp(3725.9), "01:02:05");
+        assert_eq!(parse_timestamp("01:02:05.5"), Some(3725.5));
+        assert_eq!(parse_timestamp("90"), Some(90.0));
+        assert_eq!(parse_timestamp("1:30"), Some(90.0));
+        assert_eq!(parse_timestamp("a:b"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+    }
+}
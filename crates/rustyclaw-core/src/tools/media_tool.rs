@@ -0,0 +1,434 @@
+//! The `media_convert` tool: common ffmpeg jobs with validated arguments.
+//!
+//! The model picks an operation and a few typed options; the ffmpeg command
+//! line is built here, never by the model.  Jobs run as process-manager
+//! sessions with `-progress` output, so a long encode returns a session id
+//! after `yieldMs` and can be followed with `operation: "status"` (or
+//! polled and killed with the `process` tool).
+
+use super::helpers::{check_write, is_protected_path, process_manager, resolve_path, VAULT_ACCESS_DENIED};
+use super::media::{self, parse_timestamp};
+use crate::process_manager::{ExecSession, SessionStatus};
+use crate::progress::{Progress, Unit};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+/// Prefix of the process-manager command label for conversion sessions.
+const SESSION_PREFIX: &str = "media_convert ";
+
+/// Hard cap on a single job.
+const JOB_TIMEOUT: Duration = Duration::from_secs(6 * 3600);
+
+/// Output files and expected durations of running jobs, by session id.
+static JOBS: Mutex<Option<HashMap<String, Job>>> = Mutex::new(None);
+
+#[derive(Debug, Clone)]
+struct Job {
+    output: PathBuf,
+    duration: Option<f64>,
+}
+
+/// Encoder arguments per output format.
+fn codec_args(format: &str) -> Option<&'static [&'static str]> {
+    let args: &'static [&'static str] = match format {
+        "mp3" => &["-vn", "-c:a", "libmp3lame", "-q:a", "2"],
+        "ogg" => &["-vn", "-c:a", "libvorbis", "-q:a", "5"],
+        "opus" => &["-vn", "-c:a", "libopus", "-b:a", "96k"],
+        "m4a" => &["-vn", "-c:a", "aac", "-b:a", "160k"],
+        "wav" => &["-vn", "-c:a", "pcm_s16le"],
+        "flac" => &["-vn", "-c:a", "flac"],
+        "mp4" => &["-c:v", "libx264", "-crf", "23", "-preset", "medium", "-pix_fmt", "yuv420p", "-c:a", "aac", "-movflags", "+faststart"],
+        "mkv" => &["-c:v", "libx264", "-crf", "23", "-preset", "medium", "-c:a", "aac"],
+        "webm" => &["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0", "-c:a", "libopus"],
+        "gif" => &["-an"],
+        _ => return None,
+    };
+    Some(args)
+}
+
+fn is_audio_format(format: &str) -> bool {
+    matches!(format, "mp3" | "ogg" | "opus" | "m4a" | "wav" | "flac")
+}
+
+/// A validated conversion.
+#[derive(Debug, Clone, PartialEq)]
+struct Plan {
+    input: PathBuf,
+    output: PathBuf,
+    format: String,
+    start: Option<f64>,
+    end: Option<f64>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+impl Plan {
+    fn from_args(args: &Value, operation: &str, workspace_dir: &Path) -> Result<Self, String> {
+        let input = args
+            .get("input")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing required parameter: input")?;
+        let input = resolve_path(workspace_dir, input);
+        if !input.is_file() {
+            return Err(format!("File not found: {}", input.display()));
+        }
+        let input_ext = input.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+
+        let format = match (operation, args.get("format").and_then(|v| v.as_str())) {
+            (_, Some(f)) => f.to_lowercase(),
+            ("extract_audio", None) => "mp3".to_string(),
+            ("convert", None) => return Err("convert needs a target format".to_string()),
+            // resize and trim keep the container.
+            (_, None) => input_ext.clone(),
+        };
+        if codec_args(&format).is_none() {
+            return Err(format!(
+                "Unsupported format '{}'; use mp3, ogg, opus, m4a, wav, flac, mp4, mkv, webm or gif",
+                format
+            ));
+        }
+        if operation == "extract_audio" && !is_audio_format(&format) {
+            return Err(format!("extract_audio needs an audio format, not '{}'", format));
+        }
+
+        let time = |key: &str| -> Result<Option<f64>, String> {
+            match args.get(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::Number(n)) => n
+                    .as_f64()
+                    .filter(|v| *v >= 0.0)
+                    .map(Some)
+                    .ok_or_else(|| format!("Invalid {}", key)),
+                Some(Value::String(s)) => parse_timestamp(s)
+                    .map(Some)
+                    .ok_or_else(|| format!("Invalid {} '{}'; use seconds or HH:MM:SS(.mmm)", key, s)),
+                Some(_) => Err(format!("Invalid {}", key)),
+            }
+        };
+        let (start, end) = (time("start")?, time("end")?);
+        if let (Some(s), Some(e)) = (start, end) {
+            if e <= s {
+                return Err("end must be after start".to_string());
+            }
+        }
+        if operation == "trim" && start.is_none() && end.is_none() {
+            return Err("trim needs start and/or end".to_string());
+        }
+
+        let side = |key: &str| -> Result<Option<u32>, String> {
+            match args.get(key).and_then(|v| v.as_u64()) {
+                None => Ok(None),
+                Some(v) if (16..=7680).contains(&v) => Ok(Some(v as u32 & !1)),
+                Some(v) => Err(format!("{} {} is out of range (16-7680)", key, v)),
+            }
+        };
+        let (width, height) = (side("width")?, side("height")?);
+        if operation == "resize" && width.is_none() && height.is_none() {
+            return Err("resize needs width and/or height".to_string());
+        }
+        if is_audio_format(&format) && (width.is_some() || height.is_some()) {
+            return Err(format!("'{}' is an audio format; width/height don't apply", format));
+        }
+
+        let output = match args.get("output").and_then(|v| v.as_str()) {
+            Some(o) => resolve_path(workspace_dir, o),
+            None => {
+                let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+                let suffix = match operation {
+                    "trim" => "-trimmed".to_string(),
+                    "resize" => {
+                        let dim = |v: Option<u32>| v.map_or("auto".to_string(), |v| v.to_string());
+                        format!("-{}x{}", dim(width), dim(height))
+                    }
+                    _ if format == input_ext => "-converted".to_string(),
+                    _ => String::new(),
+                };
+                input.with_file_name(format!("{}{}.{}", stem, suffix, format))
+            }
+        };
+        if output == input {
+            return Err("Output would overwrite the input; pick another output path".to_string());
+        }
+        if output.exists() && args.get("overwrite").and_then(|v| v.as_bool()) != Some(true) {
+            return Err(format!("{} already exists; pass overwrite: true to replace it", output.display()));
+        }
+        for path in [&input, &output] {
+            if is_protected_path(path) {
+                return Err(VAULT_ACCESS_DENIED.to_string());
+            }
+        }
+        check_write(&output, workspace_dir)?;
+
+        Ok(Plan { input, output, format, start, end, width, height })
+    }
+
+    /// ffmpeg arguments, after the program name.
+    fn ffmpeg_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = ["-nostdin", "-hide_banner", "-loglevel", "error", "-y", "-progress", "pipe:1", "-nostats"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        if let Some(start) = self.start {
+            args.extend(["-ss".to_string(), format!("{:.3}", start)]);
+        }
+        args.extend(["-i".to_string(), self.input.display().to_string()]);
+        if let Some(end) = self.end {
+            args.extend(["-t".to_string(), format!("{:.3}", end - self.start.unwrap_or(0.0))]);
+        }
+
+        let mut filters = Vec::new();
+        if self.format == "gif" {
+            filters.push("fps=12".to_string());
+        }
+        let (width, height) = match (self.width, self.height, self.format.as_str()) {
+            // Full-size GIFs are huge; default to 480 wide.
+            (None, None, "gif") => (Some(480), None),
+            other => (other.0, other.1),
+        };
+        if width.is_some() || height.is_some() {
+            // -2 keeps the aspect ratio with an even size, which encoders need.
+            let side = |v: Option<u32>| v.map_or("-2".to_string(), |v| v.to_string());
+            filters.push(format!("scale={}:{}", side(width), side(height)));
+        }
+        if !filters.is_empty() {
+            args.extend(["-vf".to_string(), filters.join(",")]);
+        }
+        args.extend(codec_args(&self.format).unwrap_or(&[]).iter().map(|s| s.to_string()));
+        args.push(self.output.display().to_string());
+        args
+    }
+
+    /// Length of the output, when the input's duration is known.
+    fn expected_duration(&self) -> Option<f64> {
+        let total = media::probe_duration(&self.input)?;
+        let end = self.end.unwrap_or(total).min(total);
+        Some((end - self.start.unwrap_or(0.0)).max(0.0))
+    }
+}
+
+/// Latest encoded position in seconds from ffmpeg `-progress` output, and
+/// whether it reported the end.
+fn parse_progress(output: &str) -> (Option<f64>, bool) {
+    let mut position = None;
+    let mut ended = false;
+    for line in output.lines() {
+        if let Some(us) = line.strip_prefix("out_time_us=").or_else(|| line.strip_prefix("out_time_ms=")) {
+            // Both keys are in microseconds.
+            if let Ok(us) = us.trim().parse::<i64>() {
+                position = Some(us.max(0) as f64 / 1_000_000.0);
+            }
+        } else if line.trim() == "progress=end" {
+            ended = true;
+        }
+    }
+    (position, ended)
+}
+
+/// ffmpeg's own messages, without the progress key=value lines.
+fn error_lines(output: &str) -> String {
+    output
+        .lines()
+        .filter(|l| !l.contains('=') || l.contains(' '))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Describe a session: running with progress, done, or failed.
+fn job_status(id: &str) -> Result<Value, String> {
+    let manager = process_manager();
+    let mut mgr = manager
+        .lock()
+        .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+    let session = mgr.get_mut(id).ok_or_else(|| format!("No session found: {}", id))?;
+    if !session.command.starts_with(SESSION_PREFIX) {
+        return Err(format!("Session {} is not a media_convert job", id));
+    }
+    while session.try_read_output() {}
+    session.check_exit();
+    let output = session.full_output().to_string();
+    let status = session.status.clone();
+    let elapsed = session.elapsed().as_secs();
+
+    let job = JOBS.lock().ok().and_then(|jobs| jobs.as_ref()?.get(id).cloned());
+    let (position, _) = parse_progress(&output);
+    let percent = match (position, job.as_ref().and_then(|j| j.duration)) {
+        (Some(p), Some(d)) if d > 0.0 => Some(((p / d) * 100.0).clamp(0.0, 100.0).round()),
+        _ => None,
+    };
+    let out = job.as_ref().map(|j| j.output.display().to_string());
+
+    Ok(match status {
+        SessionStatus::Running => json!({
+            "sessionId": id,
+            "status": "running",
+            "percent": percent,
+            "elapsedSecs": elapsed,
+            "output": out,
+        }),
+        SessionStatus::Exited(0) => {
+            mgr.remove(id);
+            if let Ok(mut jobs) = JOBS.lock() {
+                jobs.get_or_insert_with(HashMap::new).remove(id);
+            }
+            let size = out.as_ref().and_then(|o| std::fs::metadata(o).ok()).map(|m| m.len());
+            json!({ "sessionId": id, "status": "done", "output": out, "bytes": size, "elapsedSecs": elapsed })
+        }
+        other => json!({
+            "sessionId": id,
+            "status": "failed",
+            "reason": format!("{:?}", other),
+            "error": error_lines(&output),
+        }),
+    })
+}
+
+/// Start ffmpeg for `plan` as a background session.
+fn start_job(plan: &Plan) -> Result<String, String> {
+    let child = Command::new("ffmpeg")
+        .args(plan.ffmpeg_args())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+    let label = format!("{}{} -> {}", SESSION_PREFIX, plan.input.display(), plan.output.display());
+    let working_dir = plan.input.parent().map(|p| p.display().to_string()).unwrap_or_default();
+    let id = process_manager()
+        .lock()
+        .map_err(|_| "Failed to acquire process manager lock".to_string())?
+        .insert(ExecSession::new(label, working_dir, Some(JOB_TIMEOUT), child));
+    if let Ok(mut jobs) = JOBS.lock() {
+        jobs.get_or_insert_with(HashMap::new).insert(
+            id.clone(),
+            Job { output: plan.output.clone(), duration: plan.expected_duration() },
+        );
+    }
+    Ok(id)
+}
+
+/// Convert, trim, resize or extract audio from a media file with ffmpeg.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_media_convert(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let operation = args
+        .get("operation")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing required parameter: operation")?;
+
+    if operation == "status" {
+        let id = args
+            .get("sessionId")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing required parameter: sessionId")?;
+        return Ok(job_status(id)?.to_string());
+    }
+    if !matches!(operation, "convert" | "extract_audio" | "resize" | "trim") {
+        return Err(format!(
+            "Unknown operation: {}. Use convert, extract_audio, resize, trim or status",
+            operation
+        ));
+    }
+
+    let plan = Plan::from_args(args, operation, workspace_dir)?;
+    media::require("ffmpeg")?;
+    if let Some(parent) = plan.output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    debug!(input = %plan.input.display(), output = %plan.output.display(), format = %plan.format, "Starting conversion");
+    let id = start_job(&plan)?;
+
+    // Short jobs finish inline; long ones are left running in the background.
+    let yield_ms = args.get("yieldMs").and_then(|v| v.as_u64()).unwrap_or(10_000);
+    let deadline = Instant::now() + Duration::from_millis(yield_ms);
+    let label = format!(
+        "Converting {}",
+        plan.input.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()
+    );
+    let mut progress = Progress::start(label, Unit::Percent, plan.expected_duration().map(|_| 100));
+    loop {
+        let status = job_status(&id)?;
+        let running = status["status"] == "running";
+        if let Some(percent) = status["percent"].as_f64() {
+            progress.set(percent as u64);
+        }
+        if !running || Instant::now() >= deadline {
+            if status["status"] == "failed" {
+                return Err(format!("ffmpeg failed: {}", status["error"].as_str().unwrap_or("unknown error")));
+            }
+            if running {
+                return Ok(json!({
+                    "sessionId": id,
+                    "status": "running",
+                    "percent": status["percent"],
+                    "output": plan.output.display().to_string(),
+                    "message": format!(
+                        "Still converting; check with operation=status and sessionId '{}'.",
+                        id
+                    ),
+                })
+                .to_string());
+            }
+            return Ok(format!(
+                "Converted {} to {} ({} bytes).\n\nMEDIA: {}",
+                plan.input.display(),
+                plan.output.display(),
+                status["bytes"],
+                plan.output.display()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(args: Value, operation: &str) -> Result<Plan, String> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut args = args;
+        args["input"] = json!("Cargo.toml");
+        Plan::from_args(&args, operation, dir)
+    }
+
+    #[test]
+    fn test_plan_validation() {
+        assert!(plan(json!({}), "convert").unwrap_err().contains("target format"));
+        assert!(plan(json!({ "format": "exe" }), "convert").unwrap_err().contains("Unsupported format"));
+        assert!(plan(json!({ "format": "mp4" }), "extract_audio").unwrap_err().contains("audio format"));
+        assert!(plan(json!({ "format": "mp3" }), "trim").unwrap_err().contains("start and/or end"));
+        assert!(plan(json!({ "format": "mp4", "start": "1:00", "end": 30 }), "trim").unwrap_err().contains("after start"));
+        assert!(plan(json!({ "format": "mp4", "width": 5 }), "resize").unwrap_err().contains("out of range"));
+        assert!(plan(json!({ "format": "mp3", "width": 640 }), "convert").unwrap_err().contains("audio format"));
+    }
+
+    #[test]
+    fn test_plan_output_and_args() {
+        let p = plan(json!({ "format": "mp4", "width": 641, "start": "00:00:05", "end": 20 }), "resize").unwrap();
+        assert!(p.output.ends_with("Cargo-640xauto.mp4"));
+        let args = p.ffmpeg_args();
+        let joined = args.join(" ");
+        assert!(joined.contains("-ss 5.000 -i"));
+        assert!(joined.contains("-t 15.000"));
+        assert!(joined.contains("-vf scale=640:-2"));
+        assert!(joined.ends_with("Cargo-640xauto.mp4"));
+
+        let p = plan(json!({}), "extract_audio").unwrap();
+        assert!(p.output.ends_with("Cargo.mp3"));
+        assert!(p.ffmpeg_args().contains(&"libmp3lame".to_string()));
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        let out = "frame=10\nout_time_us=1500000\nprogress=continue\nout_time_ms=3000000\nprogress=end\n";
+        assert_eq!(parse_progress(out), (Some(3.0), true));
+        assert_eq!(parse_progress(""), (None, false));
+        assert_eq!(error_lines("out_time_us=1\nUnknown encoder 'libx264'\n"), "Unknown encoder 'libx264'");
+    }
+}
@@ -1,4 +1,8 @@
 //! Memory tools: memory_search and memory_get.
+//!
+//! Both take an optional `scope`, a memory namespace such as
+//! `memory/people/bob`; the messenger gateway sets it for non-owners so
+//! they only see their own memory (see [`crate::users::scoped_args`]).
 
 use serde_json::Value;
 use std::path::Path;
@@ -41,16 +45,25 @@ pub fn exec_memory_search(args: &Value, workspace_dir: &Path) -> Result<String,
         .and_then(|v| v.as_f64())
         .unwrap_or(DEFAULT_HALF_LIFE_DAYS);
 
-    debug!(max_results, min_score, use_recency, half_life_days, "Searching memory");
+    let scope = scope_arg(args)?;
+
+    debug!(max_results, min_score, use_recency, half_life_days, scope, "Searching memory");
 
     // Build index and search
     let index = crate::memory::MemoryIndex::index_workspace(workspace_dir)?;
-    
+
+    // A scoped search ranks everything, then keeps the scope's hits.
+    let limit = if scope.is_some() { usize::MAX } else { max_results };
     let results = if use_recency {
-        index.search_with_decay(query, max_results, half_life_days)
+        index.search_with_decay(query, limit, half_life_days)
     } else {
-        index.search(query, max_results)
+        index.search(query, limit)
     };
+    let results: Vec<_> = results
+        .into_iter()
+        .filter(|r| scope.is_none_or(|scope| in_scope(&r.chunk.path, scope)))
+        .take(max_results)
+        .collect();
 
     if results.is_empty() {
         return Ok("No matching memories found.".to_string());
@@ -120,7 +133,70 @@ pub fn exec_memory_get(args: &Value, workspace_dir: &Path) -> Result<String, Str
         .and_then(|v| v.as_u64())
         .map(|n| n as usize);
 
+    if let Some(scope) = scope_arg(args)? {
+        if !in_scope(path, scope) {
+            return Err(format!(
+                "'{}' is outside your memory. You can read files under {}/ (and {}.md).",
+                path, scope, scope
+            ));
+        }
+    }
+
     debug!(path, from_line, num_lines, "Reading memory file");
 
     crate::memory::read_memory_file(workspace_dir, path, from_line, num_lines)
 }
+
+/// The `scope` argument, if any.
+fn scope_arg(args: &Value) -> Result<Option<&str>, String> {
+    match args.get("scope") {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(scope)) if !scope.contains("..") => Ok(Some(scope.trim_end_matches('/'))),
+        Some(_) => Err("Invalid scope".to_string()),
+    }
+}
+
+/// Whether a memory path lies in `scope`: the directory itself or the
+/// `<scope>.md` file next to it.
+fn in_scope(path: &str, scope: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    !path.contains("..")
+        && (path.starts_with(&format!("{}/", scope)) || path == format!("{}.md", scope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        let people = dir.path().join("memory/people");
+        std::fs::create_dir_all(people.join("bob")).unwrap();
+        std::fs::write(dir.path().join("MEMORY.md"), "The owner's bank is Zorbank.\n").unwrap();
+        std::fs::write(people.join("alice.md"), "Alice banks at Zorbank too.\n").unwrap();
+        std::fs::write(people.join("bob/notes.md"), "Bob likes Zorbank coffee.\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scoped_memory_get() {
+        let dir = workspace();
+        let scoped = |path: &str| json!({"path": path, "scope": "memory/people/bob"});
+        assert!(exec_memory_get(&scoped("MEMORY.md"), dir.path()).is_err());
+        assert!(exec_memory_get(&scoped("memory/people/alice.md"), dir.path()).is_err());
+        assert!(exec_memory_get(&scoped("memory/people/bob/../alice.md"), dir.path()).is_err());
+        assert!(exec_memory_get(&scoped("memory/people/bob/notes.md"), dir.path()).unwrap().contains("coffee"));
+        assert!(exec_memory_get(&json!({"path": "MEMORY.md"}), dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_scoped_memory_search() {
+        let dir = workspace();
+        let args = json!({"query": "Zorbank", "scope": "memory/people/bob", "minScore": 0.0});
+        let out = exec_memory_search(&args, dir.path()).unwrap();
+        assert!(out.contains("memory/people/bob/notes.md"));
+        assert!(!out.contains("MEMORY.md"));
+        assert!(!out.contains("alice"));
+    }
+}
@@ -9,19 +9,51 @@ pub mod agent_setup;
 use tracing::{debug, warn, instrument};
 
 mod helpers;
+mod binary;
+mod edit_conflict;
 mod file;
+mod tree_tool;
+mod lint_tool;
+mod deps_tool;
+mod review_tool;
+mod snapshots_tool;
+mod contacts_tool;
+mod mqtt_tool;
+mod cloud_tool;
+mod charts;
+mod report_tool;
+mod table;
+mod plot_tool;
+mod xlsx_tool;
+mod ocr_tool;
+mod qr_tool;
+mod media;
+mod transcribe_tool;
+mod media_tool;
+mod translate_tool;
+mod calc_tool;
+mod lsp_tool;
 mod runtime;
+mod serial_tool;
 mod web;
 mod qmd_tools;
 mod cron_tool;
+mod tasks_tool;
+mod conversations_tool;
+mod plan_tool;
+mod script_tool;
 mod sessions_tools;
 mod patch;
 mod gateway_tools;
 mod devices;
+mod discovery;
+mod ble;
 mod browser;
 mod skills_tools;
 mod secrets_tools;
 mod system_tools;
+mod sysinfo_tool;
+mod logs_tool;
 mod sysadmin;
 pub mod exo_ai;
 pub mod npm;
@@ -36,22 +68,84 @@ use npm::exec_npm_manage;
 // Agent setup orchestrator
 use agent_setup::exec_agent_setup;
 mod params;
+mod dry_run;
+mod file_index;
+mod file_locks;
+mod registry;
+mod schema_budget;
+mod timeouts;
+mod tool_groups;
+
+// Dry-run mode (simulate mutating tools)
+pub use dry_run::{
+    DryRunSession, current_dry_run_session, dry_run_session_scope, force_dry_run, is_dry_run, is_mutating,
+    set_dry_run, set_session_dry_run, sync_dry_run_session_scope,
+};
+// Background file index for find_files and search_files
+pub use file_index::{run_file_index_loop, set_config as set_file_index, FileIndexConfig};
+// Advisory file locks between sessions
+pub use file_locks::{release_owner as release_file_locks, table as file_lock_table};
+// Per-tool timeouts and the turn deadline
+pub use timeouts::{set_timeouts, timeouts, TimeoutConfig, TurnDeadline};
+// Tool groups loaded on demand
+pub use tool_groups::{load_requested, turn_tools};
+// Trimmed tool schemas and the tool router
+pub use schema_budget::{
+    parse_router_reply, router_prompt, schema_budget, set_schema_budget, SchemaBudget, ToolRouterConfig,
+};
+pub use registry::{registry, schema_for, Tool, ToolRegistry, TypedTool};
 
 // Re-export helpers for external use
 pub use helpers::{
     process_manager, set_credentials_dir, is_protected_path,
-    expand_tilde, VAULT_ACCESS_DENIED, command_references_credentials,
-    init_sandbox, sandbox, run_sandboxed_command,
+    expand_tilde, resolve_path, VAULT_ACCESS_DENIED, command_references_credentials,
+    init_sandbox, sandbox, run_sandboxed_command, set_write_policy, check_write,
     set_vault, vault, SharedVault,
-    sanitize_tool_output,
+    sanitize_tool_output, set_extra_roots, extra_roots,
 };
 
 // File operations
-use file::{exec_read_file, exec_write_file, exec_edit_file, exec_list_directory, exec_search_files, exec_find_files};
+use file::{exec_read_file, exec_write_file, exec_edit_file, exec_delete_file, exec_list_directory, exec_search_files, exec_find_files};
+use tree_tool::exec_tree;
+
+// Code intelligence (language servers)
+use lsp_tool::{exec_lsp_definition, exec_lsp_references, exec_lsp_diagnostics, exec_lsp_rename};
+
+// Linters and formatters
+use lint_tool::{exec_lint, exec_format};
+
+// Dependency management
+use deps_tool::exec_deps;
+
+// Code review
+use review_tool::exec_review_diff;
+
+// Workspace snapshots
+use snapshots_tool::exec_snapshots;
+use tool_groups::exec_request_tools;
+
+// Contact book
+use contacts_tool::exec_contacts;
+
+// MQTT
+use mqtt_tool::exec_mqtt;
+
+// Cloud spend
+use cloud_tool::exec_cloud_status;
+
+// Reports
+use report_tool::exec_render_report;
+
+// Data
+use plot_tool::exec_plot;
+use xlsx_tool::exec_xlsx_write;
 
 // Runtime operations
 use runtime::{exec_execute_command, exec_process};
 
+// Serial ports
+use serial_tool::exec_serial;
+
 // Web operations
 use web::{exec_web_fetch, exec_web_search};
 
@@ -61,8 +155,20 @@ use qmd_tools::{exec_qmd_search, exec_qmd_deep_search, exec_qmd_get};
 // Cron operations
 use cron_tool::exec_cron;
 
+// Task queue operations
+use tasks_tool::exec_tasks;
+
+// Persisted messenger conversations
+use conversations_tool::exec_conversations;
+
+// Plan operations
+use plan_tool::exec_plan;
+
+// Script tool
+use script_tool::exec_script_run;
+
 // Session operations
-use sessions_tools::{exec_sessions_list, exec_sessions_spawn, exec_sessions_send, exec_sessions_history, exec_session_status, exec_agents_list};
+use sessions_tools::{exec_sessions_list, exec_sessions_spawn, exec_sessions_worktree, exec_sessions_send, exec_sessions_history, exec_session_status, exec_agents_list, exec_orchestrate};
 
 // Patch operations
 use patch::exec_apply_patch;
@@ -70,12 +176,31 @@ use patch::exec_apply_patch;
 // Gateway operations
 use gateway_tools::{exec_gateway, exec_message, exec_tts, exec_image};
 
+// OCR
+use ocr_tool::exec_ocr;
+
+// QR codes
+use qr_tool::exec_qr;
+
+// Media
+use transcribe_tool::exec_transcribe;
+use media_tool::exec_media_convert;
+
+// Translation
+use translate_tool::exec_translate;
+
+// Calculation and conversions
+use calc_tool::exec_calc;
+
 // Device operations
 use devices::{exec_nodes, exec_canvas};
 
 // Browser automation (separate module with feature-gated implementation)
 use browser::exec_browser;
 
+// Bluetooth LE (feature-gated implementation)
+use ble::exec_ble;
+
 // Skill operations
 use skills_tools::{exec_skill_list, exec_skill_search, exec_skill_install, exec_skill_info, exec_skill_enable, exec_skill_link_secret, exec_skill_create};
 
@@ -90,6 +215,12 @@ use system_tools::{
     exec_audit_sensitive, exec_secure_delete, exec_summarize_file,
 };
 
+// Structured system information (local or node)
+use sysinfo_tool::exec_system_info;
+
+// Log tailing and analysis
+use logs_tool::exec_logs;
+
 // System administration tools
 use sysadmin::{
     exec_pkg_manage, exec_net_info, exec_net_scan,
@@ -185,6 +316,86 @@ impl ToolPermission {
     }
 }
 
+/// Tool actions with their own permission entry, keyed `"<tool>.<action>"`
+/// (e.g. `nodes.reboot`).  Without an entry they require confirmation,
+/// even when the tool itself is allowed.
+const GATED_ACTIONS: &[(&str, &[&str])] = &[
+    ("nodes", &["wake", "sleep", "reboot"]),
+    ("ble", &["write"]),
+    ("delete_file", &["delete"]),
+];
+
+/// Effective permission for a tool call, taking gated actions into account.
+pub fn permission_for(
+    permissions: &std::collections::HashMap<String, ToolPermission>,
+    name: &str,
+    args: &Value,
+) -> ToolPermission {
+    let tool_permission = permissions.get(name).cloned().unwrap_or_default();
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
+    let gated = GATED_ACTIONS
+        .iter()
+        .any(|(tool, actions)| *tool == name && actions.contains(&action));
+    if !gated {
+        return tool_permission;
+    }
+    match permissions.get(&format!("{}.{}", name, action)) {
+        Some(permission) => permission.clone(),
+        // A blocked or skill-only tool stays that way; otherwise ask.
+        None if tool_permission == ToolPermission::Allow => ToolPermission::Ask,
+        None => tool_permission,
+    }
+}
+
+/// Refusal for a call that is not allowed to run where nobody can confirm
+/// it (messengers, background tasks, scripts), or `None` if the call may
+/// run unattended.  Messengers turn an `Ask` refusal into an approval
+/// prompt in the chat.
+pub fn unattended_denial(
+    permissions: &std::collections::HashMap<String, ToolPermission>,
+    name: &str,
+    args: &Value,
+) -> Option<String> {
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
+    let gated = GATED_ACTIONS
+        .iter()
+        .any(|(tool, actions)| *tool == name && actions.contains(&action));
+    match permission_for(permissions, name, args) {
+        ToolPermission::Allow => None,
+        _ if gated => Some(format!(
+            "'{} {}' needs confirmation and can't run here. Run it from the TUI, or set \
+             \"{}.{}\" = \"allow\" under [tool_permissions].",
+            name, action, name, action
+        )),
+        ToolPermission::Deny => Some(format!("Tool '{}' is denied by user policy.", name)),
+        ToolPermission::SkillOnly(_) => Some(format!(
+            "Tool '{}' is restricted to skill-based invocations only. It cannot be used here.",
+            name
+        )),
+        ToolPermission::Ask => Some(format!(
+            "'{}' needs confirmation and can't run here. Run it from the TUI, or set \
+             \"{}\" = \"allow\" under [tool_permissions].",
+            name, name
+        )),
+    }
+}
+
+static PERMISSIONS: std::sync::Mutex<Option<std::collections::HashMap<String, ToolPermission>>> =
+    std::sync::Mutex::new(None);
+
+/// Set the `[tool_permissions]` table applied to tool calls made from
+/// scripts.  Called at gateway startup and on reload.
+pub fn set_permissions(permissions: std::collections::HashMap<String, ToolPermission>) {
+    if let Ok(mut guard) = PERMISSIONS.lock() {
+        *guard = Some(permissions);
+    }
+}
+
+/// The configured `[tool_permissions]`, empty (everything allowed) if unset.
+pub fn permissions() -> std::collections::HashMap<String, ToolPermission> {
+    PERMISSIONS.lock().ok().and_then(|g| g.clone()).unwrap_or_default()
+}
+
 /// Return all tool names as a sorted list.
 pub fn all_tool_names() -> Vec<&'static str> {
     let mut names: Vec<&'static str> = all_tools().iter().map(|t| t.name).collect();
@@ -198,34 +409,70 @@ pub fn tool_summary(name: &str) -> &'static str {
         "read_file" => "Read files on your computer",
         "write_file" => "Create or overwrite files",
         "edit_file" => "Edit existing files",
+        "delete_file" => "Move files to the trash or delete them",
         "list_directory" => "List folder contents",
+        "tree" => "Show a directory tree",
         "search_files" => "Search inside file contents",
         "find_files" => "Find files by name",
+        "lsp_definition" => "Go to symbol definitions",
+        "lsp_references" => "Find symbol references",
+        "lsp_diagnostics" => "Show compiler diagnostics",
+        "lsp_rename" => "Rename symbols across a project",
+        "lint" => "Run the project's linters",
+        "format" => "Format source files",
+        "deps" => "Manage project dependencies",
+        "review_diff" => "Review code changes",
+        "snapshots" => "List, diff and restore workspace snapshots",
         "execute_command" => "Run shell commands",
         "web_fetch" => "Fetch content from URLs",
         "web_search" => "Search the web",
         "process" => "Manage background processes",
+        "serial" => "Talk to devices on serial ports",
         "qmd_search" => "Search knowledge vault (hybrid keyword + semantic)",
         "qmd_deep_search" => "Deep search vault with LLM re-ranking",
         "qmd_get" => "Retrieve document from knowledge vault",
         "cron" => "Manage scheduled jobs",
+        "tasks" => "Queue background work with priorities",
+        "conversations" => "Browse, clear & export messenger chat history",
+        "plan" => "Track a step-by-step task checklist",
+        "script_run" => "Run automation scripts",
         "sessions_list" => "List active sessions",
         "sessions_spawn" => "Spawn sub-agent sessions",
+        "sessions_worktree" => "Merge or discard sub-agent worktrees",
         "sessions_send" => "Send messages to sessions",
         "sessions_history" => "Read session message history",
         "session_status" => "Check session status & usage",
         "agents_list" => "List available agent types",
+        "orchestrate" => "Run a team of sub-agents in parallel",
         "apply_patch" => "Apply diff patches to files",
         "secrets_list" => "List vault secret names",
         "secrets_get" => "Read secrets from the vault",
         "secrets_store" => "Store secrets in the vault",
+        "generate_secret" => "Generate passwords, passphrases and tokens",
+        "totp" => "Get 2FA codes from vault-held TOTP seeds",
+        "ssh_key" => "Manage vault SSH keys and the session ssh-agent",
+        "encrypt_file" => "Encrypt files with age using vault keys",
+        "decrypt_file" => "Decrypt age-encrypted files with vault keys",
         "gateway" => "Control the gateway daemon",
         "message" => "Send messages via channels",
+        "contacts" => "Look up people in your contacts",
+        "mqtt" => "Publish and read MQTT messages",
+        "cloud_status" => "Summarise cloud spend and servers",
+        "render_report" => "Render markdown reports to PDF/HTML",
+        "plot" => "Plot tabular data as a PNG chart",
+        "xlsx_write" => "Create and update Excel workbooks",
         "tts" => "Convert text to speech",
         "image" => "Analyze images with vision AI",
+        "ocr" => "Extract text from images and scanned PDFs",
+        "qr" => "Generate and decode QR codes",
+        "transcribe" => "Transcribe audio and video files",
+        "media_convert" => "Convert, trim and resize audio/video",
+        "translate" => "Translate text and detect its language",
+        "calc" => "Exact math, unit/currency, timezone and date calculations",
         "nodes" => "Control paired companion devices",
         "browser" => "Automate a web browser",
         "canvas" => "Display UI on node canvases",
+        "ble" => "Read and control Bluetooth LE devices",
         "skill_list" => "List loaded skills",
         "skill_search" => "Search the skill registry",
         "skill_install" => "Install skills from registry",
@@ -236,6 +483,8 @@ pub fn tool_summary(name: &str) -> &'static str {
         "disk_usage" => "Scan disk usage by folder",
         "classify_files" => "Categorize files as docs, caches, etc.",
         "system_monitor" => "View CPU, memory & process info",
+        "system_info" => "Resource usage & alerts for this machine or a node",
+        "logs" => "Tail, follow & filter log files or journald units",
         "battery_health" => "Check battery status & health",
         "app_index" => "List installed apps by size",
         "cloud_browse" => "Browse local cloud storage folders",
@@ -246,6 +495,7 @@ pub fn tool_summary(name: &str) -> &'static str {
         "secure_delete" => "Securely overwrite & delete files",
         "summarize_file" => "Preview-summarize any file type",
         "ask_user" => "Ask the user structured questions",
+        "request_tools" => "Load more tool groups into a turn",
         "ollama_manage" => "Administer the Ollama model server",
         "exo_manage" => "Administer the Exo distributed AI cluster (git clone + uv run)",
         "uv_manage" => "Manage Python envs & packages via uv",
@@ -286,34 +536,70 @@ pub fn all_tools() -> Vec<&'static ToolDef> {
         &READ_FILE,
         &WRITE_FILE,
         &EDIT_FILE,
+        &DELETE_FILE,
         &LIST_DIRECTORY,
+        &TREE,
         &SEARCH_FILES,
         &FIND_FILES,
+        &LSP_DEFINITION,
+        &LSP_REFERENCES,
+        &LSP_DIAGNOSTICS,
+        &LSP_RENAME,
+        &LINT,
+        &FORMAT,
+        &DEPS,
+        &REVIEW_DIFF,
+        &SNAPSHOTS,
         &EXECUTE_COMMAND,
         &WEB_FETCH,
         &WEB_SEARCH,
         &PROCESS,
+        &SERIAL,
         &QMD_SEARCH,
         &QMD_DEEP_SEARCH,
         &QMD_GET,
         &CRON,
+        &TASKS,
+        &CONVERSATIONS,
+        &PLAN,
+        &SCRIPT_RUN,
         &SESSIONS_LIST,
         &SESSIONS_SPAWN,
+        &SESSIONS_WORKTREE,
         &SESSIONS_SEND,
         &SESSIONS_HISTORY,
         &SESSION_STATUS,
         &AGENTS_LIST,
+        &ORCHESTRATE,
         &APPLY_PATCH,
         &SECRETS_LIST,
         &SECRETS_GET,
         &SECRETS_STORE,
+        &GENERATE_SECRET,
+        &TOTP,
+        &SSH_KEY,
+        &ENCRYPT_FILE,
+        &DECRYPT_FILE,
         &GATEWAY,
         &MESSAGE,
+        &CONTACTS,
+        &MQTT,
+        &CLOUD_STATUS,
+        &RENDER_REPORT,
+        &PLOT,
+        &XLSX_WRITE,
         &TTS,
         &IMAGE,
+        &OCR,
+        &QR,
+        &TRANSCRIBE,
+        &MEDIA_CONVERT,
+        &TRANSLATE,
+        &CALC,
         &NODES,
         &BROWSER,
         &CANVAS,
+        &BLE,
         &SKILL_LIST,
         &SKILL_SEARCH,
         &SKILL_INSTALL,
@@ -324,6 +610,8 @@ pub fn all_tools() -> Vec<&'static ToolDef> {
         &DISK_USAGE,
         &CLASSIFY_FILES,
         &SYSTEM_MONITOR,
+        &SYSTEM_INFO,
+        &LOGS,
         &BATTERY_HEALTH,
         &APP_INDEX,
         &CLOUD_BROWSE,
@@ -345,6 +633,7 @@ pub fn all_tools() -> Vec<&'static ToolDef> {
         &NPM_MANAGE,
         &AGENT_SETUP,
         &ASK_USER,
+        &REQUEST_TOOLS,
     ]
 }
 
@@ -358,7 +647,10 @@ pub static READ_FILE: ToolDef = ToolDef {
                   text from .docx, .doc, .rtf, .odt, .pdf, and .html files. \
                   If you have an absolute path from find_files or search_files, \
                   pass it exactly as-is. Use the optional start_line / end_line \
-                  parameters to read a specific range (1-based, inclusive).",
+                  parameters to read a specific range (1-based, inclusive). \
+                  Binary files get a short summary; pass read_binary=true \
+                  for a hexdump. Text ends with the file's [hash …]; pass it \
+                  as expected_hash when editing to catch concurrent changes.",
     parameters: vec![],  // filled by init; see `read_file_params()`.
     execute: exec_read_file,
 };
@@ -380,14 +672,39 @@ pub static EDIT_FILE: ToolDef = ToolDef {
     execute: exec_edit_file,
 };
 
+pub static DELETE_FILE: ToolDef = ToolDef {
+    name: "delete_file",
+    description: "Delete a file or directory. By default it is moved to the trash \
+                  and can be restored (action='restore'); action='delete' removes \
+                  it permanently and needs the user's confirmation. Use this \
+                  instead of `rm` in execute_command.",
+    parameters: vec![],
+    execute: exec_delete_file,
+};
+
 pub static LIST_DIRECTORY: ToolDef = ToolDef {
     name: "list_directory",
     description: "List the contents of a directory. Returns file and \
-                  directory names, with directories suffixed by '/'.",
+                  directory names, with directories suffixed by '/'. With \
+                  detail=true, returns JSON with sizes, modified times, \
+                  permissions, symlink targets and gitignore status, \
+                  optionally sorted by size or mtime.",
     parameters: vec![],
     execute: exec_list_directory,
 };
 
+pub static TREE: ToolDef = ToolDef {
+    name: "tree",
+    description: "Show the directory tree under a path in one call, as \
+                  indented text or nested JSON. Much cheaper than walking \
+                  the workspace with repeated list_directory calls. Skips \
+                  hidden files and build/VCS directories; directories past \
+                  the depth limit or the entry budget are collapsed to a \
+                  summary of their file count and size.",
+    parameters: vec![],
+    execute: exec_tree,
+};
+
 pub static SEARCH_FILES: ToolDef = ToolDef {
     name: "search_files",
     description: "Search file CONTENTS for a text pattern (like grep -i). \
@@ -413,6 +730,94 @@ pub static FIND_FILES: ToolDef = ToolDef {
     execute: exec_find_files,
 };
 
+pub static LSP_DEFINITION: ToolDef = ToolDef {
+    name: "lsp_definition",
+    description: "Find where the symbol at a position is defined, using the project's \
+                  language server (rust-analyzer, pyright, gopls, typescript-language-server). \
+                  Give the file, the 1-based line, and either the 1-based column or the \
+                  symbol text on that line. More precise than text search.",
+    parameters: vec![],
+    execute: exec_lsp_definition,
+};
+
+pub static LSP_REFERENCES: ToolDef = ToolDef {
+    name: "lsp_references",
+    description: "List every reference to the symbol at a position, using the project's \
+                  language server. Position it like lsp_definition.",
+    parameters: vec![],
+    execute: exec_lsp_references,
+};
+
+pub static LSP_DIAGNOSTICS: ToolDef = ToolDef {
+    name: "lsp_diagnostics",
+    description: "Get compiler and linter errors and warnings for a file from the \
+                  project's language server. Use after editing to check your changes.",
+    parameters: vec![],
+    execute: exec_lsp_diagnostics,
+};
+
+pub static LSP_RENAME: ToolDef = ToolDef {
+    name: "lsp_rename",
+    description: "Rename the symbol at a position everywhere it is used, via the project's \
+                  language server, and write the changed files. Safer than search and \
+                  replace. Position it like lsp_definition.",
+    parameters: vec![],
+    execute: exec_lsp_rename,
+};
+
+pub static LINT: ToolDef = ToolDef {
+    name: "lint",
+    description: "Run the project's linter (clippy, ruff or eslint, detected from the \
+                  project files) and return its diagnostics as JSON: file, line, column, \
+                  severity, rule code and message. Set fix=true to apply the linter's \
+                  automatic fixes; the changed files are listed in the result.",
+    parameters: vec![],
+    execute: exec_lint,
+};
+
+pub static FORMAT: ToolDef = ToolDef {
+    name: "format",
+    description: "Format source files with the project's formatter (rustfmt, ruff or \
+                  prettier, detected from the project files) and list the files that \
+                  changed. Set check=true to only report which files need formatting.",
+    parameters: vec![],
+    execute: exec_format,
+};
+
+pub static DEPS: ToolDef = ToolDef {
+    name: "deps",
+    description: "Manage project dependencies with the project's package manager (cargo, \
+                  npm, or pip/uv, detected from the manifest). Actions: list, add, remove, \
+                  update, outdated, audit. add/remove/update report the resulting lockfile \
+                  changes; audit checks locked versions against the OSV advisory database. \
+                  Prefer this over raw execute_command calls for dependency changes.",
+    parameters: vec![],
+    execute: exec_deps,
+};
+
+pub static REVIEW_DIFF: ToolDef = ToolDef {
+    name: "review_diff",
+    description: "Review a code change: a git ref range (e.g. 'main..HEAD'), the staged \
+                  changes, or the working tree. The diff is split per file, each chunk is \
+                  reviewed by a model with the project's guidance (AGENTS.md, \
+                  CONTRIBUTING.md, project memory) as context, and the findings are \
+                  merged into one report by severity, file and line. format='json' \
+                  gives a machine-readable report for CI.",
+    parameters: vec![],
+    execute: exec_review_diff,
+};
+
+pub static SNAPSHOTS: ToolDef = ToolDef {
+    name: "snapshots",
+    description: "Workspace snapshots taken before queued tasks, heartbeats and cron jobs. \
+                  Actions: list, create (take one now), diff (files changed since a \
+                  snapshot; with 'path', a unified diff of that file), restore (undo the \
+                  changes since a snapshot, optionally only for 'paths'). A restore \
+                  snapshots the current state first, so it can be undone.",
+    parameters: vec![],
+    execute: exec_snapshots,
+};
+
 pub static EXECUTE_COMMAND: ToolDef = ToolDef {
     name: "execute_command",
     description: "Execute a shell command and return its output (stdout + stderr). \
@@ -454,6 +859,17 @@ pub static PROCESS: ToolDef = ToolDef {
     execute: exec_process,
 };
 
+pub static SERIAL: ToolDef = ToolDef {
+    name: "serial",
+    description: "Talk to microcontrollers and other serial devices. Actions: list (ports \
+                  and open sessions), open (port at a baud rate; returns a sessionId), send \
+                  (write a line and wait for the reply, optionally until 'expect' appears), \
+                  read (wait for output), close. Sessions are background processes, so \
+                  the process tool can poll or kill them too.",
+    parameters: vec![],
+    execute: exec_serial,
+};
+
 pub static QMD_SEARCH: ToolDef = ToolDef {
     name: "qmd_search",
     description: "Search the knowledge vault using hybrid search (keyword + semantic). \
@@ -483,12 +899,56 @@ pub static QMD_GET: ToolDef = ToolDef {
 pub static CRON: ToolDef = ToolDef {
     name: "cron",
     description: "Manage scheduled jobs. Actions: status (scheduler status), list (show jobs), \
-                  add (create job), update (modify job), remove (delete job), run (trigger immediately), \
-                  runs (get run history). Use for reminders and recurring tasks.",
+                  add (create job), update (modify job), remove (delete job), run (trigger now; \
+                  the gateway's scheduler runs it), runs (get run history), snooze (postpone a reminder by duration), ack (mark a \
+                  reminder done), export (write an iCal .ics of the schedule). \
+                  Use for reminders and recurring tasks. Schedules: \
+                  {kind:'at', at, tz?}, {kind:'every', everyMs}, {kind:'cron', expr, tz?}, or \
+                  text such as 'every 10 minutes' or '0 9 * * mon-fri'; times \
+                  without an offset are read in tz (an IANA name; default: system timezone). \
+                  Check when a schedule fires with calc op=schedule first.",
     parameters: vec![],
     execute: exec_cron,
 };
 
+pub static TASKS: ToolDef = ToolDef {
+    name: "tasks",
+    description: "Manage the background task queue. Actions: add (queue a prompt with priority and retries), \
+                  list, get, cancel, prune (remove finished), status. Queued tasks run in worker sessions \
+                  in the gateway with a concurrency limit — use for batches of long-running work.",
+    parameters: vec![],
+    execute: exec_tasks,
+};
+
+pub static CONVERSATIONS: ToolDef = ToolDef {
+    name: "conversations",
+    description: "Browse the persisted history of messenger chats, namespaced by messenger. \
+                  Actions: list (optionally for one messenger), show (a chat's recent messages), \
+                  clear (one chat, or every chat of a messenger), export (a chat — or all of them — \
+                  to markdown under exports/conversations/ or `output`) for archiving.",
+    parameters: vec![],
+    execute: exec_conversations,
+};
+
+pub static PLAN: ToolDef = ToolDef {
+    name: "plan",
+    description: "Maintain a step-by-step checklist for the current task. Actions: create (title + steps), \
+                  update (set a step to pending/in_progress/done/skipped), insert (add a step), remove, \
+                  show, clear. The plan is shown to the user and kept in your context — create one for \
+                  multi-step work and update it as each step starts and finishes.",
+    parameters: vec![],
+    execute: exec_plan,
+};
+
+pub static SCRIPT_RUN: ToolDef = ToolDef {
+    name: "script_run",
+    description: "Run a Rhai automation script from skills/scripts/ by name, or inline code. Scripts can \
+                  call tools with tool(name, #{...}), read sessions() and config(\"key\"), and receive \
+                  'args' as ARGS. Call with no script or code to list available scripts.",
+    parameters: vec![],
+    execute: exec_script_run,
+};
+
 pub static SESSIONS_LIST: ToolDef = ToolDef {
     name: "sessions_list",
     description: "List active sessions with optional filters. Shows main sessions and sub-agents. \
@@ -505,6 +965,16 @@ pub static SESSIONS_SPAWN: ToolDef = ToolDef {
     execute: exec_sessions_spawn,
 };
 
+pub static SESSIONS_WORKTREE: ToolDef = ToolDef {
+    name: "sessions_worktree",
+    description: "Review the changes a sub-agent made in its isolated git worktree \
+                  (spawned with isolation='worktree'). 'diff' shows the full patch, 'stat' \
+                  the changed files, 'merge' applies the changes to your working tree and \
+                  removes the worktree, 'discard' throws them away.",
+    parameters: vec![],
+    execute: exec_sessions_worktree,
+};
+
 pub static SESSIONS_SEND: ToolDef = ToolDef {
     name: "sessions_send",
     description: "Send a message to another session. Use sessionKey or label to identify the target. \
@@ -536,6 +1006,15 @@ pub static AGENTS_LIST: ToolDef = ToolDef {
     execute: exec_agents_list,
 };
 
+pub static ORCHESTRATE: ToolDef = ToolDef {
+    name: "orchestrate",
+    description: "Fan a task out to several sub-agents with different roles and models (e.g. researcher, \
+                  coder, reviewer) that run concurrently and share a scratchpad. Actions: start, status, \
+                  scratchpad_write, scratchpad_read, aggregate. Use 'aggregate' to collect their outputs.",
+    parameters: vec![],
+    execute: exec_orchestrate,
+};
+
 pub static APPLY_PATCH: ToolDef = ToolDef {
     name: "apply_patch",
     description: "Apply a unified diff patch to one or more files. Supports multi-hunk patches. \
@@ -566,7 +1045,64 @@ pub static SECRETS_STORE: ToolDef = ToolDef {
     name: "secrets_store",
     description: "Store or update a key/value pair in the encrypted secrets vault. \
                   The value is encrypted at rest. Use for API keys, tokens, and \
-                  other sensitive material.",
+                  other sensitive material. Keys stored under a provider's name \
+                  (e.g. OPENAI_API_KEY) are checked with the provider first and \
+                  refused if it rejects them.",
+    parameters: vec![],
+    execute: exec_secrets_stub,
+};
+
+pub static GENERATE_SECRET: ToolDef = ToolDef {
+    name: "generate_secret",
+    description: "Generate a strong random secret. Styles: password (length, \
+                  character classes, excludeAmbiguous), passphrase (words from \
+                  the BIP-39 list), token (hex/base64url/alphanumeric, optional \
+                  prefix) and pin. With storeAs the value is saved straight to \
+                  the vault and never shown; only its metadata is returned.",
+    parameters: vec![],
+    execute: exec_secrets_stub,
+};
+
+pub static TOTP: ToolDef = ToolDef {
+    name: "totp",
+    description: "Two-factor codes from TOTP seeds kept in the vault. Actions: \
+                  add (store a base32 seed or otpauth:// URI under a name, with \
+                  an access policy), code (current code and seconds until it \
+                  expires — use it to finish a 2FA login in the browser), list, \
+                  remove. Seeds are never returned and every code request is \
+                  audit-logged.",
+    parameters: vec![],
+    execute: exec_secrets_stub,
+};
+
+pub static SSH_KEY: ToolDef = ToolDef {
+    name: "ssh_key",
+    description: "SSH keys held in the vault. Actions: list, generate (new \
+                  Ed25519 key; returns the public key to install), import (an \
+                  OpenSSH private key from text or a file, decrypting it with \
+                  passphrase), public, load (into a session-scoped ssh-agent \
+                  that the nodes tool then uses), unload, stop (clear and \
+                  stop the agent). Private keys are never returned.",
+    parameters: vec![],
+    execute: exec_secrets_stub,
+};
+
+pub static ENCRYPT_FILE: ToolDef = ToolDef {
+    name: "encrypt_file",
+    description: "Encrypt a file with age. Encrypt to a vault age key by name \
+                  (created on first use), to age1… recipients, or with a \
+                  passphrase held in the vault (passphraseSecret). Writes \
+                  <path>.age by default; armor gives ASCII output suitable \
+                  for pasting into email.",
+    parameters: vec![],
+    execute: exec_secrets_stub,
+};
+
+pub static DECRYPT_FILE: ToolDef = ToolDef {
+    name: "decrypt_file",
+    description: "Decrypt an age file (binary or armored) using a vault age \
+                  key, every age key the vault lets you use, or a vault-held \
+                  passphrase. Writes the path without .age by default.",
     parameters: vec![],
     execute: exec_secrets_stub,
 };
@@ -576,7 +1112,7 @@ pub static GATEWAY: ToolDef = ToolDef {
     description: "Manage the gateway daemon. Actions: restart (restart gateway), \
                   config.get (get current config), config.schema (get config schema), \
                   config.apply (replace entire config), config.patch (partial config update), \
-                  update.run (update gateway).",
+                  update.run (update gateway), locks (files locked by running sessions).",
     parameters: vec![],
     execute: exec_gateway,
 };
@@ -584,12 +1120,81 @@ pub static GATEWAY: ToolDef = ToolDef {
 pub static MESSAGE: ToolDef = ToolDef {
     name: "message",
     description: "Send messages via channel plugins. Actions: send (send a message), \
-                  broadcast (send to multiple targets). Supports various channels \
+                  broadcast (send to multiple targets), templates (list message templates). \
+                  Instead of 'message', pass 'template' and 'vars' to send a reusable \
+                  template from templates/. Supports various channels \
                   like telegram, discord, whatsapp, signal, etc.",
     parameters: vec![],
     execute: exec_message,
 };
 
+pub static CONTACTS: ToolDef = ToolDef {
+    name: "contacts",
+    description: "Look people up in the contact book (config, local contacts file, \
+                  CardDAV) and manage the local book. Actions: list, search, get, \
+                  resolve (a name to a messenger id for a channel), add (create or \
+                  update), remove. The message tool accepts contact names as targets.",
+    parameters: vec![],
+    execute: exec_contacts,
+};
+
+pub static MQTT: ToolDef = ToolDef {
+    name: "mqtt",
+    description: "Talk to IoT devices over the gateway's MQTT connection. Actions: \
+                  publish (topic + payload; JSON objects are sent as JSON), recent \
+                  (messages received on subscribed topics, optionally filtered by a \
+                  topic filter with + and # wildcards), status.",
+    parameters: vec![],
+    execute: exec_mqtt,
+};
+
+pub static CLOUD_STATUS: ToolDef = ToolDef {
+    name: "cloud_status",
+    description: "Daily cloud spend and server status from AWS Cost Explorer, the GCP \
+                  billing export and Hetzner Cloud, using credentials from the vault. \
+                  Actions: summary (default; a short text report, or JSON with \
+                  format=json, flagging days well above the average), providers (which \
+                  are configured). Suited to a daily cron job that sends the report.",
+    parameters: vec![],
+    execute: exec_cloud_status,
+};
+
+pub static RENDER_REPORT: ToolDef = ToolDef {
+    name: "render_report",
+    description: "Render markdown (inline or from a file) into a styled PDF or HTML report \
+                  in the workspace (default: reports/<title>-<date>.pdf). Fenced ```chart \
+                  blocks holding JSON like {\"type\": \"bar\", \"title\": \"...\", \
+                  \"labels\": [...], \"series\": [{\"name\": \"...\", \"values\": [...]}]} \
+                  become charts (type: line, bar or scatter). The file is returned as an \
+                  attachment that messengers can deliver.",
+    parameters: vec![],
+    execute: exec_render_report,
+};
+
+pub static PLOT: ToolDef = ToolDef {
+    name: "plot",
+    description: "Chart tabular data as a PNG and return its path as an attachment. Data is \
+                  inline ('data': CSV/TSV text, or JSON as an array of objects or of rows \
+                  with a header row) or a .csv/.tsv/.json file ('path'). Pick columns by \
+                  name: 'x' (default: first column) and 'y' (one or more; default: every \
+                  other numeric column). type: line (default), bar or scatter.",
+    parameters: vec![],
+    execute: exec_plot,
+};
+
+pub static XLSX_WRITE: ToolDef = ToolDef {
+    name: "xlsx_write",
+    description: "Create or update an .xlsx workbook. 'operations' is a list of writes, \
+                  each into a 'sheet' (created if missing) starting at 'cell' (default \
+                  A1): 'values' writes a 2-D range where strings starting with '=' are \
+                  formulas (e.g. \"=SUM(B2:B10)\"); 'data' (CSV/JSON) or 'path' (a \
+                  .csv/.tsv/.json file) writes a table with a bold header row. \
+                  mode=update keeps the existing workbook's values and formulas \
+                  (formatting is not preserved); the default mode=create replaces it.",
+    parameters: vec![],
+    execute: exec_xlsx_write,
+};
+
 pub static TTS: ToolDef = ToolDef {
     name: "tts",
     description: "Convert text to speech and return a media path. Use when the user \
@@ -607,12 +1212,84 @@ pub static IMAGE: ToolDef = ToolDef {
     execute: exec_image,
 };
 
+pub static OCR: ToolDef = ToolDef {
+    name: "ocr",
+    description: "Extract text from an image or scanned PDF with Tesseract, falling back \
+                  to the vision model when Tesseract isn't installed. format=json returns \
+                  text blocks with page numbers and pixel bounding boxes [x, y, width, \
+                  height]. read_file already OCRs images and text-less PDFs; use this \
+                  for coordinates, other languages or a specific engine.",
+    parameters: vec![],
+    execute: exec_ocr,
+};
+
+pub static QR: ToolDef = ToolDef {
+    name: "qr",
+    description: "Generate QR codes (URLs, pairing links, Wi-Fi logins) as terminal \
+                  block art and/or PNG, or decode QR codes found in an image. \
+                  For Wi-Fi, pass wifi={ssid, password, security} instead of data.",
+    parameters: vec![],
+    execute: exec_qr,
+};
+
+pub static TRANSCRIBE: ToolDef = ToolDef {
+    name: "transcribe",
+    description: "Transcribe a local audio or video file (meeting recordings, voice \
+                  notes, screencasts) into timestamped text. Audio is extracted with \
+                  ffmpeg and sent to whisper.cpp locally (WHISPER_MODEL) or an \
+                  OpenAI-compatible speech-to-text API. format=json returns segments \
+                  with start/end seconds.",
+    parameters: vec![],
+    execute: exec_transcribe,
+};
+
+pub static MEDIA_CONVERT: ToolDef = ToolDef {
+    name: "media_convert",
+    description: "Common ffmpeg jobs without writing ffmpeg commands. Operations: \
+                  extract_audio (to mp3 by default), convert (format: mp3, ogg, opus, \
+                  m4a, wav, flac, mp4, mkv, webm or gif), resize (width and/or height; \
+                  aspect ratio kept), trim (start/end as seconds or HH:MM:SS). start, \
+                  end, width and height combine with any operation. Jobs still running \
+                  after yieldMs continue in the background; check them with \
+                  operation=status and the returned sessionId.",
+    parameters: vec![],
+    execute: exec_media_convert,
+};
+
+pub static TRANSLATE: ToolDef = ToolDef {
+    name: "translate",
+    description: "Translate text between languages, detecting the source language \
+                  automatically (action=translate), or just identify the language \
+                  (action=detect). target defaults to the configured language \
+                  ([translation] default_target, usually 'en'). Uses DeepL, \
+                  LibreTranslate or local Argos models, whichever is configured.",
+    parameters: vec![],
+    execute: exec_translate,
+};
+
+pub static CALC: ToolDef = ToolDef {
+    name: "calc",
+    description: "Deterministic calculations; use this instead of doing math in your head. \
+                  Ops: eval (arithmetic expression with + - * / % ^, sqrt, log, round, min, \
+                  max, pi...), convert (value from one unit or currency code to another: \
+                  length, mass, volume, temperature, data sizes, speed, energy..., USD/EUR \
+                  with cached daily rates), time (convert a time between timezones), date \
+                  (add a duration like '3d 4h' or '-1 month', or the difference until another \
+                  date, in a timezone), schedule (next runs of a cron expression in a timezone, \
+                  as the cron tool would fire them).",
+    parameters: vec![],
+    execute: exec_calc,
+};
+
 pub static NODES: ToolDef = ToolDef {
     name: "nodes",
     description: "Discover and control paired nodes (companion devices). Actions: \
-                  status (list nodes), describe (node details), pending/approve/reject (pairing), \
+                  status (list nodes), describe (node details), discover (scan the LAN via mDNS/SSDP \
+                  for Chromecasts, printers, SSH hosts, etc.), pending (last discovery results), \
+                  pair (show a QR code to pair an Android device for wireless ADB), approve/reject (pairing), \
                   notify (send notification), camera_snap/camera_list (camera), \
-                  screen_record (screen capture), location_get (GPS), run/invoke (remote commands).",
+                  screen_record (screen capture), location_get (GPS), run/invoke (remote commands), \
+                  wake (Wake-on-LAN), sleep/reboot (power control; these ask for confirmation).",
     parameters: vec![],
     execute: exec_nodes,
 };
@@ -636,6 +1313,17 @@ pub static CANVAS: ToolDef = ToolDef {
     execute: exec_canvas,
 };
 
+pub static BLE: ToolDef = ToolDef {
+    name: "ble",
+    description: "Bluetooth Low Energy devices. Actions: scan (nearby devices with RSSI), \
+                  services (list a device's characteristics), read (decode a characteristic, \
+                  e.g. battery 2a19, temperature 2a6e, heart rate 2a37), subscribe (collect \
+                  notifications for durationMs), write (send bytes; asks for confirmation). \
+                  Requires the `ble` build feature.",
+    parameters: vec![],
+    execute: exec_ble,
+};
+
 pub static SKILL_LIST: ToolDef = ToolDef {
     name: "skill_list",
     description: "List all loaded skills with their status (enabled, gates, source, linked secrets). \
@@ -726,6 +1414,29 @@ pub static SYSTEM_MONITOR: ToolDef = ToolDef {
     execute: exec_system_monitor,
 };
 
+pub static SYSTEM_INFO: ToolDef = ToolDef {
+    name: "system_info",
+    description: "Structured report of CPU usage and load, memory and swap, disk \
+                  usage per mount, top processes, battery and uptime — for this \
+                  machine or, with `node`, a Linux SSH/ADB node. Includes `alerts` \
+                  for disks and memory above the thresholds, so scheduled checks \
+                  can notify only when something needs attention.",
+    parameters: vec![],
+    execute: exec_system_info,
+};
+
+pub static LOGS: ToolDef = ToolDef {
+    name: "logs",
+    description: "Tail a log file (path) or journald unit (unit), optionally \
+                  following it for up to 300 seconds, and filter lines with \
+                  pattern/exclude regexes. Returns matching lines plus counts: \
+                  total matches, matches per minute while following, and the \
+                  most frequent messages. Set threshold to flag an alert; each \
+                  run also fires the on_logs script hook.",
+    parameters: vec![],
+    execute: exec_logs,
+};
+
 pub static BATTERY_HEALTH: ToolDef = ToolDef {
     name: "battery_health",
     description: "Report battery status including charge level, cycle count, capacity, \
@@ -941,6 +1652,17 @@ pub static ASK_USER: ToolDef = ToolDef {
     execute: exec_ask_user_stub,
 };
 
+pub static REQUEST_TOOLS: ToolDef = ToolDef {
+    name: "request_tools",
+    description: "Load more tools for this conversation. Only the core file, search and \
+                  command tools are loaded at first; pass 'groups' (code, web, browser, \
+                  memory, cron, sessions, secrets, messaging, nodes, media, skills, system, \
+                  admin, extensions) or 'tools' by name. Call without arguments to list \
+                  the groups.",
+    parameters: vec![],
+    execute: exec_request_tools,
+};
+
 // Re-export parameter functions from params module
 pub use params::*;
 
@@ -980,34 +1702,70 @@ fn resolve_params(tool: &ToolDef) -> Vec<ToolParam> {
         "read_file" => read_file_params(),
         "write_file" => write_file_params(),
         "edit_file" => edit_file_params(),
+        "delete_file" => delete_file_params(),
         "list_directory" => list_directory_params(),
+        "tree" => tree_params(),
         "search_files" => search_files_params(),
         "find_files" => find_files_params(),
+        "lsp_definition" => lsp_definition_params(),
+        "lsp_references" => lsp_references_params(),
+        "lsp_diagnostics" => lsp_diagnostics_params(),
+        "lsp_rename" => lsp_rename_params(),
+        "lint" => lint_params(),
+        "format" => format_params(),
+        "deps" => deps_params(),
+        "review_diff" => review_diff_params(),
+        "snapshots" => snapshots_params(),
         "execute_command" => execute_command_params(),
         "web_fetch" => web_fetch_params(),
         "web_search" => web_search_params(),
         "process" => process_params(),
+        "serial" => serial_params(),
         "qmd_search" => qmd_search_params(),
         "qmd_deep_search" => qmd_deep_search_params(),
         "qmd_get" => qmd_get_params(),
         "cron" => cron_params(),
+        "tasks" => tasks_params(),
+        "conversations" => conversations_params(),
+        "plan" => plan_params(),
+        "script_run" => script_run_params(),
         "sessions_list" => sessions_list_params(),
         "sessions_spawn" => sessions_spawn_params(),
+        "sessions_worktree" => sessions_worktree_params(),
         "sessions_send" => sessions_send_params(),
         "sessions_history" => sessions_history_params(),
         "session_status" => session_status_params(),
         "agents_list" => agents_list_params(),
+        "orchestrate" => orchestrate_params(),
         "apply_patch" => apply_patch_params(),
         "secrets_list" => secrets_list_params(),
         "secrets_get" => secrets_get_params(),
         "secrets_store" => secrets_store_params(),
+        "generate_secret" => generate_secret_params(),
+        "totp" => totp_params(),
+        "ssh_key" => ssh_key_params(),
+        "encrypt_file" => encrypt_file_params(),
+        "decrypt_file" => decrypt_file_params(),
         "gateway" => gateway_params(),
         "message" => message_params(),
+        "contacts" => contacts_params(),
+        "mqtt" => mqtt_params(),
+        "cloud_status" => cloud_status_params(),
+        "render_report" => render_report_params(),
+        "plot" => plot_params(),
+        "xlsx_write" => xlsx_write_params(),
         "tts" => tts_params(),
         "image" => image_params(),
+        "ocr" => ocr_params(),
+        "qr" => qr_params(),
+        "transcribe" => transcribe_params(),
+        "media_convert" => media_convert_params(),
+        "translate" => translate_params(),
+        "calc" => calc_params(),
         "nodes" => nodes_params(),
         "browser" => browser_params(),
         "canvas" => canvas_params(),
+        "ble" => ble_params(),
         "skill_list" => skill_list_params(),
         "skill_search" => skill_search_params(),
         "skill_install" => skill_install_params(),
@@ -1018,6 +1776,8 @@ fn resolve_params(tool: &ToolDef) -> Vec<ToolParam> {
         "disk_usage" => disk_usage_params(),
         "classify_files" => classify_files_params(),
         "system_monitor" => system_monitor_params(),
+        "system_info" => system_info_params(),
+        "logs" => logs_params(),
         "battery_health" => battery_health_params(),
         "app_index" => app_index_params(),
         "cloud_browse" => cloud_browse_params(),
@@ -1028,6 +1788,7 @@ fn resolve_params(tool: &ToolDef) -> Vec<ToolParam> {
         "secure_delete" => secure_delete_params(),
         "summarize_file" => summarize_file_params(),
         "ask_user" => ask_user_params(),
+        "request_tools" => request_tools_params(),
         "pkg_manage" => pkg_manage_params(),
         "net_info" => net_info_params(),
         "net_scan" => net_scan_params(),
@@ -1043,27 +1804,71 @@ fn resolve_params(tool: &ToolDef) -> Vec<ToolParam> {
     }
 }
 
+/// Name, description and parameter schema of every tool: the built-ins
+/// followed by those in the custom [`registry`].
+fn tool_schemas() -> Vec<(String, String, Value)> {
+    let builtin = all_tools().into_iter().map(|t| {
+        let params = resolve_params(t);
+        let (properties, required) = params_to_json_schema(&params);
+        let parameters = json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        (t.name.to_string(), t.description.to_string(), parameters)
+    });
+    let custom = registry().tools().into_iter().map(|t| {
+        (t.name().to_string(), t.description().to_string(), t.parameters())
+    });
+    builtin.chain(custom).collect()
+}
+
+/// The schemas offered to `model` on `provider`: only the tools in
+/// `subset` (all of them when `None`), trimmed to the [`SchemaBudget`].
+fn budgeted_schemas(provider: &str, model: &str, subset: Option<&[String]>) -> Vec<(String, String, Value)> {
+    let budget = schema_budget();
+    let compact = budget.is_compact(provider, model);
+    tool_schemas()
+        .into_iter()
+        .filter(|(name, _, _)| match subset {
+            Some(subset) => subset.contains(name),
+            // With every tool offered there's nothing to request.
+            None => name != "request_tools",
+        })
+        .map(|(name, description, mut parameters)| {
+            let description = budget.minify(&description, &mut parameters, compact);
+            (name, description, parameters)
+        })
+        .collect()
+}
+
+/// Every tool's name and the first sentence of its description, for the
+/// tool router.
+pub fn tool_catalog() -> Vec<(String, String)> {
+    tool_schemas()
+        .into_iter()
+        .map(|(name, description, _)| {
+            let summary = schema_budget::first_sentence(&description).to_string();
+            (name, summary)
+        })
+        .collect()
+}
+
 /// OpenAI / OpenAI-compatible function-calling format.
 ///
 /// ```json
 /// { "type": "function", "function": { "name", "description", "parameters": { … } } }
 /// ```
-pub fn tools_openai() -> Vec<Value> {
-    all_tools()
+pub fn tools_openai(provider: &str, model: &str, subset: Option<&[String]>) -> Vec<Value> {
+    budgeted_schemas(provider, model, subset)
         .into_iter()
-        .map(|t| {
-            let params = resolve_params(t);
-            let (properties, required) = params_to_json_schema(&params);
+        .map(|(name, description, parameters)| {
             json!({
                 "type": "function",
                 "function": {
-                    "name": t.name,
-                    "description": t.description,
-                    "parameters": {
-                        "type": "object",
-                        "properties": properties,
-                        "required": required,
-                    }
+                    "name": name,
+                    "description": description,
+                    "parameters": parameters,
                 }
             })
         })
@@ -1075,20 +1880,14 @@ pub fn tools_openai() -> Vec<Value> {
 /// ```json
 /// { "name", "description", "input_schema": { … } }
 /// ```
-pub fn tools_anthropic() -> Vec<Value> {
-    all_tools()
+pub fn tools_anthropic(model: &str, subset: Option<&[String]>) -> Vec<Value> {
+    budgeted_schemas("anthropic", model, subset)
         .into_iter()
-        .map(|t| {
-            let params = resolve_params(t);
-            let (properties, required) = params_to_json_schema(&params);
+        .map(|(name, description, parameters)| {
             json!({
-                "name": t.name,
-                "description": t.description,
-                "input_schema": {
-                    "type": "object",
-                    "properties": properties,
-                    "required": required,
-                }
+                "name": name,
+                "description": description,
+                "input_schema": parameters,
             })
         })
         .collect()
@@ -1099,20 +1898,14 @@ pub fn tools_anthropic() -> Vec<Value> {
 /// ```json
 /// { "name", "description", "parameters": { … } }
 /// ```
-pub fn tools_google() -> Vec<Value> {
-    all_tools()
+pub fn tools_google(model: &str, subset: Option<&[String]>) -> Vec<Value> {
+    budgeted_schemas("google", model, subset)
         .into_iter()
-        .map(|t| {
-            let params = resolve_params(t);
-            let (properties, required) = params_to_json_schema(&params);
+        .map(|(name, description, parameters)| {
             json!({
-                "name": t.name,
-                "description": t.description,
-                "parameters": {
-                    "type": "object",
-                    "properties": properties,
-                    "required": required,
-                }
+                "name": name,
+                "description": description,
+                "parameters": parameters,
             })
         })
         .collect()
@@ -1123,7 +1916,17 @@ pub fn tools_google() -> Vec<Value> {
 /// Returns `true` for tools that must be routed through the gateway
 /// (i.e. handled by `execute_secrets_tool`) rather than `execute_tool`.
 pub fn is_secrets_tool(name: &str) -> bool {
-    matches!(name, "secrets_list" | "secrets_get" | "secrets_store")
+    matches!(
+        name,
+        "secrets_list"
+            | "secrets_get"
+            | "secrets_store"
+            | "generate_secret"
+            | "totp"
+            | "ssh_key"
+            | "encrypt_file"
+            | "decrypt_file"
+    )
 }
 
 /// Returns `true` for skill-management tools that are routed through the
@@ -1149,11 +1952,21 @@ pub fn is_user_prompt_tool(name: &str) -> bool {
 }
 
 /// Find a tool by name and execute it with the given arguments.
+///
+/// Registered custom tools are driven to completion on the current
+/// runtime, so sync callers (scripts, replay) must run on the blocking
+/// pool rather than on an async worker.
 #[instrument(skip(args, workspace_dir), fields(tool = name))]
 pub fn execute_tool(name: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
     debug!("Executing tool");
     for tool in all_tools() {
         if tool.name == name {
+            if is_dry_run() {
+                if let Some(simulated) = dry_run::simulate(name, args, workspace_dir) {
+                    debug!(tool = name, "Dry-run: simulated tool call");
+                    return simulated;
+                }
+            }
             let result = (tool.execute)(args, workspace_dir);
             if result.is_err() {
                 warn!(error = ?result.as_ref().err(), "Tool execution failed");
@@ -1161,10 +1974,80 @@ pub fn execute_tool(name: &str, args: &Value, workspace_dir: &Path) -> Result<St
             return result;
         }
     }
+    if let Some(tool) = registry().get(name) {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| format!("Tool {} is a custom async tool and needs the gateway runtime", name))?;
+        return handle.block_on(run_custom_tool(tool.as_ref(), args, workspace_dir));
+    }
     warn!(tool = name, "Unknown tool requested");
     Err(format!("Unknown tool: {}", name))
 }
 
+/// Execute a built-in or registered custom tool.
+///
+/// In dry-run mode custom tools answer with [`Tool::simulate`], which
+/// holds them back unless they say they're read-only.
+#[instrument(skip(args, workspace_dir), fields(tool = name))]
+pub async fn execute_tool_async(name: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let Some(tool) = registry().get(name) else {
+        return execute_tool(name, args, workspace_dir);
+    };
+    run_custom_tool(tool.as_ref(), args, workspace_dir).await
+}
+
+async fn run_custom_tool(tool: &dyn Tool, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    if is_dry_run() {
+        if let Some(simulated) = tool.simulate(args, workspace_dir) {
+            debug!(tool = tool.name(), "Dry-run: held back custom tool call");
+            return simulated;
+        }
+    }
+    debug!("Executing custom tool");
+    let result = tool.execute(args, workspace_dir).await;
+    if result.is_err() {
+        warn!(error = ?result.as_ref().err(), "Custom tool execution failed");
+    }
+    result
+}
+
+/// Like [`execute_tool_async`], but runs built-in tools on the blocking
+/// pool so the calling task stays free — the gateway uses that time to
+/// forward [`progress`](crate::progress) updates.  The turn's trace ID,
+/// session, conversation, user, `/dryrun` override and span carry over to
+/// the blocking thread.  The call is cut off after the tool's configured
+/// [timeout](TimeoutConfig).
+pub async fn execute_tool_offloaded(name: &str, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let limit = timeouts().for_tool(name, args);
+    if registry().get(name).is_some() {
+        return timeouts::with_timeout(name, limit, execute_tool_async(name, args, workspace_dir)).await;
+    }
+    let (owned_name, args, workspace_dir) = (name.to_string(), args.clone(), workspace_dir.to_path_buf());
+    let trace_id = crate::observability::trace::current();
+    let session = crate::sessions::current_session();
+    let conversation = crate::sessions::current_conversation();
+    let user = crate::users::current_user();
+    let dry_run = current_dry_run_session();
+    let span = tracing::Span::current();
+    let run = async move {
+        tokio::task::spawn_blocking(move || {
+            span.in_scope(|| {
+                crate::observability::trace::sync_scope(trace_id, || {
+                    crate::sessions::sync_session_scope(session, || {
+                        crate::sessions::sync_conversation_scope(conversation, || {
+                            crate::users::sync_user_scope(user, || {
+                                sync_dry_run_session_scope(dry_run, || execute_tool(&owned_name, &args, &workspace_dir))
+                            })
+                        })
+                    })
+                })
+            })
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Tool execution failed: {}", e)))
+    };
+    timeouts::with_timeout(name, limit, run).await
+}
+
 // ── Wire types for WebSocket protocol ───────────────────────────────────────
 
 /// A tool call requested by the model (sent gateway → client for display).
@@ -1219,6 +2102,18 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_read_file_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("blob.dat"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        let summary = exec_read_file(&json!({ "path": "blob.dat" }), dir.path()).unwrap();
+        assert!(summary.starts_with("Binary file: PNG image, 16 bytes."));
+
+        let args = json!({ "path": "blob.dat", "read_binary": true, "offset": 8, "length": 4 });
+        let dump = exec_read_file(&args, dir.path()).unwrap();
+        assert!(dump.contains("00000008: 00 00 00 0d"));
+    }
+
     #[test]
     fn test_read_file_no_path() {
         let args = json!({});
@@ -1256,6 +2151,31 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    // ── delete_file ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_delete_file_permanent() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.txt"), "a").unwrap();
+
+        let args = json!({ "path": "sub", "action": "delete" });
+        assert!(exec_delete_file(&args, dir.path()).unwrap_err().contains("recursive=true"));
+        let args = json!({ "path": "sub", "action": "delete", "recursive": true });
+        assert!(exec_delete_file(&args, dir.path()).is_ok());
+        assert!(!dir.path().join("sub").exists());
+    }
+
+    #[test]
+    fn test_delete_file_permanent_is_gated() {
+        let mut perms = std::collections::HashMap::new();
+        let delete = json!({ "path": "a.txt", "action": "delete" });
+        assert_eq!(permission_for(&perms, "delete_file", &delete), ToolPermission::Ask);
+        assert_eq!(permission_for(&perms, "delete_file", &json!({ "path": "a.txt" })), ToolPermission::Allow);
+        perms.insert("delete_file.delete".to_string(), ToolPermission::Allow);
+        assert_eq!(permission_for(&perms, "delete_file", &delete), ToolPermission::Allow);
+    }
+
     // ── edit_file ───────────────────────────────────────────────────
 
     #[test]
@@ -1274,6 +2194,21 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn test_edit_file_expected_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("f.txt"), "aaa\nbbb\n").unwrap();
+        let read = exec_read_file(&json!({ "path": "f.txt" }), dir.path()).unwrap();
+        let hash = read.rsplit("[hash ").next().unwrap().trim_end_matches(']').to_string();
+
+        // Someone else changes the file after our read.
+        std::fs::write(dir.path().join("f.txt"), "aaa\nbbb\nccc\n").unwrap();
+        let args = json!({ "path": "f.txt", "old_string": "bbb", "new_string": "BBB", "expected_hash": hash });
+        let err = exec_edit_file(&args, dir.path()).unwrap_err();
+        assert!(err.contains("changed since you read it") && err.contains("+ccc"));
+        assert_eq!(std::fs::read_to_string(dir.path().join("f.txt")).unwrap(), "aaa\nbbb\nccc\n");
+    }
+
     #[test]
     fn test_edit_file_no_match() {
         let dir = std::env::temp_dir().join("rustyclaw_test_edit_no");
@@ -1315,6 +2250,34 @@ mod tests {
         assert!(text.contains("lib.rs"));
     }
 
+    #[test]
+    fn test_list_directory_detail() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("small.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("big.txt"), "a".repeat(100)).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("missing.txt", dir.path().join("link")).unwrap();
+
+        let args = json!({ "path": dir.path().to_str().unwrap(), "detail": true, "sort": "size" });
+        let items: Vec<Value> = serde_json::from_str(&exec_list_directory(&args, ws()).unwrap()).unwrap();
+        assert_eq!(items[0]["name"], "big.txt");
+        assert_eq!(items[0]["size"], 100);
+        assert!(items[0]["permissions"].as_str().unwrap().starts_with("rw"));
+        let sub = items.iter().find(|i| i["name"] == "sub").unwrap();
+        assert_eq!(sub["type"], "dir");
+        assert!(sub["size"].is_null());
+        #[cfg(unix)]
+        {
+            let link = items.iter().find(|i| i["name"] == "link").unwrap();
+            assert_eq!(link["target"], "missing.txt");
+            assert_eq!(link["target_type"], "broken");
+        }
+
+        let args = json!({ "path": dir.path().to_str().unwrap(), "detail": true, "sort": "owner" });
+        assert!(exec_list_directory(&args, ws()).is_err());
+    }
+
     // ── search_files ────────────────────────────────────────────────
 
     #[test]
@@ -1387,63 +2350,365 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
-    // ── execute_command ─────────────────────────────────────────────
+    #[test]
+    fn test_find_and_search_include_extra_roots() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let extra = tempfile::TempDir::new().unwrap();
+        std::fs::write(extra.path().join("extra_root_marker.txt"), "qwertyextra\n").unwrap();
+
+        set_extra_roots(vec![extra.path().to_path_buf()]);
+        let found = exec_find_files(&json!({ "pattern": "extra_root_marker" }), workspace.path());
+        let searched = exec_search_files(&json!({ "pattern": "qwertyextra" }), workspace.path());
+        // An explicit path only searches that path.
+        let scoped = exec_find_files(
+            &json!({ "pattern": "extra_root_marker", "path": "." }),
+            workspace.path(),
+        );
+        set_extra_roots(Vec::new());
+
+        let marker = extra.path().join("extra_root_marker.txt").display().to_string();
+        assert!(found.unwrap().contains(&marker));
+        assert!(searched.unwrap().contains(&marker));
+        assert!(scoped.unwrap().contains("No files found"));
+    }
+
+    // ── lsp_* ───────────────────────────────────────────────────────
 
     #[test]
-    fn test_execute_command_echo() {
-        let args = json!({ "command": "echo hello" });
-        let result = exec_execute_command(&args, ws());
-        assert!(result.is_ok());
-        assert!(result.unwrap().contains("hello"));
+    fn test_lsp_params_defined() {
+        assert_eq!(lsp_definition_params().len(), 4);
+        assert_eq!(lsp_references_params().len(), 5);
+        assert_eq!(lsp_diagnostics_params().len(), 2);
+        let rename = lsp_rename_params();
+        assert!(rename.iter().any(|p| p.name == "newName" && p.required));
     }
 
     #[test]
-    fn test_execute_command_failure() {
-        let args = json!({ "command": "false" });
-        let result = exec_execute_command(&args, ws());
-        assert!(result.is_ok()); // still returns Ok with exit code
-        assert!(result.unwrap().contains("exit code"));
+    fn test_lsp_definition_requires_position() {
+        let err = exec_lsp_definition(&json!({ "path": "Cargo.toml" }), ws()).unwrap_err();
+        assert!(err.contains("line"));
+        let err = exec_lsp_definition(&json!({ "path": "Cargo.toml", "line": 1 }), ws()).unwrap_err();
+        assert!(err.contains("column"));
+        let err = exec_lsp_definition(
+            &json!({ "path": "Cargo.toml", "line": 1, "symbol": "XYZZY_NEVER_42" }),
+            ws(),
+        )
+        .unwrap_err();
+        assert!(err.contains("not found on line 1"));
     }
 
-    // ── execute_tool dispatch ───────────────────────────────────────
+    #[test]
+    fn test_lsp_unsupported_file() {
+        let err = exec_lsp_diagnostics(&json!({ "path": "Cargo.toml" }), ws()).unwrap_err();
+        assert!(err.contains("No language server"));
+    }
+
+    // ── lint / format ───────────────────────────────────────────────
 
     #[test]
-    fn test_execute_tool_dispatch() {
-        let args = json!({ "path": file!() });
-        let result = execute_tool("read_file", &args, ws());
-        assert!(result.is_ok());
+    fn test_lint_format_params_defined() {
+        assert_eq!(lint_params().len(), 3);
+        assert_eq!(format_params().len(), 3);
     }
 
     #[test]
-    fn test_execute_tool_unknown() {
-        let result = execute_tool("no_such_tool", &json!({}), ws());
-        assert!(result.is_err());
+    fn test_lint_rejects_unknown_tool() {
+        let err = exec_lint(&json!({ "linter": "pylint" }), ws()).unwrap_err();
+        assert!(err.contains("Unknown linter"));
+        let err = exec_format(&json!({ "formatter": "black" }), ws()).unwrap_err();
+        assert!(err.contains("Unknown formatter"));
+        let err = exec_lint(&json!({ "path": "no/such/dir" }), ws()).unwrap_err();
+        assert!(err.contains("Path not found"));
     }
 
-    // ── Provider format tests ───────────────────────────────────────
+    // ── deps ────────────────────────────────────────────────────────
 
     #[test]
-    fn test_openai_format() {
-        let tools = tools_openai();
-        assert_eq!(tools.len(), 62);
-        assert_eq!(tools[0]["type"], "function");
-        assert_eq!(tools[0]["function"]["name"], "read_file");
-        assert!(tools[0]["function"]["parameters"]["properties"]["path"].is_object());
+    fn test_deps_params_defined() {
+        let params = deps_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
     }
 
     #[test]
-    fn test_anthropic_format() {
-        let tools = tools_anthropic();
-        assert_eq!(tools.len(), 62);
-        assert_eq!(tools[0]["name"], "read_file");
-        assert!(tools[0]["input_schema"]["properties"]["path"].is_object());
+    fn test_deps_errors() {
+        let err = exec_deps(&json!({}), ws()).unwrap_err();
+        assert!(err.contains("action"));
+        let err = exec_deps(&json!({ "action": "add", "ecosystem": "cargo" }), ws()).unwrap_err();
+        assert!(err.contains("needs packages"));
+        let err = exec_deps(&json!({ "action": "list", "ecosystem": "maven" }), ws()).unwrap_err();
+        assert!(err.contains("Unknown ecosystem"));
+        let err = exec_deps(&json!({ "action": "frobnicate", "ecosystem": "npm" }), ws()).unwrap_err();
+        assert!(err.contains("Unknown action"));
     }
 
+    // ── review_diff ─────────────────────────────────────────────────
+
     #[test]
-    fn test_google_format() {
-        let tools = tools_google();
-        assert_eq!(tools.len(), 62);
-        assert_eq!(tools[0]["name"], "read_file");
+    fn test_review_diff_params_defined() {
+        assert_eq!(review_diff_params().len(), 6);
+    }
+
+    #[test]
+    fn test_review_diff_rejects_bad_input() {
+        let err = exec_review_diff(&json!({ "format": "xml" }), ws()).unwrap_err();
+        assert!(err.contains("Unknown format"));
+        let err = exec_review_diff(&json!({ "range": "--output=/tmp/x" }), ws()).unwrap_err();
+        assert!(err.contains("Invalid range"));
+        let err = exec_review_diff(&json!({ "range": "no-such-ref-xyzzy..HEAD" }), ws()).unwrap_err();
+        assert!(err.contains("git diff failed"));
+    }
+
+    // ── snapshots ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_snapshots_params_defined() {
+        let params = snapshots_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_snapshots_diff_and_restore() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("plan.md"), "v1").unwrap();
+        let created = exec_snapshots(&json!({ "action": "create", "reason": "test" }), dir.path()).unwrap();
+        let id = created.split_whitespace().nth(1).unwrap().to_string();
+
+        std::fs::write(dir.path().join("plan.md"), "v2!").unwrap();
+        let diff = exec_snapshots(&json!({ "action": "diff" }), dir.path()).unwrap();
+        assert!(diff.ends_with("M plan.md"));
+        let listed = exec_snapshots(&json!({ "action": "list" }), dir.path()).unwrap();
+        assert!(listed.starts_with(&id));
+
+        assert!(exec_snapshots(&json!({ "action": "restore" }), dir.path()).is_err());
+        exec_snapshots(&json!({ "action": "restore", "id": id }), dir.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.path().join("plan.md")).unwrap(), "v1");
+    }
+
+    // ── contacts ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_contacts_params_defined() {
+        let params = contacts_params();
+        assert_eq!(params.len(), 9);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_contacts_add_resolve_remove() {
+        let dir = tempfile::TempDir::new().unwrap();
+        crate::contacts::set_config(crate::contacts::ContactsConfig::default(), dir.path());
+
+        let added = exec_contacts(
+            &json!({
+                "action": "add",
+                "name": "Zed Testperson",
+                "aliases": ["zeddy"],
+                "identities": { "Telegram": "4242" }
+            }),
+            ws(),
+        );
+        assert!(added.unwrap().starts_with("Added"));
+        let resolved = exec_contacts(
+            &json!({ "action": "resolve", "name": "zeddy", "channel": "telegram" }),
+            ws(),
+        );
+        assert!(resolved.unwrap().ends_with("telegram 4242"));
+        let missing = exec_contacts(
+            &json!({ "action": "resolve", "name": "zeddy", "channel": "discord" }),
+            ws(),
+        );
+        assert!(missing.unwrap_err().contains("no discord identity"));
+        assert!(exec_contacts(&json!({ "action": "remove", "name": "Zed Testperson" }), ws()).is_ok());
+        assert!(exec_contacts(&json!({ "action": "get", "name": "zeddy" }), ws()).is_err());
+    }
+
+    // ── ble ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_ble_params() {
+        let params = ble_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_ble_write_gated() {
+        let perms = std::collections::HashMap::new();
+        let write = json!({ "action": "write", "device": "lamp", "characteristic": "2a06", "value": "0x01" });
+        assert_eq!(permission_for(&perms, "ble", &write), ToolPermission::Ask);
+        assert_eq!(permission_for(&perms, "ble", &json!({ "action": "read" })), ToolPermission::Allow);
+    }
+
+    // ── mqtt ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_mqtt_params() {
+        let params = mqtt_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_mqtt_publish_requires_topic() {
+        let result = exec_mqtt(&json!({ "action": "publish", "payload": "on" }), ws());
+        assert!(result.unwrap_err().contains("topic"));
+        let result = exec_mqtt(&json!({ "action": "status" }), ws()).unwrap();
+        assert!(result.contains("connected"));
+    }
+
+    // ── cloud_status ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_cloud_status_params() {
+        let params = cloud_status_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().all(|p| !p.required));
+    }
+
+    #[test]
+    fn test_cloud_status_unknown_provider() {
+        let result = exec_cloud_status(&json!({ "providers": ["azure"] }), ws());
+        assert!(result.unwrap_err().contains("Unknown provider"));
+    }
+
+    // ── render_report ────────────────────────────────────────────────
+
+    #[test]
+    fn test_render_report_params() {
+        let params = render_report_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().all(|p| !p.required));
+    }
+
+    #[test]
+    fn test_render_report_validation() {
+        let result = exec_render_report(&json!({}), ws());
+        assert!(result.unwrap_err().contains("markdown"));
+        let result = exec_render_report(&json!({ "markdown": "# x", "format": "docx" }), ws());
+        assert!(result.unwrap_err().contains("Unknown format"));
+    }
+
+    // ── plot ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_plot_params() {
+        let params = plot_params();
+        assert_eq!(params.len(), 11);
+        assert!(params.iter().all(|p| !p.required));
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_plot_writes_png() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = json!({
+            "data": [{ "day": "Mon", "visits": 3 }, { "day": "Tue", "visits": 5 }],
+            "type": "bar",
+            "output": "charts/visits",
+        });
+        let result = exec_plot(&args, dir.path()).unwrap();
+        let path = dir.path().join("charts/visits.png");
+        assert!(result.contains(&format!("MEDIA: {}", path.display())));
+        assert!(path.exists());
+
+        let result = exec_plot(&json!({ "data": "a,b\n" }), dir.path());
+        assert!(result.unwrap_err().contains("no rows"));
+    }
+
+    // ── xlsx_write ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_xlsx_write_params() {
+        let params = xlsx_write_params();
+        assert_eq!(params.len(), 3);
+        assert!(params.iter().any(|p| p.name == "operations" && p.required));
+    }
+
+    #[test]
+    fn test_xlsx_write_validation() {
+        let result = exec_xlsx_write(&json!({ "path": "out.csv", "operations": [] }), ws());
+        assert!(result.unwrap_err().contains(".xlsx"));
+        let result = exec_xlsx_write(&json!({ "path": "out.xlsx" }), ws());
+        assert!(result.unwrap_err().contains("operations"));
+    }
+
+    // ── serial ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_serial_params() {
+        let params = serial_params();
+        assert_eq!(params.len(), 8);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    // ── execute_command ─────────────────────────────────────────────
+
+    #[test]
+    fn test_execute_command_echo() {
+        let args = json!({ "command": "echo hello" });
+        let result = exec_execute_command(&args, ws());
+        assert!(result.is_ok());
+        assert!(result.unwrap().contains("hello"));
+    }
+
+    #[test]
+    fn test_execute_command_failure() {
+        let args = json!({ "command": "false" });
+        let result = exec_execute_command(&args, ws());
+        assert!(result.is_ok()); // still returns Ok with exit code
+        assert!(result.unwrap().contains("exit code"));
+    }
+
+    // ── execute_tool dispatch ───────────────────────────────────────
+
+    #[test]
+    fn test_execute_tool_dispatch() {
+        let args = json!({ "path": file!() });
+        let result = execute_tool("read_file", &args, ws());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_execute_tool_unknown() {
+        let result = execute_tool("no_such_tool", &json!({}), ws());
+        assert!(result.is_err());
+    }
+
+    // ── Provider format tests ───────────────────────────────────────
+
+    #[test]
+    fn test_openai_format() {
+        let tools = tools_openai("openai", "gpt-4o", None);
+        assert_eq!(tools.len(), 100);
+        assert_eq!(tools[0]["type"], "function");
+        assert_eq!(tools[0]["function"]["name"], "read_file");
+        assert!(tools[0]["function"]["parameters"]["properties"]["path"].is_object());
+    }
+
+    #[test]
+    fn test_anthropic_format() {
+        let tools = tools_anthropic("claude-sonnet-4", None);
+        assert_eq!(tools.len(), 100);
+        assert_eq!(tools[0]["name"], "read_file");
+        assert!(tools[0]["input_schema"]["properties"]["path"].is_object());
+    }
+
+    #[test]
+    fn test_google_format() {
+        let tools = tools_google("gemini-2.5-flash", None);
+        assert_eq!(tools.len(), 100);
+        assert_eq!(tools[0]["name"], "read_file");
+    }
+
+    #[test]
+    fn test_tool_subset() {
+        let subset = vec!["web_fetch".to_string(), "read_file".to_string()];
+        let tools = tools_openai("openai", "gpt-4o", Some(&subset));
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0]["function"]["name"], "read_file");
+        assert!(tool_catalog().iter().any(|(name, _)| name == "web_fetch"));
     }
 
     // ── resolve_path helper ─────────────────────────────────────────
@@ -1460,6 +2725,12 @@ mod tests {
         assert_eq!(result, std::path::PathBuf::from("/workspace/relative/path.txt"));
     }
 
+    #[test]
+    fn test_resolve_path_parent_components() {
+        let result = helpers::resolve_path(Path::new("/workspace/project"), "../../etc/./passwd");
+        assert_eq!(result, std::path::PathBuf::from("/etc/passwd"));
+    }
+
     // ── web_fetch ───────────────────────────────────────────────────
 
     #[test]
@@ -1625,7 +2896,7 @@ mod tests {
     #[test]
     fn test_cron_params_defined() {
         let params = cron_params();
-        assert_eq!(params.len(), 5);
+        assert_eq!(params.len(), 7);
         assert!(params.iter().any(|p| p.name == "action" && p.required));
         assert!(params.iter().any(|p| p.name == "jobId" && !p.required));
     }
@@ -1646,6 +2917,208 @@ mod tests {
         assert!(result.unwrap_err().contains("Unknown action"));
     }
 
+    #[test]
+    fn test_cron_export_writes_ics() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let add = json!({
+            "action": "add",
+            "job": {
+                "jobId": "job-ics",
+                "name": "Standup",
+                "schedule": { "kind": "cron", "expr": "0 9 * * *", "tz": "UTC" },
+                "sessionTarget": "main",
+                "payload": { "kind": "systemEvent", "text": "standup" },
+                "createdMs": 0
+            }
+        });
+        exec_cron(&add, dir.path()).unwrap();
+
+        let result = exec_cron(&json!({ "action": "export" }), dir.path()).unwrap();
+        assert!(result.contains("Exported 1 job"));
+        let ics = std::fs::read_to_string(dir.path().join("exports/schedule.ics")).unwrap();
+        assert!(ics.contains("SUMMARY:Standup"));
+        assert!(ics.contains("T090000Z"));
+    }
+
+    // ── tasks ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_tasks_params_defined() {
+        let params = tasks_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_tasks_missing_action() {
+        let args = json!({});
+        let result = exec_tasks(&args, ws());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing required parameter"));
+    }
+
+    #[test]
+    fn test_tasks_add_and_list() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = json!({ "action": "add", "prompt": "Refactor module", "priority": "high" });
+        let result = exec_tasks(&args, dir.path()).unwrap();
+        assert!(result.contains("Queued task"));
+
+        let list = exec_tasks(&json!({ "action": "list" }), dir.path()).unwrap();
+        assert!(list.contains("Refactor module"));
+        assert!(list.contains("High"));
+    }
+
+    // ── conversations ───────────────────────────────────────────────
+
+    #[test]
+    fn test_conversations_params_defined() {
+        let params = conversations_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_conversations_show_and_export() {
+        use crate::conversations::{conversations_dir, ConversationStore};
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ConversationStore::new(&conversations_dir(dir.path())).unwrap();
+        let mut conv = store.load_or_new("telegram", "42").unwrap();
+        conv.messages.push(crate::gateway::ChatMessage::text("user", "Remind me about the dentist"));
+        conv.messages.push(crate::gateway::ChatMessage::text("assistant", "Done."));
+        store.save(&mut conv).unwrap();
+
+        let list = exec_conversations(&json!({ "action": "list" }), dir.path()).unwrap();
+        assert!(list.contains("telegram:42"));
+
+        let args = json!({ "action": "show", "messenger": "telegram", "chat": "42", "limit": 1 });
+        let shown = exec_conversations(&args, dir.path()).unwrap();
+        assert!(shown.contains("[assistant] Done."));
+        assert!(!shown.contains("dentist"));
+
+        let args = json!({ "action": "export", "messenger": "telegram", "chat": "42" });
+        exec_conversations(&args, dir.path()).unwrap();
+        let md = dir.path().join("exports/conversations/telegram-42.md");
+        assert!(std::fs::read_to_string(md).unwrap().contains("Remind me about the dentist"));
+
+        let args = json!({ "action": "clear", "messenger": "telegram", "chat": "42" });
+        assert!(exec_conversations(&args, dir.path()).unwrap().contains("Cleared"));
+        let args = json!({ "action": "show", "messenger": "telegram", "chat": "42" });
+        assert!(exec_conversations(&args, dir.path()).is_err());
+    }
+
+    // ── dry-run ─────────────────────────────────────────────────────
+
+    #[test]
+    fn test_dry_run_simulates_mutating_tools() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = json!({ "path": "out.txt", "content": "hello" });
+        let result = dry_run::simulate("write_file", &args, dir.path()).unwrap().unwrap();
+        assert!(result.contains("[dry-run]"));
+        assert!(result.contains("would create"));
+        assert!(!dir.path().join("out.txt").exists());
+
+        let args = json!({ "command": "rm -rf build" });
+        let result = dry_run::simulate("execute_command", &args, dir.path()).unwrap().unwrap();
+        assert!(result.contains("rm -rf build"));
+
+        let args = json!({ "action": "send", "message": "hi", "target": "#general" });
+        assert!(dry_run::simulate("message", &args, dir.path()).is_some());
+    }
+
+    #[test]
+    fn test_dry_run_passes_through_read_only_tools() {
+        let args = json!({ "path": "Cargo.toml" });
+        assert!(dry_run::simulate("read_file", &args, ws()).is_none());
+        let args = json!({ "action": "list" });
+        assert!(dry_run::simulate("cron", &args, ws()).is_none());
+    }
+
+    #[test]
+    fn test_dry_run_edit_reports_missing_match() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "alpha beta").unwrap();
+        let args = json!({ "path": "a.txt", "old_string": "gamma", "new_string": "delta" });
+        let result = dry_run::simulate("edit_file", &args, dir.path()).unwrap();
+        assert!(result.unwrap_err().contains("matches 0 times"));
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).unwrap(), "alpha beta");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_override_is_per_connection() {
+        let (toggled, other) = (DryRunSession::default(), DryRunSession::default());
+        dry_run_session_scope(toggled.clone(), async { set_session_dry_run(Some(true)) }).await;
+        assert!(dry_run_session_scope(toggled.clone(), async { is_dry_run() }).await);
+        assert!(!dry_run_session_scope(other, async { is_dry_run() }).await);
+        assert!(!is_dry_run());
+
+        // The override follows the call onto the blocking pool.
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = json!({ "path": "a.txt", "content": "hi" });
+        let result = dry_run_session_scope(toggled, execute_tool_offloaded("write_file", &args, dir.path())).await;
+        assert!(result.unwrap().starts_with("[dry-run]"));
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    // ── plan ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_plan_params_defined() {
+        let params = plan_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_plan_invalid_action() {
+        let args = json!({ "action": "invalid" });
+        let result = exec_plan(&args, ws());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Unknown action"));
+    }
+
+    #[test]
+    fn test_plan_lifecycle() {
+        let args = json!({ "action": "create", "title": "Refactor", "steps": ["Read code", "Edit code"] });
+        let result = exec_plan(&args, ws()).unwrap();
+        assert!(result.contains("[ ] 1. Read code"));
+
+        let args = json!({ "action": "update", "step": 1, "status": "done" });
+        let result = exec_plan(&args, ws()).unwrap();
+        assert!(result.contains("[x] 1. Read code"));
+
+        let args = json!({ "action": "insert", "step": 2, "text": "Plan edits" });
+        let result = exec_plan(&args, ws()).unwrap();
+        assert!(result.contains("[ ] 2. Plan edits"));
+        assert!(result.contains("(1/3)"));
+
+        exec_plan(&json!({ "action": "clear" }), ws()).unwrap();
+        let result = exec_plan(&json!({ "action": "show" }), ws()).unwrap();
+        assert_eq!(result, "No plan.");
+    }
+
+    // ── script_run ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_script_run_params_defined() {
+        let params = script_run_params();
+        assert_eq!(params.len(), 3);
+        assert!(params.iter().all(|p| !p.required));
+    }
+
+    #[test]
+    #[cfg(feature = "scripting")]
+    fn test_script_run_inline() {
+        let args = json!({ "code": "ARGS.a + ARGS.b", "args": { "a": 2, "b": 3 } });
+        assert_eq!(exec_script_run(&args, ws()).unwrap(), "5");
+    }
+
+    #[test]
+    fn test_script_run_missing_script() {
+        let args = json!({ "script": "does-not-exist" });
+        assert!(exec_script_run(&args, ws()).unwrap_err().contains("Script not found"));
+    }
+
     // ── sessions_list ───────────────────────────────────────────────
 
     #[test]
@@ -1660,8 +3133,9 @@ mod tests {
     #[test]
     fn test_sessions_spawn_params_defined() {
         let params = sessions_spawn_params();
-        assert_eq!(params.len(), 7);
+        assert_eq!(params.len(), 8);
         assert!(params.iter().any(|p| p.name == "task" && p.required));
+        assert!(params.iter().any(|p| p.name == "isolation" && !p.required));
     }
 
     #[test]
@@ -1672,6 +3146,65 @@ mod tests {
         assert!(result.unwrap_err().contains("Missing required parameter"));
     }
 
+    #[test]
+    fn test_sessions_spawn_unknown_isolation() {
+        let args = json!({ "task": "x", "isolation": "container" });
+        let err = exec_sessions_spawn(&args, ws()).unwrap_err();
+        assert!(err.contains("Unknown isolation"));
+    }
+
+    // ── sessions_worktree ───────────────────────────────────────────
+
+    #[test]
+    fn test_sessions_worktree_params_defined() {
+        let params = sessions_worktree_params();
+        assert_eq!(params.len(), 2);
+        assert!(params.iter().all(|p| p.required));
+    }
+
+    #[test]
+    fn test_sessions_worktree_unknown_session() {
+        let args = json!({ "action": "diff", "sessionKey": "agent:main:subagent:nope" });
+        let err = exec_sessions_worktree(&args, ws()).unwrap_err();
+        assert!(err.contains("Session not found"));
+    }
+
+    // ── orchestrate ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_orchestrate_params_defined() {
+        let params = orchestrate_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+    }
+
+    #[test]
+    fn test_orchestrate_start_and_aggregate() {
+        let args = json!({
+            "action": "start",
+            "task": "Review the parser",
+            "agents": ["researcher", {"role": "reviewer", "model": "gpt-4o"}]
+        });
+        let result = exec_orchestrate(&args, ws()).unwrap();
+        assert!(result.contains("2 sub-agent(s)"));
+        assert!(result.contains("gpt-4o"));
+
+        let id = result
+            .split_whitespace()
+            .nth(1)
+            .unwrap()
+            .to_string();
+        let agg = exec_orchestrate(&json!({"action": "aggregate", "orchestrationId": id}), ws()).unwrap();
+        assert!(agg.contains("\"complete\": false"));
+    }
+
+    #[test]
+    fn test_orchestrate_missing_action() {
+        let result = exec_orchestrate(&json!({}), ws());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("Missing required parameter"));
+    }
+
     // ── sessions_send ───────────────────────────────────────────────
 
     #[test]
@@ -1781,6 +3314,11 @@ mod tests {
         assert!(is_secrets_tool("secrets_list"));
         assert!(is_secrets_tool("secrets_get"));
         assert!(is_secrets_tool("secrets_store"));
+        assert!(is_secrets_tool("generate_secret"));
+        assert!(is_secrets_tool("totp"));
+        assert!(is_secrets_tool("ssh_key"));
+        assert!(is_secrets_tool("encrypt_file"));
+        assert!(is_secrets_tool("decrypt_file"));
         assert!(!is_secrets_tool("read_file"));
         assert!(!is_secrets_tool("qmd_search"));
     }
@@ -1807,6 +3345,41 @@ mod tests {
         assert!(params.iter().any(|p| p.name == "value" && p.required));
     }
 
+    #[test]
+    fn test_generate_secret_params_defined() {
+        let params = generate_secret_params();
+        assert_eq!(params.len(), 17);
+        assert!(params.iter().all(|p| !p.required));
+        assert!(params.iter().any(|p| p.name == "storeAs"));
+    }
+
+    #[test]
+    fn test_totp_params_defined() {
+        let params = totp_params();
+        assert_eq!(params.len(), 8);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+        assert!(params.iter().any(|p| p.name == "secret" && !p.required));
+    }
+
+    #[test]
+    fn test_ssh_key_params_defined() {
+        let params = ssh_key_params();
+        assert_eq!(params.len(), 8);
+        assert!(params.iter().any(|p| p.name == "action" && p.required));
+        assert!(params.iter().any(|p| p.name == "lifetime"));
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_file_params_defined() {
+        let params = encrypt_file_params();
+        assert_eq!(params.len(), 7);
+        assert!(params.iter().any(|p| p.name == "path" && p.required));
+        assert!(params.iter().any(|p| p.name == "recipients" && p.param_type == "array"));
+        let params = decrypt_file_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().any(|p| p.name == "path" && p.required));
+    }
+
     #[test]
     fn test_protected_path_without_init() {
         // Before set_credentials_dir is called, nothing is protected.
@@ -1906,16 +3479,134 @@ mod tests {
         assert!(result.unwrap().contains("Is URL: true"));
     }
 
+    // ── ocr ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_ocr_params_defined() {
+        let params = ocr_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().any(|p| p.name == "path" && p.required));
+    }
+
+    #[test]
+    fn test_ocr_rejects_bad_args() {
+        let result = exec_ocr(&json!({}), ws());
+        assert!(result.unwrap_err().contains("Missing required parameter"));
+        let result = exec_ocr(&json!({ "path": "README.md" }), ws());
+        assert!(result.is_err());
+    }
+
+    // ── qr ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_qr_params_defined() {
+        let params = qr_params();
+        assert_eq!(params.len(), 7);
+        assert!(params.iter().all(|p| !p.required));
+    }
+
+    #[test]
+    fn test_qr_text_only() {
+        let result = exec_qr(&json!({ "data": "https://example.com", "format": "text" }), ws()).unwrap();
+        assert!(result.starts_with("```"));
+        assert!(!result.contains("MEDIA:"));
+        let result = exec_qr(&json!({ "action": "scan" }), ws());
+        assert!(result.unwrap_err().contains("Unknown action"));
+    }
+
+    // ── transcribe ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_transcribe_params_defined() {
+        let params = transcribe_params();
+        assert_eq!(params.len(), 4);
+        assert!(params.iter().any(|p| p.name == "path" && p.required));
+    }
+
+    // ── media_convert ───────────────────────────────────────────────
+
+    #[test]
+    fn test_media_convert_params_defined() {
+        let params = media_convert_params();
+        assert_eq!(params.len(), 11);
+        assert!(params.iter().any(|p| p.name == "operation" && p.required));
+    }
+
+    #[test]
+    fn test_media_convert_rejects_bad_args() {
+        let result = exec_media_convert(&json!({ "operation": "transcode" }), ws());
+        assert!(result.unwrap_err().contains("Unknown operation"));
+        let result = exec_media_convert(&json!({ "operation": "status", "sessionId": "nope" }), ws());
+        assert!(result.unwrap_err().contains("No session found"));
+    }
+
+    // ── translate ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_translate_params_defined() {
+        let params = translate_params();
+        assert_eq!(params.len(), 5);
+        assert!(params.iter().any(|p| p.name == "text" && p.required));
+    }
+
+    #[test]
+    fn test_translate_detect() {
+        let result = exec_translate(&json!({ "action": "detect", "text": "Wie geht es dir und was machst du?" }), ws()).unwrap();
+        assert!(result.contains("\"de\""));
+        assert!(exec_translate(&json!({}), ws()).unwrap_err().contains("text"));
+    }
+
+    // ── calc ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_calc_params_defined() {
+        let params = calc_params();
+        assert_eq!(params.len(), 11);
+        assert!(params.iter().any(|p| p.name == "op" && p.required));
+    }
+
+    #[test]
+    fn test_calc_eval() {
+        let result = exec_calc(&json!({ "op": "eval", "expression": "(17 + 3) * 1.5" }), ws()).unwrap();
+        assert_eq!(result, "(17 + 3) * 1.5 = 30");
+        assert!(exec_calc(&json!({ "op": "eval" }), ws()).unwrap_err().contains("expression"));
+    }
+
     // ── nodes ───────────────────────────────────────────────────────
 
     #[test]
     fn test_nodes_params_defined() {
         let params = nodes_params();
-        assert_eq!(params.len(), 8);
+        assert_eq!(params.len(), 11);
         assert!(params.iter().any(|p| p.name == "action" && p.required));
         assert!(params.iter().any(|p| p.name == "node" && !p.required));
     }
 
+    #[test]
+    fn test_power_actions_gated() {
+        use std::collections::HashMap;
+        let reboot = json!({ "action": "reboot", "node": "user@lab" });
+        let status = json!({ "action": "status" });
+        let mut perms = HashMap::new();
+        assert_eq!(permission_for(&perms, "nodes", &status), ToolPermission::Allow);
+        assert_eq!(permission_for(&perms, "nodes", &reboot), ToolPermission::Ask);
+
+        perms.insert("nodes.reboot".to_string(), ToolPermission::Allow);
+        assert_eq!(permission_for(&perms, "nodes", &reboot), ToolPermission::Allow);
+
+        assert!(unattended_denial(&perms, "nodes", &reboot).is_none());
+
+        perms.clear();
+        perms.insert("nodes".to_string(), ToolPermission::Deny);
+        assert_eq!(permission_for(&perms, "nodes", &reboot), ToolPermission::Deny);
+        assert!(unattended_denial(&perms, "nodes", &reboot).unwrap().contains("nodes.reboot"));
+        // A denied tool is denied outright where nobody can confirm it.
+        assert!(unattended_denial(&perms, "nodes", &status).unwrap().contains("denied by user policy"));
+
+        perms.insert("nodes".to_string(), ToolPermission::Ask);
+        assert!(unattended_denial(&perms, "nodes", &status).unwrap().contains("\"nodes\" = \"allow\""));
+    }
+
     #[test]
     fn test_nodes_missing_action() {
         let args = json!({});
@@ -2162,6 +3853,44 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    // ── system_info ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_system_info_params_defined() {
+        let params = system_info_params();
+        assert_eq!(params.len(), 6);
+        assert!(params.iter().all(|p| !p.required));
+    }
+
+    #[test]
+    fn test_system_info_local() {
+        let result = exec_system_info(&json!({ "top": 3 }), ws()).unwrap();
+        let report: Value = serde_json::from_str(&result).unwrap();
+        assert!(report["cpu"]["cores"].as_u64().unwrap() > 0);
+        assert!(report["processes"].as_array().unwrap().len() <= 3);
+    }
+
+    #[test]
+    fn test_system_info_bad_sort() {
+        assert!(exec_system_info(&json!({ "sortBy": "name" }), ws()).is_err());
+    }
+
+    // ── logs ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_logs_params_defined() {
+        let params = logs_params();
+        assert_eq!(params.len(), 11);
+        assert!(params.iter().all(|p| !p.required));
+        assert!(params.iter().any(|p| p.name == "follow"));
+    }
+
+    #[test]
+    fn test_logs_missing_source() {
+        let result = exec_logs(&json!({ "pattern": "ERROR" }), ws());
+        assert!(result.unwrap_err().contains("path or unit"));
+    }
+
     // ── battery_health ──────────────────────────────────────────────
 
     #[test]
@@ -2378,4 +4107,121 @@ mod tests {
         let result = exec_summarize_file(&args, ws());
         assert!(result.is_err());
     }
+
+    // ── registry ────────────────────────────────────────────────────
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct GreetArgs {
+        /// Who to greet.
+        name: String,
+        #[serde(default)]
+        shout: bool,
+    }
+
+    struct Greet;
+
+    #[async_trait::async_trait]
+    impl TypedTool for Greet {
+        type Args = GreetArgs;
+        const NAME: &'static str = "greet";
+        const DESCRIPTION: &'static str = "Greet someone.";
+
+        async fn run(&self, args: GreetArgs, _workspace_dir: &Path) -> Result<String, String> {
+            let text = format!("Hello, {}!", args.name);
+            Ok(if args.shout { text.to_uppercase() } else { text })
+        }
+    }
+
+    #[test]
+    fn test_registry_schema_derivation() {
+        let schema = Greet.parameters();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["name"]["type"], "string");
+        assert_eq!(schema["properties"]["name"]["description"], "Who to greet.");
+        assert_eq!(schema["required"], json!(["name"]));
+        assert!(schema.get("$schema").is_none());
+    }
+
+    #[test]
+    fn test_registry_rejects_collisions() {
+        let reg = ToolRegistry::default();
+        assert!(reg.register(Greet).is_ok());
+        assert!(reg.register(Greet).is_err());
+        assert_eq!(reg.names(), vec!["greet".to_string()]);
+        assert!(reg.unregister("greet"));
+        assert!(!reg.contains("greet"));
+    }
+
+    struct Shadow;
+
+    #[async_trait::async_trait]
+    impl Tool for Shadow {
+        fn name(&self) -> &str { "read_file" }
+        fn description(&self) -> &str { "Not the real one." }
+        fn parameters(&self) -> Value { json!({ "type": "object", "properties": {} }) }
+        async fn execute(&self, _args: &Value, _ws: &Path) -> Result<String, String> {
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_registry_rejects_builtin_names() {
+        let reg = ToolRegistry::default();
+        let err = reg.register(Shadow).unwrap_err();
+        assert!(err.contains("built-in"));
+    }
+
+    #[tokio::test]
+    async fn test_typed_tool_execute() {
+        let out = Greet.execute(&json!({ "name": "Ada", "shout": true }), ws()).await.unwrap();
+        assert_eq!(out, "HELLO, ADA!");
+        let err = Greet.execute(&json!({ "shout": true }), ws()).await.unwrap_err();
+        assert!(err.contains("Invalid arguments for greet"));
+    }
+
+    #[test]
+    fn test_custom_tools_are_held_back_in_dry_run() {
+        let held = Greet.simulate(&json!({ "name": "Ada" }), ws()).unwrap().unwrap();
+        assert!(held.starts_with("[dry-run] custom tool greet"));
+        assert!(Shadow.simulate(&json!({}), ws()).is_some());
+    }
+
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl Tool for Echo {
+        fn name(&self) -> &str { "test_echo" }
+        fn description(&self) -> &str { "Echo the arguments." }
+        fn parameters(&self) -> Value { json!({ "type": "object", "properties": {} }) }
+        async fn execute(&self, args: &Value, _ws: &Path) -> Result<String, String> {
+            Ok(args.to_string())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sync_callers_reach_custom_tools() {
+        registry().register(Echo).unwrap();
+        let out = tokio::task::spawn_blocking(|| execute_tool("test_echo", &json!({ "x": 1 }), ws()))
+            .await
+            .unwrap();
+        registry().unregister("test_echo");
+        assert_eq!(out.unwrap(), r#"{"x":1}"#);
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_async_falls_back_to_builtin() {
+        let result = execute_tool_async("read_file", &json!({ "path": "Cargo.toml" }), ws()).await;
+        assert!(result.unwrap().contains("[workspace]"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tool_offloaded_keeps_trace_id() {
+        let trace_id = crate::observability::trace::new_trace_id();
+        let result = crate::observability::trace::scope(
+            trace_id,
+            execute_tool_offloaded("read_file", &json!({ "path": "Cargo.toml" }), ws()),
+        )
+        .await;
+        assert!(result.unwrap().contains("[workspace]"));
+    }
 }
@@ -0,0 +1,48 @@
+//! The `mqtt` tool: publish commands and inspect messages from the broker
+//! connection kept by the gateway (see [`crate::mqtt`]).
+
+use crate::mqtt;
+use serde_json::{json, Value};
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Publish to MQTT topics and read recent messages.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_mqtt(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    debug!(action, "Executing mqtt tool");
+
+    match action {
+        "status" => Ok(mqtt::status().to_string()),
+        "publish" => {
+            let topic = args
+                .get("topic")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: topic")?;
+            // Objects and numbers are sent as JSON, which is what most
+            // IoT bridges (zigbee2mqtt, Tasmota) expect.
+            let payload = match args.get("payload") {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            };
+            let qos = args.get("qos").and_then(|v| v.as_u64()).unwrap_or(0);
+            let retain = args.get("retain").and_then(|v| v.as_bool()).unwrap_or(false);
+            mqtt::publish(topic, &payload, qos, retain)?;
+            Ok(format!("Published {} byte(s) to {}", payload.len(), topic))
+        }
+        "recent" => {
+            let filter = args.get("topic").and_then(|v| v.as_str());
+            let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+            let messages = mqtt::recent(filter, limit);
+            if messages.is_empty() {
+                return Ok("No MQTT messages received yet.".to_string());
+            }
+            Ok(json!({ "messages": messages }).to_string())
+        }
+        other => Err(format!("Unknown action: {}. Use status, publish or recent", other)),
+    }
+}
@@ -0,0 +1,308 @@
+//! The `ocr` tool: text from images and scanned PDFs.
+//!
+//! Tesseract is preferred: its TSV output gives every word a bounding box,
+//! which is grouped here into text blocks.  Without it, a vision model (via
+//! the `image` tool's providers) transcribes each page, without coordinates.
+//! PDFs are rasterised page by page with `pdftoppm` first.
+//!
+//! `read_file` calls [`ocr_text`] for images and for PDFs that have no text
+//! layer, so the model doesn't need to know which is which.
+
+use super::gateway_tools::exec_image;
+use super::helpers::{is_protected_path, resolve_path, ScratchDir, VAULT_ACCESS_DENIED};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, instrument};
+
+/// Extensions treated as images.
+pub(crate) const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp", "pnm"];
+
+const DEFAULT_MAX_PAGES: usize = 20;
+
+/// Rendering resolution for PDF pages; 300 dpi is what Tesseract is tuned for.
+const PDF_DPI: &str = "300";
+
+const VISION_PROMPT: &str = "Transcribe all text in this image exactly as written, \
+                             preserving line breaks and reading order. Output only the text.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Engine {
+    Auto,
+    Tesseract,
+    Vision,
+}
+
+/// A block of text, with its bounding box in pixels when the engine gives one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Block {
+    pub page: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<[u32; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    pub text: String,
+}
+
+/// Group Tesseract TSV words into blocks.  Columns are: level, page_num,
+/// block_num, par_num, line_num, word_num, left, top, width, height, conf,
+/// text; words are level 5.
+fn parse_tsv(tsv: &str, page: usize) -> Vec<Block> {
+    struct Acc {
+        block: u32,
+        line: (u32, u32),
+        bbox: [u32; 4],
+        conf: Vec<f32>,
+        text: String,
+    }
+    let mut blocks = Vec::new();
+    let mut current: Option<Acc> = None;
+    let finish = |acc: Acc, blocks: &mut Vec<Block>| {
+        let [l, t, r, b] = acc.bbox;
+        blocks.push(Block {
+            page,
+            bbox: Some([l, t, r - l, b - t]),
+            confidence: Some((acc.conf.iter().sum::<f32>() / acc.conf.len().max(1) as f32 * 10.0).round() / 10.0),
+            text: acc.text,
+        });
+    };
+
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.splitn(12, '\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let word = cols[11].trim();
+        let conf: f32 = cols[10].parse().unwrap_or(-1.0);
+        if word.is_empty() || conf < 0.0 {
+            continue;
+        }
+        let num = |i: usize| cols[i].parse::<u32>().unwrap_or(0);
+        let (block, line) = (num(2), (num(3), num(4)));
+        let (left, top) = (num(6), num(7));
+        let (right, bottom) = (left + num(8), top + num(9));
+
+        if current.as_ref().is_some_and(|acc| acc.block != block) {
+            finish(current.take().unwrap(), &mut blocks);
+        }
+        match &mut current {
+            Some(acc) => {
+                acc.text.push(if acc.line == line { ' ' } else { '\n' });
+                acc.text.push_str(word);
+                acc.line = line;
+                acc.bbox = [acc.bbox[0].min(left), acc.bbox[1].min(top), acc.bbox[2].max(right), acc.bbox[3].max(bottom)];
+                acc.conf.push(conf);
+            }
+            None => {
+                current = Some(Acc {
+                    block,
+                    line,
+                    bbox: [left, top, right, bottom],
+                    conf: vec![conf],
+                    text: word.to_string(),
+                })
+            }
+        }
+    }
+    if let Some(acc) = current {
+        finish(acc, &mut blocks);
+    }
+    blocks
+}
+
+fn tesseract_page(image: &Path, lang: &str, page: usize) -> Result<Vec<Block>, String> {
+    let output = Command::new("tesseract")
+        .arg(image)
+        .args(["stdout", "-l", lang, "tsv"])
+        .output()
+        .map_err(|e| format!("Failed to run tesseract: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("tesseract failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout), page))
+}
+
+fn vision_available() -> bool {
+    ["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GOOGLE_API_KEY"]
+        .iter()
+        .any(|k| std::env::var(k).is_ok_and(|v| !v.is_empty()))
+}
+
+fn vision_page(image: &Path, page: usize) -> Result<Vec<Block>, String> {
+    if !vision_available() {
+        return Err("Vision OCR needs OPENAI_API_KEY, ANTHROPIC_API_KEY or GOOGLE_API_KEY".to_string());
+    }
+    let args = json!({ "image": image.display().to_string(), "prompt": VISION_PROMPT });
+    let text = exec_image(&args, image.parent().unwrap_or(image))?;
+    Ok(vec![Block { page, bbox: None, confidence: None, text: text.trim().to_string() }])
+}
+
+/// Render PDF pages to PNGs in `dir`, in page order.
+fn rasterize_pdf(pdf: &Path, dir: &Path, max_pages: usize) -> Result<Vec<PathBuf>, String> {
+    let output = Command::new("pdftoppm")
+        .args(["-r", PDF_DPI, "-png", "-f", "1", "-l", &max_pages.to_string()])
+        .arg(pdf)
+        .arg(dir.join("page"))
+        .output()
+        .map_err(|e| {
+            format!(
+                "Failed to run pdftoppm: {}. Install poppler (poppler-utils) to OCR PDFs.",
+                e
+            )
+        })?;
+    if !output.status.success() {
+        return Err(format!("pdftoppm failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    // Names are zero-padded to the page count's width, so they sort.
+    let mut pages: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read rendered pages: {}", e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "png"))
+        .collect();
+    pages.sort();
+    Ok(pages)
+}
+
+/// OCR an image or PDF.  Returns the engine used and the blocks found.
+pub(crate) fn recognize(path: &Path, lang: &str, max_pages: usize, engine: Engine) -> Result<(&'static str, Vec<Block>), String> {
+    let engine = match engine {
+        Engine::Auto if which::which("tesseract").is_ok() => Engine::Tesseract,
+        Engine::Auto if vision_available() => Engine::Vision,
+        Engine::Auto => {
+            return Err("No OCR engine available: install tesseract, or set OPENAI_API_KEY, \
+                        ANTHROPIC_API_KEY or GOOGLE_API_KEY for vision-model OCR"
+                .to_string())
+        }
+        other => other,
+    };
+
+    let is_pdf = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+    let scratch = if is_pdf { Some(ScratchDir::new("ocr")?) } else { None };
+    let pages = match &scratch {
+        Some(dir) => rasterize_pdf(path, dir.path(), max_pages)?,
+        None => vec![path.to_path_buf()],
+    };
+    debug!(path = %path.display(), pages = pages.len(), ?engine, "Running OCR");
+
+    let mut blocks = Vec::new();
+    for (i, page) in pages.iter().enumerate() {
+        blocks.extend(match engine {
+            Engine::Vision => vision_page(page, i + 1)?,
+            _ => tesseract_page(page, lang, i + 1)?,
+        });
+    }
+    let name = if engine == Engine::Vision { "vision" } else { "tesseract" };
+    Ok((name, blocks))
+}
+
+/// Plain text of the blocks, with page markers for multi-page documents.
+fn blocks_text(blocks: &[Block]) -> String {
+    let multi_page = blocks.iter().any(|b| b.page > 1);
+    let mut out = String::new();
+    let mut page = 0;
+    for block in blocks {
+        if multi_page && block.page != page {
+            page = block.page;
+            out.push_str(&format!("--- page {} ---\n", page));
+        }
+        out.push_str(&block.text);
+        out.push_str("\n\n");
+    }
+    out.trim_end().to_string()
+}
+
+/// OCR text for `read_file`, or `None` when there is no engine or no text.
+pub(crate) fn ocr_text(path: &Path) -> Option<String> {
+    match recognize(path, "eng", DEFAULT_MAX_PAGES, Engine::Auto) {
+        Ok((_, blocks)) if !blocks.is_empty() => Some(blocks_text(&blocks)),
+        Ok(_) => None,
+        Err(e) => {
+            debug!(path = %path.display(), error = %e, "OCR fallback unavailable");
+            None
+        }
+    }
+}
+
+/// Extract text, with block coordinates, from an image or scanned PDF.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_ocr(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing required parameter: path")?;
+    let path = resolve_path(workspace_dir, path);
+    if is_protected_path(&path) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if ext != "pdf" && !IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(format!("Unsupported file type '{}'; use an image or a PDF", ext));
+    }
+
+    let engine = match args.get("engine").and_then(|v| v.as_str()).unwrap_or("auto") {
+        "auto" => Engine::Auto,
+        "tesseract" => Engine::Tesseract,
+        "vision" => Engine::Vision,
+        other => return Err(format!("Unknown engine '{}'; use auto, tesseract or vision", other)),
+    };
+    let lang = args.get("lang").and_then(|v| v.as_str()).unwrap_or("eng");
+    if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+') {
+        return Err(format!("Invalid language '{}'; use Tesseract codes like eng or deu+eng", lang));
+    }
+    let max_pages = args
+        .get("maxPages")
+        .and_then(|v| v.as_u64())
+        .map(|n| n.clamp(1, 200) as usize)
+        .unwrap_or(DEFAULT_MAX_PAGES);
+
+    let (engine, blocks) = recognize(&path, lang, max_pages, engine)?;
+    if blocks.is_empty() {
+        return Ok(format!("No text found in {} ({}).", path.display(), engine));
+    }
+    match args.get("format").and_then(|v| v.as_str()).unwrap_or("text") {
+        "json" => Ok(json!({ "engine": engine, "blocks": blocks }).to_string()),
+        _ => Ok(blocks_text(&blocks)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+        1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t\n\
+        5\t1\t1\t1\t1\t1\t10\t20\t50\t10\t96.5\tInvoice\n\
+        5\t1\t1\t1\t1\t2\t70\t20\t40\t10\t91\t#42\n\
+        5\t1\t1\t1\t2\t1\t10\t35\t60\t12\t88\tTotal\n\
+        5\t1\t2\t1\t1\t1\t400\t500\t30\t10\t-1\t \n\
+        5\t1\t3\t1\t1\t1\t400\t500\t30\t10\t90\tPaid\n";
+
+    #[test]
+    fn test_parse_tsv_groups_blocks() {
+        let blocks = parse_tsv(TSV, 1);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text, "Invoice #42\nTotal");
+        assert_eq!(blocks[0].bbox, Some([10, 20, 100, 27]));
+        assert_eq!(blocks[0].confidence, Some(91.8));
+        assert_eq!(blocks[1].text, "Paid");
+    }
+
+    #[test]
+    fn test_blocks_text_pages() {
+        let block = |page, text: &str| Block { page, bbox: None, confidence: None, text: text.into() };
+        assert_eq!(blocks_text(&[block(1, "a"), block(1, "b")]), "a\n\nb");
+        assert_eq!(blocks_text(&[block(1, "a"), block(2, "b")]), "--- page 1 ---\na\n\n--- page 2 ---\nb");
+    }
+
+    #[test]
+    fn test_ocr_validation() {
+        let ws = Path::new(env!("CARGO_MANIFEST_DIR"));
+        assert!(exec_ocr(&json!({ "path": "Cargo.toml" }), ws).unwrap_err().contains("Unsupported"));
+        assert!(exec_ocr(&json!({ "path": "missing.png" }), ws).unwrap_err().contains("not found"));
+    }
+}
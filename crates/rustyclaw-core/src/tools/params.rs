@@ -29,6 +29,26 @@ pub fn read_file_params() -> Vec<ToolParam> {
             param_type: "integer".into(),
             required: false,
         },
+        ToolParam {
+            name: "read_binary".into(),
+            description: "Return a hexdump instead of text. Use only when you need to \
+                          inspect raw bytes; binary files otherwise get a short summary."
+                .into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "offset".into(),
+            description: "With read_binary: first byte to dump. Default 0.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "length".into(),
+            description: "With read_binary: bytes to dump. Default 256, at most 4096.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
     ]
 }
 
@@ -71,16 +91,115 @@ pub fn edit_file_params() -> Vec<ToolParam> {
             param_type: "string".into(),
             required: true,
         },
+        ToolParam {
+            name: "expected_hash".into(),
+            description: "The [hash …] read_file showed for this file. The edit fails \
+                          with the interim changes if the file has changed since."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn delete_file_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "File or directory to delete (for 'trash' and 'delete').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "action".into(),
+            description: "'trash' (default: move to the trash, restorable), 'delete' \
+                          (permanent; needs the user's confirmation), 'list' (trashed \
+                          items) or 'restore'."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "recursive".into(),
+            description: "Required to delete a non-empty directory.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "id".into(),
+            description: "For restore: trash id from 'list'. Defaults to the most recently trashed item.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
     ]
 }
 
 pub fn list_directory_params() -> Vec<ToolParam> {
-    vec![ToolParam {
-        name: "path".into(),
-        description: "Path to the directory to list.".into(),
-        param_type: "string".into(),
-        required: true,
-    }]
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "Path to the directory to list.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "detail".into(),
+            description: "Return JSON with each entry's type, size, modified time, \
+                          permissions, symlink target and gitignore status."
+                .into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "sort".into(),
+            description: "With detail: 'name' (default), 'size' (largest first) or 'mtime' (newest first).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn tree_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "Directory to show. Defaults to the workspace root.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "depth".into(),
+            description: "Levels below the root to expand (default 3, max 10).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "max_entries".into(),
+            description: "Entry budget (default 200, max 2000). Directories that would \
+                          exceed it are collapsed to a summary."
+                .into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "ignore".into(),
+            description: "Name globs to leave out, e.g. ['*.log', 'fixtures'].".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "hidden".into(),
+            description: "Include dotfiles and dot-directories (default false).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "'text' (indented, default) or 'json' (nested objects).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
 }
 
 pub fn search_files_params() -> Vec<ToolParam> {
@@ -134,543 +253,1925 @@ pub fn find_files_params() -> Vec<ToolParam> {
     ]
 }
 
-pub fn execute_command_params() -> Vec<ToolParam> {
+/// File, line and column/symbol shared by the position-based LSP tools.
+fn lsp_position_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "command".into(),
-            description: "The shell command to execute (passed to sh -c).".into(),
+            name: "path".into(),
+            description: "File containing the symbol.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "working_dir".into(),
-            description: "Working directory for the command. Defaults to the workspace root. \
-                          Use an absolute path to run elsewhere."
-                .into(),
-            param_type: "string".into(),
-            required: false,
+            name: "line".into(),
+            description: "1-based line number.".into(),
+            param_type: "integer".into(),
+            required: true,
         },
         ToolParam {
-            name: "timeout_secs".into(),
-            description: "Maximum seconds before killing the command (default: 30).".into(),
+            name: "column".into(),
+            description: "1-based column of the symbol. Use this or symbol.".into(),
             param_type: "integer".into(),
             required: false,
         },
         ToolParam {
-            name: "background".into(),
-            description: "Run in background immediately. Returns a sessionId for use with process tool.".into(),
-            param_type: "boolean".into(),
+            name: "symbol".into(),
+            description: "Symbol text on the line (first occurrence is used). Use this or column.".into(),
+            param_type: "string".into(),
             required: false,
         },
+    ]
+}
+
+pub fn lsp_definition_params() -> Vec<ToolParam> {
+    lsp_position_params()
+}
+
+pub fn lsp_references_params() -> Vec<ToolParam> {
+    let mut params = lsp_position_params();
+    params.push(ToolParam {
+        name: "includeDeclaration".into(),
+        description: "Include the declaration itself (default: true).".into(),
+        param_type: "boolean".into(),
+        required: false,
+    });
+    params
+}
+
+pub fn lsp_diagnostics_params() -> Vec<ToolParam> {
+    vec![
         ToolParam {
-            name: "yieldMs".into(),
-            description: "Milliseconds to wait before auto-backgrounding (default: 10000). \
-                          Set to 0 to disable auto-background."
-                .into(),
+            name: "path".into(),
+            description: "File to check.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "waitSecs".into(),
+            description: "Seconds to wait for fresh diagnostics (default: 10).".into(),
             param_type: "integer".into(),
             required: false,
         },
     ]
 }
 
-pub fn web_fetch_params() -> Vec<ToolParam> {
+pub fn lsp_rename_params() -> Vec<ToolParam> {
+    let mut params = lsp_position_params();
+    params.push(ToolParam {
+        name: "newName".into(),
+        description: "The new name for the symbol.".into(),
+        param_type: "string".into(),
+        required: true,
+    });
+    params
+}
+
+pub fn lint_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "url".into(),
-            description: "HTTP or HTTPS URL to fetch.".into(),
+            name: "path".into(),
+            description: "File or directory to lint (default: workspace root).".into(),
             param_type: "string".into(),
-            required: true,
+            required: false,
         },
         ToolParam {
-            name: "extract_mode".into(),
-            description: "Extraction mode: 'markdown' (default) or 'text'. \
-                          Markdown preserves links and structure; text is plain."
-                .into(),
+            name: "linter".into(),
+            description: "'clippy', 'ruff' or 'eslint'. Detected from project files if omitted.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "max_chars".into(),
-            description: "Maximum characters to return (truncates if exceeded). \
-                          Default: 50000."
-                .into(),
-            param_type: "integer".into(),
+            name: "fix".into(),
+            description: "Apply the linter's automatic fixes (default: false).".into(),
+            param_type: "boolean".into(),
             required: false,
         },
+    ]
+}
+
+pub fn format_params() -> Vec<ToolParam> {
+    vec![
         ToolParam {
-            name: "use_cookies".into(),
-            description: "Use stored cookies for this request and save any \
-                          Set-Cookie headers from the response. Follows browser \
-                          security rules (domain scoping, Secure flag). Default: false."
-                .into(),
+            name: "path".into(),
+            description: "File or directory to format (default: workspace root).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "formatter".into(),
+            description: "'rustfmt', 'ruff' or 'prettier'. Detected from project files if omitted.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "check".into(),
+            description: "Only report files that need formatting; don't change them (default: false).".into(),
             param_type: "boolean".into(),
             required: false,
         },
     ]
 }
 
-pub fn web_search_params() -> Vec<ToolParam> {
+pub fn deps_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "query".into(),
-            description: "Search query string.".into(),
+            name: "action".into(),
+            description: "'list', 'add', 'remove', 'update', 'outdated' or 'audit'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "count".into(),
-            description: "Number of results to return (1-10). Default: 5.".into(),
-            param_type: "integer".into(),
+            name: "packages".into(),
+            description: "Packages for add/remove/update, optionally with a version \
+                          (e.g. ['serde@1', 'tokio']). update with none updates everything."
+                .into(),
+            param_type: "array".into(),
             required: false,
         },
         ToolParam {
-            name: "country".into(),
-            description: "2-letter country code for region-specific results (e.g., 'DE', 'US'). \
-                          Default: 'US'."
-                .into(),
+            name: "ecosystem".into(),
+            description: "'cargo', 'npm' or 'pip'. Detected from the manifest if omitted.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "search_lang".into(),
-            description: "ISO language code for search results (e.g., 'de', 'en', 'fr').".into(),
+            name: "dev".into(),
+            description: "Add/remove as a development dependency (default: false).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "path".into(),
+            description: "Project directory (default: workspace root).".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "freshness".into(),
-            description: "Filter results by discovery time. Values: 'pd' (past 24h), \
-                          'pw' (past week), 'pm' (past month), 'py' (past year), \
-                          or date range 'YYYY-MM-DDtoYYYY-MM-DD'."
-                .into(),
+            name: "package".into(),
+            description: "A single package; shorthand for packages.".into(),
             param_type: "string".into(),
             required: false,
         },
     ]
 }
 
-pub fn process_params() -> Vec<ToolParam> {
+pub fn review_diff_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "action".into(),
-            description: "Action to perform: 'list', 'poll', 'log', 'write', 'send_keys', 'kill', 'clear', 'remove'.".into(),
+            name: "range".into(),
+            description: "Git revision range to review, e.g. 'main..HEAD' or 'HEAD~3'. \
+                          Omit to review staged or working tree changes."
+                .into(),
             param_type: "string".into(),
-            required: true,
+            required: false,
         },
         ToolParam {
-            name: "sessionId".into(),
-            description: "Session ID for poll/log/write/send_keys/kill/remove actions.".into(),
-            param_type: "string".into(),
+            name: "staged".into(),
+            description: "Review staged changes instead of the working tree (default: false).".into(),
+            param_type: "boolean".into(),
             required: false,
         },
         ToolParam {
-            name: "data".into(),
-            description: "Data to write to stdin (for 'write' action).".into(),
-            param_type: "string".into(),
+            name: "paths".into(),
+            description: "Only review these paths.".into(),
+            param_type: "array".into(),
             required: false,
         },
         ToolParam {
-            name: "keys".into(),
-            description: "Space-separated key names to send (for 'send_keys' action). Supports: Enter, Tab, Escape, Space, Backspace, Up, Down, Left, Right, Home, End, PageUp, PageDown, Delete, Insert, Ctrl-A..Ctrl-Z, F1..F12, or literal text.".into(),
+            name: "focus".into(),
+            description: "Extra review focus, e.g. 'thread safety' or 'SQL injection'.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "offset".into(),
-            description: "Line offset for 'log' action (0-indexed). Omit to get last N lines.".into(),
-            param_type: "integer".into(),
+            name: "format".into(),
+            description: "'markdown' (default) or 'json'.".into(),
+            param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "limit".into(),
-            description: "Maximum lines to return for 'log' action. Default: 50.".into(),
+            name: "maxChunks".into(),
+            description: "Maximum diff chunks to review, one model call each (default: 20).".into(),
             param_type: "integer".into(),
             required: false,
         },
     ]
 }
 
-pub fn qmd_search_params() -> Vec<ToolParam> {
+pub fn snapshots_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "query".into(),
-            description: "Search query for finding relevant content in the knowledge vault.".into(),
+            name: "action".into(),
+            description: "'list', 'create', 'diff' or 'restore'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "limit".into(),
-            description: "Maximum number of results to return. Default: 5.".into(),
-            param_type: "integer".into(),
-            required: false,
-        },
-        ToolParam {
-            name: "collection".into(),
-            description: "Search within a specific collection (e.g. 'daily', 'tacit', 'projects'). Omit to search all.".into(),
+            name: "id".into(),
+            description: "Snapshot id from 'list' (diff defaults to the newest; required for restore).".into(),
             param_type: "string".into(),
             required: false,
         },
-    ]
-}
-
-pub fn qmd_deep_search_params() -> Vec<ToolParam> {
-    vec![
         ToolParam {
-            name: "query".into(),
-            description: "Search query for deep search with LLM re-ranking.".into(),
+            name: "path".into(),
+            description: "For diff: show the changes to this file.".into(),
             param_type: "string".into(),
-            required: true,
+            required: false,
         },
         ToolParam {
-            name: "limit".into(),
-            description: "Maximum number of results to return. Default: 5.".into(),
-            param_type: "integer".into(),
+            name: "paths".into(),
+            description: "For restore: only restore these files or directories.".into(),
+            param_type: "array".into(),
             required: false,
         },
         ToolParam {
-            name: "collection".into(),
-            description: "Search within a specific collection. Omit to search all.".into(),
+            name: "reason".into(),
+            description: "For create: what the snapshot is for.".into(),
             param_type: "string".into(),
             required: false,
         },
     ]
 }
 
-pub fn qmd_get_params() -> Vec<ToolParam> {
+pub fn execute_command_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "path".into(),
-            description: "Path to the document to retrieve from the knowledge vault.".into(),
+            name: "command".into(),
+            description: "The shell command to execute (passed to sh -c).".into(),
             param_type: "string".into(),
             required: true,
         },
-    ]
+        ToolParam {
+            name: "working_dir".into(),
+            description: "Working directory for the command. Defaults to the workspace root. \
+                          Use an absolute path to run elsewhere."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "timeout_secs".into(),
+            description: "Maximum seconds before killing the command (default: 30).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "background".into(),
+            description: "Run in background immediately. Returns a sessionId for use with process tool.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "yieldMs".into(),
+            description: "Milliseconds to wait before auto-backgrounding (default: 10000). \
+                          Set to 0 to disable auto-background."
+                .into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn web_fetch_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "url".into(),
+            description: "HTTP or HTTPS URL to fetch.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "extract_mode".into(),
+            description: "Extraction mode: 'markdown' (default) or 'text'. \
+                          Markdown preserves links and structure; text is plain."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "max_chars".into(),
+            description: "Maximum characters to return (truncates if exceeded). \
+                          Default: 50000."
+                .into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "use_cookies".into(),
+            description: "Use stored cookies for this request and save any \
+                          Set-Cookie headers from the response. Follows browser \
+                          security rules (domain scoping, Secure flag). Default: false."
+                .into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn web_search_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "query".into(),
+            description: "Search query string.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "count".into(),
+            description: "Number of results to return (1-10). Default: 5.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "country".into(),
+            description: "2-letter country code for region-specific results (e.g., 'DE', 'US'). \
+                          Default: 'US'."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "search_lang".into(),
+            description: "ISO language code for search results (e.g., 'de', 'en', 'fr').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "freshness".into(),
+            description: "Filter results by discovery time. Values: 'pd' (past 24h), \
+                          'pw' (past week), 'pm' (past month), 'py' (past year), \
+                          or date range 'YYYY-MM-DDtoYYYY-MM-DD'."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn serial_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'list', 'open', 'send', 'read', 'close'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "port".into(),
+            description: "Serial device for 'open' (e.g. '/dev/ttyUSB0').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "baud".into(),
+            description: "Baud rate for 'open' (default: 115200).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "sessionId".into(),
+            description: "Session returned by 'open'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "data".into(),
+            description: "Text to send.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "lineEnding".into(),
+            description: "Appended to 'data': 'lf' (default), 'crlf', 'cr' or 'none'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "expect".into(),
+            description: "Stop reading once this text appears (e.g. a prompt like '>>> ').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "timeoutMs".into(),
+            description: "How long 'send'/'read' wait for output (default: 2000).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn process_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action to perform: 'list', 'poll', 'log', 'write', 'send_keys', 'kill', 'clear', 'remove'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "sessionId".into(),
+            description: "Session ID for poll/log/write/send_keys/kill/remove actions.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "data".into(),
+            description: "Data to write to stdin (for 'write' action).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "keys".into(),
+            description: "Space-separated key names to send (for 'send_keys' action). Supports: Enter, Tab, Escape, Space, Backspace, Up, Down, Left, Right, Home, End, PageUp, PageDown, Delete, Insert, Ctrl-A..Ctrl-Z, F1..F12, or literal text.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "offset".into(),
+            description: "Line offset for 'log' action (0-indexed). Omit to get last N lines.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "limit".into(),
+            description: "Maximum lines to return for 'log' action. Default: 50.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn qmd_search_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "query".into(),
+            description: "Search query for finding relevant content in the knowledge vault.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "limit".into(),
+            description: "Maximum number of results to return. Default: 5.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "collection".into(),
+            description: "Search within a specific collection (e.g. 'daily', 'tacit', 'projects'). Omit to search all.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn qmd_deep_search_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "query".into(),
+            description: "Search query for deep search with LLM re-ranking.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "limit".into(),
+            description: "Maximum number of results to return. Default: 5.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "collection".into(),
+            description: "Search within a specific collection. Omit to search all.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn qmd_get_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "Path to the document to retrieve from the knowledge vault.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+    ]
+}
+
+pub fn secrets_list_params() -> Vec<ToolParam> {
+    vec![ToolParam {
+        name: "prefix".into(),
+        description: "Optional prefix to filter key names.".into(),
+        param_type: "string".into(),
+        required: false,
+    }]
+}
+
+pub fn secrets_get_params() -> Vec<ToolParam> {
+    vec![ToolParam {
+        name: "key".into(),
+        description: "The name of the secret to retrieve.".into(),
+        param_type: "string".into(),
+        required: true,
+    }]
+}
+
+pub fn secrets_store_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "key".into(),
+            description: "The name under which to store the secret.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "value".into(),
+            description: "The secret value to encrypt and store.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+    ]
+}
+
+pub fn generate_secret_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "style".into(),
+            description: "'password' (default), 'passphrase', 'token' or 'pin'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "length".into(),
+            description: "Password length (default 24, 8-256) or PIN length (default 6).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "lowercase".into(),
+            description: "Include lowercase letters (password, default true).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "uppercase".into(),
+            description: "Include uppercase letters (password, default true).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "digits".into(),
+            description: "Include digits (password, default true).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "symbols".into(),
+            description: "Include symbols (password, default true), or a string giving the exact symbol set.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "excludeAmbiguous".into(),
+            description: "Leave out look-alike characters such as 0/O and 1/l/I (password).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "words".into(),
+            description: "Number of words (passphrase, default 6, 4-24).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "separator".into(),
+            description: "Word separator (passphrase, default '-').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "capitalize".into(),
+            description: "Capitalize each word (passphrase).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "includeNumber".into(),
+            description: "Append a digit to one word (passphrase).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "Token encoding: 'base64url' (default), 'hex' or 'alphanumeric'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "bytes".into(),
+            description: "Random bytes in a token (default 32, 16-256).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "prefix".into(),
+            description: "Fixed prefix for a token, e.g. 'sk_live_'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "storeAs".into(),
+            description: "Vault key to store the result under. When set, the value is never returned.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "username".into(),
+            description: "Username to store alongside a generated password (stored as username_password).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "description".into(),
+            description: "Description for the stored credential.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn totp_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "'code' (current code), 'add', 'list' or 'remove'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "name".into(),
+            description: "Name of the TOTP seed in the vault, e.g. 'github'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "secret".into(),
+            description: "For add: the base32 seed or the full otpauth:// URI from the site's setup page.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "digits".into(),
+            description: "For add: code length, overriding the URI (default 6).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "period".into(),
+            description: "For add: seconds per code, overriding the URI (default 30).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "algorithm".into(),
+            description: "For add: 'SHA1' (default), 'SHA256' or 'SHA512'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "policy".into(),
+            description: "For add: who may read codes — 'ask' (default), 'always' or 'auth'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "description".into(),
+            description: "For add: note shown in listings (defaults to issuer and account).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn ssh_key_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "'list', 'generate', 'import', 'public', 'load', 'unload' or 'stop'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "name".into(),
+            description: "Vault name of the key.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "comment".into(),
+            description: "For generate: public key comment (default 'rustyclaw').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "privateKey".into(),
+            description: "For import: the OpenSSH private key text.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "path".into(),
+            description: "For import: file to read the private key from, e.g. '~/.ssh/id_ed25519'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "passphrase".into(),
+            description: "For import: passphrase of an encrypted key.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "policy".into(),
+            description: "For generate/import: who may load the key — 'ask' (default), 'always' or 'auth'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "lifetime".into(),
+            description: "For load: seconds the agent keeps the key (default 3600).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn encrypt_file_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "File to encrypt.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "Where to write the result (default: <path>.age).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "key".into(),
+            description: "Vault age key to encrypt to; created if it doesn't exist.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "recipients".into(),
+            description: "Extra recipients: age1… public keys or vault age key names.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "passphraseSecret".into(),
+            description: "Vault secret holding a passphrase to encrypt with instead of keys.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "armor".into(),
+            description: "Write ASCII-armored output (default false).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "overwrite".into(),
+            description: "Replace the output file if it exists.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn decrypt_file_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "age file to decrypt.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "Where to write the plaintext (default: path without .age).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "key".into(),
+            description: "Vault age key to use (default: try every accessible age key).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "passphraseSecret".into(),
+            description: "Vault secret holding the passphrase, for passphrase-encrypted files.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "overwrite".into(),
+            description: "Replace the output file if it exists.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn gateway_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'restart', 'config.get', 'config.schema', 'config.apply', 'config.patch', 'update.run', 'locks'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "raw".into(),
+            description: "JSON config content for config.apply or config.patch.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "baseHash".into(),
+            description: "Config hash from config.get (required for apply/patch when config exists).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "reason".into(),
+            description: "Reason for restart or config change.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "delayMs".into(),
+            description: "Delay before restart in milliseconds. Default: 2000.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn contacts_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "'list', 'search', 'get', 'resolve', 'add' or 'remove'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "name".into(),
+            description: "Contact name, first name or alias (get, resolve, add, remove).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "query".into(),
+            description: "Text to search for in names, ids, phone numbers and emails.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "channel".into(),
+            description: "Messenger to resolve an id for (telegram, discord, signal, ...). \
+                          Default 'auto' picks a configured one."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "identities".into(),
+            description: "For add: messenger → id, e.g. {\"telegram\": \"123456789\"}. \
+                          An empty id removes that messenger."
+                .into(),
+            param_type: "object".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "aliases".into(),
+            description: "For add: other names this person goes by.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "phone".into(),
+            description: "For add: phone number (used for Signal/SMS).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "email".into(),
+            description: "For add: email address.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "notes".into(),
+            description: "For add: free-form notes about the person.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn message_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'send', 'broadcast', or 'templates' (list message templates).".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "message".into(),
+            description: "Message content to send.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "template".into(),
+            description: "Name of a template in templates/ to render instead of 'message' \
+                          (builtins: date, time, datetime, weekday, workspace; 'message' is available as {{message}}).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "vars".into(),
+            description: "Template variables, e.g. {\"today\": \"release 1.4\"}.".into(),
+            param_type: "object".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "target".into(),
+            description: "Target channel/user ID, or a contact name from the contact book.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "channel".into(),
+            description: "Channel type: telegram, discord, whatsapp, signal, slack, etc.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "targets".into(),
+            description: "Multiple targets for broadcast action.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "replyTo".into(),
+            description: "Message ID to reply to.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "silent".into(),
+            description: "Send without notification. Default: false.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "urgent".into(),
+            description: "Send even during the channel's quiet hours (otherwise the message is held \
+                          until they end). Only for things that can't wait. Default: false.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn tts_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "text".into(),
+            description: "Text to convert to speech.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "channel".into(),
+            description: "Optional channel ID to pick output format.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn image_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "image".into(),
+            description: "Path to local image file or URL.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "prompt".into(),
+            description: "Question or instruction about the image. Default: 'Describe the image.'".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn ocr_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "Image (png, jpg, tiff, ...) or PDF to read.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "engine".into(),
+            description: "'auto' (default: tesseract if installed, else vision), 'tesseract' or 'vision'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "lang".into(),
+            description: "Tesseract language codes, e.g. 'eng' (default) or 'deu+eng'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "maxPages".into(),
+            description: "Maximum PDF pages to OCR (default: 20).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "'text' (default) or 'json' for blocks with bounding boxes.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn qr_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "'generate' (default) or 'decode'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "data".into(),
+            description: "Text or URL to encode.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "wifi".into(),
+            description: "Encode a Wi-Fi network instead of data: {ssid, password?, security? ('WPA', 'WEP', 'SAE' or 'nopass'), hidden?}.".into(),
+            param_type: "object".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "'text' (terminal block art), 'png' or 'both' (default).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "PNG path (default: qr/qr-<timestamp>.png in the workspace).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "scale".into(),
+            description: "Pixels per module in the PNG, 1-32 (default: 8).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "path".into(),
+            description: "Image to decode, for action 'decode'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn transcribe_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "Audio or video file to transcribe.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "language".into(),
+            description: "Spoken language as an ISO-639-1 code, e.g. 'en' (default: detect).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "backend".into(),
+            description: "'auto' (default: local whisper.cpp if configured, else the API), 'local' or 'api'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "'text' (default; one '[HH:MM:SS] text' line per segment) or 'json'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn media_convert_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "operation".into(),
+            description: "'extract_audio', 'convert', 'resize', 'trim' or 'status'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "input".into(),
+            description: "Source media file.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "Output file (default: next to the input, named after the operation).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "Target format: mp3, ogg, opus, m4a, wav, flac, mp4, mkv, webm, gif.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "start".into(),
+            description: "Start time, as seconds or HH:MM:SS(.mmm).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "end".into(),
+            description: "End time, as seconds or HH:MM:SS(.mmm).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "width".into(),
+            description: "Output width in pixels (height follows the aspect ratio if omitted).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "height".into(),
+            description: "Output height in pixels (width follows the aspect ratio if omitted).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "overwrite".into(),
+            description: "Replace an existing output file (default: false).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "yieldMs".into(),
+            description: "How long to wait before leaving the job in the background (default: 10000).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "sessionId".into(),
+            description: "Job to check, for operation=status.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn translate_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "text".into(),
+            description: "Text to translate or identify.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "action".into(),
+            description: "'translate' (default) or 'detect'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "target".into(),
+            description: "Target language code, e.g. 'en', 'de', 'pt-BR' (default: the configured default_target).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "source".into(),
+            description: "Source language code; detected when omitted.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "provider".into(),
+            description: "'auto' (default), 'deepl', 'libretranslate' or 'argos'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn calc_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "op".into(),
+            description: "'eval', 'convert', 'time', 'date' or 'schedule'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "expression".into(),
+            description: "Arithmetic expression (eval) or cron expression (schedule).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "value".into(),
+            description: "Amount to convert; a number or an expression (default: 1).".into(),
+            param_type: "number".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "from".into(),
+            description: "Unit or currency code to convert from (convert), or source timezone (time).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "to".into(),
+            description: "Unit or currency code to convert to (convert), or target timezone(s) for time: a name or an array (default: UTC).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "time".into(),
+            description: "Time to convert, e.g. '2026-03-01 09:00' or 'now' (default).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "date".into(),
+            description: "Start date/time for date math (default: now).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "tz".into(),
+            description: "IANA timezone for date and schedule, e.g. 'Europe/Berlin' (default: system timezone).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "add".into(),
+            description: "Duration to add, e.g. '3d 4h', '-2w', '1 month'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "until".into(),
+            description: "End date/time; returns the difference from date.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "count".into(),
+            description: "How many upcoming runs to list for schedule (default: 5).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn nodes_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'status', 'describe', 'pending', 'approve', 'reject', 'notify', 'camera_snap', 'camera_list', 'screen_record', 'location_get', 'run', 'invoke', 'discover', 'pair', 'wake', 'sleep', 'reboot'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "node".into(),
+            description: "Node ID or name to target.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "requestId".into(),
+            description: "Pairing request ID for approve/reject.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "title".into(),
+            description: "Notification title.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "body".into(),
+            description: "Notification body text.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "command".into(),
+            description: "Command array for 'run' action.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "invokeCommand".into(),
+            description: "Command name for 'invoke' action.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "facing".into(),
+            description: "Camera facing: 'front', 'back', or 'both'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "mac".into(),
+            description: "MAC address for 'wake' (looked up from the node's host if omitted).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "broadcast".into(),
+            description: "Broadcast address for 'wake' (default: 255.255.255.255).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "timeoutMs".into(),
+            description: "How long 'discover' listens for replies (default: 3000), or how long 'pair' waits for the phone (default: 120000), in milliseconds.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn cloud_status_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'summary' (default) or 'providers'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "providers".into(),
+            description: "Providers to include: 'aws', 'gcp', 'hetzner' (default: all configured).".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "days".into(),
+            description: "Number of full days to report (default: 7).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "'text' (default) or 'json'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "gcpBillingTable".into(),
+            description: "BigQuery billing export table (project.dataset.table); overrides GCP_BILLING_TABLE.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
 }
 
-pub fn secrets_list_params() -> Vec<ToolParam> {
-    vec![ToolParam {
-        name: "prefix".into(),
-        description: "Optional prefix to filter key names.".into(),
-        param_type: "string".into(),
-        required: false,
-    }]
+pub fn render_report_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "markdown".into(),
+            description: "Report body as markdown. Use ```chart blocks with a JSON chart spec for charts.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "path".into(),
+            description: "Markdown file to render instead of inline 'markdown'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "Output file (default: reports/<title>-<date>.<format>).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "format".into(),
+            description: "'pdf' (default) or 'html'; inferred from the output extension when omitted.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "title".into(),
+            description: "Document title (default: the first '# ' heading).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
 }
 
-pub fn secrets_get_params() -> Vec<ToolParam> {
-    vec![ToolParam {
-        name: "key".into(),
-        description: "The name of the secret to retrieve.".into(),
-        param_type: "string".into(),
-        required: true,
-    }]
+pub fn plot_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "data".into(),
+            description: "Inline data: CSV/TSV text, or JSON (array of objects, or array of rows with a header row first).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "path".into(),
+            description: "CSV, TSV or JSON file to plot instead of inline 'data'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "type".into(),
+            description: "Chart type: 'line' (default), 'bar' or 'scatter'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "x".into(),
+            description: "Column for the x axis (default: the first column).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "y".into(),
+            description: "Column name or list of column names to plot (default: all other numeric columns).".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "title".into(),
+            description: "Chart title.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "xLabel".into(),
+            description: "X axis label (default: the x column name).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "yLabel".into(),
+            description: "Y axis label (default: the y column name when there is one).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "PNG file to write (default: plots/<title>-<timestamp>.png).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "width".into(),
+            description: "Image width in pixels (default: 1000).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "height".into(),
+            description: "Image height in pixels (default: 600).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
 }
 
-pub fn secrets_store_params() -> Vec<ToolParam> {
+pub fn xlsx_write_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "key".into(),
-            description: "The name under which to store the secret.".into(),
+            name: "path".into(),
+            description: "Workbook to write (.xlsx).".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "value".into(),
-            description: "The secret value to encrypt and store.".into(),
+            name: "operations".into(),
+            description: "Writes to apply, each {sheet, cell?, values? | data? | path?}. \
+                          'values' is a 2-D array; strings starting with '=' are formulas.".into(),
+            param_type: "array".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "mode".into(),
+            description: "'create' (default; replaces the file) or 'update' (keeps existing sheets and cells).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn mqtt_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'publish', 'recent', 'status'.".into(),
             param_type: "string".into(),
             required: true,
         },
+        ToolParam {
+            name: "topic".into(),
+            description: "Topic to publish to, or a filter for 'recent' (e.g. 'home/+/temperature').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "payload".into(),
+            description: "Message payload for 'publish': text, or a JSON object.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "qos".into(),
+            description: "Quality of service for 'publish': 0, 1 or 2 (default: 0).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "retain".into(),
+            description: "Ask the broker to retain the message (default: false).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "limit".into(),
+            description: "Maximum messages for 'recent' (default: 20).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
     ]
 }
 
-pub fn gateway_params() -> Vec<ToolParam> {
+pub fn ble_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
             name: "action".into(),
-            description: "Action: 'restart', 'config.get', 'config.schema', 'config.apply', 'config.patch', 'update.run'.".into(),
+            description: "Action: 'scan', 'services', 'read', 'subscribe', 'write'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "raw".into(),
-            description: "JSON config content for config.apply or config.patch.".into(),
+            name: "device".into(),
+            description: "Device address (AA:BB:CC:DD:EE:FF) or part of its name, from scan.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "baseHash".into(),
-            description: "Config hash from config.get (required for apply/patch when config exists).".into(),
+            name: "characteristic".into(),
+            description: "Characteristic UUID, full or 16-bit short form (e.g. '2a19').".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "reason".into(),
-            description: "Reason for restart or config change.".into(),
+            name: "value".into(),
+            description: "Value for 'write': hex bytes ('0x01ff' or 'hex:01 ff') or plain text.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "delayMs".into(),
-            description: "Delay before restart in milliseconds. Default: 2000.".into(),
+            name: "durationMs".into(),
+            description: "Scan or subscribe window in milliseconds (default: 5000).".into(),
             param_type: "integer".into(),
             required: false,
         },
+        ToolParam {
+            name: "withoutResponse".into(),
+            description: "Write without waiting for an acknowledgement (default: false).".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
     ]
 }
 
-pub fn message_params() -> Vec<ToolParam> {
+pub fn browser_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
             name: "action".into(),
-            description: "Action: 'send' or 'broadcast'.".into(),
+            description: "Action: 'status', 'start', 'stop', 'profiles', 'tabs', 'open', 'focus', 'close', 'snapshot', 'screenshot', 'navigate', 'console', 'pdf', 'act'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "message".into(),
-            description: "Message content to send.".into(),
+            name: "profile".into(),
+            description: "Browser profile: 'openclaw' (managed) or 'chrome' (extension relay).".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "target".into(),
-            description: "Target channel/user ID or name.".into(),
+            name: "targetUrl".into(),
+            description: "URL for 'open' or 'navigate' actions.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "channel".into(),
-            description: "Channel type: telegram, discord, whatsapp, signal, slack, etc.".into(),
+            name: "targetId".into(),
+            description: "Tab ID for targeting specific tab.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "targets".into(),
-            description: "Multiple targets for broadcast action.".into(),
-            param_type: "array".into(),
+            name: "ref".into(),
+            description: "Element reference from snapshot for actions.".into(),
+            param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "replyTo".into(),
-            description: "Message ID to reply to.".into(),
-            param_type: "string".into(),
+            name: "request".into(),
+            description: "Action request object with kind (click/type/press/hover/drag), ref, text, etc.".into(),
+            param_type: "object".into(),
             required: false,
         },
         ToolParam {
-            name: "silent".into(),
-            description: "Send without notification. Default: false.".into(),
+            name: "fullPage".into(),
+            description: "Capture full page for screenshot. Default: false.".into(),
             param_type: "boolean".into(),
             required: false,
         },
     ]
 }
 
-pub fn tts_params() -> Vec<ToolParam> {
+pub fn canvas_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "text".into(),
-            description: "Text to convert to speech.".into(),
+            name: "action".into(),
+            description: "Action: 'present', 'hide', 'navigate', 'eval', 'snapshot', 'a2ui_push', 'a2ui_reset'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "channel".into(),
-            description: "Optional channel ID to pick output format.".into(),
+            name: "node".into(),
+            description: "Target node for canvas operations.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "url".into(),
+            description: "URL to present or navigate to.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "javaScript".into(),
+            description: "JavaScript code for 'eval' action.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "width".into(),
+            description: "Canvas width in pixels.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "height".into(),
+            description: "Canvas height in pixels.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn cron_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'status', 'list', 'add', 'update', 'remove', 'run', 'runs', 'snooze', 'ack', 'export'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "jobId".into(),
+            description: "Job ID for update/remove/run/runs/snooze/ack actions.".into(),
             param_type: "string".into(),
             required: false,
         },
-    ]
-}
-
-pub fn image_params() -> Vec<ToolParam> {
-    vec![
         ToolParam {
-            name: "image".into(),
-            description: "Path to local image file or URL.".into(),
+            name: "job".into(),
+            description: "Job definition object for 'add' action.".into(),
+            param_type: "object".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "patch".into(),
+            description: "Patch object for 'update' action.".into(),
+            param_type: "object".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "includeDisabled".into(),
+            description: "Include disabled jobs in list. Default: false.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "output".into(),
+            description: "Path of the .ics file for 'export'. Default: exports/schedule.ics.".into(),
             param_type: "string".into(),
-            required: true,
+            required: false,
         },
         ToolParam {
-            name: "prompt".into(),
-            description: "Question or instruction about the image. Default: 'Describe the image.'".into(),
+            name: "duration".into(),
+            description: "How long to snooze, e.g. '10m', '1h', '1d'. Default: 10m.".into(),
             param_type: "string".into(),
             required: false,
         },
     ]
 }
 
-pub fn nodes_params() -> Vec<ToolParam> {
+pub fn tasks_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
             name: "action".into(),
-            description: "Action: 'status', 'describe', 'pending', 'approve', 'reject', 'notify', 'camera_snap', 'camera_list', 'screen_record', 'location_get', 'run', 'invoke'.".into(),
+            description: "Action: 'add', 'list', 'get', 'cancel', 'prune', 'status'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "node".into(),
-            description: "Node ID or name to target.".into(),
-            param_type: "string".into(),
-            required: false,
-        },
-        ToolParam {
-            name: "requestId".into(),
-            description: "Pairing request ID for approve/reject.".into(),
-            param_type: "string".into(),
-            required: false,
-        },
-        ToolParam {
-            name: "title".into(),
-            description: "Notification title.".into(),
+            name: "prompt".into(),
+            description: "What the worker session should do (for 'add').".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "body".into(),
-            description: "Notification body text.".into(),
+            name: "priority".into(),
+            description: "Priority: 'low', 'normal', 'high', 'urgent'. Default: normal.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "command".into(),
-            description: "Command array for 'run' action.".into(),
-            param_type: "array".into(),
+            name: "maxRetries".into(),
+            description: "Retries after a failed attempt. Default: 2.".into(),
+            param_type: "integer".into(),
             required: false,
         },
         ToolParam {
-            name: "invokeCommand".into(),
-            description: "Command name for 'invoke' action.".into(),
+            name: "taskId".into(),
+            description: "Task ID for get/cancel actions.".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "facing".into(),
-            description: "Camera facing: 'front', 'back', or 'both'.".into(),
-            param_type: "string".into(),
+            name: "includeFinished".into(),
+            description: "Include finished tasks in list. Default: false.".into(),
+            param_type: "boolean".into(),
             required: false,
         },
     ]
 }
 
-pub fn browser_params() -> Vec<ToolParam> {
+pub fn conversations_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
             name: "action".into(),
-            description: "Action: 'status', 'start', 'stop', 'profiles', 'tabs', 'open', 'focus', 'close', 'snapshot', 'screenshot', 'navigate', 'console', 'pdf', 'act'.".into(),
+            description: "Action: 'list', 'show', 'clear', 'export'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "profile".into(),
-            description: "Browser profile: 'openclaw' (managed) or 'chrome' (extension relay).".into(),
+            name: "messenger".into(),
+            description: "Messenger name (telegram, discord, signal, ...). Filters 'list'; \
+                          required for show/clear and single-chat export."
+                .into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "targetUrl".into(),
-            description: "URL for 'open' or 'navigate' actions.".into(),
+            name: "chat".into(),
+            description: "Chat id (or sender id for direct messages). Omit with 'clear' to clear \
+                          every chat of the messenger, or with 'export' to export them all."
+                .into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "targetId".into(),
-            description: "Tab ID for targeting specific tab.".into(),
-            param_type: "string".into(),
+            name: "limit".into(),
+            description: "Number of recent messages for 'show'. Default: 20.".into(),
+            param_type: "integer".into(),
             required: false,
         },
         ToolParam {
-            name: "ref".into(),
-            description: "Element reference from snapshot for actions.".into(),
+            name: "output".into(),
+            description: "Markdown file (single chat) or directory (several chats) for 'export'. \
+                          Default: exports/conversations/ in the workspace."
+                .into(),
             param_type: "string".into(),
             required: false,
         },
-        ToolParam {
-            name: "request".into(),
-            description: "Action request object with kind (click/type/press/hover/drag), ref, text, etc.".into(),
-            param_type: "object".into(),
-            required: false,
-        },
-        ToolParam {
-            name: "fullPage".into(),
-            description: "Capture full page for screenshot. Default: false.".into(),
-            param_type: "boolean".into(),
-            required: false,
-        },
     ]
 }
 
-pub fn canvas_params() -> Vec<ToolParam> {
+pub fn plan_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
             name: "action".into(),
-            description: "Action: 'present', 'hide', 'navigate', 'eval', 'snapshot', 'a2ui_push', 'a2ui_reset'.".into(),
+            description: "Action: 'create', 'update', 'insert', 'remove', 'show', 'clear'.".into(),
             param_type: "string".into(),
             required: true,
         },
         ToolParam {
-            name: "node".into(),
-            description: "Target node for canvas operations.".into(),
+            name: "title".into(),
+            description: "Plan title (for 'create').".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "url".into(),
-            description: "URL to present or navigate to.".into(),
-            param_type: "string".into(),
+            name: "steps".into(),
+            description: "Step descriptions in order (for 'create').".into(),
+            param_type: "array".into(),
             required: false,
         },
         ToolParam {
-            name: "javaScript".into(),
-            description: "JavaScript code for 'eval' action.".into(),
-            param_type: "string".into(),
+            name: "step".into(),
+            description: "1-based step number for update/remove, or insert position (default: end).".into(),
+            param_type: "integer".into(),
             required: false,
         },
         ToolParam {
-            name: "width".into(),
-            description: "Canvas width in pixels.".into(),
-            param_type: "integer".into(),
+            name: "status".into(),
+            description: "New status for 'update': 'pending', 'in_progress', 'done', 'skipped'.".into(),
+            param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "height".into(),
-            description: "Canvas height in pixels.".into(),
-            param_type: "integer".into(),
+            name: "text".into(),
+            description: "Step description (for 'insert').".into(),
+            param_type: "string".into(),
             required: false,
         },
     ]
 }
 
-pub fn cron_params() -> Vec<ToolParam> {
+pub fn script_run_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
-            name: "action".into(),
-            description: "Action: 'status', 'list', 'add', 'update', 'remove', 'run', 'runs'.".into(),
-            param_type: "string".into(),
-            required: true,
-        },
-        ToolParam {
-            name: "jobId".into(),
-            description: "Job ID for update/remove/run/runs actions.".into(),
+            name: "script".into(),
+            description: "Name of a script in skills/scripts/ (without .rhai).".into(),
             param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "job".into(),
-            description: "Job definition object for 'add' action.".into(),
-            param_type: "object".into(),
+            name: "code".into(),
+            description: "Inline Rhai source to run instead of a named script.".into(),
+            param_type: "string".into(),
             required: false,
         },
         ToolParam {
-            name: "patch".into(),
-            description: "Patch object for 'update' action.".into(),
+            name: "args".into(),
+            description: "Arguments passed to the script as ARGS.".into(),
             param_type: "object".into(),
             required: false,
         },
-        ToolParam {
-            name: "includeDisabled".into(),
-            description: "Include disabled jobs in list. Default: false.".into(),
-            param_type: "boolean".into(),
-            required: false,
-        },
     ]
 }
 
@@ -747,6 +2248,31 @@ pub fn sessions_spawn_params() -> Vec<ToolParam> {
             param_type: "string".into(),
             required: false,
         },
+        ToolParam {
+            name: "isolation".into(),
+            description: "'worktree' to give the sub-agent its own git worktree and branch \
+                          so its edits can't collide with yours; 'none' (default) shares \
+                          your workspace.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn sessions_worktree_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "'diff', 'stat', 'merge' or 'discard'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "sessionKey".into(),
+            description: "Key of the sub-agent session that owns the worktree.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
     ]
 }
 
@@ -823,6 +2349,48 @@ pub fn agents_list_params() -> Vec<ToolParam> {
     vec![]
 }
 
+pub fn orchestrate_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "action".into(),
+            description: "Action: 'start', 'status', 'scratchpad_write', 'scratchpad_read', or 'aggregate'.".into(),
+            param_type: "string".into(),
+            required: true,
+        },
+        ToolParam {
+            name: "task".into(),
+            description: "Shared task for all sub-agents (for 'start').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "agents".into(),
+            description: "Roles to spawn: role names or objects {role, model, instructions}. \
+                          Default: researcher, coder, reviewer.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "orchestrationId".into(),
+            description: "Orchestration ID returned by 'start'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "content".into(),
+            description: "Note to add to the shared scratchpad (for 'scratchpad_write').".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "author".into(),
+            description: "Author of the scratchpad note (role name). Default: parent.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
 pub fn apply_patch_params() -> Vec<ToolParam> {
     vec![
         ToolParam {
@@ -843,6 +2411,14 @@ pub fn apply_patch_params() -> Vec<ToolParam> {
             param_type: "boolean".into(),
             required: false,
         },
+        ToolParam {
+            name: "expected_hash".into(),
+            description: "The [hash …] read_file showed for the patched file (single-file \
+                          patches only). Fails with the interim changes if it changed since."
+                .into(),
+            param_type: "string".into(),
+            required: false,
+        },
     ]
 }
 
@@ -1006,6 +2582,118 @@ pub fn system_monitor_params() -> Vec<ToolParam> {
     }]
 }
 
+pub fn system_info_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "node".into(),
+            description: "SSH or ADB node to query (as for the nodes tool); omit for this machine.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "sections".into(),
+            description: "Limit the report to some of: 'cpu', 'memory', 'disk', 'processes', 'battery'.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "top".into(),
+            description: "Number of top processes to list (default 5).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "sortBy".into(),
+            description: "Rank processes by 'cpu' (default) or 'memory'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "diskThreshold".into(),
+            description: "Alert when a disk is at least this % full (default 90).".into(),
+            param_type: "number".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "memoryThreshold".into(),
+            description: "Alert when memory use is at least this % (default 90).".into(),
+            param_type: "number".into(),
+            required: false,
+        },
+    ]
+}
+
+pub fn logs_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "path".into(),
+            description: "Log file to read (workspace-relative or absolute).".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "unit".into(),
+            description: "journald unit to read instead of a file, e.g. 'nginx.service'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "lines".into(),
+            description: "How many of the most recent lines to scan first (default 100).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "follow".into(),
+            description: "Keep reading new lines for this many seconds (max 300, default 0).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "pattern".into(),
+            description: "Regex a line must match to be counted and returned.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "exclude".into(),
+            description: "Regex for lines to drop even if they match pattern.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "ignoreCase".into(),
+            description: "Case-insensitive pattern/exclude matching.".into(),
+            param_type: "boolean".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "maxLines".into(),
+            description: "Most matching lines to return, newest kept (default 200).".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "threshold".into(),
+            description: "Set alert: true when at least this many lines match.".into(),
+            param_type: "integer".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "since".into(),
+            description: "journald only: start time, e.g. '1 hour ago' or '2026-10-15 09:00'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "priority".into(),
+            description: "journald only: maximum priority, e.g. 'err' or 'warning'.".into(),
+            param_type: "string".into(),
+            required: false,
+        },
+    ]
+}
+
 pub fn battery_health_params() -> Vec<ToolParam> {
     vec![]
 }
@@ -1212,6 +2900,25 @@ pub fn ask_user_params() -> Vec<ToolParam> {
     ]
 }
 
+pub fn request_tools_params() -> Vec<ToolParam> {
+    vec![
+        ToolParam {
+            name: "groups".into(),
+            description: "Groups to load: code, web, browser, memory, cron, sessions, secrets, \
+                          messaging, nodes, media, skills, system, admin, extensions."
+                .into(),
+            param_type: "array".into(),
+            required: false,
+        },
+        ToolParam {
+            name: "tools".into(),
+            description: "Individual tools to load by name.".into(),
+            param_type: "array".into(),
+            required: false,
+        },
+    ]
+}
+
 // ── Sysadmin tools ──────────────────────────────────────────────────────────
 
 pub fn pkg_manage_params() -> Vec<ToolParam> {
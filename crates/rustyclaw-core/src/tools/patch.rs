@@ -1,6 +1,6 @@
 //! Patch tool: apply unified diff patches.
 
-use super::helpers::resolve_path;
+use super::helpers::{check_write, resolve_path};
 use serde_json::Value;
 use std::path::Path;
 use tracing::{debug, warn, instrument};
@@ -14,6 +14,7 @@ pub fn exec_apply_patch(args: &Value, workspace_dir: &Path) -> Result<String, St
         .ok_or_else(|| "Missing required parameter: patch".to_string())?;
 
     let explicit_path = args.get("path").and_then(|v| v.as_str());
+    let expected_hash = args.get("expected_hash").and_then(|v| v.as_str());
     let dry_run = args
         .get("dry_run")
         .and_then(|v| v.as_bool())
@@ -40,6 +41,14 @@ pub fn exec_apply_patch(args: &Value, workspace_dir: &Path) -> Result<String, St
         let path = explicit_path.unwrap_or(&hunk.file_path);
         files.entry(path.to_string()).or_default().push(hunk);
     }
+    if expected_hash.is_some() && files.len() > 1 {
+        return Err("expected_hash applies to single-file patches; split the patch per file".to_string());
+    }
+
+    // Check every target before touching any of them.
+    for file_path in files.keys() {
+        check_write(&resolve_path(workspace_dir, file_path), workspace_dir)?;
+    }
 
     for (file_path, file_hunks) in files {
         let full_path = resolve_path(workspace_dir, &file_path);
@@ -51,6 +60,7 @@ pub fn exec_apply_patch(args: &Value, workspace_dir: &Path) -> Result<String, St
         } else {
             String::new()
         };
+        super::edit_conflict::check(&full_path, expected_hash, &content)?;
 
         let mut lines: Vec<String> = content.lines().map(String::from).collect();
 
@@ -72,6 +82,8 @@ pub fn exec_apply_patch(args: &Value, workspace_dir: &Path) -> Result<String, St
                 file_hunks.len()
             ));
         } else {
+            super::file_locks::acquire(&full_path, "apply_patch")?;
+
             // Ensure parent directory exists
             if let Some(parent) = full_path.parent() {
                 std::fs::create_dir_all(parent)
@@ -0,0 +1,103 @@
+//! Plan tool: maintain a step-by-step checklist for the current task.
+
+use serde_json::Value;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Plan management.
+#[instrument(skip(args, _workspace_dir), fields(action))]
+pub fn exec_plan(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    use crate::plan::*;
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+
+    tracing::Span::current().record("action", action);
+    debug!("Executing plan tool");
+
+    let step = || {
+        args.get("step")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .ok_or_else(|| "Missing required parameter: step".to_string())
+    };
+
+    // Each sub-agent session, chat and connection has its own plan.
+    with_plan(|guard| match action {
+        "create" => {
+            let steps: Vec<String> = args
+                .get("steps")
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|s| s.as_str())
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+            if steps.is_empty() {
+                return Err("Missing required parameter: steps".to_string());
+            }
+            let title = args
+                .get("title")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Plan");
+
+            let plan = Plan::new(title, &steps);
+            let rendered = plan.render();
+            *guard = Some(plan);
+            Ok(format!("Created plan:\n{}", rendered))
+        }
+
+        "update" => {
+            let plan = guard.as_mut().ok_or("No plan exists. Use action 'create' first.")?;
+            let status: StepStatus = args
+                .get("status")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: status".to_string())?
+                .parse()?;
+            plan.set_status(step()?, status)?;
+            Ok(plan.render())
+        }
+
+        "insert" => {
+            let plan = guard.as_mut().ok_or("No plan exists. Use action 'create' first.")?;
+            let text = args
+                .get("text")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: text".to_string())?;
+            // Default to appending at the end.
+            let position = args
+                .get("step")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(usize::MAX);
+            plan.insert(position, text);
+            Ok(plan.render())
+        }
+
+        "remove" => {
+            let plan = guard.as_mut().ok_or("No plan exists. Use action 'create' first.")?;
+            plan.remove(step()?)?;
+            Ok(plan.render())
+        }
+
+        "show" => match guard.as_ref() {
+            Some(plan) => Ok(plan.render()),
+            None => Ok("No plan.".to_string()),
+        },
+
+        "clear" => {
+            *guard = None;
+            Ok("Plan cleared.".to_string())
+        }
+
+        _ => Err(format!(
+            "Unknown action: {}. Valid: create, update, insert, remove, show, clear",
+            action
+        )),
+    })?
+}
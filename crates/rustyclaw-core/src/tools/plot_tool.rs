@@ -0,0 +1,170 @@
+//! The `plot` tool: chart tabular data as a PNG.
+//!
+//! Columns are picked by name: `x` for the horizontal axis (default: the
+//! first column) and `y` for one series per column (default: every other
+//! numeric column).  A numeric `x` gives a true numeric axis for line and
+//! scatter charts; anything else becomes category labels.
+
+use super::charts::{self, ChartKind, ChartSpec, Series};
+use super::helpers::{check_write, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use super::report_tool::slug;
+use super::table::Table;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+const MAX_SIDE: u32 = 4000;
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(|v| v.as_str())
+}
+
+/// Build a chart spec from table columns and the call's arguments.
+fn build_spec(table: &Table, args: &Value) -> Result<ChartSpec, String> {
+    let kind = match str_arg(args, "type").unwrap_or("line").to_lowercase().as_str() {
+        "line" => ChartKind::Line,
+        "bar" => ChartKind::Bar,
+        "scatter" => ChartKind::Scatter,
+        other => return Err(format!("Unknown chart type '{}'; use line, bar or scatter", other)),
+    };
+    if table.rows.is_empty() {
+        return Err("Data has no rows to plot".to_string());
+    }
+
+    let x_col = match str_arg(args, "x") {
+        Some(name) => table.column(name)?,
+        None => 0,
+    };
+    let y_cols: Vec<usize> = match args.get("y") {
+        Some(Value::String(name)) => vec![table.column(name)?],
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|n| {
+                n.as_str()
+                    .ok_or_else(|| "'y' must be a list of column names".to_string())
+                    .and_then(|n| table.column(n))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("'y' must be a column name or a list of names".to_string()),
+        None => (0..table.headers.len())
+            .filter(|&c| c != x_col && table.is_numeric(c))
+            .collect(),
+    };
+    if y_cols.is_empty() {
+        return Err(format!(
+            "No numeric columns to plot besides '{}'; pass 'y' explicitly",
+            table.headers[x_col]
+        ));
+    }
+
+    let numeric_x = kind != ChartKind::Bar && table.is_numeric(x_col);
+    let x = if numeric_x { table.numbers(x_col)? } else { Vec::new() };
+    let labels = if numeric_x {
+        Vec::new()
+    } else {
+        (0..table.rows.len()).map(|r| table.cell(r, x_col).to_string()).collect()
+    };
+    let series = y_cols
+        .iter()
+        .map(|&c| {
+            Ok(Series {
+                name: table.headers[c].clone(),
+                values: table.numbers(c)?,
+                x: x.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let y_label = match y_cols.as_slice() {
+        [only] => table.headers[*only].clone(),
+        _ => String::new(),
+    };
+    Ok(ChartSpec {
+        kind,
+        title: str_arg(args, "title").unwrap_or("").to_string(),
+        x_label: str_arg(args, "xLabel").unwrap_or(&table.headers[x_col]).to_string(),
+        y_label: str_arg(args, "yLabel").map(String::from).unwrap_or(y_label),
+        labels,
+        series,
+    })
+}
+
+/// Plot table data to a PNG in the workspace.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_plot(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let table = Table::from_args(args, workspace_dir)?;
+    let spec = build_spec(&table, args)?;
+
+    let side = |key: &str, default: u32| {
+        args.get(key)
+            .and_then(|v| v.as_u64())
+            .map(|v| (v as u32).clamp(200, MAX_SIDE))
+            .unwrap_or(default)
+    };
+    let (width, height) = (side("width", 1000), side("height", 600));
+
+    let output: PathBuf = match str_arg(args, "output") {
+        Some(o) => resolve_path(workspace_dir, o).with_extension("png"),
+        None => workspace_dir.join("plots").join(format!(
+            "{}-{}.png",
+            slug(if spec.title.is_empty() { "plot" } else { &spec.title }),
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        )),
+    };
+    if is_protected_path(&output) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    check_write(&output, workspace_dir)?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    debug!(path = %output.display(), series = spec.series.len(), rows = table.rows.len(), "Rendering plot");
+    charts::render_png(&spec, width, height, &output)?;
+
+    let names: Vec<&str> = spec.series.iter().map(|s| s.name.as_str()).collect();
+    Ok(format!(
+        "Plotted {} against {} ({} rows): {}\n\nMEDIA: {}",
+        names.join(", "),
+        spec.x_label,
+        table.rows.len(),
+        output.display(),
+        output.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn table() -> Table {
+        Table::parse("month,web,app,note\nJan,10,4,x\nFeb,12,6,y\nMar,9,8,z\n").unwrap()
+    }
+
+    #[test]
+    fn test_build_spec_defaults() {
+        let spec = build_spec(&table(), &json!({ "type": "bar" })).unwrap();
+        assert_eq!(spec.kind, ChartKind::Bar);
+        assert_eq!(spec.labels, vec!["Jan", "Feb", "Mar"]);
+        let names: Vec<&str> = spec.series.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["web", "app"]);
+        assert_eq!(spec.x_label, "month");
+    }
+
+    #[test]
+    fn test_build_spec_numeric_x() {
+        let table = Table::parse("t,temp\n0,20.5\n5,21\n10,22.5\n").unwrap();
+        let spec = build_spec(&table, &json!({ "type": "scatter", "y": "temp" })).unwrap();
+        assert!(spec.labels.is_empty());
+        assert_eq!(spec.series[0].x, vec![0.0, 5.0, 10.0]);
+        assert_eq!(spec.y_label, "temp");
+    }
+
+    #[test]
+    fn test_build_spec_errors() {
+        assert!(build_spec(&table(), &json!({ "type": "pie" })).unwrap_err().contains("Unknown chart type"));
+        assert!(build_spec(&table(), &json!({ "y": ["web", "nope"] })).unwrap_err().contains("No column 'nope'"));
+        assert!(build_spec(&table(), &json!({ "y": "note" })).unwrap_err().contains("not a number"));
+    }
+}
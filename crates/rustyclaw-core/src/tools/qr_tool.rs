@@ -0,0 +1,226 @@
+//! The `qr` tool: make QR codes and read them back from images.
+//!
+//! Codes are rendered as Unicode half-blocks (two modules per character
+//! cell, inverted so they scan from dark terminals) and/or as a PNG.  The
+//! text renderer is also used by `nodes pair` to show the ADB pairing code.
+//! Reading codes back needs the `qr-decode` feature.
+
+use super::helpers::{check_write, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use qrcode::render::unicode;
+use qrcode::{Color, QrCode};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+/// Light margin around the code, in modules (the spec asks for 4).
+const QUIET_ZONE: u32 = 4;
+
+/// Pixels per module in PNG output.
+const DEFAULT_SCALE: u32 = 8;
+
+fn encode(data: &str) -> Result<QrCode, String> {
+    if data.is_empty() {
+        return Err("Nothing to encode".to_string());
+    }
+    QrCode::new(data.as_bytes()).map_err(|e| format!("Can't encode QR code: {}", e))
+}
+
+/// Render `data` as Unicode half-block art.
+pub(crate) fn render_text(data: &str) -> Result<String, String> {
+    Ok(encode(data)?
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+/// Write `data` as a black-on-white PNG.
+pub(crate) fn write_png(data: &str, path: &Path, scale: u32) -> Result<(), String> {
+    let code = encode(data)?;
+    let width = code.width() as u32;
+    let colors = code.to_colors();
+    let side = (width + 2 * QUIET_ZONE) * scale;
+    let image = image::GrayImage::from_fn(side, side, |x, y| {
+        let (mx, my) = (x / scale, y / scale);
+        let inside = (QUIET_ZONE..QUIET_ZONE + width).contains(&mx) && (QUIET_ZONE..QUIET_ZONE + width).contains(&my);
+        let dark = inside && colors[((my - QUIET_ZONE) * width + (mx - QUIET_ZONE)) as usize] == Color::Dark;
+        image::Luma([if dark { 0 } else { 255 }])
+    });
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Escape a field of a `WIFI:` payload.
+fn escape_wifi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// The de-facto Wi-Fi network payload understood by phone cameras.
+pub(crate) fn wifi_payload(ssid: &str, password: Option<&str>, security: &str, hidden: bool) -> String {
+    let mut payload = format!("WIFI:T:{};S:{};", security, escape_wifi(ssid));
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        payload.push_str(&format!("P:{};", escape_wifi(password)));
+    }
+    if hidden {
+        payload.push_str("H:true;");
+    }
+    payload.push(';');
+    payload
+}
+
+/// Decode every QR code found in an image.
+#[cfg(feature = "qr-decode")]
+fn decode(path: &Path) -> Result<Vec<String>, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to open image {}: {}", path.display(), e))?
+        .to_luma8();
+    let (w, h) = image.dimensions();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(w as usize, h as usize, |x, y| {
+        image.get_pixel(x as u32, y as u32).0[0]
+    });
+    let grids = prepared.detect_grids();
+    debug!(path = %path.display(), grids = grids.len(), "Detected QR grids");
+    let mut found = Vec::new();
+    let mut errors = Vec::new();
+    for grid in grids {
+        match grid.decode() {
+            Ok((_, content)) => found.push(content),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    if found.is_empty() && !errors.is_empty() {
+        return Err(format!("Found a QR code but couldn't decode it: {}", errors.join("; ")));
+    }
+    Ok(found)
+}
+
+#[cfg(not(feature = "qr-decode"))]
+fn decode(_path: &Path) -> Result<Vec<String>, String> {
+    Err("QR decoding isn't compiled in. Rebuild with `--features qr-decode`.".to_string())
+}
+
+/// The payload for a `generate` call: `data`, or a `wifi` object.
+fn payload(args: &Value) -> Result<String, String> {
+    if let Some(data) = args.get("data").and_then(|v| v.as_str()) {
+        return Ok(data.to_string());
+    }
+    let wifi = args.get("wifi").ok_or("Provide 'data' or 'wifi'")?;
+    let ssid = wifi
+        .get("ssid")
+        .and_then(|v| v.as_str())
+        .ok_or("wifi needs an 'ssid'")?;
+    let password = wifi.get("password").and_then(|v| v.as_str());
+    let security = match wifi.get("security").and_then(|v| v.as_str()) {
+        Some(s) => s.to_uppercase(),
+        None if password.is_some() => "WPA".to_string(),
+        None => "nopass".to_string(),
+    };
+    if !matches!(security.as_str(), "WPA" | "WEP" | "SAE" | "NOPASS") {
+        return Err(format!("Unknown wifi security '{}'; use WPA, WEP, SAE or nopass", security));
+    }
+    let security = if security == "NOPASS" { "nopass".to_string() } else { security };
+    let hidden = wifi.get("hidden").and_then(|v| v.as_bool()).unwrap_or(false);
+    Ok(wifi_payload(ssid, password, &security, hidden))
+}
+
+/// Generate or decode QR codes.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_qr(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("generate");
+    match action {
+        "generate" => {
+            let data = payload(args)?;
+            let format = args.get("format").and_then(|v| v.as_str()).unwrap_or("both");
+            if !matches!(format, "text" | "png" | "both") {
+                return Err(format!("Unknown format '{}'; use text, png or both", format));
+            }
+            let mut out = String::new();
+            if format != "png" {
+                out.push_str(&format!("```\n{}\n```\n", render_text(&data)?));
+            }
+            if format != "text" {
+                let path: PathBuf = match args.get("output").and_then(|v| v.as_str()) {
+                    Some(o) => resolve_path(workspace_dir, o).with_extension("png"),
+                    None => workspace_dir
+                        .join("qr")
+                        .join(format!("qr-{}.png", chrono::Local::now().format("%Y%m%d-%H%M%S"))),
+                };
+                if is_protected_path(&path) {
+                    return Err(VAULT_ACCESS_DENIED.to_string());
+                }
+                check_write(&path, workspace_dir)?;
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                let scale = args
+                    .get("scale")
+                    .and_then(|v| v.as_u64())
+                    .map(|s| s.clamp(1, 32) as u32)
+                    .unwrap_or(DEFAULT_SCALE);
+                write_png(&data, &path, scale)?;
+                out.push_str(&format!("PNG: {}\n\nMEDIA: {}", path.display(), path.display()));
+            }
+            Ok(out.trim_end().to_string())
+        }
+        "decode" => {
+            let path = args
+                .get("path")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: path")?;
+            let path = resolve_path(workspace_dir, path);
+            if is_protected_path(&path) {
+                return Err(VAULT_ACCESS_DENIED.to_string());
+            }
+            let codes = decode(&path)?;
+            if codes.is_empty() {
+                return Ok(format!("No QR code found in {}.", path.display()));
+            }
+            Ok(json!({ "codes": codes }).to_string())
+        }
+        other => Err(format!("Unknown action: {}. Use generate or decode", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wifi_payload() {
+        assert_eq!(wifi_payload("Home", Some("pa;ss"), "WPA", false), "WIFI:T:WPA;S:Home;P:pa\\;ss;;");
+        assert_eq!(wifi_payload("Cafe", None, "nopass", true), "WIFI:T:nopass;S:Cafe;H:true;;");
+        let args = json!({ "wifi": { "ssid": "Lab", "password": "x" } });
+        assert_eq!(payload(&args).unwrap(), "WIFI:T:WPA;S:Lab;P:x;;");
+        assert!(payload(&json!({ "wifi": { "ssid": "Lab", "security": "open" } })).is_err());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let art = render_text("https://example.com/pair?code=123").unwrap();
+        let lines: Vec<&str> = art.lines().collect();
+        assert!(lines.len() > 10);
+        assert!(lines.iter().all(|l| l.chars().count() == lines[0].chars().count()));
+        assert!(render_text("").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "qr-decode")]
+    fn test_png_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = json!({ "data": "rustyclaw://pair/abc123", "format": "png", "output": "code" });
+        let result = exec_qr(&args, dir.path()).unwrap();
+        assert!(result.contains("MEDIA:"));
+        let decoded = exec_qr(&json!({ "action": "decode", "path": "code.png" }), dir.path()).unwrap();
+        assert!(decoded.contains("rustyclaw://pair/abc123"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Runtime registry for tools supplied by embedders and plugins.
+//!
+//! Built-in tools are static [`ToolDef`](super::ToolDef)s; anything else
+//! implements [`Tool`] (or the typed [`TypedTool`]) and is registered at
+//! startup.  Registered tools are listed in every provider format next to
+//! the built-ins and are dispatched by
+//! [`execute_tool_async`](super::execute_tool_async).
+//!
+//! ```ignore
+//! use rustyclaw_core::tools::{registry, TypedTool};
+//! use schemars::JsonSchema;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, JsonSchema)]
+//! struct LookupArgs {
+//!     /// Order number to look up.
+//!     order_id: String,
+//! }
+//!
+//! struct OrderLookup;
+//!
+//! #[async_trait::async_trait]
+//! impl TypedTool for OrderLookup {
+//!     type Args = LookupArgs;
+//!     const NAME: &'static str = "order_lookup";
+//!     const DESCRIPTION: &'static str = "Look up an order by number.";
+//!
+//!     async fn run(&self, args: LookupArgs, _ws: &Path) -> Result<String, String> {
+//!         Ok(format!("Order {} has shipped", args.order_id))
+//!     }
+//! }
+//!
+//! registry().register(OrderLookup)?;
+//! ```
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing::debug;
+
+/// A tool implemented outside the built-in set.
+#[async_trait]
+pub trait Tool: Send + Sync + 'static {
+    /// Unique tool name as shown to the model.
+    fn name(&self) -> &str;
+
+    /// Description shown to the model.
+    fn description(&self) -> &str;
+
+    /// JSON Schema (`"type": "object"`) describing the arguments.
+    fn parameters(&self) -> Value;
+
+    /// Run the tool.
+    async fn execute(&self, args: &Value, workspace_dir: &Path) -> Result<String, String>;
+
+    /// Stand-in result in dry-run mode.  The default holds the call back,
+    /// since nothing is known about the tool's effects; read-only tools
+    /// return `None` to run normally.
+    fn simulate(&self, _args: &Value, _workspace_dir: &Path) -> Option<Result<String, String>> {
+        Some(held_back(self.name()))
+    }
+}
+
+fn held_back(name: &str) -> Result<String, String> {
+    Ok(format!("[dry-run] custom tool {} was not run — no changes were made.", name))
+}
+
+/// A tool with strongly typed arguments whose schema is derived with
+/// `schemars`.  Every `TypedTool` is also a [`Tool`].
+#[async_trait]
+pub trait TypedTool: Send + Sync + 'static {
+    type Args: DeserializeOwned + JsonSchema + Send;
+
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+    /// Read-only tools run normally in dry-run mode; others are held back.
+    const READ_ONLY: bool = false;
+
+    async fn run(&self, args: Self::Args, workspace_dir: &Path) -> Result<String, String>;
+}
+
+#[async_trait]
+impl<T: TypedTool> Tool for T {
+    fn name(&self) -> &str {
+        T::NAME
+    }
+
+    fn description(&self) -> &str {
+        T::DESCRIPTION
+    }
+
+    fn parameters(&self) -> Value {
+        schema_for::<T::Args>()
+    }
+
+    fn simulate(&self, _args: &Value, _workspace_dir: &Path) -> Option<Result<String, String>> {
+        (!T::READ_ONLY).then(|| held_back(T::NAME))
+    }
+
+    async fn execute(&self, args: &Value, workspace_dir: &Path) -> Result<String, String> {
+        let args: T::Args = serde_json::from_value(args.clone())
+            .map_err(|e| format!("Invalid arguments for {}: {}", T::NAME, e))?;
+        self.run(args, workspace_dir).await
+    }
+}
+
+/// Derive a provider-friendly JSON Schema for an argument type.
+///
+/// Sub-schemas are inlined and the meta-schema / title keys dropped, since
+/// Gemini rejects `$ref` and unknown top-level keywords.
+pub fn schema_for<A: JsonSchema>() -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.meta_schema = None;
+        })
+        .into_generator();
+    let mut schema = serde_json::to_value(generator.into_root_schema_for::<A>())
+        .unwrap_or_else(|_| json!({}));
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("title");
+        obj.remove("$schema");
+        obj.remove("definitions");
+        obj.remove("$defs");
+        obj.entry("type").or_insert(json!("object"));
+        obj.entry("properties").or_insert(json!({}));
+    }
+    schema
+}
+
+/// Registered custom tools, keyed by name.
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: RwLock<BTreeMap<String, Arc<dyn Tool>>>,
+}
+
+impl ToolRegistry {
+    /// Register a tool.  Fails if the name is empty or already taken by a
+    /// built-in or previously registered tool.
+    pub fn register(&self, tool: impl Tool) -> Result<(), String> {
+        self.register_arc(Arc::new(tool))
+    }
+
+    /// Register an already shared tool.
+    pub fn register_arc(&self, tool: Arc<dyn Tool>) -> Result<(), String> {
+        let name = tool.name().to_string();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("Invalid tool name '{}'", name));
+        }
+        if super::all_tools().iter().any(|t| t.name == name) {
+            return Err(format!("'{}' is a built-in tool", name));
+        }
+        let mut tools = self.tools.write().map_err(|_| "Tool registry poisoned".to_string())?;
+        if tools.contains_key(&name) {
+            return Err(format!("Tool '{}' is already registered", name));
+        }
+        debug!(tool = %name, "Registered custom tool");
+        tools.insert(name, tool);
+        Ok(())
+    }
+
+    /// Remove a tool.  Returns whether it was registered.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.tools
+            .write()
+            .map(|mut tools| tools.remove(name).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Look up a tool by name.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.read().ok()?.get(name).cloned()
+    }
+
+    /// Whether a tool with this name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Names of all registered tools, sorted.
+    pub fn names(&self) -> Vec<String> {
+        self.tools
+            .read()
+            .map(|tools| tools.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Snapshot of all registered tools, sorted by name.
+    pub fn tools(&self) -> Vec<Arc<dyn Tool>> {
+        self.tools
+            .read()
+            .map(|tools| tools.values().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// The process-wide tool registry.
+pub fn registry() -> &'static ToolRegistry {
+    static REGISTRY: OnceLock<ToolRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ToolRegistry::default)
+}
@@ -0,0 +1,257 @@
+//! The `render_report` tool: markdown to a styled HTML or PDF file.
+//!
+//! Fenced ```` ```chart ```` blocks holding a JSON [`ChartSpec`] become inline
+//! SVG charts.  HTML is rendered in-process; PDF is printed from that HTML
+//! by the first converter found (headless Chromium/Chrome, wkhtmltopdf or
+//! WeasyPrint).  The result path is returned as `MEDIA:` so messengers can
+//! send it as an attachment.
+
+use super::charts::{self, ChartSpec};
+use super::helpers::{check_write, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, instrument, warn};
+
+/// HTML-to-PDF converters, tried in order: binary and arguments, with
+/// `{html}` / `{pdf}` placeholders.
+const PDF_CONVERTERS: &[(&str, &[&str])] = &[
+    ("chromium", &["--headless", "--disable-gpu", "--no-pdf-header-footer", "--print-to-pdf={pdf}", "{html}"]),
+    ("chromium-browser", &["--headless", "--disable-gpu", "--no-pdf-header-footer", "--print-to-pdf={pdf}", "{html}"]),
+    ("google-chrome", &["--headless", "--disable-gpu", "--no-pdf-header-footer", "--print-to-pdf={pdf}", "{html}"]),
+    ("google-chrome-stable", &["--headless", "--disable-gpu", "--no-pdf-header-footer", "--print-to-pdf={pdf}", "{html}"]),
+    ("wkhtmltopdf", &["--quiet", "--enable-local-file-access", "{html}", "{pdf}"]),
+    ("weasyprint", &["{html}", "{pdf}"]),
+];
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2328;
+       max-width: 860px; margin: 2.5em auto; padding: 0 1.5em; line-height: 1.55; }
+h1, h2, h3 { line-height: 1.25; }
+h1 { border-bottom: 2px solid #d0d7de; padding-bottom: .3em; }
+h2 { border-bottom: 1px solid #d8dee4; padding-bottom: .25em; margin-top: 1.8em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #d0d7de; padding: .4em .8em; }
+th { background: #f6f8fa; }
+tr:nth-child(even) td { background: #fbfcfd; }
+code { background: #f6f8fa; padding: .15em .35em; border-radius: 4px; font-size: 90%; }
+pre { background: #f6f8fa; padding: 1em; overflow-x: auto; border-radius: 6px; }
+pre code { background: none; padding: 0; }
+blockquote { color: #57606a; border-left: 4px solid #d0d7de; margin: 0; padding: 0 1em; }
+figure.chart { margin: 1.5em 0; text-align: center; }
+figure.chart svg { max-width: 100%; height: auto; }
+.chart-error { color: #cf222e; }
+.meta { color: #57606a; font-size: 90%; }
+@media print { body { margin: 0; max-width: none; } h2 { page-break-after: avoid; } figure, table { page-break-inside: avoid; } }
+"#;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Render markdown to an HTML fragment, turning ```chart blocks into SVG.
+fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let mut events = Vec::new();
+    let mut chart_source: Option<String> = None;
+    for event in Parser::new_ext(markdown, options) {
+        match (&mut chart_source, event) {
+            (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang)))) if lang.trim() == "chart" => {
+                chart_source = Some(String::new());
+            }
+            (Some(source), Event::Text(text)) => source.push_str(&text),
+            (Some(_), Event::End(TagEnd::CodeBlock)) => {
+                let source = chart_source.take().unwrap_or_default();
+                let rendered = serde_json::from_str::<ChartSpec>(&source)
+                    .map_err(|e| format!("Invalid chart spec: {}", e))
+                    .and_then(|spec| charts::render_svg(&spec, 720, 400));
+                let html = match rendered {
+                    Ok(svg) => format!("<figure class=\"chart\">{}</figure>\n", svg),
+                    Err(e) => format!("<p class=\"chart-error\">{}</p>\n", escape(&e)),
+                };
+                events.push(Event::Html(CowStr::from(html)));
+            }
+            (Some(_), _) => {}
+            (None, event) => events.push(event),
+        }
+    }
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+/// First `# heading` of the document, if any.
+fn first_heading(markdown: &str) -> Option<String> {
+    markdown
+        .lines()
+        .find_map(|l| l.strip_prefix("# "))
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}<p class=\"meta\">Generated {}</p>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body,
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// Lower-case, dash-separated file name stem for `text`.
+pub(super) fn slug(text: &str) -> String {
+    let slug: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "report".to_string() } else { slug.chars().take(60).collect() }
+}
+
+/// Print `html` to `pdf` with the first available converter.
+fn html_to_pdf(html: &Path, pdf: &Path) -> Result<&'static str, String> {
+    let html_arg = html.display().to_string();
+    let pdf_arg = pdf.display().to_string();
+    let mut tried = Vec::new();
+    for (binary, args) in PDF_CONVERTERS {
+        if which::which(binary).is_err() {
+            continue;
+        }
+        let args: Vec<String> = args
+            .iter()
+            .map(|a| a.replace("{html}", &html_arg).replace("{pdf}", &pdf_arg))
+            .collect();
+        debug!(converter = binary, "Converting report to PDF");
+        match Command::new(binary).args(&args).output() {
+            Ok(out) if out.status.success() && pdf.exists() => return Ok(binary),
+            Ok(out) => tried.push(format!("{}: {}", binary, String::from_utf8_lossy(&out.stderr).trim())),
+            Err(e) => tried.push(format!("{}: {}", binary, e)),
+        }
+    }
+    if tried.is_empty() {
+        return Err(
+            "No PDF converter found. Install Chromium/Chrome, wkhtmltopdf or WeasyPrint, or use format=html"
+                .to_string(),
+        );
+    }
+    Err(format!("PDF conversion failed: {}", tried.join("; ")))
+}
+
+/// Render markdown (inline or from a file) into an HTML or PDF report.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_render_report(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let markdown = match (
+        args.get("markdown").and_then(|v| v.as_str()),
+        args.get("path").and_then(|v| v.as_str()),
+    ) {
+        (Some(md), _) => md.to_string(),
+        (None, Some(path)) => {
+            let path = resolve_path(workspace_dir, path);
+            if is_protected_path(&path) {
+                return Err(VAULT_ACCESS_DENIED.to_string());
+            }
+            std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        }
+        (None, None) => return Err("Provide 'markdown' or 'path'".to_string()),
+    };
+
+    let title = args
+        .get("title")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .or_else(|| first_heading(&markdown))
+        .unwrap_or_else(|| "Report".to_string());
+    let output_arg = args.get("output").and_then(|v| v.as_str());
+    let format = args
+        .get("format")
+        .and_then(|v| v.as_str())
+        .or_else(|| output_arg.and_then(|o| Path::new(o).extension()).and_then(|e| e.to_str()))
+        .unwrap_or("pdf")
+        .to_lowercase();
+    if format != "pdf" && format != "html" {
+        return Err(format!("Unknown format '{}'; use pdf or html", format));
+    }
+
+    let output: PathBuf = match output_arg {
+        Some(o) => resolve_path(workspace_dir, o).with_extension(&format),
+        None => workspace_dir.join("reports").join(format!(
+            "{}-{}.{}",
+            slug(&title),
+            chrono::Local::now().format("%Y-%m-%d"),
+            format
+        )),
+    };
+    if is_protected_path(&output) {
+        warn!(path = %output.display(), "Attempted report write to protected path");
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    check_write(&output, workspace_dir)?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let html = document(&title, &markdown_to_html(&markdown));
+    let via = if format == "html" {
+        std::fs::write(&output, &html).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        "html"
+    } else {
+        // Converters need a file; keep it next to the PDF so relative
+        // image links resolve the same way.
+        let staging = output.with_extension("render.html");
+        std::fs::write(&staging, &html).map_err(|e| format!("Failed to write {}: {}", staging.display(), e))?;
+        let result = html_to_pdf(&staging, &output);
+        let _ = std::fs::remove_file(&staging);
+        result?
+    };
+
+    debug!(path = %output.display(), via, "Report rendered");
+    Ok(format!(
+        "Rendered '{}' to {} ({}).\n\nMEDIA: {}",
+        title,
+        output.display(),
+        via,
+        output.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_markdown_with_chart() {
+        let md = "# Weekly\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```chart\n{\"type\": \"bar\", \"labels\": [\"x\"], \"series\": [{\"values\": [3]}]}\n```\n\n```chart\nnot json\n```\n";
+        let html = markdown_to_html(md);
+        assert!(html.contains("<h1>Weekly</h1>"));
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<figure class=\"chart\"><svg"));
+        assert!(html.contains("Invalid chart spec"));
+        assert!(!html.contains("not json"));
+    }
+
+    #[test]
+    fn test_title_and_slug() {
+        assert_eq!(first_heading("intro\n# Q3 Numbers\n"), Some("Q3 Numbers".to_string()));
+        assert_eq!(slug("Q3 Numbers: Final!"), "q3-numbers-final");
+        assert_eq!(slug("!!!"), "report");
+    }
+
+    #[test]
+    fn test_render_html_report() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = serde_json::json!({ "markdown": "# Hello\n\nWorld", "output": "out/hello.html" });
+        let result = exec_render_report(&args, dir.path()).unwrap();
+        let path = dir.path().join("out/hello.html");
+        assert!(result.contains(&format!("MEDIA: {}", path.display())));
+        let html = std::fs::read_to_string(path).unwrap();
+        assert!(html.contains("<title>Hello</title>"));
+        assert!(html.contains("<p>World</p>"));
+    }
+}
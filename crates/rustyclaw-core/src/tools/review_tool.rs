@@ -0,0 +1,439 @@
+//! Code review over git diffs.
+//!
+//! `review_diff` collects a diff (a ref range, the staged changes, or the
+//! working tree), splits it into per-file chunks annotated with new-file
+//! line numbers, asks a model to review each chunk with the project's own
+//! guidance as context, and merges the findings into one report.
+//!
+//! The model is the gateway's configured one, with its key from the
+//! vault, so reviews go wherever the rest of the conversation does.
+
+use super::helpers::{is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::process::Command;
+use tracing::{debug, instrument, warn};
+
+/// Largest chunk of diff text sent in one model call.
+const MAX_CHUNK_CHARS: usize = 12_000;
+
+/// Default cap on model calls per review.
+const DEFAULT_MAX_CHUNKS: usize = 20;
+
+/// Project files whose contents guide the review.
+const GUIDANCE_FILES: &[&str] = &["AGENTS.md", "CONTRIBUTING.md", ".github/CONTRIBUTING.md"];
+
+/// A single review finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// "critical", "high", "medium", "low" or "info".
+    pub severity: String,
+    #[serde(default)]
+    pub file: String,
+    #[serde(default)]
+    pub line: Option<u64>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "critical" => 0,
+        "high" => 1,
+        "medium" => 2,
+        "low" => 3,
+        _ => 4,
+    }
+}
+
+/// Map the many names models use for severities onto ours.
+fn normalize_severity(s: &str) -> String {
+    match s.to_ascii_lowercase().as_str() {
+        "critical" | "blocker" => "critical",
+        "high" | "major" | "error" => "high",
+        "medium" | "moderate" | "warning" => "medium",
+        "low" | "minor" | "nit" => "low",
+        _ => "info",
+    }
+    .to_string()
+}
+
+// ── Diffs ───────────────────────────────────────────────────────────────────
+
+/// One file's part of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: String,
+    pub text: String,
+}
+
+/// Split unified `git diff` output into per-file pieces.
+pub fn split_files(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            // "a/x b/x": take the b/ side so renames report the new path.
+            let path = rest
+                .rsplit_once(" b/")
+                .map(|(_, b)| b)
+                .unwrap_or(rest)
+                .to_string();
+            files.push(FileDiff { path, text: String::new() });
+        }
+        if let Some(file) = files.last_mut() {
+            file.text.push_str(line);
+            file.text.push('\n');
+        }
+    }
+    files
+}
+
+/// Prefix added and context lines with their new-file line numbers so the
+/// model can cite exact lines.
+pub fn annotate(file_diff: &str) -> String {
+    let mut out = String::with_capacity(file_diff.len() + file_diff.len() / 4);
+    let mut next: Option<u64> = None;
+    for line in file_diff.lines() {
+        if line.starts_with("@@") {
+            // @@ -a,b +c,d @@
+            next = line
+                .split_whitespace()
+                .find_map(|t| t.strip_prefix('+'))
+                .and_then(|t| t.split(',').next())
+                .and_then(|n| n.parse().ok());
+            out.push_str(line);
+        } else if let Some(n) = next.filter(|_| !line.starts_with('-') && !line.starts_with('\\')) {
+            out.push_str(&format!("{:>5} {}", n, line));
+            next = Some(n + 1);
+        } else if next.is_some() {
+            out.push_str(&format!("      {}", line));
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Split an annotated file diff into chunks of at most `max` characters,
+/// breaking between hunks where possible.
+pub fn chunk(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let hunk_start = line.starts_with("@@");
+        if !current.is_empty() && (current.len() + line.len() + 1 > max || (hunk_start && current.len() > max / 2)) {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Run `git diff` for the requested source.
+fn git_diff(workspace_dir: &Path, range: Option<&str>, staged: bool, paths: &[String]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(workspace_dir).args(["diff", "--no-color", "--no-ext-diff", "-U5"]);
+    match (range, staged) {
+        (Some(range), _) => {
+            if range.starts_with('-') {
+                return Err(format!("Invalid range: {}", range));
+            }
+            cmd.arg(range);
+        }
+        (None, true) => {
+            cmd.arg("--cached");
+        }
+        (None, false) => {
+            cmd.arg("HEAD");
+        }
+    }
+    cmd.arg("--");
+    cmd.args(paths);
+    let output = cmd.output().map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// ── Model ───────────────────────────────────────────────────────────────────
+
+/// The project's review guidance, trimmed to a sensible size.
+fn project_context(workspace_dir: &Path) -> String {
+    let mut context = String::new();
+    for name in GUIDANCE_FILES {
+        if let Ok(text) = std::fs::read_to_string(workspace_dir.join(name)) {
+            context.push_str(&format!("## {}\n{}\n\n", name, text.chars().take(4000).collect::<String>()));
+        }
+    }
+    if let Some(memory) = crate::project::project_memory_section(workspace_dir) {
+        context.push_str(&memory.chars().take(4000).collect::<String>());
+    }
+    context
+}
+
+fn review_prompt(context: &str, focus: Option<&str>, file: &str, chunk: &str) -> String {
+    let mut prompt = String::from(
+        "You are reviewing a code change. Report real problems only: bugs, \
+         security issues, data loss, race conditions, missing error handling, \
+         and clear violations of the project's conventions. Skip praise and \
+         style nits a formatter would fix.\n\n\
+         Lines in the diff are prefixed with their line number in the new \
+         file; removed lines have no number.\n\n\
+         Reply with ONLY a JSON array (empty if nothing is wrong) of objects: \
+         {\"severity\": \"critical|high|medium|low|info\", \"line\": <number>, \
+         \"message\": \"...\", \"suggestion\": \"...\"}\n\n",
+    );
+    if !context.trim().is_empty() {
+        prompt.push_str(&format!("Project guidance:\n{}\n\n", context));
+    }
+    if let Some(focus) = focus {
+        prompt.push_str(&format!("Pay particular attention to: {}\n\n", focus));
+    }
+    prompt.push_str(&format!("File: {}\n```diff\n{}```\n", file, chunk));
+    prompt
+}
+
+/// Send `prompt` to the gateway's configured model.
+fn call_model(prompt: &str) -> Result<String, String> {
+    crate::gateway::complete_blocking(prompt)
+}
+
+/// Pull the findings array out of a model reply, tolerating prose or code
+/// fences around it.
+pub fn parse_findings(reply: &str, file: &str) -> Vec<Finding> {
+    let (Some(start), Some(end)) = (reply.find('['), reply.rfind(']')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    let Ok(Value::Array(items)) = serde_json::from_str::<Value>(&reply[start..=end]) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let message = item["message"].as_str()?.trim();
+            if message.is_empty() {
+                return None;
+            }
+            Some(Finding {
+                severity: normalize_severity(item["severity"].as_str().unwrap_or("info")),
+                file: file.to_string(),
+                line: item["line"].as_u64(),
+                message: message.to_string(),
+                suggestion: item["suggestion"]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from),
+            })
+        })
+        .collect()
+}
+
+/// Markdown report, suitable for a PR comment.
+pub fn render_markdown(source: &str, files: usize, findings: &[Finding], skipped: usize) -> String {
+    let mut out = format!("## Review of {}\n\n", source);
+    if findings.is_empty() {
+        out.push_str(&format!("No issues found in {} file(s).\n", files));
+    } else {
+        out.push_str(&format!("{} finding(s) in {} file(s):\n\n", findings.len(), files));
+        for f in findings {
+            let location = match f.line {
+                Some(line) => format!("{}:{}", f.file, line),
+                None => f.file.clone(),
+            };
+            out.push_str(&format!("- **{}** `{}` — {}\n", f.severity, location, f.message));
+            if let Some(suggestion) = &f.suggestion {
+                out.push_str(&format!("  - Suggestion: {}\n", suggestion));
+            }
+        }
+    }
+    if skipped > 0 {
+        out.push_str(&format!("\n_{} chunk(s) not reviewed (maxChunks reached)._\n", skipped));
+    }
+    out
+}
+
+// ── Tool ────────────────────────────────────────────────────────────────────
+
+/// Review a diff and return aggregated findings.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_review_diff(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let range = args.get("range").and_then(|v| v.as_str()).filter(|r| !r.trim().is_empty());
+    let staged = args.get("staged").and_then(|v| v.as_bool()).unwrap_or(false);
+    let focus = args.get("focus").and_then(|v| v.as_str());
+    let format = args.get("format").and_then(|v| v.as_str()).unwrap_or("markdown");
+    if !matches!(format, "markdown" | "json") {
+        return Err(format!("Unknown format: {}. Use markdown or json", format));
+    }
+    let max_chunks = args
+        .get("maxChunks")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_MAX_CHUNKS)
+        .max(1);
+    let paths: Vec<String> = args
+        .get("paths")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    for path in &paths {
+        if is_protected_path(&resolve_path(workspace_dir, path)) {
+            return Err(VAULT_ACCESS_DENIED.to_string());
+        }
+    }
+
+    let source = match (range, staged) {
+        (Some(r), _) => r.to_string(),
+        (None, true) => "staged changes".to_string(),
+        (None, false) => "working tree changes".to_string(),
+    };
+    let diff = git_diff(workspace_dir, range, staged, &paths)?;
+    let files: Vec<FileDiff> = split_files(&diff)
+        .into_iter()
+        .filter(|f| !is_protected_path(&resolve_path(workspace_dir, &f.path)))
+        .collect();
+    if files.is_empty() {
+        return Ok(format!("No changes to review in {}.", source));
+    }
+
+    let context = project_context(workspace_dir);
+    let chunks: Vec<(String, String)> = files
+        .iter()
+        .flat_map(|f| {
+            chunk(&annotate(&f.text), MAX_CHUNK_CHARS)
+                .into_iter()
+                .map(move |c| (f.path.clone(), c))
+        })
+        .collect();
+    let skipped = chunks.len().saturating_sub(max_chunks);
+    debug!(files = files.len(), chunks = chunks.len(), skipped, "Reviewing diff");
+
+    let mut findings = Vec::new();
+    let mut failures = Vec::new();
+    for (file, text) in chunks.iter().take(max_chunks) {
+        match call_model(&review_prompt(&context, focus, file, text)) {
+            Ok(reply) => findings.extend(parse_findings(&reply, file)),
+            // A missing model fails every chunk the same way; stop early.
+            Err(e) if e == "No model is configured" => return Err(e),
+            Err(e) => {
+                warn!(file = %file, error = %e, "Review of chunk failed");
+                failures.push(format!("{}: {}", file, e));
+            }
+        }
+    }
+    findings.sort_by(|a, b| {
+        severity_rank(&a.severity)
+            .cmp(&severity_rank(&b.severity))
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+
+    if format == "json" {
+        return serde_json::to_string_pretty(&json!({
+            "source": source,
+            "files": files.len(),
+            "chunks_reviewed": chunks.len() - skipped,
+            "chunks_skipped": skipped,
+            "findings": findings,
+            "errors": failures,
+        }))
+        .map_err(|e| e.to_string());
+    }
+    let mut report = render_markdown(&source, files.len(), &findings, skipped);
+    if !failures.is_empty() {
+        report.push_str(&format!("\nChunks that failed to review:\n- {}\n", failures.join("\n- ")));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,3 +10,3 @@\n fn a() {\n-    old();\n+    new();\n }\ndiff --git a/b.txt b/b.txt\n--- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-x\n+y\n";
+
+    #[test]
+    fn test_split_and_annotate() {
+        let files = split_files(DIFF);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/a.rs");
+        let annotated = annotate(&files[0].text);
+        assert!(annotated.contains("   10  fn a() {"));
+        assert!(annotated.contains("      -    old();"));
+        assert!(annotated.contains("   11 +    new();"));
+        assert!(annotated.contains("   12  }"));
+    }
+
+    #[test]
+    fn test_chunk_respects_limit() {
+        let text = (0..100).map(|i| format!("+line {}\n", i)).collect::<String>();
+        let chunks = chunk(&text, 200);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= 200));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn test_parse_findings() {
+        let reply = "Here you go:\n```json\n[{\"severity\":\"Major\",\"line\":11,\"message\":\"new() can panic\",\"suggestion\":\"handle the error\"},{\"severity\":\"low\",\"message\":\"\"}]\n```";
+        let findings = parse_findings(reply, "src/a.rs");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, "high");
+        assert_eq!(findings[0].line, Some(11));
+        assert!(parse_findings("Looks good!", "x").is_empty());
+
+        let md = render_markdown("HEAD~1..HEAD", 1, &findings, 0);
+        assert!(md.contains("**high** `src/a.rs:11`"));
+        assert!(md.contains("Suggestion: handle the error"));
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git").current_dir(dir).args(args).output().is_ok_and(|o| o.status.success())
+    }
+
+    #[test]
+    fn test_review_uses_the_configured_model() {
+        let dir = tempfile::TempDir::new().unwrap();
+        if !git(dir.path(), &["init", "-q"]) {
+            return;
+        }
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        git(dir.path(), &["add", "-A"]);
+        git(dir.path(), &["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-qm", "init"]);
+        std::fs::write(dir.path().join("lib.rs"), "fn a() { todo!() }\n").unwrap();
+
+        let fixtures = tempfile::TempDir::new().unwrap();
+        let fixture = fixtures.path().join("mock.json");
+        let reply = r#"[{"severity": "high", "line": 1, "message": "todo!() panics"}]"#;
+        std::fs::write(&fixture, json!({ "default": reply }).to_string()).unwrap();
+        crate::gateway::set_tool_model(Some(std::sync::Arc::new(crate::gateway::ModelContext {
+            provider: "mock".to_string(),
+            model: "mock".to_string(),
+            base_url: fixture.display().to_string(),
+            api_key: None,
+        })));
+
+        let workspace = dir.path().to_path_buf();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let report = runtime
+            .block_on(tokio::task::spawn_blocking(move || exec_review_diff(&json!({}), &workspace)))
+            .unwrap()
+            .unwrap();
+        crate::gateway::set_tool_model(None);
+        assert!(report.contains("**high** `lib.rs:1` — todo!() panics"), "{}", report);
+    }
+}
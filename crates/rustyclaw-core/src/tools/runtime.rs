@@ -1,8 +1,8 @@
 //! Runtime tools: execute_command and process management.
 
 use super::helpers::{
-    command_references_credentials, is_protected_path, process_manager, resolve_path,
-    run_sandboxed_command, VAULT_ACCESS_DENIED,
+    check_write, command_references_credentials, expand_tilde, is_protected_path, process_manager,
+    resolve_path, run_sandboxed_command, VAULT_ACCESS_DENIED,
 };
 use crate::process_manager::SessionStatus;
 use serde_json::{json, Value};
@@ -54,17 +54,71 @@ pub fn exec_execute_command(args: &Value, workspace_dir: &Path) -> Result<String
         return Err(VAULT_ACCESS_DENIED.to_string());
     }
 
+    // With a container runtime the container is the isolation boundary,
+    // so commands go through `docker exec` instead of the host sandbox.
+    let container = crate::container::active();
+
+    // On the host, the command runs with our permissions: hold its working
+    // directory, the files it redirects into and every absolute or `~` path
+    // it names to the write policy.  We can't tell which arguments a program
+    // writes (`cp`, `mv`, `sed -i`, `rm`…), so with `confine_writes` a host
+    // command may not name paths outside the workspace at all.  This is
+    // best-effort: paths built at run time (variables, `$HOME`, `cd ..`,
+    // relative `../` climbs, scripts the command runs) are not seen.
+    if container.is_none() {
+        check_write(&cwd, workspace_dir)?;
+        for target in crate::sandbox::redirect_targets(command) {
+            check_write(&resolve_path(&cwd, &expand_tilde(&target).to_string_lossy()), workspace_dir)?;
+        }
+        for path in crate::sandbox::extract_paths_from_command(command) {
+            let named = path.to_string_lossy();
+            if named == "/dev/null" || named.starts_with("/dev/std") {
+                continue;
+            }
+            check_write(&expand_tilde(&named), workspace_dir)
+                .map_err(|e| format!("{} The command names this path, and host commands may write any path they name.", e))?;
+        }
+    }
+
+    // On the host, enter the project's direnv / Nix environment if enabled.
+    let activated;
+    let command = if container.is_none() {
+        activated = crate::dev_env::activate(workspace_dir, command);
+        activated.as_str()
+    } else {
+        command
+    };
+
     // If background requested immediately, spawn and return session ID
     // Note: Background processes can't be fully sandboxed (we need the child handle)
     // but we still do path validation checks above.
     if background {
         debug!("Spawning background process");
+        let container_child = match container {
+            Some(ref exec) => Some(
+                exec.command(command, &cwd, workspace_dir)?
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| format!("Failed to spawn process: {}", e))?,
+            ),
+            None => None,
+        };
         let manager = process_manager();
         let mut mgr = manager
             .lock()
             .map_err(|_| "Failed to acquire process manager lock".to_string())?;
 
-        let session_id = mgr.spawn(command, cwd.to_string_lossy().as_ref(), Some(timeout_secs))?;
+        let session_id = match container_child {
+            Some(child) => mgr.insert(crate::process_manager::ExecSession::new(
+                command.to_string(),
+                cwd.to_string_lossy().to_string(),
+                Some(Duration::from_secs(timeout_secs)),
+                child,
+            )),
+            None => mgr.spawn(command, cwd.to_string_lossy().as_ref(), Some(timeout_secs))?,
+        };
         debug!(session_id = %session_id, "Background process spawned");
 
         return Ok(json!({
@@ -77,17 +131,28 @@ pub fn exec_execute_command(args: &Value, workspace_dir: &Path) -> Result<String
 
     // For foreground execution with no yield (immediate), use sandbox
     if yield_ms == 0 {
-        let output = run_sandboxed_command(command, &cwd)?;
+        let output = match container {
+            Some(ref exec) => exec
+                .command(command, &cwd, workspace_dir)?
+                .output()
+                .map_err(|e| format!("Command failed: {}", e))?,
+            None => run_sandboxed_command(command, &cwd)?,
+        };
         return format_output(output, timeout_secs);
     }
 
     // For commands with yield support, we need to spawn directly so we can
     // transfer the child to the process manager if it takes too long.
     // Path validation is done above; sandboxing is best-effort here.
-    let mut child = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .current_dir(&cwd)
+    let mut shell = match container {
+        Some(ref exec) => exec.command(command, &cwd, workspace_dir)?,
+        None => {
+            let mut sh = std::process::Command::new("sh");
+            sh.arg("-c").arg(command).current_dir(&cwd);
+            sh
+        }
+    };
+    let mut child = shell
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -0,0 +1,230 @@
+//! Keeping tool schemas small.
+//!
+//! With a hundred tools the schemas alone cost tens of thousands of tokens
+//! on every model call.  The `[tool_schemas]` section trims them:
+//!
+//! ```toml
+//! [tool_schemas]
+//! max_description_chars = 200        # cut at a sentence end; 0 = keep
+//! max_param_chars = 80               # the same for parameter descriptions
+//! compact_models = ["ollama/*", "*-mini"]   # one-sentence descriptions, no optional params
+//! lazy = true                        # start with the core tools, load groups on request
+//!
+//! [tool_schemas.router]
+//! enabled = true
+//! model = "gpt-4o-mini"              # picks the tools for each turn; see below for the default
+//! max_tools = 12
+//! always = ["read_file", "list_directory"]
+//! ```
+//!
+//! `compact_models` patterns match the model name or `provider/model`.
+//! Without a router `model`, the fastest model on the turn's provider from
+//! `rustyclaw bench providers` is used, or else the turn's own model.
+//! With the router on, a turn is offered only the tools it picked plus
+//! `always`; if the router call fails, the turn gets every tool.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+
+/// The `[tool_schemas]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaBudget {
+    /// Longest tool description sent to the model; 0 means no limit.
+    #[serde(default)]
+    pub max_description_chars: usize,
+    /// Longest parameter description sent to the model; 0 means no limit.
+    #[serde(default)]
+    pub max_param_chars: usize,
+    /// Models (`*` globs) that get compact schemas.
+    #[serde(default)]
+    pub compact_models: Vec<String>,
+    /// Start turns with the core tools and let the model load more groups
+    /// with `request_tools`.
+    #[serde(default)]
+    pub lazy: bool,
+    /// Per-turn tool selection by a cheap model.
+    #[serde(default)]
+    pub router: ToolRouterConfig,
+}
+
+/// The `[tool_schemas.router]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolRouterConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Model that picks the tools, on the turn's provider.  Defaults to
+    /// the fastest benchmarked one, then the turn's own model.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Most tools the router may pick.
+    #[serde(default = "default_max_tools")]
+    pub max_tools: usize,
+    /// Tools offered on every turn regardless of the router.
+    #[serde(default)]
+    pub always: Vec<String>,
+}
+
+fn default_max_tools() -> usize {
+    12
+}
+
+impl Default for ToolRouterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: None,
+            max_tools: default_max_tools(),
+            always: Vec::new(),
+        }
+    }
+}
+
+static CONFIG: Mutex<Option<SchemaBudget>> = Mutex::new(None);
+
+/// Set the schema budget used when formatting tools.  Called at startup
+/// and on reload.
+pub fn set_schema_budget(config: SchemaBudget) {
+    if let Ok(mut guard) = CONFIG.lock() {
+        *guard = Some(config);
+    }
+}
+
+/// The configured schema budget, or the defaults (no trimming).
+pub fn schema_budget() -> SchemaBudget {
+    CONFIG.lock().ok().and_then(|g| g.clone()).unwrap_or_default()
+}
+
+/// `text` up to and including its first sentence.
+pub fn first_sentence(text: &str) -> &str {
+    match text.find(". ") {
+        Some(i) => &text[..=i],
+        None => text,
+    }
+}
+
+/// `text` cut to at most `max` characters, at the last sentence end if
+/// there is one, otherwise at a word boundary with an ellipsis.
+pub fn shorten(text: &str, max: usize) -> String {
+    if max == 0 || text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max).collect();
+    if let Some(i) = cut.rfind(". ").or_else(|| cut.strip_suffix('.').map(|s| s.len())) {
+        return cut[..=i].to_string();
+    }
+    let word_end = cut.rfind(char::is_whitespace).unwrap_or(cut.len());
+    format!("{}…", cut[..word_end].trim_end())
+}
+
+impl SchemaBudget {
+    /// Whether `model` on `provider` gets compact schemas.
+    pub fn is_compact(&self, provider: &str, model: &str) -> bool {
+        let qualified = format!("{}/{}", provider, model);
+        self.compact_models
+            .iter()
+            .any(|p| crate::wildcard::glob_match(p, model) || crate::wildcard::glob_match(p, &qualified))
+    }
+
+    /// Shrink a tool's parameter schema in place and return its shortened
+    /// description.
+    pub fn minify(&self, description: &str, parameters: &mut Value, compact: bool) -> String {
+        let description = if compact { first_sentence(description) } else { description };
+
+        let required: Vec<String> = parameters
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        if let Some(properties) = parameters.get_mut("properties").and_then(|p| p.as_object_mut()) {
+            if compact {
+                properties.retain(|name, _| required.contains(name));
+            }
+            for prop in properties.values_mut() {
+                let Some(text) = prop.get("description").and_then(|d| d.as_str()) else {
+                    continue;
+                };
+                let text = if compact { first_sentence(text) } else { text };
+                let short = shorten(text, self.max_param_chars);
+                prop["description"] = Value::String(short);
+            }
+        }
+
+        shorten(description, self.max_description_chars)
+    }
+}
+
+/// Prompt asking the router model which tools a request needs.
+pub fn router_prompt(request: &str, catalog: &[(String, String)], max_tools: usize) -> String {
+    let mut prompt = format!(
+        "Pick the tools an assistant will need for the request below, at most {}. \
+         Reply with the tool names only, comma-separated, or \"none\".\n\nTools:\n",
+        max_tools
+    );
+    for (name, summary) in catalog {
+        prompt.push_str(&format!("- {}: {}\n", name, summary));
+    }
+    prompt.push_str(&format!("\nRequest:\n{}", request));
+    prompt
+}
+
+/// Known tool names in the router's reply, in order, at most `max`.
+pub fn parse_router_reply(reply: &str, known: &[String], max: usize) -> Vec<String> {
+    let mut picked: Vec<String> = Vec::new();
+    for word in reply.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-')) {
+        if picked.len() >= max {
+            break;
+        }
+        if known.iter().any(|k| k == word) && !picked.iter().any(|p| p == word) {
+            picked.push(word.to_string());
+        }
+    }
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_shorten() {
+        let text = "Read a file. Supports line ranges and binary detection.";
+        assert_eq!(shorten(text, 0), text);
+        assert_eq!(shorten(text, 30), "Read a file.");
+        assert_eq!(shorten("Read the contents of a file", 14), "Read the…");
+        assert_eq!(first_sentence(text), "Read a file.");
+    }
+
+    #[test]
+    fn test_compact_schema_drops_optional_params() {
+        let budget: SchemaBudget = toml::from_str("max_param_chars = 10\ncompact_models = [\"ollama/*\"]").unwrap();
+        assert!(budget.is_compact("ollama", "llama3.2"));
+        assert!(!budget.is_compact("openai", "gpt-4o"));
+        assert_eq!(budget.router.max_tools, 12);
+
+        let mut params = json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string", "description": "Path to read. Relative to the workspace."},
+                "offset": {"type": "integer", "description": "First line"},
+            },
+            "required": ["path"],
+        });
+        let description = budget.minify("Read a file. Long explanation.", &mut params, true);
+        assert_eq!(description, "Read a file.");
+        assert!(params["properties"].get("offset").is_none());
+        assert_eq!(params["properties"]["path"]["description"], "Path to…");
+    }
+
+    #[test]
+    fn test_parse_router_reply() {
+        let known: Vec<String> = ["read_file", "web_fetch", "write_file"].map(String::from).to_vec();
+        let picked = parse_router_reply("web_fetch, read_file, bogus, web_fetch", &known, 5);
+        assert_eq!(picked, ["web_fetch", "read_file"]);
+        assert_eq!(parse_router_reply("read_file, write_file", &known, 1), ["read_file"]);
+        assert!(parse_router_reply("none", &known, 5).is_empty());
+        let prompt = router_prompt("fetch example.com", &[("web_fetch".into(), "Fetch a URL".into())], 5);
+        assert!(prompt.contains("- web_fetch: Fetch a URL") && prompt.ends_with("fetch example.com"));
+    }
+}
@@ -0,0 +1,31 @@
+//! Script tool: run Rhai automation scripts.
+
+use serde_json::{json, Value};
+use std::path::Path;
+use tracing::{debug, instrument};
+
+use crate::scripting;
+
+/// Run a named script, inline code, or list the available scripts.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_script_run(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let script_args = args.get("args").cloned().unwrap_or_else(|| json!({}));
+
+    if let Some(code) = args.get("code").and_then(|v| v.as_str()) {
+        debug!("Running inline script");
+        return scripting::run_script(code, &script_args, workspace_dir);
+    }
+
+    if let Some(name) = args.get("script").and_then(|v| v.as_str()) {
+        return scripting::run_named(name, &script_args, workspace_dir);
+    }
+
+    let names = scripting::list_scripts(workspace_dir);
+    if names.is_empty() {
+        return Ok(format!(
+            "No scripts found in {}",
+            scripting::scripts_dir(workspace_dir).display()
+        ));
+    }
+    Ok(format!("Available scripts:\n{}", names.join("\n")))
+}
@@ -1,4 +1,5 @@
-//! Secrets tools: secrets_list, secrets_get, secrets_store.
+//! Secrets tools: secrets_list, secrets_get, secrets_store, generate_secret,
+//! totp, ssh_key, encrypt_file, decrypt_file.
 //!
 //! These are stub implementations. Real execution is intercepted by the gateway
 //! before `execute_tool` is reached. If we end up here it means something
@@ -0,0 +1,318 @@
+//! The `serial` tool: talk to microcontrollers and other devices on serial
+//! ports.
+//!
+//! An open port is a background session in the process manager: a small
+//! `sh` bridge holds the tty open, copies device output to its stdout and
+//! its stdin to the device.  So besides this tool's own actions, `process`
+//! can poll, log and kill serial sessions like any other.
+
+use super::helpers::process_manager;
+use crate::process_manager::{ExecSession, SessionStatus};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+/// Prefix of the process-manager command label for serial sessions.
+const SESSION_PREFIX: &str = "serial ";
+
+const BAUD_RATES: &[u32] = &[
+    300, 1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
+];
+
+/// Configure the tty on fd 3 and copy in both directions.  The reader runs
+/// in the background; both die together because the session kills the
+/// whole process group.
+#[cfg(unix)]
+const BRIDGE: &str = r#"exec 3<>"$SERIAL_PORT" || exit 1
+stty "$SERIAL_BAUD" raw -echo -hupcl clocal <&3 || exit 1
+cat <&3 &
+exec cat >&3"#;
+
+/// Candidate serial devices, with stable `/dev/serial/by-id` names where
+/// udev provides them.
+fn list_ports() -> Vec<Value> {
+    let mut ports = Vec::new();
+    let mut seen: Vec<PathBuf> = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir("/dev/serial/by-id") {
+        for entry in entries.flatten() {
+            let link = entry.path();
+            let target = std::fs::canonicalize(&link).unwrap_or_else(|_| link.clone());
+            ports.push(json!({
+                "port": target.display().to_string(),
+                "id": entry.file_name().to_string_lossy(),
+            }));
+            seen.push(target);
+        }
+    }
+
+    // ttyUSB/ttyACM: USB adapters and CDC boards (Linux); ttyAMA: Pi UART;
+    // cu.*: macOS call-out devices, which don't wait for carrier detect.
+    let prefixes = ["ttyUSB", "ttyACM", "ttyAMA", "cu."];
+    if let Ok(entries) = std::fs::read_dir("/dev") {
+        let mut extra: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|e| prefixes.iter().any(|p| e.file_name().to_string_lossy().starts_with(p)))
+            .map(|e| e.path())
+            .filter(|p| !seen.contains(p))
+            .collect();
+        extra.sort();
+        ports.extend(extra.into_iter().map(|p| json!({ "port": p.display().to_string() })));
+    }
+    ports
+}
+
+/// Line ending appended by `send`.
+fn line_ending(name: &str) -> Result<&'static str, String> {
+    match name.to_lowercase().as_str() {
+        "lf" | "\\n" => Ok("\n"),
+        "crlf" | "\\r\\n" => Ok("\r\n"),
+        "cr" | "\\r" => Ok("\r"),
+        "none" | "" => Ok(""),
+        other => Err(format!("Unknown lineEnding '{}'; use lf, crlf, cr or none", other)),
+    }
+}
+
+#[cfg(unix)]
+fn open_port(port: &str, baud: u32) -> Result<String, String> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    if !Path::new(port).exists() {
+        return Err(format!("No such port: {} (use the list action)", port));
+    }
+    let label = format!("{}{} @ {}", SESSION_PREFIX, port, baud);
+    let manager = process_manager();
+    let mut mgr = manager
+        .lock()
+        .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+    mgr.poll_all();
+    if let Some(existing) = mgr
+        .list_active()
+        .into_iter()
+        .find(|s| s.command.starts_with(&format!("{}{} ", SESSION_PREFIX, port)))
+    {
+        return Err(format!("{} is already open in session {}", port, existing.id));
+    }
+
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(BRIDGE)
+        .env("SERIAL_PORT", port)
+        .env("SERIAL_BAUD", baud.to_string())
+        .process_group(0)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start serial bridge: {}", e))?;
+    let id = mgr.insert(ExecSession::new(label, "/".to_string(), None, child));
+    drop(mgr);
+
+    // Permission or stty errors show up straight away.
+    std::thread::sleep(Duration::from_millis(200));
+    let mut mgr = manager
+        .lock()
+        .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+    if let Some(session) = mgr.get_mut(&id) {
+        session.try_read_output();
+        if session.check_exit() {
+            let output = session.full_output().trim().to_string();
+            mgr.remove(&id);
+            return Err(format!("Failed to open {}: {}", port, output));
+        }
+    }
+    Ok(id)
+}
+
+#[cfg(not(unix))]
+fn open_port(_port: &str, _baud: u32) -> Result<String, String> {
+    Err("The serial tool currently needs a Unix host".to_string())
+}
+
+/// Collect session output until `expect` shows up, the device goes quiet
+/// for a while, or `timeout` passes.  The process manager is only locked
+/// between polls so other tools aren't held up.
+fn read_output(id: &str, timeout: Duration, expect: Option<&str>) -> Result<String, String> {
+    let manager = process_manager();
+    let start = Instant::now();
+    let mut last_data = start;
+    let mut collected = String::new();
+    loop {
+        let exited = {
+            let mut mgr = manager
+                .lock()
+                .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+            let session = mgr.get_mut(id).ok_or_else(|| format!("No session found: {}", id))?;
+            if !session.command.starts_with(SESSION_PREFIX) {
+                return Err(format!("Session {} is not a serial session", id));
+            }
+            session.try_read_output();
+            let new = session.poll_output().to_string();
+            if !new.is_empty() {
+                collected.push_str(&new);
+                last_data = Instant::now();
+            }
+            session.check_exit()
+        };
+        if exited {
+            collected.push_str("\n[serial session closed]");
+            break;
+        }
+        let done = match expect {
+            Some(pattern) => collected.contains(pattern),
+            // Without a pattern, 300ms of silence after some data is a reply.
+            None => !collected.is_empty() && last_data.elapsed() > Duration::from_millis(300),
+        };
+        if done || start.elapsed() >= timeout {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Ok(collected)
+}
+
+fn session_arg(args: &Value) -> Result<&str, String> {
+    args.get("sessionId")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: sessionId".to_string())
+}
+
+/// List, open, talk to and close serial ports.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_serial(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    debug!(action, "Executing serial tool");
+    let timeout = Duration::from_millis(
+        args.get("timeoutMs").and_then(|v| v.as_u64()).unwrap_or(2000).min(60_000),
+    );
+
+    match action {
+        "list" => {
+            let manager = process_manager();
+            let mut mgr = manager
+                .lock()
+                .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+            mgr.poll_all();
+            let sessions: Vec<Value> = mgr
+                .list_active()
+                .into_iter()
+                .filter(|s| s.command.starts_with(SESSION_PREFIX))
+                .map(|s| json!({ "sessionId": s.id, "port": &s.command[SESSION_PREFIX.len()..] }))
+                .collect();
+            Ok(json!({ "ports": list_ports(), "open": sessions }).to_string())
+        }
+        "open" => {
+            let port = args
+                .get("port")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: port")?;
+            let baud = args.get("baud").and_then(|v| v.as_u64()).unwrap_or(115_200) as u32;
+            if !BAUD_RATES.contains(&baud) {
+                return Err(format!("Unsupported baud rate {}; use one of {:?}", baud, BAUD_RATES));
+            }
+            let id = open_port(port, baud)?;
+            // Boot banners and prompts often arrive right after opening.
+            let greeting = read_output(&id, Duration::from_millis(500), None)?;
+            Ok(json!({
+                "sessionId": id,
+                "port": port,
+                "baud": baud,
+                "output": greeting,
+            })
+            .to_string())
+        }
+        "send" => {
+            let id = session_arg(args)?;
+            let data = args
+                .get("data")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing required parameter: data")?;
+            let ending = line_ending(args.get("lineEnding").and_then(|v| v.as_str()).unwrap_or("lf"))?;
+            {
+                let manager = process_manager();
+                let mut mgr = manager
+                    .lock()
+                    .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+                let session = mgr.get_mut(id).ok_or_else(|| format!("No session found: {}", id))?;
+                if !session.command.starts_with(SESSION_PREFIX) {
+                    return Err(format!("Session {} is not a serial session", id));
+                }
+                // Drop anything unread so the reply isn't mixed with old output.
+                session.try_read_output();
+                session.poll_output();
+                session.write_stdin(&format!("{}{}", data, ending))?;
+            }
+            let expect = args.get("expect").and_then(|v| v.as_str());
+            let reply = read_output(id, timeout, expect)?;
+            if reply.is_empty() {
+                return Ok(format!("Sent {} byte(s); no reply within {}ms.", data.len() + ending.len(), timeout.as_millis()));
+            }
+            Ok(reply)
+        }
+        "read" => {
+            let id = session_arg(args)?;
+            let expect = args.get("expect").and_then(|v| v.as_str());
+            let output = read_output(id, timeout, expect)?;
+            if output.is_empty() {
+                return Ok(format!("No output within {}ms.", timeout.as_millis()));
+            }
+            Ok(output)
+        }
+        "close" => {
+            let id = session_arg(args)?;
+            let manager = process_manager();
+            let mut mgr = manager
+                .lock()
+                .map_err(|_| "Failed to acquire process manager lock".to_string())?;
+            let session = mgr.get_mut(id).ok_or_else(|| format!("No session found: {}", id))?;
+            if !session.command.starts_with(SESSION_PREFIX) {
+                return Err(format!("Session {} is not a serial session", id));
+            }
+            if session.status == SessionStatus::Running {
+                session.kill()?;
+            }
+            let label = session.command.clone();
+            mgr.remove(id);
+            Ok(format!("Closed {}", &label[SESSION_PREFIX.len()..]))
+        }
+        other => Err(format!(
+            "Unknown action: {}. Use list, open, send, read or close",
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_ending() {
+        assert_eq!(line_ending("CRLF").unwrap(), "\r\n");
+        assert_eq!(line_ending("none").unwrap(), "");
+        assert!(line_ending("tab").is_err());
+    }
+
+    #[test]
+    fn test_open_validation() {
+        let ws = Path::new("/tmp");
+        let err = exec_serial(&json!({ "action": "open", "port": "/dev/ttyUSB0", "baud": 12345 }), ws);
+        assert!(err.unwrap_err().contains("Unsupported baud rate"));
+        let err = exec_serial(&json!({ "action": "open", "port": "/dev/rustyclaw-no-such-tty" }), ws);
+        assert!(err.is_err());
+        let err = exec_serial(&json!({ "action": "read", "sessionId": "nope" }), ws);
+        assert!(err.unwrap_err().contains("No session found"));
+    }
+
+    #[test]
+    fn test_list() {
+        let result = exec_serial(&json!({ "action": "list" }), Path::new("/tmp")).unwrap();
+        let parsed: Value = serde_json::from_str(&result).unwrap();
+        assert!(parsed["ports"].is_array());
+    }
+}
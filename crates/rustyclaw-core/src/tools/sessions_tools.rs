@@ -1,4 +1,4 @@
-//! Session tools: sessions_list, sessions_spawn, sessions_send, sessions_history, session_status, agents_list.
+//! Session tools: sessions_list, sessions_spawn, sessions_worktree, sessions_send, sessions_history, session_status, agents_list, orchestrate.
 
 use serde_json::Value;
 use std::path::Path;
@@ -43,9 +43,12 @@ pub fn exec_sessions_list(args: &Value, _workspace_dir: &Path) -> Result<String,
         };
         let label = session.label.as_deref().unwrap_or("");
         let runtime = session.runtime_secs();
+        let depth = mgr.depth(&session.key);
+        let indent = "  ".repeat(depth.saturating_sub(1));
 
         output.push_str(&format!(
-            "{} [{}] {} — {}s{}\n",
+            "{}{} [{}] {} — {}s{}\n",
+            indent,
             status,
             kind,
             session.key,
@@ -62,8 +65,8 @@ pub fn exec_sessions_list(args: &Value, _workspace_dir: &Path) -> Result<String,
 }
 
 /// Spawn a sub-agent.
-#[instrument(skip(args, _workspace_dir), fields(task, agent_id))]
-pub fn exec_sessions_spawn(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+#[instrument(skip(args, workspace_dir), fields(task, agent_id))]
+pub fn exec_sessions_spawn(args: &Value, workspace_dir: &Path) -> Result<String, String> {
     use crate::sessions::*;
 
     let task = args
@@ -76,18 +79,73 @@ pub fn exec_sessions_spawn(args: &Value, _workspace_dir: &Path) -> Result<String
         .get("agentId")
         .and_then(|v| v.as_str())
         .unwrap_or("main");
+    // Sub-agents spawning sub-agents can't opt out of their lineage, and
+    // top-level turns spawn under the main session so a tree budget applies.
+    let parent_key = Some(spawn_parent());
+
+    let isolation = args
+        .get("isolation")
+        .and_then(|v| v.as_str())
+        .unwrap_or("none");
+    if !matches!(isolation, "none" | "worktree") {
+        return Err(format!("Unknown isolation: {}. Use 'none' or 'worktree'", isolation));
+    }
 
     tracing::Span::current().record("task", &task[..task.len().min(50)]);
     tracing::Span::current().record("agent_id", agent_id);
-    debug!(label = label.as_deref(), "Spawning sub-agent");
+    debug!(label = label.as_deref(), isolation, "Spawning sub-agent");
 
     let manager = session_manager();
+
+    // Check the delegation policy before creating a worktree we'd have to
+    // clean up again.  Git can be slow; don't hold the session lock while
+    // it runs.
+    manager
+        .lock()
+        .map_err(|_| "Failed to acquire session manager lock".to_string())?
+        .check_spawn(parent_key.as_deref(), 1)?;
+    let worktree = if isolation == "worktree" {
+        let name = format!(
+            "{}-{}",
+            label.as_deref().unwrap_or("subagent"),
+            crate::sessions::generate_uuid()
+        );
+        Some(crate::worktree::Worktree::create(workspace_dir, &name)?)
+    } else {
+        None
+    };
+
     let mut mgr = manager
         .lock()
         .map_err(|_| "Failed to acquire session manager lock".to_string())?;
-
-    let session_key = mgr.spawn_subagent(agent_id, task, label.clone(), None);
+    // Another spawn may have taken the last slot meanwhile.
+    let session_key = match mgr.try_spawn_subagent(agent_id, task, label.clone(), parent_key) {
+        Ok(key) => key,
+        Err(err) => {
+            drop(mgr);
+            if let Some(wt) = worktree {
+                let _ = wt.discard();
+            }
+            return Err(err);
+        }
+    };
     debug!(session_key = %session_key, "Sub-agent spawned");
+    let worktree_path = worktree.as_ref().map(|w| w.path.clone());
+    // The sub-agent works in its worktree, so its edits stay there until
+    // merged.
+    let workspace = match worktree {
+        Some(ref wt) => wt.workspace_for(workspace_dir),
+        None => workspace_dir.to_path_buf(),
+    };
+    if let Some(session) = mgr.get_mut(&session_key) {
+        session.worktree = worktree;
+    }
+    mgr.queue_run(SubagentRun {
+        session_key: session_key.clone(),
+        task: task.to_string(),
+        model: None,
+        workspace,
+    });
 
     // Get the run_id
     let run_id = mgr
@@ -99,16 +157,94 @@ pub fn exec_sessions_spawn(args: &Value, _workspace_dir: &Path) -> Result<String
         status: "accepted".to_string(),
         run_id: run_id.clone(),
         session_key: session_key.clone(),
-        message: format!(
-            "Sub-agent spawned. Task: '{}'. Use sessions_history or sessions_send to interact.",
-            task
-        ),
+        message: match worktree_path {
+            Some(ref path) => format!(
+                "Sub-agent spawned in worktree {}. Task: '{}'. Use sessions_worktree to review, \
+                 merge or discard its changes.",
+                path.display(),
+                task
+            ),
+            None => format!(
+                "Sub-agent spawned. Task: '{}'. Use sessions_history or sessions_send to interact.",
+                task
+            ),
+        },
+        worktree: worktree_path,
     };
 
     serde_json::to_string_pretty(&result)
         .map_err(|e| format!("Failed to serialize result: {}", e))
 }
 
+/// Review, merge or discard a sub-agent's worktree.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_sessions_worktree(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    use crate::sessions::*;
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    let session_key = args
+        .get("sessionKey")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: sessionKey".to_string())?;
+
+    debug!(action, session_key, "Sub-agent worktree action");
+
+    // Git can be slow; don't hold the session lock while it runs.
+    let worktree = {
+        let manager = session_manager();
+        let mgr = manager
+            .lock()
+            .map_err(|_| "Failed to acquire session manager lock".to_string())?;
+        let session = mgr
+            .get(session_key)
+            .ok_or_else(|| format!("Session not found: {}", session_key))?;
+        session
+            .worktree
+            .clone()
+            .ok_or_else(|| format!("Session {} has no worktree", session_key))?
+    };
+
+    let output = match action {
+        "diff" => {
+            let diff = worktree.diff()?;
+            if diff.trim().is_empty() {
+                return Ok(format!("No changes in {}", worktree.path.display()));
+            }
+            return Ok(diff);
+        }
+        "stat" => {
+            let stat = worktree.diff_stat()?;
+            if stat.trim().is_empty() {
+                return Ok(format!("No changes in {}", worktree.path.display()));
+            }
+            return Ok(stat);
+        }
+        "merge" => worktree.merge()?,
+        "discard" => {
+            worktree.discard()?;
+            format!("Discarded worktree {}", worktree.path.display())
+        }
+        other => {
+            return Err(format!(
+                "Unknown action: {}. Use diff, stat, merge or discard",
+                other
+            ))
+        }
+    };
+
+    // The worktree is gone after a merge or discard.
+    let manager = session_manager();
+    if let Ok(mut mgr) = manager.lock() {
+        if let Some(session) = mgr.get_mut(session_key) {
+            session.worktree = None;
+        }
+    }
+    Ok(output)
+}
+
 /// Send a message to a session.
 #[instrument(skip(args, _workspace_dir))]
 pub fn exec_sessions_send(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
@@ -217,6 +353,21 @@ pub fn exec_session_status(args: &Value, _workspace_dir: &Path) -> Result<String
             output.push_str(&format!("Status: {:?}\n", session.status));
             output.push_str(&format!("Runtime: {}s\n", session.runtime_secs()));
             output.push_str(&format!("Messages: {}\n", session.messages.len()));
+            if let Some(ref wt) = session.worktree {
+                output.push_str(&format!("Worktree: {} ({})\n", wt.path.display(), wt.branch));
+            }
+            if session.kind == SessionKind::Subagent {
+                let lineage = mgr.lineage(&session.key);
+                output.push_str(&format!("Depth: {}/{}\n", mgr.depth(&session.key), mgr.policy().max_depth));
+                output.push_str(&format!("Lineage: {}\n", lineage.join(" → ")));
+                output.push_str(&format!(
+                    "Tree budget: {}/{} sub-agents, {}/{} tokens\n",
+                    mgr.tree_spawn_count(&session.key),
+                    mgr.policy().max_tree_spawns,
+                    mgr.tree_token_count(&session.key),
+                    mgr.policy().max_tree_tokens
+                ));
+            }
         } else {
             return Err(format!("Session not found: {}", key));
         }
@@ -230,6 +381,17 @@ pub fn exec_session_status(args: &Value, _workspace_dir: &Path) -> Result<String
 
         output.push_str(&format!("Active sessions: {}\n", active));
         output.push_str(&format!("Total sessions: {}\n", all_sessions.len()));
+        output.push_str(&format!(
+            "Sub-agents: {}/{} concurrent (max depth {})\n",
+            mgr.active_subagents(),
+            mgr.policy().max_concurrent,
+            mgr.policy().max_depth
+        ));
+        let (loops_warned, loops_aborted) = crate::loop_guard::counters();
+        output.push_str(&format!(
+            "Tool loops: {} warned, {} turns stopped\n",
+            loops_warned, loops_aborted
+        ));
         output.push_str(&format!("Timestamp: {} ms\n", now.as_millis()));
     }
 
@@ -265,3 +427,153 @@ pub fn exec_agents_list(_args: &Value, workspace_dir: &Path) -> Result<String, S
 
     Ok(output)
 }
+
+/// Fan a task out to several role-specialised sub-agents and aggregate their outputs.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_orchestrate(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    use crate::sessions::*;
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+
+    debug!(action, "Orchestrate");
+
+    let manager = session_manager();
+    let mut mgr = manager
+        .lock()
+        .map_err(|_| "Failed to acquire session manager lock".to_string())?;
+
+    let orchestration_id = || {
+        args.get("orchestrationId")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing required parameter: orchestrationId".to_string())
+    };
+
+    match action {
+        "start" => {
+            let task = args
+                .get("task")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: task".to_string())?;
+
+            // Roles may be given as plain strings or as {role, model, instructions}.
+            let roles: Vec<RoleSpec> = match args.get("agents").and_then(|v| v.as_array()) {
+                Some(list) => list
+                    .iter()
+                    .map(|v| match v {
+                        Value::String(role) => Ok(RoleSpec {
+                            role: role.clone(),
+                            model: None,
+                            instructions: None,
+                        }),
+                        other => serde_json::from_value(other.clone())
+                            .map_err(|e| format!("Invalid agent spec: {}", e)),
+                    })
+                    .collect::<Result<_, _>>()?,
+                None => ["researcher", "coder", "reviewer"]
+                    .iter()
+                    .map(|r| RoleSpec {
+                        role: r.to_string(),
+                        model: None,
+                        instructions: None,
+                    })
+                    .collect(),
+            };
+
+            let parent_key = Some(spawn_parent());
+
+            // Members belong to the same agent as the session starting them.
+            let agent_id = parent_key
+                .as_deref()
+                .and_then(|k| mgr.get(k))
+                .map(|s| s.agent_id.clone())
+                .unwrap_or_else(|| "main".to_string());
+
+            let id = mgr.start_orchestration(&agent_id, task, &roles, parent_key, workspace_dir)?;
+            let orch = mgr
+                .get_orchestration(&id)
+                .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+
+            let mut output = format!(
+                "Orchestration {} started with {} sub-agent(s):\n\n",
+                id,
+                orch.members.len()
+            );
+            for m in &orch.members {
+                output.push_str(&format!(
+                    "- {} → {}{}\n",
+                    m.role,
+                    m.session_key,
+                    m.model
+                        .as_deref()
+                        .map(|model| format!(" (model: {})", model))
+                        .unwrap_or_default()
+                ));
+            }
+            output.push_str("\nThey run in parallel; use action 'aggregate' to collect their outputs.");
+            Ok(output)
+        }
+        "status" => {
+            let id = orchestration_id()?;
+            let outputs = mgr.aggregate_orchestration(id)?;
+            let mut output = format!("Orchestration {}:\n\n", id);
+            for m in outputs {
+                output.push_str(&format!(
+                    "{:?} [{}] {}\n",
+                    m.status, m.role, m.session_key
+                ));
+            }
+            Ok(output)
+        }
+        "scratchpad_write" => {
+            let id = orchestration_id()?;
+            let content = args
+                .get("content")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: content".to_string())?;
+            let author = args
+                .get("author")
+                .and_then(|v| v.as_str())
+                .unwrap_or("parent");
+            mgr.scratchpad_write(id, author, content)?;
+            Ok(format!("Scratchpad note added to {}", id))
+        }
+        "scratchpad_read" => {
+            let id = orchestration_id()?;
+            let orch = mgr
+                .get_orchestration(id)
+                .ok_or_else(|| format!("Orchestration not found: {}", id))?;
+            if orch.scratchpad.is_empty() {
+                return Ok(format!("Scratchpad for {} is empty.", id));
+            }
+            let mut output = format!("Scratchpad for {}:\n\n", id);
+            for entry in &orch.scratchpad {
+                output.push_str(&format!("[{}] {}\n", entry.author, entry.content));
+            }
+            Ok(output)
+        }
+        "aggregate" => {
+            let id = orchestration_id()?;
+            let outputs = mgr.aggregate_orchestration(id)?;
+            let scratchpad = mgr
+                .get_orchestration(id)
+                .map(|o| o.scratchpad.clone())
+                .unwrap_or_default();
+
+            let result = serde_json::json!({
+                "orchestrationId": id,
+                "complete": outputs.iter().all(|m| m.status != SessionStatus::Active),
+                "members": outputs,
+                "scratchpad": scratchpad,
+            });
+            serde_json::to_string_pretty(&result)
+                .map_err(|e| format!("Failed to serialize result: {}", e))
+        }
+        _ => Err(format!(
+            "Unknown action: {}. Valid: start, status, scratchpad_write, scratchpad_read, aggregate",
+            action
+        )),
+    }
+}
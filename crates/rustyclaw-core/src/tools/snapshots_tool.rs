@@ -0,0 +1,121 @@
+//! The `snapshots` tool: list, diff and restore workspace snapshots.
+
+use crate::snapshots::{self, Change};
+use serde_json::Value;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Largest diff returned in one call.
+const MAX_DIFF_CHARS: usize = 20_000;
+
+fn id_arg(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    match args.get("id").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()) {
+        Some(id) => Ok(id.trim().to_string()),
+        // Default to the newest snapshot.
+        None => snapshots::list(workspace_dir)
+            .into_iter()
+            .next()
+            .map(|s| s.id)
+            .ok_or_else(|| "No snapshots yet".to_string()),
+    }
+}
+
+fn format_changes(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(|c| format!("{} {}", c.marker(), c.path()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// List, diff, take and restore workspace snapshots.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_snapshots(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+    debug!(action, "Executing snapshots tool");
+    let config = snapshots::config();
+
+    match action {
+        "list" => {
+            let all = snapshots::list(workspace_dir);
+            if all.is_empty() {
+                return Ok(if config.enabled {
+                    "No snapshots yet.".to_string()
+                } else {
+                    "No snapshots. Automatic snapshots are off; enable [snapshots] in the config.".to_string()
+                });
+            }
+            let tz = crate::cron::local_tz();
+            let lines: Vec<String> = all
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{}  {}  {} files, {} KB  — {}",
+                        s.id,
+                        crate::cron::format_ms(s.created_ms, tz),
+                        s.files,
+                        s.bytes.div_ceil(1024),
+                        s.reason
+                    )
+                })
+                .collect();
+            Ok(lines.join("\n"))
+        }
+        "create" => {
+            let reason = args.get("reason").and_then(|v| v.as_str()).unwrap_or("manual");
+            let snapshot = snapshots::create(workspace_dir, &config, reason)?;
+            Ok(format!("Snapshot {} ({} files)", snapshot.id, snapshot.files))
+        }
+        "diff" => {
+            let id = id_arg(args, workspace_dir)?;
+            if let Some(path) = args.get("path").and_then(|v| v.as_str()) {
+                let diff = snapshots::diff_file(workspace_dir, &id, path)?;
+                if diff.trim().is_empty() {
+                    return Ok(format!("{} is unchanged since snapshot {}", path, id));
+                }
+                if diff.chars().count() > MAX_DIFF_CHARS {
+                    let mut cut: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+                    cut.push_str("\n\n(diff truncated)");
+                    return Ok(cut);
+                }
+                return Ok(diff);
+            }
+            let changes = snapshots::diff(workspace_dir, &id, &config.exclude)?;
+            if changes.is_empty() {
+                return Ok(format!("No changes since snapshot {}", id));
+            }
+            Ok(format!(
+                "{} changes since snapshot {}:\n{}",
+                changes.len(),
+                id,
+                format_changes(&changes)
+            ))
+        }
+        "restore" => {
+            let id = args
+                .get("id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: id".to_string())?;
+            let paths: Vec<String> = args
+                .get("paths")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            let restored = snapshots::restore(workspace_dir, &config, id, &paths)?;
+            Ok(format!(
+                "Restored {} files from snapshot {}:\n{}\n\nThe previous state is in snapshot {}.",
+                restored.changes.len(),
+                id,
+                format_changes(&restored.changes),
+                restored.undo.id
+            ))
+        }
+        _ => Err(format!(
+            "Unknown action: {}. Valid: list, create, diff, restore",
+            action
+        )),
+    }
+}
@@ -0,0 +1,472 @@
+//! The `system_info` tool: CPU, memory, disk, top processes, battery and
+//! uptime as structured JSON, for this machine or a Linux node.
+//!
+//! Local figures come from the `sysinfo` crate.  Nodes are queried through
+//! `nodes run` with a small POSIX script reading `/proc`, so any SSH or ADB
+//! node works without installing anything.  Each report carries `alerts`
+//! for disks and memory above the given thresholds, so a cron job can ask
+//! "anything to worry about?" and only speak up when there is.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+use sysinfo::{Disks, ProcessesToUpdate, System};
+use tracing::{debug, instrument};
+
+use super::devices;
+
+const DEFAULT_TOP: usize = 5;
+const DEFAULT_DISK_THRESHOLD: f64 = 90.0;
+const DEFAULT_MEMORY_THRESHOLD: f64 = 90.0;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Cpu {
+    cores: usize,
+    usage_percent: f64,
+    load: Option<[f64; 3]>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Memory {
+    total_bytes: u64,
+    used_bytes: u64,
+    used_percent: f64,
+    swap_total_bytes: u64,
+    swap_used_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiskUsage {
+    mount: String,
+    filesystem: String,
+    total_bytes: u64,
+    available_bytes: u64,
+    used_percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessUsage {
+    pid: u32,
+    name: String,
+    cpu_percent: f64,
+    memory_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Battery {
+    percent: u8,
+    status: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    host: String,
+    os: String,
+    kernel: String,
+    uptime_seconds: u64,
+    uptime: String,
+    cpu: Cpu,
+    memory: Memory,
+    disks: Vec<DiskUsage>,
+    processes: Vec<ProcessUsage>,
+    battery: Option<Battery>,
+    alerts: Vec<String>,
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        (part as f64 * 1000.0 / whole as f64).round() / 10.0
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds % 86_400 / 3600, seconds % 3600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+/// Pseudo and container filesystems that only add noise.
+fn is_virtual_fs(fs: &str) -> bool {
+    matches!(
+        fs,
+        "tmpfs" | "devtmpfs" | "overlay" | "squashfs" | "proc" | "sysfs" | "devfs" | "autofs" | "efivarfs"
+    ) || fs.starts_with("fuse.")
+}
+
+fn sort_processes(processes: &mut Vec<ProcessUsage>, by_memory: bool, top: usize) {
+    if by_memory {
+        processes.sort_by(|a, b| b.memory_bytes.cmp(&a.memory_bytes));
+    } else {
+        processes.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+    }
+    processes.truncate(top);
+}
+
+fn local_battery() -> Option<Battery> {
+    if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let is_battery = std::fs::read_to_string(dir.join("type")).is_ok_and(|t| t.trim() == "Battery");
+            let capacity = std::fs::read_to_string(dir.join("capacity")).ok();
+            if let (true, Some(capacity)) = (is_battery, capacity) {
+                return Some(Battery {
+                    percent: capacity.trim().parse().ok()?,
+                    status: std::fs::read_to_string(dir.join("status"))
+                        .map(|s| s.trim().to_lowercase())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+    }
+    // macOS: "… 87%; discharging; 4:12 remaining …"
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let caps = regex::Regex::new(r"(\d+)%;\s*([\w ]+?);").ok()?.captures(&text)?;
+    Some(Battery { percent: caps[1].parse().ok()?, status: caps[2].to_lowercase() })
+}
+
+fn local(top: usize, by_memory: bool) -> Snapshot {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    sys.refresh_cpu_usage();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    // CPU usage is a delta, so it needs two samples.
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL.max(Duration::from_millis(250)));
+    sys.refresh_cpu_usage();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+
+    let load = System::load_average();
+    let uptime = System::uptime();
+    let disks = Disks::new_with_refreshed_list()
+        .iter()
+        .filter(|d| !is_virtual_fs(&d.file_system().to_string_lossy()) && d.total_space() > 0)
+        .map(|d| DiskUsage {
+            mount: d.mount_point().display().to_string(),
+            filesystem: d.file_system().to_string_lossy().to_string(),
+            total_bytes: d.total_space(),
+            available_bytes: d.available_space(),
+            used_percent: percent(d.total_space() - d.available_space(), d.total_space()),
+        })
+        .collect();
+    let mut processes: Vec<ProcessUsage> = sys
+        .processes()
+        .values()
+        .map(|p| ProcessUsage {
+            pid: p.pid().as_u32(),
+            name: p.name().to_string_lossy().to_string(),
+            cpu_percent: (p.cpu_usage() as f64 * 10.0).round() / 10.0,
+            memory_bytes: p.memory(),
+        })
+        .collect();
+    sort_processes(&mut processes, by_memory, top);
+
+    Snapshot {
+        host: System::host_name().unwrap_or_default(),
+        os: System::long_os_version().unwrap_or_default(),
+        kernel: System::kernel_version().unwrap_or_default(),
+        uptime_seconds: uptime,
+        uptime: format_uptime(uptime),
+        cpu: Cpu {
+            cores: sys.cpus().len(),
+            usage_percent: (sys.global_cpu_usage() as f64 * 10.0).round() / 10.0,
+            load: (load.one > 0.0 || load.five > 0.0).then_some([load.one, load.five, load.fifteen]),
+        },
+        memory: Memory {
+            total_bytes: sys.total_memory(),
+            used_bytes: sys.used_memory(),
+            used_percent: percent(sys.used_memory(), sys.total_memory()),
+            swap_total_bytes: sys.total_swap(),
+            swap_used_bytes: sys.used_swap(),
+        },
+        disks,
+        processes,
+        battery: local_battery(),
+        alerts: Vec::new(),
+    }
+}
+
+/// Collects everything `parse_remote` needs, one `==section` at a time.
+const REMOTE_SCRIPT: &str = "echo ==host; hostname; \
+echo ==os; (. /etc/os-release 2>/dev/null && echo \"$PRETTY_NAME\") || uname -s; \
+echo ==kernel; uname -r; \
+echo ==cpus; grep -c ^processor /proc/cpuinfo; \
+echo ==load; cat /proc/loadavg; \
+echo ==stat; head -n1 /proc/stat; sleep 1; head -n1 /proc/stat; \
+echo ==mem; cat /proc/meminfo; \
+echo ==uptime; cat /proc/uptime; \
+echo ==disk; df -P -k -T 2>/dev/null || df -P -k; \
+echo ==ps; ps -eo pid=,pcpu=,rss=,comm= 2>/dev/null; \
+echo ==battery; for b in /sys/class/power_supply/BAT*; do cat $b/capacity $b/status 2>/dev/null; break; done";
+
+fn parse_remote(output: &str, top: usize, by_memory: bool) -> Snapshot {
+    let mut sections: std::collections::HashMap<&str, Vec<&str>> = std::collections::HashMap::new();
+    let mut current = "";
+    for line in output.lines() {
+        match line.strip_prefix("==") {
+            Some(name) => current = name.trim(),
+            None => sections.entry(current).or_default().push(line),
+        }
+    }
+    let section = |name: &str| sections.get(name).cloned().unwrap_or_default();
+    let first = |name: &str| section(name).first().map(|s| s.trim().to_string()).unwrap_or_default();
+
+    let mut snapshot = Snapshot { host: first("host"), os: first("os"), kernel: first("kernel"), ..Default::default() };
+
+    snapshot.cpu.cores = first("cpus").parse().unwrap_or(0);
+    let load: Vec<f64> = first("load").split_whitespace().take(3).filter_map(|v| v.parse().ok()).collect();
+    if let [one, five, fifteen] = load[..] {
+        snapshot.cpu.load = Some([one, five, fifteen]);
+    }
+    // Two `cpu user nice system idle iowait …` samples a second apart.
+    let samples: Vec<(u64, u64)> = section("stat")
+        .iter()
+        .map(|line| {
+            let fields: Vec<u64> = line.split_whitespace().skip(1).filter_map(|v| v.parse().ok()).collect();
+            let idle = fields.get(3).copied().unwrap_or(0) + fields.get(4).copied().unwrap_or(0);
+            (fields.iter().sum(), idle)
+        })
+        .collect();
+    if let [(total_a, idle_a), (total_b, idle_b)] = samples[..] {
+        let total = total_b.saturating_sub(total_a);
+        snapshot.cpu.usage_percent = percent(total.saturating_sub(idle_b.saturating_sub(idle_a)), total);
+    }
+
+    let meminfo: std::collections::HashMap<&str, u64> = section("mem")
+        .iter()
+        .filter_map(|line| {
+            let (key, rest) = line.split_once(':')?;
+            Some((key, rest.split_whitespace().next()?.parse::<u64>().ok()? * 1024))
+        })
+        .collect();
+    let mem = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+    let total = mem("MemTotal");
+    let used = total.saturating_sub(mem("MemAvailable"));
+    snapshot.memory = Memory {
+        total_bytes: total,
+        used_bytes: used,
+        used_percent: percent(used, total),
+        swap_total_bytes: mem("SwapTotal"),
+        swap_used_bytes: mem("SwapTotal").saturating_sub(mem("SwapFree")),
+    };
+
+    snapshot.uptime_seconds = first("uptime")
+        .split_whitespace()
+        .next()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.0) as u64;
+    snapshot.uptime = format_uptime(snapshot.uptime_seconds);
+
+    // `df -P -k -T`: filesystem type blocks used available capacity mount;
+    // without -T the type column is missing.
+    let disk_lines = section("disk");
+    let typed = disk_lines.first().is_some_and(|h| h.contains("Type"));
+    for line in disk_lines.iter().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let offset = usize::from(typed);
+        if fields.len() < 6 + offset {
+            continue;
+        }
+        let filesystem = if typed { fields[1] } else { fields[0] };
+        let (Ok(total), Ok(available)) = (fields[1 + offset].parse::<u64>(), fields[3 + offset].parse::<u64>()) else {
+            continue;
+        };
+        if total == 0 || is_virtual_fs(filesystem) || (!typed && !fields[0].starts_with("/dev/")) {
+            continue;
+        }
+        snapshot.disks.push(DiskUsage {
+            mount: fields[5 + offset..].join(" "),
+            filesystem: filesystem.to_string(),
+            total_bytes: total * 1024,
+            available_bytes: available * 1024,
+            used_percent: percent(total - available, total),
+        });
+    }
+
+    snapshot.processes = section("ps")
+        .iter()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(ProcessUsage {
+                pid: fields.next()?.parse().ok()?,
+                cpu_percent: fields.next()?.parse().ok()?,
+                memory_bytes: fields.next()?.parse::<u64>().ok()? * 1024,
+                name: fields.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect();
+    sort_processes(&mut snapshot.processes, by_memory, top);
+
+    let battery = section("battery");
+    if let (Some(capacity), Some(status)) = (battery.first(), battery.get(1)) {
+        if let Ok(percent) = capacity.trim().parse() {
+            snapshot.battery = Some(Battery { percent, status: status.trim().to_lowercase() });
+        }
+    }
+    snapshot
+}
+
+fn add_alerts(snapshot: &mut Snapshot, disk_threshold: f64, memory_threshold: f64) {
+    for disk in &snapshot.disks {
+        if disk.used_percent >= disk_threshold {
+            snapshot.alerts.push(format!(
+                "Disk {} is {}% full ({} free)",
+                disk.mount,
+                disk.used_percent,
+                format_bytes(disk.available_bytes)
+            ));
+        }
+    }
+    if snapshot.memory.used_percent >= memory_threshold {
+        snapshot.alerts.push(format!(
+            "Memory is {}% used ({} of {})",
+            snapshot.memory.used_percent,
+            format_bytes(snapshot.memory.used_bytes),
+            format_bytes(snapshot.memory.total_bytes)
+        ));
+    }
+    if let Some(battery) = &snapshot.battery {
+        if battery.percent <= 10 && battery.status == "discharging" {
+            snapshot.alerts.push(format!("Battery is at {}% and discharging", battery.percent));
+        }
+    }
+}
+
+/// Report system resources for this machine or a node.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_system_info(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let top = args.get("top").and_then(|v| v.as_u64()).map(|n| n.min(50) as usize).unwrap_or(DEFAULT_TOP);
+    let by_memory = match args.get("sortBy").and_then(|v| v.as_str()).unwrap_or("cpu") {
+        "cpu" => false,
+        "memory" => true,
+        other => return Err(format!("Unknown sortBy '{}'; use cpu or memory", other)),
+    };
+    let disk_threshold = args.get("diskThreshold").and_then(|v| v.as_f64()).unwrap_or(DEFAULT_DISK_THRESHOLD);
+    let memory_threshold = args.get("memoryThreshold").and_then(|v| v.as_f64()).unwrap_or(DEFAULT_MEMORY_THRESHOLD);
+
+    let mut snapshot = match args.get("node").and_then(|v| v.as_str()) {
+        Some(node) => {
+            debug!(node, "Collecting system info from node");
+            let result: Value = serde_json::from_str(&devices::node_run(node, &[REMOTE_SCRIPT])?)
+                .map_err(|e| format!("Unexpected nodes output: {}", e))?;
+            let stdout = result.get("stdout").and_then(|v| v.as_str()).unwrap_or_default();
+            if !stdout.contains("==mem") {
+                let stderr = result.get("stderr").and_then(|v| v.as_str()).unwrap_or_default();
+                return Err(format!("Couldn't read system info from {}: {}", node, stderr));
+            }
+            parse_remote(stdout, top, by_memory)
+        }
+        None => local(top, by_memory),
+    };
+    add_alerts(&mut snapshot, disk_threshold, memory_threshold);
+
+    let mut report = serde_json::to_value(&snapshot).map_err(|e| e.to_string())?;
+    if let Some(sections) = args.get("sections").and_then(|v| v.as_array()) {
+        let keep: Vec<&str> = sections.iter().filter_map(|v| v.as_str()).collect();
+        let map = report.as_object_mut().expect("snapshot serializes to an object");
+        for (section, key) in [("cpu", "cpu"), ("memory", "memory"), ("disk", "disks"), ("processes", "processes"), ("battery", "battery")] {
+            if !keep.contains(&section) {
+                map.remove(key);
+            }
+        }
+    }
+    Ok(report.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "==host
+web-1
+==os
+Debian GNU/Linux 12 (bookworm)
+==kernel
+6.1.0-18-amd64
+==cpus
+4
+==load
+0.52 0.40 0.31 1/234 5678
+==stat
+cpu  1000 0 500 8000 500 0 0 0 0 0
+cpu  1030 0 520 8040 510 0 0 0 0 0
+==mem
+MemTotal:        8000000 kB
+MemFree:          500000 kB
+MemAvailable:     400000 kB
+SwapTotal:       1000000 kB
+SwapFree:         750000 kB
+==uptime
+273600.55 1000000.00
+==disk
+Filesystem     Type     1024-blocks     Used Available Capacity Mounted on
+/dev/sda1      ext4       100000000 95000000   5000000      95% /
+tmpfs          tmpfs        4000000        0   4000000       0% /run
+/dev/sdb1      xfs        200000000 10000000 190000000       5% /srv/data
+==ps
+  1   0.0  12000 systemd
+ 812  45.5 900000 postgres
+ 913   3.2 150000 nginx
+==battery
+";
+
+    #[test]
+    fn test_parse_remote() {
+        let snapshot = parse_remote(SAMPLE, 2, false);
+        assert_eq!(snapshot.host, "web-1");
+        assert_eq!(snapshot.cpu.cores, 4);
+        assert_eq!(snapshot.cpu.load, Some([0.52, 0.40, 0.31]));
+        // 100 jiffies elapsed, 50 of them idle or iowait.
+        assert_eq!(snapshot.cpu.usage_percent, 50.0);
+        assert_eq!(snapshot.memory.used_percent, 95.0);
+        assert_eq!(snapshot.memory.swap_used_bytes, 250_000 * 1024);
+        assert_eq!(snapshot.uptime, "3d 4h 0m");
+        assert_eq!(snapshot.disks.len(), 2);
+        assert_eq!(snapshot.disks[0].mount, "/");
+        assert_eq!(snapshot.disks[0].used_percent, 95.0);
+        assert_eq!(snapshot.processes.len(), 2);
+        assert_eq!(snapshot.processes[0].name, "postgres");
+        assert!(snapshot.battery.is_none());
+    }
+
+    #[test]
+    fn test_alerts() {
+        let mut snapshot = parse_remote(SAMPLE, 5, true);
+        assert_eq!(snapshot.processes[0].name, "postgres");
+        add_alerts(&mut snapshot, 90.0, 99.0);
+        assert_eq!(snapshot.alerts.len(), 1);
+        assert!(snapshot.alerts[0].starts_with("Disk / is 95% full"));
+        add_alerts(&mut snapshot, 100.0, 90.0);
+        assert!(snapshot.alerts[1].starts_with("Memory is 95% used"));
+    }
+
+    #[test]
+    fn test_local_sections() {
+        let args = serde_json::json!({ "sections": ["memory"], "top": 1 });
+        let report: Value = serde_json::from_str(&exec_system_info(&args, Path::new(".")).unwrap()).unwrap();
+        assert!(report["memory"]["totalBytes"].as_u64().unwrap() > 0);
+        assert!(report.get("processes").is_none());
+        assert!(report.get("alerts").is_some());
+    }
+}
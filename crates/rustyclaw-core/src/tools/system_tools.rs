@@ -6,7 +6,7 @@ use std::path::Path;
 use std::process::Command;
 use tracing::{debug, warn, instrument};
 
-use super::helpers::{resolve_path, expand_tilde};
+use super::helpers::{check_write, resolve_path, expand_tilde};
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
@@ -641,6 +641,7 @@ pub fn exec_screenshot(args: &Value, workspace_dir: &Path) -> Result<String, Str
     } else {
         resolve_path(workspace_dir, output_path)
     };
+    check_write(&target, workspace_dir)?;
 
     // Create parent directories
     if let Some(parent) = target.parent() {
@@ -0,0 +1,215 @@
+//! Tabular data shared by the data tools (`plot`, `xlsx_write`).
+//!
+//! Data comes inline — a JSON array of objects, a JSON array of rows whose
+//! first row is the header, or CSV/TSV text — or from a `.csv`, `.tsv` or
+//! `.json` file in the workspace.
+
+use super::helpers::{is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use serde_json::Value;
+use std::path::Path;
+
+/// Rows beyond this are rejected rather than silently dropped.
+pub(crate) const MAX_ROWS: usize = 100_000;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Parse CSV text with a header row.
+    pub fn from_csv(text: &str, delimiter: u8) -> Result<Self, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| format!("Invalid CSV header: {}", e))?
+            .iter()
+            .map(String::from)
+            .collect();
+        let mut rows = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("Invalid CSV at row {}: {}", i + 2, e))?;
+            if record.iter().all(|c| c.is_empty()) {
+                continue;
+            }
+            rows.push(record.iter().map(String::from).collect());
+        }
+        Self::checked(headers, rows)
+    }
+
+    /// Build from JSON: an array of objects (keys become columns, in
+    /// first-seen order) or an array of arrays with a header row.
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let items = value
+            .as_array()
+            .ok_or("JSON data must be an array of objects or rows")?;
+        if items.iter().all(|v| v.is_array()) {
+            let mut rows = items.iter().map(|row| {
+                row.as_array().into_iter().flatten().map(cell_text).collect::<Vec<_>>()
+            });
+            let headers = rows.next().ok_or("JSON data is empty")?;
+            return Self::checked(headers, rows.collect());
+        }
+        let mut headers: Vec<String> = Vec::new();
+        for item in items {
+            let object = item
+                .as_object()
+                .ok_or("JSON data must be an array of objects or rows")?;
+            for key in object.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+        let rows = items
+            .iter()
+            .map(|item| {
+                headers
+                    .iter()
+                    .map(|h| item.get(h).map(cell_text).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        Self::checked(headers, rows)
+    }
+
+    /// Parse inline text, detecting JSON, TSV or CSV.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('[') {
+            let value: Value = serde_json::from_str(trimmed).map_err(|e| format!("Invalid JSON data: {}", e))?;
+            return Self::from_json(&value);
+        }
+        let first = trimmed.lines().next().unwrap_or("");
+        let delimiter = if first.contains('\t') && !first.contains(',') { b'\t' } else { b',' };
+        Self::from_csv(trimmed, delimiter)
+    }
+
+    /// Load from the `data` argument (string or JSON array) or the `path`
+    /// argument (a file in the workspace).
+    pub fn from_args(args: &Value, workspace_dir: &Path) -> Result<Self, String> {
+        match (args.get("data"), args.get("path").and_then(|v| v.as_str())) {
+            (Some(Value::String(text)), _) => Self::parse(text),
+            (Some(value @ Value::Array(_)), _) => Self::from_json(value),
+            (Some(_), _) => Err("'data' must be CSV/JSON text or a JSON array".to_string()),
+            (None, Some(path)) => {
+                let path = resolve_path(workspace_dir, path);
+                if is_protected_path(&path) {
+                    return Err(VAULT_ACCESS_DENIED.to_string());
+                }
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+                    Some("tsv") => Self::from_csv(&text, b'\t'),
+                    Some("csv") => Self::from_csv(&text, b','),
+                    _ => Self::parse(&text),
+                }
+            }
+            (None, None) => Err("Provide 'data' or 'path'".to_string()),
+        }
+    }
+
+    fn checked(headers: Vec<String>, rows: Vec<Vec<String>>) -> Result<Self, String> {
+        if headers.is_empty() {
+            return Err("Data has no columns".to_string());
+        }
+        if rows.len() > MAX_ROWS {
+            return Err(format!("Data has {} rows; the limit is {}", rows.len(), MAX_ROWS));
+        }
+        Ok(Self { headers, rows })
+    }
+
+    /// Index of a column by exact, then case-insensitive, name.
+    pub fn column(&self, name: &str) -> Result<usize, String> {
+        self.headers
+            .iter()
+            .position(|h| h == name)
+            .or_else(|| self.headers.iter().position(|h| h.eq_ignore_ascii_case(name)))
+            .ok_or_else(|| format!("No column '{}'; columns are: {}", name, self.headers.join(", ")))
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> &str {
+        self.rows[row].get(col).map(String::as_str).unwrap_or("")
+    }
+
+    /// Whether every non-empty cell in the column is a number.
+    pub fn is_numeric(&self, col: usize) -> bool {
+        let mut cells = (0..self.rows.len()).map(|r| self.cell(r, col)).filter(|c| !c.is_empty()).peekable();
+        cells.peek().is_some() && cells.all(|c| parse_number(c).is_some())
+    }
+
+    /// The column as numbers; empty or non-numeric cells are an error.
+    pub fn numbers(&self, col: usize) -> Result<Vec<f64>, String> {
+        (0..self.rows.len())
+            .map(|r| {
+                let cell = self.cell(r, col);
+                parse_number(cell).ok_or_else(|| {
+                    format!("Column '{}' row {} is not a number: '{}'", self.headers[col], r + 1, cell)
+                })
+            })
+            .collect()
+    }
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Parse a cell as a number, allowing currency symbols, thousands
+/// separators and a trailing percent sign.
+pub(crate) fn parse_number(cell: &str) -> Option<f64> {
+    let cleaned: String = cell
+        .trim()
+        .trim_start_matches(['$', '€', '£'])
+        .trim_end_matches('%')
+        .chars()
+        .filter(|c| *c != ',' && *c != '_')
+        .collect();
+    cleaned.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_csv_and_tsv() {
+        let table = Table::parse("month, revenue\nJan, \"1,200\"\nFeb,900\n\n").unwrap();
+        assert_eq!(table.headers, vec!["month", "revenue"]);
+        assert_eq!(table.numbers(1).unwrap(), vec![1200.0, 900.0]);
+        assert!(!table.is_numeric(0));
+
+        let table = Table::parse("a\tb\n1\t2\n").unwrap();
+        assert_eq!(table.rows, vec![vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let table = Table::from_json(&json!([{ "x": 1, "y": "a" }, { "y": "b", "z": null }])).unwrap();
+        assert_eq!(table.headers, vec!["x", "y", "z"]);
+        assert_eq!(table.rows[1], vec!["", "b", ""]);
+
+        let table = Table::parse(r#"[["day", "n"], ["Mon", 3]]"#).unwrap();
+        assert_eq!(table.column("N").unwrap(), 1);
+        assert!(table.column("missing").is_err());
+        assert!(Table::from_json(&json!({ "x": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("$1,234.5"), Some(1234.5));
+        assert_eq!(parse_number("12%"), Some(12.0));
+        assert_eq!(parse_number("n/a"), None);
+        assert_eq!(parse_number(""), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Tasks tool: background task queue management.
+
+use serde_json::Value;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Task queue management.
+#[instrument(skip(args, workspace_dir), fields(action))]
+pub fn exec_tasks(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    use crate::task_queue::*;
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| "Missing required parameter: action".to_string())?;
+
+    tracing::Span::current().record("action", action);
+    debug!("Executing tasks tool");
+
+    let dir = queue_dir(workspace_dir);
+    let mut queue = TaskQueue::new(&dir)?;
+
+    let task_id = || {
+        args.get("taskId")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing required parameter: taskId".to_string())
+    };
+
+    match action {
+        "add" => {
+            let prompt = args
+                .get("prompt")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "Missing required parameter: prompt".to_string())?;
+            let priority = match args.get("priority").and_then(|v| v.as_str()) {
+                Some(p) => p.parse::<TaskPriority>()?,
+                None => TaskPriority::Normal,
+            };
+            let max_retries = args
+                .get("maxRetries")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(2) as u32;
+
+            let mut task = QueuedTask::new(prompt, priority, max_retries);
+            task.source = Some("tool".to_string());
+            let id = queue.enqueue(task)?;
+            debug!(task_id = %id, "Queued task");
+            Ok(format!("Queued task {} ({:?} priority)", id, priority))
+        }
+
+        "list" => {
+            let include_finished = args
+                .get("includeFinished")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            let tasks = queue.list(include_finished);
+            if tasks.is_empty() {
+                return Ok("Task queue is empty.".to_string());
+            }
+
+            let mut output = String::from("Tasks:\n\n");
+            for task in tasks {
+                let status = match task.status {
+                    TaskStatus::Queued => "⏳",
+                    TaskStatus::Running => "🔄",
+                    TaskStatus::Done => "✅",
+                    TaskStatus::Failed => "❌",
+                    TaskStatus::Cancelled => "⏹",
+                };
+                let preview: String = task.prompt.chars().take(60).collect();
+                output.push_str(&format!(
+                    "{} {} [{:?}] attempt {}/{} — {}\n",
+                    status,
+                    task.id,
+                    task.priority,
+                    task.attempts,
+                    task.max_retries + 1,
+                    preview
+                ));
+            }
+            Ok(output)
+        }
+
+        "get" => {
+            let id = task_id()?;
+            let task = queue
+                .get(id)
+                .ok_or_else(|| format!("Task not found: {}", id))?;
+            serde_json::to_string_pretty(task)
+                .map_err(|e| format!("Failed to serialize task: {}", e))
+        }
+
+        "cancel" => {
+            let id = task_id()?;
+            queue.cancel(id)?;
+            Ok(format!("Cancelled task: {}", id))
+        }
+
+        "prune" => {
+            let removed = queue.prune()?;
+            Ok(format!("Removed {} finished task(s).", removed))
+        }
+
+        "status" => {
+            let all = queue.list(true);
+            let count = |s: TaskStatus| all.iter().filter(|t| t.status == s).count();
+            Ok(format!(
+                "Task queue status:\n- Queued: {}\n- Running: {}\n- Done: {}\n- Failed: {}\n- Store: {:?}",
+                count(TaskStatus::Queued),
+                count(TaskStatus::Running),
+                count(TaskStatus::Done),
+                count(TaskStatus::Failed),
+                dir
+            ))
+        }
+
+        _ => Err(format!(
+            "Unknown action: {}. Valid: add, list, get, cancel, prune, status",
+            action
+        )),
+    }
+}
@@ -0,0 +1,233 @@
+//! Tool timeouts and the per-turn deadline.
+//!
+//! Every tool call gets a time limit, so a hung `web_fetch` can't stall a
+//! conversation forever, and the tool loop as a whole gets a deadline.
+//! Once the deadline passes, remaining tool calls are cancelled and the
+//! model gets one last round to answer with what it has.
+//!
+//! ```toml
+//! [timeouts]
+//! default_secs = 300     # any tool; 0 = no limit
+//! turn_secs = 900        # the whole tool loop of one turn; 0 = no limit
+//!
+//! [timeouts.tools]
+//! web_fetch = 30
+//! execute_command = 0    # rely on its own timeout_secs
+//! ```
+//!
+//! Tools that take a `timeout_secs` argument (like `execute_command`) get
+//! at least that long plus a little grace.  A timed-out tool running on the
+//! blocking pool can't be killed; its thread finishes in the background and
+//! the result is dropped.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Extra time on top of a tool's own `timeout_secs`.
+const GRACE: Duration = Duration::from_secs(5);
+
+/// Tools that wait for a person; only an explicit per-tool entry limits them.
+const UNTIMED: &[&str] = &["ask_user"];
+
+/// The `[timeouts]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeoutConfig {
+    /// Seconds any tool call may take; 0 means no limit.
+    #[serde(default = "default_secs")]
+    pub default_secs: u64,
+    /// Seconds the tool loop of one turn may take; 0 means no limit.
+    #[serde(default = "default_turn_secs")]
+    pub turn_secs: u64,
+    /// Per-tool overrides in seconds; 0 means no limit.
+    #[serde(default)]
+    pub tools: HashMap<String, u64>,
+}
+
+fn default_secs() -> u64 {
+    300
+}
+
+fn default_turn_secs() -> u64 {
+    900
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            default_secs: default_secs(),
+            turn_secs: default_turn_secs(),
+            tools: HashMap::new(),
+        }
+    }
+}
+
+fn limit(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+impl TimeoutConfig {
+    /// Time limit for one call of `name`, if any.
+    pub fn for_tool(&self, name: &str, args: &Value) -> Option<Duration> {
+        if let Some(secs) = self.tools.get(name) {
+            return limit(*secs);
+        }
+        if UNTIMED.contains(&name) {
+            return None;
+        }
+        let default = limit(self.default_secs)?;
+        match args.get("timeout_secs").and_then(|v| v.as_u64()) {
+            Some(own) => Some(default.max(Duration::from_secs(own) + GRACE)),
+            None => Some(default),
+        }
+    }
+}
+
+static CONFIG: Mutex<Option<TimeoutConfig>> = Mutex::new(None);
+
+/// Set the timeouts used by the tool layer.  Called at startup and on
+/// reload.
+pub fn set_timeouts(config: TimeoutConfig) {
+    if let Ok(mut guard) = CONFIG.lock() {
+        *guard = Some(config);
+    }
+}
+
+/// The configured timeouts, or the defaults.
+pub fn timeouts() -> TimeoutConfig {
+    CONFIG.lock().ok().and_then(|g| g.clone()).unwrap_or_default()
+}
+
+/// Run `fut`, failing with a timeout message after `limit`.
+pub(crate) async fn with_timeout<F>(name: &str, limit: Option<Duration>, fut: F) -> Result<String, String>
+where
+    F: Future<Output = Result<String, String>>,
+{
+    let Some(limit) = limit else {
+        return fut.await;
+    };
+    match tokio::time::timeout(limit, fut).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::warn!(tool = name, secs = limit.as_secs(), "Tool call timed out");
+            Err(format!("{} timed out after {}s", name, limit.as_secs()))
+        }
+    }
+}
+
+/// Deadline for the tool loop of one turn.
+#[derive(Debug, Clone)]
+pub struct TurnDeadline {
+    started: Instant,
+    limit: Option<Duration>,
+    final_round: bool,
+}
+
+impl TurnDeadline {
+    /// Start the clock with the configured `turn_secs`.
+    pub fn start() -> Self {
+        Self::new(limit(timeouts().turn_secs))
+    }
+
+    pub fn new(limit: Option<Duration>) -> Self {
+        Self { started: Instant::now(), limit, final_round: false }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.limit.is_some_and(|l| self.started.elapsed() >= l)
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.limit.map(|l| l.saturating_sub(self.started.elapsed()))
+    }
+
+    fn secs(&self) -> u64 {
+        self.limit.map(|l| l.as_secs()).unwrap_or(0)
+    }
+
+    /// Whether the loop may call the model again.  After the deadline the
+    /// model gets one more round to answer; then the loop must stop.
+    pub fn allow_round(&mut self) -> bool {
+        if !self.expired() {
+            return true;
+        }
+        !std::mem::replace(&mut self.final_round, true)
+    }
+
+    /// Run a tool call in the time left; once the deadline has passed the
+    /// call isn't started at all.
+    pub async fn run<F>(&self, name: &str, fut: F) -> Result<String, String>
+    where
+        F: Future<Output = Result<String, String>>,
+    {
+        let Some(remaining) = self.remaining() else {
+            return fut.await;
+        };
+        if remaining.is_zero() {
+            return Err(self.skipped(name));
+        }
+        match tokio::time::timeout(remaining, fut).await {
+            Ok(result) => result,
+            Err(_) => Err(format!(
+                "{} was cancelled: this turn's {}s time limit ran out. Don't call more tools; \
+                 answer with what you have.",
+                name,
+                self.secs()
+            )),
+        }
+    }
+
+    /// Result for a tool call not run because the deadline passed.
+    pub fn skipped(&self, name: &str) -> String {
+        format!(
+            "{} was not run: this turn's {}s time limit is used up. Don't call more tools; \
+             answer with what you have.",
+            name,
+            self.secs()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_limits() {
+        let config: TimeoutConfig =
+            toml::from_str("default_secs = 60\n[tools]\nweb_fetch = 10\nexecute_command = 0\n").unwrap();
+        assert_eq!(config.turn_secs, 900);
+        assert_eq!(config.for_tool("web_fetch", &json!({})), Some(Duration::from_secs(10)));
+        assert_eq!(config.for_tool("execute_command", &json!({"timeout_secs": 5})), None);
+        assert_eq!(config.for_tool("read_file", &json!({})), Some(Duration::from_secs(60)));
+        assert_eq!(config.for_tool("process", &json!({"timeout_secs": 120})), Some(Duration::from_secs(125)));
+        assert_eq!(config.for_tool("ask_user", &json!({})), None);
+        let unlimited = TimeoutConfig { default_secs: 0, ..Default::default() };
+        assert_eq!(unlimited.for_tool("read_file", &json!({})), None);
+    }
+
+    #[tokio::test]
+    async fn test_hung_tool_times_out() {
+        let hung = std::future::pending::<Result<String, String>>();
+        let err = with_timeout("web_fetch", Some(Duration::from_millis(10)), hung).await.unwrap_err();
+        assert!(err.starts_with("web_fetch timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_turn_deadline_allows_one_last_round() {
+        let mut deadline = TurnDeadline::new(Some(Duration::from_millis(20)));
+        assert!(deadline.allow_round());
+        let hung = std::future::pending::<Result<String, String>>();
+        assert!(deadline.run("web_fetch", hung).await.unwrap_err().contains("time limit ran out"));
+        assert!(deadline.expired());
+        assert!(deadline.allow_round());
+        assert!(!deadline.allow_round());
+
+        let mut unlimited = TurnDeadline::new(None);
+        assert!(unlimited.allow_round() && !unlimited.expired());
+    }
+}
@@ -0,0 +1,271 @@
+//! Tool groups and lazy tool loading.
+//!
+//! With `lazy = true` under `[tool_schemas]`, a turn starts with the
+//! [`CORE`] tools (files, search, commands) plus whatever the tool router
+//! picked, and the model loads the rest a group at a time through the
+//! `request_tools` meta-tool.  The tool loop adds the requested tools to
+//! the turn, so they can be called from the next step on.
+
+use super::schema_budget::schema_budget;
+use serde_json::Value;
+use tracing::{debug, instrument};
+
+/// The meta-tool that loads more tools into a turn.
+const META_TOOL: &str = "request_tools";
+
+/// Tools every lazy turn starts with.
+pub const CORE: &[&str] = &[
+    "read_file",
+    "write_file",
+    "edit_file",
+    "apply_patch",
+    "delete_file",
+    "list_directory",
+    "tree",
+    "search_files",
+    "find_files",
+    "execute_command",
+    "process",
+    "plan",
+    "ask_user",
+];
+
+/// Built-in tools outside [`CORE`], by group.  Custom tools from the
+/// registry form the `extensions` group.
+pub const GROUPS: &[(&str, &[&str])] = &[
+    (
+        "code",
+        &[
+            "lsp_definition",
+            "lsp_references",
+            "lsp_diagnostics",
+            "lsp_rename",
+            "lint",
+            "format",
+            "deps",
+            "review_diff",
+            "snapshots",
+            "script_run",
+        ],
+    ),
+    ("web", &["web_fetch", "web_search"]),
+    ("browser", &["browser", "browser_cache"]),
+    ("memory", &["qmd_search", "qmd_deep_search", "qmd_get", "conversations", "summarize_file"]),
+    ("cron", &["cron", "tasks"]),
+    (
+        "sessions",
+        &[
+            "sessions_list",
+            "sessions_spawn",
+            "sessions_worktree",
+            "sessions_send",
+            "sessions_history",
+            "session_status",
+            "agents_list",
+            "orchestrate",
+        ],
+    ),
+    (
+        "secrets",
+        &[
+            "secrets_list",
+            "secrets_get",
+            "secrets_store",
+            "generate_secret",
+            "totp",
+            "ssh_key",
+            "encrypt_file",
+            "decrypt_file",
+        ],
+    ),
+    ("messaging", &["message", "contacts", "mqtt", "gateway"]),
+    ("nodes", &["nodes", "canvas", "ble", "serial", "cloud_status"]),
+    (
+        "media",
+        &[
+            "render_report",
+            "plot",
+            "xlsx_write",
+            "tts",
+            "image",
+            "ocr",
+            "qr",
+            "transcribe",
+            "media_convert",
+            "translate",
+            "calc",
+            "screenshot",
+            "clipboard",
+        ],
+    ),
+    (
+        "skills",
+        &[
+            "skill_list",
+            "skill_search",
+            "skill_install",
+            "skill_info",
+            "skill_enable",
+            "skill_link_secret",
+            "skill_create",
+        ],
+    ),
+    (
+        "system",
+        &[
+            "disk_usage",
+            "classify_files",
+            "system_monitor",
+            "system_info",
+            "logs",
+            "battery_health",
+            "app_index",
+            "cloud_browse",
+            "audit_sensitive",
+            "secure_delete",
+            "net_info",
+            "net_scan",
+        ],
+    ),
+    (
+        "admin",
+        &[
+            "pkg_manage",
+            "service_manage",
+            "user_manage",
+            "firewall",
+            "ollama_manage",
+            "exo_manage",
+            "uv_manage",
+            "npm_manage",
+            "agent_setup",
+        ],
+    ),
+];
+
+/// The tools in group `name`, if there is one.
+pub fn group(name: &str) -> Option<Vec<String>> {
+    if name == "extensions" {
+        return Some(super::registry().tools().iter().map(|t| t.name().to_string()).collect());
+    }
+    GROUPS
+        .iter()
+        .find(|(group, _)| *group == name)
+        .map(|(_, tools)| tools.iter().map(|t| t.to_string()).collect())
+}
+
+/// The tools offered at the start of a turn, given the router's picks.
+/// `None` offers every tool.
+pub fn turn_tools(routed: Option<Vec<String>>) -> Option<Vec<String>> {
+    let mut tools = match (schema_budget().lazy, routed) {
+        (false, None) => return None,
+        (false, Some(routed)) => routed,
+        (true, routed) => {
+            let mut tools: Vec<String> = CORE.iter().map(|t| t.to_string()).collect();
+            for name in routed.unwrap_or_default() {
+                if !tools.contains(&name) {
+                    tools.push(name);
+                }
+            }
+            tools
+        }
+    };
+    // A turn that doesn't see every tool can always ask for more.
+    if !tools.iter().any(|t| t == META_TOOL) {
+        tools.push(META_TOOL.to_string());
+    }
+    Some(tools)
+}
+
+/// The tools a `request_tools` call asks for: its `groups` expanded,
+/// plus any `tools` named directly.
+fn requested(args: &Value) -> Result<Vec<String>, String> {
+    let list = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.trim().to_string())).collect())
+            .unwrap_or_default()
+    };
+    let mut tools = Vec::new();
+    for name in list("groups") {
+        let members = group(&name).ok_or_else(|| format!("Unknown tool group: {}. {}", name, group_list()))?;
+        tools.extend(members);
+    }
+    let known = super::all_tool_names();
+    for name in list("tools") {
+        if !known.contains(&name.as_str()) && !super::registry().contains(&name) {
+            return Err(format!("Unknown tool: {}", name));
+        }
+        tools.push(name);
+    }
+    let mut unique: Vec<String> = Vec::new();
+    for name in tools {
+        if !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+    Ok(unique)
+}
+
+fn group_list() -> String {
+    let names: Vec<&str> = GROUPS.iter().map(|(name, _)| *name).chain(["extensions"]).collect();
+    format!("Groups: {}", names.join(", "))
+}
+
+/// Add the tools requested by a `request_tools` call to a turn's tools.
+pub fn load_requested(turn: &mut Option<Vec<String>>, args: &Value) {
+    let (Some(turn), Ok(tools)) = (turn.as_mut(), requested(args)) else {
+        return;
+    };
+    for name in tools {
+        if !turn.contains(&name) {
+            turn.push(name);
+        }
+    }
+}
+
+/// Describe the tools a `request_tools` call loads; the tool loop adds
+/// them to the turn.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_request_tools(args: &Value, _workspace_dir: &std::path::Path) -> Result<String, String> {
+    let tools = requested(args)?;
+    debug!(count = tools.len(), "Loading tools");
+    if tools.is_empty() {
+        let mut out = String::from("Pass 'groups' (or 'tools') to load more tools.\n");
+        for (name, members) in GROUPS {
+            out.push_str(&format!("- {}: {}\n", name, members.join(", ")));
+        }
+        out.push_str("- extensions: custom tools");
+        return Ok(out);
+    }
+    Ok(format!("Loaded: {}. You can call them from your next step.", tools.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_every_builtin_is_reachable() {
+        for name in crate::tools::all_tool_names() {
+            let grouped = CORE.contains(&name) || GROUPS.iter().any(|(_, tools)| tools.contains(&name));
+            assert!(grouped || name == META_TOOL, "{} is in no tool group", name);
+        }
+    }
+
+    #[test]
+    fn test_load_requested_groups() {
+        let mut turn = Some(vec!["read_file".to_string()]);
+        load_requested(&mut turn, &json!({"groups": ["cron"], "tools": ["web_fetch"]}));
+        assert_eq!(turn.unwrap(), ["read_file", "cron", "tasks", "web_fetch"]);
+
+        let mut all = None;
+        load_requested(&mut all, &json!({"groups": ["cron"]}));
+        assert!(all.is_none());
+
+        let err = exec_request_tools(&json!({"groups": ["nope"]}), std::path::Path::new(".")).unwrap_err();
+        assert!(err.contains("Unknown tool group: nope"));
+        assert!(exec_request_tools(&json!({}), std::path::Path::new(".")).unwrap().contains("- browser:"));
+    }
+}
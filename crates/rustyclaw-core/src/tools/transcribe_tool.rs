@@ -0,0 +1,267 @@
+//! The `transcribe` tool: timestamped text from local audio or video.
+//!
+//! ffmpeg extracts mono audio in ten-minute chunks (keeping uploads under
+//! API size limits and memory flat), and each chunk goes to a speech-to-text
+//! backend:
+//!
+//! - `local`: whisper.cpp (`whisper-cli`), with the ggml model named by
+//!   `WHISPER_MODEL`.
+//! - `api`: an OpenAI-compatible `/audio/transcriptions` endpoint, keyed by
+//!   `STT_API_KEY` or `OPENAI_API_KEY`.  `STT_BASE_URL` and `STT_MODEL`
+//!   point it elsewhere (Groq, a local server, ...).
+
+use super::helpers::{is_protected_path, resolve_path, ScratchDir, VAULT_ACCESS_DENIED};
+use super::media::{self, format_timestamp, parse_timestamp};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+const CHUNK_SECS: f64 = 600.0;
+
+/// whisper.cpp binary names across releases and packages.
+const WHISPER_CPP_BINARIES: &[&str] = &["whisper-cli", "whisper-cpp", "whisper.cpp"];
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "whisper-1";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Segment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+enum Backend {
+    Local { binary: String, model: String },
+    Api { base_url: String, key: String, model: String },
+}
+
+impl Backend {
+    fn name(&self) -> &'static str {
+        match self {
+            Backend::Local { .. } => "whisper.cpp",
+            Backend::Api { .. } => "api",
+        }
+    }
+
+    fn local() -> Option<Self> {
+        let model = std::env::var("WHISPER_MODEL").ok().filter(|m| Path::new(m).is_file())?;
+        let binary = WHISPER_CPP_BINARIES.iter().find(|b| which::which(b).is_ok())?;
+        Some(Backend::Local { binary: binary.to_string(), model })
+    }
+
+    fn api() -> Option<Self> {
+        let key = std::env::var("STT_API_KEY")
+            .or_else(|_| std::env::var("OPENAI_API_KEY"))
+            .ok()
+            .filter(|k| !k.is_empty())?;
+        Some(Backend::Api {
+            base_url: std::env::var("STT_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+            key,
+            model: std::env::var("STT_MODEL").unwrap_or_else(|_| DEFAULT_MODEL.to_string()),
+        })
+    }
+
+    fn select(choice: &str) -> Result<Self, String> {
+        let missing_local = "Local transcription needs whisper.cpp (whisper-cli) and WHISPER_MODEL set to a ggml model file";
+        let missing_api = "API transcription needs STT_API_KEY or OPENAI_API_KEY";
+        match choice {
+            "auto" => Self::local()
+                .or_else(Self::api)
+                .ok_or_else(|| format!("No speech-to-text backend. {}; or {}.", missing_local, missing_api)),
+            "local" => Self::local().ok_or_else(|| missing_local.to_string()),
+            "api" => Self::api().ok_or_else(|| missing_api.to_string()),
+            other => Err(format!("Unknown backend '{}'; use auto, local or api", other)),
+        }
+    }
+
+    /// ffmpeg encoder arguments for this backend's input.
+    fn codec(&self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            // whisper.cpp wants 16 kHz 16-bit PCM.
+            Backend::Local { .. } => ("wav", &["-ar", "16000", "-c:a", "pcm_s16le"]),
+            // ~4 KB/s, so a ten-minute chunk is well under upload limits.
+            Backend::Api { .. } => ("mp3", &["-ar", "16000", "-c:a", "libmp3lame", "-b:a", "32k"]),
+        }
+    }
+
+    fn transcribe(&self, audio: &Path, language: Option<&str>) -> Result<Vec<Segment>, String> {
+        match self {
+            Backend::Local { binary, model } => {
+                let mut cmd = Command::new(binary);
+                cmd.args(["-m", model.as_str(), "-l", language.unwrap_or("auto"), "-f"]).arg(audio);
+                let output = cmd.output().map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+                if !output.status.success() {
+                    return Err(format!("{} failed: {}", binary, String::from_utf8_lossy(&output.stderr).trim()));
+                }
+                Ok(String::from_utf8_lossy(&output.stdout).lines().filter_map(parse_whisper_cpp_line).collect())
+            }
+            Backend::Api { base_url, key, model } => {
+                let mut form = reqwest::blocking::multipart::Form::new()
+                    .text("model", model.clone())
+                    .text("response_format", "verbose_json")
+                    .file("file", audio)
+                    .map_err(|e| format!("Failed to read {}: {}", audio.display(), e))?;
+                if let Some(language) = language {
+                    form = form.text("language", language.to_string());
+                }
+                let response = reqwest::blocking::Client::builder()
+                    .timeout(Duration::from_secs(300))
+                    .build()
+                    .map_err(|e| format!("Failed to build HTTP client: {}", e))?
+                    .post(format!("{}/audio/transcriptions", base_url.trim_end_matches('/')))
+                    .bearer_auth(key)
+                    .multipart(form)
+                    .send()
+                    .map_err(|e| format!("Transcription request failed: {}", e))?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    return Err(format!("Transcription API error ({}): {}", status, response.text().unwrap_or_default()));
+                }
+                let body: Value = response
+                    .json()
+                    .map_err(|e| format!("Failed to parse transcription response: {}", e))?;
+                Ok(parse_verbose_json(&body))
+            }
+        }
+    }
+}
+
+/// Parse a whisper.cpp output line: `[00:00:01.000 --> 00:00:04.500]   Hello`.
+fn parse_whisper_cpp_line(line: &str) -> Option<Segment> {
+    let rest = line.trim().strip_prefix('[')?;
+    let (times, text) = rest.split_once(']')?;
+    let (start, end) = times.split_once("-->")?;
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(Segment { start: parse_timestamp(start)?, end: parse_timestamp(end)?, text: text.to_string() })
+}
+
+/// Segments from an OpenAI `verbose_json` response; a plain `text` answer
+/// (some compatible servers ignore the format) becomes one segment.
+fn parse_verbose_json(body: &Value) -> Vec<Segment> {
+    let segments: Vec<Segment> = body["segments"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|s| {
+            let text = s["text"].as_str()?.trim();
+            (!text.is_empty()).then(|| Segment {
+                start: s["start"].as_f64().unwrap_or(0.0),
+                end: s["end"].as_f64().unwrap_or(0.0),
+                text: text.to_string(),
+            })
+        })
+        .collect();
+    if !segments.is_empty() {
+        return segments;
+    }
+    match body["text"].as_str().map(str::trim) {
+        Some(text) if !text.is_empty() => vec![Segment {
+            start: 0.0,
+            end: body["duration"].as_f64().unwrap_or(0.0),
+            text: text.to_string(),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Transcribe a local audio or video file.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_transcribe(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing required parameter: path")?;
+    let path = resolve_path(workspace_dir, path);
+    if is_protected_path(&path) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    let language = args.get("language").and_then(|v| v.as_str()).filter(|l| !l.is_empty());
+    let backend = Backend::select(args.get("backend").and_then(|v| v.as_str()).unwrap_or("auto"))?;
+    media::require("ffmpeg")?;
+
+    // Chunk boundaries; without a duration the whole file is one chunk.
+    let duration = media::probe_duration(&path);
+    let spans: Vec<Option<(f64, f64)>> = match duration {
+        Some(d) if d > CHUNK_SECS => (0..(d / CHUNK_SECS).ceil() as usize)
+            .map(|i| Some((i as f64 * CHUNK_SECS, CHUNK_SECS)))
+            .collect(),
+        _ => vec![None],
+    };
+    debug!(path = %path.display(), backend = backend.name(), ?duration, chunks = spans.len(), "Transcribing");
+
+    let scratch = ScratchDir::new("transcribe")?;
+    let (ext, codec) = backend.codec();
+    let mut segments = Vec::new();
+    for (i, span) in spans.iter().enumerate() {
+        let chunk = scratch.path().join(format!("chunk{:03}.{}", i, ext));
+        media::extract_audio(&path, &chunk, *span, codec)?;
+        let offset = span.map(|(start, _)| start).unwrap_or(0.0);
+        for mut segment in backend.transcribe(&chunk, language)? {
+            segment.start += offset;
+            segment.end += offset;
+            segments.push(segment);
+        }
+        let _ = std::fs::remove_file(&chunk);
+    }
+
+    if segments.is_empty() {
+        return Ok(format!("No speech recognised in {}.", path.display()));
+    }
+    match args.get("format").and_then(|v| v.as_str()).unwrap_or("text") {
+        "json" => Ok(json!({
+            "backend": backend.name(),
+            "duration": duration,
+            "segments": segments,
+        })
+        .to_string()),
+        _ => Ok(segments
+            .iter()
+            .map(|s| format!("[{}] {}", format_timestamp(s.start), s.text))
+            .collect::<Vec<_>>()
+            .join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_whisper_cpp_line() {
+        let segment = parse_whisper_cpp_line("[00:01:02.500 --> 00:01:05.000]   Let's begin.").unwrap();
+        assert_eq!(segment, Segment { start: 62.5, end: 65.0, text: "Let's begin.".into() });
+        assert!(parse_whisper_cpp_line("[00:00:00.000 --> 00:00:01.000]  ").is_none());
+        assert!(parse_whisper_cpp_line("whisper_init_from_file: loading model").is_none());
+    }
+
+    #[test]
+    fn test_parse_verbose_json() {
+        let body = json!({ "segments": [
+            { "start": 0.0, "end": 2.0, "text": " Hi all." },
+            { "start": 2.0, "end": 3.0, "text": " " },
+        ]});
+        assert_eq!(parse_verbose_json(&body), vec![Segment { start: 0.0, end: 2.0, text: "Hi all.".into() }]);
+        let plain = parse_verbose_json(&json!({ "text": "Hello", "duration": 1.5 }));
+        assert_eq!(plain[0].end, 1.5);
+        assert!(parse_verbose_json(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_transcribe_validation() {
+        let ws = Path::new(env!("CARGO_MANIFEST_DIR"));
+        assert!(exec_transcribe(&json!({}), ws).unwrap_err().contains("path"));
+        assert!(exec_transcribe(&json!({ "path": "missing.mp3" }), ws).unwrap_err().contains("not found"));
+        let err = exec_transcribe(&json!({ "path": "Cargo.toml", "backend": "cloud" }), ws).unwrap_err();
+        assert!(err.contains("Unknown backend"));
+    }
+}
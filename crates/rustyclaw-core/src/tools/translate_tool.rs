@@ -0,0 +1,35 @@
+//! The `translate` tool: translate text and detect its language (see
+//! [`crate::translate`] for providers and configuration).
+
+use crate::translate;
+use serde_json::{json, Value};
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Translate text or detect its language.
+#[instrument(skip(args, _workspace_dir))]
+pub fn exec_translate(args: &Value, _workspace_dir: &Path) -> Result<String, String> {
+    let text = args
+        .get("text")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing required parameter: text")?;
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("translate");
+    debug!(action, chars = text.len(), "Executing translate tool");
+
+    match action {
+        "detect" => match translate::detect(text) {
+            Some(detection) => Ok(json!(detection).to_string()),
+            None => Ok("Couldn't tell what language this is.".to_string()),
+        },
+        "translate" => {
+            let result = translate::translate(
+                text,
+                args.get("source").and_then(|v| v.as_str()),
+                args.get("target").and_then(|v| v.as_str()),
+                args.get("provider").and_then(|v| v.as_str()),
+            )?;
+            Ok(json!(result).to_string())
+        }
+        other => Err(format!("Unknown action: {}. Use translate or detect", other)),
+    }
+}
@@ -0,0 +1,302 @@
+//! The `tree` tool: a depth-limited view of a directory tree.
+//!
+//! Directories are expanded breadth-first until the entry budget runs
+//! out, so one huge directory can't crowd out the rest of the tree.  A
+//! directory that would overflow the budget, or sits at the depth limit,
+//! is shown as a one-line summary of what it holds instead.
+
+use super::helpers::{display_path, is_noise_dir, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use crate::wildcard::glob_match;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+const DEFAULT_DEPTH: usize = 3;
+const MAX_DEPTH: usize = 10;
+const DEFAULT_BUDGET: usize = 200;
+const MAX_BUDGET: usize = 2000;
+
+/// What a directory holds, for directories that aren't expanded.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Summary {
+    files: usize,
+    dirs: usize,
+    bytes: u64,
+}
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    children: Vec<usize>,
+    /// Entries not shown: everything for a collapsed directory, the
+    /// overflow for a truncated root.
+    hidden: Option<Summary>,
+}
+
+struct Entry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Visible entries of `dir`, directories first, then by name.
+fn read_entries(dir: &Path, hidden: bool, ignore: &[String]) -> Vec<Entry> {
+    let Ok(read) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<Entry> = read
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let path = e.path();
+            // Symlinks are listed but never followed.
+            let meta = std::fs::symlink_metadata(&path).ok()?;
+            let is_dir = meta.is_dir();
+            let skip = (!hidden && name.starts_with('.'))
+                || (is_dir && is_noise_dir(&name))
+                || ignore.iter().any(|p| glob_match(p, &name))
+                || is_protected_path(&path);
+            (!skip).then(|| Entry { name, path, is_dir, size: if is_dir { 0 } else { meta.len() } })
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+fn summarize(entries: &[Entry]) -> Summary {
+    entries.iter().fold(Summary::default(), |mut s, e| {
+        if e.is_dir {
+            s.dirs += 1;
+        } else {
+            s.files += 1;
+            s.bytes += e.size;
+        }
+        s
+    })
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{:.1} GB", b as f64 / (1u64 << 30) as f64),
+        b if b >= 1 << 20 => format!("{:.1} MB", b as f64 / (1u64 << 20) as f64),
+        b if b >= 1 << 10 => format!("{:.1} KB", b as f64 / (1u64 << 10) as f64),
+        b => format!("{} B", b),
+    }
+}
+
+fn describe(summary: &Summary) -> String {
+    let mut parts = Vec::new();
+    if summary.dirs > 0 {
+        parts.push(format!("{} dir{}", summary.dirs, if summary.dirs == 1 { "" } else { "s" }));
+    }
+    if summary.files > 0 {
+        parts.push(format!(
+            "{} file{}, {}",
+            summary.files,
+            if summary.files == 1 { "" } else { "s" },
+            format_bytes(summary.bytes)
+        ));
+    }
+    if parts.is_empty() {
+        "empty".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Build the tree under `root`.  Returns the arena (root at index 0), the
+/// number of entries shown and the number of collapsed directories.
+fn build(root: &Path, depth: usize, budget: usize, hidden: bool, ignore: &[String]) -> (Vec<Node>, usize, usize) {
+    let mut nodes = vec![Node {
+        name: root.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| root.display().to_string()),
+        is_dir: true,
+        size: 0,
+        children: Vec::new(),
+        hidden: None,
+    }];
+    let mut queue = VecDeque::from([(0usize, root.to_path_buf(), 0usize)]);
+    let mut shown = 0;
+    let mut collapsed = 0;
+
+    while let Some((index, path, level)) = queue.pop_front() {
+        let mut entries = read_entries(&path, hidden, ignore);
+        let remaining = budget - shown;
+        if index != 0 && (level >= depth || entries.len() > remaining) {
+            if !entries.is_empty() {
+                nodes[index].hidden = Some(summarize(&entries));
+                collapsed += 1;
+            }
+            continue;
+        }
+        // The root is always listed, as far as the budget goes.
+        if entries.len() > remaining {
+            nodes[index].hidden = Some(summarize(&entries[remaining..]));
+            entries.truncate(remaining);
+        }
+        shown += entries.len();
+        for entry in entries {
+            let child = nodes.len();
+            nodes.push(Node {
+                name: entry.name,
+                is_dir: entry.is_dir,
+                size: entry.size,
+                children: Vec::new(),
+                hidden: None,
+            });
+            nodes[index].children.push(child);
+            if entry.is_dir {
+                queue.push_back((child, entry.path, level + 1));
+            }
+        }
+    }
+    (nodes, shown, collapsed)
+}
+
+fn render_text(nodes: &[Node], index: usize, indent: usize, out: &mut String) {
+    let node = &nodes[index];
+    out.push_str(&"  ".repeat(indent));
+    out.push_str(&node.name);
+    if node.is_dir {
+        out.push('/');
+    }
+    if let Some(summary) = &node.hidden {
+        if node.children.is_empty() {
+            out.push_str(&format!("  [{}]", describe(summary)));
+        }
+    }
+    out.push('\n');
+    for &child in &node.children {
+        render_text(nodes, child, indent + 1, out);
+    }
+    if let Some(summary) = node.hidden.filter(|_| !node.children.is_empty()) {
+        out.push_str(&"  ".repeat(indent + 1));
+        out.push_str(&format!("… [{} more: {}]\n", summary.files + summary.dirs, describe(&summary)));
+    }
+}
+
+fn render_json(nodes: &[Node], index: usize) -> Value {
+    let node = &nodes[index];
+    if !node.is_dir {
+        return json!({ "name": node.name, "type": "file", "size": node.size });
+    }
+    let mut value = json!({
+        "name": node.name,
+        "type": "dir",
+        "children": node.children.iter().map(|&c| render_json(nodes, c)).collect::<Vec<_>>(),
+    });
+    if let Some(summary) = &node.hidden {
+        value["not_shown"] = json!({ "files": summary.files, "dirs": summary.dirs, "bytes": summary.bytes });
+    }
+    value
+}
+
+/// Show the directory tree under `path`.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_tree(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let path_str = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+    let root = resolve_path(workspace_dir, path_str);
+    if is_protected_path(&root) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+
+    let depth = args
+        .get("depth")
+        .and_then(|v| v.as_u64())
+        .map(|d| (d as usize).clamp(1, MAX_DEPTH))
+        .unwrap_or(DEFAULT_DEPTH);
+    let budget = args
+        .get("max_entries")
+        .and_then(|v| v.as_u64())
+        .map(|b| (b as usize).clamp(1, MAX_BUDGET))
+        .unwrap_or(DEFAULT_BUDGET);
+    let hidden = args.get("hidden").and_then(|v| v.as_bool()).unwrap_or(false);
+    let ignore: Vec<String> = args
+        .get("ignore")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    let format = args.get("format").and_then(|v| v.as_str()).unwrap_or("text");
+
+    debug!(root = %root.display(), depth, budget, "Building tree");
+    let (nodes, shown, collapsed) = build(&root, depth, budget, hidden, &ignore);
+
+    match format {
+        "json" => serde_json::to_string(&json!({
+            "root": display_path(&root, workspace_dir),
+            "tree": render_json(&nodes, 0),
+            "entries": shown,
+            "collapsed_dirs": collapsed,
+        }))
+        .map_err(|e| e.to_string()),
+        "text" => {
+            let mut out = String::new();
+            render_text(&nodes, 0, 0, &mut out);
+            out.push_str(&format!("\n{} entries", shown));
+            if collapsed > 0 {
+                out.push_str(&format!(
+                    "; {} director{} collapsed (raise depth or max_entries, or call tree on one)",
+                    collapsed,
+                    if collapsed == 1 { "y" } else { "ies" }
+                ));
+            }
+            Ok(out)
+        }
+        other => Err(format!("Unknown format: {}. Valid: text, json", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "x").unwrap();
+    }
+
+    #[test]
+    fn test_tree_depth_and_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        write(ws, "src/lib.rs");
+        write(ws, "src/deep/er/mod.rs");
+        write(ws, "notes.log");
+        write(ws, ".hidden");
+        write(ws, "target/debug/out");
+
+        let out = exec_tree(&json!({ "depth": 2, "ignore": ["*.log"] }), ws).unwrap();
+        assert!(out.contains("  src/\n"));
+        assert!(out.contains("    lib.rs\n"));
+        assert!(out.contains("    deep/  [1 dir]"));
+        assert!(!out.contains("notes.log"));
+        assert!(!out.contains(".hidden"));
+        assert!(!out.contains("target"));
+    }
+
+    #[test]
+    fn test_tree_budget_collapses_large_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        for i in 0..50 {
+            write(ws, &format!("big/f{:02}.txt", i));
+        }
+        write(ws, "small/a.txt");
+
+        let out = exec_tree(&json!({ "max_entries": 10 }), ws).unwrap();
+        assert!(out.contains("big/  [50 files, 50 B]"));
+        assert!(out.contains("    a.txt"));
+
+        let json: Value = serde_json::from_str(&exec_tree(&json!({ "max_entries": 1, "format": "json" }), ws).unwrap()).unwrap();
+        assert_eq!(json["entries"], 1);
+        assert_eq!(json["tree"]["children"][0]["name"], "big");
+        assert_eq!(json["tree"]["not_shown"]["dirs"], 1);
+    }
+}
@@ -112,8 +112,9 @@ pub fn exec_web_fetch(args: &Value, _workspace_dir: &Path) -> Result<String, Str
         .unwrap_or("")
         .to_lowercase();
 
-    let body = response
-        .text()
+    let size = response.content_length();
+    let body = crate::progress::read_to_end(response, &format!("Fetching {}", domain), size)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
         .map_err(|e| format!("Failed to read response body: {}", e))?;
 
     // If it's not HTML, return as-is (might be JSON, plain text, etc.)
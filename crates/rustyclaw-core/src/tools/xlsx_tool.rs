@@ -0,0 +1,359 @@
+//! The `xlsx_write` tool: build or update Excel workbooks.
+//!
+//! A call is a list of operations, each writing into one sheet (created
+//! on first use):
+//!
+//! ```json
+//! { "sheet": "Summary", "cell": "A1", "values": [["Month", "Total"], ["Jan", "=SUM(Jan!B:B)"]] }
+//! { "sheet": "Jan", "data": "item,amount\nrent,1200\n" }
+//! { "sheet": "Feb", "path": "exports/feb.csv", "cell": "A3" }
+//! ```
+//!
+//! `values` is a 2-D range where strings starting with `=` are formulas;
+//! `data`/`path` take a table like the `plot` tool and write it with a bold
+//! header row.  With `mode: "update"` an existing workbook's values and
+//! formulas are loaded first, so new sheets and ranges are added around
+//! them (cell formatting is not carried over).
+//!
+//! Reading and writing workbooks needs the `spreadsheets` feature.
+
+use super::helpers::{check_write, is_protected_path, resolve_path, VAULT_ACCESS_DENIED};
+use super::table::Table;
+#[cfg(feature = "spreadsheets")]
+use calamine::{open_workbook_auto, Data, Reader};
+#[cfg(feature = "spreadsheets")]
+use rust_xlsxwriter::{Format, Workbook};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, instrument};
+
+/// Excel's limits.
+const MAX_ROW: u32 = 1_048_575;
+const MAX_COL: u16 = 16_383;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Formula(String),
+}
+
+#[derive(Debug, Default)]
+struct Sheet {
+    cells: BTreeMap<(u32, u16), Cell>,
+    /// Header cells written from tables.
+    bold: Vec<(u32, u16)>,
+}
+
+/// Sheets in workbook order.
+#[derive(Debug, Default)]
+struct Book {
+    sheets: Vec<(String, Sheet)>,
+}
+
+impl Book {
+    fn sheet(&mut self, name: &str) -> Result<&mut Sheet, String> {
+        if name.is_empty() || name.chars().count() > 31 || name.contains(['[', ']', ':', '*', '?', '/', '\\']) {
+            return Err(format!(
+                "Invalid sheet name '{}': 1-31 characters, none of [ ] : * ? / \\",
+                name
+            ));
+        }
+        let index = match self.sheets.iter().position(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(i) => i,
+            None => {
+                self.sheets.push((name.to_string(), Sheet::default()));
+                self.sheets.len() - 1
+            }
+        };
+        Ok(&mut self.sheets[index].1)
+    }
+
+    /// Load values and formulas from an existing workbook.
+    #[cfg(feature = "spreadsheets")]
+    fn load(path: &Path) -> Result<Self, String> {
+        let mut workbook = open_workbook_auto(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut book = Book::default();
+        for name in workbook.sheet_names() {
+            let mut sheet = Sheet::default();
+            if let Ok(range) = workbook.worksheet_range(&name) {
+                let (r0, c0) = range.start().unwrap_or((0, 0));
+                for (r, c, data) in range.cells() {
+                    let cell = match data {
+                        Data::Empty | Data::Error(_) => continue,
+                        Data::Int(i) => Cell::Number(*i as f64),
+                        Data::Float(f) => Cell::Number(*f),
+                        Data::Bool(b) => Cell::Bool(*b),
+                        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => Cell::Text(s.clone()),
+                        Data::DateTime(dt) => Cell::Number(dt.as_f64()),
+                    };
+                    sheet.cells.insert((r0 + r as u32, (c0 + c as u32) as u16), cell);
+                }
+            }
+            if let Ok(formulas) = workbook.worksheet_formula(&name) {
+                let (r0, c0) = formulas.start().unwrap_or((0, 0));
+                for (r, c, formula) in formulas.cells() {
+                    if !formula.is_empty() {
+                        let formula = format!("={}", formula.trim_start_matches('='));
+                        sheet.cells.insert((r0 + r as u32, (c0 + c as u32) as u16), Cell::Formula(formula));
+                    }
+                }
+            }
+            book.sheets.push((name, sheet));
+        }
+        Ok(book)
+    }
+
+    #[cfg(not(feature = "spreadsheets"))]
+    fn load(_path: &Path) -> Result<Self, String> {
+        Err(NOT_COMPILED.to_string())
+    }
+
+    #[cfg(feature = "spreadsheets")]
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let err = |e: rust_xlsxwriter::XlsxError| format!("Failed to write workbook: {}", e);
+        let mut workbook = Workbook::new();
+        let bold = Format::new().set_bold();
+        for (name, sheet) in &self.sheets {
+            let worksheet = workbook.add_worksheet();
+            worksheet.set_name(name).map_err(err)?;
+            for (&(row, col), cell) in &sheet.cells {
+                let is_bold = sheet.bold.contains(&(row, col));
+                match (cell, is_bold) {
+                    (Cell::Number(n), _) => worksheet.write_number(row, col, *n).map(|_| ()),
+                    (Cell::Bool(b), _) => worksheet.write_boolean(row, col, *b).map(|_| ()),
+                    (Cell::Formula(f), _) => worksheet.write_formula(row, col, f.as_str()).map(|_| ()),
+                    (Cell::Text(t), true) => worksheet.write_string_with_format(row, col, t, &bold).map(|_| ()),
+                    (Cell::Text(t), false) => worksheet.write_string(row, col, t).map(|_| ()),
+                }
+                .map_err(err)?;
+            }
+            worksheet.autofit();
+        }
+        workbook.save(path).map_err(err)
+    }
+
+    #[cfg(not(feature = "spreadsheets"))]
+    fn save(&self, _path: &Path) -> Result<(), String> {
+        Err(NOT_COMPILED.to_string())
+    }
+}
+
+#[cfg(not(feature = "spreadsheets"))]
+const NOT_COMPILED: &str = "Spreadsheet support isn't compiled in. Rebuild with `--features spreadsheets`.";
+
+/// Parse an A1-style reference ("B3", "$AA$10") to zero-based (row, col).
+fn parse_cell(reference: &str) -> Result<(u32, u16), String> {
+    let invalid = || format!("Invalid cell reference '{}'; use A1 style", reference);
+    let cleaned: String = reference.trim().chars().filter(|c| *c != '$').collect();
+    let split = cleaned.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (letters, digits) = cleaned.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(invalid());
+    }
+    let col = letters
+        .to_ascii_uppercase()
+        .bytes()
+        .fold(0u32, |acc, b| acc.saturating_mul(26).saturating_add((b - b'A' + 1) as u32));
+    let row: u32 = digits.parse().map_err(|_| invalid())?;
+    if row == 0 || row - 1 > MAX_ROW || col - 1 > MAX_COL as u32 {
+        return Err(format!("Cell '{}' is outside the sheet", reference));
+    }
+    Ok((row - 1, (col - 1) as u16))
+}
+
+/// Convert a JSON value from a `values` range.  `None` leaves the cell as is.
+fn json_cell(value: &Value) -> Option<Cell> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(Cell::Bool(*b)),
+        Value::Number(n) => n.as_f64().map(Cell::Number),
+        Value::String(s) if s.starts_with('=') && s.len() > 1 => Some(Cell::Formula(s.clone())),
+        Value::String(s) => Some(Cell::Text(s.clone())),
+        other => Some(Cell::Text(other.to_string())),
+    }
+}
+
+/// Convert a table cell; plain numbers are written as numbers.
+fn text_cell(text: &str) -> Option<Cell> {
+    if text.is_empty() {
+        return None;
+    }
+    if text.starts_with('=') && text.len() > 1 {
+        return Some(Cell::Formula(text.to_string()));
+    }
+    match text.parse::<f64>() {
+        Ok(n) if n.is_finite() => Some(Cell::Number(n)),
+        _ => Some(Cell::Text(text.to_string())),
+    }
+}
+
+fn place(sheet: &mut Sheet, origin: (u32, u16), r: usize, c: usize, cell: Option<Cell>) -> Result<(), String> {
+    let row = origin.0 as u64 + r as u64;
+    let col = origin.1 as u64 + c as u64;
+    if row > MAX_ROW as u64 || col > MAX_COL as u64 {
+        return Err("Range runs past the edge of the sheet".to_string());
+    }
+    let key = (row as u32, col as u16);
+    match cell {
+        Some(cell) => sheet.cells.insert(key, cell),
+        None => sheet.cells.remove(&key),
+    };
+    sheet.bold.retain(|k| *k != key);
+    Ok(())
+}
+
+/// Apply one operation; returns the number of cells written.
+fn apply(book: &mut Book, op: &Value, workspace_dir: &Path) -> Result<usize, String> {
+    let name = op.get("sheet").and_then(|v| v.as_str()).unwrap_or("Sheet1");
+    let origin = parse_cell(op.get("cell").and_then(|v| v.as_str()).unwrap_or("A1"))?;
+    let sheet = book.sheet(name)?;
+
+    if let Some(values) = op.get("values") {
+        let values = values.as_array().ok_or("'values' must be an array of rows")?;
+        // A flat array is a single row.
+        let rows: Vec<&[Value]> = if values.iter().any(|v| v.is_array()) {
+            values
+                .iter()
+                .map(|row| row.as_array().map(Vec::as_slice).ok_or("'values' mixes rows and cells"))
+                .collect::<Result<_, _>>()?
+        } else {
+            vec![values.as_slice()]
+        };
+        let mut written = 0;
+        for (r, cells) in rows.iter().enumerate() {
+            for (c, value) in cells.iter().enumerate() {
+                place(sheet, origin, r, c, json_cell(value))?;
+                written += 1;
+            }
+        }
+        return Ok(written);
+    }
+
+    if op.get("data").is_some() || op.get("path").is_some() {
+        let table = Table::from_args(op, workspace_dir)?;
+        for (c, header) in table.headers.iter().enumerate() {
+            place(sheet, origin, 0, c, Some(Cell::Text(header.clone())))?;
+            sheet.bold.push((origin.0, origin.1 + c as u16));
+        }
+        for (r, row) in table.rows.iter().enumerate() {
+            for (c, text) in row.iter().enumerate().take(table.headers.len()) {
+                place(sheet, origin, r + 1, c, text_cell(text))?;
+            }
+        }
+        return Ok(table.headers.len() * (table.rows.len() + 1));
+    }
+
+    // A bare sheet name just makes sure the sheet exists.
+    Ok(0)
+}
+
+/// Create or update an .xlsx workbook from a list of operations.
+#[instrument(skip(args, workspace_dir))]
+pub fn exec_xlsx_write(args: &Value, workspace_dir: &Path) -> Result<String, String> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing required parameter: path")?;
+    let path = resolve_path(workspace_dir, path);
+    if path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() != Some("xlsx") {
+        return Err("path must end in .xlsx".to_string());
+    }
+    if is_protected_path(&path) {
+        return Err(VAULT_ACCESS_DENIED.to_string());
+    }
+    check_write(&path, workspace_dir)?;
+    let operations = args
+        .get("operations")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing required parameter: operations (an array)")?;
+    let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("create");
+
+    let mut book = match mode {
+        "create" => Book::default(),
+        "update" if path.exists() => Book::load(&path)?,
+        "update" => Book::default(),
+        other => return Err(format!("Unknown mode '{}'; use create or update", other)),
+    };
+    debug!(path = %path.display(), mode, operations = operations.len(), "Writing workbook");
+
+    let mut written = 0;
+    for (i, op) in operations.iter().enumerate() {
+        written += apply(&mut book, op, workspace_dir).map_err(|e| format!("Operation {}: {}", i + 1, e))?;
+    }
+    if book.sheets.is_empty() {
+        return Err("No sheets to write; give at least one operation".to_string());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    book.save(&path)?;
+
+    let sheets: Vec<&str> = book.sheets.iter().map(|(n, _)| n.as_str()).collect();
+    Ok(format!(
+        "Wrote {} cell(s) to {} (sheets: {}).\n\nMEDIA: {}",
+        written,
+        path.display(),
+        sheets.join(", "),
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_cell() {
+        assert_eq!(parse_cell("A1").unwrap(), (0, 0));
+        assert_eq!(parse_cell("$c$12").unwrap(), (11, 2));
+        assert_eq!(parse_cell("AA3").unwrap(), (2, 26));
+        assert!(parse_cell("A0").is_err());
+        assert!(parse_cell("12").is_err());
+        assert!(parse_cell("XFE1").is_err());
+    }
+
+    #[test]
+    fn test_apply_values_and_tables() {
+        let ws = Path::new("/tmp");
+        let mut book = Book::default();
+        let op = json!({ "sheet": "Sum", "cell": "B2", "values": [["a", 1], [true, "=A1*2"]] });
+        assert_eq!(apply(&mut book, &op, ws).unwrap(), 4);
+        let sheet = &book.sheets[0].1;
+        assert_eq!(sheet.cells[&(1, 1)], Cell::Text("a".into()));
+        assert_eq!(sheet.cells[&(2, 2)], Cell::Formula("=A1*2".into()));
+
+        let op = json!({ "sheet": "Jan", "data": "item,amount\nrent,1200\n" });
+        apply(&mut book, &op, ws).unwrap();
+        let sheet = &book.sheets[1].1;
+        assert_eq!(sheet.cells[&(1, 1)], Cell::Number(1200.0));
+        assert!(sheet.bold.contains(&(0, 0)));
+
+        assert!(apply(&mut book, &json!({ "sheet": "bad/name" }), ws).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "spreadsheets")]
+    fn test_write_and_update() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let args = json!({
+            "path": "book.xlsx",
+            "operations": [{ "sheet": "Jan", "values": [["x", 2]] }],
+        });
+        exec_xlsx_write(&args, dir.path()).unwrap();
+        let args = json!({
+            "path": "book.xlsx",
+            "mode": "update",
+            "operations": [{ "sheet": "Summary", "values": [["=Jan!B1*10"]] }],
+        });
+        let result = exec_xlsx_write(&args, dir.path()).unwrap();
+        assert!(result.contains("sheets: Jan, Summary"));
+
+        let book = Book::load(&dir.path().join("book.xlsx")).unwrap();
+        assert_eq!(book.sheets[0].1.cells[&(0, 1)], Cell::Number(2.0));
+        assert_eq!(book.sheets[1].1.cells[&(0, 0)], Cell::Formula("=Jan!B1*10".into()));
+    }
+}
@@ -0,0 +1,433 @@
+//! Machine translation and language detection.
+//!
+//! Backs the `translate` tool and per-chat auto-translation in the
+//! messenger handler.  Providers:
+//!
+//! - `deepl`: the DeepL API (`api_key` or `DEEPL_API_KEY`; free-tier keys
+//!   ending in `:fx` use the free endpoint),
+//! - `libretranslate`: a LibreTranslate server (`base_url` or
+//!   `LIBRETRANSLATE_URL`, optional `api_key` / `LIBRETRANSLATE_API_KEY`),
+//! - `argos`: Argos Translate models run locally by the `argos-translate`
+//!   CLI, fully offline.
+//!
+//! `auto` picks the first one that's configured, in that order.  Source
+//! languages are detected by the provider when it can, otherwise by a small
+//! built-in detector (Unicode scripts plus common-word counts).
+//!
+//! ```toml
+//! [translation]
+//! default_target = "en"
+//!
+//! # Translate this Telegram chat into English for the agent, and replies
+//! # back into whatever language the sender wrote in.
+//! [[translation.chats]]
+//! chat = "telegram:123456789"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::debug;
+
+const DEEPL_URL: &str = "https://api.deepl.com/v2/translate";
+const DEEPL_FREE_URL: &str = "https://api-free.deepl.com/v2/translate";
+
+/// Texts longer than this are refused rather than sent off in one request.
+pub const MAX_CHARS: usize = 50_000;
+
+/// Auto-translation for one chat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatTranslation {
+    /// `messenger:chat_id` (e.g. `telegram:123456789`), a bare chat id, or
+    /// `*` for every chat.
+    pub chat: String,
+    /// Translate incoming messages into `default_target` for the agent.
+    #[serde(default = "default_true")]
+    pub incoming: bool,
+    /// Translate replies into the chat's language.
+    #[serde(default = "default_true")]
+    pub outgoing: bool,
+    /// The chat's language; detected from each incoming message when unset.
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// The `[translation]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationConfig {
+    /// `auto`, `deepl`, `libretranslate` or `argos`.
+    #[serde(default = "default_provider")]
+    pub provider: String,
+    /// Target language when a call doesn't name one, and the language the
+    /// agent works in for auto-translated chats.
+    #[serde(default = "default_target")]
+    pub default_target: String,
+    /// LibreTranslate server URL.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// DeepL or LibreTranslate API key.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub chats: Vec<ChatTranslation>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_provider() -> String {
+    "auto".to_string()
+}
+
+fn default_target() -> String {
+    "en".to_string()
+}
+
+impl Default for TranslationConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_provider(),
+            default_target: default_target(),
+            base_url: None,
+            api_key: None,
+            chats: Vec::new(),
+        }
+    }
+}
+
+impl TranslationConfig {
+    /// The auto-translation rule for a chat, if any.  An exact match wins
+    /// over `*`.
+    pub fn chat_rule(&self, messenger_type: &str, chat_id: &str) -> Option<&ChatTranslation> {
+        let key = format!("{}:{}", messenger_type, chat_id);
+        self.chats
+            .iter()
+            .find(|c| c.chat == key || c.chat == chat_id)
+            .or_else(|| self.chats.iter().find(|c| c.chat == "*"))
+    }
+}
+
+static CONFIG: Mutex<Option<TranslationConfig>> = Mutex::new(None);
+
+/// Register the translation config.  Called at startup and on reload.
+pub fn set_config(config: TranslationConfig) {
+    debug!(provider = %config.provider, target = %config.default_target, chats = config.chats.len(), "Setting translation config");
+    if let Ok(mut guard) = CONFIG.lock() {
+        *guard = Some(config);
+    }
+}
+
+/// The registered config, or the defaults outside the gateway.
+pub fn config() -> TranslationConfig {
+    CONFIG.lock().ok().and_then(|c| c.clone()).unwrap_or_default()
+}
+
+/// The primary subtag of a language code, lower-cased: `pt-BR` → `pt`.
+pub fn primary(lang: &str) -> String {
+    lang.split(['-', '_']).next().unwrap_or(lang).trim().to_lowercase()
+}
+
+/// Whether two language codes name the same language.
+pub fn same_language(a: &str, b: &str) -> bool {
+    primary(a) == primary(b)
+}
+
+// ── Detection ───────────────────────────────────────────────────────────────
+
+/// A detected language with a rough 0–1 confidence.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Detection {
+    pub language: String,
+    pub confidence: f64,
+}
+
+/// Frequent short words for Latin-script languages.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "are", "you", "to", "of", "it", "that", "what", "this", "with", "have", "for", "not"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "ich", "du", "sie", "ein", "eine", "mit", "auf", "zu", "wie"]),
+    ("fr", &["le", "la", "les", "et", "est", "je", "tu", "vous", "une", "des", "pas", "que", "pour", "avec", "ce"]),
+    ("es", &["el", "los", "las", "y", "es", "que", "no", "por", "con", "una", "para", "como", "pero", "está", "qué"]),
+    ("it", &["il", "gli", "e", "è", "che", "non", "per", "con", "una", "sono", "come", "ma", "questo", "della", "ciao"]),
+    ("pt", &["o", "os", "as", "e", "é", "que", "não", "por", "com", "uma", "para", "como", "mas", "você", "obrigado"]),
+    ("nl", &["de", "het", "een", "en", "is", "niet", "ik", "je", "van", "dat", "met", "op", "zijn", "wat", "hoe"]),
+    ("sv", &["och", "är", "det", "att", "jag", "du", "inte", "en", "som", "på", "med", "för", "har", "vad", "hur"]),
+    ("pl", &["i", "jest", "nie", "się", "że", "to", "na", "w", "z", "co", "jak", "ale", "czy", "tak", "dla"]),
+    ("tr", &["ve", "bir", "bu", "de", "da", "ne", "için", "ile", "değil", "ben", "sen", "çok", "mı", "mi", "nasıl"]),
+];
+
+/// Best-effort language detection without a provider.
+pub fn detect(text: &str) -> Option<Detection> {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let share = |range: &dyn Fn(char) -> bool| letters.iter().filter(|c| range(**c)).count() as f64 / letters.len() as f64;
+
+    let kana = share(&|c| ('\u{3040}'..='\u{30ff}').contains(&c));
+    let han = share(&|c| ('\u{4e00}'..='\u{9fff}').contains(&c));
+    let scripts: [(&str, f64); 9] = [
+        ("ja", if kana > 0.05 { kana + han } else { 0.0 }),
+        ("zh", if kana > 0.05 { 0.0 } else { han }),
+        ("ko", share(&|c| ('\u{ac00}'..='\u{d7af}').contains(&c) || ('\u{1100}'..='\u{11ff}').contains(&c))),
+        ("ar", share(&|c| ('\u{0600}'..='\u{06ff}').contains(&c))),
+        ("he", share(&|c| ('\u{0590}'..='\u{05ff}').contains(&c))),
+        ("el", share(&|c| ('\u{0370}'..='\u{03ff}').contains(&c))),
+        ("th", share(&|c| ('\u{0e00}'..='\u{0e7f}').contains(&c))),
+        ("hi", share(&|c| ('\u{0900}'..='\u{097f}').contains(&c))),
+        ("ru", share(&|c| ('\u{0400}'..='\u{04ff}').contains(&c))),
+    ];
+    if let Some((lang, score)) = scripts.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1)).filter(|(_, s)| *s > 0.3) {
+        // Ukrainian shares Cyrillic with Russian but has its own letters.
+        let lang = if lang == "ru" && text.chars().any(|c| matches!(c, 'і' | 'ї' | 'є' | 'ґ')) { "uk" } else { lang };
+        return Some(Detection { language: lang.to_string(), confidence: score.min(1.0) });
+    }
+
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    let mut scores: Vec<(&str, usize)> = STOPWORDS
+        .iter()
+        .map(|(lang, list)| (*lang, words.iter().filter(|w| list.contains(&w.as_str())).count()))
+        .filter(|(_, n)| *n > 0)
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+    let (lang, best) = *scores.first()?;
+    let total: usize = scores.iter().map(|(_, n)| n).sum();
+    Some(Detection { language: lang.to_string(), confidence: best as f64 / total as f64 })
+}
+
+// ── Providers ───────────────────────────────────────────────────────────────
+
+/// A translated text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Translation {
+    pub text: String,
+    /// Source language, as given, reported by the provider, or detected.
+    pub source: Option<String>,
+    pub target: String,
+    pub provider: &'static str,
+}
+
+enum Provider {
+    DeepL { key: String },
+    Libre { base_url: String, key: Option<String> },
+    Argos,
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+impl Provider {
+    fn deepl(config: &TranslationConfig) -> Option<Self> {
+        let key = env("DEEPL_API_KEY").or_else(|| (config.provider == "deepl").then(|| config.api_key.clone()).flatten())?;
+        Some(Provider::DeepL { key })
+    }
+
+    fn libre(config: &TranslationConfig) -> Option<Self> {
+        let base_url = config.base_url.clone().or_else(|| env("LIBRETRANSLATE_URL"))?;
+        let key = env("LIBRETRANSLATE_API_KEY").or_else(|| config.api_key.clone());
+        Some(Provider::Libre { base_url, key })
+    }
+
+    fn argos() -> Option<Self> {
+        which::which("argos-translate").ok().map(|_| Provider::Argos)
+    }
+
+    fn select(name: &str, config: &TranslationConfig) -> Result<Self, String> {
+        let missing_deepl = "DeepL needs api_key in [translation] or DEEPL_API_KEY";
+        let missing_libre = "LibreTranslate needs base_url in [translation] or LIBRETRANSLATE_URL";
+        let missing_argos = "Local translation needs argos-translate (`pip install argostranslate`) with language packages installed";
+        match name {
+            "auto" => Self::deepl(config)
+                .or_else(|| Self::libre(config))
+                .or_else(Self::argos)
+                .ok_or_else(|| format!("No translation provider. {}; or {}; or {}.", missing_deepl, missing_libre, missing_argos)),
+            "deepl" => Self::deepl(config).ok_or_else(|| missing_deepl.to_string()),
+            "libretranslate" => Self::libre(config).ok_or_else(|| missing_libre.to_string()),
+            "argos" | "local" => Self::argos().ok_or_else(|| missing_argos.to_string()),
+            other => Err(format!("Unknown provider '{}'; use auto, deepl, libretranslate or argos", other)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Provider::DeepL { .. } => "deepl",
+            Provider::Libre { .. } => "libretranslate",
+            Provider::Argos => "argos",
+        }
+    }
+
+    fn translate(&self, text: &str, source: Option<&str>, target: &str) -> Result<(String, Option<String>), String> {
+        let client = || {
+            reqwest::blocking::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .map_err(|e| format!("Failed to build HTTP client: {}", e))
+        };
+        match self {
+            Provider::DeepL { key } => {
+                let url = if key.ends_with(":fx") { DEEPL_FREE_URL } else { DEEPL_URL };
+                let mut body = json!({ "text": [text], "target_lang": target.to_uppercase() });
+                if let Some(source) = source {
+                    body["source_lang"] = json!(primary(source).to_uppercase());
+                }
+                let response = client()?
+                    .post(url)
+                    .header("Authorization", format!("DeepL-Auth-Key {}", key))
+                    .json(&body)
+                    .send()
+                    .map_err(|e| format!("DeepL request failed: {}", e))?;
+                let body = response_json(response, "DeepL")?;
+                let first = &body["translations"][0];
+                let translated = first["text"].as_str().ok_or("DeepL returned no translation")?;
+                let detected = first["detected_source_language"].as_str().map(|l| l.to_lowercase());
+                Ok((translated.to_string(), detected))
+            }
+            Provider::Libre { base_url, key } => {
+                let mut body = json!({
+                    "q": text,
+                    "source": source.map(primary).unwrap_or_else(|| "auto".to_string()),
+                    "target": primary(target),
+                    "format": "text",
+                });
+                if let Some(key) = key {
+                    body["api_key"] = json!(key);
+                }
+                let response = client()?
+                    .post(format!("{}/translate", base_url.trim_end_matches('/')))
+                    .json(&body)
+                    .send()
+                    .map_err(|e| format!("LibreTranslate request failed: {}", e))?;
+                let body = response_json(response, "LibreTranslate")?;
+                let translated = body["translatedText"].as_str().ok_or("LibreTranslate returned no translation")?;
+                let detected = body["detectedLanguage"]["language"].as_str().map(str::to_string);
+                Ok((translated.to_string(), detected))
+            }
+            Provider::Argos => {
+                // Argos needs to be told the source language.
+                let source = match source {
+                    Some(s) => primary(s),
+                    None => detect(text)
+                        .map(|d| d.language)
+                        .ok_or("Couldn't detect the source language; pass 'source'")?,
+                };
+                let output = std::process::Command::new("argos-translate")
+                    .args(["--from-lang", &source, "--to-lang", &primary(target), text])
+                    .output()
+                    .map_err(|e| format!("Failed to run argos-translate: {}", e))?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "argos-translate failed (is the {}→{} package installed?): {}",
+                        source,
+                        primary(target),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+                Ok((String::from_utf8_lossy(&output.stdout).trim().to_string(), Some(source)))
+            }
+        }
+    }
+}
+
+fn response_json(response: reqwest::blocking::Response, provider: &str) -> Result<Value, String> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("{} API error ({}): {}", provider, status, response.text().unwrap_or_default()));
+    }
+    response.json().map_err(|e| format!("Failed to parse {} response: {}", provider, e))
+}
+
+/// Translate `text` into `target` (the configured default when `None`).
+/// Blocking; call from `spawn_blocking` in async code.
+pub fn translate(text: &str, source: Option<&str>, target: Option<&str>, provider: Option<&str>) -> Result<Translation, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to translate".to_string());
+    }
+    if text.chars().count() > MAX_CHARS {
+        return Err(format!("Text is too long to translate ({} characters; the limit is {})", text.chars().count(), MAX_CHARS));
+    }
+    let config = config();
+    let target = target.unwrap_or(&config.default_target).to_string();
+    let source = source.filter(|s| !s.is_empty() && *s != "auto");
+
+    // Nothing to do when the text is already in the target language.
+    let detected = source.map(str::to_string).or_else(|| detect(text).filter(|d| d.confidence >= 0.6).map(|d| d.language));
+    if detected.as_deref().is_some_and(|d| same_language(d, &target)) {
+        return Ok(Translation { text: text.to_string(), source: detected, target, provider: "none" });
+    }
+
+    let provider = Provider::select(provider.unwrap_or(&config.provider), &config)?;
+    debug!(provider = provider.name(), ?source, %target, chars = text.len(), "Translating");
+    let (translated, reported) = provider.translate(text, source, &target)?;
+    Ok(Translation {
+        text: translated,
+        source: source.map(str::to_string).or(reported).or(detected),
+        target,
+        provider: provider.name(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_scripts() {
+        assert_eq!(detect("こんにちは、元気ですか").unwrap().language, "ja");
+        assert_eq!(detect("你好，你今天怎么样").unwrap().language, "zh");
+        assert_eq!(detect("Привет, как дела?").unwrap().language, "ru");
+        assert_eq!(detect("Привіт, як справи? Дякую, їжа").unwrap().language, "uk");
+        assert_eq!(detect("مرحبا كيف حالك").unwrap().language, "ar");
+        assert!(detect("12345 !!!").is_none());
+    }
+
+    #[test]
+    fn test_detect_latin() {
+        assert_eq!(detect("What is the weather like, and is it going to rain?").unwrap().language, "en");
+        assert_eq!(detect("Ich weiß nicht, wie das Wetter ist und ob die Sonne scheint").unwrap().language, "de");
+        assert_eq!(detect("Je ne sais pas si vous avez le temps pour une pause").unwrap().language, "fr");
+        assert_eq!(detect("¿Qué tal? No sé si está lloviendo por la mañana").unwrap().language, "es");
+    }
+
+    #[test]
+    fn test_chat_rule() {
+        let config: TranslationConfig = toml::from_str(
+            r#"
+            default_target = "en"
+            [[chats]]
+            chat = "telegram:42"
+            language = "de"
+            [[chats]]
+            chat = "*"
+            outgoing = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.provider, "auto");
+        assert_eq!(config.chat_rule("telegram", "42").unwrap().language.as_deref(), Some("de"));
+        let fallback = config.chat_rule("discord", "7").unwrap();
+        assert!(fallback.incoming && !fallback.outgoing);
+        assert!(TranslationConfig::default().chat_rule("telegram", "42").is_none());
+    }
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!(primary("pt-BR"), "pt");
+        assert!(same_language("EN", "en_GB"));
+        assert!(!same_language("de", "en"));
+    }
+
+    #[test]
+    fn test_translate_skips_same_language() {
+        let result = translate("Hello, what is the plan for this week and the next?", None, Some("en"), None).unwrap();
+        assert_eq!(result.provider, "none");
+        assert!(translate("   ", None, None, None).is_err());
+        let err = translate("Bonjour", Some("fr"), Some("en"), Some("babel")).unwrap_err();
+        assert!(err.contains("Unknown provider"));
+    }
+}
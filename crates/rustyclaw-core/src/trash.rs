@@ -0,0 +1,285 @@
+//! Trash for files removed by the `delete_file` tool.
+//!
+//! Deleting moves the file or directory into `<settings_dir>/trash/<id>/`
+//! instead of unlinking it, so a wrong delete can be taken back with the
+//! tool's `restore` action or `/undo` in the TUI.  Entries older than
+//! `retention_days` are purged whenever something is trashed:
+//!
+//! ```toml
+//! [trash]
+//! retention_days = 30
+//! ```
+//!
+//! A permanent delete skips the trash; it is a gated action
+//! (`delete_file.delete`) and needs confirmation unless allowed under
+//! `[tool_permissions]`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::{debug, info, warn};
+
+const META_FILE: &str = "entry.json";
+const ITEM: &str = "item";
+
+/// The `[trash]` config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashConfig {
+    /// Days a trashed item is kept before it is purged.  0 keeps items
+    /// until they are restored.
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u64,
+    /// Where trashed items go (default: `<settings_dir>/trash`).
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+}
+
+fn default_retention_days() -> u64 {
+    30
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: default_retention_days(),
+            dir: None,
+        }
+    }
+}
+
+/// A trashed file or directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// Where the item lived before it was trashed.
+    pub original: PathBuf,
+    /// Milliseconds since the epoch.
+    pub deleted_ms: u64,
+    pub is_dir: bool,
+    pub bytes: u64,
+}
+
+/// Directory trashed items go to under this config.
+pub fn trash_dir(config: &TrashConfig, settings_dir: &Path) -> PathBuf {
+    config.dir.clone().unwrap_or_else(|| settings_dir.join("trash"))
+}
+
+fn entry_dir(trash_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(format!("Invalid trash id '{}'", id));
+    }
+    let dir = trash_dir.join(id);
+    if !dir.join(META_FILE).is_file() {
+        return Err(format!("Trash entry '{}' not found", id));
+    }
+    Ok(dir)
+}
+
+fn size_of(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Copy a file or directory tree, keeping symlinks as links.
+pub(crate) fn copy_tree(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(src)?;
+    #[cfg(unix)]
+    if meta.file_type().is_symlink() {
+        return std::os::unix::fs::symlink(std::fs::read_link(src)?, dst);
+    }
+    if !meta.is_dir() {
+        return std::fs::copy(src, dst).map(|_| ());
+    }
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        copy_tree(&entry.path(), &dst.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Move `src` to `dst`, copying when they are on different filesystems.
+fn move_path(src: &Path, dst: &Path) -> Result<(), String> {
+    if std::fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+    copy_tree(src, dst).map_err(|e| format!("Failed to move {}: {}", src.display(), e))?;
+    let removed = if std::fs::symlink_metadata(src).is_ok_and(|m| m.is_dir()) {
+        std::fs::remove_dir_all(src)
+    } else {
+        std::fs::remove_file(src)
+    };
+    removed.map_err(|e| format!("Copied {} but failed to remove it: {}", src.display(), e))
+}
+
+fn read_meta(dir: &Path) -> Option<TrashEntry> {
+    let text = std::fs::read_to_string(dir.join(META_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Trashed items, newest first.
+pub fn list(trash_dir: &Path) -> Vec<TrashEntry> {
+    let Ok(entries) = std::fs::read_dir(trash_dir) else {
+        return Vec::new();
+    };
+    let mut items: Vec<TrashEntry> = entries.flatten().filter_map(|e| read_meta(&e.path())).collect();
+    items.sort_by(|a, b| b.deleted_ms.cmp(&a.deleted_ms).then_with(|| b.id.cmp(&a.id)));
+    items
+}
+
+/// Move `path` into the trash.
+pub fn move_to_trash(trash_dir: &Path, path: &Path) -> Result<TrashEntry, String> {
+    let meta = std::fs::symlink_metadata(path).map_err(|e| format!("Cannot delete {}: {}", path.display(), e))?;
+    let original = path.to_path_buf();
+    if trash_dir.starts_with(&original) {
+        return Err(format!("Refusing to trash {}: it contains the trash", original.display()));
+    }
+
+    let now = chrono::Local::now();
+    let stamp = now.format("%Y%m%d-%H%M%S-%3f").to_string();
+    // Several deletes can land in the same millisecond.
+    let id = (0..)
+        .map(|n| if n == 0 { stamp.clone() } else { format!("{}-{}", stamp, n) })
+        .find(|id| !trash_dir.join(id).exists())
+        .unwrap_or(stamp);
+    let dir = trash_dir.join(&id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let entry = TrashEntry {
+        id,
+        original,
+        deleted_ms: now.timestamp_millis() as u64,
+        is_dir: meta.is_dir(),
+        bytes: size_of(path),
+    };
+    let json = serde_json::to_string_pretty(&entry).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(META_FILE), json).map_err(|e| format!("Failed to write trash entry: {}", e))?;
+    if let Err(e) = move_path(path, &dir.join(ITEM)) {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+    info!(id = %entry.id, path = %entry.original.display(), "Moved to trash");
+    Ok(entry)
+}
+
+/// Put a trashed item back where it came from.  `None` restores the most
+/// recently trashed item.
+pub fn restore(trash_dir: &Path, id: Option<&str>) -> Result<TrashEntry, String> {
+    let entry = match id {
+        Some(id) => read_meta(&entry_dir(trash_dir, id)?).ok_or_else(|| format!("Trash entry '{}' is unreadable", id))?,
+        None => list(trash_dir).into_iter().next().ok_or_else(|| "The trash is empty".to_string())?,
+    };
+    if std::fs::symlink_metadata(&entry.original).is_ok() {
+        return Err(format!(
+            "Cannot restore {}: something already exists there",
+            entry.original.display()
+        ));
+    }
+    if let Some(parent) = entry.original.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let dir = trash_dir.join(&entry.id);
+    move_path(&dir.join(ITEM), &entry.original)?;
+    let _ = std::fs::remove_dir_all(&dir);
+    info!(id = %entry.id, path = %entry.original.display(), "Restored from trash");
+    Ok(entry)
+}
+
+/// Delete items trashed more than `retention_days` ago.  Returns how many
+/// were purged.
+pub fn purge_expired(trash_dir: &Path, retention_days: u64) -> usize {
+    if retention_days == 0 {
+        return 0;
+    }
+    let cutoff = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(retention_days * 86_400_000);
+    let mut purged = 0;
+    for entry in list(trash_dir).into_iter().filter(|e| e.deleted_ms < cutoff) {
+        match std::fs::remove_dir_all(trash_dir.join(&entry.id)) {
+            Ok(()) => purged += 1,
+            Err(e) => warn!(id = %entry.id, error = %e, "Failed to purge trash entry"),
+        }
+    }
+    if purged > 0 {
+        debug!(purged, "Purged expired trash entries");
+    }
+    purged
+}
+
+// ── Gateway state ───────────────────────────────────────────────────────────
+
+static CONFIG: RwLock<Option<(TrashConfig, PathBuf)>> = RwLock::new(None);
+
+/// Register the `[trash]` config.  `settings_dir` supplies the default
+/// location.  Called at startup and on reload.
+pub fn set_config(config: TrashConfig, settings_dir: &Path) {
+    let dir = trash_dir(&config, settings_dir);
+    debug!(dir = %dir.display(), retention_days = config.retention_days, "Setting trash config");
+    if let Ok(mut guard) = CONFIG.write() {
+        *guard = Some((config, dir));
+    }
+}
+
+/// The registered config and trash directory, or the defaults.
+pub fn config() -> (TrashConfig, PathBuf) {
+    if let Some(registered) = CONFIG.read().ok().and_then(|g| g.clone()) {
+        return registered;
+    }
+    let config = TrashConfig::default();
+    let dir = trash_dir(&config, &crate::config::Config::default().settings_dir);
+    (config, dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path().join("trash");
+        let ws = dir.path().join("ws");
+        std::fs::create_dir_all(ws.join("src")).unwrap();
+        std::fs::write(ws.join("notes.md"), "keep me").unwrap();
+        std::fs::write(ws.join("src/lib.rs"), "fn a() {}").unwrap();
+
+        let file = move_to_trash(&trash, &ws.join("notes.md")).unwrap();
+        assert!(!ws.join("notes.md").exists());
+        assert_eq!(file.bytes, 7);
+        let tree = move_to_trash(&trash, &ws.join("src")).unwrap();
+        assert!(tree.is_dir);
+        assert_eq!(list(&trash).len(), 2);
+
+        // Newest first.
+        assert_eq!(restore(&trash, None).unwrap().id, tree.id);
+        assert_eq!(std::fs::read_to_string(ws.join("src/lib.rs")).unwrap(), "fn a() {}");
+
+        std::fs::write(ws.join("notes.md"), "new").unwrap();
+        assert!(restore(&trash, Some(&file.id)).unwrap_err().contains("already exists"));
+        std::fs::remove_file(ws.join("notes.md")).unwrap();
+        restore(&trash, Some(&file.id)).unwrap();
+        assert_eq!(std::fs::read_to_string(ws.join("notes.md")).unwrap(), "keep me");
+        assert!(list(&trash).is_empty());
+        assert!(restore(&trash, Some("../x")).is_err());
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path().join("trash");
+        std::fs::write(dir.path().join("old.txt"), "x").unwrap();
+        let mut entry = move_to_trash(&trash, &dir.path().join("old.txt")).unwrap();
+
+        assert_eq!(purge_expired(&trash, 1), 0);
+        entry.deleted_ms -= 2 * 86_400_000;
+        std::fs::write(trash.join(&entry.id).join(META_FILE), serde_json::to_string(&entry).unwrap()).unwrap();
+        assert_eq!(purge_expired(&trash, 0), 0);
+        assert_eq!(purge_expired(&trash, 1), 1);
+        assert!(list(&trash).is_empty());
+    }
+}
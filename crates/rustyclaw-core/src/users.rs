@@ -0,0 +1,396 @@
+//! Multi-user roles for messenger conversations.
+//!
+//! When several people talk to the same agent, the `[users]` section gives
+//! each of them a role.  A profile matches a sender by contact name (see
+//! [`crate::identity`]) or by `messenger:id`:
+//!
+//! ```toml
+//! [users]
+//! default_role = "guest"
+//!
+//! [[users.profiles]]
+//! name = "Ada Lovelace"
+//! role = "owner"
+//!
+//! [[users.profiles]]
+//! name = "Charles"
+//! role = "trusted"
+//! ids = ["telegram:123456789"]
+//! ```
+//!
+//! - **owner** — everything, including `MEMORY.md`, `USER.md` and secrets.
+//! - **trusted** — most tools, but no secrets, system administration, the
+//!   knowledge vault, other people's conversations or the owner's memory.
+//! - **guest** — web lookups, translation and a few harmless helpers.
+//!
+//! Everyone but the owner gets a private memory namespace,
+//! `memory/people/<key>/` (plus `memory/people/<key>.md`); file tools can
+//! reach that, and nothing else under `memory/`.  The memory tools are
+//! given the namespace as their `scope` (see [`scoped_args`]).  Nobody but
+//! the owner may write under `skills/` or `.rustyclaw/`: scripts there run
+//! as hooks with the owner's tools.
+//!
+//! A messenger turn runs its tools inside [`user_scope`], so tools that call
+//! other tools — scripts — apply the same rules (see [`current_user`]).
+//!
+//! Without profiles every sender is an owner, as before roles existed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::path::{Component, Path};
+
+use crate::identity::{self, Person};
+
+/// What a user may do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Owner,
+    Trusted,
+    #[default]
+    Guest,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Trusted => "trusted",
+            Self::Guest => "guest",
+        }
+    }
+}
+
+/// One user's role.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserProfile {
+    /// Contact name; matches every id linked to that contact.
+    #[serde(default)]
+    pub name: String,
+    pub role: Role,
+    /// Extra `messenger:id` senders, e.g. `telegram:123`.
+    #[serde(default)]
+    pub ids: Vec<String>,
+}
+
+/// The `[users]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsersConfig {
+    /// Role of senders no profile matches.
+    #[serde(default)]
+    pub default_role: Role,
+    #[serde(default)]
+    pub profiles: Vec<UserProfile>,
+}
+
+/// The sender of a message, as far as access control is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub role: Role,
+    /// Key of the user's memory namespace.
+    pub key: String,
+}
+
+impl User {
+    /// `memory/people/<key>` — the user's own corner of memory.
+    pub fn namespace(&self) -> String {
+        format!("memory/people/{}", self.key)
+    }
+}
+
+/// Work out who sent a message.
+pub fn resolve(config: &UsersConfig, person: Option<&Person>, messenger: &str, sender: &str) -> User {
+    let key = person
+        .map(|p| p.key.clone())
+        .unwrap_or_else(|| identity::person_key(&format!("{} {}", messenger, sender)));
+    if config.profiles.is_empty() {
+        return User { role: Role::Owner, key };
+    }
+    let id = format!("{}:{}", messenger, sender);
+    let role = config
+        .profiles
+        .iter()
+        .find(|p| {
+            person.is_some_and(|person| !p.name.is_empty() && p.name.eq_ignore_ascii_case(&person.name))
+                || p.ids.iter().any(|i| i.eq_ignore_ascii_case(&id))
+        })
+        .map(|p| p.role)
+        .unwrap_or(config.default_role);
+    User { role, key }
+}
+
+tokio::task_local! {
+    static CURRENT_USER: User;
+}
+
+/// The user whose messenger turn is running on the current task, if any.
+/// Everything else — the TUI, tasks, cron, hooks — runs as the owner.
+pub fn current_user() -> Option<User> {
+    CURRENT_USER.try_with(|user| user.clone()).ok()
+}
+
+/// Run `fut` as a turn of `user`.
+pub async fn user_scope<F: Future>(user: User, fut: F) -> F::Output {
+    CURRENT_USER.scope(user, fut).await
+}
+
+/// Run `f` with `user` (if any) as the current user — for turn work moved
+/// onto the blocking pool.
+pub fn sync_user_scope<R>(user: Option<User>, f: impl FnOnce() -> R) -> R {
+    match user {
+        Some(user) => CURRENT_USER.sync_scope(user, f),
+        None => f(),
+    }
+}
+
+/// Tools only the owner may use.
+const OWNER_ONLY: &[&str] = &[
+    "execute_command",
+    // Scripts call any tool, and become hooks that run as the owner.
+    "script_run",
+    "apply_patch",
+    "process",
+    "gateway",
+    "conversations",
+    "contacts",
+    "sessions_history",
+    "sessions_send",
+    // These run turns in the background with the owner's tools.
+    "sessions_spawn",
+    "sessions_worktree",
+    "orchestrate",
+    "cron",
+    "tasks",
+    "qmd_search",
+    "qmd_deep_search",
+    "qmd_get",
+    "skill_install",
+    "skill_enable",
+    "skill_link_secret",
+    "skill_create",
+    "agent_setup",
+    "audit_sensitive",
+    "secure_delete",
+    "snapshots",
+    "clipboard",
+    "screenshot",
+    "browser_cache",
+    "pkg_manage",
+    "service_manage",
+    "user_manage",
+    "firewall",
+    "net_scan",
+    "ollama_manage",
+    "exo_manage",
+    "uv_manage",
+    "npm_manage",
+    "nodes",
+    "ble",
+    "serial",
+    "mqtt",
+];
+
+/// Everything a guest may use.
+const GUEST_TOOLS: &[&str] = &[
+    "web_search",
+    "web_fetch",
+    "translate",
+    "calc",
+    "image",
+    "ocr",
+    "qr",
+    "tts",
+    "ask_user",
+    "request_tools",
+    "read_file",
+    "write_file",
+    "edit_file",
+    "list_directory",
+    "tree",
+];
+
+/// Tools whose `path` argument is checked against the user's space.
+const FILE_TOOLS: &[&str] = &[
+    "read_file",
+    "write_file",
+    "edit_file",
+    "delete_file",
+    "list_directory",
+    "tree",
+    "search_files",
+    "find_files",
+    "summarize_file",
+    "classify_files",
+];
+
+/// Memory tools that take a `scope` confining them to one namespace.
+const SCOPED_TOOLS: &[&str] = &["memory_search", "memory_get"];
+
+/// The arguments to run this call with when they differ from the model's:
+/// non-owners' memory tools get their own namespace as `scope`, whatever
+/// the model asked for.
+pub fn scoped_args(user: &User, name: &str, args: &Value) -> Option<Value> {
+    if user.role == Role::Owner || !SCOPED_TOOLS.contains(&name) {
+        return None;
+    }
+    let mut args = args.clone();
+    if !args.is_object() {
+        args = Value::Object(Default::default());
+    }
+    args["scope"] = Value::String(user.namespace());
+    Some(args)
+}
+
+/// Why `user` may not make this tool call, or `None` if they may.
+pub fn tool_denial(user: &User, name: &str, args: &Value) -> Option<String> {
+    match user.role {
+        Role::Owner => return None,
+        Role::Trusted => {
+            if crate::tools::is_secrets_tool(name) || OWNER_ONLY.contains(&name) {
+                return Some(format!("'{}' is only available to the owner.", name));
+            }
+        }
+        Role::Guest => {
+            if !GUEST_TOOLS.contains(&name) {
+                return Some(format!("'{}' isn't available to guests.", name));
+            }
+        }
+    }
+    if FILE_TOOLS.contains(&name) {
+        return path_denial(user, name, args);
+    }
+    None
+}
+
+/// Keep non-owners out of other people's memory and outside the
+/// workspace.  Guests only get their own namespace.
+fn path_denial(user: &User, name: &str, args: &Value) -> Option<String> {
+    let namespace = user.namespace();
+    let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+
+    let mut rel = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => rel.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return Some(format!("'{}' is outside the workspace.", path)),
+        }
+    }
+    if rel.first().is_some_and(|p| p.starts_with('~')) {
+        return Some(format!("'{}' is outside the workspace.", path));
+    }
+    // Scripts and settings under these run with the owner's tools.
+    let writes = matches!(name, "write_file" | "edit_file" | "delete_file");
+    let owner_config = rel
+        .first()
+        .is_some_and(|top| top.eq_ignore_ascii_case("skills") || top.eq_ignore_ascii_case(".rustyclaw"));
+    if writes && owner_config {
+        return Some(format!("Only the owner can change '{}'.", path));
+    }
+    let rel = rel.join("/");
+
+    let own = rel == namespace
+        || rel.starts_with(&format!("{}/", namespace))
+        || rel == format!("{}.md", namespace);
+    if own {
+        return None;
+    }
+    let denied = Some(format!(
+        "That's outside your space. You can use files under {}/ (and {}.md).",
+        namespace, namespace
+    ));
+    if user.role == Role::Guest {
+        return denied;
+    }
+    let private = rel.eq_ignore_ascii_case("MEMORY.md")
+        || rel.eq_ignore_ascii_case("USER.md")
+        || rel == "memory"
+        || rel.starts_with("memory/");
+    // Searching the workspace root would read memory too.
+    let covers_memory = rel.is_empty() && matches!(name, "search_files" | "find_files" | "classify_files");
+    if private || covers_memory {
+        return denied;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> UsersConfig {
+        toml::from_str(
+            "[[profiles]]\nname = \"Ada Lovelace\"\nrole = \"owner\"\n\
+             [[profiles]]\nrole = \"trusted\"\nids = [\"telegram:42\"]\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_roles() {
+        let ada = Person::new("Ada Lovelace");
+        assert_eq!(resolve(&config(), Some(&ada), "signal", "+44").role, Role::Owner);
+        let charles = resolve(&config(), None, "telegram", "42");
+        assert_eq!(charles.role, Role::Trusted);
+        assert_eq!(charles.namespace(), "memory/people/telegram-42");
+        assert_eq!(resolve(&config(), None, "telegram", "7").role, Role::Guest);
+        // No profiles: single-user setup, everyone is the owner.
+        assert_eq!(resolve(&UsersConfig::default(), None, "telegram", "7").role, Role::Owner);
+    }
+
+    #[test]
+    fn test_trusted_users_keep_out_of_owner_memory() {
+        let user = User { role: Role::Trusted, key: "bob".into() };
+        assert!(tool_denial(&user, "secrets_get", &json!({})).is_some());
+        assert!(tool_denial(&user, "execute_command", &json!({"command": "ls"})).is_some());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "MEMORY.md"})).is_some());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "memory/people/alice.md"})).is_some());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "../secrets.json"})).is_some());
+        assert!(tool_denial(&user, "search_files", &json!({"pattern": "x"})).is_some());
+        assert!(tool_denial(&user, "write_file", &json!({"path": "memory/people/bob/notes.md"})).is_none());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "./src/main.rs"})).is_none());
+        assert!(tool_denial(&user, "web_search", &json!({"query": "x"})).is_none());
+    }
+
+    #[test]
+    fn test_trusted_users_cannot_run_headless_tools() {
+        let user = User { role: Role::Trusted, key: "bob".into() };
+        for tool in ["cron", "tasks", "sessions_spawn", "orchestrate"] {
+            assert!(tool_denial(&user, tool, &json!({})).is_some(), "{}", tool);
+        }
+    }
+
+    #[test]
+    fn test_trusted_users_cannot_change_scripts() {
+        let user = User { role: Role::Trusted, key: "bob".into() };
+        assert!(tool_denial(&user, "script_run", &json!({"code": "tool(\"execute_command\", #{})"})).is_some());
+        assert!(tool_denial(&user, "write_file", &json!({"path": "skills/scripts/hook.rhai"})).is_some());
+        assert!(tool_denial(&user, "edit_file", &json!({"path": "./Skills/x.md"})).is_some());
+        assert!(tool_denial(&user, "write_file", &json!({"path": ".rustyclaw/config.toml"})).is_some());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "skills/scripts/hook.rhai"})).is_none());
+    }
+
+    #[test]
+    fn test_memory_tools_are_scoped_for_non_owners() {
+        let bob = User { role: Role::Trusted, key: "bob".into() };
+        let args = scoped_args(&bob, "memory_get", &json!({"path": "MEMORY.md", "scope": "memory"})).unwrap();
+        assert_eq!(args["scope"], "memory/people/bob");
+        assert_eq!(args["path"], "MEMORY.md");
+        assert!(scoped_args(&bob, "memory_search", &json!({"query": "x"})).is_some());
+        assert!(scoped_args(&bob, "read_file", &json!({"path": "a"})).is_none());
+
+        let owner = User { role: Role::Owner, key: "ada".into() };
+        assert!(scoped_args(&owner, "memory_search", &json!({"query": "x"})).is_none());
+    }
+
+    #[test]
+    fn test_guests_get_their_namespace_only() {
+        let user = User { role: Role::Guest, key: "eve".into() };
+        assert!(tool_denial(&user, "cron", &json!({})).is_some());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "src/main.rs"})).is_some());
+        assert!(tool_denial(&user, "read_file", &json!({"path": "memory/people/eve.md"})).is_none());
+        assert!(tool_denial(&user, "translate", &json!({"text": "hi"})).is_none());
+    }
+}
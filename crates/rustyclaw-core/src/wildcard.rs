@@ -0,0 +1,39 @@
+//! Simple `*` wildcard matching.
+//!
+//! Shared by snapshot excludes, sandbox path rules, model-name patterns
+//! and the tree tool's ignore list.  Unlike the `glob` crate this has no
+//! character classes or path semantics: `*` matches any run of characters,
+//! `/` included.
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(mut remaining) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or("");
+    for part in parts {
+        match remaining.find(part) {
+            Some(i) => remaining = &remaining[i + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.iso", "ubuntu.iso"));
+        assert!(glob_match("data", "data"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("*.iso", "iso.txt"));
+        assert!(!glob_match("a*b", "ab-"));
+    }
+}
@@ -244,6 +244,13 @@ impl WorkspaceContext {
                 }
             }
         }
+        // Project memory from the workspace's `.rustyclaw/` overlay
+        if session_type == SessionType::Main && self.config.inject_memory {
+            if let Some(section) = crate::project::project_memory_section(&self.workspace_dir) {
+                sections.push(section);
+            }
+        }
+
         // Load personality from Tacit/ directories (takes precedence over SOUL.md if configured)
         if let Some(ref soul_dir) = self.personality.soul_dir {
             if let Some(soul_section) = self.load_directory_files(soul_dir, "Personality") {
@@ -405,6 +412,18 @@ mod tests {
         assert!(prompt.contains("User prefers Rust"));
     }
 
+    #[test]
+    fn test_main_session_includes_project_memory() {
+        let dir = setup_workspace();
+        let overlay = dir.path().join(crate::project::PROJECT_DIR);
+        fs::create_dir_all(&overlay).unwrap();
+        fs::write(overlay.join("MEMORY.md"), "Build with cargo xtask.").unwrap();
+        let ctx = WorkspaceContext::new(dir.path().to_path_buf());
+
+        assert!(ctx.build_context(SessionType::Main).contains("cargo xtask"));
+        assert!(!ctx.build_context(SessionType::Group).contains("cargo xtask"));
+    }
+
     #[test]
     fn test_group_session_excludes_memory() {
         let workspace = setup_workspace();
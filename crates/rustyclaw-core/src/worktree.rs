@@ -0,0 +1,233 @@
+//! Git worktrees for isolated sub-agent workspaces.
+//!
+//! A sub-agent spawned with `isolation: "worktree"` gets its own checkout
+//! of the parent's repository on a fresh branch, so parallel agents can
+//! edit the same project without clobbering each other.  When it is done
+//! the parent reviews the diff and either merges it into its own working
+//! tree or discards it.
+//!
+//! Worktrees live under `<git-common-dir>/rustyclaw-worktrees/` so they
+//! never show up in the parent's `git status`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+/// Directory (inside the git common dir) holding sub-agent worktrees.
+const WORKTREES_DIR: &str = "rustyclaw-worktrees";
+
+/// Prefix for sub-agent branches.
+const BRANCH_PREFIX: &str = "rustyclaw/";
+
+/// An isolated worktree checked out for one sub-agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Worktree {
+    /// Top level of the parent checkout.
+    pub repo: PathBuf,
+    /// The worktree checkout the sub-agent works in.
+    pub path: PathBuf,
+    /// Branch created for the worktree.
+    pub branch: String,
+    /// Commit the worktree was branched from.
+    pub base: String,
+}
+
+/// Run git in `dir`, returning trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or(""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// Reduce `name` to characters that are safe in a branch and directory name.
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    slug.trim_matches('-').to_string()
+}
+
+impl Worktree {
+    /// Create a worktree of the repository containing `workspace_dir`,
+    /// branched from its current `HEAD`.
+    pub fn create(workspace_dir: &Path, name: &str) -> Result<Self, String> {
+        let name = slug(name);
+        if name.is_empty() {
+            return Err("Worktree name must contain letters or digits".to_string());
+        }
+        let repo = PathBuf::from(
+            git(workspace_dir, &["rev-parse", "--show-toplevel"])
+                .map_err(|_| format!("Not a git repository: {}", workspace_dir.display()))?,
+        );
+        let base = git(&repo, &["rev-parse", "HEAD"])
+            .map_err(|_| "Repository has no commits to branch from".to_string())?;
+        let common_dir = PathBuf::from(git(&repo, &["rev-parse", "--git-common-dir"])?);
+        let common_dir = if common_dir.is_absolute() { common_dir } else { repo.join(common_dir) };
+
+        let path = common_dir.join(WORKTREES_DIR).join(&name);
+        if path.exists() {
+            return Err(format!("Worktree already exists: {}", path.display()));
+        }
+        let branch = format!("{}{}", BRANCH_PREFIX, name);
+        let path_str = path.to_string_lossy().to_string();
+        git(&repo, &["worktree", "add", "-b", &branch, &path_str, &base])?;
+
+        info!(path = %path.display(), branch = %branch, "Created sub-agent worktree");
+        Ok(Self { repo, path, branch, base })
+    }
+
+    /// Where `workspace_dir` is inside the worktree: the sub-agent's
+    /// workspace when the workspace is a subdirectory of the repository.
+    pub fn workspace_for(&self, workspace_dir: &Path) -> PathBuf {
+        let dir = workspace_dir
+            .canonicalize()
+            .unwrap_or_else(|_| workspace_dir.to_path_buf());
+        match dir.strip_prefix(&self.repo) {
+            Ok(rel) => self.path.join(rel),
+            Err(_) => self.path.clone(),
+        }
+    }
+
+    /// Stage everything in the worktree, including new files, so the diff
+    /// covers all of the sub-agent's edits.
+    fn stage_all(&self) -> Result<(), String> {
+        git(&self.path, &["add", "-A"]).map(|_| ())
+    }
+
+    /// Changes made in the worktree since it was created, as a unified diff.
+    /// Empty when nothing changed.
+    pub fn diff(&self) -> Result<String, String> {
+        self.stage_all()?;
+        git(&self.path, &["diff", "--cached", "--binary", &self.base])
+    }
+
+    /// One line per changed file plus a totals line.
+    pub fn diff_stat(&self) -> Result<String, String> {
+        self.stage_all()?;
+        git(&self.path, &["diff", "--cached", "--stat", &self.base])
+    }
+
+    /// Apply the worktree's changes to the parent checkout's working tree
+    /// (three-way, so the parent's own edits are kept), then remove the
+    /// worktree.  The changes are left uncommitted for the parent.
+    pub fn merge(&self) -> Result<String, String> {
+        let diff = self.diff()?;
+        if diff.trim().is_empty() {
+            self.discard()?;
+            return Ok("No changes to merge; worktree removed.".to_string());
+        }
+        let stat = self.diff_stat()?;
+
+        let patch = self.path.with_extension("patch");
+        std::fs::write(&patch, format!("{}\n", diff))
+            .map_err(|e| format!("Failed to write patch: {}", e))?;
+        let applied = git(
+            &self.repo,
+            &["apply", "--3way", "--whitespace=nowarn", &patch.to_string_lossy()],
+        );
+        let _ = std::fs::remove_file(&patch);
+        applied.map_err(|e| format!("Merge failed, worktree kept at {}: {}", self.path.display(), e))?;
+
+        self.discard()?;
+        Ok(format!("Merged into {}:\n{}", self.repo.display(), stat))
+    }
+
+    /// Remove the worktree and its branch, dropping any changes.
+    pub fn discard(&self) -> Result<(), String> {
+        let path_str = self.path.to_string_lossy().to_string();
+        git(&self.repo, &["worktree", "remove", "--force", &path_str])?;
+        if let Err(e) = git(&self.repo, &["branch", "-D", &self.branch]) {
+            debug!(branch = %self.branch, error = %e, "Worktree branch not deleted");
+        }
+        info!(path = %self.path.display(), "Removed sub-agent worktree");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A repository with one committed file, or `None` when git is missing.
+    fn repo() -> Option<TempDir> {
+        let dir = TempDir::new().unwrap();
+        git(dir.path(), &["init", "-q"]).ok()?;
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        git(dir.path(), &["add", "-A"]).unwrap();
+        git(
+            dir.path(),
+            &["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-qm", "init"],
+        )
+        .unwrap();
+        Some(dir)
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("fix the parser!"), "fix-the-parser");
+        assert_eq!(slug("///"), "");
+    }
+
+    #[test]
+    fn test_worktree_diff_and_merge() {
+        let Some(dir) = repo() else { return };
+        let wt = Worktree::create(dir.path(), "fix parser").unwrap();
+        assert_eq!(wt.branch, "rustyclaw/fix-parser");
+        assert!(wt.path.join("lib.rs").exists());
+        // Edits in the worktree don't touch the parent checkout.
+        std::fs::write(wt.path.join("lib.rs"), "fn a() { todo!() }\n").unwrap();
+        std::fs::write(wt.path.join("new.rs"), "fn b() {}\n").unwrap();
+        assert!(!dir.path().join("new.rs").exists());
+
+        let diff = wt.diff().unwrap();
+        assert!(diff.contains("todo!()"));
+        assert!(diff.contains("new.rs"));
+
+        let summary = wt.merge().unwrap();
+        assert!(summary.contains("new.rs"));
+        assert!(dir.path().join("new.rs").exists());
+        assert!(!wt.path.exists());
+    }
+
+    #[test]
+    fn test_workspace_inside_worktree() {
+        let Some(dir) = repo() else { return };
+        let sub = dir.path().join("crates/app");
+        std::fs::create_dir_all(&sub).unwrap();
+        let wt = Worktree::create(&sub, "nested").unwrap();
+        assert_eq!(wt.workspace_for(&sub), wt.path.join("crates/app"));
+        assert_eq!(wt.workspace_for(dir.path()), wt.path);
+        wt.discard().unwrap();
+    }
+
+    #[test]
+    fn test_worktree_discard() {
+        let Some(dir) = repo() else { return };
+        let wt = Worktree::create(dir.path(), "scratch").unwrap();
+        std::fs::write(wt.path.join("junk.txt"), "x").unwrap();
+        wt.discard().unwrap();
+        assert!(!wt.path.exists());
+        assert!(!dir.path().join("junk.txt").exists());
+        assert!(git(dir.path(), &["rev-parse", "--verify", "rustyclaw/scratch"]).is_err());
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let dir = TempDir::new().unwrap();
+        assert!(Worktree::create(dir.path(), "x").is_err());
+    }
+}
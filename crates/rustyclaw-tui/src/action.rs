@@ -211,6 +211,18 @@ pub enum Action {
     UserPromptRequest(rustyclaw_core::user_prompt_types::UserPrompt),
     /// User responded to a structured prompt
     UserPromptResponse(rustyclaw_core::user_prompt_types::UserPromptResponse),
+    /// The agent's plan changed (None once cleared)
+    PlanUpdate(Option<rustyclaw_core::plan::Plan>),
+    /// Live gateway stats for the header (latency, queues, cron)
+    GatewayStats(rustyclaw_core::gateway::stats::GatewayStats),
+    /// The watched split-pane view changed (None once closed)
+    SessionView(Option<rustyclaw_core::gateway::session_view::SessionView>),
+    /// Tool operations in flight changed (progress bars)
+    Progress(Vec<rustyclaw_core::progress::ProgressOp>),
+    /// A background event for the notification center
+    Notification(rustyclaw_core::notifications::Notification),
+    /// Context usage and cost of the model call that just finished
+    Usage(rustyclaw_core::gateway::usage::Usage),
     /// A long-running slash-command tool finished (msg, is_error)
     ToolCommandDone {
         message: String,
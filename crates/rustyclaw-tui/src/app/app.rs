@@ -20,7 +20,12 @@ use rustyclaw_core::gateway::{
     ChatMessage, ClientFrame, ClientFrameType, ClientPayload, ServerFrame,
     deserialize_frame, serialize_frame,
 };
+use rustyclaw_core::gateway::protocol::compression::CompressingSink;
+use rustyclaw_core::gateway::protocol::version;
+use rustyclaw_core::i18n::{self, t, tf};
+use rustyclaw_core::journal::TurnJournal;
 use rustyclaw_core::secrets::SecretsManager;
+use rustyclaw_core::session_meta::{sync_pin_message, SessionMeta};
 use rustyclaw_core::skills::SkillManager;
 use rustyclaw_core::soul::SoulManager;
 
@@ -55,6 +60,10 @@ pub(crate) enum GwEvent {
     },
     /// Gateway requests structured user input (ask_user tool)
     UserPromptRequest(rustyclaw_core::user_prompt_types::UserPrompt),
+    /// The agent's plan changed
+    PlanUpdate(Option<rustyclaw_core::plan::Plan>),
+    /// Gateway stats changed
+    Stats(rustyclaw_core::gateway::stats::GatewayStats),
     /// Vault is locked — user needs to provide password
     VaultLocked,
     /// Vault was successfully unlocked
@@ -73,8 +82,32 @@ pub(crate) enum GwEvent {
     ShowToolPerms {
         tools: Vec<crate::components::tool_perms_dialog::ToolPermInfo>,
     },
+    /// Show the workspace picker
+    ShowWorkspaces {
+        workspaces: Vec<crate::components::workspace_dialog::WorkspaceInfo>,
+    },
+    /// Show the command palette
+    ShowPalette {
+        entries: Vec<crate::palette::PaletteEntry>,
+    },
     /// A secrets mutation succeeded — re-fetch the list from the gateway
     RefreshSecrets,
+    /// An interrupted turn was restored from the journal and re-sent
+    SessionRestored { messages: Vec<ChatMessage> },
+    /// The session title or pinned topic changed
+    SessionMeta(SessionMeta),
+    /// The split-pane view changed (None once closed)
+    SessionView(Option<rustyclaw_core::gateway::session_view::SessionView>),
+    /// Tool operations in flight (progress bars)
+    Progress(Vec<rustyclaw_core::progress::ProgressOp>),
+    /// A background event for the notification center
+    Notification(rustyclaw_core::notifications::Notification),
+    /// Open the notification center
+    ShowNotifications,
+    /// Context usage and cost of a model call
+    Usage(rustyclaw_core::gateway::usage::Usage),
+    /// A file sent by the gateway was saved locally
+    MediaReceived { name: String, path: std::path::PathBuf },
 }
 
 /// Messages from the iocraft render component back to tokio.
@@ -99,6 +132,8 @@ pub(crate) enum UserInput {
     ToggleSkill { name: String },
     /// Cycle a tool's permission level (Allow → Ask → Deny → SkillOnly → Allow)
     CycleToolPermission { name: String },
+    /// Make the given directory the active workspace
+    SwitchWorkspace { path: String },
     /// Cycle a secret's access policy (OPEN → ASK → AUTH → SKILL)
     CycleSecretPolicy { name: String, current_policy: String },
     /// Delete a secret credential
@@ -107,6 +142,12 @@ pub(crate) enum UserInput {
     AddSecret { name: String, value: String },
     /// Re-request secrets list from gateway (after a mutation)
     RefreshSecrets,
+    /// Gather the command palette entries
+    OpenPalette,
+    /// The gateway said hello; answer with our protocol version and
+    /// capabilities.  Sent by the connection reader, which has no sink.
+    /// `compress` is set when the gateway accepts compressed frames.
+    Negotiate { compress: bool },
     Quit,
 }
 
@@ -166,6 +207,8 @@ impl App {
 
     /// Run the TUI — this takes over the terminal.
     pub async fn run(&mut self) -> Result<()> {
+        i18n::set_locale(i18n::resolve(self.config.locale.as_deref()));
+
         // Apply deferred vault password if one was provided at startup
         if let Some(pw) = self.deferred_vault_password.take() {
             self.secrets_manager.set_password(pw);
@@ -215,21 +258,27 @@ impl App {
             .clone()
             .unwrap_or_else(|| "ws://127.0.0.1:9001".to_string());
 
-        let hint = "Ctrl+C quit · /help commands · ↑↓ scroll".to_string();
+        let hint = t("status.hint").to_string();
+        let vim_mode = self.config.vim_mode;
 
         // ── Connect to gateway ──────────────────────────────────────────
         let gw_tx_conn = gw_tx.clone();
         let gateway_url_clone = gateway_url.clone();
 
         // Use a oneshot for the write-half of the WS connection.
-        type WsSink = futures_util::stream::SplitSink<
-            tokio_tungstenite::WebSocketStream<
-                tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        type WsSink = CompressingSink<
+            futures_util::stream::SplitSink<
+                tokio_tungstenite::WebSocketStream<
+                    tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+                >,
+                tokio_tungstenite::tungstenite::Message,
             >,
-            tokio_tungstenite::tungstenite::Message,
         >;
 
         let (sink_tx, sink_rx) = tokio::sync::oneshot::channel::<WsSink>();
+        let user_tx_conn = user_tx.clone();
+        // Files tools produced on the gateway host are saved here.
+        let media_dir = self.config.settings_dir.join("media");
 
         let _reader_handle = tokio::spawn(async move {
             use futures_util::StreamExt;
@@ -238,7 +287,8 @@ impl App {
             match connect_async(&gateway_url_clone).await {
                 Ok((ws, _)) => {
                     let (write, mut read) = StreamExt::split(ws);
-                    let _ = sink_tx.send(write);
+                    let _ = sink_tx.send(CompressingSink::new(write));
+                    let mut media_assembler = rustyclaw_core::gateway::media::MediaAssembler::new();
                     // Don't report Connected yet — wait for auth flow.
                     // The gateway will send AuthChallenge or Hello+Status frames.
 
@@ -247,6 +297,26 @@ impl App {
                             Ok(tokio_tungstenite::tungstenite::Message::Binary(data)) => {
                                 match deserialize_frame::<ServerFrame>(&data) {
                                     Ok(frame) => {
+                                    if let rustyclaw_core::gateway::ServerPayload::Hello { capabilities, .. } = &frame.payload {
+                                        let compress = capabilities.iter().any(|c| c == version::CAP_COMPRESSION);
+                                        let _ = user_tx_conn.send(UserInput::Negotiate { compress });
+                                    }
+                                    if let rustyclaw_core::gateway::ServerPayload::MediaChunk { chunk } = frame.payload {
+                                        use rustyclaw_core::gateway::media;
+                                        let saved = media_assembler.push(chunk).map(|received| {
+                                            received.and_then(|m| media::save(&media_dir, &m).map(|path| (m.name, path)))
+                                        });
+                                        match saved {
+                                            Some(Ok((name, path))) => {
+                                                let _ = gw_tx_conn.send(GwEvent::MediaReceived { name, path });
+                                            }
+                                            Some(Err(e)) => {
+                                                let _ = gw_tx_conn.send(GwEvent::Warning(tf("media.failed", &[&e])));
+                                            }
+                                            None => {}
+                                        }
+                                        continue;
+                                    }
                                     // Check for ModelReady status before action conversion
                                     // since it maps to a generic Success action otherwise.
                                     let is_model_ready = matches!(
@@ -263,6 +333,10 @@ impl App {
                                     } else {
                                         let fa = gateway_client::server_frame_to_action(&frame);
                                         if let Some(action) = fa.action {
+                                            // Stored provider keys come back with the result of their check.
+                                            if let crate::action::Action::SecretsStoreResult { ok: true, message } = &action {
+                                                let _ = gw_tx_conn.send(GwEvent::Success(message.clone()));
+                                            }
                                             let ev = action_to_gw_event(&action);
                                             if let Some(ev) = ev {
                                                 let _ = gw_tx_conn.send(ev);
@@ -319,6 +393,7 @@ impl App {
                     soul_name: soul_name,
                     model_label: model_label,
                     hint: hint,
+                    vim_mode: vim_mode,
                 ))
                 .fullscreen()
                 .disable_mouse_capture()
@@ -331,18 +406,37 @@ impl App {
         let secrets_manager = &mut self.secrets_manager;
         let skill_manager = &mut self.skill_manager;
 
+        // ── Offer to resume a turn interrupted by a crash ───────────────
+        let journal = TurnJournal::open(&config.sessions_dir()).ok();
+        if let Some(turn) = journal.as_ref().and_then(|j| j.recover().ok().flatten()) {
+            let preview: String = turn.user_input().unwrap_or("").chars().take(60).collect();
+            let _ = gw_tx.send(GwEvent::Warning(tf(
+                "session.interrupted",
+                &[&preview, &turn.tool_results.len().to_string()],
+            )));
+        }
+
+        // ── Session title and pinned topic ──────────────────────────────
+        let sessions_dir = config.sessions_dir();
+        let mut session_meta = SessionMeta::load(&sessions_dir);
+        let _ = gw_tx.send(GwEvent::SessionMeta(session_meta.clone()));
+
         loop {
             // Poll user_rx (non-blocking on tokio side)
             match user_rx.try_recv() {
                 Ok(UserInput::Chat(text)) => {
                     conversation.push(ChatMessage::text("user", &text));
+                    if session_meta.ensure_title(&text) {
+                        let _ = session_meta.save(&sessions_dir);
+                        let _ = gw_tx.send(GwEvent::SessionMeta(session_meta.clone()));
+                    }
                     if let Some(ref mut sink) = ws_sink {
                         use futures_util::SinkExt;
+                        let mut messages = conversation.clone();
+                        sync_pin_message(&mut messages, session_meta.pinned.as_deref());
                         let frame = ClientFrame {
                             frame_type: ClientFrameType::Chat,
-                            payload: ClientPayload::Chat {
-                                messages: conversation.clone(),
-                            },
+                            payload: ClientPayload::Chat { messages },
                         };
                         if let Ok(data) = serialize_frame(&frame) {
                             let _ = sink
@@ -427,6 +521,114 @@ impl App {
                     }
                     match resp.action {
                         CommandAction::Quit => break,
+                        CommandAction::ResumeSession => {
+                            let turn = journal.as_ref().and_then(|j| j.recover().ok().flatten());
+                            let Some(turn) = turn else {
+                                let _ = gw_tx.send(GwEvent::Warning(t("session.none_interrupted").into()));
+                                continue;
+                            };
+                            // The gateway journals the re-sent turn afresh,
+                            // so the old entry can go.  Other turns still in
+                            // flight keep theirs.
+                            if let Some(ref j) = journal {
+                                let _ = j.discard(&turn.turn_id);
+                            }
+                            conversation = turn.messages.clone();
+                            let _ = gw_tx.send(GwEvent::SessionRestored {
+                                messages: turn.messages.clone(),
+                            });
+                            if let Some(ref mut sink) = ws_sink {
+                                use futures_util::SinkExt;
+                                let mut messages = turn.replay_messages();
+                                sync_pin_message(&mut messages, session_meta.pinned.as_deref());
+                                let frame = ClientFrame {
+                                    frame_type: ClientFrameType::Chat,
+                                    payload: ClientPayload::Chat { messages },
+                                };
+                                if let Ok(data) = serialize_frame(&frame) {
+                                    let _ = sink
+                                        .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                        .await;
+                                }
+                            }
+                        }
+                        CommandAction::DiscardSession => {
+                            if let Some(ref j) = journal {
+                                let _ = j.clear();
+                            }
+                        }
+                        CommandAction::PinTopic(None) => {
+                            let info = match &session_meta.pinned {
+                                Some(note) => tf("pin.current", &[note.as_str()]),
+                                None => t("pin.none").to_string(),
+                            };
+                            let _ = gw_tx.send(GwEvent::Info(info));
+                        }
+                        CommandAction::PinTopic(Some(note)) => {
+                            session_meta.pin(&note);
+                            match session_meta.save(&sessions_dir) {
+                                Ok(()) => {
+                                    let _ = gw_tx.send(GwEvent::Success(
+                                        t("pin.pinned").into(),
+                                    ));
+                                }
+                                Err(e) => {
+                                    let _ = gw_tx.send(GwEvent::Warning(format!("Pin not saved: {}", e)));
+                                }
+                            }
+                            let _ = gw_tx.send(GwEvent::SessionMeta(session_meta.clone()));
+                        }
+                        CommandAction::SplitView(target) => {
+                            if let Some(ref mut sink) = ws_sink {
+                                use futures_util::SinkExt;
+                                let frame = ClientFrame {
+                                    frame_type: ClientFrameType::WatchSession,
+                                    payload: ClientPayload::WatchSession { target },
+                                };
+                                if let Ok(data) = serialize_frame(&frame) {
+                                    let _ = sink
+                                        .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                        .await;
+                                }
+                            } else {
+                                let _ = gw_tx.send(GwEvent::Warning(t("split.needs_gateway").into()));
+                            }
+                        }
+                        CommandAction::UnpinTopic => {
+                            session_meta.pin("");
+                            let _ = session_meta.save(&sessions_dir);
+                            let _ = gw_tx.send(GwEvent::Info(t("pin.removed").into()));
+                            let _ = gw_tx.send(GwEvent::SessionMeta(session_meta.clone()));
+                        }
+                        CommandAction::SetDryRun(enabled) => {
+                            if let Some(ref mut sink) = ws_sink {
+                                use futures_util::SinkExt;
+                                let frame = ClientFrame {
+                                    frame_type: ClientFrameType::SetDryRun,
+                                    payload: ClientPayload::SetDryRun { enabled },
+                                };
+                                if let Ok(data) = serialize_frame(&frame) {
+                                    let _ = sink
+                                        .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                        .await;
+                                }
+                            }
+                        }
+                        CommandAction::GatewayReload => {
+                            // Ask the gateway to re-read config.toml.
+                            if let Some(ref mut sink) = ws_sink {
+                                use futures_util::SinkExt;
+                                let frame = ClientFrame {
+                                    frame_type: ClientFrameType::Reload,
+                                    payload: ClientPayload::Reload,
+                                };
+                                if let Ok(data) = serialize_frame(&frame) {
+                                    let _ = sink
+                                        .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                        .await;
+                                }
+                            }
+                        }
                         CommandAction::ShowSecrets => {
                             // Request secrets list from the gateway daemon
                             // (secrets live in the gateway's vault, not locally).
@@ -468,6 +670,30 @@ impl App {
                             }).collect();
                             let _ = gw_tx.send(GwEvent::ShowToolPerms { tools });
                         }
+                        CommandAction::ShowWorkspacePicker => {
+                            let _ = gw_tx.send(GwEvent::ShowWorkspaces {
+                                workspaces: workspace_list(config),
+                            });
+                        }
+                        CommandAction::ShowNotifications => {
+                            let _ = gw_tx.send(GwEvent::ShowNotifications);
+                        }
+                        CommandAction::ShowEvents { limit, level } => {
+                            if let Some(ref mut sink) = ws_sink {
+                                use futures_util::SinkExt;
+                                let frame = ClientFrame {
+                                    frame_type: ClientFrameType::EventsTail,
+                                    payload: ClientPayload::EventsTail { limit, level },
+                                };
+                                if let Ok(data) = serialize_frame(&frame) {
+                                    let _ = sink
+                                        .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                        .await;
+                                }
+                            } else {
+                                let _ = gw_tx.send(GwEvent::Warning(t("events.needs_gateway").into()));
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -509,6 +735,36 @@ impl App {
                     }).collect();
                     let _ = gw_tx.send(GwEvent::ShowToolPerms { tools });
                 }
+                Ok(UserInput::SwitchWorkspace { path }) => {
+                    match config.set_workspace(std::path::Path::new(&path)) {
+                        Ok(ws) => {
+                            let _ = config.save(None);
+                            let _ = gw_tx.send(GwEvent::Info(format!(
+                                "Workspace switched to {}",
+                                ws.display()
+                            )));
+                            // The gateway resolves the workspace from config.toml.
+                            if let Some(ref mut sink) = ws_sink {
+                                use futures_util::SinkExt;
+                                let frame = ClientFrame {
+                                    frame_type: ClientFrameType::Reload,
+                                    payload: ClientPayload::Reload,
+                                };
+                                if let Ok(data) = serialize_frame(&frame) {
+                                    let _ = sink
+                                        .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                        .await;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            let _ = gw_tx.send(GwEvent::Error(format!(
+                                "Cannot switch workspace: {}",
+                                e
+                            )));
+                        }
+                    }
+                }
                 Ok(UserInput::CycleSecretPolicy { name, current_policy }) => {
                     // Cycle OPEN → ASK → AUTH → SKILL → OPEN
                     let next_policy = match current_policy.as_str() {
@@ -577,6 +833,25 @@ impl App {
                         }
                     }
                 }
+                Ok(UserInput::Negotiate { compress }) => {
+                    if let Some(ref mut sink) = ws_sink {
+                        use futures_util::SinkExt;
+                        if compress {
+                            sink.set_threshold(config.compression());
+                        }
+                        if let Ok(data) = serialize_frame(&gateway_client::negotiate_frame()) {
+                            let _ = sink
+                                .send(tokio_tungstenite::tungstenite::Message::Binary(data.into()))
+                                .await;
+                        }
+                    }
+                }
+                Ok(UserInput::OpenPalette) => {
+                    let mut entries = crate::palette::commands();
+                    entries.extend(crate::palette::sessions(&config.workspace_dir()));
+                    entries.extend(crate::palette::skills(skill_manager.get_skills()));
+                    let _ = gw_tx.send(GwEvent::ShowPalette { entries });
+                }
                 Ok(UserInput::Quit) => break,
                 Err(sync_mpsc::TryRecvError::Empty) => {}
                 Err(sync_mpsc::TryRecvError::Disconnected) => break,
@@ -594,6 +869,19 @@ impl App {
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
+/// Current workspace followed by the recently used ones that still exist.
+fn workspace_list(config: &Config) -> Vec<crate::components::workspace_dialog::WorkspaceInfo> {
+    let current = config.workspace_dir();
+    std::iter::once(current.clone())
+        .chain(config.recent_workspaces.iter().filter(|p| p.is_dir()).cloned())
+        .map(|path| crate::components::workspace_dialog::WorkspaceInfo {
+            current: path == current,
+            has_overlay: path.join(rustyclaw_core::project::PROJECT_DIR).is_dir(),
+            path: path.display().to_string(),
+        })
+        .collect()
+}
+
 /// Map an Action enum value to a GwEvent.
 ///
 /// Every Action that `server_frame_to_action()` can produce MUST be handled
@@ -640,6 +928,22 @@ fn action_to_gw_event(action: &crate::action::Action) -> Option<GwEvent> {
             Some(GwEvent::UserPromptRequest(prompt.clone()))
         }
 
+        // ── Plan checklist ──────────────────────────────────────────────
+        Action::PlanUpdate(plan) => Some(GwEvent::PlanUpdate(plan.clone())),
+
+        // ── Header stats ────────────────────────────────────────────────
+        Action::GatewayStats(stats) => Some(GwEvent::Stats(stats.clone())),
+
+        // ── Split pane ──────────────────────────────────────────────────
+        Action::SessionView(view) => Some(GwEvent::SessionView(view.clone())),
+        Action::Progress(ops) => Some(GwEvent::Progress(ops.clone())),
+
+        // ── Notification center ─────────────────────────────────────────
+        Action::Notification(n) => Some(GwEvent::Notification(n.clone())),
+
+        // ── Usage bar ───────────────────────────────────────────────────
+        Action::Usage(u) => Some(GwEvent::Usage(u.clone())),
+
         // ── Generic messages ────────────────────────────────────────────
         Action::Info(s) => Some(GwEvent::Info(s.clone())),
         Action::Success(s) => Some(GwEvent::Success(s.clone())),
@@ -797,6 +1101,19 @@ mod tui_component {
         pub soul_name: String,
         pub model_label: String,
         pub hint: String,
+        /// Vim-style modal editing in the input bar.
+        pub vim_mode: bool,
+    }
+
+    /// Slash-command completions for the current input.
+    fn completions_for(value: &str) -> Vec<String> {
+        let Some(partial) = value.strip_prefix('/') else {
+            return Vec::new();
+        };
+        rustyclaw_core::commands::command_names()
+            .into_iter()
+            .filter(|c: &String| c.starts_with(partial))
+            .collect()
     }
 
     // ── Static channels ─────────────────────────────────────────────────
@@ -821,6 +1138,22 @@ mod tui_component {
         let mut spinner_tick = hooks.use_state(|| 0usize);
         let mut should_quit = hooks.use_state(|| false);
         let mut streaming_buf = hooks.use_state(|| String::new());
+        let mut plan: State<Option<rustyclaw_core::plan::Plan>> = hooks.use_state(|| None);
+        let mut gw_stats: State<Option<rustyclaw_core::gateway::stats::GatewayStats>> = hooks.use_state(|| None);
+        let mut session_usage: State<crate::types::SessionUsage> = hooks.use_state(Default::default);
+        let mut session_meta: State<SessionMeta> = hooks.use_state(SessionMeta::default);
+        let mut side_view: State<Option<rustyclaw_core::gateway::session_view::SessionView>> = hooks.use_state(|| None);
+        let mut progress_ops: State<Vec<rustyclaw_core::progress::ProgressOp>> = hooks.use_state(Vec::new);
+        let mut side_focused = hooks.use_state(|| false);
+        let mut side_scroll = hooks.use_state(|| 0i32);
+        let vim_enabled = props.vim_mode;
+        let mut vim: State<crate::vim::VimEditor> = hooks.use_state(crate::vim::VimEditor::default);
+
+        // ── Pastes (see paste.rs) ───────────────────────────────────────
+        let mut paste: State<crate::paste::PasteDetector> = hooks.use_state(Default::default);
+        let mut pastes: State<Vec<String>> = hooks.use_state(Vec::new);
+        let mut pastes_expanded = hooks.use_state(|| false);
+        let mut paste_confirm = hooks.use_state(|| false);
 
         // ── Auth dialog state ───────────────────────────────────────────
         let mut show_auth_dialog = hooks.use_state(|| false);
@@ -849,6 +1182,8 @@ mod tui_component {
         // ── Command menu (slash-command completions) ────────────────────
         let mut command_completions: State<Vec<String>> = hooks.use_state(Vec::new);
         let mut command_selected: State<Option<usize>> = hooks.use_state(|| None);
+        // Input holding dropped file paths the user was offered to attach
+        let mut pending_attach: State<Option<String>> = hooks.use_state(|| None);
 
         // ── Info dialog state (secrets / skills / tool permissions) ──────
         let mut show_secrets_dialog = hooks.use_state(|| false);
@@ -873,9 +1208,28 @@ mod tui_component {
             hooks.use_state(Vec::new);
         let mut tool_perms_selected: State<Option<usize>> = hooks.use_state(|| Some(0));
 
+        let mut show_workspace_dialog = hooks.use_state(|| false);
+        let mut workspace_dialog_data: State<Vec<crate::components::workspace_dialog::WorkspaceInfo>> =
+            hooks.use_state(Vec::new);
+        let mut workspace_selected: State<Option<usize>> = hooks.use_state(|| Some(0));
+
         // Scroll offsets for interactive dialogs
         let mut skills_scroll_offset = hooks.use_state(|| 0usize);
         let mut tool_perms_scroll_offset = hooks.use_state(|| 0usize);
+        let mut workspace_scroll_offset = hooks.use_state(|| 0usize);
+
+        // ── Command palette ─────────────────────────────────────────────
+        let mut show_palette = hooks.use_state(|| false);
+        let mut palette_entries: State<Vec<crate::palette::PaletteEntry>> = hooks.use_state(Vec::new);
+        let mut palette_query = hooks.use_state(|| String::new());
+        let mut palette_selected = hooks.use_state(|| 0usize);
+        // Files the agent recently touched, most recent first
+        let mut recent_files: State<Vec<String>> = hooks.use_state(Vec::new);
+
+        // ── Notification center ─────────────────────────────────────────
+        let mut notifications: State<Vec<crate::types::NotificationItem>> = hooks.use_state(Vec::new);
+        let mut show_notifications = hooks.use_state(|| false);
+        let mut notif_selected = hooks.use_state(|| 0usize);
 
         // ── Channel access ──────────────────────────────────────────────
         let gw_rx: Arc<StdMutex<Option<sync_mpsc::Receiver<GwEvent>>>> = hooks.use_const(|| {
@@ -910,6 +1264,7 @@ mod tui_component {
                                     GwEvent::Disconnected(reason) => {
                                         gw_status.set(rustyclaw_core::types::GatewayStatus::Disconnected);
                                         show_auth_dialog.set(false);
+                                        progress_ops.set(Vec::new());
                                         let mut m = messages.read().clone();
                                         m.push(DisplayMessage::warning(format!("Disconnected: {}", reason)));
                                         messages.set(m);
@@ -953,6 +1308,45 @@ mod tui_component {
                                         m.push(DisplayMessage::error(s));
                                         messages.set(m);
                                     }
+                                    GwEvent::SessionRestored { messages: restored } => {
+                                        let mut m = messages.read().clone();
+                                        for msg in &restored {
+                                            match msg.role.as_str() {
+                                                "user" => m.push(DisplayMessage::user(&msg.content)),
+                                                "assistant" if !msg.content.is_empty() => {
+                                                    m.push(DisplayMessage::assistant(&msg.content))
+                                                }
+                                                _ => {}
+                                            }
+                                        }
+                                        messages.set(m);
+                                        // The replayed turn is now in flight.
+                                        streaming.set(true);
+                                        stream_start.set(Some(Instant::now()));
+                                        streaming_buf.set(String::new());
+                                    }
+                                    GwEvent::PlanUpdate(p) => {
+                                        plan.set(p);
+                                    }
+                                    GwEvent::Stats(s) => {
+                                        gw_stats.set(Some(s));
+                                    }
+                                    GwEvent::Usage(u) => {
+                                        session_usage.write().record(&u);
+                                    }
+                                    GwEvent::SessionMeta(meta) => {
+                                        session_meta.set(meta);
+                                    }
+                                    GwEvent::SessionView(view) => {
+                                        if view.is_none() {
+                                            side_focused.set(false);
+                                            side_scroll.set(0);
+                                        }
+                                        side_view.set(view);
+                                    }
+                                    GwEvent::Progress(ops) => {
+                                        progress_ops.set(ops);
+                                    }
                                     GwEvent::StreamStart => {
                                         streaming.set(true);
                                         // Keep the earlier start time if we already
@@ -1020,6 +1414,11 @@ mod tui_component {
                                         messages.set(m);
                                     }
                                     GwEvent::ToolCall { name, arguments } => {
+                                        if let Some(path) = crate::palette::tool_call_path(&arguments) {
+                                            let mut r = recent_files.read().clone();
+                                            crate::palette::remember_file(&mut r, path);
+                                            recent_files.set(r);
+                                        }
                                         let msg = if name == "ask_user" {
                                             // Don't show raw JSON args for ask_user — the dialog handles it
                                             format!("🔧 {} — preparing question…", name)
@@ -1035,14 +1434,24 @@ mod tui_component {
                                         m.push(DisplayMessage::tool_call(msg));
                                         messages.set(m);
                                     }
+                                    GwEvent::MediaReceived { name, path } => {
+                                        let image = crate::image_preview::load(&path);
+                                        let text = tf("media.received", &[&name, &path.display().to_string()]);
+                                        let mut m = messages.read().clone();
+                                        m.push(DisplayMessage::info(text).with_image(image));
+                                        messages.set(m);
+                                    }
                                     GwEvent::ToolResult { result } => {
+                                        let image = crate::image_preview::media_images(&result)
+                                            .first()
+                                            .and_then(|path| crate::image_preview::load(path));
                                         let preview = if result.len() > 200 {
                                             format!("{}…", &result[..200])
                                         } else {
                                             result
                                         };
                                         let mut m = messages.read().clone();
-                                        m.push(DisplayMessage::tool_result(preview));
+                                        m.push(DisplayMessage::tool_result(preview).with_image(image));
                                         messages.set(m);
                                     }
                                     GwEvent::ToolApprovalRequest { id, name, arguments } => {
@@ -1129,6 +1538,40 @@ mod tui_component {
                                         }
                                         show_tool_perms_dialog.set(true);
                                     }
+                                    GwEvent::ShowWorkspaces { workspaces } => {
+                                        workspace_dialog_data.set(workspaces);
+                                        workspace_selected.set(Some(0));
+                                        workspace_scroll_offset.set(0);
+                                        show_workspace_dialog.set(true);
+                                    }
+                                    GwEvent::ShowPalette { mut entries } => {
+                                        // Recent files go right after the commands
+                                        let at = entries
+                                            .iter()
+                                            .take_while(|e| e.kind == crate::palette::EntryKind::Command)
+                                            .count();
+                                        entries.splice(at..at, crate::palette::files(&recent_files.read()));
+                                        palette_entries.set(entries);
+                                        palette_query.set(String::new());
+                                        palette_selected.set(0);
+                                        show_palette.set(true);
+                                    }
+                                    GwEvent::Notification(n) => {
+                                        crate::types::push_notification(&mut notifications.write(), n);
+                                        if show_notifications.get() {
+                                            // Already looking at the list
+                                            for item in notifications.write().iter_mut() {
+                                                item.read = true;
+                                            }
+                                        }
+                                    }
+                                    GwEvent::ShowNotifications => {
+                                        for item in notifications.write().iter_mut() {
+                                            item.read = true;
+                                        }
+                                        notif_selected.set(0);
+                                        show_notifications.set(true);
+                                    }
                                     GwEvent::RefreshSecrets => {
                                         // Gateway mutation succeeded — re-fetch list
                                         if let Ok(guard) = tx_for_history.lock() {
@@ -1142,6 +1585,20 @@ mod tui_component {
                         }
                     }
 
+                    // Settle a paste once its keys stop arriving
+                    let finished = paste.write().poll(Instant::now());
+                    if let Some(text) = finished {
+                        let (value, attached) = crate::paste::settle(&input_value.to_string(), text);
+                        if let Some(attached) = attached {
+                            let mut p = pastes.read().clone();
+                            p.push(attached);
+                            pastes.set(p);
+                        }
+                        vim.write().move_to_end(&value);
+                        command_completions.set(completions_for(&value));
+                        input_value.set(value);
+                    }
+
                     // Update spinner and elapsed timer
                     spinner_tick.set(spinner_tick.get().wrapping_add(1));
                     if let Some(start) = stream_start.get() {
@@ -1181,7 +1638,7 @@ mod tui_component {
                                 auth_code.set(String::new());
                                 auth_error.set(String::new());
                                 let mut m = messages.read().clone();
-                                m.push(DisplayMessage::info("Authentication cancelled."));
+                                m.push(DisplayMessage::info(t("auth.cancelled")));
                                 messages.set(m);
                                 gw_status.set(rustyclaw_core::types::GatewayStatus::Disconnected);
                             }
@@ -1313,7 +1770,7 @@ mod tui_component {
                                 vault_password.set(String::new());
                                 vault_error.set(String::new());
                                 let mut m = messages.read().clone();
-                                m.push(DisplayMessage::info("Vault unlock cancelled."));
+                                m.push(DisplayMessage::info(t("vault.unlock_cancelled")));
                                 messages.set(m);
                             }
                             KeyCode::Char(c) => {
@@ -1510,6 +1967,168 @@ mod tui_component {
                         }
                         return;
                     }
+                    if show_notifications.get() {
+                        let count = notifications.read().len();
+                        match code {
+                            KeyCode::Esc => {
+                                show_notifications.set(false);
+                            }
+                            KeyCode::Up => {
+                                notif_selected.set(notif_selected.get().saturating_sub(1));
+                            }
+                            KeyCode::Down => {
+                                if notif_selected.get() + 1 < count {
+                                    notif_selected.set(notif_selected.get() + 1);
+                                }
+                            }
+                            KeyCode::Char('d') | KeyCode::Delete => {
+                                let sel = notif_selected.get();
+                                if sel < count {
+                                    notifications.write().remove(sel);
+                                    notif_selected.set(sel.min(count.saturating_sub(2)));
+                                }
+                            }
+                            KeyCode::Char('c') => {
+                                notifications.set(Vec::new());
+                                notif_selected.set(0);
+                            }
+                            KeyCode::Enter => {
+                                // Watch the sub-agent/heartbeat session or cron job in the split pane
+                                let target = notifications.read().get(notif_selected.get()).and_then(|item| {
+                                    let n = &item.notification;
+                                    let kind = match n.source {
+                                        rustyclaw_core::notifications::Source::Subagent
+                                        | rustyclaw_core::notifications::Source::Heartbeat => "session",
+                                        rustyclaw_core::notifications::Source::Cron => "cron",
+                                        _ => return None,
+                                    };
+                                    n.session.as_ref().map(|key| format!("split {} {}", kind, key))
+                                });
+                                if let Some(cmd) = target {
+                                    if let Ok(guard) = tx_for_keys.lock() {
+                                        if let Some(ref tx) = *guard {
+                                            let _ = tx.send(UserInput::Command(cmd));
+                                        }
+                                    }
+                                    show_notifications.set(false);
+                                }
+                            }
+                            _ => {}
+                        }
+                        return;
+                    }
+                    if show_palette.get() {
+                        let matches = crate::palette::filter(&palette_entries.read(), &palette_query.to_string());
+                        match code {
+                            KeyCode::Esc => {
+                                show_palette.set(false);
+                            }
+                            KeyCode::Up => {
+                                let cur = palette_selected.get();
+                                if !matches.is_empty() {
+                                    palette_selected.set(if cur == 0 { matches.len() - 1 } else { cur - 1 });
+                                }
+                            }
+                            KeyCode::Down => {
+                                if !matches.is_empty() {
+                                    palette_selected.set((palette_selected.get() + 1) % matches.len());
+                                }
+                            }
+                            KeyCode::Backspace => {
+                                let mut q = palette_query.to_string();
+                                q.pop();
+                                palette_query.set(q);
+                                palette_selected.set(0);
+                            }
+                            KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                                palette_query.set(format!("{}{}", palette_query, c));
+                                palette_selected.set(0);
+                            }
+                            KeyCode::Enter => {
+                                let picked = matches
+                                    .get(palette_selected.get())
+                                    .and_then(|&i| palette_entries.read().get(i).cloned());
+                                show_palette.set(false);
+                                match picked.map(|e| e.action) {
+                                    Some(crate::palette::PaletteAction::Run(cmd)) => {
+                                        if let Ok(guard) = tx_for_keys.lock() {
+                                            if let Some(ref tx) = *guard {
+                                                let _ = tx.send(UserInput::Command(cmd));
+                                            }
+                                        }
+                                    }
+                                    Some(crate::palette::PaletteAction::Insert(text)) => {
+                                        let current = input_value.to_string();
+                                        let value = if current.is_empty() || current.ends_with(' ') {
+                                            format!("{}{}", current, text)
+                                        } else {
+                                            format!("{} {}", current, text)
+                                        };
+                                        vim.write().move_to_end(&value);
+                                        input_value.set(value);
+                                    }
+                                    None => {}
+                                }
+                            }
+                            _ => {}
+                        }
+                        return;
+                    }
+                    if show_workspace_dialog.get() {
+                        const VISIBLE_ROWS: usize = 20;
+                        match code {
+                            KeyCode::Esc => {
+                                show_workspace_dialog.set(false);
+                            }
+                            KeyCode::Up => {
+                                let cur = workspace_selected.get().unwrap_or(0);
+                                let len = workspace_dialog_data.read().len();
+                                if len > 0 {
+                                    let next = if cur == 0 { len - 1 } else { cur - 1 };
+                                    workspace_selected.set(Some(next));
+                                    let so = workspace_scroll_offset.get();
+                                    if next < so {
+                                        workspace_scroll_offset.set(next);
+                                    } else if next >= so + VISIBLE_ROWS {
+                                        workspace_scroll_offset.set(next.saturating_sub(VISIBLE_ROWS - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Down => {
+                                let cur = workspace_selected.get().unwrap_or(0);
+                                let len = workspace_dialog_data.read().len();
+                                if len > 0 {
+                                    let next = (cur + 1) % len;
+                                    workspace_selected.set(Some(next));
+                                    let so = workspace_scroll_offset.get();
+                                    if next < so {
+                                        workspace_scroll_offset.set(next);
+                                    } else if next >= so + VISIBLE_ROWS {
+                                        workspace_scroll_offset.set(next.saturating_sub(VISIBLE_ROWS - 1));
+                                    }
+                                }
+                            }
+                            KeyCode::Enter => {
+                                let idx = workspace_selected.get().unwrap_or(0);
+                                let data = workspace_dialog_data.read();
+                                if let Some(ws) = data.get(idx) {
+                                    let path = ws.path.clone();
+                                    let current = ws.current;
+                                    drop(data);
+                                    show_workspace_dialog.set(false);
+                                    if !current {
+                                        if let Ok(guard) = tx_for_keys.lock() {
+                                            if let Some(ref tx) = *guard {
+                                                let _ = tx.send(UserInput::SwitchWorkspace { path });
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                        return;
+                    }
                     if show_secrets_dialog.get() {
                         const VISIBLE_ROWS: usize = 20;
                         // Add-secret inline input mode
@@ -1642,10 +2261,47 @@ mod tui_component {
                         return;
                     }
 
+                    // Keys arriving in a burst are a paste, not typing.
+                    let typed = match code {
+                        KeyCode::Char(c) if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Some(c),
+                        KeyCode::Enter => Some('\n'),
+                        KeyCode::Tab => Some('\t'),
+                        _ => None,
+                    };
+                    if typed.is_some_and(|c| paste.write().key(c, Instant::now())) {
+                        return;
+                    }
+
                     // Command menu intercepts when visible
                     let menu_open = !command_completions.read().is_empty();
+                    let vim_inserting = vim_enabled && vim.read().mode == crate::vim::Mode::Insert;
 
                     match code {
+                        KeyCode::Char('k') if modifiers.contains(KeyModifiers::CONTROL) => {
+                            // Open the command palette
+                            if let Ok(guard) = tx_for_keys.lock() {
+                                if let Some(ref tx) = *guard {
+                                    let _ = tx.send(UserInput::OpenPalette);
+                                }
+                            }
+                        }
+                        KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
+                            // Open the notification center
+                            for item in notifications.write().iter_mut() {
+                                item.read = true;
+                            }
+                            notif_selected.set(0);
+                            show_notifications.set(true);
+                        }
+                        KeyCode::Char('o') if modifiers.contains(KeyModifiers::CONTROL) => {
+                            // Expand / collapse pasted text in the chat
+                            pastes_expanded.set(!pastes_expanded.get());
+                        }
+                        KeyCode::Esc if !menu_open && !vim_inserting && !pastes.read().is_empty() => {
+                            // Discard the pending pastes
+                            pastes.set(Vec::new());
+                            paste_confirm.set(false);
+                        }
                         KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
                             should_quit.set(true);
                             if let Ok(guard) = tx_for_keys.lock() {
@@ -1665,6 +2321,7 @@ mod tui_component {
                             // Apply the selected completion into the input
                             if let Some(cmd) = completions.get(new_idx) {
                                 input_value.set(format!("/{}", cmd));
+                                vim.write().move_to_end(&format!("/{}", cmd));
                             }
                         }
                         KeyCode::BackTab if menu_open => {
@@ -1677,6 +2334,7 @@ mod tui_component {
                             command_selected.set(Some(new_idx));
                             if let Some(cmd) = completions.get(new_idx) {
                                 input_value.set(format!("/{}", cmd));
+                                vim.write().move_to_end(&format!("/{}", cmd));
                             }
                         }
                         KeyCode::Up if menu_open => {
@@ -1689,6 +2347,7 @@ mod tui_component {
                             command_selected.set(Some(new_idx));
                             if let Some(cmd) = completions.get(new_idx) {
                                 input_value.set(format!("/{}", cmd));
+                                vim.write().move_to_end(&format!("/{}", cmd));
                             }
                         }
                         KeyCode::Down if menu_open => {
@@ -1701,6 +2360,7 @@ mod tui_component {
                             command_selected.set(Some(new_idx));
                             if let Some(cmd) = completions.get(new_idx) {
                                 input_value.set(format!("/{}", cmd));
+                                vim.write().move_to_end(&format!("/{}", cmd));
                             }
                         }
                         KeyCode::Esc if menu_open => {
@@ -1710,8 +2370,69 @@ mod tui_component {
                         }
                         KeyCode::Enter => {
                             let val = input_value.to_string();
-                            if !val.is_empty() {
+                            let dropped = crate::attach::dropped_paths(&val);
+                            if let Some(ref paths) = dropped {
+                                if pending_attach.read().as_deref() != Some(val.as_str()) {
+                                    // First Enter on a bare path: offer to attach
+                                    // it instead of sending the path string.
+                                    pending_attach.set(Some(val.clone()));
+                                    let mut m = messages.read().clone();
+                                    m.push(DisplayMessage::info(tf(
+                                        "attach.offer",
+                                        &[crate::attach::describe(paths).as_str()],
+                                    )));
+                                    messages.set(m);
+                                    scroll_offset.set(0);
+                                    return;
+                                }
+                            }
+                            pending_attach.set(None);
+                            let pasted = pastes.read().clone();
+                            let pasted_chars = crate::paste::total_chars(&pasted);
+                            if pasted_chars > crate::paste::CONFIRM_CHARS && !paste_confirm.get() {
+                                // Large paste: make sure before sending it
+                                paste_confirm.set(true);
+                                let mut m = messages.read().clone();
+                                m.push(DisplayMessage::warning(tf(
+                                    "paste.confirm",
+                                    &[crate::paste::label(pasted_chars).as_str()],
+                                )));
+                                messages.set(m);
+                                scroll_offset.set(0);
+                                return;
+                            }
+                            if let Some(paths) = dropped {
+                                input_value.set(String::new());
+                                vim.write().reset();
+                                scroll_offset.set(0);
+                                let mut m = messages.read().clone();
+                                match crate::attach::read_context(&paths) {
+                                    Ok(context) => {
+                                        let mut r = recent_files.read().clone();
+                                        for path in &paths {
+                                            crate::palette::remember_file(&mut r, path.display().to_string());
+                                        }
+                                        recent_files.set(r);
+                                        m.push(DisplayMessage::user(format!(
+                                            "📎 {}",
+                                            crate::attach::describe(&paths)
+                                        )));
+                                        if let Ok(guard) = tx_for_keys.lock() {
+                                            if let Some(ref tx) = *guard {
+                                                streaming.set(true);
+                                                stream_start.set(Some(Instant::now()));
+                                                let _ = tx.send(UserInput::Chat(context));
+                                            }
+                                        }
+                                    }
+                                    Err(e) => m.push(DisplayMessage::error(tf("attach.failed", &[e.as_str()]))),
+                                }
+                                messages.set(m);
+                            } else if !val.is_empty() || !pasted.is_empty() {
                                 input_value.set(String::new());
+                                pastes.set(Vec::new());
+                                paste_confirm.set(false);
+                                vim.write().reset();
                                 // Close command menu
                                 command_completions.set(Vec::new());
                                 command_selected.set(None);
@@ -1719,30 +2440,49 @@ mod tui_component {
                                 scroll_offset.set(0);
                                 if let Ok(guard) = tx_for_keys.lock() {
                                     if let Some(ref tx) = *guard {
-                                        if val.starts_with('/') {
+                                        if val.starts_with('/') && pasted.is_empty() {
                                             let _ = tx.send(UserInput::Command(
                                                 val.trim_start_matches('/').to_string(),
                                             ));
                                         } else {
                                             let mut m = messages.read().clone();
-                                            m.push(DisplayMessage::user(&val));
+                                            m.push(DisplayMessage::user(&val).with_pastes(pasted.clone()));
                                             messages.set(m);
                                             // Start the spinner immediately so the user
                                             // sees feedback while waiting for the model.
                                             streaming.set(true);
                                             stream_start.set(Some(Instant::now()));
-                                            let _ = tx.send(UserInput::Chat(val));
+                                            let _ = tx.send(UserInput::Chat(crate::paste::compose(&val, &pasted)));
                                         }
                                     }
                                 }
                             }
                         }
+                        KeyCode::Tab if side_view.read().is_some() => {
+                            // Move focus (and scrolling) between chat and split pane
+                            side_focused.set(!side_focused.get());
+                        }
+                        KeyCode::Up if side_focused.get() => {
+                            side_scroll.set(side_scroll.get() + 1);
+                        }
+                        KeyCode::Down if side_focused.get() => {
+                            side_scroll.set((side_scroll.get() - 1).max(0));
+                        }
                         KeyCode::Up => {
                             scroll_offset.set(scroll_offset.get() + 1);
                         }
                         KeyCode::Down => {
                             scroll_offset.set((scroll_offset.get() - 1).max(0));
                         }
+                        _ if vim_enabled => {
+                            let mut value = input_value.to_string();
+                            if vim.write().handle_key(&mut value, code, modifiers) {
+                                let completions = completions_for(&value);
+                                command_completions.set(completions);
+                                command_selected.set(None);
+                                input_value.set(value);
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -1776,41 +2516,44 @@ mod tui_component {
                 gateway_color: gw_color,
                 messages: messages.read().clone(),
                 scroll_offset: scroll_offset.get(),
+                plan: plan.read().clone(),
+                stats: gw_stats.read().clone(),
+                usage: Some(session_usage.read().clone()),
+                session_meta: session_meta.read().clone(),
+                side_view: side_view.read().clone(),
+                progress: progress_ops.read().clone(),
+                side_focused: side_focused.get(),
+                side_scroll: side_scroll.get(),
                 command_completions: command_completions.read().clone(),
                 command_selected: command_selected.get(),
                 input_value: input_value.to_string(),
+                pastes: pastes.read().iter().map(|p| crate::paste::label(p.chars().count())).collect::<Vec<_>>(),
+                pastes_expanded: pastes_expanded.get(),
                 input_has_focus: !show_auth_dialog.get()
                     && !show_tool_approval.get()
                     && !show_vault_unlock.get()
                     && !show_user_prompt.get()
                     && !show_secrets_dialog.get()
                     && !show_skills_dialog.get()
-                    && !show_tool_perms_dialog.get(),
+                    && !show_tool_perms_dialog.get()
+                    && !show_workspace_dialog.get()
+                    && !show_palette.get()
+                    && !show_notifications.get(),
                 on_change: move |new_val: String| {
-                    input_value.set(new_val.clone());
-                    // Update slash-command completions
-                    if let Some(partial) = new_val.strip_prefix('/') {
-                        let names = rustyclaw_core::commands::command_names();
-                        let filtered: Vec<String> = names
-                            .into_iter()
-                            .filter(|c: &String| c.starts_with(partial))
-                            .collect();
-                        if filtered.is_empty() {
-                            command_completions.set(Vec::new());
-                            command_selected.set(None);
-                        } else {
-                            command_completions.set(filtered);
-                            command_selected.set(None);
-                        }
-                    } else {
-                        command_completions.set(Vec::new());
+                    // Update slash-command completions (not per key of a paste)
+                    if !paste.read().active() {
+                        command_completions.set(completions_for(&new_val));
                         command_selected.set(None);
                     }
+                    input_value.set(new_val);
                 },
+                vim_mode: vim_enabled.then(|| vim.read().mode),
+                vim_cursor: vim.read().cursor,
+                vim_pending: vim.read().pending.clone(),
                 on_submit: move |_val: String| {
                     // Submit handled by Enter key above
                 },
-                task_text: if streaming.get() { "Streaming…".to_string() } else { "Idle".to_string() },
+                task_text: if streaming.get() { t("task.streaming").to_string() } else { t("task.idle").to_string() },
                 streaming: streaming.get(),
                 elapsed: elapsed.to_string(),
                 hint: props.hint.clone(),
@@ -1846,6 +2589,24 @@ mod tui_component {
                 tool_perms_data: tool_perms_dialog_data.read().clone(),
                 tool_perms_selected: tool_perms_selected.get(),
                 tool_perms_scroll_offset: tool_perms_scroll_offset.get(),
+                show_workspace_dialog: show_workspace_dialog.get(),
+                workspace_data: workspace_dialog_data.read().clone(),
+                workspace_selected: workspace_selected.get(),
+                workspace_scroll_offset: workspace_scroll_offset.get(),
+                show_palette: show_palette.get(),
+                palette_entries: {
+                    let entries = palette_entries.read();
+                    crate::palette::filter(&entries, &palette_query.to_string())
+                        .into_iter()
+                        .map(|i| entries[i].clone())
+                        .collect::<Vec<_>>()
+                },
+                palette_query: palette_query.to_string(),
+                palette_selected: palette_selected.get(),
+                show_notifications: show_notifications.get(),
+                notifications: notifications.read().clone(),
+                notifications_selected: notif_selected.get(),
+                unread_notifications: notifications.read().iter().filter(|n| !n.read).count(),
             )
         }
     }
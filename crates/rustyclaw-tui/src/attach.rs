@@ -0,0 +1,160 @@
+// ── Dropped / pasted file paths ─────────────────────────────────────────────
+//
+// Dragging a file onto the terminal (or pasting its path) types the path
+// into the input bar — usually shell-escaped or as a `file://` URL. When the
+// whole input is nothing but paths to existing files, the TUI offers to
+// attach them: the contents are read through `read_file` (which extracts
+// text from PDFs, office documents and images) and sent as context instead
+// of the bare path string.
+
+use crate::components::progress_bars::format_bytes;
+use rustyclaw_core::tools;
+use std::path::{Path, PathBuf};
+
+/// Files larger than this aren't attached.
+pub const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Extracted text beyond this is cut off before it's sent.
+const MAX_CONTEXT_BYTES: usize = 256 * 1024;
+
+/// The files named by `input`, if it consists only of absolute paths or
+/// `file://` URLs to existing files.
+pub fn dropped_paths(input: &str) -> Option<Vec<PathBuf>> {
+    let paths: Vec<PathBuf> = split_words(input)
+        .iter()
+        .map(|word| to_path(word))
+        .collect::<Option<_>>()?;
+    if paths.is_empty() || !paths.iter().all(|p| p.is_file()) {
+        return None;
+    }
+    Some(paths)
+}
+
+/// Split on unquoted whitespace, honouring quotes and the backslash
+/// escapes terminals add when a dropped path contains spaces.
+fn split_words(input: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = input.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (q, Some(open)) if q == open => quote = None,
+            ('\\', None) if chars.peek().is_some_and(|n| " ()[]{}'\"&;$!`".contains(*n)) => {
+                word.extend(chars.next());
+            }
+            (c, None) if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            (c, _) => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn to_path(word: &str) -> Option<PathBuf> {
+    if word.starts_with("file://") {
+        return url::Url::parse(word).ok()?.to_file_path().ok();
+    }
+    let path = PathBuf::from(word);
+    path.is_absolute().then_some(path)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Short description for the attach prompt, e.g. `notes.md (4.2 KB)`.
+pub fn describe(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| {
+            let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            format!("{} ({})", file_name(path), format_bytes(size))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Read `paths` into one chat message that carries their contents.
+pub fn read_context(paths: &[PathBuf]) -> Result<String, String> {
+    let mut message = String::new();
+    for path in paths {
+        let size = std::fs::metadata(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .len();
+        if size > MAX_FILE_BYTES {
+            return Err(format!(
+                "{} is {}; files over {} can't be attached",
+                file_name(path),
+                format_bytes(size),
+                format_bytes(MAX_FILE_BYTES),
+            ));
+        }
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        let args = serde_json::json!({ "path": path.display().to_string() });
+        let mut content = tools::execute_tool("read_file", &args, dir)?;
+        if content.len() > MAX_CONTEXT_BYTES {
+            let mut cut = MAX_CONTEXT_BYTES;
+            while !content.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            content.truncate(cut);
+            content.push_str("\n[… truncated]");
+        }
+        message.push_str(&format!(
+            "Attached file: {}\n```\n{}\n```\n\n",
+            path.display(),
+            content.trim_end(),
+        ));
+    }
+    Ok(message.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words_handles_escapes_and_quotes() {
+        assert_eq!(
+            split_words(r"/tmp/My\ Report\ (final).pdf '/tmp/other file.txt'"),
+            vec!["/tmp/My Report (final).pdf", "/tmp/other file.txt"],
+        );
+        // Backslashes that don't escape anything stay (Windows paths).
+        assert_eq!(split_words(r"C:\Users\me\a.txt"), vec![r"C:\Users\me\a.txt"]);
+    }
+
+    #[test]
+    fn test_file_urls_are_decoded() {
+        assert_eq!(to_path("file:///tmp/a%20b.txt"), Some(PathBuf::from("/tmp/a b.txt")));
+        assert_eq!(to_path("relative/path.txt"), None);
+    }
+
+    #[test]
+    fn test_dropped_paths_requires_existing_files() {
+        let dir = std::env::temp_dir().join(format!("rustyclaw-attach-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("dropped note.txt");
+        std::fs::write(&file, "hello\n").unwrap();
+
+        let escaped = file.display().to_string().replace(' ', "\\ ");
+        assert_eq!(dropped_paths(&escaped), Some(vec![file.clone()]));
+        assert_eq!(dropped_paths(&format!("summarise {}", escaped)), None);
+        assert_eq!(dropped_paths(&dir.display().to_string()), None);
+        assert_eq!(dropped_paths(""), None);
+
+        let context = read_context(&[file]).unwrap();
+        assert!(context.starts_with("Attached file: "));
+        assert!(context.contains("hello"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 // ── Auth dialog — TOTP code entry overlay ───────────────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
 
 #[derive(Default, Props)]
@@ -37,8 +38,8 @@ pub fn AuthDialog(props: &AuthDialogProps) -> impl Into<AnyElement<'static>> {
                 width: 48,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::ACCENT_BRIGHT,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -46,8 +47,8 @@ pub fn AuthDialog(props: &AuthDialogProps) -> impl Into<AnyElement<'static>> {
             ) {
                 // Title
                 Text(
-                    content: "🔑 Gateway Authentication",
-                    color: theme::ACCENT_BRIGHT,
+                    content: t("dialog.auth"),
+                    color: theme::accent_bright(),
                     weight: Weight::Bold,
                 )
 
@@ -57,7 +58,7 @@ pub fn AuthDialog(props: &AuthDialogProps) -> impl Into<AnyElement<'static>> {
                 // Label
                 Text(
                     content: "Enter your 6-digit TOTP code:",
-                    color: theme::TEXT,
+                    color: theme::text(),
                 )
 
                 // Spacer
@@ -70,7 +71,7 @@ pub fn AuthDialog(props: &AuthDialogProps) -> impl Into<AnyElement<'static>> {
                 ) {
                     Text(
                         content: format!("  {}  ", display),
-                        color: theme::ACCENT_BRIGHT,
+                        color: theme::accent_bright(),
                         weight: Weight::Bold,
                     )
                 }
@@ -79,7 +80,7 @@ pub fn AuthDialog(props: &AuthDialogProps) -> impl Into<AnyElement<'static>> {
                 #(if has_error {
                     element! {
                         View(margin_top: 1) {
-                            Text(content: props.error.clone(), color: theme::ERROR)
+                            Text(content: props.error.clone(), color: theme::error())
                         }
                     }.into_any()
                 } else {
@@ -94,7 +95,7 @@ pub fn AuthDialog(props: &AuthDialogProps) -> impl Into<AnyElement<'static>> {
                 // Hint
                 Text(
                     content: "Enter ↩ submit  ·  Esc cancel",
-                    color: theme::MUTED,
+                    color: theme::muted(),
                 )
             }
         }
@@ -28,13 +28,13 @@ pub fn CommandMenu(props: &CommandMenuProps) -> impl Into<AnyElement<'static>> {
             flex_direction: FlexDirection::Column,
             max_height: max_rows + 2, // rows + top/bottom border
             border_style: BorderStyle::Round,
-            border_color: theme::ACCENT,
-            background_color: theme::BG_SURFACE,
+            border_color: theme::accent(),
+            background_color: theme::bg_surface(),
         ) {
             #(props.completions.iter().enumerate().take(max_rows as usize).map(|(i, cmd)| {
                 let is_selected = props.selected == Some(i);
-                let bg = if is_selected { theme::ACCENT_DIM } else { theme::BG_SURFACE };
-                let fg = if is_selected { theme::ACCENT_BRIGHT } else { theme::TEXT };
+                let bg = if is_selected { theme::accent_dim() } else { theme::bg_surface() };
+                let fg = if is_selected { theme::accent_bright() } else { theme::text() };
                 element! {
                     View(
                         key: i as u64,
@@ -0,0 +1,125 @@
+// ── Command palette (Ctrl+K) ────────────────────────────────────────────────
+
+use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
+use crate::palette::PaletteEntry;
+use crate::theme;
+
+/// Rows of results shown at once.
+pub const VISIBLE_ROWS: usize = 12;
+
+#[derive(Default, Props)]
+pub struct CommandPaletteProps {
+    pub query: String,
+    /// Matching entries, best first.
+    pub entries: Vec<PaletteEntry>,
+    pub selected: usize,
+}
+
+#[component]
+pub fn CommandPalette(props: &CommandPaletteProps) -> impl Into<AnyElement<'static>> {
+    let skip = props.selected.saturating_sub(VISIBLE_ROWS - 1);
+
+    element! {
+        View(
+            width: 100pct,
+            height: 100pct,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+        ) {
+            View(
+                width: 70pct,
+                max_height: 80pct,
+                flex_direction: FlexDirection::Column,
+                border_style: BorderStyle::Round,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
+                padding_left: 2,
+                padding_right: 2,
+                padding_top: 1,
+                padding_bottom: 1,
+                overflow: Overflow::Hidden,
+            ) {
+                Text(
+                    content: t("dialog.palette"),
+                    color: theme::accent_bright(),
+                    weight: Weight::Bold,
+                )
+
+                View(height: 1)
+
+                // Query line
+                View(flex_direction: FlexDirection::Row) {
+                    Text(content: "❯ ", color: theme::accent_bright())
+                    #(if props.query.is_empty() {
+                        element! {
+                            Text(content: t("palette.placeholder"), color: theme::muted())
+                        }.into_any()
+                    } else {
+                        element! {
+                            Text(content: format!("{}▏", props.query), color: theme::text())
+                        }.into_any()
+                    })
+                }
+
+                View(height: 1)
+
+                // Results
+                View(
+                    flex_direction: FlexDirection::Column,
+                    width: 100pct,
+                    overflow: Overflow::Hidden,
+                ) {
+                    #(if props.entries.is_empty() {
+                        element! {
+                            Text(content: t("palette.empty"), color: theme::text_dim())
+                        }.into_any()
+                    } else {
+                        element! {
+                            View(flex_direction: FlexDirection::Column, width: 100pct) {
+                                #(props.entries.iter().enumerate().skip(skip).take(VISIBLE_ROWS).map(|(i, e)| {
+                                    let is_selected = i == props.selected;
+                                    let bg = if is_selected { Some(theme::accent_bright()) } else { None };
+                                    let pointer = if is_selected { "▸ " } else { "  " };
+                                    let fg = if is_selected { theme::bg_main() } else { theme::text() };
+                                    let dim = if is_selected { theme::bg_main() } else { theme::muted() };
+                                    element! {
+                                        View(
+                                            key: i as u64,
+                                            width: 100pct,
+                                            flex_direction: FlexDirection::Row,
+                                            background_color: bg.unwrap_or(Color::Reset),
+                                        ) {
+                                            Text(
+                                                content: format!("{}{} {}", pointer, e.kind.icon(), e.label),
+                                                color: fg,
+                                                wrap: TextWrap::NoWrap,
+                                            )
+                                            Text(
+                                                content: format!("  {}  {}", e.kind.label(), e.detail),
+                                                color: dim,
+                                                wrap: TextWrap::NoWrap,
+                                            )
+                                        }
+                                    }
+                                }))
+                            }
+                        }.into_any()
+                    })
+                }
+
+                View(height: 1)
+
+                // Hint
+                View(flex_direction: FlexDirection::Row) {
+                    Text(content: "↑↓ ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.navigate")), color: theme::muted())
+                    Text(content: "Enter ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.select")), color: theme::muted())
+                    Text(content: "Esc ", color: theme::accent_bright())
+                    Text(content: t("key.close"), color: theme::muted())
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,19 @@
 // ── Input bar ───────────────────────────────────────────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
+use crate::types::SessionUsage;
+use crate::vim::Mode;
+
+/// Context use (fraction of the window) above which the bar turns
+/// yellow, and red — the latter is where the gateway starts compacting.
+const CONTEXT_WARN: f64 = 0.60;
+const CONTEXT_HIGH: f64 = 0.75;
+
+/// Session cost (USD) above which the bar turns yellow, and red.
+const COST_WARN: f64 = 1.0;
+const COST_HIGH: f64 = 5.0;
 
 #[derive(Default, Props)]
 pub struct InputBarProps {
@@ -13,11 +25,71 @@ pub struct InputBarProps {
     pub gateway_color: Option<Color>,
     /// When false, the TextInput won't capture keystrokes (e.g. dialog open).
     pub has_focus: bool,
+    /// Vim editing mode; when set the line is drawn here with its own
+    /// cursor and the app feeds keys to the editor instead of TextInput.
+    pub vim_mode: Option<Mode>,
+    pub vim_cursor: usize,
+    pub vim_pending: String,
+    /// Labels of pastes that will be sent with the line.
+    pub pastes: Vec<String>,
+    /// Context and cost so far; hidden until the first model call.
+    pub usage: Option<SessionUsage>,
+}
+
+fn format_tokens(n: u64) -> String {
+    if n >= 1_000_000 {
+        format!("{:.1}M", n as f64 / 1_000_000.0)
+    } else if n >= 1_000 {
+        format!("{}k", n / 1_000)
+    } else {
+        n.to_string()
+    }
+}
+
+fn threshold_color(value: f64, warn: f64, high: f64) -> Color {
+    if value >= high {
+        theme::error()
+    } else if value >= warn {
+        theme::warn()
+    } else {
+        theme::muted()
+    }
+}
+
+/// Context label and colour, e.g. `42k/200k 21%`.
+fn context_label(usage: &SessionUsage) -> (String, Color) {
+    let fraction = usage.context_fraction();
+    let label = format!(
+        "{}/{} {:.0}%",
+        format_tokens(usage.context_tokens),
+        format_tokens(usage.context_window),
+        fraction * 100.0,
+    );
+    (label, threshold_color(fraction, CONTEXT_WARN, CONTEXT_HIGH))
+}
+
+/// Cost label and colour, e.g. `$0.14`; `None` when no call was priced.
+/// A trailing `+` means some calls went to models without a known price.
+fn cost_label(usage: &SessionUsage) -> Option<(String, Color)> {
+    if usage.unpriced_calls == usage.calls {
+        return None;
+    }
+    let more = if usage.unpriced_calls > 0 { "+" } else { "" };
+    let label = format!("${:.2}{}", usage.cost_usd, more);
+    Some((label, threshold_color(usage.cost_usd, COST_WARN, COST_HIGH)))
+}
+
+/// The input line split around the cursor: before, under, after.
+fn split_at_cursor(value: &str, cursor: usize) -> (String, String, String) {
+    let before: String = value.chars().take(cursor).collect();
+    let under = value.chars().nth(cursor).map(String::from).unwrap_or_else(|| " ".to_string());
+    let after: String = value.chars().skip(cursor + 1).collect();
+    (before, under, after)
 }
 
 #[component]
 pub fn InputBar(props: &mut InputBarProps) -> impl Into<AnyElement<'static>> {
-    let status_color = props.gateway_color.unwrap_or(theme::MUTED);
+    let status_color = props.gateway_color.unwrap_or(theme::muted());
 
     element! {
         View(
@@ -25,23 +97,71 @@ pub fn InputBar(props: &mut InputBarProps) -> impl Into<AnyElement<'static>> {
             height: 3,
             flex_direction: FlexDirection::Column,
             border_style: BorderStyle::Round,
-            border_color: theme::ACCENT,
+            border_color: theme::accent(),
             border_edges: Edges::Top,
         ) {
             View(width: 100pct, height: 1, flex_direction: FlexDirection::Row) {
-                Text(content: "❯ ", color: theme::ACCENT_BRIGHT, weight: Weight::Bold)
-                View(flex_grow: 1.0, height: 1, background_color: theme::BG_MAIN) {
-                    TextInput(
-                        has_focus: props.has_focus,
-                        value: props.value.clone(),
-                        on_change: props.on_change.take(),
-                        color: theme::TEXT,
-                    )
-                }
+                Text(content: "❯ ", color: theme::accent_bright(), weight: Weight::Bold)
+                #(if let Some(mode) = props.vim_mode {
+                    let (before, under, after) = split_at_cursor(&props.value, props.vim_cursor);
+                    let cursor_bg = match mode {
+                        Mode::Normal => theme::accent(),
+                        Mode::Insert => theme::text_dim(),
+                    };
+                    element! {
+                        View(flex_grow: 1.0, height: 1, flex_direction: FlexDirection::Row, background_color: theme::bg_main(), overflow: Overflow::Hidden) {
+                            Text(content: before, color: theme::text(), wrap: TextWrap::NoWrap)
+                            View(background_color: cursor_bg) {
+                                Text(content: under, color: theme::bg_main(), wrap: TextWrap::NoWrap)
+                            }
+                            Text(content: after, color: theme::text(), wrap: TextWrap::NoWrap)
+                        }
+                    }.into_any()
+                } else {
+                    element! {
+                        View(flex_grow: 1.0, height: 1, background_color: theme::bg_main()) {
+                            TextInput(
+                                has_focus: props.has_focus,
+                                value: props.value.clone(),
+                                on_change: props.on_change.take(),
+                                color: theme::text(),
+                            )
+                        }
+                    }.into_any()
+                })
+                #(props.vim_mode.map(|mode| element! {
+                    View(padding_left: 1) {
+                        Text(content: format!("{}{}", props.vim_pending, mode.label()), color: theme::accent())
+                    }
+                }))
+                #(props.usage.as_ref().filter(|u| u.calls > 0).map(|usage| {
+                    let (context, context_color) = context_label(usage);
+                    let cost = cost_label(usage);
+                    element! {
+                        View(padding_left: 1, flex_direction: FlexDirection::Row) {
+                            Text(content: format!("⛁ {}", context), color: context_color, wrap: TextWrap::NoWrap)
+                            #(cost.map(|(cost, cost_color)| element! {
+                                View(flex_direction: FlexDirection::Row) {
+                                    Text(content: " · ", color: theme::muted())
+                                    Text(content: cost, color: cost_color, wrap: TextWrap::NoWrap)
+                                }
+                            }))
+                        }
+                    }
+                }))
                 View(padding_left: 1) {
                     Text(content: format!("{} {}", props.gateway_icon, props.gateway_label), color: status_color)
                 }
             }
+            #((!props.pastes.is_empty()).then(|| element! {
+                View(width: 100pct, height: 1, padding_left: 2) {
+                    Text(
+                        content: format!("📋 {}  · {}", props.pastes.join(" "), t("paste.discard")),
+                        color: theme::muted(),
+                        wrap: TextWrap::NoWrap,
+                    )
+                }
+            }))
         }
     }
 }
@@ -1,13 +1,61 @@
 // ── Message bubble ──────────────────────────────────────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use rustyclaw_core::types::MessageRole;
+use std::sync::Arc;
+use crate::image_preview::Preview;
 use crate::theme;
 
 #[derive(Default, Props)]
 pub struct MessageBubbleProps {
     pub role: Option<MessageRole>,
     pub content: String,
+    pub image: Option<Arc<Preview>>,
+    pub pastes: Vec<String>,
+    pub pastes_expanded: bool,
+}
+
+/// Runs of identical half-block cells, merged so a row is a handful of
+/// views rather than one per cell.
+fn block_runs(row: &[crate::image_preview::BlockCell]) -> Vec<(crate::image_preview::BlockCell, usize)> {
+    let mut runs: Vec<(crate::image_preview::BlockCell, usize)> = Vec::new();
+    for cell in row {
+        match runs.last_mut() {
+            Some((last, n)) if last == cell => *n += 1,
+            _ => runs.push((*cell, 1)),
+        }
+    }
+    runs
+}
+
+fn image_element(preview: &Preview) -> AnyElement<'static> {
+    match preview {
+        Preview::Blocks(rows) => element! {
+            View(flex_direction: FlexDirection::Column, margin_top: 1) {
+                #(rows.iter().enumerate().map(|(r, row)| element! {
+                    View(key: r as u64, flex_direction: FlexDirection::Row, height: 1) {
+                        #(block_runs(row).into_iter().enumerate().map(|(i, ((top, bottom), n))| element! {
+                            View(key: i as u64, width: n as u32, height: 1, background_color: theme::pixel(bottom)) {
+                                Text(content: "▀".repeat(n), color: theme::pixel(top), wrap: TextWrap::NoWrap)
+                            }
+                        }))
+                    }
+                }))
+            }
+        }.into_any(),
+        Preview::Kitty { id, rows } => {
+            // The image id travels in the placeholder cells' colour.
+            let color = Color::Rgb { r: (id >> 16) as u8, g: (id >> 8) as u8, b: *id as u8 };
+            element! {
+                View(flex_direction: FlexDirection::Column, margin_top: 1) {
+                    #(rows.iter().enumerate().map(|(r, row)| element! {
+                        Text(key: r as u64, content: row.clone(), color: color, wrap: TextWrap::NoWrap)
+                    }))
+                }
+            }.into_any()
+        }
+    }
 }
 
 #[component]
@@ -51,6 +99,24 @@ pub fn MessageBubble(props: &MessageBubbleProps) -> impl Into<AnyElement<'static
         ) {
             Text(content: format!("{} {}", icon, label), color: border, weight: Weight::Bold)
             Text(content: display, color: fg, wrap: TextWrap::Wrap)
+            #(props.pastes.iter().enumerate().map(|(i, paste)| {
+                let label = crate::paste::label(paste.chars().count());
+                if props.pastes_expanded {
+                    element! {
+                        View(key: i as u64, flex_direction: FlexDirection::Column, margin_top: 1) {
+                            Text(content: format!("📋 {} {}", label, t("paste.collapse")), color: theme::muted())
+                            Text(content: paste.clone(), color: theme::text_dim(), wrap: TextWrap::Wrap)
+                        }
+                    }
+                } else {
+                    element! {
+                        View(key: i as u64) {
+                            Text(content: format!("📋 {} {}", label, t("paste.expand")), color: theme::muted())
+                        }
+                    }
+                }
+            }))
+            #(props.image.as_deref().map(image_element))
         }
     }
 }
@@ -15,6 +15,8 @@ use crate::types::DisplayMessage;
 pub struct MessagesProps {
     pub messages: Vec<DisplayMessage>,
     pub scroll_offset: i32,
+    /// Show pasted text in full instead of as labels.
+    pub pastes_expanded: bool,
 }
 
 #[component]
@@ -38,6 +40,9 @@ pub fn Messages(props: &MessagesProps) -> impl Into<AnyElement<'static>> {
                             key: i as u64,
                             role: msg.role,
                             content: msg.content.clone(),
+                            image: msg.image.clone(),
+                            pastes: msg.pastes.clone(),
+                            pastes_expanded: props.pastes_expanded,
                         )
                     }
                 }))
@@ -1,11 +1,17 @@
 pub mod auth_dialog;
 pub mod command_menu;
+pub mod command_palette;
 pub mod dialogs;
 pub mod input_bar;
 pub mod message_bubble;
 pub mod messages;
+pub mod notifications_pane;
+pub mod plan_pane;
+pub mod progress_bars;
 pub mod root;
 pub mod secrets_dialog;
+pub mod session_header;
+pub mod session_pane;
 pub mod sidebar;
 pub mod skills_dialog;
 pub mod status_bar;
@@ -13,3 +19,4 @@ pub mod tool_approval_dialog;
 pub mod tool_perms_dialog;
 pub mod user_prompt_dialog;
 pub mod vault_unlock_dialog;
+pub mod workspace_dialog;
@@ -0,0 +1,147 @@
+// ── Notification center ─────────────────────────────────────────────────────
+//
+// Background events — sub-agents finishing, cron failures, device pairing,
+// messenger errors — listed newest first instead of being interleaved into
+// the chat. Opened with Ctrl+N or /notifications; unread ones are counted
+// in the session header.
+
+use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
+use rustyclaw_core::notifications::{Severity, Source};
+use crate::theme;
+use crate::types::NotificationItem;
+
+/// Rows of notifications shown at once.
+pub const VISIBLE_ROWS: usize = 10;
+
+#[derive(Default, Props)]
+pub struct NotificationsPaneProps {
+    pub items: Vec<NotificationItem>,
+    pub selected: usize,
+}
+
+fn severity_style(severity: Severity) -> (&'static str, Color) {
+    match severity {
+        Severity::Info => ("ℹ", theme::info()),
+        Severity::Success => ("✔", theme::success()),
+        Severity::Warning => ("⚠", theme::warn()),
+        Severity::Error => ("✖", theme::error()),
+    }
+}
+
+fn source_label(source: Source) -> &'static str {
+    match source {
+        Source::Subagent => t("notify.subagent"),
+        Source::Cron => t("notify.cron"),
+        Source::Pairing => t("notify.pairing"),
+        Source::Messenger => t("notify.messenger"),
+        Source::Heartbeat => t("notify.heartbeat"),
+    }
+}
+
+fn clock(at_ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(at_ms as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[component]
+pub fn NotificationsPane(props: &NotificationsPaneProps) -> impl Into<AnyElement<'static>> {
+    let skip = props.selected.saturating_sub(VISIBLE_ROWS - 1);
+
+    element! {
+        View(
+            width: 100pct,
+            height: 100pct,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+        ) {
+            View(
+                width: 80pct,
+                max_height: 80pct,
+                flex_direction: FlexDirection::Column,
+                border_style: BorderStyle::Round,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
+                padding_left: 2,
+                padding_right: 2,
+                padding_top: 1,
+                padding_bottom: 1,
+                overflow: Overflow::Hidden,
+            ) {
+                Text(
+                    content: t("dialog.notifications"),
+                    color: theme::accent_bright(),
+                    weight: Weight::Bold,
+                )
+
+                View(height: 1)
+
+                View(
+                    flex_direction: FlexDirection::Column,
+                    width: 100pct,
+                    overflow: Overflow::Hidden,
+                ) {
+                    #(if props.items.is_empty() {
+                        element! {
+                            Text(content: t("notify.empty"), color: theme::text_dim())
+                        }.into_any()
+                    } else {
+                        element! {
+                            View(flex_direction: FlexDirection::Column, width: 100pct) {
+                                #(props.items.iter().enumerate().skip(skip).take(VISIBLE_ROWS).map(|(i, item)| {
+                                    let n = &item.notification;
+                                    let is_selected = i == props.selected;
+                                    let (icon, color) = severity_style(n.severity);
+                                    let bg = if is_selected { theme::accent_bright() } else { Color::Reset };
+                                    let fg = if is_selected { theme::bg_main() } else { theme::text() };
+                                    let dim = if is_selected { theme::bg_main() } else { theme::muted() };
+                                    let unread = if item.read { " " } else { "•" };
+                                    element! {
+                                        View(
+                                            key: n.id,
+                                            width: 100pct,
+                                            flex_direction: FlexDirection::Column,
+                                            background_color: bg,
+                                        ) {
+                                            View(flex_direction: FlexDirection::Row) {
+                                                Text(content: format!("{}{} ", unread, icon), color: if is_selected { fg } else { color })
+                                                Text(content: n.title.clone(), color: fg, weight: Weight::Bold, wrap: TextWrap::NoWrap)
+                                                Text(
+                                                    content: format!("  {} · {}", source_label(n.source), clock(n.at_ms)),
+                                                    color: dim,
+                                                    wrap: TextWrap::NoWrap,
+                                                )
+                                            }
+                                            #((!n.body.is_empty()).then(|| element! {
+                                                View(padding_left: 3) {
+                                                    Text(content: n.body.clone(), color: dim, wrap: TextWrap::NoWrap)
+                                                }
+                                            }))
+                                        }
+                                    }
+                                }))
+                            }
+                        }.into_any()
+                    })
+                }
+
+                View(height: 1)
+
+                // Hint
+                View(flex_direction: FlexDirection::Row) {
+                    Text(content: "↑↓ ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.navigate")), color: theme::muted())
+                    Text(content: "Enter ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.jump")), color: theme::muted())
+                    Text(content: "d ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.dismiss")), color: theme::muted())
+                    Text(content: "c ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.clear_all")), color: theme::muted())
+                    Text(content: "Esc ", color: theme::accent_bright())
+                    Text(content: t("key.close"), color: theme::muted())
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// ── Plan pane ───────────────────────────────────────────────────────────────
+//
+// Live checklist of the agent's current plan, shown beside the chat while a
+// plan exists. Updated from `PlanUpdate` frames sent after each `plan` call.
+
+use iocraft::prelude::*;
+use rustyclaw_core::plan::{Plan, StepStatus};
+use crate::theme;
+
+#[derive(Default, Props)]
+pub struct PlanPaneProps {
+    /// The plan to render (None ⇒ pane is hidden).
+    pub plan: Option<Plan>,
+}
+
+#[component]
+pub fn PlanPane(props: &PlanPaneProps) -> impl Into<AnyElement<'static>> {
+    let Some(plan) = &props.plan else {
+        return element! { View() }.into_any();
+    };
+
+    let (finished, total) = plan.progress();
+
+    element! {
+        View(
+            width: 32,
+            height: 100pct,
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: theme::muted(),
+            border_edges: Edges::Left,
+            padding_left: 1,
+            padding_right: 1,
+        ) {
+            Text(content: format!(" {}", plan.title), color: theme::accent_bright(), weight: Weight::Bold)
+            Text(content: format!(" {}/{} done", finished, total), color: theme::text_dim())
+            View(margin_top: 1, flex_direction: FlexDirection::Column) {
+                #(plan.steps.iter().enumerate().map(|(i, step)| {
+                    let color = match step.status {
+                        StepStatus::Pending => theme::text(),
+                        StepStatus::InProgress => theme::accent_bright(),
+                        StepStatus::Done => theme::success(),
+                        StepStatus::Skipped => theme::muted(),
+                    };
+                    element! {
+                        View(key: i as u64, flex_direction: FlexDirection::Row) {
+                            Text(content: format!("{} ", step.status.marker()), color: color)
+                            Text(content: step.text.clone(), color: color)
+                        }
+                    }
+                }))
+            }
+        }
+    }.into_any()
+}
@@ -0,0 +1,107 @@
+// ── Progress bars ───────────────────────────────────────────────────────────
+//
+// One line per long-running tool operation (download, conversion, indexing,
+// skill install), stacked above the input while they run. Known totals get a
+// percentage bar with an ETA; unknown ones a sweeping bar and a running
+// count. Fed by `Progress` frames; hidden when nothing is in flight.
+
+use iocraft::prelude::*;
+use rustyclaw_core::progress::{ProgressOp, Unit};
+use std::time::Duration;
+use crate::theme;
+
+/// Width of the bar itself, in cells.
+const BAR_WIDTH: usize = 24;
+
+#[derive(Default, Props)]
+pub struct ProgressBarsProps {
+    pub ops: Vec<ProgressOp>,
+    pub spinner_tick: usize,
+}
+
+pub(crate) fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = n as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", n)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs.max(1))
+    }
+}
+
+fn bar(op: &ProgressOp, tick: usize) -> String {
+    match op.fraction() {
+        Some(fraction) => {
+            let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+            format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+        }
+        None => {
+            // Indeterminate: a short block sweeping across.
+            let pos = tick % BAR_WIDTH;
+            (0..BAR_WIDTH)
+                .map(|i| if (pos..pos + 4).contains(&i) { '█' } else { '░' })
+                .collect()
+        }
+    }
+}
+
+fn detail(op: &ProgressOp) -> String {
+    let amount = match (op.unit, op.total) {
+        (Unit::Percent, _) => String::new(),
+        (Unit::Bytes, Some(total)) => format!("{} / {}", format_bytes(op.done), format_bytes(total)),
+        (Unit::Bytes, None) => format_bytes(op.done),
+        (Unit::Items, Some(total)) => format!("{}/{}", op.done, total),
+        (Unit::Items, None) => op.done.to_string(),
+    };
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(fraction) = op.fraction() {
+        parts.push(format!("{:>3.0}%", fraction * 100.0));
+    }
+    if !amount.is_empty() {
+        parts.push(amount);
+    }
+    if let Some(eta) = op.eta() {
+        parts.push(format!("ETA {}", format_eta(eta)));
+    }
+    parts.join("  ")
+}
+
+#[component]
+pub fn ProgressBars(props: &ProgressBarsProps) -> impl Into<AnyElement<'static>> {
+    if props.ops.is_empty() {
+        return element! { View() }.into_any();
+    }
+
+    element! {
+        View(
+            width: 100pct,
+            flex_direction: FlexDirection::Column,
+            padding_left: 1,
+            padding_right: 1,
+        ) {
+            #(props.ops.iter().map(|op| {
+                element! {
+                    View(key: op.id, flex_direction: FlexDirection::Row, height: 1) {
+                        Text(content: format!("{} ", op.label), color: theme::text_dim(), wrap: TextWrap::NoWrap)
+                        Text(content: bar(op, props.spinner_tick), color: theme::accent())
+                        Text(content: format!(" {}", detail(op)), color: theme::muted(), wrap: TextWrap::NoWrap)
+                    }
+                }
+            }))
+        }
+    }.into_any()
+}
@@ -1,7 +1,8 @@
 // ── Root ────────────────────────────────────────────────────────────────────
 //
 // Top-level layout. Receives terminal size explicitly (as iocraft fullscreen
-// examples do) and composes Messages+Sidebar, InputBar, StatusBar.
+// examples do) and composes SessionHeader+Messages(+SessionPane when split)
+// +PlanPane+Sidebar, ProgressBars, InputBar, StatusBar.
 
 use iocraft::prelude::*;
 
@@ -9,12 +10,19 @@ use crate::components::auth_dialog::AuthDialog;
 use crate::components::command_menu::CommandMenu;
 use crate::components::input_bar::InputBar;
 use crate::components::messages::Messages;
+use crate::components::plan_pane::PlanPane;
+use crate::components::progress_bars::ProgressBars;
 use crate::components::secrets_dialog::{SecretsDialog, SecretInfo};
+use crate::components::session_header::SessionHeader;
+use crate::components::session_pane::SessionPane;
 use crate::components::sidebar::Sidebar;
 use crate::components::skills_dialog::{SkillsDialog, SkillInfo};
+use crate::components::command_palette::CommandPalette;
+use crate::components::notifications_pane::NotificationsPane;
 use crate::components::status_bar::StatusBar;
 use crate::components::tool_approval_dialog::ToolApprovalDialog;
 use crate::components::tool_perms_dialog::{ToolPermsDialog, ToolPermInfo};
+use crate::components::workspace_dialog::{WorkspaceDialog, WorkspaceInfo};
 use crate::components::user_prompt_dialog::UserPromptDialog;
 use crate::components::vault_unlock_dialog::VaultUnlockDialog;
 use crate::theme;
@@ -35,19 +43,40 @@ pub struct RootProps {
     pub gateway_label: String,
     pub gateway_color: Option<Color>,
 
+    // session header (title + pinned topic)
+    pub session_meta: rustyclaw_core::session_meta::SessionMeta,
+
     // messages
     pub messages: Vec<DisplayMessage>,
     pub scroll_offset: i32,
 
+    // split pane: watched session / cron log (hidden when None)
+    pub side_view: Option<rustyclaw_core::gateway::session_view::SessionView>,
+    pub side_focused: bool,
+    pub side_scroll: i32,
+
+    // plan checklist pane (hidden when None)
+    pub plan: Option<rustyclaw_core::plan::Plan>,
+
+    // tool progress bars (hidden when empty)
+    pub progress: Vec<rustyclaw_core::progress::ProgressOp>,
+
     // command menu (slash completions)
     pub command_completions: Vec<String>,
     pub command_selected: Option<usize>,
 
     // input
     pub input_value: String,
+    /// Labels of pastes waiting to be sent with the input
+    pub pastes: Vec<String>,
+    /// Show pasted text in full in the chat
+    pub pastes_expanded: bool,
     pub on_change: HandlerMut<'static, String>,
     pub on_submit: HandlerMut<'static, String>,
     pub input_has_focus: bool,
+    pub vim_mode: Option<crate::vim::Mode>,
+    pub vim_cursor: usize,
+    pub vim_pending: String,
 
     // sidebar
     pub task_text: String,
@@ -57,6 +86,7 @@ pub struct RootProps {
     // status bar
     pub hint: String,
     pub spinner_tick: usize,
+    pub stats: Option<rustyclaw_core::gateway::stats::GatewayStats>,
 
     // auth dialog overlay
     pub show_auth_dialog: bool,
@@ -102,6 +132,27 @@ pub struct RootProps {
     pub tool_perms_data: Vec<ToolPermInfo>,
     pub tool_perms_selected: Option<usize>,
     pub tool_perms_scroll_offset: usize,
+
+    // workspace picker overlay
+    pub show_workspace_dialog: bool,
+    pub workspace_data: Vec<WorkspaceInfo>,
+    pub workspace_selected: Option<usize>,
+    pub workspace_scroll_offset: usize,
+
+    // command palette overlay
+    pub show_palette: bool,
+    pub palette_query: String,
+    pub palette_entries: Vec<crate::palette::PaletteEntry>,
+    pub palette_selected: usize,
+
+    // usage bar (context / cost)
+    pub usage: Option<crate::types::SessionUsage>,
+
+    // notification center overlay
+    pub show_notifications: bool,
+    pub notifications: Vec<crate::types::NotificationItem>,
+    pub notifications_selected: usize,
+    pub unread_notifications: usize,
 }
 
 #[component]
@@ -131,13 +182,20 @@ pub fn Root(props: &mut RootProps) -> impl Into<AnyElement<'static>> {
     let tool_perms_scroll = props.tool_perms_scroll_offset;
     #[allow(unused_variables)]
     let show_tool_perms = props.show_tool_perms_dialog;
+    let workspace_data = std::mem::take(&mut props.workspace_data);
+    let workspace_selected = props.workspace_selected;
+    let workspace_scroll = props.workspace_scroll_offset;
+    let show_workspaces = props.show_workspace_dialog;
+    let palette_entries = std::mem::take(&mut props.palette_entries);
+    let palette_query = std::mem::take(&mut props.palette_query);
+    let notifications = std::mem::take(&mut props.notifications);
 
     element! {
         View(
             width: props.width,
             height: props.height,
             flex_direction: FlexDirection::Column,
-            background_color: theme::BG_MAIN,
+            background_color: theme::bg_main(),
         ) {
             // ── Main area (flex grow) ───────────────────────────────────
             View(
@@ -150,14 +208,25 @@ pub fn Root(props: &mut RootProps) -> impl Into<AnyElement<'static>> {
                     flex_grow: 1.0,
                     flex_direction: FlexDirection::Column,
                 ) {
+                    SessionHeader(meta: props.session_meta.clone(), unread: props.unread_notifications)
                     Messages(
                         messages: props.messages.clone(),
                         scroll_offset: props.scroll_offset,
+                        pastes_expanded: props.pastes_expanded,
+                    )
+                    SessionPane(
+                        view: props.side_view.clone(),
+                        focused: props.side_focused,
+                        scroll_offset: props.side_scroll,
                     )
                     CommandMenu(
                         completions: props.command_completions.clone(),
                         selected: props.command_selected,
                     )
+                    ProgressBars(
+                        ops: props.progress.clone(),
+                        spinner_tick: props.spinner_tick,
+                    )
                     InputBar(
                         value: props.input_value.clone(),
                         on_change: props.on_change.take(),
@@ -166,8 +235,15 @@ pub fn Root(props: &mut RootProps) -> impl Into<AnyElement<'static>> {
                         gateway_label: props.gateway_label.clone(),
                         gateway_color: props.gateway_color,
                         has_focus: props.input_has_focus,
+                        vim_mode: props.vim_mode,
+                        vim_cursor: props.vim_cursor,
+                        vim_pending: props.vim_pending.clone(),
+                        pastes: props.pastes.clone(),
+                        usage: props.usage.clone(),
                     )
                 }
+                // Plan checklist
+                PlanPane(plan: props.plan.clone())
                 // Sidebar
                 Sidebar(
                     gateway_label: props.gateway_label.clone(),
@@ -185,6 +261,7 @@ pub fn Root(props: &mut RootProps) -> impl Into<AnyElement<'static>> {
                 spinner_tick: props.spinner_tick,
                 soul_name: props.soul_name.clone(),
                 model_label: props.model_label.clone(),
+                stats: props.stats.clone(),
             )
 
             // ── Auth dialog overlay ─────────────────────────────────────
@@ -336,6 +413,68 @@ pub fn Root(props: &mut RootProps) -> impl Into<AnyElement<'static>> {
             } else {
                 element! { View() }.into_any()
             })
+
+            // ── Workspace picker overlay ────────────────────────────────
+            #(if show_workspaces {
+                element! {
+                    View(
+                        width: props.width,
+                        height: props.height,
+                        position: Position::Absolute,
+                        top: 0,
+                        left: 0,
+                    ) {
+                        WorkspaceDialog(
+                            workspaces: workspace_data,
+                            selected: workspace_selected,
+                            scroll_offset: workspace_scroll,
+                        )
+                    }
+                }.into_any()
+            } else {
+                element! { View() }.into_any()
+            })
+
+            // ── Command palette overlay ─────────────────────────────────
+            #(if props.show_palette {
+                element! {
+                    View(
+                        width: props.width,
+                        height: props.height,
+                        position: Position::Absolute,
+                        top: 0,
+                        left: 0,
+                    ) {
+                        CommandPalette(
+                            query: palette_query,
+                            entries: palette_entries,
+                            selected: props.palette_selected,
+                        )
+                    }
+                }.into_any()
+            } else {
+                element! { View() }.into_any()
+            })
+
+            // ── Notification center overlay ─────────────────────────────
+            #(if props.show_notifications {
+                element! {
+                    View(
+                        width: props.width,
+                        height: props.height,
+                        position: Position::Absolute,
+                        top: 0,
+                        left: 0,
+                    ) {
+                        NotificationsPane(
+                            items: notifications,
+                            selected: props.notifications_selected,
+                        )
+                    }
+                }.into_any()
+            } else {
+                element! { View() }.into_any()
+            })
         }
     }
 }
@@ -1,6 +1,7 @@
 // ── Secrets dialog — interactive vault management overlay ────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
 
 #[derive(Debug, Clone, Default)]
@@ -28,9 +29,9 @@ pub struct SecretsDialogProps {
 #[component]
 pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static>> {
     let access_label = if props.agent_access { "Enabled" } else { "Disabled" };
-    let access_color = if props.agent_access { theme::SUCCESS } else { theme::WARN };
+    let access_color = if props.agent_access { theme::success() } else { theme::warn() };
     let totp_label = if props.has_totp { "On" } else { "Off" };
-    let totp_color = if props.has_totp { theme::SUCCESS } else { theme::MUTED };
+    let totp_color = if props.has_totp { theme::success() } else { theme::muted() };
     let count = props.secrets.len();
     let sel = props.selected.unwrap_or(0);
 
@@ -46,8 +47,8 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                 max_height: 80pct,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::ACCENT_BRIGHT,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -56,8 +57,8 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
             ) {
                 // Title
                 Text(
-                    content: "🔐 Secrets Vault",
-                    color: theme::ACCENT_BRIGHT,
+                    content: t("dialog.secrets"),
+                    color: theme::accent_bright(),
                     weight: Weight::Bold,
                 )
 
@@ -65,11 +66,11 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
 
                 // Summary line
                 View(flex_direction: FlexDirection::Row) {
-                    Text(content: "Agent Access: ", color: theme::TEXT_DIM)
+                    Text(content: "Agent Access: ", color: theme::text_dim())
                     Text(content: access_label, color: access_color)
-                    Text(content: "  │  ", color: theme::MUTED)
-                    Text(content: format!("{} credential{}", count, if count == 1 { "" } else { "s" }), color: theme::TEXT_DIM)
-                    Text(content: "  │  2FA: ", color: theme::TEXT_DIM)
+                    Text(content: "  │  ", color: theme::muted())
+                    Text(content: format!("{} credential{}", count, if count == 1 { "" } else { "s" }), color: theme::text_dim())
+                    Text(content: "  │  2FA: ", color: theme::text_dim())
                     Text(content: totp_label, color: totp_color)
                 }
 
@@ -78,7 +79,7 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                 // Credential list
                 #(if props.secrets.is_empty() {
                     element! {
-                        Text(content: "  No credentials stored.  Press 'a' to add one.", color: theme::MUTED)
+                        Text(content: t("secrets.empty"), color: theme::muted())
                     }.into_any()
                 } else {
                     element! {
@@ -89,14 +90,14 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                         ) {
                             #(props.secrets.iter().enumerate().skip(props.scroll_offset).take(20).map(|(i, s)| {
                                 let is_selected = i == sel;
-                                let bg = if is_selected { Some(theme::ACCENT_BRIGHT) } else { None };
+                                let bg = if is_selected { Some(theme::accent_bright()) } else { None };
                                 let pointer = if is_selected { "▸ " } else { "  " };
                                 let fg = if is_selected {
-                                    theme::BG_MAIN
+                                    theme::bg_main()
                                 } else if s.disabled {
-                                    theme::MUTED
+                                    theme::muted()
                                 } else {
-                                    theme::TEXT
+                                    theme::text()
                                 };
                                 let status = if s.disabled { "OFF".to_string() } else { s.policy.clone() };
                                 let suffix = if !s.name.is_empty() && s.name != s.label {
@@ -134,15 +135,15 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                             flex_direction: FlexDirection::Column,
                             width: 100pct,
                         ) {
-                            Text(content: "Add Secret", color: theme::ACCENT_BRIGHT, weight: Weight::Bold)
+                            Text(content: t("secrets.add"), color: theme::accent_bright(), weight: Weight::Bold)
                             View(
                                 width: 100pct,
                                 border_style: BorderStyle::Round,
-                                border_color: theme::ACCENT_BRIGHT,
+                                border_color: theme::accent_bright(),
                                 padding_left: 1,
                                 padding_right: 1,
                             ) {
-                                Text(content: cursor_display, color: theme::TEXT, wrap: TextWrap::NoWrap)
+                                Text(content: cursor_display, color: theme::text(), wrap: TextWrap::NoWrap)
                             }
                             Text(
                                 content: if props.add_step == 1 {
@@ -150,7 +151,7 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                                 } else {
                                     "Enter value, then press Enter to save   │  Esc cancel"
                                 },
-                                color: theme::MUTED,
+                                color: theme::muted(),
                             )
                         }
                     }.into_any()
@@ -162,22 +163,22 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                 #(if props.add_step == 0 {
                     element! {
                         View(flex_direction: FlexDirection::Row) {
-                            View(background_color: theme::SUCCESS) {
-                                Text(content: " OPEN ", color: theme::BG_MAIN)
+                            View(background_color: theme::success()) {
+                                Text(content: " OPEN ", color: theme::bg_main())
                             }
-                            Text(content: " anytime  ", color: theme::TEXT_DIM)
-                            View(background_color: theme::WARN) {
-                                Text(content: " ASK ", color: theme::BG_MAIN)
+                            Text(content: " anytime  ", color: theme::text_dim())
+                            View(background_color: theme::warn()) {
+                                Text(content: " ASK ", color: theme::bg_main())
                             }
-                            Text(content: " per-use  ", color: theme::TEXT_DIM)
-                            View(background_color: theme::ERROR) {
-                                Text(content: " AUTH ", color: theme::BG_MAIN)
+                            Text(content: " per-use  ", color: theme::text_dim())
+                            View(background_color: theme::error()) {
+                                Text(content: " AUTH ", color: theme::bg_main())
                             }
-                            Text(content: " re-auth  ", color: theme::TEXT_DIM)
-                            View(background_color: theme::INFO) {
-                                Text(content: " SKILL ", color: theme::BG_MAIN)
+                            Text(content: " re-auth  ", color: theme::text_dim())
+                            View(background_color: theme::info()) {
+                                Text(content: " SKILL ", color: theme::bg_main())
                             }
-                            Text(content: " gated", color: theme::TEXT_DIM)
+                            Text(content: " gated", color: theme::text_dim())
                         }
                     }.into_any()
                 } else {
@@ -190,16 +191,16 @@ pub fn SecretsDialog(props: &SecretsDialogProps) -> impl Into<AnyElement<'static
                 #(if props.add_step == 0 {
                     element! {
                         View(flex_direction: FlexDirection::Row) {
-                            Text(content: "↑↓ ", color: theme::ACCENT_BRIGHT)
-                            Text(content: "navigate  ", color: theme::MUTED)
-                            Text(content: "Enter ", color: theme::ACCENT_BRIGHT)
-                            Text(content: "cycle policy  ", color: theme::MUTED)
-                            Text(content: "a ", color: theme::ACCENT_BRIGHT)
-                            Text(content: "add  ", color: theme::MUTED)
-                            Text(content: "d ", color: theme::ACCENT_BRIGHT)
-                            Text(content: "delete  ", color: theme::MUTED)
-                            Text(content: "Esc ", color: theme::ACCENT_BRIGHT)
-                            Text(content: "close", color: theme::MUTED)
+                            Text(content: "↑↓ ", color: theme::accent_bright())
+                            Text(content: format!("{}  ", t("key.navigate")), color: theme::muted())
+                            Text(content: "Enter ", color: theme::accent_bright())
+                            Text(content: format!("{}  ", t("key.cycle_policy")), color: theme::muted())
+                            Text(content: "a ", color: theme::accent_bright())
+                            Text(content: format!("{}  ", t("key.add")), color: theme::muted())
+                            Text(content: "d ", color: theme::accent_bright())
+                            Text(content: format!("{}  ", t("key.delete")), color: theme::muted())
+                            Text(content: "Esc ", color: theme::accent_bright())
+                            Text(content: t("key.close"), color: theme::muted())
                         }
                     }.into_any()
                 } else {
@@ -0,0 +1,53 @@
+// ── Session header ──────────────────────────────────────────────────────────
+//
+// One-line header above the chat with the session's generated title, the
+// topic pinned with `/pin` and the count of unread notifications. Hidden
+// until there's any of those.
+
+use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
+use rustyclaw_core::session_meta::SessionMeta;
+use crate::theme;
+
+#[derive(Default, Props)]
+pub struct SessionHeaderProps {
+    pub meta: SessionMeta,
+    /// Unread notification-center entries.
+    pub unread: usize,
+}
+
+#[component]
+pub fn SessionHeader(props: &SessionHeaderProps) -> impl Into<AnyElement<'static>> {
+    let meta = &props.meta;
+    if meta.title.is_none() && meta.pinned.is_none() && props.unread == 0 {
+        return element! { View() }.into_any();
+    }
+
+    let title = meta.title.clone().unwrap_or_else(|| t("session.untitled").to_string());
+
+    element! {
+        View(
+            width: 100pct,
+            height: 2,
+            flex_direction: FlexDirection::Row,
+            padding_left: 1,
+            padding_right: 1,
+            border_style: BorderStyle::Round,
+            border_color: theme::muted(),
+            border_edges: Edges::Bottom,
+        ) {
+            Text(content: title, color: theme::accent_bright(), weight: Weight::Bold, wrap: TextWrap::NoWrap)
+            #(meta.pinned.as_ref().map(|note| element! {
+                View(flex_direction: FlexDirection::Row, flex_shrink: 1.0) {
+                    Text(content: "  📌 ", color: theme::accent())
+                    Text(content: note.clone(), color: theme::text_dim(), wrap: TextWrap::NoWrap)
+                }
+            }))
+            #((props.unread > 0).then(|| element! {
+                View(flex_grow: 1.0, justify_content: JustifyContent::End) {
+                    Text(content: format!("🔔 {}", props.unread), color: theme::warn(), weight: Weight::Bold)
+                }
+            }))
+        }
+    }.into_any()
+}
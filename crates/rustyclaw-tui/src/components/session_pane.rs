@@ -0,0 +1,79 @@
+// ── Session pane ────────────────────────────────────────────────────────────
+//
+// Lower half of the split layout: a live, read-only view of a sub-agent
+// session or a cron job's run log, watched with `/split`. Anchored from the
+// bottom like Messages; Tab moves focus (and ↑↓ scrolling) between the chat
+// and this pane.
+
+use iocraft::prelude::*;
+use rustyclaw_core::gateway::session_view::SessionView;
+use crate::theme;
+
+#[derive(Default, Props)]
+pub struct SessionPaneProps {
+    /// The watched view (None ⇒ pane is hidden).
+    pub view: Option<SessionView>,
+    pub focused: bool,
+    pub scroll_offset: i32,
+}
+
+fn role_color(role: &str) -> Color {
+    match role {
+        "user" => theme::info(),
+        "assistant" => theme::text(),
+        "tool" | "system" => theme::text_dim(),
+        "ok" | "acknowledged" => theme::success(),
+        "error" | "timeout" => theme::error(),
+        "running" | "snoozed" => theme::warn(),
+        _ => theme::muted(),
+    }
+}
+
+#[component]
+pub fn SessionPane(props: &SessionPaneProps) -> impl Into<AnyElement<'static>> {
+    let Some(view) = &props.view else {
+        return element! { View() }.into_any();
+    };
+
+    let border_color = if props.focused { theme::accent() } else { theme::muted() };
+
+    element! {
+        View(
+            width: 100pct,
+            height: 40pct,
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: border_color,
+            border_edges: Edges::Top,
+            padding_left: 1,
+            padding_right: 1,
+        ) {
+            View(height: 1, flex_direction: FlexDirection::Row) {
+                Text(content: view.title.clone(), color: theme::accent_bright(), weight: Weight::Bold, wrap: TextWrap::NoWrap)
+                Text(content: format!("  {}", view.status), color: theme::text_dim(), wrap: TextWrap::NoWrap)
+            }
+            View(
+                flex_direction: FlexDirection::Column,
+                flex_grow: 1.0,
+                overflow: Overflow::Hidden,
+                width: 100pct,
+            ) {
+                View(
+                    flex_direction: FlexDirection::Column,
+                    width: 100pct,
+                    position: Position::Absolute,
+                    bottom: -(props.scroll_offset),
+                ) {
+                    #(view.lines.iter().enumerate().map(|(i, line)| {
+                        element! {
+                            View(key: i as u64, flex_direction: FlexDirection::Row) {
+                                Text(content: format!("{:>9} ", line.role), color: role_color(&line.role))
+                                Text(content: line.text.clone(), color: theme::text(), wrap: TextWrap::Wrap)
+                            }
+                        }
+                    }))
+                }
+            }
+        }
+    }.into_any()
+}
@@ -1,6 +1,7 @@
 // ── Sidebar ─────────────────────────────────────────────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::{t, tf};
 use crate::theme;
 
 #[derive(Default, Props)]
@@ -19,32 +20,32 @@ pub fn Sidebar(props: &SidebarProps) -> impl Into<AnyElement<'static>> {
             height: 100pct,
             flex_direction: FlexDirection::Column,
             border_style: BorderStyle::Round,
-            border_color: theme::MUTED,
+            border_color: theme::muted(),
             border_edges: Edges::Left,
             padding_left: 1,
             padding_right: 1,
         ) {
             // Session
-            Text(content: " Session", color: theme::ACCENT_BRIGHT, weight: Weight::Bold)
+            Text(content: t("sidebar.session"), color: theme::accent_bright(), weight: Weight::Bold)
             View(margin_top: 1) {
-                Text(content: format!("Status: {}", props.gateway_label), color: theme::TEXT_DIM)
+                Text(content: tf("sidebar.status", &[&props.gateway_label]), color: theme::text_dim())
             }
 
             // Tasks
             View(margin_top: 1) {
-                Text(content: " Tasks", color: theme::ACCENT_BRIGHT, weight: Weight::Bold)
+                Text(content: t("sidebar.tasks"), color: theme::accent_bright(), weight: Weight::Bold)
             }
             View(margin_top: 1) {
                 #(if props.streaming {
                     element! {
                         View(flex_direction: FlexDirection::Row) {
-                            Text(content: "⠋ ", color: theme::ACCENT)
-                            Text(content: format!("Streaming {}", props.elapsed), color: theme::TEXT_DIM)
+                            Text(content: "⠋ ", color: theme::accent())
+                            Text(content: tf("sidebar.streaming", &[&props.elapsed]), color: theme::text_dim())
                         }
                     }.into_any()
                 } else {
                     element! {
-                        Text(content: &props.task_text, color: theme::MUTED)
+                        Text(content: &props.task_text, color: theme::muted())
                     }.into_any()
                 })
             }
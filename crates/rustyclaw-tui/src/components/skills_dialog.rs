@@ -1,6 +1,7 @@
 // ── Skills dialog — interactive skill list overlay ──────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
 
 #[derive(Debug, Clone, Default)]
@@ -36,8 +37,8 @@ pub fn SkillsDialog(props: &SkillsDialogProps) -> impl Into<AnyElement<'static>>
                 max_height: 80pct,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::ACCENT_BRIGHT,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -46,8 +47,8 @@ pub fn SkillsDialog(props: &SkillsDialogProps) -> impl Into<AnyElement<'static>>
             ) {
                 // Title
                 Text(
-                    content: "⚡ Skills",
-                    color: theme::ACCENT_BRIGHT,
+                    content: t("dialog.skills"),
+                    color: theme::accent_bright(),
                     weight: Weight::Bold,
                 )
 
@@ -55,9 +56,9 @@ pub fn SkillsDialog(props: &SkillsDialogProps) -> impl Into<AnyElement<'static>>
 
                 // Summary
                 View(flex_direction: FlexDirection::Row) {
-                    Text(content: format!("{} skill{}  │  ", count, if count == 1 { "" } else { "s" }), color: theme::TEXT_DIM)
-                    Text(content: format!("{} enabled  ", enabled), color: theme::SUCCESS)
-                    Text(content: format!("{} disabled", disabled), color: theme::MUTED)
+                    Text(content: format!("{} skill{}  │  ", count, if count == 1 { "" } else { "s" }), color: theme::text_dim())
+                    Text(content: format!("{} enabled  ", enabled), color: theme::success())
+                    Text(content: format!("{} disabled", disabled), color: theme::muted())
                 }
 
                 View(height: 1)
@@ -65,7 +66,7 @@ pub fn SkillsDialog(props: &SkillsDialogProps) -> impl Into<AnyElement<'static>>
                 // Skill list
                 #(if props.skills.is_empty() {
                     element! {
-                        Text(content: "  No skills loaded.", color: theme::MUTED)
+                        Text(content: t("skills.empty"), color: theme::muted())
                     }.into_any()
                 } else {
                     element! {
@@ -82,14 +83,14 @@ pub fn SkillsDialog(props: &SkillsDialogProps) -> impl Into<AnyElement<'static>>
                                 } else {
                                     s.description.clone()
                                 };
-                                let bg = if is_selected { Some(theme::ACCENT_BRIGHT) } else { None };
+                                let bg = if is_selected { Some(theme::accent_bright()) } else { None };
                                 let pointer = if is_selected { "▸ " } else { "  " };
                                 let fg = if is_selected {
-                                    theme::BG_MAIN
+                                    theme::bg_main()
                                 } else if s.enabled {
-                                    theme::ACCENT_BRIGHT
+                                    theme::accent_bright()
                                 } else {
-                                    theme::TEXT_DIM
+                                    theme::text_dim()
                                 };
                                 let line = format!("{}{} {} — {}", pointer, icon, s.name, desc);
                                 element! {
@@ -110,12 +111,12 @@ pub fn SkillsDialog(props: &SkillsDialogProps) -> impl Into<AnyElement<'static>>
 
                 // Hint
                 View(flex_direction: FlexDirection::Row) {
-                    Text(content: "↑↓ ", color: theme::ACCENT_BRIGHT)
-                    Text(content: "navigate  ", color: theme::MUTED)
-                    Text(content: "Enter ", color: theme::ACCENT_BRIGHT)
-                    Text(content: "toggle  ", color: theme::MUTED)
-                    Text(content: "Esc ", color: theme::ACCENT_BRIGHT)
-                    Text(content: "close", color: theme::MUTED)
+                    Text(content: "↑↓ ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.navigate")), color: theme::muted())
+                    Text(content: "Enter ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.toggle")), color: theme::muted())
+                    Text(content: "Esc ", color: theme::accent_bright())
+                    Text(content: t("key.close"), color: theme::muted())
                 }
             }
         }
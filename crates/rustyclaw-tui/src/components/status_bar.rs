@@ -1,6 +1,8 @@
 // ── Status bar ──────────────────────────────────────────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::{t, tf};
+use rustyclaw_core::gateway::stats::GatewayStats;
 use crate::theme;
 
 #[derive(Default, Props)]
@@ -11,27 +13,65 @@ pub struct StatusBarProps {
     pub spinner_tick: usize,
     pub soul_name: String,
     pub model_label: String,
+    /// Live gateway stats; replaces the default hint when present.
+    pub stats: Option<GatewayStats>,
+}
+
+/// Compact one-line summary of the gateway stats, e.g.
+/// `⏱ anthropic 1.8s · 🔧 2 pending · ✉ 1 queued · ⏰ 1 due (backup)`.
+/// Counters that are zero are left out.
+pub fn stats_label(stats: &GatewayStats) -> String {
+    let mut parts = Vec::new();
+    if let Some(ms) = stats.last_latency_ms {
+        let latency = if ms < 1000 {
+            format!("{}ms", ms)
+        } else {
+            format!("{:.1}s", ms as f64 / 1000.0)
+        };
+        match &stats.last_provider {
+            Some(provider) => parts.push(format!("⏱ {} {}", provider, latency)),
+            None => parts.push(format!("⏱ {}", latency)),
+        }
+    }
+    if stats.pending_tool_calls > 0 {
+        parts.push(format!("🔧 {} pending", stats.pending_tool_calls));
+    }
+    if stats.messenger_queue > 0 {
+        parts.push(format!("✉ {} queued", stats.messenger_queue));
+    }
+    if stats.cron_due_soon > 0 {
+        match &stats.next_cron_job {
+            Some(name) => parts.push(format!("⏰ {} due ({})", stats.cron_due_soon, name)),
+            None => parts.push(format!("⏰ {} due", stats.cron_due_soon)),
+        }
+    }
+    parts.join(" · ")
 }
 
 #[component]
 pub fn StatusBar(props: &StatusBarProps) -> impl Into<AnyElement<'static>> {
     let right_text = if props.streaming {
         let ch = theme::SPINNER[props.spinner_tick % theme::SPINNER.len()];
-        format!("{} Streaming response {}", ch, props.elapsed)
+        format!("{} {}", ch, tf("status.streaming", &[&props.elapsed]))
     } else if props.hint.is_empty() {
-        "Ctrl+C quit · /help commands · ↑↓ scroll".to_string()
+        props
+            .stats
+            .as_ref()
+            .map(stats_label)
+            .filter(|label| !label.is_empty())
+            .unwrap_or_else(|| t("status.hint").to_string())
     } else {
         props.hint.clone()
     };
 
-    let right_color = if props.streaming { theme::ACCENT } else { theme::MUTED };
+    let right_color = if props.streaming { theme::accent() } else { theme::muted() };
 
     let model_text = if props.model_label.is_empty() {
-        "(no model)".to_string()
+        t("status.no_model").to_string()
     } else {
         props.model_label.clone()
     };
-    let model_color = if props.model_label.is_empty() { theme::WARN } else { theme::INFO };
+    let model_color = if props.model_label.is_empty() { theme::warn() } else { theme::info() };
 
     element! {
         View(
@@ -43,10 +83,10 @@ pub fn StatusBar(props: &StatusBarProps) -> impl Into<AnyElement<'static>> {
             padding_right: 1,
         ) {
             View(flex_direction: FlexDirection::Row) {
-                Text(content: "🦀 ", color: theme::ACCENT)
-                Text(content: &props.soul_name, color: theme::ACCENT_BRIGHT, weight: Weight::Bold)
-                Text(content: format!(" v{}", env!("CARGO_PKG_VERSION")), color: theme::MUTED)
-                Text(content: " · ", color: theme::MUTED)
+                Text(content: "🦀 ", color: theme::accent())
+                Text(content: &props.soul_name, color: theme::accent_bright(), weight: Weight::Bold)
+                Text(content: format!(" v{}", env!("CARGO_PKG_VERSION")), color: theme::muted())
+                Text(content: " · ", color: theme::muted())
                 Text(content: model_text, color: model_color)
             }
             Text(content: right_text, color: right_color)
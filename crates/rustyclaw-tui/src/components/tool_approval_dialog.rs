@@ -1,6 +1,7 @@
 // ── Tool approval dialog — ask user to approve/deny a tool call ─────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
 
 #[derive(Default, Props)]
@@ -15,8 +16,8 @@ pub struct ToolApprovalDialogProps {
 
 #[component]
 pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyElement<'static>> {
-    let allow_color = if props.selected_allow { theme::SUCCESS } else { theme::MUTED };
-    let deny_color = if props.selected_allow { theme::MUTED } else { theme::ERROR };
+    let allow_color = if props.selected_allow { theme::success() } else { theme::muted() };
+    let deny_color = if props.selected_allow { theme::muted() } else { theme::error() };
     let allow_indicator = if props.selected_allow { "▸ " } else { "  " };
     let deny_indicator = if props.selected_allow { "  " } else { "▸ " };
 
@@ -38,8 +39,8 @@ pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyEleme
                 width: 56,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::WARN,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::warn(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -47,8 +48,8 @@ pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyEleme
             ) {
                 // Title
                 Text(
-                    content: "🔐 Tool Approval Required",
-                    color: theme::WARN,
+                    content: t("dialog.tool_approval"),
+                    color: theme::warn(),
                     weight: Weight::Bold,
                 )
 
@@ -57,7 +58,7 @@ pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyEleme
                 // Tool name
                 Text(
                     content: format!("Tool: {}", props.tool_name),
-                    color: theme::TEXT,
+                    color: theme::text(),
                     weight: Weight::Bold,
                 )
 
@@ -66,11 +67,11 @@ pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyEleme
                 // Arguments
                 Text(
                     content: "Arguments:",
-                    color: theme::MUTED,
+                    color: theme::muted(),
                 )
                 Text(
                     content: args_display,
-                    color: theme::TEXT,
+                    color: theme::text(),
                 )
 
                 View(height: 1)
@@ -82,12 +83,12 @@ pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyEleme
                     gap: 4,
                 ) {
                     Text(
-                        content: format!("{}Allow (y)", allow_indicator),
+                        content: format!("{}{}", allow_indicator, t("approval.allow")),
                         color: allow_color,
                         weight: Weight::Bold,
                     )
                     Text(
-                        content: format!("{}Deny (n)", deny_indicator),
+                        content: format!("{}{}", deny_indicator, t("approval.deny")),
                         color: deny_color,
                         weight: Weight::Bold,
                     )
@@ -97,8 +98,8 @@ pub fn ToolApprovalDialog(props: &ToolApprovalDialogProps) -> impl Into<AnyEleme
 
                 // Hint
                 Text(
-                    content: "y allow · n/Esc deny · Tab toggle · Enter confirm",
-                    color: theme::MUTED,
+                    content: t("approval.footer"),
+                    color: theme::muted(),
                 )
             }
         }
@@ -1,6 +1,7 @@
 // ── Tool permissions dialog — interactive tool permission control ────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
 
 #[derive(Debug, Clone, Default)]
@@ -37,8 +38,8 @@ pub fn ToolPermsDialog(props: &ToolPermsDialogProps) -> impl Into<AnyElement<'st
                 max_height: 80pct,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::ACCENT_BRIGHT,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -47,8 +48,8 @@ pub fn ToolPermsDialog(props: &ToolPermsDialogProps) -> impl Into<AnyElement<'st
             ) {
                 // Title
                 Text(
-                    content: "🔧 Tool Permissions",
-                    color: theme::ACCENT_BRIGHT,
+                    content: t("dialog.tool_perms"),
+                    color: theme::accent_bright(),
                     weight: Weight::Bold,
                 )
 
@@ -56,10 +57,10 @@ pub fn ToolPermsDialog(props: &ToolPermsDialogProps) -> impl Into<AnyElement<'st
 
                 // Summary
                 View(flex_direction: FlexDirection::Row) {
-                    Text(content: format!("{} tools  │  ", total), color: theme::TEXT_DIM)
-                    Text(content: format!("{} allow  ", allowed), color: theme::SUCCESS)
-                    Text(content: format!("{} ask  ", ask), color: theme::WARN)
-                    Text(content: format!("{} deny", denied), color: theme::ERROR)
+                    Text(content: format!("{} tools  │  ", total), color: theme::text_dim())
+                    Text(content: format!("{} allow  ", allowed), color: theme::success())
+                    Text(content: format!("{} ask  ", ask), color: theme::warn())
+                    Text(content: format!("{} deny", denied), color: theme::error())
                 }
 
                 View(height: 1)
@@ -72,9 +73,9 @@ pub fn ToolPermsDialog(props: &ToolPermsDialogProps) -> impl Into<AnyElement<'st
                 ) {
                     #(props.tools.iter().enumerate().skip(props.scroll_offset).take(20).map(|(i, t)| {
                         let is_selected = i == sel;
-                        let bg = if is_selected { Some(theme::ACCENT_BRIGHT) } else { None };
+                        let bg = if is_selected { Some(theme::accent_bright()) } else { None };
                         let pointer = if is_selected { "▸ " } else { "  " };
-                        let fg = if is_selected { theme::BG_MAIN } else { theme::TEXT };
+                        let fg = if is_selected { theme::bg_main() } else { theme::text() };
                         let line = format!("{} {:5}  {} — {}", pointer, t.permission, t.name, t.summary);
                         element! {
                             View(
@@ -92,12 +93,12 @@ pub fn ToolPermsDialog(props: &ToolPermsDialogProps) -> impl Into<AnyElement<'st
 
                 // Hint
                 View(flex_direction: FlexDirection::Row) {
-                    Text(content: "↑↓ ", color: theme::ACCENT_BRIGHT)
-                    Text(content: "navigate  ", color: theme::MUTED)
-                    Text(content: "Enter ", color: theme::ACCENT_BRIGHT)
-                    Text(content: "cycle permission  ", color: theme::MUTED)
-                    Text(content: "Esc ", color: theme::ACCENT_BRIGHT)
-                    Text(content: "close", color: theme::MUTED)
+                    Text(content: "↑↓ ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.navigate")), color: theme::muted())
+                    Text(content: "Enter ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.cycle_permission")), color: theme::muted())
+                    Text(content: "Esc ", color: theme::accent_bright())
+                    Text(content: t("key.close"), color: theme::muted())
                 }
             }
         }
@@ -29,8 +29,8 @@ pub fn UserPromptDialog(props: &UserPromptDialogProps) -> impl Into<AnyElement<'
                 width: 60,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::ACCENT_BRIGHT,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -39,7 +39,7 @@ pub fn UserPromptDialog(props: &UserPromptDialogProps) -> impl Into<AnyElement<'
                 // Title
                 Text(
                     content: "❓ Agent Question",
-                    color: theme::ACCENT_BRIGHT,
+                    color: theme::accent_bright(),
                     weight: Weight::Bold,
                 )
 
@@ -48,7 +48,7 @@ pub fn UserPromptDialog(props: &UserPromptDialogProps) -> impl Into<AnyElement<'
                 // Question text
                 Text(
                     content: props.title.clone(),
-                    color: theme::TEXT,
+                    color: theme::text(),
                     weight: Weight::Bold,
                 )
 
@@ -58,7 +58,7 @@ pub fn UserPromptDialog(props: &UserPromptDialogProps) -> impl Into<AnyElement<'
                         View(margin_top: 1) {
                             Text(
                                 content: props.description.clone(),
-                                color: theme::MUTED,
+                                color: theme::muted(),
                             )
                         }
                     }.into_any()
@@ -71,19 +71,19 @@ pub fn UserPromptDialog(props: &UserPromptDialogProps) -> impl Into<AnyElement<'
                 // Input field
                 Text(
                     content: "Your answer:",
-                    color: theme::MUTED,
+                    color: theme::muted(),
                 )
                 View(
                     flex_direction: FlexDirection::Row,
                     border_style: BorderStyle::Single,
-                    border_color: theme::ACCENT,
+                    border_color: theme::accent(),
                     padding_left: 1,
                     padding_right: 1,
                     min_height: 1u32,
                 ) {
                     Text(
                         content: format!("{}{}", props.input, cursor),
-                        color: theme::TEXT,
+                        color: theme::text(),
                     )
                 }
 
@@ -92,7 +92,7 @@ pub fn UserPromptDialog(props: &UserPromptDialogProps) -> impl Into<AnyElement<'
                 // Hint
                 Text(
                     content: "Enter ↩ submit  ·  Esc dismiss",
-                    color: theme::MUTED,
+                    color: theme::muted(),
                 )
             }
         }
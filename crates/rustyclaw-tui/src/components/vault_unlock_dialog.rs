@@ -1,6 +1,7 @@
 // ── Vault unlock dialog — password entry overlay ────────────────────────────
 
 use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
 use crate::theme;
 
 #[derive(Default, Props)]
@@ -28,8 +29,8 @@ pub fn VaultUnlockDialog(props: &VaultUnlockDialogProps) -> impl Into<AnyElement
                 width: 48,
                 flex_direction: FlexDirection::Column,
                 border_style: BorderStyle::Round,
-                border_color: theme::WARN,
-                background_color: theme::BG_SURFACE,
+                border_color: theme::warn(),
+                background_color: theme::bg_surface(),
                 padding_left: 2,
                 padding_right: 2,
                 padding_top: 1,
@@ -37,8 +38,8 @@ pub fn VaultUnlockDialog(props: &VaultUnlockDialogProps) -> impl Into<AnyElement
             ) {
                 // Title
                 Text(
-                    content: "🔒 Vault Locked",
-                    color: theme::WARN,
+                    content: t("dialog.vault_locked"),
+                    color: theme::warn(),
                     weight: Weight::Bold,
                 )
 
@@ -46,7 +47,7 @@ pub fn VaultUnlockDialog(props: &VaultUnlockDialogProps) -> impl Into<AnyElement
 
                 Text(
                     content: "Enter your vault password:",
-                    color: theme::TEXT,
+                    color: theme::text(),
                 )
 
                 View(height: 1)
@@ -55,13 +56,13 @@ pub fn VaultUnlockDialog(props: &VaultUnlockDialogProps) -> impl Into<AnyElement
                 View(
                     flex_direction: FlexDirection::Row,
                     border_style: BorderStyle::Single,
-                    border_color: theme::ACCENT,
+                    border_color: theme::accent(),
                     padding_left: 1,
                     padding_right: 1,
                 ) {
                     Text(
                         content: format!("{}{}", dots, cursor),
-                        color: theme::ACCENT_BRIGHT,
+                        color: theme::accent_bright(),
                     )
                 }
 
@@ -69,7 +70,7 @@ pub fn VaultUnlockDialog(props: &VaultUnlockDialogProps) -> impl Into<AnyElement
                 #(if has_error {
                     element! {
                         View(margin_top: 1) {
-                            Text(content: props.error.clone(), color: theme::ERROR)
+                            Text(content: props.error.clone(), color: theme::error())
                         }
                     }.into_any()
                 } else {
@@ -80,7 +81,7 @@ pub fn VaultUnlockDialog(props: &VaultUnlockDialogProps) -> impl Into<AnyElement
 
                 Text(
                     content: "Enter ↩ submit  ·  Esc cancel",
-                    color: theme::MUTED,
+                    color: theme::muted(),
                 )
             }
         }
@@ -0,0 +1,109 @@
+// ── Workspace dialog — switch the active workspace ──────────────────────────
+
+use iocraft::prelude::*;
+use rustyclaw_core::i18n::t;
+use crate::theme;
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceInfo {
+    pub path: String,
+    /// The workspace the gateway is currently using.
+    pub current: bool,
+    /// The workspace has a `.rustyclaw/` project overlay.
+    pub has_overlay: bool,
+}
+
+#[derive(Default, Props)]
+pub struct WorkspaceDialogProps {
+    pub workspaces: Vec<WorkspaceInfo>,
+    pub selected: Option<usize>,
+    pub scroll_offset: usize,
+}
+
+#[component]
+pub fn WorkspaceDialog(props: &WorkspaceDialogProps) -> impl Into<AnyElement<'static>> {
+    let sel = props.selected.unwrap_or(0);
+
+    element! {
+        View(
+            width: 100pct,
+            height: 100pct,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+        ) {
+            View(
+                width: 70pct,
+                max_height: 80pct,
+                flex_direction: FlexDirection::Column,
+                border_style: BorderStyle::Round,
+                border_color: theme::accent_bright(),
+                background_color: theme::bg_surface(),
+                padding_left: 2,
+                padding_right: 2,
+                padding_top: 1,
+                padding_bottom: 1,
+                overflow: Overflow::Hidden,
+            ) {
+                // Title
+                Text(
+                    content: t("dialog.workspaces"),
+                    color: theme::accent_bright(),
+                    weight: Weight::Bold,
+                )
+
+                View(height: 1)
+
+                Text(
+                    content: "Current and recently used workspaces (◆ = project overlay)",
+                    color: theme::text_dim(),
+                )
+
+                View(height: 1)
+
+                // Workspace list
+                View(
+                    flex_direction: FlexDirection::Column,
+                    width: 100pct,
+                    overflow: Overflow::Hidden,
+                ) {
+                    #(props.workspaces.iter().enumerate().skip(props.scroll_offset).take(20).map(|(i, w)| {
+                        let is_selected = i == sel;
+                        let bg = if is_selected { Some(theme::accent_bright()) } else { None };
+                        let pointer = if is_selected { "▸ " } else { "  " };
+                        let fg = if is_selected {
+                            theme::bg_main()
+                        } else if w.current {
+                            theme::success()
+                        } else {
+                            theme::text()
+                        };
+                        let marker = if w.has_overlay { "◆" } else { " " };
+                        let suffix = if w.current { "  (current)" } else { "" };
+                        let line = format!("{}{} {}{}", pointer, marker, w.path, suffix);
+                        element! {
+                            View(
+                                key: i as u64,
+                                width: 100pct,
+                                background_color: bg.unwrap_or(Color::Reset),
+                            ) {
+                                Text(content: line, color: fg, wrap: TextWrap::NoWrap)
+                            }
+                        }
+                    }))
+                }
+
+                View(height: 1)
+
+                // Hint
+                View(flex_direction: FlexDirection::Row) {
+                    Text(content: "↑↓ ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.navigate")), color: theme::muted())
+                    Text(content: "Enter ", color: theme::accent_bright())
+                    Text(content: format!("{}  ", t("key.switch")), color: theme::muted())
+                    Text(content: "Esc ", color: theme::accent_bright())
+                    Text(content: t("key.close"), color: theme::muted())
+                }
+            }
+        }
+    }
+}
@@ -3,7 +3,12 @@
 //! This module provides helpers for the TUI client to convert server frames
 //! into application actions.
 
-use rustyclaw_core::gateway::{ServerFrame, ServerPayload, StatusType};
+use rustyclaw_core::error::Error;
+use rustyclaw_core::gateway::protocol::version;
+use rustyclaw_core::gateway::{
+    ClientFrame, ClientFrameType, ClientPayload, ServerFrame, ServerPayload, StatusType,
+};
+use rustyclaw_core::i18n::t;
 use crate::action::Action;
 
 /// Result of processing a server frame - includes optional action and whether to update UI.
@@ -35,6 +40,43 @@ impl FrameAction {
 
 /// Convert a server frame into TUI actions.
 /// This encapsulates all the protocol parsing logic in one place.
+/// Error text for the chat, with a hint on what to do for the errors a
+/// user can act on.
+pub fn describe_error(error: &Error) -> String {
+    let hint = match error {
+        Error::ProviderError { status: Some(401 | 403), .. } => Some(t("error.hint.auth")),
+        Error::ProviderError { status: Some(429), .. } => Some(t("error.hint.rate_limit")),
+        Error::ProviderError { .. } if error.is_retryable() => Some(t("error.hint.retry")),
+        Error::PolicyDenied { .. } => Some(t("error.hint.policy")),
+        _ => None,
+    };
+    match hint {
+        Some(hint) => format!("{}\n  ↳ {}", error, hint),
+        None => error.to_string(),
+    }
+}
+
+/// Capabilities the TUI asks the gateway for.
+pub const CAPABILITIES: &[&str] = &[
+    version::CAP_STREAMING,
+    version::CAP_TOOL_EVENTS,
+    version::CAP_BINARY_FRAMES,
+    version::CAP_NOTIFICATIONS,
+    version::CAP_USAGE,
+    version::CAP_COMPRESSION,
+];
+
+/// The `Negotiate` frame sent in reply to the gateway's `Hello`.
+pub fn negotiate_frame() -> ClientFrame {
+    ClientFrame {
+        frame_type: ClientFrameType::Negotiate,
+        payload: ClientPayload::Negotiate {
+            protocol_version: version::PROTOCOL_VERSION,
+            capabilities: version::capability_list(CAPABILITIES),
+        },
+    }
+}
+
 pub fn server_frame_to_action(frame: &ServerFrame) -> FrameAction {
     use ServerPayload;
 
@@ -203,14 +245,15 @@ pub fn server_frame_to_action(frame: &ServerFrame) -> FrameAction {
             name,
             result,
             is_error,
+            ..
         } => FrameAction::just_action(Action::GatewayToolResult {
             id: id.clone(),
             name: name.clone(),
             result: result.clone(),
             is_error: *is_error,
         }),
-        ServerPayload::Error { message, .. } => {
-            FrameAction::just_action(Action::Error(message.clone()))
+        ServerPayload::Error { error, .. } => {
+            FrameAction::just_action(Action::Error(describe_error(error)))
         }
         ServerPayload::Info { message } => FrameAction::just_action(Action::Info(message.clone())),
         ServerPayload::ToolApprovalRequest {
@@ -227,7 +270,37 @@ pub fn server_frame_to_action(frame: &ServerFrame) -> FrameAction {
             prompt.id = id.clone();
             FrameAction::just_action(Action::UserPromptRequest(prompt))
         }
+        ServerPayload::PlanUpdate { plan } => {
+            FrameAction::just_action(Action::PlanUpdate(plan.clone()))
+        }
+        ServerPayload::Stats { stats } => {
+            FrameAction::just_action(Action::GatewayStats(stats.clone()))
+        }
+        ServerPayload::SessionView { view } => {
+            FrameAction::just_action(Action::SessionView(view.clone()))
+        }
+        ServerPayload::Progress { ops } => {
+            FrameAction::just_action(Action::Progress(ops.clone()))
+        }
+        ServerPayload::Notification { notification } => {
+            FrameAction::just_action(Action::Notification(notification.clone()))
+        }
+        ServerPayload::Usage { usage } => {
+            FrameAction::just_action(Action::Usage(usage.clone()))
+        }
+        ServerPayload::EventsTailResult { events } => {
+            let text = if events.is_empty() {
+                "No gateway events recorded.".to_string()
+            } else {
+                let lines: Vec<String> = events.iter().map(|e| e.line()).collect();
+                format!("Gateway events (last {}):\n{}", events.len(), lines.join("\n"))
+            };
+            FrameAction::just_action(Action::Info(text))
+        }
         ServerPayload::Empty => FrameAction::none(),
+        ServerPayload::Negotiated { .. } => FrameAction::none(),
+        // Reassembled by the connection reader, which keeps the partial files.
+        ServerPayload::MediaChunk { .. } => FrameAction::none(),
     }
 }
 
@@ -272,6 +345,8 @@ mod tests {
                     vault_locked: false,
                     provider: None,
                     model: None,
+                    protocol_version: version::PROTOCOL_VERSION,
+                    capabilities: vec![],
                 },
             };
 
@@ -279,6 +354,18 @@ mod tests {
             assert!(matches!(result.action, Some(Action::Info(_))));
         }
 
+        #[test]
+        fn test_negotiate_frame_is_accepted_by_gateway() {
+            let frame = negotiate_frame();
+            let ClientPayload::Negotiate { protocol_version, capabilities } = frame.payload else {
+                panic!("Expected Negotiate payload");
+            };
+            let agreed = version::Negotiated::negotiate(protocol_version, &capabilities).unwrap();
+            assert_eq!(agreed.version, version::PROTOCOL_VERSION);
+            assert!(agreed.has(version::CAP_NOTIFICATIONS));
+            assert!(agreed.has(version::CAP_USAGE));
+        }
+
         #[test]
         fn test_status_model_ready_to_action() {
             let frame = ServerFrame {
@@ -355,6 +442,7 @@ mod tests {
                 payload: ServerPayload::Error {
                     ok: false,
                     message: "Connection failed".into(),
+                    error: Error::Internal("Connection failed".into()),
                 },
             };
 
@@ -365,6 +453,18 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_error_hints_by_category() {
+            let unauthorized = Error::provider("openai", Some(401), "invalid api key");
+            assert_eq!(
+                describe_error(&unauthorized),
+                format!("openai returned 401 — invalid api key\n  ↳ {}", t("error.hint.auth")),
+            );
+            let denied = Error::PolicyDenied { tool: "execute_command".into(), reason: "is denied by user policy".into() };
+            assert!(describe_error(&denied).ends_with(t("error.hint.policy")));
+            assert_eq!(describe_error(&Error::UserError("No model configured".into())), "No model configured");
+        }
+
         #[test]
         fn test_secrets_list_result_to_action() {
             let frame = ServerFrame {
@@ -414,6 +514,125 @@ mod tests {
             assert!(matches!(result.action, Some(Action::GatewayResponseDone)));
         }
 
+        #[test]
+        fn test_stats_frame_to_action() {
+            let stats = rustyclaw_core::gateway::stats::GatewayStats {
+                last_latency_ms: Some(90_000),
+                pending_tool_calls: 1,
+                ..Default::default()
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Stats,
+                payload: ServerPayload::Stats { stats: stats.clone() },
+            };
+
+            match server_frame_to_action(&frame).action {
+                Some(Action::GatewayStats(s)) => assert_eq!(s, stats),
+                _ => panic!("Expected GatewayStats action"),
+            }
+        }
+
+        #[test]
+        fn test_session_view_frame_to_action() {
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::SessionView,
+                payload: ServerPayload::SessionView { view: None },
+            };
+
+            assert!(matches!(
+                server_frame_to_action(&frame).action,
+                Some(Action::SessionView(None))
+            ));
+        }
+
+        #[test]
+        fn test_progress_frame_to_action() {
+            use rustyclaw_core::progress::{ProgressOp, Unit};
+            let op = ProgressOp {
+                id: 1,
+                label: "Converting talk.mov".into(),
+                unit: Unit::Percent,
+                done: 40,
+                total: Some(100),
+                elapsed_ms: 8000,
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Progress,
+                payload: ServerPayload::Progress { ops: vec![op.clone()] },
+            };
+
+            match server_frame_to_action(&frame).action {
+                Some(Action::Progress(ops)) => assert_eq!(ops, vec![op]),
+                _ => panic!("Expected Progress action"),
+            }
+        }
+
+        #[test]
+        fn test_notification_frame_to_action() {
+            use rustyclaw_core::notifications::{Notification, Severity, Source};
+            let notification = Notification {
+                id: 4,
+                severity: Severity::Success,
+                source: Source::Subagent,
+                title: "Sub-agent research finished".into(),
+                body: "Found three candidate libraries.".into(),
+                session: Some("agent:main:subagent:1".into()),
+                at_ms: 1_700_000_000_000,
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Notification,
+                payload: ServerPayload::Notification { notification: notification.clone() },
+            };
+
+            match server_frame_to_action(&frame).action {
+                Some(Action::Notification(n)) => assert_eq!(n, notification),
+                _ => panic!("Expected Notification action"),
+            }
+        }
+
+        #[test]
+        fn test_events_tail_frame_to_action() {
+            let event = rustyclaw_core::observability::event_log::LogEvent {
+                at_ms: 1_700_000_000_000,
+                level: "ERROR".into(),
+                target: "rustyclaw_core::gateway::providers".into(),
+                message: "Provider returned 529".into(),
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::EventsTailResult,
+                payload: ServerPayload::EventsTailResult { events: vec![event] },
+            };
+
+            match server_frame_to_action(&frame).action {
+                Some(Action::Info(text)) => {
+                    assert!(text.starts_with("Gateway events (last 1):"));
+                    assert!(text.contains("ERROR gateway::providers  Provider returned 529"));
+                }
+                _ => panic!("Expected Info action"),
+            }
+        }
+
+        #[test]
+        fn test_usage_frame_to_action() {
+            let usage = rustyclaw_core::gateway::usage::Usage {
+                model: "gpt-4.1".into(),
+                context_tokens: 12_000,
+                context_window: 1_000_000,
+                prompt_tokens: 12_000,
+                completion_tokens: 300,
+                cost_usd: Some(0.0264),
+            };
+            let frame = ServerFrame {
+                frame_type: ServerFrameType::Usage,
+                payload: ServerPayload::Usage { usage: usage.clone() },
+            };
+
+            match server_frame_to_action(&frame).action {
+                Some(Action::Usage(u)) => assert_eq!(u, usage),
+                _ => panic!("Expected Usage action"),
+            }
+        }
+
         #[test]
         fn test_streaming_frames_to_actions() {
             let start_frame = ServerFrame {
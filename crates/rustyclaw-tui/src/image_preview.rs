@@ -0,0 +1,286 @@
+// ── Inline image previews ───────────────────────────────────────────────────
+//
+// Tool results that end in `MEDIA: <path>` (screenshots, plots, camera
+// snaps, generated images) get a small preview under the result bubble.
+//
+// How it's drawn depends on the terminal:
+//
+// * kitty / Ghostty — the kitty graphics protocol with Unicode placeholders:
+//   the image is transmitted once, out of band, and the bubble contains
+//   ordinary placeholder cells that the terminal replaces with pixels, so the
+//   preview scrolls and re-renders with the rest of the layout.
+// * iTerm2 / WezTerm and sixel terminals — their protocols paint pixels at
+//   the cursor, outside the cell grid iocraft diffs, so an inline image
+//   would be painted over on the next frame. They use the block fallback.
+// * everything else — Unicode half blocks (`▀`), two pixels per cell.
+//
+// `RUSTYCLAW_IMAGES=kitty|blocks|off` overrides detection; previews are off
+// under NO_COLOR.
+
+use base64::Engine;
+use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageFormat};
+use rustyclaw_core::theme::{self as core_theme, ColorLevel};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Preview size limits, in cells.
+pub const MAX_COLS: u32 = 48;
+pub const MAX_ROWS: u32 = 16;
+
+/// Files larger than this aren't previewed.
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Longest side of the image sent over the kitty protocol, in pixels.
+const KITTY_MAX_PIXELS: u32 = 800;
+
+/// Base64 payload per kitty escape sequence.
+const KITTY_CHUNK: usize = 4096;
+
+/// The placeholder character kitty replaces with image pixels.
+const KITTY_PLACEHOLDER: char = '\u{10EEEE}';
+
+/// Row/column diacritics from kitty's `rowcolumn-diacritics.txt`; the
+/// n-th entry encodes row (or column) n.
+const KITTY_DIACRITICS: [char; MAX_ROWS as usize] = [
+    '\u{0305}', '\u{030D}', '\u{030E}', '\u{0310}', '\u{0312}', '\u{033D}', '\u{033E}', '\u{033F}',
+    '\u{0346}', '\u{034A}', '\u{034B}', '\u{034C}', '\u{0350}', '\u{0351}', '\u{0352}', '\u{0357}',
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// What the terminal can draw images with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Graphics {
+    Kitty,
+    Iterm2,
+    Sixel,
+    Blocks,
+    Off,
+}
+
+impl Graphics {
+    /// Detect from the environment.
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        if core_theme::color_level() == ColorLevel::None {
+            return Graphics::Off;
+        }
+        Self::from_env_values(
+            &var("RUSTYCLAW_IMAGES"),
+            &var("TERM"),
+            &var("TERM_PROGRAM"),
+            !var("KITTY_WINDOW_ID").is_empty(),
+        )
+    }
+
+    fn from_env_values(overridden: &str, term: &str, term_program: &str, kitty_window: bool) -> Self {
+        match overridden.to_ascii_lowercase().as_str() {
+            "kitty" => return Graphics::Kitty,
+            "blocks" => return Graphics::Blocks,
+            "off" | "none" => return Graphics::Off,
+            _ => {}
+        }
+        let term = term.to_ascii_lowercase();
+        let program = term_program.to_ascii_lowercase();
+        if kitty_window || term == "xterm-kitty" || term == "xterm-ghostty" || program == "ghostty" {
+            Graphics::Kitty
+        } else if program == "iterm.app" || program == "wezterm" {
+            Graphics::Iterm2
+        } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+            Graphics::Sixel
+        } else {
+            Graphics::Blocks
+        }
+    }
+
+    /// How previews are drawn inline in the TUI (see the module comment).
+    fn inline(self) -> Self {
+        match self {
+            Graphics::Iterm2 | Graphics::Sixel => Graphics::Blocks,
+            other => other,
+        }
+    }
+}
+
+/// One half-block cell: the top and bottom pixel.
+pub type BlockCell = ((u8, u8, u8), (u8, u8, u8));
+
+/// A preview ready to render.
+#[derive(Debug)]
+pub enum Preview {
+    /// Rows of half-block cells.
+    Blocks(Vec<Vec<BlockCell>>),
+    /// Rows of kitty placeholder text; the image id is the foreground colour.
+    Kitty { id: u32, rows: Vec<String> },
+}
+
+/// Image files referenced by `MEDIA:` lines in a tool result.
+pub fn media_images(result: &str) -> Vec<PathBuf> {
+    result
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("MEDIA:"))
+        .map(|path| PathBuf::from(path.trim()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        })
+        .collect()
+}
+
+/// Build a preview of `path`, or `None` if previews are off or the file
+/// can't be read (e.g. it lives on a remote gateway's disk).
+pub fn load(path: &Path) -> Option<Arc<Preview>> {
+    let graphics = Graphics::detect().inline();
+    if graphics == Graphics::Off {
+        return None;
+    }
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let img = image::open(path).ok()?;
+    let preview = match graphics {
+        Graphics::Kitty => kitty_preview(&img)?,
+        _ => Preview::Blocks(blocks(&img, MAX_COLS, MAX_ROWS)),
+    };
+    Some(Arc::new(preview))
+}
+
+/// Cell grid (cols, rows) that fits `img` within the limits, keeping the
+/// aspect ratio with cells about twice as tall as wide.
+fn fit(img: &DynamicImage, max_cols: u32, max_rows: u32) -> (u32, u32) {
+    let (w, h) = img.dimensions();
+    let (w, h) = (w.max(1) as f64, h.max(1) as f64);
+    let scale = (max_cols as f64 / w).min(2.0 * max_rows as f64 / h);
+    let cols = ((w * scale).round() as u32).clamp(1, max_cols);
+    let rows = ((h * scale / 2.0).round() as u32).clamp(1, max_rows);
+    (cols, rows)
+}
+
+/// Downsample `img` into half-block cells.
+fn blocks(img: &DynamicImage, max_cols: u32, max_rows: u32) -> Vec<Vec<BlockCell>> {
+    let (cols, rows) = fit(img, max_cols, max_rows);
+    let small = img.resize_exact(cols, rows * 2, FilterType::Triangle).to_rgb8();
+    (0..rows)
+        .map(|row| {
+            (0..cols)
+                .map(|col| {
+                    let top = small.get_pixel(col, row * 2).0;
+                    let bottom = small.get_pixel(col, row * 2 + 1).0;
+                    ((top[0], top[1], top[2]), (bottom[0], bottom[1], bottom[2]))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn kitty_preview(img: &DynamicImage) -> Option<Preview> {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (cols, rows) = fit(img, MAX_COLS, MAX_ROWS);
+
+    let scaled = if img.width().max(img.height()) > KITTY_MAX_PIXELS {
+        img.resize(KITTY_MAX_PIXELS, KITTY_MAX_PIXELS, FilterType::Triangle)
+    } else {
+        img.clone()
+    };
+    let mut png = Vec::new();
+    scaled.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).ok()?;
+
+    // Transmission is out of band (no cursor movement, replies suppressed),
+    // so it can go straight to the terminal between frames.
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(kitty_transmit(id, &png, cols, rows).as_bytes()).ok()?;
+    stdout.flush().ok()?;
+
+    Some(Preview::Kitty { id, rows: kitty_rows(cols, rows) })
+}
+
+/// Escape sequences that upload `png` as image `id` with a virtual
+/// placement of `cols`×`rows` cells.
+fn kitty_transmit(id: u32, png: &[u8], cols: u32, rows: u32) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(png);
+    let chunks: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|c| std::str::from_utf8(c).unwrap_or_default())
+        .collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=100,q=2,U=1,i={},c={},r={},m={};{}\x1b\\",
+                id, cols, rows, more, chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+/// Placeholder text for each row; only the first cell of a row carries
+/// diacritics, kitty infers the following columns.
+fn kitty_rows(cols: u32, rows: u32) -> Vec<String> {
+    (0..rows as usize)
+        .map(|row| {
+            let mut line = String::new();
+            line.push(KITTY_PLACEHOLDER);
+            line.push(KITTY_DIACRITICS[row]);
+            line.push(KITTY_DIACRITICS[0]);
+            for _ in 1..cols {
+                line.push(KITTY_PLACEHOLDER);
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_detects_graphics_support() {
+        assert_eq!(Graphics::from_env_values("", "xterm-kitty", "", false), Graphics::Kitty);
+        assert_eq!(Graphics::from_env_values("", "xterm-256color", "iTerm.app", false), Graphics::Iterm2);
+        assert_eq!(Graphics::from_env_values("", "foot", "", false), Graphics::Sixel);
+        assert_eq!(Graphics::from_env_values("", "xterm-256color", "", false), Graphics::Blocks);
+        assert_eq!(Graphics::from_env_values("off", "xterm-kitty", "", true), Graphics::Off);
+        assert_eq!(Graphics::Sixel.inline(), Graphics::Blocks);
+    }
+
+    #[test]
+    fn test_media_images() {
+        let result = "Plotted x against y\n\nMEDIA: /tmp/plot.png\nMEDIA: /tmp/report.pdf";
+        assert_eq!(media_images(result), vec![PathBuf::from("/tmp/plot.png")]);
+    }
+
+    #[test]
+    fn test_blocks_keep_top_and_bottom_pixels() {
+        // Top half red, bottom half blue: one row of red-over-blue cells.
+        let img = RgbImage::from_fn(4, 4, |_, y| if y < 2 { Rgb([255, 0, 0]) } else { Rgb([0, 0, 255]) });
+        let cells = blocks(&DynamicImage::ImageRgb8(img), 4, 1);
+        assert_eq!(cells.len(), 1);
+        let (top, bottom) = cells[0][0];
+        assert!(top.0 > 200 && top.2 < 50);
+        assert!(bottom.2 > 200 && bottom.0 < 50);
+    }
+
+    #[test]
+    fn test_kitty_transmission_is_chunked() {
+        let payload = vec![0u8; KITTY_CHUNK * 2];
+        let escapes = kitty_transmit(7, &payload, 10, 5);
+        assert!(escapes.starts_with("\x1b_Ga=T,f=100,q=2,U=1,i=7,c=10,r=5,m=1;"));
+        assert!(escapes.ends_with("\x1b\\"));
+        assert!(escapes.contains("\x1b_Gm=0;"));
+
+        let rows = kitty_rows(3, 2);
+        assert_eq!(rows[1].chars().count(), 5);
+        assert_eq!(rows[1].chars().nth(1), Some(KITTY_DIACRITICS[1]));
+    }
+}
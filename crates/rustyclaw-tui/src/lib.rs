@@ -4,8 +4,13 @@
 
 pub mod action;
 pub mod app;
+pub mod attach;
 pub mod components;
 pub mod gateway_client;
+pub mod image_preview;
 pub mod onboard;
+pub mod palette;
+pub mod paste;
 pub mod theme;
 pub mod types;
+pub mod vim;
@@ -10,6 +10,7 @@ use anyhow::{Context, Result};
 use crossterm::terminal;
 
 use rustyclaw_core::config::{Config, MessengerConfig, ModelProvider};
+use rustyclaw_core::i18n;
 use rustyclaw_core::providers::PROVIDERS;
 use rustyclaw_core::secrets::SecretsManager;
 use rustyclaw_core::soul::{SoulManager, DEFAULT_SOUL_CONTENT};
@@ -27,8 +28,10 @@ pub fn run_onboard_wizard(
     let stdin = io::stdin();
     let mut reader = stdin.lock();
 
+    i18n::set_locale(i18n::resolve(config.locale.as_deref()));
+
     println!();
-    t::print_header("🦀  RustyClaw Onboarding  🦀");
+    t::print_header(i18n::t("onboard.header"));
     println!();
 
     // ── Optional reset ─────────────────────────────────────────────
@@ -38,41 +41,31 @@ pub fn run_onboard_wizard(
     }
 
     // ── 0. Safety acknowledgment ───────────────────────────────────
-    println!("{}", t::warn("⚠  Important: Please read before continuing."));
-    println!();
-    println!("  RustyClaw is an {}, meaning it can",
-        t::accent_bright("agentic coding tool"));
-    println!("  read, write, and execute code on your machine on your");
-    println!("  behalf. Like any powerful tool, it should be used with");
-    println!("  care and awareness.");
+    println!("{}", t::warn(i18n::t("onboard.warning")));
     println!();
-    println!("  • {} and modify files in your project", t::bold("It can create"));
-    println!("  • {} commands in your terminal", t::bold("It can run"));
-    println!("  • {} with external APIs using your credentials", t::bold("It can interact"));
-    println!();
-    println!("  Always review actions before approving them, especially");
-    println!("  in production environments. You are responsible for any");
-    println!("  changes made by the tool.");
+    for line in i18n::t("onboard.safety").lines() {
+        println!("  {}", line);
+    }
     println!();
 
     let ack = prompt_line(
         &mut reader,
-        &format!("{} ", t::accent("Do you acknowledge and wish to continue? [y/N]:")),
+        &format!("{} ", t::accent(i18n::t("onboard.acknowledge"))),
     )?;
     if !ack.trim().eq_ignore_ascii_case("y") {
         println!();
-        println!("  {}", t::muted("Onboarding cancelled."));
+        println!("  {}", t::muted(i18n::t("onboard.cancelled")));
         println!();
         return Ok(false);
     }
     println!();
 
     // ── 0b. Name your agent ────────────────────────────────────────
-    println!("{}", t::heading("Name your agent:"));
+    println!("{}", t::heading(i18n::t("onboard.name_heading")));
     println!();
-    println!("  Give your RustyClaw agent a name. This appears in the TUI");
-    println!("  title bar, authenticator app labels, and anywhere the");
-    println!("  agent identifies itself.");
+    for line in i18n::t("onboard.name_help").lines() {
+        println!("  {}", line);
+    }
     println!();
 
     let current_name = if config.agent_name.is_empty() || config.agent_name == "RustyClaw" {
@@ -80,11 +73,10 @@ pub fn run_onboard_wizard(
     } else {
         Some(config.agent_name.clone())
     };
-    let name_prompt = if let Some(ref current) = current_name {
-        format!("Agent name [{}]: ", current)
-    } else {
-        "Agent name [RustyClaw]: ".to_string()
-    };
+    let name_prompt = i18n::tf(
+        "onboard.name_prompt",
+        &[current_name.as_deref().unwrap_or("RustyClaw")],
+    );
     let name_input = prompt_line(&mut reader, &format!("{} ", t::accent(&name_prompt)))?;
     let name_input = name_input.trim().to_string();
     if !name_input.is_empty() {
@@ -92,7 +84,7 @@ pub fn run_onboard_wizard(
     } else if current_name.is_none() {
         config.agent_name = "RustyClaw".to_string();
     }
-    println!("  {}", t::icon_ok(&format!("Agent name: {}", t::accent_bright(&config.agent_name))));
+    println!("  {}", t::icon_ok(&i18n::tf("onboard.agent_name", &[&t::accent_bright(&config.agent_name)])));
     println!();
 
     // ── 1. Secrets vault setup ─────────────────────────────────────
@@ -114,7 +106,7 @@ pub fn run_onboard_wizard(
 
     if !vault_exists {
         // ── First-time setup ───────────────────────────────────────
-        println!("{}", t::heading("Secrets vault setup:"));
+        println!("{}", t::heading(i18n::t("onboard.vault_heading")));
         println!();
         println!("  RustyClaw stores API keys, tokens, and other credentials");
         println!("  in an encrypted vault.  You can protect it with a");
@@ -287,9 +279,11 @@ pub fn run_onboard_wizard(
                         let key = prompt_secret(&mut reader, &format!("{} ", t::accent("Enter API key:")))?;
                         if key.trim().is_empty() {
                             println!("  {}", t::icon_warn("No key entered — keeping existing key."));
-                        } else {
+                        } else if confirm_api_key(&mut reader, provider, key.trim())? {
                             secrets.store_secret(secret_key, key.trim())?;
                             println!("  {}", t::icon_ok("API key updated."));
+                        } else {
+                            println!("  {}", t::icon_warn("Keeping existing key."));
                         }
                     } else {
                         println!("  {}", t::icon_ok("Keeping existing API key."));
@@ -299,9 +293,12 @@ pub fn run_onboard_wizard(
                     if key.trim().is_empty() {
                         println!("  {}", t::icon_warn("No key entered — you can add one later with:"));
                         println!("      {}", t::accent_bright("rustyclaw onboard"));
-                    } else {
+                    } else if confirm_api_key(&mut reader, provider, key.trim())? {
                         secrets.store_secret(secret_key, key.trim())?;
                         println!("  {}", t::icon_ok("API key stored securely."));
+                    } else {
+                        println!("  {}", t::icon_warn("Key not stored — you can add one later with:"));
+                        println!("      {}", t::accent_bright("rustyclaw onboard"));
                     }
                 }
             }
@@ -605,6 +602,44 @@ pub fn run_onboard_wizard(
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
+/// Check a freshly entered API key with the provider and report what it
+/// says.  Returns whether to store the key: one the provider rejects is
+/// only stored if the user insists.
+fn confirm_api_key(
+    reader: &mut impl BufRead,
+    provider: &rustyclaw_core::providers::ProviderDef,
+    key: &str,
+) -> Result<bool> {
+    use rustyclaw_core::providers::{check_api_key, KeyCheckError};
+
+    // Custom endpoints don't have a URL yet at this point.
+    if provider.base_url.is_none() {
+        return Ok(true);
+    }
+    print!("  {} Checking key…", t::muted("⠋"));
+    io::stdout().flush()?;
+    let handle = tokio::runtime::Handle::current();
+    let result = tokio::task::block_in_place(|| handle.block_on(check_api_key(provider.id, key, None)));
+    print!("\r{}\r", " ".repeat(50));
+    io::stdout().flush()?;
+
+    match result {
+        Ok(check) => {
+            println!("  {}", t::icon_ok(&check.summary()));
+            Ok(true)
+        }
+        Err(KeyCheckError::Unverified(msg)) => {
+            println!("  {}", t::icon_warn(&msg));
+            Ok(true)
+        }
+        Err(KeyCheckError::Rejected(msg)) => {
+            println!("  {}", t::icon_fail(&msg));
+            let answer = prompt_line(reader, &format!("{} ", t::accent("Store it anyway? [y/N]:")))?;
+            Ok(answer.trim().eq_ignore_ascii_case("y"))
+        }
+    }
+}
+
 /// Perform OAuth device flow authentication and store the token.
 fn perform_device_flow_auth(
     reader: &mut impl BufRead,
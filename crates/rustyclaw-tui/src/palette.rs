@@ -0,0 +1,220 @@
+// ── Command palette ─────────────────────────────────────────────────────────
+//
+// Ctrl+K opens a fuzzy-searchable list of everything the TUI can jump to:
+// slash commands, files the agent recently touched, saved messenger
+// conversations and installed skills. Commands, conversations and skills
+// run a slash command when picked; files insert their path into the input
+// (where it can be attached, see attach.rs).
+
+use rustyclaw_core::conversations::{conversations_dir, ConversationStore};
+use rustyclaw_core::i18n::t;
+use rustyclaw_core::skills::Skill;
+use std::path::Path;
+
+/// Recently touched files kept for the palette.
+const MAX_RECENT_FILES: usize = 20;
+
+/// Conversations listed in the palette.
+const MAX_SESSIONS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Command,
+    File,
+    Session,
+    Skill,
+}
+
+impl EntryKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            EntryKind::Command => "/",
+            EntryKind::File => "📄",
+            EntryKind::Session => "💬",
+            EntryKind::Skill => "⚡",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EntryKind::Command => t("palette.command"),
+            EntryKind::File => t("palette.file"),
+            EntryKind::Session => t("palette.session"),
+            EntryKind::Skill => t("palette.skill"),
+        }
+    }
+}
+
+/// What picking an entry does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Run a slash command (without the leading `/`).
+    Run(String),
+    /// Insert text into the input line.
+    Insert(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub kind: EntryKind,
+    pub label: String,
+    pub detail: String,
+    pub action: PaletteAction,
+}
+
+/// Every slash command.
+pub fn commands() -> Vec<PaletteEntry> {
+    rustyclaw_core::commands::command_names()
+        .into_iter()
+        .map(|name| PaletteEntry {
+            kind: EntryKind::Command,
+            label: format!("/{}", name),
+            detail: String::new(),
+            action: PaletteAction::Run(name),
+        })
+        .collect()
+}
+
+/// Recently touched files, most recent first.
+pub fn files(recent: &[String]) -> Vec<PaletteEntry> {
+    recent
+        .iter()
+        .map(|path| PaletteEntry {
+            kind: EntryKind::File,
+            label: Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.clone()),
+            detail: path.clone(),
+            action: PaletteAction::Insert(path.clone()),
+        })
+        .collect()
+}
+
+/// Saved messenger conversations in `workspace_dir`, most recent first.
+pub fn sessions(workspace_dir: &Path) -> Vec<PaletteEntry> {
+    let Ok(store) = ConversationStore::new(&conversations_dir(workspace_dir)) else {
+        return Vec::new();
+    };
+    store
+        .list(None)
+        .unwrap_or_default()
+        .into_iter()
+        .take(MAX_SESSIONS)
+        .map(|c| PaletteEntry {
+            kind: EntryKind::Session,
+            label: format!("{}/{}", c.messenger, c.chat),
+            detail: c.preview,
+            action: PaletteAction::Run(format!("conversations show {} {}", c.messenger, c.chat)),
+        })
+        .collect()
+}
+
+/// Installed skills.
+pub fn skills(skills: &[Skill]) -> Vec<PaletteEntry> {
+    skills
+        .iter()
+        .map(|s| PaletteEntry {
+            kind: EntryKind::Skill,
+            label: s.name.clone(),
+            detail: s.description.clone().unwrap_or_default(),
+            action: PaletteAction::Run(format!("skill info {}", s.name)),
+        })
+        .collect()
+}
+
+/// The file a tool call works on, if its arguments name one.
+pub fn tool_call_path(arguments: &str) -> Option<String> {
+    let args: serde_json::Value = serde_json::from_str(arguments).ok()?;
+    let path = args.get("path")?.as_str()?;
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// Move `path` to the front of the recent-files list.
+pub fn remember_file(recent: &mut Vec<String>, path: String) {
+    recent.retain(|p| *p != path);
+    recent.insert(0, path);
+    recent.truncate(MAX_RECENT_FILES);
+}
+
+/// Fuzzy match `query` against `text`: every query character must appear
+/// in order. Consecutive matches and matches at word starts score higher,
+/// gaps lower. `None` when it doesn't match.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut pos = 0usize;
+    let mut last: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|&c| c == q)?;
+        score += 10;
+        match last {
+            Some(prev) if found == prev + 1 => score += 15,
+            Some(prev) => score -= (found - prev - 1).min(10) as i64,
+            None => score -= found.min(10) as i64,
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 10;
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// Indices of the entries matching `query`, best first. An empty query
+/// keeps every entry in its original order.
+pub fn filter(entries: &[PaletteEntry], query: &str) -> Vec<usize> {
+    let mut matches: Vec<(usize, i64)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| {
+            let on_label = score(query, &e.label);
+            let on_detail = score(query, &e.detail).map(|s| s - 20);
+            on_label.max(on_detail).map(|s| (i, s))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.1.cmp(&a.1));
+    matches.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str) -> PaletteEntry {
+        PaletteEntry {
+            kind: EntryKind::Command,
+            label: label.to_string(),
+            detail: String::new(),
+            action: PaletteAction::Run(label.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_score_prefers_contiguous_and_word_starts() {
+        assert!(score("gw", "/gateway").is_some());
+        assert_eq!(score("xyz", "/gateway"), None);
+        assert!(score("split", "/split session") > score("split", "/skills list it"));
+        assert!(score("ss", "/split session") > score("ss", "/dismiss"));
+    }
+
+    #[test]
+    fn test_filter_ranks_best_match_first() {
+        let entries = vec![entry("/dryrun off"), entry("/workspace"), entry("/reload")];
+        assert_eq!(filter(&entries, ""), vec![0, 1, 2]);
+        assert_eq!(filter(&entries, "ws")[0], 1);
+        assert_eq!(filter(&entries, "rel"), vec![2]);
+    }
+
+    #[test]
+    fn test_recent_files() {
+        assert_eq!(tool_call_path(r#"{"path":"src/main.rs","content":"x"}"#).as_deref(), Some("src/main.rs"));
+        assert_eq!(tool_call_path(r#"{"command":"ls"}"#), None);
+
+        let mut recent = vec!["a".to_string(), "b".to_string()];
+        remember_file(&mut recent, "b".to_string());
+        assert_eq!(recent, vec!["b", "a"]);
+        assert_eq!(files(&["/tmp/notes.md".to_string()])[0].label, "notes.md");
+    }
+}
@@ -0,0 +1,173 @@
+// ── Paste detection ─────────────────────────────────────────────────────────
+//
+// iocraft doesn't surface crossterm's bracketed-paste events, so a paste
+// reaches the TUI as one key event per character. Typed into the input
+// line as-is, a large paste grows the single-line text field until every
+// keystroke re-lays out thousands of characters, and the first newline in
+// it submits whatever has arrived so far.
+//
+// Keys that arrive back to back, faster than anyone types, are therefore
+// collected into one paste: newlines inside it don't submit, and once the
+// burst goes quiet it's settled into the input. Short single-line pastes
+// stay inline; anything longer or multi-line becomes a collapsed
+// "[pasted 4.2k chars]" attachment that's sent along with the message.
+
+use rustyclaw_core::i18n::tf;
+use std::time::{Duration, Instant};
+
+/// Keys closer together than this start a paste.
+const BURST_GAP: Duration = Duration::from_millis(10);
+
+/// A paste ends once no key has arrived for this long.
+const QUIET: Duration = Duration::from_millis(40);
+
+/// Single-line pastes up to this many characters stay in the input line.
+const INLINE_MAX_CHARS: usize = 200;
+
+/// Pastes totalling more than this many characters need a second Enter.
+pub const CONFIRM_CHARS: usize = 8_000;
+
+/// Groups rapid key events into pastes.
+#[derive(Debug, Default)]
+pub struct PasteDetector {
+    burst: String,
+    last_key: Option<Instant>,
+    active: bool,
+}
+
+impl PasteDetector {
+    /// Record a key (`'\n'` for Enter, `'\t'` for Tab). Returns true when
+    /// it belongs to a paste and must not be handled as a keystroke.
+    pub fn key(&mut self, c: char, now: Instant) -> bool {
+        let fast = self.last_key.is_some_and(|t| now.duration_since(t) < BURST_GAP);
+        self.last_key = Some(now);
+        if self.active || (fast && !self.burst.is_empty()) {
+            self.active = true;
+            self.burst.push(c);
+            return true;
+        }
+        // A lone keystroke; remember it in case it's the start of a paste.
+        self.burst.clear();
+        if c != '\n' {
+            self.burst.push(c);
+        }
+        false
+    }
+
+    /// Whether a paste is arriving right now.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// The finished paste, once the burst has gone quiet.
+    pub fn poll(&mut self, now: Instant) -> Option<String> {
+        let quiet = self.last_key.is_none_or(|t| now.duration_since(t) >= QUIET);
+        if !self.active || !quiet {
+            return None;
+        }
+        self.active = false;
+        self.last_key = None;
+        Some(std::mem::take(&mut self.burst))
+    }
+}
+
+/// Fold a finished paste into the input line. The text field has already
+/// inserted the paste's printable characters, so they're taken back out;
+/// returns the new input and the paste to attach, if it's too big to
+/// stay inline.
+pub fn settle(input: &str, paste: String) -> (String, Option<String>) {
+    let echoed: Vec<char> = paste.chars().filter(|c| !c.is_control()).collect();
+    let chars: Vec<char> = input.chars().collect();
+    let overlap = (0..=echoed.len().min(chars.len()))
+        .rev()
+        .find(|&k| chars[chars.len() - k..] == echoed[..k])
+        .unwrap_or(0);
+    let mut value: String = chars[..chars.len() - overlap].iter().collect();
+
+    if paste.contains('\n') || paste.chars().count() > INLINE_MAX_CHARS {
+        (value, Some(paste))
+    } else {
+        value.push_str(&paste);
+        (value, None)
+    }
+}
+
+/// Label for `n` pasted characters, e.g. `[pasted 4.2k chars]`.
+pub fn label(n: usize) -> String {
+    let count = if n >= 1000 {
+        format!("{:.1}k", n as f64 / 1000.0)
+    } else {
+        n.to_string()
+    };
+    tf("paste.label", &[count.as_str()])
+}
+
+/// Total characters across `pastes`.
+pub fn total_chars(pastes: &[String]) -> usize {
+    pastes.iter().map(|p| p.chars().count()).sum()
+}
+
+/// The message sent to the model: the typed text followed by the pastes.
+pub fn compose(input: &str, pastes: &[String]) -> String {
+    std::iter::once(input.trim())
+        .chain(pastes.iter().map(|p| p.as_str()))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(detector: &mut PasteDetector, text: &str, start: Instant, gap_ms: u64) -> Vec<bool> {
+        text.chars()
+            .enumerate()
+            .map(|(i, c)| detector.key(c, start + Duration::from_millis(i as u64 * gap_ms)))
+            .collect()
+    }
+
+    #[test]
+    fn test_typing_is_not_a_paste() {
+        let mut detector = PasteDetector::default();
+        let start = Instant::now();
+        assert!(feed(&mut detector, "hello\n", start, 80).iter().all(|swallowed| !swallowed));
+        assert_eq!(detector.poll(start + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_burst_becomes_one_paste() {
+        let mut detector = PasteDetector::default();
+        let start = Instant::now();
+        let swallowed = feed(&mut detector, "fn main() {\n}\n", start, 0);
+        // The first key is handled as typing; the rest, newlines included, are held.
+        assert!(!swallowed[0]);
+        assert!(swallowed[1..].iter().all(|s| *s));
+        assert!(detector.active());
+        assert_eq!(detector.poll(start), None);
+        assert_eq!(detector.poll(start + QUIET).as_deref(), Some("fn main() {\n}\n"));
+        assert!(!detector.active());
+    }
+
+    #[test]
+    fn test_settle_removes_echo() {
+        // The text field echoed "ab" (before the paste was noticed) and "cd".
+        let (value, attached) = settle("say: abcd", "ab\ncd".to_string());
+        assert_eq!(value, "say: ");
+        assert_eq!(attached.as_deref(), Some("ab\ncd"));
+
+        let (value, attached) = settle("open https://exa", "https://example.com".to_string());
+        assert_eq!(value, "open https://example.com");
+        assert_eq!(attached, None);
+    }
+
+    #[test]
+    fn test_compose_and_label() {
+        let pastes = vec!["x".repeat(4200)];
+        assert_eq!(label(4200), "[pasted 4.2k chars]");
+        assert_eq!(label(850), "[pasted 850 chars]");
+        assert_eq!(compose(" explain ", &["a\nb".to_string()]), "explain\n\na\nb");
+        assert_eq!(compose("", &pastes).len(), 4200);
+        assert_eq!(total_chars(&pastes), 4200);
+    }
+}
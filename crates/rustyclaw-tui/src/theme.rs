@@ -3,34 +3,156 @@
 // Colour palette for the iocraft TUI.
 
 use iocraft::prelude::*;
+use rustyclaw_core::theme::{self as core_theme, ColorLevel, PaletteName};
 use rustyclaw_core::types::MessageRole;
 
-// ── Accent (teal / cyan) ────────────────────────────────────────────────────
+// ── Palettes ────────────────────────────────────────────────────────────────
+//
+// Colours are picked at render time from the palette selected in config
+// (`palette`) and degraded to the terminal's colour level — 256 colours,
+// the basic ANSI set, or nothing at all under NO_COLOR.
+
+type Rgb = (u8, u8, u8);
+
+struct TuiPalette {
+    accent: Rgb,
+    accent_bright: Rgb,
+    accent_dim: Rgb,
+    text: Rgb,
+    text_dim: Rgb,
+    muted: Rgb,
+    info: Rgb,
+    success: Rgb,
+    warn: Rgb,
+    error: Rgb,
+    bg_main: Rgb,
+    bg_surface: Rgb,
+    bg_user: Rgb,
+    bg_assistant: Rgb,
+    bg_code: Rgb,
+}
+
+/// Teal accent with the usual red / green / amber semantics.
+const DEFAULT: TuiPalette = TuiPalette {
+    accent: (0, 175, 175),
+    accent_bright: (0, 215, 215),
+    accent_dim: (0, 95, 95),
+    text: (198, 208, 220),
+    text_dim: (110, 120, 135),
+    muted: (75, 85, 99),
+    info: (66, 165, 245),
+    success: (102, 187, 106),
+    warn: (255, 167, 38),
+    error: (239, 83, 80),
+    bg_main: (22, 22, 30),
+    bg_surface: (30, 30, 40),
+    bg_user: (24, 35, 45),
+    bg_assistant: (28, 28, 38),
+    bg_code: (26, 26, 36),
+};
+
+/// Red-green safe (Okabe–Ito): success is sky blue, errors vermillion.
+const RED_GREEN: TuiPalette = TuiPalette {
+    info: (204, 121, 167),
+    success: (86, 180, 233),
+    warn: (240, 228, 66),
+    error: (213, 94, 0),
+    ..DEFAULT
+};
+
+/// Blue-yellow safe: success is teal, warnings magenta, errors red.
+const BLUE_YELLOW: TuiPalette = TuiPalette {
+    accent: (204, 121, 167),
+    accent_bright: (224, 156, 196),
+    accent_dim: (120, 70, 98),
+    info: (170, 170, 200),
+    success: (0, 158, 115),
+    warn: (224, 92, 176),
+    error: (215, 38, 30),
+    ..DEFAULT
+};
+
+fn palette() -> &'static TuiPalette {
+    match core_theme::palette_name() {
+        PaletteName::Default => &DEFAULT,
+        PaletteName::RedGreen => &RED_GREEN,
+        PaletteName::BlueYellow => &BLUE_YELLOW,
+    }
+}
+
+/// Foreground colour for `rgb` at the terminal's colour level.
+fn fg(rgb: Rgb) -> Color {
+    match core_theme::color_level() {
+        ColorLevel::None => Color::Reset,
+        ColorLevel::Ansi8 => basic(rgb),
+        ColorLevel::Ansi256 => Color::AnsiValue(core_theme::nearest_ansi256(rgb)),
+        ColorLevel::TrueColor => Color::Rgb { r: rgb.0, g: rgb.1, b: rgb.2 },
+    }
+}
+
+/// Background colour; dropped below 256 colours, where the dark panel
+/// shades can't be told apart from the terminal's own background.
+fn bg(rgb: Rgb) -> Color {
+    match core_theme::color_level() {
+        ColorLevel::None | ColorLevel::Ansi8 => Color::Reset,
+        _ => fg(rgb),
+    }
+}
 
-pub const ACCENT: Color = Color::Rgb { r: 0, g: 175, b: 175 };
-pub const ACCENT_BRIGHT: Color = Color::Rgb { r: 0, g: 215, b: 215 };
-pub const ACCENT_DIM: Color = Color::Rgb { r: 0, g: 95, b: 95 };
+fn basic(rgb: Rgb) -> Color {
+    let max = rgb.0.max(rgb.1).max(rgb.2);
+    let min = rgb.0.min(rgb.1).min(rgb.2);
+    if max - min < 40 {
+        // Greys keep text / dim / muted distinguishable.
+        return match max {
+            0..=59 => Color::Black,
+            60..=149 => Color::DarkGrey,
+            _ => Color::Grey,
+        };
+    }
+    match core_theme::nearest_ansi8(rgb) {
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        0 => Color::Black,
+        _ => Color::Grey,
+    }
+}
+
+// ── Accent ──────────────────────────────────────────────────────────────────
+
+pub fn accent() -> Color { fg(palette().accent) }
+pub fn accent_bright() -> Color { fg(palette().accent_bright) }
+pub fn accent_dim() -> Color { fg(palette().accent_dim) }
 
 // ── Text ────────────────────────────────────────────────────────────────────
 
-pub const TEXT: Color = Color::Rgb { r: 198, g: 208, b: 220 };
-pub const TEXT_DIM: Color = Color::Rgb { r: 110, g: 120, b: 135 };
-pub const MUTED: Color = Color::Rgb { r: 75, g: 85, b: 99 };
+pub fn text() -> Color { fg(palette().text) }
+pub fn text_dim() -> Color { fg(palette().text_dim) }
+pub fn muted() -> Color { fg(palette().muted) }
 
 // ── Semantic ────────────────────────────────────────────────────────────────
 
-pub const INFO: Color = Color::Rgb { r: 66, g: 165, b: 245 };
-pub const SUCCESS: Color = Color::Rgb { r: 102, g: 187, b: 106 };
-pub const WARN: Color = Color::Rgb { r: 255, g: 167, b: 38 };
-pub const ERROR: Color = Color::Rgb { r: 239, g: 83, b: 80 };
+pub fn info() -> Color { fg(palette().info) }
+pub fn success() -> Color { fg(palette().success) }
+pub fn warn() -> Color { fg(palette().warn) }
+pub fn error() -> Color { fg(palette().error) }
 
 // ── Backgrounds ─────────────────────────────────────────────────────────────
 
-pub const BG_MAIN: Color = Color::Rgb { r: 22, g: 22, b: 30 };
-pub const BG_SURFACE: Color = Color::Rgb { r: 30, g: 30, b: 40 };
-pub const BG_USER: Color = Color::Rgb { r: 24, g: 35, b: 45 };
-pub const BG_ASSISTANT: Color = Color::Rgb { r: 28, g: 28, b: 38 };
-pub const BG_CODE: Color = Color::Rgb { r: 26, g: 26, b: 36 };
+pub fn bg_main() -> Color { bg(palette().bg_main) }
+pub fn bg_surface() -> Color { bg(palette().bg_surface) }
+pub fn bg_user() -> Color { bg(palette().bg_user) }
+pub fn bg_assistant() -> Color { bg(palette().bg_assistant) }
+pub fn bg_code() -> Color { bg(palette().bg_code) }
+
+// ── Images ──────────────────────────────────────────────────────────────────
+
+/// An image pixel at the terminal's colour level.
+pub fn pixel(rgb: Rgb) -> Color { fg(rgb) }
 
 // ── Spinner frames ──────────────────────────────────────────────────────────
 
@@ -40,48 +162,48 @@ pub const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦',
 
 pub fn role_color(role: &MessageRole) -> Color {
     match role {
-        MessageRole::User => ACCENT_BRIGHT,
-        MessageRole::Assistant => TEXT,
-        MessageRole::Info => INFO,
-        MessageRole::Success => SUCCESS,
-        MessageRole::Warning => WARN,
-        MessageRole::Error => ERROR,
-        MessageRole::System => MUTED,
-        MessageRole::ToolCall => MUTED,
-        MessageRole::ToolResult => TEXT_DIM,
-        MessageRole::Thinking => MUTED,
+        MessageRole::User => accent_bright(),
+        MessageRole::Assistant => text(),
+        MessageRole::Info => info(),
+        MessageRole::Success => success(),
+        MessageRole::Warning => warn(),
+        MessageRole::Error => error(),
+        MessageRole::System => muted(),
+        MessageRole::ToolCall => muted(),
+        MessageRole::ToolResult => text_dim(),
+        MessageRole::Thinking => muted(),
     }
 }
 
 pub fn role_bg(role: &MessageRole) -> Color {
     match role {
-        MessageRole::User => BG_USER,
-        MessageRole::Assistant => BG_ASSISTANT,
-        MessageRole::ToolCall | MessageRole::ToolResult => BG_CODE,
-        _ => BG_SURFACE,
+        MessageRole::User => bg_user(),
+        MessageRole::Assistant => bg_assistant(),
+        MessageRole::ToolCall | MessageRole::ToolResult => bg_code(),
+        _ => bg_surface(),
     }
 }
 
 pub fn role_border(role: &MessageRole) -> Color {
     match role {
-        MessageRole::User => ACCENT_BRIGHT,
-        MessageRole::Assistant => MUTED,
-        MessageRole::Error => ERROR,
-        MessageRole::Warning => WARN,
-        MessageRole::Success => SUCCESS,
-        MessageRole::Info => INFO,
-        _ => MUTED,
+        MessageRole::User => accent_bright(),
+        MessageRole::Assistant => muted(),
+        MessageRole::Error => error(),
+        MessageRole::Warning => warn(),
+        MessageRole::Success => success(),
+        MessageRole::Info => info(),
+        _ => muted(),
     }
 }
 
 pub fn gateway_color(status: &rustyclaw_core::types::GatewayStatus) -> Color {
     use rustyclaw_core::types::GatewayStatus::*;
     match status {
-        Connected | ModelReady => SUCCESS,
-        Connecting => WARN,
-        Disconnected | Error | ModelError => ERROR,
-        Unconfigured => MUTED,
-        VaultLocked | AuthRequired => WARN,
+        Connected | ModelReady => success(),
+        Connecting => warn(),
+        Disconnected | Error | ModelError => error(),
+        Unconfigured => muted(),
+        VaultLocked | AuthRequired => warn(),
     }
 }
 
@@ -1,12 +1,21 @@
 // ── Display types for TUI messages ──────────────────────────────────────────
 
 use rustyclaw_core::types::MessageRole;
+use std::sync::Arc;
+
+use crate::image_preview::Preview;
+use rustyclaw_core::gateway::usage::Usage;
+use rustyclaw_core::notifications::Notification;
 
 /// A single message displayed in the chat pane.
 #[derive(Debug, Clone)]
 pub struct DisplayMessage {
     pub role: MessageRole,
     pub content: String,
+    /// Inline preview of an image the message refers to.
+    pub image: Option<Arc<Preview>>,
+    /// Pasted text sent with the message, shown collapsed.
+    pub pastes: Vec<String>,
 }
 
 impl DisplayMessage {
@@ -14,6 +23,8 @@ impl DisplayMessage {
         Self {
             role,
             content: content.into(),
+            image: None,
+            pastes: Vec::new(),
         }
     }
 
@@ -48,8 +59,76 @@ impl DisplayMessage {
         Self::new(MessageRole::Thinking, content)
     }
 
+    /// Attach an image preview.
+    pub fn with_image(mut self, image: Option<Arc<Preview>>) -> Self {
+        self.image = image;
+        self
+    }
+
+    /// Attach pasted text.
+    pub fn with_pastes(mut self, pastes: Vec<String>) -> Self {
+        self.pastes = pastes;
+        self
+    }
+
     /// Append text to the message content.
     pub fn append(&mut self, text: &str) {
         self.content.push_str(text);
     }
 }
+
+/// An entry in the notification center.
+#[derive(Debug, Clone)]
+pub struct NotificationItem {
+    pub notification: Notification,
+    pub read: bool,
+}
+
+/// Notifications kept in the notification center.
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// Add `notification` unless it's already listed (the gateway replays its
+/// recent events on reconnect); newest first.
+pub fn push_notification(items: &mut Vec<NotificationItem>, notification: Notification) {
+    let seen = items.iter().any(|i| {
+        i.notification.id == notification.id && i.notification.at_ms == notification.at_ms
+    });
+    if !seen {
+        items.insert(0, NotificationItem { notification, read: false });
+        items.truncate(MAX_NOTIFICATIONS);
+    }
+}
+
+/// Context usage and running cost of the session, for the usage bar.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionUsage {
+    /// Tokens in the context of the latest model call.
+    pub context_tokens: u64,
+    pub context_window: u64,
+    /// Summed cost of the priced calls so far.
+    pub cost_usd: f64,
+    pub calls: u32,
+    /// Calls to models without a known price (not in `cost_usd`).
+    pub unpriced_calls: u32,
+}
+
+impl SessionUsage {
+    pub fn record(&mut self, usage: &Usage) {
+        self.context_tokens = usage.context_tokens;
+        self.context_window = usage.context_window;
+        self.calls += 1;
+        match usage.cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced_calls += 1,
+        }
+    }
+
+    /// Fraction of the context window in use.
+    pub fn context_fraction(&self) -> f64 {
+        if self.context_window == 0 {
+            0.0
+        } else {
+            self.context_tokens as f64 / self.context_window as f64
+        }
+    }
+}
@@ -0,0 +1,556 @@
+// ── Vim-style input editing ─────────────────────────────────────────────────
+//
+// Optional modal editing for the input bar (`vim_mode = true` in config).
+// The editor owns the cursor and mode; the text itself stays in the app's
+// `input_value` state and is passed in on every key.  Enter, Ctrl+C and the
+// command-menu keys are still handled by the app.
+//
+// Normal mode supports counts, `"x` registers, the motions h l w b e 0 ^ $,
+// the operators d c y (with a motion, doubled for the whole line, or with
+// iw/aw), x X D C s S p P u, and i a I A to enter insert mode.
+
+use iocraft::prelude::{KeyCode, KeyModifiers};
+use std::collections::HashMap;
+
+/// Editing mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Insert,
+    Normal,
+}
+
+impl Mode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::Insert => "INSERT",
+            Mode::Normal => "NORMAL",
+        }
+    }
+}
+
+/// Register that every yank and delete also lands in.
+const UNNAMED: char = '"';
+
+/// Register whose contents are discarded.
+const BLACK_HOLE: char = '_';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    WordForward,
+    WordBackward,
+    WordEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Motion(Motion),
+    Line,
+    InnerWord,
+    AWord,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Move(Motion),
+    Operate(Operator, Target),
+    Simple(char),
+}
+
+enum Parse {
+    Incomplete,
+    Invalid,
+    Done {
+        register: Option<char>,
+        count: usize,
+        command: Command,
+    },
+}
+
+fn motion(c: char) -> Option<Motion> {
+    Some(match c {
+        'h' => Motion::Left,
+        'l' => Motion::Right,
+        '0' => Motion::LineStart,
+        '^' => Motion::FirstNonBlank,
+        '$' => Motion::LineEnd,
+        'w' => Motion::WordForward,
+        'b' => Motion::WordBackward,
+        'e' => Motion::WordEnd,
+        _ => return None,
+    })
+}
+
+/// Parse a pending normal-mode key sequence: `["x][count]command`.
+fn parse(seq: &[char]) -> Parse {
+    let mut i = 0;
+    let mut register = None;
+    if seq.first() == Some(&'"') {
+        match seq.get(1) {
+            Some(&r) => register = Some(r),
+            None => return Parse::Incomplete,
+        }
+        i = 2;
+    }
+
+    let digits_start = i;
+    while i < seq.len() && seq[i].is_ascii_digit() && !(i == digits_start && seq[i] == '0') {
+        i += 1;
+    }
+    let count = seq[digits_start..i]
+        .iter()
+        .collect::<String>()
+        .parse()
+        .unwrap_or(1usize);
+
+    let Some(&c) = seq.get(i) else {
+        return Parse::Incomplete;
+    };
+    let command = match c {
+        'd' | 'c' | 'y' => {
+            let op = match c {
+                'd' => Operator::Delete,
+                'c' => Operator::Change,
+                _ => Operator::Yank,
+            };
+            let Some(&next) = seq.get(i + 1) else {
+                return Parse::Incomplete;
+            };
+            let target = if next == c {
+                Target::Line
+            } else if next == 'i' || next == 'a' {
+                match seq.get(i + 2) {
+                    None => return Parse::Incomplete,
+                    Some('w') if next == 'i' => Target::InnerWord,
+                    Some('w') => Target::AWord,
+                    Some(_) => return Parse::Invalid,
+                }
+            } else if let Some(m) = motion(next) {
+                Target::Motion(m)
+            } else {
+                return Parse::Invalid;
+            };
+            Command::Operate(op, target)
+        }
+        'x' | 'X' | 'D' | 'C' | 's' | 'S' | 'p' | 'P' | 'u' | 'i' | 'a' | 'I' | 'A' => Command::Simple(c),
+        _ => match motion(c) {
+            Some(m) => Command::Move(m),
+            None => return Parse::Invalid,
+        },
+    };
+    Parse::Done { register, count, command }
+}
+
+#[derive(PartialEq, Eq)]
+enum Class {
+    Space,
+    Word,
+    Punct,
+}
+
+fn class(c: char) -> Class {
+    if c.is_whitespace() {
+        Class::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        Class::Word
+    } else {
+        Class::Punct
+    }
+}
+
+fn next_word_start(chars: &[char], mut i: usize) -> usize {
+    let len = chars.len();
+    if i >= len {
+        return len;
+    }
+    let start = class(chars[i]);
+    if start != Class::Space {
+        while i < len && class(chars[i]) == start {
+            i += 1;
+        }
+    }
+    while i < len && class(chars[i]) == Class::Space {
+        i += 1;
+    }
+    i
+}
+
+fn prev_word_start(chars: &[char], mut i: usize) -> usize {
+    while i > 0 && class(chars[i - 1]) == Class::Space {
+        i -= 1;
+    }
+    if i == 0 {
+        return 0;
+    }
+    let c = class(chars[i - 1]);
+    while i > 0 && class(chars[i - 1]) == c {
+        i -= 1;
+    }
+    i
+}
+
+/// Index of the last character of the word at or after `i + 1`.
+fn word_end(chars: &[char], i: usize) -> usize {
+    let len = chars.len();
+    let mut j = i + 1;
+    while j < len && class(chars[j]) == Class::Space {
+        j += 1;
+    }
+    if j >= len {
+        return len.saturating_sub(1);
+    }
+    let c = class(chars[j]);
+    while j + 1 < len && class(chars[j + 1]) == c {
+        j += 1;
+    }
+    j
+}
+
+/// Modal editor state for the input bar.
+#[derive(Debug, Clone, Default)]
+pub struct VimEditor {
+    pub mode: Mode,
+    /// Cursor position in characters.
+    pub cursor: usize,
+    /// Keys typed so far of an unfinished normal-mode command.
+    pub pending: String,
+    registers: HashMap<char, String>,
+    /// Text and cursor before the last change, for `u`.
+    undo: Option<(String, usize)>,
+}
+
+impl VimEditor {
+    /// Back to insert mode at the start of an empty line (after submit).
+    /// Registers are kept.
+    pub fn reset(&mut self) {
+        self.mode = Mode::Insert;
+        self.cursor = 0;
+        self.pending.clear();
+        self.undo = None;
+    }
+
+    /// Put the cursor at the end of text that was replaced from outside
+    /// (e.g. a command completion).
+    pub fn move_to_end(&mut self, text: &str) {
+        self.cursor = text.chars().count();
+        self.clamp(text.chars().count());
+    }
+
+    /// Apply a key to `text`.  Returns true if the text changed.
+    pub fn handle_key(&mut self, text: &mut String, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let mut chars: Vec<char> = text.chars().collect();
+        self.cursor = self.cursor.min(chars.len());
+        let before = chars.clone();
+
+        match self.mode {
+            Mode::Insert => self.insert_key(&mut chars, code, modifiers),
+            Mode::Normal => self.normal_key(&mut chars, code),
+        }
+
+        self.clamp(chars.len());
+        if chars == before {
+            return false;
+        }
+        *text = chars.into_iter().collect();
+        true
+    }
+
+    fn clamp(&mut self, len: usize) {
+        let max = match self.mode {
+            Mode::Insert => len,
+            Mode::Normal => len.saturating_sub(1),
+        };
+        self.cursor = self.cursor.min(max);
+    }
+
+    fn snapshot(&mut self, chars: &[char]) {
+        self.undo = Some((chars.iter().collect(), self.cursor));
+    }
+
+    fn insert_key(&mut self, chars: &mut Vec<char>, code: KeyCode, modifiers: KeyModifiers) {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            KeyCode::Char('w') if ctrl => {
+                let start = prev_word_start(chars, self.cursor);
+                chars.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            KeyCode::Char('u') if ctrl => {
+                chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                chars.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < chars.len() => {
+                chars.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(chars.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = chars.len(),
+            _ => {}
+        }
+    }
+
+    fn normal_key(&mut self, chars: &mut Vec<char>, code: KeyCode) {
+        let c = match code {
+            KeyCode::Char(c) => c,
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Backspace => 'h',
+            KeyCode::Delete => 'x',
+            KeyCode::Esc => {
+                self.pending.clear();
+                return;
+            }
+            _ => return,
+        };
+        self.pending.push(c);
+        let seq: Vec<char> = self.pending.chars().collect();
+        match parse(&seq) {
+            Parse::Incomplete => {}
+            Parse::Invalid => self.pending.clear(),
+            Parse::Done { register, count, command } => {
+                self.pending.clear();
+                self.execute(chars, register, count, command);
+            }
+        }
+    }
+
+    /// Where `motion` repeated `count` times lands, for moving the cursor.
+    fn target(&self, chars: &[char], motion: Motion, count: usize) -> usize {
+        let len = chars.len();
+        match motion {
+            Motion::Left => self.cursor.saturating_sub(count),
+            Motion::Right => (self.cursor + count).min(len),
+            Motion::LineStart => 0,
+            Motion::FirstNonBlank => chars.iter().position(|c| !c.is_whitespace()).unwrap_or(len),
+            Motion::LineEnd => len,
+            Motion::WordForward => (0..count).fold(self.cursor, |i, _| next_word_start(chars, i)),
+            Motion::WordBackward => (0..count).fold(self.cursor, |i, _| prev_word_start(chars, i)),
+            Motion::WordEnd => (0..count).fold(self.cursor, |i, _| word_end(chars, i)),
+        }
+    }
+
+    /// Character range an operator acts on.
+    fn range(&self, chars: &[char], op: Operator, target: Target, count: usize) -> (usize, usize) {
+        let len = chars.len();
+        match target {
+            Target::Line => (0, len),
+            Target::InnerWord | Target::AWord => {
+                if len == 0 {
+                    return (0, 0);
+                }
+                let at = self.cursor.min(len - 1);
+                let c = class(chars[at]);
+                let mut start = at;
+                while start > 0 && class(chars[start - 1]) == c {
+                    start -= 1;
+                }
+                let mut end = at + 1;
+                while end < len && class(chars[end]) == c {
+                    end += 1;
+                }
+                if target == Target::AWord {
+                    let trailing = end;
+                    while end < len && class(chars[end]) == Class::Space {
+                        end += 1;
+                    }
+                    if end == trailing {
+                        while start > 0 && class(chars[start - 1]) == Class::Space {
+                            start -= 1;
+                        }
+                    }
+                }
+                (start, end)
+            }
+            // `cw` changes to the end of the word, like vim.
+            Target::Motion(Motion::WordForward)
+                if op == Operator::Change && chars.get(self.cursor).is_some_and(|c| !c.is_whitespace()) =>
+            {
+                let c = class(chars[self.cursor]);
+                let mut end = self.cursor;
+                while end + 1 < len && class(chars[end + 1]) == c {
+                    end += 1;
+                }
+                let end = (1..count).fold(end, |i, _| word_end(chars, i));
+                (self.cursor, end + 1)
+            }
+            Target::Motion(Motion::WordEnd) => {
+                let end = self.target(chars, Motion::WordEnd, count);
+                (self.cursor, (end + 1).min(len))
+            }
+            Target::Motion(m) => {
+                let t = self.target(chars, m, count);
+                (self.cursor.min(t), self.cursor.max(t))
+            }
+        }
+    }
+
+    fn store(&mut self, register: Option<char>, text: String) {
+        let register = register.unwrap_or(UNNAMED);
+        if register == BLACK_HOLE {
+            return;
+        }
+        if register != UNNAMED {
+            self.registers.insert(register, text.clone());
+        }
+        self.registers.insert(UNNAMED, text);
+    }
+
+    fn operate(&mut self, chars: &mut Vec<char>, register: Option<char>, op: Operator, start: usize, end: usize) {
+        let end = end.min(chars.len());
+        let start = start.min(end);
+        let taken: String = chars[start..end].iter().collect();
+        self.store(register, taken);
+        if op != Operator::Yank {
+            self.snapshot(chars);
+            chars.drain(start..end);
+        }
+        self.cursor = start;
+        if op == Operator::Change {
+            self.mode = Mode::Insert;
+        }
+    }
+
+    fn execute(&mut self, chars: &mut Vec<char>, register: Option<char>, count: usize, command: Command) {
+        let len = chars.len();
+        match command {
+            Command::Move(m) => self.cursor = self.target(chars, m, count),
+            Command::Operate(op, target) => {
+                let (start, end) = self.range(chars, op, target, count);
+                self.operate(chars, register, op, start, end);
+            }
+            Command::Simple(c) => match c {
+                'x' if len > 0 => self.operate(chars, register, Operator::Delete, self.cursor, self.cursor + count),
+                'X' if self.cursor > 0 => {
+                    let start = self.cursor.saturating_sub(count);
+                    self.operate(chars, register, Operator::Delete, start, self.cursor)
+                }
+                'D' => self.operate(chars, register, Operator::Delete, self.cursor, len),
+                'C' => self.operate(chars, register, Operator::Change, self.cursor, len),
+                's' => self.operate(chars, register, Operator::Change, self.cursor, self.cursor + count),
+                'S' => self.operate(chars, register, Operator::Change, 0, len),
+                'p' | 'P' => {
+                    let Some(text) = self.registers.get(&register.unwrap_or(UNNAMED)).cloned() else {
+                        return;
+                    };
+                    if text.is_empty() {
+                        return;
+                    }
+                    self.snapshot(chars);
+                    let at = if c == 'p' && len > 0 { (self.cursor + 1).min(len) } else { self.cursor };
+                    let pasted: Vec<char> = text.repeat(count).chars().collect();
+                    let n = pasted.len();
+                    chars.splice(at..at, pasted);
+                    self.cursor = at + n - 1;
+                }
+                'u' => {
+                    if let Some((text, cursor)) = self.undo.take() {
+                        self.undo = Some((chars.iter().collect(), self.cursor));
+                        *chars = text.chars().collect();
+                        self.cursor = cursor;
+                    }
+                }
+                'i' | 'a' | 'I' | 'A' => {
+                    self.snapshot(chars);
+                    self.mode = Mode::Insert;
+                    self.cursor = match c {
+                        'a' => (self.cursor + 1).min(len),
+                        'I' => chars.iter().position(|c| !c.is_whitespace()).unwrap_or(len),
+                        'A' => len,
+                        _ => self.cursor,
+                    };
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(editor: &mut VimEditor, text: &mut String, seq: &str) {
+        for c in seq.chars() {
+            let code = if c == '\x1b' { KeyCode::Esc } else { KeyCode::Char(c) };
+            editor.handle_key(text, code, KeyModifiers::NONE);
+        }
+    }
+
+    #[test]
+    fn test_insert_then_normal_motions() {
+        let mut ed = VimEditor::default();
+        let mut text = String::new();
+        keys(&mut ed, &mut text, "hello brave world\x1b");
+        assert_eq!(ed.mode, Mode::Normal);
+        assert_eq!(ed.cursor, 16);
+
+        keys(&mut ed, &mut text, "0w");
+        assert_eq!(ed.cursor, 6);
+        keys(&mut ed, &mut text, "dw");
+        assert_eq!(text, "hello world");
+        keys(&mut ed, &mut text, "u");
+        assert_eq!(text, "hello brave world");
+        keys(&mut ed, &mut text, "$");
+        assert_eq!(ed.cursor, 16);
+    }
+
+    #[test]
+    fn test_ciw_and_registers() {
+        let mut ed = VimEditor::default();
+        let mut text = "fix the bug".to_string();
+        ed.mode = Mode::Normal;
+        ed.cursor = 5;
+
+        keys(&mut ed, &mut text, "\"ayiw");
+        keys(&mut ed, &mut text, "ciwa\x1b");
+        assert_eq!(text, "fix a bug");
+
+        keys(&mut ed, &mut text, "$\"ap");
+        assert_eq!(text, "fix a bugthe");
+        keys(&mut ed, &mut text, "dd");
+        assert_eq!(text, "");
+        keys(&mut ed, &mut text, "P");
+        assert_eq!(text, "fix a bugthe");
+    }
+
+    #[test]
+    fn test_counts_and_change_word() {
+        let mut ed = VimEditor::default();
+        let mut text = "one two three four".to_string();
+        ed.mode = Mode::Normal;
+
+        keys(&mut ed, &mut text, "2dw");
+        assert_eq!(text, "three four");
+        keys(&mut ed, &mut text, "cwfive\x1b");
+        assert_eq!(text, "five four");
+        keys(&mut ed, &mut text, "A!\x1b");
+        assert_eq!(text, "five four!");
+    }
+}
@@ -62,6 +62,10 @@ host = "127.0.0.1"
 
 [provider]
 kind = "mock"
+
+[model]
+provider = "mock"
+model = "mock"
 "#)).ok()?;
 
         let process = Command::new(&binary)